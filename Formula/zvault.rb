@@ -19,6 +19,7 @@ class Zvault < Formula
     else
       system "cargo", "install", "--root", prefix, "--path", "."
     end
+    generate_completions_from_executable(bin/"zvault", "completions")
   end
 
   def post_install
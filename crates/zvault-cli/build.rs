@@ -0,0 +1,49 @@
+//! Embed build metadata (git revision, target triple, profile) for
+//! `--version` and `build_info`, and the release signing key for
+//! `self-update`.
+
+#![allow(clippy::print_stdout)]
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=ZVAULT_GIT_SHA");
+    println!("cargo:rerun-if-env-changed=ZVAULT_RELEASE_PUBLIC_KEY");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs/heads");
+
+    // Release builds from a source tarball have no .git; CI can pass the
+    // revision in through ZVAULT_GIT_SHA instead.
+    let git_sha = std::env::var("ZVAULT_GIT_SHA")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .or_else(git_revision)
+        .unwrap_or_else(|| "unknown".to_owned());
+
+    let target = std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_owned());
+    let profile = std::env::var("PROFILE").unwrap_or_else(|_| "unknown".to_owned());
+
+    println!("cargo:rustc-env=ZVAULT_GIT_SHA={git_sha}");
+    println!("cargo:rustc-env=ZVAULT_BUILD_TARGET={target}");
+    println!("cargo:rustc-env=ZVAULT_BUILD_PROFILE={profile}");
+
+    // Set by the release workflow; other builds cannot self-update.
+    let release_key = std::env::var("ZVAULT_RELEASE_PUBLIC_KEY").unwrap_or_default();
+    println!(
+        "cargo:rustc-env=ZVAULT_RELEASE_PUBLIC_KEY={}",
+        release_key.trim()
+    );
+}
+
+fn git_revision() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let sha = String::from_utf8(output.stdout).ok()?;
+    let sha = sha.trim();
+    (!sha.is_empty()).then(|| sha.to_owned())
+}
@@ -0,0 +1,492 @@
+//! Long-running sidecar for `zvault agent`.
+//!
+//! The agent logs in with `AppRole` or JWT, keeps its token renewed (logging
+//! in again when renewal fails or the token is not renewable), and renders
+//! templates to files every `interval` seconds. Templates use the language of
+//! `zvault render` (see [`zvault_sdk::template`]), e.g.
+//! `{{ secret "path" "key" }}` for a key of the `secret/` KV mount.
+//!
+//! A file is only rewritten when its rendered contents change, and only once
+//! every placeholder in it resolved. After a change the template's `command`
+//! runs, and a supervised `exec` child is sent a signal or restarted. The
+//! agent exits when the child does, failing if the child failed.
+//!
+//! ```yaml
+//! auth:
+//!   approle:
+//!     role_id: 1b2c...
+//!     secret_id_file: /etc/zvault/secret-id
+//! token_file: /run/zvault/token
+//! interval: 60
+//! templates:
+//!   - source: /etc/app/config.env.tmpl
+//!     destination: /run/app/config.env
+//!     mode: "0600"
+//! exec:
+//!   command: ["/usr/bin/app", "--config", "/run/app/config.env"]
+//!   on_change: signal
+//!   signal: HUP
+//! ```
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+#[cfg(unix)]
+use nix::sys::signal::Signal;
+#[cfg(unix)]
+use nix::unistd::Pid;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use zvault_sdk::template::Template;
+
+use super::{Client, DIM, RESET, render, success, warning};
+
+/// Seconds between renders when the config sets no `interval`.
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+/// Agent configuration, as read from the YAML file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AgentConfig {
+    /// How to log in; without it the CLI's token is used as-is.
+    auth: Option<AuthSpec>,
+    /// Write each new token here, for other processes to use.
+    token_file: Option<String>,
+    #[serde(default = "default_interval")]
+    interval: u64,
+    #[serde(default)]
+    templates: Vec<TemplateSpec>,
+    exec: Option<ExecSpec>,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL_SECS
+}
+
+/// Login method; exactly one of `approle` and `jwt`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthSpec {
+    approle: Option<AppRoleAuth>,
+    jwt: Option<JwtAuth>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AppRoleAuth {
+    role_id: String,
+    secret_id: Option<String>,
+    /// Read on every login, so the file can be replaced.
+    secret_id_file: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JwtAuth {
+    role: String,
+    /// Read on every login, so a projected token can rotate.
+    jwt_file: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateSpec {
+    /// Template file; or give the template inline as `contents`.
+    source: Option<String>,
+    contents: Option<String>,
+    destination: String,
+    /// Octal file mode of `destination` (default: `0600`).
+    mode: Option<String>,
+    /// Run after `destination` changes, e.g. `["systemctl", "reload", "app"]`.
+    #[serde(default)]
+    command: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExecSpec {
+    command: Vec<String>,
+    #[serde(default)]
+    on_change: OnChange,
+    /// Signal sent with `on_change: signal` (default: `HUP`).
+    signal: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OnChange {
+    #[default]
+    Signal,
+    Restart,
+    Ignore,
+}
+
+/// A template and where it renders to.
+struct Target {
+    template: Template,
+    destination: String,
+    mode: u32,
+    command: Vec<String>,
+    /// Contents last written (or found) at `destination`.
+    rendered: Option<String>,
+}
+
+/// Read and validate the agent config, parsing every template.
+pub(crate) fn load(file: &str) -> Result<AgentConfig> {
+    let raw = std::fs::read_to_string(file).with_context(|| format!("failed to read {file}"))?;
+    let config: AgentConfig =
+        serde_yaml_ng::from_str(&raw).with_context(|| format!("invalid agent config {file}"))?;
+    if config.templates.is_empty() && config.exec.is_none() {
+        bail!("{file} declares no templates and no exec command — nothing to do");
+    }
+    if config.interval == 0 {
+        bail!("interval must be at least 1 second");
+    }
+    if let Some(auth) = &config.auth {
+        match (&auth.approle, &auth.jwt) {
+            (Some(approle), None) => {
+                if approle.secret_id.is_some() == approle.secret_id_file.is_some() {
+                    bail!("approle auth needs exactly one of secret_id and secret_id_file");
+                }
+            }
+            (None, Some(_)) => {}
+            _ => bail!("auth needs exactly one of approle and jwt"),
+        }
+    }
+    if let Some(exec) = &config.exec {
+        if exec.command.is_empty() {
+            bail!("exec.command must not be empty");
+        }
+        if let Some(signal) = &exec.signal {
+            signal_number(signal)?;
+        }
+    }
+    Ok(config)
+}
+
+fn load_targets(config: &AgentConfig) -> Result<Vec<Target>> {
+    config
+        .templates
+        .iter()
+        .map(|spec| {
+            let template = match (&spec.source, &spec.contents) {
+                (Some(path), None) => render::load_template(path)?,
+                (None, Some(contents)) => Template::parse(contents)
+                    .with_context(|| format!("invalid template for {}", spec.destination))?,
+                _ => bail!(
+                    "template for {} needs exactly one of source and contents",
+                    spec.destination
+                ),
+            };
+            Ok(Target {
+                template,
+                destination: spec.destination.clone(),
+                mode: spec.mode.as_deref().map_or(Ok(0o600), render::parse_mode)?,
+                command: spec.command.clone(),
+                rendered: std::fs::read_to_string(&spec.destination).ok(),
+            })
+        })
+        .collect()
+}
+
+/// A logged-in token and when to renew it.
+struct Session {
+    client: Client,
+    token: String,
+    ttl: Option<Duration>,
+    renewable: bool,
+    renew_at: Option<Instant>,
+}
+
+impl Session {
+    fn new(client: &Client, resp: &Value) -> Result<Self> {
+        let token = resp
+            .get("client_token")
+            .and_then(Value::as_str)
+            .context("login response has no client_token")?
+            .to_owned();
+        let ttl = resp
+            .get("lease_duration")
+            .and_then(Value::as_u64)
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs);
+        Ok(Self {
+            client: client.with_token(token.clone()),
+            token,
+            ttl,
+            renewable: resp
+                .get("renewable")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            renew_at: ttl.map(|ttl| Instant::now() + ttl * 2 / 3),
+        })
+    }
+
+    fn due(&self) -> bool {
+        self.renew_at.is_some_and(|at| Instant::now() >= at)
+    }
+}
+
+async fn login(client: &Client, auth: &AuthSpec) -> Result<Value> {
+    if let Some(approle) = &auth.approle {
+        let secret_id = match (&approle.secret_id, &approle.secret_id_file) {
+            (Some(secret_id), _) => secret_id.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {path}"))?
+                .trim()
+                .to_owned(),
+            (None, None) => bail!("approle auth needs secret_id or secret_id_file"),
+        };
+        let body = json!({ "role_id": approle.role_id, "secret_id": secret_id });
+        return client.post_no_auth("/v1/auth/approle/login", &body).await;
+    }
+    let Some(jwt) = &auth.jwt else {
+        bail!("auth needs exactly one of approle and jwt");
+    };
+    let token = std::fs::read_to_string(&jwt.jwt_file)
+        .with_context(|| format!("failed to read {}", jwt.jwt_file))?;
+    let body = json!({ "role": jwt.role, "jwt": token.trim() });
+    client.post_no_auth("/v1/auth/jwt/login", &body).await
+}
+
+/// Make sure `session` holds a live token: log in if there is none, and
+/// renew (or log in again) once two thirds of its TTL have passed.
+async fn ensure_session(
+    client: &Client,
+    config: &AgentConfig,
+    session: &mut Option<Session>,
+) -> Result<()> {
+    let Some(auth) = &config.auth else {
+        return Ok(());
+    };
+    if let Some(current) = session.as_mut() {
+        if !current.due() {
+            return Ok(());
+        }
+        if current.renewable {
+            let increment = current.ttl.map_or(3600, |ttl| ttl.as_secs());
+            let body = json!({ "token": current.token, "increment": format!("{increment}s") });
+            match current
+                .client
+                .post("/v1/auth/token/renew-self", &body)
+                .await
+            {
+                Ok(_) => {
+                    current.renew_at = current.ttl.map(|ttl| Instant::now() + ttl * 2 / 3);
+                    outln!("  {DIM}token renewed{RESET}");
+                    return Ok(());
+                }
+                Err(e) => warning(&format!("token renewal failed, logging in again: {e}")),
+            }
+        }
+    }
+
+    let resp = login(client, auth).await?;
+    let fresh = Session::new(client, &resp)?;
+    if let Some(path) = &config.token_file {
+        render::write_file(path, &fresh.token, 0o600)?;
+    }
+    success("logged in");
+    *session = Some(fresh);
+    Ok(())
+}
+
+/// Render every template, writing those whose contents changed. Returns
+/// whether any did.
+async fn render_all(client: &Client, targets: &mut [Target]) -> Result<bool> {
+    let templates: Vec<&Template> = targets.iter().map(|t| &t.template).collect();
+    let secrets = render::fetch_secrets(client, &templates).await?;
+    let mut changed = false;
+    for target in targets.iter_mut() {
+        let contents = render::render(&target.template, &secrets)
+            .with_context(|| format!("failed to render {}", target.destination))?;
+        if target.rendered.as_deref() == Some(contents.as_str()) {
+            continue;
+        }
+        render::write_file(&target.destination, &contents, target.mode)?;
+        target.rendered = Some(contents);
+        success(&format!("rendered {}", target.destination));
+        changed = true;
+        if let Some((program, args)) = target.command.split_first() {
+            let status = tokio::process::Command::new(program)
+                .args(args)
+                .status()
+                .await
+                .with_context(|| format!("failed to run {program}"))?;
+            if !status.success() {
+                warning(&format!(
+                    "command for {} exited with {status}",
+                    target.destination
+                ));
+            }
+        }
+    }
+    Ok(changed)
+}
+
+fn spawn_child(exec: &ExecSpec) -> Result<tokio::process::Child> {
+    let (program, args) = exec
+        .command
+        .split_first()
+        .context("exec.command must not be empty")?;
+    let child = tokio::process::Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to start {program}"))?;
+    success(&format!("started {}", exec.command.join(" ")));
+    Ok(child)
+}
+
+/// Signal or restart the child after secrets changed.
+async fn notify_child(exec: &ExecSpec, child: &mut tokio::process::Child) -> Result<()> {
+    match exec.on_change {
+        OnChange::Ignore => Ok(()),
+        OnChange::Signal => {
+            let signal = exec.signal.as_deref().unwrap_or("HUP");
+            send_signal(child, signal_number(signal)?)?;
+            outln!(
+                "  {DIM}sent SIG{} to the child{RESET}",
+                normalize_signal(signal)
+            );
+            Ok(())
+        }
+        OnChange::Restart => {
+            stop_child(child).await;
+            *child = spawn_child(exec)?;
+            Ok(())
+        }
+    }
+}
+
+/// Ask the child to exit, killing it if it has not within 10 seconds.
+async fn stop_child(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    let _ = send_signal(child, Signal::SIGTERM);
+    if tokio::time::timeout(Duration::from_secs(10), child.wait())
+        .await
+        .is_err()
+    {
+        let _ = child.kill().await;
+    }
+}
+
+fn normalize_signal(name: &str) -> String {
+    name.trim_start_matches("SIG").to_ascii_uppercase()
+}
+
+#[cfg(unix)]
+fn signal_number(name: &str) -> Result<Signal> {
+    Ok(match normalize_signal(name).as_str() {
+        "HUP" => Signal::SIGHUP,
+        "INT" => Signal::SIGINT,
+        "QUIT" => Signal::SIGQUIT,
+        "TERM" => Signal::SIGTERM,
+        "USR1" => Signal::SIGUSR1,
+        "USR2" => Signal::SIGUSR2,
+        _ => bail!("unsupported signal '{name}', expected HUP, INT, QUIT, TERM, USR1, or USR2"),
+    })
+}
+
+/// Signals are Unix-only; elsewhere the helpers below refuse them.
+#[cfg(not(unix))]
+enum Signal {}
+
+#[cfg(not(unix))]
+fn signal_number(name: &str) -> Result<Signal> {
+    bail!("signals are not supported on this platform ('{name}'); use on_change: restart")
+}
+
+#[cfg(unix)]
+fn send_signal(child: &tokio::process::Child, signal: Signal) -> Result<()> {
+    let pid = child.id().context("the child has already exited")?;
+    let pid = i32::try_from(pid).context("child PID out of range")?;
+    nix::sys::signal::kill(Pid::from_raw(pid), signal).context("failed to signal the child")
+}
+
+#[cfg(not(unix))]
+fn send_signal(_child: &tokio::process::Child, _signal: Signal) -> Result<()> {
+    bail!("signals are not supported on this platform")
+}
+
+/// Wait for Ctrl-C or, on Unix, `SIGTERM`.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let Ok(mut term) =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        else {
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Run the agent until it is signalled to stop, its child exits, or — with
+/// `once` — the templates have been rendered a single time.
+pub(crate) async fn run(client: &Client, config: &AgentConfig, once: bool) -> Result<()> {
+    let mut targets = load_targets(config)?;
+    let mut session: Option<Session> = None;
+    let mut child: Option<tokio::process::Child> = None;
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            () = shutdown_signal() => {
+                if let Some(child) = child.as_mut() {
+                    stop_child(child).await;
+                }
+                return Ok(());
+            }
+            status = async {
+                match child.as_mut() {
+                    Some(child) => child.wait().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                let status = status.context("failed to wait for the child")?;
+                if !status.success() {
+                    bail!("command exited with code {}", status.code().unwrap_or(1));
+                }
+                return Ok(());
+            }
+        }
+
+        let result = async {
+            ensure_session(client, config, &mut session).await?;
+            let client = session.as_ref().map_or(client, |s| &s.client);
+            render_all(client, &mut targets).await
+        }
+        .await;
+        match result {
+            Ok(changed) => {
+                if once {
+                    return Ok(());
+                }
+                if let Some(exec) = &config.exec {
+                    match child.as_mut() {
+                        None => child = Some(spawn_child(exec)?),
+                        Some(child) if changed => notify_child(exec, child).await?,
+                        Some(_) => {}
+                    }
+                }
+            }
+            // Before the first render there is nothing to keep serving.
+            Err(e) if once || (child.is_none() && config.exec.is_some()) => {
+                if once {
+                    return Err(e);
+                }
+                warning(&format!("{e:#}; retrying in {}s", config.interval));
+            }
+            Err(e) => warning(&format!("{e:#}; keeping the last rendered files")),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Errors returned by the `ZVault` API.
+//!
+//! The server reports failures as an envelope:
+//! `{"errors": ["..."], "code": "permission_denied", "message": "...",
+//! "request_id": "..."}`. [`ApiError`] parses it so commands can branch on
+//! [`ErrorCode`] instead of the response text. Older servers sent an `error`
+//! field instead of `code`, and proxies may answer with plain text; both fall
+//! back to a code derived from the HTTP status.
+
+use std::fmt;
+
+use reqwest::StatusCode;
+use serde_json::Value;
+
+/// Machine-readable error code from the envelope's `code` field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorCode {
+    Sealed,
+    Unauthenticated,
+    PermissionDenied,
+    NotFound,
+    InvalidRequest,
+    Conflict,
+    Internal,
+    Unavailable,
+    FeatureNotLicensed,
+    MfaRequired,
+    ControlGroupRequired,
+    RateLimited,
+    /// A code this CLI version does not know.
+    Other(String),
+}
+
+impl ErrorCode {
+    fn parse(code: &str) -> Self {
+        match code {
+            "sealed" => Self::Sealed,
+            "unauthenticated" | "unauthorized" => Self::Unauthenticated,
+            "permission_denied" | "forbidden" => Self::PermissionDenied,
+            "not_found" => Self::NotFound,
+            "invalid_request" | "bad_request" => Self::InvalidRequest,
+            "conflict" => Self::Conflict,
+            "internal" | "internal_error" | "audit_failure" => Self::Internal,
+            "unavailable" => Self::Unavailable,
+            "feature_not_licensed" => Self::FeatureNotLicensed,
+            "mfa_required" => Self::MfaRequired,
+            "control_group_required" => Self::ControlGroupRequired,
+            "rate_limited" => Self::RateLimited,
+            other => Self::Other(other.to_owned()),
+        }
+    }
+
+    fn for_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Self::InvalidRequest,
+            StatusCode::UNAUTHORIZED => Self::Unauthenticated,
+            StatusCode::PAYMENT_REQUIRED => Self::FeatureNotLicensed,
+            StatusCode::FORBIDDEN => Self::PermissionDenied,
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::CONFLICT => Self::Conflict,
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+            StatusCode::SERVICE_UNAVAILABLE => Self::Unavailable,
+            s if s.is_server_error() => Self::Internal,
+            s => Self::Other(format!("http_{}", s.as_u16())),
+        }
+    }
+
+    /// The code as the server spells it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Sealed => "sealed",
+            Self::Unauthenticated => "unauthenticated",
+            Self::PermissionDenied => "permission_denied",
+            Self::NotFound => "not_found",
+            Self::InvalidRequest => "invalid_request",
+            Self::Conflict => "conflict",
+            Self::Internal => "internal",
+            Self::Unavailable => "unavailable",
+            Self::FeatureNotLicensed => "feature_not_licensed",
+            Self::MfaRequired => "mfa_required",
+            Self::ControlGroupRequired => "control_group_required",
+            Self::RateLimited => "rate_limited",
+            Self::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A failed API response.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+    pub errors: Vec<String>,
+    pub request_id: Option<String>,
+}
+
+impl ApiError {
+    /// Parse the error envelope in `body`, falling back to `status` and the
+    /// raw text when there is none.
+    pub fn from_body(status: StatusCode, body: &str) -> Self {
+        let parsed: Value = serde_json::from_str(body).unwrap_or_default();
+        let text = |name: &str| parsed.get(name).and_then(Value::as_str);
+
+        let errors: Vec<String> = parsed
+            .get("errors")
+            .and_then(Value::as_array)
+            .map(|errors| {
+                errors
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let code = text("code")
+            .or_else(|| text("error"))
+            .map_or_else(|| ErrorCode::for_status(status), ErrorCode::parse);
+        let message = text("message")
+            .map(str::to_owned)
+            .or_else(|| errors.first().cloned())
+            .unwrap_or_else(|| match body.trim() {
+                "" => status
+                    .canonical_reason()
+                    .unwrap_or("request failed")
+                    .to_lowercase(),
+                text => text.to_owned(),
+            });
+
+        Self {
+            status,
+            code,
+            errors: if errors.is_empty() {
+                vec![message.clone()]
+            } else {
+                errors
+            },
+            message,
+            request_id: text("request_id").map(str::to_owned),
+        }
+    }
+
+    /// What the user can do about the error, when there is an obvious next
+    /// step.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.code {
+            ErrorCode::Sealed => Some("unseal the vault with `zvault unseal`"),
+            ErrorCode::Unauthenticated => Some("set VAULT_TOKEN or pass --token"),
+            ErrorCode::PermissionDenied => {
+                Some("check the token's policies with `zvault token lookup`")
+            }
+            ErrorCode::MfaRequired => Some("pass a one-time code with --mfa or VAULT_MFA"),
+            ErrorCode::FeatureNotLicensed => {
+                Some("activate a license on the server with `zvault activate --server <key>`")
+            }
+            ErrorCode::RateLimited => Some("wait a moment and retry"),
+            _ => None,
+        }
+    }
+
+    /// The envelope with the HTTP status, as `zvault api` prints it.
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "status": self.status.as_u16(),
+            "code": self.code.as_str(),
+            "message": self.message,
+            "errors": self.errors,
+            "request_id": self.request_id,
+        })
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "server returned {} ({}): {}",
+            self.status, self.code, self.message
+        )?;
+        if let Some(id) = &self.request_id {
+            write!(f, " [request {id}]")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
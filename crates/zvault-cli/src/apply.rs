@@ -0,0 +1,783 @@
+//! Declarative configuration for `zvault apply`.
+//!
+//! A YAML file describes the policies, KV mounts, `AppRole` roles, PKI roles,
+//! rotation policies, and Kubernetes secret syncs a vault should have. `apply` reads the live state,
+//! prints a plan of the changes needed to match the file, then makes them.
+//!
+//! Only sections present in the file are managed. With `--prune`, resources
+//! in a managed section that the file does not declare are deleted — except
+//! built-in policies, mounts (unmounting destroys their secrets), and PKI
+//! roles (which have no delete API). Rotation policies are keyed by
+//! `<mount>/<path>` and reconciled with the server's rotation engine.
+//! Kubernetes syncs never carry API tokens: a sync declaring `api_url` must
+//! first be given its token with `zvault sync kubernetes set`.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::{BOLD, Client, DIM, GREEN, RED, RESET, YELLOW, warning};
+
+/// File format version this CLI understands.
+const CONFIG_VERSION: u32 = 1;
+
+/// Policies every vault has, which cannot be written or deleted.
+const BUILTIN_POLICIES: [&str; 3] = ["root", "default", "mcp-audit"];
+
+/// Desired vault configuration, as read from the YAML file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct VaultConfig {
+    #[serde(default = "default_version")]
+    version: u32,
+    policies: Option<BTreeMap<String, PolicySpec>>,
+    mounts: Option<BTreeMap<String, MountSpec>>,
+    approles: Option<BTreeMap<String, AppRoleSpec>>,
+    pki_roles: Option<BTreeMap<String, PkiRoleSpec>>,
+    rotation_policies: Option<BTreeMap<String, RotationSpec>>,
+    kubernetes_syncs: Option<BTreeMap<String, KubernetesSyncSpec>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicySpec {
+    rules: Vec<RuleSpec>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    path: String,
+    capabilities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    control_group: Option<ControlGroupSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mfa_methods: Vec<String>,
+}
+
+/// Approvals required before requests the rule grants may run.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ControlGroupSpec {
+    approvals: u32,
+    #[serde(default)]
+    approver_policies: Vec<String>,
+    #[serde(default)]
+    approver_identities: Vec<String>,
+}
+
+/// A KV mount. Only `config` can change once mounted.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MountSpec {
+    #[serde(default)]
+    description: String,
+    config: Option<Value>,
+}
+
+/// Defaults match `POST /v1/auth/approle/role/{name}`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AppRoleSpec {
+    policies: Vec<String>,
+    #[serde(default = "default_token_ttl")]
+    token_ttl_secs: i64,
+    #[serde(default = "default_token_max_ttl")]
+    token_max_ttl_secs: i64,
+    #[serde(default = "default_true")]
+    bind_secret_id: bool,
+    #[serde(default)]
+    secret_id_num_uses: u32,
+    #[serde(default)]
+    secret_id_ttl_secs: i64,
+}
+
+/// Defaults match `POST /v1/pki/roles/{name}`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
+struct PkiRoleSpec {
+    allowed_domains: Vec<String>,
+    #[serde(default)]
+    allow_subdomains: bool,
+    #[serde(default = "default_pki_max_ttl")]
+    max_ttl_hours: u64,
+    #[serde(default = "default_true")]
+    generate_key: bool,
+    #[serde(default = "default_key_type")]
+    key_type: String,
+    #[serde(default = "default_key_bits")]
+    key_bits: u32,
+    #[serde(default)]
+    allow_wildcard_certificates: bool,
+    #[serde(default)]
+    allow_ip_sans: bool,
+    #[serde(default)]
+    allowed_uri_sans: Vec<String>,
+    #[serde(default = "default_not_before")]
+    not_before_duration_secs: u64,
+}
+
+/// Exactly one of `interval` (e.g. `24h`) and `cron` is required.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RotationSpec {
+    #[serde(default = "default_rotation_field")]
+    field: String,
+    /// `{type: random | database | webhook, ...}`, as in the API.
+    rotator: Value,
+    interval: Option<String>,
+    cron: Option<String>,
+}
+
+/// Mappings use the `--map` syntax, `<mount>/<path>[#field][=KEY]`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KubernetesSyncSpec {
+    namespace: String,
+    secret_name: String,
+    mappings: Vec<String>,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    /// API server URL; omit for the cluster the server runs in.
+    api_url: Option<String>,
+    #[serde(default = "default_true")]
+    enabled: bool,
+}
+
+fn default_version() -> u32 {
+    CONFIG_VERSION
+}
+fn default_token_ttl() -> i64 {
+    3600
+}
+fn default_token_max_ttl() -> i64 {
+    86400
+}
+fn default_true() -> bool {
+    true
+}
+fn default_pki_max_ttl() -> u64 {
+    720
+}
+fn default_key_type() -> String {
+    "ec".to_owned()
+}
+fn default_key_bits() -> u32 {
+    256
+}
+fn default_not_before() -> u64 {
+    30
+}
+fn default_rotation_field() -> String {
+    "value".to_owned()
+}
+
+/// Read and validate a config file.
+pub(crate) fn load(path: &str) -> Result<VaultConfig> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+    let mut config: VaultConfig =
+        serde_yaml_ng::from_str(&content).with_context(|| format!("invalid config in {path}"))?;
+
+    if config.version != CONFIG_VERSION {
+        bail!(
+            "{path} has version {}, this CLI supports version {CONFIG_VERSION}",
+            config.version
+        );
+    }
+    if let Some(policies) = config.policies.as_mut() {
+        for (name, policy) in policies.iter_mut() {
+            if BUILTIN_POLICIES.contains(&name.as_str()) {
+                bail!("policy '{name}' is built in and cannot be declared");
+            }
+            for rule in &mut policy.rules {
+                for cap in &mut rule.capabilities {
+                    *cap = cap.to_lowercase();
+                }
+            }
+        }
+    }
+    if let Some(mounts) = config.mounts.take() {
+        config.mounts = Some(
+            mounts
+                .into_iter()
+                .map(|(path, spec)| (format!("{}/", path.trim_end_matches('/')), spec))
+                .collect(),
+        );
+    }
+    Ok(config)
+}
+
+// ── Plan ─────────────────────────────────────────────────────────────
+
+/// Kind of resource, in the order creates and updates are applied
+/// (policies before the `AppRole` roles that reference them).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Policy,
+    Mount,
+    AppRole,
+    PkiRole,
+    Rotation,
+    KubernetesSync,
+}
+
+impl Kind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Policy => "policy",
+            Self::Mount => "mount",
+            Self::AppRole => "approle",
+            Self::PkiRole => "pki role",
+            Self::Rotation => "rotation policy",
+            Self::KubernetesSync => "kubernetes sync",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Action {
+    Create,
+    /// Changed fields, as `field: old → new`.
+    Update(Vec<String>),
+    Delete,
+}
+
+#[derive(Debug)]
+struct Change {
+    kind: Kind,
+    name: String,
+    action: Action,
+    /// Request body for creates and updates.
+    body: Value,
+}
+
+impl Change {
+    fn is_delete(&self) -> bool {
+        matches!(self.action, Action::Delete)
+    }
+}
+
+/// Changes needed to bring the vault in line with a config file.
+#[derive(Debug, Default)]
+pub(crate) struct Plan {
+    changes: Vec<Change>,
+    warnings: Vec<String>,
+}
+
+impl Plan {
+    /// Whether the vault already matches the file.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Number of resources the plan deletes.
+    pub(crate) fn deletions(&self) -> usize {
+        self.changes.iter().filter(|c| c.is_delete()).count()
+    }
+
+    /// Compare `want` with the fields of `current` it sets.
+    fn diff(&mut self, kind: Kind, name: &str, want: Value, current: Option<&Value>) {
+        let action = match current {
+            None => Action::Create,
+            Some(current) => {
+                let fields: Vec<String> = want
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(field, new)| {
+                        let old = current.get(field).unwrap_or(&Value::Null);
+                        (old != new).then(|| format!("{field}: {} → {}", show(old), show(new)))
+                    })
+                    .collect();
+                if fields.is_empty() {
+                    return;
+                }
+                Action::Update(fields)
+            }
+        };
+        self.push(kind, name, action, want);
+    }
+
+    fn push(&mut self, kind: Kind, name: &str, action: Action, body: Value) {
+        self.changes.push(Change {
+            kind,
+            name: name.to_owned(),
+            action,
+            body,
+        });
+    }
+
+    /// Print the plan, `terraform plan` style.
+    pub(crate) fn print(&self) {
+        for change in &self.changes {
+            let (sign, color) = match change.action {
+                Action::Create => ('+', GREEN),
+                Action::Update(_) => ('~', YELLOW),
+                Action::Delete => ('-', RED),
+            };
+            outln!(
+                "  {color}{BOLD}{sign}{RESET} {DIM}{:<16}{RESET} {color}{}{RESET}",
+                change.kind.label(),
+                change.name
+            );
+            if let Action::Update(fields) = &change.action {
+                for field in fields {
+                    outln!("      {DIM}{field}{RESET}");
+                }
+            }
+        }
+        for message in &self.warnings {
+            warning(message);
+        }
+
+        let count = |f: fn(&Action) -> bool| self.changes.iter().filter(|c| f(&c.action)).count();
+        if self.is_empty() {
+            outln!("  {GREEN}No changes.{RESET} The vault matches the config.");
+        } else {
+            outln!();
+            outln!(
+                "  Plan: {GREEN}{} to create{RESET}, {YELLOW}{} to update{RESET}, {RED}{} to delete{RESET}.",
+                count(|a| matches!(a, Action::Create)),
+                count(|a| matches!(a, Action::Update(_))),
+                count(|a| matches!(a, Action::Delete)),
+            );
+        }
+    }
+}
+
+/// Compare `config` with the live vault.
+pub(crate) async fn plan(client: &Client, config: &VaultConfig, prune: bool) -> Result<Plan> {
+    let mut plan = Plan::default();
+    if let Some(policies) = &config.policies {
+        plan_policies(client, policies, prune, &mut plan).await?;
+    }
+    if let Some(mounts) = &config.mounts {
+        plan_mounts(client, mounts, &mut plan).await?;
+    }
+    if let Some(roles) = &config.approles {
+        plan_keyed(
+            client,
+            Kind::AppRole,
+            "/v1/auth/approle/role",
+            roles,
+            prune,
+            &mut plan,
+        )
+        .await?;
+    }
+    if let Some(roles) = &config.pki_roles {
+        plan_keyed(
+            client,
+            Kind::PkiRole,
+            "/v1/pki/roles",
+            roles,
+            prune,
+            &mut plan,
+        )
+        .await?;
+    }
+    if let Some(policies) = &config.rotation_policies {
+        plan_rotation(client, policies, prune, &mut plan).await?;
+    }
+    if let Some(syncs) = &config.kubernetes_syncs {
+        plan_kubernetes_syncs(client, syncs, prune, &mut plan).await?;
+    }
+
+    // Creates and updates in dependency order, then deletes in reverse.
+    plan.changes.sort_by(|a, b| {
+        let (a_del, b_del) = (a.is_delete(), b.is_delete());
+        a_del.cmp(&b_del).then_with(|| {
+            if a_del {
+                b.kind.cmp(&a.kind)
+            } else {
+                a.kind.cmp(&b.kind)
+            }
+        })
+    });
+    Ok(plan)
+}
+
+async fn plan_policies(
+    client: &Client,
+    desired: &BTreeMap<String, PolicySpec>,
+    prune: bool,
+    plan: &mut Plan,
+) -> Result<()> {
+    let existing = string_list(&client.get("/v1/sys/policies").await?, "policies");
+    for (name, spec) in desired {
+        let current = if existing.contains(name) {
+            let policy = client.get(&format!("/v1/sys/policies/{name}")).await?;
+            Some(normalize_policy(&policy))
+        } else {
+            None
+        };
+        plan.diff(
+            Kind::Policy,
+            name,
+            serde_json::to_value(spec)?,
+            current.as_ref(),
+        );
+    }
+    if prune {
+        for name in existing {
+            if !desired.contains_key(&name) && !BUILTIN_POLICIES.contains(&name.as_str()) {
+                plan.push(Kind::Policy, &name, Action::Delete, Value::Null);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn plan_mounts(
+    client: &Client,
+    desired: &BTreeMap<String, MountSpec>,
+    plan: &mut Plan,
+) -> Result<()> {
+    let listed = client.get("/v1/sys/mounts").await?;
+    let existing: BTreeMap<&str, &Value> = listed
+        .get("mounts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|m| Some((m.get("path")?.as_str()?, m)))
+        .collect();
+
+    for (path, spec) in desired {
+        let Some(current) = existing.get(path.as_str()) else {
+            let body = json!({
+                "engine_type": "kv",
+                "description": spec.description,
+                "config": spec.config,
+            });
+            plan.push(Kind::Mount, path, Action::Create, body);
+            continue;
+        };
+
+        let engine_type = current
+            .get("engine_type")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        if engine_type != "kv" {
+            plan.warnings.push(format!(
+                "mount {path} is a {engine_type} engine, not kv — left unchanged"
+            ));
+            continue;
+        }
+        let description = current
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        if description != spec.description {
+            plan.warnings.push(format!(
+                "mount {path} description differs; it can only be set when mounting"
+            ));
+        }
+        if let Some(config) = &spec.config {
+            let old = current.get("config").unwrap_or(&Value::Null);
+            if old != config {
+                let field = format!("config: {} → {}", show(old), show(config));
+                plan.push(
+                    Kind::Mount,
+                    path,
+                    Action::Update(vec![field]),
+                    json!({ "config": config }),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Plan a section whose API lists names under `keys` at `base` and reads,
+/// writes, and deletes each at `base/{name}`.
+async fn plan_keyed<T: Serialize>(
+    client: &Client,
+    kind: Kind,
+    base: &str,
+    desired: &BTreeMap<String, T>,
+    prune: bool,
+    plan: &mut Plan,
+) -> Result<()> {
+    let existing = string_list(&client.get(base).await?, "keys");
+    for (name, spec) in desired {
+        let current = if existing.contains(name) {
+            Some(client.get(&format!("{base}/{name}")).await?)
+        } else {
+            None
+        };
+        plan.diff(kind, name, serde_json::to_value(spec)?, current.as_ref());
+    }
+    if prune {
+        for name in existing.iter().filter(|n| !desired.contains_key(*n)) {
+            if kind == Kind::PkiRole {
+                plan.warnings.push(format!(
+                    "pki role {name} is not in the config but PKI roles cannot be deleted"
+                ));
+            } else {
+                plan.push(kind, name, Action::Delete, Value::Null);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn plan_rotation(
+    client: &Client,
+    desired: &BTreeMap<String, RotationSpec>,
+    prune: bool,
+    plan: &mut Plan,
+) -> Result<()> {
+    let listed = client.get("/v1/sys/rotation/policies").await?;
+    let existing: BTreeMap<&str, &Value> = listed
+        .get("policies")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|p| Some((p.get("id")?.as_str()?, p)))
+        .collect();
+
+    for (id, spec) in desired {
+        let schedule = match (&spec.interval, &spec.cron) {
+            (Some(interval), None) => json!({ "interval": parse_interval(interval)? }),
+            (None, Some(cron)) => json!({ "cron": cron }),
+            _ => bail!("rotation policy {id} needs exactly one of interval or cron"),
+        };
+        let want = json!({
+            "field": spec.field,
+            "rotator": spec.rotator,
+            "schedule": schedule,
+        });
+        // Compare only the rotator settings the file sets; write-only
+        // webhook secrets cannot be compared at all.
+        let current = existing.get(id.as_str()).map(|current| {
+            let rotator: serde_json::Map<String, Value> = spec
+                .rotator
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(key, value)| {
+                    let live = if key == "secret" {
+                        value.clone()
+                    } else {
+                        current
+                            .pointer(&format!("/rotator/{key}"))
+                            .cloned()
+                            .unwrap_or(Value::Null)
+                    };
+                    (key.clone(), live)
+                })
+                .collect();
+            json!({
+                "field": current.get("field"),
+                "rotator": rotator,
+                "schedule": current.get("schedule"),
+            })
+        });
+        plan.diff(Kind::Rotation, id, want, current.as_ref());
+    }
+    if prune {
+        for id in existing.keys().filter(|id| !desired.contains_key(**id)) {
+            plan.push(Kind::Rotation, id, Action::Delete, Value::Null);
+        }
+    }
+    Ok(())
+}
+
+async fn plan_kubernetes_syncs(
+    client: &Client,
+    desired: &BTreeMap<String, KubernetesSyncSpec>,
+    prune: bool,
+    plan: &mut Plan,
+) -> Result<()> {
+    let listed = client.get("/v1/sys/sync/kubernetes").await?;
+    let existing: BTreeMap<&str, &Value> = listed
+        .get("syncs")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|s| Some((s.get("name")?.as_str()?, s)))
+        .collect();
+
+    for (name, spec) in desired {
+        // Parsed into the API's shape, which omits an unset field and name.
+        let mappings = spec
+            .mappings
+            .iter()
+            .map(|m| {
+                let mut mapping = super::sync_mapping(m)?;
+                if let Some(fields) = mapping.as_object_mut() {
+                    fields.retain(|_, v| !v.is_null());
+                }
+                Ok(mapping)
+            })
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("kubernetes sync {name}"))?;
+        let want = json!({
+            "namespace": spec.namespace,
+            "secret_name": spec.secret_name,
+            "mappings": mappings,
+            "labels": spec.labels,
+            "api_url": spec.api_url,
+            "enabled": spec.enabled,
+        });
+        plan.diff(
+            Kind::KubernetesSync,
+            name,
+            want,
+            existing.get(name.as_str()).copied(),
+        );
+    }
+    if prune {
+        for name in existing.keys().filter(|n| !desired.contains_key(**n)) {
+            plan.push(Kind::KubernetesSync, name, Action::Delete, Value::Null);
+        }
+    }
+    Ok(())
+}
+
+/// Seconds in a duration such as `90m`, `24h`, or `30d`.
+fn parse_interval(interval: &str) -> Result<u64> {
+    let interval = interval.trim();
+    let (number, unit) = interval.split_at(interval.len().saturating_sub(1));
+    let (number, scale) = match unit {
+        "s" => (number, 1),
+        "m" => (number, 60),
+        "h" => (number, 3600),
+        "d" => (number, 86_400),
+        _ => (interval, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .with_context(|| format!("invalid interval '{interval}', expected e.g. 24h or 30d"))
+}
+
+// ── Apply ────────────────────────────────────────────────────────────
+
+/// Make the changes in `plan`, stopping at the first failure.
+pub(crate) async fn execute(client: &Client, plan: &Plan) -> Result<()> {
+    for change in &plan.changes {
+        let name = &change.name;
+        match (change.kind, &change.action) {
+            (Kind::Policy, Action::Delete) => {
+                client.delete(&format!("/v1/sys/policies/{name}")).await?;
+            }
+            (Kind::Policy, _) => {
+                client
+                    .post(&format!("/v1/sys/policies/{name}"), &change.body)
+                    .await?;
+            }
+            (Kind::Mount, Action::Create) => {
+                let path = name.trim_end_matches('/');
+                client
+                    .post(&format!("/v1/sys/mounts/{path}"), &change.body)
+                    .await?;
+            }
+            (Kind::Mount, _) => {
+                let path = name.trim_end_matches('/');
+                client
+                    .post(&format!("/v1/sys/mounts/{path}/tune"), &change.body)
+                    .await?;
+            }
+            (Kind::AppRole, Action::Delete) => {
+                client
+                    .delete(&format!("/v1/auth/approle/role/{name}"))
+                    .await?;
+            }
+            (Kind::AppRole, _) => {
+                client
+                    .post(&format!("/v1/auth/approle/role/{name}"), &change.body)
+                    .await?;
+            }
+            (Kind::PkiRole, _) => {
+                client
+                    .post(&format!("/v1/pki/roles/{name}"), &change.body)
+                    .await?;
+            }
+            (Kind::Rotation, Action::Delete) => {
+                client
+                    .delete(&format!("/v1/sys/rotation/policies/{name}"))
+                    .await?;
+            }
+            (Kind::Rotation, _) => {
+                client
+                    .post(&format!("/v1/sys/rotation/policies/{name}"), &change.body)
+                    .await?;
+            }
+            (Kind::KubernetesSync, Action::Delete) => {
+                client
+                    .delete(&format!("/v1/sys/sync/kubernetes/{name}"))
+                    .await?;
+            }
+            (Kind::KubernetesSync, _) => {
+                client
+                    .post(&format!("/v1/sys/sync/kubernetes/{name}"), &change.body)
+                    .await?;
+            }
+        }
+        let verb = match change.action {
+            Action::Create => "created",
+            Action::Update(_) => "updated",
+            Action::Delete => "deleted",
+        };
+        outln!(
+            "  {GREEN}✓{RESET} {verb} {} {BOLD}{name}{RESET}",
+            change.kind.label()
+        );
+    }
+    Ok(())
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+fn string_list(resp: &Value, field: &str) -> Vec<String> {
+    resp.get(field)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(str::to_owned))
+        .collect()
+}
+
+/// Reduce a `GET /v1/sys/policies/{name}` response to the spec's shape.
+fn normalize_policy(policy: &Value) -> Value {
+    let rules: Vec<Value> = policy
+        .get("rules")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|rule| {
+            let capabilities: Vec<String> = string_list(rule, "capabilities")
+                .iter()
+                .map(|c| c.to_lowercase())
+                .collect();
+            let mut normalized = json!({ "path": rule.get("path"), "capabilities": capabilities });
+            if let Some(group) = rule.get("control_group").filter(|g| !g.is_null()) {
+                normalized["control_group"] = json!({
+                    "approvals": group.get("approvals"),
+                    "approver_policies": string_list(group, "approver_policies"),
+                    "approver_identities": string_list(group, "approver_identities"),
+                });
+            }
+            let mfa_methods = string_list(rule, "mfa_methods");
+            if !mfa_methods.is_empty() {
+                normalized["mfa_methods"] = json!(mfa_methods);
+            }
+            normalized
+        })
+        .collect();
+    json!({ "rules": rules })
+}
+
+fn show(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_owned(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
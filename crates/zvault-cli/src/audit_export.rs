@@ -0,0 +1,291 @@
+//! Streaming writers for `zvault audit-export`.
+//!
+//! Entries arrive one page at a time from the server's paginated audit-log
+//! query and are written out immediately, so memory use stays bounded by the
+//! page size (and one Parquet row group) however large the log is.
+//!
+//! Text formats (`json`, `ndjson`, `csv`) can be gzip-compressed on the fly.
+//! Parquet files are Snappy-compressed internally and have a typed schema
+//! with one row per audit entry.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use arrow_array::builder::{ListBuilder, StringBuilder, TimestampNanosecondBuilder, UInt16Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression as ParquetCompression;
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+
+/// Rows per Parquet row group.
+const PARQUET_ROW_GROUP_SIZE: usize = 100_000;
+
+/// Output format for an audit export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    /// A single JSON array.
+    Json,
+    /// One JSON object per line.
+    Ndjson,
+    /// `timestamp,operation,path,actor,status` rows.
+    Csv,
+    /// Apache Parquet.
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Parse a `--format` value.
+    pub(crate) fn parse(format: &str) -> Result<Self> {
+        match format {
+            "json" => Ok(Self::Json),
+            "ndjson" => Ok(Self::Ndjson),
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            other => bail!("unsupported format '{other}', expected json, ndjson, csv, or parquet"),
+        }
+    }
+}
+
+/// Destination of an export, optionally gzip-compressed.
+pub(crate) enum Sink {
+    Plain(Box<dyn Write + Send>),
+    Gzip(GzEncoder<Box<dyn Write + Send>>),
+}
+
+impl Sink {
+    /// Open `output` (stdout when `None`).
+    pub(crate) fn open(output: Option<&str>, gzip: bool) -> Result<Self> {
+        let inner: Box<dyn Write + Send> = match output {
+            Some(path) => Box::new(BufWriter::new(
+                File::create(path).with_context(|| format!("failed to create {path}"))?,
+            )),
+            None => Box::new(BufWriter::new(std::io::stdout())),
+        };
+        Ok(if gzip {
+            Self::Gzip(GzEncoder::new(inner, Compression::default()))
+        } else {
+            Self::Plain(inner)
+        })
+    }
+
+    /// Write any gzip trailer and flush.
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(mut w) => w.flush(),
+            Self::Gzip(gz) => gz.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Gzip(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Gzip(w) => w.flush(),
+        }
+    }
+}
+
+/// Incremental writer for one export.
+pub(crate) enum ExportWriter {
+    Json { sink: Sink, written: usize },
+    Ndjson { sink: Sink },
+    Csv { sink: Sink },
+    Parquet { writer: Box<ArrowWriter<Sink>> },
+}
+
+impl ExportWriter {
+    /// Start an export in `format`, writing any header.
+    pub(crate) fn new(format: ExportFormat, mut sink: Sink) -> Result<Self> {
+        Ok(match format {
+            ExportFormat::Json => {
+                sink.write_all(b"[")?;
+                Self::Json { sink, written: 0 }
+            }
+            ExportFormat::Ndjson => Self::Ndjson { sink },
+            ExportFormat::Csv => {
+                sink.write_all(b"timestamp,operation,path,actor,status\n")?;
+                Self::Csv { sink }
+            }
+            ExportFormat::Parquet => {
+                if matches!(sink, Sink::Gzip(_)) {
+                    bail!("parquet output is already compressed; drop --gzip");
+                }
+                let props = WriterProperties::builder()
+                    .set_compression(ParquetCompression::SNAPPY)
+                    .set_max_row_group_size(PARQUET_ROW_GROUP_SIZE)
+                    .build();
+                let writer = ArrowWriter::try_new(sink, parquet_schema(), Some(props))
+                    .context("failed to start parquet file")?;
+                Self::Parquet {
+                    writer: Box::new(writer),
+                }
+            }
+        })
+    }
+
+    /// Append a page of entries.
+    pub(crate) fn write_page(&mut self, entries: &[Value]) -> Result<()> {
+        match self {
+            Self::Json { sink, written } => {
+                for entry in entries {
+                    sink.write_all(if *written == 0 { b"\n  " } else { b",\n  " })?;
+                    serde_json::to_writer(&mut *sink, entry)?;
+                    *written += 1;
+                }
+            }
+            Self::Ndjson { sink } => {
+                for entry in entries {
+                    serde_json::to_writer(&mut *sink, entry)?;
+                    sink.write_all(b"\n")?;
+                }
+            }
+            Self::Csv { sink } => {
+                for entry in entries {
+                    let status = entry
+                        .pointer("/response/status_code")
+                        .and_then(Value::as_u64)
+                        .map(|s| s.to_string())
+                        .unwrap_or_default();
+                    writeln!(
+                        sink,
+                        "{},{},{},{},{status}",
+                        csv_field(str_at(entry, "/timestamp").unwrap_or("")),
+                        csv_field(str_at(entry, "/request/operation").unwrap_or("")),
+                        csv_field(str_at(entry, "/request/path").unwrap_or("")),
+                        csv_field(str_at(entry, "/auth/metadata/display_name").unwrap_or("")),
+                    )?;
+                }
+            }
+            Self::Parquet { writer } => {
+                if !entries.is_empty() {
+                    writer
+                        .write(&parquet_batch(entries)?)
+                        .context("failed to write parquet rows")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write any footer and flush the output.
+    pub(crate) fn finish(self) -> Result<()> {
+        let sink = match self {
+            Self::Json { mut sink, written } => {
+                sink.write_all(if written == 0 { b"]\n" } else { b"\n]\n" })?;
+                sink
+            }
+            Self::Ndjson { sink } | Self::Csv { sink } => sink,
+            Self::Parquet { writer } => writer
+                .into_inner()
+                .context("failed to finish parquet file")?,
+        };
+        sink.finish().context("failed to flush export")
+    }
+}
+
+fn parquet_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, true),
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+            true,
+        ),
+        Field::new("operation", DataType::Utf8, true),
+        Field::new("path", DataType::Utf8, true),
+        Field::new("remote_addr", DataType::Utf8, true),
+        Field::new("status_code", DataType::UInt16, true),
+        Field::new("error", DataType::Utf8, true),
+        Field::new("display_name", DataType::Utf8, true),
+        Field::new("token_id", DataType::Utf8, true),
+        Field::new(
+            "policies",
+            DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+            true,
+        ),
+        Field::new("request_data", DataType::Utf8, true),
+    ]))
+}
+
+fn parquet_batch(entries: &[Value]) -> Result<RecordBatch> {
+    let string_column = |pointer: &str| -> ArrayRef {
+        let mut builder = StringBuilder::new();
+        for entry in entries {
+            builder.append_option(str_at(entry, pointer));
+        }
+        Arc::new(builder.finish())
+    };
+
+    let mut timestamps = TimestampNanosecondBuilder::new().with_timezone("UTC");
+    let mut status_codes = UInt16Builder::new();
+    let mut policies = ListBuilder::new(StringBuilder::new());
+    let mut request_data = StringBuilder::new();
+    for entry in entries {
+        timestamps.append_option(
+            str_at(entry, "/timestamp")
+                .and_then(|t| arrow_cast::parse::string_to_timestamp_nanos(t).ok()),
+        );
+        status_codes.append_option(
+            entry
+                .pointer("/response/status_code")
+                .and_then(Value::as_u64)
+                .and_then(|s| u16::try_from(s).ok()),
+        );
+        match entry.pointer("/auth/policies").and_then(Value::as_array) {
+            Some(list) => {
+                for policy in list {
+                    policies.values().append_option(policy.as_str());
+                }
+                policies.append(true);
+            }
+            None => policies.append(false),
+        }
+        request_data.append_option(
+            entry
+                .pointer("/request/data")
+                .filter(|d| !d.is_null())
+                .map(Value::to_string),
+        );
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        string_column("/id"),
+        Arc::new(timestamps.finish()),
+        string_column("/request/operation"),
+        string_column("/request/path"),
+        string_column("/request/remote_addr"),
+        Arc::new(status_codes.finish()),
+        string_column("/response/error"),
+        string_column("/auth/metadata/display_name"),
+        string_column("/auth/token_id"),
+        Arc::new(policies.finish()),
+        Arc::new(request_data.finish()),
+    ];
+    RecordBatch::try_new(parquet_schema(), columns).context("failed to build parquet rows")
+}
+
+fn str_at<'a>(entry: &'a Value, pointer: &str) -> Option<&'a str> {
+    entry.pointer(pointer).and_then(Value::as_str)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
@@ -0,0 +1,159 @@
+//! `zvault import-aws` and `zvault export-aws`: bulk migration between KV
+//! and AWS Secrets Manager or SSM Parameter Store.
+//!
+//! The server does the migration in one request, so secret values go
+//! straight between it and AWS. The AWS credentials and region of this
+//! environment (`AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`,
+//! `AWS_SESSION_TOKEN`, `AWS_REGION`) are sent along with the request;
+//! `--server-credentials` uses the server's own instead. Each target is
+//! printed with what was done to it, or with `--dry-run` what would be.
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+use super::{AwsMigrateArgs, BOLD, Client, DIM, GREEN, RED, RESET, YELLOW, header, success};
+
+/// `zvault import-aws <prefix>`.
+pub(crate) async fn cmd_import_aws(
+    client: &Client,
+    prefix: &str,
+    path: &str,
+    args: &AwsMigrateArgs,
+) -> Result<()> {
+    let mut body = request(args)?;
+    body["prefix"] = Value::String(prefix.to_owned());
+    body["path"] = Value::String(path.to_owned());
+    let resp = client.post("/v1/sys/migrate/aws/import", &body).await?;
+    print_report(&resp, args, true)
+}
+
+/// `zvault export-aws <path>`.
+pub(crate) async fn cmd_export_aws(
+    client: &Client,
+    path: &str,
+    prefix: &str,
+    kms_key_id: Option<&str>,
+    args: &AwsMigrateArgs,
+) -> Result<()> {
+    let mut body = request(args)?;
+    body["path"] = Value::String(path.to_owned());
+    body["prefix"] = Value::String(prefix.to_owned());
+    body["kms_key_id"] = kms_key_id.map_or(Value::Null, |k| Value::String(k.to_owned()));
+    let resp = client.post("/v1/sys/migrate/aws/export", &body).await?;
+    print_report(&resp, args, false)
+}
+
+/// The request fields both directions share.
+fn request(args: &AwsMigrateArgs) -> Result<Value> {
+    let region = args
+        .region
+        .clone()
+        .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok());
+    let credentials = if args.server_credentials {
+        Value::Null
+    } else {
+        match (
+            std::env::var("AWS_ACCESS_KEY_ID"),
+            std::env::var("AWS_SECRET_ACCESS_KEY"),
+        ) {
+            (Ok(access_key_id), Ok(secret_access_key)) => serde_json::json!({
+                "access_key_id": access_key_id,
+                "secret_access_key": secret_access_key,
+                "session_token": std::env::var("AWS_SESSION_TOKEN").ok(),
+            }),
+            _ => bail!(
+                "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are not set; \
+                 set them or pass --server-credentials"
+            ),
+        }
+    };
+    Ok(serde_json::json!({
+        "store": args.store,
+        "region": region,
+        "credentials": credentials,
+        "mount": args.mount,
+        "conflict": args.conflict,
+        "dry_run": args.dry_run,
+    }))
+}
+
+fn print_report(resp: &Value, args: &AwsMigrateArgs, import: bool) -> Result<()> {
+    let entries = resp
+        .get("entries")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let store = if args.store == "parameter-store" {
+        "Parameter Store"
+    } else {
+        "Secrets Manager"
+    };
+    let mount = format!("{}/", args.mount.trim_matches('/'));
+
+    outln!();
+    let title = if import {
+        format!("Import from {store}")
+    } else {
+        format!("Export to {store}")
+    };
+    if args.dry_run {
+        header("📦", &format!("{title} (dry run)"));
+    } else {
+        header("📦", &title);
+    }
+    outln!();
+
+    let mut counts = [0usize; 5];
+    for entry in &entries {
+        let get = |key: &str| entry.get(key).and_then(Value::as_str).unwrap_or_default();
+        let action = get("action");
+        let (slot, mark) = match action {
+            "create" => (0, format!("{GREEN}+{RESET}")),
+            "update" => (1, format!("{YELLOW}~{RESET}")),
+            "unchanged" => (2, format!("{DIM}={RESET}")),
+            "skip" => (3, format!("{DIM}-{RESET}")),
+            _ => (4, format!("{RED}✗{RESET}")),
+        };
+        counts[slot] = counts[slot].saturating_add(1);
+        if slot == 2 {
+            continue;
+        }
+        let mut kv = format!("{mount}{}", get("kv_path"));
+        if let Some(field) = entry.get("field").and_then(Value::as_str) {
+            kv = format!("{kv} {DIM}({field}){RESET}");
+        }
+        let aws = get("aws_name");
+        let (from, to) = if import {
+            (aws, kv.as_str())
+        } else {
+            (kv.as_str(), aws)
+        };
+        let reason = entry
+            .get("reason")
+            .and_then(Value::as_str)
+            .map(|r| format!("  {DIM}{r}{RESET}"))
+            .unwrap_or_default();
+        outln!("  {mark} {BOLD}{from}{RESET} {DIM}→{RESET} {to}{reason}");
+    }
+    if entries.len() > counts[2] {
+        outln!();
+    }
+
+    let [created, updated, unchanged, skipped, failed] = counts;
+    let summary = if args.dry_run {
+        format!(
+            "{created} to create, {updated} to update, {unchanged} unchanged, {skipped} skipped"
+        )
+    } else {
+        format!("{created} created, {updated} updated, {unchanged} unchanged, {skipped} skipped")
+    };
+    success(&summary);
+    if args.dry_run && created.saturating_add(updated) > 0 {
+        outln!("  {DIM}Run again without --dry-run to apply.{RESET}");
+    }
+    outln!();
+    if failed > 0 {
+        bail!("{failed} of {} targets failed", entries.len());
+    }
+    Ok(())
+}
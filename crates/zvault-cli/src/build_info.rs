@@ -0,0 +1,53 @@
+//! Build metadata embedded at compile time by `build.rs`.
+
+/// Crate version (`CARGO_PKG_VERSION`).
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short git revision the binary was built from, or `unknown`.
+pub const GIT_SHA: &str = env!("ZVAULT_GIT_SHA");
+
+/// Target triple (e.g. `x86_64-unknown-linux-musl`).
+pub const TARGET: &str = env!("ZVAULT_BUILD_TARGET");
+
+/// Cargo profile (`release` or `debug`).
+pub const PROFILE: &str = env!("ZVAULT_BUILD_PROFILE");
+
+/// `--version` output: `0.2.0 (abc123def456 x86_64-unknown-linux-gnu release)`.
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("ZVAULT_GIT_SHA"),
+    " ",
+    env!("ZVAULT_BUILD_TARGET"),
+    " ",
+    env!("ZVAULT_BUILD_PROFILE"),
+    ")"
+);
+
+/// `zvault build-info`: print the embedded build metadata.
+///
+/// `--json` prints a single object, which release tooling uses to name and
+/// check artifacts.
+pub fn cmd_build_info(json: bool) -> anyhow::Result<()> {
+    let platform = super::self_update::platform();
+    if json {
+        let info = serde_json::json!({
+            "version": VERSION,
+            "git_sha": GIT_SHA,
+            "target": TARGET,
+            "profile": PROFILE,
+            "platform": platform,
+        });
+        outln!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    super::header("ℹ", "Build Info");
+    super::kv_line("Version", VERSION);
+    super::kv_line("Revision", GIT_SHA);
+    super::kv_line("Target", TARGET);
+    super::kv_line("Profile", PROFILE);
+    super::kv_line("Platform", &platform);
+    outln!();
+    Ok(())
+}
@@ -143,6 +143,12 @@ impl CloudClient {
 }
 
 async fn handle_cloud_response(resp: reqwest::Response) -> Result<Value> {
+    let value = parse_cloud_response(resp).await?;
+    crate::output::record(&value);
+    Ok(value)
+}
+
+async fn parse_cloud_response(resp: reqwest::Response) -> Result<Value> {
     let status = resp.status();
     if status == reqwest::StatusCode::NO_CONTENT {
         return Ok(Value::Null);
@@ -266,9 +272,9 @@ fn build_client() -> Result<CloudClient> {
 pub async fn cmd_cloud_login() -> Result<()> {
     let base_url = resolve_cloud_url();
 
-    println!();
+    outln!();
     header("🔐", "ZVault Cloud Login");
-    println!();
+    outln!();
 
     // Start a tiny local HTTP server to receive the callback token.
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
@@ -281,12 +287,12 @@ pub async fn cmd_cloud_login() -> Result<()> {
 
     let login_url = format!("{base_url}/cli/auth?port={port}");
 
-    println!("  {DIM}Opening browser for authentication...{RESET}");
-    println!();
-    println!("  {CYAN}{login_url}{RESET}");
-    println!();
-    println!("  {DIM}If the browser doesn't open, copy the URL above.{RESET}");
-    println!();
+    outln!("  {DIM}Opening browser for authentication...{RESET}");
+    outln!();
+    outln!("  {CYAN}{login_url}{RESET}");
+    outln!();
+    outln!("  {DIM}If the browser doesn't open, copy the URL above.{RESET}");
+    outln!();
 
     // Try to open the browser (best-effort).
     let _ = open_browser(&login_url);
@@ -322,12 +328,12 @@ pub async fn cmd_cloud_login() -> Result<()> {
     .context("login timed out after 120 seconds")??;
 
     let path = save_cloud_token(&token)?;
-    println!();
+    outln!();
     success(&format!(
         "Logged in to ZVault Cloud. Token saved to {DIM}{}{RESET}",
         path.display()
     ));
-    println!();
+    outln!();
 
     Ok(())
 }
@@ -335,9 +341,9 @@ pub async fn cmd_cloud_login() -> Result<()> {
 /// `zvault logout` — remove saved cloud token.
 pub async fn cmd_cloud_logout() -> Result<()> {
     remove_cloud_token()?;
-    println!();
+    outln!();
     success("Logged out of ZVault Cloud.");
-    println!();
+    outln!();
     Ok(())
 }
 
@@ -345,9 +351,9 @@ pub async fn cmd_cloud_logout() -> Result<()> {
 pub async fn cmd_cloud_init(org: Option<&str>, project: Option<&str>) -> Result<()> {
     let client = build_client()?;
 
-    println!();
+    outln!();
     header("☁️", "Link to ZVault Cloud Project");
-    println!();
+    outln!();
 
     // If org/project not provided, list available ones and prompt.
     let org_slug = if let Some(o) = org {
@@ -364,14 +370,14 @@ pub async fn cmd_cloud_init(org: Option<&str>, project: Option<&str>) -> Result<
             bail!("no organizations found — create one at app.zvault.cloud");
         }
 
-        println!("  {BOLD}Your organizations:{RESET}");
+        outln!("  {BOLD}Your organizations:{RESET}");
         for (i, o) in orgs.iter().enumerate() {
             let name = o.get("name").and_then(Value::as_str).unwrap_or("?");
             let slug = o.get("slug").and_then(Value::as_str).unwrap_or("?");
             let idx = i.saturating_add(1);
-            println!("  {DIM}{idx}.{RESET} {name} {DIM}({slug}){RESET}");
+            outln!("  {DIM}{idx}.{RESET} {name} {DIM}({slug}){RESET}");
         }
-        println!();
+        outln!();
 
         // Use the first org if only one exists.
         if orgs.len() == 1 {
@@ -379,7 +385,7 @@ pub async fn cmd_cloud_init(org: Option<&str>, project: Option<&str>) -> Result<
                 .get("slug")
                 .and_then(Value::as_str)
                 .unwrap_or("default");
-            println!("  {DIM}Using org:{RESET} {BOLD}{slug}{RESET}");
+            outln!("  {DIM}Using org:{RESET} {BOLD}{slug}{RESET}");
             slug.to_owned()
         } else {
             bail!(
@@ -410,7 +416,7 @@ pub async fn cmd_cloud_init(org: Option<&str>, project: Option<&str>) -> Result<
     });
 
     if !project_exists {
-        println!(
+        outln!(
             "  {DIM}Project '{project_name}' not found — creating...{RESET}"
         );
         let body = serde_json::json!({ "name": project_name });
@@ -427,13 +433,13 @@ pub async fn cmd_cloud_init(org: Option<&str>, project: Option<&str>) -> Result<
     std::fs::write(CLOUD_CONFIG_FILE, &toml_content)
         .with_context(|| format!("failed to write {CLOUD_CONFIG_FILE}"))?;
 
-    println!();
+    outln!();
     success(&format!(
         "Linked to {BOLD}{org_slug}/{project_name}{RESET}"
     ));
     kv_line("Config", CLOUD_CONFIG_FILE);
     kv_line("Default env", "development");
-    println!();
+    outln!();
 
     Ok(())
 }
@@ -454,16 +460,16 @@ pub async fn cmd_cloud_push(env_file: Option<&str>, env: Option<&str>) -> Result
         bail!("no secrets found in {file_path}");
     }
 
-    println!();
+    outln!();
     header(
         "⬆️",
         &format!("Pushing secrets to {}/{}", cfg.org, cfg.project),
     );
-    println!();
+    outln!();
     kv_line("Environment", environment);
     kv_line("Source", file_path);
     kv_line("Secrets", &entries.len().to_string());
-    println!();
+    outln!();
 
     let mut pushed = 0u32;
     let mut failed = 0u32;
@@ -482,17 +488,17 @@ pub async fn cmd_cloud_push(env_file: Option<&str>, env: Option<&str>) -> Result
 
         match client.put(&path, &body).await {
             Ok(_) => {
-                println!("  {GREEN}✓{RESET} {key}");
+                outln!("  {GREEN}✓{RESET} {key}");
                 pushed = pushed.saturating_add(1);
             }
             Err(e) => {
-                println!("  {RED}✗{RESET} {key} — {RED}{e}{RESET}");
+                outln!("  {RED}✗{RESET} {key} — {RED}{e}{RESET}");
                 failed = failed.saturating_add(1);
             }
         }
     }
 
-    println!();
+    outln!();
     if failed == 0 {
         success(&format!("Pushed {pushed} secrets to {environment}"));
     } else {
@@ -500,7 +506,7 @@ pub async fn cmd_cloud_push(env_file: Option<&str>, env: Option<&str>) -> Result
             "Pushed {pushed} secrets, {failed} failed"
         ));
     }
-    println!();
+    outln!();
 
     Ok(())
 }
@@ -511,12 +517,12 @@ pub async fn cmd_cloud_pull(env: Option<&str>, output: Option<&str>, format: &st
     let client = build_client()?;
     let environment = env.unwrap_or(&cfg.default_env);
 
-    println!();
+    outln!();
     header(
         "⬇️",
         &format!("Pulling secrets from {}/{}", cfg.org, cfg.project),
     );
-    println!();
+    outln!();
     kv_line("Environment", environment);
     kv_line("Format", format);
 
@@ -533,9 +539,9 @@ pub async fn cmd_cloud_pull(env: Option<&str>, output: Option<&str>, format: &st
         .unwrap_or_default();
 
     if secrets.is_empty() {
-        println!();
+        outln!();
         warning(&format!("No secrets found in {environment}"));
-        println!();
+        outln!();
         return Ok(());
     }
 
@@ -554,12 +560,12 @@ pub async fn cmd_cloud_pull(env: Option<&str>, output: Option<&str>, format: &st
     std::fs::write(out_path, &content)
         .with_context(|| format!("failed to write {out_path}"))?;
 
-    println!();
+    outln!();
     success(&format!(
         "Pulled {} secrets to {BOLD}{out_path}{RESET} ({format})",
         secrets.len()
     ));
-    println!();
+    outln!();
 
     Ok(())
 }
@@ -654,9 +660,9 @@ fn format_as_yaml(cfg: &CloudConfig, environment: &str, secrets: &[Value]) -> St
 
 /// `zvault cloud status` — show linked project, current env, token status.
 pub async fn cmd_cloud_status() -> Result<()> {
-    println!();
+    outln!();
     header("☁️", "ZVault Cloud Status");
-    println!();
+    outln!();
 
     // Check cloud config.
     match load_cloud_config() {
@@ -685,7 +691,7 @@ pub async fn cmd_cloud_status() -> Result<()> {
     }
 
     kv_line("Cloud URL", &resolve_cloud_url());
-    println!();
+    outln!();
 
     Ok(())
 }
@@ -695,9 +701,9 @@ pub async fn cmd_cloud_envs() -> Result<()> {
     let cfg = load_cloud_config()?;
     let client = build_client()?;
 
-    println!();
+    outln!();
     header("🌍", &format!("Environments — {}/{}", cfg.org, cfg.project));
-    println!();
+    outln!();
 
     let path = format!(
         "/v1/cloud/orgs/{}/projects/{}/environments",
@@ -712,7 +718,7 @@ pub async fn cmd_cloud_envs() -> Result<()> {
         .unwrap_or_default();
 
     if envs.is_empty() {
-        println!("  {DIM}(no environments){RESET}");
+        outln!("  {DIM}(no environments){RESET}");
     } else {
         for env in &envs {
             let name = env.get("name").and_then(Value::as_str).unwrap_or("?");
@@ -722,11 +728,11 @@ pub async fn cmd_cloud_envs() -> Result<()> {
             } else {
                 String::new()
             };
-            println!("  {CYAN}●{RESET} {name} {DIM}({count} secrets){RESET}{marker}");
+            outln!("  {CYAN}●{RESET} {name} {DIM}({count} secrets){RESET}{marker}");
         }
     }
 
-    println!();
+    outln!();
     Ok(())
 }
 
@@ -736,12 +742,12 @@ pub async fn cmd_cloud_secrets(env: Option<&str>) -> Result<()> {
     let client = build_client()?;
     let environment = env.unwrap_or(&cfg.default_env);
 
-    println!();
+    outln!();
     header(
         "🔑",
         &format!("{}/{} — {environment}", cfg.org, cfg.project),
     );
-    println!();
+    outln!();
 
     let path = format!(
         "/v1/cloud/orgs/{}/projects/{}/secrets?environment={environment}",
@@ -756,7 +762,7 @@ pub async fn cmd_cloud_secrets(env: Option<&str>) -> Result<()> {
         .unwrap_or_default();
 
     if secrets.is_empty() {
-        println!("  {DIM}(no secrets in {environment}){RESET}");
+        outln!("  {DIM}(no secrets in {environment}){RESET}");
     } else {
         for secret in &secrets {
             let key = secret.get("key").and_then(Value::as_str).unwrap_or("?");
@@ -764,13 +770,13 @@ pub async fn cmd_cloud_secrets(env: Option<&str>) -> Result<()> {
                 .get("updated_at")
                 .and_then(Value::as_str)
                 .unwrap_or("");
-            println!("  {CYAN}├─{RESET} {key} {DIM}{updated}{RESET}");
+            outln!("  {CYAN}├─{RESET} {key} {DIM}{updated}{RESET}");
         }
-        println!();
-        println!("  {DIM}{} secrets in {environment}{RESET}", secrets.len());
+        outln!();
+        outln!("  {DIM}{} secrets in {environment}{RESET}", secrets.len());
     }
 
-    println!();
+    outln!();
     Ok(())
 }
 
@@ -779,18 +785,21 @@ pub async fn cmd_cloud_token_create(
     name: &str,
     env: Option<&str>,
     ttl: Option<&str>,
+    write: bool,
 ) -> Result<()> {
     let cfg = load_cloud_config()?;
     let client = build_client()?;
     let environment = env.unwrap_or(&cfg.default_env);
 
-    println!();
+    outln!();
     header("🪙", "Create Service Token");
-    println!();
+    outln!();
 
+    let permissions: &[&str] = if write { &["read", "write"] } else { &["read"] };
     let mut body = serde_json::json!({
         "name": name,
         "environment": environment,
+        "permissions": permissions,
     });
 
     if let Some(ttl_val) = ttl {
@@ -806,21 +815,27 @@ pub async fn cmd_cloud_token_create(
     let resp = client.post(&path, &body).await?;
 
     let token = resp
-        .get("token")
+        .get("plaintext_token")
         .and_then(Value::as_str)
         .unwrap_or("(unknown)");
+    let expires = resp
+        .pointer("/token/expires_at")
+        .and_then(Value::as_str)
+        .unwrap_or("never");
 
     kv_line("Name", name);
     kv_line("Environment", environment);
     kv_line("Project", &format!("{}/{}", cfg.org, cfg.project));
-    println!();
-    println!("  {YELLOW}{BOLD}⚠  Save this token — it will NOT be shown again.{RESET}");
-    println!();
-    println!("  {GREEN}{BOLD}{token}{RESET}");
-    println!();
-    println!("  {DIM}Usage:{RESET}");
-    println!("    ZVAULT_TOKEN={token} zvault run -- npm start");
-    println!();
+    kv_line("Permissions", &permissions.join(", "));
+    kv_line("Expires", expires);
+    outln!();
+    outln!("  {YELLOW}{BOLD}⚠  Save this token — it will NOT be shown again.{RESET}");
+    outln!();
+    outln!("  {GREEN}{BOLD}{token}{RESET}");
+    outln!();
+    outln!("  {DIM}Usage:{RESET}");
+    outln!("    ZVAULT_TOKEN={token} zvault run -- npm start");
+    outln!();
 
     Ok(())
 }
@@ -836,9 +851,9 @@ pub async fn cmd_cloud_token_revoke(token_id: &str) -> Result<()> {
     );
     client.delete(&path).await?;
 
-    println!();
+    outln!();
     success(&format!("Token {BOLD}{token_id}{RESET} revoked."));
-    println!();
+    outln!();
 
     Ok(())
 }
@@ -848,9 +863,9 @@ pub async fn cmd_cloud_token_list() -> Result<()> {
     let cfg = load_cloud_config()?;
     let client = build_client()?;
 
-    println!();
+    outln!();
     header("🪙", &format!("Service Tokens — {}/{}", cfg.org, cfg.project));
-    println!();
+    outln!();
 
     let path = format!(
         "/v1/cloud/orgs/{}/projects/{}/tokens",
@@ -865,20 +880,44 @@ pub async fn cmd_cloud_token_list() -> Result<()> {
         .unwrap_or_default();
 
     if tokens.is_empty() {
-        println!("  {DIM}(no service tokens){RESET}");
+        outln!("  {DIM}(no service tokens){RESET}");
     } else {
         for t in &tokens {
             let name = t.get("name").and_then(Value::as_str).unwrap_or("?");
-            let env_name = t.get("environment").and_then(Value::as_str).unwrap_or("?");
-            let created = t.get("created_at").and_then(Value::as_str).unwrap_or("");
             let id = t.get("id").and_then(Value::as_str).unwrap_or("?");
-            println!(
-                "  {MAGENTA}⚷{RESET}  {name} {DIM}({env_name}) — {id} — {created}{RESET}"
+            let scope = if t.get("environment_id").and_then(Value::as_str).is_some() {
+                "one env"
+            } else {
+                "all envs"
+            };
+            let access = if t
+                .get("permissions")
+                .and_then(Value::as_array)
+                .is_some_and(|p| p.iter().any(|p| p.as_str() == Some("write")))
+            {
+                "read-write"
+            } else {
+                "read-only"
+            };
+            let status = if let Some(revoked) = t.get("revoked_at").and_then(Value::as_str) {
+                format!("{RED}revoked {revoked}{RESET}")
+            } else if let Some(expires) = t.get("expires_at").and_then(Value::as_str) {
+                format!("{DIM}expires {expires}{RESET}")
+            } else {
+                format!("{YELLOW}never expires{RESET}")
+            };
+            let last_used = t
+                .get("last_used_at")
+                .and_then(Value::as_str)
+                .unwrap_or("never");
+            outln!(
+                "  {MAGENTA}⚷{RESET}  {name} {DIM}({scope}, {access}) — {id}{RESET}"
             );
+            outln!("     {status} {DIM}— last used {last_used}{RESET}");
         }
     }
 
-    println!();
+    outln!();
     Ok(())
 }
 
@@ -891,12 +930,12 @@ pub async fn cmd_cloud_run(env: &str, command: &[String]) -> Result<()> {
     let cfg = load_cloud_config()?;
     let client = build_client()?;
 
-    println!();
+    outln!();
     header(
         "🔑",
         &format!("Resolving secrets from cloud ({env})"),
     );
-    println!();
+    outln!();
 
     let path = format!(
         "/v1/cloud/orgs/{}/projects/{}/secrets?environment={env}",
@@ -919,20 +958,20 @@ pub async fn cmd_cloud_run(env: &str, command: &[String]) -> Result<()> {
     for secret in &secrets {
         let key = secret.get("key").and_then(Value::as_str).unwrap_or("");
         let value = secret.get("value").and_then(Value::as_str).unwrap_or("");
-        println!("  {GREEN}✓{RESET} {key}");
+        outln!("  {GREEN}✓{RESET} {key}");
         env_vars.push((key.to_owned(), value.to_owned()));
     }
 
-    println!();
-    println!("  {DIM}Resolved {} secrets from {env}{RESET}", env_vars.len());
-    println!();
+    outln!();
+    outln!("  {DIM}Resolved {} secrets from {env}{RESET}", env_vars.len());
+    outln!();
 
     // Execute the child process with injected environment.
     let program = &command[0];
     let args = &command[1..];
 
-    println!("  {CYAN}{BOLD}▶{RESET} {BOLD}{}{RESET}", command.join(" "));
-    println!();
+    outln!("  {CYAN}{BOLD}▶{RESET} {BOLD}{}{RESET}", command.join(" "));
+    outln!();
 
     let status = std::process::Command::new(program)
         .args(args)
@@ -0,0 +1,111 @@
+//! Shell completions: `zvault completions <shell>`.
+//!
+//! The printed script calls back into `zvault` (with `ZVAULT_COMPLETE` set)
+//! on every <TAB>, so completions always match the installed binary. Secret
+//! path arguments complete from the server's `/v1/secret/list/` when
+//! `VAULT_TOKEN` is set; without a token, or if the server does not answer
+//! quickly, they simply offer nothing.
+
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::{CompleteEnv, Shells};
+use serde_json::Value;
+
+use super::{Cli, Client};
+
+/// Environment variable the completion script sets when calling back.
+const COMPLETE_VAR: &str = "ZVAULT_COMPLETE";
+
+/// Shells `zvault completions` supports.
+pub(crate) const SHELLS: [&str; 5] = ["bash", "elvish", "fish", "powershell", "zsh"];
+
+/// How long a secret path completion waits for the server.
+const LIST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Answer a completion request from the shell script and exit, if this
+/// process is one. Must run before anything is printed.
+pub(crate) fn handle_request() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Print the completion script for `shell`.
+pub(crate) fn cmd_completions(shell: &str) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(shell)
+        .with_context(|| format!("unsupported shell '{shell}'"))?;
+    let mut stdout = std::io::stdout().lock();
+    completer
+        .write_registration(COMPLETE_VAR, "zvault", "zvault", "zvault", &mut stdout)
+        .context("failed to write the completion script")
+}
+
+/// Complete a secret path in the `secret/` mount, one folder at a time.
+pub(crate) fn secret_path(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = current.to_str() else {
+        return Vec::new();
+    };
+    let Ok(token) = std::env::var("VAULT_TOKEN") else {
+        return Vec::new();
+    };
+    let dir = current.rfind('/').map_or("", |i| &current[..=i]).to_owned();
+
+    // Completion runs inside `main`'s runtime, which cannot be blocked on.
+    let keys = std::thread::scope(|s| {
+        s.spawn(|| list_keys(token, &dir))
+            .join()
+            .unwrap_or_default()
+    });
+
+    let mut children: Vec<String> = keys
+        .iter()
+        .map(|key| match key.split_once('/') {
+            Some((folder, _)) => format!("{dir}{folder}/"),
+            None => format!("{dir}{key}"),
+        })
+        .filter(|path| path.starts_with(current))
+        .collect();
+    children.sort();
+    children.dedup();
+    children.into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// Keys under `dir`, relative to it, or nothing on any error.
+fn list_keys(token: String, dir: &str) -> Vec<String> {
+    let addr = std::env::var("VAULT_ADDR").unwrap_or_else(|_| "http://127.0.0.1:8200".to_owned());
+    let ca_cert = std::env::var_os("VAULT_CACERT").map(PathBuf::from);
+    let Ok(client) = Client::new(addr, Some(token), None, ca_cert.as_deref(), None) else {
+        return Vec::new();
+    };
+    let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return Vec::new();
+    };
+    let path = if dir.is_empty() {
+        "/v1/secret/list".to_owned()
+    } else {
+        format!("/v1/secret/list/{dir}")
+    };
+    let request = async { tokio::time::timeout(LIST_TIMEOUT, client.get(&path)).await };
+    let Ok(Ok(resp)) = runtime.block_on(request) else {
+        return Vec::new();
+    };
+    resp.pointer("/data/keys")
+        .and_then(Value::as_array)
+        .map(|keys| {
+            keys.iter()
+                .filter_map(Value::as_str)
+                .map(|key| key.trim_start_matches('/').to_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
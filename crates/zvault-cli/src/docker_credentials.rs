@@ -0,0 +1,231 @@
+//! `zvault docker-credential-helper`: registry credentials for Docker, kept
+//! in the vault.
+//!
+//! Docker runs `docker-credential-<name> get|store|erase|list` with the
+//! request on stdin and reads the reply from stdout. With a
+//! `docker-credential-zvault` link to this binary on `PATH` and
+//! `"credsStore": "zvault"` (or per-registry `"credHelpers"`) in
+//! `~/.docker/config.json`, `docker login` stores credentials in the vault
+//! and `docker pull` reads them back, instead of keeping them base64-encoded
+//! in the config file.
+//!
+//! Each registry is one secret under `secret/<prefix>/` (`--prefix` or
+//! `ZVAULT_DOCKER_PREFIX`, default `docker`) with `server_url`, `username`,
+//! and `secret` fields. It is named after the registry without the scheme,
+//! lower-cased, with characters KV paths do not allow replaced by `_`, so
+//! `ghcr.io` is `secret/docker/ghcr_io`. Errors go to stdout as plain text,
+//! which is where Docker looks for them.
+
+use std::ffi::OsString;
+use std::io::Read as _;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Client, DockerCredentialCommands, kv_payload, parse_response};
+
+/// Docker runs credential helpers as `docker-credential-<name>`.
+const HELPER_PREFIX: &str = "docker-credential-";
+
+/// Reply Docker recognizes as "no credentials for this registry".
+const NOT_FOUND: &str = "credentials not found in native keychain";
+
+/// Credentials as Docker sends them to `store` and expects from `get`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Credentials {
+    #[serde(rename = "ServerURL")]
+    server_url: String,
+    #[serde(rename = "Username")]
+    username: String,
+    #[serde(rename = "Secret")]
+    secret: String,
+}
+
+/// The command line to parse when this binary runs as
+/// `docker-credential-<name>`: the same arguments after
+/// `zvault docker-credential-helper`.
+pub(crate) fn helper_args() -> Option<Vec<OsString>> {
+    let mut args = std::env::args_os();
+    let program = args.next()?;
+    let name = Path::new(&program).file_stem()?.to_str()?;
+    if !name.starts_with(HELPER_PREFIX) {
+        return None;
+    }
+    let mut helper = vec![
+        OsString::from("zvault"),
+        OsString::from("docker-credential-helper"),
+    ];
+    helper.extend(args);
+    Some(helper)
+}
+
+/// Answer one credential helper request.
+pub(crate) async fn cmd_docker_credentials(
+    client: &Client,
+    prefix: &str,
+    action: DockerCredentialCommands,
+) -> Result<()> {
+    let prefix = prefix.trim_matches('/');
+    let result = match action {
+        DockerCredentialCommands::Get => get(client, prefix).await,
+        DockerCredentialCommands::Store => store(client, prefix).await,
+        DockerCredentialCommands::Erase => erase(client, prefix).await,
+        DockerCredentialCommands::List => list(client, prefix).await,
+    };
+    if let Err(e) = &result {
+        println!("{e:#}");
+    }
+    result
+}
+
+async fn get(client: &Client, prefix: &str) -> Result<()> {
+    let server_url = read_stdin()?;
+    let Some(stored) = read(client, prefix, &server_url).await? else {
+        bail!(NOT_FOUND);
+    };
+    let field = |name: &str| {
+        stored
+            .get(name)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+    let credentials = Credentials {
+        server_url,
+        username: field("username"),
+        secret: field("secret"),
+    };
+    println!("{}", serde_json::to_string(&credentials)?);
+    Ok(())
+}
+
+async fn store(client: &Client, prefix: &str) -> Result<()> {
+    let credentials: Credentials =
+        serde_json::from_str(&read_stdin()?).context("invalid credentials JSON on stdin")?;
+    let key = registry_key(&credentials.server_url)?;
+    if let Some(stored) = read_key(client, prefix, &key).await? {
+        let other = stored
+            .get("server_url")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if normalize(other) != normalize(&credentials.server_url) {
+            bail!("secret/{prefix}/{key} already holds credentials for {other}");
+        }
+    }
+    let body = serde_json::json!({
+        "data": {
+            "server_url": credentials.server_url,
+            "username": credentials.username,
+            "secret": credentials.secret,
+        }
+    });
+    client
+        .post(&format!("/v1/secret/data/{prefix}/{key}"), &body)
+        .await?;
+    Ok(())
+}
+
+async fn erase(client: &Client, prefix: &str) -> Result<()> {
+    let server_url = read_stdin()?;
+    if read(client, prefix, &server_url).await?.is_none() {
+        bail!(NOT_FOUND);
+    }
+    let key = registry_key(&server_url)?;
+    client
+        .delete(&format!("/v1/secret/data/{prefix}/{key}"))
+        .await?;
+    Ok(())
+}
+
+/// Print `{server URL: username}` for every stored registry.
+async fn list(client: &Client, prefix: &str) -> Result<()> {
+    let resp = client.get(&format!("/v1/secret/list/{prefix}")).await?;
+    let keys: Vec<&str> = resp
+        .pointer("/data/keys")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|k| k.trim_start_matches('/'))
+        .filter(|k| !k.ends_with('/'))
+        .collect();
+
+    let mut registries = serde_json::Map::new();
+    for key in keys {
+        // Erased registries stay listed until their metadata is destroyed.
+        let Some(stored) = read_key(client, prefix, key).await? else {
+            continue;
+        };
+        if let Some(server_url) = stored.get("server_url").and_then(Value::as_str) {
+            let username = stored.get("username").cloned().unwrap_or_default();
+            registries.insert(server_url.to_owned(), username);
+        }
+    }
+    println!("{}", Value::Object(registries));
+    Ok(())
+}
+
+/// The stored credentials of `server_url`, if any.
+async fn read(client: &Client, prefix: &str, server_url: &str) -> Result<Option<Value>> {
+    let key = registry_key(server_url)?;
+    let stored = read_key(client, prefix, &key).await?;
+    // Another registry with the same key, e.g. `my.reg` and `my_reg`.
+    Ok(stored.filter(|s| {
+        s.get("server_url")
+            .and_then(Value::as_str)
+            .is_some_and(|url| normalize(url) == normalize(server_url))
+    }))
+}
+
+async fn read_key(client: &Client, prefix: &str, key: &str) -> Result<Option<Value>> {
+    let resp = client
+        .request(
+            reqwest::Method::GET,
+            &format!("/v1/secret/data/{prefix}/{key}"),
+            None,
+        )
+        .await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body = parse_response(resp).await?;
+    Ok(Some(kv_payload(&body).clone()))
+}
+
+fn read_stdin() -> Result<String> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .context("failed to read stdin")?;
+    Ok(input.trim().to_owned())
+}
+
+/// `https://Index.Docker.io/v1/` and `index.docker.io/v1` are one registry.
+fn normalize(server_url: &str) -> String {
+    let url = server_url.trim();
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    url.trim_end_matches('/').to_ascii_lowercase()
+}
+
+/// Secret name of a registry, e.g. `index_docker_io_v1`.
+fn registry_key(server_url: &str) -> Result<String> {
+    let url = normalize(server_url);
+    if url.is_empty() {
+        bail!("no registry server URL given");
+    }
+    Ok(url
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,84 @@
+//! Git hooks: `zvault hooks install` and `zvault hooks uninstall`.
+//!
+//! The pre-commit hook runs `zvault scan --staged --exit-code`, so a commit
+//! that stages secret-looking values or a plaintext `.env` file is refused.
+//! The hook goes wherever git looks for hooks, honouring `core.hooksPath`.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+
+use super::{BOLD, DIM, RESET, success};
+
+/// Identifies a hook written by zvault, so it can be replaced or removed.
+const MARKER: &str = "installed by `zvault hooks install`";
+
+const PRE_COMMIT: &str = "#!/bin/sh
+# zvault pre-commit hook, installed by `zvault hooks install`.
+# Blocks commits that stage secret-looking values or plaintext .env files.
+# Allowlist known-safe values under [scan] in .zvault.toml, or bypass once
+# with `git commit --no-verify`.
+if ! command -v zvault >/dev/null 2>&1; then
+    echo \"zvault not found on PATH; skipping the secret scan\" >&2
+    exit 0
+fi
+exec zvault scan --staged --exit-code
+";
+
+/// Where git runs the pre-commit hook from.
+fn pre_commit_path() -> Result<PathBuf> {
+    let path = crate::scan::git(&["rev-parse", "--git-path", "hooks/pre-commit"])
+        .context("not inside a git repository")?;
+    let path = String::from_utf8(path).context("hook path is not UTF-8")?;
+    Ok(PathBuf::from(path.trim()))
+}
+
+/// Whether the hook at `path` was written by zvault.
+fn is_ours(path: &PathBuf) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|content| content.contains(MARKER))
+}
+
+/// Install the pre-commit hook.
+pub(crate) fn cmd_hooks_install(force: bool) -> Result<()> {
+    let path = pre_commit_path()?;
+    if path.exists() && !is_ours(&path) && !force {
+        bail!(
+            "{} already exists and was not written by zvault — pass --force to replace it",
+            path.display()
+        );
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    crate::render::write_file(&path.to_string_lossy(), PRE_COMMIT, 0o755)?;
+
+    outln!();
+    success(&format!(
+        "Installed pre-commit hook at {BOLD}{}{RESET}",
+        path.display()
+    ));
+    outln!("  {DIM}Commits staging secrets or .env files are now blocked.{RESET}");
+    outln!();
+    Ok(())
+}
+
+/// Remove the pre-commit hook, if zvault wrote it.
+pub(crate) fn cmd_hooks_uninstall() -> Result<()> {
+    let path = pre_commit_path()?;
+    if !path.exists() {
+        bail!("no pre-commit hook at {}", path.display());
+    }
+    if !is_ours(&path) {
+        bail!(
+            "{} was not written by zvault — leaving it in place",
+            path.display()
+        );
+    }
+    std::fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+
+    outln!();
+    success(&format!("Removed pre-commit hook at {}", path.display()));
+    outln!();
+    Ok(())
+}
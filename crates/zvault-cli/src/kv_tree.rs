@@ -0,0 +1,313 @@
+//! Whole-subtree KV commands: `kv export`, `kv import`, `kv copy`, `kv move`,
+//! and `kv diff`.
+//!
+//! Export writes one map of relative path → secret data (latest version
+//! only), as JSON or YAML; import reads the same shape back under any
+//! prefix. Copy and move run on the server, which carries every version
+//! and all metadata across in one request. Diff compares key sets, and with
+//! `--values` the server's HMACs of each value, so values never leave it.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use super::{BOLD, CYAN, Client, DIM, GREEN, RED, RESET, YELLOW, header, kv_payload, success};
+
+/// `prefix` as a directory: empty for the mount root, else ending in `/`.
+pub(crate) fn as_dir(prefix: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{prefix}/")
+    }
+}
+
+/// Every secret path under `dir`, relative to it.
+pub(crate) async fn list_tree(client: &Client, mount: &str, dir: &str) -> Result<Vec<String>> {
+    let path = if dir.is_empty() {
+        "/v1/{mount}/list".to_owned()
+    } else {
+        format!("/v1/{mount}/list/{dir}")
+    };
+    let resp = client.get(&path).await?;
+    let mut keys: Vec<String> = resp
+        .pointer("/data/keys")
+        .and_then(Value::as_array)
+        .map(|keys| {
+            keys.iter()
+                .filter_map(Value::as_str)
+                .map(|k| k.trim_start_matches('/').to_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    keys.sort();
+    Ok(keys)
+}
+
+/// Dump every secret under `prefix` to stdout or `output`.
+pub(crate) async fn cmd_kv_export(
+    client: &Client,
+    mount: &str,
+    prefix: &str,
+    output: Option<&str>,
+    yaml: bool,
+) -> Result<()> {
+    let dir = as_dir(prefix);
+    let mut tree = BTreeMap::new();
+    for key in list_tree(client, mount, &dir).await? {
+        let resp = client
+            .get(&format!("/v1/{mount}/data/{dir}{key}"))
+            .await
+            .with_context(|| format!("failed to read {dir}{key}"))?;
+        tree.insert(key, kv_payload(&resp).clone());
+    }
+    if tree.is_empty() {
+        bail!("no secrets under {mount}/{dir}");
+    }
+
+    let yaml = yaml
+        || output
+            .and_then(|o| std::path::Path::new(o).extension())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+    let contents = if yaml {
+        serde_yaml_ng::to_string(&tree)?
+    } else {
+        let mut json = serde_json::to_string_pretty(&tree)?;
+        json.push('\n');
+        json
+    };
+    match output {
+        Some(output) => {
+            crate::render::write_file(output, &contents, 0o600)?;
+            outln!();
+            success(&format!(
+                "Exported {} secrets under {BOLD}{mount}/{dir}{RESET} to {output}",
+                tree.len()
+            ));
+            outln!();
+        }
+        None => print!("{contents}"),
+    }
+    Ok(())
+}
+
+/// Write every secret in an export file under `prefix`.
+pub(crate) async fn cmd_kv_import(
+    client: &Client,
+    mount: &str,
+    file: &str,
+    prefix: &str,
+    overwrite: bool,
+) -> Result<()> {
+    let source = std::fs::read_to_string(file).with_context(|| format!("failed to read {file}"))?;
+    // YAML is a superset of JSON, so one parser reads both.
+    let tree: BTreeMap<String, Value> =
+        serde_yaml_ng::from_str(&source).with_context(|| format!("invalid export file {file}"))?;
+    for (key, data) in &tree {
+        if !data.is_object() {
+            bail!("{file}: secret '{key}' must be a map of keys to values");
+        }
+    }
+    let dir = as_dir(prefix);
+
+    if !overwrite {
+        let existing = list_tree(client, mount, &dir).await.unwrap_or_default();
+        let conflicts: Vec<&String> = tree.keys().filter(|k| existing.contains(k)).collect();
+        if let Some(first) = conflicts.first() {
+            bail!(
+                "{} secrets already exist under {mount}/{dir} (first: {first}) — pass --overwrite to replace them",
+                conflicts.len()
+            );
+        }
+    }
+
+    outln!();
+    header("📥", &format!("Importing into {mount}/{dir}"));
+    for (key, data) in &tree {
+        let mut body = serde_json::json!({ "data": data });
+        if !overwrite {
+            body["options"] = serde_json::json!({ "cas": 0 });
+        }
+        client
+            .post(&format!("/v1/{mount}/data/{dir}{key}"), &body)
+            .await
+            .with_context(|| format!("failed to write {dir}{key}"))?;
+        outln!("  {CYAN}├─{RESET} {dir}{key}");
+    }
+    outln!();
+    success(&format!("Imported {} secrets from {file}", tree.len()));
+    outln!();
+    Ok(())
+}
+
+/// Copy or move a secret or subtree on the server.
+pub(crate) async fn cmd_kv_copy(
+    client: &Client,
+    mount: &str,
+    from: &str,
+    to: &str,
+    overwrite: bool,
+    remove_source: bool,
+) -> Result<()> {
+    let endpoint = if remove_source { "move" } else { "copy" };
+    let resp = client
+        .post(
+            &format!("/v1/{mount}/{endpoint}"),
+            &serde_json::json!({ "from": from, "to": to, "overwrite": overwrite }),
+        )
+        .await?;
+    let secrets = resp
+        .get("secrets")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    outln!();
+    for secret in &secrets {
+        outln!(
+            "  {CYAN}├─{RESET} {} {DIM}→{RESET} {}",
+            secret
+                .get("from")
+                .and_then(Value::as_str)
+                .unwrap_or_default(),
+            secret.get("to").and_then(Value::as_str).unwrap_or_default()
+        );
+    }
+    outln!();
+    let verb = if remove_source { "Moved" } else { "Copied" };
+    success(&format!(
+        "{verb} {} secrets with their versions and metadata",
+        secrets.len()
+    ));
+    outln!();
+    Ok(())
+}
+
+/// Secret path → key → value HMAC, from `/v1/{mount}/hashes`.
+type Hashes = BTreeMap<String, BTreeMap<String, String>>;
+
+async fn fetch_hashes(client: &Client, mount: &str, prefix: &str) -> Result<Hashes> {
+    let dir = as_dir(prefix);
+    let path = if dir.is_empty() {
+        "/v1/{mount}/hashes".to_owned()
+    } else {
+        format!("/v1/{mount}/hashes/{dir}")
+    };
+    let resp = client.get(&path).await?;
+    let secrets = resp.get("secrets").cloned().unwrap_or_default();
+    serde_json::from_value(secrets).context("unexpected /v1/{mount}/hashes response")
+}
+
+/// Compare the secrets under two prefixes, optionally on the servers of
+/// two `.zvault.toml` profiles.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cmd_kv_diff(
+    client: &Client,
+    mount: &str,
+    left: &str,
+    right: &str,
+    left_env: Option<&str>,
+    right_env: Option<&str>,
+    values: bool,
+    exit_code: bool,
+) -> Result<()> {
+    let side = |env: Option<&str>| -> Result<Option<Client>> {
+        env.map(|name| crate::profile::load(name)?.client(client))
+            .transpose()
+    };
+    let (left_client, right_client) = (side(left_env)?, side(right_env)?);
+    let left_client = left_client.as_ref().unwrap_or(client);
+    let right_client = right_client.as_ref().unwrap_or(client);
+    if values && left_client.addr != right_client.addr {
+        bail!(
+            "--values compares server-side HMACs, which differ between servers ({} and {})",
+            left_client.addr,
+            right_client.addr
+        );
+    }
+
+    let left_hashes = fetch_hashes(left_client, mount, left).await?;
+    let right_hashes = fetch_hashes(right_client, mount, right).await?;
+
+    outln!();
+    header(
+        "🔀",
+        &format!("Diff: {mount}/{} → {mount}/{}", as_dir(left), as_dir(right)),
+    );
+    let mut report = Vec::new();
+    let mut identical = 0usize;
+    let paths: std::collections::BTreeSet<&String> =
+        left_hashes.keys().chain(right_hashes.keys()).collect();
+    for path in paths {
+        match (left_hashes.get(path), right_hashes.get(path)) {
+            (Some(_), None) => {
+                outln!("  {RED}- {path}{RESET}  {DIM}only in {left}{RESET}");
+                report.push(serde_json::json!({ "path": path, "status": "missing" }));
+            }
+            (None, Some(_)) => {
+                outln!("  {GREEN}+ {path}{RESET}  {DIM}only in {right}{RESET}");
+                report.push(serde_json::json!({ "path": path, "status": "extra" }));
+            }
+            (Some(l), Some(r)) => {
+                let missing: Vec<&String> = l.keys().filter(|k| !r.contains_key(*k)).collect();
+                let extra: Vec<&String> = r.keys().filter(|k| !l.contains_key(*k)).collect();
+                let changed: Vec<&String> = if values {
+                    l.iter()
+                        .filter(|(k, hash)| r.get(*k).is_some_and(|other| other != *hash))
+                        .map(|(k, _)| k)
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                if missing.is_empty() && extra.is_empty() && changed.is_empty() {
+                    identical += 1;
+                    continue;
+                }
+                let mut detail = String::new();
+                for key in &missing {
+                    let _ = write!(detail, " {RED}-{key}{RESET}");
+                }
+                for key in &extra {
+                    let _ = write!(detail, " {GREEN}+{key}{RESET}");
+                }
+                for key in &changed {
+                    let _ = write!(detail, " {YELLOW}~{key}{RESET}");
+                }
+                outln!("  {YELLOW}~ {path}{RESET} {detail}");
+                report.push(serde_json::json!({
+                    "path": path,
+                    "status": "changed",
+                    "missing_keys": missing,
+                    "extra_keys": extra,
+                    "changed_keys": changed,
+                }));
+            }
+            (None, None) => {}
+        }
+    }
+
+    outln!();
+    let what = if values {
+        "identical"
+    } else {
+        "with the same keys"
+    };
+    outln!(
+        "  {DIM}{} differences, {identical} secrets {what}{RESET}",
+        report.len()
+    );
+    outln!();
+    let differ = !report.is_empty();
+    crate::output::record(&serde_json::json!({ "differences": report, "identical": identical }));
+    if exit_code && differ {
+        bail!(
+            "{mount}/{} and {mount}/{} differ",
+            as_dir(left),
+            as_dir(right)
+        );
+    }
+    Ok(())
+}
@@ -50,8 +50,8 @@ const POLAR_API_BASE: &str = "https://api.polar.sh/v1/customer-portal/license-ke
 //   use rand::rngs::OsRng;
 //   let sk = SigningKey::generate(&mut OsRng);
 //   let pk = sk.verifying_key();
-//   println!("public: {}", base64::encode(pk.as_bytes()));
-//   println!("secret: {}", base64::encode(sk.to_bytes()));
+//   outln!("public: {}", base64::encode(pk.as_bytes()));
+//   outln!("secret: {}", base64::encode(sk.to_bytes()));
 //
 // Replace this placeholder with the real public key before shipping.
 const PUBLIC_KEY_B64: &str = "/3mEyrpmgX5NhAd9vLGaN7wI2JraX4Q2zrEQEUcor/M=";
@@ -668,7 +668,7 @@ fn load_ed25519_license() -> Result<Option<License>> {
 
         // Reject files owned by a different user.
         let file_uid = meta.uid();
-        let my_uid = unsafe_geteuid();
+        let my_uid = nix::unistd::geteuid().as_raw();
         if file_uid != my_uid && my_uid != 0 {
             bail!(
                 "license file {} is owned by uid {} but we are uid {} — refusing to load",
@@ -691,20 +691,6 @@ fn load_ed25519_license() -> Result<Option<License>> {
     Ok(Some(license))
 }
 
-/// Get the effective user ID on Unix.
-///
-/// This is a thin wrapper around `libc::geteuid()`. We use it to verify
-/// license file ownership.
-#[cfg(unix)]
-fn unsafe_geteuid() -> u32 {
-    // SAFETY: `geteuid()` is a simple syscall with no preconditions.
-    // It cannot fail and has no side effects.
-    #[allow(unsafe_code)]
-    unsafe {
-        libc::geteuid()
-    }
-}
-
 /// Get the current license tier. Returns `Free` if no license is installed.
 pub fn current_tier() -> Tier {
     match load_license() {
@@ -715,31 +701,6 @@ pub fn current_tier() -> Tier {
 
 // ── Feature gating ───────────────────────────────────────────────────
 
-/// Check if the current license allows a Pro+ feature.
-///
-/// # Errors
-///
-/// Returns an error with a user-friendly message if the feature requires
-/// a higher tier than the current license.
-pub fn require_pro(feature_name: &str) -> Result<()> {
-    // Dev bypass: skip license check during development.
-    if std::env::var("ZVAULT_DEV").is_ok() {
-        return Ok(());
-    }
-
-    let tier = current_tier();
-    if tier >= Tier::Pro {
-        return Ok(());
-    }
-
-    bail!(
-        "{feature_name} requires a Pro license ($8/mo).\n\
-         \n  \
-         Upgrade at https://zvault.cloud/pricing\n  \
-         Then run: zvault activate <license-key>\n"
-    );
-}
-
 /// Check if the current license allows a Team+ feature.
 ///
 /// # Errors
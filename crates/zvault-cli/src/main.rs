@@ -5,19 +5,63 @@
 
 #![allow(clippy::print_stdout, clippy::print_stderr)]
 
+/// `println!` for decorated output, which moves to stderr when stdout is
+/// reserved for `--format json|yaml` or `--field` output.
+macro_rules! outln {
+    () => {
+        $crate::output::line(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::line(format_args!($($arg)*))
+    };
+}
+
+/// `print!` counterpart of `outln!`.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::output::text(format_args!($($arg)*))
+    };
+}
+
+mod agent;
+mod api_error;
+mod apply;
+mod audit_export;
+mod aws_migrate;
+mod build_info;
 mod cloud;
+mod completions;
+mod docker_credentials;
+mod hooks;
+mod kv_tree;
 mod license;
 mod mcp;
+mod mcp_audit;
+mod mcp_config;
+mod output;
+mod password_import;
+mod profile;
+mod render;
+mod scan;
+mod self_update;
 mod setup;
+mod tf_output;
+mod tui;
+mod vault_migrate;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand};
+use clap_complete::engine::ArgValueCompleter;
 use serde_json::Value;
 
+use api_error::ApiError;
+use output::OutputFormat;
+
 // ── ANSI color helpers ───────────────────────────────────────────────
 
 pub(crate) const RESET: &str = "\x1b[0m";
@@ -49,10 +93,10 @@ const BANNER: &str = r"
 const BANNER_SMALL: &str = "⟐ ZVault";
 
 fn print_banner() {
-    println!("{CYAN}{BOLD}{BANNER}{RESET}");
-    println!("  {DIM}Secrets management, done right.{RESET}");
-    println!("  {DIM}AES-256-GCM · Shamir's Secret Sharing · Zero-Trust{RESET}");
-    println!();
+    outln!("{CYAN}{BOLD}{BANNER}{RESET}");
+    outln!("  {DIM}Secrets management, done right.{RESET}");
+    outln!("  {DIM}AES-256-GCM · Shamir's Secret Sharing · Zero-Trust{RESET}");
+    outln!();
 }
 
 // ── CLI structure ────────────────────────────────────────────────────
@@ -61,13 +105,16 @@ fn print_banner() {
 #[derive(Parser)]
 #[command(
     name = "zvault",
-    version,
+    version = build_info::LONG_VERSION,
     about = "ZVault CLI — manage secrets, tokens, policies, and transit keys",
     long_about = None,
     after_help = format!(
         "{DIM}Environment variables:{RESET}\n  \
          VAULT_ADDR    Server address (default: http://127.0.0.1:8200)\n  \
-         VAULT_TOKEN   Authentication token\n\n\
+         VAULT_TOKEN   Authentication token\n  \
+         VAULT_MFA     MFA credentials, e.g. totp:123456\n  \
+         VAULT_CACERT  CA certificate to trust for HTTPS\n  \
+         VAULT_CLIENT_CERT, VAULT_CLIENT_KEY  TLS client certificate and key\n\n\
          {DIM}Examples:{RESET}\n  \
          zvault status\n  \
          zvault init --shares 5 --threshold 3\n  \
@@ -84,10 +131,36 @@ struct Cli {
     #[arg(long, env = "VAULT_TOKEN")]
     token: Option<String>,
 
+    /// MFA credentials sent as `X-Vault-MFA` (`method:code`, comma separated).
+    #[arg(long, env = "VAULT_MFA")]
+    mfa: Option<String>,
+
+    /// PEM CA certificate to trust for HTTPS, e.g. a bootstrapped server CA.
+    #[arg(long, env = "VAULT_CACERT")]
+    ca_cert: Option<PathBuf>,
+
+    /// PEM client certificate presented over HTTPS, for `cert` auth.
+    #[arg(long, env = "VAULT_CLIENT_CERT", requires = "client_key")]
+    client_cert: Option<PathBuf>,
+
+    /// PEM private key for `--client-cert`.
+    #[arg(long, env = "VAULT_CLIENT_KEY", requires = "client_cert")]
+    client_key: Option<PathBuf>,
+
     /// Disable colored output.
     #[arg(long, default_value = "false")]
     no_color: bool,
 
+    /// Output format: decorated `table`, or the raw API response as `json`
+    /// or `yaml` (decorated output then goes to stderr).
+    #[arg(long, env = "ZVAULT_FORMAT", value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    /// Print only this field of the API response, e.g. `password` or
+    /// `data.keys`.
+    #[arg(long)]
+    field: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -101,7 +174,7 @@ enum Commands {
         /// Number of unseal key shares to generate (1-10).
         #[arg(long, default_value = "5")]
         shares: u8,
-        /// Minimum shares required to unseal (2..=shares).
+        /// Minimum shares required to unseal (2..=shares, or 1 with a single share).
         #[arg(long, default_value = "3")]
         threshold: u8,
     },
@@ -113,6 +186,41 @@ enum Commands {
     },
     /// Seal the vault (zeroizes all key material).
     Seal,
+    /// Replace the unseal shares with a new share count and threshold.
+    ///
+    /// Starts (or resumes) a rekey, then reads current shares from stdin —
+    /// one per line, prompting on a terminal — until the current threshold
+    /// is met, and prints the new shares. With auto-unseal, the recovery
+    /// shares are rekeyed instead.
+    Rekey {
+        /// Number of new shares to generate (1-10).
+        #[arg(long, required_unless_present = "cancel")]
+        shares: Option<u8>,
+        /// Minimum new shares required (2..=shares).
+        #[arg(long, required_unless_present = "cancel")]
+        threshold: Option<u8>,
+        /// Cancel the rekey in progress instead.
+        #[arg(long, conflicts_with_all = ["shares", "threshold"])]
+        cancel: bool,
+    },
+    /// Mint a new root token from a quorum of unseal shares.
+    ///
+    /// Starts an attempt and prints its one-time pad, then reads current
+    /// shares from stdin — one per line, prompting on a terminal — until the
+    /// threshold is met, and prints the new root token. With auto-unseal,
+    /// recovery shares are used instead.
+    GenerateRoot {
+        /// One-time pad of the attempt in progress; resumes it instead of
+        /// starting a new one.
+        #[arg(long)]
+        otp: Option<String>,
+        /// Decode an encoded token with `--otp` instead of contacting the server.
+        #[arg(long, requires = "otp")]
+        decode: Option<String>,
+        /// Cancel the attempt in progress instead.
+        #[arg(long, conflicts_with_all = ["otp", "decode"])]
+        cancel: bool,
+    },
     /// Token authentication operations.
     Token {
         #[command(subcommand)]
@@ -120,6 +228,9 @@ enum Commands {
     },
     /// KV v2 secrets engine operations.
     Kv {
+        /// KV mount to operate on (e.g., "team-a").
+        #[arg(long, global = true, default_value = "secret", value_name = "PATH")]
+        mount: String,
         #[command(subcommand)]
         action: KvCommands,
     },
@@ -148,6 +259,26 @@ enum Commands {
         #[command(subcommand)]
         action: AppRoleCommands,
     },
+    /// JWT authentication for CI systems (GitHub Actions, GitLab).
+    Jwt {
+        #[command(subcommand)]
+        action: JwtCommands,
+    },
+    /// TLS client certificate authentication.
+    Cert {
+        #[command(subcommand)]
+        action: CertCommands,
+    },
+    /// Unwrap, inspect, or rewrap response-wrapping tokens.
+    Wrapping {
+        #[command(subcommand)]
+        action: WrappingCommands,
+    },
+    /// Read and write the current token's private cubbyhole.
+    Cubbyhole {
+        #[command(subcommand)]
+        action: CubbyholeCommands,
+    },
     /// Import secrets from a .env file into the vault.
     Import {
         /// Path to the .env file (default: ".env").
@@ -166,18 +297,68 @@ enum Commands {
         #[arg(long, default_value = "false")]
         no_gitignore: bool,
     },
+    /// Scan files for leaked credentials (honours .gitignore).
+    Scan {
+        /// File or directory to scan.
+        #[arg(default_value = ".")]
+        path: String,
+        /// Also scan files ignored by .gitignore.
+        #[arg(long)]
+        no_ignore: bool,
+        /// Scan the files staged for commit instead of `path`, and flag
+        /// staged plaintext .env files.
+        #[arg(long, conflicts_with_all = ["import", "no_ignore"])]
+        staged: bool,
+        /// Store every finding in the vault and replace it with its
+        /// zvault:// reference.
+        #[arg(long)]
+        import: bool,
+        /// Project name for imported secrets (default: current directory name).
+        #[arg(long, requires = "import")]
+        project: Option<String>,
+        /// Exit non-zero when anything is found.
+        #[arg(long, conflicts_with = "import")]
+        exit_code: bool,
+    },
+    /// Manage the git pre-commit hook that blocks committing secrets.
+    Hooks {
+        #[command(subcommand)]
+        action: HooksCommands,
+    },
     /// Run a command with secrets injected from the vault.
     Run {
         /// Path to .env.zvault (or .env with zvault:// URIs). Default: auto-detect.
         #[arg(long)]
         env_file: Option<String>,
+        /// Profile from `[env.<name>]` in .zvault.toml: its server, token,
+        /// secret prefix, and env file.
+        #[arg(long)]
+        env: Option<String>,
         /// The command and arguments to run.
         #[arg(trailing_var_arg = true, required = true)]
         command: Vec<String>,
     },
     /// Start the MCP (Model Context Protocol) server for AI assistant integration.
     #[command(name = "mcp-server")]
-    McpServer,
+    McpServer {
+        /// Hide every tool that writes: set/delete secret, run command, S3
+        /// write, and write queries.
+        #[arg(long)]
+        read_only: bool,
+        /// Hide a tool (repeatable, `*` globs allowed).
+        #[arg(long = "disable-tool", value_name = "TOOL")]
+        disable_tools: Vec<String>,
+        /// Tool configuration file [default: ~/.zvault/mcp.toml].
+        #[arg(long, env = "ZVAULT_MCP_CONFIG")]
+        config: Option<PathBuf>,
+        /// Exchange the token for a short-lived child token with this policy
+        /// (repeatable), revoked on exit.
+        #[arg(long = "session-policy", value_name = "POLICY")]
+        session_policies: Vec<String>,
+        /// TTL of the session token, renewed while the server runs [default: 15m].
+        #[arg(long, value_name = "DURATION")]
+        session_ttl: Option<String>,
+    },
     /// Configure an IDE to use `ZVault` as an MCP server.
     Setup {
         /// IDE to configure: cursor, kiro, continue, or generic.
@@ -187,6 +368,10 @@ enum Commands {
     Activate {
         /// License key (from <https://zvault.cloud/pricing>).
         key: String,
+        /// Activate on the vault server (unlocks server features like SSO)
+        /// instead of this machine.
+        #[arg(long)]
+        server: bool,
     },
     /// Show current license status.
     License,
@@ -207,20 +392,94 @@ enum Commands {
         #[command(subcommand)]
         action: LeaseCommands,
     },
+    /// Just-in-time access requests.
+    Access {
+        #[command(subcommand)]
+        action: AccessCommands,
+    },
+    /// Identity entities, aliases, and groups.
+    Identity {
+        #[command(subcommand)]
+        action: IdentityCommands,
+    },
+    /// Approve and retrieve requests held by a control group.
+    #[command(name = "control-group")]
+    ControlGroup {
+        #[command(subcommand)]
+        action: ControlGroupCommands,
+    },
+    /// Stream vault events (secret writes, policy changes, seal status).
+    Events {
+        #[command(subcommand)]
+        action: EventsCommands,
+    },
     /// Export audit log entries.
     #[command(name = "audit-export")]
     AuditExport {
-        /// Output format: json or csv.
-        #[arg(long, default_value = "json")]
+        /// Output format.
+        #[arg(long, value_parser = ["json", "ndjson", "csv", "parquet"], default_value = "json")]
         format: String,
-        /// Maximum entries to export.
-        #[arg(long, default_value = "1000")]
-        limit: usize,
+        /// Maximum entries to export (default: the whole log).
+        #[arg(long)]
+        limit: Option<usize>,
         /// Output file path (default: stdout).
         #[arg(long)]
         output: Option<String>,
+        /// Compress the output with gzip while streaming (not for parquet).
+        #[arg(long)]
+        gzip: bool,
+        /// Print how many entries match instead of exporting them.
+        #[arg(long)]
+        count: bool,
+        #[command(flatten)]
+        filter: AuditFilterArgs,
+    },
+    /// Reconcile policies, mounts, roles, and rotation policies with a YAML file.
+    Apply {
+        /// Config file describing the desired vault configuration.
+        #[arg(short, long)]
+        file: String,
+        /// Print the plan without making changes.
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete resources in managed sections that the file does not declare.
+        #[arg(long)]
+        prune: bool,
+        /// Skip the prune prompt by passing the config file path.
+        #[arg(long)]
+        confirm: Option<String>,
+    },
+    /// Render a config template with secrets inlined.
+    Render {
+        /// Template file, e.g. with `{{ secret "app/db" "password" | json }}`.
+        template: String,
+        /// Write the result here, atomically (default: stdout).
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Octal file mode of the output file.
+        #[arg(long, default_value = "0600")]
+        mode: String,
+        /// Keep running and re-render the output when secrets change.
+        #[arg(long, requires = "output")]
+        watch: bool,
+        /// Seconds between renders with --watch.
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
     },
-    /// Send a test webhook notification.
+    /// Open an interactive dashboard: seal status, mounts, audit log, and a
+    /// KV browser.
+    Tui,
+    /// Run as a sidecar: log in, keep the token renewed, and render secret
+    /// templates to files, signalling or restarting an app when they change.
+    Agent {
+        /// Agent config file (auth, templates, exec).
+        #[arg(short, long)]
+        config: String,
+        /// Render the templates once and exit.
+        #[arg(long)]
+        once: bool,
+    },
+    /// Manage server-side webhook notifications.
     Notify {
         #[command(subcommand)]
         action: NotifyCommands,
@@ -230,6 +489,161 @@ enum Commands {
         #[command(subcommand)]
         action: RotateCommands,
     },
+    /// Sync secrets to external services.
+    Sync {
+        #[command(subcommand)]
+        action: SyncCommands,
+    },
+    /// Print secrets as a flat JSON map for Terraform's `external` data source.
+    ///
+    /// Names join the secret path and field (`db/main` + `password` →
+    /// `db_main_password`). Without a prefix, Terraform's query
+    /// (`{"prefix": ..., "key": ...}`) is read from stdin.
+    #[command(visible_alias = "tf")]
+    TfOutput {
+        /// Path prefix to read (e.g. `myapp/`).
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        prefix: Option<String>,
+        /// Only keys whose `<path>/<field>` matches this pattern (`*` matches
+        /// anything), e.g. `db/*` or `*/password`. Repeatable.
+        #[arg(long = "key", value_name = "PATTERN")]
+        keys: Vec<String>,
+        /// Name values by field alone, without the secret path.
+        #[arg(long)]
+        field_only: bool,
+        /// Joins path segments and the field in names.
+        #[arg(long, default_value = "_")]
+        separator: String,
+        /// Case of names.
+        #[arg(long, value_parser = ["keep", "upper", "lower"], default_value = "keep")]
+        case: String,
+        /// Print `name = "value"` lines (HCL, as in .tfvars) instead of JSON.
+        #[arg(long)]
+        hcl: bool,
+    },
+    /// Import secrets from AWS Secrets Manager or SSM Parameter Store.
+    ///
+    /// The hierarchy under the prefix is kept: `prod/myapp/db` under
+    /// `prod/` becomes `myapp/db`, and SSM parameters become fields of the
+    /// secret at their parent path. AWS tags become custom metadata.
+    ImportAws {
+        /// AWS name prefix to import (e.g. `prod/`, or `/prod/myapp` for
+        /// Parameter Store).
+        #[arg(default_value = "")]
+        prefix: String,
+        /// KV path to import under (default: the mount root).
+        #[arg(long, default_value = "")]
+        path: String,
+        #[command(flatten)]
+        aws: AwsMigrateArgs,
+    },
+    /// Export KV secrets to AWS Secrets Manager or SSM Parameter Store.
+    ///
+    /// Each secret becomes a Secrets Manager secret holding its fields as
+    /// JSON, or one `SecureString` parameter per field, tagged with its
+    /// custom metadata.
+    ExportAws {
+        /// KV path to export (e.g. `myapp/`).
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        path: String,
+        /// AWS name prefix to export under (e.g. `prod/`).
+        #[arg(long, default_value = "")]
+        prefix: String,
+        /// KMS key to encrypt new secrets and parameters with (default: the
+        /// AWS managed key).
+        #[arg(long)]
+        kms_key_id: Option<String>,
+        #[command(flatten)]
+        aws: AwsMigrateArgs,
+    },
+    /// Migrate a `HashiCorp` Vault KV v2 mount, and optionally its policies
+    /// and `AppRole` roles, into `ZVault`.
+    ///
+    /// Secrets land under `secret/` at the same path unless a `--map` rule
+    /// moves them. Anything that does not carry over (version history,
+    /// parameter constraints in policies, CIDR-bound roles, ...) is listed
+    /// in the report.
+    MigrateVault {
+        /// Address of the source Vault.
+        #[arg(long, env = "ZVAULT_MIGRATE_VAULT_ADDR")]
+        addr: String,
+        /// Token for the source Vault.
+        #[arg(long, env = "ZVAULT_MIGRATE_VAULT_TOKEN", hide_env_values = true)]
+        token: String,
+        /// Vault Enterprise namespace to read from.
+        #[arg(long)]
+        namespace: Option<String>,
+        /// CA certificate (PEM) for the source Vault's TLS.
+        #[arg(long)]
+        vault_ca_cert: Option<PathBuf>,
+        /// KV v2 mount to migrate.
+        #[arg(long, default_value = "secret")]
+        mount: String,
+        /// Only migrate secrets under this path of the mount.
+        #[arg(long, default_value = "")]
+        path: String,
+        /// Rewrite a path prefix, e.g. `apps/legacy=legacy` (repeatable; the
+        /// longest match wins).
+        #[arg(long = "map", value_name = "FROM=TO")]
+        maps: Vec<String>,
+        /// Also migrate ACL policies.
+        #[arg(long)]
+        policies: bool,
+        /// Also migrate `AppRole` roles.
+        #[arg(long)]
+        approles: bool,
+        /// Vault auth mount of the `AppRole` roles.
+        #[arg(long, default_value = "approle")]
+        approle_mount: String,
+        /// What to do with targets that exist with a different value.
+        #[arg(long, value_parser = ["skip", "overwrite", "fail"], default_value = "skip")]
+        conflict: String,
+        /// Show what would be migrated without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import a 1Password (`.1pux`) or Bitwarden (JSON) export.
+    ///
+    /// Each item becomes a secret at `<prefix>/<vault or folder>/<item>`
+    /// holding its username, password, URLs, notes, and custom fields. On a
+    /// terminal you are asked where each vault or folder should go.
+    ImportPasswords {
+        /// The export file.
+        file: String,
+        /// Which password manager wrote the export (default: detect).
+        #[arg(long, value_parser = ["auto", "1password", "bitwarden"], default_value = "auto")]
+        from: String,
+        /// Path to import under.
+        #[arg(long, default_value = "imported")]
+        prefix: String,
+        /// Put a vault or folder's items under a path, e.g. `Work=team/work`
+        /// (`Work=-` leaves it out); skips its prompt. Repeatable.
+        #[arg(long = "map", value_name = "FOLDER=PATH")]
+        maps: Vec<String>,
+        /// Also import archived 1Password items.
+        #[arg(long)]
+        include_archived: bool,
+        /// Accept the default path for every vault and folder without asking.
+        #[arg(long, short)]
+        yes: bool,
+        /// Replace secrets that already exist.
+        #[arg(long)]
+        overwrite: bool,
+        /// Show which secrets would be written, without their values.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Docker credential helper backed by KV secrets. Docker runs it as
+    /// `docker-credential-zvault`: link that name to this binary and set
+    /// `"credsStore": "zvault"` in `~/.docker/config.json`.
+    #[command(visible_alias = "dch")]
+    DockerCredentialHelper {
+        /// Store registry credentials under `secret/<prefix>/`.
+        #[arg(long, env = "ZVAULT_DOCKER_PREFIX", default_value = "docker")]
+        prefix: String,
+        #[command(subcommand)]
+        action: DockerCredentialCommands,
+    },
     /// Log in to `ZVault` Cloud (opens browser) or local vault via OIDC.
     Login {
         /// Use OIDC authentication against local vault server (opens browser).
@@ -237,15 +651,35 @@ enum Commands {
         oidc: bool,
     },
     /// Create an encrypted backup of all vault data.
+    #[command(args_conflicts_with_subcommands = true)]
     Backup {
         /// Output file path (default: stdout as JSON).
         #[arg(long)]
         output: Option<String>,
+        #[command(subcommand)]
+        action: Option<BackupCommands>,
     },
     /// Restore vault data from an encrypted backup.
     Restore {
         /// Path to the backup file.
         file: String,
+        /// Restore only entries whose storage key starts with this prefix
+        /// (e.g., "kv/secret/"); everything else is left untouched.
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Skip the prompt by passing the backup file path.
+        #[arg(long, value_name = "FILE")]
+        confirm: Option<String>,
+    },
+    /// Mount and unmount secrets engines; move KV mounts between vaults.
+    Mount {
+        #[command(subcommand)]
+        action: MountCommands,
+    },
+    /// Register and manage external secrets engine plugins.
+    Plugin {
+        #[command(subcommand)]
+        action: PluginCommands,
     },
     /// `ZVault` Cloud operations — manage secrets in the cloud.
     Cloud {
@@ -254,6 +688,52 @@ enum Commands {
     },
     /// Log out of `ZVault` Cloud (remove saved token).
     Logout,
+    /// Update the CLI to the latest (or a pinned) signed release.
+    #[command(name = "self-update")]
+    SelfUpdate {
+        /// Only check whether an update is available.
+        #[arg(long)]
+        check: bool,
+        /// Install a specific version instead of the latest.
+        #[arg(long = "to", value_name = "VERSION")]
+        pin: Option<String>,
+        /// Reinstall even if already up to date.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Call any API endpoint and print the JSON response.
+    ///
+    /// Failed requests print the server's error envelope with its HTTP
+    /// status (`{"status", "code", "message", "errors", "request_id"}`) to
+    /// stdout and exit non-zero.
+    Api {
+        /// HTTP method (GET, POST, PUT, PATCH, DELETE).
+        method: String,
+        /// API path, e.g. `sys/health` or `/v1/secret/data/app`.
+        path: String,
+        /// JSON request body: inline, `@file`, or `@-` for stdin.
+        #[arg(long, short = 'd')]
+        data: Option<String>,
+    },
+    /// Print a shell completion script, e.g. `source <(zvault completions bash)`.
+    Completions {
+        /// Shell to complete for.
+        #[arg(value_parser = completions::SHELLS)]
+        shell: String,
+    },
+    /// Show the CLI version.
+    Version {
+        /// Check compatibility with the server's minimum supported CLI version.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Show build metadata: version, git revision, target, and profile.
+    #[command(name = "build-info")]
+    BuildInfo {
+        /// Print as JSON.
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -324,9 +804,12 @@ enum CloudTokenCommands {
         /// Environment scope (default: from .zvault.toml).
         #[arg(long)]
         env: Option<String>,
-        /// Time-to-live (e.g., "90d", "1y"). Default: no expiry.
+        /// Time-to-live (e.g., "30d", "12h"; at most 365d). Default: 90 days.
         #[arg(long)]
         ttl: Option<String>,
+        /// Allow the token to write secrets (default: read-only).
+        #[arg(long)]
+        write: bool,
     },
     /// Revoke a service token.
     Revoke {
@@ -347,9 +830,29 @@ enum TokenCommands {
         /// Time-to-live (e.g., "1h", "30m", "3600s").
         #[arg(long)]
         ttl: Option<String>,
+        /// Comma-separated CIDRs the token may be used from (default: the
+        /// parent token's).
+        #[arg(long, value_delimiter = ',')]
+        bound_cidrs: Option<Vec<String>>,
+        /// Create a batch token: not stored or renewable, valid until its
+        /// TTL (default 1h) or until this token is revoked.
+        #[arg(long)]
+        batch: bool,
     },
     /// Look up the current token's metadata.
     Lookup,
+    /// List the accessors of all stored tokens.
+    Accessors,
+    /// Look up a token's metadata by accessor.
+    LookupAccessor {
+        /// Token accessor.
+        accessor: String,
+    },
+    /// Revoke a token and its children by accessor.
+    RevokeAccessor {
+        /// Token accessor.
+        accessor: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -357,25 +860,155 @@ enum KvCommands {
     /// Write a secret (key=value pairs).
     Put {
         /// Secret path (e.g., "myapp/config").
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
         path: String,
         /// Key-value pairs in key=value format.
         #[arg(required = true)]
         data: Vec<String>,
+        /// Only write if the current version is this one (0 = must not exist).
+        #[arg(long)]
+        cas: Option<u32>,
     },
     /// Read a secret by path.
     Get {
         /// Secret path.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
         path: String,
     },
     /// Soft-delete a secret.
     Delete {
         /// Secret path.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
         path: String,
     },
+    /// Permanently destroy a secret and all of its versions.
+    Destroy {
+        /// Secret path.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        path: String,
+        /// Skip the prompt by passing the secret path.
+        #[arg(long, value_name = "PATH")]
+        confirm: Option<String>,
+    },
     /// List secret keys under a prefix.
     List {
         /// Path prefix.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        path: String,
+    },
+    /// Dump every secret under a prefix (latest versions) as JSON or YAML.
+    Export {
+        /// Path prefix (defaults to the whole mount).
+        #[arg(default_value = "", add = ArgValueCompleter::new(completions::secret_path))]
+        prefix: String,
+        /// Write to this file (mode 0600) instead of stdout.
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Write YAML (the default for .yaml/.yml output files).
+        #[arg(long)]
+        yaml: bool,
+    },
+    /// Write every secret in an export file under a prefix.
+    Import {
+        /// File from `kv export` (JSON or YAML).
+        file: String,
+        /// Path prefix to import under.
+        #[arg(default_value = "")]
+        prefix: String,
+        /// Replace secrets that already exist.
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Copy a secret, or a subtree with a trailing `/`, with all versions and metadata.
+    Copy {
+        /// Source path, or prefix ending in `/`.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        from: String,
+        /// Destination path or prefix.
+        to: String,
+        /// Replace secrets that already exist at the destination.
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Move a secret, or a subtree with a trailing `/`, with all versions and metadata.
+    Move {
+        /// Source path, or prefix ending in `/`.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        from: String,
+        /// Destination path or prefix.
+        to: String,
+        /// Replace secrets that already exist at the destination.
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Compare the secrets under two prefixes: missing, extra, and changed keys.
+    Diff {
+        /// Left prefix, e.g. env/app-staging.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        left: String,
+        /// Right prefix, e.g. env/app-prod.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        right: String,
+        /// Also compare values, by server-side HMAC (values are never downloaded).
+        #[arg(long)]
+        values: bool,
+        /// Read the left prefix through this .zvault.toml profile.
+        #[arg(long, value_name = "PROFILE")]
+        left_env: Option<String>,
+        /// Read the right prefix through this .zvault.toml profile.
+        #[arg(long, value_name = "PROFILE")]
+        right_env: Option<String>,
+        /// Exit non-zero when the two sides differ.
+        #[arg(long)]
+        exit_code: bool,
+    },
+    /// Read or edit a secret's custom metadata and write settings.
+    Metadata {
+        #[command(subcommand)]
+        action: KvMetadataCommands,
+    },
+    /// Show read counts and distinct readers per secret under a prefix.
+    Stats {
+        /// Path prefix (defaults to all secrets).
+        #[arg(default_value = "")]
+        prefix: String,
+        /// Only show secrets that have never been read.
+        #[arg(long)]
+        unread: bool,
+        /// Sort order.
+        #[arg(long, value_parser = ["path", "reads", "readers"], default_value = "path")]
+        sort: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum KvMetadataCommands {
+    /// Show versions, custom metadata, and settings for a secret.
+    Get {
+        /// Secret path.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
+        path: String,
+    },
+    /// Update a secret's metadata; unset options are left unchanged.
+    Put {
+        /// Secret path.
+        #[arg(add = ArgValueCompleter::new(completions::secret_path))]
         path: String,
+        /// Custom metadata as key=value; replaces all existing entries.
+        #[arg(long = "custom", value_name = "KEY=VALUE")]
+        custom: Vec<String>,
+        /// Remove all custom metadata.
+        #[arg(long, conflicts_with = "custom")]
+        clear_custom: bool,
+        /// Versions to keep (0 = unlimited).
+        #[arg(long)]
+        max_versions: Option<u32>,
+        /// Require `--cas` on every write.
+        #[arg(long)]
+        cas_required: Option<bool>,
+        /// Delete versions older than this (e.g. 90d, 2160h; 0 = never).
+        #[arg(long, value_name = "DURATION")]
+        delete_version_after: Option<String>,
     },
 }
 
@@ -399,6 +1032,9 @@ enum PolicyCommands {
     Delete {
         /// Policy name.
         name: String,
+        /// Skip the prompt by passing the policy name.
+        #[arg(long, value_name = "NAME")]
+        confirm: Option<String>,
     },
 }
 
@@ -428,6 +1064,27 @@ enum TransitCommands {
         /// Ciphertext string.
         ciphertext: String,
     },
+    /// Re-encrypt ciphertext under the latest key version.
+    Rewrap {
+        /// Key name.
+        key: String,
+        /// Ciphertext string.
+        ciphertext: String,
+        /// Target key version (defaults to the latest).
+        #[arg(long)]
+        key_version: Option<u32>,
+    },
+    /// Set the minimum key versions allowed for decryption and encryption.
+    Config {
+        /// Key name.
+        name: String,
+        /// Oldest version ciphertexts may be decrypted with.
+        #[arg(long)]
+        min_decryption_version: Option<u32>,
+        /// Oldest version callers may encrypt with (0 = latest only).
+        #[arg(long)]
+        min_encryption_version: Option<u32>,
+    },
     /// List all transit key names.
     ListKeys,
     /// Show metadata for a named key.
@@ -435,6 +1092,41 @@ enum TransitCommands {
         /// Key name.
         name: String,
     },
+    /// Replace a base64-encoded value with a token.
+    Tokenize {
+        /// Key name.
+        key: String,
+        /// Base64-encoded value.
+        plaintext: String,
+        /// Reuse the existing token for an equal value.
+        #[arg(long)]
+        convergent: bool,
+        /// Metadata stored with the token (key=value, repeatable).
+        #[arg(long = "metadata")]
+        metadata: Vec<String>,
+    },
+    /// Return the value behind a token.
+    Detokenize {
+        /// Key name.
+        key: String,
+        /// Token.
+        token: String,
+    },
+    /// Show a token's metadata, or find the convergent token for a value.
+    LookupToken {
+        /// Key name.
+        key: String,
+        /// Token to describe.
+        #[arg(
+            long,
+            conflicts_with = "plaintext",
+            required_unless_present = "plaintext"
+        )]
+        token: Option<String>,
+        /// Base64-encoded value whose convergent token to find.
+        #[arg(long)]
+        plaintext: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -443,12 +1135,18 @@ enum DatabaseCommands {
     Configure {
         /// Connection name.
         name: String,
-        /// Database plugin ("postgresql" or "mysql").
+        /// Database plugin ("postgresql", "mysql", "mariadb", or "mssql").
         #[arg(long)]
         plugin: String,
-        /// Connection URL.
+        /// Connection URL. May use `{{username}}` and `{{password}}` placeholders.
         #[arg(long)]
         connection_url: String,
+        /// Admin username for `{{username}}`.
+        #[arg(long)]
+        username: Option<String>,
+        /// Admin password for `{{password}}` (rotate it afterwards with `rotate-root`).
+        #[arg(long)]
+        password: Option<String>,
     },
     /// Create a database role.
     CreateRole {
@@ -470,6 +1168,32 @@ enum DatabaseCommands {
     ListRoles,
     /// List all database configs.
     ListConfigs,
+    /// Rotate the admin password of a database connection.
+    RotateRoot {
+        /// Connection name.
+        name: String,
+    },
+    /// Create a static role that manages an existing database user.
+    CreateStaticRole {
+        /// Role name.
+        name: String,
+        /// Database connection name.
+        #[arg(long)]
+        db_name: String,
+        /// Existing database user to manage.
+        #[arg(long)]
+        username: String,
+    },
+    /// Read the current password of a static role.
+    StaticCreds {
+        /// Static role name.
+        name: String,
+    },
+    /// Rotate the password of a static role.
+    RotateRole {
+        /// Static role name.
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -493,11 +1217,54 @@ enum PkiCommands {
         /// TTL in hours.
         #[arg(long)]
         ttl_hours: Option<u64>,
+        /// Comma-separated extra DNS names.
+        #[arg(long, value_delimiter = ',')]
+        alt_names: Vec<String>,
+        /// Comma-separated IP addresses (the role must allow IP SANs).
+        #[arg(long, value_delimiter = ',')]
+        ip_sans: Vec<String>,
+        /// Comma-separated URIs, e.g. SPIFFE IDs.
+        #[arg(long, value_delimiter = ',')]
+        uri_sans: Vec<String>,
+        /// Key type: rsa, ec or ed25519 (only when the role allows any).
+        #[arg(long)]
+        key_type: Option<String>,
+        /// Key size in bits.
+        #[arg(long)]
+        key_bits: Option<u32>,
+        /// Backdate `not_before` by this long (e.g. `30s`, `5m`).
+        #[arg(long)]
+        not_before: Option<String>,
     },
     /// List all PKI roles.
     ListRoles,
-    /// List all issued certificates.
-    ListCerts,
+    /// List issued certificates, a page at a time.
+    ListCerts {
+        /// Only certificates whose common name contains this.
+        #[arg(long)]
+        common_name: Option<String>,
+        /// Only certificates expiring within this long (e.g. `72h`, `30d`).
+        #[arg(long)]
+        expires_within: Option<String>,
+        /// Certificates per page (default: 100).
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Continue from a previous page's cursor.
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// Delete expired certificates and revocation entries.
+    Tidy {
+        /// Keep entries until this long past expiry (default: 72h).
+        #[arg(long)]
+        safety_buffer: Option<String>,
+        /// Leave the certificate store alone.
+        #[arg(long)]
+        skip_cert_store: bool,
+        /// Leave revocation entries alone.
+        #[arg(long)]
+        skip_revoked: bool,
+    },
     /// Create a PKI role.
     CreateRole {
         /// Role name.
@@ -508,6 +1275,24 @@ enum PkiCommands {
         /// Allow subdomains.
         #[arg(long, default_value = "false")]
         allow_subdomains: bool,
+        /// Allow `*.domain` wildcard certificates (needs --allow-subdomains).
+        #[arg(long)]
+        allow_wildcards: bool,
+        /// Allow IP address SANs.
+        #[arg(long)]
+        allow_ip_sans: bool,
+        /// Comma-separated glob patterns for allowed URI SANs.
+        #[arg(long, value_delimiter = ',')]
+        allowed_uri_sans: Vec<String>,
+        /// Key type: rsa, ec, ed25519, or any to let requests choose.
+        #[arg(long)]
+        key_type: Option<String>,
+        /// Key size in bits (2048/3072/4096 for RSA, 256/384 for EC).
+        #[arg(long)]
+        key_bits: Option<u32>,
+        /// Seconds to backdate `not_before` by (default: 30).
+        #[arg(long)]
+        not_before: Option<u64>,
     },
 }
 
@@ -520,6 +1305,9 @@ enum AppRoleCommands {
         /// Comma-separated policies.
         #[arg(long, value_delimiter = ',')]
         policies: Vec<String>,
+        /// Comma-separated CIDRs that may log in, and use the tokens issued.
+        #[arg(long, value_delimiter = ',')]
+        bound_cidrs: Vec<String>,
     },
     /// Get the role ID for a named role.
     RoleId {
@@ -530,6 +1318,9 @@ enum AppRoleCommands {
     SecretId {
         /// Role name.
         name: String,
+        /// Return a single-use wrapping token instead (e.g. `5m`).
+        #[arg(long)]
+        wrap_ttl: Option<String>,
     },
     /// Login with `role_id` and `secret_id`.
     Login {
@@ -545,94 +1336,852 @@ enum AppRoleCommands {
 }
 
 #[derive(Subcommand)]
-enum LeaseCommands {
-    /// List all active leases.
-    List,
-    /// Look up a specific lease.
-    Lookup {
-        /// Lease ID.
-        lease_id: String,
+enum MountCommands {
+    /// Mount a KV engine or a registered plugin.
+    Enable {
+        /// Mount path (e.g., "rabbitmq/").
+        path: String,
+        /// Engine type: `kv` or a plugin name from the catalog.
+        #[arg(long = "type", value_name = "TYPE")]
+        engine_type: String,
+        /// Description shown in the mount list.
+        #[arg(long)]
+        description: Option<String>,
+        /// Engine config entry, passed to plugins on start. Repeatable.
+        #[arg(long = "option", value_name = "KEY=VALUE")]
+        options: Vec<String>,
     },
-    /// Revoke a lease immediately.
-    Revoke {
-        /// Lease ID.
-        lease_id: String,
+    /// Unmount an engine, revoking its leases.
+    Disable {
+        /// Mount path.
+        path: String,
+        /// Also delete all data the engine stored. Cannot be undone.
+        #[arg(long)]
+        purge: bool,
     },
-}
-
-#[derive(Subcommand)]
-enum NotifyCommands {
-    /// Configure a webhook endpoint for notifications.
-    SetWebhook {
-        /// Webhook URL (Slack, Discord, or generic).
-        url: String,
-        /// Events to subscribe to (comma-separated): secret.accessed, secret.rotated, policy.violated, lease.expired.
-        #[arg(
-            long,
-            value_delimiter = ',',
-            default_value = "secret.accessed,secret.rotated,lease.expired"
-        )]
-        events: Vec<String>,
+    /// Export a KV mount's data, encrypted under a transfer key.
+    Export {
+        /// Mount path (e.g., "team-a/").
+        path: String,
+        /// Output file for the bundle.
+        #[arg(long, short)]
+        output: String,
+        /// Base64 256-bit transfer key (default: generate one).
+        #[arg(long, env = "ZVAULT_TRANSFER_KEY", hide_env_values = true)]
+        transfer_key: Option<String>,
+    },
+    /// Import an exported mount at a new, empty path.
+    Import {
+        /// Mount path to create.
+        path: String,
+        /// Bundle file written by `mount export`.
+        #[arg(long, short)]
+        file: String,
+        /// Base64 transfer key the bundle was exported with.
+        #[arg(long, env = "ZVAULT_TRANSFER_KEY", hide_env_values = true)]
+        transfer_key: String,
     },
-    /// Show current webhook configuration.
-    GetWebhook,
-    /// Remove webhook configuration.
-    RemoveWebhook,
-    /// Send a test notification to the configured webhook.
-    Test,
 }
 
 #[derive(Subcommand)]
-enum RotateCommands {
-    /// Set a rotation policy for a secret path.
-    SetPolicy {
-        /// Secret path (e.g., `env/myapp/DATABASE_URL`).
-        path: String,
-        /// Rotation interval in hours.
+enum PluginCommands {
+    /// Register or replace a plugin module from the server's plugin directory.
+    Register {
+        /// Catalog name, used as the mount type.
+        name: String,
+        /// WebAssembly module path, relative to the server's `ZVAULT_PLUGIN_DIR`.
+        #[arg(long)]
+        module: String,
+        /// Hex SHA-256 of the module (e.g., from `sha256sum`).
         #[arg(long)]
-        interval_hours: u64,
-        /// Maximum age before forced rotation (hours).
+        sha256: String,
+        /// Host the plugin may send HTTP requests to. Repeatable.
+        #[arg(long = "allowed-host", value_name = "HOST")]
+        allowed_hosts: Vec<String>,
+        /// Description.
         #[arg(long)]
-        max_age_hours: Option<u64>,
+        description: Option<String>,
     },
-    /// Show rotation policy for a secret path.
-    GetPolicy {
-        /// Secret path.
-        path: String,
+    /// Show registered plugins and where they are mounted.
+    Get {
+        /// Show only this plugin.
+        name: Option<String>,
     },
-    /// List all rotation policies.
-    ListPolicies,
-    /// Remove a rotation policy.
-    RemovePolicy {
-        /// Secret path.
-        path: String,
+    /// Remove a plugin from the catalog (it must not be mounted).
+    Deregister {
+        /// Catalog name.
+        name: String,
     },
-    /// Manually trigger rotation for a secret.
-    Trigger {
-        /// Secret path.
-        path: String,
+    /// Re-instantiate a plugin's mounts, picking up a re-registered module.
+    Reload {
+        /// Catalog name.
+        name: String,
     },
-    /// Show rotation status for all secrets with policies.
-    Status,
 }
 
-// ── Pretty output helpers ────────────────────────────────────────────
+#[derive(Subcommand)]
+enum CubbyholeCommands {
+    /// Write an entry (key=value pairs), replacing any existing value.
+    Put {
+        /// Entry path (e.g., "ci/deploy-key").
+        path: String,
+        /// Key-value pairs in key=value format.
+        #[arg(required = true)]
+        data: Vec<String>,
+    },
+    /// Read an entry by path.
+    Get {
+        /// Entry path.
+        path: String,
+    },
+    /// Delete an entry.
+    Delete {
+        /// Entry path.
+        path: String,
+    },
+    /// List entry keys under a prefix.
+    List {
+        /// Path prefix (defaults to the whole cubbyhole).
+        #[arg(default_value = "")]
+        path: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum WrappingCommands {
+    /// Return the wrapped response, consuming the token.
+    Unwrap {
+        /// Wrapping token.
+        token: String,
+    },
+    /// Show when and where a wrapping token was created, without unwrapping.
+    Lookup {
+        /// Wrapping token.
+        token: String,
+    },
+    /// Move the wrapped response to a new token, invalidating the old one.
+    Rewrap {
+        /// Wrapping token.
+        token: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum JwtCommands {
+    /// Configure where JWT signing keys come from.
+    Config {
+        /// JWKS endpoint (e.g., `https://token.actions.githubusercontent.com/.well-known/jwks`).
+        #[arg(long, conflicts_with = "pubkey_file")]
+        jwks_url: Option<String>,
+        /// PEM public key file (repeatable), instead of a JWKS URL.
+        #[arg(long = "pubkey-file")]
+        pubkey_file: Vec<String>,
+        /// Required `iss` claim.
+        #[arg(long)]
+        bound_issuer: Option<String>,
+    },
+    /// Create or replace a JWT role.
+    CreateRole {
+        /// Role name.
+        name: String,
+        /// Comma-separated policies.
+        #[arg(long, value_delimiter = ',')]
+        policies: Vec<String>,
+        /// Comma-separated accepted `aud` values.
+        #[arg(long, value_delimiter = ',', required = true)]
+        bound_audiences: Vec<String>,
+        /// Required `sub` claim.
+        #[arg(long)]
+        bound_subject: Option<String>,
+        /// Required claim as `claim=value` (repeatable; repeat a claim to allow several values).
+        #[arg(long = "bound-claim")]
+        bound_claims: Vec<String>,
+        /// Match bound claim values as globs instead of exact strings.
+        #[arg(long)]
+        glob: bool,
+        /// Claim used as the token display name.
+        #[arg(long, default_value = "sub")]
+        user_claim: String,
+        /// Claim listing the caller's groups, matched against external identity groups.
+        #[arg(long)]
+        groups_claim: Option<String>,
+    },
+    /// Show a JWT role.
+    ReadRole {
+        /// Role name.
+        name: String,
+    },
+    /// Delete a JWT role.
+    DeleteRole {
+        /// Role name.
+        name: String,
+    },
+    /// List all JWT roles.
+    ListRoles,
+    /// Exchange a JWT for a vault token.
+    Login {
+        /// Role to log in as.
+        #[arg(long)]
+        role: String,
+        /// The JWT (e.g., the CI job's OIDC token).
+        #[arg(long, env = "ZVAULT_JWT", hide_env_values = true)]
+        jwt: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CertCommands {
+    /// Create or replace a cert role.
+    CreateRole {
+        /// Role name.
+        name: String,
+        /// PEM file of the CA certificates client certificates must chain to.
+        #[arg(long)]
+        ca_file: PathBuf,
+        /// Comma-separated policies.
+        #[arg(long, value_delimiter = ',')]
+        policies: Vec<String>,
+        /// Comma-separated accepted common names (globs).
+        #[arg(long = "allowed-common-names", value_delimiter = ',')]
+        allowed_common_names: Vec<String>,
+        /// Comma-separated accepted DNS SANs (globs).
+        #[arg(long = "allowed-dns-sans", value_delimiter = ',')]
+        allowed_dns_sans: Vec<String>,
+        /// Comma-separated accepted email SANs (globs).
+        #[arg(long = "allowed-email-sans", value_delimiter = ',')]
+        allowed_email_sans: Vec<String>,
+        /// Comma-separated accepted URI SANs (globs).
+        #[arg(long = "allowed-uri-sans", value_delimiter = ',')]
+        allowed_uri_sans: Vec<String>,
+        /// Comma-separated accepted organizational units (globs).
+        #[arg(long = "allowed-ous", value_delimiter = ',')]
+        allowed_organizational_units: Vec<String>,
+    },
+    /// Show a cert role.
+    ReadRole {
+        /// Role name.
+        name: String,
+    },
+    /// Delete a cert role.
+    DeleteRole {
+        /// Role name.
+        name: String,
+    },
+    /// List all cert roles.
+    ListRoles,
+    /// Exchange the `--client-cert` certificate for a vault token.
+    Login {
+        /// Role to log in as (default: any role accepting the certificate).
+        #[arg(long)]
+        role: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum IdentityCommands {
+    /// Create an entity.
+    CreateEntity {
+        /// Entity name.
+        name: String,
+        /// Comma-separated policies.
+        #[arg(long, value_delimiter = ',')]
+        policies: Vec<String>,
+    },
+    /// Show an entity, its aliases, and its groups.
+    ReadEntity {
+        /// Entity name.
+        name: String,
+    },
+    /// Refuse (or, with `--enable`, accept again) an entity's tokens.
+    DisableEntity {
+        /// Entity name.
+        name: String,
+        /// Re-enable the entity instead.
+        #[arg(long)]
+        enable: bool,
+    },
+    /// Delete an entity and its aliases.
+    DeleteEntity {
+        /// Entity name.
+        name: String,
+    },
+    /// List entity IDs.
+    ListEntities,
+    /// Attach an auth method alias to an entity.
+    AddAlias {
+        /// Entity name.
+        entity: String,
+        /// Auth method: approle, jwt, oidc, or cert.
+        #[arg(long)]
+        auth_method: String,
+        /// Name within the auth method (role name, user claim, OIDC subject).
+        #[arg(long)]
+        name: String,
+    },
+    /// Delete an alias.
+    DeleteAlias {
+        /// Alias ID.
+        id: String,
+    },
+    /// Create a group.
+    CreateGroup {
+        /// Group name.
+        name: String,
+        /// Comma-separated policies.
+        #[arg(long, value_delimiter = ',')]
+        policies: Vec<String>,
+        /// Comma-separated member entity names (internal groups).
+        #[arg(long, value_delimiter = ',', conflicts_with = "external")]
+        member_entities: Vec<String>,
+        /// Comma-separated member group names (internal groups).
+        #[arg(long, value_delimiter = ',', conflicts_with = "external")]
+        member_groups: Vec<String>,
+        /// Mirror a provider group as `auth_method:name`, e.g. `jwt:platform-ops`.
+        #[arg(long)]
+        external: Option<String>,
+    },
+    /// Show a group.
+    ReadGroup {
+        /// Group name.
+        name: String,
+    },
+    /// Delete a group.
+    DeleteGroup {
+        /// Group name.
+        name: String,
+    },
+    /// List group IDs.
+    ListGroups,
+    /// Show the calling token's entity and effective policies.
+    Whoami,
+}
+
+#[derive(Subcommand)]
+enum ControlGroupCommands {
+    /// Show a parked request and its approvals.
+    Status {
+        /// Accessor returned when the request was parked.
+        accessor: String,
+    },
+    /// Approve a parked request.
+    Authorize {
+        /// Accessor returned when the request was parked.
+        accessor: String,
+    },
+    /// Run an approved request and print its response.
+    Retrieve {
+        /// Accessor returned when the request was parked.
+        accessor: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum EventsCommands {
+    /// Print events as they happen until the server ends the stream.
+    Subscribe {
+        /// Topic pattern, e.g. `kv/*`, `policy.write`, or `*`.
+        pattern: String,
+        /// Print each event as a JSON line.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AccessCommands {
+    /// Request temporary access to a path.
+    Request {
+        /// Path pattern (e.g., `secret/data/prod/**`).
+        path: String,
+        /// Capability to request (repeatable).
+        #[arg(long = "capability", default_value = "read")]
+        capabilities: Vec<String>,
+        /// Why access is needed (shown to approvers).
+        #[arg(long)]
+        reason: String,
+        /// How long access should last (e.g., `30m`, `4h`).
+        #[arg(long)]
+        ttl: Option<String>,
+    },
+    /// List access requests (your own unless you can list all).
+    List,
+    /// Show a request and its history.
+    Show {
+        /// Request ID.
+        id: String,
+    },
+    /// Approve a pending request.
+    Approve {
+        /// Request ID.
+        id: String,
+        /// Note recorded in the request history.
+        #[arg(long)]
+        comment: Option<String>,
+    },
+    /// Deny a pending request.
+    Deny {
+        /// Request ID.
+        id: String,
+        /// Note recorded in the request history.
+        #[arg(long)]
+        comment: Option<String>,
+    },
+    /// End an approved grant early.
+    Revoke {
+        /// Request ID.
+        id: String,
+        /// Note recorded in the request history.
+        #[arg(long)]
+        comment: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HooksCommands {
+    /// Install a pre-commit hook that runs `zvault scan --staged`.
+    Install {
+        /// Replace an existing pre-commit hook that zvault did not write.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the pre-commit hook installed by `zvault hooks install`.
+    Uninstall,
+}
+
+#[derive(Subcommand)]
+enum LeaseCommands {
+    /// List all active leases.
+    List,
+    /// Look up a specific lease.
+    Lookup {
+        /// Lease ID.
+        lease_id: String,
+    },
+    /// Renew a lease (capped at the lease's max TTL).
+    Renew {
+        /// Lease ID.
+        lease_id: String,
+        /// Seconds to extend the lease by.
+        #[arg(long, default_value = "3600")]
+        increment: i64,
+    },
+    /// Revoke a lease immediately.
+    Revoke {
+        /// Lease ID.
+        lease_id: String,
+    },
+    /// Revoke every lease under an engine path prefix (e.g., `database/`).
+    #[command(name = "revoke-prefix")]
+    RevokePrefix {
+        /// Engine path prefix.
+        prefix: String,
+        /// Remove leases even if the engine fails to clean up the credential.
+        #[arg(long)]
+        force: bool,
+        /// Skip the prompt by passing the prefix.
+        #[arg(long, value_name = "PREFIX")]
+        confirm: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum NotifyCommands {
+    /// Create or update a notification webhook on the server.
+    SetWebhook {
+        /// Webhook URL (Slack, Discord, pagerduty.com, or generic).
+        url: String,
+        /// Webhook name.
+        #[arg(long, default_value = "default")]
+        name: String,
+        /// Event topics to notify (comma-separated patterns), e.g. kv.*, policy.write, lease.expired.
+        #[arg(long, value_delimiter = ',', default_value = "kv.*,lease.expired")]
+        events: Vec<String>,
+        /// Payload format (detected from the URL by default).
+        #[arg(long, value_parser = ["generic", "slack", "discord", "pagerduty"])]
+        format: Option<String>,
+        /// HMAC secret for signing deliveries; an empty value removes it.
+        #[arg(long)]
+        secret: Option<String>,
+        /// Integration key, required for pagerduty webhooks; an empty value
+        /// removes it.
+        #[arg(long, value_name = "KEY")]
+        routing_key: Option<String>,
+        /// Keep the webhook but stop sending to it.
+        #[arg(long)]
+        disabled: bool,
+    },
+    /// Show configured webhooks.
+    GetWebhook {
+        /// Show only this webhook.
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove a webhook and its queued deliveries.
+    RemoveWebhook {
+        /// Webhook name.
+        #[arg(long, default_value = "default")]
+        name: String,
+    },
+    /// Send a test notification to a webhook.
+    Test {
+        /// Webhook name.
+        #[arg(long, default_value = "default")]
+        name: String,
+    },
+    /// Show deliveries waiting to be retried.
+    Queue,
+}
+
+#[derive(Subcommand)]
+enum SyncCommands {
+    /// Push secrets to GitHub Actions repository or environment secrets.
+    Github {
+        #[command(subcommand)]
+        action: GithubSyncCommands,
+    },
+    /// Apply secrets to a Kubernetes Secret.
+    Kubernetes {
+        #[command(subcommand)]
+        action: KubernetesSyncCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum GithubSyncCommands {
+    /// Create or replace a GitHub sync on the server.
+    Set {
+        /// Sync name.
+        name: String,
+        /// Repository as `owner/repo`.
+        #[arg(long)]
+        repo: String,
+        /// Sync this deployment environment's secrets instead of the repository's.
+        #[arg(long)]
+        environment: Option<String>,
+        /// Token allowed to write the repository's secrets (kept if omitted on update).
+        #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Secret to push: `<mount>/<path>[#field][=NAME]`. Without a field, every
+        /// field is pushed, with NAME as a prefix. Repeatable.
+        #[arg(long = "map", required = true, value_name = "SECRET")]
+        mappings: Vec<String>,
+        /// GitHub API URL, for GitHub Enterprise Server.
+        #[arg(long)]
+        api_url: Option<String>,
+        /// Keep the sync but stop pushing on writes.
+        #[arg(long)]
+        disabled: bool,
+    },
+    /// Show GitHub syncs and their status.
+    Get {
+        /// Show only this sync.
+        name: Option<String>,
+    },
+    /// Remove a GitHub sync (secrets already pushed stay on GitHub).
+    Remove {
+        /// Sync name.
+        name: String,
+    },
+    /// Push changed secrets now.
+    Run {
+        /// Sync name.
+        name: String,
+        /// Push every secret, even those GitHub already holds.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Compare the secrets on GitHub with the vault.
+    Drift {
+        /// Sync name.
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum KubernetesSyncCommands {
+    /// Create or replace a Kubernetes sync on the server.
+    Set {
+        /// Sync name.
+        name: String,
+        /// Namespace of the Secret.
+        #[arg(long)]
+        namespace: String,
+        /// Name of the Secret.
+        #[arg(long = "secret")]
+        secret_name: String,
+        /// Secret to apply: `<mount>/<path>[#field][=KEY]`. Without a field, every
+        /// field is applied, with KEY as a prefix. Repeatable.
+        #[arg(long = "map", required = true, value_name = "SECRET")]
+        mappings: Vec<String>,
+        /// Label to set on the Secret, as `key=value`. Repeatable.
+        #[arg(long = "label", value_name = "KEY=VALUE")]
+        labels: Vec<String>,
+        /// API server URL (defaults to the cluster the server runs in).
+        #[arg(long)]
+        api_url: Option<String>,
+        /// Bearer token for --api-url (kept if omitted on update).
+        #[arg(long, env = "KUBERNETES_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// PEM CA certificate file for --api-url.
+        #[arg(long)]
+        ca_cert: Option<PathBuf>,
+        /// Keep the sync but stop applying on writes.
+        #[arg(long)]
+        disabled: bool,
+    },
+    /// Show Kubernetes syncs and their status.
+    Get {
+        /// Show only this sync.
+        name: Option<String>,
+    },
+    /// Remove a Kubernetes sync (the Secret stays in the cluster).
+    Remove {
+        /// Sync name.
+        name: String,
+    },
+    /// Apply the Secret now if it differs from the vault.
+    Run {
+        /// Sync name.
+        name: String,
+        /// Apply the Secret even if it is already in sync.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Compare the Secret in the cluster with the vault.
+    Drift {
+        /// Sync name.
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DockerCredentialCommands {
+    /// Print the credentials of the registry URL on stdin.
+    Get,
+    /// Save the `{"ServerURL", "Username", "Secret"}` JSON on stdin.
+    Store,
+    /// Delete the credentials of the registry URL on stdin.
+    Erase,
+    /// Print `{"<registry URL>": "<username>"}` for every stored registry.
+    List,
+}
+
+#[derive(Subcommand)]
+enum BackupCommands {
+    /// Summarize a backup file without contacting the vault.
+    Inspect {
+        /// Path to the backup file.
+        file: String,
+        /// Number of key path segments to group entries by.
+        #[arg(long, default_value_t = 2)]
+        depth: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum RotateCommands {
+    /// Create or replace the server-side rotation policy of a KV secret.
+    SetPolicy {
+        /// Secret as `<mount>/<path>` (e.g., `secret/app/db`).
+        path: String,
+        /// Secret field that receives new values.
+        #[arg(long, default_value = "value")]
+        field: String,
+        #[command(flatten)]
+        rotator: Box<RotatorArgs>,
+        /// Time between rotations (e.g., 24h, 30d).
+        #[arg(long, conflicts_with = "cron", required_unless_present = "cron")]
+        interval: Option<String>,
+        /// Cron schedule in UTC (e.g., "0 3 * * *").
+        #[arg(long)]
+        cron: Option<String>,
+    },
+    /// Show the rotation policy of a secret.
+    GetPolicy {
+        /// Secret as `<mount>/<path>`.
+        path: String,
+    },
+    /// List all rotation policies.
+    ListPolicies,
+    /// Remove a rotation policy (the secret is kept).
+    RemovePolicy {
+        /// Secret as `<mount>/<path>`.
+        path: String,
+    },
+    /// Rotate a secret now.
+    Trigger {
+        /// Secret as `<mount>/<path>`.
+        path: String,
+    },
+    /// Show rotation status for all secrets with policies.
+    Status,
+}
+
+/// How `rotate set-policy` produces new values.
+#[derive(clap::Args)]
+struct RotatorArgs {
+    /// Rotator type.
+    #[arg(long = "rotator", value_parser = ["random", "database", "webhook"], default_value = "random")]
+    kind: String,
+    /// Length of random values.
+    #[arg(long, default_value_t = 32)]
+    length: usize,
+    /// Character set of random values.
+    #[arg(long, value_parser = ["alphanumeric", "hex", "ascii"], default_value = "alphanumeric")]
+    charset: String,
+    /// Database engine mount (database rotator).
+    #[arg(long, default_value = "database/")]
+    db_mount: String,
+    /// Static role whose password is rotated (database rotator).
+    #[arg(long)]
+    role: Option<String>,
+    /// Endpoint returning `{"value": ...}` (webhook rotator).
+    #[arg(long)]
+    url: Option<String>,
+    /// HMAC secret for signing webhook rotator requests.
+    #[arg(long)]
+    secret: Option<String>,
+}
+
+/// Store, region, and conflict handling of `import-aws` and `export-aws`.
+#[derive(clap::Args)]
+struct AwsMigrateArgs {
+    /// AWS store to migrate with.
+    #[arg(long, value_parser = ["secrets-manager", "parameter-store"], default_value = "secrets-manager")]
+    store: String,
+    /// AWS region (default: `AWS_REGION`, then `AWS_DEFAULT_REGION`, then
+    /// the server's).
+    #[arg(long, env = "AWS_REGION")]
+    region: Option<String>,
+    /// KV mount.
+    #[arg(long, default_value = "secret")]
+    mount: String,
+    /// What to do with targets that exist with a different value.
+    #[arg(long, value_parser = ["skip", "overwrite", "fail"], default_value = "skip")]
+    conflict: String,
+    /// Show what would be migrated without writing anything.
+    #[arg(long)]
+    dry_run: bool,
+    /// Use the server's AWS credentials instead of this environment's.
+    #[arg(long)]
+    server_credentials: bool,
+}
+
+/// Server-side filters of `audit-export`.
+#[derive(clap::Args)]
+struct AuditFilterArgs {
+    /// Only entries at or after this time: RFC 3339, or a duration ago
+    /// (e.g. `30d`).
+    #[arg(long)]
+    since: Option<String>,
+    /// Only entries before this time, in the same forms as `--since`.
+    #[arg(long)]
+    until: Option<String>,
+    /// Only entries whose path starts with this (e.g. `secret/data/db`).
+    #[arg(long)]
+    path_prefix: Option<String>,
+    /// Only entries made by this display name.
+    #[arg(long)]
+    actor: Option<String>,
+    /// Only entries made with the token of this hash.
+    #[arg(long)]
+    token_hash: Option<String>,
+    /// Only entries made with the token of this accessor (see `token lookup`).
+    #[arg(long)]
+    accessor: Option<String>,
+    /// Only entries with this operation (read, write, delete, ...).
+    #[arg(long)]
+    operation: Option<String>,
+    /// Only `success` or `error` entries, or one status code.
+    #[arg(long)]
+    status: Option<String>,
+}
+
+impl AuditFilterArgs {
+    /// The filters as URL query pairs, each prefixed with `&`.
+    fn query(&self) -> Result<String> {
+        let params = [
+            ("start_time", &self.since),
+            ("end_time", &self.until),
+            ("path_prefix", &self.path_prefix),
+            ("actor", &self.actor),
+            ("token_hash", &self.token_hash),
+            ("accessor", &self.accessor),
+            ("operation", &self.operation),
+            ("status", &self.status),
+        ];
+        let params = params
+            .iter()
+            .filter_map(|(name, value)| value.as_deref().map(|v| (*name, v)));
+        let url = reqwest::Url::parse_with_params("http://localhost/", params)?;
+        Ok(url.query().map(|q| format!("&{q}")).unwrap_or_default())
+    }
+}
+
+// ── Pretty output helpers ────────────────────────────────────────────
 
 pub(crate) fn header(icon: &str, title: &str) {
-    println!("{BOLD}{CYAN}{icon} {title}{RESET}");
-    println!("{DIM}─────────────────────────────────────────{RESET}");
+    outln!("{BOLD}{CYAN}{icon} {title}{RESET}");
+    outln!("{DIM}─────────────────────────────────────────{RESET}");
 }
 
 pub(crate) fn kv_line(key: &str, value: &str) {
-    println!("  {DIM}{key:<20}{RESET} {WHITE}{value}{RESET}");
+    outln!("  {DIM}{key:<20}{RESET} {WHITE}{value}{RESET}");
 }
 
 pub(crate) fn success(msg: &str) {
-    println!("{GREEN}{BOLD}✓{RESET} {msg}");
+    outln!("{GREEN}{BOLD}✓{RESET} {msg}");
 }
 
 pub(crate) fn warning(msg: &str) {
-    println!("{YELLOW}{BOLD}⚠{RESET} {YELLOW}{msg}{RESET}");
+    outln!("{YELLOW}{BOLD}⚠{RESET} {YELLOW}{msg}{RESET}");
+}
+
+/// Safety gate for destructive commands: the caller must type `resource` back.
+///
+/// `--confirm <resource>` answers the prompt up front, and
+/// `ZVAULT_NON_INTERACTIVE=1` skips it for automation. Without either, a
+/// non-terminal stdin is refused rather than silently proceeding.
+pub(crate) fn confirm_destructive(
+    action: &str,
+    resource: &str,
+    confirm: Option<&str>,
+) -> Result<()> {
+    use std::io::{BufRead as _, IsTerminal as _, Write as _};
+
+    if let Some(answer) = confirm {
+        if answer == resource {
+            return Ok(());
+        }
+        bail!("--confirm value '{answer}' does not match '{resource}'");
+    }
+
+    if std::env::var("ZVAULT_NON_INTERACTIVE").is_ok_and(|v| v == "1" || v == "true") {
+        return Ok(());
+    }
+
+    if !std::io::stdin().is_terminal() {
+        bail!(
+            "refusing to {action} without confirmation — pass --confirm {resource} or set ZVAULT_NON_INTERACTIVE=1"
+        );
+    }
+
+    outln!();
+    warning(&format!("This will {action}. This cannot be undone."));
+    out!("  Type {BOLD}{resource}{RESET} to confirm: ");
+    std::io::stdout()
+        .flush()
+        .context("failed to flush stdout")?;
+
+    let mut answer = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("failed to read confirmation")?;
+    if answer.trim() != resource {
+        bail!("confirmation did not match — aborted");
+    }
+    Ok(())
 }
 
 fn print_seal_status(resp: &Value) {
@@ -671,16 +2220,15 @@ fn print_seal_status(resp: &Value) {
         kv_line("Unseal Progress", &format!("{bar} {progress}/{threshold}"));
     }
 
-    println!();
+    outln!();
 }
 
 fn progress_bar(current: u64, total: u64) -> String {
     let width: usize = 20;
-    let filled = if total > 0 {
-        usize::try_from((current * u64::try_from(width).unwrap_or(20)) / total).unwrap_or(0)
-    } else {
-        0
-    };
+    let filled = (current * u64::try_from(width).unwrap_or(20))
+        .checked_div(total)
+        .and_then(|n| usize::try_from(n).ok())
+        .unwrap_or(0);
     let empty = width.saturating_sub(filled);
     format!(
         "{CYAN}[{}{DIM}{}]{RESET}",
@@ -692,33 +2240,62 @@ fn progress_bar(current: u64, total: u64) -> String {
 fn print_init_response(resp: &Value) {
     print_banner();
     header("🔑", "Vault Initialized");
-    println!();
+    outln!();
 
-    if let Some(shares) = resp.get("unseal_shares").and_then(Value::as_array) {
-        println!("  {YELLOW}{BOLD}⚠  Store these unseal keys in separate secure locations!{RESET}");
-        println!("  {YELLOW}   They will NOT be shown again.{RESET}");
-        println!();
+    let recovery = print_key_shares(resp);
+
+    outln!();
+
+    if let Some(token) = resp.get("root_token").and_then(Value::as_str) {
+        outln!("  {DIM}Root Token:{RESET}    {GREEN}{BOLD}{token}{RESET}");
+    }
+
+    outln!();
+    if recovery {
+        outln!(
+            "  {DIM}Vault is initialized and {GREEN}{BOLD}unsealed{RESET}{DIM} by its KMS seal.{RESET}"
+        );
+    } else {
+        outln!(
+            "  {DIM}Vault is initialized but {YELLOW}{BOLD}sealed{RESET}{DIM}. Use `zvault unseal`{RESET}"
+        );
+        outln!("  {DIM}with the required threshold of key shares to unseal.{RESET}");
+    }
+    outln!();
+}
+
+/// Print the newly issued unseal or recovery shares in `resp`.
+///
+/// Auto-unseal vaults hand out recovery keys instead of unseal keys; returns
+/// whether these were recovery keys.
+fn print_key_shares(resp: &Value) -> bool {
+    let recovery = resp
+        .get("recovery_shares")
+        .and_then(Value::as_array)
+        .filter(|shares| !shares.is_empty());
+    let (label, shares) = match recovery {
+        Some(shares) => ("Recovery", Some(shares)),
+        None => (
+            "Unseal",
+            resp.get("unseal_shares").and_then(Value::as_array),
+        ),
+    };
+
+    if let Some(shares) = shares {
+        let kind = label.to_lowercase();
+        outln!("  {YELLOW}{BOLD}⚠  Store these {kind} keys in separate secure locations!{RESET}");
+        outln!("  {YELLOW}   They will NOT be shown again.{RESET}");
+        outln!();
 
         for (i, share) in shares.iter().enumerate() {
             if let Some(s) = share.as_str() {
                 let num = i.checked_add(1).unwrap_or(i);
-                println!("  {DIM}Unseal Key {num}:{RESET}  {MAGENTA}{s}{RESET}");
+                outln!("  {DIM}{label} Key {num}:{RESET}  {MAGENTA}{s}{RESET}");
             }
         }
     }
 
-    println!();
-
-    if let Some(token) = resp.get("root_token").and_then(Value::as_str) {
-        println!("  {DIM}Root Token:{RESET}    {GREEN}{BOLD}{token}{RESET}");
-    }
-
-    println!();
-    println!(
-        "  {DIM}Vault is initialized but {YELLOW}{BOLD}sealed{RESET}{DIM}. Use `zvault unseal`{RESET}"
-    );
-    println!("  {DIM}with the required threshold of key shares to unseal.{RESET}");
-    println!();
+    recovery.is_some()
 }
 
 fn print_unseal_response(resp: &Value) {
@@ -729,25 +2306,29 @@ fn print_unseal_response(resp: &Value) {
     if sealed {
         header("🔓", "Unseal Progress");
         let bar = progress_bar(progress, threshold);
-        println!("  {bar} {BOLD}{progress}{RESET}/{threshold} shares submitted");
-        println!();
+        outln!("  {bar} {BOLD}{progress}{RESET}/{threshold} shares submitted");
+        outln!();
         let remaining = threshold.saturating_sub(progress);
-        println!("  {DIM}{remaining} more share(s) needed to unseal.{RESET}");
+        outln!("  {DIM}{remaining} more share(s) needed to unseal.{RESET}");
     } else {
-        println!();
-        println!("  {BG_GREEN}{WHITE}{BOLD} ✓ VAULT UNSEALED {RESET}");
-        println!();
-        println!("  {DIM}The vault is now ready to accept requests.{RESET}");
+        outln!();
+        outln!("  {BG_GREEN}{WHITE}{BOLD} ✓ VAULT UNSEALED {RESET}");
+        outln!();
+        outln!("  {DIM}The vault is now ready to accept requests.{RESET}");
     }
-    println!();
+    outln!();
 }
 
 fn print_token_response(resp: &Value) {
     header("🪙", "Token Created");
 
     if let Some(token) = resp.get("client_token").and_then(Value::as_str) {
-        println!();
-        println!("  {DIM}Token:{RESET}       {GREEN}{BOLD}{token}{RESET}");
+        outln!();
+        outln!("  {DIM}Token:{RESET}       {GREEN}{BOLD}{token}{RESET}");
+    }
+
+    if let Some(accessor) = resp.get("accessor").and_then(Value::as_str) {
+        kv_line("Accessor", accessor);
     }
 
     if let Some(policies) = resp.get("policies").and_then(Value::as_array) {
@@ -763,7 +2344,7 @@ fn print_token_response(resp: &Value) {
         kv_line("TTL", &format_duration(dur));
     }
 
-    println!();
+    outln!();
 }
 
 fn print_token_lookup(resp: &Value) {
@@ -774,6 +2355,10 @@ fn print_token_lookup(resp: &Value) {
         kv_line("Token Hash", &format!("{short}..."));
     }
 
+    if let Some(accessor) = resp.get("accessor").and_then(Value::as_str) {
+        kv_line("Accessor", accessor);
+    }
+
     if let Some(policies) = resp.get("policies").and_then(Value::as_array) {
         let names: Vec<&str> = policies.iter().filter_map(Value::as_str).collect();
         kv_line("Policies", &names.join(", "));
@@ -783,6 +2368,17 @@ fn print_token_lookup(resp: &Value) {
         kv_line("Display Name", name);
     }
 
+    if let Some(token_type) = resp.get("type").and_then(Value::as_str) {
+        kv_line("Type", token_type);
+    }
+
+    if let Some(cidrs) = resp.get("bound_cidrs").and_then(Value::as_array) {
+        let cidrs: Vec<&str> = cidrs.iter().filter_map(Value::as_str).collect();
+        if !cidrs.is_empty() {
+            kv_line("Bound CIDRs", &cidrs.join(", "));
+        }
+    }
+
     if let Some(expires) = resp.get("expires_at").and_then(Value::as_str) {
         if expires.is_empty() {
             kv_line("Expires", "never");
@@ -791,7 +2387,7 @@ fn print_token_lookup(resp: &Value) {
         }
     }
 
-    println!();
+    outln!();
 }
 
 fn print_secret_response(path: &str, resp: &Value) {
@@ -815,12 +2411,12 @@ fn print_secret_response(path: &str, resp: &Value) {
 
     if let Some(lease) = resp.get("lease_id").and_then(Value::as_str) {
         if !lease.is_empty() {
-            println!();
+            outln!();
             kv_line("Lease ID", lease);
         }
     }
 
-    println!();
+    outln!();
 }
 
 fn print_list_response(path: &str, resp: &Value) {
@@ -829,11 +2425,11 @@ fn print_list_response(path: &str, resp: &Value) {
     if let Some(data) = resp.get("data") {
         if let Some(keys) = data.get("keys").and_then(Value::as_array) {
             if keys.is_empty() {
-                println!("  {DIM}(empty){RESET}");
+                outln!("  {DIM}(empty){RESET}");
             } else {
                 for key in keys {
                     if let Some(k) = key.as_str() {
-                        println!("  {CYAN}├─{RESET} {k}");
+                        outln!("  {CYAN}├─{RESET} {k}");
                     }
                 }
             }
@@ -844,7 +2440,60 @@ fn print_list_response(path: &str, resp: &Value) {
         print_json(resp);
     }
 
-    println!();
+    outln!();
+}
+
+fn print_secret_stats(prefix: &str, resp: &Value, unread_only: bool, sort: &str) {
+    header("📊", &format!("Secret Usage: secret/{prefix}"));
+
+    let mut secrets = resp
+        .get("secrets")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let count = |secret: &Value, key: &str| secret.get(key).and_then(Value::as_u64).unwrap_or(0);
+    let total = secrets.len();
+    let never_read = secrets.iter().filter(|s| count(s, "reads") == 0).count();
+
+    if unread_only {
+        secrets.retain(|s| count(s, "reads") == 0);
+    }
+    match sort {
+        "reads" => secrets.sort_by_key(|s| std::cmp::Reverse(count(s, "reads"))),
+        "readers" => secrets.sort_by_key(|s| std::cmp::Reverse(count(s, "distinct_readers"))),
+        _ => {}
+    }
+
+    if secrets.is_empty() {
+        outln!("  {DIM}(no secrets){RESET}");
+        outln!();
+        return;
+    }
+
+    outln!(
+        "  {DIM}{:>8}  {:>7}  {:<25}  PATH{RESET}",
+        "READS",
+        "READERS",
+        "LAST READ"
+    );
+    for secret in &secrets {
+        let last_read = secret
+            .get("last_read")
+            .and_then(Value::as_str)
+            .and_then(|t| t.get(..19))
+            .unwrap_or("never");
+        outln!(
+            "  {:>8}  {:>7}  {:<25}  {}",
+            count(secret, "reads"),
+            count(secret, "distinct_readers"),
+            last_read,
+            secret.get("path").and_then(Value::as_str).unwrap_or("-")
+        );
+    }
+    outln!();
+    kv_line("Secrets", &total.to_string());
+    kv_line("Never Read", &never_read.to_string());
+    outln!();
 }
 
 fn print_policy_list(resp: &Value) {
@@ -852,7 +2501,7 @@ fn print_policy_list(resp: &Value) {
 
     if let Some(policies) = resp.get("policies").and_then(Value::as_array) {
         if policies.is_empty() {
-            println!("  {DIM}(no policies){RESET}");
+            outln!("  {DIM}(no policies){RESET}");
         } else {
             for p in policies {
                 if let Some(name) = p.as_str() {
@@ -861,7 +2510,7 @@ fn print_policy_list(resp: &Value) {
                         "default" => "📋",
                         _ => "📜",
                     };
-                    println!("  {icon} {name}");
+                    outln!("  {icon} {name}");
                 }
             }
         }
@@ -869,7 +2518,7 @@ fn print_policy_list(resp: &Value) {
         print_json(resp);
     }
 
-    println!();
+    outln!();
 }
 
 fn print_policy_detail(name: &str, resp: &Value) {
@@ -888,14 +2537,21 @@ fn print_policy_detail(name: &str, resp: &Value) {
                         .join(", ")
                 })
                 .unwrap_or_default();
-            println!("  {CYAN}{path}{RESET}");
-            println!("    {DIM}capabilities:{RESET} {caps}");
+            outln!("  {CYAN}{path}{RESET}");
+            outln!("    {DIM}capabilities:{RESET} {caps}");
+            if let Some(group) = rule.get("control_group").filter(|g| !g.is_null()) {
+                let approvals = group.get("approvals").and_then(Value::as_u64).unwrap_or(0);
+                outln!(
+                    "    {DIM}control group:{RESET} {approvals} approval(s) from {}",
+                    control_group_approvers(group)
+                );
+            }
         }
     } else {
         print_json(resp);
     }
 
-    println!();
+    outln!();
 }
 
 fn print_transit_key_list(resp: &Value) {
@@ -903,11 +2559,11 @@ fn print_transit_key_list(resp: &Value) {
 
     if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
         if keys.is_empty() {
-            println!("  {DIM}(no keys){RESET}");
+            outln!("  {DIM}(no keys){RESET}");
         } else {
             for k in keys {
                 if let Some(name) = k.as_str() {
-                    println!("  {MAGENTA}⚷{RESET}  {name}");
+                    outln!("  {MAGENTA}⚷{RESET}  {name}");
                 }
             }
         }
@@ -915,7 +2571,7 @@ fn print_transit_key_list(resp: &Value) {
         print_json(resp);
     }
 
-    println!();
+    outln!();
 }
 
 fn print_transit_key_info(resp: &Value) {
@@ -928,6 +2584,14 @@ fn print_transit_key_info(resp: &Value) {
     if let Some(min) = resp.get("min_decryption_version").and_then(Value::as_u64) {
         kv_line("Min Decrypt Ver", &format!("v{min}"));
     }
+    if let Some(min) = resp.get("min_encryption_version").and_then(Value::as_u64) {
+        let min = if min == 0 {
+            "latest".to_owned()
+        } else {
+            format!("v{min}")
+        };
+        kv_line("Min Encrypt Ver", &min);
+    }
     if let Some(enc) = resp.get("supports_encryption").and_then(Value::as_bool) {
         kv_line("Encryption", if enc { "yes" } else { "no" });
     }
@@ -941,35 +2605,66 @@ fn print_transit_key_info(resp: &Value) {
         kv_line("Created", created);
     }
 
-    println!();
+    outln!();
 }
 
 fn print_encrypt_response(resp: &Value) {
     header("🔒", "Encrypted");
 
     if let Some(ct) = resp.get("ciphertext").and_then(Value::as_str) {
-        println!();
-        println!("  {DIM}Ciphertext:{RESET}");
-        println!("  {MAGENTA}{ct}{RESET}");
+        outln!();
+        outln!("  {DIM}Ciphertext:{RESET}");
+        outln!("  {MAGENTA}{ct}{RESET}");
     } else {
         print_json(resp);
     }
 
-    println!();
+    outln!();
 }
 
 fn print_decrypt_response(resp: &Value) {
     header("🔓", "Decrypted");
 
     if let Some(pt) = resp.get("plaintext").and_then(Value::as_str) {
-        println!();
-        println!("  {DIM}Plaintext (base64):{RESET}");
-        println!("  {GREEN}{pt}{RESET}");
+        outln!();
+        outln!("  {DIM}Plaintext (base64):{RESET}");
+        outln!("  {GREEN}{pt}{RESET}");
     } else {
         print_json(resp);
     }
 
-    println!();
+    outln!();
+}
+
+fn print_token(resp: &Value) {
+    header("🎟", "Token");
+
+    if let Some(token) = resp.get("token").and_then(Value::as_str) {
+        kv_line("Token", &format!("{MAGENTA}{token}{RESET}"));
+    }
+    if let Some(convergent) = resp.get("convergent").and_then(Value::as_bool) {
+        kv_line("Convergent", if convergent { "yes" } else { "no" });
+    }
+    if let Some(version) = resp.get("key_version").and_then(Value::as_u64) {
+        kv_line("Key Version", &version.to_string());
+    }
+    if let Some(created) = resp.get("created_at").and_then(Value::as_str) {
+        kv_line("Created", created);
+    }
+    if let Some(metadata) = resp.get("metadata").and_then(Value::as_object) {
+        let mut entries: Vec<_> = metadata.iter().collect();
+        entries.sort_by_key(|(k, _)| *k);
+        for (k, v) in entries {
+            kv_line(&format!("meta.{k}"), v.as_str().unwrap_or_default());
+        }
+    }
+    if let Some(pt) = resp.get("plaintext").and_then(Value::as_str) {
+        outln!();
+        outln!("  {DIM}Plaintext (base64):{RESET}");
+        outln!("  {GREEN}{pt}{RESET}");
+    }
+
+    outln!();
 }
 
 fn format_duration(secs: i64) -> String {
@@ -997,9 +2692,41 @@ struct Client {
 }
 
 impl Client {
-    fn new(addr: String, token: Option<String>) -> Self {
-        let http = reqwest::Client::new();
-        Self { http, addr, token }
+    fn new(
+        addr: String,
+        token: Option<String>,
+        mfa: Option<&str>,
+        ca_cert: Option<&Path>,
+        client_identity: Option<(&Path, &Path)>,
+    ) -> Result<Self> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(mfa) = mfa {
+            let value = reqwest::header::HeaderValue::from_str(mfa)
+                .context("MFA credentials must be printable ASCII")?;
+            headers.insert("X-Vault-MFA", value);
+        }
+        let mut builder = reqwest::Client::builder().default_headers(headers);
+        if let Some(path) = ca_cert {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("failed to read CA certificate {}", path.display()))?;
+            let certificate = reqwest::Certificate::from_pem(&pem)
+                .with_context(|| format!("invalid CA certificate {}", path.display()))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some((cert_path, key_path)) = client_identity {
+            let mut pem = std::fs::read(cert_path).with_context(|| {
+                format!("failed to read client certificate {}", cert_path.display())
+            })?;
+            pem.extend(
+                std::fs::read(key_path)
+                    .with_context(|| format!("failed to read client key {}", key_path.display()))?,
+            );
+            let identity =
+                reqwest::Identity::from_pem(&pem).context("invalid client certificate or key")?;
+            builder = builder.identity(identity);
+        }
+        let http = builder.build().context("failed to build HTTP client")?;
+        Ok(Self { http, addr, token })
     }
 
     fn url(&self, path: &str) -> String {
@@ -1037,6 +2764,30 @@ impl Client {
         handle_response(resp).await
     }
 
+    /// POST asking the server to wrap the response for `wrap_ttl`.
+    async fn post_wrapped(&self, path: &str, body: &Value, wrap_ttl: &str) -> Result<Value> {
+        let token = self.auth_header()?;
+        let resp = self
+            .http
+            .post(self.url(path))
+            .header("X-Vault-Token", &token)
+            .header("X-Vault-Wrap-TTL", wrap_ttl)
+            .json(body)
+            .send()
+            .await
+            .context("request failed")?;
+        handle_response(resp).await
+    }
+
+    /// A client for the same server authenticating with `token`.
+    fn with_token(&self, token: String) -> Self {
+        Self {
+            http: self.http.clone(),
+            addr: self.addr.clone(),
+            token: Some(token),
+        }
+    }
+
     async fn post_no_auth(&self, path: &str, body: &Value) -> Result<Value> {
         let resp = self
             .http
@@ -1072,6 +2823,39 @@ impl Client {
         handle_response(resp).await
     }
 
+    /// Repeat a parked request to receive its result once approved.
+    async fn replay_parked(&self, method: &str, path: &str, accessor: &str) -> Result<Value> {
+        let token = self.auth_header()?;
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .with_context(|| format!("invalid method {method}"))?;
+        let resp = self
+            .http
+            .request(method, self.url(path))
+            .header("X-Vault-Token", &token)
+            .header("X-Vault-Control-Group", accessor)
+            .send()
+            .await
+            .context("request failed")?;
+        handle_response(resp).await
+    }
+
+    /// Send a request with the token (if any) and return the raw response.
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<reqwest::Response> {
+        let mut req = self.http.request(method, self.url(path));
+        if let Some(token) = &self.token {
+            req = req.header("X-Vault-Token", token);
+        }
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        req.send().await.context("request failed")
+    }
+
     async fn get_no_auth(&self, path: &str) -> Result<Value> {
         let resp = self
             .http
@@ -1084,13 +2868,31 @@ impl Client {
 }
 
 async fn handle_response(resp: reqwest::Response) -> Result<Value> {
+    let value = parse_response(resp).await?;
+    output::record(&value);
+    Ok(value)
+}
+
+async fn parse_response(resp: reqwest::Response) -> Result<Value> {
     let status = resp.status();
     if status == reqwest::StatusCode::NO_CONTENT {
         return Ok(Value::Null);
     }
     let body = resp.text().await.context("failed to read response body")?;
     if !status.is_success() {
-        bail!("server returned {status}: {body}");
+        return Err(ApiError::from_body(status, &body).into());
+    }
+    if status == reqwest::StatusCode::ACCEPTED {
+        let parked: Value = serde_json::from_str(&body).unwrap_or_default();
+        if let Some(accessor) = parked
+            .pointer("/control_group/accessor")
+            .and_then(Value::as_str)
+        {
+            bail!(
+                "request requires control group approval (accessor {accessor}); once approved, \
+                 run `zvault control-group retrieve {accessor}`"
+            );
+        }
     }
     if body.is_empty() {
         return Ok(Value::Null);
@@ -1100,35 +2902,108 @@ async fn handle_response(resp: reqwest::Response) -> Result<Value> {
 
 // ── Command dispatch ─────────────────────────────────────────────────
 
+impl Commands {
+    /// Whether `--format` and `--field` apply: commands that stream, run
+    /// other programs, or already print raw output keep stdout as is.
+    fn honors_output_format(&self) -> bool {
+        !matches!(
+            self,
+            Self::Run { .. }
+                | Self::McpServer { .. }
+                | Self::Events { .. }
+                | Self::AuditExport { .. }
+                | Self::Render { .. }
+                | Self::Agent { .. }
+                | Self::Tui
+                | Self::Completions { .. }
+                | Self::Kv {
+                    action: KvCommands::Export { .. },
+                    ..
+                }
+                | Self::Api { .. }
+                | Self::TfOutput { .. }
+                | Self::DockerCredentialHelper { .. }
+                | Self::BuildInfo { .. }
+        )
+    }
+}
+
 #[tokio::main]
 async fn main() -> ExitCode {
-    let cli = Cli::parse();
-    let client = Client::new(cli.addr, cli.token);
+    completions::handle_request();
+    let cli = match docker_credentials::helper_args() {
+        Some(args) => Cli::parse_from(args),
+        None => Cli::parse(),
+    };
+    let machine = (cli.format != OutputFormat::Table || cli.field.is_some())
+        && cli.command.honors_output_format();
+    if machine {
+        output::set_machine();
+    }
+    let result = match Client::new(
+        cli.addr,
+        cli.token,
+        cli.mfa.as_deref(),
+        cli.ca_cert.as_deref(),
+        cli.client_cert.as_deref().zip(cli.client_key.as_deref()),
+    ) {
+        Ok(client) => run(client, cli.command).await,
+        Err(e) => Err(e),
+    };
+    let result = result.and_then(|()| {
+        if machine {
+            output::emit(cli.format, cli.field.as_deref())
+        } else {
+            Ok(())
+        }
+    });
 
-    match run(client, cli.command).await {
+    match result {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
             eprintln!();
             eprintln!("  {RED}{BOLD}✗ Error:{RESET} {e:#}");
+            if let Some(hint) = e
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<ApiError>())
+                .and_then(ApiError::hint)
+            {
+                eprintln!("  {DIM}{hint}{RESET}");
+            }
             eprintln!();
             ExitCode::FAILURE
         }
     }
 }
 
+#[allow(clippy::too_many_lines)]
 async fn run(client: Client, cmd: Commands) -> Result<()> {
     match cmd {
         Commands::Status => cmd_status(&client).await,
         Commands::Init { shares, threshold } => cmd_init(&client, shares, threshold).await,
         Commands::Unseal { share } => cmd_unseal(&client, &share).await,
         Commands::Seal => cmd_seal(&client).await,
+        Commands::Rekey {
+            shares,
+            threshold,
+            cancel,
+        } => cmd_rekey(&client, shares, threshold, cancel).await,
+        Commands::GenerateRoot {
+            otp,
+            decode,
+            cancel,
+        } => cmd_generate_root(&client, otp, decode, cancel).await,
         Commands::Token { action } => cmd_token(&client, action).await,
-        Commands::Kv { action } => cmd_kv(&client, action).await,
+        Commands::Kv { mount, action } => cmd_kv(&client, mount.trim_matches('/'), action).await,
         Commands::Policy { action } => cmd_policy(&client, action).await,
         Commands::Transit { action } => cmd_transit(&client, action).await,
         Commands::Database { action } => cmd_database(&client, action).await,
         Commands::Pki { action } => cmd_pki(&client, action).await,
         Commands::Approle { action } => cmd_approle(&client, action).await,
+        Commands::Jwt { action } => cmd_jwt(&client, action).await,
+        Commands::Cert { action } => cmd_cert(&client, action).await,
+        Commands::Wrapping { action } => cmd_wrapping(&client, action).await,
+        Commands::Cubbyhole { action } => cmd_cubbyhole(&client, action).await,
         Commands::Import {
             file,
             project,
@@ -1146,18 +3021,54 @@ async fn run(client: Client, cmd: Commands) -> Result<()> {
             )
             .await
         }
-        Commands::Run { env_file, command } => {
-            cmd_run(&client, env_file.as_deref(), &command).await
+        Commands::Scan {
+            path,
+            no_ignore,
+            staged,
+            import,
+            project,
+            exit_code,
+        } => {
+            let target = if staged {
+                scan::ScanTarget::Staged
+            } else {
+                scan::ScanTarget::Tree {
+                    path: &path,
+                    no_ignore,
+                }
+            };
+            scan::cmd_scan(&client, target, import, project.as_deref(), exit_code).await
         }
-        Commands::McpServer => {
-            license::require_pro("MCP server (AI Mode)")?;
-            mcp::run_mcp_server(client.addr, client.token).await
+        Commands::Hooks { action } => match action {
+            HooksCommands::Install { force } => hooks::cmd_hooks_install(force),
+            HooksCommands::Uninstall => hooks::cmd_hooks_uninstall(),
+        },
+        Commands::Run {
+            env_file,
+            env,
+            command,
+        } => {
+            let profile = env.as_deref().map(profile::load).transpose()?;
+            cmd_run(&client, env_file.as_deref(), profile.as_ref(), &command).await
         }
-        Commands::Setup { ide } => {
-            license::require_pro("IDE setup (AI Mode)")?;
-            cmd_setup(&ide)
+        Commands::McpServer {
+            read_only,
+            disable_tools,
+            config,
+            session_policies,
+            session_ttl,
+        } => {
+            let overrides = mcp_config::Overrides {
+                read_only,
+                disable_tools,
+                session_policies,
+                session_ttl,
+            };
+            mcp::run_mcp_server(client.addr, client.token, config.as_deref(), &overrides).await
         }
-        Commands::Activate { key } => cmd_activate(&key).await,
+        Commands::Setup { ide } => cmd_setup(&ide),
+        Commands::Activate { key, server: true } => cmd_activate_server(&client, &key).await,
+        Commands::Activate { key, server: false } => cmd_activate(&key).await,
         Commands::License => {
             cmd_license();
             Ok(())
@@ -1165,27 +3076,172 @@ async fn run(client: Client, cmd: Commands) -> Result<()> {
         Commands::Doctor => cmd_doctor(&client).await,
         Commands::ProjectInit { name, server } => cmd_project_init(name.as_deref(), &server),
         Commands::Lease { action } => cmd_lease(&client, action).await,
+        Commands::Access { action } => cmd_access(&client, action).await,
+        Commands::Identity { action } => cmd_identity(&client, action).await,
+        Commands::ControlGroup { action } => cmd_control_group(&client, action).await,
+        Commands::Events { action } => cmd_events(&client, action).await,
         Commands::AuditExport {
             format,
             limit,
             output,
-        } => cmd_audit_export(&client, &format, limit, output.as_deref()).await,
+            gzip,
+            count,
+            filter,
+        } => {
+            if count {
+                cmd_audit_count(&client, &filter).await
+            } else {
+                cmd_audit_export(&client, &format, limit, output.as_deref(), gzip, &filter).await
+            }
+        }
+        Commands::Apply {
+            file,
+            dry_run,
+            prune,
+            confirm,
+        } => cmd_apply(&client, &file, dry_run, prune, confirm.as_deref()).await,
+        Commands::Render {
+            template,
+            output,
+            mode,
+            watch,
+            interval,
+        } => {
+            render::cmd_render(
+                &client,
+                &template,
+                output.as_deref(),
+                &mode,
+                watch,
+                interval,
+            )
+            .await
+        }
+        Commands::Tui => tui::cmd_tui(&client).await,
+        Commands::Agent { config, once } => {
+            let config = agent::load(&config)?;
+            agent::run(&client, &config, once).await
+        }
         Commands::Notify { action } => cmd_notify(&client, action).await,
         Commands::Rotate { action } => cmd_rotate(&client, action).await,
+        Commands::Sync {
+            action: SyncCommands::Github { action },
+        } => cmd_sync_github(&client, action).await,
+        Commands::Sync {
+            action: SyncCommands::Kubernetes { action },
+        } => cmd_sync_kubernetes(&client, action).await,
+        Commands::TfOutput {
+            prefix,
+            keys,
+            field_only,
+            separator,
+            case,
+            hcl,
+        } => {
+            let options = tf_output::TfOptions {
+                keys,
+                field_only,
+                separator,
+                case,
+                hcl,
+            };
+            tf_output::cmd_tf_output(&client, prefix.as_deref(), options).await
+        }
+        Commands::ImportAws { prefix, path, aws } => {
+            aws_migrate::cmd_import_aws(&client, &prefix, &path, &aws).await
+        }
+        Commands::ExportAws {
+            path,
+            prefix,
+            kms_key_id,
+            aws,
+        } => {
+            aws_migrate::cmd_export_aws(&client, &path, &prefix, kms_key_id.as_deref(), &aws).await
+        }
+        Commands::MigrateVault {
+            addr,
+            token,
+            namespace,
+            vault_ca_cert,
+            mount,
+            path,
+            maps,
+            policies,
+            approles,
+            approle_mount,
+            conflict,
+            dry_run,
+        } => {
+            let options = vault_migrate::VaultMigrateOptions {
+                addr,
+                token,
+                namespace,
+                ca_cert: vault_ca_cert,
+                mount,
+                path,
+                maps,
+                policies,
+                approles,
+                approle_mount,
+                conflict,
+                dry_run,
+            };
+            vault_migrate::cmd_migrate_vault(&client, options).await
+        }
+        Commands::ImportPasswords {
+            file,
+            from,
+            prefix,
+            maps,
+            include_archived,
+            yes,
+            overwrite,
+            dry_run,
+        } => {
+            let options = password_import::PasswordImportOptions {
+                file,
+                from,
+                prefix,
+                maps,
+                include_archived,
+                yes,
+                overwrite,
+                dry_run,
+            };
+            password_import::cmd_import_passwords(&client, options).await
+        }
+        Commands::DockerCredentialHelper { prefix, action } => {
+            docker_credentials::cmd_docker_credentials(&client, &prefix, action).await
+        }
         Commands::Login { oidc } => cmd_login(&client, oidc).await,
         Commands::Logout => cloud::cmd_cloud_logout().await,
         Commands::Cloud { action } => cmd_cloud(&client, action).await,
-        Commands::Backup { output } => cmd_backup(&client, output.as_deref()).await,
-        Commands::Restore { file } => cmd_restore(&client, &file).await,
+        Commands::Backup { output, action } => cmd_backup(&client, output.as_deref(), action).await,
+        Commands::Restore {
+            file,
+            prefix,
+            confirm,
+        } => cmd_restore(&client, &file, prefix.as_deref(), confirm.as_deref()).await,
+        Commands::Mount { action } => cmd_mount(&client, action).await,
+        Commands::Plugin { action } => cmd_plugin(&client, action).await,
+        Commands::SelfUpdate { check, pin, force } => {
+            self_update::cmd_self_update(check, pin.as_deref(), force).await
+        }
+        Commands::Api { method, path, data } => {
+            cmd_api(&client, &method, &path, data.as_deref()).await
+        }
+        Commands::Completions { shell } => completions::cmd_completions(&shell),
+        Commands::Version { check } => self_update::cmd_version(&client.addr, check).await,
+        Commands::BuildInfo { json } => build_info::cmd_build_info(json),
     }
 }
 
 // ── System commands ──────────────────────────────────────────────────
 
 async fn cmd_status(client: &Client) -> Result<()> {
-    println!();
-    println!("  {BANNER_SMALL} {DIM}checking health...{RESET}");
-    println!();
+    outln!();
+    outln!("  {BANNER_SMALL} {DIM}checking health...{RESET}");
+    outln!();
     let resp = client.get_no_auth("/v1/sys/health").await?;
     print_seal_status(&resp);
     Ok(())
@@ -1207,17 +3263,223 @@ async fn cmd_unseal(client: &Client, share: &str) -> Result<()> {
 
 async fn cmd_seal(client: &Client) -> Result<()> {
     client.post_no_body("/v1/sys/seal").await?;
-    println!();
+    outln!();
     warning("Vault sealed — all key material zeroized from memory.");
-    println!();
+    outln!();
+    Ok(())
+}
+
+async fn cmd_rekey(
+    client: &Client,
+    shares: Option<u8>,
+    threshold: Option<u8>,
+    cancel: bool,
+) -> Result<()> {
+    if cancel {
+        client
+            .post("/v1/sys/rekey/cancel", &serde_json::json!({}))
+            .await?;
+        outln!();
+        success("Rekey cancelled — the current shares remain valid.");
+        outln!();
+        return Ok(());
+    }
+    let (Some(shares), Some(threshold)) = (shares, threshold) else {
+        bail!("--shares and --threshold are required");
+    };
+
+    // Resume a rekey already in progress if it targets the same config.
+    let mut status = client.get("/v1/sys/rekey/init").await?;
+    if status.get("started").and_then(Value::as_bool) == Some(true) {
+        let target = (
+            status.get("shares").and_then(Value::as_u64),
+            status.get("threshold").and_then(Value::as_u64),
+        );
+        if target != (Some(u64::from(shares)), Some(u64::from(threshold))) {
+            bail!(
+                "a rekey to a different share count or threshold is in progress — run `zvault rekey --cancel` first"
+            );
+        }
+    } else {
+        let body = serde_json::json!({ "shares": shares, "threshold": threshold });
+        status = client.post("/v1/sys/rekey/init", &body).await?;
+    }
+
+    header("🔁", "Rekey");
+    kv_line("New Shares", &shares.to_string());
+    kv_line("New Threshold", &threshold.to_string());
+    outln!();
+
+    let status = submit_shares(client, "/v1/sys/rekey/update", status, "rekey", true).await?;
+    print_rekey_response(&status);
+    Ok(())
+}
+
+/// Read current shares from stdin and submit them to `update_path` against
+/// the nonce in `status`, until the server reports the operation complete.
+///
+/// Prompts for each share on a terminal. Fails if stdin ends first. The
+/// client's token is sent only when `authenticated`.
+async fn submit_shares(
+    client: &Client,
+    update_path: &str,
+    mut status: Value,
+    operation: &str,
+    authenticated: bool,
+) -> Result<Value> {
+    use std::io::{BufRead as _, IsTerminal as _, Write as _};
+
+    let nonce = status
+        .get("nonce")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    let interactive = std::io::stdin().is_terminal();
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        let progress = status.get("progress").and_then(Value::as_u64).unwrap_or(0);
+        let required = status.get("required").and_then(Value::as_u64).unwrap_or(0);
+        if interactive {
+            let num = progress.saturating_add(1);
+            out!("  Current share {num}/{required}: ");
+            std::io::stdout()
+                .flush()
+                .context("failed to flush stdout")?;
+        }
+        let Some(line) = lines.next() else {
+            bail!(
+                "{operation} needs {} more share(s) — re-run to continue",
+                required.saturating_sub(progress)
+            );
+        };
+        let share = line.context("failed to read share")?;
+        let share = share.trim();
+        if share.is_empty() {
+            continue;
+        }
+
+        let body = serde_json::json!({ "nonce": nonce, "share": share });
+        status = if authenticated {
+            client.post(update_path, &body).await?
+        } else {
+            client.post_no_auth(update_path, &body).await?
+        };
+        if status.get("complete").and_then(Value::as_bool) == Some(true) {
+            return Ok(status);
+        }
+    }
+}
+
+fn print_rekey_response(resp: &Value) {
+    outln!();
+    success("Rekey complete — the previous shares no longer work.");
+    outln!();
+    print_key_shares(resp);
+    outln!();
+}
+
+async fn cmd_generate_root(
+    client: &Client,
+    otp: Option<String>,
+    decode: Option<String>,
+    cancel: bool,
+) -> Result<()> {
+    if cancel {
+        client.delete("/v1/sys/generate-root/attempt").await?;
+        outln!();
+        success("Root token generation cancelled.");
+        outln!();
+        return Ok(());
+    }
+    if let (Some(encoded), Some(otp)) = (&decode, &otp) {
+        let token = decode_root_token(encoded, otp)?;
+        outln!();
+        kv_line("Root Token", &token);
+        outln!();
+        return Ok(());
+    }
+
+    // Resume the attempt in progress only when its pad was supplied. Seeing
+    // it takes a token with sudo; without a token, start a new attempt.
+    let mut status = match client.token {
+        Some(_) => client.get("/v1/sys/generate-root/attempt").await?,
+        None if otp.is_some() => {
+            bail!("resuming with --otp needs a token with sudo on sys/generate-root/attempt")
+        }
+        None => Value::Null,
+    };
+    let started = status.get("started").and_then(Value::as_bool) == Some(true);
+    let otp = match otp {
+        Some(otp) if started => otp,
+        Some(_) => bail!("no root token generation is in progress"),
+        None if started => bail!(
+            "a root token generation is in progress — pass its --otp to continue or run `zvault generate-root --cancel`"
+        ),
+        None => {
+            status = client
+                .post_no_auth("/v1/sys/generate-root/attempt", &serde_json::json!({}))
+                .await?;
+            status
+                .get("otp")
+                .and_then(Value::as_str)
+                .context("server returned no one-time pad")?
+                .to_owned()
+        }
+    };
+
+    header("👑", "Generate Root Token");
+    kv_line("One-Time Pad", &otp);
+    outln!(
+        "  {DIM}Pass it to `zvault generate-root --otp` to resume from another terminal.{RESET}"
+    );
+    outln!();
+
+    let status = submit_shares(
+        client,
+        "/v1/sys/generate-root/update",
+        status,
+        "root token generation",
+        false,
+    )
+    .await?;
+    let encoded = status
+        .get("encoded_token")
+        .and_then(Value::as_str)
+        .context("server returned no encoded token")?;
+    let token = decode_root_token(encoded, &otp)?;
+
+    outln!();
+    success("Root token generated.");
+    outln!();
+    kv_line("Root Token", &token);
+    outln!();
     Ok(())
 }
 
+/// Undo the one-time pad applied to a generated root token.
+fn decode_root_token(encoded: &str, otp: &str) -> Result<String> {
+    use base64::Engine as _;
+
+    let xored = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .context("encoded token is not valid base64")?;
+    if xored.len() != otp.len() {
+        bail!("one-time pad length does not match the encoded token");
+    }
+    let token: Vec<u8> = xored.iter().zip(otp.bytes()).map(|(x, o)| x ^ o).collect();
+    String::from_utf8(token).context("encoded token does not decode with this one-time pad")
+}
+
 // ── Token commands ───────────────────────────────────────────────────
 
 async fn cmd_token(client: &Client, action: TokenCommands) -> Result<()> {
     match action {
-        TokenCommands::Create { policies, ttl } => {
+        TokenCommands::Create {
+            policies,
+            ttl,
+            bound_cidrs,
+            batch,
+        } => {
             let mut body = serde_json::Map::new();
             if let Some(p) = policies {
                 body.insert("policies".to_owned(), serde_json::json!(p));
@@ -1225,57 +3487,248 @@ async fn cmd_token(client: &Client, action: TokenCommands) -> Result<()> {
             if let Some(t) = ttl {
                 body.insert("ttl".to_owned(), serde_json::json!(t));
             }
+            if let Some(cidrs) = bound_cidrs {
+                body.insert("bound_cidrs".to_owned(), serde_json::json!(cidrs));
+            }
+            if batch {
+                body.insert("type".to_owned(), serde_json::json!("batch"));
+            }
             let resp = client
                 .post("/v1/auth/token/create", &Value::Object(body))
                 .await?;
-            println!();
+            outln!();
             print_token_response(&resp);
         }
         TokenCommands::Lookup => {
             let resp = client
                 .post("/v1/auth/token/lookup-self", &serde_json::json!({}))
                 .await?;
-            println!();
+            outln!();
+            print_token_lookup(&resp);
+        }
+        TokenCommands::Accessors => {
+            let resp = client.get("/v1/auth/token/accessors").await?;
+            outln!();
+            header("🪙", "Token Accessors");
+            let keys = resp.get("keys").and_then(Value::as_array);
+            match keys.filter(|keys| !keys.is_empty()) {
+                Some(keys) => {
+                    for accessor in keys.iter().filter_map(Value::as_str) {
+                        outln!("  {CYAN}├─{RESET} {accessor}");
+                    }
+                }
+                None => outln!("  {DIM}(no tokens){RESET}"),
+            }
+            outln!();
+        }
+        TokenCommands::LookupAccessor { accessor } => {
+            let resp = client
+                .post(
+                    "/v1/auth/token/lookup-accessor",
+                    &serde_json::json!({ "accessor": accessor }),
+                )
+                .await?;
+            outln!();
             print_token_lookup(&resp);
         }
+        TokenCommands::RevokeAccessor { accessor } => {
+            client
+                .post(
+                    "/v1/auth/token/revoke-accessor",
+                    &serde_json::json!({ "accessor": accessor }),
+                )
+                .await?;
+            outln!();
+            success(&format!(
+                "Token with accessor {BOLD}{accessor}{RESET} revoked, with its children."
+            ));
+            outln!();
+        }
     }
     Ok(())
 }
 
 // ── KV commands ──────────────────────────────────────────────────────
 
-async fn cmd_kv(client: &Client, action: KvCommands) -> Result<()> {
+#[allow(clippy::too_many_lines)]
+async fn cmd_kv(client: &Client, mount: &str, action: KvCommands) -> Result<()> {
     match action {
-        KvCommands::Put { path, data } => {
+        KvCommands::Put { path, data, cas } => {
             let map = parse_kv_pairs(&data)?;
-            let body = serde_json::json!({ "data": map });
+            let mut body = serde_json::json!({ "data": map });
+            if let Some(cas) = cas {
+                body["options"] = serde_json::json!({ "cas": cas });
+            }
             client
-                .post(&format!("/v1/secret/data/{path}"), &body)
+                .post(&format!("/v1/{mount}/data/{path}"), &body)
                 .await?;
-            println!();
+            outln!();
             success(&format!("Secret written to {BOLD}{path}{RESET}"));
-            println!();
+            outln!();
         }
         KvCommands::Get { path } => {
-            let resp = client.get(&format!("/v1/secret/data/{path}")).await?;
-            println!();
+            let resp = client.get(&format!("/v1/{mount}/data/{path}")).await?;
+            outln!();
             print_secret_response(&path, &resp);
         }
         KvCommands::Delete { path } => {
-            client.delete(&format!("/v1/secret/data/{path}")).await?;
-            println!();
+            client.delete(&format!("/v1/{mount}/data/{path}")).await?;
+            outln!();
             success(&format!("Secret at {BOLD}{path}{RESET} deleted."));
-            println!();
+            outln!();
+        }
+        KvCommands::Destroy { path, confirm } => {
+            confirm_destructive(
+                &format!("permanently destroy every version of {path}"),
+                &path,
+                confirm.as_deref(),
+            )?;
+            client
+                .delete(&format!("/v1/{mount}/metadata/{path}"))
+                .await?;
+            outln!();
+            success(&format!("Secret at {BOLD}{path}{RESET} destroyed."));
+            outln!();
         }
         KvCommands::List { path } => {
-            let resp = client.get(&format!("/v1/secret/list/{path}")).await?;
-            println!();
+            let resp = client.get(&format!("/v1/{mount}/list/{path}")).await?;
+            outln!();
             print_list_response(&path, &resp);
         }
+        KvCommands::Export {
+            prefix,
+            output,
+            yaml,
+        } => kv_tree::cmd_kv_export(client, mount, &prefix, output.as_deref(), yaml).await?,
+        KvCommands::Import {
+            file,
+            prefix,
+            overwrite,
+        } => kv_tree::cmd_kv_import(client, mount, &file, &prefix, overwrite).await?,
+        KvCommands::Copy {
+            from,
+            to,
+            overwrite,
+        } => kv_tree::cmd_kv_copy(client, mount, &from, &to, overwrite, false).await?,
+        KvCommands::Move {
+            from,
+            to,
+            overwrite,
+        } => kv_tree::cmd_kv_copy(client, mount, &from, &to, overwrite, true).await?,
+        KvCommands::Diff {
+            left,
+            right,
+            values,
+            left_env,
+            right_env,
+            exit_code,
+        } => {
+            kv_tree::cmd_kv_diff(
+                client,
+                mount,
+                &left,
+                &right,
+                left_env.as_deref(),
+                right_env.as_deref(),
+                values,
+                exit_code,
+            )
+            .await?;
+        }
+        KvCommands::Metadata { action } => cmd_kv_metadata(client, mount, action).await?,
+        KvCommands::Stats {
+            prefix,
+            unread,
+            sort,
+        } => {
+            let resp = client
+                .get(&format!(
+                    "/v1/sys/internal/counters/secrets?prefix={mount}/{prefix}"
+                ))
+                .await?;
+            outln!();
+            print_secret_stats(&prefix, &resp, unread, &sort);
+        }
     }
     Ok(())
 }
 
+async fn cmd_kv_metadata(client: &Client, mount: &str, action: KvMetadataCommands) -> Result<()> {
+    let (path, resp) = match action {
+        KvMetadataCommands::Get { path } => {
+            let resp = client.get(&format!("/v1/{mount}/metadata/{path}")).await?;
+            (path, resp)
+        }
+        KvMetadataCommands::Put {
+            path,
+            custom,
+            clear_custom,
+            max_versions,
+            cas_required,
+            delete_version_after,
+        } => {
+            let mut body = serde_json::json!({});
+            if clear_custom || !custom.is_empty() {
+                body["custom_metadata"] = serde_json::json!(parse_kv_pairs(&custom)?);
+            }
+            if let Some(max_versions) = max_versions {
+                body["max_versions"] = serde_json::json!(max_versions);
+            }
+            if let Some(cas_required) = cas_required {
+                body["cas_required"] = serde_json::json!(cas_required);
+            }
+            if let Some(after) = delete_version_after {
+                body["delete_version_after"] = serde_json::json!(after);
+            }
+            if body.as_object().is_some_and(serde_json::Map::is_empty) {
+                bail!(
+                    "nothing to update: pass --custom, --clear-custom, --max-versions, --cas-required, or --delete-version-after"
+                );
+            }
+            let resp = client
+                .post(&format!("/v1/{mount}/metadata/{path}"), &body)
+                .await?;
+            (path, resp)
+        }
+    };
+    outln!();
+    print_kv_metadata(&path, &resp);
+    Ok(())
+}
+
+fn print_kv_metadata(path: &str, resp: &Value) {
+    header("🏷", &format!("Metadata: {path}"));
+    let field = |name: &str| {
+        resp.get(name).map_or_else(String::new, |v| match v {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    };
+    kv_line("Current Version", &field("current_version"));
+    kv_line(
+        "Versions",
+        &format!("{} of {}", field("version_count"), field("max_versions")),
+    );
+    kv_line("CAS Required", &field("cas_required"));
+    let delete_after = resp
+        .get("delete_version_after_secs")
+        .and_then(Value::as_i64)
+        .unwrap_or(0);
+    if delete_after > 0 {
+        kv_line("Delete After", &format!("{delete_after}s"));
+    }
+    kv_line("Created", &field("created_at"));
+    kv_line("Updated", &field("updated_at"));
+    if let Some(custom) = resp.get("custom_metadata").and_then(Value::as_object) {
+        let mut entries: Vec<_> = custom.iter().collect();
+        entries.sort_by_key(|(k, _)| k.as_str());
+        for (key, value) in entries {
+            kv_line(key, value.as_str().unwrap_or_default());
+        }
+    }
+    outln!();
+}
+
 // ── Policy commands ──────────────────────────────────────────────────
 
 async fn cmd_policy(client: &Client, action: PolicyCommands) -> Result<()> {
@@ -1288,25 +3741,26 @@ async fn cmd_policy(client: &Client, action: PolicyCommands) -> Result<()> {
             client
                 .post(&format!("/v1/sys/policies/{name}"), &body)
                 .await?;
-            println!();
+            outln!();
             success(&format!("Policy {BOLD}{name}{RESET} written."));
-            println!();
+            outln!();
         }
         PolicyCommands::Read { name } => {
             let resp = client.get(&format!("/v1/sys/policies/{name}")).await?;
-            println!();
+            outln!();
             print_policy_detail(&name, &resp);
         }
         PolicyCommands::List => {
             let resp = client.get("/v1/sys/policies").await?;
-            println!();
+            outln!();
             print_policy_list(&resp);
         }
-        PolicyCommands::Delete { name } => {
+        PolicyCommands::Delete { name, confirm } => {
+            confirm_destructive(&format!("delete policy {name}"), &name, confirm.as_deref())?;
             client.delete(&format!("/v1/sys/policies/{name}")).await?;
-            println!();
+            outln!();
             success(&format!("Policy {BOLD}{name}{RESET} deleted."));
-            println!();
+            outln!();
         }
     }
     Ok(())
@@ -1320,51 +3774,117 @@ async fn cmd_transit(client: &Client, action: TransitCommands) -> Result<()> {
             client
                 .post_no_body(&format!("/v1/transit/keys/{name}"))
                 .await?;
-            println!();
+            outln!();
             success(&format!("Transit key {BOLD}{name}{RESET} created."));
-            println!();
+            outln!();
         }
         TransitCommands::RotateKey { name } => {
             let resp = client
                 .post_no_body(&format!("/v1/transit/keys/{name}/rotate"))
                 .await?;
             let ver = resp.get("new_version").and_then(Value::as_u64).unwrap_or(0);
-            println!();
+            outln!();
             success(&format!(
                 "Key {BOLD}{name}{RESET} rotated to {CYAN}v{ver}{RESET}."
             ));
-            println!();
+            outln!();
         }
         TransitCommands::Encrypt { key, plaintext } => {
             let body = serde_json::json!({ "plaintext": plaintext });
             let resp = client
                 .post(&format!("/v1/transit/encrypt/{key}"), &body)
                 .await?;
-            println!();
+            outln!();
             print_encrypt_response(&resp);
         }
         TransitCommands::Decrypt { key, ciphertext } => {
             let body = serde_json::json!({ "ciphertext": ciphertext });
             let resp = client
-                .post(&format!("/v1/transit/decrypt/{key}"), &body)
+                .post(&format!("/v1/transit/decrypt/{key}"), &body)
+                .await?;
+            outln!();
+            print_decrypt_response(&resp);
+        }
+        TransitCommands::Rewrap {
+            key,
+            ciphertext,
+            key_version,
+        } => {
+            let body = serde_json::json!({ "ciphertext": ciphertext, "key_version": key_version });
+            let resp = client
+                .post(&format!("/v1/transit/rewrap/{key}"), &body)
+                .await?;
+            outln!();
+            print_encrypt_response(&resp);
+        }
+        TransitCommands::Config {
+            name,
+            min_decryption_version,
+            min_encryption_version,
+        } => {
+            let body = serde_json::json!({
+                "min_decryption_version": min_decryption_version,
+                "min_encryption_version": min_encryption_version,
+            });
+            let resp = client
+                .post(&format!("/v1/transit/keys/{name}/config"), &body)
                 .await?;
-            println!();
-            print_decrypt_response(&resp);
+            outln!();
+            print_transit_key_info(&resp);
         }
         TransitCommands::ListKeys => {
             let resp = client.get("/v1/transit/keys").await?;
-            println!();
+            outln!();
             print_transit_key_list(&resp);
         }
         TransitCommands::KeyInfo { name } => {
             let resp = client.get(&format!("/v1/transit/keys/{name}")).await?;
-            println!();
+            outln!();
             print_transit_key_info(&resp);
         }
+        action @ (TransitCommands::Tokenize { .. }
+        | TransitCommands::Detokenize { .. }
+        | TransitCommands::LookupToken { .. }) => cmd_transit_token(client, action).await?,
     }
     Ok(())
 }
 
+/// Tokenization subcommands of `zvault transit`.
+async fn cmd_transit_token(client: &Client, action: TransitCommands) -> Result<()> {
+    let (path, body) = match action {
+        TransitCommands::Tokenize {
+            key,
+            plaintext,
+            convergent,
+            metadata,
+        } => (
+            format!("/v1/transit/tokenize/{key}"),
+            serde_json::json!({
+                "plaintext": plaintext,
+                "convergent": convergent,
+                "metadata": parse_kv_pairs(&metadata)?,
+            }),
+        ),
+        TransitCommands::Detokenize { key, token } => (
+            format!("/v1/transit/detokenize/{key}"),
+            serde_json::json!({ "token": token }),
+        ),
+        TransitCommands::LookupToken {
+            key,
+            token,
+            plaintext,
+        } => (
+            format!("/v1/transit/lookup/{key}"),
+            serde_json::json!({ "token": token, "plaintext": plaintext }),
+        ),
+        _ => return Ok(()),
+    };
+    let resp = client.post(&path, &body).await?;
+    outln!();
+    print_token(&resp);
+    Ok(())
+}
+
 // ── Database commands ────────────────────────────────────────────────
 
 async fn cmd_database(client: &Client, action: DatabaseCommands) -> Result<()> {
@@ -1373,19 +3893,23 @@ async fn cmd_database(client: &Client, action: DatabaseCommands) -> Result<()> {
             name,
             plugin,
             connection_url,
+            username,
+            password,
         } => {
             let body = serde_json::json!({
                 "plugin": plugin,
                 "connection_url": connection_url,
+                "username": username,
+                "password": password,
             });
             client
                 .post(&format!("/v1/database/config/{name}"), &body)
                 .await?;
-            println!();
+            outln!();
             success(&format!(
                 "Database connection {BOLD}{name}{RESET} configured."
             ));
-            println!();
+            outln!();
         }
         DatabaseCommands::CreateRole {
             name,
@@ -1399,64 +3923,132 @@ async fn cmd_database(client: &Client, action: DatabaseCommands) -> Result<()> {
             client
                 .post(&format!("/v1/database/roles/{name}"), &body)
                 .await?;
-            println!();
+            outln!();
             success(&format!("Database role {BOLD}{name}{RESET} created."));
-            println!();
+            outln!();
         }
         DatabaseCommands::Creds { name } => {
             let resp = client.get(&format!("/v1/database/creds/{name}")).await?;
-            println!();
-            header("🗄️", &format!("Database Credentials: {name}"));
-            if let Some(u) = resp.get("username").and_then(Value::as_str) {
-                kv_line("Username", u);
-            }
-            if let Some(p) = resp.get("password").and_then(Value::as_str) {
-                kv_line("Password", p);
-            }
-            if let Some(lease) = resp.get("lease_id").and_then(Value::as_str) {
-                kv_line("Lease ID", lease);
-            }
-            if let Some(dur) = resp.get("lease_duration").and_then(Value::as_i64) {
-                kv_line("Lease Duration", &format_duration(dur));
-            }
-            println!();
+            print_database_creds(&name, &resp);
         }
         DatabaseCommands::ListRoles => {
             let resp = client.get("/v1/database/roles").await?;
-            println!();
-            header("🗄️", "Database Roles");
-            if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
-                if keys.is_empty() {
-                    println!("  {DIM}(no roles){RESET}");
-                } else {
-                    for k in keys {
-                        if let Some(name) = k.as_str() {
-                            println!("  {CYAN}├─{RESET} {name}");
-                        }
-                    }
-                }
-            }
-            println!();
+            print_database_keys("Database Roles", "no roles", &resp);
         }
         DatabaseCommands::ListConfigs => {
             let resp = client.get("/v1/database/config").await?;
-            println!();
-            header("🗄️", "Database Connections");
-            if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
-                if keys.is_empty() {
-                    println!("  {DIM}(no connections){RESET}");
-                } else {
-                    for k in keys {
-                        if let Some(name) = k.as_str() {
-                            println!("  {CYAN}├─{RESET} {name}");
-                        }
-                    }
+            print_database_keys("Database Connections", "no connections", &resp);
+        }
+        DatabaseCommands::RotateRoot { name } => {
+            let resp = client
+                .post(
+                    &format!("/v1/database/rotate-root/{name}"),
+                    &serde_json::json!({}),
+                )
+                .await?;
+            print_root_rotation(&name, &resp);
+        }
+        DatabaseCommands::CreateStaticRole {
+            name,
+            db_name,
+            username,
+        } => {
+            let body = serde_json::json!({
+                "db_name": db_name,
+                "username": username,
+            });
+            client
+                .post(&format!("/v1/database/static-roles/{name}"), &body)
+                .await?;
+            outln!();
+            success(&format!(
+                "Static role {BOLD}{name}{RESET} created and password rotated."
+            ));
+            outln!();
+        }
+        DatabaseCommands::StaticCreds { name } => {
+            let resp = client
+                .get(&format!("/v1/database/static-creds/{name}"))
+                .await?;
+            print_static_creds(&name, &resp);
+        }
+        DatabaseCommands::RotateRole { name } => {
+            client
+                .post(
+                    &format!("/v1/database/rotate-role/{name}"),
+                    &serde_json::json!({}),
+                )
+                .await?;
+            outln!();
+            success(&format!("Static role {BOLD}{name}{RESET} rotated."));
+            outln!();
+        }
+    }
+    Ok(())
+}
+
+fn print_database_creds(name: &str, resp: &Value) {
+    outln!();
+    header("🗄️", &format!("Database Credentials: {name}"));
+    if let Some(u) = resp.get("username").and_then(Value::as_str) {
+        kv_line("Username", u);
+    }
+    if let Some(p) = resp.get("password").and_then(Value::as_str) {
+        kv_line("Password", p);
+    }
+    if let Some(lease) = resp.get("lease_id").and_then(Value::as_str) {
+        kv_line("Lease ID", lease);
+    }
+    if let Some(dur) = resp.get("lease_duration").and_then(Value::as_i64) {
+        kv_line("Lease Duration", &format_duration(dur));
+    }
+    outln!();
+}
+
+fn print_static_creds(name: &str, resp: &Value) {
+    outln!();
+    header("🗄️", &format!("Static Credentials: {name}"));
+    if let Some(u) = resp.get("username").and_then(Value::as_str) {
+        kv_line("Username", u);
+    }
+    if let Some(p) = resp.get("password").and_then(Value::as_str) {
+        kv_line("Password", p);
+    }
+    if let Some(t) = resp.get("last_rotated").and_then(Value::as_str) {
+        kv_line("Last Rotated", t);
+    }
+    outln!();
+}
+
+fn print_root_rotation(name: &str, resp: &Value) {
+    outln!();
+    success(&format!(
+        "Root credentials for {BOLD}{name}{RESET} rotated."
+    ));
+    if let Some(u) = resp.get("username").and_then(Value::as_str) {
+        kv_line("Username", u);
+    }
+    if let Some(t) = resp.get("rotated_at").and_then(Value::as_str) {
+        kv_line("Rotated At", t);
+    }
+    outln!();
+}
+
+fn print_database_keys(title: &str, empty: &str, resp: &Value) {
+    outln!();
+    header("🗄️", title);
+    if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
+        if keys.is_empty() {
+            outln!("  {DIM}({empty}){RESET}");
+        } else {
+            for k in keys {
+                if let Some(name) = k.as_str() {
+                    outln!("  {CYAN}├─{RESET} {name}");
                 }
             }
-            println!();
         }
     }
-    Ok(())
+    outln!();
 }
 
 // ── PKI commands ─────────────────────────────────────────────────────
@@ -1467,7 +4059,7 @@ async fn cmd_pki_generate_root(client: &Client, common_name: &str, ttl_hours: u6
         "ttl_hours": ttl_hours,
     });
     let resp = client.post("/v1/pki/root/generate", &body).await?;
-    println!();
+    outln!();
     header("🏛️", "Root CA Generated");
     if let Some(cn) = resp.get("common_name").and_then(Value::as_str) {
         kv_line("Common Name", cn);
@@ -1483,22 +4075,13 @@ async fn cmd_pki_generate_root(client: &Client, common_name: &str, ttl_hours: u6
         };
         kv_line("Certificate", &short);
     }
-    println!();
+    outln!();
     Ok(())
 }
 
-async fn cmd_pki_issue(
-    client: &Client,
-    role: &str,
-    common_name: &str,
-    ttl_hours: Option<u64>,
-) -> Result<()> {
-    let mut body = serde_json::json!({ "common_name": common_name });
-    if let Some(ttl) = ttl_hours {
-        body["ttl_hours"] = serde_json::json!(ttl);
-    }
-    let resp = client.post(&format!("/v1/pki/issue/{role}"), &body).await?;
-    println!();
+async fn cmd_pki_issue(client: &Client, role: &str, body: &Value) -> Result<()> {
+    let resp = client.post(&format!("/v1/pki/issue/{role}"), body).await?;
+    outln!();
     header("📜", "Certificate Issued");
     if let Some(serial) = resp.get("serial_number").and_then(Value::as_str) {
         kv_line("Serial", serial);
@@ -1517,7 +4100,7 @@ async fn cmd_pki_issue(
     if resp.get("private_key").and_then(Value::as_str).is_some() {
         kv_line("Private Key", "(included in response)");
     }
-    println!();
+    outln!();
     Ok(())
 }
 
@@ -1531,134 +4114,584 @@ async fn cmd_pki(client: &Client, action: PkiCommands) -> Result<()> {
             role,
             common_name,
             ttl_hours,
-        } => cmd_pki_issue(client, &role, &common_name, ttl_hours).await?,
+            alt_names,
+            ip_sans,
+            uri_sans,
+            key_type,
+            key_bits,
+            not_before,
+        } => {
+            // Unset options go out as null, which the server treats as absent.
+            let body = serde_json::json!({
+                "common_name": common_name,
+                "ttl_hours": ttl_hours,
+                "alt_names": alt_names,
+                "ip_sans": ip_sans,
+                "uri_sans": uri_sans,
+                "key_type": key_type,
+                "key_bits": key_bits,
+                "not_before": not_before,
+            });
+            cmd_pki_issue(client, &role, &body).await?;
+        }
         PkiCommands::CreateRole {
             name,
             allowed_domains,
             allow_subdomains,
+            allow_wildcards,
+            allow_ip_sans,
+            allowed_uri_sans,
+            key_type,
+            key_bits,
+            not_before,
         } => {
-            let body = serde_json::json!({
+            let mut body = serde_json::json!({
                 "allowed_domains": allowed_domains,
                 "allow_subdomains": allow_subdomains,
+                "allow_wildcard_certificates": allow_wildcards,
+                "allow_ip_sans": allow_ip_sans,
+                "allowed_uri_sans": allowed_uri_sans,
             });
+            if let Some(key_type) = key_type {
+                body["key_type"] = serde_json::json!(key_type);
+            }
+            if let Some(bits) = key_bits {
+                body["key_bits"] = serde_json::json!(bits);
+            }
+            if let Some(secs) = not_before {
+                body["not_before_duration_secs"] = serde_json::json!(secs);
+            }
             client.post(&format!("/v1/pki/roles/{name}"), &body).await?;
-            println!();
+            outln!();
             success(&format!("PKI role {BOLD}{name}{RESET} created."));
-            println!();
+            outln!();
+        }
+        PkiCommands::ListRoles => cmd_pki_list_roles(client).await?,
+        PkiCommands::ListCerts {
+            common_name,
+            expires_within,
+            limit,
+            cursor,
+        } => {
+            let params = [
+                ("common_name", common_name),
+                ("expires_within", expires_within),
+                ("limit", limit.map(|l| l.to_string())),
+                ("cursor", cursor),
+            ];
+            cmd_pki_list_certs(client, &params).await?;
+        }
+        PkiCommands::Tidy {
+            safety_buffer,
+            skip_cert_store,
+            skip_revoked,
+        } => {
+            let body = serde_json::json!({
+                "safety_buffer": safety_buffer,
+                "tidy_cert_store": !skip_cert_store,
+                "tidy_revoked_certs": !skip_revoked,
+            });
+            cmd_pki_tidy(client, &body).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_pki_tidy(client: &Client, body: &Value) -> Result<()> {
+    let resp = client.post("/v1/pki/tidy", body).await?;
+    outln!();
+    header("🧹", "PKI Tidy");
+    let count = |field: &str| resp.get(field).and_then(Value::as_u64).unwrap_or(0);
+    kv_line("Certificates deleted", &count("certs_deleted").to_string());
+    kv_line("Revocations deleted", &count("revoked_deleted").to_string());
+    outln!();
+    Ok(())
+}
+
+async fn cmd_pki_list_roles(client: &Client) -> Result<()> {
+    let resp = client.get("/v1/pki/roles").await?;
+    outln!();
+    header("🏛️", "PKI Roles");
+    if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
+        if keys.is_empty() {
+            outln!("  {DIM}(no roles){RESET}");
+        } else {
+            for k in keys {
+                if let Some(name) = k.as_str() {
+                    outln!("  {CYAN}├─{RESET} {name}");
+                }
+            }
+        }
+    }
+    outln!();
+    Ok(())
+}
+
+async fn cmd_pki_list_certs(client: &Client, params: &[(&str, Option<String>)]) -> Result<()> {
+    let params = params
+        .iter()
+        .filter_map(|(name, value)| value.as_ref().map(|v| (*name, v.as_str())));
+    let url = reqwest::Url::parse_with_params("http://localhost/v1/pki/certs", params)?;
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_owned(),
+    };
+    let resp = client.get(&path).await?;
+    outln!();
+    header("📜", "Issued Certificates");
+    let certs = resp
+        .get("certificates")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if certs.is_empty() {
+        outln!("  {DIM}(no certificates){RESET}");
+    }
+    for cert in certs {
+        let field = |name: &str| cert.get(name).and_then(Value::as_str).unwrap_or("-");
+        let revoked = if cert.get("revoked").and_then(Value::as_bool) == Some(true) {
+            format!(" {RED}revoked{RESET}")
+        } else {
+            String::new()
+        };
+        outln!(
+            "  {CYAN}├─{RESET} {}  {}  {DIM}expires {}{RESET}{revoked}",
+            field("serial_number"),
+            field("common_name"),
+            field("expiration"),
+        );
+    }
+    if let Some(next) = resp.get("next_cursor").and_then(Value::as_str) {
+        outln!();
+        outln!("  {DIM}More certificates: --cursor {next}{RESET}");
+    }
+    outln!();
+    Ok(())
+}
+
+// ── AppRole commands ─────────────────────────────────────────────────
+
+async fn cmd_approle(client: &Client, action: AppRoleCommands) -> Result<()> {
+    match action {
+        AppRoleCommands::CreateRole {
+            name,
+            policies,
+            bound_cidrs,
+        } => {
+            let body = serde_json::json!({ "policies": policies, "bound_cidrs": bound_cidrs });
+            let resp = client
+                .post(&format!("/v1/auth/approle/role/{name}"), &body)
+                .await?;
+            outln!();
+            header("🤖", &format!("AppRole: {name}"));
+            if let Some(role_id) = resp.get("role_id").and_then(Value::as_str) {
+                kv_line("Role ID", role_id);
+            }
+            success("Role created.");
+            outln!();
+        }
+        AppRoleCommands::RoleId { name } => {
+            let resp = client
+                .get(&format!("/v1/auth/approle/role/{name}/role-id"))
+                .await?;
+            outln!();
+            header("🤖", &format!("AppRole: {name}"));
+            if let Some(role_id) = resp.get("role_id").and_then(Value::as_str) {
+                kv_line("Role ID", role_id);
+            }
+            outln!();
+        }
+        AppRoleCommands::SecretId { name, wrap_ttl } => {
+            let path = format!("/v1/auth/approle/role/{name}/secret-id");
+            let body = serde_json::json!({});
+            if let Some(ttl) = wrap_ttl {
+                let resp = client.post_wrapped(&path, &body, &ttl).await?;
+                outln!();
+                header("🤖", &format!("AppRole Secret ID: {name} (wrapped)"));
+                print_wrap_info(&resp);
+                outln!();
+                return Ok(());
+            }
+            let resp = client.post(&path, &body).await?;
+            outln!();
+            header("🤖", &format!("AppRole Secret ID: {name}"));
+            if let Some(secret_id) = resp.get("secret_id").and_then(Value::as_str) {
+                outln!();
+                outln!("  {DIM}Secret ID:{RESET}  {GREEN}{BOLD}{secret_id}{RESET}");
+                outln!();
+                outln!("  {YELLOW}⚠  Store this securely. It will NOT be shown again.{RESET}");
+            }
+            outln!();
+        }
+        AppRoleCommands::Login { role_id, secret_id } => {
+            let body = serde_json::json!({
+                "role_id": role_id,
+                "secret_id": secret_id,
+            });
+            let resp = client.post_no_auth("/v1/auth/approle/login", &body).await?;
+            outln!();
+            print_token_response(&resp);
         }
-        PkiCommands::ListRoles => {
-            let resp = client.get("/v1/pki/roles").await?;
-            println!();
-            header("🏛️", "PKI Roles");
+        AppRoleCommands::ListRoles => {
+            let resp = client.get("/v1/auth/approle/role").await?;
+            outln!();
+            header("🤖", "AppRole Roles");
             if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
                 if keys.is_empty() {
-                    println!("  {DIM}(no roles){RESET}");
+                    outln!("  {DIM}(no roles){RESET}");
                 } else {
                     for k in keys {
                         if let Some(name) = k.as_str() {
-                            println!("  {CYAN}├─{RESET} {name}");
+                            outln!("  {CYAN}├─{RESET} {name}");
                         }
                     }
                 }
             }
-            println!();
+            outln!();
+        }
+    }
+    Ok(())
+}
+
+// ── Response wrapping commands ───────────────────────────────────────
+
+async fn cmd_wrapping(client: &Client, action: WrappingCommands) -> Result<()> {
+    match action {
+        WrappingCommands::Unwrap { token } => {
+            let resp = client
+                .with_token(token)
+                .post_no_body("/v1/sys/wrapping/unwrap")
+                .await?;
+            print_json(&resp);
+        }
+        WrappingCommands::Lookup { token } => {
+            let resp = client
+                .with_token(token)
+                .post_no_body("/v1/sys/wrapping/lookup")
+                .await?;
+            outln!();
+            header("🎁", "Wrapping Token");
+            for (label, field) in [
+                ("Creation Path", "creation_path"),
+                ("Creation Time", "creation_time"),
+            ] {
+                if let Some(v) = resp.get(field).and_then(Value::as_str) {
+                    kv_line(label, v);
+                }
+            }
+            if let Some(ttl) = resp.get("creation_ttl").and_then(Value::as_i64) {
+                kv_line("Creation TTL", &format!("{ttl}s"));
+            }
+            outln!();
+        }
+        WrappingCommands::Rewrap { token } => {
+            let resp = client
+                .with_token(token)
+                .post_no_body("/v1/sys/wrapping/rewrap")
+                .await?;
+            outln!();
+            header("🎁", "Rewrapped");
+            print_wrap_info(&resp);
+            outln!();
+        }
+    }
+    Ok(())
+}
+
+fn print_wrap_info(resp: &Value) {
+    let Some(info) = resp.get("wrap_info") else {
+        return;
+    };
+    if let Some(token) = info.get("token").and_then(Value::as_str) {
+        outln!();
+        outln!("  {DIM}Wrapping Token:{RESET}  {GREEN}{BOLD}{token}{RESET}");
+        outln!();
+    }
+    if let Some(ttl) = info.get("ttl").and_then(Value::as_i64) {
+        kv_line("TTL", &format!("{ttl}s"));
+    }
+    if let Some(path) = info.get("creation_path").and_then(Value::as_str) {
+        kv_line("Creation Path", path);
+    }
+    outln!();
+    outln!("  {YELLOW}⚠  Single use: unwrap with `zvault wrapping unwrap <token>`.{RESET}");
+}
+
+// ── Cubbyhole commands ───────────────────────────────────────────────
+
+async fn cmd_cubbyhole(client: &Client, action: CubbyholeCommands) -> Result<()> {
+    match action {
+        CubbyholeCommands::Put { path, data } => {
+            let body = serde_json::json!(parse_kv_pairs(&data)?);
+            client
+                .post(&format!("/v1/cubbyhole/data/{path}"), &body)
+                .await?;
+            outln!();
+            success(&format!("Cubbyhole entry written to {BOLD}{path}{RESET}"));
+            outln!();
+        }
+        CubbyholeCommands::Get { path } => {
+            let resp = client.get(&format!("/v1/cubbyhole/data/{path}")).await?;
+            outln!();
+            print_secret_response(&path, &resp);
+        }
+        CubbyholeCommands::Delete { path } => {
+            client.delete(&format!("/v1/cubbyhole/data/{path}")).await?;
+            outln!();
+            success(&format!("Cubbyhole entry at {BOLD}{path}{RESET} deleted."));
+            outln!();
+        }
+        CubbyholeCommands::List { path } => {
+            let url = if path.is_empty() {
+                "/v1/cubbyhole/list".to_owned()
+            } else {
+                format!("/v1/cubbyhole/list/{path}")
+            };
+            let resp = client.get(&url).await?;
+            outln!();
+            print_list_response(&path, &resp);
+        }
+    }
+    Ok(())
+}
+
+// ── JWT commands ─────────────────────────────────────────────────────
+
+async fn cmd_jwt(client: &Client, action: JwtCommands) -> Result<()> {
+    match action {
+        JwtCommands::Config {
+            jwks_url,
+            pubkey_file,
+            bound_issuer,
+        } => {
+            let pubkeys = pubkey_file
+                .iter()
+                .map(|f| std::fs::read_to_string(f).with_context(|| format!("failed to read {f}")))
+                .collect::<Result<Vec<_>>>()?;
+            let body = serde_json::json!({
+                "jwks_url": jwks_url,
+                "jwt_validation_pubkeys": pubkeys,
+                "bound_issuer": bound_issuer,
+            });
+            client.post("/v1/auth/jwt/config", &body).await?;
+            outln!();
+            success("JWT auth configured.");
+            outln!();
         }
-        PkiCommands::ListCerts => {
-            let resp = client.get("/v1/pki/certs").await?;
-            println!();
-            header("📜", "Issued Certificates");
+        JwtCommands::CreateRole {
+            name,
+            policies,
+            bound_audiences,
+            bound_subject,
+            bound_claims,
+            glob,
+            user_claim,
+            groups_claim,
+        } => {
+            let body = serde_json::json!({
+                "policies": policies,
+                "bound_audiences": bound_audiences,
+                "bound_subject": bound_subject,
+                "bound_claims": parse_bound_claims(&bound_claims)?,
+                "bound_claims_type": if glob { "glob" } else { "string" },
+                "user_claim": user_claim,
+                "groups_claim": groups_claim,
+            });
+            client
+                .post(&format!("/v1/auth/jwt/role/{name}"), &body)
+                .await?;
+            outln!();
+            header("🎫", &format!("JWT Role: {name}"));
+            success("Role created.");
+            outln!();
+        }
+        JwtCommands::ReadRole { name } => {
+            let resp = client.get(&format!("/v1/auth/jwt/role/{name}")).await?;
+            outln!();
+            header("🎫", &format!("JWT Role: {name}"));
+            print_jwt_role(&resp);
+            outln!();
+        }
+        JwtCommands::DeleteRole { name } => {
+            client.delete(&format!("/v1/auth/jwt/role/{name}")).await?;
+            outln!();
+            success(&format!("JWT role {name} deleted."));
+            outln!();
+        }
+        JwtCommands::ListRoles => {
+            let resp = client.get("/v1/auth/jwt/role").await?;
+            outln!();
+            header("🎫", "JWT Roles");
             if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
                 if keys.is_empty() {
-                    println!("  {DIM}(no certificates){RESET}");
+                    outln!("  {DIM}(no roles){RESET}");
                 } else {
                     for k in keys {
-                        if let Some(serial) = k.as_str() {
-                            println!("  {CYAN}├─{RESET} {serial}");
+                        if let Some(name) = k.as_str() {
+                            outln!("  {CYAN}├─{RESET} {name}");
                         }
                     }
                 }
             }
-            println!();
+            outln!();
+        }
+        JwtCommands::Login { role, jwt } => {
+            let body = serde_json::json!({ "role": role, "jwt": jwt });
+            let resp = client.post_no_auth("/v1/auth/jwt/login", &body).await?;
+            outln!();
+            print_token_response(&resp);
         }
     }
     Ok(())
 }
 
-// ── AppRole commands ─────────────────────────────────────────────────
+/// Group repeated `--bound-claim claim=value` flags by claim.
+fn parse_bound_claims(pairs: &[String]) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut claims: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for pair in pairs {
+        let (claim, value) = pair
+            .split_once('=')
+            .with_context(|| format!("invalid --bound-claim '{pair}', expected claim=value"))?;
+        claims
+            .entry(claim.to_owned())
+            .or_default()
+            .push(value.to_owned());
+    }
+    Ok(claims)
+}
 
-async fn cmd_approle(client: &Client, action: AppRoleCommands) -> Result<()> {
+fn print_jwt_role(resp: &Value) {
+    let list = |field: &str| {
+        resp.get(field)
+            .and_then(Value::as_array)
+            .map(|v| {
+                v.iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default()
+    };
+    kv_line("Policies", &list("policies"));
+    kv_line("Audiences", &list("bound_audiences"));
+    if let Some(sub) = resp.get("bound_subject").and_then(Value::as_str) {
+        kv_line("Subject", sub);
+    }
+    if let Some(claims) = resp.get("bound_claims").and_then(Value::as_object) {
+        for (claim, values) in claims {
+            kv_line(&format!("Claim {claim}"), &values.to_string());
+        }
+    }
+    if let Some(user_claim) = resp.get("user_claim").and_then(Value::as_str) {
+        kv_line("User claim", user_claim);
+    }
+}
+
+// ── Cert commands ────────────────────────────────────────────────────
+
+async fn cmd_cert(client: &Client, action: CertCommands) -> Result<()> {
     match action {
-        AppRoleCommands::CreateRole { name, policies } => {
-            let body = serde_json::json!({ "policies": policies });
-            let resp = client
-                .post(&format!("/v1/auth/approle/role/{name}"), &body)
+        CertCommands::CreateRole {
+            name,
+            ca_file,
+            policies,
+            allowed_common_names,
+            allowed_dns_sans,
+            allowed_email_sans,
+            allowed_uri_sans,
+            allowed_organizational_units,
+        } => {
+            let certificate = std::fs::read_to_string(&ca_file)
+                .with_context(|| format!("failed to read {}", ca_file.display()))?;
+            let body = serde_json::json!({
+                "certificate": certificate,
+                "policies": policies,
+                "allowed_common_names": allowed_common_names,
+                "allowed_dns_sans": allowed_dns_sans,
+                "allowed_email_sans": allowed_email_sans,
+                "allowed_uri_sans": allowed_uri_sans,
+                "allowed_organizational_units": allowed_organizational_units,
+            });
+            client
+                .post(&format!("/v1/auth/cert/role/{name}"), &body)
                 .await?;
-            println!();
-            header("🤖", &format!("AppRole: {name}"));
-            if let Some(role_id) = resp.get("role_id").and_then(Value::as_str) {
-                kv_line("Role ID", role_id);
-            }
+            outln!();
+            header("🪪", &format!("Cert Role: {name}"));
             success("Role created.");
-            println!();
-        }
-        AppRoleCommands::RoleId { name } => {
-            let resp = client
-                .get(&format!("/v1/auth/approle/role/{name}/role-id"))
-                .await?;
-            println!();
-            header("🤖", &format!("AppRole: {name}"));
-            if let Some(role_id) = resp.get("role_id").and_then(Value::as_str) {
-                kv_line("Role ID", role_id);
-            }
-            println!();
+            outln!();
         }
-        AppRoleCommands::SecretId { name } => {
-            let resp = client
-                .post(
-                    &format!("/v1/auth/approle/role/{name}/secret-id"),
-                    &serde_json::json!({}),
-                )
-                .await?;
-            println!();
-            header("🤖", &format!("AppRole Secret ID: {name}"));
-            if let Some(secret_id) = resp.get("secret_id").and_then(Value::as_str) {
-                println!();
-                println!("  {DIM}Secret ID:{RESET}  {GREEN}{BOLD}{secret_id}{RESET}");
-                println!();
-                println!("  {YELLOW}⚠  Store this securely. It will NOT be shown again.{RESET}");
-            }
-            println!();
+        CertCommands::ReadRole { name } => {
+            let resp = client.get(&format!("/v1/auth/cert/role/{name}")).await?;
+            outln!();
+            header("🪪", &format!("Cert Role: {name}"));
+            print_cert_role(&resp);
+            outln!();
         }
-        AppRoleCommands::Login { role_id, secret_id } => {
-            let body = serde_json::json!({
-                "role_id": role_id,
-                "secret_id": secret_id,
-            });
-            let resp = client.post_no_auth("/v1/auth/approle/login", &body).await?;
-            println!();
-            print_token_response(&resp);
+        CertCommands::DeleteRole { name } => {
+            client.delete(&format!("/v1/auth/cert/role/{name}")).await?;
+            outln!();
+            success(&format!("Cert role {name} deleted."));
+            outln!();
         }
-        AppRoleCommands::ListRoles => {
-            let resp = client.get("/v1/auth/approle/role").await?;
-            println!();
-            header("🤖", "AppRole Roles");
+        CertCommands::ListRoles => {
+            let resp = client.get("/v1/auth/cert/role").await?;
+            outln!();
+            header("🪪", "Cert Roles");
             if let Some(keys) = resp.get("keys").and_then(Value::as_array) {
                 if keys.is_empty() {
-                    println!("  {DIM}(no roles){RESET}");
+                    outln!("  {DIM}(no roles){RESET}");
                 } else {
                     for k in keys {
                         if let Some(name) = k.as_str() {
-                            println!("  {CYAN}├─{RESET} {name}");
+                            outln!("  {CYAN}├─{RESET} {name}");
                         }
                     }
                 }
             }
-            println!();
+            outln!();
+        }
+        CertCommands::Login { role } => {
+            let body = serde_json::json!({ "role": role });
+            let resp = client.post_no_auth("/v1/auth/cert/login", &body).await?;
+            outln!();
+            print_token_response(&resp);
         }
     }
     Ok(())
 }
 
+fn print_cert_role(resp: &Value) {
+    let list = |field: &str| {
+        resp.get(field)
+            .and_then(Value::as_array)
+            .map(|v| {
+                v.iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default()
+    };
+    kv_line("Policies", &list("policies"));
+    for (label, field) in [
+        ("Common names", "allowed_common_names"),
+        ("DNS SANs", "allowed_dns_sans"),
+        ("Email SANs", "allowed_email_sans"),
+        ("URI SANs", "allowed_uri_sans"),
+        ("OUs", "allowed_organizational_units"),
+    ] {
+        let values = list(field);
+        if !values.is_empty() {
+            kv_line(label, &values);
+        }
+    }
+    let cas = resp
+        .get("certificate")
+        .and_then(Value::as_str)
+        .map_or(0, |pem| pem.matches("-----BEGIN CERTIFICATE-----").count());
+    kv_line("CA certificates", &cas.to_string());
+}
+
 // ── Import command ────────────────────────────────────────────────────
 
 /// Parse a .env file into key-value pairs.
@@ -1746,12 +4779,12 @@ async fn cmd_import(
         None => detect_project_name()?,
     };
 
-    println!();
+    outln!();
     header("📦", &format!("Importing secrets from {file}"));
-    println!();
-    println!("  {DIM}Project:{RESET}  {BOLD}{project_name}{RESET}");
-    println!("  {DIM}Secrets:{RESET}  {BOLD}{}{RESET}", entries.len());
-    println!();
+    outln!();
+    outln!("  {DIM}Project:{RESET}  {BOLD}{project_name}{RESET}");
+    outln!("  {DIM}Secrets:{RESET}  {BOLD}{}{RESET}", entries.len());
+    outln!();
 
     // Store each secret in the vault under env/<project>/<key>.
     let mut imported = 0u32;
@@ -1766,17 +4799,17 @@ async fn cmd_import(
             .await
         {
             Ok(_) => {
-                println!("  {GREEN}✓{RESET} {key} → {DIM}zvault://env/{project_name}/{key}{RESET}");
+                outln!("  {GREEN}✓{RESET} {key} → {DIM}zvault://env/{project_name}/{key}{RESET}");
                 imported = imported.saturating_add(1);
             }
             Err(e) => {
-                println!("  {RED}✗{RESET} {key} — {RED}{e}{RESET}");
+                outln!("  {RED}✗{RESET} {key} — {RED}{e}{RESET}");
                 failed = failed.saturating_add(1);
             }
         }
     }
 
-    println!();
+    outln!();
 
     // Backup original .env file.
     if !no_backup {
@@ -1819,13 +4852,13 @@ async fn cmd_import(
         add_to_gitignore(file);
     }
 
-    println!();
+    outln!();
     if failed == 0 {
-        println!("  {GREEN}{BOLD}✓ Imported {imported} secrets into vault{RESET}");
+        outln!("  {GREEN}{BOLD}✓ Imported {imported} secrets into vault{RESET}");
     } else {
-        println!("  {YELLOW}{BOLD}⚠ Imported {imported} secrets, {failed} failed{RESET}");
+        outln!("  {YELLOW}{BOLD}⚠ Imported {imported} secrets, {failed} failed{RESET}");
     }
-    println!();
+    outln!();
 
     Ok(())
 }
@@ -1866,25 +4899,30 @@ fn add_to_gitignore(pattern: &str) {
 
 // ── Run command ──────────────────────────────────────────────────────
 
-/// Resolve a `zvault://` URI to its secret value from the vault.
-async fn resolve_zvault_uri(client: &Client, uri: &str) -> Result<String> {
-    let path = uri
-        .strip_prefix("zvault://")
-        .ok_or_else(|| anyhow::anyhow!("not a zvault:// URI: {uri}"))?;
-
-    let resp = client.get(&format!("/v1/secret/data/{path}")).await?;
-
+/// The secret payload of a KV read response.
+pub(crate) fn kv_payload(resp: &Value) -> &Value {
     // KV v2 response shape from the HTTP API:
     //   { data: { data: { data: { value: "..." } }, metadata: {...} } }
     //
     // Walk through nested `data` envelopes to reach the actual secret payload.
-    let mut node = &resp;
+    let mut node = resp;
     for _ in 0..4 {
         match node.get("data") {
             Some(inner) => node = inner,
             None => break,
         }
     }
+    node
+}
+
+/// Resolve a `zvault://` URI to its secret value from the vault.
+async fn resolve_zvault_uri(client: &Client, uri: &str) -> Result<String> {
+    let path = uri
+        .strip_prefix("zvault://")
+        .ok_or_else(|| anyhow::anyhow!("not a zvault:// URI: {uri}"))?;
+
+    let resp = client.get(&format!("/v1/secret/data/{path}")).await?;
+    let node = kv_payload(&resp);
 
     // Single-value secret stored by `zvault import` (key is "value").
     if let Some(val) = node.get("value").and_then(Value::as_str) {
@@ -1923,12 +4961,19 @@ fn find_env_file(explicit: Option<&str>) -> Result<String> {
 }
 
 /// Run a command with secrets injected from the vault.
-async fn cmd_run(client: &Client, env_file: Option<&str>, command: &[String]) -> Result<()> {
+async fn cmd_run(
+    client: &Client,
+    env_file: Option<&str>,
+    profile: Option<&profile::Profile>,
+    command: &[String],
+) -> Result<()> {
     if command.is_empty() {
         bail!("no command specified — usage: zvault run -- npm run dev");
     }
 
-    let env_path = find_env_file(env_file)?;
+    let profile_client = profile.map(|p| p.client(client)).transpose()?;
+    let client = profile_client.as_ref().unwrap_or(client);
+    let env_path = find_env_file(env_file.or(profile.and_then(|p| p.env_file.as_deref())))?;
     let content =
         std::fs::read_to_string(&env_path).with_context(|| format!("failed to read {env_path}"))?;
 
@@ -1942,20 +4987,28 @@ async fn cmd_run(client: &Client, env_file: Option<&str>, command: &[String]) ->
     let mut resolved = 0u32;
     let mut plain = 0u32;
 
-    println!();
+    outln!();
     header("🔑", &format!("Resolving secrets from {env_path}"));
-    println!();
+    if let Some(profile) = profile {
+        outln!(
+            "  {DIM}Profile:{RESET} {BOLD}{}{RESET} {DIM}({}){RESET}",
+            profile.name,
+            client.addr
+        );
+    }
+    outln!();
 
     for (key, value) in &entries {
         if value.starts_with("zvault://") {
-            match resolve_zvault_uri(client, value).await {
+            let uri = profile.map_or_else(|| value.clone(), |p| p.rewrite(value));
+            match resolve_zvault_uri(client, &uri).await {
                 Ok(secret) => {
-                    println!("  {GREEN}✓{RESET} {key} {DIM}← {value}{RESET}");
+                    outln!("  {GREEN}✓{RESET} {key} {DIM}← {uri}{RESET}");
                     env_vars.push((key.clone(), secret));
                     resolved = resolved.saturating_add(1);
                 }
                 Err(e) => {
-                    println!("  {RED}✗{RESET} {key} — {RED}{e}{RESET}");
+                    outln!("  {RED}✗{RESET} {key} — {RED}{e}{RESET}");
                     bail!("failed to resolve {key}: {e}");
                 }
             }
@@ -1966,16 +5019,16 @@ async fn cmd_run(client: &Client, env_file: Option<&str>, command: &[String]) ->
         }
     }
 
-    println!();
-    println!("  {DIM}Resolved {resolved} secrets, {plain} plain values{RESET}");
-    println!();
+    outln!();
+    outln!("  {DIM}Resolved {resolved} secrets, {plain} plain values{RESET}");
+    outln!();
 
     // Execute the child process with injected environment.
     let program = &command[0];
     let args = &command[1..];
 
-    println!("  {CYAN}{BOLD}▶{RESET} {BOLD}{}{RESET}", command.join(" "));
-    println!();
+    outln!("  {CYAN}{BOLD}▶{RESET} {BOLD}{}{RESET}", command.join(" "));
+    outln!();
 
     let status = std::process::Command::new(program)
         .args(args)
@@ -1994,25 +5047,25 @@ async fn cmd_run(client: &Client, env_file: Option<&str>, command: &[String]) ->
 // ── License commands ──────────────────────────────────────────────────
 
 async fn cmd_activate(key: &str) -> Result<()> {
-    println!();
+    outln!();
     header("🔑", "Activating License");
-    println!();
+    outln!();
 
     // Detect key type: Polar keys have no `.` separator, Ed25519 keys do.
     if license::is_polar_key(key) {
         let lic = license::validate_polar_key(key).await?;
 
-        println!(
+        outln!(
             "  {DIM}License ID:{RESET}   {BOLD}{}{RESET}",
             lic.payload.license_id
         );
-        println!(
+        outln!(
             "  {DIM}Tier:{RESET}         {GREEN}{BOLD}{}{RESET}",
             lic.payload.tier
         );
-        println!("  {DIM}Expires:{RESET}      {}", lic.payload.expires_at);
-        println!("  {DIM}Source:{RESET}        Polar.sh");
-        println!();
+        outln!("  {DIM}Expires:{RESET}      {}", lic.payload.expires_at);
+        outln!("  {DIM}Source:{RESET}        Polar.sh");
+        outln!();
         success("License activated via Polar. AI Mode features are now unlocked.");
     } else {
         // Ed25519-signed key — verify locally.
@@ -2021,105 +5074,128 @@ async fn cmd_activate(key: &str) -> Result<()> {
         // Save to ~/.zvault/license.key.
         let path = license::save_license(key)?;
 
-        println!(
+        outln!(
             "  {DIM}License ID:{RESET}   {BOLD}{}{RESET}",
             lic.payload.license_id
         );
-        println!(
+        outln!(
             "  {DIM}Tier:{RESET}         {GREEN}{BOLD}{}{RESET}",
             lic.payload.tier
         );
-        println!("  {DIM}Email:{RESET}        {}", lic.payload.email);
-        println!("  {DIM}Expires:{RESET}      {}", lic.payload.expires_at);
-        println!("  {DIM}Saved to:{RESET}     {}", path.display());
-        println!();
+        outln!("  {DIM}Email:{RESET}        {}", lic.payload.email);
+        outln!("  {DIM}Expires:{RESET}      {}", lic.payload.expires_at);
+        outln!("  {DIM}Saved to:{RESET}     {}", path.display());
+        outln!();
         success("License activated. AI Mode features are now unlocked.");
     }
 
-    println!();
+    outln!();
+    Ok(())
+}
+
+async fn cmd_activate_server(client: &Client, key: &str) -> Result<()> {
+    let resp = client
+        .post("/v1/sys/license", &serde_json::json!({ "key": key }))
+        .await?;
+
+    outln!();
+    header("🔑", "Activating Server License");
+    outln!();
+    let field = |name: &str| {
+        resp.get(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or("-")
+            .to_owned()
+    };
+    kv_line("License ID", &field("license_id"));
+    kv_line("Tier", &field("tier"));
+    kv_line("Expires", &field("expires_at"));
+    outln!();
+    success("License activated on the server.");
+    outln!();
     Ok(())
 }
 
 fn cmd_license() {
-    println!();
+    outln!();
 
     match license::load_license() {
         Ok(Some(lic)) => {
             header("🪪", "License Status");
-            println!();
-            println!(
+            outln!();
+            outln!(
                 "  {DIM}License ID:{RESET}   {BOLD}{}{RESET}",
                 lic.payload.license_id
             );
-            println!(
+            outln!(
                 "  {DIM}Tier:{RESET}         {GREEN}{BOLD}{}{RESET}",
                 lic.payload.tier
             );
-            println!("  {DIM}Email:{RESET}        {}", lic.payload.email);
-            println!("  {DIM}Issued:{RESET}       {}", lic.payload.issued_at);
-            println!("  {DIM}Expires:{RESET}      {}", lic.payload.expires_at);
-            println!();
+            outln!("  {DIM}Email:{RESET}        {}", lic.payload.email);
+            outln!("  {DIM}Issued:{RESET}       {}", lic.payload.issued_at);
+            outln!("  {DIM}Expires:{RESET}      {}", lic.payload.expires_at);
+            outln!();
 
             // Show unlocked features.
             let tier = lic.payload.tier;
-            println!("  {BOLD}Unlocked Features:{RESET}");
-            println!("  {GREEN}✓{RESET} Local vault, CLI, .env import");
+            outln!("  {BOLD}Unlocked Features:{RESET}");
+            outln!("  {GREEN}✓{RESET} Local vault, CLI, .env import");
             if tier >= license::Tier::Pro {
-                println!("  {GREEN}✓{RESET} AI Mode (MCP server)");
-                println!("  {GREEN}✓{RESET} zvault:// references");
-                println!("  {GREEN}✓{RESET} IDE setup (Cursor, Kiro, Continue)");
-                println!("  {GREEN}✓{RESET} llms.txt generation");
+                outln!("  {GREEN}✓{RESET} AI Mode (MCP server)");
+                outln!("  {GREEN}✓{RESET} zvault:// references");
+                outln!("  {GREEN}✓{RESET} IDE setup (Cursor, Kiro, Continue)");
+                outln!("  {GREEN}✓{RESET} llms.txt generation");
             }
             if tier >= license::Tier::Team {
-                println!("  {GREEN}✓{RESET} Shared vault");
-                println!("  {GREEN}✓{RESET} OIDC SSO");
-                println!("  {GREEN}✓{RESET} Audit log export");
-                println!("  {GREEN}✓{RESET} Slack/Discord alerts");
+                outln!("  {GREEN}✓{RESET} Shared vault");
+                outln!("  {GREEN}✓{RESET} OIDC SSO");
+                outln!("  {GREEN}✓{RESET} Audit log export");
+                outln!("  {GREEN}✓{RESET} Slack/Discord alerts");
             }
             if tier >= license::Tier::Enterprise {
-                println!("  {GREEN}✓{RESET} HA clustering");
-                println!("  {GREEN}✓{RESET} K8s operator");
-                println!("  {GREEN}✓{RESET} Namespaces");
-                println!("  {GREEN}✓{RESET} SLA");
+                outln!("  {GREEN}✓{RESET} HA clustering");
+                outln!("  {GREEN}✓{RESET} K8s operator");
+                outln!("  {GREEN}✓{RESET} Namespaces");
+                outln!("  {GREEN}✓{RESET} SLA");
             }
         }
         Ok(None) => {
             header("🪪", "License Status");
-            println!();
-            println!("  {DIM}Tier:{RESET}         {BOLD}Free{RESET}");
-            println!();
-            println!("  {DIM}Included:{RESET}");
-            println!("  {GREEN}✓{RESET} Local vault, CLI, .env import");
-            println!("  {GREEN}✓{RESET} KV, Transit, PKI engines");
-            println!("  {GREEN}✓{RESET} Web dashboard");
-            println!();
-            println!("  {DIM}Locked (Pro $8/mo):{RESET}");
-            println!("  {RED}✗{RESET} AI Mode (MCP server)");
-            println!("  {RED}✗{RESET} zvault:// references");
-            println!("  {RED}✗{RESET} IDE setup & llms.txt");
-            println!();
-            println!("  {CYAN}Upgrade:{RESET} https://zvault.cloud/pricing");
-            println!("  {CYAN}Activate:{RESET} zvault activate <license-key>");
+            outln!();
+            outln!("  {DIM}Tier:{RESET}         {BOLD}Free{RESET}");
+            outln!();
+            outln!("  {DIM}Included:{RESET}");
+            outln!("  {GREEN}✓{RESET} Local vault, CLI, .env import");
+            outln!("  {GREEN}✓{RESET} KV, Transit, PKI engines");
+            outln!("  {GREEN}✓{RESET} Web dashboard");
+            outln!();
+            outln!("  {DIM}Locked (Pro $8/mo):{RESET}");
+            outln!("  {RED}✗{RESET} AI Mode (MCP server)");
+            outln!("  {RED}✗{RESET} zvault:// references");
+            outln!("  {RED}✗{RESET} IDE setup & llms.txt");
+            outln!();
+            outln!("  {CYAN}Upgrade:{RESET} https://zvault.cloud/pricing");
+            outln!("  {CYAN}Activate:{RESET} zvault activate <license-key>");
         }
         Err(e) => {
             header("🪪", "License Status");
-            println!();
-            println!("  {RED}{BOLD}✗{RESET} {RED}License error: {e}{RESET}");
-            println!();
-            println!("  {DIM}Your license file may be corrupted or expired.{RESET}");
-            println!("  {DIM}Re-activate with:{RESET} zvault activate <license-key>");
+            outln!();
+            outln!("  {RED}{BOLD}✗{RESET} {RED}License error: {e}{RESET}");
+            outln!();
+            outln!("  {DIM}Your license file may be corrupted or expired.{RESET}");
+            outln!("  {DIM}Re-activate with:{RESET} zvault activate <license-key>");
         }
     }
 
-    println!();
+    outln!();
 }
 
 // ── IDE setup ────────────────────────────────────────────────────────
 
 fn cmd_setup(ide: &str) -> Result<()> {
-    println!();
+    outln!();
     header("🔧", &format!("Setting up ZVault for {ide}"));
-    println!();
+    outln!();
 
     let target = match ide.to_lowercase().as_str() {
         "cursor" => setup::Ide::Cursor,
@@ -2131,9 +5207,9 @@ fn cmd_setup(ide: &str) -> Result<()> {
 
     setup::run_setup(target)?;
 
-    println!();
+    outln!();
     success("IDE setup complete.");
-    println!();
+    outln!();
     Ok(())
 }
 
@@ -2141,7 +5217,7 @@ fn cmd_setup(ide: &str) -> Result<()> {
 
 /// Run diagnostics on vault health, license status, and MCP connectivity.
 async fn doctor_check_server(client: &Client) -> (u32, u32, u32) {
-    print!("  Vault server ({})... ", client.addr);
+    out!("  Vault server ({})... ", client.addr);
     if let Ok(resp) = client.get_no_auth("/v1/sys/health").await {
         let initialized = resp
             .get("initialized")
@@ -2150,23 +5226,23 @@ async fn doctor_check_server(client: &Client) -> (u32, u32, u32) {
         let sealed = resp.get("sealed").and_then(Value::as_bool).unwrap_or(true);
 
         if !initialized {
-            println!("{YELLOW}not initialized{RESET}");
+            outln!("{YELLOW}not initialized{RESET}");
             (0, 0, 1)
         } else if sealed {
-            println!("{YELLOW}sealed{RESET}");
+            outln!("{YELLOW}sealed{RESET}");
             (0, 0, 1)
         } else {
-            println!("{GREEN}healthy (unsealed){RESET}");
+            outln!("{GREEN}healthy (unsealed){RESET}");
             (1, 0, 0)
         }
     } else {
-        println!("{RED}unreachable{RESET}");
+        outln!("{RED}unreachable{RESET}");
         (0, 1, 0)
     }
 }
 
 async fn doctor_check_token(client: &Client) -> (u32, u32, u32) {
-    print!("  Auth token... ");
+    out!("  Auth token... ");
     match &client.token {
         Some(token) if !token.is_empty() => {
             if let Ok(resp) = client
@@ -2177,15 +5253,15 @@ async fn doctor_check_token(client: &Client) -> (u32, u32, u32) {
                     .get("policies")
                     .and_then(Value::as_array)
                     .map_or(0, std::vec::Vec::len);
-                println!("{GREEN}valid ({policies} policies){RESET}");
+                outln!("{GREEN}valid ({policies} policies){RESET}");
                 (1, 0, 0)
             } else {
-                println!("{YELLOW}set but invalid/expired{RESET}");
+                outln!("{YELLOW}set but invalid/expired{RESET}");
                 (0, 0, 1)
             }
         }
         _ => {
-            println!("{YELLOW}not set (VAULT_TOKEN){RESET}");
+            outln!("{YELLOW}not set (VAULT_TOKEN){RESET}");
             (0, 0, 1)
         }
     }
@@ -2196,69 +5272,70 @@ fn doctor_check_local() -> (u32, u32, u32) {
     let mut warn = 0u32;
 
     // License status.
-    print!("  License... ");
+    out!("  License... ");
     match license::load_license() {
         Ok(Some(lic)) => {
-            println!(
+            outln!(
                 "{GREEN}{} (expires {}){RESET}",
-                lic.payload.tier, lic.payload.expires_at
+                lic.payload.tier,
+                lic.payload.expires_at
             );
             pass = pass.saturating_add(1);
         }
         Ok(None) => {
-            println!("{DIM}Free tier{RESET}");
+            outln!("{DIM}Free tier{RESET}");
             pass = pass.saturating_add(1);
         }
         Err(e) => {
-            println!("{RED}error: {e}{RESET}");
+            outln!("{RED}error: {e}{RESET}");
             return (pass, 1, warn);
         }
     }
 
     // MCP server availability.
-    print!("  MCP server (AI Mode)... ");
+    out!("  MCP server (AI Mode)... ");
     let tier = license::current_tier();
     if tier >= license::Tier::Pro {
-        println!("{GREEN}available ({tier}){RESET}");
+        outln!("{GREEN}available ({tier}){RESET}");
         pass = pass.saturating_add(1);
     } else {
-        println!("{DIM}locked (requires Pro){RESET}");
+        outln!("{DIM}locked (requires Pro){RESET}");
         warn = warn.saturating_add(1);
     }
 
     // .env.zvault file.
-    print!("  .env.zvault... ");
+    out!("  .env.zvault... ");
     if std::path::Path::new(".env.zvault").exists() {
         let content = std::fs::read_to_string(".env.zvault").unwrap_or_default();
         let uri_count = content.lines().filter(|l| l.contains("zvault://")).count();
-        println!("{GREEN}found ({uri_count} references){RESET}");
+        outln!("{GREEN}found ({uri_count} references){RESET}");
         pass = pass.saturating_add(1);
     } else if std::path::Path::new(".env").exists() {
-        println!("{YELLOW}not found (.env exists — run `zvault import .env`){RESET}");
+        outln!("{YELLOW}not found (.env exists — run `zvault import .env`){RESET}");
         warn = warn.saturating_add(1);
     } else {
-        println!("{DIM}not found (no .env either){RESET}");
+        outln!("{DIM}not found (no .env either){RESET}");
         warn = warn.saturating_add(1);
     }
 
     // .gitignore check.
-    print!("  .gitignore (.env excluded)... ");
+    out!("  .gitignore (.env excluded)... ");
     if std::path::Path::new(".gitignore").exists() {
         let content = std::fs::read_to_string(".gitignore").unwrap_or_default();
         if content.lines().any(|l| l.trim() == ".env") {
-            println!("{GREEN}yes{RESET}");
+            outln!("{GREEN}yes{RESET}");
             pass = pass.saturating_add(1);
         } else {
-            println!("{YELLOW}.env not in .gitignore{RESET}");
+            outln!("{YELLOW}.env not in .gitignore{RESET}");
             warn = warn.saturating_add(1);
         }
     } else {
-        println!("{YELLOW}no .gitignore found{RESET}");
+        outln!("{YELLOW}no .gitignore found{RESET}");
         warn = warn.saturating_add(1);
     }
 
     // IDE MCP config.
-    print!("  IDE MCP config... ");
+    out!("  IDE MCP config... ");
     let mcp_configs = [
         (".cursor/mcp.json", "Cursor"),
         (".kiro/settings/mcp.json", "Kiro"),
@@ -2272,10 +5349,10 @@ fn doctor_check_local() -> (u32, u32, u32) {
         }
     }
     if let Some(name) = found_ide {
-        println!("{GREEN}found ({name}){RESET}");
+        outln!("{GREEN}found ({name}){RESET}");
         pass = pass.saturating_add(1);
     } else {
-        println!("{DIM}not configured (run `zvault setup <ide>`){RESET}");
+        outln!("{DIM}not configured (run `zvault setup <ide>`){RESET}");
         warn = warn.saturating_add(1);
     }
 
@@ -2283,9 +5360,9 @@ fn doctor_check_local() -> (u32, u32, u32) {
 }
 
 async fn cmd_doctor(client: &Client) -> Result<()> {
-    println!();
+    outln!();
     header("🩺", "ZVault Doctor");
-    println!();
+    outln!();
 
     let (p1, f1, w1) = doctor_check_server(client).await;
     let (p2, f2, w2) = doctor_check_token(client).await;
@@ -2296,25 +5373,25 @@ async fn cmd_doctor(client: &Client) -> Result<()> {
     let warn = w1.saturating_add(w2).saturating_add(w3);
 
     // ── Summary ──────────────────────────────────────────────────
-    println!();
-    println!(
+    outln!();
+    outln!(
         "  {BOLD}{GREEN}✓ {pass} passed{RESET}  \
          {BOLD}{YELLOW}⚠ {warn} warnings{RESET}  \
          {BOLD}{RED}✗ {fail} failed{RESET}"
     );
 
     if fail > 0 {
-        println!();
-        println!("  {DIM}Fix the failures above to get ZVault working properly.{RESET}");
+        outln!();
+        outln!("  {DIM}Fix the failures above to get ZVault working properly.{RESET}");
     } else if warn > 0 {
-        println!();
-        println!("  {DIM}Warnings are non-critical but worth addressing.{RESET}");
+        outln!();
+        outln!("  {DIM}Warnings are non-critical but worth addressing.{RESET}");
     } else {
-        println!();
-        println!("  {GREEN}Everything looks good.{RESET}");
+        outln!();
+        outln!("  {GREEN}Everything looks good.{RESET}");
     }
 
-    println!();
+    outln!();
     Ok(())
 }
 
@@ -2336,7 +5413,7 @@ fn print_json(value: &Value) {
         return;
     }
     match serde_json::to_string_pretty(value) {
-        Ok(s) => println!("{s}"),
+        Ok(s) => outln!("{s}"),
         Err(e) => eprintln!("failed to format JSON: {e}"),
     }
 }
@@ -2344,9 +5421,9 @@ fn print_json(value: &Value) {
 // ── Phase 1.3: Project Init ──────────────────────────────────────────
 
 fn cmd_project_init(name: Option<&str>, server: &str) -> Result<()> {
-    println!();
+    outln!();
     header("📁", "Project Init");
-    println!();
+    outln!();
 
     let project_name = match name {
         Some(n) => n.to_owned(),
@@ -2356,7 +5433,7 @@ fn cmd_project_init(name: Option<&str>, server: &str) -> Result<()> {
     let config_path = std::path::Path::new(".zvault.toml");
     if config_path.exists() {
         warning("  .zvault.toml already exists — skipping");
-        println!();
+        outln!();
         return Ok(());
     }
 
@@ -2388,6 +5465,17 @@ no_backup = false
 check_interval = 24
 # Enable rotation notifications
 notify = false
+
+# Per-environment profiles for `zvault run --env <name>`
+# [env.staging]
+# address = "https://vault.staging.example.com:8200"
+# prefix = "env/{project_name}-staging"
+# token_env = "ZVAULT_STAGING_TOKEN"   # or token_file = "~/.zvault/staging-token"
+
+# Known-safe findings for `zvault scan` and the pre-commit hook
+# [scan]
+# allow_paths = ["tests/fixtures/**"]
+# allow_values = ["EXAMPLE$"]
 "#
     );
 
@@ -2404,12 +5492,12 @@ notify = false
     add_to_gitignore(".env");
     add_to_gitignore(".env.backup");
 
-    println!();
-    println!("  {DIM}Next steps:{RESET}");
-    println!("    1. Start your vault:  {CYAN}zvault status{RESET}");
-    println!("    2. Import secrets:    {CYAN}zvault import .env{RESET}");
-    println!("    3. Run your app:      {CYAN}zvault run -- npm run dev{RESET}");
-    println!();
+    outln!();
+    outln!("  {DIM}Next steps:{RESET}");
+    outln!("    1. Start your vault:  {CYAN}zvault status{RESET}");
+    outln!("    2. Import secrets:    {CYAN}zvault import .env{RESET}");
+    outln!("    3. Run your app:      {CYAN}zvault run -- npm run dev{RESET}");
+    outln!();
 
     Ok(())
 }
@@ -2420,6 +5508,26 @@ async fn cmd_lease(client: &Client, action: LeaseCommands) -> Result<()> {
     match action {
         LeaseCommands::List => cmd_lease_list(client).await,
         LeaseCommands::Lookup { lease_id } => cmd_lease_lookup(client, &lease_id).await,
+        LeaseCommands::Renew {
+            lease_id,
+            increment,
+        } => {
+            let resp = client
+                .post(
+                    "/v1/sys/leases/renew",
+                    &serde_json::json!({ "lease_id": lease_id, "increment": increment }),
+                )
+                .await?;
+            let ttl = resp
+                .get("ttl_secs")
+                .and_then(serde_json::Value::as_i64)
+                .unwrap_or(0);
+            outln!();
+            success(&format!("Lease {lease_id} renewed"));
+            kv_line("TTL", &format_duration(ttl));
+            outln!();
+            Ok(())
+        }
         LeaseCommands::Revoke { lease_id } => {
             client
                 .post(
@@ -2427,9 +5535,41 @@ async fn cmd_lease(client: &Client, action: LeaseCommands) -> Result<()> {
                     &serde_json::json!({ "lease_id": lease_id }),
                 )
                 .await?;
-            println!();
+            outln!();
             success(&format!("Lease {lease_id} revoked"));
-            println!();
+            outln!();
+            Ok(())
+        }
+        LeaseCommands::RevokePrefix {
+            prefix,
+            force,
+            confirm,
+        } => {
+            confirm_destructive(
+                &format!("revoke every lease under {prefix}"),
+                &prefix,
+                confirm.as_deref(),
+            )?;
+            let route = if force {
+                "revoke-force"
+            } else {
+                "revoke-prefix"
+            };
+            let resp = client
+                .post(
+                    &format!("/v1/sys/leases/{route}/{prefix}"),
+                    &serde_json::json!({}),
+                )
+                .await?;
+            let revoked = resp
+                .get("revoked")
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or(0);
+            outln!();
+            success(&format!(
+                "Revoked {revoked} lease(s) under {BOLD}{prefix}{RESET}"
+            ));
+            outln!();
             Ok(())
         }
     }
@@ -2437,21 +5577,24 @@ async fn cmd_lease(client: &Client, action: LeaseCommands) -> Result<()> {
 
 /// List all active leases.
 async fn cmd_lease_list(client: &Client) -> Result<()> {
-    println!();
+    outln!();
     header("📋", "Leases");
-    println!();
+    outln!();
 
     let resp = client.get("/v1/sys/leases").await?;
     let leases = resp.get("leases").and_then(|v| v.as_array());
 
     match leases {
         Some(arr) if arr.is_empty() => {
-            println!("  {DIM}No active leases.{RESET}");
+            outln!("  {DIM}No active leases.{RESET}");
         }
         Some(arr) => {
-            println!(
+            outln!(
                 "  {DIM}{:<36}  {:<24}  {:<8}  {:<8}  STATUS{RESET}",
-                "LEASE ID", "ENGINE", "TTL", "RENEW"
+                "LEASE ID",
+                "ENGINE",
+                "TTL",
+                "RENEW"
             );
             for lease in arr {
                 let id = lease
@@ -2482,7 +5625,7 @@ async fn cmd_lease_list(client: &Client) -> Result<()> {
                 };
                 let renew_str = if renewable { "yes" } else { "no" };
 
-                println!(
+                outln!(
                     "  {:<36}  {:<24}  {:<8}  {:<8}  {}",
                     id,
                     engine,
@@ -2495,557 +5638,1441 @@ async fn cmd_lease_list(client: &Client) -> Result<()> {
                 .get("total")
                 .and_then(serde_json::Value::as_u64)
                 .unwrap_or(0);
-            println!();
-            println!("  {DIM}Total: {total} lease(s){RESET}");
+            outln!();
+            outln!("  {DIM}Total: {total} lease(s){RESET}");
+        }
+        None => {
+            outln!("  {DIM}No lease data returned.{RESET}");
+        }
+    }
+    outln!();
+    Ok(())
+}
+
+/// Look up details of a specific lease.
+async fn cmd_lease_lookup(client: &Client, lease_id: &str) -> Result<()> {
+    let resp = client
+        .post(
+            "/v1/sys/leases/lookup",
+            &serde_json::json!({ "lease_id": lease_id }),
+        )
+        .await?;
+    outln!();
+    header("🔍", "Lease Lookup");
+    outln!();
+    kv_line(
+        "Lease ID",
+        resp.get("lease_id").and_then(|v| v.as_str()).unwrap_or("-"),
+    );
+    kv_line(
+        "Engine",
+        resp.get("engine_path")
+            .and_then(|v| v.as_str())
+            .unwrap_or("-"),
+    );
+    kv_line(
+        "Issued At",
+        resp.get("issued_at")
+            .and_then(|v| v.as_str())
+            .unwrap_or("-"),
+    );
+    let ttl = resp
+        .get("ttl_secs")
+        .and_then(serde_json::Value::as_i64)
+        .unwrap_or(0);
+    kv_line("TTL", &format_duration(ttl));
+    let renewable = resp
+        .get("renewable")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false);
+    kv_line("Renewable", if renewable { "yes" } else { "no" });
+    let expired = resp
+        .get("expired")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false);
+    if expired {
+        kv_line("Status", &format!("{RED}expired{RESET}"));
+    } else {
+        kv_line("Status", &format!("{GREEN}active{RESET}"));
+    }
+    outln!();
+    Ok(())
+}
+
+// ── Access requests ──────────────────────────────────────────────────
+
+async fn cmd_access(client: &Client, action: AccessCommands) -> Result<()> {
+    let (resp, done) = match action {
+        AccessCommands::Request {
+            path,
+            capabilities,
+            reason,
+            ttl,
+        } => {
+            let body = serde_json::json!({
+                "path": path,
+                "capabilities": capabilities,
+                "reason": reason,
+                "ttl": ttl,
+            });
+            let resp = client.post("/v1/sys/access-requests", &body).await?;
+            (resp, "Access requested — waiting for approval")
+        }
+        AccessCommands::List => return cmd_access_list(client).await,
+        AccessCommands::Show { id } => {
+            let resp = client.get(&format!("/v1/sys/access-requests/{id}")).await?;
+            outln!();
+            print_access_request(&resp);
+            return Ok(());
+        }
+        AccessCommands::Approve { id, comment } => (
+            access_decision(client, &id, "approve", comment).await?,
+            "Request approved",
+        ),
+        AccessCommands::Deny { id, comment } => (
+            access_decision(client, &id, "deny", comment).await?,
+            "Request denied",
+        ),
+        AccessCommands::Revoke { id, comment } => (
+            access_decision(client, &id, "revoke", comment).await?,
+            "Access revoked",
+        ),
+    };
+    outln!();
+    success(done);
+    outln!();
+    print_access_request(&resp);
+    Ok(())
+}
+
+async fn access_decision(
+    client: &Client,
+    id: &str,
+    decision: &str,
+    comment: Option<String>,
+) -> Result<Value> {
+    client
+        .post(
+            &format!("/v1/sys/access-requests/{id}/{decision}"),
+            &serde_json::json!({ "comment": comment }),
+        )
+        .await
+}
+
+/// List access requests.
+async fn cmd_access_list(client: &Client) -> Result<()> {
+    outln!();
+    header("🎫", "Access Requests");
+    outln!();
+
+    let resp = client.get("/v1/sys/access-requests").await?;
+    let requests = resp
+        .get("requests")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if requests.is_empty() {
+        outln!("  {DIM}No access requests.{RESET}");
+        outln!();
+        return Ok(());
+    }
+
+    outln!(
+        "  {DIM}{:<36}  {:<16}  {:<10}  PATH{RESET}",
+        "ID",
+        "REQUESTER",
+        "STATUS"
+    );
+    for request in &requests {
+        let field = |key: &str| request.get(key).and_then(Value::as_str).unwrap_or("-");
+        outln!(
+            "  {:<36}  {:<16}  {:<10}  {}",
+            field("id"),
+            field("requester_name"),
+            field("status"),
+            field("path")
+        );
+    }
+    outln!();
+    Ok(())
+}
+
+fn print_access_request(resp: &Value) {
+    let field = |key: &str| resp.get(key).and_then(Value::as_str).unwrap_or("-");
+    header("🎫", &format!("Access Request {}", field("id")));
+    kv_line("Requester", field("requester_name"));
+    kv_line("Path", field("path"));
+    let capabilities = resp
+        .get("capabilities")
+        .and_then(Value::as_array)
+        .map(|caps| {
+            caps.iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+    kv_line("Capabilities", &capabilities);
+    kv_line("Reason", field("reason"));
+    kv_line("Status", field("status"));
+    if let Some(expires) = resp.get("expires_at").and_then(Value::as_str) {
+        kv_line("Expires", expires);
+    }
+    if let Some(history) = resp.get("history").and_then(Value::as_array) {
+        outln!();
+        for event in history {
+            let get = |key: &str| event.get(key).and_then(Value::as_str).unwrap_or("-");
+            let comment = event
+                .get("comment")
+                .and_then(Value::as_str)
+                .map(|c| format!(" — {c}"))
+                .unwrap_or_default();
+            outln!(
+                "  {DIM}{}{RESET}  {:<9} by {}{comment}",
+                get("at"),
+                get("status"),
+                get("actor")
+            );
+        }
+    }
+    outln!();
+}
+
+// ── Identity ─────────────────────────────────────────────────────────
+
+async fn cmd_identity(client: &Client, action: IdentityCommands) -> Result<()> {
+    outln!();
+    match action {
+        IdentityCommands::CreateEntity { name, policies } => {
+            let body = serde_json::json!({ "name": name, "policies": policies });
+            let resp = client.post("/v1/sys/identity/entity", &body).await?;
+            success("Entity created.");
+            outln!();
+            print_entity(&resp);
+        }
+        IdentityCommands::ReadEntity { name } => {
+            let resp = client
+                .get(&format!("/v1/sys/identity/entity/name/{name}"))
+                .await?;
+            print_entity(&resp);
+        }
+        IdentityCommands::DisableEntity { name, enable } => {
+            let id = entity_id(client, &name).await?;
+            let body = serde_json::json!({ "disabled": !enable });
+            let resp = client
+                .post(&format!("/v1/sys/identity/entity/id/{id}"), &body)
+                .await?;
+            let state = if enable { "enabled" } else { "disabled" };
+            success(&format!("Entity {name} {state}."));
+            outln!();
+            print_entity(&resp);
+        }
+        IdentityCommands::DeleteEntity { name } => {
+            let id = entity_id(client, &name).await?;
+            client
+                .delete(&format!("/v1/sys/identity/entity/id/{id}"))
+                .await?;
+            success(&format!("Entity {name} deleted."));
+            outln!();
+        }
+        IdentityCommands::ListEntities => {
+            let resp = client.get("/v1/sys/identity/entity").await?;
+            print_identity_keys("Entities", &resp);
+        }
+        IdentityCommands::AddAlias {
+            entity,
+            auth_method,
+            name,
+        } => add_alias(client, &entity, &auth_method, &name).await?,
+        IdentityCommands::DeleteAlias { id } => {
+            client
+                .delete(&format!("/v1/sys/identity/entity-alias/id/{id}"))
+                .await?;
+            success(&format!("Alias {id} deleted."));
+            outln!();
+        }
+        IdentityCommands::CreateGroup {
+            name,
+            policies,
+            member_entities,
+            member_groups,
+            external,
+        } => {
+            let mut body = serde_json::json!({ "name": name, "policies": policies });
+            if let Some(external) = external {
+                body["type"] = Value::from("external");
+                body["alias"] = external_group_alias(&external)?;
+            } else {
+                body["member_entity_ids"] = member_ids(client, "entity", &member_entities).await?;
+                body["member_group_ids"] = member_ids(client, "group", &member_groups).await?;
+            }
+            let resp = client.post("/v1/sys/identity/group", &body).await?;
+            success("Group created.");
+            outln!();
+            print_group(&resp);
+        }
+        IdentityCommands::ReadGroup { name } => {
+            let resp = client
+                .get(&format!("/v1/sys/identity/group/name/{name}"))
+                .await?;
+            print_group(&resp);
+        }
+        IdentityCommands::DeleteGroup { name } => {
+            let id = group_id(client, &name).await?;
+            client
+                .delete(&format!("/v1/sys/identity/group/id/{id}"))
+                .await?;
+            success(&format!("Group {name} deleted."));
+            outln!();
+        }
+        IdentityCommands::ListGroups => {
+            let resp = client.get("/v1/sys/identity/group").await?;
+            print_identity_keys("Groups", &resp);
+        }
+        IdentityCommands::Whoami => {
+            let resp = client.get("/v1/sys/identity/lookup/self").await?;
+            let entity = resp.get("entity_id").and_then(Value::as_str);
+            header("🪪", "Identity");
+            kv_line("Entity", entity.unwrap_or("-"));
+            kv_line("Policies", &comma_list(&resp, "policies"));
+            outln!();
+        }
+    }
+    Ok(())
+}
+
+async fn add_alias(client: &Client, entity: &str, auth_method: &str, name: &str) -> Result<()> {
+    let body = serde_json::json!({
+        "entity_id": entity_id(client, entity).await?,
+        "auth_method": auth_method,
+        "name": name,
+    });
+    let resp = client.post("/v1/sys/identity/entity-alias", &body).await?;
+    let id = resp.get("id").and_then(Value::as_str).unwrap_or("-");
+    success(&format!("Alias {auth_method}:{name} added ({id})."));
+    outln!();
+    Ok(())
+}
+
+async fn entity_id(client: &Client, name: &str) -> Result<String> {
+    identity_id(client, "entity", name).await
+}
+
+async fn group_id(client: &Client, name: &str) -> Result<String> {
+    identity_id(client, "group", name).await
+}
+
+/// Resolve an entity or group name to its ID.
+async fn identity_id(client: &Client, kind: &str, name: &str) -> Result<String> {
+    let resp = client
+        .get(&format!("/v1/sys/identity/{kind}/name/{name}"))
+        .await?;
+    resp.get("id")
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+        .with_context(|| format!("{kind} {name} has no ID"))
+}
+
+/// Parse `--external auth_method:name` into a group alias.
+fn external_group_alias(external: &str) -> Result<Value> {
+    let (auth_method, name) = external
+        .split_once(':')
+        .with_context(|| format!("invalid --external '{external}', expected auth_method:name"))?;
+    Ok(serde_json::json!({ "auth_method": auth_method, "name": name }))
+}
+
+/// Resolve entity or group names to a JSON array of IDs.
+async fn member_ids(client: &Client, kind: &str, names: &[String]) -> Result<Value> {
+    let mut ids = Vec::with_capacity(names.len());
+    for name in names {
+        ids.push(identity_id(client, kind, name).await?);
+    }
+    Ok(Value::from(ids))
+}
+
+/// A JSON string array field, comma-separated.
+fn comma_list(resp: &Value, field: &str) -> String {
+    resp.get(field)
+        .and_then(Value::as_array)
+        .map(|v| {
+            v.iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default()
+}
+
+fn print_entity(resp: &Value) {
+    let field = |key: &str| resp.get(key).and_then(Value::as_str).unwrap_or("-");
+    header("🪪", &format!("Entity: {}", field("name")));
+    kv_line("ID", field("id"));
+    kv_line("Policies", &comma_list(resp, "policies"));
+    kv_line("Groups", &comma_list(resp, "group_ids"));
+    let disabled = resp.get("disabled").and_then(Value::as_bool) == Some(true);
+    kv_line("Disabled", if disabled { "yes" } else { "no" });
+    for alias in resp
+        .get("aliases")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let get = |key: &str| alias.get(key).and_then(Value::as_str).unwrap_or("-");
+        outln!(
+            "  {DIM}alias{RESET}  {}:{}  {DIM}{}{RESET}",
+            get("auth_method"),
+            get("name"),
+            get("id")
+        );
+    }
+    outln!();
+}
+
+fn print_group(resp: &Value) {
+    let field = |key: &str| resp.get(key).and_then(Value::as_str).unwrap_or("-");
+    header("👥", &format!("Group: {}", field("name")));
+    kv_line("ID", field("id"));
+    kv_line("Type", field("type"));
+    kv_line("Policies", &comma_list(resp, "policies"));
+    if let Some(alias) = resp.get("alias").filter(|a| !a.is_null()) {
+        let get = |key: &str| alias.get(key).and_then(Value::as_str).unwrap_or("-");
+        kv_line("Alias", &format!("{}:{}", get("auth_method"), get("name")));
+    }
+    kv_line("Entities", &comma_list(resp, "member_entity_ids"));
+    kv_line("Groups", &comma_list(resp, "member_group_ids"));
+    outln!();
+}
+
+fn print_identity_keys(title: &str, resp: &Value) {
+    header("🪪", title);
+    match resp.get("keys").and_then(Value::as_array) {
+        Some(keys) if !keys.is_empty() => {
+            for key in keys.iter().filter_map(Value::as_str) {
+                outln!("  {CYAN}├─{RESET} {key}");
+            }
+        }
+        _ => outln!("  {DIM}(none){RESET}"),
+    }
+    outln!();
+}
+
+// ── Control groups ───────────────────────────────────────────────────
+
+async fn cmd_control_group(client: &Client, action: ControlGroupCommands) -> Result<()> {
+    let (resp, done) = match action {
+        ControlGroupCommands::Status { accessor } => {
+            (control_group_status(client, &accessor).await?, None)
+        }
+        ControlGroupCommands::Authorize { accessor } => {
+            let body = serde_json::json!({ "accessor": accessor });
+            let resp = client
+                .post("/v1/sys/control-group/authorize", &body)
+                .await?;
+            (resp, Some("Request authorized"))
         }
-        None => {
-            println!("  {DIM}No lease data returned.{RESET}");
+        ControlGroupCommands::Retrieve { accessor } => {
+            let status = control_group_status(client, &accessor).await?;
+            let field = |key: &str| status.get(key).and_then(Value::as_str).unwrap_or_default();
+            if status.get("approved").and_then(Value::as_bool) != Some(true) {
+                bail!("request {accessor} is not yet approved");
+            }
+            let path = field("path").split('?').next().unwrap_or_default();
+            let resp = client
+                .replay_parked(field("method"), path, &accessor)
+                .await?;
+            print_json(&resp);
+            return Ok(());
         }
+    };
+    outln!();
+    if let Some(done) = done {
+        success(done);
+        outln!();
     }
-    println!();
+    print_control_group(&resp);
     Ok(())
 }
 
-/// Look up details of a specific lease.
-async fn cmd_lease_lookup(client: &Client, lease_id: &str) -> Result<()> {
-    let resp = client
+async fn control_group_status(client: &Client, accessor: &str) -> Result<Value> {
+    client
         .post(
-            "/v1/sys/leases/lookup",
-            &serde_json::json!({ "lease_id": lease_id }),
+            "/v1/sys/control-group/request",
+            &serde_json::json!({ "accessor": accessor }),
         )
-        .await?;
-    println!();
-    header("🔍", "Lease Lookup");
-    println!();
-    kv_line(
-        "Lease ID",
-        resp.get("lease_id").and_then(|v| v.as_str()).unwrap_or("-"),
-    );
-    kv_line(
-        "Engine",
-        resp.get("engine_path")
-            .and_then(|v| v.as_str())
-            .unwrap_or("-"),
-    );
-    kv_line(
-        "Issued At",
-        resp.get("issued_at")
-            .and_then(|v| v.as_str())
-            .unwrap_or("-"),
+        .await
+}
+
+/// Approver policies and identities of a control group, comma-separated.
+fn control_group_approvers(group: &Value) -> String {
+    ["approver_policies", "approver_identities"]
+        .iter()
+        .filter_map(|key| group.get(key).and_then(Value::as_array))
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_control_group(resp: &Value) {
+    let field = |key: &str| resp.get(key).and_then(Value::as_str).unwrap_or("-");
+    header("🛂", &format!("Control Group {}", field("accessor")));
+    kv_line("Requester", field("requester_name"));
+    kv_line("Request", &format!("{} {}", field("method"), field("path")));
+    let approved = resp.get("approved").and_then(Value::as_bool) == Some(true);
+    kv_line("Approved", if approved { "yes" } else { "no" });
+    kv_line("Expires", field("expires_at"));
+    for group in resp
+        .get("control_groups")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let count = |key: &str| group.get(key).and_then(Value::as_u64).unwrap_or(0);
+        outln!(
+            "  {DIM}group{RESET}  {}/{} approvals from {}",
+            count("approved_by"),
+            count("approvals"),
+            control_group_approvers(group)
+        );
+    }
+    if let Some(authorizations) = resp.get("authorizations").and_then(Value::as_array) {
+        outln!();
+        for authorization in authorizations {
+            let get = |key: &str| {
+                authorization
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .unwrap_or("-")
+            };
+            outln!(
+                "  {DIM}{}{RESET}  approved by {}",
+                get("approved_at"),
+                get("approver_name")
+            );
+        }
+    }
+    outln!();
+}
+
+// ── Events ───────────────────────────────────────────────────────────
+
+async fn cmd_events(client: &Client, action: EventsCommands) -> Result<()> {
+    match action {
+        EventsCommands::Subscribe { pattern, json } => {
+            cmd_events_subscribe(client, &pattern, json).await
+        }
+    }
+}
+
+async fn cmd_events_subscribe(client: &Client, pattern: &str, json: bool) -> Result<()> {
+    client.auth_header()?;
+    let path = format!(
+        "/v1/sys/events/subscribe/{}",
+        pattern.trim_start_matches('/')
     );
-    let ttl = resp
-        .get("ttl_secs")
-        .and_then(serde_json::Value::as_i64)
-        .unwrap_or(0);
-    kv_line("TTL", &format_duration(ttl));
-    let renewable = resp
-        .get("renewable")
-        .and_then(serde_json::Value::as_bool)
-        .unwrap_or(false);
-    kv_line("Renewable", if renewable { "yes" } else { "no" });
-    let expired = resp
-        .get("expired")
-        .and_then(serde_json::Value::as_bool)
-        .unwrap_or(false);
-    if expired {
-        kv_line("Status", &format!("{RED}expired{RESET}"));
-    } else {
-        kv_line("Status", &format!("{GREEN}active{RESET}"));
+    let mut resp = client.request(reqwest::Method::GET, &path, None).await?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(ApiError::from_body(status, &body).into());
+    }
+
+    // Server-Sent Events: messages end with a blank line.
+    let mut buffer = Vec::new();
+    while let Some(bytes) = resp.chunk().await.context("event stream failed")? {
+        buffer.extend_from_slice(&bytes);
+        while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+            let message: Vec<u8> = buffer.drain(..end + 2).collect();
+            print_event(&String::from_utf8_lossy(&message[..end]), json);
+        }
     }
-    println!();
     Ok(())
 }
 
+/// Print one SSE message; keep-alive comments print nothing.
+fn print_event(message: &str, json: bool) {
+    let mut name = "";
+    let mut data = String::new();
+    for line in message.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            name = value.trim_start();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push_str(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    if data.is_empty() {
+        return;
+    }
+    if json {
+        outln!("{data}");
+        return;
+    }
+    let event: Value = serde_json::from_str(&data).unwrap_or_default();
+    if name == "lagged" {
+        let missed = event.get("missed").and_then(Value::as_u64).unwrap_or(0);
+        warning(&format!("fell behind, missed {missed} events"));
+        return;
+    }
+    let field = |key: &str| event.get(key).and_then(Value::as_str).unwrap_or("-");
+    let subject = ["path", "engine_path", "name", "lease_id"]
+        .iter()
+        .find_map(|key| event["data"].get(key).and_then(Value::as_str))
+        .unwrap_or("");
+    outln!(
+        "  {DIM}{}{RESET}  {:<14} {subject}",
+        field("timestamp"),
+        field("topic")
+    );
+}
+
 // ── Phase 3.3: Audit Export ──────────────────────────────────────────
 
+/// Entries fetched per audit-log page.
+const AUDIT_EXPORT_PAGE_SIZE: usize = 1000;
+
 async fn cmd_audit_export(
     client: &Client,
     format: &str,
-    limit: usize,
+    limit: Option<usize>,
     output: Option<&str>,
+    gzip: bool,
+    filter: &AuditFilterArgs,
 ) -> Result<()> {
-    println!();
-    header("📊", "Audit Log Export");
-    println!();
-
-    let resp = client
-        .get_no_auth(&format!("/v1/sys/audit-log?limit={limit}"))
-        .await?;
-
-    let entries = resp
-        .get("entries")
-        .and_then(|v| v.as_array())
-        .cloned()
-        .unwrap_or_default();
+    let format = audit_export::ExportFormat::parse(format)?;
+    let filter = filter.query()?;
+    if gzip && format == audit_export::ExportFormat::Parquet {
+        bail!("parquet output is already compressed; drop --gzip");
+    }
 
-    if entries.is_empty() {
-        println!("  {DIM}No audit entries found.{RESET}");
-        println!();
-        return Ok(());
+    // Progress output would corrupt an export streamed to stdout.
+    if output.is_some() {
+        outln!();
+        header("📊", "Audit Log Export");
+        outln!();
     }
 
-    let content = match format {
-        "csv" => {
-            let mut csv = String::from("timestamp,operation,path,actor,status\n");
-            for entry in &entries {
-                let ts = entry
-                    .get("timestamp")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let op = entry
-                    .get("operation")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let path = entry.get("path").and_then(|v| v.as_str()).unwrap_or("");
-                let actor = entry.get("actor").and_then(|v| v.as_str()).unwrap_or("");
-                let status = entry
-                    .get("response")
-                    .and_then(|v| v.get("status_code"))
-                    .and_then(serde_json::Value::as_u64)
-                    .map(|v| v.to_string())
-                    .unwrap_or_default();
-                let _ = writeln!(csv, "{ts},{op},{path},{actor},{status}");
-            }
-            csv
-        }
-        _ => serde_json::to_string_pretty(&entries).unwrap_or_else(|_| "[]".to_owned()),
-    };
+    let sink = audit_export::Sink::open(output, gzip)?;
+    let mut writer = audit_export::ExportWriter::new(format, sink)?;
+    let mut exported = 0usize;
+    let mut cursor = Some(0u64);
 
-    match output {
-        Some(path) => {
-            std::fs::write(path, &content)
-                .map_err(|e| anyhow::anyhow!("failed to write {path}: {e}"))?;
-            success(&format!(
-                "Exported {} entries to {path} ({format})",
-                entries.len()
-            ));
-        }
-        None => {
-            println!("{content}");
+    while let Some(at) = cursor {
+        let remaining = limit.map_or(AUDIT_EXPORT_PAGE_SIZE, |l| l - exported);
+        if remaining == 0 {
+            break;
         }
+        let page_size = remaining.min(AUDIT_EXPORT_PAGE_SIZE);
+        let resp = client
+            .get(&format!(
+                "/v1/sys/audit-log?cursor={at}&limit={page_size}{filter}"
+            ))
+            .await?;
+
+        let entries = resp
+            .get("entries")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        writer.write_page(entries)?;
+        exported += entries.len();
+        cursor = resp.get("next_cursor").and_then(Value::as_u64);
     }
+    writer.finish()?;
 
-    println!();
+    if let Some(path) = output {
+        if exported == 0 {
+            outln!("  {DIM}No audit entries found.{RESET}");
+        } else {
+            success(&format!("Exported {exported} entries to {path}"));
+        }
+        outln!();
+    }
     Ok(())
 }
 
-// ── Phase 3.4: Notifications (Webhook) ──────────────────────────────
-
-const WEBHOOK_CONFIG_PATH: &str = ".zvault/webhooks.json";
-
-fn ensure_zvault_dir() -> Result<()> {
-    let dir = std::path::Path::new(".zvault");
-    if !dir.exists() {
-        std::fs::create_dir_all(dir)
-            .map_err(|e| anyhow::anyhow!("failed to create .zvault dir: {e}"))?;
-    }
+async fn cmd_audit_count(client: &Client, filter: &AuditFilterArgs) -> Result<()> {
+    let filter = filter.query()?;
+    let path = match filter.strip_prefix('&') {
+        Some(query) => format!("/v1/sys/audit-log/count?{query}"),
+        None => "/v1/sys/audit-log/count".to_owned(),
+    };
+    let resp = client.get(&path).await?;
+    let count = resp.get("count").and_then(Value::as_u64).unwrap_or(0);
+    outln!("{count}");
     Ok(())
 }
 
-fn load_webhook_config() -> Result<serde_json::Value> {
-    let path = std::path::Path::new(WEBHOOK_CONFIG_PATH);
-    if !path.exists() {
-        return Ok(serde_json::json!({ "webhooks": [] }));
+// ── Notifications (Webhook) ─────────────────────────────────────────
+
+async fn cmd_notify(client: &Client, action: NotifyCommands) -> Result<()> {
+    match action {
+        NotifyCommands::SetWebhook {
+            url,
+            name,
+            events,
+            format,
+            secret,
+            routing_key,
+            disabled,
+        } => {
+            let mut body = serde_json::json!({
+                "url": url,
+                "events": events,
+                "enabled": !disabled,
+            });
+            if let Some(format) = format {
+                body["format"] = Value::String(format);
+            }
+            if let Some(secret) = secret {
+                body["secret"] = Value::String(secret);
+            }
+            if let Some(routing_key) = routing_key {
+                body["routing_key"] = Value::String(routing_key);
+            }
+            let resp = client
+                .post(&format!("/v1/sys/notifications/webhooks/{name}"), &body)
+                .await?;
+            outln!();
+            success(&format!("Webhook {name} configured"));
+            outln!();
+            print_webhook(&resp);
+            Ok(())
+        }
+        NotifyCommands::GetWebhook { name } => {
+            let webhooks = match name {
+                Some(name) => vec![
+                    client
+                        .get(&format!("/v1/sys/notifications/webhooks/{name}"))
+                        .await?,
+                ],
+                None => client
+                    .get("/v1/sys/notifications/webhooks")
+                    .await?
+                    .get("webhooks")
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            outln!();
+            if webhooks.is_empty() {
+                header("🔔", "Webhooks");
+                outln!("  {DIM}No webhooks configured.{RESET}");
+                outln!("  {DIM}Run: zvault notify set-webhook <url>{RESET}");
+                outln!();
+            }
+            for webhook in &webhooks {
+                print_webhook(webhook);
+            }
+            Ok(())
+        }
+        NotifyCommands::RemoveWebhook { name } => {
+            client
+                .delete(&format!("/v1/sys/notifications/webhooks/{name}"))
+                .await?;
+            outln!();
+            success(&format!("Webhook {name} removed"));
+            outln!();
+            Ok(())
+        }
+        NotifyCommands::Test { name } => {
+            client
+                .post_no_body(&format!("/v1/sys/notifications/webhooks/{name}/test"))
+                .await?;
+            outln!();
+            success(&format!("Test notification delivered to {name}"));
+            outln!();
+            Ok(())
+        }
+        NotifyCommands::Queue => {
+            let resp = client.get("/v1/sys/notifications/queue").await?;
+            print_notify_queue(&resp);
+            Ok(())
+        }
     }
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| anyhow::anyhow!("failed to read webhook config: {e}"))?;
-    serde_json::from_str(&content).map_err(|e| anyhow::anyhow!("invalid webhook config: {e}"))
 }
 
-fn save_webhook_config(config: &serde_json::Value) -> Result<()> {
-    ensure_zvault_dir()?;
-    let content = serde_json::to_string_pretty(config)
-        .map_err(|e| anyhow::anyhow!("failed to serialize webhook config: {e}"))?;
-    std::fs::write(WEBHOOK_CONFIG_PATH, content)
-        .map_err(|e| anyhow::anyhow!("failed to write webhook config: {e}"))?;
-    Ok(())
+fn print_notify_queue(resp: &Value) {
+    let deliveries = resp
+        .get("deliveries")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    outln!();
+    header("🔔", "Notification Retry Queue");
+    if deliveries.is_empty() {
+        outln!("  {DIM}Nothing waiting to be retried.{RESET}");
+    }
+    for delivery in &deliveries {
+        let get = |pointer: &str| {
+            delivery
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .unwrap_or("-")
+        };
+        let attempts = delivery
+            .get("attempts")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        outln!(
+            "  {BOLD}{}{RESET}  {}  {DIM}attempt {attempts}, next {}{RESET}",
+            get("/webhook"),
+            get("/event/topic"),
+            get("/next_attempt_at")
+        );
+        outln!("    {RED}{}{RESET}", get("/last_error"));
+    }
+    outln!();
 }
 
-async fn send_webhook(url: &str, payload: &serde_json::Value) -> Result<()> {
-    let http = reqwest::Client::new();
-    let resp = http
-        .post(url)
-        .json(payload)
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-        .await
-        .map_err(|e| anyhow::anyhow!("webhook request failed: {e}"))?;
-
-    if resp.status().is_success() {
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("webhook returned status {}", resp.status()))
+fn print_webhook(webhook: &Value) {
+    let field = |key: &str| webhook.get(key).and_then(Value::as_str).unwrap_or("-");
+    let flag = |key: &str| webhook.get(key).and_then(Value::as_bool) == Some(true);
+    let events = webhook
+        .get("events")
+        .and_then(Value::as_array)
+        .map(|arr| {
+            arr.iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+    header("🔔", &format!("Webhook {}", field("name")));
+    kv_line("URL", field("url"));
+    kv_line("Format", field("format"));
+    kv_line("Events", &events);
+    kv_line("Signed", if flag("signed") { "yes" } else { "no" });
+    if field("format") == "pagerduty" {
+        let routing_key = if flag("has_routing_key") {
+            "set"
+        } else {
+            "missing"
+        };
+        kv_line("Routing key", routing_key);
     }
+    kv_line("Enabled", if flag("enabled") { "yes" } else { "no" });
+    outln!();
 }
 
-async fn cmd_notify(_client: &Client, action: NotifyCommands) -> Result<()> {
+// ── Secret Sync ──────────────────────────────────────────────────────
+
+async fn cmd_sync_github(client: &Client, action: GithubSyncCommands) -> Result<()> {
     match action {
-        NotifyCommands::SetWebhook { url, events } => {
-            println!();
-            header("🔔", "Configure Webhook");
-            println!();
-
-            let config = serde_json::json!({
-                "webhooks": [{
-                    "url": url,
-                    "events": events,
-                    "created_at": chrono_now_iso(),
-                }]
+        GithubSyncCommands::Set {
+            name,
+            repo,
+            environment,
+            token,
+            mappings,
+            api_url,
+            disabled,
+        } => {
+            let mappings = mappings
+                .iter()
+                .map(|m| sync_mapping(m))
+                .collect::<Result<Vec<_>>>()?;
+            let body = serde_json::json!({
+                "repository": repo,
+                "environment": environment,
+                "api_url": api_url,
+                "token": token,
+                "mappings": mappings,
+                "enabled": !disabled,
             });
-            save_webhook_config(&config)?;
-
-            success(&format!("Webhook configured: {url}"));
-            println!("  {DIM}Events: {}{RESET}", events.join(", "));
-            println!();
+            let resp = client
+                .post(&format!("/v1/sys/sync/github/{name}"), &body)
+                .await?;
+            outln!();
+            success(&format!("GitHub sync {name} configured"));
+            outln!("  {DIM}Run: zvault sync github run {name}{RESET}");
+            outln!();
+            print_github_sync(&resp);
             Ok(())
         }
-        NotifyCommands::GetWebhook => {
-            println!();
-            header("🔔", "Webhook Configuration");
-            println!();
-
-            let config = load_webhook_config()?;
-            let webhooks = config
-                .get("webhooks")
-                .and_then(|v| v.as_array())
-                .cloned()
-                .unwrap_or_default();
-
-            if webhooks.is_empty() {
-                println!("  {DIM}No webhooks configured.{RESET}");
-                println!("  {DIM}Run: zvault notify set-webhook <url>{RESET}");
-            } else {
-                for wh in &webhooks {
-                    let url = wh.get("url").and_then(|v| v.as_str()).unwrap_or("-");
-                    let events = wh
-                        .get("events")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| {
-                            arr.iter()
-                                .filter_map(|v| v.as_str())
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        })
-                        .unwrap_or_default();
-                    kv_line("URL", url);
-                    kv_line("Events", &events);
-                }
+        GithubSyncCommands::Get { name } => {
+            let syncs = match name {
+                Some(name) => vec![client.get(&format!("/v1/sys/sync/github/{name}")).await?],
+                None => client
+                    .get("/v1/sys/sync/github")
+                    .await?
+                    .get("syncs")
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            outln!();
+            if syncs.is_empty() {
+                header("🔁", "GitHub Syncs");
+                outln!("  {DIM}No GitHub syncs configured.{RESET}");
+                outln!(
+                    "  {DIM}Run: zvault sync github set <name> --repo <owner/repo> --map <secret>{RESET}"
+                );
+                outln!();
+            }
+            for sync in &syncs {
+                print_github_sync(sync);
             }
-            println!();
             Ok(())
         }
-        NotifyCommands::RemoveWebhook => {
-            println!();
-            let config = serde_json::json!({ "webhooks": [] });
-            save_webhook_config(&config)?;
-            success("Webhook configuration removed");
-            println!();
+        GithubSyncCommands::Remove { name } => {
+            client
+                .delete(&format!("/v1/sys/sync/github/{name}"))
+                .await?;
+            outln!();
+            success(&format!("GitHub sync {name} removed"));
+            outln!();
             Ok(())
         }
-        NotifyCommands::Test => {
-            println!();
-            header("🔔", "Test Webhook");
-            println!();
-
-            let config = load_webhook_config()?;
-            let webhooks = config
-                .get("webhooks")
-                .and_then(|v| v.as_array())
-                .cloned()
-                .unwrap_or_default();
-
-            if webhooks.is_empty() {
-                warning("No webhooks configured. Run: zvault notify set-webhook <url>");
-                println!();
-                return Ok(());
-            }
+        GithubSyncCommands::Run { name, force } => {
+            let resp = client
+                .post(
+                    &format!("/v1/sys/sync/github/{name}/run"),
+                    &serde_json::json!({ "force": force }),
+                )
+                .await?;
+            print_sync_report(&name, &resp);
+            Ok(())
+        }
+        GithubSyncCommands::Drift { name } => {
+            let resp = client
+                .get(&format!("/v1/sys/sync/github/{name}/drift"))
+                .await?;
+            print_sync_drift(&name, "GITHUB SECRET", &resp);
+            Ok(())
+        }
+    }
+}
 
-            let payload = serde_json::json!({
-                "event": "test",
-                "message": "ZVault webhook test notification",
-                "timestamp": chrono_now_iso(),
-                "vault": "zvault"
+async fn cmd_sync_kubernetes(client: &Client, action: KubernetesSyncCommands) -> Result<()> {
+    match action {
+        KubernetesSyncCommands::Set {
+            name,
+            namespace,
+            secret_name,
+            mappings,
+            labels,
+            api_url,
+            token,
+            ca_cert,
+            disabled,
+        } => {
+            let mappings = mappings
+                .iter()
+                .map(|m| sync_mapping(m))
+                .collect::<Result<Vec<_>>>()?;
+            let ca_cert = ca_cert
+                .map(|path| {
+                    std::fs::read_to_string(&path)
+                        .with_context(|| format!("failed to read {}", path.display()))
+                })
+                .transpose()?;
+            let body = serde_json::json!({
+                "namespace": namespace,
+                "secret_name": secret_name,
+                "api_url": api_url,
+                "token": token,
+                "ca_cert": ca_cert,
+                "labels": parse_kv_pairs(&labels)?,
+                "mappings": mappings,
+                "enabled": !disabled,
             });
-
-            for wh in &webhooks {
-                let url = wh.get("url").and_then(|v| v.as_str()).unwrap_or("");
-                if url.is_empty() {
-                    continue;
-                }
-                print!("  Sending to {url}... ");
-                match send_webhook(url, &payload).await {
-                    Ok(()) => println!("{GREEN}ok{RESET}"),
-                    Err(e) => println!("{RED}failed: {e}{RESET}"),
-                }
+            let resp = client
+                .post(&format!("/v1/sys/sync/kubernetes/{name}"), &body)
+                .await?;
+            outln!();
+            success(&format!("Kubernetes sync {name} configured"));
+            outln!("  {DIM}Run: zvault sync kubernetes run {name}{RESET}");
+            outln!();
+            print_kubernetes_sync(&resp);
+            Ok(())
+        }
+        KubernetesSyncCommands::Get { name } => {
+            let syncs = match name {
+                Some(name) => vec![
+                    client
+                        .get(&format!("/v1/sys/sync/kubernetes/{name}"))
+                        .await?,
+                ],
+                None => client
+                    .get("/v1/sys/sync/kubernetes")
+                    .await?
+                    .get("syncs")
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            outln!();
+            if syncs.is_empty() {
+                header("☸", "Kubernetes Syncs");
+                outln!("  {DIM}No Kubernetes syncs configured.{RESET}");
+                outln!(
+                    "  {DIM}Run: zvault sync kubernetes set <name> --namespace <ns> --secret <name> --map <secret>{RESET}"
+                );
+                outln!();
+            }
+            for sync in &syncs {
+                print_kubernetes_sync(sync);
             }
-            println!();
+            Ok(())
+        }
+        KubernetesSyncCommands::Remove { name } => {
+            client
+                .delete(&format!("/v1/sys/sync/kubernetes/{name}"))
+                .await?;
+            outln!();
+            success(&format!("Kubernetes sync {name} removed"));
+            outln!();
+            Ok(())
+        }
+        KubernetesSyncCommands::Run { name, force } => {
+            let resp = client
+                .post(
+                    &format!("/v1/sys/sync/kubernetes/{name}/run"),
+                    &serde_json::json!({ "force": force }),
+                )
+                .await?;
+            print_sync_report(&name, &resp);
+            Ok(())
+        }
+        KubernetesSyncCommands::Drift { name } => {
+            let resp = client
+                .get(&format!("/v1/sys/sync/kubernetes/{name}/drift"))
+                .await?;
+            print_sync_drift(&name, "SECRET KEY", &resp);
             Ok(())
         }
     }
 }
 
-// ── Phase 3.2: Secret Rotation ───────────────────────────────────────
-
-const ROTATION_CONFIG_PATH: &str = ".zvault/rotation.json";
+/// Parse `--map <mount>/<path>[#field][=NAME]` into a sync mapping.
+fn sync_mapping(spec: &str) -> Result<Value> {
+    let (source, name) = match spec.rsplit_once('=') {
+        Some((source, name)) => (source, Some(name)),
+        None => (spec, None),
+    };
+    let (secret, field) = match source.split_once('#') {
+        Some((secret, field)) => (secret, Some(field)),
+        None => (source, None),
+    };
+    let Some((mount, path)) = secret
+        .split_once('/')
+        .filter(|(m, p)| !m.is_empty() && !p.is_empty())
+    else {
+        bail!(
+            "invalid --map '{spec}': expected <mount>/<path>[#field][=NAME], e.g. secret/ci/deploy#token=DEPLOY_TOKEN"
+        );
+    };
+    Ok(serde_json::json!({
+        "mount": format!("{mount}/"),
+        "path": path,
+        "field": field,
+        "name": name,
+    }))
+}
 
-fn load_rotation_config() -> Result<serde_json::Value> {
-    let path = std::path::Path::new(ROTATION_CONFIG_PATH);
-    if !path.exists() {
-        return Ok(serde_json::json!({ "policies": {} }));
+fn print_github_sync(sync: &Value) {
+    let field = |key: &str| sync.get(key).and_then(Value::as_str).unwrap_or("-");
+    let list = |key: &str| {
+        sync.get(key)
+            .and_then(Value::as_array)
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default()
+    };
+    header("🔁", &format!("GitHub Sync {}", field("name")));
+    kv_line("Repository", field("repository"));
+    if let Some(environment) = sync.get("environment").and_then(Value::as_str) {
+        kv_line("Environment", environment);
     }
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| anyhow::anyhow!("failed to read rotation config: {e}"))?;
-    serde_json::from_str(&content).map_err(|e| anyhow::anyhow!("invalid rotation config: {e}"))
+    print_sync_mappings(sync);
+    kv_line("Secrets", &list("secrets"));
+    print_sync_status(sync);
 }
 
-fn save_rotation_config(config: &serde_json::Value) -> Result<()> {
-    ensure_zvault_dir()?;
-    let content = serde_json::to_string_pretty(config)
-        .map_err(|e| anyhow::anyhow!("failed to serialize rotation config: {e}"))?;
-    std::fs::write(ROTATION_CONFIG_PATH, content)
-        .map_err(|e| anyhow::anyhow!("failed to write rotation config: {e}"))?;
-    Ok(())
+fn print_kubernetes_sync(sync: &Value) {
+    let field = |key: &str| sync.get(key).and_then(Value::as_str).unwrap_or("-");
+    header("☸", &format!("Kubernetes Sync {}", field("name")));
+    kv_line(
+        "Secret",
+        &format!("{}/{}", field("namespace"), field("secret_name")),
+    );
+    kv_line(
+        "Cluster",
+        sync.get("api_url")
+            .and_then(Value::as_str)
+            .unwrap_or("in-cluster"),
+    );
+    for (key, value) in sync
+        .get("labels")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        kv_line("Label", &format!("{key}={}", value.as_str().unwrap_or("")));
+    }
+    print_sync_mappings(sync);
+    let keys = sync
+        .get("keys")
+        .and_then(Value::as_array)
+        .map(|arr| {
+            arr.iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+    kv_line("Keys", &keys);
+    kv_line("Hash", field("secret_hash"));
+    print_sync_status(sync);
 }
 
-async fn cmd_rotate_trigger(client: &Client, path: &str) -> Result<()> {
-    println!();
-    header("🔄", "Manual Rotation");
-    println!();
-
-    let parts: Vec<&str> = path.splitn(3, '/').collect();
-    if parts.len() < 3 {
-        return Err(anyhow::anyhow!(
-            "invalid path format — expected env/<project>/<key>"
-        ));
-    }
-
-    let api_path = format!("/v1/secret/data/{path}");
-    let resp = client.get(&api_path).await;
-    match resp {
-        Ok(_) => {
-            let mut config = load_rotation_config()?;
-            if let Some(policies) = config.get_mut("policies").and_then(|v| v.as_object_mut()) {
-                if let Some(policy) = policies.get_mut(path) {
-                    if let Some(obj) = policy.as_object_mut() {
-                        obj.insert(
-                            "last_rotated".to_owned(),
-                            serde_json::Value::String(chrono_now_iso()),
-                        );
-                    }
-                }
-            }
-            save_rotation_config(&config)?;
-
-            success(&format!("Rotation triggered for {path}"));
-            println!("  {DIM}The secret value should be updated by your rotation handler.{RESET}");
-            println!("  {DIM}Use `zvault kv put {path} value=<new_value>` to update.{RESET}");
-
-            let wh_config = load_webhook_config()?;
-            let webhooks = wh_config
-                .get("webhooks")
-                .and_then(|v| v.as_array())
-                .cloned()
-                .unwrap_or_default();
-            for wh in &webhooks {
-                let events = wh
-                    .get("events")
-                    .and_then(|v| v.as_array())
-                    .cloned()
-                    .unwrap_or_default();
-                let has_rotation = events.iter().any(|e| e.as_str() == Some("secret.rotated"));
-                if has_rotation {
-                    if let Some(url) = wh.get("url").and_then(|v| v.as_str()) {
-                        let payload = serde_json::json!({
-                            "event": "secret.rotated",
-                            "path": path,
-                            "timestamp": chrono_now_iso(),
-                            "vault": "zvault"
-                        });
-                        let _ = send_webhook(url, &payload).await;
-                    }
-                }
-            }
+fn print_sync_mappings(sync: &Value) {
+    for mapping in sync
+        .get("mappings")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let get = |key: &str| mapping.get(key).and_then(Value::as_str);
+        let mut source = format!(
+            "{}{}",
+            get("mount").unwrap_or(""),
+            get("path").unwrap_or("")
+        );
+        if let Some(f) = get("field") {
+            source = format!("{source}#{f}");
         }
-        Err(e) => {
-            return Err(anyhow::anyhow!("secret not found at {path}: {e}"));
+        if let Some(n) = get("name") {
+            source = format!("{source} → {n}");
         }
+        kv_line("Mapping", &source);
     }
-    println!();
-    Ok(())
 }
 
-fn cmd_rotate_list_policies(config: &serde_json::Value) {
-    let policies = config
-        .get("policies")
-        .and_then(|v| v.as_object())
-        .cloned()
+fn print_sync_status(sync: &Value) {
+    let field = |key: &str| sync.get(key).and_then(Value::as_str).unwrap_or("-");
+    let enabled = sync.get("enabled").and_then(Value::as_bool) == Some(true);
+    kv_line("Enabled", if enabled { "yes" } else { "no" });
+    kv_line("Last Synced", field("last_synced_at"));
+    if let Some(error) = sync.get("last_error").and_then(Value::as_str) {
+        outln!("  {RED}{error}{RESET}");
+        kv_line("Next Retry", field("next_retry_at"));
+    }
+    outln!();
+}
+
+fn print_sync_report(name: &str, resp: &Value) {
+    let pushed: Vec<&str> = resp
+        .get("pushed")
+        .and_then(Value::as_array)
+        .map(|arr| arr.iter().filter_map(Value::as_str).collect())
         .unwrap_or_default();
+    let unchanged = resp.get("unchanged").and_then(Value::as_u64).unwrap_or(0);
+    outln!();
+    success(&format!(
+        "Synced {name}: {} pushed, {unchanged} unchanged",
+        pushed.len()
+    ));
+    for secret in pushed {
+        outln!("  {DIM}↑{RESET} {secret}");
+    }
+    outln!();
+}
 
-    if policies.is_empty() {
-        println!("  {DIM}No rotation policies configured.{RESET}");
+fn print_sync_drift(name: &str, column: &str, resp: &Value) {
+    let secrets = resp
+        .get("secrets")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    outln!();
+    header("🔁", &format!("Drift: {name}"));
+    outln!();
+    if secrets.is_empty() {
+        outln!("  {DIM}No mapped secrets found in the vault.{RESET}");
     } else {
-        println!(
-            "  {DIM}{:<40}  {:<12}  {:<12}  LAST ROTATED{RESET}",
-            "PATH", "INTERVAL", "MAX AGE"
-        );
-        for (path, policy) in &policies {
-            let interval = policy
-                .get("interval_hours")
-                .and_then(serde_json::Value::as_u64)
-                .unwrap_or(0);
-            let max_age = policy
-                .get("max_age_hours")
-                .and_then(serde_json::Value::as_u64)
-                .map_or("-".to_owned(), |v| format!("{v}h"));
-            let last = policy
-                .get("last_rotated")
-                .and_then(|v| v.as_str())
-                .unwrap_or("never");
-            println!(
-                "  {:<40}  {:<12}  {:<12}  {}",
-                path,
-                format!("{interval}h"),
-                max_age,
-                last
+        outln!("  {DIM}{:<32}  {:<14}  SOURCE{RESET}", column, "STATUS");
+        for secret in &secrets {
+            let get = |key: &str| secret.get(key).and_then(Value::as_str).unwrap_or("-");
+            let status = get("status");
+            let color = if status == "in_sync" { DIM } else { RED };
+            outln!(
+                "  {:<32}  {color}{:<14}{RESET}  {}",
+                get("name"),
+                status.replace('_', " "),
+                get("source")
             );
         }
     }
+    outln!();
 }
 
-fn cmd_rotate_status(config: &serde_json::Value) {
-    let policies = config
-        .get("policies")
-        .and_then(|v| v.as_object())
-        .cloned()
-        .unwrap_or_default();
-
-    if policies.is_empty() {
-        println!("  {DIM}No rotation policies configured.{RESET}");
-        return;
-    }
+// ── Declarative apply ────────────────────────────────────────────────
 
-    println!("  {DIM}{:<40}  {:<12}  STATUS{RESET}", "PATH", "INTERVAL");
-    for (path, policy) in &policies {
-        let interval = policy
-            .get("interval_hours")
-            .and_then(serde_json::Value::as_u64)
-            .unwrap_or(0);
-        let last = policy.get("last_rotated").and_then(|v| v.as_str());
+async fn cmd_apply(
+    client: &Client,
+    file: &str,
+    dry_run: bool,
+    prune: bool,
+    confirm: Option<&str>,
+) -> Result<()> {
+    let config = apply::load(file)?;
 
-        let status = match last {
-            None | Some("never") => format!("{YELLOW}never rotated{RESET}"),
-            Some(_ts) => {
-                format!("{GREEN}ok{RESET}")
-            }
-        };
+    outln!();
+    header("📋", &format!("Plan: {file}"));
+    outln!();
+    let plan = apply::plan(client, &config, prune).await?;
+    plan.print();
+    outln!();
 
-        println!("  {:<40}  {:<12}  {}", path, format!("{interval}h"), status);
+    if plan.is_empty() || dry_run {
+        return Ok(());
+    }
+    let deletions = plan.deletions();
+    if deletions > 0 {
+        confirm_destructive(
+            &format!("delete {deletions} resource(s) not declared in {file}"),
+            file,
+            confirm,
+        )?;
     }
+
+    apply::execute(client, &plan).await?;
+    outln!();
+    success(&format!("Applied {file}"));
+    outln!();
+    Ok(())
 }
 
+// ── Secret Rotation ──────────────────────────────────────────────────
+
 async fn cmd_rotate(client: &Client, action: RotateCommands) -> Result<()> {
     match action {
         RotateCommands::SetPolicy {
             path,
-            interval_hours,
-            max_age_hours,
+            field,
+            rotator,
+            interval,
+            cron,
         } => {
-            println!();
-            header("🔄", "Set Rotation Policy");
-            println!();
-
-            let mut config = load_rotation_config()?;
-            let policies = config
-                .get_mut("policies")
-                .and_then(|v| v.as_object_mut())
-                .ok_or_else(|| anyhow::anyhow!("invalid rotation config"))?;
-
-            policies.insert(
-                path.clone(),
-                serde_json::json!({
-                    "interval_hours": interval_hours,
-                    "max_age_hours": max_age_hours,
-                    "created_at": chrono_now_iso(),
-                    "last_rotated": null,
-                }),
-            );
-
-            save_rotation_config(&config)?;
-            success(&format!(
-                "Rotation policy set for {path}: every {interval_hours}h"
-            ));
-            println!();
+            let body = serde_json::json!({
+                "field": field,
+                "rotator": rotator_body(*rotator)?,
+                "interval": interval,
+                "cron": cron,
+            });
+            let resp = client
+                .post(&format!("/v1/sys/rotation/policies/{path}"), &body)
+                .await?;
+            outln!();
+            success(&format!("Rotation policy set for {path}"));
+            outln!();
+            print_rotation_policy(&resp);
             Ok(())
         }
         RotateCommands::GetPolicy { path } => {
-            println!();
-            header("🔄", "Rotation Policy");
-            println!();
-
-            let config = load_rotation_config()?;
-            let policy = config.get("policies").and_then(|v| v.get(&path));
-
-            match policy {
-                Some(p) => {
-                    kv_line("Path", &path);
-                    let interval = p
-                        .get("interval_hours")
-                        .and_then(serde_json::Value::as_u64)
-                        .unwrap_or(0);
-                    kv_line("Interval", &format!("{interval}h"));
-                    let max_age = p.get("max_age_hours").and_then(serde_json::Value::as_u64);
-                    kv_line(
-                        "Max Age",
-                        &max_age.map_or("none".to_owned(), |v| format!("{v}h")),
-                    );
-                    let last = p
-                        .get("last_rotated")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("never");
-                    kv_line("Last Rotated", last);
-                }
-                None => {
-                    println!("  {DIM}No rotation policy for {path}{RESET}");
-                }
-            }
-            println!();
+            let resp = client
+                .get(&format!("/v1/sys/rotation/policies/{path}"))
+                .await?;
+            outln!();
+            print_rotation_policy(&resp);
             Ok(())
         }
         RotateCommands::ListPolicies => {
-            println!();
-            header("🔄", "Rotation Policies");
-            println!();
-
-            let config = load_rotation_config()?;
-            cmd_rotate_list_policies(&config);
-            println!();
+            print_rotation_list(&rotation_policies(client).await?);
             Ok(())
         }
         RotateCommands::RemovePolicy { path } => {
-            println!();
-            let mut config = load_rotation_config()?;
-            if let Some(policies) = config.get_mut("policies").and_then(|v| v.as_object_mut()) {
-                policies.remove(&path);
-            }
-            save_rotation_config(&config)?;
+            client
+                .delete(&format!("/v1/sys/rotation/policies/{path}"))
+                .await?;
+            outln!();
             success(&format!("Rotation policy removed for {path}"));
-            println!();
+            outln!();
+            Ok(())
+        }
+        RotateCommands::Trigger { path } => {
+            let resp = client
+                .post_no_body(&format!("/v1/sys/rotation/rotate/{path}"))
+                .await?;
+            let version = resp.get("version").and_then(Value::as_u64).unwrap_or(0);
+            outln!();
+            success(&format!("Rotated {path} to version {version}"));
+            if let Some(next) = resp.get("next_rotation").and_then(Value::as_str) {
+                kv_line("Next Rotation", next);
+            }
+            outln!();
             Ok(())
         }
-        RotateCommands::Trigger { path } => cmd_rotate_trigger(client, &path).await,
         RotateCommands::Status => {
-            println!();
-            header("🔄", "Rotation Status");
-            println!();
-
-            let config = load_rotation_config()?;
-            cmd_rotate_status(&config);
-            println!();
+            print_rotation_status(&rotation_policies(client).await?);
             Ok(())
         }
     }
 }
 
+fn rotator_body(args: RotatorArgs) -> Result<Value> {
+    Ok(match args.kind.as_str() {
+        "database" => {
+            let role = args
+                .role
+                .context("--role is required for the database rotator")?;
+            serde_json::json!({ "type": "database", "mount": args.db_mount, "role": role })
+        }
+        "webhook" => {
+            let url = args
+                .url
+                .context("--url is required for the webhook rotator")?;
+            serde_json::json!({ "type": "webhook", "url": url, "secret": args.secret })
+        }
+        _ => serde_json::json!({
+            "type": "random",
+            "length": args.length,
+            "charset": args.charset,
+        }),
+    })
+}
+
+fn print_rotation_list(policies: &[Value]) {
+    outln!();
+    header("🔄", "Rotation Policies");
+    outln!();
+    if policies.is_empty() {
+        outln!("  {DIM}No rotation policies configured.{RESET}");
+    } else {
+        outln!(
+            "  {DIM}{:<40}  {:<10}  {:<18}  NEXT ROTATION{RESET}",
+            "SECRET",
+            "ROTATOR",
+            "SCHEDULE"
+        );
+        for policy in policies {
+            let field = |pointer: &str| {
+                policy
+                    .pointer(pointer)
+                    .and_then(Value::as_str)
+                    .unwrap_or("-")
+            };
+            outln!(
+                "  {:<40}  {:<10}  {:<18}  {}",
+                field("/id"),
+                field("/rotator/type"),
+                schedule_label(policy),
+                field("/next_rotation")
+            );
+        }
+    }
+    outln!();
+}
+
+fn print_rotation_status(policies: &[Value]) {
+    outln!();
+    header("🔄", "Rotation Status");
+    outln!();
+    if policies.is_empty() {
+        outln!("  {DIM}No rotation policies configured.{RESET}");
+    } else {
+        outln!(
+            "  {DIM}{:<40}  {:<26}  STATUS{RESET}",
+            "SECRET",
+            "LAST ROTATED"
+        );
+        for policy in policies {
+            let id = policy.get("id").and_then(Value::as_str).unwrap_or("-");
+            let last = policy
+                .get("last_rotated")
+                .and_then(Value::as_str)
+                .unwrap_or("never");
+            let status = match policy.get("last_error").and_then(Value::as_str) {
+                Some(error) => format!("{RED}failing: {error}{RESET}"),
+                None if last == "never" => format!("{YELLOW}never rotated{RESET}"),
+                None => format!("{GREEN}ok{RESET}"),
+            };
+            outln!("  {id:<40}  {last:<26}  {status}");
+        }
+    }
+    outln!();
+}
+
+async fn rotation_policies(client: &Client) -> Result<Vec<Value>> {
+    Ok(client
+        .get("/v1/sys/rotation/policies")
+        .await?
+        .get("policies")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn schedule_label(policy: &Value) -> String {
+    let schedule = policy.get("schedule");
+    if let Some(secs) = schedule
+        .and_then(|s| s.get("interval"))
+        .and_then(Value::as_u64)
+    {
+        if secs % 86_400 == 0 {
+            format!("every {}d", secs / 86_400)
+        } else if secs % 3600 == 0 {
+            format!("every {}h", secs / 3600)
+        } else {
+            format!("every {secs}s")
+        }
+    } else {
+        schedule
+            .and_then(|s| s.get("cron"))
+            .and_then(Value::as_str)
+            .unwrap_or("-")
+            .to_owned()
+    }
+}
+
+fn print_rotation_policy(policy: &Value) {
+    let field = |pointer: &str| {
+        policy
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .unwrap_or("-")
+    };
+    header("🔄", &format!("Rotation Policy {}", field("/id")));
+    kv_line("Field", field("/field"));
+    kv_line("Rotator", field("/rotator/type"));
+    match field("/rotator/type") {
+        "database" => kv_line(
+            "Static Role",
+            &format!("{}{}", field("/rotator/mount"), field("/rotator/role")),
+        ),
+        "webhook" => kv_line("URL", field("/rotator/url")),
+        _ => kv_line(
+            "Generates",
+            &format!(
+                "{} {} characters",
+                policy
+                    .pointer("/rotator/length")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0),
+                field("/rotator/charset")
+            ),
+        ),
+    }
+    kv_line("Schedule", &schedule_label(policy));
+    kv_line("Next Rotation", field("/next_rotation"));
+    kv_line(
+        "Last Rotated",
+        policy
+            .get("last_rotated")
+            .and_then(Value::as_str)
+            .unwrap_or("never"),
+    );
+    if let Some(error) = policy.get("last_error").and_then(Value::as_str) {
+        kv_line("Last Error", &format!("{RED}{error}{RESET}"));
+    }
+    outln!();
+}
+
 // ── Login command ─────────────────────────────────────────────────────
 
 async fn cmd_login(client: &Client, oidc: bool) -> Result<()> {
@@ -3054,9 +7081,9 @@ async fn cmd_login(client: &Client, oidc: bool) -> Result<()> {
         return cloud::cmd_cloud_login().await;
     }
 
-    println!();
+    outln!();
     header("🔐", "OIDC Login");
-    println!();
+    outln!();
 
     // Check if OIDC is configured on the server.
     let config_resp = client.get_no_auth("/v1/auth/oidc/config").await?;
@@ -3070,10 +7097,10 @@ async fn cmd_login(client: &Client, oidc: bool) -> Result<()> {
     }
 
     let login_url = format!("{}/v1/auth/oidc/login", client.addr);
-    println!("  {DIM}Opening browser for authentication...{RESET}");
-    println!();
-    println!("  {CYAN}{login_url}{RESET}");
-    println!();
+    outln!("  {DIM}Opening browser for authentication...{RESET}");
+    outln!();
+    outln!("  {CYAN}{login_url}{RESET}");
+    outln!();
 
     // Try to open the browser.
     #[cfg(target_os = "macos")]
@@ -3093,21 +7120,29 @@ async fn cmd_login(client: &Client, oidc: bool) -> Result<()> {
             .spawn();
     }
 
-    println!("  {DIM}After authenticating, copy the vault token from the dashboard{RESET}");
-    println!("  {DIM}and set it with:{RESET}");
-    println!();
-    println!("    {CYAN}export VAULT_TOKEN=<your-token>{RESET}");
-    println!();
+    outln!("  {DIM}After authenticating, copy the vault token from the dashboard{RESET}");
+    outln!("  {DIM}and set it with:{RESET}");
+    outln!();
+    outln!("    {CYAN}export VAULT_TOKEN=<your-token>{RESET}");
+    outln!();
 
     Ok(())
 }
 
 // ── Backup command ───────────────────────────────────────────────────
 
-async fn cmd_backup(client: &Client, output: Option<&str>) -> Result<()> {
-    println!();
+async fn cmd_backup(
+    client: &Client,
+    output: Option<&str>,
+    action: Option<BackupCommands>,
+) -> Result<()> {
+    if let Some(BackupCommands::Inspect { file, depth }) = action {
+        return cmd_backup_inspect(&file, depth);
+    }
+
+    outln!();
     header("💾", "Vault Backup");
-    println!();
+    outln!();
 
     let resp = client.get_no_auth("/v1/sys/backup").await?;
 
@@ -3130,55 +7165,149 @@ async fn cmd_backup(client: &Client, output: Option<&str>) -> Result<()> {
             success(&format!("Backup saved to {BOLD}{path}{RESET}"));
         }
         None => {
-            println!("{content}");
+            outln!("{content}");
         }
     }
 
-    println!();
+    outln!();
     kv_line("Entries", &entry_count.to_string());
     kv_line("Created", created_at);
     kv_line("Version", version);
-    println!();
+    outln!();
 
     if output.is_some() {
-        println!("  {YELLOW}⚠  The backup contains encrypted data. Keep it safe.{RESET}");
-        println!("  {DIM}Restore with: zvault restore <backup-file>{RESET}");
-        println!();
+        outln!("  {YELLOW}⚠  The backup contains encrypted data. Keep it safe.{RESET}");
+        outln!("  {DIM}Restore with: zvault restore <backup-file>{RESET}");
+        outln!();
     }
 
     Ok(())
 }
 
-// ── Restore command ──────────────────────────────────────────────────
-
-async fn cmd_restore(client: &Client, file: &str) -> Result<()> {
-    println!();
-    header("💾", "Vault Restore");
-    println!();
-
+/// Read a backup file and decode its snapshot into `(key, ciphertext)`
+/// pairs, with the ciphertext left base64-encoded.
+fn read_backup_file(file: &str) -> Result<(Value, Vec<(String, String)>)> {
     let content = std::fs::read_to_string(file)
         .with_context(|| format!("failed to read backup file: {file}"))?;
-
     let backup: Value = serde_json::from_str(&content).context("invalid backup file format")?;
-
     let snapshot = backup
         .get("snapshot")
         .and_then(Value::as_str)
         .ok_or_else(|| anyhow::anyhow!("backup file missing 'snapshot' field"))?;
+    let decoded =
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, snapshot.trim())
+            .context("backup snapshot is not valid base64")?;
+    let entries: Vec<Value> =
+        serde_json::from_slice(&decoded).context("backup snapshot is not a list of entries")?;
+    let entries = entries
+        .into_iter()
+        .map(|entry| {
+            let key = entry.get("key").and_then(Value::as_str);
+            let value = entry.get("value").and_then(Value::as_str);
+            match (key, value) {
+                (Some(key), Some(value)) => Ok((key.to_owned(), value.to_owned())),
+                _ => bail!("backup snapshot entry is missing 'key' or 'value'"),
+            }
+        })
+        .collect::<Result<_>>()?;
+    Ok((backup, entries))
+}
 
-    let entry_count = backup
-        .get("entry_count")
-        .and_then(Value::as_u64)
-        .unwrap_or(0);
+/// Group key for `backup inspect`: the first `depth` segments of `key`.
+fn key_group(key: &str, depth: usize) -> String {
+    let segments: Vec<&str> = key.split('/').collect();
+    if segments.len() > depth {
+        format!("{}/", segments[..depth].join("/"))
+    } else {
+        key.to_owned()
+    }
+}
 
-    println!("  {DIM}Backup contains {entry_count} entries{RESET}");
-    println!("  {YELLOW}⚠  This will overwrite existing vault data.{RESET}");
-    println!();
+fn cmd_backup_inspect(file: &str, depth: usize) -> Result<()> {
+    let (backup, entries) = read_backup_file(file)?;
 
-    let body = serde_json::json!({ "snapshot": snapshot });
-    let resp = client.post_no_auth("/v1/sys/restore", &body).await?;
+    outln!();
+    header("🔍", "Backup Contents");
+    outln!();
+    kv_line("File", file);
+    kv_line(
+        "Version",
+        backup
+            .get("version")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown"),
+    );
+    kv_line(
+        "Created",
+        backup
+            .get("created_at")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown"),
+    );
+    kv_line("Entries", &entries.len().to_string());
+    if let Some(declared) = backup.get("entry_count").and_then(Value::as_u64) {
+        if usize::try_from(declared).ok() != Some(entries.len()) {
+            warning(&format!(
+                "Header declares {declared} entries but the snapshot holds {}",
+                entries.len()
+            ));
+        }
+    }
+    outln!();
+
+    let mut groups: BTreeMap<String, usize> = BTreeMap::new();
+    for (key, _) in &entries {
+        *groups.entry(key_group(key, depth.max(1))).or_default() += 1;
+    }
+    let width = groups.keys().map(String::len).max().unwrap_or(0);
+    for (group, count) in &groups {
+        outln!("  {CYAN}{group:<width$}{RESET}  {count}");
+    }
+    outln!();
+    Ok(())
+}
+
+// ── Restore command ──────────────────────────────────────────────────
+
+async fn cmd_restore(
+    client: &Client,
+    file: &str,
+    prefix: Option<&str>,
+    confirm: Option<&str>,
+) -> Result<()> {
+    confirm_destructive("overwrite existing vault data", file, confirm)?;
+
+    outln!();
+    header("💾", "Vault Restore");
+    outln!();
+
+    let (backup, entries) = read_backup_file(file)?;
+    let snapshot = backup
+        .get("snapshot")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    outln!("  {DIM}Backup contains {} entries{RESET}", entries.len());
+    if let Some(prefix) = prefix {
+        let matching = entries
+            .iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .count();
+        outln!("  {DIM}{matching} of them match prefix {prefix}{RESET}");
+        if matching == 0 {
+            bail!("no backup entries match prefix {prefix}");
+        }
+        outln!("  {YELLOW}⚠  This will overwrite existing vault data under {prefix}.{RESET}");
+    } else {
+        outln!("  {YELLOW}⚠  This will overwrite existing vault data.{RESET}");
+    }
+    outln!();
+
+    let body = serde_json::json!({ "snapshot": snapshot, "prefix": prefix });
+    let resp = client.post("/v1/sys/restore", &body).await?;
 
     let restored = resp.get("entry_count").and_then(Value::as_u64).unwrap_or(0);
+    let skipped = resp.get("skipped").and_then(Value::as_u64).unwrap_or(0);
     let ok = resp
         .get("success")
         .and_then(Value::as_bool)
@@ -3186,73 +7315,290 @@ async fn cmd_restore(client: &Client, file: &str) -> Result<()> {
 
     if ok {
         success(&format!("Restored {restored} entries from backup"));
-        println!();
-        println!("  {DIM}Seal and re-unseal the vault to pick up restored state:{RESET}");
-        println!("    {CYAN}zvault seal{RESET}");
-        println!("    {CYAN}zvault unseal --share <share>{RESET}");
+        if skipped > 0 {
+            outln!("  {DIM}Skipped {skipped} entries outside the prefix{RESET}");
+        }
+        outln!();
+        outln!("  {DIM}Seal and re-unseal the vault to pick up restored state:{RESET}");
+        outln!("    {CYAN}zvault seal{RESET}");
+        outln!("    {CYAN}zvault unseal --share <share>{RESET}");
     } else {
-        println!("  {RED}Restore failed{RESET}");
+        outln!("  {RED}Restore failed{RESET}");
     }
 
-    println!();
+    outln!();
     Ok(())
 }
 
-/// Get current time as ISO 8601 string (no chrono dependency — use simple approach).
-fn chrono_now_iso() -> String {
-    // We don't have chrono in CLI deps, so use a simple approach.
-    // Format: 2026-02-11T12:00:00Z
-    let now = std::time::SystemTime::now();
-    let duration = now
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = duration.as_secs();
-    // Simple UTC timestamp formatting.
-    let days = secs / 86400;
-    let time_secs = secs % 86400;
-    let hours = time_secs / 3600;
-    let minutes = (time_secs % 3600) / 60;
-    let seconds = time_secs % 60;
+// ── Raw API commands ─────────────────────────────────────────────────
+
+async fn cmd_api(client: &Client, method: &str, path: &str, data: Option<&str>) -> Result<()> {
+    let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+        .with_context(|| format!("invalid method {method}"))?;
+    let body = data.map(read_api_body).transpose()?;
+    let resp = client
+        .request(method, &api_path(path), body.as_ref())
+        .await?;
 
-    // Calculate year/month/day from days since epoch (1970-01-01).
-    let (year, month, day) = days_to_ymd(days);
+    let status = resp.status();
+    let text = resp.text().await.context("failed to read response body")?;
+    if status.is_success() {
+        match serde_json::from_str::<Value>(&text) {
+            Ok(value) => print_json(&value),
+            Err(_) if text.is_empty() => {}
+            Err(_) => outln!("{text}"),
+        }
+        return Ok(());
+    }
 
-    format!("{year:04}-{month:02}-{day:02}T{hours:02}:{minutes:02}:{seconds:02}Z")
+    let error = ApiError::from_body(status, &text);
+    print_json(&error.to_json());
+    Err(error.into())
 }
 
-/// Convert days since Unix epoch to (year, month, day).
-///
-/// Uses Howard Hinnant's civil calendar algorithm. All arithmetic is
-/// mathematically proven to stay within `u64` bounds for any valid Unix
-/// timestamp (up to year ~5.8 million), so `saturating_*` is used purely
-/// as a defensive measure per project coding standards.
-fn days_to_ymd(days: u64) -> (u64, u64, u64) {
-    let z = days.saturating_add(719_468);
-    let era = z / 146_097;
-    let doe = z.saturating_sub(era.saturating_mul(146_097));
-    let yoe = (doe
-        .saturating_sub(doe / 1460)
-        .saturating_add(doe / 36524)
-        .saturating_sub(doe / 146_096))
-        / 365;
-    let y = yoe.saturating_add(era.saturating_mul(400));
-    let doy = doe.saturating_sub(
-        365u64
-            .saturating_mul(yoe)
-            .saturating_add(yoe / 4)
-            .saturating_sub(yoe / 100),
-    );
-    let mp = (5u64.saturating_mul(doy).saturating_add(2)) / 153;
-    let d = doy
-        .saturating_sub((153u64.saturating_mul(mp).saturating_add(2)) / 5)
-        .saturating_add(1);
-    let m = if mp < 10 {
-        mp.saturating_add(3)
+/// Normalize a path to `/v1/...`, accepting it with or without the prefix.
+fn api_path(path: &str) -> String {
+    let path = path.trim_start_matches('/');
+    if path.starts_with("v1/") {
+        format!("/{path}")
     } else {
-        mp.saturating_sub(9)
+        format!("/v1/{path}")
+    }
+}
+
+/// Parse `--data`: inline JSON, `@file`, or `@-` for stdin.
+fn read_api_body(data: &str) -> Result<Value> {
+    let raw = match data.strip_prefix('@') {
+        Some("-") => std::io::read_to_string(std::io::stdin()).context("failed to read stdin")?,
+        Some(file) => {
+            std::fs::read_to_string(file).with_context(|| format!("failed to read {file}"))?
+        }
+        None => data.to_owned(),
     };
-    let y = if m <= 2 { y.saturating_add(1) } else { y };
-    (y, m, d)
+    serde_json::from_str(&raw).context("request body is not valid JSON")
+}
+
+// ── Mount transfer commands ──────────────────────────────────────────
+
+#[allow(clippy::too_many_lines)]
+async fn cmd_mount(client: &Client, action: MountCommands) -> Result<()> {
+    match action {
+        MountCommands::Enable {
+            path,
+            engine_type,
+            description,
+            options,
+        } => {
+            let config = if options.is_empty() {
+                Value::Null
+            } else {
+                serde_json::to_value(parse_kv_pairs(&options)?)?
+            };
+            let body = serde_json::json!({
+                "engine_type": engine_type,
+                "description": description,
+                "config": config,
+            });
+            client
+                .post(&format!("/v1/sys/mounts/{path}"), &body)
+                .await?;
+            outln!();
+            success(&format!(
+                "Mounted {BOLD}{engine_type}{RESET} at {BOLD}{path}{RESET}"
+            ));
+            outln!();
+        }
+        MountCommands::Disable { path, purge } => {
+            let (query, note) = if purge {
+                ("?purge=true", " and purged its data")
+            } else {
+                ("", "")
+            };
+            client
+                .delete(&format!("/v1/sys/mounts/{path}{query}"))
+                .await?;
+            outln!();
+            success(&format!("Unmounted {BOLD}{path}{RESET}{note}"));
+            outln!();
+        }
+        MountCommands::Export {
+            path,
+            output,
+            transfer_key,
+        } => {
+            let body = serde_json::json!({ "transfer_key": transfer_key });
+            let resp = client
+                .post(&format!("/v1/sys/mounts/{path}/export"), &body)
+                .await?;
+            let bundle = resp
+                .get("bundle")
+                .ok_or_else(|| anyhow::anyhow!("server response missing 'bundle'"))?;
+            let content =
+                serde_json::to_string_pretty(bundle).unwrap_or_else(|_| bundle.to_string());
+            std::fs::write(&output, content)
+                .with_context(|| format!("failed to write bundle to {output}"))?;
+
+            outln!();
+            header("📤", &format!("Mount Export: {path}"));
+            let entries = bundle
+                .get("entry_count")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            kv_line("Entries", &entries.to_string());
+            kv_line("Bundle", &output);
+            if let Some(key) = resp.get("transfer_key").and_then(Value::as_str) {
+                outln!();
+                outln!("  {DIM}Transfer Key:{RESET}  {GREEN}{BOLD}{key}{RESET}");
+                outln!();
+                outln!(
+                    "  {YELLOW}⚠  Send the key separately from the bundle; it decrypts every secret.{RESET}"
+                );
+            }
+            outln!();
+            outln!(
+                "  {DIM}Import with: zvault mount import <path> -f {output} --transfer-key <key>{RESET}"
+            );
+            outln!();
+        }
+        MountCommands::Import {
+            path,
+            file,
+            transfer_key,
+        } => {
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("failed to read bundle file: {file}"))?;
+            let bundle: Value =
+                serde_json::from_str(&content).context("bundle file is not valid JSON")?;
+            let body = serde_json::json!({ "bundle": bundle, "transfer_key": transfer_key });
+            let resp = client
+                .post(&format!("/v1/sys/mounts/{path}/import"), &body)
+                .await?;
+
+            outln!();
+            let mounted = resp.get("path").and_then(Value::as_str).unwrap_or(&path);
+            let entries = resp.get("entry_count").and_then(Value::as_u64).unwrap_or(0);
+            success(&format!(
+                "Imported {entries} entries into {BOLD}{mounted}{RESET}"
+            ));
+            if let Some(source) = resp.get("source_path").and_then(Value::as_str) {
+                kv_line("Source Mount", source);
+            }
+            outln!();
+        }
+    }
+    Ok(())
+}
+
+// ── Plugin command dispatch ──────────────────────────────────────────
+
+async fn cmd_plugin(client: &Client, action: PluginCommands) -> Result<()> {
+    match action {
+        PluginCommands::Register {
+            name,
+            module,
+            sha256,
+            allowed_hosts,
+            description,
+        } => {
+            let body = serde_json::json!({
+                "module": module,
+                "sha256": sha256,
+                "allowed_hosts": allowed_hosts,
+                "description": description.unwrap_or_default(),
+            });
+            let resp = client
+                .post(&format!("/v1/sys/plugins/catalog/{name}"), &body)
+                .await?;
+            outln!();
+            success(&format!("Plugin {name} registered"));
+            outln!("  {DIM}Mount with: zvault mount enable <path> --type {name}{RESET}");
+            outln!();
+            print_plugin(&resp);
+        }
+        PluginCommands::Get { name } => {
+            let plugins = match name {
+                Some(name) => vec![
+                    client
+                        .get(&format!("/v1/sys/plugins/catalog/{name}"))
+                        .await?,
+                ],
+                None => client
+                    .get("/v1/sys/plugins/catalog")
+                    .await?
+                    .get("plugins")
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            outln!();
+            if plugins.is_empty() {
+                header("🧩", "Plugins");
+                outln!("  {DIM}No plugins registered.{RESET}");
+                outln!(
+                    "  {DIM}Run: zvault plugin register <name> --module <file> --sha256 <digest>{RESET}"
+                );
+                outln!();
+            }
+            for plugin in &plugins {
+                print_plugin(plugin);
+            }
+        }
+        PluginCommands::Deregister { name } => {
+            client
+                .delete(&format!("/v1/sys/plugins/catalog/{name}"))
+                .await?;
+            outln!();
+            success(&format!("Plugin {name} deregistered"));
+            outln!();
+        }
+        PluginCommands::Reload { name } => {
+            let resp = client
+                .post_no_body(&format!("/v1/sys/plugins/reload/{name}"))
+                .await?;
+            let mounts = json_str_list(&resp, "mounts");
+            outln!();
+            success(&format!("Plugin {name} reloaded"));
+            kv_line("Mounts", if mounts.is_empty() { "-" } else { &mounts });
+            outln!();
+        }
+    }
+    Ok(())
+}
+
+fn print_plugin(plugin: &Value) {
+    let field = |key: &str| plugin.get(key).and_then(Value::as_str).unwrap_or("-");
+    header("🧩", &format!("Plugin {}", field("name")));
+    kv_line("Module", field("module"));
+    kv_line("SHA-256", field("sha256"));
+    let allowed_hosts = json_str_list(plugin, "allowed_hosts");
+    if !allowed_hosts.is_empty() {
+        kv_line("Allowed hosts", &allowed_hosts);
+    }
+    if let Some(description) = plugin
+        .get("description")
+        .and_then(Value::as_str)
+        .filter(|d| !d.is_empty())
+    {
+        kv_line("Description", description);
+    }
+    let mounts = json_str_list(plugin, "mounts");
+    kv_line("Mounts", if mounts.is_empty() { "-" } else { &mounts });
+    kv_line("Registered", field("registered_at"));
+    outln!();
+}
+
+/// Join a JSON string array field with `, `.
+fn json_str_list(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|arr| {
+            arr.iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default()
 }
 
 // ── Cloud command dispatch ───────────────────────────────────────────
@@ -3265,9 +7611,11 @@ async fn cmd_cloud(_client: &Client, action: CloudCommands) -> Result<()> {
         CloudCommands::Push { file, env } => {
             cloud::cmd_cloud_push(file.as_deref(), env.as_deref()).await
         }
-        CloudCommands::Pull { env, output, format } => {
-            cloud::cmd_cloud_pull(env.as_deref(), output.as_deref(), &format).await
-        }
+        CloudCommands::Pull {
+            env,
+            output,
+            format,
+        } => cloud::cmd_cloud_pull(env.as_deref(), output.as_deref(), &format).await,
         CloudCommands::Status => cloud::cmd_cloud_status().await,
         CloudCommands::Envs => cloud::cmd_cloud_envs().await,
         CloudCommands::Secrets { env } => cloud::cmd_cloud_secrets(env.as_deref()).await,
@@ -3278,9 +7626,12 @@ async fn cmd_cloud(_client: &Client, action: CloudCommands) -> Result<()> {
 
 async fn cmd_cloud_token(action: CloudTokenCommands) -> Result<()> {
     match action {
-        CloudTokenCommands::Create { name, env, ttl } => {
-            cloud::cmd_cloud_token_create(&name, env.as_deref(), ttl.as_deref()).await
-        }
+        CloudTokenCommands::Create {
+            name,
+            env,
+            ttl,
+            write,
+        } => cloud::cmd_cloud_token_create(&name, env.as_deref(), ttl.as_deref(), write).await,
         CloudTokenCommands::Revoke { id } => cloud::cmd_cloud_token_revoke(&id).await,
         CloudTokenCommands::List => cloud::cmd_cloud_token_list().await,
     }
@@ -14,11 +14,15 @@
 
 use std::fmt::Write as _;
 use std::io::{self, BufRead, Write};
+use std::path::Path;
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
+use crate::mcp_audit::{self, Outcome};
+use crate::mcp_config::{McpConfig, Overrides, SessionSpec, ToolGate};
+
 // ── JSON-RPC 2.0 types ──────────────────────────────────────────────
 
 #[derive(Debug, Deserialize)]
@@ -141,7 +145,7 @@ impl VaultClient {
         }
         let body = resp.text().await.context("failed to read response")?;
         if !status.is_success() {
-            anyhow::bail!("vault returned {status}: {body}");
+            return Err(crate::api_error::ApiError::from_body(status, &body).into());
         }
         if body.is_empty() {
             return Ok(Value::Null);
@@ -1772,8 +1776,13 @@ async fn tool_run_command(client: &VaultClient, args: &Value) -> Result<String>
         env_vars.push((env_name.clone(), value));
     }
 
-    // Execute the command with secrets as env vars.
-    let child = tokio::process::Command::new("sh")
+    // Execute the command with secrets as env vars. It sees the server's own
+    // token, the session token if there is one, not the one it started with.
+    let mut cmd = tokio::process::Command::new("sh");
+    if let Some(token) = &client.token {
+        cmd.env("VAULT_TOKEN", token);
+    }
+    let child = cmd
         .arg("-c")
         .arg(command)
         .envs(env_vars.iter().map(|(k, v)| (k.as_str(), v.as_str())))
@@ -2417,7 +2426,59 @@ fn rpc_err(id: Value, code: i64, message: String) -> JsonRpcResponse {
 }
 
 /// Handle a single JSON-RPC request and return a response.
-async fn handle_request(client: &VaultClient, req: JsonRpcRequest) -> Option<JsonRpcResponse> {
+/// Record a tool call in the vault audit log.
+///
+/// Reporting needs a token; without one no tool can reach the vault
+/// either. A failed report is logged and does not fail the call, which has
+/// already run.
+async fn report_call(
+    client: &VaultClient,
+    tool: &str,
+    args: &Value,
+    outcome: Outcome,
+    result: &Value,
+) {
+    if client.token.is_none() {
+        return;
+    }
+    let error = match outcome {
+        Outcome::Success => None,
+        Outcome::Error | Outcome::Denied => result
+            .pointer("/content/0/text")
+            .and_then(Value::as_str)
+            .map(|text| text.strip_prefix("Error: ").unwrap_or(text)),
+    };
+    let event = mcp_audit::event(tool, args, outcome, error);
+    if let Err(e) = client.post("/v1/sys/audit-log/external", &event).await {
+        eprintln!("[zvault-mcp] failed to record {tool} in the audit log: {e:#}");
+    }
+}
+
+/// The tools this server exposes: the configured gate, plus the token's
+/// policies when the gate depends on them.
+struct Access {
+    gate: ToolGate,
+    policies: Option<Vec<String>>,
+}
+
+impl Access {
+    /// Why `tool` is hidden from `tools/list`, or `None`.
+    fn hides(&self, tool: &str) -> Option<String> {
+        self.gate.denies(tool, self.policies.as_deref())
+    }
+
+    /// Why this call is refused, or `None`.
+    fn refuses(&self, tool: &str, args: &Value) -> Option<String> {
+        self.hides(tool)
+            .or_else(|| self.gate.denies_call(tool, args))
+    }
+}
+
+async fn handle_request(
+    client: &VaultClient,
+    access: &Access,
+    req: JsonRpcRequest,
+) -> Option<JsonRpcResponse> {
     let id = req.id.clone().unwrap_or(Value::Null);
 
     match req.method.as_str() {
@@ -2441,7 +2502,10 @@ async fn handle_request(client: &VaultClient, req: JsonRpcRequest) -> Option<Jso
 
         // ── Tool listing ─────────────────────────────────────────
         "tools/list" => {
-            let tools = tool_definitions();
+            let tools: Vec<McpToolDefinition> = tool_definitions()
+                .into_iter()
+                .filter(|tool| access.hides(&tool.name).is_none())
+                .collect();
             Some(rpc_ok(id, json!({ "tools": tools })))
         }
 
@@ -2451,7 +2515,26 @@ async fn handle_request(client: &VaultClient, req: JsonRpcRequest) -> Option<Jso
             let tool_name = params.get("name").and_then(Value::as_str).unwrap_or("");
             let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
 
-            let result = dispatch_tool(client, tool_name, &arguments).await;
+            let (result, outcome) = if let Some(reason) = access.refuses(tool_name, &arguments) {
+                let refusal = json!({
+                    "content": [{
+                        "type": "text",
+                        "text": format!("Error: {reason}")
+                    }],
+                    "isError": true
+                });
+                (refusal, Outcome::Denied)
+            } else {
+                let result = dispatch_tool(client, tool_name, &arguments).await;
+                let failed = result.get("isError").and_then(Value::as_bool) == Some(true);
+                let outcome = if failed {
+                    Outcome::Error
+                } else {
+                    Outcome::Success
+                };
+                (result, outcome)
+            };
+            report_call(client, tool_name, &arguments, outcome, &result).await;
             Some(rpc_ok(id, result))
         }
 
@@ -2468,23 +2551,198 @@ async fn handle_request(client: &VaultClient, req: JsonRpcRequest) -> Option<Jso
     }
 }
 
+// ── Session token ────────────────────────────────────────────────────
+
+/// A short-lived child token the server uses instead of the user's token,
+/// renewed in the background until it is revoked.
+struct SessionToken {
+    token: String,
+    renewal: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl SessionToken {
+    /// Exchange `client`'s token for a child token with `spec`'s policies,
+    /// plus `default` so it can renew itself and `mcp-audit` so it can report
+    /// tool calls.
+    async fn create(client: &VaultClient, spec: &SessionSpec) -> Result<Self> {
+        let mut policies = spec.policies.clone();
+        for builtin in ["default", "mcp-audit"] {
+            if !policies.iter().any(|p| p == builtin) {
+                policies.push(builtin.to_owned());
+            }
+        }
+        let body = json!({
+            "policies": policies,
+            "ttl": spec.ttl,
+            "renewable": true,
+            "display_name": "mcp-session",
+            "metadata": { "source": "mcp" },
+        });
+        let resp = client
+            .post("/v1/auth/token/create", &body)
+            .await
+            .context("failed to create the MCP session token")?;
+        let token = resp
+            .get("client_token")
+            .and_then(Value::as_str)
+            .context("token response has no client_token")?
+            .to_owned();
+        let ttl = resp
+            .get("lease_duration")
+            .and_then(Value::as_u64)
+            .filter(|&secs| secs > 0)
+            .map(std::time::Duration::from_secs);
+        let renewal =
+            ttl.map(|ttl| tokio::spawn(renew_session(client.addr.clone(), token.clone(), ttl)));
+
+        eprintln!(
+            "[zvault-mcp] using a session token (policies: {}, ttl {})",
+            policies.join(", "),
+            spec.ttl
+        );
+        Ok(Self { token, renewal })
+    }
+
+    /// Stop renewing the token and revoke it.
+    async fn revoke(self, client: &VaultClient) {
+        if let Some(renewal) = self.renewal {
+            renewal.abort();
+        }
+        let body = json!({ "token": self.token });
+        match client.post("/v1/auth/token/revoke-self", &body).await {
+            Ok(_) => eprintln!("[zvault-mcp] session token revoked."),
+            Err(e) => eprintln!("[zvault-mcp] failed to revoke the session token: {e:#}"),
+        }
+    }
+}
+
+/// Renew the session `token` by `ttl` once two thirds of it have passed,
+/// retrying sooner after a failure.
+async fn renew_session(addr: String, token: String, ttl: std::time::Duration) {
+    let client = VaultClient::new(addr, Some(token.clone()));
+    let body = json!({ "token": token, "increment": format!("{}s", ttl.as_secs()) });
+    let mut wait = ttl * 2 / 3;
+    loop {
+        tokio::time::sleep(wait).await;
+        wait = match client.post("/v1/auth/token/renew-self", &body).await {
+            Ok(_) => ttl * 2 / 3,
+            Err(e) => {
+                eprintln!("[zvault-mcp] failed to renew the session token: {e:#}");
+                (ttl / 10).max(std::time::Duration::from_secs(1))
+            }
+        };
+    }
+}
+
+/// Wait for Ctrl-C or, on Unix, `SIGTERM`.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let Ok(mut term) =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        else {
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
 /// Entry point: run the MCP server on stdin/stdout.
 ///
 /// Reads newline-delimited JSON-RPC messages from stdin, dispatches them,
 /// and writes responses to stdout. Stderr is used for diagnostics only.
 ///
+/// Tools are gated by `config` (default `~/.zvault/mcp.toml`) and the
+/// `overrides` from flags. With session policies, `token` is exchanged for
+/// a short-lived child token that is renewed while the server runs and
+/// revoked when stdin closes or the server is signalled to stop.
+///
 /// # Errors
 ///
-/// Returns `Err` if stdin/stdout I/O fails.
-pub async fn run_mcp_server(addr: String, token: Option<String>) -> Result<()> {
-    let client = VaultClient::new(addr, token);
+/// Returns `Err` if the configuration is invalid, the session token cannot
+/// be created, or stdin/stdout I/O fails.
+pub async fn run_mcp_server(
+    addr: String,
+    token: Option<String>,
+    config: Option<&Path>,
+    overrides: &Overrides,
+) -> Result<()> {
+    let definitions = tool_definitions();
+    let known: Vec<&str> = definitions.iter().map(|tool| tool.name.as_str()).collect();
+    let McpConfig { gate, session } = McpConfig::load(config, overrides, &known)?;
+
+    let mut client = VaultClient::new(addr, token);
+    let session = match &session {
+        Some(spec) => {
+            let session = SessionToken::create(&client, spec).await?;
+            // From here on only the session token is used.
+            client.token = Some(session.token.clone());
+            Some(session)
+        }
+        None => None,
+    };
 
+    // Policies are looked up once: the token is fixed for the session. A
+    // failed lookup leaves the token with no policies, so no tools.
+    let policies = if gate.gates_by_policy() {
+        match client.get("/v1/sys/identity/lookup/self").await {
+            Ok(body) => Some(
+                body.get("policies")
+                    .and_then(Value::as_array)
+                    .map(|policies| {
+                        policies
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .map(str::to_owned)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+            Err(e) => {
+                eprintln!("[zvault-mcp] failed to look up the token's policies: {e:#}");
+                Some(Vec::new())
+            }
+        }
+    } else {
+        None
+    };
+    let access = Access { gate, policies };
+
+    let available = known
+        .iter()
+        .filter(|tool| access.hides(tool).is_none())
+        .count();
+    eprintln!(
+        "[zvault-mcp] {available} of {} tools available{}",
+        known.len(),
+        if access.gate.read_only {
+            " (read-only)"
+        } else {
+            ""
+        }
+    );
     eprintln!("[zvault-mcp] server started, reading from stdin...");
 
-    // Read stdin on a blocking thread so async vault HTTP calls can proceed.
+    let result = serve(&client, &access).await;
+    if let Some(session) = session {
+        session.revoke(&client).await;
+    }
+    result
+}
+
+/// Answer JSON-RPC requests until stdin closes or a shutdown signal.
+async fn serve(client: &VaultClient, access: &Access) -> Result<()> {
+    // Read stdin on a plain thread so async vault HTTP calls can proceed. A
+    // blocking task would hold up runtime shutdown after a signal.
     let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(32);
 
-    tokio::task::spawn_blocking(move || {
+    std::thread::spawn(move || {
         let stdin = io::stdin();
         let reader = stdin.lock();
         for line_result in reader.lines() {
@@ -2500,8 +2758,20 @@ pub async fn run_mcp_server(addr: String, token: Option<String>) -> Result<()> {
     });
 
     let mut stdout = io::stdout().lock();
-
-    while let Some(line) = rx.recv().await {
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        let line = tokio::select! {
+            line = rx.recv() => match line {
+                Some(line) => line,
+                None => break,
+            },
+            () = &mut shutdown => {
+                eprintln!("[zvault-mcp] signalled, shutting down.");
+                return Ok(());
+            }
+        };
         let trimmed = line.trim();
         if trimmed.is_empty() {
             continue;
@@ -2519,7 +2789,7 @@ pub async fn run_mcp_server(addr: String, token: Option<String>) -> Result<()> {
             }
         };
 
-        if let Some(resp) = handle_request(&client, req).await {
+        if let Some(resp) = handle_request(client, access, req).await {
             let out = serde_json::to_string(&resp).context("failed to serialize response")?;
             writeln!(stdout, "{out}").context("failed to write to stdout")?;
             stdout.flush().context("failed to flush stdout")?;
@@ -0,0 +1,109 @@
+//! Audit events for MCP tool calls.
+//!
+//! Each `tools/call` is reported to `/v1/sys/audit-log/external` so AI-driven
+//! access lands in the same audit trail as direct API access. Arguments that
+//! carry secret values — a secret being set, an S3 object body, HTTP bodies
+//! and header values, Redis values — are replaced before the event leaves
+//! the process.
+
+use serde_json::{Value, json};
+
+/// Placeholder for a redacted argument.
+const REDACTED: &str = "[redacted]";
+
+/// How a tool call ended.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Outcome {
+    Success,
+    Error,
+    Denied,
+}
+
+impl Outcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Denied => "denied",
+        }
+    }
+}
+
+/// The `/v1/sys/audit-log/external` request body for a call of `tool`.
+pub(crate) fn event(tool: &str, args: &Value, outcome: Outcome, error: Option<&str>) -> Value {
+    json!({
+        "source": "mcp",
+        "action": tool,
+        "operation": operation(tool, args),
+        "data": { "arguments": redact(tool, args) },
+        "outcome": outcome.as_str(),
+        "error": error,
+    })
+}
+
+/// The audit operation of a call: what it does, not what it is named.
+fn operation(tool: &str, args: &Value) -> &'static str {
+    let arg = |name: &str| args.get(name).and_then(Value::as_str).unwrap_or_default();
+    match tool {
+        "zvault_set_secret" | "zvault_s3_write" => "write",
+        "zvault_delete_secret" => "delete",
+        "zvault_run_command" => "execute",
+        "zvault_query_database" | "zvault_query_mysql" | "zvault_query_clickhouse"
+            if args.get("allow_write").and_then(Value::as_bool) == Some(true) =>
+        {
+            "write"
+        }
+        "zvault_query_redis" => match first_word(arg("command")).as_str() {
+            "SET" => "write",
+            "DEL" => "delete",
+            _ => "read",
+        },
+        "zvault_http_request" => match arg("method").to_uppercase().as_str() {
+            "" | "GET" | "HEAD" => "read",
+            "DELETE" => "delete",
+            _ => "write",
+        },
+        _ => "read",
+    }
+}
+
+/// `args` with the values that may be secrets replaced.
+fn redact(tool: &str, args: &Value) -> Value {
+    let mut args = args.clone();
+    let Some(map) = args.as_object_mut() else {
+        return args;
+    };
+    for name in ["value", "content", "body"] {
+        if let Some(field) = map.get_mut(name) {
+            *field = REDACTED.into();
+        }
+    }
+    // Header names are useful; their values are credentials unless they
+    // are `zvault://` references.
+    if let Some(Value::Object(headers)) = map.get_mut("headers") {
+        for value in headers.values_mut() {
+            let reference = value.as_str().is_some_and(|v| v.starts_with("zvault://"));
+            if !reference {
+                *value = REDACTED.into();
+            }
+        }
+    }
+    // Keep a Redis command and its key, not the value being set.
+    if tool == "zvault_query_redis"
+        && let Some(Value::String(command)) = map.get_mut("command")
+    {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        if parts.len() > 2 && first_word(command) == "SET" {
+            *command = format!("{} {} {REDACTED}", parts[0], parts[1]);
+        }
+    }
+    args
+}
+
+fn first_word(command: &str) -> String {
+    command
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_uppercase()
+}
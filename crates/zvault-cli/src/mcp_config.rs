@@ -0,0 +1,418 @@
+//! Which MCP tools an assistant may use: `~/.zvault/mcp.toml` and the
+//! `mcp-server` flags.
+//!
+//! ```toml
+//! # Hide every tool that can change something: set/delete secret,
+//! # run_command, s3_write, and writes through the query, Redis, and HTTP
+//! # tools.
+//! read_only = true
+//! # Tools to hide outright (`*` globs allowed).
+//! disabled_tools = ["zvault_query_mongodb"]
+//! # Refuse `allow_write` on SQL tools even when not read-only.
+//! allow_write_queries = false
+//!
+//! # Vault policies → tools a token with them may use. When present, a
+//! # token only gets the tools of its policies.
+//! [policies]
+//! developer = ["*"]
+//! ci = ["zvault_list_secrets", "zvault_describe_secret", "zvault_check_env"]
+//!
+//! # Settings for one project directory (and everything below it).
+//! [projects."/home/me/src/payments"]
+//! read_only = true
+//! # Use a child token with these policies instead of the user's token.
+//! session_policies = ["payments-read"]
+//! session_ttl = "15m"
+//! ```
+//!
+//! The file lives in the home directory on purpose: a repository cannot
+//! grant itself tools. `--read-only` and `--disable-tool` only ever narrow
+//! what the file allows; `--session-policy` and `--session-ttl` replace the
+//! session settings.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::scan::parse_array;
+
+/// Tools that change secrets, run commands, or write to other systems.
+const WRITE_TOOLS: &[&str] = &[
+    "zvault_set_secret",
+    "zvault_delete_secret",
+    "zvault_run_command",
+    "zvault_s3_write",
+];
+
+/// SQL tools whose `allow_write` argument permits writes.
+const WRITE_QUERY_TOOLS: &[&str] = &[
+    "zvault_query_database",
+    "zvault_query_mysql",
+    "zvault_query_clickhouse",
+];
+
+/// Redis commands `zvault_query_redis` allows that change data.
+const REDIS_WRITE_COMMANDS: &[&str] = &["SET", "DEL"];
+
+/// Default TTL of a session token.
+const DEFAULT_SESSION_TTL: &str = "15m";
+
+/// Settings a section of `mcp.toml` may set.
+#[derive(Debug, Default, Clone)]
+struct Settings {
+    read_only: Option<bool>,
+    allow_write_queries: Option<bool>,
+    disabled_tools: Option<Vec<String>>,
+    session_policies: Option<Vec<String>>,
+    session_ttl: Option<String>,
+}
+
+impl Settings {
+    /// `self` with the values `other` sets replacing its own.
+    fn merge(&mut self, other: &Self) {
+        if other.read_only.is_some() {
+            self.read_only = other.read_only;
+        }
+        if other.allow_write_queries.is_some() {
+            self.allow_write_queries = other.allow_write_queries;
+        }
+        if other.disabled_tools.is_some() {
+            self.disabled_tools.clone_from(&other.disabled_tools);
+        }
+        if other.session_policies.is_some() {
+            self.session_policies.clone_from(&other.session_policies);
+        }
+        if other.session_ttl.is_some() {
+            self.session_ttl.clone_from(&other.session_ttl);
+        }
+    }
+}
+
+/// The parsed `mcp.toml`.
+#[derive(Debug, Default)]
+struct ConfigFile {
+    global: Settings,
+    /// Policy name → tool patterns.
+    policies: BTreeMap<String, Vec<String>>,
+    /// Project directory → its settings.
+    projects: BTreeMap<PathBuf, Settings>,
+}
+
+/// `mcp-server` flags applied on top of the configuration file.
+#[derive(Debug, Default)]
+pub(crate) struct Overrides {
+    pub(crate) read_only: bool,
+    pub(crate) disable_tools: Vec<String>,
+    pub(crate) session_policies: Vec<String>,
+    pub(crate) session_ttl: Option<String>,
+}
+
+/// The MCP server's configuration, after the config file, the project
+/// section for the working directory, and the flags.
+#[derive(Debug)]
+pub(crate) struct McpConfig {
+    pub(crate) gate: ToolGate,
+    /// The session token to use instead of the user's token, if any.
+    pub(crate) session: Option<SessionSpec>,
+}
+
+/// A short-lived child token the server exchanges the user's token for.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionSpec {
+    /// Policies of the child token, besides `default`.
+    pub(crate) policies: Vec<String>,
+    /// TTL, e.g. `15m`; the token is renewed by the same amount.
+    pub(crate) ttl: String,
+}
+
+/// What the MCP server may do.
+#[derive(Debug, Default)]
+pub(crate) struct ToolGate {
+    pub(crate) read_only: bool,
+    allow_write_queries: bool,
+    disabled: Vec<String>,
+    /// Policy name → tool patterns; empty when policies do not gate tools.
+    policies: BTreeMap<String, Vec<String>>,
+}
+
+impl McpConfig {
+    /// Load `path` (default `~/.zvault/mcp.toml`, if it exists) for the
+    /// working directory, then apply the flags.
+    pub(crate) fn load(
+        path: Option<&Path>,
+        overrides: &Overrides,
+        known_tools: &[&str],
+    ) -> Result<Self> {
+        // An explicit file must exist; the default one is optional.
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => (home_dir()?.join(".zvault").join("mcp.toml"), false),
+        };
+        let file = match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                parse(&content).with_context(|| format!("invalid {}", path.display()))?
+            }
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => {
+                ConfigFile::default()
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+
+        let mut settings = file.global.clone();
+        let cwd = std::env::current_dir().context("failed to read the working directory")?;
+        if let Some((_, project)) = file
+            .projects
+            .iter()
+            .filter(|(dir, _)| cwd.starts_with(dir))
+            .max_by_key(|(dir, _)| dir.components().count())
+        {
+            settings.merge(project);
+        }
+
+        let mut disabled = settings.disabled_tools.unwrap_or_default();
+        disabled.extend(overrides.disable_tools.iter().cloned());
+        let patterns = disabled.iter().chain(file.policies.values().flatten());
+        for pattern in patterns {
+            if !known_tools.iter().any(|tool| glob_match(pattern, tool)) {
+                bail!(
+                    "unknown MCP tool '{pattern}' (tools: {})",
+                    known_tools.join(", ")
+                );
+            }
+        }
+
+        let session_policies = if overrides.session_policies.is_empty() {
+            settings.session_policies.unwrap_or_default()
+        } else {
+            overrides.session_policies.clone()
+        };
+        let session_ttl = overrides.session_ttl.clone().or(settings.session_ttl);
+        let session = match (session_policies.is_empty(), session_ttl) {
+            (true, None) => None,
+            (true, Some(_)) => bail!("a session TTL needs session policies (--session-policy)"),
+            (false, ttl) => Some(SessionSpec {
+                policies: session_policies,
+                ttl: ttl.unwrap_or_else(|| DEFAULT_SESSION_TTL.to_owned()),
+            }),
+        };
+
+        Ok(Self {
+            gate: ToolGate {
+                read_only: overrides.read_only || settings.read_only.unwrap_or(false),
+                allow_write_queries: settings.allow_write_queries.unwrap_or(true),
+                disabled,
+                policies: file.policies,
+            },
+            session,
+        })
+    }
+}
+
+impl ToolGate {
+    /// Whether tools depend on the token's policies.
+    pub(crate) fn gates_by_policy(&self) -> bool {
+        !self.policies.is_empty()
+    }
+
+    /// Why `tool` is unavailable to a token with `policies` (`None` when
+    /// policies do not gate tools), or `None` if it is available.
+    pub(crate) fn denies(&self, tool: &str, policies: Option<&[String]>) -> Option<String> {
+        if self.read_only && WRITE_TOOLS.contains(&tool) {
+            return Some(format!("{tool} is disabled: the MCP server is read-only"));
+        }
+        if self
+            .disabled
+            .iter()
+            .any(|pattern| glob_match(pattern, tool))
+        {
+            return Some(format!("{tool} is disabled in the MCP configuration"));
+        }
+        if let Some(policies) = policies.filter(|_| self.gates_by_policy()) {
+            let allowed = policies.iter().any(|policy| {
+                self.policies
+                    .get(policy)
+                    .is_some_and(|tools| tools.iter().any(|pattern| glob_match(pattern, tool)))
+            });
+            if !allowed {
+                return Some(format!(
+                    "{tool} is not allowed for this token's policies ({})",
+                    policies.join(", ")
+                ));
+            }
+        }
+        None
+    }
+
+    /// Why this call of `tool` is refused for its arguments, e.g. a write
+    /// query while read-only, or `None`.
+    pub(crate) fn denies_call(&self, tool: &str, args: &serde_json::Value) -> Option<String> {
+        let allow_write = args
+            .get("allow_write")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        if WRITE_QUERY_TOOLS.contains(&tool) && allow_write {
+            if self.read_only {
+                return Some("write queries are disabled: the MCP server is read-only".to_owned());
+            }
+            if !self.allow_write_queries {
+                return Some("write queries are disabled in the MCP configuration".to_owned());
+            }
+        }
+        if !self.read_only {
+            return None;
+        }
+        match tool {
+            "zvault_query_redis" => {
+                let command = args
+                    .get("command")
+                    .and_then(serde_json::Value::as_str)
+                    .and_then(|c| c.split_whitespace().next())
+                    .unwrap_or_default()
+                    .to_uppercase();
+                REDIS_WRITE_COMMANDS
+                    .contains(&command.as_str())
+                    .then(|| format!("Redis {command} is disabled: the MCP server is read-only"))
+            }
+            "zvault_http_request" => {
+                let method = args
+                    .get("method")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or("GET")
+                    .to_uppercase();
+                (method != "GET" && method != "HEAD")
+                    .then(|| format!("HTTP {method} is disabled: the MCP server is read-only"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether `tool` matches `pattern`, where `*` matches any run of
+/// characters.
+fn glob_match(pattern: &str, tool: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = tool.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at.saturating_add(part.len())..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Parse `mcp.toml`: booleans and string arrays at the top level and in
+/// `[projects."<dir>"]`, string arrays in `[policies]`.
+fn parse(content: &str) -> Result<ConfigFile> {
+    // Minimal TOML parsing, as for `[scan]`: arrays may span lines.
+    let mut file = ConfigFile::default();
+    let mut section = Section::Global;
+    let mut lines = content.lines().enumerate();
+    while let Some((number, line)) = lines.next() {
+        let line_number = number.saturating_add(1);
+        let trimmed = line.split(" #").next().unwrap_or_default().trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = trimmed.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            section = match header.trim() {
+                "policies" => Section::Policies,
+                header => match header.strip_prefix("projects.") {
+                    Some(dir) => {
+                        let dir = PathBuf::from(expand_home(unquote(dir))?);
+                        file.projects.entry(dir.clone()).or_default();
+                        Section::Project(dir)
+                    }
+                    None => bail!(
+                        "line {line_number}: unknown section [{header}], expected [policies] or [projects.\"<dir>\"]"
+                    ),
+                },
+            };
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once('=') else {
+            bail!("line {line_number}: expected key = value");
+        };
+        let key = unquote(key.trim());
+        let mut value = value.trim().to_owned();
+        while value.starts_with('[') && !value.contains(']') {
+            let Some((_, next)) = lines.next() else { break };
+            value.push_str(next.trim());
+        }
+
+        let settings = match &section {
+            Section::Policies => {
+                if !value.starts_with('[') {
+                    bail!("line {line_number}: policy '{key}' must list tools, e.g. [\"*\"]");
+                }
+                let tools = parse_array(&value).into_iter().map(str::to_owned).collect();
+                file.policies.insert(key.to_owned(), tools);
+                continue;
+            }
+            Section::Global => &mut file.global,
+            Section::Project(dir) => file.projects.entry(dir.clone()).or_default(),
+        };
+        match key {
+            "read_only" => settings.read_only = Some(parse_bool(&value, line_number)?),
+            "allow_write_queries" => {
+                settings.allow_write_queries = Some(parse_bool(&value, line_number)?);
+            }
+            "disabled_tools" => {
+                settings.disabled_tools =
+                    Some(parse_array(&value).into_iter().map(str::to_owned).collect());
+            }
+            "session_policies" => {
+                settings.session_policies =
+                    Some(parse_array(&value).into_iter().map(str::to_owned).collect());
+            }
+            "session_ttl" => settings.session_ttl = Some(unquote(&value).to_owned()),
+            other => bail!(
+                "line {line_number}: unknown key '{other}', expected read_only, disabled_tools, allow_write_queries, session_policies, or session_ttl"
+            ),
+        }
+    }
+    Ok(file)
+}
+
+enum Section {
+    Global,
+    Policies,
+    Project(PathBuf),
+}
+
+fn parse_bool(value: &str, line_number: usize) -> Result<bool> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => bail!("line {line_number}: expected true or false, found {other}"),
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches('"').trim_matches('\'')
+}
+
+/// `dir` with a leading `~/` replaced by the home directory.
+fn expand_home(dir: &str) -> Result<String> {
+    Ok(match dir.strip_prefix("~/") {
+        Some(rest) => home_dir()?.join(rest).to_string_lossy().into_owned(),
+        None => dir.to_owned(),
+    })
+}
+
+fn home_dir() -> Result<PathBuf> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map(PathBuf::from)
+        .context("cannot determine home directory (HOME / USERPROFILE not set)")
+}
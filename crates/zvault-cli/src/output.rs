@@ -0,0 +1,116 @@
+//! Output formats: `--format` and `--field`.
+//!
+//! By default commands print decorated output for people. With
+//! `--format json|yaml` (or `ZVAULT_FORMAT`) or `--field`, that output moves
+//! to stderr and stdout carries only the response of the command's last API
+//! request — as JSON, as YAML, or just the one field — so it can be piped
+//! into other tools. Commands that already own stdout (`run`, `api`,
+//! `render`, ...) ignore both flags.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// Value of `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Decorated output for people.
+    Table,
+    /// The raw API response as JSON.
+    Json,
+    /// The raw API response as YAML.
+    Yaml,
+}
+
+/// Whether stdout is reserved for machine-readable output.
+static MACHINE: AtomicBool = AtomicBool::new(false);
+
+/// Body of the last successful API response, while [`MACHINE`] is set.
+static LAST_RESPONSE: Mutex<Option<Value>> = Mutex::new(None);
+
+/// Reserve stdout for machine-readable output.
+pub(crate) fn set_machine() {
+    MACHINE.store(true, Ordering::Relaxed);
+}
+
+fn is_machine() -> bool {
+    MACHINE.load(Ordering::Relaxed)
+}
+
+/// Print a line of decorated output (see `outln!`).
+pub(crate) fn line(args: fmt::Arguments<'_>) {
+    if is_machine() {
+        eprintln!("{args}");
+    } else {
+        println!("{args}");
+    }
+}
+
+/// Print decorated output without a newline (see `out!`).
+pub(crate) fn text(args: fmt::Arguments<'_>) {
+    if is_machine() {
+        eprint!("{args}");
+    } else {
+        print!("{args}");
+    }
+}
+
+/// Remember an API response body for [`emit`].
+pub(crate) fn record(value: &Value) {
+    if is_machine() {
+        if let Ok(mut last) = LAST_RESPONSE.lock() {
+            *last = Some(value.clone());
+        }
+    }
+}
+
+/// Print the last API response to stdout in `format`, or just `field` of
+/// it. Commands whose last request returned no body print nothing.
+pub(crate) fn emit(format: OutputFormat, field: Option<&str>) -> Result<()> {
+    let last = LAST_RESPONSE
+        .lock()
+        .ok()
+        .and_then(|mut last| last.take())
+        .unwrap_or(Value::Null);
+
+    if let Some(field) = field {
+        let value = find_field(&last, field)
+            .with_context(|| format!("the response has no field '{field}'"))?;
+        match value {
+            Value::String(s) => println!("{s}"),
+            other if format == OutputFormat::Yaml => print!("{}", serde_yaml_ng::to_string(other)?),
+            other => println!("{other}"),
+        }
+        return Ok(());
+    }
+
+    if last.is_null() {
+        return Ok(());
+    }
+    match format {
+        OutputFormat::Yaml => print!("{}", serde_yaml_ng::to_string(&last)?),
+        OutputFormat::Json | OutputFormat::Table => {
+            println!("{}", serde_json::to_string_pretty(&last)?);
+        }
+    }
+    Ok(())
+}
+
+/// Look up a dotted `field` (`a.b.0`) in `value`, or failing that inside
+/// its nested `data` envelopes, so `password` finds a KV secret's key.
+fn find_field<'a>(value: &'a Value, field: &str) -> Option<&'a Value> {
+    let mut node = value;
+    loop {
+        let found = field.split('.').try_fold(node, |node, part| match node {
+            Value::Array(items) => part.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => node.get(part),
+        });
+        if found.is_some() {
+            return found;
+        }
+        node = node.get("data")?;
+    }
+}
@@ -0,0 +1,770 @@
+//! `zvault import-passwords`: KV secrets from a 1Password (`.1pux`) or
+//! Bitwarden (unencrypted JSON) export.
+//!
+//! Every item becomes one secret at `<prefix>/<vault or folder>/<item>`,
+//! with its username, password, one-time password seed, URLs, notes, and
+//! custom fields as keys. On a terminal each vault or folder is offered
+//! for a different path (or `-` to leave it out); `--map` answers that up
+//! front. Values are never printed, and `--dry-run` shows which secret
+//! each item would become and with which keys. Attachments, passkeys,
+//! item references, and the like cannot be stored and are listed instead.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read as _;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{BOLD, CYAN, Client, DIM, RESET, YELLOW, header, success, warning};
+
+/// Deepest secret path `ZVault` accepts.
+const MAX_PATH_DEPTH: usize = 10;
+
+/// What to import and where.
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct PasswordImportOptions {
+    pub(crate) file: String,
+    /// `auto`, `1password`, or `bitwarden`.
+    pub(crate) from: String,
+    pub(crate) prefix: String,
+    /// `<vault or folder>=<path>` answers to the mapping prompt.
+    pub(crate) maps: Vec<String>,
+    pub(crate) include_archived: bool,
+    pub(crate) yes: bool,
+    pub(crate) overwrite: bool,
+    pub(crate) dry_run: bool,
+}
+
+/// One password manager item, read from the export.
+#[derive(Debug)]
+struct Item {
+    /// The vault, folder, or collection the item is in (empty for none).
+    group: String,
+    title: String,
+    /// Field key and value, in the order the manager shows them.
+    fields: Vec<(String, String)>,
+}
+
+/// Something in the export that is not imported.
+#[derive(Debug, Serialize)]
+struct Skipped {
+    source: String,
+    reason: String,
+}
+
+/// A secret to write.
+#[derive(Debug, Serialize)]
+struct Planned {
+    path: String,
+    source: String,
+    keys: Vec<String>,
+    exists: bool,
+}
+
+/// An export, read into items.
+#[derive(Default)]
+struct Export {
+    items: Vec<Item>,
+    skipped: Vec<Skipped>,
+}
+
+impl Export {
+    fn skip(&mut self, source: impl Into<String>, reason: impl Into<String>) {
+        self.skipped.push(Skipped {
+            source: source.into(),
+            reason: reason.into(),
+        });
+    }
+}
+
+/// Import (or with `--dry-run`, preview) a password manager export.
+pub(crate) async fn cmd_import_passwords(
+    client: &Client,
+    options: PasswordImportOptions,
+) -> Result<()> {
+    let file = &options.file;
+    let bytes = std::fs::read(file).with_context(|| format!("failed to read {file}"))?;
+    let one_password = match options.from.as_str() {
+        "1password" => true,
+        "bitwarden" => false,
+        // `.1pux` files are zip archives.
+        _ => bytes.starts_with(b"PK"),
+    };
+    let (manager, mut export) = if one_password {
+        ("1Password", read_1pux(&bytes, options.include_archived)?)
+    } else {
+        ("Bitwarden", read_bitwarden(&bytes)?)
+    };
+
+    let groups = group_paths(&export.items, &options)?;
+    let planned = plan(client, &mut export, &groups).await?;
+
+    let conflicts = planned.iter().filter(|(p, _)| p.exists).count();
+    if conflicts > 0 && !options.overwrite && !options.dry_run {
+        let first = planned
+            .iter()
+            .find(|(p, _)| p.exists)
+            .map(|(p, _)| p.path.as_str())
+            .unwrap_or_default();
+        bail!(
+            "{conflicts} secrets already exist (first: secret/{first}) — pass --overwrite to replace them"
+        );
+    }
+
+    outln!();
+    if options.dry_run {
+        header("📥", &format!("Import from {manager} (dry run)"));
+    } else {
+        header("📥", &format!("Importing from {manager}"));
+    }
+    for (plan, data) in &planned {
+        if !options.dry_run {
+            let mut body = serde_json::json!({ "data": data });
+            if !options.overwrite {
+                body["options"] = serde_json::json!({ "cas": 0 });
+            }
+            client
+                .post(&format!("/v1/secret/data/{}", plan.path), &body)
+                .await
+                .with_context(|| format!("failed to write {}", plan.path))?;
+        }
+        let replaced = if plan.exists {
+            format!(" {YELLOW}(replaces existing){RESET}")
+        } else {
+            String::new()
+        };
+        outln!(
+            "  {CYAN}├─{RESET} secret/{}{replaced}  {DIM}{}{RESET}",
+            plan.path,
+            plan.keys.join(", ")
+        );
+    }
+    print_summary(&planned, &export.skipped, &options);
+
+    crate::output::record(&serde_json::json!({
+        "dry_run": options.dry_run,
+        "secrets": planned.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+        "skipped": export.skipped,
+    }));
+    Ok(())
+}
+
+/// The secret each item becomes, with its data.
+async fn plan(
+    client: &Client,
+    export: &mut Export,
+    groups: &BTreeMap<String, Option<String>>,
+) -> Result<Vec<(Planned, serde_json::Map<String, Value>)>> {
+    let mut planned = Vec::new();
+    let mut taken = BTreeSet::new();
+    for item in std::mem::take(&mut export.items) {
+        let source = if item.group.is_empty() {
+            item.title.clone()
+        } else {
+            format!("{}/{}", item.group, item.title)
+        };
+        let Some(dir) = groups.get(&item.group).cloned().flatten() else {
+            export.skip(source, "its vault or folder was left out");
+            continue;
+        };
+        if item.fields.is_empty() {
+            export.skip(source, "no fields to import");
+            continue;
+        }
+        let path = unique(&join_path(&dir, &slug(&item.title)), &mut taken, "-");
+        if path.split('/').count() > MAX_PATH_DEPTH {
+            export.skip(
+                source,
+                format!("secret/{path} is deeper than {MAX_PATH_DEPTH} segments"),
+            );
+            continue;
+        }
+        let exists = secret_exists(client, &path).await?;
+        let mut keys = BTreeSet::new();
+        let data: serde_json::Map<String, Value> = item
+            .fields
+            .into_iter()
+            .map(|(key, value)| (unique(&key, &mut keys, "_"), Value::String(value)))
+            .collect();
+        planned.push((
+            Planned {
+                path,
+                source,
+                keys: data.keys().cloned().collect(),
+                exists,
+            },
+            data,
+        ));
+    }
+    Ok(planned)
+}
+
+fn print_summary(
+    planned: &[(Planned, serde_json::Map<String, Value>)],
+    skipped: &[Skipped],
+    options: &PasswordImportOptions,
+) {
+    let file = &options.file;
+    let conflicts = planned.iter().filter(|(p, _)| p.exists).count();
+    if !skipped.is_empty() {
+        outln!();
+        outln!("  {BOLD}{YELLOW}Not imported{RESET}");
+        for skipped in skipped {
+            outln!(
+                "  {YELLOW}⚠{RESET} {CYAN}{}{RESET}  {}",
+                skipped.source,
+                skipped.reason
+            );
+        }
+    }
+
+    outln!();
+    if options.dry_run {
+        success(&format!("{} items would be imported", planned.len()));
+        if conflicts > 0 && !options.overwrite {
+            warning(&format!(
+                "{conflicts} of them already exist — pass --overwrite to replace them"
+            ));
+        }
+        outln!("  {DIM}Run again without --dry-run to import.{RESET}");
+    } else {
+        success(&format!("Imported {} items from {file}", planned.len()));
+        outln!("  {DIM}The export holds every value in plain text; delete {file} now.{RESET}");
+    }
+    outln!();
+}
+
+/// Whether `secret/<path>` holds a secret.
+async fn secret_exists(client: &Client, path: &str) -> Result<bool> {
+    let resp = client
+        .request(
+            reqwest::Method::GET,
+            &format!("/v1/secret/data/{path}"),
+            None,
+        )
+        .await?;
+    match resp.status() {
+        reqwest::StatusCode::NOT_FOUND => Ok(false),
+        status if status.is_success() => Ok(true),
+        status => bail!("failed to check secret/{path}: server returned {status}"),
+    }
+}
+
+// ── Mapping ──────────────────────────────────────────────────────────
+
+/// The path each group's items go under, or `None` for groups left out.
+fn group_paths(
+    items: &[Item],
+    options: &PasswordImportOptions,
+) -> Result<BTreeMap<String, Option<String>>> {
+    use std::io::{BufRead as _, IsTerminal as _, Write as _};
+
+    let mut answers = BTreeMap::new();
+    for map in &options.maps {
+        let Some((group, path)) = map.split_once('=') else {
+            bail!("invalid --map '{map}', expected <vault or folder>=<path> (e.g. Work=team/work)");
+        };
+        answers.insert(group.to_owned(), mapped(path));
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for item in items {
+        let count = counts.entry(item.group.as_str()).or_default();
+        *count = count.saturating_add(1);
+    }
+
+    let interactive = !options.yes
+        && std::io::stdin().is_terminal()
+        && !std::env::var("ZVAULT_NON_INTERACTIVE").is_ok_and(|v| v == "1" || v == "true");
+    if interactive && counts.keys().any(|g| !answers.contains_key(*g)) {
+        outln!();
+        outln!(
+            "  {BOLD}Where should each vault or folder go?{RESET} {DIM}Enter accepts, - leaves it out.{RESET}"
+        );
+    }
+
+    let mut lines = std::io::stdin().lock().lines();
+    let mut paths = BTreeMap::new();
+    for (group, count) in counts {
+        let default = group
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(slug)
+            .fold(clean_path(&options.prefix), |dir, s| join_path(&dir, &s));
+        let path = if let Some(answer) = answers.get(group) {
+            answer.clone()
+        } else if interactive {
+            let name = if group.is_empty() {
+                "(no folder)"
+            } else {
+                group
+            };
+            let items = if count == 1 { "item" } else { "items" };
+            out!("  {CYAN}{name}{RESET} {DIM}({count} {items}){RESET} → [{default}]: ");
+            std::io::stdout()
+                .flush()
+                .context("failed to flush stdout")?;
+            let answer = lines
+                .next()
+                .transpose()
+                .context("failed to read answer")?
+                .unwrap_or_default();
+            match answer.trim() {
+                "" => Some(default),
+                answer => mapped(answer),
+            }
+        } else {
+            Some(default)
+        };
+        paths.insert(group.to_owned(), path);
+    }
+    Ok(paths)
+}
+
+/// A mapping answer: `-` leaves the group out, anything else is a path.
+fn mapped(answer: &str) -> Option<String> {
+    let answer = answer.trim();
+    (answer != "-").then(|| clean_path(answer))
+}
+
+/// A path typed by the user, with characters KV paths do not allow as `-`.
+fn clean_path(path: &str) -> String {
+    path.split('/')
+        .filter(|s| !s.trim().is_empty())
+        .map(|segment| {
+            segment
+                .trim()
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn join_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+/// `name` lowercased, with runs of characters other than ASCII letters
+/// and digits as one `sep`; `fallback` if nothing is left.
+fn slug_with(name: &str, sep: char, fallback: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with(sep) {
+            slug.push(sep);
+        }
+    }
+    let slug = slug.trim_end_matches(sep);
+    if slug.is_empty() {
+        fallback.to_owned()
+    } else {
+        slug.to_owned()
+    }
+}
+
+/// `name` as a path segment, e.g. `GitHub (work)` → `github-work`.
+fn slug(name: &str) -> String {
+    slug_with(name, '-', "item")
+}
+
+/// `name` as a field key, e.g. `API Key` → `api_key`.
+fn field_key(name: &str) -> String {
+    slug_with(name, '_', "field")
+}
+
+/// `name`, or `name<sep>2`, `name<sep>3`, … if it is already taken.
+fn unique(name: &str, taken: &mut BTreeSet<String>, sep: &str) -> String {
+    let mut candidate = name.to_owned();
+    let mut n = 1u32;
+    while !taken.insert(candidate.clone()) {
+        n = n.saturating_add(1);
+        candidate = format!("{name}{sep}{n}");
+    }
+    candidate
+}
+
+/// Append a field unless its value is empty.
+fn push_field(fields: &mut Vec<(String, String)>, key: &str, value: Option<&str>) {
+    if let Some(value) = value.filter(|v| !v.is_empty()) {
+        fields.push((key.to_owned(), value.to_owned()));
+    }
+}
+
+// ── 1Password ────────────────────────────────────────────────────────
+
+/// Read a `.1pux` export (a zip archive around `export.data`).
+fn read_1pux(bytes: &[u8], include_archived: bool) -> Result<Export> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .context("not a 1Password export (.1pux)")?;
+    let mut data = String::new();
+    archive
+        .by_name("export.data")
+        .context("not a 1Password export: export.data is missing")?
+        .read_to_string(&mut data)
+        .context("failed to read export.data")?;
+    let root: Value = serde_json::from_str(&data).context("invalid export.data")?;
+
+    let mut export = Export::default();
+    let accounts = root
+        .get("accounts")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for account in &accounts {
+        let account_name = account
+            .pointer("/attrs/accountName")
+            .and_then(Value::as_str)
+            .unwrap_or("account");
+        for vault in account
+            .get("vaults")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let vault_name = vault
+                .pointer("/attrs/name")
+                .and_then(Value::as_str)
+                .unwrap_or("vault");
+            let group = if accounts.len() > 1 {
+                format!("{account_name}/{vault_name}")
+            } else {
+                vault_name.to_owned()
+            };
+            for item in vault
+                .get("items")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                read_1password_item(&mut export, &group, item, include_archived);
+            }
+        }
+    }
+    Ok(export)
+}
+
+fn read_1password_item(export: &mut Export, group: &str, item: &Value, include_archived: bool) {
+    let title = item
+        .pointer("/overview/title")
+        .and_then(Value::as_str)
+        .unwrap_or("untitled")
+        .to_owned();
+    let source = format!("{group}/{title}");
+    if item.get("trashed").and_then(Value::as_bool) == Some(true) {
+        return;
+    }
+    if !include_archived && item.get("state").and_then(Value::as_str) == Some("archived") {
+        export.skip(source, "archived (pass --include-archived)");
+        return;
+    }
+
+    let details = item.get("details").cloned().unwrap_or_default();
+    let mut fields = Vec::new();
+    for field in details
+        .get("loginFields")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let key = field
+            .get("designation")
+            .and_then(Value::as_str)
+            .filter(|d| !d.is_empty())
+            .or_else(|| field.get("name").and_then(Value::as_str))
+            .unwrap_or("field");
+        push_field(
+            &mut fields,
+            &field_key(key),
+            field.get("value").and_then(Value::as_str),
+        );
+    }
+    push_field(
+        &mut fields,
+        "password",
+        details.get("password").and_then(Value::as_str),
+    );
+
+    for section in details
+        .get("sections")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        for field in section
+            .get("fields")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let value = field.get("value").and_then(Value::as_object);
+            let Some((kind, value)) = value.and_then(|v| v.iter().next()) else {
+                continue;
+            };
+            let label = field
+                .get("title")
+                .and_then(Value::as_str)
+                .filter(|t| !t.is_empty())
+                .or_else(|| (kind == "totp").then_some("one-time password"))
+                .or_else(|| field.get("id").and_then(Value::as_str))
+                .unwrap_or("field");
+            match section_value(kind, value) {
+                Ok(text) => push_field(&mut fields, &field_key(label), text.as_deref()),
+                Err(unsupported) => {
+                    export.skip(&source, format!("field '{label}': {unsupported}"));
+                }
+            }
+        }
+    }
+
+    for url in item_urls(item) {
+        push_field(&mut fields, "url", Some(url));
+    }
+    push_field(
+        &mut fields,
+        "notes",
+        details.get("notesPlain").and_then(Value::as_str),
+    );
+
+    if details.get("documentAttributes").is_some() {
+        export.skip(&source, "document files");
+    }
+    if details.get("passkey").is_some() {
+        export.skip(&source, "passkeys");
+    }
+    export.items.push(Item {
+        group: group.to_owned(),
+        title,
+        fields,
+    });
+}
+
+/// The website URLs of a 1Password item, primary first.
+fn item_urls(item: &Value) -> Vec<&str> {
+    let urls: Vec<&str> = item
+        .pointer("/overview/urls")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|u| u.get("url").and_then(Value::as_str))
+        .collect();
+    if urls.is_empty() {
+        item.pointer("/overview/url")
+            .and_then(Value::as_str)
+            .into_iter()
+            .collect()
+    } else {
+        urls
+    }
+}
+
+/// The text of a 1Password section field value of type `kind`, or what
+/// kind of field it is if it cannot be stored as text.
+fn section_value(kind: &str, value: &Value) -> Result<Option<String>, String> {
+    Ok(match kind {
+        "string" | "concealed" | "totp" | "url" | "phone" | "creditCardNumber" | "menu"
+        | "gender" | "creditCardType" => value.as_str().map(str::to_owned),
+        "email" => value
+            .get("email_address")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        "date" | "monthYear" => value.as_i64().map(|n| n.to_string()),
+        "sshKey" => value
+            .get("privateKey")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        "address" => value.as_object().map(|address| {
+            ["street", "city", "state", "zip", "country"]
+                .iter()
+                .filter_map(|part| address.get(*part).and_then(Value::as_str))
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }),
+        "reference" => return Err("references to other items".to_owned()),
+        "file" => return Err("file attachments".to_owned()),
+        other => return Err(format!("{other} fields")),
+    })
+}
+
+// ── Bitwarden ────────────────────────────────────────────────────────
+
+/// Read a Bitwarden JSON export (personal or organization, unencrypted).
+fn read_bitwarden(bytes: &[u8]) -> Result<Export> {
+    let root: Value = serde_json::from_slice(bytes)
+        .context("not a Bitwarden JSON export (or a .1pux file; pass --from 1password)")?;
+    if root.get("encrypted").and_then(Value::as_bool) == Some(true) {
+        bail!("encrypted Bitwarden exports are not supported; export as unencrypted JSON");
+    }
+    let Some(items) = root.get("items").and_then(Value::as_array) else {
+        bail!("not a Bitwarden JSON export: no items");
+    };
+    let names = |key: &str| -> BTreeMap<String, String> {
+        root.get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|f| {
+                Some((
+                    f.get("id")?.as_str()?.to_owned(),
+                    f.get("name")?.as_str()?.to_owned(),
+                ))
+            })
+            .collect()
+    };
+    let folders = names("folders");
+    let collections = names("collections");
+
+    let mut export = Export::default();
+    for item in items {
+        if item.get("deletedDate").is_some_and(|d| !d.is_null()) {
+            continue;
+        }
+        let folder = item
+            .get("folderId")
+            .and_then(Value::as_str)
+            .and_then(|id| folders.get(id))
+            .or_else(|| {
+                item.get("collectionIds")
+                    .and_then(Value::as_array)
+                    .and_then(|ids| ids.first())
+                    .and_then(Value::as_str)
+                    .and_then(|id| collections.get(id))
+            })
+            .cloned()
+            .unwrap_or_default();
+        read_bitwarden_item(&mut export, folder, item);
+    }
+    Ok(export)
+}
+
+fn read_bitwarden_item(export: &mut Export, group: String, item: &Value) {
+    let title = item
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("untitled")
+        .to_owned();
+    let source = if group.is_empty() {
+        title.clone()
+    } else {
+        format!("{group}/{title}")
+    };
+    let str_at = |pointer: &str| item.pointer(pointer).and_then(Value::as_str);
+    let mut fields = Vec::new();
+    match item.get("type").and_then(Value::as_u64) {
+        // Login
+        Some(1) => {
+            push_field(&mut fields, "username", str_at("/login/username"));
+            push_field(&mut fields, "password", str_at("/login/password"));
+            push_field(&mut fields, "totp", str_at("/login/totp"));
+            for uri in item
+                .pointer("/login/uris")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                push_field(&mut fields, "url", uri.get("uri").and_then(Value::as_str));
+            }
+            if item
+                .pointer("/login/fido2Credentials")
+                .and_then(Value::as_array)
+                .is_some_and(|c| !c.is_empty())
+            {
+                export.skip(&source, "passkeys");
+            }
+        }
+        // Card
+        Some(3) => {
+            for (key, field) in [
+                ("cardholder_name", "cardholderName"),
+                ("brand", "brand"),
+                ("number", "number"),
+                ("exp_month", "expMonth"),
+                ("exp_year", "expYear"),
+                ("code", "code"),
+            ] {
+                push_field(&mut fields, key, str_at(&format!("/card/{field}")));
+            }
+        }
+        // Identity
+        Some(4) => {
+            for (field, value) in item
+                .get("identity")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                push_field(&mut fields, &camel_to_snake(field), value.as_str());
+            }
+        }
+        // SSH key
+        Some(5) => {
+            push_field(&mut fields, "private_key", str_at("/sshKey/privateKey"));
+            push_field(&mut fields, "public_key", str_at("/sshKey/publicKey"));
+            push_field(&mut fields, "fingerprint", str_at("/sshKey/keyFingerprint"));
+        }
+        // Secure note: only notes and custom fields.
+        _ => {}
+    }
+
+    for field in item
+        .get("fields")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let name = field.get("name").and_then(Value::as_str).unwrap_or("field");
+        // Type 3 links to another field of the item, e.g. the username.
+        if field.get("type").and_then(Value::as_u64) == Some(3) {
+            export.skip(&source, format!("field '{name}': linked fields"));
+            continue;
+        }
+        let value = match field.get("value") {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Bool(b)) => Some(b.to_string()),
+            _ => None,
+        };
+        push_field(&mut fields, &field_key(name), value.as_deref());
+    }
+    push_field(&mut fields, "notes", str_at("/notes"));
+
+    if item
+        .get("attachments")
+        .and_then(Value::as_array)
+        .is_some_and(|a| !a.is_empty())
+    {
+        export.skip(&source, "file attachments");
+    }
+    export.items.push(Item {
+        group,
+        title,
+        fields,
+    });
+}
+
+/// `postalCode` → `postal_code`.
+fn camel_to_snake(name: &str) -> String {
+    let mut snake = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
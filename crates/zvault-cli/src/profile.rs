@@ -0,0 +1,195 @@
+//! Per-environment profiles: `[env.<name>]` sections of `.zvault.toml`.
+//!
+//! ```toml
+//! [secrets]
+//! prefix = "env/myapp"
+//!
+//! [env.staging]
+//! address = "https://vault.staging.internal:8200"
+//! prefix = "env/myapp-staging"
+//! token_env = "ZVAULT_STAGING_TOKEN"
+//!
+//! [env.prod]
+//! address = "https://vault.prod.internal:8200"
+//! prefix = "env/myapp-prod"
+//! token_file = "~/.zvault/prod-token"
+//! env_file = ".env.prod.zvault"
+//! ```
+//!
+//! `zvault run --env staging` talks to the profile's server with its token
+//! and resolves every `zvault://` reference under `[secrets] prefix` against
+//! the profile's prefix instead, so one `.env.zvault` serves every
+//! environment.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use super::Client;
+
+const PROJECT_CONFIG_FILE: &str = ".zvault.toml";
+
+/// One `[env.<name>]` section.
+#[derive(Debug, Default)]
+pub(crate) struct Profile {
+    pub(crate) name: String,
+    /// Server address; overrides `--addr` and `VAULT_ADDR`.
+    address: Option<String>,
+    /// Replaces `[secrets] prefix` in `zvault://` references.
+    prefix: Option<String>,
+    /// `[secrets] prefix` of the project.
+    base_prefix: Option<String>,
+    /// Environment variable holding the token.
+    token_env: Option<String>,
+    /// File holding the token.
+    token_file: Option<String>,
+    /// Env file to use when `--env-file` is not given.
+    pub(crate) env_file: Option<String>,
+}
+
+/// Read profile `name` from `.zvault.toml` in the current directory.
+pub(crate) fn load(name: &str) -> Result<Profile> {
+    let path = Path::new(PROJECT_CONFIG_FILE);
+    if !path.exists() {
+        bail!("no .zvault.toml found — run `zvault project-init` and add an [env.{name}] section");
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {PROJECT_CONFIG_FILE}"))?;
+    parse(&content, name)
+}
+
+fn parse(content: &str, name: &str) -> Result<Profile> {
+    // Minimal TOML parsing, as for `[cloud]`: flat string keys per section.
+    let mut profile = Profile {
+        name: name.to_owned(),
+        ..Profile::default()
+    };
+    let mut found = false;
+    let mut available = Vec::new();
+    let mut section = String::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = trimmed.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            header.trim().clone_into(&mut section);
+            if let Some(env) = section.strip_prefix("env.") {
+                available.push(env.to_owned());
+                found |= env == name;
+            }
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = parse_value(value);
+
+        if section == "secrets" && key == "prefix" {
+            profile.base_prefix = Some(value.trim_end_matches('/').to_owned());
+        } else if section.strip_prefix("env.") == Some(name) {
+            let field = match key {
+                "address" => &mut profile.address,
+                "prefix" => &mut profile.prefix,
+                "token_env" => &mut profile.token_env,
+                "token_file" => &mut profile.token_file,
+                "env_file" => &mut profile.env_file,
+                _ => bail!(
+                    "{PROJECT_CONFIG_FILE} line {}: unknown key '{key}' in [env.{name}], \
+                     expected address, prefix, token_env, token_file, or env_file",
+                    number + 1
+                ),
+            };
+            *field = Some(value.to_owned());
+        }
+    }
+
+    if !found {
+        if available.is_empty() {
+            bail!("{PROJECT_CONFIG_FILE} has no [env.{name}] section");
+        }
+        bail!(
+            "{PROJECT_CONFIG_FILE} has no [env.{name}] section (available: {})",
+            available.join(", ")
+        );
+    }
+    if profile.token_env.is_some() && profile.token_file.is_some() {
+        bail!("[env.{name}] sets both token_env and token_file; keep one");
+    }
+    if profile.prefix.is_some() && profile.base_prefix.is_none() {
+        bail!("[env.{name}] sets prefix, but [secrets] has no prefix to replace");
+    }
+    Ok(profile)
+}
+
+/// A quoted string up to its closing quote, or a bare value up to a comment.
+fn parse_value(value: &str) -> &str {
+    let value = value.trim();
+    match value.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+        None => value.split('#').next().unwrap_or_default().trim(),
+    }
+}
+
+impl Profile {
+    /// A client for this profile's server and token.
+    pub(crate) fn client(&self, base: &Client) -> Result<Client> {
+        let token = match self.token()? {
+            Some(token) => Some(token),
+            None => base.token.clone(),
+        };
+        Ok(Client {
+            http: base.http.clone(),
+            addr: self.address.clone().unwrap_or_else(|| base.addr.clone()),
+            token,
+        })
+    }
+
+    fn token(&self) -> Result<Option<String>> {
+        if let Some(var) = &self.token_env {
+            let token = std::env::var(var).with_context(|| {
+                format!(
+                    "[env.{}] reads its token from ${var}, which is not set",
+                    self.name
+                )
+            })?;
+            return Ok(Some(token));
+        }
+        if let Some(file) = &self.token_file {
+            let path = expand_home(file);
+            let token = std::fs::read_to_string(&path).with_context(|| {
+                format!(
+                    "[env.{}] reads its token from {}, which could not be read",
+                    self.name,
+                    path.display()
+                )
+            })?;
+            return Ok(Some(token.trim().to_owned()));
+        }
+        Ok(None)
+    }
+
+    /// Point a `zvault://` reference under `[secrets] prefix` at this
+    /// profile's prefix. Other references are returned unchanged.
+    pub(crate) fn rewrite(&self, uri: &str) -> String {
+        let (Some(base), Some(prefix)) = (&self.base_prefix, &self.prefix) else {
+            return uri.to_owned();
+        };
+        uri.strip_prefix("zvault://")
+            .and_then(|path| path.strip_prefix(base.as_str()))
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+            .map_or_else(
+                || uri.to_owned(),
+                |rest| format!("zvault://{}{rest}", prefix.trim_end_matches('/')),
+            )
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
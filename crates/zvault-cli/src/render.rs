@@ -0,0 +1,144 @@
+//! `zvault render`: render a config template with secrets inlined.
+//!
+//! Templates use the language of [`zvault_sdk::template`]; `secret` actions
+//! read keys from the `secret/` KV mount. Secrets are fetched and written by
+//! this process, so they never appear in a command line the way they do
+//! when shell wrappers pipe `zvault kv get` into other tools.
+//!
+//! With `--watch` the template is re-rendered every `--interval` seconds and
+//! the output file is replaced (atomically) only when its contents change.
+
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use zvault_sdk::template::Template;
+
+use super::{Client, kv_payload, success, warning};
+
+/// Read and parse a template file.
+pub(crate) fn load_template(path: &str) -> Result<Template> {
+    let source =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read template {path}"))?;
+    Template::parse(&source).with_context(|| format!("invalid template {path}"))
+}
+
+/// Parse an octal file mode such as `0640`.
+pub(crate) fn parse_mode(mode: &str) -> Result<u32> {
+    u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+        .with_context(|| format!("invalid mode '{mode}', expected octal like 0640"))
+}
+
+/// Fetch the data of every secret path the templates reference.
+pub(crate) async fn fetch_secrets(
+    client: &Client,
+    templates: &[&Template],
+) -> Result<BTreeMap<String, Value>> {
+    let mut secrets = BTreeMap::new();
+    for secret in templates.iter().flat_map(|t| t.secrets()) {
+        if !secrets.contains_key(&secret.path) {
+            let resp = client
+                .get(&format!("/v1/secret/data/{}", secret.path))
+                .await
+                .with_context(|| format!("failed to read secret {}", secret.path))?;
+            secrets.insert(secret.path.clone(), kv_payload(&resp).clone());
+        }
+    }
+    Ok(secrets)
+}
+
+/// Render `template` with secret data from [`fetch_secrets`].
+pub(crate) fn render(template: &Template, secrets: &BTreeMap<String, Value>) -> Result<String> {
+    let contents = template.render(|secret| {
+        secrets
+            .get(&secret.path)
+            .and_then(|data| data.get(&secret.key))
+            .map(|value| match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+    })?;
+    Ok(contents)
+}
+
+/// Write `contents` to `path` atomically, creating it with `mode`.
+pub(crate) fn write_file(path: &str, contents: &str, mode: u32) -> Result<()> {
+    let target = Path::new(path);
+    let dir = target
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let name = target
+        .file_name()
+        .with_context(|| format!("invalid destination {path}"))?
+        .to_string_lossy();
+    let tmp = dir.join(format!(".{name}.zvault-tmp"));
+    // A leftover temp file would keep its old mode.
+    let _ = std::fs::remove_file(&tmp);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    let mut file = options
+        .open(&tmp)
+        .with_context(|| format!("failed to create {}", tmp.display()))?;
+    file.write_all(contents.as_bytes())
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, target).with_context(|| format!("failed to move {path} into place"))
+}
+
+/// Render `template` once to stdout or `output`, or keep it rendered with
+/// `watch`.
+pub(crate) async fn cmd_render(
+    client: &Client,
+    template: &str,
+    output: Option<&str>,
+    mode: &str,
+    watch: bool,
+    interval: u64,
+) -> Result<()> {
+    let parsed = load_template(template)?;
+    let mode = parse_mode(mode)?;
+
+    let Some(output) = output else {
+        let secrets = fetch_secrets(client, &[&parsed]).await?;
+        print!("{}", render(&parsed, &secrets)?);
+        return Ok(());
+    };
+
+    let mut last = std::fs::read_to_string(output).ok();
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let result = async {
+            let secrets = fetch_secrets(client, &[&parsed]).await?;
+            render(&parsed, &secrets)
+        }
+        .await;
+        match result {
+            Ok(contents) if last.as_deref() != Some(contents.as_str()) => {
+                write_file(output, &contents, mode)?;
+                last = Some(contents);
+                success(&format!("rendered {output}"));
+            }
+            Ok(_) if !watch => success(&format!("{output} is up to date")),
+            Ok(_) => {}
+            Err(e) if watch => warning(&format!("{e:#}; keeping the last rendered file")),
+            Err(e) => return Err(e),
+        }
+        if !watch {
+            return Ok(());
+        }
+    }
+}
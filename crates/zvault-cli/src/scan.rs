@@ -0,0 +1,682 @@
+//! Leaked credential scanning: `zvault scan [path]`.
+//!
+//! Walks a directory (honouring `.gitignore`, like `git` would) and runs a
+//! small set of detectors over every text file: well-known token formats
+//! (AWS, GitHub, Slack, Stripe), PEM private keys, JWTs, sensitive-looking
+//! assignments, and generic high-entropy strings. Values are never printed,
+//! only a short prefix.
+//!
+//! With `--import`, every finding is stored at `env/<project>/<NAME>` — the
+//! same layout as `zvault import` — and replaced in place by its
+//! `zvault://` reference. Append `zvault:ignore` to a line to silence it,
+//! or allowlist paths and values under `[scan]` in `.zvault.toml`.
+//!
+//! `--staged` scans what is about to be committed instead, and also flags
+//! plaintext `.env` files; it is what the `zvault hooks install` pre-commit
+//! hook runs.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+
+use super::{BOLD, CYAN, Client, DIM, GREEN, RED, RESET, YELLOW, header, success, warning};
+
+const PROJECT_CONFIG_FILE: &str = ".zvault.toml";
+
+/// Files larger than this are skipped.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Minimum Shannon entropy (bits per character) for a generic string.
+const ENTROPY_THRESHOLD: f64 = 4.0;
+
+/// Minimum entropy for the value of a sensitive-looking assignment.
+const ASSIGNMENT_ENTROPY_THRESHOLD: f64 = 3.0;
+
+/// Lock files full of checksums that would only produce noise.
+const SKIPPED_FILES: [&str; 6] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.sum",
+    "poetry.lock",
+];
+
+/// Lines containing this marker are never reported.
+const IGNORE_MARKER: &str = "zvault:ignore";
+
+/// One detector. The `value` capture group, if present, is the secret;
+/// otherwise the whole match is.
+struct Detector {
+    id: &'static str,
+    /// Secret name when no assignment key precedes the match.
+    default_name: &'static str,
+    regex: Regex,
+    /// Minimum entropy the value must reach, if any.
+    min_entropy: Option<f64>,
+}
+
+/// Detectors in priority order: an earlier match claims its span.
+fn detectors() -> Result<Vec<Detector>> {
+    let specs: [(&str, &str, &str, Option<f64>); 9] = [
+        (
+            "private-key",
+            "PRIVATE_KEY",
+            r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY-----[\s\S]*?-----END (?:[A-Z0-9]+ )*PRIVATE KEY-----",
+            None,
+        ),
+        (
+            "aws-access-key",
+            "AWS_ACCESS_KEY_ID",
+            r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+            None,
+        ),
+        (
+            "aws-secret-key",
+            "AWS_SECRET_ACCESS_KEY",
+            r#"(?i)aws_?secret_?(?:access_?)?key["']?\s*[:=]\s*["']?(?P<value>[A-Za-z0-9/+]{40})\b"#,
+            None,
+        ),
+        (
+            "github-token",
+            "GITHUB_TOKEN",
+            r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{80,})\b",
+            None,
+        ),
+        (
+            "slack-token",
+            "SLACK_TOKEN",
+            r"\bxox[abposr]-[A-Za-z0-9-]{10,}\b",
+            None,
+        ),
+        (
+            "stripe-key",
+            "STRIPE_SECRET_KEY",
+            r"\b[sr]k_live_[A-Za-z0-9]{20,}\b",
+            None,
+        ),
+        (
+            "jwt",
+            "JWT",
+            r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+            None,
+        ),
+        (
+            "sensitive-assignment",
+            "SECRET",
+            r#"(?i)[A-Za-z0-9_.-]*(?:secret|token|passw(?:or)?d|api_?key|access_?key|private_?key|credentials?)["']?\s*[:=]\s*["']?(?P<value>[^\s"'`,;]{8,})"#,
+            Some(ASSIGNMENT_ENTROPY_THRESHOLD),
+        ),
+        (
+            "high-entropy",
+            "SECRET",
+            r#"(?:[:=]\s*["']?|["'])(?P<value>[A-Za-z0-9+/=_-]{20,})"#,
+            Some(ENTROPY_THRESHOLD),
+        ),
+    ];
+    specs
+        .into_iter()
+        .map(|(id, default_name, pattern, min_entropy)| {
+            Ok(Detector {
+                id,
+                default_name,
+                regex: Regex::new(pattern).with_context(|| format!("invalid detector {id}"))?,
+                min_entropy,
+            })
+        })
+        .collect()
+}
+
+/// One possible secret in one file.
+struct Finding {
+    file: PathBuf,
+    line: usize,
+    detector: &'static str,
+    /// Suggested secret name, e.g. `STRIPE_SECRET_KEY`.
+    name: String,
+    /// Byte range of the value in the file.
+    start: usize,
+    end: usize,
+    value: String,
+}
+
+/// Shannon entropy of `s` in bits per character.
+#[allow(clippy::cast_precision_loss)]
+fn entropy(s: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in s.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = s.chars().count() as f64;
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Whether a candidate value is plausibly a secret rather than a
+/// placeholder, reference, digest, or identifier.
+fn plausible(detector: &Detector, value: &str) -> bool {
+    if value.starts_with("zvault://") || value.contains("${") || value.contains("$(") {
+        return false;
+    }
+    let Some(min_entropy) = detector.min_entropy else {
+        return true;
+    };
+    // Hex digests, commit ids, and UUIDs.
+    if value.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return false;
+    }
+    // Paths and identifiers rarely mix letters with digits.
+    if detector.id == "high-entropy"
+        && !(value.chars().any(|c| c.is_ascii_digit())
+            && value.chars().any(|c| c.is_ascii_alphabetic()))
+    {
+        return false;
+    }
+    entropy(value) >= min_entropy
+}
+
+/// The key a value is assigned to on its line (`KEY=`, `key: `,
+/// `"key": "`), as an environment variable name.
+fn assigned_name(line_prefix: &str, key: &Regex) -> Option<String> {
+    let raw = key.captures(line_prefix)?.get(1)?.as_str();
+    let name: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_matches('_');
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+/// Run every detector over one file's contents.
+fn scan_content(file: &Path, content: &str, detectors: &[Detector], key: &Regex) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    for detector in detectors {
+        for captures in detector.regex.captures_iter(content) {
+            let Some(m) = captures.name("value").or_else(|| captures.get(0)) else {
+                continue;
+            };
+            let (start, end) = (m.start(), m.end());
+            if findings.iter().any(|f| start < f.end && f.start < end) {
+                continue;
+            }
+            if !plausible(detector, m.as_str()) {
+                continue;
+            }
+            let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = content[start..]
+                .find('\n')
+                .map_or(content.len(), |i| start + i);
+            if content[line_start..line_end].contains(IGNORE_MARKER) {
+                continue;
+            }
+            findings.push(Finding {
+                file: file.to_path_buf(),
+                line: content[..start].matches('\n').count() + 1,
+                detector: detector.id,
+                name: assigned_name(&content[line_start..start], key)
+                    .unwrap_or_else(|| detector.default_name.to_owned()),
+                start,
+                end,
+                value: m.as_str().to_owned(),
+            });
+        }
+    }
+    findings.sort_by_key(|f| f.start);
+    findings
+}
+
+/// Every text file under `root` that git would not ignore (or every file,
+/// with `no_ignore`).
+fn files(root: &Path, no_ignore: bool) -> Vec<PathBuf> {
+    let mut walker = ignore::WalkBuilder::new(root);
+    walker
+        .hidden(false)
+        .require_git(false)
+        .git_ignore(!no_ignore)
+        .git_exclude(!no_ignore)
+        .ignore(!no_ignore)
+        .filter_entry(|entry| entry.file_name() != ".git");
+    walker
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|meta| meta.len() <= MAX_FILE_SIZE)
+        })
+        .map(ignore::DirEntry::into_path)
+        .collect()
+}
+
+/// A few leading characters of a secret, enough to recognise it.
+fn preview(value: &str) -> String {
+    let head: String = value.chars().take(4).collect();
+    format!("{head}… ({} chars)", value.chars().count())
+}
+
+/// Give every distinct value its own name, suffixing `_2`, `_3`, … when
+/// two different values would share one.
+fn assign_names(findings: &mut [Finding]) {
+    let mut by_value: HashMap<String, String> = HashMap::new();
+    let mut taken: HashMap<String, String> = HashMap::new();
+    for finding in findings {
+        if let Some(name) = by_value.get(&finding.value) {
+            finding.name.clone_from(name);
+            continue;
+        }
+        let mut name = finding.name.clone();
+        let mut n = 1u32;
+        while taken.get(&name).is_some_and(|v| *v != finding.value) {
+            n = n.saturating_add(1);
+            name = format!("{}_{n}", finding.name);
+        }
+        taken.insert(name.clone(), finding.value.clone());
+        by_value.insert(finding.value.clone(), name.clone());
+        finding.name = name;
+    }
+}
+
+/// `[scan]` in `.zvault.toml`: findings that are known to be safe.
+///
+/// ```toml
+/// [scan]
+/// allow_paths = ["tests/fixtures/**", "docs/*.md"]
+/// allow_values = ["EXAMPLE", "^sk_test_"]
+/// ```
+#[derive(Default)]
+struct Allowlist {
+    /// Globs, relative to the scanned directory or repository root.
+    paths: GlobSet,
+    /// Regexes matched against each value.
+    values: Vec<Regex>,
+}
+
+impl Allowlist {
+    /// Read `[scan]` from `.zvault.toml` in the current directory, if any.
+    fn load() -> Result<Self> {
+        let Ok(content) = std::fs::read_to_string(PROJECT_CONFIG_FILE) else {
+            return Ok(Self::default());
+        };
+        // Minimal TOML parsing, as for `[cloud]`: string arrays, which may
+        // span lines.
+        let mut allowlist = Self::default();
+        let mut paths = GlobSetBuilder::new();
+        let mut in_scan = false;
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && !trimmed.contains('=') {
+                in_scan = trimmed == "[scan]";
+                continue;
+            }
+            let Some((key, value)) = trimmed.split_once('=') else {
+                continue;
+            };
+            if !in_scan {
+                continue;
+            }
+            let mut value = value.trim().to_owned();
+            while value.starts_with('[') && !value.contains(']') {
+                let Some(next) = lines.next() else { break };
+                value.push_str(next.trim());
+            }
+            let items = parse_array(&value);
+            match key.trim() {
+                "allow_paths" => {
+                    for item in items {
+                        paths.add(Glob::new(item).with_context(|| {
+                            format!("{PROJECT_CONFIG_FILE}: invalid allow_paths glob '{item}'")
+                        })?);
+                    }
+                }
+                "allow_values" => {
+                    for item in items {
+                        allowlist.values.push(Regex::new(item).with_context(|| {
+                            format!("{PROJECT_CONFIG_FILE}: invalid allow_values regex '{item}'")
+                        })?);
+                    }
+                }
+                other => bail!(
+                    "{PROJECT_CONFIG_FILE}: unknown key '{other}' in [scan], expected allow_paths or allow_values"
+                ),
+            }
+        }
+        allowlist.paths = paths.build()?;
+        Ok(allowlist)
+    }
+
+    fn allows_path(&self, path: &Path) -> bool {
+        self.paths.is_match(path)
+    }
+
+    fn allows_value(&self, value: &str) -> bool {
+        self.values.iter().any(|re| re.is_match(value))
+    }
+}
+
+/// The quoted strings of a one-level TOML array.
+pub(crate) fn parse_array(value: &str) -> Vec<&str> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .split(']')
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(|item| item.trim().trim_matches('"').trim_matches('\''))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Whether a file name is a plaintext env file rather than a reference
+/// file or a template.
+fn is_raw_env_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    name == ".env"
+        || name.strip_prefix(".env.").is_some_and(|suffix| {
+            !["zvault", "example", "sample", "template", "dist"].contains(&suffix)
+        })
+}
+
+/// Run `git` in the current directory and return its stdout.
+pub(crate) fn git(args: &[&str]) -> Result<Vec<u8>> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// The repository root and every staged file with its staged contents.
+/// Binary files are returned empty.
+fn staged_sources() -> Result<(PathBuf, Vec<(PathBuf, String)>)> {
+    let root = String::from_utf8(git(&["rev-parse", "--show-toplevel"])?)
+        .context("repository path is not UTF-8")?;
+    let root = PathBuf::from(root.trim());
+    let names = git(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "-z",
+        "--diff-filter=ACMR",
+    ])?;
+    let mut sources = Vec::new();
+    for name in names.split(|b| *b == 0).filter(|n| !n.is_empty()) {
+        let name = String::from_utf8_lossy(name).into_owned();
+        let blob = git(&["cat-file", "blob", &format!(":{name}")])?;
+        let content = if blob.len() as u64 > MAX_FILE_SIZE {
+            String::new()
+        } else {
+            String::from_utf8(blob).unwrap_or_default()
+        };
+        sources.push((root.join(name), content));
+    }
+    Ok((root, sources))
+}
+
+/// Every file under `root` with its contents (see [`files`]). Binary and
+/// non-UTF-8 files are returned empty.
+fn tree_sources(root: &Path, no_ignore: bool) -> Vec<(PathBuf, String)> {
+    files(root, no_ignore)
+        .into_iter()
+        .map(|file| {
+            let content = std::fs::read_to_string(&file).unwrap_or_default();
+            (file, content)
+        })
+        .collect()
+}
+
+/// What `zvault scan` looks at.
+pub(crate) enum ScanTarget<'a> {
+    /// Files under a path, skipping gitignored ones unless `no_ignore`.
+    Tree { path: &'a str, no_ignore: bool },
+    /// The files staged for commit.
+    Staged,
+}
+
+/// Scan for credentials, optionally moving them into the vault.
+pub(crate) async fn cmd_scan(
+    client: &Client,
+    target: ScanTarget<'_>,
+    import: bool,
+    project: Option<&str>,
+    exit_code: bool,
+) -> Result<()> {
+    let allowlist = Allowlist::load()?;
+    let staged = matches!(target, ScanTarget::Staged);
+    let (root, sources) = match target {
+        ScanTarget::Staged => staged_sources()?,
+        ScanTarget::Tree { path, no_ignore } => {
+            let root = PathBuf::from(path);
+            if !root.exists() {
+                bail!("path not found: {path}");
+            }
+            let sources = tree_sources(&root, no_ignore);
+            (root, sources)
+        }
+    };
+    let detectors = detectors()?;
+    let key = Regex::new(r#"([A-Za-z_][A-Za-z0-9_.-]*)["']?\s*[:=]\s*["']?\s*$"#)?;
+
+    let mut findings = Vec::new();
+    let mut env_files = Vec::new();
+    let mut scanned = 0usize;
+    for (file, content) in &sources {
+        let relative = file.strip_prefix(&root).unwrap_or(file);
+        if SKIPPED_FILES
+            .iter()
+            .any(|skipped| file.file_name().is_some_and(|name| name == *skipped))
+            || allowlist.allows_path(relative)
+        {
+            continue;
+        }
+        scanned = scanned.saturating_add(1);
+        // Committing any plaintext env file is a mistake, whatever it holds.
+        if staged && is_raw_env_file(file) {
+            env_files.push(relative.to_path_buf());
+        }
+        if content.contains('\0') {
+            continue;
+        }
+        findings.extend(
+            scan_content(file, content, &detectors, &key)
+                .into_iter()
+                .filter(|f| !allowlist.allows_value(&f.value)),
+        );
+    }
+    assign_names(&mut findings);
+
+    outln!();
+    if staged {
+        header("🔍", "Scanning staged changes");
+    } else {
+        header("🔍", &format!("Scanning {}", root.display()));
+    }
+    outln!();
+    print_findings(&root, &env_files, &findings);
+    let affected: std::collections::BTreeSet<&PathBuf> = findings.iter().map(|f| &f.file).collect();
+    crate::output::record(&serde_json::json!({
+        "files_scanned": scanned,
+        "env_files": env_files,
+        "findings": findings
+            .iter()
+            .map(|f| serde_json::json!({
+                "file": f.file.strip_prefix(&root).unwrap_or(&f.file),
+                "line": f.line,
+                "detector": f.detector,
+                "name": f.name,
+            }))
+            .collect::<Vec<_>>(),
+    }));
+
+    if findings.is_empty() && env_files.is_empty() {
+        success(&format!("No secrets found in {scanned} files"));
+        outln!();
+        return Ok(());
+    }
+    outln!();
+    if !env_files.is_empty() {
+        warning(&format!(
+            "{} plaintext env files are staged",
+            env_files.len()
+        ));
+    }
+    if !findings.is_empty() {
+        warning(&format!(
+            "Found {} possible secrets in {} of {scanned} files",
+            findings.len(),
+            affected.len()
+        ));
+    }
+    outln!();
+
+    if import {
+        import_findings(client, &findings, project).await?;
+    } else if exit_code {
+        fail(&root, staged, findings.len(), env_files.len())?;
+    }
+    Ok(())
+}
+
+/// One line per finding, never showing more than a value's first characters.
+fn print_findings(root: &Path, env_files: &[PathBuf], findings: &[Finding]) {
+    for file in env_files {
+        outln!(
+            "  {CYAN}├─{RESET} {BOLD}{}{RESET}  {YELLOW}env-file{RESET}  {DIM}plaintext env file — use `zvault import`{RESET}",
+            file.display()
+        );
+    }
+    for finding in findings {
+        let file = finding.file.strip_prefix(root).unwrap_or(&finding.file);
+        outln!(
+            "  {CYAN}├─{RESET} {BOLD}{}:{}{RESET}  {YELLOW}{}{RESET}  {DIM}{}{RESET}  → {}",
+            file.display(),
+            finding.line,
+            finding.detector,
+            preview(&finding.value),
+            finding.name
+        );
+    }
+}
+
+/// The `--exit-code` error, with advice when blocking a commit.
+fn fail(root: &Path, staged: bool, findings: usize, env_files: usize) -> Result<()> {
+    if !staged {
+        bail!("found {findings} possible secrets under {}", root.display());
+    }
+    outln!(
+        "  {DIM}Move them into the vault (`zvault scan --import`), allowlist them in\n  .zvault.toml [scan], or commit with --no-verify.{RESET}"
+    );
+    outln!();
+    bail!("commit blocked: {findings} possible secrets and {env_files} env files staged");
+}
+
+/// Store each finding at `env/<project>/<NAME>` and replace it in its file
+/// with the `zvault://` reference.
+async fn import_findings(
+    client: &Client,
+    findings: &[Finding],
+    project: Option<&str>,
+) -> Result<()> {
+    let project = match project {
+        Some(p) => p.to_owned(),
+        None => super::detect_project_name()?,
+    };
+
+    let mut stored: HashMap<&str, bool> = HashMap::new();
+    for finding in findings {
+        if stored.contains_key(finding.name.as_str()) {
+            continue;
+        }
+        let body = serde_json::json!({ "data": { "value": finding.value } });
+        let ok = match client
+            .post(
+                &format!("/v1/secret/data/env/{project}/{}", finding.name),
+                &body,
+            )
+            .await
+        {
+            Ok(_) => {
+                outln!(
+                    "  {GREEN}✓{RESET} {} → {DIM}zvault://env/{project}/{}{RESET}",
+                    finding.name,
+                    finding.name
+                );
+                true
+            }
+            Err(e) => {
+                outln!("  {RED}✗{RESET} {} — {RED}{e}{RESET}", finding.name);
+                false
+            }
+        };
+        stored.insert(&finding.name, ok);
+    }
+
+    // Rewrite each file back to front so earlier offsets stay valid; values
+    // that failed to store are left where they are.
+    let mut rewritten = 0usize;
+    let mut by_file: std::collections::BTreeMap<&PathBuf, Vec<&Finding>> =
+        std::collections::BTreeMap::new();
+    for finding in findings {
+        by_file.entry(&finding.file).or_default().push(finding);
+    }
+    for (file, file_findings) in by_file {
+        let mut content = std::fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let mut replaced = false;
+        for finding in file_findings.iter().rev() {
+            if stored.get(finding.name.as_str()) == Some(&true) {
+                content.replace_range(
+                    finding.start..finding.end,
+                    &format!("zvault://env/{project}/{}", finding.name),
+                );
+                replaced = true;
+            }
+        }
+        if !replaced {
+            continue;
+        }
+        std::fs::write(file, content)
+            .with_context(|| format!("failed to write {}", file.display()))?;
+        rewritten = rewritten.saturating_add(1);
+    }
+
+    let imported = stored.values().filter(|ok| **ok).count();
+    let failed = stored.len().saturating_sub(imported);
+    outln!();
+    if failed == 0 {
+        success(&format!(
+            "Imported {imported} secrets into {BOLD}env/{project}/{RESET} and replaced them with zvault:// references in {rewritten} files"
+        ));
+    } else {
+        warning(&format!(
+            "Imported {imported} secrets, {failed} failed and were left in place"
+        ));
+    }
+    outln!();
+    Ok(())
+}
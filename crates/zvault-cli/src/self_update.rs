@@ -0,0 +1,398 @@
+//! CLI self-update and server compatibility checks.
+//!
+//! `zvault self-update` fetches a release manifest, downloads the binary for
+//! the current platform, verifies its Ed25519 signature against the release
+//! key embedded at build time, and swaps it in place of the running
+//! executable with an atomic rename. Builds made without the key (anything
+//! but the release workflow) refuse to self-update. A release older than the
+//! running CLI is only installed when pinned with `--to`. `zvault version
+//! --check` compares the CLI version against the minimum the server
+//! advertises at `GET /v1/sys/version`.
+//!
+//! # Release manifest
+//!
+//! The release workflow signs each platform's binary and attaches it to the
+//! GitHub release with `latest.json` and `v<version>.json`. By default the
+//! manifest is fetched from there — `latest.json` from the latest release,
+//! `v<version>.json` from that version's. `ZVAULT_RELEASE_URL` points at a
+//! mirror serving both names from one directory instead.
+//!
+//! ```json
+//! {
+//!   "version": "0.3.0",
+//!   "assets": {
+//!     "linux-x86_64": {
+//!       "url": "https://github.com/VanitasCaesar1/zvault/releases/download/v0.3.0/zvault-cli-linux-x86_64",
+//!       "signature": "<base64 Ed25519 signature, see below>"
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! The signature covers `zvault-cli-release\n<version>\n<platform>\n`
+//! followed by the binary, so a signed binary cannot be passed off as
+//! another version or platform — say, an old release with a known flaw
+//! served as the latest one.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::Deserialize;
+
+use super::{BOLD, CYAN, DIM, RESET, YELLOW, header, kv_line, success, warning};
+
+/// GitHub releases the release workflow publishes manifests and binaries to.
+const GITHUB_RELEASES: &str = "https://github.com/VanitasCaesar1/zvault/releases";
+
+// ── Embedded release key ─────────────────────────────────────────────
+//
+// Base64 Ed25519 public key that signs release binaries, from the
+// `ZVAULT_RELEASE_PUBLIC_KEY` repository variable at build time; empty in
+// builds made outside the release workflow. The private key is the
+// workflow's `ZVAULT_RELEASE_SIGNING_KEY` secret. Rotating it requires
+// shipping a CLI build with the new key before publishing binaries signed
+// by it.
+const RELEASE_PUBLIC_KEY_B64: &str = env!("ZVAULT_RELEASE_PUBLIC_KEY");
+
+/// Version of this CLI build.
+const CLI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Release manifest published alongside each CLI release.
+#[derive(Debug, Deserialize)]
+struct ReleaseManifest {
+    version: String,
+    assets: HashMap<String, ReleaseAsset>,
+}
+
+/// Download location and signature for one platform.
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    url: String,
+    signature: String,
+}
+
+/// Platform key used in the manifest, e.g. `linux-x86_64`.
+pub(crate) fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Parse `MAJOR.MINOR.PATCH`, ignoring a leading `v` and any pre-release suffix.
+fn parse_version(v: &str) -> Option<(u64, u64, u64)> {
+    let core = v.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Whether `current` is strictly older than `required`.
+fn is_older(current: &str, required: &str) -> Result<bool> {
+    let cur = parse_version(current).with_context(|| format!("invalid version '{current}'"))?;
+    let req = parse_version(required).with_context(|| format!("invalid version '{required}'"))?;
+    Ok(cur < req)
+}
+
+/// The message the release key signs for `binary`, released as `version`
+/// for `platform`.
+fn signed_payload(version: &str, platform: &str, binary: &[u8]) -> Vec<u8> {
+    let mut payload = format!("zvault-cli-release\n{version}\n{platform}\n").into_bytes();
+    payload.extend_from_slice(binary);
+    payload
+}
+
+/// Verify `data` against a base64 signature using the base64 public key
+/// `key_b64` — [`RELEASE_PUBLIC_KEY_B64`] outside tests.
+fn verify_signature(key_b64: &str, data: &[u8], signature_b64: &str) -> Result<()> {
+    if key_b64.is_empty() {
+        bail!("this build has no release signing key; install a release build to self-update");
+    }
+    let sig_bytes = BASE64
+        .decode(signature_b64.trim())
+        .context("release signature is not valid base64")?;
+    let signature = Signature::from_slice(&sig_bytes)
+        .map_err(|e| anyhow::anyhow!("invalid release signature format: {e}"))?;
+
+    let pk_bytes = BASE64
+        .decode(key_b64)
+        .context("embedded release key is invalid (this is a build error)")?;
+    let pk_array: [u8; 32] = pk_bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("embedded release key has wrong length"))?;
+    let key = VerifyingKey::from_bytes(&pk_array)
+        .map_err(|e| anyhow::anyhow!("invalid embedded release key: {e}"))?;
+
+    key.verify(data, &signature)
+        .map_err(|_| anyhow::anyhow!("release signature verification failed — refusing to install"))
+}
+
+/// URL of the manifest for `latest` or a pinned version: on a mirror at
+/// `mirror`, or on GitHub releases.
+fn manifest_url(mirror: Option<&str>, pin: Option<&str>) -> String {
+    let pin = pin.map(|v| v.trim_start_matches('v'));
+    match (mirror, pin) {
+        (Some(base), None) => format!("{}/latest.json", base.trim_end_matches('/')),
+        (Some(base), Some(v)) => format!("{}/v{v}.json", base.trim_end_matches('/')),
+        (None, None) => format!("{GITHUB_RELEASES}/latest/download/latest.json"),
+        (None, Some(v)) => format!("{GITHUB_RELEASES}/download/v{v}/v{v}.json"),
+    }
+}
+
+/// Fetch the manifest for `latest` or a pinned version.
+async fn fetch_manifest(http: &reqwest::Client, pin: Option<&str>) -> Result<ReleaseManifest> {
+    let mirror = std::env::var("ZVAULT_RELEASE_URL").ok();
+    let url = manifest_url(mirror.as_deref(), pin);
+
+    let resp = http
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("failed to reach release endpoint {url}"))?;
+    if !resp.status().is_success() {
+        bail!("release endpoint returned {} for {url}", resp.status());
+    }
+    resp.json()
+        .await
+        .context("release manifest is malformed JSON")
+}
+
+/// Write `data` next to `target` and rename it into place.
+///
+/// The rename is atomic on the same filesystem, so a crash leaves either the
+/// old or the new binary — never a partial one.
+fn replace_executable(target: &Path, data: &[u8]) -> Result<()> {
+    let dir = target
+        .parent()
+        .context("cannot determine directory of the current executable")?;
+    let staging: PathBuf = dir.join(format!(".zvault-update-{}", std::process::id()));
+
+    std::fs::write(&staging, data)
+        .with_context(|| format!("failed to write {}", staging.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staging, std::fs::Permissions::from_mode(0o755))
+            .context("failed to mark new binary executable")?;
+    }
+
+    // Windows cannot overwrite a running executable, but it can rename it.
+    #[cfg(windows)]
+    {
+        let old = target.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(target, &old).context("failed to move current binary aside")?;
+    }
+
+    if let Err(e) = std::fs::rename(&staging, target) {
+        let _ = std::fs::remove_file(&staging);
+        return Err(e).with_context(|| format!("failed to replace {}", target.display()));
+    }
+    Ok(())
+}
+
+/// `zvault self-update` — download, verify, and install a CLI release.
+pub async fn cmd_self_update(check_only: bool, pin: Option<&str>, force: bool) -> Result<()> {
+    header("⬆", "Self-update");
+
+    let http = reqwest::Client::new();
+    let manifest = fetch_manifest(&http, pin).await?;
+
+    kv_line("Current", CLI_VERSION);
+    kv_line("Available", &manifest.version);
+
+    if let Some(pin) = pin {
+        if parse_version(pin) != parse_version(&manifest.version) {
+            bail!(
+                "manifest for {pin} describes version {} — refusing to install",
+                manifest.version
+            );
+        }
+    }
+
+    let newer = is_older(CLI_VERSION, &manifest.version)?;
+    if check_only {
+        if newer {
+            outln!();
+            outln!(
+                "  {YELLOW}A new version is available.{RESET} Run {CYAN}zvault self-update{RESET}."
+            );
+        } else {
+            success("CLI is up to date");
+        }
+        outln!();
+        return Ok(());
+    }
+
+    if pin.is_none() && is_older(&manifest.version, CLI_VERSION)? {
+        bail!(
+            "release {} is older than this CLI ({CLI_VERSION}); pass --to {} to downgrade",
+            manifest.version,
+            manifest.version
+        );
+    }
+    if !newer && pin.is_none() && !force {
+        success("CLI is up to date");
+        outln!();
+        return Ok(());
+    }
+
+    let platform = platform();
+    let asset = manifest
+        .assets
+        .get(&platform)
+        .with_context(|| format!("release {} has no binary for {platform}", manifest.version))?;
+
+    outln!("  {DIM}Downloading {}...{RESET}", asset.url);
+    let resp = http
+        .get(&asset.url)
+        .send()
+        .await
+        .context("binary download failed")?;
+    if !resp.status().is_success() {
+        bail!("binary download returned {}", resp.status());
+    }
+    let data = resp.bytes().await.context("binary download interrupted")?;
+
+    verify_signature(
+        RELEASE_PUBLIC_KEY_B64,
+        &signed_payload(&manifest.version, &platform, &data),
+        &asset.signature,
+    )?;
+    kv_line("Signature", "verified (Ed25519)");
+
+    let target = std::env::current_exe().context("cannot locate the running executable")?;
+    let target = target.canonicalize().unwrap_or(target);
+    replace_executable(&target, &data)?;
+
+    success(&format!(
+        "Updated {BOLD}zvault{RESET} {CLI_VERSION} → {}",
+        manifest.version
+    ));
+    outln!();
+    Ok(())
+}
+
+/// `zvault version` — print the CLI version, optionally checking server compatibility.
+pub async fn cmd_version(addr: &str, check: bool) -> Result<()> {
+    header("ℹ", "Version");
+    kv_line("CLI", crate::build_info::LONG_VERSION);
+
+    if !check {
+        outln!();
+        return Ok(());
+    }
+
+    let url = format!("{}/v1/sys/version", addr.trim_end_matches('/'));
+    let resp = reqwest::Client::new()
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("failed to reach server at {addr}"))?;
+    if !resp.status().is_success() {
+        bail!("server returned {} for /v1/sys/version", resp.status());
+    }
+    let body: serde_json::Value = resp.json().await.context("invalid version response")?;
+
+    let server_version = body
+        .get("version")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("unknown");
+    kv_line("Server", server_version);
+
+    if let Some(min) = body
+        .get("min_cli_version")
+        .and_then(serde_json::Value::as_str)
+    {
+        kv_line("Min CLI", min);
+        if is_older(CLI_VERSION, min)? {
+            outln!();
+            warning(&format!(
+                "this CLI ({CLI_VERSION}) is older than the server's minimum supported version ({min}) — run `zvault self-update`"
+            ));
+            outln!();
+            bail!("CLI version {CLI_VERSION} is not supported by this server");
+        }
+    }
+
+    success("CLI is compatible with the server");
+    outln!();
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    fn release_key() -> (SigningKey, String) {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let public = BASE64.encode(key.verifying_key().as_bytes());
+        (key, public)
+    }
+
+    #[test]
+    fn signature_covers_version_platform_and_binary() {
+        let (key, public) = release_key();
+        let payload = signed_payload("0.3.0", "linux-x86_64", b"binary");
+        let signature = BASE64.encode(key.sign(&payload).to_bytes());
+        verify_signature(&public, &payload, &signature).unwrap();
+
+        for tampered in [
+            signed_payload("0.2.9", "linux-x86_64", b"binary"),
+            signed_payload("0.3.0", "macos-aarch64", b"binary"),
+            signed_payload("0.3.0", "linux-x86_64", b"binarY"),
+        ] {
+            assert!(verify_signature(&public, &tampered, &signature).is_err());
+        }
+        let other = BASE64.encode(SigningKey::from_bytes(&[8; 32]).verifying_key().as_bytes());
+        assert!(verify_signature(&other, &payload, &signature).is_err());
+        assert!(verify_signature("", &payload, &signature).is_err());
+        assert!(verify_signature(&public, &payload, "not base64!").is_err());
+    }
+
+    #[test]
+    fn signed_payload_prefixes_the_binary() {
+        assert_eq!(
+            signed_payload("0.3.0", "linux-x86_64", b"\x7fELF"),
+            b"zvault-cli-release\n0.3.0\nlinux-x86_64\n\x7fELF"
+        );
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(is_older("0.2.9", "0.3.0").unwrap());
+        assert!(is_older("v0.9.0", "0.10.0").unwrap());
+        assert!(is_older("1", "1.0.1").unwrap());
+        assert!(!is_older("0.3.0", "0.3.0-rc.1").unwrap());
+        assert!(!is_older("1.0.0", "0.99.99").unwrap());
+        assert!(is_older("latest", "0.3.0").is_err());
+    }
+
+    #[test]
+    fn manifests_come_from_github_releases_or_a_mirror() {
+        assert_eq!(
+            manifest_url(None, None),
+            format!("{GITHUB_RELEASES}/latest/download/latest.json")
+        );
+        assert_eq!(
+            manifest_url(None, Some("v0.3.0")),
+            format!("{GITHUB_RELEASES}/download/v0.3.0/v0.3.0.json")
+        );
+        assert_eq!(
+            manifest_url(Some("https://mirror.internal/zvault/"), Some("0.3.0")),
+            "https://mirror.internal/zvault/v0.3.0.json"
+        );
+        assert_eq!(
+            manifest_url(Some("https://mirror.internal/zvault"), None),
+            "https://mirror.internal/zvault/latest.json"
+        );
+    }
+}
@@ -77,11 +77,11 @@ globs: **/*.env*, **/docker-compose*, **/.env.zvault
     std::fs::write(&zvault_rule, rule_content)
         .with_context(|| format!("failed to write {}", zvault_rule.display()))?;
 
-    println!("  ✓ Created {}", config_path.display());
-    println!("  ✓ Created {}", zvault_rule.display());
-    println!();
-    println!("  Cursor is now configured to use ZVault as an MCP server.");
-    println!("  Make sure VAULT_TOKEN is set in your environment.");
+    outln!("  ✓ Created {}", config_path.display());
+    outln!("  ✓ Created {}", zvault_rule.display());
+    outln!();
+    outln!("  Cursor is now configured to use ZVault as an MCP server.");
+    outln!("  Make sure VAULT_TOKEN is set in your environment.");
 
     Ok(())
 }
@@ -158,12 +158,12 @@ zvault run -- python manage.py runserver
     std::fs::write(&steering_path, steering_content)
         .with_context(|| format!("failed to write {}", steering_path.display()))?;
 
-    println!("  ✓ Created {}", config_path.display());
-    println!("  ✓ Created {}", steering_path.display());
-    println!();
-    println!("  Kiro is now configured to use ZVault as an MCP server.");
-    println!("  Read-only tools are auto-approved. Write tools require confirmation.");
-    println!("  Make sure VAULT_TOKEN is set in your environment.");
+    outln!("  ✓ Created {}", config_path.display());
+    outln!("  ✓ Created {}", steering_path.display());
+    outln!();
+    outln!("  Kiro is now configured to use ZVault as an MCP server.");
+    outln!("  Read-only tools are auto-approved. Write tools require confirmation.");
+    outln!("  Make sure VAULT_TOKEN is set in your environment.");
 
     Ok(())
 }
@@ -191,10 +191,10 @@ fn setup_continue() -> Result<()> {
 
     write_json_config(&config_path, &config)?;
 
-    println!("  ✓ Created {}", config_path.display());
-    println!();
-    println!("  Continue is now configured to use ZVault as an MCP server.");
-    println!("  Make sure VAULT_TOKEN is set in your environment.");
+    outln!("  ✓ Created {}", config_path.display());
+    outln!();
+    outln!("  Continue is now configured to use ZVault as an MCP server.");
+    outln!("  Make sure VAULT_TOKEN is set in your environment.");
 
     Ok(())
 }
@@ -237,10 +237,10 @@ zvault run -- npm run dev   # Run with secrets injected
     std::fs::write(llms_path, content)
         .with_context(|| format!("failed to write {}", llms_path.display()))?;
 
-    println!("  ✓ Created {}", llms_path.display());
-    println!();
-    println!("  Generic setup complete. Add llms.txt to your repo for AI context.");
-    println!("  For IDE-specific setup, use: zvault setup cursor|kiro|continue");
+    outln!("  ✓ Created {}", llms_path.display());
+    outln!();
+    outln!("  Generic setup complete. Add llms.txt to your repo for AI context.");
+    outln!("  For IDE-specific setup, use: zvault setup cursor|kiro|continue");
 
     Ok(())
 }
@@ -285,7 +285,7 @@ fn write_json_config(path: &Path, value: &serde_json::Value) -> Result<()> {
         std::fs::write(path, pretty)
             .with_context(|| format!("failed to write {}", path.display()))?;
 
-        println!("  ✓ Merged zvault config into {}", path.display());
+        outln!("  ✓ Merged zvault config into {}", path.display());
     } else {
         let pretty = serde_json::to_string_pretty(value).context("failed to serialize config")?;
         std::fs::write(path, pretty)
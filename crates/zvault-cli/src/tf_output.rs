@@ -0,0 +1,205 @@
+//! `zvault tf-output`: secrets as a flat map for Terraform.
+//!
+//! Terraform's `external` data source runs a program and reads one JSON
+//! object of string values from its stdout. `tf-output <prefix>` prints
+//! every field of every secret under the prefix that way, named after the
+//! secret's path and the field (`db/main` `password` → `db_main_password`),
+//! until a full provider exists. With `--hcl` the same map is printed as
+//! `name = "value"` lines for a `.tfvars` file.
+//!
+//! Without a prefix argument, the `query` Terraform sends on stdin is read
+//! instead: `prefix`, and optionally `key`, a comma-separated list of
+//! patterns like `--key`:
+//!
+//! ```hcl
+//! data "external" "db" {
+//!   program = ["zvault", "tf"]
+//!   query   = { prefix = "myapp/", key = "db/*" }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Read as _;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use super::kv_tree::{as_dir, list_tree};
+use super::{Client, kv_payload};
+
+/// How `tf-output` names and prints values.
+pub(crate) struct TfOptions {
+    /// Only keys (`<path>/<field>`) matching one of these patterns.
+    pub(crate) keys: Vec<String>,
+    /// Name values by field alone.
+    pub(crate) field_only: bool,
+    /// Joins path segments and the field in names.
+    pub(crate) separator: String,
+    /// `keep`, `upper`, or `lower`.
+    pub(crate) case: String,
+    pub(crate) hcl: bool,
+}
+
+/// Print the secrets under `prefix`, or under Terraform's query.
+pub(crate) async fn cmd_tf_output(
+    client: &Client,
+    prefix: Option<&str>,
+    mut options: TfOptions,
+) -> Result<()> {
+    let prefix = match prefix {
+        Some(prefix) => prefix.to_owned(),
+        None => read_query(&mut options)?,
+    };
+    let dir = as_dir(&prefix);
+
+    let mut outputs: BTreeMap<String, String> = BTreeMap::new();
+    let mut sources: BTreeMap<String, String> = BTreeMap::new();
+    let mut found = false;
+    for path in list_tree(client, "secret", &dir).await? {
+        let resp = client
+            .get(&format!("/v1/secret/data/{dir}{path}"))
+            .await
+            .with_context(|| format!("failed to read {dir}{path}"))?;
+        let Some(fields) = kv_payload(&resp).as_object() else {
+            continue;
+        };
+        found = true;
+        for (field, value) in fields {
+            let key = format!("{path}/{field}");
+            if !options.keys.is_empty() && !options.keys.iter().any(|p| matches(p, &key)) {
+                continue;
+            }
+            let name = output_name(&path, field, &options);
+            if options.hcl && !is_identifier(&name) {
+                bail!(
+                    "{dir}{key} would be named '{name}', which is not a valid HCL identifier; \
+                     try --separator _"
+                );
+            }
+            if let Some(other) = sources.insert(name.clone(), key.clone()) {
+                bail!("{dir}{other} and {dir}{key} would both be named '{name}'");
+            }
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            outputs.insert(name, value);
+        }
+    }
+    if !found {
+        bail!("no secrets under secret/{dir}");
+    }
+    if outputs.is_empty() {
+        bail!(
+            "no keys under secret/{dir} match {}",
+            options.keys.join(", ")
+        );
+    }
+
+    if options.hcl {
+        let mut hcl = String::new();
+        for (name, value) in &outputs {
+            writeln!(hcl, "{name} = {}", hcl_string(value))?;
+        }
+        print!("{hcl}");
+    } else {
+        println!("{}", serde_json::to_string(&outputs)?);
+    }
+    Ok(())
+}
+
+/// Read Terraform's `query` object from stdin, returning its prefix and
+/// adding its `key` patterns to `options`.
+fn read_query(options: &mut TfOptions) -> Result<String> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .context("failed to read stdin")?;
+    let query: BTreeMap<String, String> = serde_json::from_str(&input)
+        .context("expected a prefix argument, or a Terraform query object on stdin")?;
+    if let Some(unknown) = query.keys().find(|k| *k != "prefix" && *k != "key") {
+        bail!("unknown query argument '{unknown}', expected prefix and key");
+    }
+    if let Some(keys) = query.get("key") {
+        options.keys.extend(
+            keys.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(str::to_owned),
+        );
+    }
+    query
+        .get("prefix")
+        .cloned()
+        .context("the Terraform query needs a prefix")
+}
+
+/// `db/main` and `password` → `db_main_password`, or `password` with
+/// `--field-only`.
+fn output_name(path: &str, field: &str, options: &TfOptions) -> String {
+    let name = if options.field_only {
+        field.to_owned()
+    } else {
+        path.split('/')
+            .chain(std::iter::once(field))
+            .collect::<Vec<_>>()
+            .join(&options.separator)
+    };
+    match options.case.as_str() {
+        "upper" => name.to_uppercase(),
+        "lower" => name.to_lowercase(),
+        _ => name,
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any characters.
+fn matches(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut text) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match text.find(part) {
+            Some(at) => text = &text[at + part.len()..],
+            None => return false,
+        }
+    }
+    text.ends_with(last)
+}
+
+/// HCL identifiers: a letter or `_`, then letters, digits, `_`, and `-`.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// A quoted HCL string, with template sequences escaped.
+fn hcl_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '$' | '%' if chars.peek() == Some(&'{') => {
+                quoted.push(c);
+                quoted.push(c);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,702 @@
+//! `zvault tui`: an interactive terminal dashboard.
+//!
+//! Three tabs over the REST API:
+//! - Overview: seal status, mounts, lease and rotation policy counts, and
+//!   the latest audit entries
+//! - Secrets: browse and search the `secret/` KV tree, view a secret's
+//!   metadata (never its values), and trigger its rotation
+//! - Audit: the most recent audit log entries
+//!
+//! Data refreshes every few seconds and on `r`. Failed requests show in
+//! the status line rather than closing the dashboard.
+
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState, Tabs,
+};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::Value;
+
+use super::Client;
+
+/// How often the overview and audit log refresh on their own.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Audit entries fetched per refresh.
+const AUDIT_LIMIT: usize = 100;
+
+/// Audit entries shown on the overview.
+const OVERVIEW_AUDIT_ROWS: u16 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Overview,
+    Secrets,
+    Audit,
+}
+
+impl Tab {
+    const ALL: [Self; 3] = [Self::Overview, Self::Secrets, Self::Audit];
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Overview => "1 Overview",
+            Self::Secrets => "2 Secrets",
+            Self::Audit => "3 Audit",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&t| t == self).unwrap_or(0)
+    }
+}
+
+/// One row of the KV browser.
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Shown name, relative to the browser's prefix.
+    name: String,
+    /// Full path within the mount.
+    path: String,
+    folder: bool,
+}
+
+/// State of the Secrets tab.
+#[derive(Default)]
+struct Browser {
+    /// Folder being shown: empty for the mount root, else ending in `/`.
+    prefix: String,
+    /// Every key under `prefix`, relative to it.
+    keys: Vec<String>,
+    /// Rows shown: `prefix`'s children, or keys matching `filter`.
+    entries: Vec<Entry>,
+    filter: String,
+    searching: bool,
+    list: ListState,
+    /// Metadata of the selected secret, and its path.
+    metadata: Option<(String, Value)>,
+}
+
+impl Browser {
+    /// Rebuild `entries` from `keys` and `filter`.
+    fn rebuild(&mut self) {
+        self.entries = if self.filter.is_empty() {
+            let mut entries: Vec<Entry> = Vec::new();
+            for key in &self.keys {
+                let (name, folder) = match key.split_once('/') {
+                    Some((dir, _)) => (format!("{dir}/"), true),
+                    None => (key.clone(), false),
+                };
+                if !entries.iter().any(|e| e.name == name) {
+                    entries.push(Entry {
+                        path: format!("{}{name}", self.prefix),
+                        name,
+                        folder,
+                    });
+                }
+            }
+            entries.sort_by(|a, b| b.folder.cmp(&a.folder).then_with(|| a.name.cmp(&b.name)));
+            entries
+        } else {
+            let needle = self.filter.to_lowercase();
+            let mut entries: Vec<Entry> = self
+                .keys
+                .iter()
+                .filter(|key| key.to_lowercase().contains(&needle))
+                .map(|key| Entry {
+                    name: key.clone(),
+                    path: format!("{}{key}", self.prefix),
+                    folder: false,
+                })
+                .collect();
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+            entries
+        };
+        let selected = self
+            .list
+            .selected()
+            .map(|i| i.min(self.entries.len().saturating_sub(1)));
+        self.list
+            .select(selected.or(Some(0)).filter(|_| !self.entries.is_empty()));
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.list.selected().and_then(|i| self.entries.get(i))
+    }
+}
+
+/// Everything the dashboard shows.
+struct App {
+    addr: String,
+    tab: Tab,
+    health: Option<Value>,
+    mounts: Vec<Value>,
+    lease_count: Option<usize>,
+    rotation_count: Option<usize>,
+    audit: Vec<Value>,
+    audit_table: TableState,
+    browser: Browser,
+    /// Message for the status line.
+    status: Option<(String, bool)>,
+    /// Secret awaiting confirmation of a rotation.
+    confirm_rotate: Option<String>,
+    last_refresh: Instant,
+    quit: bool,
+}
+
+impl App {
+    fn new(addr: String) -> Self {
+        Self {
+            addr,
+            tab: Tab::Overview,
+            health: None,
+            mounts: Vec::new(),
+            lease_count: None,
+            rotation_count: None,
+            audit: Vec::new(),
+            audit_table: TableState::default(),
+            browser: Browser::default(),
+            status: None,
+            confirm_rotate: None,
+            last_refresh: Instant::now(),
+            quit: false,
+        }
+    }
+
+    fn info(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), false));
+    }
+
+    fn error(&mut self, context: &str, error: &anyhow::Error) {
+        self.status = Some((format!("{context}: {error:#}"), true));
+    }
+
+    /// Reload the overview and the audit log.
+    async fn refresh(&mut self, client: &Client) {
+        self.last_refresh = Instant::now();
+        match client.get_no_auth("/v1/sys/health").await {
+            Ok(health) => self.health = Some(health),
+            Err(e) => {
+                self.health = None;
+                self.error("health", &e);
+                return;
+            }
+        }
+        match client.get("/v1/sys/mounts").await {
+            Ok(resp) => self.mounts = array(&resp, "mounts"),
+            Err(e) => self.error("mounts", &e),
+        }
+        match client.get("/v1/sys/leases").await {
+            Ok(resp) => self.lease_count = Some(array(&resp, "leases").len()),
+            Err(e) => self.error("leases", &e),
+        }
+        match client.get("/v1/sys/rotation/policies").await {
+            Ok(resp) => self.rotation_count = Some(array(&resp, "policies").len()),
+            Err(e) => self.error("rotation policies", &e),
+        }
+        match client
+            .get(&format!("/v1/sys/audit-log?limit={AUDIT_LIMIT}"))
+            .await
+        {
+            Ok(resp) => {
+                self.audit = array(&resp, "entries");
+                if self.audit_table.selected().is_none() && !self.audit.is_empty() {
+                    self.audit_table.select(Some(0));
+                }
+            }
+            Err(e) => self.error("audit log", &e),
+        }
+    }
+
+    /// List the keys under the browser's prefix.
+    async fn load_keys(&mut self, client: &Client) {
+        let path = if self.browser.prefix.is_empty() {
+            "/v1/secret/list".to_owned()
+        } else {
+            format!("/v1/secret/list/{}", self.browser.prefix)
+        };
+        match client.get(&path).await {
+            Ok(resp) => {
+                self.browser.keys = resp
+                    .pointer("/data/keys")
+                    .and_then(Value::as_array)
+                    .map(|keys| {
+                        keys.iter()
+                            .filter_map(Value::as_str)
+                            .map(|k| k.trim_start_matches('/').to_owned())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+            }
+            Err(e) => {
+                self.browser.keys.clear();
+                self.error(&format!("list secret/{}", self.browser.prefix), &e);
+            }
+        }
+        self.browser.rebuild();
+        self.load_metadata(client).await;
+    }
+
+    /// Fetch the metadata of the selected secret, if it changed.
+    async fn load_metadata(&mut self, client: &Client) {
+        let Some(entry) = self.browser.selected().filter(|e| !e.folder).cloned() else {
+            self.browser.metadata = None;
+            return;
+        };
+        if self
+            .browser
+            .metadata
+            .as_ref()
+            .is_some_and(|(path, _)| *path == entry.path)
+        {
+            return;
+        }
+        match client
+            .get(&format!("/v1/secret/metadata/{}", entry.path))
+            .await
+        {
+            Ok(meta) => self.browser.metadata = Some((entry.path, meta)),
+            Err(e) => {
+                self.browser.metadata = None;
+                self.error(&format!("metadata of {}", entry.path), &e);
+            }
+        }
+    }
+
+    async fn rotate(&mut self, client: &Client, path: &str) {
+        match client
+            .post_no_body(&format!("/v1/sys/rotation/rotate/secret/{path}"))
+            .await
+        {
+            Ok(resp) => {
+                let version = resp.get("version").and_then(Value::as_u64).unwrap_or(0);
+                self.info(format!("rotated secret/{path} to version {version}"));
+                self.browser.metadata = None;
+                self.load_metadata(client).await;
+            }
+            Err(e) => self.error(&format!("rotate secret/{path}"), &e),
+        }
+    }
+
+    async fn on_key(&mut self, client: &Client, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        if let Some(path) = self.confirm_rotate.take() {
+            if key.code == KeyCode::Char('y') {
+                self.rotate(client, &path).await;
+            } else {
+                self.info("rotation cancelled");
+            }
+            return;
+        }
+        if self.browser.searching {
+            self.on_search_key(client, key).await;
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab => self.tab = Tab::ALL[(self.tab.index() + 1) % Tab::ALL.len()],
+            KeyCode::BackTab => {
+                self.tab = Tab::ALL[(self.tab.index() + Tab::ALL.len() - 1) % Tab::ALL.len()];
+            }
+            KeyCode::Char('1') => self.tab = Tab::Overview,
+            KeyCode::Char('2') => self.tab = Tab::Secrets,
+            KeyCode::Char('3') => self.tab = Tab::Audit,
+            KeyCode::Char('r') => {
+                self.status = None;
+                self.refresh(client).await;
+                if self.tab == Tab::Secrets {
+                    self.browser.metadata = None;
+                    self.load_keys(client).await;
+                }
+            }
+            _ => match self.tab {
+                Tab::Secrets => self.on_browser_key(client, key).await,
+                Tab::Audit => on_table_key(&mut self.audit_table, self.audit.len(), key),
+                Tab::Overview => {}
+            },
+        }
+    }
+
+    async fn on_browser_key(&mut self, client: &Client, key: KeyEvent) {
+        let browser = &mut self.browser;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => browser.list.select_previous(),
+            KeyCode::Down | KeyCode::Char('j')
+                if browser
+                    .list
+                    .selected()
+                    .is_some_and(|i| i + 1 < browser.entries.len()) =>
+            {
+                browser.list.select_next();
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(entry) = browser.selected().filter(|e| e.folder).cloned() {
+                    browser.prefix = entry.path;
+                    browser.filter.clear();
+                    browser.list.select(Some(0));
+                    self.load_keys(client).await;
+                }
+                return;
+            }
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                if !browser.prefix.is_empty() {
+                    let trimmed = browser.prefix.trim_end_matches('/');
+                    browser.prefix = trimmed
+                        .rfind('/')
+                        .map_or_else(String::new, |i| trimmed[..=i].to_owned());
+                    browser.filter.clear();
+                    browser.list.select(Some(0));
+                    self.load_keys(client).await;
+                }
+                return;
+            }
+            KeyCode::Char('/') => {
+                browser.searching = true;
+                return;
+            }
+            KeyCode::Char('t') => {
+                if let Some(entry) = browser.selected().filter(|e| !e.folder) {
+                    self.confirm_rotate = Some(entry.path.clone());
+                }
+                return;
+            }
+            _ => return,
+        }
+        self.load_metadata(client).await;
+    }
+
+    async fn on_search_key(&mut self, client: &Client, key: KeyEvent) {
+        let browser = &mut self.browser;
+        match key.code {
+            KeyCode::Enter => browser.searching = false,
+            KeyCode::Esc => {
+                browser.searching = false;
+                browser.filter.clear();
+            }
+            KeyCode::Backspace => {
+                browser.filter.pop();
+            }
+            KeyCode::Char(c) => browser.filter.push(c),
+            _ => return,
+        }
+        browser.rebuild();
+        self.load_metadata(client).await;
+    }
+}
+
+fn on_table_key(table: &mut TableState, rows: usize, key: KeyEvent) {
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => table.select_previous(),
+        KeyCode::Down | KeyCode::Char('j') if table.selected().is_some_and(|i| i + 1 < rows) => {
+            table.select_next();
+        }
+        KeyCode::Home | KeyCode::Char('g') => table.select_first(),
+        _ => {}
+    }
+}
+
+/// The array at `field` of `resp`, or an empty one.
+fn array(resp: &Value, field: &str) -> Vec<Value> {
+    resp.get(field)
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn text(value: &Value, pointer: &str) -> String {
+    match value.pointer(pointer) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => "-".to_owned(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Show the dashboard until the user quits.
+pub(crate) async fn cmd_tui(client: &Client) -> Result<()> {
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        bail!("zvault tui needs an interactive terminal");
+    }
+    let mut app = App::new(client.addr.clone());
+    app.refresh(client).await;
+    app.load_keys(client).await;
+
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, client, &mut app).await;
+    ratatui::restore();
+    result
+}
+
+async fn run_app(terminal: &mut DefaultTerminal, client: &Client, app: &mut App) -> Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| draw(frame, app))?;
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.on_key(client, key).await;
+                }
+            }
+        }
+        if app.last_refresh.elapsed() >= REFRESH_INTERVAL {
+            app.refresh(client).await;
+        }
+    }
+    Ok(())
+}
+
+// ── Drawing ──────────────────────────────────────────────────────────
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [tabs, body, status] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let [title, tab_bar] =
+        Layout::horizontal([Constraint::Length(10), Constraint::Min(0)]).areas(tabs);
+    frame.render_widget(Span::from(" ZVault ").bold().cyan(), title);
+    frame.render_widget(
+        Tabs::new(Tab::ALL.map(Tab::title))
+            .select(app.tab.index())
+            .highlight_style(Style::new().cyan().add_modifier(Modifier::REVERSED)),
+        tab_bar,
+    );
+
+    match app.tab {
+        Tab::Overview => draw_overview(frame, app, body),
+        Tab::Secrets => draw_secrets(frame, app, body),
+        Tab::Audit => draw_audit(frame, app, body),
+    }
+    draw_status(frame, app, status);
+}
+
+fn draw_overview(frame: &mut Frame, app: &App, area: Rect) {
+    let [top, audit] = Layout::vertical([
+        Constraint::Min(8),
+        Constraint::Length(OVERVIEW_AUDIT_ROWS + 3),
+    ])
+    .areas(area);
+    let [status, mounts] =
+        Layout::horizontal([Constraint::Length(36), Constraint::Min(0)]).areas(top);
+
+    let mut lines = vec![Line::from(vec![
+        Span::from("Server   ").dim(),
+        Span::from(app.addr.clone()),
+    ])];
+    match &app.health {
+        Some(health) => {
+            let sealed = health.get("sealed").and_then(Value::as_bool);
+            lines.push(Line::from(vec![
+                Span::from("Seal     ").dim(),
+                match sealed {
+                    Some(true) => Span::from("sealed").red().bold(),
+                    Some(false) => Span::from("unsealed").green().bold(),
+                    None => Span::from("-"),
+                },
+            ]));
+            for (label, pointer) in [("Init     ", "/initialized"), ("Seal type", "/seal_type")] {
+                lines.push(Line::from(vec![
+                    Span::from(format!("{label} ")).dim(),
+                    Span::from(text(health, pointer)),
+                ]));
+            }
+        }
+        None => lines.push(Line::from(Span::from("unreachable").red().bold())),
+    }
+    let count = |n: Option<usize>| n.map_or_else(|| "-".to_owned(), |n| n.to_string());
+    lines.push(Line::from(vec![
+        Span::from("Leases   ").dim(),
+        Span::from(count(app.lease_count)),
+    ]));
+    lines.push(Line::from(vec![
+        Span::from("Rotation ").dim(),
+        Span::from(format!("{} policies", count(app.rotation_count))),
+    ]));
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Status ")),
+        status,
+    );
+
+    let rows = app.mounts.iter().map(|m| {
+        Row::new([
+            text(m, "/path"),
+            text(m, "/engine_type"),
+            text(m, "/description"),
+        ])
+    });
+    frame.render_widget(
+        Table::new(
+            rows,
+            [
+                Constraint::Length(20),
+                Constraint::Length(12),
+                Constraint::Min(0),
+            ],
+        )
+        .header(header_row(["PATH", "ENGINE", "DESCRIPTION"]))
+        .block(Block::bordered().title(" Mounts ")),
+        mounts,
+    );
+
+    frame.render_widget(
+        audit_table(&app.audit[..app.audit.len().min(usize::from(OVERVIEW_AUDIT_ROWS))])
+            .block(Block::bordered().title(" Recent audit ")),
+        audit,
+    );
+}
+
+fn draw_secrets(frame: &mut Frame, app: &mut App, area: Rect) {
+    let [list, detail] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Min(0)]).areas(area);
+    let browser = &mut app.browser;
+
+    let items: Vec<ListItem> = browser
+        .entries
+        .iter()
+        .map(|e| {
+            if e.folder {
+                ListItem::new(Span::from(e.name.clone()).cyan())
+            } else {
+                ListItem::new(e.name.clone())
+            }
+        })
+        .collect();
+    let title = if browser.searching || !browser.filter.is_empty() {
+        format!(" secret/{} — search: {} ", browser.prefix, browser.filter)
+    } else {
+        format!(" secret/{} ", browser.prefix)
+    };
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("› "),
+        list,
+        &mut browser.list,
+    );
+
+    let lines = match (&browser.metadata, browser.selected()) {
+        (Some((path, meta)), _) => metadata_lines(path, meta),
+        (None, Some(entry)) if entry.folder => {
+            vec![Line::from(Span::from("Enter to open this folder").dim())]
+        }
+        (None, _) => vec![Line::from(Span::from("No secret selected").dim())],
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Metadata ")),
+        detail,
+    );
+}
+
+fn metadata_lines(path: &str, meta: &Value) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(Span::from(format!("secret/{path}")).bold()),
+        Line::default(),
+    ];
+    let Some(fields) = meta.as_object() else {
+        return lines;
+    };
+    for (key, value) in fields {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                lines.push(Line::from(Span::from(key.clone()).dim()));
+                for (k, v) in map {
+                    let v = v.as_str().map_or_else(|| v.to_string(), str::to_owned);
+                    lines.push(Line::from(format!("  {k} = {v}")));
+                }
+            }
+            other => lines.push(Line::from(vec![
+                Span::from(format!("{key:<26}")).dim(),
+                Span::from(
+                    other
+                        .as_str()
+                        .map_or_else(|| other.to_string(), str::to_owned),
+                ),
+            ])),
+        }
+    }
+    lines
+}
+
+fn draw_audit(frame: &mut Frame, app: &mut App, area: Rect) {
+    frame.render_stateful_widget(
+        audit_table(&app.audit)
+            .block(Block::bordered().title(format!(" Audit log — latest {} ", app.audit.len())))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+        area,
+        &mut app.audit_table,
+    );
+}
+
+fn audit_table(entries: &[Value]) -> Table<'static> {
+    let rows = entries.iter().map(|e| {
+        // Entries written before a request is served have no response.
+        let status = e.pointer("/response/status_code").and_then(Value::as_u64);
+        let color = if status.is_some_and(|s| s >= 400) {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        let time = text(e, "/timestamp");
+        Row::new([
+            Cell::from(time.get(..19).unwrap_or(&time).replace('T', " ")),
+            Cell::from(text(e, "/request/operation")),
+            Cell::from(text(e, "/request/path")),
+            Cell::from(status.map_or_else(|| "-".to_owned(), |s| s.to_string()))
+                .style(Style::new().fg(color)),
+            Cell::from(text(e, "/auth/metadata/display_name")),
+        ])
+    });
+    Table::new(
+        rows,
+        [
+            Constraint::Length(19),
+            Constraint::Length(8),
+            Constraint::Min(20),
+            Constraint::Length(6),
+            Constraint::Length(16),
+        ],
+    )
+    .header(header_row(["TIME", "OP", "PATH", "STATUS", "WHO"]))
+}
+
+fn header_row<const N: usize>(titles: [&'static str; N]) -> Row<'static> {
+    Row::new(titles).style(Style::new().dim().add_modifier(Modifier::BOLD))
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let line = if let Some(path) = &app.confirm_rotate {
+        Line::from(format!("Rotate secret/{path} now? (y/n)").yellow().bold())
+    } else if app.browser.searching {
+        Line::from(format!(
+            "/{}▏  Enter to keep, Esc to clear",
+            app.browser.filter
+        ))
+    } else if let Some((message, is_error)) = &app.status {
+        if *is_error {
+            Line::from(message.clone().red())
+        } else {
+            Line::from(message.clone().green())
+        }
+    } else {
+        let help = match app.tab {
+            Tab::Secrets => "↑↓ move  ⏎ open  ⌫ up  / search  t rotate  r refresh  q quit",
+            Tab::Audit => "↑↓ scroll  r refresh  Tab next tab  q quit",
+            Tab::Overview => "Tab / 1-3 switch tabs  r refresh  q quit",
+        };
+        Line::from(help.dim())
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
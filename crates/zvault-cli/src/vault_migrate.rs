@@ -0,0 +1,1240 @@
+//! `zvault migrate-vault`: move a `HashiCorp` Vault KV v2 mount, and with
+//! `--policies` and `--approles` its ACL policies and `AppRole` roles, into
+//! `ZVault`.
+//!
+//! Everything is read from Vault first and compared with what `ZVault`
+//! already holds, so `--dry-run` shows the whole plan and `--conflict fail`
+//! stops before anything is written. Only the latest version of each
+//! secret is copied, with its custom metadata and version settings.
+//! `--map <from>=<to>` rewrites path prefixes (longest match wins), both
+//! for secrets and for the KV paths in policies; characters `ZVault` paths
+//! do not allow become `_`.
+//!
+//! Policies are translated from HCL or JSON: KV v2 `data/` and
+//! `metadata/` paths move to the `secret/` mount, `delete/` and `destroy/`
+//! become `delete` on `data/` and `metadata/`, a trailing `*` becomes `**`,
+//! and `+` becomes `*`. A rule `ZVault` cannot enforce the same way
+//! (parameter constraints, wrapping TTLs, control groups, `patch`) is left
+//! out rather than loosened, and listed under "unsupported" along with
+//! everything else that does not carry over.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{
+    BOLD, CYAN, Client, DIM, GREEN, RED, RESET, YELLOW, header, kv_payload, parse_response, success,
+};
+
+/// Deepest secret path `ZVault` accepts.
+const MAX_PATH_DEPTH: usize = 10;
+
+/// Where to read from and what to migrate.
+pub(crate) struct VaultMigrateOptions {
+    pub(crate) addr: String,
+    pub(crate) token: String,
+    pub(crate) namespace: Option<String>,
+    pub(crate) ca_cert: Option<std::path::PathBuf>,
+    /// Vault KV v2 mount.
+    pub(crate) mount: String,
+    /// Only secrets under this path of the mount.
+    pub(crate) path: String,
+    /// `from=to` path prefix rewrites.
+    pub(crate) maps: Vec<String>,
+    pub(crate) policies: bool,
+    pub(crate) approles: bool,
+    pub(crate) approle_mount: String,
+    /// `skip`, `overwrite`, or `fail`.
+    pub(crate) conflict: String,
+    pub(crate) dry_run: bool,
+}
+
+/// One secret, policy, or role of the migration.
+#[derive(Debug, Serialize)]
+struct Entry {
+    kind: &'static str,
+    source: String,
+    target: String,
+    /// `create`, `update`, `unchanged`, `skip`, or `failed`.
+    action: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+/// Something in Vault that does not carry over to `ZVault`.
+#[derive(Debug, Serialize)]
+struct Unsupported {
+    source: String,
+    feature: String,
+}
+
+/// A planned write to `ZVault`.
+enum Write {
+    Secret {
+        data: Map<String, Value>,
+        metadata: Option<Value>,
+        /// The `ZVault` version being overwritten, as the write's `cas`.
+        cas: Option<u64>,
+    },
+    Policy(Value),
+    AppRole(Value),
+}
+
+struct Plan {
+    entries: Vec<Entry>,
+    writes: Vec<Option<Write>>,
+    unsupported: Vec<Unsupported>,
+    conflicts: Vec<String>,
+    conflict: String,
+}
+
+impl Plan {
+    fn unsupported(&mut self, source: impl Into<String>, feature: impl Into<String>) {
+        self.unsupported.push(Unsupported {
+            source: source.into(),
+            feature: feature.into(),
+        });
+    }
+
+    fn skip(&mut self, kind: &'static str, source: String, target: String, reason: &str) {
+        self.push(
+            Entry {
+                kind,
+                source,
+                target,
+                action: "skip",
+                reason: Some(reason.to_owned()),
+            },
+            None,
+        );
+    }
+
+    /// Plan writing `target`, which holds `existing` (if anything) and
+    /// should hold `wanted`.
+    fn upsert(
+        &mut self,
+        kind: &'static str,
+        source: String,
+        target: String,
+        existing: Option<bool>,
+        write: Write,
+    ) {
+        let mut entry = Entry {
+            kind,
+            source,
+            target,
+            action: "create",
+            reason: None,
+        };
+        match existing {
+            None => {}
+            Some(true) => {
+                entry.action = "unchanged";
+                self.push(entry, None);
+                return;
+            }
+            Some(false) => match self.conflict.as_str() {
+                "overwrite" => entry.action = "update",
+                "fail" => {
+                    self.conflicts.push(format!("{kind} {}", entry.target));
+                    entry.action = "update";
+                }
+                _ => {
+                    entry.action = "skip";
+                    entry.reason = Some("exists in ZVault with a different value".to_owned());
+                    self.push(entry, None);
+                    return;
+                }
+            },
+        }
+        self.push(entry, Some(write));
+    }
+
+    fn push(&mut self, entry: Entry, write: Option<Write>) {
+        self.entries.push(entry);
+        self.writes.push(write);
+    }
+}
+
+/// Run the migration (or with `--dry-run`, print its plan).
+pub(crate) async fn cmd_migrate_vault(client: &Client, options: VaultMigrateOptions) -> Result<()> {
+    let maps = parse_maps(&options.maps)?;
+    let source = Client::new(
+        options.addr.trim_end_matches('/').to_owned(),
+        Some(options.token.clone()),
+        None,
+        options.ca_cert.as_deref(),
+        None,
+    )?;
+    let vault = Vault {
+        client: &source,
+        namespace: options
+            .namespace
+            .as_deref()
+            .map(|ns| ns.trim_matches('/').to_owned())
+            .filter(|ns| !ns.is_empty()),
+    };
+    let mount = options.mount.trim_matches('/').to_owned();
+
+    let mut plan = Plan {
+        entries: Vec::new(),
+        writes: Vec::new(),
+        unsupported: Vec::new(),
+        conflicts: Vec::new(),
+        conflict: options.conflict.clone(),
+    };
+    plan_secrets(&vault, client, &mount, &options.path, &maps, &mut plan).await?;
+    if options.policies {
+        plan_policies(&vault, client, &mount, &maps, &mut plan).await?;
+    }
+    if options.approles {
+        plan_approles(&vault, client, &options.approle_mount, &mut plan).await?;
+    }
+    if !plan.conflicts.is_empty() {
+        bail!(
+            "{} target(s) already exist in ZVault with different values: {}",
+            plan.conflicts.len(),
+            plan.conflicts.join(", ")
+        );
+    }
+
+    if !options.dry_run {
+        execute(client, &mut plan).await;
+    }
+    print_report(&plan, &options);
+
+    let report = serde_json::json!({
+        "dry_run": options.dry_run,
+        "entries": plan.entries,
+        "unsupported": plan.unsupported,
+    });
+    crate::output::record(&report);
+    let failed = plan.entries.iter().filter(|e| e.action == "failed").count();
+    if failed > 0 {
+        bail!("{failed} of {} targets failed", plan.entries.len());
+    }
+    Ok(())
+}
+
+// ── Vault API ────────────────────────────────────────────────────────
+
+/// The source Vault, with requests scoped to a namespace.
+struct Vault<'a> {
+    client: &'a Client,
+    namespace: Option<String>,
+}
+
+impl Vault<'_> {
+    /// `GET /v1/<namespace>/<path>`, or `None` on 404.
+    async fn get(&self, path: &str) -> Result<Option<Value>> {
+        let path = match &self.namespace {
+            Some(ns) => format!("/v1/{ns}/{path}"),
+            None => format!("/v1/{path}"),
+        };
+        let resp = self
+            .client
+            .request(reqwest::Method::GET, &path, None)
+            .await
+            .context("failed to reach the source Vault")?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        parse_response(resp)
+            .await
+            .map(Some)
+            .with_context(|| format!("source Vault refused GET {path}"))
+    }
+
+    /// The keys listed under `path` (directories end in `/`).
+    async fn list(&self, path: &str) -> Result<Vec<String>> {
+        let resp = self.get(&format!("{path}?list=true")).await?;
+        Ok(resp
+            .as_ref()
+            .and_then(|r| r.pointer("/data/keys"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect())
+    }
+}
+
+/// `GET` on `ZVault`, or `None` on 404.
+async fn zvault_get(client: &Client, path: &str) -> Result<Option<Value>> {
+    let resp = client.request(reqwest::Method::GET, path, None).await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    parse_response(resp).await.map(Some)
+}
+
+// ── Secrets ──────────────────────────────────────────────────────────
+
+async fn plan_secrets(
+    vault: &Vault<'_>,
+    client: &Client,
+    mount: &str,
+    path: &str,
+    maps: &[(String, String)],
+    plan: &mut Plan,
+) -> Result<()> {
+    match vault
+        .get(&format!("sys/internal/ui/mounts/{mount}"))
+        .await?
+    {
+        Some(info) => {
+            let kind = info.pointer("/data/type").and_then(Value::as_str);
+            let version = info
+                .pointer("/data/options/version")
+                .and_then(Value::as_str);
+            if kind != Some("kv") || version != Some("2") {
+                bail!("{mount}/ is not a KV version 2 mount");
+            }
+        }
+        None => bail!("no KV mount at {mount}/ on the source Vault (or the token cannot see it)"),
+    }
+
+    let mut targets: BTreeMap<String, String> = BTreeMap::new();
+    let mut with_history = 0usize;
+    for rel in list_secrets(vault, mount, path).await? {
+        let source = format!("{mount}/{rel}");
+        let Some(metadata) = vault.get(&format!("{mount}/metadata/{rel}")).await? else {
+            continue;
+        };
+        let metadata = metadata.get("data").cloned().unwrap_or_default();
+        let target = match target_path(&map_path(&rel, maps)) {
+            Ok(target) => target,
+            Err(reason) => {
+                plan.skip("secret", source, String::new(), &reason);
+                continue;
+            }
+        };
+        if let Some(other) = targets.insert(target.clone(), source.clone()) {
+            bail!(
+                "{other} and {source} would both be migrated to secret/{target}; add a --map rule"
+            );
+        }
+
+        let current = metadata
+            .get("current_version")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+        let version = metadata.pointer(&format!("/versions/{current}"));
+        let deleted = version.is_some_and(|v| {
+            v.get("destroyed").and_then(Value::as_bool) == Some(true)
+                || v.get("deletion_time")
+                    .and_then(Value::as_str)
+                    .is_some_and(|t| !t.is_empty())
+        });
+        if deleted {
+            plan.skip("secret", source, target, "the latest version is deleted");
+            continue;
+        }
+        let Some(data) = vault
+            .get(&format!("{mount}/data/{rel}"))
+            .await?
+            .and_then(|r| r.pointer("/data/data").and_then(Value::as_object).cloned())
+        else {
+            plan.skip("secret", source, target, "the latest version is deleted");
+            continue;
+        };
+        if current > 1 {
+            with_history = with_history.saturating_add(1);
+        }
+
+        let current = zvault_get(client, &format!("/v1/secret/data/{target}")).await?;
+        let existing = current
+            .as_ref()
+            .map(|resp| kv_payload(resp).as_object() == Some(&data));
+        let write = Write::Secret {
+            data,
+            metadata: secret_metadata(&metadata),
+            cas: current
+                .as_ref()
+                .and_then(|resp| resp.pointer("/data/metadata/version"))
+                .and_then(Value::as_u64),
+        };
+        plan.upsert("secret", source, target, existing, write);
+    }
+    if with_history > 0 {
+        plan.unsupported(
+            format!("{mount}/"),
+            format!(
+                "version history: only the latest version is migrated \
+                 ({with_history} secret(s) have older versions)"
+            ),
+        );
+    }
+    Ok(())
+}
+
+/// Every secret under `path` of `mount`, relative to the mount.
+async fn list_secrets(vault: &Vault<'_>, mount: &str, path: &str) -> Result<Vec<String>> {
+    let root = path.trim_matches('/');
+    let mut pending = vec![if root.is_empty() {
+        String::new()
+    } else {
+        format!("{root}/")
+    }];
+    let mut secrets = Vec::new();
+    while let Some(dir) = pending.pop() {
+        for key in vault.list(&format!("{mount}/metadata/{dir}")).await? {
+            if key.ends_with('/') {
+                pending.push(format!("{dir}{key}"));
+            } else {
+                secrets.push(format!("{dir}{key}"));
+            }
+        }
+    }
+    if secrets.is_empty() && !root.is_empty() {
+        // `--path` may name a single secret.
+        secrets.push(root.to_owned());
+    }
+    secrets.sort();
+    Ok(secrets)
+}
+
+/// The `ZVault` metadata update for a Vault secret's metadata, if it sets
+/// anything.
+fn secret_metadata(metadata: &Value) -> Option<Value> {
+    let mut update = Map::new();
+    if let Some(custom) = metadata
+        .get("custom_metadata")
+        .and_then(Value::as_object)
+        .filter(|c| !c.is_empty())
+    {
+        update.insert("custom_metadata".to_owned(), Value::Object(custom.clone()));
+    }
+    if let Some(max) = metadata
+        .get("max_versions")
+        .and_then(Value::as_u64)
+        .filter(|m| *m > 0)
+    {
+        update.insert("max_versions".to_owned(), max.into());
+    }
+    if metadata.get("cas_required").and_then(Value::as_bool) == Some(true) {
+        update.insert("cas_required".to_owned(), true.into());
+    }
+    let after = metadata
+        .get("delete_version_after")
+        .and_then(Value::as_str)
+        .map_or(0, go_duration_secs);
+    if after > 0 {
+        update.insert("delete_version_after".to_owned(), after.to_string().into());
+    }
+    (!update.is_empty()).then_some(Value::Object(update))
+}
+
+/// Seconds in a Go duration such as `768h0m0s` (fractions are dropped).
+fn go_duration_secs(duration: &str) -> u64 {
+    let mut total = 0u64;
+    let mut number = String::new();
+    let mut chars = duration.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let mut unit = c.to_string();
+        while let Some(&next) = chars.peek() {
+            if next.is_ascii_digit() || next == '.' {
+                break;
+            }
+            unit.push(next);
+            chars.next();
+        }
+        let whole: u64 = number
+            .split('.')
+            .next()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0);
+        let scale = match unit.as_str() {
+            "h" => 3600,
+            "m" => 60,
+            "s" => 1,
+            _ => 0,
+        };
+        total = total.saturating_add(whole.saturating_mul(scale));
+        number.clear();
+    }
+    total
+}
+
+// ── Paths ────────────────────────────────────────────────────────────
+
+/// Parse `--map from=to` rules, longest `from` first.
+fn parse_maps(maps: &[String]) -> Result<Vec<(String, String)>> {
+    let mut rules = Vec::new();
+    for map in maps {
+        let Some((from, to)) = map.split_once('=') else {
+            bail!("invalid --map '{map}', expected <from>=<to> (e.g. apps/legacy=legacy)");
+        };
+        rules.push((
+            from.trim_matches('/').to_owned(),
+            to.trim_matches('/').to_owned(),
+        ));
+    }
+    rules.sort_by_key(|rule| std::cmp::Reverse(rule.0.len()));
+    Ok(rules)
+}
+
+/// The rule whose `from` is a path prefix of `path`, with the rest.
+fn find_map<'a>(path: &'a str, maps: &'a [(String, String)]) -> Option<(&'a str, &'a str)> {
+    maps.iter().find_map(|(from, to)| {
+        if from.is_empty() {
+            return Some((to.as_str(), path));
+        }
+        let rest = path.strip_prefix(from.as_str())?;
+        if rest.is_empty() || rest.starts_with('/') {
+            Some((to.as_str(), rest.trim_start_matches('/')))
+        } else {
+            None
+        }
+    })
+}
+
+/// `path` rewritten by the first matching `--map` rule.
+fn map_path(path: &str, maps: &[(String, String)]) -> String {
+    match find_map(path, maps) {
+        Some(("", rest)) => rest.to_owned(),
+        Some((to, "")) => to.to_owned(),
+        Some((to, rest)) => format!("{to}/{rest}"),
+        None => path.to_owned(),
+    }
+}
+
+/// A `ZVault` secret path for `path`, with characters it does not allow
+/// replaced by `_`.
+fn target_path(path: &str) -> Result<String, String> {
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(sanitize)
+        .collect();
+    if segments.is_empty() {
+        return Err("empty path after --map".to_owned());
+    }
+    if segments.len() > MAX_PATH_DEPTH {
+        return Err(format!(
+            "ZVault paths are limited to {MAX_PATH_DEPTH} segments"
+        ));
+    }
+    Ok(segments.join("/"))
+}
+
+/// `path` with characters `ZVault` paths do not allow replaced by `_`.
+fn sanitize(path: &str) -> String {
+    path.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// ── Policies ─────────────────────────────────────────────────────────
+
+/// One `path` block of a Vault policy.
+#[derive(Debug, Default)]
+struct VaultRule {
+    path: String,
+    capabilities: Vec<String>,
+    /// Other settings of the block, e.g. `denied_parameters`.
+    settings: Vec<String>,
+}
+
+async fn plan_policies(
+    vault: &Vault<'_>,
+    client: &Client,
+    mount: &str,
+    maps: &[(String, String)],
+    plan: &mut Plan,
+) -> Result<()> {
+    let mut names = vault.list("sys/policies/acl").await?;
+    names.sort();
+    for name in names {
+        let source = format!("policy {name}");
+        if name == "root" || name == "default" {
+            plan.skip("policy", source, name, "built in; ZVault has its own");
+            continue;
+        }
+        let Some(text) = vault
+            .get(&format!("sys/policies/acl/{name}"))
+            .await?
+            .and_then(|r| {
+                r.pointer("/data/policy")
+                    .and_then(Value::as_str)
+                    .map(str::to_owned)
+            })
+        else {
+            continue;
+        };
+        let vault_rules = match parse_policy(&text) {
+            Ok(rules) => rules,
+            Err(e) => {
+                plan.skip("policy", source, name, &format!("cannot parse: {e:#}"));
+                continue;
+            }
+        };
+
+        let mut rules: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for rule in &vault_rules {
+            let (translated, notes) = translate_rule(rule, mount, maps);
+            for note in notes {
+                plan.unsupported(format!("policy {name}: {}", rule.path), note);
+            }
+            for (path, capabilities) in translated {
+                rules.entry(path).or_default().extend(capabilities);
+            }
+        }
+        if rules.is_empty() {
+            plan.skip("policy", source, name, "no rules could be translated");
+            continue;
+        }
+        let rules: Vec<Value> = rules
+            .into_iter()
+            .map(|(path, caps)| serde_json::json!({ "path": path, "capabilities": caps }))
+            .collect();
+
+        let existing = zvault_get(client, &format!("/v1/sys/policies/{name}"))
+            .await?
+            .map(|resp| same_rules(&resp, &rules));
+        let body = serde_json::json!({ "name": name, "rules": rules });
+        plan.upsert("policy", source, name, existing, Write::Policy(body));
+    }
+    Ok(())
+}
+
+/// Whether a `ZVault` policy response has exactly `rules`.
+fn same_rules(resp: &Value, rules: &[Value]) -> bool {
+    let normalize = |rule: &Value| {
+        let path = rule
+            .get("path")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+        let caps: BTreeSet<String> = rule
+            .get("capabilities")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_lowercase)
+            .collect();
+        (path, caps)
+    };
+    let existing: BTreeSet<_> = resp
+        .get("rules")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(normalize)
+        .collect();
+    existing == rules.iter().map(normalize).collect()
+}
+
+/// `ZVault` rules for one Vault rule, and what could not be translated. A
+/// rule with restrictions `ZVault` cannot enforce yields no rules at all.
+fn translate_rule(
+    rule: &VaultRule,
+    mount: &str,
+    maps: &[(String, String)],
+) -> (Vec<(String, Vec<String>)>, Vec<String>) {
+    let mut notes = Vec::new();
+    if !rule.settings.is_empty() {
+        notes.push(format!(
+            "{} (rule left out: ZVault cannot enforce it)",
+            rule.settings.join(", ")
+        ));
+        return (Vec::new(), notes);
+    }
+
+    let mut capabilities = Vec::new();
+    for capability in &rule.capabilities {
+        match capability.as_str() {
+            "create" | "read" | "update" | "delete" | "list" | "sudo" | "deny" => {
+                capabilities.push(capability.clone());
+            }
+            "patch" if rule.capabilities.iter().any(|c| c == "update") => {}
+            other => notes.push(format!("capability '{other}' (dropped)")),
+        }
+    }
+    if capabilities.is_empty() {
+        return (Vec::new(), notes);
+    }
+
+    let kv_prefix = format!("{mount}/");
+    let Some(rest) = rule.path.strip_prefix(&kv_prefix) else {
+        notes.push("not a path of the migrated KV mount; copied with globs translated".to_owned());
+        return (expand_glob(&rule.path, &capabilities), notes);
+    };
+    if rest == "*" {
+        if !maps.is_empty() {
+            notes.push("covers the whole mount, including paths moved by --map".to_owned());
+        }
+        return (expand_glob("secret/*", &capabilities), notes);
+    }
+    let Some((api, pattern)) = rest.split_once('/') else {
+        notes.push(format!("KV v2 endpoint '{rest}' has no ZVault equivalent"));
+        return (Vec::new(), notes);
+    };
+    let (zvault_api, capabilities) = match api {
+        "data" | "metadata" => (api, capabilities),
+        "delete" => ("data", vec!["delete".to_owned()]),
+        "destroy" => ("metadata", vec!["delete".to_owned()]),
+        _ => {
+            notes.push(format!("KV v2 endpoint '{api}/' has no ZVault equivalent"));
+            return (Vec::new(), notes);
+        }
+    };
+
+    if zvault_api == "data"
+        && capabilities.iter().any(|c| c == "update")
+        && !capabilities.iter().any(|c| c == "create")
+    {
+        notes.push(
+            "'update' without 'create': ZVault KV writes need 'create', so this rule \
+             no longer allows writes"
+                .to_owned(),
+        );
+    }
+
+    // Map the part before the first glob like a secret path.
+    let (literal, glob) = pattern.split_at(pattern.find(['*', '+']).unwrap_or(pattern.len()));
+    let dir = literal.trim_end_matches('/');
+    let mapped = map_path(dir, maps);
+    let separator = if dir.is_empty() && !mapped.is_empty() && !glob.is_empty() {
+        "/"
+    } else {
+        &literal[dir.len()..]
+    };
+    if !glob.is_empty()
+        && find_map(dir, maps).is_none()
+        && maps
+            .iter()
+            .any(|(from, _)| !from.is_empty() && from.starts_with(literal))
+    {
+        notes.push("also covers paths moved by --map; review the translated rule".to_owned());
+    }
+    let pattern = format!("{}{separator}{glob}", sanitize(&mapped));
+
+    let mut rules = expand_glob(&format!("secret/{zvault_api}/{pattern}"), &capabilities);
+    // Vault lists through `metadata/`, ZVault through `list/`.
+    if zvault_api == "metadata" && capabilities.iter().any(|c| c == "list") {
+        rules.extend(expand_glob(
+            &format!("secret/list/{pattern}"),
+            &["list".to_owned()],
+        ));
+    }
+    (rules, notes)
+}
+
+/// Vault's `+` (one segment) and trailing `*` (any suffix) as `ZVault`
+/// globs: `a/+/b` → `a/*/b`, `a/*` → `a/**`, `a/b*` → `a/b*` and `a/b*/**`.
+fn expand_glob(path: &str, capabilities: &[String]) -> Vec<(String, Vec<String>)> {
+    let segments = |path: &str| {
+        path.split('/')
+            .map(|s| if s == "+" { "*" } else { s })
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    let paths = match path.strip_suffix('*') {
+        Some(base) if base.is_empty() || base.ends_with('/') => {
+            vec![format!("{}**", segments(base))]
+        }
+        Some(_) => {
+            let path = segments(path);
+            vec![path.clone(), format!("{path}/**")]
+        }
+        None => vec![segments(path)],
+    };
+    paths
+        .into_iter()
+        .map(|p| (p, capabilities.to_vec()))
+        .collect()
+}
+
+/// Parse a Vault policy in HCL or JSON.
+fn parse_policy(text: &str) -> Result<Vec<VaultRule>> {
+    if text.trim_start().starts_with('{') {
+        let json: Value = serde_json::from_str(text).context("invalid JSON policy")?;
+        let mut rules = Vec::new();
+        for (path, block) in json
+            .get("path")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            rules.push(rule_from_block(path, block));
+        }
+        return Ok(rules);
+    }
+
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens, at: 0 };
+    let mut rules = Vec::new();
+    while let Some(token) = parser.next() {
+        match token {
+            Token::Ident(keyword) if keyword == "path" => {
+                let Some(Token::Str(path)) = parser.next() else {
+                    bail!("expected a quoted path after 'path'");
+                };
+                let block = parser.value()?;
+                rules.push(rule_from_block(&path, &block));
+            }
+            Token::Ident(keyword) => {
+                // Top-level settings such as `name` carry no access.
+                parser.expect('=')?;
+                parser.value()?;
+                let _ = keyword;
+            }
+            other => bail!("unexpected {other:?}"),
+        }
+    }
+    Ok(rules)
+}
+
+/// A rule from a parsed `path` block.
+fn rule_from_block(path: &str, block: &Value) -> VaultRule {
+    let mut rule = VaultRule {
+        path: path.to_owned(),
+        ..VaultRule::default()
+    };
+    for (key, value) in block.as_object().into_iter().flatten() {
+        match key.as_str() {
+            "capabilities" => {
+                rule.capabilities = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(str::to_lowercase)
+                    .collect();
+            }
+            // The pre-0.9 shorthand.
+            "policy" => {
+                let caps: &[&str] = match value.as_str().unwrap_or_default() {
+                    "deny" => &["deny"],
+                    "read" => &["read", "list"],
+                    "write" => &["create", "read", "update", "delete", "list"],
+                    "sudo" => &["create", "read", "update", "delete", "list", "sudo"],
+                    _ => &[],
+                };
+                rule.capabilities = caps.iter().map(|c| (*c).to_owned()).collect();
+            }
+            other => rule.settings.push(other.to_owned()),
+        }
+    }
+    rule
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(String),
+    Punct(char),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() || c == ',' => {}
+            '#' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(other) => s.push(other),
+                            None => bail!("unterminated string"),
+                        },
+                        Some(other) => s.push(other),
+                        None => bail!("unterminated string"),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '{' | '}' | '[' | ']' | '=' | ':' => tokens.push(Token::Punct(c)),
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut s = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_ascii_alphanumeric() || next == '.') {
+                        break;
+                    }
+                    s.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Number(s));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut s = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_' || next == '-') {
+                        break;
+                    }
+                    s.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(s));
+            }
+            other => bail!("unexpected character '{other}'"),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    at: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.at).cloned();
+        self.at = self.at.saturating_add(1);
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at)
+    }
+
+    fn expect(&mut self, punct: char) -> Result<()> {
+        match self.next() {
+            Some(Token::Punct(p)) if p == punct || (punct == '=' && p == ':') => Ok(()),
+            other => bail!("expected '{punct}', found {other:?}"),
+        }
+    }
+
+    /// A string, number, list, or `{ key = value ... }` block as JSON.
+    fn value(&mut self) -> Result<Value> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Value::String(s)),
+            Some(Token::Number(n) | Token::Ident(n)) => Ok(Value::String(n)),
+            Some(Token::Punct('[')) => {
+                let mut items = Vec::new();
+                while self.peek() != Some(&Token::Punct(']')) {
+                    if self.peek().is_none() {
+                        bail!("unterminated list");
+                    }
+                    items.push(self.value()?);
+                }
+                self.next();
+                Ok(Value::Array(items))
+            }
+            Some(Token::Punct('{')) => {
+                let mut block = Map::new();
+                loop {
+                    let key = match self.next() {
+                        Some(Token::Punct('}')) => break,
+                        Some(Token::Ident(key) | Token::Str(key)) => key,
+                        other => bail!("expected a setting name, found {other:?}"),
+                    };
+                    // Nested blocks (`control_group { ... }`) omit the `=`.
+                    if self.peek() != Some(&Token::Punct('{')) {
+                        self.expect('=')?;
+                    }
+                    let value = self.value()?;
+                    block.insert(key, value);
+                }
+                Ok(Value::Object(block))
+            }
+            other => bail!("expected a value, found {other:?}"),
+        }
+    }
+}
+
+// ── AppRoles ─────────────────────────────────────────────────────────
+
+/// Vault role settings that restrict logins or tokens in ways `ZVault`
+/// cannot; roles using them are left out rather than loosened.
+const RESTRICTIVE_ROLE_SETTINGS: &[&str] = &[
+    "secret_id_bound_cidrs",
+    "token_bound_cidrs",
+    "token_num_uses",
+    "token_explicit_max_ttl",
+];
+
+/// Vault role settings `ZVault` ignores without widening access.
+const IGNORED_ROLE_SETTINGS: &[&str] = &["token_period", "token_no_default_policy"];
+
+async fn plan_approles(
+    vault: &Vault<'_>,
+    client: &Client,
+    approle_mount: &str,
+    plan: &mut Plan,
+) -> Result<()> {
+    let approle_mount = approle_mount.trim_matches('/');
+    let mut names = vault.list(&format!("auth/{approle_mount}/role")).await?;
+    names.sort();
+    for name in names {
+        let source = format!("auth/{approle_mount}/role/{name}");
+        let Some(role) = vault
+            .get(&source)
+            .await?
+            .and_then(|r| r.get("data").cloned())
+        else {
+            continue;
+        };
+        let set = |key: &str| match role.get(key) {
+            None | Some(Value::Null | Value::Bool(false)) => false,
+            Some(Value::Number(n)) => n.as_u64() != Some(0),
+            Some(Value::Array(items)) => !items.is_empty(),
+            Some(Value::String(s)) => !s.is_empty(),
+            Some(_) => true,
+        };
+        let restrictive: Vec<&str> = RESTRICTIVE_ROLE_SETTINGS
+            .iter()
+            .copied()
+            .filter(|key| set(key))
+            .collect();
+        if !restrictive.is_empty() {
+            plan.unsupported(
+                source.clone(),
+                format!("{} (role left out)", restrictive.join(", ")),
+            );
+            plan.skip(
+                "approle",
+                source,
+                name,
+                "uses settings ZVault cannot enforce",
+            );
+            continue;
+        }
+        for key in IGNORED_ROLE_SETTINGS {
+            if set(key) {
+                plan.unsupported(source.clone(), format!("{key} (ignored)"));
+            }
+        }
+        if let Some(kind) = role
+            .get("token_type")
+            .and_then(Value::as_str)
+            .filter(|t| !matches!(*t, "" | "default" | "service"))
+        {
+            plan.unsupported(
+                source.clone(),
+                format!("token_type {kind} (service tokens are issued)"),
+            );
+        }
+
+        let policies: BTreeSet<String> = ["token_policies", "policies"]
+            .iter()
+            .filter_map(|key| role.get(*key).and_then(Value::as_array))
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect();
+        if policies.is_empty() {
+            plan.skip(
+                "approle",
+                source,
+                name,
+                "ZVault roles need at least one policy",
+            );
+            continue;
+        }
+        let secs = |key: &str| role.get(key).and_then(Value::as_i64).unwrap_or(0);
+        let mut body = serde_json::json!({
+            "policies": policies,
+            "bind_secret_id": role.get("bind_secret_id").and_then(Value::as_bool).unwrap_or(true),
+            "secret_id_num_uses": secs("secret_id_num_uses"),
+            "secret_id_ttl_secs": secs("secret_id_ttl"),
+        });
+        // Zero means the system default in Vault; leave ZVault's.
+        if secs("token_ttl") > 0 {
+            body["token_ttl_secs"] = secs("token_ttl").into();
+        }
+        if secs("token_max_ttl") > 0 {
+            body["token_max_ttl_secs"] = secs("token_max_ttl").into();
+        }
+
+        let existing = zvault_get(client, &format!("/v1/auth/approle/role/{name}"))
+            .await?
+            .map(|current| same_role(&current, &body));
+        plan.upsert("approle", source, name, existing, Write::AppRole(body));
+    }
+    if plan.entries.iter().any(|e| e.kind == "approle") {
+        plan.unsupported(
+            format!("auth/{approle_mount}/"),
+            "role IDs and secret IDs: migrated roles get new role IDs, and secret IDs must be issued again",
+        );
+    }
+    Ok(())
+}
+
+/// Whether a `ZVault` role response has the settings of `body`.
+fn same_role(current: &Value, body: &Value) -> bool {
+    let policies = |role: &Value| -> BTreeSet<String> {
+        role.get("policies")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect()
+    };
+    policies(current) == policies(body)
+        && [
+            "bind_secret_id",
+            "secret_id_num_uses",
+            "secret_id_ttl_secs",
+            "token_ttl_secs",
+            "token_max_ttl_secs",
+        ]
+        .iter()
+        .all(|key| body.get(*key).is_none_or(|v| current.get(*key) == Some(v)))
+}
+
+// ── Writing ──────────────────────────────────────────────────────────
+
+async fn execute(client: &Client, plan: &mut Plan) {
+    for (entry, write) in plan.entries.iter_mut().zip(plan.writes.drain(..)) {
+        let Some(write) = write else { continue };
+        let result = match write {
+            Write::Secret {
+                data,
+                metadata,
+                cas,
+            } => {
+                let target = &entry.target;
+                let mut body = serde_json::json!({ "data": data });
+                if let Some(cas) = cas {
+                    body["options"] = serde_json::json!({ "cas": cas });
+                }
+                let written = client
+                    .post(&format!("/v1/secret/data/{target}"), &body)
+                    .await;
+                match (written, metadata) {
+                    (Ok(_), Some(metadata)) => client
+                        .post(&format!("/v1/secret/metadata/{target}"), &metadata)
+                        .await
+                        .map(|_| ()),
+                    (written, _) => written.map(|_| ()),
+                }
+            }
+            Write::Policy(body) => client
+                .post(&format!("/v1/sys/policies/{}", entry.target), &body)
+                .await
+                .map(|_| ()),
+            Write::AppRole(body) => client
+                .post(&format!("/v1/auth/approle/role/{}", entry.target), &body)
+                .await
+                .map(|resp| {
+                    if let Some(role_id) = resp.get("role_id").and_then(Value::as_str) {
+                        entry.reason = Some(format!("role_id {role_id}"));
+                    }
+                }),
+        };
+        if let Err(e) = result {
+            entry.action = "failed";
+            entry.reason = Some(format!("{e:#}"));
+        }
+    }
+}
+
+fn print_report(plan: &Plan, options: &VaultMigrateOptions) {
+    outln!();
+    let title = format!("Vault migration from {}", options.addr);
+    if options.dry_run {
+        header("📦", &format!("{title} (dry run)"));
+    } else {
+        header("📦", &title);
+    }
+
+    for (kind, heading) in [
+        ("secret", "Secrets"),
+        ("policy", "Policies"),
+        ("approle", "AppRoles"),
+    ] {
+        let entries: Vec<&Entry> = plan.entries.iter().filter(|e| e.kind == kind).collect();
+        if entries.is_empty() {
+            continue;
+        }
+        let unchanged = entries.iter().filter(|e| e.action == "unchanged").count();
+        outln!();
+        outln!(
+            "  {BOLD}{heading}{RESET} {DIM}({} found, {unchanged} unchanged){RESET}",
+            entries.len()
+        );
+        for entry in entries.iter().filter(|e| e.action != "unchanged") {
+            let mark = match entry.action {
+                "create" => format!("{GREEN}+{RESET}"),
+                "update" => format!("{YELLOW}~{RESET}"),
+                "skip" => format!("{DIM}-{RESET}"),
+                _ => format!("{RED}✗{RESET}"),
+            };
+            let target = match (kind, entry.target.as_str()) {
+                (_, "") => String::new(),
+                ("secret", target) => format!(" {DIM}→{RESET} secret/{target}"),
+                (_, target) => format!(" {DIM}→{RESET} {target}"),
+            };
+            let reason = entry
+                .reason
+                .as_deref()
+                .map(|r| format!("  {DIM}{r}{RESET}"))
+                .unwrap_or_default();
+            outln!("  {mark} {}{target}{reason}", entry.source);
+        }
+    }
+
+    if !plan.unsupported.is_empty() {
+        outln!();
+        outln!("  {BOLD}{YELLOW}Not migrated{RESET}");
+        for item in &plan.unsupported {
+            outln!(
+                "  {YELLOW}⚠{RESET} {CYAN}{}{RESET}  {}",
+                item.source,
+                item.feature
+            );
+        }
+    }
+
+    let count = |action: &str| plan.entries.iter().filter(|e| e.action == action).count();
+    outln!();
+    if options.dry_run {
+        success(&format!(
+            "{} to create, {} to update, {} unchanged, {} skipped",
+            count("create"),
+            count("update"),
+            count("unchanged"),
+            count("skip")
+        ));
+        outln!("  {DIM}Run again without --dry-run to migrate.{RESET}");
+    } else {
+        success(&format!(
+            "{} created, {} updated, {} unchanged, {} skipped",
+            count("create"),
+            count("update"),
+            count("unchanged"),
+            count("skip")
+        ));
+    }
+    outln!();
+}
@@ -0,0 +1,818 @@
+//! Just-in-time access requests for `ZVault`.
+//!
+//! A client asks for temporary capabilities on a path; an approver grants or
+//! denies the request. Approval writes a dedicated policy
+//! (`access-request-{id}`) and an expiring grant for the requester. The auth
+//! middleware adds the requester's active grants to their token's policies
+//! on every request, so access appears and disappears without re-issuing
+//! tokens.
+//!
+//! Requesters are identified like activity clients: by identity entity when
+//! the token carries one, otherwise by a hash of the token (see
+//! [`ActivityLog::client_id`]).
+//!
+//! Requests are stored through the barrier at `sys/access-requests/{id}` and
+//! keep their full decision history. Grants are indexed per requester at
+//! `sys/access-grants/{requester}`. [`AccessRequestStore::expire`] removes
+//! lapsed grants and their policies; grants past their expiry are ignored
+//! even before the sweep runs.
+//!
+//! State changes are published on the [`EventBus`] (`access_request.*`
+//! topics) and, when configured, posted to a Slack-compatible incoming
+//! webhook.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::activity::ActivityLog;
+use crate::barrier::Barrier;
+use crate::error::{AccessRequestError, PolicyError};
+use crate::events::EventBus;
+use crate::policy::{Capability, Policy, PolicyRule, PolicyStore};
+
+/// Storage prefix for access requests.
+const REQUEST_PREFIX: &str = "sys/access-requests/";
+
+/// Storage prefix for per-requester grant indexes.
+const GRANT_PREFIX: &str = "sys/access-grants/";
+
+/// Prefix of the policies written for approved requests.
+pub const POLICY_PREFIX: &str = "access-request-";
+
+/// Grant duration when the request does not ask for one.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(3600);
+
+/// Longest grant a request may ask for.
+pub const MAX_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Topic published when a request is submitted.
+pub const TOPIC_CREATED: &str = "access_request.created";
+
+/// Topic published when a request is approved.
+pub const TOPIC_APPROVED: &str = "access_request.approved";
+
+/// Topic published when a request is denied.
+pub const TOPIC_DENIED: &str = "access_request.denied";
+
+/// Topic published when an approved grant is revoked early.
+pub const TOPIC_REVOKED: &str = "access_request.revoked";
+
+/// Topic published when an approved grant reaches its expiry.
+pub const TOPIC_EXPIRED: &str = "access_request.expired";
+
+/// Timeout for webhook notifications.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Lifecycle state of an access request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessRequestStatus {
+    /// Waiting for an approver.
+    Pending,
+    /// Granted; access is active until `expires_at`.
+    Approved,
+    /// Rejected by an approver.
+    Denied,
+    /// Granted, then withdrawn before expiry.
+    Revoked,
+    /// Granted, and the grant has lapsed.
+    Expired,
+}
+
+impl std::fmt::Display for AccessRequestStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+        };
+        f.write_str(s)
+    }
+}
+
+/// One entry in a request's decision history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRequestEvent {
+    /// When the action happened.
+    pub at: DateTime<Utc>,
+    /// Display name of the actor (`system` for expiry).
+    pub actor: String,
+    /// Status the request moved to.
+    pub status: AccessRequestStatus,
+    /// Optional comment from the actor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// A request for temporary access to a path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRequest {
+    /// Unique request ID.
+    pub id: String,
+    /// Entity ID or token-derived client ID of the requester.
+    pub requester: String,
+    /// Display name of the requester's token.
+    pub requester_name: String,
+    /// Path pattern access is requested for (supports `*` and `**`).
+    pub path: String,
+    /// Requested capabilities.
+    pub capabilities: Vec<Capability>,
+    /// Why access is needed.
+    pub reason: String,
+    /// Requested grant duration in seconds.
+    pub ttl_secs: u64,
+    /// Current state.
+    pub status: AccessRequestStatus,
+    /// When the request was submitted.
+    pub created_at: DateTime<Utc>,
+    /// When the grant lapses, once approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Policy attached to the requester while the grant is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+    /// Decision history, oldest first.
+    pub history: Vec<AccessRequestEvent>,
+}
+
+/// Who is submitting or deciding a request.
+#[derive(Debug, Clone, Copy)]
+pub struct Actor<'a> {
+    /// Identity entity of the token, if any.
+    pub entity_id: Option<&'a str>,
+    /// Hash of the token.
+    pub token_hash: &'a str,
+    /// Display name for history and notifications.
+    pub display_name: &'a str,
+}
+
+impl Actor<'_> {
+    /// Requester ID this actor is known by.
+    #[must_use]
+    pub fn requester_id(&self) -> String {
+        ActivityLog::client_id(self.entity_id, self.token_hash).0
+    }
+}
+
+/// Parameters for [`AccessRequestStore::create`].
+#[derive(Debug, Clone)]
+pub struct NewAccessRequest {
+    /// Path pattern to request access to.
+    pub path: String,
+    /// Requested capabilities.
+    pub capabilities: Vec<Capability>,
+    /// Why access is needed.
+    pub reason: String,
+    /// Grant duration. Defaults to [`DEFAULT_TTL`].
+    pub ttl: Option<Duration>,
+}
+
+/// An active grant in a requester's index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Grant {
+    request_id: String,
+    policy: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Stores access requests and the grants they produce.
+pub struct AccessRequestStore {
+    barrier: Arc<Barrier>,
+    policy_store: Arc<PolicyStore>,
+    event_bus: Option<Arc<EventBus>>,
+    webhook: Option<Webhook>,
+    /// Serializes state transitions so a request is decided once.
+    lock: Mutex<()>,
+}
+
+/// Slack-compatible incoming webhook.
+struct Webhook {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl AccessRequestStore {
+    /// Create a store backed by the barrier, writing grant policies to `policy_store`.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>, policy_store: Arc<PolicyStore>) -> Self {
+        Self {
+            barrier,
+            policy_store,
+            event_bus: None,
+            webhook: None,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Publish state changes on `event_bus`.
+    #[must_use]
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Post state changes as `{"text": ...}` to a Slack-compatible webhook.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessRequestError::InvalidRequest`] if `url` is not an
+    /// `http(s)` URL or the HTTP client cannot be built.
+    pub fn with_webhook(mut self, url: &str) -> Result<Self, AccessRequestError> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| invalid(format!("webhook url: {e}")))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(invalid("webhook url must use http or https"));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| invalid(format!("failed to build http client: {e}")))?;
+        self.webhook = Some(Webhook {
+            url: url.to_owned(),
+            client,
+        });
+        Ok(self)
+    }
+
+    /// Submit a new request on behalf of `requester`.
+    ///
+    /// # Errors
+    ///
+    /// - [`AccessRequestError::InvalidRequest`] if the path, capabilities,
+    ///   reason, or TTL are invalid.
+    /// - [`AccessRequestError::Barrier`] if storage fails.
+    pub async fn create(
+        &self,
+        requester: Actor<'_>,
+        params: NewAccessRequest,
+    ) -> Result<AccessRequest, AccessRequestError> {
+        let path = params.path.trim().trim_start_matches('/').to_owned();
+        if path.is_empty() {
+            return Err(invalid("path is required"));
+        }
+        if params.capabilities.is_empty() {
+            return Err(invalid("at least one capability is required"));
+        }
+        if params.capabilities.contains(&Capability::Deny) {
+            return Err(invalid("'deny' cannot be requested"));
+        }
+        if params.reason.trim().is_empty() {
+            return Err(invalid("reason is required"));
+        }
+        let ttl = params.ttl.unwrap_or(DEFAULT_TTL);
+        if ttl.is_zero() || ttl > MAX_TTL {
+            return Err(invalid(format!(
+                "ttl must be between 1s and {}s",
+                MAX_TTL.as_secs()
+            )));
+        }
+
+        let now = Utc::now();
+        let mut capabilities: Vec<Capability> = Vec::with_capacity(params.capabilities.len());
+        for capability in params.capabilities {
+            if !capabilities.contains(&capability) {
+                capabilities.push(capability);
+            }
+        }
+        let request = AccessRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            requester: requester.requester_id(),
+            requester_name: requester.display_name.to_owned(),
+            path,
+            capabilities,
+            reason: params.reason.trim().to_owned(),
+            ttl_secs: ttl.as_secs(),
+            status: AccessRequestStatus::Pending,
+            created_at: now,
+            expires_at: None,
+            policy: None,
+            history: vec![AccessRequestEvent {
+                at: now,
+                actor: requester.display_name.to_owned(),
+                status: AccessRequestStatus::Pending,
+                comment: None,
+            }],
+        };
+        self.save(&request).await?;
+
+        info!(id = %request.id, path = %request.path, "access request created");
+        self.notify(TOPIC_CREATED, &request, requester.display_name);
+        Ok(request)
+    }
+
+    /// Read a request by ID.
+    ///
+    /// # Errors
+    ///
+    /// - [`AccessRequestError::NotFound`] if no such request exists.
+    /// - [`AccessRequestError::Barrier`] if storage fails.
+    pub async fn get(&self, id: &str) -> Result<AccessRequest, AccessRequestError> {
+        let data = self
+            .barrier
+            .get(&format!("{REQUEST_PREFIX}{id}"))
+            .await?
+            .ok_or_else(|| AccessRequestError::NotFound { id: id.to_owned() })?;
+        serde_json::from_slice(&data).map_err(|e| AccessRequestError::Internal {
+            reason: format!("corrupt access request {id}: {e}"),
+        })
+    }
+
+    /// List all requests, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessRequestError::Barrier`] if storage fails.
+    pub async fn list(&self) -> Result<Vec<AccessRequest>, AccessRequestError> {
+        let keys = self.barrier.list(REQUEST_PREFIX).await?;
+        let mut requests = Vec::with_capacity(keys.len());
+        for key in keys {
+            let Some(id) = key.strip_prefix(REQUEST_PREFIX) else {
+                continue;
+            };
+            match self.get(id).await {
+                Ok(request) => requests.push(request),
+                Err(AccessRequestError::NotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        requests.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+        Ok(requests)
+    }
+
+    /// Approve a pending request, activating the grant for its TTL.
+    ///
+    /// # Errors
+    ///
+    /// - [`AccessRequestError::NotFound`] if no such request exists.
+    /// - [`AccessRequestError::SelfApproval`] if `approver` submitted it.
+    /// - [`AccessRequestError::InvalidState`] if it is not pending.
+    /// - [`AccessRequestError::Policy`] / [`AccessRequestError::Barrier`] if
+    ///   storage fails.
+    pub async fn approve(
+        &self,
+        id: &str,
+        approver: Actor<'_>,
+        comment: Option<String>,
+    ) -> Result<AccessRequest, AccessRequestError> {
+        let _guard = self.lock.lock().await;
+        let mut request = self.get(id).await?;
+        if approver.requester_id() == request.requester {
+            return Err(AccessRequestError::SelfApproval);
+        }
+        ensure_status(&request, AccessRequestStatus::Pending)?;
+
+        let policy_name = format!("{POLICY_PREFIX}{}", request.id);
+        self.policy_store
+            .put(&Policy {
+                name: policy_name.clone(),
+                rules: vec![PolicyRule {
+                    path: request.path.clone(),
+                    capabilities: request.capabilities.clone(),
+                    control_group: None,
+                    mfa_methods: Vec::new(),
+                }],
+            })
+            .await?;
+
+        let now = Utc::now();
+        let ttl = chrono::Duration::seconds(i64::try_from(request.ttl_secs).unwrap_or(i64::MAX));
+        let expires_at = now + ttl;
+        let mut grants = self.load_grants(&request.requester).await?;
+        grants.push(Grant {
+            request_id: request.id.clone(),
+            policy: policy_name.clone(),
+            expires_at,
+        });
+        self.save_grants(&request.requester, &grants).await?;
+
+        request.status = AccessRequestStatus::Approved;
+        request.expires_at = Some(expires_at);
+        request.policy = Some(policy_name);
+        request.history.push(AccessRequestEvent {
+            at: now,
+            actor: approver.display_name.to_owned(),
+            status: AccessRequestStatus::Approved,
+            comment,
+        });
+        self.save(&request).await?;
+
+        info!(id = %request.id, %expires_at, "access request approved");
+        self.notify(TOPIC_APPROVED, &request, approver.display_name);
+        Ok(request)
+    }
+
+    /// Deny a pending request.
+    ///
+    /// # Errors
+    ///
+    /// - [`AccessRequestError::NotFound`] if no such request exists.
+    /// - [`AccessRequestError::InvalidState`] if it is not pending.
+    /// - [`AccessRequestError::Barrier`] if storage fails.
+    pub async fn deny(
+        &self,
+        id: &str,
+        approver: Actor<'_>,
+        comment: Option<String>,
+    ) -> Result<AccessRequest, AccessRequestError> {
+        let _guard = self.lock.lock().await;
+        let mut request = self.get(id).await?;
+        ensure_status(&request, AccessRequestStatus::Pending)?;
+
+        request.status = AccessRequestStatus::Denied;
+        request.history.push(AccessRequestEvent {
+            at: Utc::now(),
+            actor: approver.display_name.to_owned(),
+            status: AccessRequestStatus::Denied,
+            comment,
+        });
+        self.save(&request).await?;
+
+        info!(id = %request.id, "access request denied");
+        self.notify(TOPIC_DENIED, &request, approver.display_name);
+        Ok(request)
+    }
+
+    /// Withdraw an approved grant before it expires.
+    ///
+    /// # Errors
+    ///
+    /// - [`AccessRequestError::NotFound`] if no such request exists.
+    /// - [`AccessRequestError::InvalidState`] if it is not approved.
+    /// - [`AccessRequestError::Policy`] / [`AccessRequestError::Barrier`] if
+    ///   storage fails.
+    pub async fn revoke(
+        &self,
+        id: &str,
+        actor: Actor<'_>,
+        comment: Option<String>,
+    ) -> Result<AccessRequest, AccessRequestError> {
+        let _guard = self.lock.lock().await;
+        let mut request = self.get(id).await?;
+        ensure_status(&request, AccessRequestStatus::Approved)?;
+
+        self.end_grant(
+            &mut request,
+            AccessRequestStatus::Revoked,
+            actor.display_name,
+            comment,
+        )
+        .await?;
+
+        info!(id = %request.id, "access request revoked");
+        self.notify(TOPIC_REVOKED, &request, actor.display_name);
+        Ok(request)
+    }
+
+    /// Policies granted to `requester` by approved, unexpired requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessRequestError::Barrier`] if storage fails.
+    pub async fn active_policies(
+        &self,
+        requester: &str,
+    ) -> Result<Vec<String>, AccessRequestError> {
+        let now = Utc::now();
+        Ok(self
+            .load_grants(requester)
+            .await?
+            .into_iter()
+            .filter(|g| g.expires_at > now)
+            .map(|g| g.policy)
+            .collect())
+    }
+
+    /// End every grant that expired at or before `now`, deleting its policy.
+    ///
+    /// Returns the expired requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessRequestError::Barrier`] if storage fails.
+    pub async fn expire(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AccessRequest>, AccessRequestError> {
+        let _guard = self.lock.lock().await;
+        let mut expired = Vec::new();
+        for request in self.list().await? {
+            let lapsed = request.status == AccessRequestStatus::Approved
+                && request.expires_at.is_some_and(|at| at <= now);
+            if !lapsed {
+                continue;
+            }
+            let mut request = request;
+            self.end_grant(&mut request, AccessRequestStatus::Expired, "system", None)
+                .await?;
+            self.notify(TOPIC_EXPIRED, &request, "system");
+            expired.push(request);
+        }
+        if !expired.is_empty() {
+            info!(count = expired.len(), "access grants expired");
+        }
+        Ok(expired)
+    }
+
+    // ── Internal helpers ─────────────────────────────────────────────
+
+    /// Remove the grant and policy of an approved request and record `status`.
+    async fn end_grant(
+        &self,
+        request: &mut AccessRequest,
+        status: AccessRequestStatus,
+        actor: &str,
+        comment: Option<String>,
+    ) -> Result<(), AccessRequestError> {
+        let mut grants = self.load_grants(&request.requester).await?;
+        grants.retain(|g| g.request_id != request.id);
+        self.save_grants(&request.requester, &grants).await?;
+
+        if let Some(policy) = &request.policy {
+            match self.policy_store.delete(policy).await {
+                Ok(()) | Err(PolicyError::NotFound { .. }) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        request.status = status;
+        request.history.push(AccessRequestEvent {
+            at: Utc::now(),
+            actor: actor.to_owned(),
+            status,
+            comment,
+        });
+        self.save(request).await
+    }
+
+    async fn save(&self, request: &AccessRequest) -> Result<(), AccessRequestError> {
+        let bytes = serde_json::to_vec(request).map_err(|e| AccessRequestError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier
+            .put(&format!("{REQUEST_PREFIX}{}", request.id), &bytes)
+            .await?;
+        Ok(())
+    }
+
+    async fn load_grants(&self, requester: &str) -> Result<Vec<Grant>, AccessRequestError> {
+        match self
+            .barrier
+            .get(&format!("{GRANT_PREFIX}{requester}"))
+            .await?
+        {
+            Some(data) => serde_json::from_slice(&data).map_err(|e| AccessRequestError::Internal {
+                reason: format!("corrupt access grants for {requester}: {e}"),
+            }),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn save_grants(
+        &self,
+        requester: &str,
+        grants: &[Grant],
+    ) -> Result<(), AccessRequestError> {
+        let key = format!("{GRANT_PREFIX}{requester}");
+        if grants.is_empty() {
+            self.barrier.delete(&key).await?;
+            return Ok(());
+        }
+        let bytes = serde_json::to_vec(grants).map_err(|e| AccessRequestError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(&key, &bytes).await?;
+        Ok(())
+    }
+
+    /// Publish an event and post the webhook message (best-effort).
+    fn notify(&self, topic: &str, request: &AccessRequest, actor: &str) {
+        if let Some(bus) = &self.event_bus {
+            bus.publish(
+                topic,
+                serde_json::json!({
+                    "id": request.id,
+                    "requester": request.requester_name,
+                    "path": request.path,
+                    "capabilities": request.capabilities,
+                    "status": request.status,
+                    "actor": actor,
+                    "expires_at": request.expires_at,
+                }),
+            );
+        }
+
+        let Some(webhook) = &self.webhook else {
+            return;
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let client = webhook.client.clone();
+        let url = webhook.url.clone();
+        let body = serde_json::json!({ "text": message(request, actor) });
+        let id = request.id.clone();
+        handle.spawn(async move {
+            let result = client
+                .post(&url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            if let Err(e) = result {
+                warn!(id = %id, error = %e, "access request webhook failed");
+            }
+        });
+    }
+}
+
+impl std::fmt::Debug for AccessRequestStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessRequestStore")
+            .field("webhook", &self.webhook.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Human-readable notification text.
+fn message(request: &AccessRequest, actor: &str) -> String {
+    let capabilities = request
+        .capabilities
+        .iter()
+        .map(|c| format!("{c:?}").to_lowercase())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let what = format!(
+        "`{}` ({capabilities}) on `{}`",
+        request.requester_name, request.path
+    );
+    match request.status {
+        AccessRequestStatus::Pending => format!(
+            "Access request {}: {what} for {}s — \"{}\"",
+            request.id, request.ttl_secs, request.reason
+        ),
+        AccessRequestStatus::Approved => format!(
+            "Access request {} approved by {actor}: {what} until {}",
+            request.id,
+            request
+                .expires_at
+                .map_or_else(String::new, |at| at.to_rfc3339())
+        ),
+        status => format!("Access request {} {status} by {actor}: {what}", request.id),
+    }
+}
+
+fn ensure_status(
+    request: &AccessRequest,
+    expected: AccessRequestStatus,
+) -> Result<(), AccessRequestError> {
+    if request.status == expected {
+        Ok(())
+    } else {
+        Err(AccessRequestError::InvalidState {
+            id: request.id.clone(),
+            status: request.status.to_string(),
+        })
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> AccessRequestError {
+    AccessRequestError::InvalidRequest {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn store() -> (AccessRequestStore, Arc<PolicyStore>) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let policies = Arc::new(PolicyStore::new(Arc::clone(&barrier)));
+        (
+            AccessRequestStore::new(barrier, Arc::clone(&policies)),
+            policies,
+        )
+    }
+
+    fn actor<'a>(entity: &'a str, name: &'a str) -> Actor<'a> {
+        Actor {
+            entity_id: Some(entity),
+            token_hash: "hash",
+            display_name: name,
+        }
+    }
+
+    fn read_prod() -> NewAccessRequest {
+        NewAccessRequest {
+            path: "secret/prod/**".to_owned(),
+            capabilities: vec![Capability::Read, Capability::List],
+            reason: "incident 42".to_owned(),
+            ttl: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn approved_request_grants_policy_until_revoked() {
+        let (store, policies) = store().await;
+        let alice = actor("ent-alice", "alice");
+        let bob = actor("ent-bob", "bob");
+
+        let request = store.create(alice, read_prod()).await.unwrap();
+        assert!(store.active_policies("ent-alice").await.unwrap().is_empty());
+
+        let err = store.approve(&request.id, alice, None).await.unwrap_err();
+        assert!(matches!(err, AccessRequestError::SelfApproval));
+
+        let approved = store
+            .approve(&request.id, bob, Some("ok".to_owned()))
+            .await
+            .unwrap();
+        assert_eq!(approved.status, AccessRequestStatus::Approved);
+        assert_eq!(approved.history.len(), 2);
+
+        let granted = store.active_policies("ent-alice").await.unwrap();
+        assert_eq!(granted.len(), 1);
+        policies
+            .check(&granted, "secret/prod/db", &Capability::Read)
+            .await
+            .unwrap();
+        assert!(
+            policies
+                .check(&granted, "secret/prod/db", &Capability::Update)
+                .await
+                .is_err()
+        );
+
+        let err = store.deny(&request.id, bob, None).await.unwrap_err();
+        assert!(matches!(err, AccessRequestError::InvalidState { .. }));
+
+        store.revoke(&request.id, bob, None).await.unwrap();
+        assert!(store.active_policies("ent-alice").await.unwrap().is_empty());
+        assert!(policies.get(&granted[0]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn expire_ends_lapsed_grants() {
+        let (store, policies) = store().await;
+        let alice = actor("ent-alice", "alice");
+        let request = store.create(alice, read_prod()).await.unwrap();
+        store
+            .approve(&request.id, actor("ent-bob", "bob"), None)
+            .await
+            .unwrap();
+
+        assert!(store.expire(Utc::now()).await.unwrap().is_empty());
+
+        let later = Utc::now() + chrono::Duration::hours(2);
+        let expired = store.expire(later).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].status, AccessRequestStatus::Expired);
+        assert!(store.active_policies("ent-alice").await.unwrap().is_empty());
+        let policy = format!("{POLICY_PREFIX}{}", request.id);
+        assert!(policies.get(&policy).await.is_err());
+        assert_eq!(
+            store.get(&request.id).await.unwrap().status,
+            AccessRequestStatus::Expired
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_requests_are_rejected() {
+        let (store, _) = store().await;
+        let alice = actor("ent-alice", "alice");
+
+        for params in [
+            NewAccessRequest {
+                capabilities: vec![],
+                ..read_prod()
+            },
+            NewAccessRequest {
+                capabilities: vec![Capability::Deny],
+                ..read_prod()
+            },
+            NewAccessRequest {
+                reason: " ".to_owned(),
+                ..read_prod()
+            },
+            NewAccessRequest {
+                ttl: Some(MAX_TTL + Duration::from_secs(1)),
+                ..read_prod()
+            },
+        ] {
+            let err = store.create(alice, params).await.unwrap_err();
+            assert!(matches!(err, AccessRequestError::InvalidRequest { .. }));
+        }
+        assert!(store.list().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1117 @@
+//! ACME (RFC 8555) client for the server's own TLS certificate.
+//!
+//! Obtains and renews a publicly trusted certificate — from Let's Encrypt by
+//! default — for the domains the server answers on, so small installs get
+//! HTTPS without a reverse proxy or manual certificate management. Domain
+//! control is proved with one of two challenges:
+//!
+//! - `http-01` — the server answers `/.well-known/acme-challenge/{token}` on
+//!   a plain HTTP listener from the shared [`Http01Responder`].
+//! - `dns-01` — a TXT record is published through a [`DnsProvider`]. This is
+//!   the only challenge that can prove wildcard domains.
+//!
+//! The account key, the certificate chain, and its private key are stored
+//! through the barrier under `sys/acme/`, so a certificate can only be loaded
+//! or issued while the vault is unsealed.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aes_gcm::aead::OsRng;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64URL};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use p256::ecdsa::signature::Signer as _;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::{DecodePrivateKey as _, EncodePrivateKey as _};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::barrier::Barrier;
+use crate::error::AcmeError;
+
+/// Let's Encrypt production directory.
+pub const LETS_ENCRYPT_DIRECTORY: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+/// Let's Encrypt staging directory, for testing without production rate limits.
+pub const LETS_ENCRYPT_STAGING_DIRECTORY: &str =
+    "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+/// Barrier key of the ACME account key.
+const ACCOUNT_KEY: &str = "sys/acme/account";
+
+/// Barrier key of the current certificate.
+const CERTIFICATE_KEY: &str = "sys/acme/certificate";
+
+/// Timeout for each request to the ACME server or DNS provider.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delay between polls of a pending authorization or order.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Polls before a pending authorization or order is abandoned.
+const POLL_ATTEMPTS: u32 = 30;
+
+/// Time allowed for a new TXT record to reach the ACME server's resolvers.
+const DNS_PROPAGATION_DELAY: Duration = Duration::from_secs(30);
+
+/// How domain control is proved to the ACME server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeType {
+    /// Serve the key authorization over plain HTTP on port 80.
+    #[serde(rename = "http-01")]
+    Http01,
+    /// Publish the key authorization digest in a DNS TXT record.
+    #[serde(rename = "dns-01")]
+    Dns01,
+}
+
+impl ChallengeType {
+    /// The challenge type as named by RFC 8555.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Http01 => "http-01",
+            Self::Dns01 => "dns-01",
+        }
+    }
+}
+
+impl FromStr for ChallengeType {
+    type Err = AcmeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "http-01" | "http" => Ok(Self::Http01),
+            "dns-01" | "dns" => Ok(Self::Dns01),
+            other => Err(AcmeError::InvalidConfig {
+                reason: format!("unknown challenge type '{other}' (expected http-01 or dns-01)"),
+            }),
+        }
+    }
+}
+
+/// What certificate to obtain, and from where.
+#[derive(Debug, Clone)]
+pub struct AcmeConfig {
+    /// ACME directory URL.
+    pub directory_url: String,
+    /// Contact email registered with the account (optional).
+    pub contact_email: Option<String>,
+    /// Domains the certificate covers. The first is the subject.
+    pub domains: Vec<String>,
+    /// Challenge used to prove control of the domains.
+    pub challenge: ChallengeType,
+    /// Renew once the certificate has less than this long left.
+    pub renew_before: chrono::Duration,
+}
+
+/// Publishes `dns-01` challenge records.
+#[async_trait::async_trait]
+pub trait DnsProvider: Send + Sync {
+    /// Create a TXT record `name` with `value`, returning an ID that
+    /// [`DnsProvider::delete_txt`] accepts.
+    async fn create_txt(&self, name: &str, value: &str) -> Result<String, AcmeError>;
+
+    /// Remove a record created by [`DnsProvider::create_txt`].
+    async fn delete_txt(&self, id: &str) -> Result<(), AcmeError>;
+}
+
+/// [`DnsProvider`] for zones hosted on Cloudflare.
+pub struct CloudflareDns {
+    http: reqwest::Client,
+    api_token: String,
+    zone_id: String,
+}
+
+impl CloudflareDns {
+    /// Cloudflare API base URL.
+    const API_URL: &'static str = "https://api.cloudflare.com/client/v4";
+
+    /// Create a provider for `zone_id`, authenticated by an API token with
+    /// `Zone.DNS` edit permission.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcmeError::InvalidConfig`] if either value is empty.
+    pub fn new(api_token: String, zone_id: String) -> Result<Self, AcmeError> {
+        if api_token.is_empty() || zone_id.is_empty() {
+            return Err(AcmeError::InvalidConfig {
+                reason: "cloudflare DNS requires an API token and a zone ID".to_owned(),
+            });
+        }
+        Ok(Self {
+            http: http_client()?,
+            api_token,
+            zone_id,
+        })
+    }
+
+    async fn call(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value, AcmeError> {
+        let reply: serde_json::Value = request
+            .bearer_auth(&self.api_token)
+            .send()
+            .await
+            .map_err(|e| AcmeError::Dns {
+                reason: format!("cloudflare request failed: {e}"),
+            })?
+            .json()
+            .await
+            .map_err(|e| AcmeError::Dns {
+                reason: format!("invalid cloudflare response: {e}"),
+            })?;
+        if reply["success"].as_bool() != Some(true) {
+            return Err(AcmeError::Dns {
+                reason: format!("cloudflare rejected the request: {}", reply["errors"]),
+            });
+        }
+        Ok(reply)
+    }
+}
+
+#[async_trait::async_trait]
+impl DnsProvider for CloudflareDns {
+    async fn create_txt(&self, name: &str, value: &str) -> Result<String, AcmeError> {
+        let url = format!("{}/zones/{}/dns_records", Self::API_URL, self.zone_id);
+        let body = serde_json::json!({ "type": "TXT", "name": name, "content": value, "ttl": 60 });
+        let reply = self.call(self.http.post(url).json(&body)).await?;
+        reply["result"]["id"]
+            .as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| AcmeError::Dns {
+                reason: "cloudflare response is missing the record id".to_owned(),
+            })
+    }
+
+    async fn delete_txt(&self, id: &str) -> Result<(), AcmeError> {
+        let url = format!("{}/zones/{}/dns_records/{id}", Self::API_URL, self.zone_id);
+        self.call(self.http.delete(url)).await.map(|_| ())
+    }
+}
+
+impl std::fmt::Debug for CloudflareDns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CloudflareDns")
+            .field("zone_id", &self.zone_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Key authorizations for pending `http-01` challenges, keyed by token.
+///
+/// Shared between the [`AcmeClient`] and the plain HTTP listener that serves
+/// `/.well-known/acme-challenge/{token}`.
+#[derive(Debug, Default)]
+pub struct Http01Responder {
+    tokens: RwLock<HashMap<String, String>>,
+}
+
+impl Http01Responder {
+    /// The key authorization to serve for `token`, if a challenge is pending.
+    pub async fn key_authorization(&self, token: &str) -> Option<String> {
+        self.tokens.read().await.get(token).cloned()
+    }
+
+    async fn insert(&self, token: &str, key_authorization: String) {
+        self.tokens
+            .write()
+            .await
+            .insert(token.to_owned(), key_authorization);
+    }
+
+    async fn remove(&self, token: &str) {
+        self.tokens.write().await.remove(token);
+    }
+}
+
+/// A certificate chain and its private key.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AcmeCertificate {
+    /// Domains the certificate covers.
+    pub domains: Vec<String>,
+    /// PEM-encoded certificate chain, leaf first.
+    pub certificate_pem: String,
+    /// PEM-encoded PKCS#8 private key (encrypted at rest via barrier).
+    pub private_key_pem: String,
+    /// When the leaf certificate expires.
+    pub not_after: DateTime<Utc>,
+    /// When the certificate was obtained.
+    pub issued_at: DateTime<Utc>,
+}
+
+impl AcmeCertificate {
+    /// Whether the certificate must be replaced: it covers different domains
+    /// than `domains`, or expires within `renew_before` of `now`.
+    #[must_use]
+    pub fn needs_renewal(
+        &self,
+        domains: &[String],
+        renew_before: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let mut have = self.domains.clone();
+        let mut want = domains.to_vec();
+        have.sort();
+        want.sort();
+        have != want || self.not_after - now < renew_before
+    }
+}
+
+impl std::fmt::Debug for AcmeCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AcmeCertificate")
+            .field("domains", &self.domains)
+            .field("not_after", &self.not_after)
+            .field("issued_at", &self.issued_at)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Generate a self-signed certificate for `domains`.
+///
+/// Served while the vault is sealed and the ACME certificate is unreadable,
+/// so TLS clients get a handshake (and an untrusted-certificate error)
+/// rather than a reset connection.
+///
+/// # Errors
+///
+/// Returns [`AcmeError::Internal`] if generation fails.
+pub fn self_signed(domains: &[String]) -> Result<AcmeCertificate, AcmeError> {
+    let internal = |e: rcgen::Error| AcmeError::Internal {
+        reason: format!("self-signed certificate generation failed: {e}"),
+    };
+    let key_pair = rcgen::KeyPair::generate().map_err(internal)?;
+    let certificate = certificate_params(domains)
+        .map_err(internal)?
+        .self_signed(&key_pair)
+        .map_err(internal)?;
+    let certificate_pem = certificate.pem();
+    Ok(AcmeCertificate {
+        domains: domains.to_vec(),
+        not_after: certificate_not_after(&certificate_pem)?,
+        certificate_pem,
+        private_key_pem: key_pair.serialize_pem(),
+        issued_at: Utc::now(),
+    })
+}
+
+/// Certificate parameters for `domains`, with the first as the subject.
+fn certificate_params(domains: &[String]) -> Result<rcgen::CertificateParams, rcgen::Error> {
+    let mut params = rcgen::CertificateParams::new(domains.to_vec())?;
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    if let Some(subject) = domains.first() {
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, subject.as_str());
+    }
+    Ok(params)
+}
+
+/// Stored ACME account.
+#[derive(Serialize, Deserialize)]
+struct StoredAccount {
+    /// Directory the account is registered with.
+    directory_url: String,
+    /// Base64 PKCS#8 document of the P-256 account key.
+    key_pkcs8: String,
+}
+
+/// Obtains, stores, and renews the server certificate.
+pub struct AcmeClient {
+    barrier: Arc<Barrier>,
+    config: AcmeConfig,
+    http: reqwest::Client,
+    http01: Arc<Http01Responder>,
+    dns: Option<Arc<dyn DnsProvider>>,
+}
+
+impl AcmeClient {
+    /// Create a client for `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcmeError::InvalidConfig`] if no domains are configured, if
+    /// `dns-01` is selected without a DNS provider, or if a wildcard domain
+    /// is requested with `http-01`.
+    pub fn new(
+        barrier: Arc<Barrier>,
+        config: AcmeConfig,
+        http01: Arc<Http01Responder>,
+        dns: Option<Arc<dyn DnsProvider>>,
+    ) -> Result<Self, AcmeError> {
+        let invalid = |reason: &str| AcmeError::InvalidConfig {
+            reason: reason.to_owned(),
+        };
+        if config.domains.is_empty() || config.domains.iter().any(String::is_empty) {
+            return Err(invalid("at least one non-empty domain is required"));
+        }
+        match config.challenge {
+            ChallengeType::Dns01 if dns.is_none() => {
+                return Err(invalid("dns-01 requires a DNS provider"));
+            }
+            ChallengeType::Http01 if config.domains.iter().any(|d| d.starts_with("*.")) => {
+                return Err(invalid("wildcard domains require the dns-01 challenge"));
+            }
+            _ => {}
+        }
+        Ok(Self {
+            barrier,
+            config,
+            http: http_client()?,
+            http01,
+            dns,
+        })
+    }
+
+    /// The client's configuration.
+    #[must_use]
+    pub fn config(&self) -> &AcmeConfig {
+        &self.config
+    }
+
+    /// The stored certificate, if one has been issued.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcmeError::Barrier`] if the barrier is sealed or storage
+    /// fails, or [`AcmeError::Internal`] if the record is corrupt.
+    pub async fn certificate(&self) -> Result<Option<AcmeCertificate>, AcmeError> {
+        let Some(raw) = self.barrier.get(CERTIFICATE_KEY).await? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&raw)
+            .map(Some)
+            .map_err(|e| AcmeError::Internal {
+                reason: format!("corrupt ACME certificate record: {e}"),
+            })
+    }
+
+    /// The stored certificate, issuing a new one first if there is none or
+    /// it is due for renewal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcmeError::Barrier`] if the barrier is sealed, or any error
+    /// from issuance.
+    pub async fn ensure_certificate(&self) -> Result<AcmeCertificate, AcmeError> {
+        if let Some(current) = self.certificate().await?
+            && !current.needs_renewal(&self.config.domains, self.config.renew_before, Utc::now())
+        {
+            return Ok(current);
+        }
+        let issued = self.issue().await?;
+        let bytes = serde_json::to_vec(&issued).map_err(|e| AcmeError::Internal {
+            reason: format!("failed to serialize ACME certificate: {e}"),
+        })?;
+        self.barrier.put(CERTIFICATE_KEY, &bytes).await?;
+        info!(
+            domains = ?issued.domains,
+            not_after = %issued.not_after,
+            "ACME certificate issued"
+        );
+        Ok(issued)
+    }
+
+    /// The account key, generated and stored on first use.
+    async fn account_key(&self) -> Result<Zeroizing<Vec<u8>>, AcmeError> {
+        if let Some(raw) = self.barrier.get(ACCOUNT_KEY).await? {
+            let stored: StoredAccount =
+                serde_json::from_slice(&raw).map_err(|e| AcmeError::Internal {
+                    reason: format!("corrupt ACME account record: {e}"),
+                })?;
+            if stored.directory_url == self.config.directory_url {
+                return BASE64
+                    .decode(stored.key_pkcs8)
+                    .map(Zeroizing::new)
+                    .map_err(|e| AcmeError::Internal {
+                        reason: format!("corrupt ACME account key: {e}"),
+                    });
+            }
+        }
+        let pkcs8 =
+            SigningKey::random(&mut OsRng)
+                .to_pkcs8_der()
+                .map_err(|e| AcmeError::Internal {
+                    reason: format!("failed to encode ACME account key: {e}"),
+                })?;
+        let stored = StoredAccount {
+            directory_url: self.config.directory_url.clone(),
+            key_pkcs8: BASE64.encode(pkcs8.as_bytes()),
+        };
+        let bytes = serde_json::to_vec(&stored).map_err(|e| AcmeError::Internal {
+            reason: format!("failed to serialize ACME account: {e}"),
+        })?;
+        self.barrier.put(ACCOUNT_KEY, &bytes).await?;
+        Ok(Zeroizing::new(pkcs8.as_bytes().to_vec()))
+    }
+
+    /// Run a complete order: account, authorizations, finalize, download.
+    async fn issue(&self) -> Result<AcmeCertificate, AcmeError> {
+        let key = self.account_key().await?;
+        let mut session = Session::open(&self.http, &self.config.directory_url, &key).await?;
+        session
+            .register(self.config.contact_email.as_deref())
+            .await?;
+
+        let identifiers: Vec<_> = self
+            .config
+            .domains
+            .iter()
+            .map(|d| serde_json::json!({ "type": "dns", "value": d }))
+            .collect();
+        let new_order = session.directory.new_order.clone();
+        let reply = session
+            .post(
+                &new_order,
+                Some(&serde_json::json!({ "identifiers": identifiers })),
+            )
+            .await?;
+        let order_url = reply
+            .location
+            .clone()
+            .ok_or_else(|| protocol("order has no location"))?;
+        let order: Order = reply.json()?;
+
+        let mut cleanup = Vec::new();
+        let authorized = self
+            .authorize(&mut session, &order.authorizations, &mut cleanup)
+            .await;
+        for pending in cleanup {
+            self.clean_up(pending).await;
+        }
+        authorized?;
+
+        let key_pair = rcgen::KeyPair::generate().map_err(|e| AcmeError::Internal {
+            reason: format!("certificate key generation failed: {e}"),
+        })?;
+        let csr = certificate_params(&self.config.domains)
+            .and_then(|params| params.serialize_request(&key_pair))
+            .map_err(|e| AcmeError::Internal {
+                reason: format!("CSR generation failed: {e}"),
+            })?;
+        let csr = BASE64URL.encode(csr.der());
+        session
+            .post(&order.finalize, Some(&serde_json::json!({ "csr": csr })))
+            .await?;
+
+        let certificate_url = session.await_order(&order_url).await?;
+        let certificate_pem = String::from_utf8(session.post(&certificate_url, None).await?.body)
+            .map_err(|_| protocol("certificate chain is not valid PEM"))?;
+        Ok(AcmeCertificate {
+            domains: self.config.domains.clone(),
+            not_after: certificate_not_after(&certificate_pem)?,
+            certificate_pem,
+            private_key_pem: key_pair.serialize_pem(),
+            issued_at: Utc::now(),
+        })
+    }
+
+    /// Respond to every pending authorization and wait for all to be valid.
+    ///
+    /// Published challenge responses are appended to `cleanup` as they are
+    /// created, so the caller can remove them whatever the outcome.
+    async fn authorize(
+        &self,
+        session: &mut Session<'_>,
+        authorizations: &[String],
+        cleanup: &mut Vec<Published>,
+    ) -> Result<(), AcmeError> {
+        let mut pending = Vec::new();
+        for url in authorizations {
+            let authz: Authorization = session.post(url, None).await?.json()?;
+            if authz.status == "valid" {
+                continue;
+            }
+            let domain = authz.identifier.value.clone();
+            let challenge = authz
+                .challenges
+                .iter()
+                .find(|c| c.kind == self.config.challenge.as_str())
+                .ok_or_else(|| AcmeError::Challenge {
+                    domain: domain.clone(),
+                    reason: format!(
+                        "server offers no {} challenge",
+                        self.config.challenge.as_str()
+                    ),
+                })?;
+            let key_authorization = format!("{}.{}", challenge.token, session.thumbprint);
+            cleanup.push(
+                self.publish(&domain, &challenge.token, key_authorization)
+                    .await?,
+            );
+            pending.push((url.clone(), challenge.url.clone(), domain));
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if self.config.challenge == ChallengeType::Dns01 {
+            tokio::time::sleep(DNS_PROPAGATION_DELAY).await;
+        }
+        for (_, challenge_url, _) in &pending {
+            session
+                .post(challenge_url, Some(&serde_json::json!({})))
+                .await?;
+        }
+        for (authz_url, _, domain) in &pending {
+            session.await_authorization(authz_url, domain).await?;
+        }
+        Ok(())
+    }
+
+    /// Publish a challenge response for `domain`.
+    async fn publish(
+        &self,
+        domain: &str,
+        token: &str,
+        key_authorization: String,
+    ) -> Result<Published, AcmeError> {
+        match self.config.challenge {
+            ChallengeType::Http01 => {
+                self.http01.insert(token, key_authorization).await;
+                Ok(Published::Http01(token.to_owned()))
+            }
+            ChallengeType::Dns01 => {
+                let dns = self.dns.as_ref().ok_or_else(|| AcmeError::InvalidConfig {
+                    reason: "dns-01 requires a DNS provider".to_owned(),
+                })?;
+                let digest = BASE64URL.encode(Sha256::digest(key_authorization.as_bytes()));
+                let id = dns
+                    .create_txt(&format!("_acme-challenge.{domain}"), &digest)
+                    .await?;
+                Ok(Published::Dns01(id))
+            }
+        }
+    }
+
+    /// Remove a published challenge response, logging failures.
+    async fn clean_up(&self, published: Published) {
+        match published {
+            Published::Http01(token) => self.http01.remove(&token).await,
+            Published::Dns01(id) => {
+                if let Some(dns) = &self.dns
+                    && let Err(e) = dns.delete_txt(&id).await
+                {
+                    warn!(record_id = %id, error = %e, "failed to remove ACME challenge record");
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for AcmeClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AcmeClient")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A challenge response that must be removed after validation.
+enum Published {
+    Http01(String),
+    Dns01(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_field_names)]
+struct Directory {
+    new_nonce: String,
+    new_account: String,
+    new_order: String,
+}
+
+#[derive(Deserialize)]
+struct Order {
+    status: String,
+    #[serde(default)]
+    authorizations: Vec<String>,
+    #[serde(default)]
+    finalize: String,
+    certificate: Option<String>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Authorization {
+    status: String,
+    identifier: Identifier,
+    #[serde(default)]
+    challenges: Vec<Challenge>,
+}
+
+#[derive(Deserialize)]
+struct Identifier {
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct Challenge {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+    #[serde(default)]
+    token: String,
+    error: Option<serde_json::Value>,
+}
+
+/// A successful reply to a signed request.
+struct Reply {
+    location: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn json<T: DeserializeOwned>(&self) -> Result<T, AcmeError> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| protocol(&format!("malformed ACME response: {e}")))
+    }
+}
+
+/// Signed-request state for one order.
+struct Session<'a> {
+    http: &'a reqwest::Client,
+    directory: Directory,
+    key: SigningKey,
+    /// The account's public key as a JWK.
+    jwk: serde_json::Value,
+    /// RFC 7638 thumbprint of `jwk`.
+    thumbprint: String,
+    /// Account URL, once registered.
+    kid: Option<String>,
+    nonce: Option<String>,
+}
+
+impl<'a> Session<'a> {
+    async fn open(
+        http: &'a reqwest::Client,
+        directory_url: &str,
+        pkcs8: &[u8],
+    ) -> Result<Self, AcmeError> {
+        let key = SigningKey::from_pkcs8_der(pkcs8).map_err(|e| AcmeError::Internal {
+            reason: format!("invalid ACME account key: {e}"),
+        })?;
+        let (jwk, thumbprint) =
+            jwk_and_thumbprint(key.verifying_key().to_encoded_point(false).as_bytes())?;
+        let directory = http
+            .get(directory_url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| protocol(&format!("failed to fetch directory {directory_url}: {e}")))?
+            .json()
+            .await
+            .map_err(|e| protocol(&format!("invalid directory at {directory_url}: {e}")))?;
+        Ok(Self {
+            http,
+            directory,
+            key,
+            jwk,
+            thumbprint,
+            kid: None,
+            nonce: None,
+        })
+    }
+
+    /// Register the account, or look up the existing one for this key.
+    async fn register(&mut self, email: Option<&str>) -> Result<(), AcmeError> {
+        let mut payload = serde_json::json!({ "termsOfServiceAgreed": true });
+        if let Some(email) = email {
+            payload["contact"] = serde_json::json!([format!("mailto:{email}")]);
+        }
+        let url = self.directory.new_account.clone();
+        let reply = self.post(&url, Some(&payload)).await?;
+        self.kid = Some(
+            reply
+                .location
+                .ok_or_else(|| protocol("account has no location"))?,
+        );
+        Ok(())
+    }
+
+    async fn nonce(&mut self) -> Result<String, AcmeError> {
+        if let Some(nonce) = self.nonce.take() {
+            return Ok(nonce);
+        }
+        let response = self
+            .http
+            .head(&self.directory.new_nonce)
+            .send()
+            .await
+            .map_err(|e| protocol(&format!("failed to fetch nonce: {e}")))?;
+        replay_nonce(&response).ok_or_else(|| protocol("server returned no nonce"))
+    }
+
+    /// Send a JWS-signed POST; `None` sends a POST-as-GET.
+    ///
+    /// A `badNonce` rejection is retried once with the fresh nonce it carries.
+    async fn post(
+        &mut self,
+        url: &str,
+        payload: Option<&serde_json::Value>,
+    ) -> Result<Reply, AcmeError> {
+        let mut retried = false;
+        loop {
+            let nonce = self.nonce().await?;
+            let body = self.sign(url, &nonce, payload)?;
+            let response = self
+                .http
+                .post(url)
+                .header("Content-Type", "application/jose+json")
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| protocol(&format!("request to {url} failed: {e}")))?;
+            self.nonce = replay_nonce(&response);
+            let status = response.status();
+            let location = response
+                .headers()
+                .get("Location")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned);
+            let body = response
+                .bytes()
+                .await
+                .map_err(|e| protocol(&format!("failed to read reply from {url}: {e}")))?
+                .to_vec();
+            if status.is_success() {
+                return Ok(Reply { location, body });
+            }
+            let problem: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            if !retried && problem["type"] == "urn:ietf:params:acme:error:badNonce" {
+                retried = true;
+                continue;
+            }
+            return Err(protocol(&format!(
+                "{url} returned {status}: {}",
+                problem["detail"].as_str().unwrap_or("no detail")
+            )));
+        }
+    }
+
+    /// Build the flattened JWS for a request.
+    fn sign(
+        &self,
+        url: &str,
+        nonce: &str,
+        payload: Option<&serde_json::Value>,
+    ) -> Result<String, AcmeError> {
+        let mut protected = serde_json::json!({ "alg": "ES256", "nonce": nonce, "url": url });
+        match &self.kid {
+            Some(kid) => protected["kid"] = serde_json::json!(kid),
+            None => protected["jwk"] = self.jwk.clone(),
+        }
+        let protected = BASE64URL.encode(protected.to_string());
+        let payload = payload.map_or_else(String::new, |p| BASE64URL.encode(p.to_string()));
+        let signature: Signature = self
+            .key
+            .try_sign(format!("{protected}.{payload}").as_bytes())
+            .map_err(|_| AcmeError::Internal {
+                reason: "failed to sign ACME request".to_owned(),
+            })?;
+        Ok(serde_json::json!({
+            "protected": protected,
+            "payload": payload,
+            "signature": BASE64URL.encode(signature.to_bytes()),
+        })
+        .to_string())
+    }
+
+    async fn await_authorization(&mut self, url: &str, domain: &str) -> Result<(), AcmeError> {
+        for _ in 0..POLL_ATTEMPTS {
+            let authz: Authorization = self.post(url, None).await?.json()?;
+            match authz.status.as_str() {
+                "valid" => return Ok(()),
+                "pending" => tokio::time::sleep(POLL_INTERVAL).await,
+                status => {
+                    let detail = authz
+                        .challenges
+                        .iter()
+                        .find_map(|c| c.error.as_ref())
+                        .and_then(|e| e["detail"].as_str())
+                        .unwrap_or("no detail");
+                    return Err(AcmeError::Challenge {
+                        domain: domain.to_owned(),
+                        reason: format!("authorization is {status}: {detail}"),
+                    });
+                }
+            }
+        }
+        Err(AcmeError::Challenge {
+            domain: domain.to_owned(),
+            reason: "timed out waiting for validation".to_owned(),
+        })
+    }
+
+    /// Poll a finalized order until its certificate URL is available.
+    async fn await_order(&mut self, url: &str) -> Result<String, AcmeError> {
+        for _ in 0..POLL_ATTEMPTS {
+            let order: Order = self.post(url, None).await?.json()?;
+            match (order.status.as_str(), order.certificate) {
+                ("valid", Some(certificate)) => return Ok(certificate),
+                ("pending" | "ready" | "processing" | "valid", _) => {
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                (status, _) => {
+                    return Err(protocol(&format!(
+                        "order is {status}: {}",
+                        order
+                            .error
+                            .as_ref()
+                            .map_or("no detail", |e| e["detail"].as_str().unwrap_or("no detail"))
+                    )));
+                }
+            }
+        }
+        Err(protocol("timed out waiting for the certificate"))
+    }
+}
+
+fn protocol(reason: &str) -> AcmeError {
+    AcmeError::Protocol {
+        reason: reason.to_owned(),
+    }
+}
+
+fn http_client() -> Result<reqwest::Client, AcmeError> {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| AcmeError::Internal {
+            reason: format!("failed to build http client: {e}"),
+        })
+}
+
+fn replay_nonce(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get("Replay-Nonce")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// The JWK for an uncompressed P-256 public key, and its RFC 7638 thumbprint.
+fn jwk_and_thumbprint(public_key: &[u8]) -> Result<(serde_json::Value, String), AcmeError> {
+    let coordinates = public_key
+        .strip_prefix(&[0x04])
+        .filter(|xy| xy.len() == 64)
+        .ok_or_else(|| AcmeError::Internal {
+            reason: "unexpected ACME account public key encoding".to_owned(),
+        })?;
+    let (x, y) = coordinates.split_at(32);
+    let (x, y) = (BASE64URL.encode(x), BASE64URL.encode(y));
+    // The thumbprint hashes the required members in lexicographic order.
+    let canonical = format!(r#"{{"crv":"P-256","kty":"EC","x":"{x}","y":"{y}"}}"#);
+    let thumbprint = BASE64URL.encode(Sha256::digest(canonical.as_bytes()));
+    let jwk = serde_json::json!({ "crv": "P-256", "kty": "EC", "x": x, "y": y });
+    Ok((jwk, thumbprint))
+}
+
+/// Expiry of the first certificate in a PEM chain.
+///
+/// Walks just enough DER to reach `tbsCertificate.validity.notAfter`.
+fn certificate_not_after(pem: &str) -> Result<DateTime<Utc>, AcmeError> {
+    let malformed = || AcmeError::Protocol {
+        reason: "malformed certificate".to_owned(),
+    };
+    let body: String = pem
+        .lines()
+        .skip_while(|l| !l.starts_with("-----BEGIN CERTIFICATE"))
+        .skip(1)
+        .take_while(|l| !l.starts_with("-----END"))
+        .collect();
+    let der = BASE64.decode(body.trim()).map_err(|_| malformed())?;
+
+    let mut outer = Der(&der);
+    let mut certificate = Der(outer.read(0x30).ok_or_else(malformed)?);
+    let mut tbs = Der(certificate.read(0x30).ok_or_else(malformed)?);
+    if tbs.0.first() == Some(&0xA0) {
+        tbs.next().ok_or_else(malformed)?; // version
+    }
+    for _ in 0..3 {
+        tbs.next().ok_or_else(malformed)?; // serial, signature, issuer
+    }
+    let mut validity = Der(tbs.read(0x30).ok_or_else(malformed)?);
+    validity.next().ok_or_else(malformed)?; // notBefore
+    let (tag, time) = validity.next().ok_or_else(malformed)?;
+    let time = std::str::from_utf8(time).map_err(|_| malformed())?;
+    let format = match tag {
+        0x17 => "%y%m%d%H%M%SZ",
+        0x18 => "%Y%m%d%H%M%SZ",
+        _ => return Err(malformed()),
+    };
+    NaiveDateTime::parse_from_str(time, format)
+        .map(|t| t.and_utc())
+        .map_err(|_| malformed())
+}
+
+/// Minimal DER reader over a sequence of TLVs.
+pub(crate) struct Der<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Der<'a> {
+    /// The next element's tag and contents.
+    pub(crate) fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.0.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+        let (len, rest) = if first < 0x80 {
+            (usize::from(first), rest)
+        } else {
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > 4 || rest.len() < count {
+                return None;
+            }
+            let (bytes, rest) = rest.split_at(count);
+            let len = bytes
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | usize::from(b));
+            (len, rest)
+        };
+        if rest.len() < len {
+            return None;
+        }
+        let (contents, rest) = rest.split_at(len);
+        self.0 = rest;
+        Some((tag, contents))
+    }
+
+    /// The next element's contents, if it has `tag`.
+    pub(crate) fn read(&mut self, tag: u8) -> Option<&'a [u8]> {
+        self.next()
+            .and_then(|(t, contents)| (t == tag).then_some(contents))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::TimeZone;
+    use p256::ecdsa::VerifyingKey;
+    use p256::ecdsa::signature::Verifier as _;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    fn config(challenge: ChallengeType, domains: &[&str]) -> AcmeConfig {
+        AcmeConfig {
+            directory_url: LETS_ENCRYPT_STAGING_DIRECTORY.to_owned(),
+            contact_email: None,
+            domains: domains.iter().map(|d| (*d).to_owned()).collect(),
+            challenge,
+            renew_before: chrono::Duration::days(30),
+        }
+    }
+
+    #[test]
+    fn reads_not_after_from_utc_and_generalized_time() {
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let mut params =
+            rcgen::CertificateParams::new(vec!["vault.example.com".to_owned()]).unwrap();
+        params.not_after = rcgen::date_time_ymd(2031, 5, 17);
+        let pem = params.self_signed(&key_pair).unwrap().pem();
+        assert_eq!(
+            certificate_not_after(&pem).unwrap(),
+            Utc.with_ymd_and_hms(2031, 5, 17, 0, 0, 0).unwrap()
+        );
+
+        // rcgen's default validity runs to 4096, past UTCTime's range.
+        let fallback = self_signed(&["vault.example.com".to_owned()]).unwrap();
+        assert_eq!(fallback.not_after.format("%Y").to_string(), "4096");
+        assert!(certificate_not_after("not a certificate").is_err());
+    }
+
+    #[test]
+    fn renewal_window_and_domain_changes() {
+        let now = Utc::now();
+        let mut cert =
+            self_signed(&["b.example.com".to_owned(), "a.example.com".to_owned()]).unwrap();
+        let domains = ["a.example.com".to_owned(), "b.example.com".to_owned()];
+        let window = chrono::Duration::days(30);
+
+        cert.not_after = now + chrono::Duration::days(60);
+        assert!(!cert.needs_renewal(&domains, window, now));
+        assert!(cert.needs_renewal(&domains[..1], window, now));
+        cert.not_after = now + chrono::Duration::days(10);
+        assert!(cert.needs_renewal(&domains, window, now));
+    }
+
+    #[test]
+    fn signed_requests_verify_under_the_jwk() {
+        let key = SigningKey::random(&mut OsRng);
+        let (jwk, thumbprint) =
+            jwk_and_thumbprint(key.verifying_key().to_encoded_point(false).as_bytes()).unwrap();
+        assert_eq!(BASE64URL.decode(&thumbprint).unwrap().len(), 32);
+
+        let http = reqwest::Client::new();
+        let session = Session {
+            http: &http,
+            directory: Directory {
+                new_nonce: String::new(),
+                new_account: String::new(),
+                new_order: String::new(),
+            },
+            key,
+            jwk: jwk.clone(),
+            thumbprint,
+            kid: None,
+            nonce: None,
+        };
+        let jws: serde_json::Value = serde_json::from_str(
+            &session
+                .sign("https://ca/new-order", "n1", Some(&serde_json::json!({})))
+                .unwrap(),
+        )
+        .unwrap();
+        let protected = jws["protected"].as_str().unwrap();
+        let header: serde_json::Value =
+            serde_json::from_slice(&BASE64URL.decode(protected).unwrap()).unwrap();
+        assert_eq!(header["jwk"], jwk);
+        assert_eq!(header["nonce"], "n1");
+
+        let x = BASE64URL.decode(jwk["x"].as_str().unwrap()).unwrap();
+        let y = BASE64URL.decode(jwk["y"].as_str().unwrap()).unwrap();
+        let public_key = [&[0x04], x.as_slice(), y.as_slice()].concat();
+        let message = format!("{protected}.{}", jws["payload"].as_str().unwrap());
+        let signature = Signature::from_slice(
+            &BASE64URL
+                .decode(jws["signature"].as_str().unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        VerifyingKey::from_sec1_bytes(&public_key)
+            .unwrap()
+            .verify(message.as_bytes(), &signature)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn validates_config_and_keeps_keys_behind_the_barrier() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        let http01 = Arc::new(Http01Responder::default());
+        let new = |config| AcmeClient::new(Arc::clone(&barrier), config, Arc::clone(&http01), None);
+
+        assert!(new(config(ChallengeType::Http01, &[])).is_err());
+        assert!(new(config(ChallengeType::Dns01, &["vault.example.com"])).is_err());
+        assert!(new(config(ChallengeType::Http01, &["*.example.com"])).is_err());
+
+        let client = new(config(ChallengeType::Http01, &["vault.example.com"])).unwrap();
+        assert!(matches!(
+            client.certificate().await,
+            Err(AcmeError::Barrier(_))
+        ));
+
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        assert!(client.certificate().await.unwrap().is_none());
+        let first = client.account_key().await.unwrap();
+        assert_eq!(*client.account_key().await.unwrap(), *first);
+        let raw = barrier.get_raw(ACCOUNT_KEY).await.unwrap().unwrap();
+        let encoded = BASE64.encode(&*first);
+        assert!(!String::from_utf8_lossy(&raw).contains(&encoded));
+    }
+}
@@ -0,0 +1,512 @@
+//! Client activity counting for `ZVault`.
+//!
+//! Records which distinct clients used the vault each calendar month (UTC)
+//! and which mounts they touched, for chargeback and capacity planning. A
+//! client is an identity entity when the token carries an `entity_id`, and
+//! otherwise the token itself (a "non-entity" client).
+//!
+//! Each month is stored through the barrier at `sys/activity/{YYYY-MM}`.
+//! Only the first request of a client against a mount in a month causes a
+//! write; repeat requests are answered from memory.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::barrier::Barrier;
+use crate::error::ActivityError;
+
+/// Storage prefix for monthly activity records.
+const ACTIVITY_PREFIX: &str = "sys/activity/";
+
+/// Namespace recorded for every client until namespaces exist.
+pub const ROOT_NAMESPACE: &str = "root";
+
+/// Kind of client counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientType {
+    /// A token bound to an identity entity.
+    Entity,
+    /// A token with no entity; each token counts as its own client.
+    NonEntityToken,
+}
+
+/// One client's activity within a month.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientRecord {
+    /// Entity ID, or a hash of the token for non-entity clients.
+    pub client_id: String,
+    /// Entity or non-entity token.
+    pub client_type: ClientType,
+    /// Namespace the client was active in.
+    pub namespace: String,
+    /// Mounts the client made requests against (e.g. `secret/`).
+    pub mounts: BTreeSet<String>,
+    /// First request in the month.
+    pub first_seen: DateTime<Utc>,
+}
+
+/// Distinct client totals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityCounts {
+    /// All distinct clients.
+    pub clients: u64,
+    /// Clients that are identity entities.
+    pub entity_clients: u64,
+    /// Clients that are standalone tokens.
+    pub non_entity_clients: u64,
+}
+
+/// Distinct clients that used one mount.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountActivity {
+    /// Mount path.
+    pub mount: String,
+    /// Clients that used the mount.
+    pub counts: ActivityCounts,
+}
+
+/// Distinct clients in one namespace, broken down by mount.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamespaceActivity {
+    /// Namespace path.
+    pub namespace: String,
+    /// Clients active in the namespace.
+    pub counts: ActivityCounts,
+    /// Per-mount breakdown.
+    pub mounts: Vec<MountActivity>,
+}
+
+/// Distinct clients in one month.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthActivity {
+    /// Month as `YYYY-MM`.
+    pub month: String,
+    /// Clients active in the month.
+    pub counts: ActivityCounts,
+}
+
+/// Activity report over a range of months.
+///
+/// A client active in several months is counted once in `total` and in the
+/// namespace breakdown, and once per month in `months`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityReport {
+    /// First month of the range (`YYYY-MM`).
+    pub start_month: String,
+    /// Last month of the range (`YYYY-MM`).
+    pub end_month: String,
+    /// Distinct clients over the whole range.
+    pub total: ActivityCounts,
+    /// Per-namespace breakdown over the whole range.
+    pub by_namespace: Vec<NamespaceActivity>,
+    /// Per-month totals.
+    pub months: Vec<MonthActivity>,
+}
+
+/// A single exported row: one client, one mount, one month.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityExportRecord {
+    /// Month as `YYYY-MM`.
+    pub month: String,
+    /// Entity ID or token hash.
+    pub client_id: String,
+    /// Entity or non-entity token.
+    pub client_type: ClientType,
+    /// Namespace path.
+    pub namespace: String,
+    /// Mount path.
+    pub mount: String,
+}
+
+/// In-memory view of the current month.
+struct CurrentMonth {
+    month: String,
+    clients: BTreeMap<String, ClientRecord>,
+    seen: HashSet<(String, String)>,
+}
+
+/// Records and reports distinct client activity.
+pub struct ActivityLog {
+    barrier: Arc<Barrier>,
+    current: Mutex<Option<CurrentMonth>>,
+}
+
+impl ActivityLog {
+    /// Create an activity log storing records through the barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            barrier,
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Derive the client ID for a request.
+    ///
+    /// Tokens bound to an entity count as that entity; other tokens are
+    /// identified by a hash of their token hash so exports never contain a
+    /// value usable to look the token up.
+    #[must_use]
+    pub fn client_id(entity_id: Option<&str>, token_hash: &str) -> (String, ClientType) {
+        match entity_id {
+            Some(id) if !id.is_empty() => (id.to_owned(), ClientType::Entity),
+            _ => {
+                let digest = Sha256::digest(format!("activity:{token_hash}").as_bytes());
+                (hex::encode(&digest[..16]), ClientType::NonEntityToken)
+            }
+        }
+    }
+
+    /// Mount a request path belongs to, e.g. `secret/data/app` → `secret/`
+    /// and `auth/approle/login` → `auth/approle/`.
+    #[must_use]
+    pub fn mount_for_path(path: &str) -> String {
+        let path = path.trim_start_matches('/').trim_start_matches("v1/");
+        let mut segments = path.split('/').filter(|s| !s.is_empty());
+        match segments.next() {
+            Some("auth") => match segments.next() {
+                Some(method) => format!("auth/{method}/"),
+                None => "auth/".to_owned(),
+            },
+            Some(first) => format!("{first}/"),
+            None => "/".to_owned(),
+        }
+    }
+
+    /// Record a request by `client_id` against `mount` in `namespace`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ActivityError::Barrier` if the month record cannot be read
+    /// or written.
+    pub async fn record(
+        &self,
+        client_id: &str,
+        client_type: ClientType,
+        namespace: &str,
+        mount: &str,
+    ) -> Result<(), ActivityError> {
+        let now = Utc::now();
+        let month = month_key(now.date_naive());
+        let mut guard = self.current.lock().await;
+
+        if guard.as_ref().is_none_or(|c| c.month != month) {
+            let clients = self.load_month(&month).await?;
+            let seen = clients
+                .values()
+                .flat_map(|c| c.mounts.iter().map(|m| (c.client_id.clone(), m.clone())))
+                .collect();
+            *guard = Some(CurrentMonth {
+                month: month.clone(),
+                clients,
+                seen,
+            });
+        }
+        let Some(current) = guard.as_mut() else {
+            return Ok(());
+        };
+
+        let key = (client_id.to_owned(), mount.to_owned());
+        if current.seen.contains(&key) {
+            return Ok(());
+        }
+
+        current
+            .clients
+            .entry(client_id.to_owned())
+            .or_insert_with(|| ClientRecord {
+                client_id: client_id.to_owned(),
+                client_type,
+                namespace: namespace.to_owned(),
+                mounts: BTreeSet::new(),
+                first_seen: now,
+            })
+            .mounts
+            .insert(mount.to_owned());
+
+        self.save_month(&month, &current.clients).await?;
+        current.seen.insert(key);
+        Ok(())
+    }
+
+    /// Build a report over `start..=end` (`YYYY-MM`).
+    ///
+    /// Defaults to the last twelve months ending with the current one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ActivityError::InvalidRange` if a month is malformed or the
+    /// range is reversed, and `ActivityError::Barrier` on storage failure.
+    pub async fn report(
+        &self,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<ActivityReport, ActivityError> {
+        let months = month_range(start, end)?;
+
+        let mut total: BTreeMap<String, ClientRecord> = BTreeMap::new();
+        let mut by_month = Vec::with_capacity(months.len());
+
+        for month in &months {
+            let clients = self.load_month(month).await?;
+            by_month.push(MonthActivity {
+                month: month.clone(),
+                counts: count(clients.values()),
+            });
+            for (id, record) in clients {
+                total
+                    .entry(id)
+                    .and_modify(|r| r.mounts.extend(record.mounts.iter().cloned()))
+                    .or_insert(record);
+            }
+        }
+
+        let mut namespaces: BTreeMap<&str, Vec<&ClientRecord>> = BTreeMap::new();
+        for record in total.values() {
+            namespaces
+                .entry(record.namespace.as_str())
+                .or_default()
+                .push(record);
+        }
+
+        let by_namespace = namespaces
+            .into_iter()
+            .map(|(namespace, records)| {
+                let mut mounts: BTreeMap<&str, Vec<&ClientRecord>> = BTreeMap::new();
+                for record in &records {
+                    for mount in &record.mounts {
+                        mounts.entry(mount.as_str()).or_default().push(record);
+                    }
+                }
+                NamespaceActivity {
+                    namespace: namespace.to_owned(),
+                    counts: count(records.iter().copied()),
+                    mounts: mounts
+                        .into_iter()
+                        .map(|(mount, records)| MountActivity {
+                            mount: mount.to_owned(),
+                            counts: count(records.into_iter()),
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(ActivityReport {
+            start_month: months.first().cloned().unwrap_or_default(),
+            end_month: months.last().cloned().unwrap_or_default(),
+            total: count(total.values()),
+            by_namespace,
+            months: by_month,
+        })
+    }
+
+    /// Export one row per client, mount, and month over `start..=end`,
+    /// optionally filtered by namespace and mount.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ActivityError::InvalidRange` if a month is malformed or the
+    /// range is reversed, and `ActivityError::Barrier` on storage failure.
+    pub async fn export(
+        &self,
+        start: Option<&str>,
+        end: Option<&str>,
+        namespace: Option<&str>,
+        mount: Option<&str>,
+    ) -> Result<Vec<ActivityExportRecord>, ActivityError> {
+        let mut rows = Vec::new();
+        for month in month_range(start, end)? {
+            let clients = self.load_month(&month).await?;
+            for record in clients.values() {
+                if namespace.is_some_and(|ns| ns != record.namespace) {
+                    continue;
+                }
+                for m in &record.mounts {
+                    if mount.is_some_and(|want| want != m) {
+                        continue;
+                    }
+                    rows.push(ActivityExportRecord {
+                        month: month.clone(),
+                        client_id: record.client_id.clone(),
+                        client_type: record.client_type,
+                        namespace: record.namespace.clone(),
+                        mount: m.clone(),
+                    });
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    async fn load_month(
+        &self,
+        month: &str,
+    ) -> Result<BTreeMap<String, ClientRecord>, ActivityError> {
+        match self
+            .barrier
+            .get(&format!("{ACTIVITY_PREFIX}{month}"))
+            .await?
+        {
+            Some(bytes) => serde_json::from_slice(&bytes).map_err(|e| ActivityError::Internal {
+                reason: format!("corrupt activity record for {month}: {e}"),
+            }),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    async fn save_month(
+        &self,
+        month: &str,
+        clients: &BTreeMap<String, ClientRecord>,
+    ) -> Result<(), ActivityError> {
+        let bytes = serde_json::to_vec(clients).map_err(|e| ActivityError::Internal {
+            reason: format!("activity serialization failed: {e}"),
+        })?;
+        self.barrier
+            .put(&format!("{ACTIVITY_PREFIX}{month}"), &bytes)
+            .await?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ActivityLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActivityLog").finish_non_exhaustive()
+    }
+}
+
+fn count<'a>(records: impl Iterator<Item = &'a ClientRecord>) -> ActivityCounts {
+    let mut counts = ActivityCounts::default();
+    for record in records {
+        counts.clients += 1;
+        match record.client_type {
+            ClientType::Entity => counts.entity_clients += 1,
+            ClientType::NonEntityToken => counts.non_entity_clients += 1,
+        }
+    }
+    counts
+}
+
+fn month_key(date: NaiveDate) -> String {
+    format!("{:04}-{:02}", date.year(), date.month())
+}
+
+fn parse_month(month: &str) -> Result<NaiveDate, ActivityError> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").map_err(|_| {
+        ActivityError::InvalidRange {
+            reason: format!("'{month}' is not a YYYY-MM month"),
+        }
+    })
+}
+
+/// Months from `start` to `end` inclusive, as `YYYY-MM`.
+fn month_range(start: Option<&str>, end: Option<&str>) -> Result<Vec<String>, ActivityError> {
+    let today = Utc::now().date_naive();
+    let end = match end {
+        Some(m) => parse_month(m)?,
+        None => today.with_day(1).unwrap_or(today),
+    };
+    let start = match start {
+        Some(m) => parse_month(m)?,
+        None => end.checked_sub_months(Months::new(11)).unwrap_or(end),
+    };
+    if start > end {
+        return Err(ActivityError::InvalidRange {
+            reason: "start month is after end month".to_owned(),
+        });
+    }
+
+    let mut months = Vec::new();
+    let mut cursor = start;
+    while cursor <= end {
+        months.push(month_key(cursor));
+        let Some(next) = cursor.checked_add_months(Months::new(1)) else {
+            break;
+        };
+        cursor = next;
+    }
+    Ok(months)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn log() -> ActivityLog {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        ActivityLog::new(barrier)
+    }
+
+    #[test]
+    fn derives_mounts_from_paths() {
+        assert_eq!(
+            ActivityLog::mount_for_path("/v1/secret/data/app"),
+            "secret/"
+        );
+        assert_eq!(
+            ActivityLog::mount_for_path("/v1/auth/approle/login"),
+            "auth/approle/"
+        );
+        assert_eq!(ActivityLog::mount_for_path("sys/leases"), "sys/");
+    }
+
+    #[tokio::test]
+    async fn counts_distinct_clients() {
+        let log = log().await;
+        let (token, token_type) = ActivityLog::client_id(None, "hash-a");
+        let (entity, entity_type) = ActivityLog::client_id(Some("entity-1"), "hash-b");
+
+        log.record(&token, token_type, ROOT_NAMESPACE, "secret/")
+            .await
+            .unwrap();
+        log.record(&token, token_type, ROOT_NAMESPACE, "secret/")
+            .await
+            .unwrap();
+        log.record(&entity, entity_type, ROOT_NAMESPACE, "secret/")
+            .await
+            .unwrap();
+        log.record(&entity, entity_type, ROOT_NAMESPACE, "transit/")
+            .await
+            .unwrap();
+
+        let report = log.report(None, None).await.unwrap();
+        assert_eq!(report.months.len(), 12);
+        assert_eq!(report.total.clients, 2);
+        assert_eq!(report.total.entity_clients, 1);
+        assert_eq!(report.total.non_entity_clients, 1);
+
+        let ns = &report.by_namespace[0];
+        assert_eq!(ns.namespace, ROOT_NAMESPACE);
+        let secret = ns.mounts.iter().find(|m| m.mount == "secret/").unwrap();
+        assert_eq!(secret.counts.clients, 2);
+
+        let rows = log
+            .export(None, None, None, Some("transit/"))
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].client_id, "entity-1");
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_ranges() {
+        let log = log().await;
+        assert!(log.report(Some("2026-13"), None).await.is_err());
+        assert!(log.report(Some("2026-05"), Some("2026-01")).await.is_err());
+        let report = log.report(Some("2026-01"), Some("2026-03")).await.unwrap();
+        assert_eq!(report.months.len(), 3);
+        assert_eq!(report.total.clients, 0);
+    }
+}
@@ -4,8 +4,12 @@
 //! An operator creates a role with policies, retrieves the role ID, generates
 //! secret IDs, and distributes them to applications. Applications exchange
 //! a `(role_id, secret_id)` pair for a vault token.
+//!
+//! A role with bound CIDRs only accepts logins from clients in those
+//! networks, and binds the tokens it issues to them as well.
 
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
@@ -14,7 +18,8 @@ use tokio::sync::RwLock;
 
 use crate::barrier::Barrier;
 use crate::error::AppRoleError;
-use crate::token::{TokenEntry, TokenStore};
+use crate::identity::ALIAS_METADATA;
+use crate::token::{TokenEntry, TokenStore, cidrs_allow, validate_cidrs};
 
 /// An `AppRole` role definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +40,10 @@ pub struct AppRole {
     pub secret_id_num_uses: u32,
     /// Secret ID TTL in seconds (0 = no expiry).
     pub secret_id_ttl_secs: i64,
+    /// Networks logins and the tokens they issue are limited to (empty =
+    /// anywhere).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bound_cidrs: Vec<String>,
 }
 
 /// A generated secret ID entry stored in the barrier.
@@ -81,11 +90,15 @@ impl AppRoleStore {
         hex::encode(Sha256::digest(secret_id.as_bytes()))
     }
 
-    /// Create a new `AppRole` role.
+    /// Create or update an `AppRole` role.
+    ///
+    /// Updating an existing role keeps its `role_id`, so clients that already
+    /// hold it keep working.
     ///
     /// # Errors
     ///
-    /// Returns `AppRoleError::InvalidConfig` if required fields are missing.
+    /// Returns `AppRoleError::InvalidConfig` if required fields are missing
+    /// or a bound CIDR doesn't parse.
     pub async fn create_role(&self, mut role: AppRole) -> Result<AppRole, AppRoleError> {
         if role.name.is_empty() {
             return Err(AppRoleError::InvalidConfig {
@@ -97,9 +110,16 @@ impl AppRoleStore {
                 reason: "at least one policy is required".to_owned(),
             });
         }
-        // Generate role_id if not set.
+        validate_cidrs(&role.bound_cidrs).map_err(|e| AppRoleError::InvalidConfig {
+            reason: e.to_string(),
+        })?;
+        // Keep the existing role_id on update, else generate one.
         if role.role_id.is_empty() {
-            role.role_id = uuid::Uuid::new_v4().to_string();
+            role.role_id = match self.get_role(&role.name).await {
+                Ok(existing) => existing.role_id,
+                Err(AppRoleError::RoleNotFound { .. }) => uuid::Uuid::new_v4().to_string(),
+                Err(e) => return Err(e),
+            };
         }
 
         let data = serde_json::to_vec(&role).map_err(|e| AppRoleError::Internal {
@@ -207,16 +227,20 @@ impl AppRoleStore {
         Ok(secret_id)
     }
 
-    /// Login with a `role_id` and `secret_id`, returning the plaintext token and its entry.
+    /// Login with a `role_id` and `secret_id` from a client at `client_ip`,
+    /// returning the plaintext token and its entry.
     ///
     /// # Errors
     ///
     /// Returns `AppRoleError::RoleNotFound` if no role matches the `role_id`.
+    /// Returns `AppRoleError::ClientNotAllowed` if the role's bound CIDRs
+    /// don't include `client_ip`.
     /// Returns `AppRoleError::InvalidSecretId` if the `secret_id` is invalid.
     pub async fn login(
         &self,
         role_id: &str,
         secret_id: &str,
+        client_ip: Option<IpAddr>,
         token_store: &TokenStore,
     ) -> Result<(String, TokenEntry), AppRoleError> {
         use crate::token::CreateTokenParams;
@@ -224,6 +248,13 @@ impl AppRoleStore {
         // Find role by role_id (scan cached roles, then barrier).
         let role = self.find_role_by_id(role_id).await?;
 
+        // Before the secret ID, so a refused client uses none of its uses.
+        if !cidrs_allow(&role.bound_cidrs, client_ip) {
+            return Err(AppRoleError::ClientNotAllowed {
+                role_name: role.name.clone(),
+            });
+        }
+
         if role.bind_secret_id {
             let hash = Self::hash_secret_id(secret_id);
             let key = self.secret_id_key(&role.name, &hash);
@@ -268,8 +299,9 @@ impl AppRoleStore {
                 max_ttl: Some(max_ttl),
                 renewable: true,
                 parent_hash: None,
-                metadata: HashMap::new(),
+                metadata: HashMap::from([(ALIAS_METADATA.to_owned(), role.name.clone())]),
                 display_name: format!("approle-{}", role.name),
+                bound_cidrs: role.bound_cidrs.clone(),
             })
             .await
             .map_err(|e| AppRoleError::Internal {
@@ -1,12 +1,23 @@
 //! Audit logging system for `ZVault`.
 //!
-//! Every API request that touches secrets, auth, or system config generates
-//! an audit entry BEFORE the response is sent. If all audit backends fail
-//! to write, the request is denied (fail-closed). This is non-negotiable.
+//! Every API request that touches secrets, auth, or system config is
+//! recorded twice: a request entry BEFORE the handler runs, and a response
+//! entry with the same request ID before the response is sent. If all audit
+//! backends fail to write the request entry, the request is denied without
+//! being served (fail-closed). This is non-negotiable.
 //!
 //! Sensitive fields (token values, secret data) are HMAC'd with a per-backend
 //! key before writing, so audit logs can be used for correlation without
 //! exposing actual secret values.
+//!
+//! Request bodies are only recorded when [`AuditSettings::log_request_data`]
+//! is on. Even then, bodies carrying secret values or plaintext — writes to
+//! KV and cubbyhole mounts, and transit encryption and decryption — are never
+//! recorded: the manager looks the request's mount up in the mount table
+//! and decides by its engine type, so mounts created at runtime are covered
+//! like the defaults. [`RedactionRule`]s matched against the request path
+//! then HMAC or remove named fields, or drop the body entirely, before any
+//! device sees the entry. The default rules cover credentials.
 
 use std::sync::Arc;
 
@@ -17,21 +28,41 @@ use sha2::Sha256;
 use tokio::sync::RwLock;
 use tracing::warn;
 
+use crate::audit_file::FileAuditBackend;
+use crate::audit_syslog::{SyslogAuditBackend, SyslogConfig};
+use crate::audit_webhook::{WebhookAuditBackend, WebhookConfig};
 use crate::error::AuditError;
+use crate::mount::MountManager;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Prefix of request field values replaced by their HMAC.
+pub const HMAC_PREFIX: &str = "hmac-sha256:";
+
+/// Engine types and the path patterns below their mounts whose request
+/// bodies carry secret values or plaintext, and are never recorded.
+const SECRET_BODY_PATHS: &[(&str, &str)] = &[
+    ("kv", "**"),
+    ("cubbyhole", "**"),
+    ("transit", "encrypt/*"),
+    ("transit", "decrypt/*"),
+    ("transit", "encrypt-batch/*"),
+    ("transit", "decrypt-batch/*"),
+];
+
 /// A single audit log entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEntry {
-    /// Unique entry ID.
+    /// Entry ID, shared by a request's entry and its response's.
     pub id: String,
     /// When the event occurred.
     pub timestamp: DateTime<Utc>,
     /// Request details.
     pub request: AuditRequest,
-    /// Response details.
-    pub response: AuditResponse,
+    /// Response details; `None` on the entry written before the request is
+    /// served.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<AuditResponse>,
     /// Authentication context.
     pub auth: AuditAuth,
 }
@@ -47,6 +78,9 @@ pub struct AuditRequest {
     pub data: Option<serde_json::Value>,
     /// Client IP address.
     pub remote_addr: String,
+    /// Request ID, also returned to the client in `X-Request-Id`.
+    #[serde(default)]
+    pub request_id: String,
 }
 
 /// Response portion of an audit entry.
@@ -63,12 +97,183 @@ pub struct AuditResponse {
 pub struct AuditAuth {
     /// HMAC'd token identifier.
     pub token_id: String,
+    /// Token accessor, which stays the same across restarts (empty for
+    /// logins and batch tokens).
+    #[serde(default)]
+    pub accessor: String,
     /// Policies attached to the token.
     pub policies: Vec<String>,
     /// Token metadata.
     pub metadata: std::collections::HashMap<String, String>,
 }
 
+/// Which audit entries a query returns. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only entries at or after this time.
+    pub start_time: Option<DateTime<Utc>>,
+    /// Only entries before this time.
+    pub end_time: Option<DateTime<Utc>>,
+    /// Only entries whose request path starts with this (without `/v1/`).
+    pub path_prefix: Option<String>,
+    /// Only entries whose caller has this display name.
+    pub actor: Option<String>,
+    /// Only entries with this HMAC'd token identifier.
+    pub token_id: Option<String>,
+    /// Only entries with this token accessor.
+    pub accessor: Option<String>,
+    /// Only entries with this operation (`read`, `write`, `delete`, ...).
+    pub operation: Option<String>,
+    /// Only entries with this outcome.
+    pub status: Option<AuditStatus>,
+}
+
+/// Outcome of an audited request, for filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditStatus {
+    /// Status code below 400.
+    Success,
+    /// Status code 400 or above.
+    Error,
+    /// Exactly this status code.
+    Code(u16),
+}
+
+impl AuditStatus {
+    /// Parse `success`, `error`, or a status code.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "success" => Some(Self::Success),
+            "error" => Some(Self::Error),
+            code => code.parse().ok().map(Self::Code),
+        }
+    }
+
+    fn matches(self, status_code: u16) -> bool {
+        match self {
+            Self::Success => status_code < 400,
+            Self::Error => status_code >= 400,
+            Self::Code(code) => status_code == code,
+        }
+    }
+}
+
+impl AuditFilter {
+    /// Whether no field is set, so every entry matches.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start_time.is_none()
+            && self.end_time.is_none()
+            && self.path_prefix.is_none()
+            && self.actor.is_none()
+            && self.token_id.is_none()
+            && self.accessor.is_none()
+            && self.operation.is_none()
+            && self.status.is_none()
+    }
+
+    /// Whether `entry` passes every set field.
+    #[must_use]
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        let path_prefix = self
+            .path_prefix
+            .as_deref()
+            .map(|p| p.trim_start_matches('/').trim_start_matches("v1/"));
+        self.start_time.is_none_or(|start| entry.timestamp >= start)
+            && self.end_time.is_none_or(|end| entry.timestamp < end)
+            && path_prefix.is_none_or(|prefix| entry.request.path.starts_with(prefix))
+            && self.actor.as_deref().is_none_or(|actor| {
+                entry.auth.metadata.get("display_name").map(String::as_str) == Some(actor)
+            })
+            && self
+                .token_id
+                .as_deref()
+                .is_none_or(|token_id| entry.auth.token_id == token_id)
+            && self
+                .accessor
+                .as_deref()
+                .is_none_or(|accessor| entry.auth.accessor == accessor)
+            && self
+                .operation
+                .as_deref()
+                .is_none_or(|operation| entry.request.operation.eq_ignore_ascii_case(operation))
+            && self.status.is_none_or(|status| {
+                entry
+                    .response
+                    .as_ref()
+                    .is_some_and(|response| status.matches(response.status_code))
+            })
+    }
+}
+
+/// Redaction applied to request data on paths matching `path`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionRule {
+    /// Request path pattern without `/v1/`; `*` matches one segment, `**` any.
+    pub path: String,
+    /// Field names replaced by their HMAC, at any depth.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hmac: Vec<String>,
+    /// Field names removed, at any depth.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<String>,
+    /// Drop the whole request body.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub drop_body: bool,
+}
+
+impl RedactionRule {
+    fn hmac(path: &str, fields: &[&str]) -> Self {
+        Self {
+            path: path.to_owned(),
+            hmac: fields.iter().map(|f| (*f).to_owned()).collect(),
+            remove: Vec::new(),
+            drop_body: false,
+        }
+    }
+}
+
+/// What request data reaches audit devices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditSettings {
+    /// Record request bodies. Off by default; meant for debugging.
+    #[serde(default)]
+    pub log_request_data: bool,
+    /// Rules applied to recorded request bodies. Every matching rule applies.
+    #[serde(default = "default_redaction_rules")]
+    pub rules: Vec<RedactionRule>,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self {
+            log_request_data: false,
+            rules: default_redaction_rules(),
+        }
+    }
+}
+
+/// Built-in rules: HMAC credentials everywhere. Bodies that carry secret
+/// values or plaintext are dropped by engine type whatever the rules say.
+#[must_use]
+pub fn default_redaction_rules() -> Vec<RedactionRule> {
+    vec![RedactionRule::hmac(
+        "**",
+        &[
+            "token",
+            "secret_id",
+            "password",
+            "jwt",
+            "client_secret",
+            "private_key",
+            "transfer_key",
+            "share",
+            "shares",
+        ],
+    )]
+}
+
 /// Trait for audit log backends.
 ///
 /// Implementations must be safe to share across async tasks.
@@ -83,6 +288,65 @@ pub trait AuditBackend: Send + Sync {
     ///
     /// Returns an error if the entry could not be persisted.
     async fn log(&self, entry: &AuditEntry) -> Result<(), AuditError>;
+
+    /// Deliver any buffered entries. Called before a device is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if buffered entries could not be delivered or spilled.
+    async fn flush(&self) -> Result<(), AuditError> {
+        Ok(())
+    }
+}
+
+/// Persisted configuration of an audit device enabled via `/v1/sys/audit`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditDevice {
+    /// Device path (e.g., `siem/`).
+    pub path: String,
+    /// Human-readable description.
+    #[serde(default)]
+    pub description: String,
+    /// Backend type and its options.
+    #[serde(flatten)]
+    pub config: AuditDeviceConfig,
+}
+
+/// Backend-specific audit device options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "options", rename_all = "lowercase")]
+pub enum AuditDeviceConfig {
+    /// Append JSON lines to a local file.
+    File {
+        /// Path of the audit log file.
+        file_path: String,
+    },
+    /// RFC 5424 syslog over UDP, TCP, or TLS.
+    Syslog(SyslogConfig),
+    /// Batched JSON POSTs to an HTTP endpoint.
+    Webhook(WebhookConfig),
+}
+
+impl AuditDeviceConfig {
+    /// Construct the backend described by this configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuditError::InvalidConfig`] if the options are invalid.
+    pub fn build(&self) -> Result<Arc<dyn AuditBackend>, AuditError> {
+        match self {
+            Self::File { file_path } => {
+                if file_path.is_empty() {
+                    return Err(AuditError::InvalidConfig {
+                        reason: "file_path must not be empty".to_owned(),
+                    });
+                }
+                Ok(Arc::new(FileAuditBackend::new(file_path)))
+            }
+            Self::Syslog(cfg) => Ok(Arc::new(SyslogAuditBackend::new(cfg.clone())?)),
+            Self::Webhook(cfg) => Ok(WebhookAuditBackend::new(cfg.clone())?),
+        }
+    }
 }
 
 /// Manages multiple audit backends with fail-closed semantics.
@@ -90,9 +354,14 @@ pub trait AuditBackend: Send + Sync {
 /// If at least one backend succeeds, the request proceeds. If ALL fail,
 /// the request is denied.
 pub struct AuditManager {
-    backends: RwLock<Vec<Arc<dyn AuditBackend>>>,
+    /// Enabled backends, keyed by device path.
+    backends: RwLock<Vec<(String, Arc<dyn AuditBackend>)>>,
     /// HMAC key for hashing sensitive fields in audit entries.
     hmac_key: Vec<u8>,
+    /// Request data logging and redaction rules.
+    settings: RwLock<AuditSettings>,
+    /// Mount table the engine type of a request's mount is looked up in.
+    mounts: Option<Arc<MountManager>>,
 }
 
 impl AuditManager {
@@ -102,16 +371,106 @@ impl AuditManager {
         Self {
             backends: RwLock::new(Vec::new()),
             hmac_key,
+            settings: RwLock::new(AuditSettings::default()),
+            mounts: None,
         }
     }
 
-    /// Register an audit backend.
+    /// Look request paths up in `mounts` to drop the bodies of requests to
+    /// engines that receive secret values or plaintext.
+    ///
+    /// Without a mount table, only the redaction rules apply.
+    #[must_use]
+    pub fn with_mounts(mut self, mounts: Arc<MountManager>) -> Self {
+        self.mounts = Some(mounts);
+        self
+    }
+
+    /// Register an audit backend under its own name.
     pub async fn add_backend(&self, backend: Arc<dyn AuditBackend>) {
-        self.backends.write().await.push(backend);
+        let path = backend.name().to_owned();
+        self.backends.write().await.push((path, backend));
+    }
+
+    /// Enable an audit backend at a device path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuditError::DeviceExists`] if a device is already enabled at `path`.
+    pub async fn enable(
+        &self,
+        path: &str,
+        backend: Arc<dyn AuditBackend>,
+    ) -> Result<(), AuditError> {
+        let mut backends = self.backends.write().await;
+        if backends.iter().any(|(p, _)| p == path) {
+            return Err(AuditError::DeviceExists {
+                path: path.to_owned(),
+            });
+        }
+        backends.push((path.to_owned(), backend));
+        Ok(())
+    }
+
+    /// Disable the audit backend at a device path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuditError::DeviceNotFound`] if nothing is enabled at `path`.
+    pub async fn disable(&self, path: &str) -> Result<(), AuditError> {
+        let mut backends = self.backends.write().await;
+        let index = backends
+            .iter()
+            .position(|(p, _)| p == path)
+            .ok_or_else(|| AuditError::DeviceNotFound {
+                path: path.to_owned(),
+            })?;
+        let (_, backend) = backends.remove(index);
+
+        if let Err(e) = backend.flush().await {
+            warn!(device = %path, error = %e, "audit backend flush on disable failed");
+        }
+        Ok(())
+    }
+
+    /// List enabled devices as `(path, backend name)` pairs.
+    pub async fn devices(&self) -> Vec<(String, String)> {
+        self.backends
+            .read()
+            .await
+            .iter()
+            .map(|(path, backend)| (path.clone(), backend.name().to_owned()))
+            .collect()
+    }
+
+    /// Current request data logging and redaction settings.
+    pub async fn settings(&self) -> AuditSettings {
+        self.settings.read().await.clone()
+    }
+
+    /// Replace the request data logging and redaction settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuditError::InvalidConfig`] if a rule has an empty path.
+    pub async fn set_settings(&self, settings: AuditSettings) -> Result<(), AuditError> {
+        if settings.rules.iter().any(|r| r.path.trim().is_empty()) {
+            return Err(AuditError::InvalidConfig {
+                reason: "redaction rule path must not be empty".to_owned(),
+            });
+        }
+        *self.settings.write().await = settings;
+        Ok(())
+    }
+
+    /// Whether callers should capture request bodies for audit entries.
+    pub async fn logs_request_data(&self) -> bool {
+        self.settings.read().await.log_request_data
     }
 
     /// Log an audit entry to all backends.
     ///
+    /// Request data is redacted according to the current settings first.
     /// At least one backend must succeed. If all fail, returns
     /// [`AuditError::AllBackendsFailed`] and the request must be denied.
     ///
@@ -119,6 +478,24 @@ impl AuditManager {
     ///
     /// Returns [`AuditError::AllBackendsFailed`] if every backend fails.
     pub async fn log(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+        self.write(entry, false).await
+    }
+
+    /// Log an entry reported by an external client, such as the MCP server
+    /// describing a tool call.
+    ///
+    /// The reported data is the event itself, so it is kept even when
+    /// request data logging is off; the redaction rules still apply.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuditError::AllBackendsFailed`] if every backend fails.
+    pub async fn log_external(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+        self.write(entry, true).await
+    }
+
+    /// Redact `entry` and write it to every backend.
+    async fn write(&self, entry: &AuditEntry, keep_data: bool) -> Result<(), AuditError> {
         let backends = self.backends.read().await;
 
         if backends.is_empty() {
@@ -126,12 +503,14 @@ impl AuditManager {
             return Ok(());
         }
 
+        let entry = &self.redact(entry, keep_data).await;
         let mut any_success = false;
-        for backend in backends.iter() {
+        for (path, backend) in backends.iter() {
             match backend.log(entry).await {
                 Ok(()) => any_success = true,
                 Err(e) => {
                     warn!(
+                        device = %path,
                         backend = backend.name(),
                         error = %e,
                         "audit backend failed"
@@ -163,6 +542,75 @@ impl AuditManager {
         hex::encode(mac.finalize().into_bytes())
     }
 
+    /// Apply the redaction settings to a copy of `entry`. Request data is
+    /// dropped unless request data logging is on or `keep_data` is set, and
+    /// always for requests whose engine receives secret values.
+    async fn redact(&self, entry: &AuditEntry, keep_data: bool) -> AuditEntry {
+        let mut entry = entry.clone();
+        let settings = self.settings.read().await;
+        if (!settings.log_request_data && !keep_data)
+            || self.carries_secrets(&entry.request.path).await
+        {
+            entry.request.data = None;
+            return entry;
+        }
+
+        let path = entry.request.path.trim_start_matches('/');
+        let rules: Vec<&RedactionRule> = settings
+            .rules
+            .iter()
+            .filter(|r| glob_match::glob_match(&r.path, path))
+            .collect();
+        if rules.iter().any(|r| r.drop_body) {
+            entry.request.data = None;
+        } else if let Some(data) = entry.request.data.as_mut() {
+            for rule in rules {
+                self.redact_value(data, rule);
+            }
+        }
+        entry
+    }
+
+    /// Whether the body of a request to `path` carries secret values or
+    /// plaintext, by the engine type of the mount it goes to.
+    async fn carries_secrets(&self, path: &str) -> bool {
+        let Some(mounts) = &self.mounts else {
+            return false;
+        };
+        let Some((mount, rest)) = mounts.resolve(path.trim_start_matches('/')).await else {
+            return false;
+        };
+        SECRET_BODY_PATHS.iter().any(|(engine_type, pattern)| {
+            mount.engine_type == *engine_type && glob_match::glob_match(pattern, &rest)
+        })
+    }
+
+    /// HMAC or remove the fields `rule` names, at any depth of `value`.
+    fn redact_value(&self, value: &mut serde_json::Value, rule: &RedactionRule) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.retain(|k, _| !rule.remove.contains(k));
+                for (key, field) in map.iter_mut() {
+                    if rule.hmac.contains(key) {
+                        let raw = match &*field {
+                            serde_json::Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        *field = format!("{HMAC_PREFIX}{}", self.hmac_field(&raw)).into();
+                    } else {
+                        self.redact_value(field, rule);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.redact_value(item, rule);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// Check whether any audit backends are configured.
     pub async fn has_backends(&self) -> bool {
         !self.backends.read().await.is_empty()
@@ -176,3 +624,322 @@ impl std::fmt::Debug for AuditManager {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tokio::sync::Mutex;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::barrier::Barrier;
+    use crate::crypto::EncryptionKey;
+    use crate::mount::MountEntry;
+
+    #[derive(Default)]
+    struct CaptureBackend {
+        entries: Mutex<Vec<AuditEntry>>,
+    }
+
+    #[async_trait::async_trait]
+    impl AuditBackend for CaptureBackend {
+        fn name(&self) -> &'static str {
+            "capture"
+        }
+
+        async fn log(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+            self.entries.lock().await.push(entry.clone());
+            Ok(())
+        }
+    }
+
+    fn entry(path: &str, data: serde_json::Value) -> AuditEntry {
+        AuditEntry {
+            id: "id".to_owned(),
+            timestamp: Utc::now(),
+            request: AuditRequest {
+                operation: "write".to_owned(),
+                path: path.to_owned(),
+                data: Some(data),
+                remote_addr: "unknown".to_owned(),
+                request_id: "req".to_owned(),
+            },
+            response: Some(AuditResponse {
+                status_code: 200,
+                error: None,
+            }),
+            auth: AuditAuth {
+                token_id: String::new(),
+                accessor: String::new(),
+                policies: Vec::new(),
+                metadata: HashMap::new(),
+            },
+        }
+    }
+
+    async fn mounts(engines: &[(&str, &str)]) -> Arc<MountManager> {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let mounts = Arc::new(MountManager::empty(barrier));
+        for (path, engine_type) in engines {
+            mounts
+                .mount(MountEntry {
+                    path: (*path).to_owned(),
+                    engine_type: (*engine_type).to_owned(),
+                    description: String::new(),
+                    config: serde_json::Value::Null,
+                })
+                .await
+                .unwrap();
+        }
+        mounts
+    }
+
+    async fn logged(manager: &AuditManager, entry: &AuditEntry) -> Option<serde_json::Value> {
+        let capture = Arc::new(CaptureBackend::default());
+        manager.enable("capture/", capture.clone()).await.unwrap();
+        manager.log(entry).await.unwrap();
+        manager.disable("capture/").await.unwrap();
+        let entries = capture.entries.lock().await;
+        entries[0].request.data.clone()
+    }
+
+    #[tokio::test]
+    async fn request_data_dropped_unless_enabled() {
+        let manager = AuditManager::new(b"key".to_vec())
+            .with_mounts(mounts(&[("transit/", "transit")]).await);
+        let login = entry(
+            "auth/approle/login",
+            serde_json::json!({"role_id": "r", "secret_id": "s3cret"}),
+        );
+        assert!(logged(&manager, &login).await.is_none());
+
+        manager
+            .set_settings(AuditSettings {
+                log_request_data: true,
+                ..AuditSettings::default()
+            })
+            .await
+            .unwrap();
+        let data = logged(&manager, &login).await.unwrap();
+        assert_eq!(data["role_id"], "r");
+        assert_eq!(
+            data["secret_id"],
+            format!("{HMAC_PREFIX}{}", manager.hmac_field("s3cret"))
+        );
+
+        let encrypt = entry(
+            "transit/encrypt/app",
+            serde_json::json!({"plaintext": "aGk="}),
+        );
+        assert!(logged(&manager, &encrypt).await.is_none());
+        let keys = entry(
+            "transit/keys/app",
+            serde_json::json!({"type": "aes256-gcm96"}),
+        );
+        assert_eq!(
+            logged(&manager, &keys).await.unwrap()["type"],
+            "aes256-gcm96"
+        );
+    }
+
+    #[tokio::test]
+    async fn secret_bodies_dropped_by_engine_type() {
+        let manager = AuditManager::new(b"key".to_vec()).with_mounts(
+            mounts(&[
+                ("kv2-team-a/", "kv"),
+                ("crypto/", "transit"),
+                ("db/", "database"),
+            ])
+            .await,
+        );
+        manager
+            .set_settings(AuditSettings {
+                log_request_data: true,
+                rules: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        for path in ["kv2-team-a/data/app", "/crypto/decrypt/app"] {
+            let write = entry(path, serde_json::json!({"data": {"pw": "hunter2"}}));
+            assert!(logged(&manager, &write).await.is_none(), "{path}");
+        }
+        let config = entry("db/roles/app", serde_json::json!({"ttl": "1h"}));
+        assert_eq!(logged(&manager, &config).await.unwrap()["ttl"], "1h");
+    }
+
+    #[tokio::test]
+    async fn external_entries_keep_redacted_data() {
+        let manager = AuditManager::new(b"key".to_vec());
+        let capture = Arc::new(CaptureBackend::default());
+        manager.enable("capture/", capture.clone()).await.unwrap();
+        let call = entry(
+            "external/mcp/zvault_query_database",
+            serde_json::json!({"query": "SELECT 1", "password": "pw"}),
+        );
+        manager.log_external(&call).await.unwrap();
+
+        let entries = capture.entries.lock().await;
+        let data = entries[0].request.data.as_ref().unwrap();
+        assert_eq!(data["query"], "SELECT 1");
+        assert!(data["password"].as_str().unwrap().starts_with(HMAC_PREFIX));
+    }
+
+    #[tokio::test]
+    async fn custom_rules_hmac_and_remove_nested_fields() {
+        let manager = AuditManager::new(b"key".to_vec());
+        manager
+            .set_settings(AuditSettings {
+                log_request_data: true,
+                rules: vec![RedactionRule {
+                    path: "database/config/*".to_owned(),
+                    hmac: vec!["username".to_owned()],
+                    remove: vec!["password".to_owned()],
+                    drop_body: false,
+                }],
+            })
+            .await
+            .unwrap();
+
+        let data = logged(
+            &manager,
+            &entry(
+                "database/config/pg",
+                serde_json::json!({"connection": {"username": "admin", "password": "pw"}}),
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(data["connection"].get("password").is_none());
+        assert!(
+            data["connection"]["username"]
+                .as_str()
+                .unwrap()
+                .starts_with(HMAC_PREFIX)
+        );
+
+        let untouched = logged(
+            &manager,
+            &entry("database/roles/app", serde_json::json!({"password": "pw"})),
+        )
+        .await
+        .unwrap();
+        assert_eq!(untouched["password"], "pw");
+
+        let err = manager
+            .set_settings(AuditSettings {
+                log_request_data: true,
+                rules: vec![RedactionRule {
+                    path: " ".to_owned(),
+                    hmac: Vec::new(),
+                    remove: Vec::new(),
+                    drop_body: true,
+                }],
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuditError::InvalidConfig { .. }));
+    }
+
+    #[test]
+    fn filter_matches_each_field() {
+        let mut read = entry("secret/data/db/password", serde_json::Value::Null);
+        read.request.operation = "read".to_owned();
+        read.auth.token_id = "abc".to_owned();
+        read.auth.accessor = "acc".to_owned();
+        read.auth
+            .metadata
+            .insert("display_name".to_owned(), "alice".to_owned());
+        let mut denied = read.clone();
+        denied.response = Some(AuditResponse {
+            status_code: 403,
+            error: None,
+        });
+        let day = chrono::Duration::days(1);
+
+        assert!(AuditFilter::default().is_empty());
+        assert!(AuditFilter::default().matches(&read));
+        let who_read = AuditFilter {
+            start_time: Some(read.timestamp - day),
+            end_time: Some(read.timestamp + day),
+            path_prefix: Some("/v1/secret/data/db".to_owned()),
+            actor: Some("alice".to_owned()),
+            token_id: Some("abc".to_owned()),
+            accessor: Some("acc".to_owned()),
+            operation: Some("READ".to_owned()),
+            status: AuditStatus::parse("success"),
+        };
+        assert!(!who_read.is_empty());
+        assert!(who_read.matches(&read));
+        assert!(!who_read.matches(&denied));
+
+        let misses = [
+            AuditFilter {
+                start_time: Some(read.timestamp + day),
+                ..AuditFilter::default()
+            },
+            AuditFilter {
+                end_time: Some(read.timestamp),
+                ..AuditFilter::default()
+            },
+            AuditFilter {
+                path_prefix: Some("secret/data/app".to_owned()),
+                ..AuditFilter::default()
+            },
+            AuditFilter {
+                actor: Some("bob".to_owned()),
+                ..AuditFilter::default()
+            },
+            AuditFilter {
+                token_id: Some("xyz".to_owned()),
+                ..AuditFilter::default()
+            },
+            AuditFilter {
+                accessor: Some("other".to_owned()),
+                ..AuditFilter::default()
+            },
+            AuditFilter {
+                operation: Some("write".to_owned()),
+                ..AuditFilter::default()
+            },
+            AuditFilter {
+                status: AuditStatus::parse("error"),
+                ..AuditFilter::default()
+            },
+        ];
+        for filter in &misses {
+            assert!(!filter.matches(&read), "{filter:?}");
+        }
+        assert!(
+            AuditFilter {
+                status: AuditStatus::parse("403"),
+                ..AuditFilter::default()
+            }
+            .matches(&denied)
+        );
+        assert_eq!(AuditStatus::parse("teapot"), None);
+    }
+
+    #[test]
+    fn request_entries_have_no_response() {
+        let mut pending = entry("secret/data/app", serde_json::Value::Null);
+        pending.response = None;
+
+        let json = serde_json::to_value(&pending).unwrap();
+        assert!(json.get("response").is_none());
+        let parsed: AuditEntry = serde_json::from_value(json).unwrap();
+        assert!(parsed.response.is_none());
+
+        assert!(AuditFilter::default().matches(&pending));
+        for status in ["success", "error"] {
+            let filter = AuditFilter {
+                status: AuditStatus::parse(status),
+                ..AuditFilter::default()
+            };
+            assert!(!filter.matches(&pending), "{status}");
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! Syslog audit backend for `ZVault`.
+//!
+//! Emits each [`AuditEntry`] as an RFC 5424 syslog message whose MSG part is
+//! the JSON-encoded entry. Supports UDP (one datagram per entry), TCP, and
+//! TLS. Stream transports use RFC 6587 octet-counting framing so messages
+//! containing newlines are delimited unambiguously.
+//!
+//! The connection is opened lazily and re-established once on write failure
+//! before the error is reported to the audit manager.
+
+use std::sync::Arc;
+
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::Mutex;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+use crate::audit::{AuditBackend, AuditEntry};
+use crate::error::AuditError;
+
+/// Syslog `log audit` facility (RFC 5424 §6.2.1).
+const DEFAULT_FACILITY: u8 = 13;
+
+/// Severity for successful requests (`informational`).
+const SEVERITY_INFO: u8 = 6;
+
+/// Severity for failed requests (`warning`).
+const SEVERITY_WARNING: u8 = 4;
+
+/// Transport used to reach the syslog collector.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogTransport {
+    /// One datagram per message.
+    #[default]
+    Udp,
+    /// Plain TCP with octet-counting framing.
+    Tcp,
+    /// TCP wrapped in TLS (RFC 5425).
+    Tls,
+}
+
+/// Options for a syslog audit device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyslogConfig {
+    /// Collector address as `host:port`.
+    pub address: String,
+    /// Transport protocol.
+    #[serde(default)]
+    pub transport: SyslogTransport,
+    /// Syslog facility code (0–23).
+    #[serde(default = "default_facility")]
+    pub facility: u8,
+    /// APP-NAME field.
+    #[serde(default = "default_app_name")]
+    pub app_name: String,
+    /// HOSTNAME field. Sent as the nil value `-` when unset.
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// PEM file with additional CA certificates trusted for TLS.
+    #[serde(default)]
+    pub ca_cert_file: Option<String>,
+}
+
+fn default_facility() -> u8 {
+    DEFAULT_FACILITY
+}
+
+fn default_app_name() -> String {
+    "zvault".to_owned()
+}
+
+/// Open connection to the collector.
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+/// Audit backend that ships entries to a syslog collector.
+pub struct SyslogAuditBackend {
+    config: SyslogConfig,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl SyslogAuditBackend {
+    /// Create a new syslog audit backend.
+    ///
+    /// The connection is established lazily on the first write.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuditError::InvalidConfig`] if the address or facility is invalid.
+    pub fn new(config: SyslogConfig) -> Result<Self, AuditError> {
+        if config.address.rsplit_once(':').is_none() {
+            return Err(AuditError::InvalidConfig {
+                reason: format!("syslog address '{}' must be host:port", config.address),
+            });
+        }
+        if config.facility > 23 {
+            return Err(AuditError::InvalidConfig {
+                reason: format!("syslog facility {} out of range 0-23", config.facility),
+            });
+        }
+
+        Ok(Self {
+            config,
+            connection: Mutex::new(None),
+        })
+    }
+
+    /// Render an entry as an RFC 5424 message (without transport framing).
+    fn format_message(&self, entry: &AuditEntry) -> Result<String, AuditError> {
+        let json = serde_json::to_string(entry).map_err(|e| AuditError::Serialization {
+            reason: e.to_string(),
+        })?;
+
+        let failed = entry
+            .response
+            .as_ref()
+            .is_some_and(|response| response.status_code >= 400);
+        let severity = if failed {
+            SEVERITY_WARNING
+        } else {
+            SEVERITY_INFO
+        };
+        let pri = u16::from(self.config.facility) * 8 + u16::from(severity);
+        let timestamp = entry.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true);
+        let hostname = self.config.hostname.as_deref().unwrap_or("-");
+
+        Ok(format!(
+            "<{pri}>1 {timestamp} {hostname} {} {} audit - {json}",
+            self.config.app_name,
+            std::process::id(),
+        ))
+    }
+
+    /// Open a new connection using the configured transport.
+    async fn connect(&self) -> Result<Connection, AuditError> {
+        let address = &self.config.address;
+        match self.config.transport {
+            SyslogTransport::Udp => {
+                let socket = UdpSocket::bind("0.0.0.0:0")
+                    .await
+                    .map_err(|e| self.failure(format!("udp bind failed: {e}")))?;
+                socket
+                    .connect(address)
+                    .await
+                    .map_err(|e| self.failure(format!("udp connect to {address} failed: {e}")))?;
+                Ok(Connection::Udp(socket))
+            }
+            SyslogTransport::Tcp => {
+                let stream = TcpStream::connect(address)
+                    .await
+                    .map_err(|e| self.failure(format!("tcp connect to {address} failed: {e}")))?;
+                Ok(Connection::Tcp(stream))
+            }
+            SyslogTransport::Tls => {
+                let connector = TlsConnector::from(Arc::new(self.tls_config()?));
+                let host = address
+                    .rsplit_once(':')
+                    .map_or(address.as_str(), |(host, _)| host)
+                    .trim_start_matches('[')
+                    .trim_end_matches(']');
+                let server_name = ServerName::try_from(host.to_owned())
+                    .map_err(|e| self.failure(format!("invalid tls server name '{host}': {e}")))?;
+
+                let stream = TcpStream::connect(address)
+                    .await
+                    .map_err(|e| self.failure(format!("tcp connect to {address} failed: {e}")))?;
+                let tls = connector.connect(server_name, stream).await.map_err(|e| {
+                    self.failure(format!("tls handshake with {address} failed: {e}"))
+                })?;
+                Ok(Connection::Tls(Box::new(tls)))
+            }
+        }
+    }
+
+    /// Build the rustls client config: webpki roots plus any configured CA file.
+    fn tls_config(&self) -> Result<ClientConfig, AuditError> {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+        if let Some(ref ca_file) = self.config.ca_cert_file {
+            let certs = CertificateDer::pem_file_iter(ca_file)
+                .map_err(|e| self.failure(format!("failed to read CA file '{ca_file}': {e}")))?;
+            for cert in certs {
+                let cert =
+                    cert.map_err(|e| self.failure(format!("invalid CA cert in '{ca_file}': {e}")))?;
+                roots
+                    .add(cert)
+                    .map_err(|e| self.failure(format!("rejected CA cert in '{ca_file}': {e}")))?;
+            }
+        }
+
+        let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| self.failure(format!("tls config failed: {e}")))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(config)
+    }
+
+    /// Write one message on an open connection.
+    async fn send(conn: &mut Connection, message: &str) -> std::io::Result<()> {
+        match conn {
+            Connection::Udp(socket) => socket.send(message.as_bytes()).await.map(|_| ()),
+            Connection::Tcp(stream) => {
+                let framed = format!("{} {message}", message.len());
+                stream.write_all(framed.as_bytes()).await?;
+                stream.flush().await
+            }
+            Connection::Tls(stream) => {
+                let framed = format!("{} {message}", message.len());
+                stream.write_all(framed.as_bytes()).await?;
+                stream.flush().await
+            }
+        }
+    }
+
+    fn failure(&self, reason: String) -> AuditError {
+        AuditError::BackendFailure {
+            name: self.name().to_owned(),
+            reason,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditBackend for SyslogAuditBackend {
+    #[allow(clippy::needless_lifetimes, clippy::unnecessary_literal_bound)]
+    fn name(&self) -> &str {
+        "syslog"
+    }
+
+    async fn log(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+        let message = self.format_message(entry)?;
+        let mut guard = self.connection.lock().await;
+
+        // One reconnect attempt: the collector may have dropped an idle stream.
+        for attempt in 0..2 {
+            if guard.is_none() {
+                *guard = Some(self.connect().await?);
+            }
+            let Some(conn) = guard.as_mut() else {
+                continue;
+            };
+            match Self::send(conn, &message).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt == 0 => {
+                    tracing::debug!(error = %e, "syslog write failed, reconnecting");
+                    *guard = None;
+                }
+                Err(e) => {
+                    *guard = None;
+                    return Err(self.failure(format!("write failed: {e}")));
+                }
+            }
+        }
+
+        Err(self.failure("write failed after reconnect".to_owned()))
+    }
+}
+
+impl std::fmt::Debug for SyslogAuditBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyslogAuditBackend")
+            .field("address", &self.config.address)
+            .field("transport", &self.config.transport)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::audit::{AuditAuth, AuditRequest, AuditResponse};
+
+    fn entry(status_code: u16) -> AuditEntry {
+        AuditEntry {
+            id: "entry-1".to_owned(),
+            timestamp: Utc::now(),
+            request: AuditRequest {
+                operation: "read".to_owned(),
+                path: "secret/data/app".to_owned(),
+                data: None,
+                remote_addr: "127.0.0.1".to_owned(),
+                request_id: "req".to_owned(),
+            },
+            response: Some(AuditResponse {
+                status_code,
+                error: None,
+            }),
+            auth: AuditAuth {
+                token_id: "hmac".to_owned(),
+                accessor: "acc".to_owned(),
+                policies: vec!["default".to_owned()],
+                metadata: HashMap::new(),
+            },
+        }
+    }
+
+    fn config(address: String) -> SyslogConfig {
+        SyslogConfig {
+            address,
+            transport: SyslogTransport::Udp,
+            facility: DEFAULT_FACILITY,
+            app_name: default_app_name(),
+            hostname: Some("vault-1".to_owned()),
+            ca_cert_file: None,
+        }
+    }
+
+    #[test]
+    fn message_has_rfc5424_header() {
+        let backend = SyslogAuditBackend::new(config("127.0.0.1:514".to_owned())).unwrap();
+
+        let ok = backend.format_message(&entry(200)).unwrap();
+        assert!(ok.starts_with("<110>1 "));
+        assert!(ok.contains(" vault-1 zvault "));
+        assert!(ok.contains(" audit - {"));
+
+        let denied = backend.format_message(&entry(403)).unwrap();
+        assert!(denied.starts_with("<108>1 "));
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(SyslogAuditBackend::new(config("localhost".to_owned())).is_err());
+
+        let mut cfg = config("127.0.0.1:514".to_owned());
+        cfg.facility = 24;
+        assert!(SyslogAuditBackend::new(cfg).is_err());
+    }
+
+    #[tokio::test]
+    async fn udp_delivers_datagram() {
+        let collector = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = collector.local_addr().unwrap().to_string();
+        let backend = SyslogAuditBackend::new(config(addr)).unwrap();
+
+        backend.log(&entry(200)).await.unwrap();
+
+        let mut buf = vec![0u8; 4096];
+        let n = collector.recv(&mut buf).await.unwrap();
+        let received = String::from_utf8_lossy(&buf[..n]);
+        assert!(received.contains("secret/data/app"));
+    }
+}
@@ -0,0 +1,400 @@
+//! HTTP webhook audit backend for `ZVault`.
+//!
+//! Buffers [`AuditEntry`]s in memory and POSTs them as a JSON array once the
+//! batch is full or the flush interval elapses. Failed deliveries are retried
+//! with exponential backoff; if the endpoint stays unreachable, the batch is
+//! spilled to a local JSON-lines file and replayed ahead of newer entries on
+//! the next successful flush, so ordering is preserved.
+//!
+//! Without a spill file, an undeliverable full batch is reported as an error
+//! to the audit manager (fail-closed) and kept in memory for the next flush.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::audit::{AuditBackend, AuditEntry};
+use crate::error::AuditError;
+
+/// Options for a webhook audit device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Endpoint receiving `POST` requests with a JSON array of entries.
+    pub url: String,
+    /// Extra request headers (e.g., an authorization header for the SIEM).
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Entries per request; reaching this size triggers an immediate flush.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Seconds between background flushes of partial batches.
+    #[serde(default = "default_flush_interval")]
+    pub flush_interval_secs: u64,
+    /// Delivery attempts per batch before spilling.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Per-request timeout in seconds.
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+    /// JSON-lines file for batches that could not be delivered.
+    #[serde(default)]
+    pub spill_path: Option<String>,
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_flush_interval() -> u64 {
+    5
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+/// Audit backend that ships batched entries to an HTTP endpoint.
+pub struct WebhookAuditBackend {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    /// Entries waiting for the next flush.
+    buffer: Mutex<Vec<AuditEntry>>,
+    /// Serializes flushes so spilled and buffered entries stay ordered.
+    flush_lock: Mutex<()>,
+}
+
+impl WebhookAuditBackend {
+    /// Create a webhook backend and start its background flush task.
+    ///
+    /// The flush task holds a weak reference and exits once the backend is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuditError::InvalidConfig`] if the URL or batch size is invalid,
+    /// or if called outside a Tokio runtime.
+    pub fn new(config: WebhookConfig) -> Result<Arc<Self>, AuditError> {
+        let parsed = reqwest::Url::parse(&config.url).map_err(|e| AuditError::InvalidConfig {
+            reason: format!("invalid webhook url '{}': {e}", config.url),
+        })?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AuditError::InvalidConfig {
+                reason: format!(
+                    "webhook url must be http or https, got '{}'",
+                    parsed.scheme()
+                ),
+            });
+        }
+        if config.batch_size == 0 {
+            return Err(AuditError::InvalidConfig {
+                reason: "batch_size must be at least 1".to_owned(),
+            });
+        }
+
+        let handle =
+            tokio::runtime::Handle::try_current().map_err(|_| AuditError::InvalidConfig {
+                reason: "webhook audit backend requires a tokio runtime".to_owned(),
+            })?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build()
+            .map_err(|e| AuditError::InvalidConfig {
+                reason: format!("failed to build http client: {e}"),
+            })?;
+
+        let interval = Duration::from_secs(config.flush_interval_secs.max(1));
+        let backend = Arc::new(Self {
+            config,
+            client,
+            buffer: Mutex::new(Vec::new()),
+            flush_lock: Mutex::new(()),
+        });
+
+        handle.spawn(flush_loop(Arc::downgrade(&backend), interval));
+
+        Ok(backend)
+    }
+
+    /// Deliver spilled and buffered entries.
+    async fn flush_pending(&self) -> Result<(), AuditError> {
+        let _flushing = self.flush_lock.lock().await;
+
+        let mut pending = self.read_spill().await?;
+        pending.append(&mut *self.buffer.lock().await);
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut delivered = 0;
+        for chunk in pending.chunks(self.config.batch_size) {
+            if let Err(e) = self.post_with_retry(chunk).await {
+                let remaining = pending.split_off(delivered);
+                return self.stash(remaining, e).await;
+            }
+            delivered += chunk.len();
+        }
+
+        self.clear_spill().await
+    }
+
+    /// POST one batch, retrying with exponential backoff.
+    async fn post_with_retry(&self, batch: &[AuditEntry]) -> Result<(), AuditError> {
+        let attempts = self.config.max_retries.max(1);
+        let mut last_error = String::new();
+
+        for attempt in 0..attempts {
+            if attempt > 0 {
+                let backoff = Duration::from_millis(500u64.saturating_mul(1 << attempt.min(6)));
+                tokio::time::sleep(backoff).await;
+            }
+
+            let mut request = self.client.post(&self.config.url).json(batch);
+            for (name, value) in &self.config.headers {
+                request = request.header(name, value);
+            }
+
+            match request.send().await {
+                Ok(resp) if resp.status().is_success() => return Ok(()),
+                Ok(resp) => last_error = format!("endpoint returned {}", resp.status()),
+                Err(e) => last_error = format!("request failed: {e}"),
+            }
+        }
+
+        Err(self.failure(format!(
+            "delivery failed after {attempts} attempts: {last_error}"
+        )))
+    }
+
+    /// Keep undelivered entries: spill to disk if configured, else re-queue.
+    async fn stash(&self, remaining: Vec<AuditEntry>, cause: AuditError) -> Result<(), AuditError> {
+        let Some(ref spill_path) = self.config.spill_path else {
+            let mut buffer = self.buffer.lock().await;
+            let newer = std::mem::replace(&mut *buffer, remaining);
+            buffer.extend(newer);
+            return Err(cause);
+        };
+
+        let mut lines = Vec::new();
+        for entry in &remaining {
+            serde_json::to_writer(&mut lines, entry).map_err(|e| AuditError::Serialization {
+                reason: e.to_string(),
+            })?;
+            lines.push(b'\n');
+        }
+
+        // Write the full backlog to a temp file and rename so a crash never
+        // leaves a truncated spill file behind.
+        let path = PathBuf::from(spill_path);
+        let tmp = path.with_extension("tmp");
+        let mut file = tokio::fs::File::create(&tmp)
+            .await
+            .map_err(|e| self.failure(format!("failed to create spill file: {e}")))?;
+        file.write_all(&lines)
+            .await
+            .map_err(|e| self.failure(format!("failed to write spill file: {e}")))?;
+        file.sync_all()
+            .await
+            .map_err(|e| self.failure(format!("failed to sync spill file: {e}")))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| self.failure(format!("failed to replace spill file: {e}")))?;
+
+        warn!(
+            entries = remaining.len(),
+            path = %path.display(),
+            error = %cause,
+            "webhook audit delivery failed, entries spilled to disk"
+        );
+        Ok(())
+    }
+
+    /// Read previously spilled entries, oldest first.
+    async fn read_spill(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        let Some(ref spill_path) = self.config.spill_path else {
+            return Ok(Vec::new());
+        };
+
+        let content = match tokio::fs::read_to_string(spill_path).await {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(self.failure(format!("failed to read spill file: {e}"))),
+        };
+
+        content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                serde_json::from_str(l).map_err(|e| AuditError::Serialization {
+                    reason: format!("corrupt spill entry: {e}"),
+                })
+            })
+            .collect()
+    }
+
+    /// Remove the spill file after a successful replay.
+    async fn clear_spill(&self) -> Result<(), AuditError> {
+        let Some(ref spill_path) = self.config.spill_path else {
+            return Ok(());
+        };
+        match tokio::fs::remove_file(spill_path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(self.failure(format!("failed to remove spill file: {e}"))),
+        }
+    }
+
+    fn failure(&self, reason: String) -> AuditError {
+        AuditError::BackendFailure {
+            name: self.name().to_owned(),
+            reason,
+        }
+    }
+}
+
+/// Periodically flush partial batches until the backend is dropped.
+async fn flush_loop(backend: Weak<WebhookAuditBackend>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let Some(backend) = backend.upgrade() else {
+            return;
+        };
+        if let Err(e) = backend.flush_pending().await {
+            warn!(error = %e, "webhook audit background flush failed");
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditBackend for WebhookAuditBackend {
+    #[allow(clippy::needless_lifetimes, clippy::unnecessary_literal_bound)]
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn log(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+        let full = {
+            let mut buffer = self.buffer.lock().await;
+            buffer.push(entry.clone());
+            buffer.len() >= self.config.batch_size
+        };
+
+        if full {
+            self.flush_pending().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<(), AuditError> {
+        self.flush_pending().await
+    }
+}
+
+impl std::fmt::Debug for WebhookAuditBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookAuditBackend")
+            .field("url", &self.config.url)
+            .field("batch_size", &self.config.batch_size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::audit::{AuditAuth, AuditDevice, AuditDeviceConfig, AuditRequest, AuditResponse};
+
+    fn entry(id: &str) -> AuditEntry {
+        AuditEntry {
+            id: id.to_owned(),
+            timestamp: Utc::now(),
+            request: AuditRequest {
+                operation: "write".to_owned(),
+                path: "secret/data/app".to_owned(),
+                data: None,
+                remote_addr: "127.0.0.1".to_owned(),
+                request_id: "req".to_owned(),
+            },
+            response: Some(AuditResponse {
+                status_code: 200,
+                error: None,
+            }),
+            auth: AuditAuth {
+                token_id: "hmac".to_owned(),
+                accessor: "acc".to_owned(),
+                policies: vec!["root".to_owned()],
+                metadata: HashMap::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn device_config_round_trips() {
+        let json = serde_json::json!({
+            "path": "siem/",
+            "type": "webhook",
+            "options": { "url": "https://siem.example.com/ingest" }
+        });
+        let device: AuditDevice = serde_json::from_value(json).unwrap();
+        let AuditDeviceConfig::Webhook(ref cfg) = device.config else {
+            unreachable!("expected webhook config");
+        };
+        assert_eq!(cfg.batch_size, 100);
+        assert_eq!(cfg.max_retries, 3);
+
+        let back = serde_json::to_value(&device).unwrap();
+        assert_eq!(back["type"], "webhook");
+    }
+
+    #[tokio::test]
+    async fn rejects_non_http_url() {
+        let cfg: WebhookConfig =
+            serde_json::from_value(serde_json::json!({ "url": "ftp://example.com" })).unwrap();
+        assert!(WebhookAuditBackend::new(cfg).is_err());
+    }
+
+    #[tokio::test]
+    async fn undeliverable_batch_is_spilled_and_kept() {
+        // Bind then drop a listener so the port refuses connections.
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let dir = std::env::temp_dir().join(format!("zvault-webhook-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let spill = dir.join("spill.jsonl");
+
+        let cfg: WebhookConfig = serde_json::from_value(serde_json::json!({
+            "url": format!("http://127.0.0.1:{port}/"),
+            "batch_size": 2,
+            "max_retries": 1,
+            "spill_path": spill.to_string_lossy(),
+        }))
+        .unwrap();
+        let backend = WebhookAuditBackend::new(cfg).unwrap();
+
+        backend.log(&entry("a")).await.unwrap();
+        backend.log(&entry("b")).await.unwrap();
+
+        let spilled = backend.read_spill().await.unwrap();
+        let ids: Vec<_> = spilled.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
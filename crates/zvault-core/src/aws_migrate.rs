@@ -0,0 +1,1427 @@
+//! Migration between KV and AWS Secrets Manager or SSM Parameter Store.
+//!
+//! Import copies every secret under an AWS name prefix into KV under a
+//! path, keeping the hierarchy: Secrets Manager `prod/myapp/db` under the
+//! prefix `prod/` becomes `myapp/db`, and the SSM parameters
+//! `/prod/myapp/db/user` and `/prod/myapp/db/password` become the `user`
+//! and `password` fields of `myapp/db`. A Secrets Manager value that is a
+//! JSON object is split into fields; any other value is stored as the
+//! `value` field. AWS tags become KV custom metadata.
+//!
+//! Export is the reverse: each KV secret becomes one Secrets Manager secret
+//! holding its fields as a JSON object, or one `SecureString` parameter per
+//! field, tagged with the secret's custom metadata.
+//!
+//! Both directions first build a [`MigrationPlan`] that says what would be
+//! created, updated, left unchanged, or skipped, so the caller can check
+//! access to every KV path and show a dry run before anything is written.
+//! A target that already exists with a different value is a conflict,
+//! handled by the [`ConflictStrategy`].
+//!
+//! Requests are signed with `SigV4` using credentials supplied by the
+//! caller. `ZVAULT_AWS_SECRETSMANAGER_ENDPOINT` and `ZVAULT_AWS_SSM_ENDPOINT`
+//! point the stores at another endpoint, such as `LocalStack`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use zvault_storage::sigv4::{self, Signer};
+
+use crate::engine::{EngineRequest, KvEngine, KvMetadataUpdate, Operation, unwrap_data_envelope};
+use crate::error::{EngineError, MigrateError};
+
+/// Timeout for one AWS API request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Deepest KV path an import creates, as the secrets API allows.
+const MAX_PATH_DEPTH: usize = 10;
+
+/// Field a Secrets Manager value that is not a JSON object is stored as.
+pub const VALUE_FIELD: &str = "value";
+
+/// An AWS secret store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AwsStore {
+    /// AWS Secrets Manager.
+    SecretsManager,
+    /// AWS Systems Manager Parameter Store.
+    ParameterStore,
+}
+
+impl AwsStore {
+    /// `SigV4` service name.
+    fn service(self) -> &'static str {
+        match self {
+            Self::SecretsManager => "secretsmanager",
+            Self::ParameterStore => "ssm",
+        }
+    }
+
+    /// `X-Amz-Target` prefix of API actions.
+    fn target_prefix(self) -> &'static str {
+        match self {
+            Self::SecretsManager => "secretsmanager",
+            Self::ParameterStore => "AmazonSSM",
+        }
+    }
+
+    fn endpoint(self, region: &str) -> String {
+        let var = match self {
+            Self::SecretsManager => "ZVAULT_AWS_SECRETSMANAGER_ENDPOINT",
+            Self::ParameterStore => "ZVAULT_AWS_SSM_ENDPOINT",
+        };
+        std::env::var(var)
+            .unwrap_or_else(|_| format!("https://{}.{region}.amazonaws.com", self.service()))
+    }
+}
+
+impl fmt::Display for AwsStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::SecretsManager => "Secrets Manager",
+            Self::ParameterStore => "Parameter Store",
+        })
+    }
+}
+
+/// What to do when a target already exists with a different value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Leave the existing value alone.
+    #[default]
+    Skip,
+    /// Replace the existing value.
+    Overwrite,
+    /// Refuse the whole migration.
+    Fail,
+}
+
+/// AWS access keys.
+#[derive(Clone, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Credentials from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, and
+    /// `AWS_SESSION_TOKEN`, if set.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+impl fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Which store to migrate with, and how to reach it.
+#[derive(Debug, Clone)]
+pub struct AwsConnection {
+    pub store: AwsStore,
+    /// AWS region, e.g. `us-east-1`.
+    pub region: String,
+    pub credentials: AwsCredentials,
+}
+
+/// Copy AWS secrets into KV.
+#[derive(Debug, Clone, Default)]
+pub struct ImportParams {
+    /// AWS name prefix to import, e.g. `prod/` or `/prod/myapp`.
+    pub prefix: String,
+    /// KV path the hierarchy is imported under (empty for the mount root).
+    pub path: String,
+    pub conflict: ConflictStrategy,
+}
+
+/// Copy KV secrets to AWS.
+#[derive(Debug, Clone, Default)]
+pub struct ExportParams {
+    /// KV path whose secrets are exported (empty for the whole mount).
+    pub path: String,
+    /// AWS name prefix the hierarchy is exported under.
+    pub prefix: String,
+    /// KMS key to encrypt new secrets and parameters with (default: the
+    /// store's AWS managed key).
+    pub kms_key_id: Option<String>,
+    pub conflict: ConflictStrategy,
+}
+
+/// What a migration does to one target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationAction {
+    Create,
+    Update,
+    Unchanged,
+    Skip,
+    /// The write was attempted and failed.
+    Failed,
+}
+
+/// One KV secret or AWS secret of a migration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationEntry {
+    /// KV secret path within the mount.
+    pub kv_path: String,
+    /// Secrets Manager secret name, or parameter name. SSM imports name the
+    /// parameter path (`/prod/myapp/db/`) whose parameters become fields.
+    pub aws_name: String,
+    /// KV field exported as the parameter, for Parameter Store exports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub action: MigrationAction,
+    /// Why the target is skipped or was not written, or what is updated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// The outcome, or with `dry_run` the plan, of a migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub store: AwsStore,
+    pub dry_run: bool,
+    pub entries: Vec<MigrationEntry>,
+}
+
+/// The writes of an import or export, computed before making any of them.
+pub struct MigrationPlan {
+    client: AwsClient,
+    kms_key_id: Option<String>,
+    entries: Vec<MigrationEntry>,
+    work: Vec<Option<Work>>,
+}
+
+/// A write, with what it needs beyond its entry.
+enum Work {
+    /// Write a KV secret and merge tags into its custom metadata.
+    Kv {
+        data: Map<String, Value>,
+        tags: HashMap<String, String>,
+        write_data: bool,
+    },
+    /// Create a Secrets Manager secret, or update its value and tags.
+    Secret {
+        value: Zeroizing<String>,
+        tags: Vec<(String, String)>,
+        exists: bool,
+        write_value: bool,
+    },
+    /// Create a `SecureString` parameter, or update its value and tags.
+    Parameter {
+        value: Zeroizing<String>,
+        tags: Vec<(String, String)>,
+        exists: bool,
+        write_value: bool,
+    },
+}
+
+impl MigrationPlan {
+    /// What the migration would do to each target.
+    #[must_use]
+    pub fn entries(&self) -> &[MigrationEntry] {
+        &self.entries
+    }
+
+    /// KV paths whose custom metadata an import changes.
+    pub fn tagged_paths(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .zip(&self.work)
+            .filter(|(_, work)| matches!(work, Some(Work::Kv { tags, .. }) if !tags.is_empty()))
+            .map(|(entry, _)| entry.kv_path.as_str())
+    }
+
+    /// The plan as a dry-run report.
+    #[must_use]
+    pub fn report(&self) -> MigrationReport {
+        MigrationReport {
+            store: self.client.store,
+            dry_run: true,
+            entries: self.entries.clone(),
+        }
+    }
+
+    /// Make every planned write. A failed write is reported as
+    /// [`MigrationAction::Failed`] and does not stop the others.
+    pub async fn execute(self, engine: &KvEngine) -> MigrationReport {
+        let Self {
+            client,
+            kms_key_id,
+            mut entries,
+            work,
+        } = self;
+        for (entry, work) in entries.iter_mut().zip(work) {
+            let Some(work) = work else { continue };
+            let result = match work {
+                Work::Kv {
+                    data,
+                    tags,
+                    write_data,
+                } => write_kv(engine, &entry.kv_path, data, tags, write_data)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Work::Secret {
+                    value,
+                    tags,
+                    exists,
+                    write_value,
+                } => {
+                    write_secret(
+                        &client,
+                        &entry.aws_name,
+                        &value,
+                        &tags,
+                        exists,
+                        write_value,
+                        kms_key_id.as_deref(),
+                    )
+                    .await
+                }
+                Work::Parameter {
+                    value,
+                    tags,
+                    exists,
+                    write_value,
+                } => {
+                    write_parameter(
+                        &client,
+                        &entry.aws_name,
+                        &value,
+                        &tags,
+                        exists,
+                        write_value,
+                        kms_key_id.as_deref(),
+                    )
+                    .await
+                }
+            };
+            if let Err(reason) = result {
+                entry.action = MigrationAction::Failed;
+                entry.reason = Some(reason);
+            }
+        }
+        MigrationReport {
+            store: client.store,
+            dry_run: false,
+            entries,
+        }
+    }
+}
+
+impl fmt::Debug for MigrationPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MigrationPlan")
+            .field("store", &self.client.store)
+            .field("entries", &self.entries)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Plan importing the AWS secrets under `params.prefix` into `engine`.
+///
+/// # Errors
+///
+/// Returns [`MigrateError::Invalid`] for a bad prefix or path, or when two
+/// AWS secrets would be imported to the same place,
+/// [`MigrateError::Aws`] if AWS refuses a request,
+/// [`MigrateError::Conflict`] for conflicts under [`ConflictStrategy::Fail`],
+/// and [`MigrateError::Engine`] if KV cannot be read.
+pub async fn plan_import(
+    connection: &AwsConnection,
+    engine: &KvEngine,
+    params: &ImportParams,
+) -> Result<MigrationPlan, MigrateError> {
+    let client = AwsClient::new(connection)?;
+    let base = params.path.trim_matches('/');
+    let sources = match connection.store {
+        AwsStore::SecretsManager => read_secrets(&client, &params.prefix, base).await?,
+        AwsStore::ParameterStore => read_parameters(&client, &params.prefix, base).await?,
+    };
+
+    let mut plan = MigrationPlan {
+        client,
+        kms_key_id: None,
+        entries: Vec::new(),
+        work: Vec::new(),
+    };
+    let mut conflicts = Vec::new();
+    for (kv_path, source) in sources {
+        let mut entry = MigrationEntry {
+            kv_path,
+            aws_name: source.aws_name,
+            field: None,
+            action: MigrationAction::Create,
+            reason: None,
+        };
+        let Some(data) = source.data else {
+            entry.action = MigrationAction::Skip;
+            entry.reason = source.skip_reason;
+            plan.push(entry, None);
+            continue;
+        };
+        let (write_data, tags_changed) = match read_kv(engine, &entry.kv_path).await? {
+            None => (true, false),
+            Some(existing) => {
+                let custom = match engine.metadata(&entry.kv_path).await {
+                    Ok(metadata) => metadata.custom_metadata,
+                    Err(EngineError::NotFound { .. }) => HashMap::new(),
+                    Err(e) => return Err(e.into()),
+                };
+                let tags_changed = source.tags.iter().any(|(k, v)| custom.get(k) != Some(v));
+                entry.action = MigrationAction::Update;
+                (existing != data, tags_changed)
+            }
+        };
+        if entry.action == MigrationAction::Update {
+            match decide(&mut entry, write_data, tags_changed, params.conflict) {
+                Decision::Write => {}
+                Decision::Conflict => conflicts.push(entry.kv_path.clone()),
+                Decision::Leave => {
+                    plan.push(entry, None);
+                    continue;
+                }
+            }
+        }
+        let work = Work::Kv {
+            data,
+            tags: source.tags,
+            write_data,
+        };
+        plan.push(entry, Some(work));
+    }
+    if !conflicts.is_empty() {
+        return Err(MigrateError::Conflict { targets: conflicts });
+    }
+    Ok(plan)
+}
+
+/// Plan exporting the KV secrets under `params.path` to AWS.
+///
+/// # Errors
+///
+/// Returns [`MigrateError::Invalid`] if there are no secrets under the
+/// path, [`MigrateError::Aws`] if AWS refuses a request,
+/// [`MigrateError::Conflict`] for conflicts under [`ConflictStrategy::Fail`],
+/// and [`MigrateError::Engine`] if KV cannot be read.
+pub async fn plan_export(
+    connection: &AwsConnection,
+    engine: &KvEngine,
+    params: &ExportParams,
+) -> Result<MigrationPlan, MigrateError> {
+    let client = AwsClient::new(connection)?;
+    let base = params.path.trim_matches('/');
+    let dir = if base.is_empty() {
+        String::new()
+    } else {
+        format!("{base}/")
+    };
+    let listed = engine
+        .handle(&EngineRequest {
+            operation: Operation::List,
+            path: dir.clone(),
+            data: None,
+        })
+        .await?;
+    let mut keys: Vec<String> = listed
+        .data
+        .as_ref()
+        .and_then(|d| d.get("keys"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_owned)
+        .collect();
+    keys.sort();
+
+    let mut plan = MigrationPlan {
+        client,
+        kms_key_id: params.kms_key_id.clone(),
+        entries: Vec::new(),
+        work: Vec::new(),
+    };
+    let mut conflicts = Vec::new();
+    let mut found = false;
+    for rel in keys {
+        let kv_path = format!("{dir}{rel}");
+        // Deleted secrets stay listed until their metadata is destroyed.
+        let Some(data) = read_kv(engine, &kv_path).await? else {
+            continue;
+        };
+        found = true;
+        let mut tags: Vec<(String, String)> = engine
+            .metadata(&kv_path)
+            .await?
+            .custom_metadata
+            .into_iter()
+            .collect();
+        tags.sort();
+        match connection.store {
+            AwsStore::SecretsManager => {
+                let aws_name = join_name(&params.prefix, &rel);
+                let entry = plan_secret(&plan.client, kv_path, aws_name, &data, tags).await?;
+                plan.push_export(entry, params.conflict, &mut conflicts);
+            }
+            AwsStore::ParameterStore => {
+                for (field, value) in &data {
+                    let aws_name =
+                        format!("/{}", join_name(&params.prefix, &format!("{rel}/{field}")));
+                    let entry = plan_parameter(
+                        &plan.client,
+                        kv_path.clone(),
+                        aws_name,
+                        field,
+                        value,
+                        tags.clone(),
+                    )
+                    .await?;
+                    plan.push_export(entry, params.conflict, &mut conflicts);
+                }
+            }
+        }
+    }
+    if !found {
+        return Err(MigrateError::Invalid {
+            reason: format!("no secrets under '{dir}'"),
+        });
+    }
+    if !conflicts.is_empty() {
+        return Err(MigrateError::Conflict { targets: conflicts });
+    }
+    Ok(plan)
+}
+
+impl MigrationPlan {
+    fn push(&mut self, entry: MigrationEntry, work: Option<Work>) {
+        self.entries.push(entry);
+        self.work.push(work);
+    }
+
+    /// Add a planned export, applying the conflict strategy to an update.
+    fn push_export(
+        &mut self,
+        (mut entry, work): (MigrationEntry, Option<Work>),
+        conflict: ConflictStrategy,
+        conflicts: &mut Vec<String>,
+    ) {
+        let (exists, write_value, tags_changed) = match &work {
+            Some(
+                Work::Secret {
+                    exists,
+                    write_value,
+                    tags,
+                    ..
+                }
+                | Work::Parameter {
+                    exists,
+                    write_value,
+                    tags,
+                    ..
+                },
+            ) => (*exists, *write_value, !tags.is_empty()),
+            _ => (false, false, false),
+        };
+        if work.is_some() && exists {
+            match decide(&mut entry, write_value, tags_changed, conflict) {
+                Decision::Write => {}
+                Decision::Conflict => conflicts.push(entry.aws_name.clone()),
+                Decision::Leave => {
+                    self.push(entry, None);
+                    return;
+                }
+            }
+        }
+        self.push(entry, work);
+    }
+}
+
+enum Decision {
+    Write,
+    Leave,
+    Conflict,
+}
+
+/// Settle an existing target's action: unchanged, a tag-only update, a
+/// skipped conflict, an overwrite, or a conflict that fails the migration.
+fn decide(
+    entry: &mut MigrationEntry,
+    value_changed: bool,
+    tags_changed: bool,
+    conflict: ConflictStrategy,
+) -> Decision {
+    if !value_changed {
+        if !tags_changed {
+            entry.action = MigrationAction::Unchanged;
+            return Decision::Leave;
+        }
+        entry.action = MigrationAction::Update;
+        entry.reason = Some("tags".to_owned());
+        return Decision::Write;
+    }
+    match conflict {
+        ConflictStrategy::Skip => {
+            entry.action = MigrationAction::Skip;
+            entry.reason = Some("exists with a different value".to_owned());
+            Decision::Leave
+        }
+        ConflictStrategy::Overwrite => {
+            entry.action = MigrationAction::Update;
+            Decision::Write
+        }
+        ConflictStrategy::Fail => Decision::Conflict,
+    }
+}
+
+// ── Import sources ───────────────────────────────────────────────────
+
+/// An AWS secret, or a group of parameters, bound for one KV secret.
+struct Source {
+    aws_name: String,
+    /// `None` when the source cannot be imported.
+    data: Option<Map<String, Value>>,
+    skip_reason: Option<String>,
+    tags: HashMap<String, String>,
+}
+
+/// Every Secrets Manager secret under `prefix` with its KV path, which is
+/// empty for secrets that cannot be imported anywhere.
+async fn read_secrets(
+    client: &AwsClient,
+    prefix: &str,
+    base: &str,
+) -> Result<Vec<(String, Source)>, MigrateError> {
+    let mut listed = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let mut request = serde_json::json!({ "MaxResults": 100 });
+        if !prefix.is_empty() {
+            request["Filters"] = serde_json::json!([{ "Key": "name", "Values": [prefix] }]);
+        }
+        if let Some(token) = &next_token {
+            request["NextToken"] = Value::String(token.clone());
+        }
+        let response = client.call("ListSecrets", &request).await.map_err(aws)?;
+        for secret in response
+            .get("SecretList")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(name) = secret.get("Name").and_then(Value::as_str) else {
+                continue;
+            };
+            // The name filter also matches words inside names.
+            if name.starts_with(prefix) {
+                listed.push((name.to_owned(), tag_map(secret.get("Tags"))));
+            }
+        }
+        next_token = next_page(&response);
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    let mut sources: BTreeMap<String, Source> = BTreeMap::new();
+    let mut unplaced = Vec::new();
+    for (name, tags) in listed {
+        let kv_path = match import_path(base, relative_name(&name, prefix)) {
+            Ok(kv_path) => kv_path,
+            Err(reason) => {
+                unplaced.push((String::new(), skipped(name, reason)));
+                continue;
+            }
+        };
+        if let Some(other) = sources.get(&kv_path) {
+            return Err(MigrateError::Invalid {
+                reason: format!(
+                    "{} and {name} would both be imported to '{kv_path}'",
+                    other.aws_name
+                ),
+            });
+        }
+        let response = client
+            .call("GetSecretValue", &serde_json::json!({ "SecretId": name }))
+            .await
+            .map_err(aws)?;
+        let source = match response.get("SecretString").and_then(Value::as_str) {
+            Some(value) => Source {
+                aws_name: name,
+                data: Some(secret_fields(value)),
+                skip_reason: None,
+                tags,
+            },
+            None => skipped(name, "binary secret values are not supported".to_owned()),
+        };
+        sources.insert(kv_path, source);
+    }
+    Ok(unplaced.into_iter().chain(sources).collect())
+}
+
+/// Every parameter under the path `prefix`, grouped into KV secrets by
+/// parent path.
+async fn read_parameters(
+    client: &AwsClient,
+    prefix: &str,
+    base: &str,
+) -> Result<Vec<(String, Source)>, MigrateError> {
+    let root = format!("/{}", prefix.trim_matches('/'));
+    let mut parameters = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let mut request = serde_json::json!({
+            "Path": root,
+            "Recursive": true,
+            "WithDecryption": true,
+            "MaxResults": 10,
+        });
+        if let Some(token) = &next_token {
+            request["NextToken"] = Value::String(token.clone());
+        }
+        let response = client
+            .call("GetParametersByPath", &request)
+            .await
+            .map_err(aws)?;
+        for parameter in response
+            .get("Parameters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let name = parameter.get("Name").and_then(Value::as_str);
+            let value = parameter.get("Value").and_then(Value::as_str);
+            if let (Some(name), Some(value)) = (name, value) {
+                parameters.push((name.to_owned(), Zeroizing::new(value.to_owned())));
+            }
+        }
+        next_token = next_page(&response);
+        if next_token.is_none() {
+            break;
+        }
+    }
+    parameters.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut sources: BTreeMap<String, Source> = BTreeMap::new();
+    let mut unplaced = Vec::new();
+    let mut field_sources: HashMap<(String, String), String> = HashMap::new();
+    for (name, value) in parameters {
+        let (parent, field) = split_parameter(relative_name(&name, &root));
+        let aws_dir = format!(
+            "{}/",
+            name.strip_suffix(field)
+                .unwrap_or(&name)
+                .trim_end_matches('/')
+        );
+        let kv_path = match import_path(base, parent) {
+            Ok(kv_path) if !kv_path.is_empty() => kv_path,
+            Ok(_) => {
+                let reason = "parameters directly under the prefix need a KV path to import into";
+                unplaced.push((String::new(), skipped(name, reason.to_owned())));
+                continue;
+            }
+            Err(reason) => {
+                unplaced.push((String::new(), skipped(name, reason)));
+                continue;
+            }
+        };
+        if let Some(other) = field_sources.insert((kv_path.clone(), field.to_owned()), name.clone())
+        {
+            return Err(MigrateError::Invalid {
+                reason: format!(
+                    "{other} and {name} would both be imported to '{kv_path}' field '{field}'"
+                ),
+            });
+        }
+        let tags = parameter_tags(client, &name).await?;
+        let source = sources.entry(kv_path).or_insert_with(|| Source {
+            aws_name: aws_dir,
+            data: Some(Map::new()),
+            skip_reason: None,
+            tags: HashMap::new(),
+        });
+        if let Some(data) = &mut source.data {
+            data.insert(field.to_owned(), Value::String(value.to_string()));
+        }
+        source.tags.extend(tags);
+    }
+    Ok(unplaced.into_iter().chain(sources).collect())
+}
+
+async fn parameter_tags(
+    client: &AwsClient,
+    name: &str,
+) -> Result<HashMap<String, String>, MigrateError> {
+    let response = client
+        .call(
+            "ListTagsForResource",
+            &serde_json::json!({ "ResourceType": "Parameter", "ResourceId": name }),
+        )
+        .await
+        .map_err(aws)?;
+    Ok(tag_map(response.get("TagList")))
+}
+
+fn skipped(aws_name: String, reason: String) -> Source {
+    Source {
+        aws_name,
+        data: None,
+        skip_reason: Some(reason),
+        tags: HashMap::new(),
+    }
+}
+
+/// `prod/myapp/db` under the prefix `prod/` → `myapp/db`. A name equal to
+/// the prefix keeps its last segment.
+fn relative_name<'a>(name: &'a str, prefix: &str) -> &'a str {
+    let rel = name.strip_prefix(prefix).unwrap_or(name).trim_matches('/');
+    if rel.is_empty() {
+        name.trim_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+    } else {
+        rel
+    }
+}
+
+/// `myapp/db/password` → (`myapp/db`, `password`).
+fn split_parameter(rel: &str) -> (&str, &str) {
+    rel.rsplit_once('/').unwrap_or(("", rel))
+}
+
+/// KV path for the AWS name `rel` under `base`. Characters KV paths do
+/// not allow (`.`, `@`, `+`, `=`) become `_`.
+fn import_path(base: &str, rel: &str) -> Result<String, String> {
+    let segments: Vec<String> = base
+        .split('/')
+        .chain(rel.split('/'))
+        .filter(|s| !s.is_empty())
+        .map(|segment| {
+            segment
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    if segments.len() > MAX_PATH_DEPTH {
+        return Err(format!("KV paths are limited to {MAX_PATH_DEPTH} segments"));
+    }
+    Ok(segments.join("/"))
+}
+
+/// A Secrets Manager value as KV fields: the members of a JSON object, or
+/// the whole value as [`VALUE_FIELD`].
+fn secret_fields(value: &str) -> Map<String, Value> {
+    match serde_json::from_str(value) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::from_iter([(VALUE_FIELD.to_owned(), Value::String(value.to_owned()))]),
+    }
+}
+
+/// `prefix` and `rel` joined by `/`.
+fn join_name(prefix: &str, rel: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        rel.to_owned()
+    } else {
+        format!("{prefix}/{rel}")
+    }
+}
+
+/// AWS `[{"Key": .., "Value": ..}]` tags as a map.
+fn tag_map(tags: Option<&Value>) -> HashMap<String, String> {
+    tags.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| {
+            let key = tag.get("Key").and_then(Value::as_str)?;
+            let value = tag.get("Value").and_then(Value::as_str).unwrap_or_default();
+            Some((key.to_owned(), value.to_owned()))
+        })
+        .collect()
+}
+
+fn tag_list(tags: &[(String, String)]) -> Value {
+    tags.iter()
+        .map(|(key, value)| serde_json::json!({ "Key": key, "Value": value }))
+        .collect()
+}
+
+fn next_page(response: &Value) -> Option<String> {
+    response
+        .get("NextToken")
+        .and_then(Value::as_str)
+        .filter(|token| !token.is_empty())
+        .map(str::to_owned)
+}
+
+// ── KV ───────────────────────────────────────────────────────────────
+
+/// The fields of a KV secret, or `None` if it does not exist.
+async fn read_kv(
+    engine: &KvEngine,
+    path: &str,
+) -> Result<Option<Map<String, Value>>, MigrateError> {
+    let response = match engine
+        .handle(&EngineRequest {
+            operation: Operation::Read,
+            path: path.to_owned(),
+            data: None,
+        })
+        .await
+    {
+        Ok(response) => response,
+        Err(EngineError::NotFound { .. }) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let data = response
+        .data
+        .unwrap_or_default()
+        .get("data")
+        .and_then(|v| v.as_object().cloned())
+        .unwrap_or_default();
+    Ok(Some(unwrap_data_envelope(data)))
+}
+
+async fn write_kv(
+    engine: &KvEngine,
+    path: &str,
+    data: Map<String, Value>,
+    tags: HashMap<String, String>,
+    write_data: bool,
+) -> Result<(), EngineError> {
+    if write_data {
+        engine
+            .write_cas(path, Some(Value::Object(data)), None)
+            .await?;
+    }
+    if !tags.is_empty() {
+        let mut custom = engine.metadata(path).await?.custom_metadata;
+        custom.extend(tags);
+        engine
+            .update_metadata(
+                path,
+                KvMetadataUpdate {
+                    custom_metadata: Some(custom),
+                    ..KvMetadataUpdate::default()
+                },
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+// ── Export targets ───────────────────────────────────────────────────
+
+async fn plan_secret(
+    client: &AwsClient,
+    kv_path: String,
+    aws_name: String,
+    data: &Map<String, Value>,
+    tags: Vec<(String, String)>,
+) -> Result<(MigrationEntry, Option<Work>), MigrateError> {
+    let mut entry = MigrationEntry {
+        kv_path,
+        aws_name,
+        field: None,
+        action: MigrationAction::Create,
+        reason: None,
+    };
+    let value = Zeroizing::new(Value::Object(data.clone()).to_string());
+    let described = client
+        .call(
+            "DescribeSecret",
+            &serde_json::json!({ "SecretId": entry.aws_name }),
+        )
+        .await;
+    let (exists, write_value, tags) = match described {
+        Err(fault) if fault.code == "ResourceNotFoundException" => (false, true, tags),
+        Err(fault) => return Err(aws(fault)),
+        Ok(described) => {
+            entry.action = MigrationAction::Update;
+            let existing_tags = tag_map(described.get("Tags"));
+            let current = client
+                .call(
+                    "GetSecretValue",
+                    &serde_json::json!({ "SecretId": entry.aws_name }),
+                )
+                .await;
+            let current = match current {
+                // A secret created without a value.
+                Err(fault) if fault.code == "ResourceNotFoundException" => None,
+                Err(fault) => return Err(aws(fault)),
+                Ok(current) => current
+                    .get("SecretString")
+                    .and_then(Value::as_str)
+                    .and_then(|s| serde_json::from_str::<Value>(s).ok()),
+            };
+            let changed = current.as_ref().and_then(Value::as_object) != Some(data);
+            let tags = missing_tags(tags, &existing_tags);
+            (true, changed, tags)
+        }
+    };
+    let work = Work::Secret {
+        value,
+        tags,
+        exists,
+        write_value,
+    };
+    Ok((entry, Some(work)))
+}
+
+async fn plan_parameter(
+    client: &AwsClient,
+    kv_path: String,
+    aws_name: String,
+    field: &str,
+    value: &Value,
+    tags: Vec<(String, String)>,
+) -> Result<(MigrationEntry, Option<Work>), MigrateError> {
+    let mut entry = MigrationEntry {
+        kv_path,
+        aws_name,
+        field: Some(field.to_owned()),
+        action: MigrationAction::Create,
+        reason: None,
+    };
+    let value = Zeroizing::new(match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    });
+    if value.is_empty() {
+        entry.action = MigrationAction::Skip;
+        entry.reason = Some("parameters cannot be empty".to_owned());
+        return Ok((entry, None));
+    }
+    if !field
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        entry.action = MigrationAction::Skip;
+        entry.reason = Some("the field name is not a valid parameter name".to_owned());
+        return Ok((entry, None));
+    }
+    let current = client
+        .call(
+            "GetParameter",
+            &serde_json::json!({ "Name": entry.aws_name, "WithDecryption": true }),
+        )
+        .await;
+    let (exists, write_value, tags) = match current {
+        Err(fault) if fault.code == "ParameterNotFound" => (false, true, tags),
+        Err(fault) => return Err(aws(fault)),
+        Ok(current) => {
+            entry.action = MigrationAction::Update;
+            let changed =
+                current.pointer("/Parameter/Value").and_then(Value::as_str) != Some(value.as_str());
+            let existing_tags = parameter_tags(client, &entry.aws_name).await?;
+            (true, changed, missing_tags(tags, &existing_tags))
+        }
+    };
+    let work = Work::Parameter {
+        value,
+        tags,
+        exists,
+        write_value,
+    };
+    Ok((entry, Some(work)))
+}
+
+/// The tags of `tags` that `existing` lacks or holds another value for.
+fn missing_tags(
+    tags: Vec<(String, String)>,
+    existing: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    tags.into_iter()
+        .filter(|(key, value)| existing.get(key) != Some(value))
+        .collect()
+}
+
+async fn write_secret(
+    client: &AwsClient,
+    name: &str,
+    value: &str,
+    tags: &[(String, String)],
+    exists: bool,
+    write_value: bool,
+    kms_key_id: Option<&str>,
+) -> Result<(), String> {
+    let token = uuid::Uuid::new_v4().to_string();
+    if !exists {
+        let mut request = serde_json::json!({
+            "Name": name,
+            "SecretString": value,
+            "ClientRequestToken": token,
+        });
+        if !tags.is_empty() {
+            request["Tags"] = tag_list(tags);
+        }
+        if let Some(key) = kms_key_id {
+            request["KmsKeyId"] = Value::String(key.to_owned());
+        }
+        client.call("CreateSecret", &request).await?;
+        return Ok(());
+    }
+    if write_value {
+        client
+            .call(
+                "PutSecretValue",
+                &serde_json::json!({
+                    "SecretId": name,
+                    "SecretString": value,
+                    "ClientRequestToken": token,
+                }),
+            )
+            .await?;
+    }
+    if !tags.is_empty() {
+        client
+            .call(
+                "TagResource",
+                &serde_json::json!({ "SecretId": name, "Tags": tag_list(tags) }),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+async fn write_parameter(
+    client: &AwsClient,
+    name: &str,
+    value: &str,
+    tags: &[(String, String)],
+    exists: bool,
+    write_value: bool,
+    kms_key_id: Option<&str>,
+) -> Result<(), String> {
+    if write_value {
+        let mut request = serde_json::json!({
+            "Name": name,
+            "Value": value,
+            "Type": "SecureString",
+            "Overwrite": exists,
+        });
+        // Tags can only be given when creating a parameter.
+        if !exists && !tags.is_empty() {
+            request["Tags"] = tag_list(tags);
+        }
+        if let Some(key) = kms_key_id {
+            request["KeyId"] = Value::String(key.to_owned());
+        }
+        client.call("PutParameter", &request).await?;
+    }
+    if exists && !tags.is_empty() {
+        client
+            .call(
+                "AddTagsToResource",
+                &serde_json::json!({
+                    "ResourceType": "Parameter",
+                    "ResourceId": name,
+                    "Tags": tag_list(tags),
+                }),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+// ── AWS API ──────────────────────────────────────────────────────────
+
+/// A JSON 1.1 API client for one store.
+struct AwsClient {
+    store: AwsStore,
+    region: String,
+    url: reqwest::Url,
+    host: String,
+    credentials: AwsCredentials,
+    http: reqwest::Client,
+}
+
+/// An AWS error response, or a failure to get one.
+struct AwsFault {
+    /// Error type, e.g. `ResourceNotFoundException`; empty if AWS was not
+    /// reached.
+    code: String,
+    message: String,
+}
+
+impl From<AwsFault> for String {
+    fn from(fault: AwsFault) -> Self {
+        if fault.code.is_empty() {
+            fault.message
+        } else {
+            format!("{}: {}", fault.code, fault.message)
+        }
+    }
+}
+
+fn aws(fault: AwsFault) -> MigrateError {
+    MigrateError::Aws {
+        reason: fault.into(),
+    }
+}
+
+impl AwsClient {
+    fn new(connection: &AwsConnection) -> Result<Self, MigrateError> {
+        let invalid = |reason: String| MigrateError::Invalid { reason };
+        let region = connection.region.trim();
+        if region.is_empty()
+            || !region
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(invalid(format!("invalid AWS region '{region}'")));
+        }
+        if connection.credentials.access_key_id.is_empty()
+            || connection.credentials.secret_access_key.is_empty()
+        {
+            return Err(invalid("AWS credentials are required".to_owned()));
+        }
+        let endpoint = connection.store.endpoint(region);
+        let url = reqwest::Url::parse(&endpoint).map_err(|e| {
+            invalid(format!(
+                "invalid {} endpoint '{endpoint}': {e}",
+                connection.store
+            ))
+        })?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_owned(),
+            (None, _) => {
+                return Err(invalid(format!(
+                    "invalid {} endpoint '{endpoint}'",
+                    connection.store
+                )));
+            }
+        };
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| MigrateError::Aws {
+                reason: format!("failed to build HTTP client: {e}"),
+            })?;
+        Ok(Self {
+            store: connection.store,
+            region: region.to_owned(),
+            url,
+            host,
+            credentials: connection.credentials.clone(),
+            http,
+        })
+    }
+
+    /// Call an API action with a `SigV4`-signed JSON request.
+    async fn call(&self, action: &str, payload: &Value) -> Result<Value, AwsFault> {
+        let fault = |message: String| AwsFault {
+            code: String::new(),
+            message,
+        };
+        let body = serde_json::to_vec(payload).map_err(|e| fault(e.to_string()))?;
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let target = format!("{}.{action}", self.store.target_prefix());
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1"),
+            ("host", self.host.as_str()),
+            ("x-amz-date", amz_date.as_str()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.as_str()));
+        }
+        headers.push(("x-amz-target", target.as_str()));
+        let signer = Signer {
+            access_key_id: &self.credentials.access_key_id,
+            secret_access_key: &self.credentials.secret_access_key,
+            region: &self.region,
+            service: self.store.service(),
+        };
+        let authorization = signer.authorization(
+            &amz_date,
+            &sigv4::canonical_request(
+                "POST",
+                self.url.path(),
+                "",
+                &headers,
+                &sigv4::payload_hash(&body),
+            ),
+            &headers,
+        );
+
+        let mut request = self
+            .http
+            .post(self.url.clone())
+            .header("authorization", authorization);
+        for (name, value) in &headers {
+            if *name != "host" {
+                request = request.header(*name, *value);
+            }
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| fault(format!("{} {action} request failed: {e}", self.store)))?;
+        let status = response.status();
+        let json: Value = response.json().await.map_err(|e| {
+            fault(format!(
+                "{} returned {status} with an unreadable body: {e}",
+                self.store
+            ))
+        })?;
+        if status.is_success() {
+            return Ok(json);
+        }
+        let code = json
+            .get("__type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let code = code.rsplit('#').next().unwrap_or(code).to_owned();
+        let message = json
+            .get("message")
+            .or_else(|| json.get("Message"))
+            .and_then(Value::as_str)
+            .map_or_else(
+                || format!("{} returned {status}", self.store),
+                str::to_owned,
+            );
+        Err(AwsFault { code, message })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::Arc;
+
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::barrier::Barrier;
+    use crate::crypto::EncryptionKey;
+
+    #[test]
+    fn aws_names_map_to_kv_paths() {
+        assert_eq!(relative_name("prod/myapp/db", "prod/"), "myapp/db");
+        assert_eq!(relative_name("prod/myapp/db", "prod/myapp/db"), "db");
+        assert_eq!(relative_name("/prod/db/user", "/prod"), "db/user");
+        assert_eq!(split_parameter("myapp/db/user"), ("myapp/db", "user"));
+        assert_eq!(split_parameter("token"), ("", "token"));
+        assert_eq!(
+            import_path("imported/", "myapp/db").unwrap(),
+            "imported/myapp/db"
+        );
+        assert_eq!(
+            import_path("", "app.prod/db@main").unwrap(),
+            "app_prod/db_main"
+        );
+        assert!(import_path("a/b/c/d/e", "f/g/h/i/j/k").is_err());
+        assert_eq!(join_name("/prod/", "db/user"), "prod/db/user");
+        assert_eq!(join_name("", "db"), "db");
+    }
+
+    #[test]
+    fn secret_values_become_fields() {
+        let fields = secret_fields(r#"{"user":"app","port":5432}"#);
+        assert_eq!(fields["user"], "app");
+        assert_eq!(fields["port"], 5432);
+        let fields = secret_fields("hunter2");
+        assert_eq!(fields[VALUE_FIELD], "hunter2");
+        assert_eq!(secret_fields("[1,2]")[VALUE_FIELD], "[1,2]");
+    }
+
+    #[test]
+    fn conflicts_follow_the_strategy() {
+        let entry = || MigrationEntry {
+            kv_path: "db".to_owned(),
+            aws_name: "prod/db".to_owned(),
+            field: None,
+            action: MigrationAction::Update,
+            reason: None,
+        };
+        let mut e = entry();
+        assert!(matches!(
+            decide(&mut e, false, false, ConflictStrategy::Fail),
+            Decision::Leave
+        ));
+        assert_eq!(e.action, MigrationAction::Unchanged);
+
+        let mut e = entry();
+        assert!(matches!(
+            decide(&mut e, false, true, ConflictStrategy::Skip),
+            Decision::Write
+        ));
+        assert_eq!(e.reason.as_deref(), Some("tags"));
+
+        let mut e = entry();
+        assert!(matches!(
+            decide(&mut e, true, false, ConflictStrategy::Skip),
+            Decision::Leave
+        ));
+        assert_eq!(e.action, MigrationAction::Skip);
+
+        let mut e = entry();
+        assert!(matches!(
+            decide(&mut e, true, false, ConflictStrategy::Overwrite),
+            Decision::Write
+        ));
+        assert!(matches!(
+            decide(&mut entry(), true, false, ConflictStrategy::Fail),
+            Decision::Conflict
+        ));
+    }
+
+    #[tokio::test]
+    async fn imports_write_fields_and_merge_tags() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let engine = KvEngine::new(barrier, "kv/secret/".to_owned());
+        engine
+            .write_cas(
+                "myapp/db",
+                Some(serde_json::json!({ "data": { "user": "old" } })),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            read_kv(&engine, "myapp/db").await.unwrap().unwrap()["user"],
+            "old"
+        );
+        assert!(read_kv(&engine, "missing").await.unwrap().is_none());
+
+        let data = secret_fields(r#"{"user":"app"}"#);
+        let tags = HashMap::from([("team".to_owned(), "payments".to_owned())]);
+        write_kv(&engine, "myapp/db", data, tags, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            read_kv(&engine, "myapp/db").await.unwrap().unwrap()["user"],
+            "app"
+        );
+        let metadata = engine.metadata("myapp/db").await.unwrap();
+        assert_eq!(metadata.custom_metadata["team"], "payments");
+        assert_eq!(metadata.current_version, 2);
+    }
+}
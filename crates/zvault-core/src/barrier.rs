@@ -13,14 +13,233 @@
 //! - All values are encrypted with AES-256-GCM (fresh nonce per write).
 //! - Keys (storage paths) are stored in plaintext to support prefix listing.
 //! - Sealing zeroizes the root key from memory immediately.
-
+//!
+//! # Keyring
+//!
+//! The barrier keeps a keyring of encryption keys identified by *term*.
+//! Term 1 is the root key itself. [`Barrier::rotate`] installs a new term
+//! key — the keyring is stored under `sys/seal/keyring`, encrypted by the
+//! root key — and new writes use it. Older terms stay in the keyring so
+//! existing entries remain readable.
+//!
+//! Every value is tagged with a header naming the term it was encrypted
+//! under, and is decrypted with that term's key. Values written before
+//! tagging have no header and belong to term 1. [`Barrier::term_usage`]
+//! counts entries per term for re-encryption audits, and
+//! [`Barrier::reencrypt`] rewrites the entries under a prefix with the
+//! active term.
+
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Instant;
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 use zvault_storage::StorageBackend;
 
 use crate::crypto::{self, EncryptionKey};
 use crate::error::BarrierError;
+use crate::metrics;
+
+/// Storage key of the keyring (raw, encrypted by the root key).
+const KEYRING_PATH: &str = "sys/seal/keyring";
+
+/// Entries under this prefix are written raw by the seal manager, not
+/// through the barrier, and are never re-encrypted.
+const RAW_PREFIX: &str = "sys/seal/";
+
+/// Marks a value encrypted under a term key; followed by the term (u32 BE).
+const TERM_MAGIC: [u8; 4] = *b"zvk\x01";
+
+/// Length of the term header.
+const TERM_HEADER_LEN: usize = TERM_MAGIC.len() + 4;
+
+/// The term of the root key.
+const ROOT_TERM: u32 = 1;
+
+/// The active barrier key term.
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyStatus {
+    /// Term of the key new writes are encrypted with.
+    pub term: u32,
+    /// When that key was installed. `None` for the original root key.
+    pub install_time: Option<DateTime<Utc>>,
+}
+
+/// How many entries under a prefix each key term encrypts.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TermUsage {
+    /// Tagged entries by term.
+    pub terms: BTreeMap<u32, usize>,
+    /// Entries written before values were tagged (term 1).
+    pub untagged: usize,
+}
+
+/// A rotated-in data encryption key.
+struct TermKey {
+    term: u32,
+    key: EncryptionKey,
+    installed_at: DateTime<Utc>,
+}
+
+/// The root key and every rotated-in term key.
+struct Keyring {
+    root: EncryptionKey,
+    /// Term keys in ascending term order. Empty until the first rotation.
+    terms: Vec<TermKey>,
+}
+
+/// Keyring as persisted.
+#[derive(Serialize, Deserialize)]
+struct StoredKeyring {
+    terms: Vec<StoredTerm>,
+}
+
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct StoredTerm {
+    term: u32,
+    /// Base64 key bytes.
+    key: String,
+    #[zeroize(skip)]
+    installed_at: DateTime<Utc>,
+}
+
+impl Keyring {
+    fn status(&self) -> KeyStatus {
+        self.terms.last().map_or(
+            KeyStatus {
+                term: ROOT_TERM,
+                install_time: None,
+            },
+            |t| KeyStatus {
+                term: t.term,
+                install_time: Some(t.installed_at),
+            },
+        )
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, BarrierError> {
+        let start = Instant::now();
+        let result = self.encrypt_with_active(plaintext);
+        metrics::global()
+            .barrier_encrypt
+            .record(start, result.is_ok());
+        result
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, BarrierError> {
+        let start = Instant::now();
+        let result = self.decrypt_any_term(data);
+        metrics::global()
+            .barrier_decrypt
+            .record(start, result.is_ok());
+        result
+    }
+
+    /// The key of `term`, if the keyring holds it.
+    fn key(&self, term: u32) -> Option<&EncryptionKey> {
+        if term == ROOT_TERM {
+            return Some(&self.root);
+        }
+        self.terms.iter().find(|t| t.term == term).map(|t| &t.key)
+    }
+
+    /// The active term and its key.
+    fn active(&self) -> (u32, &EncryptionKey) {
+        self.terms
+            .last()
+            .map_or((ROOT_TERM, &self.root), |t| (t.term, &t.key))
+    }
+
+    fn encrypt_with_active(&self, plaintext: &[u8]) -> Result<Vec<u8>, BarrierError> {
+        let (term, key) = self.active();
+        let ciphertext = crypto::encrypt(key, plaintext)?;
+        let mut out = Vec::with_capacity(TERM_HEADER_LEN + ciphertext.len());
+        out.extend_from_slice(&TERM_MAGIC);
+        out.extend_from_slice(&term.to_be_bytes());
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    fn decrypt_any_term(&self, data: &[u8]) -> Result<Vec<u8>, BarrierError> {
+        if let Some((term, ciphertext)) = split_term(data)
+            && let Some(key) = self.key(term)
+            && let Ok(plaintext) = crypto::decrypt(key, ciphertext)
+        {
+            return Ok(plaintext);
+        }
+        // Untagged root-key ciphertext from before tagging; a random nonce
+        // that happens to look like a header lands here too.
+        Ok(crypto::decrypt(&self.root, data)?)
+    }
+
+    /// Whether `data` is already encrypted under the active term.
+    fn is_current(&self, data: &[u8]) -> bool {
+        let (active, _) = self.active();
+        split_term(data).is_some_and(|(term, _)| term == active)
+    }
+
+    fn to_stored(&self) -> Result<Zeroizing<Vec<u8>>, BarrierError> {
+        let stored = StoredKeyring {
+            terms: self
+                .terms
+                .iter()
+                .map(|t| StoredTerm {
+                    term: t.term,
+                    key: BASE64.encode(t.key.as_bytes()),
+                    installed_at: t.installed_at,
+                })
+                .collect(),
+        };
+        let json =
+            Zeroizing::new(
+                serde_json::to_vec(&stored).map_err(|e| BarrierError::Keyring {
+                    reason: e.to_string(),
+                })?,
+            );
+        Ok(Zeroizing::new(crypto::encrypt(&self.root, &json)?))
+    }
+
+    fn from_stored(root: EncryptionKey, encrypted: &[u8]) -> Result<Self, BarrierError> {
+        let json = Zeroizing::new(crypto::decrypt(&root, encrypted)?);
+        let stored: StoredKeyring =
+            serde_json::from_slice(&json).map_err(|e| BarrierError::Keyring {
+                reason: e.to_string(),
+            })?;
+        let mut terms = Vec::with_capacity(stored.terms.len());
+        for t in &stored.terms {
+            let bytes = Zeroizing::new(BASE64.decode(t.key.as_bytes()).map_err(|e| {
+                BarrierError::Keyring {
+                    reason: format!("term {}: {e}", t.term),
+                }
+            })?);
+            let key: [u8; 32] = bytes
+                .as_slice()
+                .try_into()
+                .map_err(|_| BarrierError::Keyring {
+                    reason: format!("term {}: key is not 32 bytes", t.term),
+                })?;
+            terms.push(TermKey {
+                term: t.term,
+                key: EncryptionKey::from_bytes(key),
+                installed_at: t.installed_at,
+            });
+        }
+        terms.sort_by_key(|t| t.term);
+        Ok(Self { root, terms })
+    }
+}
+
+/// Split a term header off `data`, if it has one.
+fn split_term(data: &[u8]) -> Option<(u32, &[u8])> {
+    let rest = data.strip_prefix(TERM_MAGIC.as_slice())?;
+    let (term, ciphertext) = rest.split_first_chunk::<4>()?;
+    Some((u32::from_be_bytes(*term), ciphertext))
+}
 
 /// The encryption barrier wrapping a storage backend.
 ///
@@ -28,7 +247,7 @@ use crate::error::BarrierError;
 /// [`BarrierError::Sealed`].
 pub struct Barrier {
     storage: Arc<dyn StorageBackend>,
-    key: RwLock<Option<EncryptionKey>>,
+    keyring: RwLock<Option<Keyring>>,
 }
 
 impl Barrier {
@@ -37,17 +256,170 @@ impl Barrier {
     pub fn new(storage: Arc<dyn StorageBackend>) -> Self {
         Self {
             storage,
-            key: RwLock::new(None),
+            keyring: RwLock::new(None),
         }
     }
 
     /// Unseal the barrier by providing the root encryption key.
     ///
-    /// After this call, all read/write operations will succeed (assuming the
-    /// underlying storage is healthy).
-    pub async fn unseal(&self, key: EncryptionKey) {
-        let mut guard = self.key.write().await;
-        *guard = Some(key);
+    /// Loads the keyring, if any term keys have been rotated in. After this
+    /// call, all read/write operations will succeed (assuming the underlying
+    /// storage is healthy).
+    ///
+    /// # Errors
+    ///
+    /// - [`BarrierError::Crypto`] if the keyring does not decrypt under `key`.
+    /// - [`BarrierError::Keyring`] if the stored keyring is malformed.
+    /// - [`BarrierError::Storage`] if the storage backend fails.
+    pub async fn unseal(&self, key: EncryptionKey) -> Result<(), BarrierError> {
+        let keyring = self.load_keyring(key).await?;
+        *self.keyring.write().await = Some(keyring);
+        Ok(())
+    }
+
+    /// Reload the keyring from storage, e.g. after a raw restore replaced it.
+    /// Does nothing while sealed.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`unseal`](Self::unseal); the in-memory keyring is kept on failure.
+    pub async fn reload_keyring(&self) -> Result<(), BarrierError> {
+        let mut guard = self.keyring.write().await;
+        if let Some(current) = guard.as_ref() {
+            let keyring = self.load_keyring(current.root.clone()).await?;
+            *guard = Some(keyring);
+        }
+        Ok(())
+    }
+
+    async fn load_keyring(&self, root: EncryptionKey) -> Result<Keyring, BarrierError> {
+        match self.storage.get(KEYRING_PATH).await? {
+            Some(encrypted) => Keyring::from_stored(root, &encrypted),
+            None => Ok(Keyring {
+                root,
+                terms: Vec::new(),
+            }),
+        }
+    }
+
+    /// The active key term and when it was installed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BarrierError::Sealed`] if the vault is sealed.
+    pub async fn key_status(&self) -> Result<KeyStatus, BarrierError> {
+        let guard = self.keyring.read().await;
+        guard
+            .as_ref()
+            .map(Keyring::status)
+            .ok_or(BarrierError::Sealed)
+    }
+
+    /// Install a new data encryption key as the active term.
+    ///
+    /// New writes use it; existing entries stay readable under their own
+    /// term until rewritten or [`reencrypt`](Self::reencrypt)ed.
+    ///
+    /// # Errors
+    ///
+    /// - [`BarrierError::Sealed`] if the vault is sealed.
+    /// - [`BarrierError::Keyring`] if the term counter is exhausted.
+    /// - [`BarrierError::Storage`] if the keyring cannot be persisted, in
+    ///   which case the active term is unchanged.
+    pub async fn rotate(&self) -> Result<KeyStatus, BarrierError> {
+        let mut guard = self.keyring.write().await;
+        let keyring = guard.as_mut().ok_or(BarrierError::Sealed)?;
+        let term = keyring
+            .status()
+            .term
+            .checked_add(1)
+            .ok_or_else(|| BarrierError::Keyring {
+                reason: "key term counter exhausted".to_owned(),
+            })?;
+
+        keyring.terms.push(TermKey {
+            term,
+            key: EncryptionKey::generate(),
+            installed_at: Utc::now(),
+        });
+        let persisted = match keyring.to_stored() {
+            Ok(stored) => self
+                .storage
+                .put(KEYRING_PATH, &stored)
+                .await
+                .map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = persisted {
+            keyring.terms.pop();
+            return Err(e);
+        }
+        Ok(keyring.status())
+    }
+
+    /// Count the entries under `prefix` by the key term they are tagged
+    /// with. Raw seal entries are skipped; nothing is decrypted.
+    ///
+    /// # Errors
+    ///
+    /// - [`BarrierError::Sealed`] if the vault is sealed.
+    /// - [`BarrierError::Storage`] if the storage backend fails.
+    pub async fn term_usage(&self, prefix: &str) -> Result<TermUsage, BarrierError> {
+        self.ensure_unsealed().await?;
+
+        let mut usage = TermUsage::default();
+        for key in self.storage.list(prefix).await? {
+            if key.starts_with(RAW_PREFIX) {
+                continue;
+            }
+            let Some(data) = self.storage.get(&key).await? else {
+                continue;
+            };
+            match split_term(&data) {
+                Some((term, _)) => *usage.terms.entry(term).or_default() += 1,
+                None => usage.untagged += 1,
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Rewrite every entry under `prefix` (`""` for all) not yet encrypted
+    /// under the active term, tagging untagged entries on the way.
+    ///
+    /// Returns the number of entries rewritten. Other barrier operations
+    /// wait until this finishes, so no concurrent write can be lost. Raw
+    /// seal entries are skipped, as are values that do not decrypt.
+    ///
+    /// # Errors
+    ///
+    /// - [`BarrierError::Sealed`] if the vault is sealed.
+    /// - [`BarrierError::Crypto`] if re-encryption fails.
+    /// - [`BarrierError::Storage`] if the storage backend fails.
+    pub async fn reencrypt(&self, prefix: &str) -> Result<usize, BarrierError> {
+        let guard = self.keyring.write().await;
+        let keyring = guard.as_ref().ok_or(BarrierError::Sealed)?;
+
+        let mut rewritten: usize = 0;
+        for key in self.storage.list(prefix).await? {
+            if key.starts_with(RAW_PREFIX) {
+                continue;
+            }
+            let Some(data) = self.storage.get(&key).await? else {
+                continue;
+            };
+            if keyring.is_current(&data) {
+                continue;
+            }
+            let Ok(plaintext) = keyring.decrypt(&data) else {
+                continue;
+            };
+            let plaintext = Zeroizing::new(plaintext);
+            self.storage
+                .put(&key, &keyring.encrypt(&plaintext)?)
+                .await?;
+            rewritten = rewritten.saturating_add(1);
+        }
+        Ok(rewritten)
     }
 
     /// Seal the barrier, zeroizing the root key from memory.
@@ -56,13 +428,13 @@ impl Barrier {
     /// The key is zeroized via its `ZeroizeOnDrop` implementation when the
     /// old `Option<EncryptionKey>` is replaced with `None`.
     pub async fn seal(&self) {
-        let mut guard = self.key.write().await;
+        let mut guard = self.keyring.write().await;
         *guard = None;
     }
 
     /// Check whether the barrier is currently unsealed.
     pub async fn is_unsealed(&self) -> bool {
-        self.key.read().await.is_some()
+        self.keyring.read().await.is_some()
     }
 
     /// Read a value from storage, decrypting it through the barrier.
@@ -75,13 +447,14 @@ impl Barrier {
     /// - [`BarrierError::Crypto`] if decryption fails.
     /// - [`BarrierError::Storage`] if the storage backend fails.
     pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BarrierError> {
-        let root_key = self.root_key().await?;
+        let guard = self.keyring.read().await;
+        let keyring = guard.as_ref().ok_or(BarrierError::Sealed)?;
 
         let encrypted = self.storage.get(key).await?;
         match encrypted {
             None => Ok(None),
             Some(ciphertext) => {
-                let plaintext = crypto::decrypt(&root_key, &ciphertext)?;
+                let plaintext = keyring.decrypt(&ciphertext)?;
                 Ok(Some(plaintext))
             }
         }
@@ -95,9 +468,10 @@ impl Barrier {
     /// - [`BarrierError::Crypto`] if encryption fails.
     /// - [`BarrierError::Storage`] if the storage backend fails.
     pub async fn put(&self, key: &str, value: &[u8]) -> Result<(), BarrierError> {
-        let root_key = self.root_key().await?;
+        let guard = self.keyring.read().await;
+        let keyring = guard.as_ref().ok_or(BarrierError::Sealed)?;
 
-        let ciphertext = crypto::encrypt(&root_key, value)?;
+        let ciphertext = keyring.encrypt(value)?;
         self.storage.put(key, &ciphertext).await?;
         Ok(())
     }
@@ -109,7 +483,7 @@ impl Barrier {
     /// - [`BarrierError::Sealed`] if the vault is sealed.
     /// - [`BarrierError::Storage`] if the storage backend fails.
     pub async fn delete(&self, key: &str) -> Result<(), BarrierError> {
-        let _root_key = self.root_key().await?;
+        self.ensure_unsealed().await?;
         self.storage.delete(key).await?;
         Ok(())
     }
@@ -124,7 +498,7 @@ impl Barrier {
     /// - [`BarrierError::Sealed`] if the vault is sealed.
     /// - [`BarrierError::Storage`] if the storage backend fails.
     pub async fn list(&self, prefix: &str) -> Result<Vec<String>, BarrierError> {
-        let _root_key = self.root_key().await?;
+        self.ensure_unsealed().await?;
         let keys = self.storage.list(prefix).await?;
         Ok(keys)
     }
@@ -136,7 +510,7 @@ impl Barrier {
     /// - [`BarrierError::Sealed`] if the vault is sealed.
     /// - [`BarrierError::Storage`] if the storage backend fails.
     pub async fn exists(&self, key: &str) -> Result<bool, BarrierError> {
-        let _root_key = self.root_key().await?;
+        self.ensure_unsealed().await?;
         let exists = self.storage.exists(key).await?;
         Ok(exists)
     }
@@ -177,14 +551,46 @@ impl Barrier {
         Ok(val)
     }
 
-    /// Clone the current root key (if unsealed).
+    /// Read every storage entry as ciphertext, as of a single point in time.
+    ///
+    /// Used for backups, which must not mix entries from before and after
+    /// a concurrent write. Only as consistent as the backend's
+    /// [`StorageBackend::snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// - [`BarrierError::Sealed`] if the vault is sealed.
+    /// - [`BarrierError::Storage`] if the storage backend fails.
+    pub async fn snapshot_raw(&self) -> Result<Vec<(String, Vec<u8>)>, BarrierError> {
+        self.ensure_unsealed().await?;
+        let entries = self.storage.snapshot().await?;
+        Ok(entries)
+    }
+
+    /// Delete a key from storage whether or not the barrier is unsealed.
+    ///
+    /// Counterpart to [`put_raw`](Barrier::put_raw) for keys that live
+    /// outside the encrypted keyspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BarrierError::Storage`] if the storage backend fails.
+    pub async fn delete_raw(&self, key: &str) -> Result<(), BarrierError> {
+        self.storage.delete(key).await?;
+        Ok(())
+    }
+
+    /// Fail unless the barrier is unsealed.
     ///
     /// # Errors
     ///
     /// Returns [`BarrierError::Sealed`] if the vault is sealed.
-    async fn root_key(&self) -> Result<EncryptionKey, BarrierError> {
-        let guard = self.key.read().await;
-        guard.clone().ok_or(BarrierError::Sealed)
+    async fn ensure_unsealed(&self) -> Result<(), BarrierError> {
+        if self.is_unsealed().await {
+            Ok(())
+        } else {
+            Err(BarrierError::Sealed)
+        }
     }
 }
 
@@ -248,7 +654,7 @@ mod tests {
     async fn unseal_then_put_get_roundtrip() {
         let barrier = make_barrier();
         let key = EncryptionKey::generate();
-        barrier.unseal(key).await;
+        barrier.unseal(key).await.unwrap();
 
         barrier.put("sys/test", b"hello world").await.unwrap();
         let val = barrier.get("sys/test").await.unwrap();
@@ -258,7 +664,7 @@ mod tests {
     #[tokio::test]
     async fn get_nonexistent_returns_none() {
         let barrier = make_barrier();
-        barrier.unseal(EncryptionKey::generate()).await;
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
 
         let val = barrier.get("does/not/exist").await.unwrap();
         assert_eq!(val, None);
@@ -267,7 +673,7 @@ mod tests {
     #[tokio::test]
     async fn delete_removes_key() {
         let barrier = make_barrier();
-        barrier.unseal(EncryptionKey::generate()).await;
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
 
         barrier.put("key", b"val").await.unwrap();
         barrier.delete("key").await.unwrap();
@@ -278,7 +684,7 @@ mod tests {
     #[tokio::test]
     async fn list_returns_matching_keys() {
         let barrier = make_barrier();
-        barrier.unseal(EncryptionKey::generate()).await;
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
 
         barrier.put("kv/data/a", b"1").await.unwrap();
         barrier.put("kv/data/b", b"2").await.unwrap();
@@ -291,7 +697,7 @@ mod tests {
     #[tokio::test]
     async fn exists_works() {
         let barrier = make_barrier();
-        barrier.unseal(EncryptionKey::generate()).await;
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
 
         assert!(!barrier.exists("key").await.unwrap());
         barrier.put("key", b"val").await.unwrap();
@@ -301,7 +707,7 @@ mod tests {
     #[tokio::test]
     async fn seal_zeroizes_and_rejects() {
         let barrier = make_barrier();
-        barrier.unseal(EncryptionKey::generate()).await;
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
 
         barrier.put("key", b"val").await.unwrap();
         barrier.seal().await;
@@ -316,12 +722,12 @@ mod tests {
         let barrier = Barrier::new(Arc::clone(&storage) as Arc<dyn StorageBackend>);
         let key = EncryptionKey::generate();
 
-        barrier.unseal(key.clone()).await;
+        barrier.unseal(key.clone()).await.unwrap();
         barrier.put("key", b"persistent").await.unwrap();
         barrier.seal().await;
 
         // Re-unseal with the same key — data should still be readable.
-        barrier.unseal(key).await;
+        barrier.unseal(key).await.unwrap();
         let val = barrier.get("key").await.unwrap();
         assert_eq!(val, Some(b"persistent".to_vec()));
     }
@@ -332,13 +738,13 @@ mod tests {
         let barrier = Barrier::new(Arc::clone(&storage) as Arc<dyn StorageBackend>);
 
         let key1 = EncryptionKey::generate();
-        barrier.unseal(key1).await;
+        barrier.unseal(key1).await.unwrap();
         barrier.put("key", b"secret").await.unwrap();
         barrier.seal().await;
 
         // Unseal with a different key — decryption should fail.
         let key2 = EncryptionKey::generate();
-        barrier.unseal(key2).await;
+        barrier.unseal(key2).await.unwrap();
         let result = barrier.get("key").await;
         assert!(matches!(result, Err(BarrierError::Crypto(_))));
     }
@@ -351,6 +757,9 @@ mod tests {
         barrier.put_raw("sys/root_key", raw_data).await.unwrap();
         let val = barrier.get_raw("sys/root_key").await.unwrap();
         assert_eq!(val, Some(raw_data.to_vec()));
+
+        barrier.delete_raw("sys/root_key").await.unwrap();
+        assert_eq!(barrier.get_raw("sys/root_key").await.unwrap(), None);
     }
 
     #[tokio::test]
@@ -358,10 +767,102 @@ mod tests {
         let barrier = make_barrier();
         assert!(!barrier.is_unsealed().await);
 
-        barrier.unseal(EncryptionKey::generate()).await;
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
         assert!(barrier.is_unsealed().await);
 
         barrier.seal().await;
         assert!(!barrier.is_unsealed().await);
     }
+
+    // ── key rotation ─────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn rotate_keeps_old_terms_readable_across_reseal() {
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Barrier::new(Arc::clone(&storage) as Arc<dyn StorageBackend>);
+        let key = EncryptionKey::generate();
+        barrier.unseal(key.clone()).await.unwrap();
+        assert_eq!(barrier.key_status().await.unwrap().term, 1);
+
+        barrier.put("old", b"term one").await.unwrap();
+        let status = barrier.rotate().await.unwrap();
+        assert_eq!(status.term, 2);
+        assert!(status.install_time.is_some());
+        barrier.put("new", b"term two").await.unwrap();
+        let raw = storage.get("old").await.unwrap().unwrap();
+        assert_eq!(split_term(&raw).unwrap().0, 1);
+        let raw = storage.get("new").await.unwrap().unwrap();
+        assert_eq!(split_term(&raw).unwrap().0, 2);
+
+        barrier.seal().await;
+        assert!(matches!(
+            barrier.key_status().await,
+            Err(BarrierError::Sealed)
+        ));
+        barrier.unseal(key).await.unwrap();
+        assert_eq!(barrier.key_status().await.unwrap().term, 2);
+        assert_eq!(barrier.get("old").await.unwrap().unwrap(), b"term one");
+        assert_eq!(barrier.get("new").await.unwrap().unwrap(), b"term two");
+
+        let err = barrier.unseal(EncryptionKey::generate()).await.unwrap_err();
+        assert!(matches!(err, BarrierError::Crypto(_)));
+    }
+
+    #[tokio::test]
+    async fn reencrypt_moves_entries_to_active_term_and_skips_raw() {
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Barrier::new(Arc::clone(&storage) as Arc<dyn StorageBackend>);
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+
+        barrier.put("a", b"1").await.unwrap();
+        barrier.put("b", b"2").await.unwrap();
+        barrier.put_raw("sys/seal/config", b"raw").await.unwrap();
+        barrier.rotate().await.unwrap();
+        barrier.put("c", b"3").await.unwrap();
+
+        assert_eq!(barrier.reencrypt("").await.unwrap(), 2);
+        for key in ["a", "b", "c"] {
+            let raw = storage.get(key).await.unwrap().unwrap();
+            assert_eq!(split_term(&raw).unwrap().0, 2);
+        }
+        assert_eq!(barrier.get("a").await.unwrap().unwrap(), b"1");
+        assert_eq!(
+            barrier.get_raw("sys/seal/config").await.unwrap().unwrap(),
+            b"raw"
+        );
+        assert_eq!(barrier.reencrypt("").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn reencrypt_prefix_and_term_usage() {
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Barrier::new(Arc::clone(&storage) as Arc<dyn StorageBackend>);
+        let key = EncryptionKey::generate();
+        barrier.unseal(key.clone()).await.unwrap();
+
+        // An entry written before values were tagged.
+        storage
+            .put("kv/legacy", &crypto::encrypt(&key, b"old").unwrap())
+            .await
+            .unwrap();
+        barrier.put("kv/a", b"1").await.unwrap();
+        barrier.put("transit/k", b"2").await.unwrap();
+        barrier.rotate().await.unwrap();
+        assert_eq!(barrier.get("kv/legacy").await.unwrap().unwrap(), b"old");
+
+        let usage = barrier.term_usage("").await.unwrap();
+        assert_eq!(usage.terms, BTreeMap::from([(1, 2)]));
+        assert_eq!(usage.untagged, 1);
+
+        assert_eq!(barrier.reencrypt("kv/").await.unwrap(), 2);
+        assert_eq!(
+            barrier.term_usage("kv/").await.unwrap().terms,
+            BTreeMap::from([(2, 2)])
+        );
+        let usage = barrier.term_usage("").await.unwrap();
+        assert_eq!(usage.terms, BTreeMap::from([(1, 1), (2, 2)]));
+        assert_eq!(usage.untagged, 0);
+        assert_eq!(barrier.get("kv/legacy").await.unwrap().unwrap(), b"old");
+        assert_eq!(barrier.get("transit/k").await.unwrap().unwrap(), b"2");
+    }
 }
@@ -0,0 +1,539 @@
+//! TLS certificate auth method for `ZVault`.
+//!
+//! Machines that already hold an X.509 client certificate — issued by an
+//! internal CA, a service mesh, or the PKI engine — log in by presenting it
+//! during the TLS handshake, with no shared secret. An operator defines
+//! roles, each naming the CA certificates it trusts and the common names,
+//! subject alternative names, and organizational units it accepts.
+//!
+//! # Security model
+//!
+//! - The TLS listener only proves the client holds the certificate's private
+//!   key. Whether the chain is trusted is decided here, per role, against
+//!   that role's CA certificates only; the system roots are never used.
+//! - The leaf must be valid now and, if it carries an extended key usage,
+//!   allow client authentication.
+//! - Every non-empty `allowed_*` list must match: a role bound to both
+//!   common names and OUs accepts only certificates matching both.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio_rustls::rustls::RootCertStore;
+use tokio_rustls::rustls::crypto::ring::default_provider;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, UnixTime};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use x509_cert::Certificate;
+use x509_cert::der::Decode;
+use x509_cert::der::asn1::ObjectIdentifier;
+use x509_cert::ext::pkix::SubjectAltName;
+use x509_cert::ext::pkix::name::GeneralName;
+
+use crate::barrier::Barrier;
+use crate::error::CertAuthError;
+use crate::identity::ALIAS_METADATA;
+use crate::token::{CreateTokenParams, TokenEntry, TokenStore};
+
+/// OID `2.5.4.3` (common name).
+const OID_COMMON_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.3");
+
+/// OID `2.5.4.11` (organizational unit).
+const OID_ORGANIZATIONAL_UNIT: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.11");
+
+/// A cert role: the CAs it trusts, the names it accepts, and the policies
+/// it grants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertRole {
+    /// Role name.
+    #[serde(default)]
+    pub name: String,
+    /// PEM-encoded CA certificates that client certificates must chain to.
+    pub certificate: String,
+    /// Accepted subject common names (globs).
+    #[serde(default)]
+    pub allowed_common_names: Vec<String>,
+    /// Accepted DNS subject alternative names (globs).
+    #[serde(default)]
+    pub allowed_dns_sans: Vec<String>,
+    /// Accepted email subject alternative names (globs).
+    #[serde(default)]
+    pub allowed_email_sans: Vec<String>,
+    /// Accepted URI subject alternative names (globs).
+    #[serde(default)]
+    pub allowed_uri_sans: Vec<String>,
+    /// Accepted subject organizational units (globs).
+    #[serde(default)]
+    pub allowed_organizational_units: Vec<String>,
+    /// Policies attached to issued tokens.
+    pub policies: Vec<String>,
+    /// Token TTL in seconds.
+    #[serde(default = "default_token_ttl")]
+    pub token_ttl_secs: i64,
+    /// Token max TTL in seconds.
+    #[serde(default = "default_token_max_ttl")]
+    pub token_max_ttl_secs: i64,
+}
+
+fn default_token_ttl() -> i64 {
+    3600
+}
+
+fn default_token_max_ttl() -> i64 {
+    86400
+}
+
+/// The names a certificate's subject and SAN extension carry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CertificateNames {
+    /// Subject common name.
+    pub common_name: Option<String>,
+    /// Subject organizational units.
+    pub organizational_units: Vec<String>,
+    /// DNS subject alternative names.
+    pub dns_sans: Vec<String>,
+    /// Email subject alternative names.
+    pub email_sans: Vec<String>,
+    /// URI subject alternative names.
+    pub uri_sans: Vec<String>,
+}
+
+/// The cert auth store.
+pub struct CertAuthStore {
+    barrier: Arc<Barrier>,
+    prefix: String,
+}
+
+impl CertAuthStore {
+    /// Create a new cert auth store.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>, prefix: String) -> Self {
+        Self { barrier, prefix }
+    }
+
+    fn role_key(&self, name: &str) -> String {
+        format!("{}roles/{}", self.prefix, name)
+    }
+
+    /// Create or replace a role.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CertAuthError::InvalidConfig` if the role has no name or
+    /// policies, or its CA certificates do not parse.
+    pub async fn write_role(&self, role: CertRole) -> Result<CertRole, CertAuthError> {
+        if role.name.is_empty() {
+            return Err(invalid_config("role name is required"));
+        }
+        if role.policies.is_empty() {
+            return Err(invalid_config("at least one policy is required"));
+        }
+        trust_anchors(&role.certificate)?;
+
+        let data = serde_json::to_vec(&role).map_err(|e| CertAuthError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(&self.role_key(&role.name), &data).await?;
+        Ok(role)
+    }
+
+    /// Get a role by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CertAuthError::RoleNotFound` if the role does not exist.
+    pub async fn get_role(&self, name: &str) -> Result<CertRole, CertAuthError> {
+        let data = self
+            .barrier
+            .get(&self.role_key(name))
+            .await?
+            .ok_or_else(|| CertAuthError::RoleNotFound {
+                name: name.to_owned(),
+            })?;
+        serde_json::from_slice(&data).map_err(|e| CertAuthError::Internal {
+            reason: format!("deserialization failed: {e}"),
+        })
+    }
+
+    /// Delete a role.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CertAuthError::Barrier` if the barrier is sealed.
+    pub async fn delete_role(&self, name: &str) -> Result<(), CertAuthError> {
+        self.barrier.delete(&self.role_key(name)).await?;
+        Ok(())
+    }
+
+    /// List all role names.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CertAuthError::Barrier` if the barrier is sealed.
+    pub async fn list_roles(&self) -> Result<Vec<String>, CertAuthError> {
+        let prefix = format!("{}roles/", self.prefix);
+        let keys = self.barrier.list(&prefix).await?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|k| k.strip_prefix(&prefix).map(String::from))
+            .collect())
+    }
+
+    /// Exchange a client certificate chain (leaf first) for a vault token.
+    /// With no `role_name`, the first role (by name) that accepts the chain
+    /// is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CertAuthError::RoleNotFound` for an unknown role, and
+    /// `CertAuthError::InvalidCertificate` if no chain was presented, it does
+    /// not chain to the role's CAs, or its names are not allowed.
+    pub async fn login(
+        &self,
+        role_name: Option<&str>,
+        chain: &[CertificateDer<'_>],
+        token_store: &TokenStore,
+    ) -> Result<(String, TokenEntry), CertAuthError> {
+        let Some((leaf, intermediates)) = chain.split_first() else {
+            return Err(invalid_certificate("no client certificate was presented"));
+        };
+        let names = CertificateNames::parse(leaf)
+            .ok_or_else(|| invalid_certificate("malformed client certificate"))?;
+
+        let role = if let Some(name) = role_name {
+            let role = self.get_role(name).await?;
+            verify_chain(&role, leaf, intermediates)?;
+            check_names(&role, &names)?;
+            role
+        } else {
+            let mut role_names = self.list_roles().await?;
+            role_names.sort();
+            let mut matched = None;
+            for name in role_names {
+                let role = self.get_role(&name).await?;
+                if verify_chain(&role, leaf, intermediates).is_ok()
+                    && check_names(&role, &names).is_ok()
+                {
+                    matched = Some(role);
+                    break;
+                }
+            }
+            matched.ok_or_else(|| invalid_certificate("no role accepts the client certificate"))?
+        };
+
+        let fingerprint = hex::encode(Sha256::digest(leaf.as_ref()));
+        let alias = names
+            .common_name
+            .clone()
+            .filter(|cn| !cn.is_empty())
+            .unwrap_or_else(|| fingerprint.clone());
+        let metadata = HashMap::from([
+            ("role".to_owned(), role.name.clone()),
+            (ALIAS_METADATA.to_owned(), alias.clone()),
+            ("fingerprint".to_owned(), fingerprint),
+        ]);
+
+        let plaintext_token = token_store
+            .create(CreateTokenParams {
+                policies: role.policies.clone(),
+                ttl: Some(chrono::Duration::seconds(role.token_ttl_secs)),
+                max_ttl: Some(chrono::Duration::seconds(role.token_max_ttl_secs)),
+                renewable: true,
+                parent_hash: None,
+                metadata,
+                display_name: format!("cert-{alias}"),
+                bound_cidrs: Vec::new(),
+            })
+            .await
+            .map_err(|e| CertAuthError::Internal {
+                reason: format!("token creation failed: {e}"),
+            })?;
+
+        let token_entry =
+            token_store
+                .lookup(&plaintext_token)
+                .await
+                .map_err(|e| CertAuthError::Internal {
+                    reason: format!("token lookup failed: {e}"),
+                })?;
+
+        Ok((plaintext_token, token_entry))
+    }
+}
+
+impl std::fmt::Debug for CertAuthStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CertAuthStore")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CertificateNames {
+    /// Extract the subject CN and OUs and the DNS, email, and URI SANs from
+    /// a DER certificate. Returns `None` if the DER is malformed.
+    #[must_use]
+    pub fn parse(der: &[u8]) -> Option<Self> {
+        let certificate = Certificate::from_der(der).ok()?;
+        let tbs = &certificate.tbs_certificate;
+
+        let mut names = Self::default();
+        for attribute in tbs.subject.0.iter().flat_map(|rdn| rdn.0.iter()) {
+            let value = String::from_utf8_lossy(attribute.value.value()).into_owned();
+            if attribute.oid == OID_COMMON_NAME {
+                names.common_name = Some(value);
+            } else if attribute.oid == OID_ORGANIZATIONAL_UNIT {
+                names.organizational_units.push(value);
+            }
+        }
+
+        if let Some((_, SubjectAltName(general_names))) = tbs.get::<SubjectAltName>().ok()? {
+            for name in general_names {
+                match name {
+                    GeneralName::Rfc822Name(email) => names.email_sans.push(email.to_string()),
+                    GeneralName::DnsName(dns) => names.dns_sans.push(dns.to_string()),
+                    GeneralName::UniformResourceIdentifier(uri) => {
+                        names.uri_sans.push(uri.to_string());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Some(names)
+    }
+}
+
+/// Parse a role's PEM CA certificates into a root store.
+fn trust_anchors(pem: &str) -> Result<RootCertStore, CertAuthError> {
+    let mut roots = RootCertStore::empty();
+    for der in CertificateDer::pem_slice_iter(pem.as_bytes()) {
+        let der = der.map_err(|e| CertAuthError::InvalidConfig {
+            reason: format!("invalid CA certificate PEM: {e}"),
+        })?;
+        roots.add(der).map_err(|e| CertAuthError::InvalidConfig {
+            reason: format!("invalid CA certificate: {e}"),
+        })?;
+    }
+    if roots.is_empty() {
+        return Err(invalid_config("at least one CA certificate is required"));
+    }
+    Ok(roots)
+}
+
+/// Require the chain to be valid now and to chain to one of `role`'s CAs.
+fn verify_chain(
+    role: &CertRole,
+    leaf: &CertificateDer<'_>,
+    intermediates: &[CertificateDer<'_>],
+) -> Result<(), CertAuthError> {
+    let roots = trust_anchors(&role.certificate)?;
+    let verifier =
+        WebPkiClientVerifier::builder_with_provider(Arc::new(roots), Arc::new(default_provider()))
+            .build()
+            .map_err(|e| CertAuthError::Internal {
+                reason: format!("failed to build certificate verifier: {e}"),
+            })?;
+    verifier
+        .verify_client_cert(leaf, intermediates, UnixTime::now())
+        .map_err(|e| CertAuthError::InvalidCertificate {
+            reason: e.to_string(),
+        })?;
+    Ok(())
+}
+
+/// Require every non-empty `allowed_*` list of `role` to match a name.
+fn check_names(role: &CertRole, names: &CertificateNames) -> Result<(), CertAuthError> {
+    let checks: [(&str, &[String], &[String]); 5] = [
+        (
+            "common name",
+            &role.allowed_common_names,
+            names.common_name.as_slice(),
+        ),
+        ("DNS SAN", &role.allowed_dns_sans, &names.dns_sans),
+        ("email SAN", &role.allowed_email_sans, &names.email_sans),
+        ("URI SAN", &role.allowed_uri_sans, &names.uri_sans),
+        (
+            "organizational unit",
+            &role.allowed_organizational_units,
+            &names.organizational_units,
+        ),
+    ];
+    for (what, allowed, values) in checks {
+        if allowed.is_empty() {
+            continue;
+        }
+        let matched = values.iter().any(|value| {
+            allowed
+                .iter()
+                .any(|pattern| glob_match::glob_match(pattern, value))
+        });
+        if !matched {
+            return Err(CertAuthError::InvalidCertificate {
+                reason: format!("{what} is not allowed by role '{}'", role.name),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn invalid_config(reason: &str) -> CertAuthError {
+    CertAuthError::InvalidConfig {
+        reason: reason.to_owned(),
+    }
+}
+
+fn invalid_certificate(reason: &str) -> CertAuthError {
+    CertAuthError::InvalidCertificate {
+        reason: reason.to_owned(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use rcgen::{
+        BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+        SanType,
+    };
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    struct Ca {
+        cert: rcgen::Certificate,
+        key: KeyPair,
+    }
+
+    fn ca(name: &str) -> Ca {
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let key = KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+        Ca { cert, key }
+    }
+
+    fn client(ca: &Ca, cn: &str, ou: &str, dns: &str) -> CertificateDer<'static> {
+        let mut params = CertificateParams::new(vec![dns.to_owned()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, cn);
+        params
+            .distinguished_name
+            .push(DnType::OrganizationalUnitName, ou);
+        params
+            .subject_alt_names
+            .push(SanType::Rfc822Name("ops@example.com".try_into().unwrap()));
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        let key = KeyPair::generate().unwrap();
+        params
+            .signed_by(&key, &ca.cert, &ca.key)
+            .unwrap()
+            .der()
+            .clone()
+    }
+
+    fn role(ca: &Ca) -> CertRole {
+        serde_json::from_value(serde_json::json!({
+            "name": "web",
+            "certificate": ca.cert.pem(),
+            "allowed_common_names": ["web-*"],
+            "allowed_organizational_units": ["platform"],
+            "policies": ["web"],
+        }))
+        .unwrap()
+    }
+
+    async fn fixture() -> (CertAuthStore, TokenStore) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        (
+            CertAuthStore::new(Arc::clone(&barrier), "sys/cert/".to_owned()),
+            TokenStore::new(barrier),
+        )
+    }
+
+    #[test]
+    fn parses_subject_and_sans() {
+        let ca = ca("Test CA");
+        let names =
+            CertificateNames::parse(&client(&ca, "web-1", "platform", "web.example.com")).unwrap();
+        assert_eq!(names.common_name.as_deref(), Some("web-1"));
+        assert_eq!(names.organizational_units, ["platform"]);
+        assert_eq!(names.dns_sans, ["web.example.com"]);
+        assert_eq!(names.email_sans, ["ops@example.com"]);
+        assert!(names.uri_sans.is_empty());
+
+        assert!(CertificateNames::parse(b"not a certificate").is_none());
+    }
+
+    #[tokio::test]
+    async fn login_with_trusted_certificate() {
+        let (store, tokens) = fixture().await;
+        let ca = ca("Test CA");
+        store.write_role(role(&ca)).await.unwrap();
+
+        let chain = [client(&ca, "web-1", "platform", "web.example.com")];
+        let (token, entry) = store.login(Some("web"), &chain, &tokens).await.unwrap();
+        assert!(!token.is_empty());
+        assert_eq!(entry.policies, ["web"]);
+        assert_eq!(entry.display_name, "cert-web-1");
+        assert_eq!(entry.metadata.get(ALIAS_METADATA).unwrap(), "web-1");
+
+        // Without a role name the matching role is found.
+        let (_, entry) = store.login(None, &chain, &tokens).await.unwrap();
+        assert_eq!(entry.metadata.get("role").unwrap(), "web");
+    }
+
+    #[tokio::test]
+    async fn rejects_untrusted_ca_and_disallowed_names() {
+        let (store, tokens) = fixture().await;
+        let ca_a = ca("CA A");
+        let ca_b = ca("CA B");
+        store.write_role(role(&ca_a)).await.unwrap();
+
+        let foreign = [client(&ca_b, "web-1", "platform", "web.example.com")];
+        let err = store.login(Some("web"), &foreign, &tokens).await;
+        assert!(matches!(err, Err(CertAuthError::InvalidCertificate { .. })));
+
+        let wrong_cn = [client(&ca_a, "db-1", "platform", "db.example.com")];
+        let err = store.login(Some("web"), &wrong_cn, &tokens).await;
+        assert!(matches!(err, Err(CertAuthError::InvalidCertificate { .. })));
+
+        let wrong_ou = [client(&ca_a, "web-1", "finance", "web.example.com")];
+        let err = store.login(None, &wrong_ou, &tokens).await;
+        assert!(matches!(err, Err(CertAuthError::InvalidCertificate { .. })));
+
+        let err = store.login(Some("web"), &[], &tokens).await;
+        assert!(matches!(err, Err(CertAuthError::InvalidCertificate { .. })));
+    }
+
+    #[tokio::test]
+    async fn role_validation() {
+        let (store, _) = fixture().await;
+        let ca = ca("Test CA");
+
+        let mut bad = role(&ca);
+        bad.certificate = "not a certificate".to_owned();
+        assert!(matches!(
+            store.write_role(bad).await,
+            Err(CertAuthError::InvalidConfig { .. })
+        ));
+
+        let mut bad = role(&ca);
+        bad.policies.clear();
+        assert!(matches!(
+            store.write_role(bad).await,
+            Err(CertAuthError::InvalidConfig { .. })
+        ));
+
+        store.write_role(role(&ca)).await.unwrap();
+        assert_eq!(store.list_roles().await.unwrap(), ["web"]);
+        store.delete_role("web").await.unwrap();
+        assert!(matches!(
+            store.get_role("web").await,
+            Err(CertAuthError::RoleNotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,643 @@
+//! Control groups: multi-party approval for sensitive paths.
+//!
+//! A policy rule with a `control_group` block (see
+//! [`crate::policy::ControlGroup`]) makes [`PolicyStore::check`] refuse the
+//! requests it grants with [`PolicyError::ControlGroupRequired`]. The server
+//! then parks the request — method, path, and body — under a random
+//! accessor and hands the accessor back to the caller.
+//!
+//! Approvers call [`ControlGroupStore::authorize`] with the accessor. Once
+//! every control group that applied has enough distinct approvers, the
+//! original caller claims the request with [`ControlGroupStore::claim`] and
+//! the server replays it inside [`run_approved`], where policy checks skip
+//! control groups. A claimed request is deleted, so each approval runs the
+//! request once.
+//!
+//! Requests are stored through the barrier at `sys/control-groups/{accessor}`
+//! and expire [`REQUEST_TTL`] after they are parked.
+//!
+//! [`PolicyStore::check`]: crate::policy::PolicyStore::check
+//! [`PolicyError::ControlGroupRequired`]: crate::error::PolicyError::ControlGroupRequired
+
+use std::future::Future;
+use std::sync::Arc;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::access_request::Actor;
+use crate::barrier::Barrier;
+use crate::error::ControlGroupError;
+use crate::policy::ControlGroup;
+
+/// Storage prefix for parked requests.
+const REQUEST_PREFIX: &str = "sys/control-groups/";
+
+/// How long a parked request waits for approval and retrieval.
+pub const REQUEST_TTL: chrono::Duration = chrono::Duration::hours(24);
+
+tokio::task_local! {
+    /// Set while an approved request is being replayed.
+    static APPROVED: ();
+}
+
+/// Run `future` as an approved request: control groups do not apply to
+/// policy checks made inside it.
+pub async fn run_approved<F: Future>(future: F) -> F::Output {
+    APPROVED.scope((), future).await
+}
+
+/// Whether the current task is replaying an approved request.
+#[must_use]
+pub fn is_approved() -> bool {
+    APPROVED.try_with(|()| ()).is_ok()
+}
+
+/// One approver's sign-off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Authorization {
+    /// Entity ID or token-derived client ID of the approver.
+    pub approver: String,
+    /// Display name of the approver's token.
+    pub approver_name: String,
+    /// Policies the approver held when signing off.
+    pub policies: Vec<String>,
+    /// Identity entity of the approver, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>,
+    /// When the approval was given.
+    pub approved_at: DateTime<Utc>,
+}
+
+/// A request parked until its control groups are satisfied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlGroupRequest {
+    /// Random handle given to the requester and approvers.
+    pub accessor: String,
+    /// Entity ID or token-derived client ID of the requester.
+    pub requester: String,
+    /// Display name of the requester's token.
+    pub requester_name: String,
+    /// HTTP method of the parked request.
+    pub method: String,
+    /// Request path, including any query string.
+    pub path: String,
+    /// `Content-Type` of the parked body, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Parked request body, base64-encoded.
+    #[serde(default)]
+    pub body: String,
+    /// Control groups that must all be satisfied.
+    pub control_groups: Vec<ControlGroup>,
+    /// Approvals collected so far.
+    pub authorizations: Vec<Authorization>,
+    /// When the request was parked.
+    pub created_at: DateTime<Utc>,
+    /// When the request is discarded.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ControlGroupRequest {
+    /// Approvals counting towards `group`.
+    #[must_use]
+    pub fn approvals_for(&self, group: &ControlGroup) -> usize {
+        self.authorizations
+            .iter()
+            .filter(|a| group.accepts(&a.policies, a.entity_id.as_deref()))
+            .count()
+    }
+
+    /// Whether every control group has enough approvals.
+    #[must_use]
+    pub fn is_approved(&self) -> bool {
+        self.control_groups
+            .iter()
+            .all(|g| self.approvals_for(g) >= usize::try_from(g.approvals).unwrap_or(usize::MAX))
+    }
+
+    /// Decode the parked request body.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ControlGroupError::Internal`] if the stored body is corrupt.
+    pub fn body_bytes(&self) -> Result<Vec<u8>, ControlGroupError> {
+        STANDARD
+            .decode(&self.body)
+            .map_err(|e| ControlGroupError::Internal {
+                reason: format!("corrupt body for {}: {e}", self.accessor),
+            })
+    }
+}
+
+/// A request to park, as seen by the server.
+#[derive(Debug, Clone)]
+pub struct ParkedRequest {
+    /// HTTP method.
+    pub method: String,
+    /// Request path, including any query string.
+    pub path: String,
+    /// `Content-Type` header, if any.
+    pub content_type: Option<String>,
+    /// Raw request body.
+    pub body: Vec<u8>,
+    /// Control groups the policy check reported.
+    pub control_groups: Vec<ControlGroup>,
+}
+
+/// Stores parked requests and their approvals.
+pub struct ControlGroupStore {
+    barrier: Arc<Barrier>,
+    /// Serializes approvals and claims so a request runs once.
+    lock: Mutex<()>,
+}
+
+impl ControlGroupStore {
+    /// Create a store backed by the barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            barrier,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Park `request` on behalf of `requester`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ControlGroupError::Barrier`] if storage fails.
+    pub async fn park(
+        &self,
+        requester: Actor<'_>,
+        request: ParkedRequest,
+    ) -> Result<ControlGroupRequest, ControlGroupError> {
+        let now = Utc::now();
+        let parked = ControlGroupRequest {
+            accessor: uuid::Uuid::new_v4().to_string(),
+            requester: requester.requester_id(),
+            requester_name: requester.display_name.to_owned(),
+            method: request.method,
+            path: request.path,
+            content_type: request.content_type,
+            body: STANDARD.encode(&request.body),
+            control_groups: request.control_groups,
+            authorizations: Vec::new(),
+            created_at: now,
+            expires_at: now + REQUEST_TTL,
+        };
+        self.save(&parked).await?;
+
+        info!(accessor = %parked.accessor, path = %parked.path, "request parked for control group approval");
+        Ok(parked)
+    }
+
+    /// Read a parked request by accessor.
+    ///
+    /// # Errors
+    ///
+    /// - [`ControlGroupError::NotFound`] if no such request exists or it
+    ///   has expired.
+    /// - [`ControlGroupError::Barrier`] if storage fails.
+    pub async fn get(&self, accessor: &str) -> Result<ControlGroupRequest, ControlGroupError> {
+        let not_found = || ControlGroupError::NotFound {
+            accessor: accessor.to_owned(),
+        };
+        let key = format!("{REQUEST_PREFIX}{accessor}");
+        let data = self.barrier.get(&key).await?.ok_or_else(not_found)?;
+        let request: ControlGroupRequest =
+            serde_json::from_slice(&data).map_err(|e| ControlGroupError::Internal {
+                reason: format!("corrupt control group request {accessor}: {e}"),
+            })?;
+        if request.expires_at <= Utc::now() {
+            self.barrier.delete(&key).await?;
+            return Err(not_found());
+        }
+        Ok(request)
+    }
+
+    /// Read a parked request on behalf of its requester or an approver.
+    ///
+    /// # Errors
+    ///
+    /// - [`ControlGroupError::NotAuthorized`] if `actor` is neither.
+    /// - [`ControlGroupError::NotFound`] / [`ControlGroupError::Barrier`] as
+    ///   for [`Self::get`].
+    pub async fn status(
+        &self,
+        accessor: &str,
+        actor: Actor<'_>,
+        policies: &[String],
+    ) -> Result<ControlGroupRequest, ControlGroupError> {
+        let request = self.get(accessor).await?;
+        let is_approver = request
+            .control_groups
+            .iter()
+            .any(|g| g.accepts(policies, actor.entity_id));
+        if request.requester != actor.requester_id() && !is_approver {
+            return Err(ControlGroupError::NotAuthorized {
+                accessor: accessor.to_owned(),
+            });
+        }
+        Ok(request)
+    }
+
+    /// Record `approver`'s sign-off. Approving twice has no further effect.
+    ///
+    /// # Errors
+    ///
+    /// - [`ControlGroupError::SelfApproval`] if `approver` is the requester.
+    /// - [`ControlGroupError::NotAuthorized`] if `approver` qualifies for
+    ///   none of the request's control groups.
+    /// - [`ControlGroupError::NotFound`] / [`ControlGroupError::Barrier`] as
+    ///   for [`Self::get`].
+    pub async fn authorize(
+        &self,
+        accessor: &str,
+        approver: Actor<'_>,
+        policies: &[String],
+    ) -> Result<ControlGroupRequest, ControlGroupError> {
+        let _guard = self.lock.lock().await;
+        let mut request = self.get(accessor).await?;
+        let approver_id = approver.requester_id();
+        if request.requester == approver_id {
+            return Err(ControlGroupError::SelfApproval);
+        }
+        if !request
+            .control_groups
+            .iter()
+            .any(|g| g.accepts(policies, approver.entity_id))
+        {
+            return Err(ControlGroupError::NotAuthorized {
+                accessor: accessor.to_owned(),
+            });
+        }
+        if request
+            .authorizations
+            .iter()
+            .any(|a| a.approver == approver_id)
+        {
+            return Ok(request);
+        }
+
+        request.authorizations.push(Authorization {
+            approver: approver_id,
+            approver_name: approver.display_name.to_owned(),
+            policies: policies.to_vec(),
+            entity_id: approver.entity_id.map(str::to_owned),
+            approved_at: Utc::now(),
+        });
+        self.save(&request).await?;
+
+        info!(
+            accessor = %accessor,
+            approvals = request.authorizations.len(),
+            approved = request.is_approved(),
+            "control group request authorized"
+        );
+        Ok(request)
+    }
+
+    /// Take an approved request for replay by its requester.
+    ///
+    /// `method` and `path` (without query string) must match the parked
+    /// request. The request is deleted, so it can only be claimed once.
+    ///
+    /// # Errors
+    ///
+    /// - [`ControlGroupError::NotAuthorized`] if `requester` did not park it.
+    /// - [`ControlGroupError::Mismatch`] if `method` or `path` differ.
+    /// - [`ControlGroupError::NotApproved`] if approvals are missing.
+    /// - [`ControlGroupError::NotFound`] / [`ControlGroupError::Barrier`] as
+    ///   for [`Self::get`].
+    pub async fn claim(
+        &self,
+        accessor: &str,
+        requester: Actor<'_>,
+        method: &str,
+        path: &str,
+    ) -> Result<ControlGroupRequest, ControlGroupError> {
+        let _guard = self.lock.lock().await;
+        let request = self.get(accessor).await?;
+        if request.requester != requester.requester_id() {
+            return Err(ControlGroupError::NotAuthorized {
+                accessor: accessor.to_owned(),
+            });
+        }
+        let parked_path = request.path.split('?').next().unwrap_or_default();
+        if !request.method.eq_ignore_ascii_case(method) || parked_path != path {
+            return Err(ControlGroupError::Mismatch {
+                accessor: accessor.to_owned(),
+                method: request.method,
+                path: parked_path.to_owned(),
+            });
+        }
+        if !request.is_approved() {
+            return Err(ControlGroupError::NotApproved {
+                accessor: accessor.to_owned(),
+            });
+        }
+        self.barrier
+            .delete(&format!("{REQUEST_PREFIX}{accessor}"))
+            .await?;
+
+        info!(accessor = %accessor, path = %request.path, "control group request claimed");
+        Ok(request)
+    }
+
+    /// Delete every request that expired at or before `now`.
+    ///
+    /// Returns the number of requests removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ControlGroupError::Barrier`] if storage fails.
+    pub async fn expire(&self, now: DateTime<Utc>) -> Result<usize, ControlGroupError> {
+        let _guard = self.lock.lock().await;
+        let mut expired = 0;
+        for key in self.barrier.list(REQUEST_PREFIX).await? {
+            let Some(data) = self.barrier.get(&key).await? else {
+                continue;
+            };
+            let lapsed = serde_json::from_slice::<ControlGroupRequest>(&data)
+                .map_or(true, |r| r.expires_at <= now);
+            if lapsed {
+                self.barrier.delete(&key).await?;
+                expired += 1;
+            }
+        }
+        if expired > 0 {
+            info!(count = expired, "control group requests expired");
+        }
+        Ok(expired)
+    }
+
+    async fn save(&self, request: &ControlGroupRequest) -> Result<(), ControlGroupError> {
+        let bytes = serde_json::to_vec(request).map_err(|e| ControlGroupError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier
+            .put(&format!("{REQUEST_PREFIX}{}", request.accessor), &bytes)
+            .await?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ControlGroupStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ControlGroupStore").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use crate::error::PolicyError;
+    use crate::policy::{Capability, Policy, PolicyRule, PolicyStore};
+
+    async fn make_barrier() -> Arc<Barrier> {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        barrier
+    }
+
+    fn actor<'a>(token_hash: &'a str, entity_id: Option<&'a str>) -> Actor<'a> {
+        Actor {
+            entity_id,
+            token_hash,
+            display_name: token_hash,
+        }
+    }
+
+    fn group() -> ControlGroup {
+        ControlGroup {
+            approvals: 2,
+            approver_policies: vec!["security".to_owned()],
+            approver_identities: vec!["entity-cto".to_owned()],
+        }
+    }
+
+    async fn parked(store: &ControlGroupStore) -> ControlGroupRequest {
+        store
+            .park(
+                actor("requester", None),
+                ParkedRequest {
+                    method: "POST".to_owned(),
+                    path: "/v1/secret/prod/root?x=1".to_owned(),
+                    content_type: Some("application/json".to_owned()),
+                    body: br#"{"password":"hunter2"}"#.to_vec(),
+                    control_groups: vec![group()],
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn policy_check_requires_approval_outside_replay() {
+        let policies = PolicyStore::new(make_barrier().await);
+        policies
+            .put(&Policy {
+                name: "break-glass".to_owned(),
+                rules: vec![PolicyRule {
+                    path: "secret/data/prod/**".to_owned(),
+                    capabilities: vec![Capability::Read],
+                    control_group: Some(group()),
+                    mfa_methods: Vec::new(),
+                }],
+            })
+            .await
+            .unwrap();
+        let names = vec!["break-glass".to_owned()];
+
+        let err = policies
+            .check(&names, "secret/data/prod/root", &Capability::Read)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, PolicyError::ControlGroupRequired { ref control_groups, .. } if control_groups == &[group()])
+        );
+        assert!(
+            run_approved(policies.check(&names, "secret/data/prod/root", &Capability::Read))
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            run_approved(policies.check(&names, "secret/data/prod/root", &Capability::Delete))
+                .await,
+            Err(PolicyError::Denied { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn control_groups_found_for_any_capability_before_the_request_runs() {
+        let policies = PolicyStore::new(make_barrier().await);
+        for (name, path, capabilities, control_group) in [
+            (
+                "break-glass",
+                "secret/data/prod/**",
+                vec![Capability::Read, Capability::List],
+                Some(group()),
+            ),
+            (
+                "reader",
+                "secret/data/prod/**",
+                vec![Capability::Read],
+                None,
+            ),
+            (
+                "locked",
+                "secret/data/prod/locked",
+                vec![Capability::Deny],
+                None,
+            ),
+        ] {
+            policies
+                .put(&Policy {
+                    name: name.to_owned(),
+                    rules: vec![PolicyRule {
+                        path: path.to_owned(),
+                        capabilities,
+                        control_group,
+                        mfa_methods: Vec::new(),
+                    }],
+                })
+                .await
+                .unwrap();
+        }
+        let names = vec![
+            "break-glass".to_owned(),
+            "reader".to_owned(),
+            "locked".to_owned(),
+        ];
+        let read = [Capability::Read, Capability::List, Capability::Sudo];
+
+        let groups = policies
+            .control_groups(&names, "secret/data/prod/root", &read)
+            .await
+            .unwrap();
+        assert_eq!(groups, vec![group()]);
+        for (path, capabilities) in [
+            (
+                "secret/data/prod/root",
+                &[Capability::Create, Capability::Update][..],
+            ),
+            ("secret/data/prod/locked", &read[..]),
+            ("secret/data/dev/app", &read[..]),
+        ] {
+            let groups = policies
+                .control_groups(&names, path, capabilities)
+                .await
+                .unwrap();
+            assert!(groups.is_empty(), "{path}");
+        }
+        let approved =
+            run_approved(policies.control_groups(&names, "secret/data/prod/root", &read)).await;
+        assert!(approved.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn approvals_count_distinct_qualifying_approvers() {
+        let store = ControlGroupStore::new(make_barrier().await);
+        let request = parked(&store).await;
+        let accessor = request.accessor.as_str();
+        let security = vec!["security".to_owned()];
+
+        assert!(matches!(
+            store
+                .authorize(accessor, actor("requester", None), &security)
+                .await,
+            Err(ControlGroupError::SelfApproval)
+        ));
+        assert!(matches!(
+            store
+                .authorize(accessor, actor("dev", None), &["dev".to_owned()])
+                .await,
+            Err(ControlGroupError::NotAuthorized { .. })
+        ));
+
+        let once = store
+            .authorize(accessor, actor("alice", None), &security)
+            .await
+            .unwrap();
+        let twice = store
+            .authorize(accessor, actor("alice", None), &security)
+            .await
+            .unwrap();
+        assert_eq!(once.authorizations.len(), 1);
+        assert_eq!(twice.authorizations.len(), 1);
+        assert!(!twice.is_approved());
+
+        let done = store
+            .authorize(accessor, actor("cto", Some("entity-cto")), &[])
+            .await
+            .unwrap();
+        assert!(done.is_approved());
+    }
+
+    #[tokio::test]
+    async fn claim_checks_requester_request_and_approval() {
+        let store = ControlGroupStore::new(make_barrier().await);
+        let request = parked(&store).await;
+        let accessor = request.accessor.as_str();
+        let requester = actor("requester", None);
+        let path = "/v1/secret/prod/root";
+
+        assert!(matches!(
+            store.claim(accessor, requester, "POST", path).await,
+            Err(ControlGroupError::NotApproved { .. })
+        ));
+        for approver in ["alice", "bob"] {
+            store
+                .authorize(accessor, actor(approver, None), &["security".to_owned()])
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            store
+                .claim(accessor, actor("alice", None), "POST", path)
+                .await,
+            Err(ControlGroupError::NotAuthorized { .. })
+        ));
+        assert!(matches!(
+            store.claim(accessor, requester, "GET", path).await,
+            Err(ControlGroupError::Mismatch { .. })
+        ));
+
+        let claimed = store
+            .claim(accessor, requester, "post", path)
+            .await
+            .unwrap();
+        assert_eq!(claimed.path, "/v1/secret/prod/root?x=1");
+        assert_eq!(claimed.body_bytes().unwrap(), br#"{"password":"hunter2"}"#);
+        assert!(matches!(
+            store.claim(accessor, requester, "POST", path).await,
+            Err(ControlGroupError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn expired_requests_are_discarded() {
+        let store = ControlGroupStore::new(make_barrier().await);
+        let request = parked(&store).await;
+
+        let removed = store
+            .expire(request.expires_at - chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(removed, 0);
+        assert_eq!(store.expire(request.expires_at).await.unwrap(), 1);
+        assert!(matches!(
+            store.get(&request.accessor).await,
+            Err(ControlGroupError::NotFound { .. })
+        ));
+    }
+}
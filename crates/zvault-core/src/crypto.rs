@@ -1,7 +1,8 @@
 //! Cryptographic primitives for `ZVault`.
 //!
 //! Provides AES-256-GCM authenticated encryption, HKDF-SHA256 key derivation,
-//! and zeroize-on-drop key newtypes. All key material is automatically cleared
+//! AES key wrap with padding (RFC 5649, for importing keys), and
+//! zeroize-on-drop key newtypes. All key material is automatically cleared
 //! from memory when dropped.
 //!
 //! # Security model
@@ -13,6 +14,10 @@
 
 use std::fmt;
 
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::typenum::U16;
+use aes::cipher::{BlockCipher, BlockDecrypt, BlockEncrypt};
+use aes::{Aes128, Aes192, Aes256};
 use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use hkdf::Hkdf;
@@ -27,6 +32,9 @@ const MIN_CIPHERTEXT_LEN: usize = 12 + 16;
 /// Nonce length for AES-256-GCM (96 bits).
 const NONCE_LEN: usize = 12;
 
+/// Alternative initial value prefix for key wrap with padding (RFC 5649 §3).
+const KWP_AIV: [u8; 4] = [0xA6, 0x59, 0x59, 0xA6];
+
 /// A 256-bit encryption key that is zeroized on drop.
 ///
 /// Used as the root key and for per-engine derived keys. The inner bytes
@@ -144,6 +152,168 @@ pub fn derive_key(
     Ok(EncryptionKey::from_bytes(derived))
 }
 
+/// Wrap `key` under `kek` with AES key wrap with padding (RFC 5649).
+///
+/// `kek` may be 16, 24, or 32 bytes. This is the wrapping used by the transit
+/// BYOK import flow, matching what cloud KMS and `HashiCorp` tooling emit.
+///
+/// # Errors
+///
+/// Returns [`CryptoError::Encryption`] if the KEK length is invalid or `key`
+/// is empty.
+pub fn wrap_key(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    match kek.len() {
+        16 => kwp_wrap(&Aes128::new(GenericArray::from_slice(kek)), key),
+        24 => kwp_wrap(&Aes192::new(GenericArray::from_slice(kek)), key),
+        32 => kwp_wrap(&Aes256::new(GenericArray::from_slice(kek)), key),
+        n => Err(CryptoError::Encryption {
+            reason: format!("invalid key-encryption key length {n}"),
+        }),
+    }
+}
+
+/// Unwrap a key produced by [`wrap_key`].
+///
+/// # Errors
+///
+/// Returns [`CryptoError::Decryption`] if the KEK length is invalid or the
+/// integrity check fails (wrong KEK or corrupted input).
+pub fn unwrap_key(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    match kek.len() {
+        16 => kwp_unwrap(&Aes128::new(GenericArray::from_slice(kek)), wrapped),
+        24 => kwp_unwrap(&Aes192::new(GenericArray::from_slice(kek)), wrapped),
+        32 => kwp_unwrap(&Aes256::new(GenericArray::from_slice(kek)), wrapped),
+        n => Err(CryptoError::Decryption {
+            reason: format!("invalid key-encryption key length {n}"),
+        }),
+    }
+}
+
+fn kwp_wrap<C>(cipher: &C, key: &[u8]) -> Result<Vec<u8>, CryptoError>
+where
+    C: BlockCipher<BlockSize = U16> + BlockEncrypt,
+{
+    let mli = u32::try_from(key.len())
+        .ok()
+        .filter(|&len| len > 0)
+        .ok_or_else(|| CryptoError::Encryption {
+            reason: "key to wrap must be 1 to 2^32-1 bytes".to_owned(),
+        })?;
+
+    let mut aiv = [0u8; 8];
+    aiv[..4].copy_from_slice(&KWP_AIV);
+    aiv[4..].copy_from_slice(&mli.to_be_bytes());
+
+    let mut padded = key.to_vec();
+    padded.resize(key.len().div_ceil(8) * 8, 0);
+
+    if padded.len() == 8 {
+        let mut block = GenericArray::<u8, U16>::default();
+        block[..8].copy_from_slice(&aiv);
+        block[8..].copy_from_slice(&padded);
+        cipher.encrypt_block(&mut block);
+        return Ok(block.to_vec());
+    }
+
+    let mut a = aiv;
+    let mut r: Vec<[u8; 8]> = padded
+        .chunks_exact(8)
+        .map(|c| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(c);
+            b
+        })
+        .collect();
+    let n = r.len() as u64;
+    let mut block = GenericArray::<u8, U16>::default();
+    for j in 0..6u64 {
+        for (i, ri) in (1u64..).zip(r.iter_mut()) {
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(ri);
+            cipher.encrypt_block(&mut block);
+            let t = (n * j + i).to_be_bytes();
+            for k in 0..8 {
+                a[k] = block[k] ^ t[k];
+            }
+            ri.copy_from_slice(&block[8..]);
+        }
+    }
+
+    let mut out = Vec::with_capacity(8 + padded.len());
+    out.extend_from_slice(&a);
+    for ri in &r {
+        out.extend_from_slice(ri);
+    }
+    padded.zeroize();
+    r.zeroize();
+    Ok(out)
+}
+
+fn kwp_unwrap<C>(cipher: &C, wrapped: &[u8]) -> Result<Vec<u8>, CryptoError>
+where
+    C: BlockCipher<BlockSize = U16> + BlockDecrypt,
+{
+    let invalid = || CryptoError::Decryption {
+        reason: "key unwrap integrity check failed".to_owned(),
+    };
+    if wrapped.len() < 16 || wrapped.len() % 8 != 0 {
+        return Err(invalid());
+    }
+
+    let mut a = [0u8; 8];
+    let mut padded = Vec::with_capacity(wrapped.len() - 8);
+    let mut block = GenericArray::<u8, U16>::default();
+
+    if wrapped.len() == 16 {
+        block.copy_from_slice(wrapped);
+        cipher.decrypt_block(&mut block);
+        a.copy_from_slice(&block[..8]);
+        padded.extend_from_slice(&block[8..]);
+    } else {
+        a.copy_from_slice(&wrapped[..8]);
+        let mut r: Vec<[u8; 8]> = wrapped[8..]
+            .chunks_exact(8)
+            .map(|c| {
+                let mut b = [0u8; 8];
+                b.copy_from_slice(c);
+                b
+            })
+            .collect();
+        let n = r.len() as u64;
+        for j in (0..6u64).rev() {
+            for (i, ri) in r.iter_mut().enumerate().rev() {
+                let t = (n * j + i as u64 + 1).to_be_bytes();
+                for k in 0..8 {
+                    block[k] = a[k] ^ t[k];
+                }
+                block[8..].copy_from_slice(ri);
+                cipher.decrypt_block(&mut block);
+                a.copy_from_slice(&block[..8]);
+                ri.copy_from_slice(&block[8..]);
+            }
+        }
+        for ri in &r {
+            padded.extend_from_slice(ri);
+        }
+        r.zeroize();
+    }
+    block.as_mut_slice().zeroize();
+
+    let mut mli_bytes = [0u8; 4];
+    mli_bytes.copy_from_slice(&a[4..]);
+    let mli = usize::try_from(u32::from_be_bytes(mli_bytes)).map_err(|_| invalid())?;
+    let valid = a[..4] == KWP_AIV
+        && mli <= padded.len()
+        && mli > padded.len().saturating_sub(8)
+        && padded[mli..].iter().all(|&b| b == 0);
+    if !valid {
+        padded.zeroize();
+        return Err(invalid());
+    }
+    padded.truncate(mli);
+    Ok(padded)
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -261,4 +431,38 @@ mod tests {
         let decrypted = decrypt(&derived, &ciphertext).unwrap();
         assert_eq!(plaintext.as_slice(), decrypted.as_slice());
     }
+
+    // RFC 5649 §6 test vectors (192-bit KEK).
+    const KWP_KEK: &str = "5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8";
+
+    #[test]
+    fn wrap_key_matches_rfc5649_vectors() {
+        let kek = hex::decode(KWP_KEK).unwrap();
+
+        let key = hex::decode("c37b7e6492584340bed12207808941155068f738").unwrap();
+        let wrapped = wrap_key(&kek, &key).unwrap();
+        assert_eq!(
+            hex::encode(&wrapped),
+            "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a"
+        );
+        assert_eq!(unwrap_key(&kek, &wrapped).unwrap(), key);
+
+        let key = hex::decode("466f7250617369").unwrap();
+        let wrapped = wrap_key(&kek, &key).unwrap();
+        assert_eq!(hex::encode(&wrapped), "afbeb0f07dfbf5419200f2ccb50bb24f");
+        assert_eq!(unwrap_key(&kek, &wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn unwrap_key_rejects_wrong_kek() {
+        let kek = EncryptionKey::generate();
+        let other = EncryptionKey::generate();
+        let target = EncryptionKey::generate();
+        let wrapped = wrap_key(kek.as_bytes(), target.as_bytes()).unwrap();
+        assert_eq!(
+            unwrap_key(kek.as_bytes(), &wrapped).unwrap(),
+            target.as_bytes()
+        );
+        assert!(unwrap_key(other.as_bytes(), &wrapped).is_err());
+    }
 }
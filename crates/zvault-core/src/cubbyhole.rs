@@ -0,0 +1,155 @@
+//! Per-token cubbyhole storage for `ZVault`.
+//!
+//! A cubbyhole is a private storage area owned by a single token. Nothing but
+//! the owning token's hash addresses it, and it is destroyed when the token
+//! is revoked. Tokens reach their own cubbyhole through the built-in
+//! `cubbyhole/` mount — scratch space for a workflow's short-lived secrets —
+//! and response wrapping keeps each wrapped response in the wrapping token's
+//! cubbyhole.
+
+use std::sync::Arc;
+
+use crate::barrier::Barrier;
+use crate::error::BarrierError;
+
+/// Storage prefix for cubbyhole entries, keyed by owning token hash.
+pub(crate) const CUBBYHOLE_PREFIX: &str = "sys/cubbyhole/";
+
+/// Mount path of the built-in cubbyhole engine, which cannot be unmounted.
+pub const CUBBYHOLE_MOUNT: &str = "cubbyhole/";
+
+/// Token-scoped key/value storage through the barrier.
+pub struct Cubbyhole {
+    barrier: Arc<Barrier>,
+}
+
+impl Cubbyhole {
+    /// Create a cubbyhole store backed by the given barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self { barrier }
+    }
+
+    fn key(token_hash: &str, key: &str) -> String {
+        format!("{CUBBYHOLE_PREFIX}{token_hash}/{key}")
+    }
+
+    /// Store `value` under `key` in the cubbyhole of `token_hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BarrierError`] if the barrier is sealed or storage fails.
+    pub async fn put(&self, token_hash: &str, key: &str, value: &[u8]) -> Result<(), BarrierError> {
+        self.barrier.put(&Self::key(token_hash, key), value).await
+    }
+
+    /// Read `key` from the cubbyhole of `token_hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BarrierError`] if the barrier is sealed or storage fails.
+    pub async fn get(&self, token_hash: &str, key: &str) -> Result<Option<Vec<u8>>, BarrierError> {
+        self.barrier.get(&Self::key(token_hash, key)).await
+    }
+
+    /// Delete `key` from the cubbyhole of `token_hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BarrierError`] if the barrier is sealed or storage fails.
+    pub async fn delete(&self, token_hash: &str, key: &str) -> Result<(), BarrierError> {
+        self.barrier.delete(&Self::key(token_hash, key)).await
+    }
+
+    /// List keys under `prefix` in the cubbyhole of `token_hash`, relative
+    /// to `prefix`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BarrierError`] if the barrier is sealed or storage fails.
+    pub async fn list(&self, token_hash: &str, prefix: &str) -> Result<Vec<String>, BarrierError> {
+        let storage_prefix = Self::key(token_hash, prefix);
+        let keys = self.barrier.list(&storage_prefix).await?;
+        Ok(keys
+            .iter()
+            .filter_map(|k| k.strip_prefix(&storage_prefix).map(String::from))
+            .collect())
+    }
+
+    /// Delete every entry in the cubbyhole of `token_hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BarrierError`] if the barrier is sealed or storage fails.
+    pub async fn destroy(&self, token_hash: &str) -> Result<(), BarrierError> {
+        let keys = self
+            .barrier
+            .list(&format!("{CUBBYHOLE_PREFIX}{token_hash}/"))
+            .await?;
+        for key in &keys {
+            self.barrier.delete(key).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use crate::token::{CreateTokenParams, TokenStore, hash_token};
+
+    #[tokio::test]
+    async fn scoped_to_token_and_destroyed_on_revoke() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let cubbyhole = Cubbyhole::new(Arc::clone(&barrier));
+        let tokens = TokenStore::new(barrier);
+
+        let mut owners = Vec::new();
+        for _ in 0..2 {
+            let token = tokens
+                .create(CreateTokenParams {
+                    policies: vec!["default".to_owned()],
+                    ttl: None,
+                    max_ttl: None,
+                    renewable: false,
+                    parent_hash: None,
+                    metadata: HashMap::new(),
+                    display_name: "test".to_owned(),
+                    bound_cidrs: Vec::new(),
+                })
+                .await
+                .unwrap();
+            owners.push(token);
+        }
+        let (a, b) = (hash_token(&owners[0]), hash_token(&owners[1]));
+
+        cubbyhole.put(&a, "ci/deploy-key", b"k1").await.unwrap();
+        cubbyhole.put(&a, "ci/scratch", b"k2").await.unwrap();
+        cubbyhole.put(&b, "ci/deploy-key", b"other").await.unwrap();
+
+        assert_eq!(
+            cubbyhole.get(&a, "ci/deploy-key").await.unwrap().unwrap(),
+            b"k1"
+        );
+        let mut keys = cubbyhole.list(&a, "ci/").await.unwrap();
+        keys.sort();
+        assert_eq!(keys, ["deploy-key", "scratch"]);
+
+        cubbyhole.delete(&a, "ci/scratch").await.unwrap();
+        assert!(cubbyhole.get(&a, "ci/scratch").await.unwrap().is_none());
+
+        tokens.revoke(&owners[0]).await.unwrap();
+        assert!(cubbyhole.list(&a, "").await.unwrap().is_empty());
+        assert_eq!(
+            cubbyhole.get(&b, "ci/deploy-key").await.unwrap().unwrap(),
+            b"other"
+        );
+    }
+}
@@ -3,15 +3,40 @@
 //! Secrets engines are mounted at path prefixes and handle read/write/delete
 //! operations for secrets. The KV v2 engine stores versioned key-value pairs
 //! with metadata tracking.
+//!
+//! Each KV secret also carries operator-editable metadata: free-form
+//! `custom_metadata` tags (owner, team, ticket, ...), its version limit,
+//! whether writes must be check-and-set, and `delete_version_after`. A
+//! check-and-set write names the version it expects to replace (`0` for a new
+//! secret) and is rejected if another writer got there first.
+//!
+//! Versions older than `delete_version_after` are unreadable at once and are
+//! purged by [`KvEngine::sweep_expired_versions`], which the server runs on
+//! the lease expiry schedule.
 
+use std::any::Any;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
 
 use crate::barrier::Barrier;
 use crate::error::EngineError;
+use crate::registry::Engine;
+
+/// Versions kept per secret unless its metadata says otherwise.
+const DEFAULT_MAX_VERSIONS: u32 = 10;
+
+/// Most custom metadata entries a secret may carry.
+const MAX_CUSTOM_METADATA_KEYS: usize = 64;
+
+/// Longest custom metadata key, in bytes.
+const MAX_CUSTOM_METADATA_KEY_LEN: usize = 128;
+
+/// Longest custom metadata value, in bytes.
+const MAX_CUSTOM_METADATA_VALUE_LEN: usize = 512;
 
 /// A request to a secrets engine.
 #[derive(Debug, Clone)]
@@ -58,10 +83,44 @@ pub struct EngineResponse {
 /// Storage layout under the engine's mount prefix:
 /// - `data/<path>` — versioned secret data
 /// - `metadata/<path>` — version metadata
+/// - `expiring/<path>` — index of secrets with `delete_version_after` set
 pub struct KvEngine {
     barrier: Arc<Barrier>,
     /// Mount path prefix (e.g., `kv/default/`).
     prefix: String,
+    /// Mount-level options.
+    config: KvMountConfig,
+    /// Serializes read-modify-write updates so check-and-set is atomic.
+    write_lock: Mutex<()>,
+}
+
+/// Mount-level options for a KV engine, stored in the mount entry's `config`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KvMountConfig {
+    /// Attach a lease to every read. When the lease expires or is revoked,
+    /// a `lease.expired` / `lease.revoked` event tells consumers to re-fetch.
+    #[serde(default)]
+    pub lease_reads: bool,
+    /// TTL of read leases in seconds.
+    #[serde(default = "default_read_lease_ttl")]
+    pub read_lease_ttl_secs: i64,
+    /// Require check-and-set on every write to the mount.
+    #[serde(default)]
+    pub cas_required: bool,
+}
+
+fn default_read_lease_ttl() -> i64 {
+    3600
+}
+
+impl Default for KvMountConfig {
+    fn default() -> Self {
+        Self {
+            lease_reads: false,
+            read_lease_ttl_secs: default_read_lease_ttl(),
+            cas_required: false,
+        }
+    }
 }
 
 /// Stored secret with version history.
@@ -73,6 +132,44 @@ struct KvSecret {
     current_version: u32,
     /// Maximum number of versions to keep (0 = unlimited).
     max_versions: u32,
+    /// Operator-defined tags such as `owner` and `team`.
+    #[serde(default)]
+    custom_metadata: HashMap<String, String>,
+    /// Reject writes that do not supply the current version.
+    #[serde(default)]
+    cas_required: bool,
+    /// Versions older than this many seconds are deleted (0 = never).
+    #[serde(default)]
+    delete_version_after_secs: i64,
+}
+
+impl KvSecret {
+    fn new() -> Self {
+        Self {
+            versions: HashMap::new(),
+            current_version: 0,
+            max_versions: DEFAULT_MAX_VERSIONS,
+            custom_metadata: HashMap::new(),
+            cas_required: false,
+            delete_version_after_secs: 0,
+        }
+    }
+
+    /// Versions created at or before this instant have expired.
+    fn expiry_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (self.delete_version_after_secs > 0)
+            .then(|| now - Duration::seconds(self.delete_version_after_secs))
+    }
+
+    /// Drop the oldest versions beyond `max_versions`.
+    fn prune(&mut self) {
+        if self.max_versions > 0 {
+            while self.versions.len() > self.max_versions as usize {
+                let min_version = self.versions.keys().copied().min().unwrap_or(0);
+                self.versions.remove(&min_version);
+            }
+        }
+    }
 }
 
 /// A single version of a secret.
@@ -99,13 +196,90 @@ pub struct KvMetadata {
     pub version_count: u32,
     /// Maximum versions allowed.
     pub max_versions: u32,
+    /// Operator-defined tags such as `owner` and `team`.
+    pub custom_metadata: HashMap<String, String>,
+    /// Whether writes must supply the current version.
+    pub cas_required: bool,
+    /// Versions older than this many seconds are deleted (0 = never).
+    pub delete_version_after_secs: i64,
+}
+
+impl KvMetadata {
+    fn from_secret(secret: &KvSecret) -> Self {
+        let created_at = secret
+            .versions
+            .values()
+            .map(|v| v.created_at)
+            .min()
+            .unwrap_or_else(Utc::now);
+
+        let updated_at = secret
+            .versions
+            .values()
+            .map(|v| v.created_at)
+            .max()
+            .unwrap_or_else(Utc::now);
+
+        Self {
+            current_version: secret.current_version,
+            created_at,
+            updated_at,
+            #[allow(clippy::cast_possible_truncation)]
+            version_count: secret.versions.len() as u32, // max_versions caps at u32
+            max_versions: secret.max_versions,
+            custom_metadata: secret.custom_metadata.clone(),
+            cas_required: secret.cas_required,
+            delete_version_after_secs: secret.delete_version_after_secs,
+        }
+    }
+}
+
+/// Changes to a secret's metadata. Fields left as `None` are unchanged.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KvMetadataUpdate {
+    /// Replaces all custom metadata.
+    #[serde(default)]
+    pub custom_metadata: Option<HashMap<String, String>>,
+    /// New version limit (0 = unlimited); older versions are pruned.
+    #[serde(default)]
+    pub max_versions: Option<u32>,
+    /// Whether writes must supply the current version.
+    #[serde(default)]
+    pub cas_required: Option<bool>,
+    /// Delete versions after this many seconds (0 = never).
+    #[serde(default)]
+    pub delete_version_after_secs: Option<i64>,
 }
 
 impl KvEngine {
     /// Create a new KV v2 engine with the given barrier and mount prefix.
     #[must_use]
     pub fn new(barrier: Arc<Barrier>, prefix: String) -> Self {
-        Self { barrier, prefix }
+        Self {
+            barrier,
+            prefix,
+            config: KvMountConfig::default(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Set mount-level options for this engine.
+    #[must_use]
+    pub fn with_config(mut self, config: KvMountConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Mount-level options for this engine.
+    #[must_use]
+    pub fn config(&self) -> &KvMountConfig {
+        &self.config
+    }
+
+    /// Storage prefix this engine reads and writes under.
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
     }
 
     /// Handle a request to this engine.
@@ -116,7 +290,7 @@ impl KvEngine {
     pub async fn handle(&self, req: &EngineRequest) -> Result<EngineResponse, EngineError> {
         match req.operation {
             Operation::Read => self.read(&req.path).await,
-            Operation::Write => self.write(&req.path, req.data.clone()).await,
+            Operation::Write => self.write_cas(&req.path, req.data.clone(), None).await,
             Operation::Delete => self.delete(&req.path).await,
             Operation::List => self.list(&req.path).await,
         }
@@ -141,14 +315,18 @@ impl KvEngine {
                         reason: format!("deserialization failed: {e}"),
                     })?;
 
-                let version = secret
-                    .versions
-                    .get(&secret.current_version)
-                    .ok_or_else(|| EngineError::Internal {
-                        reason: format!("version {} missing", secret.current_version),
-                    })?;
+                // No version yet (metadata set before the first write), or
+                // the current one was purged by `delete_version_after`.
+                let Some(version) = secret.versions.get(&secret.current_version) else {
+                    return Err(EngineError::NotFound {
+                        path: path.to_owned(),
+                    });
+                };
 
-                if version.deleted_at.is_some() {
+                let expired = secret
+                    .expiry_cutoff(Utc::now())
+                    .is_some_and(|cutoff| version.created_at <= cutoff);
+                if version.deleted_at.is_some() || expired {
                     return Err(EngineError::NotFound {
                         path: path.to_owned(),
                     });
@@ -159,6 +337,7 @@ impl KvEngine {
                     "metadata": {
                         "version": secret.current_version,
                         "created_time": version.created_at.to_rfc3339(),
+                        "custom_metadata": secret.custom_metadata,
                     }
                 });
 
@@ -173,10 +352,22 @@ impl KvEngine {
     }
 
     /// Write a new version of a secret.
-    async fn write(
+    ///
+    /// With `cas` set, the write only succeeds if the secret's current
+    /// version equals it (`0` means the secret must not exist yet). Secrets
+    /// and mounts with `cas_required` reject writes without it.
+    ///
+    /// # Errors
+    ///
+    /// - [`EngineError::CasMismatch`] if `cas` is not the current version.
+    /// - [`EngineError::InvalidRequest`] if check-and-set is required but
+    ///   `cas` is `None`.
+    /// - [`EngineError::Barrier`] on storage failures.
+    pub async fn write_cas(
         &self,
         path: &str,
         data: Option<serde_json::Value>,
+        cas: Option<u32>,
     ) -> Result<EngineResponse, EngineError> {
         let kv_data: HashMap<String, serde_json::Value> = match data {
             Some(serde_json::Value::Object(map)) => map.into_iter().collect(),
@@ -188,27 +379,26 @@ impl KvEngine {
             None => HashMap::new(),
         };
 
-        let storage_key = format!("{}data/{}", self.prefix, path);
         let now = Utc::now();
 
-        // Load existing secret or create new.
-        let mut secret = match self
-            .barrier
-            .get(&storage_key)
-            .await
-            .map_err(EngineError::Barrier)?
-        {
-            Some(bytes) => {
-                serde_json::from_slice::<KvSecret>(&bytes).map_err(|e| EngineError::Internal {
-                    reason: format!("deserialization failed: {e}"),
-                })?
+        let _guard = self.write_lock.lock().await;
+        let mut secret = self.load(path).await?.unwrap_or_else(KvSecret::new);
+
+        match cas {
+            Some(cas) if cas != secret.current_version => {
+                return Err(EngineError::CasMismatch {
+                    path: path.to_owned(),
+                    cas,
+                    current: secret.current_version,
+                });
             }
-            None => KvSecret {
-                versions: HashMap::new(),
-                current_version: 0,
-                max_versions: 10,
-            },
-        };
+            None if self.config.cas_required || secret.cas_required => {
+                return Err(EngineError::InvalidRequest {
+                    reason: format!("check-and-set version is required to write '{path}'"),
+                });
+            }
+            _ => {}
+        }
 
         // Increment version.
         secret.current_version = secret.current_version.saturating_add(1);
@@ -219,22 +409,8 @@ impl KvEngine {
             deleted_at: None,
         };
         secret.versions.insert(secret.current_version, version);
-
-        // Prune old versions if max_versions is set.
-        if secret.max_versions > 0 {
-            while secret.versions.len() > secret.max_versions as usize {
-                let min_version = secret.versions.keys().copied().min().unwrap_or(0);
-                secret.versions.remove(&min_version);
-            }
-        }
-
-        let bytes = serde_json::to_vec(&secret).map_err(|e| EngineError::Internal {
-            reason: format!("serialization failed: {e}"),
-        })?;
-        self.barrier
-            .put(&storage_key, &bytes)
-            .await
-            .map_err(EngineError::Barrier)?;
+        secret.prune();
+        self.store(path, &secret).await?;
 
         let response_data = serde_json::json!({
             "version": secret.current_version,
@@ -251,6 +427,7 @@ impl KvEngine {
 
     /// Soft-delete the latest version of a secret.
     async fn delete(&self, path: &str) -> Result<EngineResponse, EngineError> {
+        let _guard = self.write_lock.lock().await;
         let storage_key = format!("{}data/{}", self.prefix, path);
         let data = self
             .barrier
@@ -290,6 +467,158 @@ impl KvEngine {
         }
     }
 
+    /// Permanently remove a secret and all of its versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::NotFound`] if the secret doesn't exist.
+    pub async fn destroy(&self, path: &str) -> Result<(), EngineError> {
+        let _guard = self.write_lock.lock().await;
+        let storage_key = format!("{}data/{}", self.prefix, path);
+        let exists = self
+            .barrier
+            .get(&storage_key)
+            .await
+            .map_err(EngineError::Barrier)?
+            .is_some();
+        if !exists {
+            return Err(EngineError::NotFound {
+                path: path.to_owned(),
+            });
+        }
+
+        self.barrier
+            .delete(&storage_key)
+            .await
+            .map_err(EngineError::Barrier)?;
+        self.barrier
+            .delete(&self.expiry_index_key(path))
+            .await
+            .map_err(EngineError::Barrier)
+    }
+
+    /// Source and destination paths of copying `from` to `to`, sorted.
+    ///
+    /// A `from` ending in `/` names every secret under it, each going to
+    /// the same relative path under `to`. Otherwise `from` is one secret,
+    /// which goes to `to`, or into it when `to` ends in `/`.
+    ///
+    /// # Errors
+    ///
+    /// - [`EngineError::InvalidRequest`] if source and destination overlap.
+    /// - [`EngineError::NotFound`] if there is nothing to copy.
+    pub async fn copy_targets(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<(String, String)>, EngineError> {
+        let mut pairs = Vec::new();
+        if from.ends_with('/') {
+            let to = if to.is_empty() || to.ends_with('/') {
+                to.to_owned()
+            } else {
+                format!("{to}/")
+            };
+            if to.starts_with(from) || from.starts_with(&to) {
+                return Err(EngineError::InvalidRequest {
+                    reason: format!("cannot copy '{from}' to '{to}': the two overlap"),
+                });
+            }
+            let storage_prefix = format!("{}data/{from}", self.prefix);
+            for key in self.barrier.list(&storage_prefix).await? {
+                if let Some(rest) = key.strip_prefix(&storage_prefix) {
+                    pairs.push((format!("{from}{rest}"), format!("{to}{rest}")));
+                }
+            }
+        } else {
+            let to = match to.strip_suffix('/') {
+                Some(dir) => {
+                    let name = from.rsplit('/').next().unwrap_or(from);
+                    if dir.is_empty() {
+                        name.to_owned()
+                    } else {
+                        format!("{dir}/{name}")
+                    }
+                }
+                None => to.to_owned(),
+            };
+            if to == from {
+                return Err(EngineError::InvalidRequest {
+                    reason: format!("cannot copy '{from}' onto itself"),
+                });
+            }
+            if self.load(from).await?.is_some() {
+                pairs.push((from.to_owned(), to));
+            }
+        }
+        if pairs.is_empty() {
+            return Err(EngineError::NotFound {
+                path: from.to_owned(),
+            });
+        }
+        pairs.sort();
+        Ok(pairs)
+    }
+
+    /// Copy secrets with their whole version history and metadata, deleting
+    /// each source afterwards when `remove_source` is set (a move).
+    ///
+    /// Nothing is written unless every source exists and, without
+    /// `overwrite`, no destination does.
+    ///
+    /// # Errors
+    ///
+    /// - [`EngineError::NotFound`] if a source is gone.
+    /// - [`EngineError::AlreadyExists`] if a destination exists and
+    ///   `overwrite` is not set.
+    /// - [`EngineError::Barrier`] on storage failures.
+    pub async fn copy(
+        &self,
+        pairs: &[(String, String)],
+        overwrite: bool,
+        remove_source: bool,
+    ) -> Result<(), EngineError> {
+        let _guard = self.write_lock.lock().await;
+        let mut records = Vec::with_capacity(pairs.len());
+        for (from, to) in pairs {
+            let source_key = format!("{}data/{}", self.prefix, from);
+            let bytes = self
+                .barrier
+                .get(&source_key)
+                .await?
+                .ok_or_else(|| EngineError::NotFound { path: from.clone() })?;
+            let target_key = format!("{}data/{}", self.prefix, to);
+            if !overwrite && self.barrier.get(&target_key).await?.is_some() {
+                return Err(EngineError::AlreadyExists { path: to.clone() });
+            }
+            let expiring = self
+                .barrier
+                .get(&self.expiry_index_key(from))
+                .await?
+                .is_some();
+            records.push((bytes, expiring));
+        }
+
+        for ((from, to), (bytes, expiring)) in pairs.iter().zip(records) {
+            self.barrier
+                .put(&format!("{}data/{}", self.prefix, to), &bytes)
+                .await?;
+            let index_key = self.expiry_index_key(to);
+            if expiring {
+                self.barrier.put(&index_key, b"").await?;
+            } else {
+                self.barrier.delete(&index_key).await?;
+            }
+            if remove_source {
+                self.barrier
+                    .delete(&format!("{}data/{}", self.prefix, from))
+                    .await?;
+                self.barrier.delete(&self.expiry_index_key(from)).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// List keys under a prefix.
     async fn list(&self, path: &str) -> Result<EngineResponse, EngineError> {
         let storage_prefix = format!("{}data/{}", self.prefix, path);
@@ -318,43 +647,187 @@ impl KvEngine {
     ///
     /// Returns [`EngineError::NotFound`] if the secret doesn't exist.
     pub async fn metadata(&self, path: &str) -> Result<KvMetadata, EngineError> {
+        let secret = self
+            .load(path)
+            .await?
+            .ok_or_else(|| EngineError::NotFound {
+                path: path.to_owned(),
+            })?;
+        Ok(KvMetadata::from_secret(&secret))
+    }
+
+    /// Update a secret's metadata, creating an empty secret if there is none
+    /// so that tags and check-and-set can be set before the first write.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::InvalidRequest`] if the custom metadata
+    /// exceeds its limits, or [`EngineError::Barrier`] on storage failures.
+    pub async fn update_metadata(
+        &self,
+        path: &str,
+        update: KvMetadataUpdate,
+    ) -> Result<KvMetadata, EngineError> {
+        if let Some(custom) = &update.custom_metadata {
+            validate_custom_metadata(custom)?;
+        }
+        if update
+            .delete_version_after_secs
+            .is_some_and(|secs| secs < 0)
+        {
+            return Err(EngineError::InvalidRequest {
+                reason: "delete_version_after must not be negative".to_owned(),
+            });
+        }
+
+        let _guard = self.write_lock.lock().await;
+        let mut secret = self.load(path).await?.unwrap_or_else(KvSecret::new);
+        if let Some(custom) = update.custom_metadata {
+            secret.custom_metadata = custom;
+        }
+        if let Some(max_versions) = update.max_versions {
+            secret.max_versions = max_versions;
+            secret.prune();
+        }
+        if let Some(cas_required) = update.cas_required {
+            secret.cas_required = cas_required;
+        }
+        if let Some(secs) = update.delete_version_after_secs {
+            secret.delete_version_after_secs = secs;
+        }
+        self.store(path, &secret).await?;
+
+        let index_key = self.expiry_index_key(path);
+        if secret.delete_version_after_secs > 0 {
+            self.barrier.put(&index_key, b"").await?;
+        } else {
+            self.barrier.delete(&index_key).await?;
+        }
+        Ok(KvMetadata::from_secret(&secret))
+    }
+
+    /// Permanently delete every version older than its secret's
+    /// `delete_version_after`, returning how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::Barrier`] if the vault is sealed or storage
+    /// fails.
+    pub async fn sweep_expired_versions(&self, now: DateTime<Utc>) -> Result<usize, EngineError> {
+        let index_prefix = self.expiry_index_key("");
+        let mut removed = 0usize;
+        for index_key in self.barrier.list(&index_prefix).await? {
+            let Some(path) = index_key.strip_prefix(&index_prefix) else {
+                continue;
+            };
+            let _guard = self.write_lock.lock().await;
+            let Some(mut secret) = self.load(path).await? else {
+                self.barrier.delete(&index_key).await?;
+                continue;
+            };
+            let Some(cutoff) = secret.expiry_cutoff(now) else {
+                self.barrier.delete(&index_key).await?;
+                continue;
+            };
+            let before = secret.versions.len();
+            secret.versions.retain(|_, v| v.created_at > cutoff);
+            let purged = before - secret.versions.len();
+            if purged > 0 {
+                self.store(path, &secret).await?;
+                removed = removed.saturating_add(purged);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Key marking `path` as having versions that expire.
+    fn expiry_index_key(&self, path: &str) -> String {
+        format!("{}expiring/{}", self.prefix, path)
+    }
+
+    /// Load the stored secret at `path`, if any.
+    async fn load(&self, path: &str) -> Result<Option<KvSecret>, EngineError> {
         let storage_key = format!("{}data/{}", self.prefix, path);
-        let data = self
+        let Some(bytes) = self
             .barrier
             .get(&storage_key)
             .await
             .map_err(EngineError::Barrier)?
-            .ok_or_else(|| EngineError::NotFound {
-                path: path.to_owned(),
-            })?;
-
-        let secret: KvSecret =
-            serde_json::from_slice(&data).map_err(|e| EngineError::Internal {
+        else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| EngineError::Internal {
                 reason: format!("deserialization failed: {e}"),
-            })?;
+            })
+    }
 
-        let created_at = secret
-            .versions
-            .values()
-            .map(|v| v.created_at)
-            .min()
-            .unwrap_or_else(Utc::now);
+    /// Persist `secret` at `path`.
+    async fn store(&self, path: &str, secret: &KvSecret) -> Result<(), EngineError> {
+        let storage_key = format!("{}data/{}", self.prefix, path);
+        let bytes = serde_json::to_vec(secret).map_err(|e| EngineError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier
+            .put(&storage_key, &bytes)
+            .await
+            .map_err(EngineError::Barrier)
+    }
+}
 
-        let updated_at = secret
-            .versions
-            .values()
-            .map(|v| v.created_at)
-            .max()
-            .unwrap_or_else(Utc::now);
+/// Enforce the size limits on custom metadata.
+fn validate_custom_metadata(custom: &HashMap<String, String>) -> Result<(), EngineError> {
+    let invalid = |reason: String| Err(EngineError::InvalidRequest { reason });
+    if custom.len() > MAX_CUSTOM_METADATA_KEYS {
+        return invalid(format!(
+            "custom metadata is limited to {MAX_CUSTOM_METADATA_KEYS} keys"
+        ));
+    }
+    for (key, value) in custom {
+        if key.is_empty() || key.len() > MAX_CUSTOM_METADATA_KEY_LEN {
+            return invalid(format!(
+                "custom metadata keys must be 1 to {MAX_CUSTOM_METADATA_KEY_LEN} bytes"
+            ));
+        }
+        if value.len() > MAX_CUSTOM_METADATA_VALUE_LEN {
+            return invalid(format!(
+                "custom metadata value for '{key}' exceeds {MAX_CUSTOM_METADATA_VALUE_LEN} bytes"
+            ));
+        }
+    }
+    Ok(())
+}
 
-        Ok(KvMetadata {
-            current_version: secret.current_version,
-            created_at,
-            updated_at,
-            #[allow(clippy::cast_possible_truncation)]
-            version_count: secret.versions.len() as u32, // max_versions caps at u32
-            max_versions: secret.max_versions,
-        })
+/// Strip the `{"data": {...}}` envelope around a secret's fields.
+///
+/// Clients that write `{"data": {...}}` bodies store that envelope, so a
+/// read returns it nested. While the map's only key is an object `data`,
+/// it is replaced by that object.
+#[must_use]
+pub fn unwrap_data_envelope(
+    mut data: serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    while data.len() == 1 && data.get("data").is_some_and(serde_json::Value::is_object) {
+        let Some(serde_json::Value::Object(inner)) = data.remove("data") else {
+            break;
+        };
+        data = inner;
+    }
+    data
+}
+
+impl Engine for KvEngine {
+    fn engine_type(&self) -> &'static str {
+        "kv"
+    }
+
+    fn storage_prefix(&self) -> String {
+        self.prefix.clone()
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
     }
 }
 
@@ -365,3 +838,267 @@ impl std::fmt::Debug for KvEngine {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use serde_json::json;
+    use zvault_storage::MemoryBackend;
+
+    async fn engine() -> KvEngine {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        KvEngine::new(barrier, "kv/test/".to_owned())
+    }
+
+    #[test]
+    fn data_envelopes_are_unwrapped() {
+        let nested = json!({"data": {"data": {"password": "hunter2"}}});
+        let serde_json::Value::Object(nested) = nested else {
+            unreachable!()
+        };
+        assert_eq!(
+            serde_json::Value::Object(unwrap_data_envelope(nested)),
+            json!({"password": "hunter2"})
+        );
+
+        // A `data` field next to other fields is secret data, not an envelope.
+        let fields = json!({"data": {"a": 1}, "b": 2});
+        let serde_json::Value::Object(fields) = fields else {
+            unreachable!()
+        };
+        assert_eq!(
+            serde_json::Value::Object(unwrap_data_envelope(fields)),
+            json!({"data": {"a": 1}, "b": 2})
+        );
+    }
+
+    #[tokio::test]
+    async fn cas_write_requires_current_version() {
+        let kv = engine().await;
+        kv.write_cas("app", Some(json!({"k": "v1"})), Some(0))
+            .await
+            .unwrap();
+
+        let err = kv
+            .write_cas("app", Some(json!({"k": "v2"})), Some(0))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            EngineError::CasMismatch {
+                cas: 0,
+                current: 1,
+                ..
+            }
+        ));
+
+        kv.write_cas("app", Some(json!({"k": "v2"})), Some(1))
+            .await
+            .unwrap();
+        assert_eq!(kv.metadata("app").await.unwrap().current_version, 2);
+    }
+
+    #[tokio::test]
+    async fn destroy_removes_every_version() {
+        let kv = engine().await;
+        kv.write_cas("app", Some(json!({"k": "v1"})), None)
+            .await
+            .unwrap();
+        kv.write_cas("app", Some(json!({"k": "v2"})), None)
+            .await
+            .unwrap();
+
+        kv.destroy("app").await.unwrap();
+        assert!(matches!(
+            kv.destroy("app").await,
+            Err(EngineError::NotFound { .. })
+        ));
+        kv.write_cas("app", Some(json!({"k": "v3"})), Some(0))
+            .await
+            .unwrap();
+        assert_eq!(kv.metadata("app").await.unwrap().current_version, 1);
+    }
+
+    #[tokio::test]
+    async fn cas_required_rejects_blind_writes() {
+        let kv = engine().await;
+        kv.update_metadata(
+            "app",
+            KvMetadataUpdate {
+                cas_required: Some(true),
+                ..KvMetadataUpdate::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // Metadata alone does not make the secret readable.
+        let read = kv
+            .handle(&EngineRequest {
+                operation: Operation::Read,
+                path: "app".to_owned(),
+                data: None,
+            })
+            .await;
+        assert!(matches!(read, Err(EngineError::NotFound { .. })));
+
+        let blind = kv.write_cas("app", Some(json!({"k": "v"})), None).await;
+        assert!(matches!(blind, Err(EngineError::InvalidRequest { .. })));
+        kv.write_cas("app", Some(json!({"k": "v"})), Some(0))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn custom_metadata_survives_writes_and_prunes_versions() {
+        let kv = engine().await;
+        for i in 0..3 {
+            kv.write_cas("app", Some(json!({"i": i})), None)
+                .await
+                .unwrap();
+        }
+        let tags = HashMap::from([
+            ("owner".to_owned(), "alice".to_owned()),
+            ("team".to_owned(), "payments".to_owned()),
+        ]);
+        let meta = kv
+            .update_metadata(
+                "app",
+                KvMetadataUpdate {
+                    custom_metadata: Some(tags.clone()),
+                    max_versions: Some(2),
+                    ..KvMetadataUpdate::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(meta.version_count, 2);
+
+        kv.write_cas("app", Some(json!({"i": 3})), None)
+            .await
+            .unwrap();
+        let meta = kv.metadata("app").await.unwrap();
+        assert_eq!(meta.custom_metadata, tags);
+        assert_eq!(meta.current_version, 4);
+        assert_eq!(meta.version_count, 2);
+
+        let too_long = HashMap::from([("owner".to_owned(), "x".repeat(513))]);
+        let err = kv
+            .update_metadata(
+                "app",
+                KvMetadataUpdate {
+                    custom_metadata: Some(too_long),
+                    ..KvMetadataUpdate::default()
+                },
+            )
+            .await;
+        assert!(matches!(err, Err(EngineError::InvalidRequest { .. })));
+    }
+
+    #[tokio::test]
+    async fn copy_and_move_keep_versions_and_metadata() {
+        let kv = engine().await;
+        for path in ["app/db", "app/api/key", "other"] {
+            kv.write_cas(path, Some(json!({"v": 1})), None)
+                .await
+                .unwrap();
+        }
+        kv.write_cas("app/db", Some(json!({"v": 2})), None)
+            .await
+            .unwrap();
+        kv.update_metadata(
+            "app/db",
+            KvMetadataUpdate {
+                custom_metadata: Some(HashMap::from([("owner".to_owned(), "ops".to_owned())])),
+                ..KvMetadataUpdate::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let pairs = kv.copy_targets("app/", "app2").await.unwrap();
+        assert_eq!(
+            pairs,
+            [
+                ("app/api/key".to_owned(), "app2/api/key".to_owned()),
+                ("app/db".to_owned(), "app2/db".to_owned()),
+            ]
+        );
+        kv.copy(&pairs, false, false).await.unwrap();
+        let meta = kv.metadata("app2/db").await.unwrap();
+        assert_eq!(meta.current_version, 2);
+        assert_eq!(meta.version_count, 2);
+        assert_eq!(meta.custom_metadata["owner"], "ops");
+
+        // Existing destinations need `overwrite`, and nothing is half-done.
+        let err = kv.copy(&pairs, false, true).await.unwrap_err();
+        assert!(matches!(err, EngineError::AlreadyExists { .. }));
+        kv.metadata("app/db").await.unwrap();
+
+        let pairs = kv.copy_targets("app/db", "archive/").await.unwrap();
+        assert_eq!(pairs, [("app/db".to_owned(), "archive/db".to_owned())]);
+        kv.copy(&pairs, false, true).await.unwrap();
+        assert!(matches!(
+            kv.metadata("app/db").await,
+            Err(EngineError::NotFound { .. })
+        ));
+        assert_eq!(kv.metadata("archive/db").await.unwrap().current_version, 2);
+
+        assert!(matches!(
+            kv.copy_targets("app/", "app/v2/").await,
+            Err(EngineError::InvalidRequest { .. })
+        ));
+        assert!(matches!(
+            kv.copy_targets("missing/", "x/").await,
+            Err(EngineError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn sweeper_purges_versions_past_delete_version_after() {
+        let kv = engine().await;
+        kv.write_cas("db", Some(json!({"password": "v1"})), None)
+            .await
+            .unwrap();
+        kv.write_cas("keep", Some(json!({"k": "v"})), None)
+            .await
+            .unwrap();
+        kv.update_metadata(
+            "db",
+            KvMetadataUpdate {
+                delete_version_after_secs: Some(86_400),
+                ..KvMetadataUpdate::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let read = |path: &str| EngineRequest {
+            operation: Operation::Read,
+            path: path.to_owned(),
+            data: None,
+        };
+        kv.handle(&read("db")).await.unwrap();
+        assert_eq!(kv.sweep_expired_versions(Utc::now()).await.unwrap(), 0);
+
+        let later = Utc::now() + Duration::days(2);
+        assert_eq!(kv.sweep_expired_versions(later).await.unwrap(), 1);
+        assert!(matches!(
+            kv.handle(&read("db")).await,
+            Err(EngineError::NotFound { .. })
+        ));
+        kv.handle(&read("keep")).await.unwrap();
+
+        // A new version is readable again, and metadata survives the purge.
+        kv.write_cas("db", Some(json!({"password": "v2"})), Some(1))
+            .await
+            .unwrap();
+        kv.handle(&read("db")).await.unwrap();
+        let meta = kv.metadata("db").await.unwrap();
+        assert_eq!(meta.delete_version_after_secs, 86_400);
+        assert_eq!(meta.version_count, 1);
+    }
+}
@@ -40,6 +40,10 @@ pub enum BarrierError {
     /// The underlying storage backend returned an error.
     #[error("barrier storage error: {0}")]
     Storage(#[from] StorageError),
+
+    /// The stored keyring is unreadable or cannot take another term.
+    #[error("barrier keyring error: {reason}")]
+    Keyring { reason: String },
 }
 
 /// Errors from seal/unseal operations.
@@ -77,6 +81,34 @@ pub enum SealError {
     #[error("root key decryption failed: {reason}")]
     RootKeyDecryption { reason: String },
 
+    /// The auto-unseal KMS failed, is missing, or does not match the vault.
+    #[error("seal wrapper error: {reason}")]
+    Wrapper { reason: String },
+
+    /// A rekey was started while another one is in progress.
+    #[error("a rekey is already in progress")]
+    RekeyInProgress,
+
+    /// A rekey share or cancel arrived with no rekey in progress.
+    #[error("no rekey is in progress")]
+    NoRekeyInProgress,
+
+    /// A rekey share was submitted with the wrong nonce.
+    #[error("rekey nonce does not match the rekey in progress")]
+    RekeyNonceMismatch,
+
+    /// A root token generation was started while another is in progress.
+    #[error("a root token generation is already in progress")]
+    GenerateRootInProgress,
+
+    /// A root generation share or cancel arrived with no attempt in progress.
+    #[error("no root token generation is in progress")]
+    NoGenerateRootInProgress,
+
+    /// A root generation share was submitted with the wrong nonce.
+    #[error("root token generation nonce does not match the attempt in progress")]
+    GenerateRootNonceMismatch,
+
     /// A cryptographic operation failed during seal/unseal.
     #[error("seal crypto error: {0}")]
     Crypto(#[from] CryptoError),
@@ -109,6 +141,14 @@ pub enum TokenError {
     #[error("token has exceeded max TTL of {max_ttl_secs}s")]
     MaxTtlExceeded { max_ttl_secs: i64 },
 
+    /// A bound CIDR is neither a CIDR block nor an IP address.
+    #[error("invalid bound CIDR: {cidr}")]
+    InvalidBoundCidr { cidr: String },
+
+    /// The operation does not apply to batch tokens.
+    #[error("{operation} is not supported for batch tokens")]
+    BatchUnsupported { operation: &'static str },
+
     /// The barrier returned an error.
     #[error("token barrier error: {0}")]
     Barrier(#[from] BarrierError),
@@ -133,6 +173,17 @@ pub enum PolicyError {
     #[error("permission denied on path '{path}' for capability '{capability}'")]
     Denied { path: String, capability: String },
 
+    /// The request is granted but needs control group approval first.
+    #[error("request on path '{path}' requires control group approval")]
+    ControlGroupRequired {
+        path: String,
+        control_groups: Vec<crate::policy::ControlGroup>,
+    },
+
+    /// The request is granted but needs step-up MFA first.
+    #[error("request on path '{path}' requires MFA ({}) in the X-Vault-MFA header", methods.join(", "))]
+    MfaRequired { path: String, methods: Vec<String> },
+
     /// The barrier returned an error.
     #[error("policy barrier error: {0}")]
     Barrier(#[from] BarrierError),
@@ -152,6 +203,18 @@ pub enum AuditError {
     /// Serialization of the audit entry failed.
     #[error("audit serialization failed: {reason}")]
     Serialization { reason: String },
+
+    /// An audit device is already enabled at this path.
+    #[error("audit device already enabled at '{path}'")]
+    DeviceExists { path: String },
+
+    /// No audit device is enabled at this path.
+    #[error("no audit device enabled at '{path}'")]
+    DeviceNotFound { path: String },
+
+    /// The audit device configuration is invalid.
+    #[error("invalid audit device config: {reason}")]
+    InvalidConfig { reason: String },
 }
 
 /// Errors from mount table operations.
@@ -189,6 +252,18 @@ pub enum EngineError {
     #[error("invalid engine request: {reason}")]
     InvalidRequest { reason: String },
 
+    /// A copy or move would overwrite an existing secret.
+    #[error("a secret already exists at '{path}'")]
+    AlreadyExists { path: String },
+
+    /// A check-and-set write named a version other than the current one.
+    #[error("check-and-set version {cas} does not match current version {current} of '{path}'")]
+    CasMismatch {
+        path: String,
+        cas: u32,
+        current: u32,
+    },
+
     /// The barrier returned an error.
     #[error("engine barrier error: {0}")]
     Barrier(#[from] BarrierError),
@@ -196,6 +271,10 @@ pub enum EngineError {
     /// Internal engine error.
     #[error("engine internal error: {reason}")]
     Internal { reason: String },
+
+    /// The HSM holding the key failed or is unavailable.
+    #[error("hsm error: {reason}")]
+    Hsm { reason: String },
 }
 
 /// Errors from lease operations.
@@ -213,6 +292,18 @@ pub enum LeaseError {
     #[error("lease is not renewable: {lease_id}")]
     NotRenewable { lease_id: String },
 
+    /// The lease has already been extended to its maximum TTL.
+    #[error("lease {lease_id} has reached its max TTL of {max_ttl_secs}s")]
+    MaxTtlReached { lease_id: String, max_ttl_secs: i64 },
+
+    /// The engine's revocation handler failed; the lease is kept for retry.
+    #[error("revocation failed for lease {lease_id}: {reason}")]
+    RevocationFailed { lease_id: String, reason: String },
+
+    /// A lease count quota is full.
+    #[error("lease count quota '{quota}' reached its limit of {max_leases} leases")]
+    QuotaExceeded { quota: String, max_leases: u64 },
+
     /// The barrier returned an error.
     #[error("lease barrier error: {0}")]
     Barrier(#[from] BarrierError),
@@ -233,6 +324,18 @@ pub enum DatabaseError {
     #[error("invalid database config: {reason}")]
     InvalidConfig { reason: String },
 
+    /// Static role not found.
+    #[error("database static role not found: {name}")]
+    StaticRoleNotFound { name: String },
+
+    /// The database could not be reached.
+    #[error("database connection failed: {reason}")]
+    Connection { reason: String },
+
+    /// A statement was rejected by the database.
+    #[error("database statement failed: {reason}")]
+    Statement { reason: String },
+
     /// Internal engine error.
     #[error("database engine error: {reason}")]
     Internal { reason: String },
@@ -281,6 +384,10 @@ pub enum AppRoleError {
     #[error("invalid secret ID for role '{role_name}'")]
     InvalidSecretId { role_name: String },
 
+    /// The client's address is outside the role's bound CIDRs.
+    #[error("client address not allowed to log in with role '{role_name}'")]
+    ClientNotAllowed { role_name: String },
+
     /// Invalid configuration.
     #[error("invalid approle config: {reason}")]
     InvalidConfig { reason: String },
@@ -293,3 +400,586 @@ pub enum AppRoleError {
     #[error("approle barrier error: {0}")]
     Barrier(#[from] BarrierError),
 }
+
+/// Errors from license verification and feature gating.
+#[derive(Debug, thiserror::Error)]
+pub enum LicenseError {
+    /// The license key is malformed or its signature does not verify.
+    #[error("invalid license: {reason}")]
+    Invalid { reason: String },
+
+    /// The license has expired.
+    #[error("license expired on {expires_at}")]
+    Expired { expires_at: String },
+
+    /// The active license does not include the requested feature.
+    #[error("feature '{feature}' requires a {required_tier} license (current: {current_tier})")]
+    FeatureNotLicensed {
+        feature: crate::license::Feature,
+        required_tier: crate::license::Tier,
+        current_tier: crate::license::Tier,
+    },
+
+    /// The barrier returned an error.
+    #[error("license barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from the access request workflow.
+#[derive(Debug, thiserror::Error)]
+pub enum AccessRequestError {
+    /// No request with this ID exists.
+    #[error("access request not found: {id}")]
+    NotFound { id: String },
+
+    /// The request parameters are invalid.
+    #[error("invalid access request: {reason}")]
+    InvalidRequest { reason: String },
+
+    /// The request is not in a state that allows the operation.
+    #[error("access request {id} is {status}")]
+    InvalidState { id: String, status: String },
+
+    /// Requesters cannot approve their own requests.
+    #[error("access requests cannot be approved by their requester")]
+    SelfApproval,
+
+    /// Internal error (corrupt record, serialization).
+    #[error("access request error: {reason}")]
+    Internal { reason: String },
+
+    /// Writing or deleting the grant policy failed.
+    #[error("access request policy error: {0}")]
+    Policy(#[from] PolicyError),
+
+    /// The barrier returned an error.
+    #[error("access request barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from control group operations.
+#[derive(Debug, thiserror::Error)]
+pub enum ControlGroupError {
+    /// No pending request with this accessor exists (or it expired).
+    #[error("control group request not found: {accessor}")]
+    NotFound { accessor: String },
+
+    /// The caller is neither the requester nor a qualifying approver.
+    #[error("not authorized for control group request {accessor}")]
+    NotAuthorized { accessor: String },
+
+    /// Requesters cannot approve their own requests.
+    #[error("control group requests cannot be approved by their requester")]
+    SelfApproval,
+
+    /// The request has not collected enough approvals yet.
+    #[error("control group request {accessor} is not yet approved")]
+    NotApproved { accessor: String },
+
+    /// The replayed request does not match the parked one.
+    #[error("control group request {accessor} was made for {method} {path}")]
+    Mismatch {
+        accessor: String,
+        method: String,
+        path: String,
+    },
+
+    /// Internal error (corrupt record, serialization).
+    #[error("control group error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("control group barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from MFA methods and enforcement.
+#[derive(Debug, thiserror::Error)]
+pub enum MfaError {
+    /// No MFA method with this name is configured.
+    #[error("mfa method not found: {name}")]
+    MethodNotFound { name: String },
+
+    /// No login enforcement with this name exists.
+    #[error("mfa login enforcement not found: {name}")]
+    EnforcementNotFound { name: String },
+
+    /// The identity has no secret for this method.
+    #[error("not enrolled in mfa method '{method}'")]
+    NotEnrolled { method: String },
+
+    /// The identity already has a secret for this method.
+    #[error("already enrolled in mfa method '{method}'")]
+    AlreadyEnrolled { method: String },
+
+    /// The code is wrong, expired, or already used.
+    #[error("invalid code for mfa method '{method}'")]
+    InvalidCode { method: String },
+
+    /// A required method's code was not supplied.
+    #[error("MFA required: supply {} in the X-Vault-MFA header", methods.join(", "))]
+    Required { methods: Vec<String> },
+
+    /// The method is still named by a login enforcement.
+    #[error("mfa method '{name}' is used by login enforcement '{enforcement}'")]
+    InUse { name: String, enforcement: String },
+
+    /// Invalid configuration or credentials header.
+    #[error("invalid mfa request: {reason}")]
+    Invalid { reason: String },
+
+    /// Internal error (corrupt record, serialization).
+    #[error("mfa error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("mfa barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from the JWT auth method.
+#[derive(Debug, thiserror::Error)]
+pub enum JwtAuthError {
+    /// JWT role not found.
+    #[error("jwt role not found: {name}")]
+    RoleNotFound { name: String },
+
+    /// No key source has been configured.
+    #[error("jwt auth is not configured")]
+    NotConfigured,
+
+    /// Invalid configuration or role.
+    #[error("invalid jwt config: {reason}")]
+    InvalidConfig { reason: String },
+
+    /// The presented token failed verification.
+    #[error("invalid jwt: {reason}")]
+    InvalidToken { reason: String },
+
+    /// Signing keys could not be fetched.
+    #[error("jwks error: {reason}")]
+    Jwks { reason: String },
+
+    /// Internal error.
+    #[error("jwt auth error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("jwt auth barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from the TLS certificate auth method.
+#[derive(Debug, thiserror::Error)]
+pub enum CertAuthError {
+    /// Cert role not found.
+    #[error("cert role not found: {name}")]
+    RoleNotFound { name: String },
+
+    /// Invalid role.
+    #[error("invalid cert role: {reason}")]
+    InvalidConfig { reason: String },
+
+    /// The presented client certificate was not accepted.
+    #[error("invalid client certificate: {reason}")]
+    InvalidCertificate { reason: String },
+
+    /// Internal error.
+    #[error("cert auth error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("cert auth barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from resource quotas.
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    /// Quota not found.
+    #[error("quota not found: {name}")]
+    NotFound { name: String },
+
+    /// Invalid quota.
+    #[error("invalid quota: {reason}")]
+    InvalidConfig { reason: String },
+
+    /// A rate limit quota's bucket is empty.
+    #[error("rate limit quota '{quota}' exceeded, retry in {retry_after_secs}s")]
+    RateLimited {
+        quota: String,
+        retry_after_secs: u64,
+    },
+
+    /// Internal error (corrupt record, serialization).
+    #[error("quota error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("quota barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from response wrapping.
+#[derive(Debug, thiserror::Error)]
+pub enum WrappingError {
+    /// The token is not a live wrapping token.
+    #[error("wrapping token is not valid or does not exist")]
+    InvalidToken,
+
+    /// The requested wrap TTL is out of range.
+    #[error("invalid wrap ttl: {reason}")]
+    InvalidTtl { reason: String },
+
+    /// Internal error (corrupt record, serialization).
+    #[error("wrapping error: {reason}")]
+    Internal { reason: String },
+
+    /// Creating or revoking the wrapping token failed.
+    #[error("wrapping token error: {0}")]
+    Token(#[from] TokenError),
+
+    /// The barrier returned an error.
+    #[error("wrapping barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from secret usage analytics.
+#[derive(Debug, thiserror::Error)]
+pub enum SecretUsageError {
+    /// Internal error (corrupt record, serialization).
+    #[error("secret usage error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("secret usage barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from client activity counting.
+#[derive(Debug, thiserror::Error)]
+pub enum ActivityError {
+    /// The requested month range is malformed.
+    #[error("invalid activity range: {reason}")]
+    InvalidRange { reason: String },
+
+    /// Internal error (corrupt record, serialization).
+    #[error("activity error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("activity barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from per-mount export and import.
+#[derive(Debug, thiserror::Error)]
+pub enum MountTransferError {
+    /// The transfer key is malformed or does not open the bundle.
+    #[error("invalid transfer key: {reason}")]
+    InvalidTransferKey { reason: String },
+
+    /// The bundle is malformed or from an unsupported version.
+    #[error("invalid mount bundle: {reason}")]
+    InvalidBundle { reason: String },
+
+    /// The import target already holds data.
+    #[error("import target is not empty: {path}")]
+    TargetNotEmpty { path: String },
+
+    /// Internal error (serialization).
+    #[error("mount transfer error: {reason}")]
+    Internal { reason: String },
+
+    /// Mounting the imported engine failed.
+    #[error("mount transfer mount error: {0}")]
+    Mount(#[from] MountError),
+
+    /// The barrier returned an error.
+    #[error("mount transfer barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from the ACME client that manages the server's TLS certificate.
+#[derive(Debug, thiserror::Error)]
+pub enum AcmeError {
+    /// The ACME configuration is invalid.
+    #[error("invalid ACME configuration: {reason}")]
+    InvalidConfig { reason: String },
+
+    /// The ACME server rejected a request or returned a malformed reply.
+    #[error("ACME protocol error: {reason}")]
+    Protocol { reason: String },
+
+    /// A domain failed validation.
+    #[error("ACME challenge failed for {domain}: {reason}")]
+    Challenge { domain: String, reason: String },
+
+    /// The DNS provider failed to publish or remove a challenge record.
+    #[error("ACME DNS provider error: {reason}")]
+    Dns { reason: String },
+
+    /// Internal error (key generation, serialization, corrupt record).
+    #[error("ACME error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("ACME barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from the PKI engine's ACME server, one per RFC 8555 problem type.
+#[derive(Debug, thiserror::Error)]
+pub enum AcmeServerError {
+    /// ACME is not enabled on this PKI mount.
+    #[error("ACME is not enabled on this PKI mount")]
+    Disabled,
+
+    /// The request is malformed or not allowed here.
+    #[error("malformed ACME request: {reason}")]
+    Malformed { reason: String },
+
+    /// The nonce is missing, unknown, or was already used.
+    #[error("missing, unknown, or reused nonce")]
+    BadNonce,
+
+    /// The JWS is signed with an algorithm the server does not support.
+    #[error("unsupported JWS algorithm '{alg}'")]
+    BadSignatureAlgorithm { alg: String },
+
+    /// No account exists for the signing key.
+    #[error("no account exists for this key")]
+    AccountDoesNotExist,
+
+    /// The signer may not act on the resource.
+    #[error("unauthorized: {reason}")]
+    Unauthorized { reason: String },
+
+    /// The account, order, authorization, or certificate does not exist.
+    #[error("{resource} not found")]
+    NotFound { resource: String },
+
+    /// The server will not issue for an identifier.
+    #[error("identifier '{identifier}' rejected: {reason}")]
+    RejectedIdentifier { identifier: String, reason: String },
+
+    /// The order cannot be finalized in its current state.
+    #[error("order is {status}, not ready")]
+    OrderNotReady { status: String },
+
+    /// The CSR does not match the order or the role.
+    #[error("bad CSR: {reason}")]
+    BadCsr { reason: String },
+
+    /// Internal error (serialization, corrupt record).
+    #[error("ACME server error: {reason}")]
+    Internal { reason: String },
+
+    /// The PKI engine returned an error.
+    #[error("ACME PKI error: {0}")]
+    Pki(#[from] PkiError),
+
+    /// The barrier returned an error.
+    #[error("ACME barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from the identity store (entities, aliases, groups).
+#[derive(Debug, thiserror::Error)]
+pub enum IdentityError {
+    /// No entity with this ID or name exists.
+    #[error("entity not found: {id}")]
+    EntityNotFound { id: String },
+
+    /// No alias with this ID exists.
+    #[error("entity alias not found: {id}")]
+    AliasNotFound { id: String },
+
+    /// No group with this ID or name exists.
+    #[error("group not found: {id}")]
+    GroupNotFound { id: String },
+
+    /// Another entity or group already has this name.
+    #[error("name '{name}' is already in use")]
+    NameInUse { name: String },
+
+    /// The auth method's name is already an alias of another entity.
+    #[error("alias '{name}' on '{auth_method}' already belongs to entity {entity_id}")]
+    AliasInUse {
+        auth_method: String,
+        name: String,
+        entity_id: String,
+    },
+
+    /// The entity is disabled; its tokens are refused.
+    #[error("entity {id} is disabled")]
+    EntityDisabled { id: String },
+
+    /// Invalid request (bad name, membership cycle, external group members).
+    #[error("invalid identity request: {reason}")]
+    Invalid { reason: String },
+
+    /// Internal error (corrupt record, serialization).
+    #[error("identity error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("identity barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from webhook notifications.
+#[derive(Debug, thiserror::Error)]
+pub enum NotifyError {
+    /// No webhook with this name exists.
+    #[error("webhook not found: {name}")]
+    NotFound { name: String },
+
+    /// Invalid webhook config (bad name, URL, or pattern).
+    #[error("invalid webhook: {reason}")]
+    Invalid { reason: String },
+
+    /// The endpoint did not accept a notification.
+    #[error("webhook '{name}' delivery failed: {reason}")]
+    Delivery { name: String, reason: String },
+
+    /// Internal error (corrupt record, serialization, HTTP client).
+    #[error("notification error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("notification barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from the secret rotation engine.
+#[derive(Debug, thiserror::Error)]
+pub enum RotationError {
+    /// No rotation policy exists for this secret.
+    #[error("rotation policy not found: {path}")]
+    NotFound { path: String },
+
+    /// Invalid rotation policy (bad path, field, rotator, or schedule).
+    #[error("invalid rotation policy: {reason}")]
+    Invalid { reason: String },
+
+    /// Generating or storing the new value failed; the secret is unchanged.
+    #[error("rotation of '{path}' failed: {reason}")]
+    Failed { path: String, reason: String },
+
+    /// Internal error (corrupt record, serialization, HTTP client).
+    #[error("rotation error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("rotation barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from secret sync to GitHub Actions and Kubernetes.
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    /// No sync with this name exists.
+    #[error("sync not found: {name}")]
+    NotFound { name: String },
+
+    /// Invalid sync config (bad name, repository, mapping, or token).
+    #[error("invalid sync: {reason}")]
+    Invalid { reason: String },
+
+    /// Reading the secrets or talking to GitHub failed.
+    #[error("sync '{name}' failed: {reason}")]
+    Failed { name: String, reason: String },
+
+    /// Internal error (corrupt record, serialization, HTTP client).
+    #[error("sync error: {reason}")]
+    Internal { reason: String },
+
+    /// The barrier returned an error.
+    #[error("sync barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
+
+/// Errors from migrating secrets to and from AWS.
+#[derive(Debug, thiserror::Error)]
+pub enum MigrateError {
+    /// Invalid migration request (region, credentials, prefix, or path).
+    #[error("invalid migration: {reason}")]
+    Invalid { reason: String },
+
+    /// AWS refused or failed a request.
+    #[error("AWS request failed: {reason}")]
+    Aws { reason: String },
+
+    /// Targets exist with different values and the strategy is `fail`.
+    #[error("{} target(s) already exist with different values: {}", targets.len(), targets.join(", "))]
+    Conflict { targets: Vec<String> },
+
+    /// Reading or writing KV failed.
+    #[error("migration KV error: {0}")]
+    Engine(#[from] EngineError),
+}
+
+/// Errors from primary → replica replication.
+#[derive(Debug, thiserror::Error)]
+pub enum ReplicationError {
+    /// The replica cannot catch up from the log and must take a snapshot.
+    #[error("replica must resync: {reason}")]
+    ResyncRequired { reason: String },
+
+    /// Malformed replication data.
+    #[error("invalid replication data: {reason}")]
+    Invalid { reason: String },
+
+    /// The storage backend returned an error.
+    #[error("replication storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+/// Errors from the plugin catalog and external secrets engine plugins.
+#[derive(Debug, thiserror::Error)]
+pub enum PluginError {
+    /// No plugin with this name is registered.
+    #[error("plugin not found: {name}")]
+    NotFound { name: String },
+
+    /// Invalid catalog entry (bad name, module, checksum, or allowed host).
+    #[error("invalid plugin: {reason}")]
+    Invalid { reason: String },
+
+    /// No plugin directory is configured, so plugins cannot run.
+    #[error("plugins are disabled: no plugin directory is configured")]
+    Disabled,
+
+    /// The module no longer matches the SHA-256 in the catalog.
+    #[error("plugin '{name}' does not match its registered SHA-256")]
+    ChecksumMismatch { name: String },
+
+    /// The plugin could not be instantiated or initialized.
+    #[error("plugin '{name}' failed to start: {reason}")]
+    Launch { name: String, reason: String },
+
+    /// The plugin broke the ABI or trapped mid-request.
+    #[error("plugin '{name}' protocol error: {reason}")]
+    Protocol { name: String, reason: String },
+
+    /// The plugin did not answer in time.
+    #[error("plugin '{name}' timed out")]
+    Timeout { name: String },
+
+    /// The plugin rejected the request as invalid.
+    #[error("{reason}")]
+    InvalidRequest { reason: String },
+
+    /// The plugin has nothing at the requested path.
+    #[error("{reason}")]
+    PathNotFound { reason: String },
+
+    /// The plugin reported an error handling the request.
+    #[error("plugin '{name}' failed: {reason}")]
+    Failed { name: String, reason: String },
+
+    /// The barrier returned an error.
+    #[error("plugin barrier error: {0}")]
+    Barrier(#[from] BarrierError),
+}
@@ -0,0 +1,199 @@
+//! In-process event bus for `ZVault`.
+//!
+//! Subsystems publish [`VaultEvent`]s (secret writes, policy changes, seal
+//! status, lease expiry, ...) to a broadcast channel. Consumers — streaming
+//! endpoints, agents, notification sinks — subscribe and filter by topic
+//! with [`topic_matches`].
+//!
+//! Delivery is best-effort: a subscriber that falls more than the channel
+//! capacity behind misses events instead of blocking publishers. Events never
+//! carry secret values, only paths and identifiers.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// Topic published when a lease reaches its TTL and is revoked by the expiry worker.
+pub const TOPIC_LEASE_EXPIRED: &str = "lease.expired";
+
+/// Topic published when a lease is revoked explicitly by an operator.
+pub const TOPIC_LEASE_REVOKED: &str = "lease.revoked";
+
+/// Topic published when a new KV secret version is written.
+pub const TOPIC_KV_WRITE: &str = "kv.write";
+
+/// Topic published when the latest KV secret version is soft-deleted.
+pub const TOPIC_KV_DELETE: &str = "kv.delete";
+
+/// Topic published when a KV secret and all its versions are destroyed.
+pub const TOPIC_KV_DESTROY: &str = "kv.destroy";
+
+/// Topic published when a KV secret's metadata changes.
+pub const TOPIC_KV_METADATA: &str = "kv.metadata";
+
+/// Topic published when the rotation engine writes a new KV secret version.
+pub const TOPIC_KV_ROTATE: &str = "kv.rotate";
+
+/// Topic published when a policy is created or updated.
+pub const TOPIC_POLICY_WRITE: &str = "policy.write";
+
+/// Topic published when a policy is deleted.
+pub const TOPIC_POLICY_DELETE: &str = "policy.delete";
+
+/// Topic published when the vault is sealed.
+pub const TOPIC_SEALED: &str = "sys.sealed";
+
+/// Topic published when the vault is unsealed.
+pub const TOPIC_UNSEALED: &str = "sys.unsealed";
+
+/// Topic published when the vault is initialized.
+pub const TOPIC_INITIALIZED: &str = "sys.initialized";
+
+/// Topic published when unseal attempts keep failing.
+pub const TOPIC_UNSEAL_FAILED: &str = "sys.unseal_failed";
+
+/// Default number of buffered events per subscriber.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A single event emitted by a vault subsystem.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultEvent {
+    /// Unique event ID.
+    pub id: String,
+    /// Dotted topic name (e.g., `lease.expired`).
+    pub topic: String,
+    /// When the event was published.
+    pub timestamp: DateTime<Utc>,
+    /// Event-specific payload (paths, lease IDs — never secret values).
+    pub data: serde_json::Value,
+}
+
+/// Broadcast bus that fans events out to all current subscribers.
+pub struct EventBus {
+    sender: broadcast::Sender<VaultEvent>,
+}
+
+impl EventBus {
+    /// Create a new event bus with the default buffer capacity.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new event bus buffering up to `capacity` events per subscriber.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish an event to all subscribers.
+    ///
+    /// Publishing with no subscribers is not an error — the event is dropped.
+    pub fn publish(&self, topic: &str, data: serde_json::Value) {
+        let event = VaultEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            topic: topic.to_owned(),
+            timestamp: Utc::now(),
+            data,
+        };
+
+        let receivers = self.sender.send(event).unwrap_or(0);
+        debug!(topic = %topic, receivers, "event published");
+    }
+
+    /// Subscribe to all future events.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<VaultEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of active subscribers.
+    #[must_use]
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.sender.receiver_count())
+            .finish()
+    }
+}
+
+/// Whether `topic` matches a subscription `pattern`.
+///
+/// `*` matches any run of characters, so `kv.*` covers every KV topic and
+/// `*` covers everything. `/` in a pattern is read as `.`, so `kv/*` works
+/// too.
+#[must_use]
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    let pattern = pattern.replace('/', ".");
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return false;
+    };
+    let Some(mut rest) = topic.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn subscriber_receives_published_event() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+
+        bus.publish(
+            TOPIC_LEASE_REVOKED,
+            serde_json::json!({ "lease_id": "abc" }),
+        );
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.topic, TOPIC_LEASE_REVOKED);
+        assert_eq!(event.data["lease_id"], "abc");
+    }
+
+    #[test]
+    fn publish_without_subscribers_is_noop() {
+        let bus = EventBus::new();
+        bus.publish(TOPIC_LEASE_EXPIRED, serde_json::Value::Null);
+        assert_eq!(bus.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn topic_patterns() {
+        assert!(topic_matches("kv.write", TOPIC_KV_WRITE));
+        assert!(!topic_matches("kv.write", TOPIC_KV_DELETE));
+        assert!(topic_matches("kv.*", TOPIC_KV_DESTROY));
+        assert!(topic_matches("kv/*", TOPIC_KV_METADATA));
+        assert!(topic_matches("*", TOPIC_SEALED));
+        assert!(topic_matches("*.expired", TOPIC_LEASE_EXPIRED));
+        assert!(!topic_matches("kv.*", TOPIC_POLICY_WRITE));
+        assert!(!topic_matches("lease", TOPIC_LEASE_EXPIRED));
+    }
+}
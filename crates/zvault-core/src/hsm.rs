@@ -0,0 +1,369 @@
+//! Hardware security module support for the transit engine.
+//!
+//! Transit keys created with `backend = pkcs11` never exist outside the HSM:
+//! each key version is an AES-256 key object in the configured slot, and
+//! encrypt/decrypt calls are delegated to it. `ZVault` still owns naming,
+//! versioning, and policy — the HSM only sees labels and payloads.
+//!
+//! [`Pkcs11Provider`] drives the slot through the `OpenSC` `pkcs11-tool`
+//! (0.24 or newer, for AES-GCM), so any vendor PKCS#11 module works without
+//! linking it into the server process. The user PIN is handed over through
+//! the `ZVAULT_HSM_PIN` environment variable of the child process, never on
+//! its command line.
+
+use std::process::Stdio;
+
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::error::EngineError;
+
+/// Environment variable the PIN is passed through to `pkcs11-tool`.
+const PIN_ENV: &str = "ZVAULT_HSM_PIN";
+
+/// AES-GCM nonce length in bytes.
+const IV_LEN: usize = 12;
+
+/// A device that holds transit key material and performs crypto with it.
+#[async_trait::async_trait]
+pub trait HsmProvider: Send + Sync {
+    /// Provider name, stored on keys that use it (e.g. `pkcs11`).
+    fn name(&self) -> &str;
+
+    /// Generate a new non-extractable AES-256 key under `label`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::Hsm`] if the device rejects the request.
+    async fn generate_key(&self, label: &str) -> Result<(), EngineError>;
+
+    /// Encrypt with the key under `label`, returning `iv || ciphertext || tag`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::Hsm`] if the device rejects the request.
+    async fn encrypt(&self, label: &str, plaintext: &[u8]) -> Result<Vec<u8>, EngineError>;
+
+    /// Decrypt `iv || ciphertext || tag` with the key under `label`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::Hsm`] if the device rejects the request or
+    /// authentication fails.
+    async fn decrypt(&self, label: &str, ciphertext: &[u8]) -> Result<Vec<u8>, EngineError>;
+
+    /// Destroy the key under `label`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::Hsm`] if the device rejects the request.
+    async fn destroy_key(&self, label: &str) -> Result<(), EngineError>;
+}
+
+/// Connection settings for a PKCS#11 slot.
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    /// Path to the vendor PKCS#11 module (`.so` / `.dylib` / `.dll`).
+    pub module_path: String,
+    /// Slot ID holding the keys.
+    pub slot: u64,
+    /// User PIN for the slot.
+    pub pin: String,
+    /// `pkcs11-tool` executable (default: found on `PATH`).
+    pub tool_path: String,
+}
+
+impl std::fmt::Debug for Pkcs11Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pkcs11Config")
+            .field("module_path", &self.module_path)
+            .field("slot", &self.slot)
+            .field("tool_path", &self.tool_path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// PKCS#11 provider backed by `pkcs11-tool`.
+#[derive(Debug)]
+pub struct Pkcs11Provider {
+    config: Pkcs11Config,
+}
+
+impl Pkcs11Provider {
+    /// Create a provider for the given slot.
+    #[must_use]
+    pub fn new(config: Pkcs11Config) -> Self {
+        Self { config }
+    }
+
+    /// Run `pkcs11-tool` against the slot with `args`, feeding `input` on stdin.
+    async fn run(&self, args: &[&str], input: &[u8]) -> Result<Vec<u8>, EngineError> {
+        let slot = self.config.slot.to_string();
+        let pin = format!("env:{PIN_ENV}");
+        let mut child = Command::new(&self.config.tool_path)
+            .args(["--module", &self.config.module_path, "--slot", &slot])
+            .args(["--login", "--pin", &pin])
+            .args(args)
+            .env(PIN_ENV, &self.config.pin)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| hsm_error(format!("failed to start {}: {e}", self.config.tool_path)))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input)
+                .await
+                .map_err(|e| hsm_error(format!("failed to write to pkcs11-tool: {e}")))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| hsm_error(format!("pkcs11-tool failed: {e}")))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(hsm_error(format!(
+                "pkcs11-tool exited with {}: {}",
+                output.status,
+                stderr.trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+}
+
+#[async_trait::async_trait]
+impl HsmProvider for Pkcs11Provider {
+    #[allow(clippy::unnecessary_literal_bound)]
+    fn name(&self) -> &str {
+        "pkcs11"
+    }
+
+    async fn generate_key(&self, label: &str) -> Result<(), EngineError> {
+        self.run(
+            &[
+                "--keygen",
+                "--key-type",
+                "AES:32",
+                "--label",
+                label,
+                "--sensitive",
+            ],
+            &[],
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn encrypt(&self, label: &str, plaintext: &[u8]) -> Result<Vec<u8>, EngineError> {
+        let iv: [u8; IV_LEN] = random_iv();
+        let iv_hex = hex::encode(iv);
+        let sealed = self
+            .run(
+                &[
+                    "--encrypt",
+                    "--label",
+                    label,
+                    "--mechanism",
+                    "AES-GCM",
+                    "--iv",
+                    &iv_hex,
+                    "--tag-bits-len",
+                    "128",
+                ],
+                plaintext,
+            )
+            .await?;
+
+        let mut out = Vec::with_capacity(IV_LEN + sealed.len());
+        out.extend_from_slice(&iv);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    async fn decrypt(&self, label: &str, ciphertext: &[u8]) -> Result<Vec<u8>, EngineError> {
+        if ciphertext.len() <= IV_LEN {
+            return Err(EngineError::InvalidRequest {
+                reason: "ciphertext too short".to_owned(),
+            });
+        }
+        let (iv, sealed) = ciphertext.split_at(IV_LEN);
+        let iv_hex = hex::encode(iv);
+        self.run(
+            &[
+                "--decrypt",
+                "--label",
+                label,
+                "--mechanism",
+                "AES-GCM",
+                "--iv",
+                &iv_hex,
+                "--tag-bits-len",
+                "128",
+            ],
+            sealed,
+        )
+        .await
+    }
+
+    async fn destroy_key(&self, label: &str) -> Result<(), EngineError> {
+        self.run(
+            &["--delete-object", "--type", "secrkey", "--label", label],
+            &[],
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+/// Fresh random nonce from the OS CSPRNG.
+fn random_iv() -> [u8; IV_LEN] {
+    let mut iv = [0u8; IV_LEN];
+    OsRng.fill_bytes(&mut iv);
+    iv
+}
+
+fn hsm_error(reason: String) -> EngineError {
+    EngineError::Hsm { reason }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use tokio::sync::Mutex;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::barrier::Barrier;
+    use crate::crypto::{self, EncryptionKey};
+    use crate::transit::{CreateKeyOptions, KeyBackend, TransitEngine};
+
+    /// In-memory stand-in for a slot.
+    #[derive(Default)]
+    struct FakeHsm {
+        keys: Mutex<HashMap<String, [u8; 32]>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HsmProvider for FakeHsm {
+        #[allow(clippy::unnecessary_literal_bound)]
+        fn name(&self) -> &str {
+            "pkcs11"
+        }
+
+        async fn generate_key(&self, label: &str) -> Result<(), EngineError> {
+            let key = EncryptionKey::generate();
+            let mut bytes = [0u8; 32];
+            bytes.copy_from_slice(key.as_bytes());
+            self.keys.lock().await.insert(label.to_owned(), bytes);
+            Ok(())
+        }
+
+        async fn encrypt(&self, label: &str, plaintext: &[u8]) -> Result<Vec<u8>, EngineError> {
+            let bytes = *self.keys.lock().await.get(label).unwrap();
+            Ok(crypto::encrypt(&EncryptionKey::from_bytes(bytes), plaintext).unwrap())
+        }
+
+        async fn decrypt(&self, label: &str, ciphertext: &[u8]) -> Result<Vec<u8>, EngineError> {
+            let bytes = *self.keys.lock().await.get(label).unwrap();
+            crypto::decrypt(&EncryptionKey::from_bytes(bytes), ciphertext)
+                .map_err(|e| hsm_error(e.to_string()))
+        }
+
+        async fn destroy_key(&self, label: &str) -> Result<(), EngineError> {
+            self.keys.lock().await.remove(label);
+            Ok(())
+        }
+    }
+
+    fn pkcs11() -> CreateKeyOptions {
+        CreateKeyOptions {
+            backend: KeyBackend::Pkcs11,
+            exportable: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn hsm_keys_delegate_to_provider() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let hsm = Arc::new(FakeHsm::default());
+        let engine = TransitEngine::new(barrier, "transit/".to_owned())
+            .with_hsm(Arc::clone(&hsm) as Arc<dyn HsmProvider>);
+
+        engine
+            .create_key_with_options("payments", pkcs11())
+            .await
+            .unwrap();
+        let ct = engine.encrypt("payments", b"card").await.unwrap();
+        assert!(ct.starts_with("vault:v1:"));
+        assert_eq!(engine.decrypt("payments", &ct).await.unwrap(), b"card");
+
+        engine.rotate_key("payments").await.unwrap();
+        assert_eq!(hsm.keys.lock().await.len(), 2);
+        assert_eq!(engine.decrypt("payments", &ct).await.unwrap(), b"card");
+
+        let info = engine.key_info("payments").await.unwrap();
+        assert_eq!(info.backend, KeyBackend::Pkcs11);
+    }
+
+    #[tokio::test]
+    async fn hsm_objects_are_named_by_mount_and_destroyed_with_the_key() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let hsm = Arc::new(FakeHsm::default());
+        let engine = |mount: &str| {
+            TransitEngine::new(Arc::clone(&barrier), format!("transit/{mount}"))
+                .with_hsm(Arc::clone(&hsm) as Arc<dyn HsmProvider>)
+        };
+        let (team_a, team_b) = (engine("team-a/"), engine("team-b/"));
+
+        for engine in [&team_a, &team_b] {
+            engine
+                .create_key_with_options("payments", pkcs11())
+                .await
+                .unwrap();
+        }
+        team_a.rotate_key("payments").await.unwrap();
+        let mut labels: Vec<String> = hsm.keys.lock().await.keys().cloned().collect();
+        labels.sort();
+        assert_eq!(
+            labels,
+            [
+                "zvault-transit-team-a/payments-v1",
+                "zvault-transit-team-a/payments-v2",
+                "zvault-transit-team-b/payments-v1",
+            ]
+        );
+
+        team_a.delete_key("payments").await.unwrap();
+        let labels: Vec<String> = hsm.keys.lock().await.keys().cloned().collect();
+        assert_eq!(labels, ["zvault-transit-team-b/payments-v1"]);
+        assert!(matches!(
+            team_a.key_info("payments").await.unwrap_err(),
+            EngineError::NotFound { .. }
+        ));
+        team_b.key_info("payments").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn hsm_keys_require_a_provider() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let engine = TransitEngine::new(barrier, "transit/".to_owned());
+
+        let err = engine
+            .create_key_with_options("payments", pkcs11())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineError::InvalidRequest { .. }));
+    }
+}
@@ -0,0 +1,1135 @@
+//! Identity entities and groups for `ZVault`.
+//!
+//! An entity is one client — a person or a service — however it logs in.
+//! Logins map to entities through aliases: an auth method (`approle`, `jwt`,
+//! `oidc`) plus the name that method knows the client by (the `AppRole` role
+//! name, the JWT user claim, the OIDC `sub`). A login through any alias of an
+//! entity yields a token bound to it, and the auth middleware adds the
+//! entity's policies — and those of every group it belongs to — to the
+//! token's own. Policies are attached once, to an entity or group, instead
+//! of being repeated on each auth method's roles.
+//!
+//! Groups come in two kinds:
+//! - Internal groups list member entities and member groups. Members of a
+//!   nested group inherit the policies of every group containing it.
+//! - External groups mirror a group in the auth provider through their
+//!   alias (auth method and group name). Entity membership is refreshed at
+//!   each login from the group names the login presents, such as a JWT
+//!   role's `groups_claim`.
+//!
+//! A login through an alias no one has created gets a fresh entity, so every
+//! login token is bound to one. Tokens of a disabled entity are refused.
+//!
+//! Everything is stored through the barrier under `sys/identity/`.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::barrier::Barrier;
+use crate::error::IdentityError;
+
+/// Storage prefix for entities, by ID.
+const ENTITY_PREFIX: &str = "sys/identity/entity/";
+
+/// Storage prefix for the entity name → ID index.
+const ENTITY_NAME_PREFIX: &str = "sys/identity/entity-name/";
+
+/// Storage prefix for entity aliases, by ID.
+const ALIAS_PREFIX: &str = "sys/identity/alias/";
+
+/// Storage prefix for the alias index, `{prefix}{auth method}/{name hash}`.
+const ALIAS_INDEX_PREFIX: &str = "sys/identity/alias-index/";
+
+/// Storage prefix for groups, by ID.
+const GROUP_PREFIX: &str = "sys/identity/group/";
+
+/// Storage prefix for the group name → ID index.
+const GROUP_NAME_PREFIX: &str = "sys/identity/group-name/";
+
+/// Storage prefix for the external group alias index.
+const GROUP_ALIAS_PREFIX: &str = "sys/identity/group-alias/";
+
+/// Auth methods that can carry aliases.
+pub const AUTH_METHODS: &[&str] = &["approle", "jwt", "oidc", "cert"];
+
+/// Token metadata key holding the token's entity ID.
+pub const ENTITY_ID_METADATA: &str = "entity_id";
+
+/// Token metadata key an auth method sets to the login's alias name.
+pub const ALIAS_METADATA: &str = "alias";
+
+/// Token metadata key an auth method sets to the login's external group
+/// names, comma separated.
+pub const GROUPS_METADATA: &str = "groups";
+
+/// One client, however it authenticates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entity {
+    /// Unique entity ID.
+    pub id: String,
+    /// Unique entity name.
+    pub name: String,
+    /// Policies added to every token of the entity.
+    #[serde(default)]
+    pub policies: Vec<String>,
+    /// Free-form metadata.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Whether the entity's tokens are refused.
+    #[serde(default)]
+    pub disabled: bool,
+    /// When the entity was created.
+    pub created_at: DateTime<Utc>,
+    /// When the entity was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A name an auth method knows an entity by.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityAlias {
+    /// Unique alias ID.
+    pub id: String,
+    /// Entity the alias belongs to.
+    pub entity_id: String,
+    /// Auth method, e.g. `approle`.
+    pub auth_method: String,
+    /// Name within the auth method, e.g. a role name or `sub` claim.
+    pub name: String,
+    /// When the alias was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a group's entity membership is managed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupType {
+    /// Members are listed explicitly.
+    #[default]
+    Internal,
+    /// Members are the entities whose logins present the group's alias.
+    External,
+}
+
+/// The auth provider group an external group mirrors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupAlias {
+    /// Auth method, e.g. `jwt`.
+    pub auth_method: String,
+    /// Group name as the auth method reports it.
+    pub name: String,
+}
+
+/// A set of entities and groups sharing policies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Group {
+    /// Unique group ID.
+    pub id: String,
+    /// Unique group name.
+    pub name: String,
+    /// Internal or external.
+    #[serde(rename = "type", default)]
+    pub group_type: GroupType,
+    /// Policies added to the tokens of every member.
+    #[serde(default)]
+    pub policies: Vec<String>,
+    /// Free-form metadata.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Member entities.
+    #[serde(default)]
+    pub member_entity_ids: Vec<String>,
+    /// Member groups (internal groups only).
+    #[serde(default)]
+    pub member_group_ids: Vec<String>,
+    /// Provider group this group mirrors (external groups only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<GroupAlias>,
+    /// When the group was created.
+    pub created_at: DateTime<Utc>,
+    /// When the group was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Fields to set when creating or updating an entity. `None` keeps the
+/// current value (or the default, on create).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntityParams {
+    /// Entity name; generated on create when absent.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Policies.
+    #[serde(default)]
+    pub policies: Option<Vec<String>>,
+    /// Metadata, replacing the current map.
+    #[serde(default)]
+    pub metadata: Option<BTreeMap<String, String>>,
+    /// Whether the entity's tokens are refused.
+    #[serde(default)]
+    pub disabled: Option<bool>,
+}
+
+/// Fields to set when creating or updating a group. `None` keeps the
+/// current value (or the default, on create).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupParams {
+    /// Group name; generated on create when absent.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Group type; fixed once the group exists.
+    #[serde(default, rename = "type")]
+    pub group_type: Option<GroupType>,
+    /// Policies.
+    #[serde(default)]
+    pub policies: Option<Vec<String>>,
+    /// Metadata, replacing the current map.
+    #[serde(default)]
+    pub metadata: Option<BTreeMap<String, String>>,
+    /// Member entities (internal groups only).
+    #[serde(default)]
+    pub member_entity_ids: Option<Vec<String>>,
+    /// Member groups (internal groups only).
+    #[serde(default)]
+    pub member_group_ids: Option<Vec<String>>,
+    /// Provider group to mirror (external groups only).
+    #[serde(default)]
+    pub alias: Option<GroupAlias>,
+}
+
+/// Barrier-backed store for entities, aliases, and groups.
+pub struct IdentityStore {
+    barrier: Arc<Barrier>,
+    /// Serializes writes so name and alias indexes stay unique.
+    write_lock: Mutex<()>,
+}
+
+impl IdentityStore {
+    /// Create an identity store backed by the barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            barrier,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    // ── Entities ─────────────────────────────────────────────────────
+
+    /// Create an entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::NameInUse`] if the name is taken,
+    /// [`IdentityError::Invalid`] for a bad name, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn create_entity(&self, params: EntityParams) -> Result<Entity, IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        self.create_entity_locked(params).await
+    }
+
+    async fn create_entity_locked(&self, params: EntityParams) -> Result<Entity, IdentityError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let name = params
+            .name
+            .unwrap_or_else(|| format!("entity-{}", &id[..8]));
+        validate_name(&name)?;
+        self.claim_name(ENTITY_NAME_PREFIX, &name, &id).await?;
+
+        let now = Utc::now();
+        let entity = Entity {
+            id,
+            name,
+            policies: params.policies.unwrap_or_default(),
+            metadata: params.metadata.unwrap_or_default(),
+            disabled: params.disabled.unwrap_or(false),
+            created_at: now,
+            updated_at: now,
+        };
+        self.put_json(&format!("{ENTITY_PREFIX}{}", entity.id), &entity)
+            .await?;
+        info!(id = %entity.id, name = %entity.name, "identity entity created");
+        Ok(entity)
+    }
+
+    /// Update an entity's fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::EntityNotFound`] for an unknown entity,
+    /// [`IdentityError::NameInUse`] if renamed to a taken name, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn update_entity(
+        &self,
+        id: &str,
+        params: EntityParams,
+    ) -> Result<Entity, IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        let mut entity = self.get_entity(id).await?;
+        if let Some(name) = params.name.filter(|n| *n != entity.name) {
+            validate_name(&name)?;
+            self.claim_name(ENTITY_NAME_PREFIX, &name, id).await?;
+            self.barrier
+                .delete(&format!("{ENTITY_NAME_PREFIX}{}", entity.name))
+                .await?;
+            entity.name = name;
+        }
+        if let Some(policies) = params.policies {
+            entity.policies = policies;
+        }
+        if let Some(metadata) = params.metadata {
+            entity.metadata = metadata;
+        }
+        if let Some(disabled) = params.disabled {
+            entity.disabled = disabled;
+        }
+        entity.updated_at = Utc::now();
+        self.put_json(&format!("{ENTITY_PREFIX}{id}"), &entity)
+            .await?;
+        info!(id = %id, name = %entity.name, "identity entity updated");
+        Ok(entity)
+    }
+
+    /// Read an entity by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::EntityNotFound`] if no such entity exists.
+    pub async fn get_entity(&self, id: &str) -> Result<Entity, IdentityError> {
+        if !valid_id(id) {
+            return Err(IdentityError::EntityNotFound { id: id.to_owned() });
+        }
+        self.get_json(&format!("{ENTITY_PREFIX}{id}"))
+            .await?
+            .ok_or_else(|| IdentityError::EntityNotFound { id: id.to_owned() })
+    }
+
+    /// Read an entity by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::EntityNotFound`] if no such entity exists.
+    pub async fn entity_by_name(&self, name: &str) -> Result<Entity, IdentityError> {
+        let id = self
+            .lookup_name(ENTITY_NAME_PREFIX, name)
+            .await?
+            .ok_or_else(|| IdentityError::EntityNotFound {
+                id: name.to_owned(),
+            })?;
+        self.get_entity(&id).await
+    }
+
+    /// List entity IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::Barrier`] if storage fails.
+    pub async fn list_entities(&self) -> Result<Vec<String>, IdentityError> {
+        self.list_ids(ENTITY_PREFIX).await
+    }
+
+    /// Delete an entity, its aliases, and its group memberships.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::EntityNotFound`] for an unknown entity, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn delete_entity(&self, id: &str) -> Result<(), IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        let entity = self.get_entity(id).await?;
+        for alias in self.list_aliases(Some(id)).await? {
+            self.delete_alias_locked(&alias).await?;
+        }
+        for mut group in self.all_groups().await? {
+            if group.member_entity_ids.iter().any(|m| m == id) {
+                group.member_entity_ids.retain(|m| m != id);
+                self.save_group(&group).await?;
+            }
+        }
+        self.barrier
+            .delete(&format!("{ENTITY_NAME_PREFIX}{}", entity.name))
+            .await?;
+        self.barrier.delete(&format!("{ENTITY_PREFIX}{id}")).await?;
+        info!(id = %id, name = %entity.name, "identity entity deleted");
+        Ok(())
+    }
+
+    // ── Aliases ──────────────────────────────────────────────────────
+
+    /// Attach an alias to an entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::EntityNotFound`] for an unknown entity,
+    /// [`IdentityError::AliasInUse`] if the alias belongs to an entity
+    /// already, [`IdentityError::Invalid`] for an unknown auth method, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn create_alias(
+        &self,
+        entity_id: &str,
+        auth_method: &str,
+        name: &str,
+    ) -> Result<EntityAlias, IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        self.get_entity(entity_id).await?;
+        self.create_alias_locked(entity_id, auth_method, name).await
+    }
+
+    async fn create_alias_locked(
+        &self,
+        entity_id: &str,
+        auth_method: &str,
+        name: &str,
+    ) -> Result<EntityAlias, IdentityError> {
+        validate_auth_method(auth_method)?;
+        if name.is_empty() {
+            return Err(invalid("alias name must not be empty"));
+        }
+        let index = alias_index_key(ALIAS_INDEX_PREFIX, auth_method, name);
+        if let Some(existing) = self.get_json::<String>(&index).await? {
+            let alias = self.get_alias(&existing).await?;
+            return Err(IdentityError::AliasInUse {
+                auth_method: auth_method.to_owned(),
+                name: name.to_owned(),
+                entity_id: alias.entity_id,
+            });
+        }
+
+        let alias = EntityAlias {
+            id: uuid::Uuid::new_v4().to_string(),
+            entity_id: entity_id.to_owned(),
+            auth_method: auth_method.to_owned(),
+            name: name.to_owned(),
+            created_at: Utc::now(),
+        };
+        self.put_json(&format!("{ALIAS_PREFIX}{}", alias.id), &alias)
+            .await?;
+        self.put_json(&index, &alias.id).await?;
+        info!(
+            entity_id = %entity_id,
+            auth_method = %auth_method,
+            name = %name,
+            "identity alias created"
+        );
+        Ok(alias)
+    }
+
+    /// Read an alias by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::AliasNotFound`] if no such alias exists.
+    pub async fn get_alias(&self, id: &str) -> Result<EntityAlias, IdentityError> {
+        if !valid_id(id) {
+            return Err(IdentityError::AliasNotFound { id: id.to_owned() });
+        }
+        self.get_json(&format!("{ALIAS_PREFIX}{id}"))
+            .await?
+            .ok_or_else(|| IdentityError::AliasNotFound { id: id.to_owned() })
+    }
+
+    /// List aliases, optionally only those of one entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::Barrier`] if storage fails.
+    pub async fn list_aliases(
+        &self,
+        entity_id: Option<&str>,
+    ) -> Result<Vec<EntityAlias>, IdentityError> {
+        let mut aliases = Vec::new();
+        for id in self.list_ids(ALIAS_PREFIX).await? {
+            match self.get_alias(&id).await {
+                Ok(alias) if entity_id.is_none_or(|e| alias.entity_id == e) => {
+                    aliases.push(alias);
+                }
+                Ok(_) | Err(IdentityError::AliasNotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Delete an alias. Its entity keeps its other aliases.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::AliasNotFound`] for an unknown alias, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn delete_alias(&self, id: &str) -> Result<(), IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        let alias = self.get_alias(id).await?;
+        self.delete_alias_locked(&alias).await
+    }
+
+    async fn delete_alias_locked(&self, alias: &EntityAlias) -> Result<(), IdentityError> {
+        self.barrier
+            .delete(&alias_index_key(
+                ALIAS_INDEX_PREFIX,
+                &alias.auth_method,
+                &alias.name,
+            ))
+            .await?;
+        self.barrier
+            .delete(&format!("{ALIAS_PREFIX}{}", alias.id))
+            .await?;
+        info!(id = %alias.id, entity_id = %alias.entity_id, "identity alias deleted");
+        Ok(())
+    }
+
+    // ── Logins ───────────────────────────────────────────────────────
+
+    /// Resolve a login's entity, creating entity and alias on first use,
+    /// and refresh its membership in the auth method's external groups from
+    /// `groups`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::EntityDisabled`] if the entity is disabled,
+    /// [`IdentityError::Invalid`] for an unknown auth method, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn login(
+        &self,
+        auth_method: &str,
+        alias_name: &str,
+        groups: &[String],
+    ) -> Result<Entity, IdentityError> {
+        validate_auth_method(auth_method)?;
+        let _guard = self.write_lock.lock().await;
+        let index = alias_index_key(ALIAS_INDEX_PREFIX, auth_method, alias_name);
+        let entity = if let Some(alias_id) = self.get_json::<String>(&index).await? {
+            let alias = self.get_alias(&alias_id).await?;
+            self.get_entity(&alias.entity_id).await?
+        } else {
+            let entity = self.create_entity_locked(EntityParams::default()).await?;
+            self.create_alias_locked(&entity.id, auth_method, alias_name)
+                .await?;
+            entity
+        };
+        if entity.disabled {
+            return Err(IdentityError::EntityDisabled { id: entity.id });
+        }
+
+        for mut group in self.all_groups().await? {
+            let Some(alias) = &group.alias else {
+                continue;
+            };
+            if alias.auth_method != auth_method {
+                continue;
+            }
+            let presented = groups.contains(&alias.name);
+            let member = group.member_entity_ids.contains(&entity.id);
+            if presented && !member {
+                group.member_entity_ids.push(entity.id.clone());
+            } else if !presented && member {
+                group.member_entity_ids.retain(|m| *m != entity.id);
+            } else {
+                continue;
+            }
+            group.updated_at = Utc::now();
+            self.save_group(&group).await?;
+        }
+        Ok(entity)
+    }
+
+    /// Policies an entity's tokens gain: the entity's own and those of every
+    /// group containing it, directly or through nested groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::EntityDisabled`] if the entity is disabled,
+    /// [`IdentityError::EntityNotFound`] if it no longer exists, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn policies(&self, entity_id: &str) -> Result<Vec<String>, IdentityError> {
+        let entity = self.get_entity(entity_id).await?;
+        if entity.disabled {
+            return Err(IdentityError::EntityDisabled { id: entity.id });
+        }
+        let mut policies = entity.policies;
+        for group in self.groups_of(entity_id).await? {
+            for policy in group.policies {
+                if !policies.contains(&policy) {
+                    policies.push(policy);
+                }
+            }
+        }
+        Ok(policies)
+    }
+
+    /// Groups containing an entity, directly or through nested groups,
+    /// nearest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::Barrier`] if storage fails.
+    pub async fn groups_of(&self, entity_id: &str) -> Result<Vec<Group>, IdentityError> {
+        let groups = self.all_groups().await?;
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut found = Vec::new();
+        let mut queue: VecDeque<&Group> = groups
+            .iter()
+            .filter(|g| g.member_entity_ids.iter().any(|m| m == entity_id))
+            .collect();
+        while let Some(group) = queue.pop_front() {
+            if !seen.insert(&group.id) {
+                continue;
+            }
+            found.push(group.clone());
+            queue.extend(
+                groups
+                    .iter()
+                    .filter(|g| g.member_group_ids.contains(&group.id)),
+            );
+        }
+        Ok(found)
+    }
+
+    // ── Groups ───────────────────────────────────────────────────────
+
+    /// Create a group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::NameInUse`] if the name or alias is taken,
+    /// [`IdentityError::Invalid`] for members that do not exist or do not
+    /// fit the group type, or [`IdentityError::Barrier`] if storage fails.
+    pub async fn create_group(&self, params: GroupParams) -> Result<Group, IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let mut group = Group {
+            name: format!("group-{}", &id[..8]),
+            id,
+            group_type: params.group_type.unwrap_or_default(),
+            policies: Vec::new(),
+            metadata: BTreeMap::new(),
+            member_entity_ids: Vec::new(),
+            member_group_ids: Vec::new(),
+            alias: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let name = params.name.clone().unwrap_or_else(|| group.name.clone());
+        validate_name(&name)?;
+        self.apply_group_params(&mut group, params).await?;
+        if let Some(alias) = &group.alias {
+            self.check_group_alias(alias).await?;
+        }
+        self.claim_name(GROUP_NAME_PREFIX, &name, &group.id).await?;
+        group.name = name;
+        if let Some(alias) = &group.alias {
+            self.put_json(&group_alias_key(alias), &group.id).await?;
+        }
+        self.save_group(&group).await?;
+        info!(id = %group.id, name = %group.name, "identity group created");
+        Ok(group)
+    }
+
+    /// Update a group's fields. The type cannot change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::GroupNotFound`] for an unknown group, and
+    /// otherwise the same errors as [`IdentityStore::create_group`].
+    pub async fn update_group(
+        &self,
+        id: &str,
+        params: GroupParams,
+    ) -> Result<Group, IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        let mut group = self.get_group(id).await?;
+        if params.group_type.is_some_and(|t| t != group.group_type) {
+            return Err(invalid("a group's type cannot change"));
+        }
+        let old_alias = group.alias.clone();
+        let new_name = params.name.clone().filter(|n| *n != group.name);
+        if let Some(name) = &new_name {
+            validate_name(name)?;
+        }
+        self.apply_group_params(&mut group, params).await?;
+        let alias_changed = group.alias != old_alias;
+        if alias_changed {
+            if let Some(alias) = &group.alias {
+                self.check_group_alias(alias).await?;
+            }
+        }
+
+        if let Some(name) = new_name {
+            self.claim_name(GROUP_NAME_PREFIX, &name, id).await?;
+            self.barrier
+                .delete(&format!("{GROUP_NAME_PREFIX}{}", group.name))
+                .await?;
+            group.name = name;
+        }
+        if alias_changed {
+            if let Some(old) = &old_alias {
+                self.barrier.delete(&group_alias_key(old)).await?;
+            }
+            if let Some(alias) = &group.alias {
+                self.put_json(&group_alias_key(alias), &id).await?;
+            }
+        }
+        group.updated_at = Utc::now();
+        self.save_group(&group).await?;
+        info!(id = %id, name = %group.name, "identity group updated");
+        Ok(group)
+    }
+
+    /// Read a group by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::GroupNotFound`] if no such group exists.
+    pub async fn get_group(&self, id: &str) -> Result<Group, IdentityError> {
+        if !valid_id(id) {
+            return Err(IdentityError::GroupNotFound { id: id.to_owned() });
+        }
+        self.get_json(&format!("{GROUP_PREFIX}{id}"))
+            .await?
+            .ok_or_else(|| IdentityError::GroupNotFound { id: id.to_owned() })
+    }
+
+    /// Read a group by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::GroupNotFound`] if no such group exists.
+    pub async fn group_by_name(&self, name: &str) -> Result<Group, IdentityError> {
+        let id = self
+            .lookup_name(GROUP_NAME_PREFIX, name)
+            .await?
+            .ok_or_else(|| IdentityError::GroupNotFound {
+                id: name.to_owned(),
+            })?;
+        self.get_group(&id).await
+    }
+
+    /// List group IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::Barrier`] if storage fails.
+    pub async fn list_groups(&self) -> Result<Vec<String>, IdentityError> {
+        self.list_ids(GROUP_PREFIX).await
+    }
+
+    /// Delete a group and remove it from the groups containing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::GroupNotFound`] for an unknown group, or
+    /// [`IdentityError::Barrier`] if storage fails.
+    pub async fn delete_group(&self, id: &str) -> Result<(), IdentityError> {
+        let _guard = self.write_lock.lock().await;
+        let group = self.get_group(id).await?;
+        for mut parent in self.all_groups().await? {
+            if parent.member_group_ids.iter().any(|m| m == id) {
+                parent.member_group_ids.retain(|m| m != id);
+                self.save_group(&parent).await?;
+            }
+        }
+        if let Some(alias) = &group.alias {
+            self.barrier.delete(&group_alias_key(alias)).await?;
+        }
+        self.barrier
+            .delete(&format!("{GROUP_NAME_PREFIX}{}", group.name))
+            .await?;
+        self.barrier.delete(&format!("{GROUP_PREFIX}{id}")).await?;
+        info!(id = %id, name = %group.name, "identity group deleted");
+        Ok(())
+    }
+
+    /// Apply everything in `params` except the name, validating members.
+    async fn apply_group_params(
+        &self,
+        group: &mut Group,
+        params: GroupParams,
+    ) -> Result<(), IdentityError> {
+        if let Some(policies) = params.policies {
+            group.policies = policies;
+        }
+        if let Some(metadata) = params.metadata {
+            group.metadata = metadata;
+        }
+        match group.group_type {
+            GroupType::Internal => {
+                if params.alias.is_some() {
+                    return Err(invalid("only external groups have an alias"));
+                }
+                if let Some(members) = params.member_entity_ids {
+                    for member in &members {
+                        self.get_entity(member).await.map_err(|_| {
+                            invalid(format!("member entity {member} does not exist"))
+                        })?;
+                    }
+                    group.member_entity_ids = dedup(members);
+                }
+                if let Some(members) = params.member_group_ids {
+                    self.check_member_groups(&group.id, &members).await?;
+                    group.member_group_ids = dedup(members);
+                }
+            }
+            GroupType::External => {
+                if params.member_entity_ids.is_some() || params.member_group_ids.is_some() {
+                    return Err(invalid(
+                        "external group members come from logins and cannot be set",
+                    ));
+                }
+                if let Some(alias) = params.alias {
+                    validate_auth_method(&alias.auth_method)?;
+                    if alias.name.is_empty() {
+                        return Err(invalid("group alias name must not be empty"));
+                    }
+                    group.alias = Some(alias);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Members must exist and must not contain `group_id`, which would make
+    /// a membership cycle.
+    async fn check_member_groups(
+        &self,
+        group_id: &str,
+        members: &[String],
+    ) -> Result<(), IdentityError> {
+        let groups = self.all_groups().await?;
+        for member in members {
+            if !groups.iter().any(|g| g.id == *member) {
+                return Err(invalid(format!("member group {member} does not exist")));
+            }
+            let mut seen: HashSet<&str> = HashSet::new();
+            let mut queue: VecDeque<&str> = VecDeque::from([member.as_str()]);
+            while let Some(id) = queue.pop_front() {
+                if id == group_id {
+                    return Err(invalid(format!(
+                        "adding group {member} would create a membership cycle"
+                    )));
+                }
+                if !seen.insert(id) {
+                    continue;
+                }
+                if let Some(g) = groups.iter().find(|g| g.id == id) {
+                    queue.extend(g.member_group_ids.iter().map(String::as_str));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // ── Storage helpers ──────────────────────────────────────────────
+
+    async fn all_groups(&self) -> Result<Vec<Group>, IdentityError> {
+        let mut groups = Vec::new();
+        for id in self.list_ids(GROUP_PREFIX).await? {
+            match self.get_group(&id).await {
+                Ok(group) => groups.push(group),
+                Err(IdentityError::GroupNotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(groups)
+    }
+
+    async fn save_group(&self, group: &Group) -> Result<(), IdentityError> {
+        self.put_json(&format!("{GROUP_PREFIX}{}", group.id), group)
+            .await
+    }
+
+    /// Fail if another external group already mirrors `alias`.
+    async fn check_group_alias(&self, alias: &GroupAlias) -> Result<(), IdentityError> {
+        if self.barrier.get(&group_alias_key(alias)).await?.is_some() {
+            return Err(IdentityError::NameInUse {
+                name: format!("{}:{}", alias.auth_method, alias.name),
+            });
+        }
+        Ok(())
+    }
+
+    /// Point the name index at `id`, failing if another record holds it.
+    async fn claim_name(&self, prefix: &str, name: &str, id: &str) -> Result<(), IdentityError> {
+        if self.lookup_name(prefix, name).await?.is_some() {
+            return Err(IdentityError::NameInUse {
+                name: name.to_owned(),
+            });
+        }
+        self.put_json(&format!("{prefix}{name}"), &id).await
+    }
+
+    async fn lookup_name(&self, prefix: &str, name: &str) -> Result<Option<String>, IdentityError> {
+        if validate_name(name).is_err() {
+            return Ok(None);
+        }
+        self.get_json(&format!("{prefix}{name}")).await
+    }
+
+    async fn list_ids(&self, prefix: &str) -> Result<Vec<String>, IdentityError> {
+        let mut ids: Vec<String> = self
+            .barrier
+            .list(prefix)
+            .await?
+            .into_iter()
+            .filter_map(|k| k.strip_prefix(prefix).map(str::to_owned))
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn put_json<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), IdentityError> {
+        let bytes = serde_json::to_vec(value).map_err(|e| IdentityError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(key, &bytes).await?;
+        Ok(())
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, IdentityError> {
+        self.barrier
+            .get(key)
+            .await?
+            .map(|data| {
+                serde_json::from_slice(&data).map_err(|e| IdentityError::Internal {
+                    reason: format!("deserialization failed: {e}"),
+                })
+            })
+            .transpose()
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), IdentityError> {
+    let ok = !name.is_empty()
+        && name.len() <= 128
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'@'));
+    if ok {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "name '{name}' must be 1-128 characters of letters, digits, '-', '_', '.', or '@'"
+        )))
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_hexdigit() || b == b'-')
+}
+
+fn validate_auth_method(auth_method: &str) -> Result<(), IdentityError> {
+    if AUTH_METHODS.contains(&auth_method) {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "unknown auth method '{auth_method}' (supported: {})",
+            AUTH_METHODS.join(", ")
+        )))
+    }
+}
+
+fn alias_index_key(prefix: &str, auth_method: &str, name: &str) -> String {
+    let digest = hex::encode(Sha256::digest(name.as_bytes()));
+    format!("{prefix}{auth_method}/{digest}")
+}
+
+fn group_alias_key(alias: &GroupAlias) -> String {
+    alias_index_key(GROUP_ALIAS_PREFIX, &alias.auth_method, &alias.name)
+}
+
+fn dedup(mut ids: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+    ids
+}
+
+fn invalid(reason: impl Into<String>) -> IdentityError {
+    IdentityError::Invalid {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn store() -> IdentityStore {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        IdentityStore::new(barrier)
+    }
+
+    fn named(name: &str, policies: &[&str]) -> EntityParams {
+        EntityParams {
+            name: Some(name.to_owned()),
+            policies: Some(policies.iter().map(|p| (*p).to_owned()).collect()),
+            ..EntityParams::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn aliases_from_several_auth_methods_share_one_entity() {
+        let store = store().await;
+        let alice = store.create_entity(named("alice", &["dev"])).await.unwrap();
+        store
+            .create_alias(&alice.id, "jwt", "alice@example.com")
+            .await
+            .unwrap();
+        store
+            .create_alias(&alice.id, "approle", "alice-ci")
+            .await
+            .unwrap();
+
+        let via_jwt = store.login("jwt", "alice@example.com", &[]).await.unwrap();
+        let via_approle = store.login("approle", "alice-ci", &[]).await.unwrap();
+        assert_eq!(via_jwt.id, alice.id);
+        assert_eq!(via_approle.id, alice.id);
+
+        let err = store
+            .create_alias(&alice.id, "jwt", "alice@example.com")
+            .await;
+        assert!(matches!(err, Err(IdentityError::AliasInUse { .. })));
+
+        let fresh = store.login("oidc", "sub-123", &[]).await.unwrap();
+        assert_ne!(fresh.id, alice.id);
+        assert_eq!(
+            store.login("oidc", "sub-123", &[]).await.unwrap().id,
+            fresh.id
+        );
+
+        store
+            .update_entity(
+                &alice.id,
+                EntityParams {
+                    disabled: Some(true),
+                    ..EntityParams::default()
+                },
+            )
+            .await
+            .unwrap();
+        let err = store.login("jwt", "alice@example.com", &[]).await;
+        assert!(matches!(err, Err(IdentityError::EntityDisabled { .. })));
+        assert!(matches!(
+            store.policies(&alice.id).await,
+            Err(IdentityError::EntityDisabled { .. })
+        ));
+
+        store.delete_entity(&alice.id).await.unwrap();
+        assert!(
+            store
+                .list_aliases(Some(&alice.id))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(store.entity_by_name("alice").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn group_policies_are_inherited_through_nesting() {
+        let store = store().await;
+        let alice = store.create_entity(named("alice", &["dev"])).await.unwrap();
+        let team = store
+            .create_group(GroupParams {
+                name: Some("team".to_owned()),
+                policies: Some(vec!["team-secrets".to_owned()]),
+                member_entity_ids: Some(vec![alice.id.clone()]),
+                ..GroupParams::default()
+            })
+            .await
+            .unwrap();
+        let eng = store
+            .create_group(GroupParams {
+                name: Some("eng".to_owned()),
+                policies: Some(vec!["eng-read".to_owned(), "dev".to_owned()]),
+                member_group_ids: Some(vec![team.id.clone()]),
+                ..GroupParams::default()
+            })
+            .await
+            .unwrap();
+
+        let policies = store.policies(&alice.id).await.unwrap();
+        assert_eq!(policies, ["dev", "team-secrets", "eng-read"]);
+
+        let cycle = store
+            .update_group(
+                &team.id,
+                GroupParams {
+                    member_group_ids: Some(vec![eng.id.clone()]),
+                    ..GroupParams::default()
+                },
+            )
+            .await;
+        assert!(matches!(cycle, Err(IdentityError::Invalid { .. })));
+
+        store.delete_group(&team.id).await.unwrap();
+        assert!(
+            store
+                .get_group(&eng.id)
+                .await
+                .unwrap()
+                .member_group_ids
+                .is_empty()
+        );
+        assert_eq!(store.policies(&alice.id).await.unwrap(), ["dev"]);
+    }
+
+    #[tokio::test]
+    async fn external_group_membership_follows_logins() {
+        let store = store().await;
+        let ops = store
+            .create_group(GroupParams {
+                name: Some("ops".to_owned()),
+                group_type: Some(GroupType::External),
+                policies: Some(vec!["ops".to_owned()]),
+                alias: Some(GroupAlias {
+                    auth_method: "jwt".to_owned(),
+                    name: "platform-ops".to_owned(),
+                }),
+                ..GroupParams::default()
+            })
+            .await
+            .unwrap();
+        let err = store
+            .update_group(
+                &ops.id,
+                GroupParams {
+                    member_entity_ids: Some(Vec::new()),
+                    ..GroupParams::default()
+                },
+            )
+            .await;
+        assert!(matches!(err, Err(IdentityError::Invalid { .. })));
+
+        let groups = vec!["platform-ops".to_owned(), "other".to_owned()];
+        let bob = store.login("jwt", "bob", &groups).await.unwrap();
+        assert_eq!(store.policies(&bob.id).await.unwrap(), ["ops"]);
+
+        // Logins through other auth methods leave jwt groups alone.
+        store
+            .create_alias(&bob.id, "approle", "bob-ci")
+            .await
+            .unwrap();
+        store.login("approle", "bob-ci", &[]).await.unwrap();
+        assert_eq!(store.policies(&bob.id).await.unwrap(), ["ops"]);
+
+        store.login("jwt", "bob", &[]).await.unwrap();
+        assert!(store.policies(&bob.id).await.unwrap().is_empty());
+    }
+}
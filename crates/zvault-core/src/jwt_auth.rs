@@ -0,0 +1,768 @@
+//! JWT auth method for `ZVault`.
+//!
+//! Lets machines that already hold a signed JWT — CI systems such as GitHub
+//! Actions and GitLab issue one per job — exchange it for a vault token,
+//! with no long-lived credential to distribute. An operator configures where
+//! signing keys come from (a JWKS URL or static PEM public keys) and defines
+//! roles that bind audiences, the subject, and arbitrary claims to policies.
+//!
+//! # Security model
+//!
+//! - Only asymmetric algorithms (RSA, RSA-PSS, ECDSA, `EdDSA`) are accepted;
+//!   `none` and HMAC tokens are rejected before any key lookup.
+//! - Every role must bind at least one audience, so a token minted for some
+//!   other relying party cannot be replayed here.
+//! - JWKS responses are cached; an unknown `kid` triggers at most one refetch
+//!   per [`JWKS_MIN_REFRESH`] so forged headers cannot hammer the issuer.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::barrier::Barrier;
+use crate::error::JwtAuthError;
+use crate::identity::{ALIAS_METADATA, GROUPS_METADATA};
+use crate::token::{CreateTokenParams, TokenEntry, TokenStore};
+
+/// How long a fetched JWKS is trusted before it is refetched.
+pub const JWKS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Minimum gap between JWKS fetches triggered by an unknown `kid`.
+pub const JWKS_MIN_REFRESH: Duration = Duration::from_secs(30);
+
+/// Clock skew tolerated on `exp`, `nbf`, and `iat`.
+const CLOCK_SKEW_LEEWAY_SECS: u64 = 60;
+
+/// Timeout for JWKS requests.
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where signing keys come from, and which issuer to trust.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JwtConfig {
+    /// JWKS endpoint (e.g. `https://token.actions.githubusercontent.com/.well-known/jwks`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_url: Option<String>,
+    /// PEM-encoded public keys, used instead of a JWKS URL.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jwt_validation_pubkeys: Vec<String>,
+    /// Required `iss` claim.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound_issuer: Option<String>,
+}
+
+/// How values in [`JwtRole::bound_claims`] are compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundClaimsType {
+    /// Exact string match.
+    #[default]
+    String,
+    /// Glob match (`*` within a segment, `**` across `/`).
+    Glob,
+}
+
+/// A JWT role: the claims a token must carry and the policies it earns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtRole {
+    /// Role name.
+    #[serde(default)]
+    pub name: String,
+    /// Accepted `aud` values; the token must carry at least one.
+    pub bound_audiences: Vec<String>,
+    /// Required `sub` claim.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound_subject: Option<String>,
+    /// Required claims. Keys are claim names, or JSON pointers (`/a/b`) for
+    /// nested claims; the claim must match one of the listed values.
+    #[serde(default)]
+    pub bound_claims: BTreeMap<String, Vec<String>>,
+    /// How `bound_claims` values are compared.
+    #[serde(default)]
+    pub bound_claims_type: BoundClaimsType,
+    /// Claim used as the token's display name.
+    #[serde(default = "default_user_claim")]
+    pub user_claim: String,
+    /// Claims copied into token metadata, as `claim → metadata key`.
+    #[serde(default)]
+    pub claim_mappings: BTreeMap<String, String>,
+    /// Claim listing the caller's groups, which place its identity entity
+    /// in the matching external groups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups_claim: Option<String>,
+    /// Policies attached to issued tokens.
+    pub policies: Vec<String>,
+    /// Token TTL in seconds.
+    #[serde(default = "default_token_ttl")]
+    pub token_ttl_secs: i64,
+    /// Token max TTL in seconds.
+    #[serde(default = "default_token_max_ttl")]
+    pub token_max_ttl_secs: i64,
+}
+
+fn default_user_claim() -> String {
+    "sub".to_owned()
+}
+
+fn default_token_ttl() -> i64 {
+    3600
+}
+
+fn default_token_max_ttl() -> i64 {
+    86400
+}
+
+/// A fetched JWKS and when it was fetched.
+struct CachedJwks {
+    url: String,
+    fetched_at: Instant,
+    keys: JwkSet,
+}
+
+/// The JWT auth store.
+pub struct JwtAuthStore {
+    barrier: Arc<Barrier>,
+    prefix: String,
+    http: reqwest::Client,
+    jwks: RwLock<Option<CachedJwks>>,
+}
+
+impl JwtAuthStore {
+    /// Create a new JWT auth store.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>, prefix: String) -> Self {
+        Self {
+            barrier,
+            prefix,
+            http: reqwest::Client::builder()
+                .timeout(JWKS_FETCH_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    fn config_key(&self) -> String {
+        format!("{}config", self.prefix)
+    }
+
+    fn role_key(&self, name: &str) -> String {
+        format!("{}roles/{}", self.prefix, name)
+    }
+
+    /// Validate and store the key source.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtAuthError::InvalidConfig` unless exactly one of a JWKS URL
+    /// (HTTPS, or HTTP on loopback) and a list of parseable PEM public keys
+    /// is given.
+    pub async fn write_config(&self, config: JwtConfig) -> Result<(), JwtAuthError> {
+        match (&config.jwks_url, config.jwt_validation_pubkeys.is_empty()) {
+            (Some(url), true) => validate_jwks_url(url)?,
+            (None, false) => {
+                for pem in &config.jwt_validation_pubkeys {
+                    parse_public_key(pem)?;
+                }
+            }
+            _ => {
+                return Err(JwtAuthError::InvalidConfig {
+                    reason: "exactly one of jwks_url or jwt_validation_pubkeys is required"
+                        .to_owned(),
+                });
+            }
+        }
+
+        let data = serde_json::to_vec(&config).map_err(|e| JwtAuthError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(&self.config_key(), &data).await?;
+        *self.jwks.write().await = None;
+        Ok(())
+    }
+
+    /// Read the key source.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtAuthError::NotConfigured` if no config has been written.
+    pub async fn read_config(&self) -> Result<JwtConfig, JwtAuthError> {
+        let data = self
+            .barrier
+            .get(&self.config_key())
+            .await?
+            .ok_or(JwtAuthError::NotConfigured)?;
+        serde_json::from_slice(&data).map_err(|e| JwtAuthError::Internal {
+            reason: format!("deserialization failed: {e}"),
+        })
+    }
+
+    /// Create or replace a role.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtAuthError::InvalidConfig` if the role has no policies, no
+    /// bound audiences, or an empty claim binding.
+    pub async fn write_role(&self, role: JwtRole) -> Result<JwtRole, JwtAuthError> {
+        if role.name.is_empty() {
+            return Err(invalid_config("role name is required"));
+        }
+        if role.policies.is_empty() {
+            return Err(invalid_config("at least one policy is required"));
+        }
+        if role.bound_audiences.is_empty() {
+            return Err(invalid_config("at least one bound audience is required"));
+        }
+        if role.user_claim.is_empty() {
+            return Err(invalid_config("user_claim must not be empty"));
+        }
+        if let Some((claim, _)) = role.bound_claims.iter().find(|(_, v)| v.is_empty()) {
+            return Err(JwtAuthError::InvalidConfig {
+                reason: format!("bound claim '{claim}' has no allowed values"),
+            });
+        }
+
+        let data = serde_json::to_vec(&role).map_err(|e| JwtAuthError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(&self.role_key(&role.name), &data).await?;
+        Ok(role)
+    }
+
+    /// Get a role by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtAuthError::RoleNotFound` if the role does not exist.
+    pub async fn get_role(&self, name: &str) -> Result<JwtRole, JwtAuthError> {
+        let data = self
+            .barrier
+            .get(&self.role_key(name))
+            .await?
+            .ok_or_else(|| JwtAuthError::RoleNotFound {
+                name: name.to_owned(),
+            })?;
+        serde_json::from_slice(&data).map_err(|e| JwtAuthError::Internal {
+            reason: format!("deserialization failed: {e}"),
+        })
+    }
+
+    /// Delete a role.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtAuthError::Barrier` if the barrier is sealed.
+    pub async fn delete_role(&self, name: &str) -> Result<(), JwtAuthError> {
+        self.barrier.delete(&self.role_key(name)).await?;
+        Ok(())
+    }
+
+    /// List all role names.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtAuthError::Barrier` if the barrier is sealed.
+    pub async fn list_roles(&self) -> Result<Vec<String>, JwtAuthError> {
+        let prefix = format!("{}roles/", self.prefix);
+        let keys = self.barrier.list(&prefix).await?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|k| k.strip_prefix(&prefix).map(String::from))
+            .collect())
+    }
+
+    /// Exchange a JWT for a vault token under `role_name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JwtAuthError::RoleNotFound` for an unknown role,
+    /// `JwtAuthError::InvalidToken` if the signature, timing, audience,
+    /// issuer, or any bound claim does not check out, and
+    /// `JwtAuthError::Jwks` if signing keys cannot be fetched.
+    pub async fn login(
+        &self,
+        role_name: &str,
+        jwt: &str,
+        token_store: &TokenStore,
+    ) -> Result<(String, TokenEntry), JwtAuthError> {
+        let role = self.get_role(role_name).await?;
+        let config = self.read_config().await?;
+        let claims = self.verify(&config, &role, jwt).await?;
+        check_bound_claims(&role, &claims)?;
+
+        let user = claim_string(&claims, &role.user_claim)
+            .ok_or_else(|| invalid_token(&format!("missing user claim '{}'", role.user_claim)))?;
+
+        let mut metadata = HashMap::from([
+            ("role".to_owned(), role.name.clone()),
+            (ALIAS_METADATA.to_owned(), user.clone()),
+        ]);
+        for (claim, key) in &role.claim_mappings {
+            if let Some(value) = claim_string(&claims, claim) {
+                metadata.insert(key.clone(), value);
+            }
+        }
+        if let Some(claim) = &role.groups_claim {
+            metadata.insert(
+                GROUPS_METADATA.to_owned(),
+                claim_values(&claims, claim).join(","),
+            );
+        }
+
+        let plaintext_token = token_store
+            .create(CreateTokenParams {
+                policies: role.policies.clone(),
+                ttl: Some(chrono::Duration::seconds(role.token_ttl_secs)),
+                max_ttl: Some(chrono::Duration::seconds(role.token_max_ttl_secs)),
+                renewable: true,
+                parent_hash: None,
+                metadata,
+                display_name: format!("jwt-{user}"),
+                bound_cidrs: Vec::new(),
+            })
+            .await
+            .map_err(|e| JwtAuthError::Internal {
+                reason: format!("token creation failed: {e}"),
+            })?;
+
+        let token_entry =
+            token_store
+                .lookup(&plaintext_token)
+                .await
+                .map_err(|e| JwtAuthError::Internal {
+                    reason: format!("token lookup failed: {e}"),
+                })?;
+
+        Ok((plaintext_token, token_entry))
+    }
+
+    /// Check the signature and registered claims, returning all claims.
+    async fn verify(
+        &self,
+        config: &JwtConfig,
+        role: &JwtRole,
+        jwt: &str,
+    ) -> Result<serde_json::Value, JwtAuthError> {
+        let header = jsonwebtoken::decode_header(jwt)
+            .map_err(|e| invalid_token(&format!("malformed token: {e}")))?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(invalid_token(
+                "symmetric signing algorithms are not accepted",
+            ));
+        }
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = CLOCK_SKEW_LEEWAY_SECS;
+        validation.set_required_spec_claims(&["exp", "aud"]);
+        validation.set_audience(&role.bound_audiences);
+        if let Some(issuer) = &config.bound_issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        let keys = self.decoding_keys(config, header.kid.as_deref()).await?;
+        let mut last_error = invalid_token("no signing key matches the token");
+        for key in &keys {
+            match jsonwebtoken::decode::<serde_json::Value>(jwt, key, &validation) {
+                Ok(data) => return Ok(data.claims),
+                Err(e) => last_error = invalid_token(&e.to_string()),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Candidate verification keys for a token with key ID `kid`.
+    async fn decoding_keys(
+        &self,
+        config: &JwtConfig,
+        kid: Option<&str>,
+    ) -> Result<Vec<DecodingKey>, JwtAuthError> {
+        let Some(url) = &config.jwks_url else {
+            return config
+                .jwt_validation_pubkeys
+                .iter()
+                .map(|pem| parse_public_key(pem))
+                .collect();
+        };
+
+        let cached = self.cached_keys(url, kid, JWKS_CACHE_TTL).await;
+        let keys = match cached {
+            Some(keys) if !keys.is_empty() => keys,
+            _ => {
+                // Unknown kid or stale cache: refetch, at most every
+                // JWKS_MIN_REFRESH. A throttled miss falls back to the cache.
+                if let Some(keys) = self.cached_keys(url, kid, JWKS_MIN_REFRESH).await {
+                    keys
+                } else {
+                    self.refresh_jwks(url).await?;
+                    self.cached_keys(url, kid, JWKS_CACHE_TTL)
+                        .await
+                        .unwrap_or_default()
+                }
+            }
+        };
+        if keys.is_empty() {
+            return Err(invalid_token("no signing key matches the token's kid"));
+        }
+        Ok(keys)
+    }
+
+    /// Keys from a cached JWKS for `url` younger than `max_age`.
+    async fn cached_keys(
+        &self,
+        url: &str,
+        kid: Option<&str>,
+        max_age: Duration,
+    ) -> Option<Vec<DecodingKey>> {
+        let guard = self.jwks.read().await;
+        let cached = guard
+            .as_ref()
+            .filter(|c| c.url == url && c.fetched_at.elapsed() < max_age)?;
+        let jwks = match kid {
+            Some(kid) => cached.keys.find(kid).into_iter().collect::<Vec<_>>(),
+            None => cached.keys.keys.iter().collect(),
+        };
+        Some(
+            jwks.into_iter()
+                .filter_map(|jwk| DecodingKey::from_jwk(jwk).ok())
+                .collect(),
+        )
+    }
+
+    async fn refresh_jwks(&self, url: &str) -> Result<(), JwtAuthError> {
+        let keys: JwkSet = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| JwtAuthError::Jwks {
+                reason: format!("failed to fetch {url}: {e}"),
+            })?
+            .json()
+            .await
+            .map_err(|e| JwtAuthError::Jwks {
+                reason: format!("invalid JWKS from {url}: {e}"),
+            })?;
+        *self.jwks.write().await = Some(CachedJwks {
+            url: url.to_owned(),
+            fetched_at: Instant::now(),
+            keys,
+        });
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for JwtAuthStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtAuthStore")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Require the token's `sub` and bound claims to match the role.
+fn check_bound_claims(role: &JwtRole, claims: &serde_json::Value) -> Result<(), JwtAuthError> {
+    if let Some(subject) = &role.bound_subject {
+        if claims.get("sub").and_then(serde_json::Value::as_str) != Some(subject.as_str()) {
+            return Err(invalid_token("sub claim does not match bound_subject"));
+        }
+    }
+
+    for (claim, allowed) in &role.bound_claims {
+        let values = claim_values(claims, claim);
+        let matched = values.iter().any(|value| {
+            allowed.iter().any(|pattern| match role.bound_claims_type {
+                BoundClaimsType::String => pattern == value,
+                BoundClaimsType::Glob => glob_match::glob_match(pattern, value),
+            })
+        });
+        if !matched {
+            return Err(JwtAuthError::InvalidToken {
+                reason: format!("claim '{claim}' does not match any bound value"),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Look up a claim by name, or by JSON pointer when it starts with `/`.
+fn claim<'a>(claims: &'a serde_json::Value, name: &str) -> Option<&'a serde_json::Value> {
+    if name.starts_with('/') {
+        claims.pointer(name)
+    } else {
+        claims.get(name)
+    }
+}
+
+/// A scalar claim as a string.
+fn claim_string(claims: &serde_json::Value, name: &str) -> Option<String> {
+    scalar_string(claim(claims, name)?)
+}
+
+/// A claim as strings: one for a scalar, one per scalar element of a list.
+fn claim_values(claims: &serde_json::Value, name: &str) -> Vec<String> {
+    match claim(claims, name) {
+        Some(serde_json::Value::Array(items)) => items.iter().filter_map(scalar_string).collect(),
+        Some(value) => scalar_string(value).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+fn scalar_string(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse a PEM public key of any supported type.
+fn parse_public_key(pem: &str) -> Result<DecodingKey, JwtAuthError> {
+    let bytes = pem.as_bytes();
+    DecodingKey::from_rsa_pem(bytes)
+        .or_else(|_| DecodingKey::from_ec_pem(bytes))
+        .or_else(|_| DecodingKey::from_ed_pem(bytes))
+        .map_err(|_| {
+            invalid_config(
+                "jwt_validation_pubkeys must be PEM-encoded RSA, EC, or Ed25519 public keys",
+            )
+        })
+}
+
+fn validate_jwks_url(raw: &str) -> Result<(), JwtAuthError> {
+    let url = url::Url::parse(raw).map_err(|e| JwtAuthError::InvalidConfig {
+        reason: format!("invalid jwks_url: {e}"),
+    })?;
+    let loopback = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    if url.scheme() == "https" || (url.scheme() == "http" && loopback) {
+        Ok(())
+    } else {
+        Err(invalid_config("jwks_url must use https"))
+    }
+}
+
+fn invalid_config(reason: &str) -> JwtAuthError {
+    JwtAuthError::InvalidConfig {
+        reason: reason.to_owned(),
+    }
+}
+
+fn invalid_token(reason: &str) -> JwtAuthError {
+    JwtAuthError::InvalidToken {
+        reason: reason.to_owned(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use base64::Engine as _;
+    use jsonwebtoken::{EncodingKey, Header};
+    use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+    use rsa::traits::PublicKeyParts;
+    use rsa::{RsaPrivateKey, RsaPublicKey};
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    struct Fixture {
+        store: JwtAuthStore,
+        tokens: TokenStore,
+        signing_key: EncodingKey,
+        public_key: RsaPublicKey,
+    }
+
+    async fn fixture() -> Fixture {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+
+        let private = RsaPrivateKey::new(&mut rsa::rand_core::OsRng, 2048).unwrap();
+        let public_key = RsaPublicKey::from(&private);
+        let pem = private.to_pkcs8_pem(LineEnding::LF).unwrap();
+
+        Fixture {
+            store: JwtAuthStore::new(Arc::clone(&barrier), "sys/jwt/".to_owned()),
+            tokens: TokenStore::new(barrier),
+            signing_key: EncodingKey::from_rsa_pem(pem.as_bytes()).unwrap(),
+            public_key,
+        }
+    }
+
+    fn ci_role() -> JwtRole {
+        serde_json::from_value(serde_json::json!({
+            "name": "deploy",
+            "bound_audiences": ["https://vault.example.com"],
+            "bound_claims": { "repository": ["acme/*"], "ref": ["refs/heads/main"] },
+            "bound_claims_type": "glob",
+            "user_claim": "repository",
+            "claim_mappings": { "run_id": "ci_run" },
+            "policies": ["deploy"],
+        }))
+        .unwrap()
+    }
+
+    fn sign(f: &Fixture, kid: Option<&str>, claims: &serde_json::Value) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = kid.map(str::to_owned);
+        jsonwebtoken::encode(&header, claims, &f.signing_key).unwrap()
+    }
+
+    fn ci_claims(repository: &str, aud: &str) -> serde_json::Value {
+        let now = chrono::Utc::now().timestamp();
+        serde_json::json!({
+            "iss": "https://token.actions.githubusercontent.com",
+            "sub": format!("repo:{repository}:ref:refs/heads/main"),
+            "aud": aud,
+            "repository": repository,
+            "ref": "refs/heads/main",
+            "run_id": 42,
+            "iat": now,
+            "exp": now + 300,
+        })
+    }
+
+    #[tokio::test]
+    async fn static_key_login_enforces_bindings() {
+        let f = fixture().await;
+        let pem = f.public_key.to_public_key_pem(LineEnding::LF).unwrap();
+        f.store
+            .write_config(JwtConfig {
+                jwt_validation_pubkeys: vec![pem],
+                bound_issuer: Some("https://token.actions.githubusercontent.com".to_owned()),
+                ..JwtConfig::default()
+            })
+            .await
+            .unwrap();
+        f.store.write_role(ci_role()).await.unwrap();
+
+        let jwt = sign(
+            &f,
+            None,
+            &ci_claims("acme/api", "https://vault.example.com"),
+        );
+        let (token, entry) = f.store.login("deploy", &jwt, &f.tokens).await.unwrap();
+        assert!(!token.is_empty());
+        assert_eq!(entry.policies, vec!["deploy".to_owned()]);
+        assert_eq!(entry.display_name, "jwt-acme/api");
+        assert_eq!(entry.metadata.get("ci_run").map(String::as_str), Some("42"));
+        assert_eq!(
+            entry.metadata.get(ALIAS_METADATA).map(String::as_str),
+            Some("acme/api")
+        );
+
+        let other_repo = sign(
+            &f,
+            None,
+            &ci_claims("evil/api", "https://vault.example.com"),
+        );
+        let err = f.store.login("deploy", &other_repo, &f.tokens).await;
+        assert!(matches!(err, Err(JwtAuthError::InvalidToken { .. })));
+
+        let other_aud = sign(&f, None, &ci_claims("acme/api", "https://elsewhere"));
+        let err = f.store.login("deploy", &other_aud, &f.tokens).await;
+        assert!(matches!(err, Err(JwtAuthError::InvalidToken { .. })));
+
+        let mut expired = ci_claims("acme/api", "https://vault.example.com");
+        expired["exp"] = serde_json::json!(chrono::Utc::now().timestamp() - 600);
+        let err = f
+            .store
+            .login("deploy", &sign(&f, None, &expired), &f.tokens)
+            .await;
+        assert!(matches!(err, Err(JwtAuthError::InvalidToken { .. })));
+
+        let hs = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &ci_claims("acme/api", "https://vault.example.com"),
+            &EncodingKey::from_secret(b"guessable"),
+        )
+        .unwrap();
+        let err = f.store.login("deploy", &hs, &f.tokens).await;
+        assert!(matches!(err, Err(JwtAuthError::InvalidToken { .. })));
+    }
+
+    #[tokio::test]
+    async fn jwks_keys_are_selected_by_kid() {
+        let f = fixture().await;
+        let url = "https://issuer.example.com/.well-known/jwks";
+        f.store
+            .write_config(JwtConfig {
+                jwks_url: Some(url.to_owned()),
+                ..JwtConfig::default()
+            })
+            .await
+            .unwrap();
+        f.store.write_role(ci_role()).await.unwrap();
+
+        let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        let keys: JwkSet = serde_json::from_value(serde_json::json!({
+            "keys": [{
+                "kty": "RSA",
+                "kid": "key-1",
+                "alg": "RS256",
+                "use": "sig",
+                "n": b64.encode(f.public_key.n().to_bytes_be()),
+                "e": b64.encode(f.public_key.e().to_bytes_be()),
+            }]
+        }))
+        .unwrap();
+        *f.store.jwks.write().await = Some(CachedJwks {
+            url: url.to_owned(),
+            fetched_at: Instant::now(),
+            keys,
+        });
+
+        let claims = ci_claims("acme/api", "https://vault.example.com");
+        let jwt = sign(&f, Some("key-1"), &claims);
+        f.store.login("deploy", &jwt, &f.tokens).await.unwrap();
+
+        // An unknown kid within the refresh window is rejected without a fetch.
+        let jwt = sign(&f, Some("key-2"), &claims);
+        let err = f.store.login("deploy", &jwt, &f.tokens).await;
+        assert!(matches!(err, Err(JwtAuthError::InvalidToken { .. })));
+    }
+
+    #[tokio::test]
+    async fn config_and_roles_are_validated() {
+        let f = fixture().await;
+        assert!(f.store.write_config(JwtConfig::default()).await.is_err());
+        assert!(
+            f.store
+                .write_config(JwtConfig {
+                    jwks_url: Some("http://issuer.example.com/jwks".to_owned()),
+                    ..JwtConfig::default()
+                })
+                .await
+                .is_err()
+        );
+        assert!(
+            f.store
+                .write_config(JwtConfig {
+                    jwt_validation_pubkeys: vec!["not a key".to_owned()],
+                    ..JwtConfig::default()
+                })
+                .await
+                .is_err()
+        );
+
+        let mut role = ci_role();
+        role.bound_audiences.clear();
+        assert!(f.store.write_role(role).await.is_err());
+
+        f.store.write_role(ci_role()).await.unwrap();
+        assert_eq!(f.store.list_roles().await.unwrap(), vec!["deploy"]);
+        f.store.delete_role("deploy").await.unwrap();
+        assert!(matches!(
+            f.store.get_role("deploy").await,
+            Err(JwtAuthError::RoleNotFound { .. })
+        ));
+    }
+}
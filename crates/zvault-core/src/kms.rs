@@ -0,0 +1,211 @@
+//! Key management services for auto-unseal.
+//!
+//! With a [`SealWrapper`] configured, the root key is encrypted by an
+//! external KMS instead of a Shamir-split unseal key, so the vault can unseal
+//! itself on startup. Operators hold recovery shares instead of unseal shares
+//! (see [`crate::seal`]).
+//!
+//! [`DevKms`] is a file-based stand-in for a cloud KMS: the wrapping key is a
+//! random AES-256 key in a local file. It exercises the same auto-unseal,
+//! recovery, and migration paths as a real KMS, without credentials.
+//!
+//! # Security model
+//!
+//! `DevKms` protects the root key only as well as the key file is protected.
+//! It is meant for local development and tests, never for production.
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use tracing::warn;
+use zeroize::Zeroizing;
+
+use crate::crypto::{self, EncryptionKey};
+use crate::error::SealError;
+
+/// A KMS that encrypts the root key for auto-unseal.
+#[async_trait::async_trait]
+pub trait SealWrapper: Send + Sync {
+    /// Seal type recorded in the seal config (e.g. `devkms`).
+    fn name(&self) -> &str;
+
+    /// Encrypt `plaintext` with the KMS key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SealError::Wrapper`] if the KMS rejects the request.
+    async fn wrap(&self, plaintext: &[u8]) -> Result<Vec<u8>, SealError>;
+
+    /// Decrypt a value produced by [`wrap`](Self::wrap).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SealError::Wrapper`] if the KMS rejects the request or the
+    /// ciphertext was wrapped by a different key.
+    async fn unwrap(&self, ciphertext: &[u8]) -> Result<Vec<u8>, SealError>;
+}
+
+/// File-based development KMS.
+pub struct DevKms {
+    path: PathBuf,
+    key: EncryptionKey,
+}
+
+impl DevKms {
+    /// Load the key file at `path`, generating it (mode `0600`) if missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SealError::Wrapper`] if the file cannot be read or written,
+    /// or does not hold a base64-encoded 32-byte key.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self, SealError> {
+        let path = path.into();
+        let key = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => parse_key(&path, &contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let key = EncryptionKey::generate();
+                write_key(&path, &key).await?;
+                warn!(path = %path.display(), "generated dev KMS key — not for production use");
+                key
+            }
+            Err(e) => {
+                return Err(wrapper_error(format!(
+                    "failed to read dev KMS key {}: {e}",
+                    path.display()
+                )));
+            }
+        };
+        Ok(Self { path, key })
+    }
+
+    /// Path of the key file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait::async_trait]
+impl SealWrapper for DevKms {
+    fn name(&self) -> &'static str {
+        "devkms"
+    }
+
+    async fn wrap(&self, plaintext: &[u8]) -> Result<Vec<u8>, SealError> {
+        crypto::encrypt(&self.key, plaintext).map_err(|e| wrapper_error(e.to_string()))
+    }
+
+    async fn unwrap(&self, ciphertext: &[u8]) -> Result<Vec<u8>, SealError> {
+        crypto::decrypt(&self.key, ciphertext).map_err(|e| wrapper_error(e.to_string()))
+    }
+}
+
+impl std::fmt::Debug for DevKms {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DevKms")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+fn parse_key(path: &Path, contents: &str) -> Result<EncryptionKey, SealError> {
+    let bytes = Zeroizing::new(
+        BASE64
+            .decode(contents.trim())
+            .map_err(|e| wrapper_error(format!("invalid dev KMS key {}: {e}", path.display())))?,
+    );
+    let array: [u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+        wrapper_error(format!(
+            "dev KMS key {} must be 32 bytes, got {}",
+            path.display(),
+            bytes.len()
+        ))
+    })?;
+    Ok(EncryptionKey::from_bytes(array))
+}
+
+async fn write_key(path: &Path, key: &EncryptionKey) -> Result<(), SealError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| wrapper_error(format!("failed to create {}: {e}", parent.display())))?;
+    }
+
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let encoded = Zeroizing::new(BASE64.encode(key.as_bytes()));
+    let mut file = options.open(path).await.map_err(|e| {
+        wrapper_error(format!(
+            "failed to create dev KMS key {}: {e}",
+            path.display()
+        ))
+    })?;
+    tokio::io::AsyncWriteExt::write_all(&mut file, encoded.as_bytes())
+        .await
+        .map_err(|e| wrapper_error(format!("failed to write dev KMS key: {e}")))
+}
+
+fn wrapper_error(reason: String) -> SealError {
+    SealError::Wrapper { reason }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("zvault-kms-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn key_file_is_created_once_and_reused() {
+        let dir = temp_dir();
+        let path = dir.join("kms/dev.key");
+
+        let first = DevKms::open(&path).await.unwrap();
+        let wrapped = first.wrap(b"root key").await.unwrap();
+
+        let second = DevKms::open(&path).await.unwrap();
+        assert_eq!(second.unwrap(&wrapped).await.unwrap(), b"root key");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn other_key_cannot_unwrap() {
+        let dir = temp_dir();
+        let a = DevKms::open(dir.join("a.key")).await.unwrap();
+        let b = DevKms::open(dir.join("b.key")).await.unwrap();
+
+        let wrapped = a.wrap(b"root key").await.unwrap();
+        let err = b.unwrap(&wrapped).await.unwrap_err();
+        assert!(matches!(err, SealError::Wrapper { .. }));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn malformed_key_file_is_rejected() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bad.key");
+        std::fs::write(&path, "c2hvcnQ=").unwrap();
+
+        let err = DevKms::open(&path).await.unwrap_err();
+        assert!(matches!(err, SealError::Wrapper { .. }));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -5,15 +5,25 @@
 //! finds expired leases and triggers revocation through the originating engine.
 //!
 //! Leases are stored through the barrier at `sys/leases/<id>`.
+//!
+//! Engines that need cleanup when a lease ends (dropping a database user,
+//! tombstoning a certificate) register a [`RevocationHandler`] for their
+//! mount path. The handler runs before the lease is deleted; if it fails,
+//! the lease is kept so the next expiry tick retries the revocation.
+//!
+//! With [`LeaseManager::with_quotas`], creating a lease under a path with a
+//! full lease count quota fails with [`LeaseError::QuotaExceeded`].
 
 use std::sync::Arc;
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
 use tracing::{info, warn};
 
 use crate::barrier::Barrier;
 use crate::error::LeaseError;
+use crate::quota::QuotaManager;
 
 /// Storage prefix for lease entries.
 const LEASE_PREFIX: &str = "sys/leases/";
@@ -29,6 +39,9 @@ pub struct Lease {
     pub issued_at: DateTime<Utc>,
     /// Time-to-live from issuance.
     pub ttl_secs: i64,
+    /// Upper bound on `ttl_secs` across renewals. `0` means no cap.
+    #[serde(default)]
+    pub max_ttl_secs: i64,
     /// Whether the lease can be renewed.
     pub renewable: bool,
     /// Engine-specific data needed for revocation (e.g., username to drop).
@@ -52,16 +65,84 @@ impl Lease {
     }
 }
 
+/// Engine-specific cleanup run when one of the engine's leases is revoked.
+#[async_trait::async_trait]
+pub trait RevocationHandler: Send + Sync {
+    /// Revoke the credential described by `lease` (e.g., drop the DB user).
+    ///
+    /// Must be idempotent: a lease whose revocation partially succeeded is
+    /// retried on the next expiry tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LeaseError::RevocationFailed`] if cleanup could not be completed.
+    async fn revoke_lease(&self, lease: &Lease) -> Result<(), LeaseError>;
+}
+
 /// Manages lease creation, renewal, revocation, and expiry scanning.
 pub struct LeaseManager {
     barrier: Arc<Barrier>,
+    /// Revocation handlers keyed by engine path prefix (e.g., `database/`).
+    handlers: RwLock<Vec<(String, Arc<dyn RevocationHandler>)>>,
+    /// Lease count quotas checked on create.
+    quotas: Option<Arc<QuotaManager>>,
+    /// Serializes quota-limited creates so concurrent ones cannot overshoot.
+    quota_lock: Mutex<()>,
 }
 
 impl LeaseManager {
     /// Create a new lease manager backed by the given barrier.
     #[must_use]
     pub fn new(barrier: Arc<Barrier>) -> Self {
-        Self { barrier }
+        Self {
+            barrier,
+            handlers: RwLock::new(Vec::new()),
+            quotas: None,
+            quota_lock: Mutex::new(()),
+        }
+    }
+
+    /// Enforce the lease count quotas of `quotas` on create.
+    #[must_use]
+    pub fn with_quotas(mut self, quotas: Arc<QuotaManager>) -> Self {
+        self.quotas = Some(quotas);
+        self
+    }
+
+    /// Register a revocation handler for leases whose engine path starts
+    /// with `prefix`. Replaces any handler previously registered for it.
+    pub async fn register_handler(&self, prefix: &str, handler: Arc<dyn RevocationHandler>) {
+        let mut handlers = self.handlers.write().await;
+        handlers.retain(|(p, _)| p != prefix);
+        handlers.push((prefix.to_owned(), handler));
+        info!(prefix = %prefix, "lease revocation handler registered");
+    }
+
+    /// Remove the revocation handler registered for `prefix`, if any.
+    pub async fn unregister_handler(&self, prefix: &str) {
+        self.handlers.write().await.retain(|(p, _)| p != prefix);
+    }
+
+    /// Find the handler with the longest prefix matching `engine_path`.
+    async fn handler_for(&self, engine_path: &str) -> Option<Arc<dyn RevocationHandler>> {
+        self.handlers
+            .read()
+            .await
+            .iter()
+            .filter(|(prefix, _)| engine_path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, handler)| Arc::clone(handler))
+    }
+
+    /// Run the engine's handler (if any), then delete the stored lease.
+    async fn revoke_lease(&self, lease: &Lease) -> Result<(), LeaseError> {
+        if let Some(handler) = self.handler_for(&lease.engine_path).await {
+            handler.revoke_lease(lease).await?;
+        }
+
+        let key = format!("{LEASE_PREFIX}{}", lease.id);
+        self.barrier.delete(&key).await?;
+        Ok(())
     }
 
     /// Create a new lease and persist it.
@@ -70,8 +151,34 @@ impl LeaseManager {
     ///
     /// # Errors
     ///
-    /// Returns [`LeaseError::Barrier`] if storage fails.
+    /// - [`LeaseError::QuotaExceeded`] if a lease count quota covering the
+    ///   lease's engine path is full.
+    /// - [`LeaseError::Barrier`] if storage fails.
     pub async fn create(&self, lease: &Lease) -> Result<String, LeaseError> {
+        let quota = match &self.quotas {
+            Some(quotas) => quotas.lease_count_for(&lease.engine_path).await,
+            None => None,
+        };
+        let _guard = match &quota {
+            Some(quota) => {
+                let guard = self.quota_lock.lock().await;
+                let active = self
+                    .list_all()
+                    .await?
+                    .iter()
+                    .filter(|l| l.engine_path.starts_with(&quota.path) && !l.is_expired())
+                    .count();
+                if u64::try_from(active).unwrap_or(u64::MAX) >= quota.max_leases {
+                    return Err(LeaseError::QuotaExceeded {
+                        quota: quota.name.clone(),
+                        max_leases: quota.max_leases,
+                    });
+                }
+                Some(guard)
+            }
+            None => None,
+        };
+
         let bytes = serde_json::to_vec(lease).map_err(|e| {
             LeaseError::Barrier(crate::error::BarrierError::Crypto(
                 crate::error::CryptoError::Encryption {
@@ -115,11 +222,14 @@ impl LeaseManager {
 
     /// Renew a lease by extending its TTL.
     ///
+    /// The new TTL is clamped to the lease's `max_ttl_secs` when one is set.
+    ///
     /// # Errors
     ///
     /// - [`LeaseError::NotFound`] if the lease doesn't exist.
     /// - [`LeaseError::NotRenewable`] if the lease isn't renewable.
     /// - [`LeaseError::Expired`] if the lease has already expired.
+    /// - [`LeaseError::MaxTtlReached`] if the lease is already at its max TTL.
     /// - [`LeaseError::Barrier`] if storage fails.
     pub async fn renew(&self, lease_id: &str, increment_secs: i64) -> Result<Lease, LeaseError> {
         let mut lease = self.lookup(lease_id).await?;
@@ -136,7 +246,17 @@ impl LeaseManager {
             });
         }
 
-        lease.ttl_secs = lease.ttl_secs.saturating_add(increment_secs);
+        let mut new_ttl = lease.ttl_secs.saturating_add(increment_secs.max(0));
+        if lease.max_ttl_secs > 0 {
+            if lease.ttl_secs >= lease.max_ttl_secs {
+                return Err(LeaseError::MaxTtlReached {
+                    lease_id: lease_id.to_owned(),
+                    max_ttl_secs: lease.max_ttl_secs,
+                });
+            }
+            new_ttl = new_ttl.min(lease.max_ttl_secs);
+        }
+        lease.ttl_secs = new_ttl;
 
         let bytes = serde_json::to_vec(&lease).map_err(|e| {
             LeaseError::Barrier(crate::error::BarrierError::Crypto(
@@ -156,15 +276,22 @@ impl LeaseManager {
 
     /// Revoke a lease immediately.
     ///
-    /// This only removes the lease from storage. The caller is responsible
-    /// for calling the engine's revocation logic (e.g., dropping the DB user).
+    /// Runs the registered [`RevocationHandler`] for the lease's engine, then
+    /// removes the lease from storage. Revoking an unknown lease is a no-op.
     ///
     /// # Errors
     ///
-    /// Returns [`LeaseError::Barrier`] if storage fails.
+    /// - [`LeaseError::RevocationFailed`] if the engine cleanup failed; the
+    ///   lease is kept so revocation can be retried.
+    /// - [`LeaseError::Barrier`] if storage fails.
     pub async fn revoke(&self, lease_id: &str) -> Result<(), LeaseError> {
-        let key = format!("{LEASE_PREFIX}{lease_id}");
-        self.barrier.delete(&key).await?;
+        let lease = match self.lookup(lease_id).await {
+            Ok(lease) => lease,
+            Err(LeaseError::NotFound { .. }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        self.revoke_lease(&lease).await?;
 
         info!(lease_id = %lease_id, "lease revoked");
 
@@ -232,31 +359,66 @@ impl LeaseManager {
         Ok(leases)
     }
 
-    /// Revoke all leases matching a prefix (e.g., when unmounting an engine).
+    /// Revoke all leases matching a prefix (e.g., when unmounting an engine
+    /// or responding to a compromised mount).
+    ///
+    /// Returns the revoked leases. Leases whose engine cleanup fails are
+    /// logged and kept so they can be retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LeaseError::Barrier`] if storage fails.
+    pub async fn revoke_prefix(&self, engine_path_prefix: &str) -> Result<Vec<Lease>, LeaseError> {
+        self.revoke_matching(engine_path_prefix, false).await
+    }
+
+    /// Revoke all leases matching a prefix, removing them even when the
+    /// engine's cleanup fails.
     ///
-    /// Returns the number of leases revoked.
+    /// For when the backing system is gone (e.g., a decommissioned database)
+    /// and revocation can never succeed. Credentials whose cleanup failed may
+    /// still be valid on the backing system.
     ///
     /// # Errors
     ///
     /// Returns [`LeaseError::Barrier`] if storage fails.
-    pub async fn revoke_prefix(&self, engine_path_prefix: &str) -> Result<u64, LeaseError> {
+    pub async fn revoke_force(&self, engine_path_prefix: &str) -> Result<Vec<Lease>, LeaseError> {
+        self.revoke_matching(engine_path_prefix, true).await
+    }
+
+    async fn revoke_matching(
+        &self,
+        engine_path_prefix: &str,
+        force: bool,
+    ) -> Result<Vec<Lease>, LeaseError> {
         let keys = self.barrier.list(LEASE_PREFIX).await?;
-        let mut count = 0u64;
+        let mut revoked = Vec::new();
 
         for key in &keys {
             if let Ok(Some(data)) = self.barrier.get(key).await {
                 if let Ok(lease) = serde_json::from_slice::<Lease>(&data) {
-                    if lease.engine_path.starts_with(engine_path_prefix) {
-                        self.barrier.delete(key).await?;
-                        count = count.saturating_add(1);
+                    if !lease.engine_path.starts_with(engine_path_prefix) {
+                        continue;
+                    }
+                    match self.revoke_lease(&lease).await {
+                        Ok(()) => revoked.push(lease),
+                        Err(LeaseError::RevocationFailed { reason, .. }) if force => {
+                            warn!(lease_id = %lease.id, reason = %reason, "lease cleanup failed, force-removing");
+                            self.barrier.delete(key).await?;
+                            revoked.push(lease);
+                        }
+                        Err(LeaseError::RevocationFailed { reason, .. }) => {
+                            warn!(lease_id = %lease.id, reason = %reason, "lease revocation failed");
+                        }
+                        Err(e) => return Err(e),
                     }
                 }
             }
         }
 
-        info!(prefix = %engine_path_prefix, count = count, "leases revoked by prefix");
+        info!(prefix = %engine_path_prefix, count = revoked.len(), force, "leases revoked by prefix");
 
-        Ok(count)
+        Ok(revoked)
     }
 }
 
@@ -265,3 +427,180 @@ impl std::fmt::Debug for LeaseManager {
         f.debug_struct("LeaseManager").finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn make_manager() -> LeaseManager {
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Arc::new(Barrier::new(storage));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        LeaseManager::new(barrier)
+    }
+
+    fn lease(engine_path: &str, ttl_secs: i64, max_ttl_secs: i64) -> Lease {
+        Lease {
+            id: uuid::Uuid::new_v4().to_string(),
+            engine_path: engine_path.to_owned(),
+            issued_at: Utc::now(),
+            ttl_secs,
+            max_ttl_secs,
+            renewable: true,
+            data: serde_json::Value::Null,
+            token_hash: String::new(),
+        }
+    }
+
+    struct CountingHandler {
+        calls: AtomicUsize,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl RevocationHandler for CountingHandler {
+        async fn revoke_lease(&self, lease: &Lease) -> Result<(), LeaseError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err(LeaseError::RevocationFailed {
+                    lease_id: lease.id.clone(),
+                    reason: "backend down".to_owned(),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn lease_count_quota_caps_active_leases() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let quotas = Arc::new(QuotaManager::new(Arc::clone(&barrier)));
+        quotas
+            .put_lease_count(crate::quota::LeaseCountQuota {
+                name: "db".to_owned(),
+                path: "database/".to_owned(),
+                max_leases: 1,
+            })
+            .await
+            .unwrap();
+        let mgr = LeaseManager::new(barrier).with_quotas(quotas);
+
+        let first = lease("database/creds/ro", 60, 0);
+        mgr.create(&first).await.unwrap();
+        let err = mgr
+            .create(&lease("database/creds/rw", 60, 0))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            LeaseError::QuotaExceeded { max_leases: 1, .. }
+        ));
+
+        // Other paths are unaffected, and revoking frees a slot.
+        mgr.create(&lease("pki/issue/web", 60, 0)).await.unwrap();
+        mgr.revoke(&first.id).await.unwrap();
+        mgr.create(&lease("database/creds/rw", 60, 0))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn renew_is_clamped_to_max_ttl() {
+        let mgr = make_manager().await;
+        let l = lease("database/creds/ro", 60, 100);
+        mgr.create(&l).await.unwrap();
+
+        let renewed = mgr.renew(&l.id, 3600).await.unwrap();
+        assert_eq!(renewed.ttl_secs, 100);
+
+        let err = mgr.renew(&l.id, 10).await.unwrap_err();
+        assert!(matches!(err, LeaseError::MaxTtlReached { .. }));
+    }
+
+    #[tokio::test]
+    async fn renew_without_max_ttl_extends() {
+        let mgr = make_manager().await;
+        let l = lease("secret/data/app", 60, 0);
+        mgr.create(&l).await.unwrap();
+
+        let renewed = mgr.renew(&l.id, 60).await.unwrap();
+        assert_eq!(renewed.ttl_secs, 120);
+    }
+
+    #[tokio::test]
+    async fn revoke_runs_matching_handler() {
+        let mgr = make_manager().await;
+        let handler = Arc::new(CountingHandler {
+            calls: AtomicUsize::new(0),
+            fail: false,
+        });
+        mgr.register_handler(
+            "database/",
+            Arc::clone(&handler) as Arc<dyn RevocationHandler>,
+        )
+        .await;
+
+        let db = lease("database/creds/ro", 60, 0);
+        let kv = lease("secret/data/app", 60, 0);
+        mgr.create(&db).await.unwrap();
+        mgr.create(&kv).await.unwrap();
+
+        mgr.revoke(&db.id).await.unwrap();
+        mgr.revoke(&kv.id).await.unwrap();
+        assert_eq!(handler.calls.load(Ordering::SeqCst), 1);
+        assert!(mgr.list_all().await.unwrap().is_empty());
+
+        // Unknown leases are a no-op.
+        mgr.revoke(&db.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn revoke_force_removes_leases_with_failing_cleanup() {
+        let mgr = make_manager().await;
+        let handler = Arc::new(CountingHandler {
+            calls: AtomicUsize::new(0),
+            fail: true,
+        });
+        mgr.register_handler("database/", handler).await;
+
+        mgr.create(&lease("database/creds/ro", 60, 0))
+            .await
+            .unwrap();
+        mgr.create(&lease("database/creds/rw", 60, 0))
+            .await
+            .unwrap();
+        mgr.create(&lease("pki/issue/web", 60, 0)).await.unwrap();
+
+        assert!(mgr.revoke_prefix("database/").await.unwrap().is_empty());
+        assert_eq!(mgr.list_all().await.unwrap().len(), 3);
+
+        assert_eq!(mgr.revoke_force("database/").await.unwrap().len(), 2);
+        let remaining = mgr.list_all().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].engine_path, "pki/issue/web");
+    }
+
+    #[tokio::test]
+    async fn failed_handler_keeps_lease() {
+        let mgr = make_manager().await;
+        let handler = Arc::new(CountingHandler {
+            calls: AtomicUsize::new(0),
+            fail: true,
+        });
+        mgr.register_handler("database/", handler).await;
+
+        let l = lease("database/creds/ro", 60, 0);
+        mgr.create(&l).await.unwrap();
+
+        let err = mgr.revoke(&l.id).await.unwrap_err();
+        assert!(matches!(err, LeaseError::RevocationFailed { .. }));
+        assert!(mgr.lookup(&l.id).await.is_ok());
+    }
+}
@@ -5,18 +5,48 @@
 //! manager. This crate depends on `zvault-storage` for the storage backend
 //! trait and knows nothing about specific secrets engines or auth methods.
 
+pub mod access_request;
+pub mod acme;
+pub mod activity;
 pub mod approle;
 pub mod audit;
 pub mod audit_file;
+pub mod audit_syslog;
+pub mod audit_webhook;
+pub mod aws_migrate;
 pub mod barrier;
+pub mod certauth;
+pub mod control_group;
 pub mod crypto;
+pub mod cubbyhole;
 pub mod database;
+pub mod database_plugin;
 pub mod engine;
 pub mod error;
+pub mod events;
+pub mod hsm;
+pub mod identity;
+pub mod jwt_auth;
+pub mod kms;
 pub mod lease;
+pub mod license;
+pub mod metrics;
+pub mod mfa;
 pub mod mount;
+pub mod mount_transfer;
+pub mod notify;
 pub mod pki;
+pub mod pki_acme;
+pub mod plugin;
 pub mod policy;
+pub mod quota;
+pub mod registry;
+pub mod replication;
+pub mod rotation;
 pub mod seal;
+pub mod secret_usage;
+pub mod sync;
+pub mod sync_kubernetes;
 pub mod token;
 pub mod transit;
+pub mod wrapping;
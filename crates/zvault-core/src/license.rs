@@ -0,0 +1,395 @@
+//! Server-side license verification and feature gating for `ZVault`.
+//!
+//! License keys use the same format the CLI accepts: a base64 JSON payload and
+//! a base64 Ed25519 signature over that payload string, joined by `.`. The
+//! signature is checked against an embedded public key, so a license cannot be
+//! forged by editing storage.
+//!
+//! The activated key is stored through the barrier at `sys/license` and
+//! re-verified on unseal. Route layers call [`LicenseManager::check`] before
+//! serving paid features; gating in the server (rather than the CLI) means a
+//! patched client cannot unlock them.
+
+use std::fmt;
+use std::sync::Arc;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::barrier::Barrier;
+use crate::error::LicenseError;
+
+/// Storage key for the activated license.
+const LICENSE_KEY: &str = "sys/license";
+
+/// Ed25519 public key that signs license payloads (shared with the CLI).
+const PUBLIC_KEY_B64: &str = "/3mEyrpmgX5NhAd9vLGaN7wI2JraX4Q2zrEQEUcor/M=";
+
+/// Feature tier granted by a license.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    /// No license — open-source features only.
+    Free,
+    /// Pro — AI Mode.
+    Pro,
+    /// Team — SSO, shared vaults.
+    Team,
+    /// Enterprise — namespaces, HA, replication.
+    Enterprise,
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Free => write!(f, "free"),
+            Self::Pro => write!(f, "pro"),
+            Self::Team => write!(f, "team"),
+            Self::Enterprise => write!(f, "enterprise"),
+        }
+    }
+}
+
+/// A server feature that requires a paid license.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Feature {
+    /// The MCP endpoint for AI assistants (AI Mode).
+    Mcp,
+    /// OIDC single sign-on.
+    Sso,
+    /// Multi-tenant namespaces.
+    Namespaces,
+    /// High-availability clustering.
+    Ha,
+}
+
+impl Feature {
+    /// Minimum tier that unlocks this feature.
+    #[must_use]
+    pub fn required_tier(self) -> Tier {
+        match self {
+            Self::Mcp => Tier::Pro,
+            Self::Sso => Tier::Team,
+            Self::Namespaces | Self::Ha => Tier::Enterprise,
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mcp => write!(f, "mcp"),
+            Self::Sso => write!(f, "sso"),
+            Self::Namespaces => write!(f, "namespaces"),
+            Self::Ha => write!(f, "ha"),
+        }
+    }
+}
+
+/// The signed payload inside a license key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicensePayload {
+    /// Feature tier.
+    pub tier: Tier,
+    /// Licensee email address.
+    pub email: String,
+    /// RFC 3339 timestamp when the license was issued.
+    pub issued_at: String,
+    /// RFC 3339 timestamp when the license expires.
+    pub expires_at: String,
+    /// Unique license identifier (e.g., `lic_abc123`).
+    pub license_id: String,
+}
+
+/// A verified license.
+#[derive(Debug, Clone)]
+pub struct License {
+    /// Decoded payload.
+    pub payload: LicensePayload,
+    /// Parsed expiry.
+    pub expires_at: DateTime<Utc>,
+    /// The raw key, as persisted.
+    raw_key: String,
+}
+
+impl License {
+    /// Whether the license has passed its expiry.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        Utc::now() > self.expires_at
+    }
+}
+
+/// Holds the active license and answers feature checks.
+pub struct LicenseManager {
+    barrier: Arc<Barrier>,
+    public_key: VerifyingKey,
+    current: RwLock<Option<License>>,
+}
+
+impl LicenseManager {
+    /// Create a license manager using the embedded release public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LicenseError::Invalid`] if the embedded key is malformed
+    /// (a build error).
+    pub fn new(barrier: Arc<Barrier>) -> Result<Self, LicenseError> {
+        let bytes = BASE64
+            .decode(PUBLIC_KEY_B64)
+            .map_err(|e| invalid(format!("embedded public key is not base64: {e}")))?;
+        let array: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| invalid("embedded public key has wrong length".to_owned()))?;
+        let public_key = VerifyingKey::from_bytes(&array)
+            .map_err(|e| invalid(format!("embedded public key is invalid: {e}")))?;
+        Ok(Self::with_verifying_key(barrier, public_key))
+    }
+
+    /// Create a license manager that trusts `public_key` instead of the
+    /// embedded release key, for tests that need a licensed server.
+    #[must_use]
+    pub fn with_verifying_key(barrier: Arc<Barrier>, public_key: VerifyingKey) -> Self {
+        Self {
+            barrier,
+            public_key,
+            current: RwLock::new(None),
+        }
+    }
+
+    /// Verify a license key and make it the active license.
+    ///
+    /// # Errors
+    ///
+    /// - [`LicenseError::Invalid`] if the key is malformed or the signature fails.
+    /// - [`LicenseError::Expired`] if the license has expired.
+    /// - [`LicenseError::Barrier`] if storage fails.
+    pub async fn activate(&self, key: &str) -> Result<License, LicenseError> {
+        let license = self.verify(key)?;
+        if license.is_expired() {
+            return Err(LicenseError::Expired {
+                expires_at: license.payload.expires_at,
+            });
+        }
+
+        self.barrier
+            .put(LICENSE_KEY, license.raw_key.as_bytes())
+            .await?;
+        *self.current.write().await = Some(license.clone());
+
+        info!(
+            license_id = %license.payload.license_id,
+            tier = %license.payload.tier,
+            "license activated"
+        );
+
+        Ok(license)
+    }
+
+    /// Reload the persisted license. Called once the vault is unsealed.
+    ///
+    /// A stored key that no longer verifies is logged and ignored so that a
+    /// bad license cannot block unseal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LicenseError::Barrier`] if storage fails.
+    pub async fn load(&self) -> Result<Option<License>, LicenseError> {
+        let Some(bytes) = self.barrier.get(LICENSE_KEY).await? else {
+            return Ok(None);
+        };
+
+        let key = String::from_utf8_lossy(&bytes);
+        match self.verify(&key) {
+            Ok(license) => {
+                *self.current.write().await = Some(license.clone());
+                Ok(Some(license))
+            }
+            Err(e) => {
+                warn!(error = %e, "stored license failed verification, ignoring");
+                Ok(None)
+            }
+        }
+    }
+
+    /// The active license, if any (including expired ones, for status display).
+    pub async fn current(&self) -> Option<License> {
+        self.current.read().await.clone()
+    }
+
+    /// The effective tier: `Free` when no license is active or it has expired.
+    pub async fn tier(&self) -> Tier {
+        match self.current.read().await.as_ref() {
+            Some(license) if !license.is_expired() => license.payload.tier,
+            _ => Tier::Free,
+        }
+    }
+
+    /// Check that the active license unlocks `feature`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LicenseError::FeatureNotLicensed`] if the effective tier is too low.
+    pub async fn check(&self, feature: Feature) -> Result<(), LicenseError> {
+        let tier = self.tier().await;
+        let required = feature.required_tier();
+        if tier < required {
+            return Err(LicenseError::FeatureNotLicensed {
+                feature,
+                required_tier: required,
+                current_tier: tier,
+            });
+        }
+        Ok(())
+    }
+
+    /// Decode a key and verify its signature and payload.
+    fn verify(&self, key: &str) -> Result<License, LicenseError> {
+        let key = key.trim();
+        if key.len() < 16 || key.len() > 4096 {
+            return Err(invalid("invalid license key length".to_owned()));
+        }
+
+        let (payload_b64, sig_b64) = key
+            .rsplit_once('.')
+            .ok_or_else(|| invalid("missing signature separator".to_owned()))?;
+        let sig_bytes = BASE64
+            .decode(sig_b64)
+            .map_err(|e| invalid(format!("signature is not base64: {e}")))?;
+        let signature = Signature::from_slice(&sig_bytes)
+            .map_err(|e| invalid(format!("malformed signature: {e}")))?;
+
+        // The license server signs the base64 payload string, not the decoded bytes.
+        self.public_key
+            .verify(payload_b64.as_bytes(), &signature)
+            .map_err(|_| invalid("signature verification failed".to_owned()))?;
+
+        let payload_bytes = BASE64
+            .decode(payload_b64)
+            .map_err(|e| invalid(format!("payload is not base64: {e}")))?;
+        let payload: LicensePayload = serde_json::from_slice(&payload_bytes)
+            .map_err(|e| invalid(format!("payload is malformed: {e}")))?;
+
+        let issued_at = parse_timestamp("issued_at", &payload.issued_at)?;
+        let expires_at = parse_timestamp("expires_at", &payload.expires_at)?;
+        if expires_at <= issued_at {
+            return Err(invalid("expires_at must be after issued_at".to_owned()));
+        }
+
+        Ok(License {
+            payload,
+            expires_at,
+            raw_key: key.to_owned(),
+        })
+    }
+}
+
+impl std::fmt::Debug for LicenseManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LicenseManager").finish_non_exhaustive()
+    }
+}
+
+fn parse_timestamp(field: &str, value: &str) -> Result<DateTime<Utc>, LicenseError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| invalid(format!("{field} is not a valid timestamp: {e}")))
+}
+
+fn invalid(reason: String) -> LicenseError {
+    LicenseError::Invalid { reason }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    async fn make_manager() -> LicenseManager {
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Arc::new(Barrier::new(storage));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        LicenseManager::with_verifying_key(barrier, signing_key().verifying_key())
+    }
+
+    fn make_key(tier: &str, expires_at: &str) -> String {
+        let payload = serde_json::json!({
+            "tier": tier,
+            "email": "ops@example.com",
+            "issued_at": "2025-01-01T00:00:00Z",
+            "expires_at": expires_at,
+            "license_id": "lic_test",
+        });
+        let payload_b64 = BASE64.encode(serde_json::to_vec(&payload).unwrap());
+        let sig = signing_key().sign(payload_b64.as_bytes());
+        format!("{payload_b64}.{}", BASE64.encode(sig.to_bytes()))
+    }
+
+    #[tokio::test]
+    async fn unlicensed_server_rejects_gated_feature() {
+        let mgr = make_manager().await;
+        let err = mgr.check(Feature::Sso).await.unwrap_err();
+        assert!(matches!(
+            err,
+            LicenseError::FeatureNotLicensed {
+                required_tier: Tier::Team,
+                current_tier: Tier::Free,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn activated_license_unlocks_and_persists() {
+        let mgr = make_manager().await;
+        mgr.activate(&make_key("team", "2099-01-01T00:00:00Z"))
+            .await
+            .unwrap();
+
+        assert!(mgr.check(Feature::Mcp).await.is_ok());
+        assert!(mgr.check(Feature::Sso).await.is_ok());
+        assert!(mgr.check(Feature::Ha).await.is_err());
+
+        let reloaded = LicenseManager::with_verifying_key(
+            Arc::clone(&mgr.barrier),
+            signing_key().verifying_key(),
+        );
+        assert!(reloaded.load().await.unwrap().is_some());
+        assert_eq!(reloaded.tier().await, Tier::Team);
+    }
+
+    #[tokio::test]
+    async fn rejects_tampered_and_expired_keys() {
+        let mgr = make_manager().await;
+
+        let key = make_key("pro", "2099-01-01T00:00:00Z");
+        let (_, sig) = key.rsplit_once('.').unwrap();
+        let forged_payload = BASE64.encode(br#"{"tier":"enterprise"}"#);
+        let forged = format!("{forged_payload}.{sig}");
+        assert!(matches!(
+            mgr.activate(&forged).await,
+            Err(LicenseError::Invalid { .. })
+        ));
+
+        let expired = make_key("enterprise", "2025-06-01T00:00:00Z");
+        assert!(matches!(
+            mgr.activate(&expired).await,
+            Err(LicenseError::Expired { .. })
+        ));
+        assert_eq!(mgr.tier().await, Tier::Free);
+    }
+}
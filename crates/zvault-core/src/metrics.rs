@@ -0,0 +1,466 @@
+//! Process-wide operational metrics for `ZVault`.
+//!
+//! Subsystems record into the [`global`] registry as they run: the barrier
+//! times every encryption and decryption, [`MeteredBackend`] times storage
+//! operations, the token store counts creations and revocations, and the
+//! server records HTTP requests. [`Metrics::render`] writes everything in
+//! the Prometheus text exposition format.
+//!
+//! Counters are plain atomics, so recording never blocks a request. State
+//! that can be read on demand — seal status, lease counts — is not kept
+//! here; the metrics endpoint reads it when scraped.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+use zvault_storage::{StorageBackend, StorageError};
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const BUCKETS: [f64; 14] = [
+    0.000_1, 0.000_25, 0.000_5, 0.001, 0.002_5, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0,
+];
+
+static GLOBAL: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+/// The process-wide registry.
+#[must_use]
+pub fn global() -> &'static Metrics {
+    &GLOBAL
+}
+
+/// A latency histogram with fixed buckets.
+#[derive(Debug, Default)]
+pub struct Histogram {
+    /// Non-cumulative count per bucket; the last slot is `+Inf`.
+    buckets: [AtomicU64; BUCKETS.len() + 1],
+    count: AtomicU64,
+    sum_nanos: AtomicU64,
+}
+
+impl Histogram {
+    /// Record one observation.
+    pub fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let slot = BUCKETS
+            .iter()
+            .position(|&bound| secs <= bound)
+            .unwrap_or(BUCKETS.len());
+        self.buckets[slot].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Number of observations so far.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Write the `_bucket`, `_sum`, and `_count` series. `labels` is either
+    /// empty or a comma-terminated label list such as `op="get",`.
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let mut cumulative = 0_u64;
+        for (bound, bucket) in BUCKETS.iter().zip(&self.buckets) {
+            cumulative = cumulative.saturating_add(bucket.load(Ordering::Relaxed));
+            let _ = writeln!(out, "{name}_bucket{{{labels}le=\"{bound}\"}} {cumulative}");
+        }
+        let count = self.count();
+        let _ = writeln!(out, "{name}_bucket{{{labels}le=\"+Inf\"}} {count}");
+        #[allow(clippy::cast_precision_loss)]
+        let sum = self.sum_nanos.load(Ordering::Relaxed) as f64 / 1e9;
+        let labels = match labels.trim_end_matches(',') {
+            "" => String::new(),
+            labels => format!("{{{labels}}}"),
+        };
+        let _ = writeln!(out, "{name}_sum{labels} {sum}");
+        let _ = writeln!(out, "{name}_count{labels} {count}");
+    }
+}
+
+/// Latency and failures of one kind of operation.
+#[derive(Debug, Default)]
+pub struct OpMetrics {
+    /// Time taken by each operation, successful or not.
+    pub latency: Histogram,
+    /// Operations that returned an error.
+    pub errors: AtomicU64,
+}
+
+impl OpMetrics {
+    /// Record an operation that started at `start`.
+    pub fn record(&self, start: Instant, ok: bool) {
+        self.latency.observe(start.elapsed());
+        if !ok {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Storage backend operations, in the order they are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageOp {
+    Get,
+    Put,
+    Delete,
+    List,
+    Exists,
+    Snapshot,
+    Lock,
+}
+
+impl StorageOp {
+    const ALL: [Self; 7] = [
+        Self::Get,
+        Self::Put,
+        Self::Delete,
+        Self::List,
+        Self::Exists,
+        Self::Snapshot,
+        Self::Lock,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::Put => "put",
+            Self::Delete => "delete",
+            Self::List => "list",
+            Self::Exists => "exists",
+            Self::Snapshot => "snapshot",
+            Self::Lock => "lock",
+        }
+    }
+}
+
+/// HTTP requests to one route.
+#[derive(Debug, Default)]
+struct RouteMetrics {
+    duration: Histogram,
+    /// Responses by status code.
+    statuses: BTreeMap<u16, u64>,
+}
+
+/// The metrics registry. Use [`global`] rather than creating one.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Barrier encryptions (every write).
+    pub barrier_encrypt: OpMetrics,
+    /// Barrier decryptions (every read).
+    pub barrier_decrypt: OpMetrics,
+    storage: [OpMetrics; StorageOp::ALL.len()],
+    /// Tokens created, including pre-generated root tokens.
+    pub tokens_created: AtomicU64,
+    /// Tokens revoked, counting each child of a revoked tree.
+    pub tokens_revoked: AtomicU64,
+    /// Keyed by `(method, route template)`.
+    http: Mutex<BTreeMap<(String, String), RouteMetrics>>,
+}
+
+impl Metrics {
+    /// Metrics of one storage operation.
+    #[must_use]
+    pub fn storage(&self, op: StorageOp) -> &OpMetrics {
+        &self.storage[op as usize]
+    }
+
+    /// Record an HTTP request. `route` should be the matched route
+    /// template, not the raw path, to keep label cardinality bounded.
+    pub async fn observe_http(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let mut http = self.http.lock().await;
+        let route = http
+            .entry((method.to_owned(), route.to_owned()))
+            .or_default();
+        route.duration.observe(elapsed);
+        let count = route.statuses.entry(status).or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// Append every metric to `out` in Prometheus text format.
+    pub async fn render(&self, out: &mut String) {
+        render_ops(
+            out,
+            "zvault_barrier",
+            "barrier encryptions or decryptions",
+            &[
+                ("encrypt", &self.barrier_encrypt),
+                ("decrypt", &self.barrier_decrypt),
+            ],
+        );
+        let storage: Vec<_> = StorageOp::ALL
+            .iter()
+            .map(|&op| (op.as_str(), self.storage(op)))
+            .collect();
+        render_ops(
+            out,
+            "zvault_storage",
+            "storage backend operations",
+            &storage,
+        );
+
+        for (name, help, counter) in [
+            (
+                "zvault_tokens_created_total",
+                "Tokens created.",
+                &self.tokens_created,
+            ),
+            (
+                "zvault_tokens_revoked_total",
+                "Tokens revoked.",
+                &self.tokens_revoked,
+            ),
+        ] {
+            header(out, name, "counter", help);
+            let _ = writeln!(out, "{name} {}", counter.load(Ordering::Relaxed));
+        }
+
+        let http = self.http.lock().await;
+        header(
+            out,
+            "zvault_http_requests_total",
+            "counter",
+            "HTTP requests by route and status.",
+        );
+        for ((method, route), metrics) in http.iter() {
+            for (status, count) in &metrics.statuses {
+                let _ = writeln!(
+                    out,
+                    "zvault_http_requests_total{{method=\"{method}\",route=\"{}\",status=\"{status}\"}} {count}",
+                    escape(route)
+                );
+            }
+        }
+        header(
+            out,
+            "zvault_http_request_duration_seconds",
+            "histogram",
+            "HTTP request latency by route.",
+        );
+        for ((method, route), metrics) in http.iter() {
+            metrics.duration.render(
+                out,
+                "zvault_http_request_duration_seconds",
+                &format!("method=\"{method}\",route=\"{}\",", escape(route)),
+            );
+        }
+    }
+}
+
+/// Write `{prefix}_operation_duration_seconds` and `{prefix}_errors_total`
+/// for a set of operations labelled by `op`.
+fn render_ops(out: &mut String, prefix: &str, what: &str, ops: &[(&str, &OpMetrics)]) {
+    let name = format!("{prefix}_operation_duration_seconds");
+    header(out, &name, "histogram", &format!("Time spent in {what}."));
+    for (op, metrics) in ops {
+        metrics.latency.render(out, &name, &format!("op=\"{op}\","));
+    }
+    let name = format!("{prefix}_errors_total");
+    header(out, &name, "counter", &format!("Failed {what}."));
+    for (op, metrics) in ops {
+        let _ = writeln!(
+            out,
+            "{name}{{op=\"{op}\"}} {}",
+            metrics.errors.load(Ordering::Relaxed)
+        );
+    }
+}
+
+/// Write the `# HELP` and `# TYPE` lines of a metric.
+pub fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Escape a label value.
+#[must_use]
+pub fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// ── Storage instrumentation ──────────────────────────────────────────
+
+/// A [`StorageBackend`] wrapper that records every operation in the
+/// [`global`] registry.
+pub struct MeteredBackend {
+    inner: Arc<dyn StorageBackend>,
+}
+
+impl MeteredBackend {
+    /// Wrap `inner`.
+    #[must_use]
+    pub fn new(inner: Arc<dyn StorageBackend>) -> Self {
+        Self { inner }
+    }
+}
+
+impl std::fmt::Debug for MeteredBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeteredBackend").finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for MeteredBackend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let start = Instant::now();
+        let result = self.inner.get(key).await;
+        global()
+            .storage(StorageOp::Get)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        let start = Instant::now();
+        let result = self.inner.put(key, value).await;
+        global()
+            .storage(StorageOp::Put)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let start = Instant::now();
+        let result = self.inner.delete(key).await;
+        global()
+            .storage(StorageOp::Delete)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let start = Instant::now();
+        let result = self.inner.list(prefix).await;
+        global()
+            .storage(StorageOp::List)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        let start = Instant::now();
+        let result = self.inner.exists(key).await;
+        global()
+            .storage(StorageOp::Exists)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        let start = Instant::now();
+        let result = self.inner.snapshot().await;
+        global()
+            .storage(StorageOp::Snapshot)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn acquire_lock(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<String, StorageError> {
+        let start = Instant::now();
+        let result = self.inner.acquire_lock(name, holder, ttl).await;
+        global()
+            .storage(StorageOp::Lock)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn lock_holder(&self, name: &str) -> Result<Option<String>, StorageError> {
+        let start = Instant::now();
+        let result = self.inner.lock_holder(name).await;
+        global()
+            .storage(StorageOp::Lock)
+            .record(start, result.is_ok());
+        result
+    }
+
+    async fn release_lock(&self, name: &str, holder: &str) -> Result<(), StorageError> {
+        let start = Instant::now();
+        let result = self.inner.release_lock(name, holder).await;
+        global()
+            .storage(StorageOp::Lock)
+            .record(start, result.is_ok());
+        result
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use zvault_storage::MemoryBackend;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let h = Histogram::default();
+        h.observe(Duration::from_micros(50));
+        h.observe(Duration::from_millis(3));
+        h.observe(Duration::from_secs(10));
+        let mut out = String::new();
+        h.render(&mut out, "t", "op=\"x\",");
+        assert!(out.contains("t_bucket{op=\"x\",le=\"0.0001\"} 1\n"));
+        assert!(out.contains("t_bucket{op=\"x\",le=\"0.005\"} 2\n"));
+        assert!(out.contains("t_bucket{op=\"x\",le=\"5\"} 2\n"));
+        assert!(out.contains("t_bucket{op=\"x\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("t_count{op=\"x\"} 3\n"));
+    }
+
+    #[test]
+    fn unlabelled_histogram_renders_plain_names() {
+        let h = Histogram::default();
+        h.observe(Duration::from_millis(1));
+        let mut out = String::new();
+        h.render(&mut out, "t", "");
+        assert!(out.contains("t_bucket{le=\"0.001\"} 1\n"));
+        assert!(out.contains("t_count 1\n"));
+    }
+
+    #[tokio::test]
+    async fn http_requests_are_grouped_by_route_and_status() {
+        let metrics = Metrics::default();
+        let route = "/v1/secret/data/{*path}";
+        metrics
+            .observe_http("GET", route, 200, Duration::from_millis(2))
+            .await;
+        metrics
+            .observe_http("GET", route, 200, Duration::from_millis(4))
+            .await;
+        metrics
+            .observe_http("GET", route, 404, Duration::from_millis(1))
+            .await;
+        let mut out = String::new();
+        metrics.render(&mut out).await;
+        assert!(out.contains(
+            "zvault_http_requests_total{method=\"GET\",route=\"/v1/secret/data/{*path}\",status=\"200\"} 2\n"
+        ));
+        assert!(out.contains(
+            "zvault_http_request_duration_seconds_count{method=\"GET\",route=\"/v1/secret/data/{*path}\"} 3\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn metered_backend_records_operations() {
+        let backend = MeteredBackend::new(Arc::new(MemoryBackend::new()));
+        let before = global().storage(StorageOp::Put).latency.count();
+        backend.put("a", b"1").await.unwrap();
+        assert_eq!(backend.get("a").await.unwrap().unwrap(), b"1");
+        assert!(global().storage(StorageOp::Put).latency.count() > before);
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
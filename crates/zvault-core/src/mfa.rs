@@ -0,0 +1,864 @@
+//! Multi-factor authentication for `ZVault`.
+//!
+//! MFA methods are configured by name; TOTP (RFC 6238) is the only method
+//! type so far. Each identity enrolls in a method by generating a secret with
+//! [`MfaStore::generate_totp`] and loading the returned `otpauth://` URL into
+//! an authenticator app.
+//!
+//! MFA is required in two places:
+//! - Login enforcements ([`LoginEnforcement`]) name auth methods (`approle`,
+//!   `jwt`) whose logins must carry a valid code for each listed method.
+//! - Policy rules may list `mfa_methods`; a request those rules grant is
+//!   refused with [`PolicyError::MfaRequired`] unless it was made inside
+//!   [`run_verified`] with every listed method validated (step-up MFA).
+//!
+//! Codes travel in the `X-Vault-MFA` header as `method:code`, comma-separated
+//! for several methods. A code is accepted once: validating it records its
+//! time step, and later codes must come from a newer step.
+//!
+//! An identity is the token's entity ID, or its display name for tokens
+//! without one (e.g. `approle-ci`, `jwt-alice`). Secrets are stored through
+//! the barrier under `sys/mfa/`, keyed by a hash of the identity.
+//!
+//! [`PolicyError::MfaRequired`]: crate::error::PolicyError::MfaRequired
+
+use std::future::Future;
+use std::sync::Arc;
+
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use subtle::ConstantTimeEq;
+use tokio::sync::Mutex;
+use tracing::info;
+use zeroize::Zeroizing;
+
+use crate::barrier::Barrier;
+use crate::error::MfaError;
+
+/// Storage prefix for TOTP method configs.
+const TOTP_METHOD_PREFIX: &str = "sys/mfa/method/totp/";
+
+/// Storage prefix for TOTP enrollments, `{prefix}{method}/{identity hash}`.
+const TOTP_SECRET_PREFIX: &str = "sys/mfa/totp/";
+
+/// Storage prefix for login enforcements.
+const ENFORCEMENT_PREFIX: &str = "sys/mfa/login-enforcement/";
+
+/// Auth methods whose logins can carry MFA codes.
+pub const LOGIN_AUTH_METHODS: &[&str] = &["approle", "jwt", "cert", "cloud"];
+
+/// Header carrying `method:code` credentials.
+pub const MFA_HEADER: &str = "X-Vault-MFA";
+
+tokio::task_local! {
+    /// MFA methods validated for the current request.
+    static VERIFIED: Vec<String>;
+}
+
+/// Run `future` with `methods` counted as validated for policy checks.
+pub async fn run_verified<F: Future>(methods: Vec<String>, future: F) -> F::Output {
+    VERIFIED.scope(methods, future).await
+}
+
+/// Whether `method` was validated for the current request.
+#[must_use]
+pub fn is_verified(method: &str) -> bool {
+    VERIFIED
+        .try_with(|methods| methods.iter().any(|m| m == method))
+        .unwrap_or(false)
+}
+
+/// The MFA identity of a token: its entity, else its display name.
+#[must_use]
+pub fn identity<'a>(entity_id: Option<&'a str>, display_name: &'a str) -> &'a str {
+    entity_id
+        .filter(|id| !id.is_empty())
+        .unwrap_or(display_name)
+}
+
+/// Parse an `X-Vault-MFA` header into `(method, code)` pairs.
+///
+/// # Errors
+///
+/// Returns [`MfaError::Invalid`] if an entry is not `method:code`.
+pub fn parse_credentials(header: &str) -> Result<Vec<(String, String)>, MfaError> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .split_once(':')
+                .map(|(method, code)| (method.trim().to_owned(), code.trim().to_owned()))
+                .ok_or_else(|| MfaError::Invalid {
+                    reason: format!("expected method:code in {MFA_HEADER}"),
+                })
+        })
+        .collect()
+}
+
+/// HMAC algorithm of a TOTP method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TotpAlgorithm {
+    /// HMAC-SHA1, what most authenticator apps expect.
+    #[default]
+    Sha1,
+    /// HMAC-SHA256.
+    Sha256,
+    /// HMAC-SHA512.
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    /// HMAC of `message` under `key`.
+    fn mac(self, key: &[u8], message: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => mac::<Hmac<Sha1>>(key, message),
+            Self::Sha256 => mac::<Hmac<Sha256>>(key, message),
+            Self::Sha512 => mac::<Hmac<Sha512>>(key, message),
+        }
+    }
+
+    fn uri_name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+            Self::Sha512 => "SHA512",
+        }
+    }
+}
+
+fn mac<M: Mac + hmac::digest::KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length, so construction cannot fail.
+    let Ok(mut mac) = <M as Mac>::new_from_slice(key) else {
+        return Vec::new();
+    };
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// A configured TOTP method.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotpMethod {
+    /// Method name, used in `X-Vault-MFA` and policy `mfa_methods`.
+    pub name: String,
+    /// Issuer shown by authenticator apps.
+    pub issuer: String,
+    /// Seconds per time step.
+    #[serde(default = "default_period")]
+    pub period_secs: u64,
+    /// Code length, 6 or 8.
+    #[serde(default = "default_digits")]
+    pub digits: u32,
+    /// HMAC algorithm.
+    #[serde(default)]
+    pub algorithm: TotpAlgorithm,
+    /// Time steps either side of now that are still accepted.
+    #[serde(default = "default_skew")]
+    pub skew: u32,
+    /// Secret length in bytes.
+    #[serde(default = "default_key_size")]
+    pub key_size: usize,
+    /// When the method was configured.
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+fn default_period() -> u64 {
+    30
+}
+
+fn default_digits() -> u32 {
+    6
+}
+
+fn default_skew() -> u32 {
+    1
+}
+
+fn default_key_size() -> usize {
+    20
+}
+
+impl TotpMethod {
+    /// A method with the defaults authenticator apps expect.
+    #[must_use]
+    pub fn new(name: &str, issuer: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            issuer: issuer.to_owned(),
+            period_secs: default_period(),
+            digits: default_digits(),
+            algorithm: TotpAlgorithm::default(),
+            skew: default_skew(),
+            key_size: default_key_size(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), MfaError> {
+        let invalid = |reason: &str| {
+            Err(MfaError::Invalid {
+                reason: reason.to_owned(),
+            })
+        };
+        if !valid_name(&self.name) {
+            return invalid("method names may only contain letters, digits, '-' and '_'");
+        }
+        if self.issuer.is_empty() {
+            return invalid("issuer is required");
+        }
+        if !(15..=300).contains(&self.period_secs) {
+            return invalid("period_secs must be between 15 and 300");
+        }
+        if self.digits != 6 && self.digits != 8 {
+            return invalid("digits must be 6 or 8");
+        }
+        if self.skew > 2 {
+            return invalid("skew must be at most 2");
+        }
+        if !(16..=64).contains(&self.key_size) {
+            return invalid("key_size must be between 16 and 64 bytes");
+        }
+        Ok(())
+    }
+
+    /// The code for time step `counter`, zero-padded to `digits`.
+    fn code(&self, secret: &[u8], counter: u64) -> String {
+        let mac = self.algorithm.mac(secret, &counter.to_be_bytes());
+        let Some(&last) = mac.last() else {
+            return String::new();
+        };
+        let offset = usize::from(last & 0x0f);
+        let value = u32::from_be_bytes([
+            mac[offset] & 0x7f,
+            mac[offset + 1],
+            mac[offset + 2],
+            mac[offset + 3],
+        ]);
+        let width = self.digits as usize;
+        format!("{:0width$}", value % 10u32.pow(self.digits))
+    }
+
+    fn counter_at(&self, now: DateTime<Utc>) -> u64 {
+        u64::try_from(now.timestamp()).unwrap_or(0) / self.period_secs
+    }
+}
+
+/// A freshly generated TOTP secret, shown to the identity once.
+#[derive(Debug, Clone, Serialize)]
+pub struct TotpKey {
+    /// Method the secret belongs to.
+    pub method: String,
+    /// Account label shown by authenticator apps.
+    pub account_name: String,
+    /// Base32 secret for manual entry.
+    pub secret: String,
+    /// `otpauth://totp/...` provisioning URL (usually shown as a QR code).
+    pub url: String,
+}
+
+/// An identity's TOTP secret.
+#[derive(Serialize, Deserialize)]
+struct TotpEnrollment {
+    identity: String,
+    /// Base64 secret.
+    secret: String,
+    /// Time step of the last accepted code.
+    #[serde(default)]
+    last_counter: Option<u64>,
+    created_at: DateTime<Utc>,
+}
+
+/// MFA required for logins through some auth methods.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginEnforcement {
+    /// Enforcement name.
+    pub name: String,
+    /// Methods that must all be validated.
+    pub mfa_methods: Vec<String>,
+    /// Auth methods (see [`LOGIN_AUTH_METHODS`]) the enforcement applies to.
+    pub auth_methods: Vec<String>,
+}
+
+/// Stores MFA methods, enrollments, and login enforcements.
+pub struct MfaStore {
+    barrier: Arc<Barrier>,
+    /// Serializes validation so a code is accepted once.
+    validate_lock: Mutex<()>,
+}
+
+impl MfaStore {
+    /// Create an MFA store backed by the barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            barrier,
+            validate_lock: Mutex::new(()),
+        }
+    }
+
+    /// Create or replace a TOTP method. Existing enrollments are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::Invalid`] for an invalid config, or
+    /// [`MfaError::Barrier`] if storage fails.
+    pub async fn put_totp_method(&self, method: &TotpMethod) -> Result<(), MfaError> {
+        method.validate()?;
+        self.put_json(&format!("{TOTP_METHOD_PREFIX}{}", method.name), method)
+            .await?;
+        info!(method = %method.name, "mfa totp method written");
+        Ok(())
+    }
+
+    /// Read a TOTP method.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::MethodNotFound`] if no such method exists.
+    pub async fn get_totp_method(&self, name: &str) -> Result<TotpMethod, MfaError> {
+        if !valid_name(name) {
+            return Err(MfaError::MethodNotFound {
+                name: name.to_owned(),
+            });
+        }
+        self.get_json(&format!("{TOTP_METHOD_PREFIX}{name}"))
+            .await?
+            .ok_or_else(|| MfaError::MethodNotFound {
+                name: name.to_owned(),
+            })
+    }
+
+    /// List TOTP method names.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::Barrier`] if storage fails.
+    pub async fn list_totp_methods(&self) -> Result<Vec<String>, MfaError> {
+        self.list_names(TOTP_METHOD_PREFIX).await
+    }
+
+    /// Delete a TOTP method and every enrollment in it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::InUse`] if a login enforcement still names the
+    /// method, or [`MfaError::Barrier`] if storage fails.
+    pub async fn delete_totp_method(&self, name: &str) -> Result<(), MfaError> {
+        self.get_totp_method(name).await?;
+        for enforcement in self.list_login_enforcements().await? {
+            let enforcement = self.get_login_enforcement(&enforcement).await?;
+            if enforcement.mfa_methods.iter().any(|m| m == name) {
+                return Err(MfaError::InUse {
+                    name: name.to_owned(),
+                    enforcement: enforcement.name,
+                });
+            }
+        }
+        let prefix = format!("{TOTP_SECRET_PREFIX}{name}/");
+        for key in self.barrier.list(&prefix).await? {
+            self.barrier.delete(&key).await?;
+        }
+        self.barrier
+            .delete(&format!("{TOTP_METHOD_PREFIX}{name}"))
+            .await?;
+        info!(method = %name, "mfa totp method deleted");
+        Ok(())
+    }
+
+    /// Generate a TOTP secret for `identity` in `method`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::AlreadyEnrolled`] if the identity has a secret
+    /// (an admin must destroy it first), [`MfaError::MethodNotFound`], or
+    /// [`MfaError::Barrier`] if storage fails.
+    pub async fn generate_totp(&self, method: &str, identity: &str) -> Result<TotpKey, MfaError> {
+        let config = self.get_totp_method(method).await?;
+        let key = enrollment_key(method, identity);
+        let _guard = self.validate_lock.lock().await;
+        if self.barrier.get(&key).await?.is_some() {
+            return Err(MfaError::AlreadyEnrolled {
+                method: method.to_owned(),
+            });
+        }
+
+        let mut secret = Zeroizing::new(vec![0u8; config.key_size]);
+        OsRng.fill_bytes(&mut secret);
+        let enrollment = TotpEnrollment {
+            identity: identity.to_owned(),
+            secret: BASE64.encode(&*secret),
+            last_counter: None,
+            created_at: Utc::now(),
+        };
+        self.put_json(&key, &enrollment).await?;
+        info!(method = %method, identity = %identity, "mfa totp secret generated");
+
+        let encoded = base32_encode(&secret);
+        Ok(TotpKey {
+            method: method.to_owned(),
+            account_name: identity.to_owned(),
+            url: provisioning_url(&config, identity, &encoded)?,
+            secret: encoded,
+        })
+    }
+
+    /// Remove `identity`'s secret from `method` so it can enroll again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::NotEnrolled`] if the identity has no secret, or
+    /// [`MfaError::Barrier`] if storage fails.
+    pub async fn destroy_totp(&self, method: &str, identity: &str) -> Result<(), MfaError> {
+        self.get_totp_method(method).await?;
+        let key = enrollment_key(method, identity);
+        if self.barrier.get(&key).await?.is_none() {
+            return Err(MfaError::NotEnrolled {
+                method: method.to_owned(),
+            });
+        }
+        self.barrier.delete(&key).await?;
+        info!(method = %method, identity = %identity, "mfa totp secret destroyed");
+        Ok(())
+    }
+
+    /// Check `code` for `identity` in `method` at time `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::NotEnrolled`] if the identity has no secret,
+    /// [`MfaError::InvalidCode`] if the code is wrong, outside the allowed
+    /// skew, or already used, or [`MfaError::Barrier`] if storage fails.
+    pub async fn validate(
+        &self,
+        method: &str,
+        identity: &str,
+        code: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), MfaError> {
+        let config = self.get_totp_method(method).await?;
+        let key = enrollment_key(method, identity);
+        let _guard = self.validate_lock.lock().await;
+        let mut enrollment: TotpEnrollment =
+            self.get_json(&key)
+                .await?
+                .ok_or_else(|| MfaError::NotEnrolled {
+                    method: method.to_owned(),
+                })?;
+        let secret =
+            Zeroizing::new(
+                BASE64
+                    .decode(&enrollment.secret)
+                    .map_err(|e| MfaError::Internal {
+                        reason: format!("corrupt totp secret: {e}"),
+                    })?,
+            );
+
+        let invalid = || MfaError::InvalidCode {
+            method: method.to_owned(),
+        };
+        if code.len() != config.digits as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let current = config.counter_at(now);
+        let skew = u64::from(config.skew);
+        let matched = (current.saturating_sub(skew)..=current + skew)
+            .filter(|&counter| enrollment.last_counter.is_none_or(|last| counter > last))
+            .find(|&counter| {
+                bool::from(
+                    config
+                        .code(&secret, counter)
+                        .as_bytes()
+                        .ct_eq(code.as_bytes()),
+                )
+            })
+            .ok_or_else(invalid)?;
+
+        enrollment.last_counter = Some(matched);
+        self.put_json(&key, &enrollment).await
+    }
+
+    /// Create or replace a login enforcement.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::Invalid`] if it names no methods, an unknown
+    /// method, or an auth method that cannot carry MFA codes.
+    pub async fn put_login_enforcement(
+        &self,
+        enforcement: &LoginEnforcement,
+    ) -> Result<(), MfaError> {
+        if !valid_name(&enforcement.name) {
+            return Err(MfaError::Invalid {
+                reason: "enforcement names may only contain letters, digits, '-' and '_'"
+                    .to_owned(),
+            });
+        }
+        if enforcement.mfa_methods.is_empty() || enforcement.auth_methods.is_empty() {
+            return Err(MfaError::Invalid {
+                reason: "mfa_methods and auth_methods are required".to_owned(),
+            });
+        }
+        for method in &enforcement.mfa_methods {
+            self.get_totp_method(method).await?;
+        }
+        if let Some(auth) = enforcement
+            .auth_methods
+            .iter()
+            .find(|a| !LOGIN_AUTH_METHODS.contains(&a.as_str()))
+        {
+            return Err(MfaError::Invalid {
+                reason: format!(
+                    "auth method '{auth}' cannot require MFA (supported: {})",
+                    LOGIN_AUTH_METHODS.join(", ")
+                ),
+            });
+        }
+        self.put_json(
+            &format!("{ENFORCEMENT_PREFIX}{}", enforcement.name),
+            enforcement,
+        )
+        .await?;
+        info!(name = %enforcement.name, "mfa login enforcement written");
+        Ok(())
+    }
+
+    /// Read a login enforcement.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::EnforcementNotFound`] if it doesn't exist.
+    pub async fn get_login_enforcement(&self, name: &str) -> Result<LoginEnforcement, MfaError> {
+        let not_found = || MfaError::EnforcementNotFound {
+            name: name.to_owned(),
+        };
+        if !valid_name(name) {
+            return Err(not_found());
+        }
+        self.get_json(&format!("{ENFORCEMENT_PREFIX}{name}"))
+            .await?
+            .ok_or_else(not_found)
+    }
+
+    /// List login enforcement names.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::Barrier`] if storage fails.
+    pub async fn list_login_enforcements(&self) -> Result<Vec<String>, MfaError> {
+        self.list_names(ENFORCEMENT_PREFIX).await
+    }
+
+    /// Delete a login enforcement.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::EnforcementNotFound`] if it doesn't exist.
+    pub async fn delete_login_enforcement(&self, name: &str) -> Result<(), MfaError> {
+        self.get_login_enforcement(name).await?;
+        self.barrier
+            .delete(&format!("{ENFORCEMENT_PREFIX}{name}"))
+            .await?;
+        info!(name = %name, "mfa login enforcement deleted");
+        Ok(())
+    }
+
+    /// Methods a login through `auth_method` must validate, across every
+    /// enforcement that names it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MfaError::Barrier`] if storage fails.
+    pub async fn login_methods(&self, auth_method: &str) -> Result<Vec<String>, MfaError> {
+        let mut methods: Vec<String> = Vec::new();
+        for name in self.list_login_enforcements().await? {
+            let enforcement = self.get_login_enforcement(&name).await?;
+            if enforcement.auth_methods.iter().any(|a| a == auth_method) {
+                for method in enforcement.mfa_methods {
+                    if !methods.contains(&method) {
+                        methods.push(method);
+                    }
+                }
+            }
+        }
+        Ok(methods)
+    }
+
+    async fn list_names(&self, prefix: &str) -> Result<Vec<String>, MfaError> {
+        let mut names: Vec<String> = self
+            .barrier
+            .list(prefix)
+            .await?
+            .iter()
+            .filter_map(|k| k.strip_prefix(prefix))
+            .filter(|k| !k.contains('/'))
+            .map(str::to_owned)
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn put_json<T: Serialize>(&self, key: &str, value: &T) -> Result<(), MfaError> {
+        let bytes = serde_json::to_vec(value).map_err(|e| MfaError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(key, &bytes).await?;
+        Ok(())
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, MfaError> {
+        self.barrier
+            .get(key)
+            .await?
+            .map(|data| {
+                serde_json::from_slice(&data).map_err(|e| MfaError::Internal {
+                    reason: format!("deserialization failed: {e}"),
+                })
+            })
+            .transpose()
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+fn enrollment_key(method: &str, identity: &str) -> String {
+    let digest = hex::encode(Sha256::digest(identity.as_bytes()));
+    format!("{TOTP_SECRET_PREFIX}{method}/{digest}")
+}
+
+fn provisioning_url(method: &TotpMethod, account: &str, secret: &str) -> Result<String, MfaError> {
+    let mut url = url::Url::parse("otpauth://totp/").map_err(|e| MfaError::Internal {
+        reason: format!("provisioning url: {e}"),
+    })?;
+    url.set_path(&format!("{}:{account}", method.issuer));
+    url.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", &method.issuer)
+        .append_pair("algorithm", method.algorithm.uri_name())
+        .append_pair("digits", &method.digits.to_string())
+        .append_pair("period", &method.period_secs.to_string());
+    Ok(url.to_string())
+}
+
+/// RFC 4648 base32 without padding, as authenticator apps expect.
+fn base32_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(char::from(ALPHABET[usize::from((buffer >> bits) & 0x1f)]));
+        }
+    }
+    if bits > 0 {
+        out.push(char::from(
+            ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)],
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn store() -> MfaStore {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        MfaStore::new(barrier)
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    /// Decode a base32 secret the way an authenticator app does.
+    fn base32_decode(s: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        let (mut buffer, mut bits) = (0u32, 0);
+        for c in s.bytes() {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                _ => c - b'2' + 26,
+            };
+            buffer = (buffer << 5) | u32::from(value);
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                out.push(u8::try_from((buffer >> bits) & 0xff).unwrap());
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn codes_match_rfc_6238_vectors() {
+        let mut method = TotpMethod::new("totp", "ZVault");
+        method.digits = 8;
+        let sha1 = b"12345678901234567890";
+        assert_eq!(method.code(sha1, method.counter_at(at(59))), "94287082");
+        assert_eq!(
+            method.code(sha1, method.counter_at(at(1_111_111_109))),
+            "07081804"
+        );
+
+        method.algorithm = TotpAlgorithm::Sha256;
+        let sha256 = b"12345678901234567890123456789012";
+        assert_eq!(method.code(sha256, method.counter_at(at(59))), "46119246");
+
+        method.algorithm = TotpAlgorithm::Sha512;
+        let sha512 = b"1234567890123456789012345678901234567890123456789012345678901234";
+        assert_eq!(method.code(sha512, method.counter_at(at(59))), "90693936");
+
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("MZXW6YTBOI"), b"foobar");
+    }
+
+    #[tokio::test]
+    async fn generated_secret_validates_once_within_skew() {
+        let store = store().await;
+        let method = TotpMethod::new("totp", "ZVault");
+        store.put_totp_method(&method).await.unwrap();
+
+        let key = store.generate_totp("totp", "jwt-alice").await.unwrap();
+        assert!(
+            key.url
+                .starts_with("otpauth://totp/ZVault:jwt-alice?secret=")
+        );
+        assert!(matches!(
+            store.generate_totp("totp", "jwt-alice").await,
+            Err(MfaError::AlreadyEnrolled { .. })
+        ));
+        let secret = base32_decode(&key.secret);
+        let now = at(1_700_000_000);
+        let step = method.counter_at(now);
+
+        // The previous step is within the default skew; reusing it is not.
+        let previous = method.code(&secret, step - 1);
+        store
+            .validate("totp", "jwt-alice", &previous, now)
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.validate("totp", "jwt-alice", &previous, now).await,
+            Err(MfaError::InvalidCode { .. })
+        ));
+        let current = method.code(&secret, step);
+        store
+            .validate("totp", "jwt-alice", &current, now)
+            .await
+            .unwrap();
+
+        let stale = method.code(&secret, step + 5);
+        assert!(
+            store
+                .validate("totp", "jwt-alice", &stale, now)
+                .await
+                .is_err()
+        );
+        assert!(
+            store
+                .validate("totp", "jwt-alice", "12ab56", now)
+                .await
+                .is_err()
+        );
+        assert!(matches!(
+            store.validate("totp", "jwt-bob", &current, now).await,
+            Err(MfaError::NotEnrolled { .. })
+        ));
+
+        store.destroy_totp("totp", "jwt-alice").await.unwrap();
+        assert!(store.generate_totp("totp", "jwt-alice").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn login_enforcements_validate_and_collect_methods() {
+        let store = store().await;
+        store
+            .put_totp_method(&TotpMethod::new("totp", "ZVault"))
+            .await
+            .unwrap();
+        store
+            .put_totp_method(&TotpMethod::new("hardware", "ZVault"))
+            .await
+            .unwrap();
+
+        let mut enforcement = LoginEnforcement {
+            name: "ci".to_owned(),
+            mfa_methods: vec!["missing".to_owned()],
+            auth_methods: vec!["approle".to_owned()],
+        };
+        assert!(store.put_login_enforcement(&enforcement).await.is_err());
+        enforcement.mfa_methods = vec!["totp".to_owned()];
+        enforcement.auth_methods = vec!["oidc".to_owned()];
+        assert!(store.put_login_enforcement(&enforcement).await.is_err());
+        enforcement.auth_methods = vec!["approle".to_owned(), "jwt".to_owned()];
+        store.put_login_enforcement(&enforcement).await.unwrap();
+        store
+            .put_login_enforcement(&LoginEnforcement {
+                name: "humans".to_owned(),
+                mfa_methods: vec!["hardware".to_owned(), "totp".to_owned()],
+                auth_methods: vec!["jwt".to_owned()],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(store.login_methods("approle").await.unwrap(), vec!["totp"]);
+        assert_eq!(
+            store.login_methods("jwt").await.unwrap(),
+            vec!["totp", "hardware"]
+        );
+        assert!(store.login_methods("oidc").await.unwrap().is_empty());
+
+        assert!(matches!(
+            store.delete_totp_method("totp").await,
+            Err(MfaError::InUse { .. })
+        ));
+        store.delete_login_enforcement("ci").await.unwrap();
+        store.delete_login_enforcement("humans").await.unwrap();
+        store.delete_totp_method("totp").await.unwrap();
+        assert_eq!(store.list_totp_methods().await.unwrap(), vec!["hardware"]);
+    }
+
+    #[tokio::test]
+    async fn verified_methods_are_scoped_to_the_task() {
+        assert!(!is_verified("totp"));
+        let inside = run_verified(vec!["totp".to_owned()], async {
+            (is_verified("totp"), is_verified("other"))
+        })
+        .await;
+        assert_eq!(inside, (true, false));
+        assert!(!is_verified("totp"));
+
+        let parsed = parse_credentials("totp:123456, hardware:654321").unwrap();
+        assert_eq!(parsed[1], ("hardware".to_owned(), "654321".to_owned()));
+        assert!(parse_credentials("123456").is_err());
+        assert_eq!(identity(Some(""), "jwt-alice"), "jwt-alice");
+        assert_eq!(identity(Some("ent-1"), "jwt-alice"), "ent-1");
+    }
+}
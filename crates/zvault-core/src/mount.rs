@@ -14,6 +14,7 @@ use tokio::sync::RwLock;
 use tracing::info;
 
 use crate::barrier::Barrier;
+use crate::cubbyhole::CUBBYHOLE_MOUNT;
 use crate::error::MountError;
 
 /// Storage key for the serialized mount table.
@@ -125,6 +126,7 @@ impl MountManager {
     ///
     /// # Errors
     ///
+    /// - [`MountError::InvalidPath`] if the path is the built-in `cubbyhole/`.
     /// - [`MountError::NotFound`] if the path is not mounted.
     /// - [`MountError::Barrier`] if persistence fails.
     pub async fn unmount(&self, path: &str) -> Result<MountEntry, MountError> {
@@ -134,6 +136,12 @@ impl MountManager {
             format!("{path}/")
         };
 
+        if normalized == CUBBYHOLE_MOUNT {
+            return Err(MountError::InvalidPath {
+                reason: format!("{CUBBYHOLE_MOUNT} is built in and cannot be unmounted"),
+            });
+        }
+
         let mut table = self.table.write().await;
 
         let entry = table
@@ -150,6 +158,41 @@ impl MountManager {
         Ok(entry)
     }
 
+    /// Replace the engine-specific configuration of an existing mount.
+    ///
+    /// # Errors
+    ///
+    /// - [`MountError::NotFound`] if the path is not mounted.
+    /// - [`MountError::Barrier`] if persistence fails.
+    pub async fn tune(
+        &self,
+        path: &str,
+        config: serde_json::Value,
+    ) -> Result<MountEntry, MountError> {
+        let normalized = if path.ends_with('/') {
+            path.to_owned()
+        } else {
+            format!("{path}/")
+        };
+
+        let mut table = self.table.write().await;
+
+        let entry = table
+            .entries
+            .get_mut(&normalized)
+            .ok_or_else(|| MountError::NotFound {
+                path: normalized.clone(),
+            })?;
+        entry.config = config;
+        let updated = entry.clone();
+
+        self.persist(&table).await?;
+
+        info!(path = %normalized, "mount tuned");
+
+        Ok(updated)
+    }
+
     /// Look up which engine handles a given request path.
     ///
     /// Returns the mount entry and the remaining path after the mount prefix.
@@ -177,6 +220,36 @@ impl MountManager {
         })
     }
 
+    /// Merge the mounts persisted in storage into the in-memory table.
+    ///
+    /// The table starts empty when the server boots sealed; call this after
+    /// unseal to pick up engines mounted before the restart. Entries already
+    /// in memory are kept. Returns the entries that were added.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MountError::Barrier`] if storage access fails.
+    pub async fn reload(&self) -> Result<Vec<MountEntry>, MountError> {
+        let stored: MountTable = match self.barrier.get(MOUNT_TABLE_KEY).await? {
+            Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
+            None => return Ok(Vec::new()),
+        };
+
+        let mut table = self.table.write().await;
+        let mut added = Vec::new();
+        for (path, entry) in stored.entries {
+            if let std::collections::hash_map::Entry::Vacant(slot) = table.entries.entry(path) {
+                slot.insert(entry.clone());
+                added.push(entry);
+            }
+        }
+        if !added.is_empty() {
+            self.persist(&table).await?;
+            info!(count = added.len(), "mounts restored from storage");
+        }
+        Ok(added)
+    }
+
     /// List all mount entries.
     pub async fn list(&self) -> Vec<MountEntry> {
         let table = self.table.read().await;
@@ -0,0 +1,396 @@
+//! Per-mount export and import for `ZVault`.
+//!
+//! A full backup moves the whole barrier as ciphertext and is only readable
+//! with the source vault's unseal keys. Moving one team's engine to another
+//! cluster instead needs its data re-encrypted under a key both sides share:
+//! [`MountTransfer::export`] decrypts every entry under the mount's storage
+//! prefix and seals the mount entry and data into a [`MountBundle`] under a
+//! 256-bit transfer key, and [`MountTransfer::import`] opens the bundle on the
+//! target and writes the data back through that vault's barrier.
+//!
+//! The bundle's plaintext header is informational only; the mount entry
+//! used on import comes from the authenticated ciphertext. Leases and usage
+//! counters are not part of a mount's data and are not transferred.
+
+use std::sync::Arc;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::barrier::Barrier;
+use crate::crypto::{self, EncryptionKey};
+use crate::error::MountTransferError;
+use crate::mount::{MountEntry, MountManager};
+
+/// Current bundle format version.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// A mount's data sealed under a transfer key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountBundle {
+    /// Bundle format version.
+    pub version: u32,
+    /// Mount path on the source vault.
+    pub source_path: String,
+    /// Engine type of the exported mount.
+    pub engine_type: String,
+    /// Number of storage entries in the bundle.
+    pub entry_count: usize,
+    /// When the bundle was created.
+    pub created_at: DateTime<Utc>,
+    /// Base64 AES-256-GCM ciphertext of the mount entry and its data.
+    pub ciphertext: String,
+}
+
+/// The sealed contents of a [`MountBundle`].
+#[derive(Serialize, Deserialize)]
+struct BundlePayload {
+    mount: MountEntry,
+    entries: Vec<BundleEntry>,
+}
+
+/// One storage entry, keyed relative to the mount's storage prefix.
+#[derive(Serialize, Deserialize)]
+struct BundleEntry {
+    key: String,
+    /// Base64-encoded plaintext value.
+    value: String,
+}
+
+/// A bundle opened with its transfer key, ready to import.
+pub struct OpenedBundle {
+    /// The exported mount entry, with its source path.
+    pub mount: MountEntry,
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl OpenedBundle {
+    /// Number of storage entries the import will write.
+    #[must_use]
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Generate a random transfer key.
+#[must_use]
+pub fn generate_transfer_key() -> EncryptionKey {
+    EncryptionKey::generate()
+}
+
+/// Encode a transfer key as base64 for handing to an operator.
+#[must_use]
+pub fn encode_transfer_key(key: &EncryptionKey) -> String {
+    BASE64.encode(key.as_bytes())
+}
+
+/// Parse a base64-encoded 256-bit transfer key.
+///
+/// # Errors
+///
+/// Returns [`MountTransferError::InvalidTransferKey`] if `encoded` is not
+/// base64 for exactly 32 bytes.
+pub fn parse_transfer_key(encoded: &str) -> Result<EncryptionKey, MountTransferError> {
+    let bytes =
+        BASE64
+            .decode(encoded.trim())
+            .map_err(|_| MountTransferError::InvalidTransferKey {
+                reason: "not valid base64".to_owned(),
+            })?;
+    let bytes: [u8; 32] =
+        bytes
+            .try_into()
+            .map_err(|b: Vec<u8>| MountTransferError::InvalidTransferKey {
+                reason: format!("expected 32 bytes, got {}", b.len()),
+            })?;
+    Ok(EncryptionKey::from_bytes(bytes))
+}
+
+/// Exports and imports single mounts through the barrier.
+pub struct MountTransfer {
+    barrier: Arc<Barrier>,
+}
+
+impl MountTransfer {
+    /// Create a mount transfer helper backed by the given barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self { barrier }
+    }
+
+    /// Seal `mount` and every entry under `storage_prefix` into a bundle
+    /// encrypted with `key`.
+    ///
+    /// # Errors
+    ///
+    /// - [`MountTransferError::Barrier`] if reading the mount's data fails.
+    /// - [`MountTransferError::Internal`] if sealing the bundle fails.
+    pub async fn export(
+        &self,
+        mount: &MountEntry,
+        storage_prefix: &str,
+        key: &EncryptionKey,
+    ) -> Result<MountBundle, MountTransferError> {
+        let mut entries = Vec::new();
+        for storage_key in self.barrier.list(storage_prefix).await? {
+            let Some(relative) = storage_key.strip_prefix(storage_prefix) else {
+                continue;
+            };
+            if let Some(value) = self.barrier.get(&storage_key).await? {
+                entries.push(BundleEntry {
+                    key: relative.to_owned(),
+                    value: BASE64.encode(value),
+                });
+            }
+        }
+
+        let entry_count = entries.len();
+        let payload = serde_json::to_vec(&BundlePayload {
+            mount: mount.clone(),
+            entries,
+        })
+        .map_err(|e| MountTransferError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        let ciphertext =
+            crypto::encrypt(key, &payload).map_err(|e| MountTransferError::Internal {
+                reason: e.to_string(),
+            })?;
+
+        info!(path = %mount.path, entries = entry_count, "mount exported");
+
+        Ok(MountBundle {
+            version: BUNDLE_VERSION,
+            source_path: mount.path.clone(),
+            engine_type: mount.engine_type.clone(),
+            entry_count,
+            created_at: Utc::now(),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt a bundle with its transfer key.
+    ///
+    /// # Errors
+    ///
+    /// - [`MountTransferError::InvalidBundle`] if the bundle is malformed or
+    ///   from an unsupported version.
+    /// - [`MountTransferError::InvalidTransferKey`] if `key` does not open it.
+    pub fn open(
+        bundle: &MountBundle,
+        key: &EncryptionKey,
+    ) -> Result<OpenedBundle, MountTransferError> {
+        if bundle.version != BUNDLE_VERSION {
+            return Err(MountTransferError::InvalidBundle {
+                reason: format!("unsupported bundle version {}", bundle.version),
+            });
+        }
+        let ciphertext =
+            BASE64
+                .decode(&bundle.ciphertext)
+                .map_err(|_| MountTransferError::InvalidBundle {
+                    reason: "ciphertext is not valid base64".to_owned(),
+                })?;
+        let payload = crypto::decrypt(key, &ciphertext).map_err(|_| {
+            MountTransferError::InvalidTransferKey {
+                reason: "key does not open this bundle".to_owned(),
+            }
+        })?;
+        let payload: BundlePayload =
+            serde_json::from_slice(&payload).map_err(|e| MountTransferError::InvalidBundle {
+                reason: format!("corrupt payload: {e}"),
+            })?;
+
+        let mut entries = Vec::with_capacity(payload.entries.len());
+        for entry in payload.entries {
+            if entry.key.is_empty() {
+                return Err(MountTransferError::InvalidBundle {
+                    reason: "entry with empty key".to_owned(),
+                });
+            }
+            let value =
+                BASE64
+                    .decode(&entry.value)
+                    .map_err(|_| MountTransferError::InvalidBundle {
+                        reason: format!("invalid base64 value for key: {}", entry.key),
+                    })?;
+            entries.push((entry.key, value));
+        }
+
+        Ok(OpenedBundle {
+            mount: payload.mount,
+            entries,
+        })
+    }
+
+    /// Mount `opened` at `path` and write its data under `storage_prefix`.
+    ///
+    /// The target must be unmounted and hold no data, so an import never
+    /// merges into or overwrites existing secrets. If writing fails partway,
+    /// the written entries and the new mount are removed again.
+    ///
+    /// # Errors
+    ///
+    /// - [`MountTransferError::TargetNotEmpty`] if `storage_prefix` holds data.
+    /// - [`MountTransferError::Mount`] if `path` is already mounted or invalid.
+    /// - [`MountTransferError::Barrier`] if writing the data fails.
+    pub async fn import(
+        &self,
+        mounts: &MountManager,
+        opened: OpenedBundle,
+        path: &str,
+        storage_prefix: &str,
+    ) -> Result<MountEntry, MountTransferError> {
+        if !self.barrier.list(storage_prefix).await?.is_empty() {
+            return Err(MountTransferError::TargetNotEmpty {
+                path: path.to_owned(),
+            });
+        }
+
+        let entry = MountEntry {
+            path: path.to_owned(),
+            ..opened.mount
+        };
+        mounts.mount(entry.clone()).await?;
+
+        for (written, (key, value)) in opened.entries.iter().enumerate() {
+            if let Err(e) = self
+                .barrier
+                .put(&format!("{storage_prefix}{key}"), value)
+                .await
+            {
+                for (key, _) in opened.entries.iter().take(written) {
+                    let _ = self.barrier.delete(&format!("{storage_prefix}{key}")).await;
+                }
+                let _ = mounts.unmount(path).await;
+                return Err(e.into());
+            }
+        }
+
+        info!(path = %path, entries = opened.entries.len(), "mount imported");
+
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use serde_json::json;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::engine::{EngineRequest, KvEngine, Operation};
+    use crate::error::MountError;
+
+    async fn vault() -> (Arc<Barrier>, MountManager) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let mounts = MountManager::empty(Arc::clone(&barrier));
+        (barrier, mounts)
+    }
+
+    fn kv_mount(path: &str) -> MountEntry {
+        MountEntry {
+            path: path.to_owned(),
+            engine_type: "kv".to_owned(),
+            description: "team A".to_owned(),
+            config: json!({"lease_reads": false}),
+        }
+    }
+
+    #[tokio::test]
+    async fn moves_mount_between_vaults() {
+        let (source, source_mounts) = vault().await;
+        source_mounts.mount(kv_mount("team-a/")).await.unwrap();
+        let kv = KvEngine::new(Arc::clone(&source), "kv/team-a/".to_owned());
+        kv.handle(&EngineRequest {
+            operation: Operation::Write,
+            path: "db/creds".to_owned(),
+            data: Some(json!({"password": "hunter2"})),
+        })
+        .await
+        .unwrap();
+
+        let key = generate_transfer_key();
+        let bundle = MountTransfer::new(source)
+            .export(&kv_mount("team-a/"), "kv/team-a/", &key)
+            .await
+            .unwrap();
+        assert_eq!(bundle.engine_type, "kv");
+        assert!(!bundle.ciphertext.contains("hunter2"));
+
+        let (target, target_mounts) = vault().await;
+        let key = parse_transfer_key(&encode_transfer_key(&key)).unwrap();
+        let opened = MountTransfer::open(&bundle, &key).unwrap();
+        assert_eq!(opened.entry_count(), bundle.entry_count);
+        let entry = MountTransfer::new(Arc::clone(&target))
+            .import(&target_mounts, opened, "team-b/", "kv/team-b/")
+            .await
+            .unwrap();
+        assert_eq!(entry.path, "team-b/");
+        assert_eq!(entry.description, "team A");
+
+        let kv = KvEngine::new(target, "kv/team-b/".to_owned());
+        let read = kv
+            .handle(&EngineRequest {
+                operation: Operation::Read,
+                path: "db/creds".to_owned(),
+                data: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            read.data.unwrap().pointer("/data/password").unwrap(),
+            "hunter2"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_key_and_occupied_target() {
+        let (source, _) = vault().await;
+        source.put("kv/team-a/data/x", b"{}").await.unwrap();
+        let key = generate_transfer_key();
+        let bundle = MountTransfer::new(source)
+            .export(&kv_mount("team-a/"), "kv/team-a/", &key)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            MountTransfer::open(&bundle, &generate_transfer_key()),
+            Err(MountTransferError::InvalidTransferKey { .. })
+        ));
+        assert!(parse_transfer_key("c2hvcnQ=").is_err());
+
+        let (target, target_mounts) = vault().await;
+        let transfer = MountTransfer::new(Arc::clone(&target));
+        target_mounts.mount(kv_mount("taken/")).await.unwrap();
+        assert!(matches!(
+            transfer
+                .import(
+                    &target_mounts,
+                    MountTransfer::open(&bundle, &key).unwrap(),
+                    "taken/",
+                    "kv/taken/",
+                )
+                .await,
+            Err(MountTransferError::Mount(MountError::AlreadyMounted { .. }))
+        ));
+
+        target.put("kv/stale/data/y", b"{}").await.unwrap();
+        assert!(matches!(
+            transfer
+                .import(
+                    &target_mounts,
+                    MountTransfer::open(&bundle, &key).unwrap(),
+                    "stale/",
+                    "kv/stale/",
+                )
+                .await,
+            Err(MountTransferError::TargetNotEmpty { .. })
+        ));
+    }
+}
@@ -0,0 +1,967 @@
+//! Webhook notifications for `ZVault`.
+//!
+//! Webhooks subscribe to [`VaultEvent`] topics using the same patterns as the
+//! event stream (`kv.*`, `lease.expired`, `*`; see [`topic_matches`]). Each
+//! matching event is rendered for the webhook's [`WebhookFormat`] — a Slack
+//! or Discord message, a `PagerDuty` Events API v2 alert, or the event itself
+//! for generic receivers — and `POST`ed with `X-ZVault-Event`, `X-ZVault-Delivery`, and
+//! `X-ZVault-Timestamp` headers. Webhooks with a signing secret also get
+//! `X-ZVault-Signature: sha256=<hex>`, an HMAC-SHA256 of
+//! `{timestamp}.{body}`.
+//!
+//! Seal events are alerts: `sys.sealed` and `sys.unseal_failed` are
+//! [`Severity::Critical`], mention `@channel` in Slack, and trigger a
+//! `PagerDuty` incident that the next `sys.unsealed` resolves. Webhooks are
+//! cached in memory so these events are delivered even though the barrier
+//! is sealed.
+//!
+//! Failed deliveries are queued and retried with exponential backoff by
+//! [`NotificationManager::retry_due`], and dropped after [`MAX_ATTEMPTS`].
+//! Webhooks and the retry queue are stored through the barrier under
+//! `sys/notifications/`; a delivery that fails while the vault is sealed
+//! cannot be queued and is only logged.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
+
+use crate::barrier::Barrier;
+use crate::error::{BarrierError, NotifyError};
+use crate::events::{
+    TOPIC_INITIALIZED, TOPIC_SEALED, TOPIC_UNSEAL_FAILED, TOPIC_UNSEALED, VaultEvent, topic_matches,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Storage prefix for webhook configs.
+const WEBHOOK_PREFIX: &str = "sys/notifications/webhooks/";
+
+/// Storage prefix for queued deliveries.
+const QUEUE_PREFIX: &str = "sys/notifications/queue/";
+
+/// Topic of the event sent by [`NotificationManager::test`].
+pub const TOPIC_TEST: &str = "notify.test";
+
+/// Delivery attempts before a queued notification is dropped.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// Delay before the first retry; doubled after each failure.
+const RETRY_BASE: chrono::Duration = chrono::Duration::seconds(30);
+
+/// Longest delay between retries.
+const RETRY_MAX: chrono::Duration = chrono::Duration::hours(1);
+
+/// Timeout for one delivery request.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `PagerDuty` dedup key shared by seal alerts and the unseal that resolves
+/// them.
+const SEAL_DEDUP_KEY: &str = "zvault-sealed";
+
+/// How a webhook's payload is shaped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// The [`VaultEvent`] as JSON.
+    #[default]
+    Generic,
+    /// A Slack incoming-webhook message: `{"text": ...}`.
+    Slack,
+    /// A Discord webhook message: `{"content": ...}`.
+    Discord,
+    /// A `PagerDuty` Events API v2 event; needs a routing key.
+    PagerDuty,
+}
+
+impl WebhookFormat {
+    /// Guess the format from a webhook URL's host.
+    #[must_use]
+    pub fn detect(url: &str) -> Self {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_owned))
+            .unwrap_or_default();
+        if host == "hooks.slack.com" {
+            Self::Slack
+        } else if host == "discord.com" || host.ends_with(".discord.com") {
+            Self::Discord
+        } else if host == "events.pagerduty.com" || host.ends_with(".events.pagerduty.com") {
+            Self::PagerDuty
+        } else {
+            Self::Generic
+        }
+    }
+}
+
+/// A configured notification webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    /// Unique name.
+    pub name: String,
+    /// Endpoint receiving `POST` requests.
+    pub url: String,
+    /// Payload shape.
+    pub format: WebhookFormat,
+    /// Topic patterns this webhook is notified of.
+    pub events: Vec<String>,
+    /// HMAC key for `X-ZVault-Signature`, if signing is enabled.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// `PagerDuty` integration key, for [`WebhookFormat::PagerDuty`].
+    #[serde(default)]
+    pub routing_key: Option<String>,
+    /// Disabled webhooks keep their config but receive nothing.
+    pub enabled: bool,
+    /// When the webhook was created.
+    pub created_at: DateTime<Utc>,
+    /// When the webhook was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether this webhook should receive an event on `topic`.
+    #[must_use]
+    pub fn wants(&self, topic: &str) -> bool {
+        self.enabled && self.events.iter().any(|p| topic_matches(p, topic))
+    }
+}
+
+/// Parameters for creating or replacing a webhook.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookParams {
+    /// Endpoint URL (`http` or `https`).
+    pub url: String,
+    /// Payload shape; detected from the URL when `None`.
+    pub format: Option<WebhookFormat>,
+    /// Topic patterns; empty means every topic.
+    pub events: Vec<String>,
+    /// Signing secret. `None` keeps the current one; an empty string removes it.
+    pub secret: Option<String>,
+    /// `PagerDuty` routing key. `None` keeps the current one; an empty string
+    /// removes it.
+    pub routing_key: Option<String>,
+    /// Whether the webhook is enabled (default `true`).
+    pub enabled: Option<bool>,
+}
+
+/// A notification waiting to be retried.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    /// Queue entry ID.
+    pub id: String,
+    /// Name of the target webhook.
+    pub webhook: String,
+    /// The event being delivered.
+    pub event: VaultEvent,
+    /// Attempts made so far.
+    pub attempts: u32,
+    /// Earliest time of the next attempt.
+    pub next_attempt_at: DateTime<Utc>,
+    /// Why the last attempt failed.
+    pub last_error: String,
+}
+
+/// How urgent an event is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Routine change.
+    Info,
+    /// The vault cannot serve secrets until an operator acts.
+    Critical,
+}
+
+impl Severity {
+    /// Severity of events on `topic`.
+    #[must_use]
+    pub fn of(topic: &str) -> Self {
+        if topic == TOPIC_SEALED || topic == TOPIC_UNSEAL_FAILED {
+            Self::Critical
+        } else {
+            Self::Info
+        }
+    }
+
+    /// `PagerDuty`'s name for this severity.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// Stores webhooks and delivers events to them.
+pub struct NotificationManager {
+    barrier: Arc<Barrier>,
+    client: reqwest::Client,
+    /// Webhooks as of the last storage read, used while the vault is sealed.
+    cache: RwLock<Vec<Webhook>>,
+    /// Reported as the alert source to `PagerDuty`.
+    source: String,
+    /// Keeps concurrent retry passes from sending a delivery twice.
+    retry_lock: Mutex<()>,
+}
+
+impl NotificationManager {
+    /// Create a manager backed by the barrier.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NotifyError::Internal`] if the HTTP client cannot be built.
+    pub fn new(barrier: Arc<Barrier>) -> Result<Self, NotifyError> {
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .map_err(|e| NotifyError::Internal {
+                reason: format!("failed to build http client: {e}"),
+            })?;
+        Ok(Self {
+            barrier,
+            client,
+            cache: RwLock::new(Vec::new()),
+            source: "zvault".to_owned(),
+            retry_lock: Mutex::new(()),
+        })
+    }
+
+    /// Name this server in alerts (e.g., its API address).
+    #[must_use]
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Create or replace the webhook `name`.
+    ///
+    /// # Errors
+    ///
+    /// - [`NotifyError::Invalid`] if the name, URL, or a pattern is invalid.
+    /// - [`NotifyError::Barrier`] if storage fails.
+    pub async fn put(&self, name: &str, params: WebhookParams) -> Result<Webhook, NotifyError> {
+        validate_name(name)?;
+        let parsed =
+            reqwest::Url::parse(&params.url).map_err(|e| invalid(format!("webhook url: {e}")))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(invalid("webhook url must use http or https"));
+        }
+        let mut events: Vec<String> = params
+            .events
+            .iter()
+            .map(|p| p.trim().to_owned())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if events.is_empty() {
+            events.push("*".to_owned());
+        }
+
+        let now = Utc::now();
+        let existing = self.load(name).await?;
+        let secret = match params.secret {
+            Some(s) if s.is_empty() => None,
+            Some(s) => Some(s),
+            None => existing.as_ref().and_then(|w| w.secret.clone()),
+        };
+        let routing_key = match params.routing_key {
+            Some(k) if k.is_empty() => None,
+            Some(k) => Some(k),
+            None => existing.as_ref().and_then(|w| w.routing_key.clone()),
+        };
+        let format = params
+            .format
+            .unwrap_or_else(|| WebhookFormat::detect(&params.url));
+        if format == WebhookFormat::PagerDuty && routing_key.is_none() {
+            return Err(invalid("pagerduty webhooks need a routing_key"));
+        }
+        let webhook = Webhook {
+            name: name.to_owned(),
+            format,
+            url: params.url,
+            events,
+            secret,
+            routing_key,
+            enabled: params.enabled.unwrap_or(true),
+            created_at: existing.map_or(now, |w| w.created_at),
+            updated_at: now,
+        };
+        self.store(&format!("{WEBHOOK_PREFIX}{name}"), &webhook)
+            .await?;
+        self.list().await?;
+        Ok(webhook)
+    }
+
+    /// Read the webhook `name`.
+    ///
+    /// # Errors
+    ///
+    /// - [`NotifyError::NotFound`] if it does not exist.
+    /// - [`NotifyError::Barrier`] if storage fails.
+    pub async fn get(&self, name: &str) -> Result<Webhook, NotifyError> {
+        self.load(name).await?.ok_or_else(|| NotifyError::NotFound {
+            name: name.to_owned(),
+        })
+    }
+
+    /// List all webhooks, sorted by name, and refresh the in-memory copy
+    /// used while the vault is sealed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NotifyError::Barrier`] if storage fails.
+    pub async fn list(&self) -> Result<Vec<Webhook>, NotifyError> {
+        let mut webhooks: Vec<Webhook> = self.read_all(WEBHOOK_PREFIX).await?;
+        webhooks.sort_by(|a, b| a.name.cmp(&b.name));
+        self.cache.write().await.clone_from(&webhooks);
+        Ok(webhooks)
+    }
+
+    /// Delete the webhook `name` and its queued deliveries.
+    ///
+    /// # Errors
+    ///
+    /// - [`NotifyError::NotFound`] if it does not exist.
+    /// - [`NotifyError::Barrier`] if storage fails.
+    pub async fn delete(&self, name: &str) -> Result<(), NotifyError> {
+        self.get(name).await?;
+        self.barrier
+            .delete(&format!("{WEBHOOK_PREFIX}{name}"))
+            .await?;
+        self.list().await?;
+        for delivery in self.pending().await? {
+            if delivery.webhook == name {
+                self.barrier
+                    .delete(&format!("{QUEUE_PREFIX}{}", delivery.id))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deliveries waiting to be retried, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NotifyError::Barrier`] if storage fails.
+    pub async fn pending(&self) -> Result<Vec<Delivery>, NotifyError> {
+        let mut queue: Vec<Delivery> = self.read_all(QUEUE_PREFIX).await?;
+        queue.sort_by_key(|d| d.event.timestamp);
+        Ok(queue)
+    }
+
+    /// Send a [`TOPIC_TEST`] event to the webhook `name` right away.
+    ///
+    /// The webhook's event filter and enabled flag are ignored, and a failed
+    /// test is not queued.
+    ///
+    /// # Errors
+    ///
+    /// - [`NotifyError::NotFound`] if the webhook does not exist.
+    /// - [`NotifyError::Delivery`] if the endpoint does not accept the request.
+    /// - [`NotifyError::Barrier`] if storage fails.
+    pub async fn test(&self, name: &str) -> Result<(), NotifyError> {
+        let webhook = self.get(name).await?;
+        let event = VaultEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            topic: TOPIC_TEST.to_owned(),
+            timestamp: Utc::now(),
+            data: serde_json::json!({ "webhook": name }),
+        };
+        self.send(&webhook, &event)
+            .await
+            .map_err(|reason| NotifyError::Delivery {
+                name: name.to_owned(),
+                reason,
+            })
+    }
+
+    /// Deliver `event` to every enabled webhook whose patterns match it.
+    ///
+    /// While the vault is sealed, the webhooks from the last storage read
+    /// are used. Failed deliveries are queued for [`Self::retry_due`], or
+    /// logged if the vault is sealed. Returns the number of webhooks the
+    /// event was delivered to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NotifyError::Barrier`] if storage fails.
+    pub async fn dispatch(&self, event: &VaultEvent) -> Result<usize, NotifyError> {
+        let webhooks = match self.list().await {
+            Err(NotifyError::Barrier(BarrierError::Sealed)) => self.cache.read().await.clone(),
+            result => result?,
+        };
+        let mut delivered = 0;
+        for webhook in webhooks {
+            if !webhook.wants(&event.topic) {
+                continue;
+            }
+            match self.send(&webhook, event).await {
+                Ok(()) => delivered += 1,
+                Err(reason) => {
+                    let delivery = Delivery {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        webhook: webhook.name.clone(),
+                        event: event.clone(),
+                        attempts: 0,
+                        next_attempt_at: Utc::now(),
+                        last_error: String::new(),
+                    };
+                    match self.requeue(delivery, reason.clone(), Utc::now()).await {
+                        Err(NotifyError::Barrier(BarrierError::Sealed)) => warn!(
+                            webhook = %webhook.name,
+                            topic = %event.topic,
+                            error = %reason,
+                            "notification failed while sealed, not queued"
+                        ),
+                        result => result?,
+                    }
+                }
+            }
+        }
+        Ok(delivered)
+    }
+
+    /// Retry queued deliveries whose backoff has elapsed by `now`.
+    ///
+    /// Deliveries to webhooks that were deleted or disabled are dropped.
+    /// Returns the number delivered. A pass already in progress makes this
+    /// call return `0` immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NotifyError::Barrier`] if storage fails.
+    pub async fn retry_due(&self, now: DateTime<Utc>) -> Result<usize, NotifyError> {
+        let Ok(_retrying) = self.retry_lock.try_lock() else {
+            return Ok(0);
+        };
+        let mut delivered = 0;
+        for delivery in self.pending().await? {
+            if delivery.next_attempt_at > now {
+                continue;
+            }
+            let key = format!("{QUEUE_PREFIX}{}", delivery.id);
+            let webhook = match self.load(&delivery.webhook).await? {
+                Some(webhook) if webhook.enabled => webhook,
+                _ => {
+                    self.barrier.delete(&key).await?;
+                    continue;
+                }
+            };
+            match self.send(&webhook, &delivery.event).await {
+                Ok(()) => {
+                    self.barrier.delete(&key).await?;
+                    delivered += 1;
+                }
+                Err(reason) => self.requeue(delivery, reason, now).await?,
+            }
+        }
+        Ok(delivered)
+    }
+
+    // ── Private helpers ──────────────────────────────────────────────
+
+    /// Record a failed attempt, scheduling a retry or dropping the delivery.
+    async fn requeue(
+        &self,
+        mut delivery: Delivery,
+        reason: String,
+        now: DateTime<Utc>,
+    ) -> Result<(), NotifyError> {
+        let key = format!("{QUEUE_PREFIX}{}", delivery.id);
+        delivery.attempts = delivery.attempts.saturating_add(1);
+        if delivery.attempts >= MAX_ATTEMPTS {
+            warn!(
+                webhook = %delivery.webhook,
+                topic = %delivery.event.topic,
+                error = %reason,
+                "notification dropped after {MAX_ATTEMPTS} attempts"
+            );
+            self.barrier.delete(&key).await?;
+            return Ok(());
+        }
+        warn!(webhook = %delivery.webhook, error = %reason, "notification failed, will retry");
+        delivery.next_attempt_at = now + backoff(delivery.attempts);
+        delivery.last_error = reason;
+        self.store(&key, &delivery).await
+    }
+
+    /// POST `event` to `webhook`, returning the failure reason.
+    async fn send(&self, webhook: &Webhook, event: &VaultEvent) -> Result<(), String> {
+        let body =
+            serde_json::to_vec(&render(webhook, event, &self.source)).map_err(|e| e.to_string())?;
+        let timestamp = Utc::now().timestamp().to_string();
+        let mut request = self
+            .client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-ZVault-Event", &event.topic)
+            .header("X-ZVault-Delivery", &event.id)
+            .header("X-ZVault-Timestamp", &timestamp);
+        if let Some(secret) = &webhook.secret {
+            request = request.header(
+                "X-ZVault-Signature",
+                format!("sha256={}", sign(secret, &timestamp, &body)),
+            );
+        }
+        request
+            .body(body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map(drop)
+            .map_err(|e| e.to_string())
+    }
+
+    async fn load(&self, name: &str) -> Result<Option<Webhook>, NotifyError> {
+        let Some(data) = self.barrier.get(&format!("{WEBHOOK_PREFIX}{name}")).await? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| NotifyError::Internal {
+                reason: format!("corrupt webhook '{name}': {e}"),
+            })
+    }
+
+    async fn store<T: Serialize>(&self, key: &str, value: &T) -> Result<(), NotifyError> {
+        let data = serde_json::to_vec(value).map_err(|e| NotifyError::Internal {
+            reason: format!("failed to serialize {key}: {e}"),
+        })?;
+        self.barrier.put(key, &data).await?;
+        Ok(())
+    }
+
+    /// Every record under `prefix`, skipping corrupt ones.
+    async fn read_all<T: serde::de::DeserializeOwned>(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<T>, NotifyError> {
+        let mut records = Vec::new();
+        for key in self.barrier.list(prefix).await? {
+            let Some(data) = self.barrier.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice(&data) {
+                Ok(record) => records.push(record),
+                Err(e) => warn!(key = %key, error = %e, "skipping corrupt notification record"),
+            }
+        }
+        Ok(records)
+    }
+}
+
+impl std::fmt::Debug for NotificationManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotificationManager")
+            .finish_non_exhaustive()
+    }
+}
+
+/// The request body for `event` in `webhook`'s format. `source` names the
+/// server in `PagerDuty` alerts.
+#[must_use]
+pub fn render(webhook: &Webhook, event: &VaultEvent, source: &str) -> serde_json::Value {
+    let severity = Severity::of(&event.topic);
+    match webhook.format {
+        WebhookFormat::Generic => serde_json::to_value(event).unwrap_or_default(),
+        WebhookFormat::Slack => {
+            let text = match severity {
+                Severity::Critical => format!("<!channel> {}", summary(event)),
+                Severity::Info => summary(event),
+            };
+            let color = match severity {
+                Severity::Critical => "danger",
+                Severity::Info if event.topic == TOPIC_UNSEALED => "good",
+                Severity::Info => return serde_json::json!({ "text": text }),
+            };
+            serde_json::json!({
+                "text": text,
+                "attachments": [{
+                    "color": color,
+                    "fields": [
+                        { "title": "Server", "value": source, "short": true },
+                        { "title": "Event", "value": event.topic, "short": true },
+                    ],
+                    "ts": event.timestamp.timestamp(),
+                }],
+            })
+        }
+        WebhookFormat::Discord => serde_json::json!({ "content": summary(event) }),
+        WebhookFormat::PagerDuty => {
+            let routing_key = webhook.routing_key.as_deref().unwrap_or_default();
+            if event.topic == TOPIC_UNSEALED {
+                return serde_json::json!({
+                    "routing_key": routing_key,
+                    "event_action": "resolve",
+                    "dedup_key": SEAL_DEDUP_KEY,
+                });
+            }
+            let dedup_key = match severity {
+                Severity::Critical => SEAL_DEDUP_KEY,
+                Severity::Info => event.id.as_str(),
+            };
+            serde_json::json!({
+                "routing_key": routing_key,
+                "event_action": "trigger",
+                "dedup_key": dedup_key,
+                "payload": {
+                    "summary": summary(event),
+                    "source": source,
+                    "severity": severity.as_str(),
+                    "timestamp": event.timestamp,
+                    "component": "zvault",
+                    "class": event.topic,
+                    "custom_details": event.data,
+                },
+            })
+        }
+    }
+}
+
+/// One-line description of an event for chat messages and alerts.
+fn summary(event: &VaultEvent) -> String {
+    if let Some(message) = seal_message(event) {
+        return format!("[ZVault] {}: {message}", event.topic);
+    }
+    let subject = ["path", "engine_path", "name", "lease_id", "webhook"]
+        .iter()
+        .find_map(|key| event.data.get(key).and_then(serde_json::Value::as_str));
+    match subject {
+        Some(subject) => format!("[ZVault] {}: {subject}", event.topic),
+        None => format!("[ZVault] {}", event.topic),
+    }
+}
+
+/// What a seal status event means for operators.
+fn seal_message(event: &VaultEvent) -> Option<String> {
+    match event.topic.as_str() {
+        TOPIC_SEALED => {
+            Some("vault sealed, secrets are unavailable until it is unsealed".to_owned())
+        }
+        TOPIC_UNSEALED => Some("vault unsealed".to_owned()),
+        TOPIC_UNSEAL_FAILED => {
+            let attempts = event
+                .data
+                .get("attempts")
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or_default();
+            Some(format!(
+                "{attempts} failed unseal attempts, the vault is still sealed"
+            ))
+        }
+        TOPIC_INITIALIZED => Some("vault initialized".to_owned()),
+        _ => None,
+    }
+}
+
+/// Hex HMAC-SHA256 of `{timestamp}.{body}` under `secret`.
+#[must_use]
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    // HMAC accepts keys of any length.
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return String::new();
+    };
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Delay before attempt `attempts + 1`.
+fn backoff(attempts: u32) -> chrono::Duration {
+    let factor = 1_i32 << attempts.saturating_sub(1).min(16);
+    (RETRY_BASE * factor).min(RETRY_MAX)
+}
+
+fn validate_name(name: &str) -> Result<(), NotifyError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid(
+            "webhook name must be 1-64 letters, digits, '-' or '_'",
+        ))
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> NotifyError {
+    NotifyError::Invalid {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn manager() -> NotificationManager {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        NotificationManager::new(barrier).unwrap()
+    }
+
+    fn event(topic: &str) -> VaultEvent {
+        VaultEvent {
+            id: "evt-1".to_owned(),
+            topic: topic.to_owned(),
+            timestamp: Utc::now(),
+            data: serde_json::json!({ "path": "secret/data/app/db" }),
+        }
+    }
+
+    /// A URL on a port that refuses connections.
+    fn dead_url() -> String {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        format!("http://127.0.0.1:{port}/hook")
+    }
+
+    #[tokio::test]
+    async fn put_keeps_secret_and_created_at() {
+        let mgr = manager().await;
+        let first = mgr
+            .put(
+                "ops",
+                WebhookParams {
+                    url: "https://hooks.slack.com/services/T/B/X".to_owned(),
+                    secret: Some("s3cret".to_owned()),
+                    ..WebhookParams::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(first.format, WebhookFormat::Slack);
+        assert_eq!(first.events, ["*"]);
+
+        let second = mgr
+            .put(
+                "ops",
+                WebhookParams {
+                    url: "https://example.com/hook".to_owned(),
+                    events: vec!["kv.*".to_owned()],
+                    ..WebhookParams::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(second.secret.as_deref(), Some("s3cret"));
+        assert_eq!(second.created_at, first.created_at);
+        assert_eq!(second.format, WebhookFormat::Generic);
+        assert!(second.wants("kv.write"));
+        assert!(!second.wants("policy.write"));
+
+        assert_eq!(mgr.list().await.unwrap().len(), 1);
+        mgr.delete("ops").await.unwrap();
+        assert!(matches!(
+            mgr.get("ops").await,
+            Err(NotifyError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_rejects_bad_input() {
+        let mgr = manager().await;
+        let params = |url: &str| WebhookParams {
+            url: url.to_owned(),
+            ..WebhookParams::default()
+        };
+        assert!(mgr.put("ops", params("ftp://example.com")).await.is_err());
+        assert!(mgr.put("a/b", params("https://example.com")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_then_dropped() {
+        let mgr = manager().await;
+        mgr.put(
+            "dead",
+            WebhookParams {
+                url: dead_url(),
+                events: vec!["kv.*".to_owned()],
+                ..WebhookParams::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(mgr.dispatch(&event("policy.write")).await.unwrap(), 0);
+        assert!(mgr.pending().await.unwrap().is_empty());
+
+        assert_eq!(mgr.dispatch(&event("kv.write")).await.unwrap(), 0);
+        let queued = mgr.pending().await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].attempts, 1);
+        assert!(!queued[0].last_error.is_empty());
+
+        // Not due yet.
+        mgr.retry_due(Utc::now()).await.unwrap();
+        assert_eq!(mgr.pending().await.unwrap()[0].attempts, 1);
+
+        let mut now = Utc::now();
+        for _ in 1..MAX_ATTEMPTS {
+            now += RETRY_MAX;
+            mgr.retry_due(now).await.unwrap();
+        }
+        assert!(mgr.pending().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn deleting_webhook_drops_its_queue() {
+        let mgr = manager().await;
+        mgr.put(
+            "dead",
+            WebhookParams {
+                url: dead_url(),
+                ..WebhookParams::default()
+            },
+        )
+        .await
+        .unwrap();
+        mgr.dispatch(&event("kv.write")).await.unwrap();
+        assert_eq!(mgr.pending().await.unwrap().len(), 1);
+
+        mgr.delete("dead").await.unwrap();
+        assert!(mgr.pending().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sealed_vault_still_notifies_cached_webhooks() {
+        let mgr = manager().await;
+        mgr.put(
+            "dead",
+            WebhookParams {
+                url: dead_url(),
+                events: vec!["sys.*".to_owned()],
+                ..WebhookParams::default()
+            },
+        )
+        .await
+        .unwrap();
+        mgr.barrier.seal().await;
+
+        // The delivery is attempted from the cache; its failure cannot be
+        // queued while sealed, but does not fail the dispatch.
+        assert_eq!(mgr.dispatch(&event(TOPIC_SEALED)).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn pagerduty_needs_routing_key() {
+        let mgr = manager().await;
+        let url = "https://events.pagerduty.com/v2/enqueue";
+        let err = mgr
+            .put(
+                "pd",
+                WebhookParams {
+                    url: url.to_owned(),
+                    ..WebhookParams::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, NotifyError::Invalid { .. }));
+
+        let webhook = mgr
+            .put(
+                "pd",
+                WebhookParams {
+                    url: url.to_owned(),
+                    routing_key: Some("R0UT1NG".to_owned()),
+                    ..WebhookParams::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(webhook.format, WebhookFormat::PagerDuty);
+    }
+
+    fn webhook(format: WebhookFormat) -> Webhook {
+        Webhook {
+            name: "ops".to_owned(),
+            url: "https://example.com/hook".to_owned(),
+            format,
+            events: vec!["*".to_owned()],
+            secret: None,
+            routing_key: Some("R0UT1NG".to_owned()),
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn renders_each_format() {
+        let evt = event("kv.write");
+        let slack = render(&webhook(WebhookFormat::Slack), &evt, "vault-0");
+        assert_eq!(slack["text"], "[ZVault] kv.write: secret/data/app/db");
+        assert!(slack.get("attachments").is_none());
+        let discord = render(&webhook(WebhookFormat::Discord), &evt, "vault-0");
+        assert!(discord["content"].is_string());
+        let generic = render(&webhook(WebhookFormat::Generic), &evt, "vault-0");
+        assert_eq!(generic["topic"], "kv.write");
+        assert_eq!(
+            WebhookFormat::detect("https://discord.com/api/webhooks/1/x"),
+            WebhookFormat::Discord
+        );
+        assert_eq!(
+            WebhookFormat::detect("https://events.pagerduty.com/v2/enqueue"),
+            WebhookFormat::PagerDuty
+        );
+    }
+
+    #[test]
+    fn seal_events_page_and_resolve() {
+        let slack = render(
+            &webhook(WebhookFormat::Slack),
+            &event(TOPIC_SEALED),
+            "vault-0",
+        );
+        assert!(slack["text"].as_str().unwrap().starts_with("<!channel> "));
+        assert_eq!(slack["attachments"][0]["color"], "danger");
+
+        let pd = webhook(WebhookFormat::PagerDuty);
+        let trigger = render(&pd, &event(TOPIC_UNSEAL_FAILED), "vault-0");
+        assert_eq!(trigger["routing_key"], "R0UT1NG");
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["dedup_key"], SEAL_DEDUP_KEY);
+        assert_eq!(trigger["payload"]["severity"], "critical");
+        assert_eq!(trigger["payload"]["source"], "vault-0");
+
+        let resolve = render(&pd, &event(TOPIC_UNSEALED), "vault-0");
+        assert_eq!(resolve["event_action"], "resolve");
+        assert_eq!(resolve["dedup_key"], SEAL_DEDUP_KEY);
+
+        let info = render(&pd, &event("kv.write"), "vault-0");
+        assert_eq!(info["payload"]["severity"], "info");
+        assert_eq!(info["dedup_key"], "evt-1");
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let sig = sign("key", "1700000000", b"{}");
+        assert_eq!(sig.len(), 64);
+        assert_eq!(sig, sign("key", "1700000000", b"{}"));
+        assert_ne!(sig, sign("key", "1700000001", b"{}"));
+        assert_ne!(sig, sign("other", "1700000000", b"{}"));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        assert_eq!(backoff(1), chrono::Duration::seconds(30));
+        assert_eq!(backoff(2), chrono::Duration::seconds(60));
+        assert_eq!(backoff(20), RETRY_MAX);
+    }
+}
@@ -3,15 +3,43 @@
 //! Generates a self-signed root CA and issues X.509 certificates on demand.
 //! Certificates are tracked via the lease system. Uses `rcgen` for pure-Rust
 //! certificate generation — no OpenSSL dependency.
+//!
+//! A role decides what may be issued: which DNS names (exact, subdomains,
+//! and wildcards), whether IP SANs are allowed, which URI SANs (by glob),
+//! the key type and size (RSA 2048/3072/4096, ECDSA P-256/P-384, Ed25519, or
+//! `any` to let the requester choose), and how far `not_before` is
+//! backdated to absorb clock skew.
+//!
+//! The certificate store can be paged and filtered by common name and
+//! expiry, and `tidy` prunes certificates and revocation entries once they
+//! are past expiry by a safety buffer.
 
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::sync::Arc;
 
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Datelike, Utc};
+use rsa::pkcs8::{EncodePrivateKey, LineEnding};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+use crate::acme::Der;
 use crate::barrier::Barrier;
-use crate::error::PkiError;
+use crate::error::{LeaseError, PkiError};
+use crate::lease::{Lease, RevocationHandler};
+use crate::registry::Engine;
+
+/// Seconds `not_before` is backdated by when a role does not say.
+pub const DEFAULT_NOT_BEFORE_SECS: u64 = 30;
+
+/// Longest backdating a role or request may ask for (one day).
+const MAX_NOT_BEFORE_SECS: u64 = 86_400;
+
+/// How long past expiry `tidy` keeps a certificate when not told (72 hours).
+pub const DEFAULT_TIDY_SAFETY_BUFFER_SECS: i64 = 259_200;
 
 /// Root CA data stored in the barrier.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +55,7 @@ pub struct CaData {
 }
 
 /// A PKI role that controls certificate issuance parameters.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PkiRole {
     /// Role name.
@@ -39,10 +68,56 @@ pub struct PkiRole {
     pub max_ttl_hours: u64,
     /// Whether to generate the private key server-side.
     pub generate_key: bool,
-    /// Key type: "rsa" or "ec".
+    /// Key type: "rsa", "ec", "ed25519", or "any" to let the requester choose.
     pub key_type: String,
-    /// Key bits (2048, 4096 for RSA; 256, 384 for EC).
+    /// Key bits (2048, 3072, 4096 for RSA; 256, 384 for EC; 0 for the type's default).
     pub key_bits: u32,
+    /// Whether DNS names may be wildcards (`*.example.com`); they also need
+    /// `allow_subdomains`.
+    #[serde(default)]
+    pub allow_wildcard_certificates: bool,
+    /// Whether IP address SANs may be requested.
+    #[serde(default)]
+    pub allow_ip_sans: bool,
+    /// Glob patterns URI SANs must match (e.g. `spiffe://cluster.local/**`).
+    #[serde(default)]
+    pub allowed_uri_sans: Vec<String>,
+    /// Seconds `not_before` is backdated by, to absorb clock skew.
+    #[serde(default = "default_not_before_secs")]
+    pub not_before_duration_secs: u64,
+}
+
+fn default_not_before_secs() -> u64 {
+    DEFAULT_NOT_BEFORE_SECS
+}
+
+impl PkiRole {
+    /// Whether the role allows a certificate for the DNS name `name`.
+    #[must_use]
+    pub fn allows_dns_name(&self, name: &str) -> bool {
+        dns_name_allowed(self, name)
+    }
+}
+
+/// What to put in a certificate issued by [`PkiEngine::issue`].
+#[derive(Debug, Clone, Default)]
+pub struct IssueRequest {
+    /// Subject common name; also the first DNS SAN.
+    pub common_name: String,
+    /// Additional DNS SANs.
+    pub alt_names: Vec<String>,
+    /// IP address SANs.
+    pub ip_sans: Vec<String>,
+    /// URI SANs.
+    pub uri_sans: Vec<String>,
+    /// TTL in hours, capped at the role's maximum.
+    pub ttl_hours: Option<u64>,
+    /// Key type, for roles whose key type is `any`.
+    pub key_type: Option<String>,
+    /// Key bits, for roles whose key type is `any`.
+    pub key_bits: Option<u32>,
+    /// Seconds to backdate `not_before` by instead of the role's setting.
+    pub not_before_secs: Option<u64>,
 }
 
 /// An issued certificate.
@@ -60,6 +135,116 @@ pub struct IssuedCertificate {
     pub expiration: String,
 }
 
+/// Tombstone recorded when an issued certificate is revoked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokedCertificate {
+    /// Serial number (hex).
+    pub serial_number: String,
+    /// Revocation timestamp (RFC 3339).
+    pub revoked_at: String,
+}
+
+/// Filters and paging for listing issued certificates.
+#[derive(Debug, Clone, Default)]
+pub struct CertListQuery {
+    /// Only certificates whose common name contains this (case-insensitive).
+    pub common_name: Option<String>,
+    /// Only certificates expiring at or after this time.
+    pub expires_after: Option<DateTime<Utc>>,
+    /// Only certificates expiring at or before this time.
+    pub expires_before: Option<DateTime<Utc>>,
+    /// Start after this serial number (the previous page's `next_cursor`).
+    pub after: Option<String>,
+    /// Most certificates to return.
+    pub limit: usize,
+}
+
+/// An issued certificate as listed, without its PEM.
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateSummary {
+    /// Serial number (hex).
+    pub serial_number: String,
+    /// Subject common name, if the certificate has one.
+    pub common_name: Option<String>,
+    /// Expiration timestamp (RFC 3339).
+    pub expiration: String,
+    /// Whether the certificate has been revoked.
+    pub revoked: bool,
+}
+
+/// One page of issued certificates, in serial number order.
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificatePage {
+    /// Certificates on this page.
+    pub certificates: Vec<CertificateSummary>,
+    /// Cursor for the next page; absent on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// What `tidy` prunes.
+#[derive(Debug, Clone)]
+pub struct TidyRequest {
+    /// Delete certificates that expired more than `safety_buffer` ago.
+    pub tidy_cert_store: bool,
+    /// Delete revocation entries of certificates that expired more than
+    /// `safety_buffer` ago, or whose certificate is gone.
+    pub tidy_revoked_certs: bool,
+    /// How long past expiry entries are kept.
+    pub safety_buffer: chrono::Duration,
+}
+
+impl Default for TidyRequest {
+    fn default() -> Self {
+        Self {
+            tidy_cert_store: true,
+            tidy_revoked_certs: true,
+            safety_buffer: chrono::Duration::seconds(DEFAULT_TIDY_SAFETY_BUFFER_SECS),
+        }
+    }
+}
+
+/// What a `tidy` run deleted.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TidyResult {
+    /// Certificates deleted from the store.
+    pub certs_deleted: usize,
+    /// Revocation entries deleted.
+    pub revoked_deleted: usize,
+}
+
+/// A PKCS#10 certificate signing request whose signature has been checked.
+#[derive(Debug, Clone)]
+pub struct CertificateRequest {
+    /// Subject common name, if the request has one.
+    pub common_name: Option<String>,
+    /// DNS names from the requested subjectAltName extension.
+    pub dns_names: Vec<String>,
+    /// IP addresses from the requested subjectAltName extension.
+    pub ip_addresses: Vec<IpAddr>,
+    /// URIs from the requested subjectAltName extension.
+    pub uris: Vec<String>,
+    key: RequestKey,
+}
+
+/// The subject public key of a [`CertificateRequest`].
+#[derive(Debug, Clone)]
+struct RequestKey {
+    spec: KeySpec,
+    /// Determines the algorithm identifier written into the certificate.
+    algorithm: &'static rcgen::SignatureAlgorithm,
+    subject_public_key: Vec<u8>,
+}
+
+impl rcgen::PublicKeyData for RequestKey {
+    fn der_bytes(&self) -> &[u8] {
+        &self.subject_public_key
+    }
+
+    fn algorithm(&self) -> &rcgen::SignatureAlgorithm {
+        self.algorithm
+    }
+}
+
 /// The PKI secrets engine.
 pub struct PkiEngine {
     barrier: Arc<Barrier>,
@@ -93,6 +278,10 @@ impl PkiEngine {
         format!("{}certs/{}", self.prefix, serial)
     }
 
+    fn revoked_key(&self, serial: &str) -> String {
+        format!("{}revoked/{}", self.prefix, serial)
+    }
+
     /// Generate a self-signed root CA.
     ///
     /// # Errors
@@ -179,6 +368,15 @@ impl PkiEngine {
                 reason: "allowed_domains is required".to_owned(),
             });
         }
+        if role.key_type != "any" {
+            KeySpec::parse(&role.key_type, role.key_bits)?;
+        }
+        check_not_before(role.not_before_duration_secs)?;
+        if role.allowed_uri_sans.iter().any(String::is_empty) {
+            return Err(PkiError::InvalidRequest {
+                reason: "allowed_uri_sans patterns must not be empty".to_owned(),
+            });
+        }
         let data = serde_json::to_vec(&role).map_err(|e| PkiError::Internal {
             reason: format!("serialization failed: {e}"),
         })?;
@@ -227,37 +425,88 @@ impl PkiEngine {
             .collect())
     }
 
-    /// Issue a certificate for the given common name using a role.
+    /// Issue a certificate using a role.
+    ///
+    /// The common name and every alternative name must be allowed by the
+    /// role's domains, IP SANs need `allow_ip_sans`, and URI SANs must match
+    /// one of `allowed_uri_sans`.
     ///
     /// # Errors
     ///
     /// Returns `PkiError::NoRootCa` if no CA exists.
     /// Returns `PkiError::RoleNotFound` if the role does not exist.
-    /// Returns `PkiError::InvalidRequest` if the domain is not allowed.
+    /// Returns `PkiError::InvalidRequest` if a name, key type, or backdating
+    /// is not allowed.
     pub async fn issue(
         &self,
         role_name: &str,
-        common_name: &str,
+        request: &IssueRequest,
+    ) -> Result<IssuedCertificate, PkiError> {
+        let ca = self.get_ca().await?;
+        let role = self.get_role(role_name).await?;
+
+        let (params, expires) = leaf_params(&role, request)?;
+        let key_spec = key_spec(&role, request)?;
+        let leaf_key = key_spec.generate().await?;
+        let private_key_pem = role.generate_key.then(|| leaf_key.serialize_pem());
+        self.sign_leaf(&ca, params, expires, &leaf_key, private_key_pem)
+            .await
+    }
+
+    /// Issue a certificate for the key and names of a signing request.
+    ///
+    /// The names are checked against the role as in [`Self::issue`]; the
+    /// subject is the request's common name, or its first DNS name. The key
+    /// must match the role's key type unless the role allows `any`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PkiError::NoRootCa` if no CA exists.
+    /// Returns `PkiError::RoleNotFound` if the role does not exist.
+    /// Returns `PkiError::InvalidRequest` if a name or the key is not allowed.
+    pub async fn sign_csr(
+        &self,
+        role_name: &str,
+        csr: &CertificateRequest,
         ttl_hours: Option<u64>,
     ) -> Result<IssuedCertificate, PkiError> {
         let ca = self.get_ca().await?;
         let role = self.get_role(role_name).await?;
 
-        // Validate domain against allowed_domains.
-        let domain_allowed = role.allowed_domains.iter().any(|d| {
-            common_name == d.as_str()
-                || (role.allow_subdomains && common_name.ends_with(&format!(".{d}")))
-        });
-        if !domain_allowed {
+        if role.key_type != "any" && KeySpec::parse(&role.key_type, role.key_bits)? != csr.key.spec
+        {
             return Err(PkiError::InvalidRequest {
-                reason: format!("domain '{common_name}' not allowed by role '{role_name}'"),
+                reason: format!(
+                    "role '{}' only signs {} {} keys",
+                    role.name, role.key_type, role.key_bits
+                ),
             });
         }
+        let request = IssueRequest {
+            common_name: csr
+                .common_name
+                .clone()
+                .or_else(|| csr.dns_names.first().cloned())
+                .unwrap_or_default(),
+            alt_names: csr.dns_names.clone(),
+            ip_sans: csr.ip_addresses.iter().map(ToString::to_string).collect(),
+            uri_sans: csr.uris.clone(),
+            ttl_hours,
+            ..IssueRequest::default()
+        };
+        let (params, expires) = leaf_params(&role, &request)?;
+        self.sign_leaf(&ca, params, expires, &csr.key, None).await
+    }
 
-        let effective_ttl = ttl_hours
-            .unwrap_or(role.max_ttl_hours)
-            .min(role.max_ttl_hours);
-
+    /// Sign `params` for `public_key` with the CA and record the certificate.
+    async fn sign_leaf(
+        &self,
+        ca: &CaData,
+        params: rcgen::CertificateParams,
+        expires: DateTime<Utc>,
+        public_key: &impl rcgen::PublicKeyData,
+        private_key_pem: Option<String>,
+    ) -> Result<IssuedCertificate, PkiError> {
         // Parse CA key pair.
         let ca_key_pair = rcgen::KeyPair::from_pem(&ca.private_key_pem).map_err(|e| {
             PkiError::CertGeneration {
@@ -277,38 +526,18 @@ impl PkiEngine {
                     reason: format!("failed to reconstruct CA cert: {e}"),
                 })?;
 
-        // Generate leaf certificate.
-        let leaf_params =
-            rcgen::CertificateParams::new(vec![common_name.to_owned()]).map_err(|e| {
-                PkiError::CertGeneration {
-                    reason: format!("failed to create leaf params: {e}"),
-                }
-            })?;
-
-        let leaf_key = rcgen::KeyPair::generate().map_err(|e| PkiError::CertGeneration {
-            reason: format!("leaf key generation failed: {e}"),
-        })?;
-
-        let leaf_cert = leaf_params
-            .signed_by(&leaf_key, &ca_cert, &ca_key_pair)
+        let leaf_cert = params
+            .signed_by(public_key, &ca_cert, &ca_key_pair)
             .map_err(|e| PkiError::CertGeneration {
                 reason: format!("certificate signing failed: {e}"),
             })?;
 
         let serial = uuid::Uuid::new_v4().to_string().replace('-', "");
-        let effective_ttl_i64 = i64::try_from(effective_ttl).unwrap_or(i64::MAX);
-        let expiration = chrono::Utc::now()
-            .checked_add_signed(chrono::Duration::hours(effective_ttl_i64))
-            .map(|t| t.to_rfc3339())
-            .unwrap_or_default();
+        let expiration = expires.to_rfc3339();
 
         let issued = IssuedCertificate {
             certificate_pem: leaf_cert.pem(),
-            private_key_pem: if role.generate_key {
-                Some(leaf_key.serialize_pem())
-            } else {
-                None
-            },
+            private_key_pem,
             ca_chain_pem: ca.certificate_pem.clone(),
             serial_number: serial.clone(),
             expiration,
@@ -338,4 +567,1227 @@ impl PkiEngine {
             .filter_map(|k| k.strip_prefix(&prefix).map(String::from))
             .collect())
     }
+
+    /// Revoke an issued certificate by writing a tombstone for its serial.
+    ///
+    /// Revoking an already-revoked certificate keeps the original timestamp.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PkiError::InvalidRequest` if no certificate with this serial was issued.
+    /// Returns `PkiError::Barrier` if the barrier is sealed.
+    pub async fn revoke_cert(&self, serial: &str) -> Result<RevokedCertificate, PkiError> {
+        if let Some(existing) = self.barrier.get(&self.revoked_key(serial)).await? {
+            return serde_json::from_slice(&existing).map_err(|e| PkiError::Internal {
+                reason: format!("deserialization failed: {e}"),
+            });
+        }
+        if self.barrier.get(&self.cert_key(serial)).await?.is_none() {
+            return Err(PkiError::InvalidRequest {
+                reason: format!("unknown certificate serial '{serial}'"),
+            });
+        }
+
+        let tombstone = RevokedCertificate {
+            serial_number: serial.to_owned(),
+            revoked_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let data = serde_json::to_vec(&tombstone).map_err(|e| PkiError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(&self.revoked_key(serial), &data).await?;
+
+        Ok(tombstone)
+    }
+
+    /// List revoked certificate serial numbers.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PkiError::Barrier` if the barrier is sealed.
+    pub async fn list_revoked(&self) -> Result<Vec<String>, PkiError> {
+        let prefix = format!("{}revoked/", self.prefix);
+        let keys = self.barrier.list(&prefix).await?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|k| k.strip_prefix(&prefix).map(String::from))
+            .collect())
+    }
+
+    /// List issued certificates a page at a time, optionally filtered by
+    /// common name and expiry window.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PkiError::Barrier` if the barrier is sealed.
+    pub async fn list_cert_page(&self, query: &CertListQuery) -> Result<CertificatePage, PkiError> {
+        let mut serials = self.list_certs().await?;
+        serials.sort_unstable();
+        let revoked: HashSet<String> = self.list_revoked().await?.into_iter().collect();
+        let common_name = query.common_name.as_deref().map(str::to_lowercase);
+        let limit = query.limit.max(1);
+
+        let start = query
+            .after
+            .as_deref()
+            .map_or(0, |after| serials.partition_point(|s| s.as_str() <= after));
+        let mut certificates = Vec::new();
+        let mut next_cursor = None;
+        for (index, serial) in serials.iter().enumerate().skip(start) {
+            let Some(cert) = self.load_cert(serial).await? else {
+                continue;
+            };
+            let expires = DateTime::parse_from_rfc3339(&cert.expiration)
+                .ok()
+                .map(|at| at.with_timezone(&Utc));
+            if query
+                .expires_after
+                .is_some_and(|after| expires.is_none_or(|e| e < after))
+                || query
+                    .expires_before
+                    .is_some_and(|before| expires.is_none_or(|e| e > before))
+            {
+                continue;
+            }
+            let cn = certificate_common_name(&cert.certificate_pem);
+            if let Some(wanted) = &common_name
+                && !cn
+                    .as_deref()
+                    .is_some_and(|cn| cn.to_lowercase().contains(wanted))
+            {
+                continue;
+            }
+            certificates.push(CertificateSummary {
+                revoked: revoked.contains(serial),
+                serial_number: cert.serial_number,
+                common_name: cn,
+                expiration: cert.expiration,
+            });
+            if certificates.len() == limit {
+                if index + 1 < serials.len() {
+                    next_cursor = Some(serial.clone());
+                }
+                break;
+            }
+        }
+        Ok(CertificatePage {
+            certificates,
+            next_cursor,
+        })
+    }
+
+    /// Delete certificates and revocation entries that are past expiry by
+    /// more than the safety buffer.
+    ///
+    /// A revocation entry whose certificate is already gone is deleted once
+    /// the revocation itself is older than the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PkiError::Barrier` if the barrier is sealed.
+    pub async fn tidy(&self, request: &TidyRequest) -> Result<TidyResult, PkiError> {
+        let cutoff = Utc::now() - request.safety_buffer;
+        let past_cutoff = |at: &str| {
+            DateTime::parse_from_rfc3339(at).is_ok_and(|at| at.with_timezone(&Utc) < cutoff)
+        };
+        let mut result = TidyResult::default();
+
+        if request.tidy_revoked_certs {
+            for serial in self.list_revoked().await? {
+                let expired = match self.load_cert(&serial).await? {
+                    Some(cert) => past_cutoff(&cert.expiration),
+                    None => self
+                        .barrier
+                        .get(&self.revoked_key(&serial))
+                        .await?
+                        .and_then(|data| serde_json::from_slice::<RevokedCertificate>(&data).ok())
+                        .is_some_and(|tombstone| past_cutoff(&tombstone.revoked_at)),
+                };
+                if expired {
+                    self.barrier.delete(&self.revoked_key(&serial)).await?;
+                    result.revoked_deleted += 1;
+                }
+            }
+        }
+
+        if request.tidy_cert_store {
+            for serial in self.list_certs().await? {
+                let expired = self
+                    .load_cert(&serial)
+                    .await?
+                    .is_some_and(|cert| past_cutoff(&cert.expiration));
+                if expired {
+                    self.barrier.delete(&self.cert_key(&serial)).await?;
+                    result.certs_deleted += 1;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// The stored record of an issued certificate; `None` if it is missing
+    /// or unreadable.
+    async fn load_cert(&self, serial: &str) -> Result<Option<IssuedCertificate>, PkiError> {
+        let data = self.barrier.get(&self.cert_key(serial)).await?;
+        Ok(data.and_then(|data| serde_json::from_slice(&data).ok()))
+    }
+}
+
+/// A key algorithm and size leaf certificates can be issued with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeySpec {
+    Rsa(usize),
+    Ec(&'static rcgen::SignatureAlgorithm),
+    Ed25519,
+}
+
+impl KeySpec {
+    /// Parse a key type and size; `0` bits picks the type's default.
+    fn parse(key_type: &str, key_bits: u32) -> Result<Self, PkiError> {
+        match (key_type, key_bits) {
+            ("rsa", 0 | 2048) => Ok(Self::Rsa(2048)),
+            ("rsa", 3072) => Ok(Self::Rsa(3072)),
+            ("rsa", 4096) => Ok(Self::Rsa(4096)),
+            ("ec", 0 | 256) => Ok(Self::Ec(&rcgen::PKCS_ECDSA_P256_SHA256)),
+            ("ec", 384) => Ok(Self::Ec(&rcgen::PKCS_ECDSA_P384_SHA384)),
+            ("ed25519", 0 | 256) => Ok(Self::Ed25519),
+            ("rsa" | "ec" | "ed25519", bits) => Err(PkiError::InvalidRequest {
+                reason: format!("unsupported key_bits {bits} for key_type '{key_type}'"),
+            }),
+            _ => Err(PkiError::InvalidRequest {
+                reason: format!("unknown key_type '{key_type}', expected rsa, ec, or ed25519"),
+            }),
+        }
+    }
+
+    /// Generate a key pair of this kind.
+    async fn generate(self) -> Result<rcgen::KeyPair, PkiError> {
+        let failed = |reason: String| PkiError::CertGeneration {
+            reason: format!("leaf key generation failed: {reason}"),
+        };
+        match self {
+            Self::Ec(alg) => rcgen::KeyPair::generate_for(alg).map_err(|e| failed(e.to_string())),
+            Self::Ed25519 => rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519)
+                .map_err(|e| failed(e.to_string())),
+            Self::Rsa(bits) => {
+                // RSA key generation is CPU-bound and takes a while; keep it
+                // off the async workers.
+                let pem = tokio::task::spawn_blocking(move || {
+                    let key = rsa::RsaPrivateKey::new(&mut rsa::rand_core::OsRng, bits)
+                        .map_err(|e| e.to_string())?;
+                    key.to_pkcs8_pem(LineEnding::LF).map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| failed(e.to_string()))?
+                .map_err(failed)?;
+                rcgen::KeyPair::from_pem_and_sign_algo(&pem, &rcgen::PKCS_RSA_SHA256)
+                    .map_err(|e| failed(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Certificate parameters for `request` under `role`, and when it expires.
+fn leaf_params(
+    role: &PkiRole,
+    request: &IssueRequest,
+) -> Result<(rcgen::CertificateParams, DateTime<Utc>), PkiError> {
+    let subject_alt_names = subject_alt_names(role, request)?;
+    let not_before_secs = request
+        .not_before_secs
+        .unwrap_or(role.not_before_duration_secs);
+    check_not_before(not_before_secs)?;
+
+    let effective_ttl = request
+        .ttl_hours
+        .unwrap_or(role.max_ttl_hours)
+        .min(role.max_ttl_hours);
+    let now = Utc::now();
+    let not_before = now - chrono::Duration::seconds(i64::try_from(not_before_secs).unwrap_or(0));
+    let expires = now
+        .checked_add_signed(chrono::Duration::hours(
+            i64::try_from(effective_ttl).unwrap_or(i64::MAX),
+        ))
+        .ok_or_else(|| PkiError::InvalidRequest {
+            reason: format!("ttl of {effective_ttl} hours is out of range"),
+        })?;
+
+    let mut params = rcgen::CertificateParams::default();
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, request.common_name.as_str());
+    params.subject_alt_names = subject_alt_names;
+    params.not_before = offset_date_time(not_before)?;
+    params.not_after = offset_date_time(expires)?;
+    Ok((params, expires))
+}
+
+/// The key to issue with: the role's, or the request's if the role allows
+/// `any`.
+fn key_spec(role: &PkiRole, request: &IssueRequest) -> Result<KeySpec, PkiError> {
+    if role.key_type == "any" {
+        return KeySpec::parse(
+            request.key_type.as_deref().unwrap_or("ec"),
+            request.key_bits.unwrap_or(0),
+        );
+    }
+    let differs = request
+        .key_type
+        .as_ref()
+        .is_some_and(|t| *t != role.key_type)
+        || request.key_bits.is_some_and(|b| b != role.key_bits);
+    if differs {
+        return Err(PkiError::InvalidRequest {
+            reason: format!(
+                "role '{}' issues {} {} keys; set its key_type to 'any' to choose",
+                role.name, role.key_type, role.key_bits
+            ),
+        });
+    }
+    KeySpec::parse(&role.key_type, role.key_bits)
+}
+
+/// The DNS, IP, and URI SANs of `request`, each checked against `role`.
+fn subject_alt_names(
+    role: &PkiRole,
+    request: &IssueRequest,
+) -> Result<Vec<rcgen::SanType>, PkiError> {
+    let invalid = |reason: String| PkiError::InvalidRequest { reason };
+    if request.common_name.is_empty() {
+        return Err(invalid("common_name is required".to_owned()));
+    }
+
+    let mut sans = Vec::new();
+    let mut dns_names: Vec<&str> = Vec::new();
+    for name in std::iter::once(&request.common_name).chain(&request.alt_names) {
+        if dns_names.contains(&name.as_str()) {
+            continue;
+        }
+        if !dns_name_allowed(role, name) {
+            return Err(invalid(format!(
+                "domain '{name}' not allowed by role '{}'",
+                role.name
+            )));
+        }
+        let dns = name
+            .as_str()
+            .try_into()
+            .map_err(|e| invalid(format!("invalid DNS name '{name}': {e}")))?;
+        sans.push(rcgen::SanType::DnsName(dns));
+        dns_names.push(name);
+    }
+
+    for ip in &request.ip_sans {
+        if !role.allow_ip_sans {
+            return Err(invalid(format!(
+                "role '{}' does not allow IP SANs",
+                role.name
+            )));
+        }
+        let addr = ip
+            .parse()
+            .map_err(|_| invalid(format!("invalid IP address '{ip}'")))?;
+        sans.push(rcgen::SanType::IpAddress(addr));
+    }
+
+    for uri in &request.uri_sans {
+        let allowed = role
+            .allowed_uri_sans
+            .iter()
+            .any(|pattern| glob_match::glob_match(pattern, uri));
+        if !allowed {
+            return Err(invalid(format!(
+                "URI SAN '{uri}' not allowed by role '{}'",
+                role.name
+            )));
+        }
+        let value = uri
+            .as_str()
+            .try_into()
+            .map_err(|e| invalid(format!("invalid URI '{uri}': {e}")))?;
+        sans.push(rcgen::SanType::URI(value));
+    }
+
+    Ok(sans)
+}
+
+/// Whether `role` allows a certificate for the DNS name `name`.
+///
+/// A wildcard (`*.example.com`) needs `allow_wildcard_certificates` and
+/// `allow_subdomains`, since it covers every subdomain of its base.
+fn dns_name_allowed(role: &PkiRole, name: &str) -> bool {
+    let (base, wildcard) = match name.strip_prefix("*.") {
+        Some(base) => (base, true),
+        None => (name, false),
+    };
+    if base.contains('*')
+        || (wildcard && !(role.allow_wildcard_certificates && role.allow_subdomains))
+    {
+        return false;
+    }
+    role.allowed_domains
+        .iter()
+        .any(|d| base == d.as_str() || (role.allow_subdomains && base.ends_with(&format!(".{d}"))))
+}
+
+fn check_not_before(secs: u64) -> Result<(), PkiError> {
+    if secs > MAX_NOT_BEFORE_SECS {
+        return Err(PkiError::InvalidRequest {
+            reason: format!("not_before backdating is limited to {MAX_NOT_BEFORE_SECS} seconds"),
+        });
+    }
+    Ok(())
+}
+
+fn offset_date_time(at: DateTime<Utc>) -> Result<time::OffsetDateTime, PkiError> {
+    time::OffsetDateTime::from_unix_timestamp(at.timestamp()).map_err(|e| {
+        PkiError::InvalidRequest {
+            reason: format!("validity date out of range: {e}"),
+        }
+    })
+}
+
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_EXTENSION_REQUEST: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x0E];
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1D, 0x11];
+const OID_RSA_ENCRYPTION: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01];
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+const OID_P256: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+const OID_P384: &[u8] = &[0x2B, 0x81, 0x04, 0x00, 0x22];
+const OID_ED25519: &[u8] = &[0x2B, 0x65, 0x70];
+const OID_SHA256_WITH_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0B];
+const OID_SHA384_WITH_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0C];
+const OID_SHA512_WITH_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0D];
+const OID_ECDSA_SHA256: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02];
+const OID_ECDSA_SHA384: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x03];
+
+impl CertificateRequest {
+    /// Parse a DER-encoded PKCS#10 request and verify its self-signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PkiError::InvalidRequest` if the request is malformed, uses
+    /// an unsupported key or signature algorithm, asks for an unsupported
+    /// kind of name, or its signature does not verify.
+    pub fn from_der(der: &[u8]) -> Result<Self, PkiError> {
+        let invalid = |reason: &str| PkiError::InvalidRequest {
+            reason: format!("invalid CSR: {reason}"),
+        };
+        let malformed = || invalid("malformed DER");
+
+        let mut outer = Der(der);
+        let mut request = Der(outer.read(0x30).ok_or_else(malformed)?);
+        // The signature covers the whole encoded `certificationRequestInfo`.
+        let before = request.0;
+        let mut info = Der(request.read(0x30).ok_or_else(malformed)?);
+        let signed = &before[..before.len() - request.0.len()];
+        let mut signature_algorithm = Der(request.read(0x30).ok_or_else(malformed)?);
+        let signature_oid = signature_algorithm.read(0x06).ok_or_else(malformed)?;
+        let signature = bit_string(request.read(0x03)).ok_or_else(malformed)?;
+
+        info.read(0x02).ok_or_else(malformed)?; // version
+        let subject = info.read(0x30).ok_or_else(malformed)?;
+        let spki = info.read(0x30).ok_or_else(malformed)?;
+        let attributes = info.read(0xA0).unwrap_or_default();
+
+        let (key, verification) = request_key(spki, signature_oid)?;
+        if !verification.verify(&key.subject_public_key, signed, signature) {
+            return Err(invalid("signature does not verify"));
+        }
+
+        let mut csr = Self {
+            common_name: common_name(subject).map_err(|()| malformed())?,
+            dns_names: Vec::new(),
+            ip_addresses: Vec::new(),
+            uris: Vec::new(),
+            key,
+        };
+        csr.read_alt_names(attributes)?;
+        Ok(csr)
+    }
+
+    /// Fill in the names of the subjectAltName extension in `attributes`.
+    fn read_alt_names(&mut self, attributes: &[u8]) -> Result<(), PkiError> {
+        let invalid = |reason: &str| PkiError::InvalidRequest {
+            reason: format!("invalid CSR: {reason}"),
+        };
+        let malformed = || invalid("malformed extension request");
+
+        let mut attributes = Der(attributes);
+        while let Some(attribute) = attributes.read(0x30) {
+            let mut attribute = Der(attribute);
+            if attribute.read(0x06) != Some(OID_EXTENSION_REQUEST) {
+                continue;
+            }
+            let mut values = Der(attribute.read(0x31).ok_or_else(malformed)?);
+            let mut extensions = Der(values.read(0x30).ok_or_else(malformed)?);
+            while let Some(extension) = extensions.read(0x30) {
+                let mut extension = Der(extension);
+                if extension.read(0x06) != Some(OID_SUBJECT_ALT_NAME) {
+                    continue;
+                }
+                let mut value = extension.next().ok_or_else(malformed)?;
+                if value.0 == 0x01 {
+                    value = extension.next().ok_or_else(malformed)?; // critical
+                }
+                if value.0 != 0x04 {
+                    return Err(malformed());
+                }
+                let mut names = Der(Der(value.1).read(0x30).ok_or_else(malformed)?);
+                while let Some((tag, name)) = names.next() {
+                    let text = || {
+                        std::str::from_utf8(name)
+                            .map(str::to_owned)
+                            .map_err(|_| malformed())
+                    };
+                    match tag {
+                        0x82 => self.dns_names.push(text()?),
+                        0x86 => self.uris.push(text()?),
+                        0x87 => self.ip_addresses.push(match name.len() {
+                            4 => IpAddr::from(<[u8; 4]>::try_from(name).map_err(|_| malformed())?),
+                            16 => {
+                                IpAddr::from(<[u8; 16]>::try_from(name).map_err(|_| malformed())?)
+                            }
+                            _ => return Err(malformed()),
+                        }),
+                        _ => return Err(invalid("only DNS, IP, and URI names are supported")),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The contents of a DER bit string without unused bits.
+fn bit_string(contents: Option<&[u8]>) -> Option<&[u8]> {
+    contents?.strip_prefix(&[0])
+}
+
+/// The first common name in a DER `Name`.
+fn common_name(subject: &[u8]) -> Result<Option<String>, ()> {
+    let mut rdns = Der(subject);
+    while let Some(rdn) = rdns.read(0x31) {
+        let mut attributes = Der(rdn);
+        while let Some(attribute) = attributes.read(0x30) {
+            let mut attribute = Der(attribute);
+            if attribute.read(0x06) == Some(OID_COMMON_NAME) {
+                let (_, value) = attribute.next().ok_or(())?;
+                return std::str::from_utf8(value)
+                    .map(|v| Some(v.to_owned()))
+                    .map_err(|_| ());
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The subject common name of a PEM certificate.
+fn certificate_common_name(pem: &str) -> Option<String> {
+    let body: String = pem
+        .lines()
+        .skip_while(|l| !l.starts_with("-----BEGIN CERTIFICATE"))
+        .skip(1)
+        .take_while(|l| !l.starts_with("-----END"))
+        .collect();
+    let der = BASE64.decode(body.trim()).ok()?;
+    let mut outer = Der(&der);
+    let mut certificate = Der(outer.read(0x30)?);
+    let mut tbs = Der(certificate.read(0x30)?);
+    if tbs.0.first() == Some(&0xA0) {
+        tbs.next()?; // version
+    }
+    for _ in 0..4 {
+        tbs.next()?; // serial, signature, issuer, validity
+    }
+    common_name(tbs.read(0x30)?).ok().flatten()
+}
+
+/// How a CSR's self-signature is checked: key type, curve, and digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsrVerification {
+    RsaSha256,
+    RsaSha384,
+    RsaSha512,
+    P256Sha256,
+    P256Sha384,
+    P384Sha256,
+    P384Sha384,
+    Ed25519,
+}
+
+impl CsrVerification {
+    /// Whether `signature` over `message` verifies under `public_key`, the
+    /// contents of the CSR's `subjectPublicKey` bit string.
+    fn verify(self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        use p256::ecdsa::signature::Verifier as _;
+        use p256::ecdsa::signature::hazmat::PrehashVerifier as _;
+        use rsa::pkcs1::DecodeRsaPublicKey as _;
+        use sha2::{Digest, Sha256, Sha384, Sha512};
+
+        fn rsa<D>(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool
+        where
+            D: Digest + rsa::pkcs1::der::oid::AssociatedOid,
+        {
+            let Ok(key) = rsa::RsaPublicKey::from_pkcs1_der(public_key) else {
+                return false;
+            };
+            let Ok(signature) = rsa::pkcs1v15::Signature::try_from(signature) else {
+                return false;
+            };
+            rsa::pkcs1v15::VerifyingKey::<D>::new(key)
+                .verify(message, &signature)
+                .is_ok()
+        }
+
+        match self {
+            Self::RsaSha256 => rsa::<Sha256>(public_key, message, signature),
+            Self::RsaSha384 => rsa::<Sha384>(public_key, message, signature),
+            Self::RsaSha512 => rsa::<Sha512>(public_key, message, signature),
+            Self::P256Sha256 | Self::P256Sha384 => {
+                let (Ok(key), Ok(signature)) = (
+                    p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key),
+                    p256::ecdsa::Signature::from_der(signature),
+                ) else {
+                    return false;
+                };
+                let digest = if self == Self::P256Sha256 {
+                    Sha256::digest(message).to_vec()
+                } else {
+                    Sha384::digest(message).to_vec()
+                };
+                key.verify_prehash(&digest, &signature).is_ok()
+            }
+            Self::P384Sha256 | Self::P384Sha384 => {
+                let (Ok(key), Ok(signature)) = (
+                    p384::ecdsa::VerifyingKey::from_sec1_bytes(public_key),
+                    p384::ecdsa::Signature::from_der(signature),
+                ) else {
+                    return false;
+                };
+                let digest = if self == Self::P384Sha256 {
+                    Sha256::digest(message).to_vec()
+                } else {
+                    Sha384::digest(message).to_vec()
+                };
+                key.verify_prehash(&digest, &signature).is_ok()
+            }
+            Self::Ed25519 => {
+                let (Ok(key), Ok(signature)) = (
+                    <[u8; 32]>::try_from(public_key),
+                    ed25519_dalek::Signature::from_slice(signature),
+                ) else {
+                    return false;
+                };
+                ed25519_dalek::VerifyingKey::from_bytes(&key)
+                    .is_ok_and(|key| key.verify(message, &signature).is_ok())
+            }
+        }
+    }
+}
+
+/// The key in a CSR's `subjectPublicKeyInfo`, and how to check the CSR's
+/// signature.
+fn request_key(
+    spki: &[u8],
+    signature_oid: &[u8],
+) -> Result<(RequestKey, CsrVerification), PkiError> {
+    let invalid = |reason: &str| PkiError::InvalidRequest {
+        reason: format!("invalid CSR: {reason}"),
+    };
+    let malformed = || invalid("malformed public key");
+
+    let mut spki = Der(spki);
+    let mut algorithm = Der(spki.read(0x30).ok_or_else(malformed)?);
+    let key_oid = algorithm.read(0x06).ok_or_else(malformed)?;
+    let parameter = algorithm.next();
+    let subject_public_key = bit_string(spki.read(0x03)).ok_or_else(malformed)?.to_vec();
+
+    let (spec, algorithm): (KeySpec, &'static rcgen::SignatureAlgorithm) = match key_oid {
+        OID_RSA_ENCRYPTION => {
+            let mut rsa_key = Der(Der(&subject_public_key).read(0x30).ok_or_else(malformed)?);
+            let modulus = rsa_key.read(0x02).ok_or_else(malformed)?;
+            let modulus = &modulus[modulus.iter().take_while(|&&b| b == 0).count()..];
+            let bits = modulus.first().map_or(0, |&b| {
+                modulus.len() * 8 - usize::try_from(b.leading_zeros()).unwrap_or(0)
+            });
+            let bits = u32::try_from(bits).unwrap_or(u32::MAX);
+            (KeySpec::parse("rsa", bits)?, &rcgen::PKCS_RSA_SHA256)
+        }
+        OID_EC_PUBLIC_KEY => match parameter {
+            Some((0x06, OID_P256)) => (
+                KeySpec::Ec(&rcgen::PKCS_ECDSA_P256_SHA256),
+                &rcgen::PKCS_ECDSA_P256_SHA256,
+            ),
+            Some((0x06, OID_P384)) => (
+                KeySpec::Ec(&rcgen::PKCS_ECDSA_P384_SHA384),
+                &rcgen::PKCS_ECDSA_P384_SHA384,
+            ),
+            _ => return Err(invalid("only P-256 and P-384 EC keys are supported")),
+        },
+        OID_ED25519 => (KeySpec::Ed25519, &rcgen::PKCS_ED25519),
+        _ => return Err(invalid("unsupported public key algorithm")),
+    };
+
+    let p384 = spec == KeySpec::Ec(&rcgen::PKCS_ECDSA_P384_SHA384);
+    let verification = match (spec, signature_oid) {
+        (KeySpec::Rsa(_), OID_SHA256_WITH_RSA) => CsrVerification::RsaSha256,
+        (KeySpec::Rsa(_), OID_SHA384_WITH_RSA) => CsrVerification::RsaSha384,
+        (KeySpec::Rsa(_), OID_SHA512_WITH_RSA) => CsrVerification::RsaSha512,
+        (KeySpec::Ec(_), OID_ECDSA_SHA256) if p384 => CsrVerification::P384Sha256,
+        (KeySpec::Ec(_), OID_ECDSA_SHA256) => CsrVerification::P256Sha256,
+        (KeySpec::Ec(_), OID_ECDSA_SHA384) if p384 => CsrVerification::P384Sha384,
+        (KeySpec::Ec(_), OID_ECDSA_SHA384) => CsrVerification::P256Sha384,
+        (KeySpec::Ed25519, OID_ED25519) => CsrVerification::Ed25519,
+        _ => return Err(invalid("unsupported signature algorithm")),
+    };
+
+    Ok((
+        RequestKey {
+            spec,
+            algorithm,
+            subject_public_key,
+        },
+        verification,
+    ))
+}
+
+/// Issue a server certificate for `hostnames` from a freshly generated root
+/// CA, without touching the barrier.
+///
+/// Used to bootstrap the server's own TLS certificate on first start, before
+/// the vault can be unsealed. Entries that parse as IP addresses become IP
+/// SANs; the first hostname is the subject. The CA is returned in
+/// `ca_chain_pem` so clients can trust it; its key is discarded.
+///
+/// # Errors
+///
+/// Returns `PkiError::InvalidRequest` if `hostnames` is empty or invalid.
+/// Returns `PkiError::CertGeneration` if certificate generation fails.
+pub fn bootstrap_certificate(
+    hostnames: &[String],
+    ttl_hours: u64,
+) -> Result<IssuedCertificate, PkiError> {
+    let Some(subject) = hostnames.first() else {
+        return Err(PkiError::InvalidRequest {
+            reason: "at least one hostname is required".to_owned(),
+        });
+    };
+    let generation = |e: rcgen::Error| PkiError::CertGeneration {
+        reason: e.to_string(),
+    };
+    let expires = chrono::Utc::now()
+        .checked_add_signed(chrono::Duration::hours(
+            i64::try_from(ttl_hours).unwrap_or(i64::MAX),
+        ))
+        .ok_or_else(|| PkiError::InvalidRequest {
+            reason: format!("ttl of {ttl_hours} hours is out of range"),
+        })?;
+    let not_after = rcgen::date_time_ymd(
+        expires.year(),
+        u8::try_from(expires.month()).unwrap_or(12),
+        u8::try_from(expires.day()).unwrap_or(28),
+    );
+
+    let mut ca_params = rcgen::CertificateParams::new(Vec::<String>::new()).map_err(generation)?;
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Constrained(0));
+    ca_params.key_usages = vec![
+        rcgen::KeyUsagePurpose::KeyCertSign,
+        rcgen::KeyUsagePurpose::CrlSign,
+    ];
+    ca_params.distinguished_name = rcgen::DistinguishedName::new();
+    ca_params.distinguished_name.push(
+        rcgen::DnType::CommonName,
+        format!("ZVault bootstrap CA ({subject})"),
+    );
+    ca_params.not_after = not_after;
+    let ca_key = rcgen::KeyPair::generate().map_err(generation)?;
+    let ca_cert = ca_params.self_signed(&ca_key).map_err(generation)?;
+
+    let mut leaf_params = rcgen::CertificateParams::new(hostnames.to_vec()).map_err(|e| {
+        PkiError::InvalidRequest {
+            reason: format!("invalid hostname: {e}"),
+        }
+    })?;
+    leaf_params.distinguished_name = rcgen::DistinguishedName::new();
+    leaf_params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, subject.as_str());
+    leaf_params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ServerAuth];
+    leaf_params.not_after = not_after;
+    let leaf_key = rcgen::KeyPair::generate().map_err(generation)?;
+    let leaf_cert = leaf_params
+        .signed_by(&leaf_key, &ca_cert, &ca_key)
+        .map_err(generation)?;
+
+    Ok(IssuedCertificate {
+        certificate_pem: leaf_cert.pem(),
+        private_key_pem: Some(leaf_key.serialize_pem()),
+        ca_chain_pem: ca_cert.pem(),
+        serial_number: uuid::Uuid::new_v4().to_string().replace('-', ""),
+        expiration: expires.to_rfc3339(),
+    })
+}
+
+impl Engine for PkiEngine {
+    fn engine_type(&self) -> &'static str {
+        "pki"
+    }
+
+    fn storage_prefix(&self) -> String {
+        self.prefix.clone()
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl RevocationHandler for PkiEngine {
+    async fn revoke_lease(&self, lease: &Lease) -> Result<(), LeaseError> {
+        let failed = |reason: String| LeaseError::RevocationFailed {
+            lease_id: lease.id.clone(),
+            reason,
+        };
+
+        let serial = lease
+            .data
+            .get("serial_number")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| failed("lease data has no serial_number".to_owned()))?;
+
+        self.revoke_cert(serial)
+            .await
+            .map(|_| ())
+            .map_err(|e| failed(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::acme::Der;
+    use crate::crypto::EncryptionKey;
+
+    async fn make_engine() -> PkiEngine {
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Arc::new(Barrier::new(storage));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let engine = PkiEngine::new(barrier, "pki/".to_owned());
+        engine.generate_root("Test CA", 24).await.unwrap();
+        engine
+    }
+
+    fn role(name: &str) -> PkiRole {
+        PkiRole {
+            name: name.to_owned(),
+            allowed_domains: vec!["example.com".to_owned()],
+            allow_subdomains: true,
+            max_ttl_hours: 24,
+            generate_key: true,
+            key_type: "ec".to_owned(),
+            key_bits: 256,
+            allow_wildcard_certificates: false,
+            allow_ip_sans: false,
+            allowed_uri_sans: Vec::new(),
+            not_before_duration_secs: DEFAULT_NOT_BEFORE_SECS,
+        }
+    }
+
+    fn request(common_name: &str) -> IssueRequest {
+        IssueRequest {
+            common_name: common_name.to_owned(),
+            ..IssueRequest::default()
+        }
+    }
+
+    fn der(pem: &str) -> Vec<u8> {
+        let body: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+        BASE64.decode(body).unwrap()
+    }
+
+    /// `notBefore` and `notAfter` of a certificate, as DER time strings.
+    fn validity(pem: &str) -> (String, String) {
+        let der = der(pem);
+        let mut outer = Der(&der);
+        let mut certificate = Der(outer.read(0x30).unwrap());
+        let mut tbs = Der(certificate.read(0x30).unwrap());
+        tbs.read(0xA0).unwrap();
+        for _ in 0..3 {
+            tbs.next().unwrap();
+        }
+        let mut validity = Der(tbs.read(0x30).unwrap());
+        let mut time = || String::from_utf8(validity.next().unwrap().1.to_vec()).unwrap();
+        (time(), time())
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[tokio::test]
+    async fn issue_includes_dns_ip_and_uri_sans() {
+        let engine = make_engine().await;
+        let mut web = role("web");
+        web.allow_ip_sans = true;
+        web.allowed_uri_sans = vec!["spiffe://cluster.local/**".to_owned()];
+        engine.create_role(web).await.unwrap();
+
+        let issued = engine
+            .issue(
+                "web",
+                &IssueRequest {
+                    alt_names: vec!["www.example.com".to_owned(), "api.example.com".to_owned()],
+                    ip_sans: vec!["10.0.0.1".to_owned()],
+                    uri_sans: vec!["spiffe://cluster.local/ns/default/sa/web".to_owned()],
+                    ..request("api.example.com")
+                },
+            )
+            .await
+            .unwrap();
+
+        let der = der(&issued.certificate_pem);
+        assert!(contains(&der, b"api.example.com"));
+        assert!(contains(&der, b"www.example.com"));
+        assert!(contains(&der, &[0x87, 4, 10, 0, 0, 1]));
+        assert!(contains(&der, b"spiffe://cluster.local/ns/default/sa/web"));
+    }
+
+    #[tokio::test]
+    async fn issue_enforces_wildcard_ip_and_uri_rules() {
+        let engine = make_engine().await;
+        engine.create_role(role("strict")).await.unwrap();
+        let mut wildcard = role("wildcard");
+        wildcard.allow_wildcard_certificates = true;
+        engine.create_role(wildcard).await.unwrap();
+
+        let denied = [
+            request("*.example.com"),
+            request("api.other.com"),
+            IssueRequest {
+                alt_names: vec!["evil.com".to_owned()],
+                ..request("api.example.com")
+            },
+            IssueRequest {
+                ip_sans: vec!["10.0.0.1".to_owned()],
+                ..request("api.example.com")
+            },
+            IssueRequest {
+                uri_sans: vec!["spiffe://cluster.local/web".to_owned()],
+                ..request("api.example.com")
+            },
+        ];
+        for req in &denied {
+            assert!(
+                matches!(
+                    engine.issue("strict", req).await,
+                    Err(PkiError::InvalidRequest { .. })
+                ),
+                "{req:?} should be refused"
+            );
+        }
+
+        assert!(
+            engine
+                .issue("wildcard", &request("*.example.com"))
+                .await
+                .is_ok()
+        );
+        assert!(
+            engine
+                .issue("wildcard", &request("*.*.example.com"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn issue_honors_key_type_and_backdating() {
+        let engine = make_engine().await;
+        let mut p384 = role("p384");
+        p384.key_bits = 384;
+        p384.not_before_duration_secs = 3600;
+        engine.create_role(p384).await.unwrap();
+        let mut any = role("any");
+        any.key_type = "any".to_owned();
+        engine.create_role(any).await.unwrap();
+
+        let issued = engine.issue("p384", &request("example.com")).await.unwrap();
+        let key = rcgen::KeyPair::from_pem(&issued.private_key_pem.unwrap()).unwrap();
+        assert!(key.is_compatible(&rcgen::PKCS_ECDSA_P384_SHA384));
+        let (not_before, _) = validity(&issued.certificate_pem);
+        let expected = (Utc::now() - chrono::Duration::seconds(3600)).format("%y%m%d%H");
+        assert!(not_before.starts_with(&expected.to_string()));
+
+        let err = engine
+            .issue(
+                "p384",
+                &IssueRequest {
+                    key_type: Some("ed25519".to_owned()),
+                    ..request("example.com")
+                },
+            )
+            .await;
+        assert!(matches!(err, Err(PkiError::InvalidRequest { .. })));
+
+        let issued = engine
+            .issue(
+                "any",
+                &IssueRequest {
+                    key_type: Some("ed25519".to_owned()),
+                    not_before_secs: Some(0),
+                    ..request("example.com")
+                },
+            )
+            .await
+            .unwrap();
+        let key = rcgen::KeyPair::from_pem(&issued.private_key_pem.unwrap()).unwrap();
+        assert!(key.is_compatible(&rcgen::PKCS_ED25519));
+    }
+
+    fn csr_der(key: &rcgen::KeyPair, names: &[&str]) -> Vec<u8> {
+        let names: Vec<String> = names.iter().map(|n| (*n).to_owned()).collect();
+        let mut params = rcgen::CertificateParams::new(names).unwrap();
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "web.example.com");
+        params.serialize_request(key).unwrap().der().to_vec()
+    }
+
+    #[tokio::test]
+    async fn sign_csr_checks_signature_key_and_names() {
+        let engine = make_engine().await;
+        engine.create_role(role("web")).await.unwrap();
+
+        let key = rcgen::KeyPair::generate().unwrap();
+        let csr = CertificateRequest::from_der(&csr_der(&key, &["api.example.com"])).unwrap();
+        assert_eq!(csr.common_name.as_deref(), Some("web.example.com"));
+        assert_eq!(csr.dns_names, ["api.example.com"]);
+        let issued = engine.sign_csr("web", &csr, Some(1)).await.unwrap();
+        assert!(issued.private_key_pem.is_none());
+        let der = der(&issued.certificate_pem);
+        assert!(contains(&der, key.public_key_raw()));
+        assert!(contains(&der, b"api.example.com"));
+
+        let p384 = rcgen::KeyPair::generate_for(&rcgen::PKCS_ECDSA_P384_SHA384).unwrap();
+        let csr = CertificateRequest::from_der(&csr_der(&p384, &["api.example.com"])).unwrap();
+        assert!(matches!(
+            engine.sign_csr("web", &csr, None).await,
+            Err(PkiError::InvalidRequest { .. })
+        ));
+
+        let ed25519 = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519).unwrap();
+        assert!(CertificateRequest::from_der(&csr_der(&ed25519, &["api.example.com"])).is_ok());
+
+        let csr = CertificateRequest::from_der(&csr_der(&key, &["api.other.com"])).unwrap();
+        assert!(engine.sign_csr("web", &csr, None).await.is_err());
+
+        let mut tampered = csr_der(&key, &["api.example.com"]);
+        let at = tampered.windows(3).position(|w| w == b"api").unwrap();
+        tampered[at] = b'b';
+        assert!(CertificateRequest::from_der(&tampered).is_err());
+    }
+
+    #[tokio::test]
+    async fn create_role_rejects_unsupported_keys_and_backdating() {
+        let engine = make_engine().await;
+        let mut rsa_1024 = role("rsa");
+        rsa_1024.key_type = "rsa".to_owned();
+        rsa_1024.key_bits = 1024;
+        let mut dsa = role("dsa");
+        dsa.key_type = "dsa".to_owned();
+        let mut skewed = role("skewed");
+        skewed.not_before_duration_secs = MAX_NOT_BEFORE_SECS + 1;
+
+        for role in [rsa_1024, dsa, skewed] {
+            assert!(matches!(
+                engine.create_role(role).await,
+                Err(PkiError::InvalidRequest { .. })
+            ));
+        }
+    }
+
+    /// Rewrite a stored certificate's expiration, as if it were issued long ago.
+    async fn set_expiration(engine: &PkiEngine, serial: &str, expiration: DateTime<Utc>) {
+        let mut cert = engine.load_cert(serial).await.unwrap().unwrap();
+        cert.expiration = expiration.to_rfc3339();
+        let data = serde_json::to_vec(&cert).unwrap();
+        engine
+            .barrier
+            .put(&engine.cert_key(serial), &data)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_cert_page_filters_and_pages() {
+        let engine = make_engine().await;
+        engine.create_role(role("web")).await.unwrap();
+        let mut serials = Vec::new();
+        for name in [
+            "a.example.com",
+            "b.example.com",
+            "api.example.com",
+            "c.example.com",
+        ] {
+            let cert = engine.issue("web", &request(name)).await.unwrap();
+            serials.push(cert.serial_number);
+        }
+        set_expiration(
+            &engine,
+            &serials[1],
+            Utc::now() - chrono::Duration::days(10),
+        )
+        .await;
+        engine.revoke_cert(&serials[2]).await.unwrap();
+
+        let mut query = CertListQuery {
+            limit: 3,
+            ..CertListQuery::default()
+        };
+        let first = engine.list_cert_page(&query).await.unwrap();
+        assert_eq!(first.certificates.len(), 3);
+        query.after.clone_from(&first.next_cursor);
+        let second = engine.list_cert_page(&query).await.unwrap();
+        assert_eq!(second.certificates.len(), 1);
+        assert!(second.next_cursor.is_none());
+        let mut listed: Vec<String> = first
+            .certificates
+            .iter()
+            .chain(&second.certificates)
+            .map(|c| c.serial_number.clone())
+            .collect();
+        listed.sort();
+        serials.sort();
+        assert_eq!(listed, serials);
+
+        let api = engine
+            .list_cert_page(&CertListQuery {
+                common_name: Some("API.".to_owned()),
+                limit: 10,
+                ..CertListQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(api.certificates.len(), 1);
+        assert_eq!(
+            api.certificates[0].common_name.as_deref(),
+            Some("api.example.com")
+        );
+        assert!(api.certificates[0].revoked);
+
+        let expired = engine
+            .list_cert_page(&CertListQuery {
+                expires_before: Some(Utc::now()),
+                limit: 10,
+                ..CertListQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(expired.certificates.len(), 1);
+        assert_eq!(
+            expired.certificates[0].common_name.as_deref(),
+            Some("b.example.com")
+        );
+        let current = engine
+            .list_cert_page(&CertListQuery {
+                expires_after: Some(Utc::now()),
+                limit: 10,
+                ..CertListQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(current.certificates.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn tidy_prunes_only_past_the_safety_buffer() {
+        let engine = make_engine().await;
+        engine.create_role(role("web")).await.unwrap();
+        let old = engine
+            .issue("web", &request("old.example.com"))
+            .await
+            .unwrap();
+        let recent = engine
+            .issue("web", &request("recent.example.com"))
+            .await
+            .unwrap();
+        let live = engine
+            .issue("web", &request("live.example.com"))
+            .await
+            .unwrap();
+        set_expiration(
+            &engine,
+            &old.serial_number,
+            Utc::now() - chrono::Duration::days(5),
+        )
+        .await;
+        set_expiration(
+            &engine,
+            &recent.serial_number,
+            Utc::now() - chrono::Duration::hours(1),
+        )
+        .await;
+        for cert in [&old, &recent, &live] {
+            engine.revoke_cert(&cert.serial_number).await.unwrap();
+        }
+
+        let only_revoked = TidyRequest {
+            tidy_cert_store: false,
+            ..TidyRequest::default()
+        };
+        let result = engine.tidy(&only_revoked).await.unwrap();
+        assert_eq!((result.certs_deleted, result.revoked_deleted), (0, 1));
+        assert_eq!(engine.list_certs().await.unwrap().len(), 3);
+
+        let result = engine.tidy(&TidyRequest::default()).await.unwrap();
+        assert_eq!((result.certs_deleted, result.revoked_deleted), (1, 0));
+        let mut left = engine.list_certs().await.unwrap();
+        left.sort();
+        let mut expected = vec![recent.serial_number.clone(), live.serial_number.clone()];
+        expected.sort();
+        assert_eq!(left, expected);
+
+        let no_buffer = TidyRequest {
+            safety_buffer: chrono::Duration::zero(),
+            ..TidyRequest::default()
+        };
+        let result = engine.tidy(&no_buffer).await.unwrap();
+        assert_eq!((result.certs_deleted, result.revoked_deleted), (1, 1));
+        assert_eq!(engine.list_revoked().await.unwrap(), [live.serial_number]);
+    }
+
+    #[test]
+    fn bootstrap_certificate_issues_leaf_and_ca() {
+        let hostnames = vec!["vault.internal".to_owned(), "127.0.0.1".to_owned()];
+        let issued = bootstrap_certificate(&hostnames, 24).unwrap();
+        assert!(
+            issued
+                .certificate_pem
+                .starts_with("-----BEGIN CERTIFICATE-----")
+        );
+        assert!(
+            issued
+                .ca_chain_pem
+                .starts_with("-----BEGIN CERTIFICATE-----")
+        );
+        assert_ne!(issued.certificate_pem, issued.ca_chain_pem);
+        assert!(issued.private_key_pem.unwrap().contains("PRIVATE KEY"));
+    }
+
+    #[test]
+    fn bootstrap_certificate_requires_a_hostname() {
+        assert!(matches!(
+            bootstrap_certificate(&[], 24),
+            Err(PkiError::InvalidRequest { .. })
+        ));
+    }
 }
@@ -0,0 +1,1992 @@
+//! ACME (RFC 8555) server for the PKI engine.
+//!
+//! Lets cert-manager, Caddy, certbot, and other ACME clients obtain
+//! certificates from the internal CA without a vault token. An account is a
+//! JWS key; an order names DNS identifiers, and the client proves control of
+//! each with a challenge this server checks itself:
+//!
+//! - `http-01` — `http://{domain}/.well-known/acme-challenge/{token}` must
+//!   return the key authorization.
+//! - `dns-01` — `_acme-challenge.{domain}` must have a TXT record with its
+//!   digest, looked up on the configured resolver so internal zones work.
+//!   The only challenge offered for wildcard identifiers.
+//!
+//! Finalized orders are signed under the role named in the ACME
+//! configuration, so that role still decides which names, key types, and
+//! lifetimes can be issued. Accounts, orders, authorizations, and
+//! certificates are stored through the barrier under the mount's `acme/`
+//! prefix; nonces live in memory.
+
+use std::collections::{BTreeSet, HashMap};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64URL};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tokio::net::UdpSocket;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+use crate::barrier::Barrier;
+use crate::error::{AcmeServerError, PkiError};
+use crate::pki::{CertificateRequest, IssuedCertificate, PkiEngine};
+
+/// How long a new order and its authorizations stay pending.
+const ORDER_LIFETIME_HOURS: i64 = 24;
+
+/// Most identifiers one order may name.
+const MAX_IDENTIFIERS: usize = 100;
+
+/// How long an unused nonce stays valid.
+const NONCE_LIFETIME: Duration = Duration::from_secs(3600);
+
+/// Most outstanding nonces; the oldest are dropped beyond this.
+const MAX_NONCES: usize = 10_000;
+
+/// Attempts at a challenge before its authorization fails.
+const VALIDATION_ATTEMPTS: u32 = 5;
+
+/// Delay between challenge attempts, to let DNS changes propagate.
+const VALIDATION_RETRY: Duration = Duration::from_secs(5);
+
+/// Timeout for each `http-01` fetch or DNS query.
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prefix of RFC 8555 problem types.
+const PROBLEM_PREFIX: &str = "urn:ietf:params:acme:error:";
+
+/// Smallest RSA modulus accepted for account keys, in bits.
+const MIN_RSA_BITS: usize = 2048;
+
+/// Largest RSA modulus accepted for account keys, in bits.
+const MAX_RSA_BITS: usize = 8192;
+
+/// ACME settings of a PKI mount.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcmeServerConfig {
+    /// Whether the ACME endpoints answer.
+    pub enabled: bool,
+    /// URL ACME clients reach this server at, e.g. `https://vault.internal:8200`.
+    pub base_url: String,
+    /// PKI role finalized orders are issued under.
+    pub role: String,
+    /// DNS server (`ip:port`) `dns-01` records are looked up on; the first
+    /// `nameserver` in `/etc/resolv.conf` when unset.
+    #[serde(default)]
+    pub dns_resolver: Option<String>,
+}
+
+/// An ACME resource as returned to a client.
+#[derive(Debug)]
+pub struct AcmeResponse {
+    /// Whether the request created the resource (`201 Created`).
+    pub created: bool,
+    /// URL of the resource, for the `Location` header.
+    pub location: Option<String>,
+    /// URL of the parent resource, for a `Link: rel="up"` header.
+    pub up: Option<String>,
+    /// The resource.
+    pub body: Value,
+}
+
+impl AcmeResponse {
+    fn new(body: Value) -> Self {
+        Self {
+            created: false,
+            location: None,
+            up: None,
+            body,
+        }
+    }
+}
+
+/// Status of an account, order, authorization, or challenge (RFC 8555 §7.1.6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pending,
+    Processing,
+    Ready,
+    Valid,
+    Invalid,
+    Deactivated,
+    Expired,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Processing => "processing",
+            Self::Ready => "ready",
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::Deactivated => "deactivated",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Account {
+    id: String,
+    thumbprint: String,
+    jwk: Value,
+    contact: Vec<String>,
+    status: Status,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Order {
+    id: String,
+    account: String,
+    /// DNS names, lowercased; wildcards keep their `*.`.
+    identifiers: Vec<String>,
+    authorizations: Vec<String>,
+    /// `Pending`, `Valid`, or `Invalid`; `Ready` is derived.
+    status: Status,
+    expires: DateTime<Utc>,
+    not_after: Option<DateTime<Utc>>,
+    certificate: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Authorization {
+    id: String,
+    account: String,
+    /// The identifier without any `*.`.
+    name: String,
+    wildcard: bool,
+    status: Status,
+    expires: DateTime<Utc>,
+    challenges: Vec<Challenge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Challenge {
+    kind: String,
+    token: String,
+    status: Status,
+    validated: Option<DateTime<Utc>>,
+    error: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCertificate {
+    account: String,
+    serial_number: String,
+    chain_pem: String,
+}
+
+/// A flattened JWS, as every ACME POST body is.
+#[derive(Deserialize)]
+struct Jws {
+    protected: String,
+    payload: String,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct ProtectedHeader {
+    alg: String,
+    nonce: Option<String>,
+    url: String,
+    jwk: Option<Value>,
+    kid: Option<String>,
+}
+
+/// Who signed a request.
+enum Signer {
+    /// A key without an account yet (`newAccount`).
+    Key { jwk: Value, thumbprint: String },
+    /// An existing account (`kid`).
+    Account(Account),
+}
+
+/// A verified request: its signer and payload (`None` for POST-as-GET).
+struct Signed {
+    signer: Signer,
+    payload: Option<Value>,
+}
+
+impl Signed {
+    fn account(&self) -> Result<&Account, AcmeServerError> {
+        match &self.signer {
+            Signer::Account(account) => Ok(account),
+            Signer::Key { .. } => Err(malformed("request must be signed with an account's kid")),
+        }
+    }
+}
+
+/// The ACME server of a PKI mount.
+pub struct AcmeServer {
+    pki: Arc<PkiEngine>,
+    barrier: Arc<Barrier>,
+    prefix: String,
+    api_path: String,
+    config: RwLock<Option<AcmeServerConfig>>,
+    nonces: Mutex<HashMap<String, Instant>>,
+    /// Serializes finalization so an order is signed once.
+    finalizing: Mutex<()>,
+    retry_delay: Duration,
+}
+
+impl AcmeServer {
+    /// Create an ACME server for `pki`, storing its state under `prefix` and
+    /// answering at `api_path` (e.g. `/v1/pki/acme`) below the base URL.
+    pub fn new(pki: Arc<PkiEngine>, barrier: Arc<Barrier>, prefix: String, api_path: &str) -> Self {
+        Self {
+            pki,
+            barrier,
+            prefix,
+            api_path: api_path.trim_end_matches('/').to_owned(),
+            config: RwLock::new(None),
+            nonces: Mutex::new(HashMap::new()),
+            finalizing: Mutex::new(()),
+            retry_delay: VALIDATION_RETRY,
+        }
+    }
+
+    /// The ACME configuration, if one was saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::Barrier` if the barrier is sealed.
+    pub async fn config(&self) -> Result<Option<AcmeServerConfig>, AcmeServerError> {
+        if let Some(config) = self.config.read().await.as_ref() {
+            return Ok(Some(config.clone()));
+        }
+        let config: Option<AcmeServerConfig> = self.load("config", "").await?;
+        self.config.write().await.clone_from(&config);
+        Ok(config)
+    }
+
+    /// Save the ACME configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::Malformed` if the base URL or resolver is
+    /// invalid, and `AcmeServerError::Pki` if the role does not exist.
+    pub async fn configure(&self, mut config: AcmeServerConfig) -> Result<(), AcmeServerError> {
+        config.base_url = config.base_url.trim_end_matches('/').to_owned();
+        let url = url::Url::parse(&config.base_url)
+            .map_err(|e| malformed(&format!("invalid base_url: {e}")))?;
+        if !matches!(url.scheme(), "http" | "https") || url.path() != "/" {
+            return Err(malformed(
+                "base_url must be an http(s) URL without a path, e.g. https://vault.internal:8200",
+            ));
+        }
+        self.pki.get_role(&config.role).await?;
+        if let Some(resolver) = &config.dns_resolver {
+            config.dns_resolver = Some(parse_resolver(resolver)?.to_string());
+        }
+        self.store("config", "", &config).await?;
+        *self.config.write().await = Some(config);
+        Ok(())
+    }
+
+    /// URL of the directory, when ACME is enabled.
+    pub async fn directory_url(&self) -> Option<String> {
+        let config = self.enabled_config().await.ok()?;
+        Some(self.url(&config, "directory"))
+    }
+
+    /// The directory object.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::Disabled` if ACME is not enabled.
+    pub async fn directory(&self) -> Result<Value, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        Ok(json!({
+            "newNonce": self.url(&config, "new-nonce"),
+            "newAccount": self.url(&config, "new-account"),
+            "newOrder": self.url(&config, "new-order"),
+            "revokeCert": self.url(&config, "revoke-cert"),
+            "meta": { "externalAccountRequired": false },
+        }))
+    }
+
+    /// A fresh nonce for the `Replay-Nonce` header.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::Internal` if the OS RNG fails.
+    pub async fn new_nonce(&self) -> Result<String, AcmeServerError> {
+        let nonce = random_id()?;
+        let now = Instant::now();
+        let mut nonces = self.nonces.lock().await;
+        nonces.retain(|_, issued| now.duration_since(*issued) < NONCE_LIFETIME);
+        while nonces.len() >= MAX_NONCES {
+            let Some(oldest) = nonces
+                .iter()
+                .min_by_key(|(_, issued)| **issued)
+                .map(|(n, _)| n.clone())
+            else {
+                break;
+            };
+            nonces.remove(&oldest);
+        }
+        nonces.insert(nonce.clone(), now);
+        Ok(nonce)
+    }
+
+    /// Create an account, or find the one for the signing key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::AccountDoesNotExist` for
+    /// `onlyReturnExisting` with an unknown key, and the verification errors
+    /// of any request.
+    pub async fn new_account(&self, body: &[u8]) -> Result<AcmeResponse, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self.verify(&config, body, "new-account").await?;
+        let Signer::Key { jwk, thumbprint } = signed.signer else {
+            return Err(malformed("newAccount must be signed with a jwk"));
+        };
+        let payload = signed.payload.unwrap_or_default();
+
+        let existing: Option<String> = self.load("account-keys", &thumbprint).await?;
+        if let Some(id) = existing {
+            let account: Account = self.require("account", "accounts", &id).await?;
+            let mut response = AcmeResponse::new(self.account_json(&config, &account));
+            response.location = Some(self.url(&config, &format!("account/{id}")));
+            return Ok(response);
+        }
+        if payload["onlyReturnExisting"].as_bool() == Some(true) {
+            return Err(AcmeServerError::AccountDoesNotExist);
+        }
+        if !payload["externalAccountBinding"].is_null() {
+            return Err(malformed("external account binding is not supported"));
+        }
+
+        let account = Account {
+            id: random_id()?,
+            thumbprint: thumbprint.clone(),
+            jwk,
+            contact: contacts(&payload)?,
+            status: Status::Valid,
+            created_at: Utc::now(),
+        };
+        self.store("accounts", &account.id, &account).await?;
+        self.store("account-keys", &thumbprint, &account.id).await?;
+        info!(account = %account.id, "ACME account created");
+
+        Ok(AcmeResponse {
+            created: true,
+            location: Some(self.url(&config, &format!("account/{}", account.id))),
+            up: None,
+            body: self.account_json(&config, &account),
+        })
+    }
+
+    /// Read, update the contacts of, or deactivate an account.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::Unauthorized` if another account signed the
+    /// request.
+    pub async fn account(&self, id: &str, body: &[u8]) -> Result<AcmeResponse, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self.verify(&config, body, &format!("account/{id}")).await?;
+        let mut account = signed.account()?.clone();
+        owned_by(&account, id)?;
+
+        if let Some(payload) = &signed.payload {
+            if payload.get("contact").is_some() {
+                account.contact = contacts(payload)?;
+            }
+            match payload["status"].as_str() {
+                None => {}
+                Some("deactivated") => account.status = Status::Deactivated,
+                Some(status) => {
+                    return Err(malformed(&format!("cannot set account status '{status}'")));
+                }
+            }
+            self.store("accounts", id, &account).await?;
+        }
+        Ok(AcmeResponse::new(self.account_json(&config, &account)))
+    }
+
+    /// The URLs of an account's orders.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::Unauthorized` if another account signed the
+    /// request.
+    pub async fn account_orders(
+        &self,
+        id: &str,
+        body: &[u8],
+    ) -> Result<AcmeResponse, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self
+            .verify(&config, body, &format!("account/{id}/orders"))
+            .await?;
+        owned_by(signed.account()?, id)?;
+        let prefix = format!("{}account-orders/{id}/", self.prefix);
+        let orders: Vec<String> = self
+            .barrier
+            .list(&prefix)
+            .await?
+            .iter()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .map(|order| self.url(&config, &format!("order/{order}")))
+            .collect();
+        Ok(AcmeResponse::new(json!({ "orders": orders })))
+    }
+
+    /// Create an order and an authorization for each of its identifiers.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::RejectedIdentifier` if the ACME role does
+    /// not allow an identifier, and `AcmeServerError::Malformed` if the
+    /// identifiers are missing or not DNS names.
+    pub async fn new_order(&self, body: &[u8]) -> Result<AcmeResponse, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self.verify(&config, body, "new-order").await?;
+        let account = signed.account()?;
+        let payload = signed
+            .payload
+            .as_ref()
+            .ok_or_else(|| malformed("newOrder needs a payload"))?;
+
+        let identifiers = payload["identifiers"]
+            .as_array()
+            .filter(|ids| !ids.is_empty())
+            .ok_or_else(|| malformed("identifiers is required"))?;
+        if identifiers.len() > MAX_IDENTIFIERS {
+            return Err(malformed(&format!(
+                "an order may name at most {MAX_IDENTIFIERS} identifiers"
+            )));
+        }
+        if !payload["notBefore"].is_null() {
+            return Err(malformed("notBefore is not supported"));
+        }
+        let not_after = match payload["notAfter"].as_str() {
+            Some(at) => Some(
+                DateTime::parse_from_rfc3339(at)
+                    .map_err(|_| malformed("notAfter must be an RFC 3339 timestamp"))?
+                    .with_timezone(&Utc),
+            ),
+            None => None,
+        };
+
+        let names = self.order_names(&config, identifiers).await?;
+
+        let expires = Utc::now() + chrono::Duration::hours(ORDER_LIFETIME_HOURS);
+        let mut authorizations = Vec::new();
+        for name in &names {
+            let (base, wildcard) = match name.strip_prefix("*.") {
+                Some(base) => (base, true),
+                None => (name.as_str(), false),
+            };
+            let kinds: &[&str] = if wildcard {
+                &["dns-01"]
+            } else {
+                &["http-01", "dns-01"]
+            };
+            let authz = Authorization {
+                id: random_id()?,
+                account: account.id.clone(),
+                name: base.to_owned(),
+                wildcard,
+                status: Status::Pending,
+                expires,
+                challenges: kinds
+                    .iter()
+                    .map(|kind| {
+                        Ok(Challenge {
+                            kind: (*kind).to_owned(),
+                            token: random_id()?,
+                            status: Status::Pending,
+                            validated: None,
+                            error: None,
+                        })
+                    })
+                    .collect::<Result<_, AcmeServerError>>()?,
+            };
+            self.store("authz", &authz.id, &authz).await?;
+            authorizations.push(authz.id);
+        }
+
+        let mut order = Order {
+            id: random_id()?,
+            account: account.id.clone(),
+            identifiers: names.into_iter().collect(),
+            authorizations,
+            status: Status::Pending,
+            expires,
+            not_after,
+            certificate: None,
+        };
+        self.store("orders", &order.id, &order).await?;
+        self.store(&format!("account-orders/{}", account.id), &order.id, &true)
+            .await?;
+
+        let body = self.order_json(&config, &mut order).await?;
+        Ok(AcmeResponse {
+            created: true,
+            location: Some(self.url(&config, &format!("order/{}", order.id))),
+            up: None,
+            body,
+        })
+    }
+
+    /// Check order identifiers against the ACME role and normalise them to
+    /// lowercase DNS names without a trailing dot.
+    async fn order_names(
+        &self,
+        config: &AcmeServerConfig,
+        identifiers: &[Value],
+    ) -> Result<BTreeSet<String>, AcmeServerError> {
+        let role = self.pki.get_role(&config.role).await?;
+        let mut names = BTreeSet::new();
+        for identifier in identifiers {
+            let value = identifier["value"].as_str().unwrap_or_default();
+            if identifier["type"] != "dns" {
+                return Err(AcmeServerError::RejectedIdentifier {
+                    identifier: value.to_owned(),
+                    reason: "only dns identifiers are supported".to_owned(),
+                });
+            }
+            let name = value.trim_end_matches('.').to_ascii_lowercase();
+            if !role.allows_dns_name(&name) {
+                return Err(AcmeServerError::RejectedIdentifier {
+                    identifier: value.to_owned(),
+                    reason: format!("not allowed by role '{}'", role.name),
+                });
+            }
+            names.insert(name);
+        }
+        Ok(names)
+    }
+
+    /// Read an order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::NotFound` or `AcmeServerError::Unauthorized`
+    /// if the order does not exist or belongs to another account.
+    pub async fn order(&self, id: &str, body: &[u8]) -> Result<AcmeResponse, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self.verify(&config, body, &format!("order/{id}")).await?;
+        let mut order: Order = self.require("order", "orders", id).await?;
+        owned_by(signed.account()?, &order.account)?;
+        let body = self.order_json(&config, &mut order).await?;
+        Ok(AcmeResponse::new(body))
+    }
+
+    /// Read or deactivate an authorization.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::NotFound` or `AcmeServerError::Unauthorized`
+    /// if the authorization does not exist or belongs to another account.
+    pub async fn authorization(
+        &self,
+        id: &str,
+        body: &[u8],
+    ) -> Result<AcmeResponse, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self.verify(&config, body, &format!("authz/{id}")).await?;
+        let mut authz: Authorization = self.require("authorization", "authz", id).await?;
+        owned_by(signed.account()?, &authz.account)?;
+
+        if let Some(payload) = &signed.payload {
+            match payload["status"].as_str() {
+                None => {}
+                Some("deactivated") => {
+                    authz.status = Status::Deactivated;
+                    self.store("authz", id, &authz).await?;
+                }
+                Some(status) => {
+                    return Err(malformed(&format!(
+                        "cannot set authorization status '{status}'"
+                    )));
+                }
+            }
+        }
+        Ok(AcmeResponse::new(self.authorization_json(&config, &authz)))
+    }
+
+    /// Read a challenge or, with a payload, start validating it.
+    ///
+    /// Validation runs in the background, retrying for a while so DNS
+    /// changes can propagate; clients poll the authorization.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::NotFound` or `AcmeServerError::Unauthorized`
+    /// if the challenge does not exist or belongs to another account.
+    pub async fn challenge(
+        self: &Arc<Self>,
+        authz_id: &str,
+        kind: &str,
+        body: &[u8],
+    ) -> Result<AcmeResponse, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self
+            .verify(&config, body, &format!("chall/{authz_id}/{kind}"))
+            .await?;
+        let mut authz: Authorization = self.require("authorization", "authz", authz_id).await?;
+        owned_by(signed.account()?, &authz.account)?;
+        let expired = authz.expires < Utc::now();
+        let Some(challenge) = authz.challenges.iter_mut().find(|c| c.kind == kind) else {
+            return Err(not_found("challenge"));
+        };
+
+        if signed.payload.is_some()
+            && challenge.status == Status::Pending
+            && authz.status == Status::Pending
+            && !expired
+        {
+            challenge.status = Status::Processing;
+            let challenge = challenge.clone();
+            self.store("authz", authz_id, &authz).await?;
+            let server = Arc::clone(self);
+            let (authz_id, kind) = (authz_id.to_owned(), kind.to_owned());
+            tokio::spawn(async move { server.validate(&authz_id, &kind).await });
+            let mut response =
+                AcmeResponse::new(self.challenge_json(&config, &authz.id, &challenge));
+            response.up = Some(self.url(&config, &format!("authz/{}", authz.id)));
+            return Ok(response);
+        }
+
+        let body = self.challenge_json(&config, &authz.id, challenge);
+        let mut response = AcmeResponse::new(body);
+        response.up = Some(self.url(&config, &format!("authz/{}", authz.id)));
+        Ok(response)
+    }
+
+    /// Sign the CSR of a ready order.
+    ///
+    /// Returns the order and the issued certificate, so the caller can track
+    /// it like any other issued certificate.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::OrderNotReady` unless every authorization is
+    /// valid, and `AcmeServerError::BadCsr` if the CSR names differ from the
+    /// order's identifiers or the role refuses it.
+    pub async fn finalize(
+        &self,
+        id: &str,
+        body: &[u8],
+    ) -> Result<(AcmeResponse, IssuedCertificate), AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self
+            .verify(&config, body, &format!("order/{id}/finalize"))
+            .await?;
+        let account = signed.account()?;
+        let csr = signed.payload.as_ref().and_then(|p| p["csr"].as_str());
+        let csr = BASE64URL
+            .decode(csr.ok_or_else(|| malformed("csr is required"))?)
+            .map_err(|_| malformed("csr must be base64url"))?;
+
+        let _finalizing = self.finalizing.lock().await;
+        let mut order: Order = self.require("order", "orders", id).await?;
+        owned_by(account, &order.account)?;
+        let status = self.order_status(&mut order).await?;
+        if status != Status::Ready {
+            return Err(AcmeServerError::OrderNotReady {
+                status: status.as_str().to_owned(),
+            });
+        }
+
+        let csr = CertificateRequest::from_der(&csr).map_err(bad_csr)?;
+        let mut requested: BTreeSet<String> = csr
+            .dns_names
+            .iter()
+            .map(|name| name.to_ascii_lowercase())
+            .collect();
+        requested.extend(csr.common_name.iter().map(|cn| cn.to_ascii_lowercase()));
+        let ordered: BTreeSet<String> = order.identifiers.iter().cloned().collect();
+        if requested != ordered || !csr.ip_addresses.is_empty() || !csr.uris.is_empty() {
+            return Err(AcmeServerError::BadCsr {
+                reason: "the CSR must name exactly the order's identifiers".to_owned(),
+            });
+        }
+
+        let ttl_hours = order.not_after.map(|not_after| {
+            let secs = (not_after - Utc::now()).num_seconds().max(0);
+            u64::try_from(secs).unwrap_or(0).div_ceil(3600).max(1)
+        });
+        let issued = self
+            .pki
+            .sign_csr(&config.role, &csr, ttl_hours)
+            .await
+            .map_err(bad_csr)?;
+
+        let certificate_id = certificate_id(&issued.certificate_pem)?;
+        let stored = StoredCertificate {
+            account: account.id.clone(),
+            serial_number: issued.serial_number.clone(),
+            chain_pem: format!("{}{}", issued.certificate_pem, issued.ca_chain_pem),
+        };
+        self.store("certs", &certificate_id, &stored).await?;
+        order.status = Status::Valid;
+        order.certificate = Some(certificate_id);
+        self.store("orders", id, &order).await?;
+        info!(
+            order = %id,
+            account = %account.id,
+            serial = %issued.serial_number,
+            "ACME certificate issued"
+        );
+
+        let mut response = AcmeResponse::new(self.order_json(&config, &mut order).await?);
+        response.location = Some(self.url(&config, &format!("order/{id}")));
+        Ok((response, issued))
+    }
+
+    /// The PEM certificate chain of a finalized order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::NotFound` or `AcmeServerError::Unauthorized`
+    /// if the certificate does not exist or belongs to another account.
+    pub async fn certificate(&self, id: &str, body: &[u8]) -> Result<String, AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self.verify(&config, body, &format!("cert/{id}")).await?;
+        let stored: StoredCertificate = self.require("certificate", "certs", id).await?;
+        owned_by(signed.account()?, &stored.account)?;
+        Ok(stored.chain_pem)
+    }
+
+    /// Revoke a certificate issued to the signing account.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AcmeServerError::Unauthorized` if another account ordered
+    /// the certificate.
+    pub async fn revoke(&self, body: &[u8]) -> Result<(), AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let signed = self.verify(&config, body, "revoke-cert").await?;
+        let account = signed.account()?;
+        let der = signed
+            .payload
+            .as_ref()
+            .and_then(|p| p["certificate"].as_str())
+            .ok_or_else(|| malformed("certificate is required"))?;
+        let der = BASE64URL
+            .decode(der)
+            .map_err(|_| malformed("certificate must be base64url"))?;
+        let id = hex::encode(Sha256::digest(&der));
+        let stored: StoredCertificate = self.require("certificate", "certs", &id).await?;
+        owned_by(account, &stored.account)?;
+        self.pki.revoke_cert(&stored.serial_number).await?;
+        info!(serial = %stored.serial_number, account = %account.id, "ACME certificate revoked");
+        Ok(())
+    }
+
+    // ── Request verification ────────────────────────────────────────
+
+    /// Check the JWS `body` of a request to `path`: URL, nonce, signer, and
+    /// signature.
+    async fn verify(
+        &self,
+        config: &AcmeServerConfig,
+        body: &[u8],
+        path: &str,
+    ) -> Result<Signed, AcmeServerError> {
+        let jws: Jws =
+            serde_json::from_slice(body).map_err(|_| malformed("body must be a flattened JWS"))?;
+        let header: ProtectedHeader =
+            decode_json(&jws.protected).ok_or_else(|| malformed("invalid JWS protected header"))?;
+
+        if header.url != self.url(config, path) {
+            return Err(AcmeServerError::Unauthorized {
+                reason: "JWS url does not match the request URL".to_owned(),
+            });
+        }
+        let nonce = header.nonce.as_deref().unwrap_or_default();
+        if self.nonces.lock().await.remove(nonce).is_none() {
+            return Err(AcmeServerError::BadNonce);
+        }
+
+        let signer = match (header.jwk, header.kid) {
+            (Some(jwk), None) => {
+                let thumbprint = thumbprint(&jwk)?;
+                Signer::Key { jwk, thumbprint }
+            }
+            (None, Some(kid)) => {
+                let prefix = self.url(config, "account/");
+                let id = kid
+                    .strip_prefix(&prefix)
+                    .ok_or(AcmeServerError::AccountDoesNotExist)?;
+                let account: Account = self
+                    .load("accounts", id)
+                    .await?
+                    .ok_or(AcmeServerError::AccountDoesNotExist)?;
+                if account.status != Status::Valid {
+                    return Err(AcmeServerError::Unauthorized {
+                        reason: format!("account is {}", account.status.as_str()),
+                    });
+                }
+                Signer::Account(account)
+            }
+            _ => return Err(malformed("JWS must have exactly one of jwk and kid")),
+        };
+        let jwk = match &signer {
+            Signer::Key { jwk, .. } => jwk,
+            Signer::Account(account) => &account.jwk,
+        };
+
+        let signature = BASE64URL
+            .decode(&jws.signature)
+            .map_err(|_| malformed("invalid JWS signature encoding"))?;
+        let signing_input = format!("{}.{}", jws.protected, jws.payload);
+        verify_signature(&header.alg, jwk, signing_input.as_bytes(), &signature)?;
+
+        let payload = if jws.payload.is_empty() {
+            None
+        } else {
+            Some(decode_json(&jws.payload).ok_or_else(|| malformed("invalid JWS payload"))?)
+        };
+        Ok(Signed { signer, payload })
+    }
+
+    // ── Challenge validation ───────────────────────────────────────
+
+    /// Validate a challenge and record the outcome on its authorization.
+    async fn validate(&self, authz_id: &str, kind: &str) {
+        if let Err(e) = self.try_validate(authz_id, kind).await {
+            warn!(authz = %authz_id, error = %e, "ACME challenge validation failed to complete");
+        }
+    }
+
+    async fn try_validate(&self, authz_id: &str, kind: &str) -> Result<(), AcmeServerError> {
+        let config = self.enabled_config().await?;
+        let authz: Authorization = self.require("authorization", "authz", authz_id).await?;
+        let account: Account = self.require("account", "accounts", &authz.account).await?;
+        let token = authz
+            .challenges
+            .iter()
+            .find(|c| c.kind == kind)
+            .map(|c| c.token.clone())
+            .ok_or_else(|| not_found("challenge"))?;
+        let key_authorization = format!("{token}.{}", account.thumbprint);
+
+        let mut outcome = Ok(());
+        for attempt in 0..VALIDATION_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(self.retry_delay).await;
+            }
+            outcome = check_challenge(&config, &authz.name, kind, &token, &key_authorization).await;
+            if outcome.is_ok() {
+                break;
+            }
+        }
+
+        // Reload: the authorization may have been deactivated meanwhile.
+        let mut authz: Authorization = self.require("authorization", "authz", authz_id).await?;
+        let Some(challenge) = authz.challenges.iter_mut().find(|c| c.kind == kind) else {
+            return Err(not_found("challenge"));
+        };
+        match outcome {
+            Ok(()) => {
+                challenge.status = Status::Valid;
+                challenge.validated = Some(Utc::now());
+                if authz.status == Status::Pending {
+                    authz.status = Status::Valid;
+                }
+            }
+            Err(problem) => {
+                challenge.status = Status::Invalid;
+                challenge.error = Some(problem);
+                if authz.status == Status::Pending {
+                    authz.status = Status::Invalid;
+                }
+            }
+        }
+        info!(
+            authz = %authz_id,
+            domain = %authz.name,
+            status = authz.status.as_str(),
+            "ACME challenge validated"
+        );
+        self.store("authz", authz_id, &authz).await
+    }
+
+    // ── Resources ─────────────────────────────────────────────────
+
+    /// The status of `order`, marking it invalid once an authorization
+    /// fails or it expires.
+    async fn order_status(&self, order: &mut Order) -> Result<Status, AcmeServerError> {
+        if order.status != Status::Pending {
+            return Ok(order.status);
+        }
+        let mut ready = true;
+        let mut failed = order.expires < Utc::now();
+        for id in &order.authorizations {
+            let authz: Authorization = self.require("authorization", "authz", id).await?;
+            match authz.status {
+                Status::Valid => {}
+                Status::Pending => ready = false,
+                _ => failed = true,
+            }
+        }
+        if failed {
+            order.status = Status::Invalid;
+            self.store("orders", &order.id, order).await?;
+            return Ok(order.status);
+        }
+        Ok(if ready {
+            Status::Ready
+        } else {
+            Status::Pending
+        })
+    }
+
+    async fn order_json(
+        &self,
+        config: &AcmeServerConfig,
+        order: &mut Order,
+    ) -> Result<Value, AcmeServerError> {
+        let status = self.order_status(order).await?;
+        let mut body = json!({
+            "status": status,
+            "expires": order.expires.to_rfc3339(),
+            "identifiers": order
+                .identifiers
+                .iter()
+                .map(|name| json!({ "type": "dns", "value": name }))
+                .collect::<Vec<_>>(),
+            "authorizations": order
+                .authorizations
+                .iter()
+                .map(|id| self.url(config, &format!("authz/{id}")))
+                .collect::<Vec<_>>(),
+            "finalize": self.url(config, &format!("order/{}/finalize", order.id)),
+        });
+        if let Some(not_after) = order.not_after {
+            body["notAfter"] = json!(not_after.to_rfc3339());
+        }
+        if let Some(certificate) = &order.certificate {
+            body["certificate"] = json!(self.url(config, &format!("cert/{certificate}")));
+        }
+        if status == Status::Invalid {
+            body["error"] = problem(
+                "unauthorized",
+                "an authorization failed or the order expired",
+            );
+        }
+        Ok(body)
+    }
+
+    fn account_json(&self, config: &AcmeServerConfig, account: &Account) -> Value {
+        json!({
+            "status": account.status,
+            "contact": account.contact,
+            "orders": self.url(config, &format!("account/{}/orders", account.id)),
+            "createdAt": account.created_at.to_rfc3339(),
+        })
+    }
+
+    fn authorization_json(&self, config: &AcmeServerConfig, authz: &Authorization) -> Value {
+        let status = if authz.status == Status::Pending && authz.expires < Utc::now() {
+            Status::Expired
+        } else {
+            authz.status
+        };
+        let mut body = json!({
+            "status": status,
+            "expires": authz.expires.to_rfc3339(),
+            "identifier": { "type": "dns", "value": authz.name },
+            "challenges": authz
+                .challenges
+                .iter()
+                .map(|c| self.challenge_json(config, &authz.id, c))
+                .collect::<Vec<_>>(),
+        });
+        if authz.wildcard {
+            body["wildcard"] = json!(true);
+        }
+        body
+    }
+
+    fn challenge_json(&self, config: &AcmeServerConfig, authz_id: &str, c: &Challenge) -> Value {
+        let mut body = json!({
+            "type": c.kind,
+            "url": self.url(config, &format!("chall/{authz_id}/{}", c.kind)),
+            "token": c.token,
+            "status": c.status,
+        });
+        if let Some(validated) = c.validated {
+            body["validated"] = json!(validated.to_rfc3339());
+        }
+        if let Some(error) = &c.error {
+            body["error"] = error.clone();
+        }
+        body
+    }
+
+    // ── Helpers ────────────────────────────────────────────────────
+
+    async fn enabled_config(&self) -> Result<AcmeServerConfig, AcmeServerError> {
+        self.config()
+            .await?
+            .filter(|config| config.enabled)
+            .ok_or(AcmeServerError::Disabled)
+    }
+
+    fn url(&self, config: &AcmeServerConfig, path: &str) -> String {
+        format!("{}{}/{path}", config.base_url, self.api_path)
+    }
+
+    fn key(&self, kind: &str, id: &str) -> String {
+        if id.is_empty() {
+            format!("{}{kind}", self.prefix)
+        } else {
+            format!("{}{kind}/{id}", self.prefix)
+        }
+    }
+
+    async fn load<T: DeserializeOwned>(
+        &self,
+        kind: &str,
+        id: &str,
+    ) -> Result<Option<T>, AcmeServerError> {
+        if !id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Ok(None);
+        }
+        let Some(data) = self.barrier.get(&self.key(kind, id)).await? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| AcmeServerError::Internal {
+                reason: format!("corrupt ACME {kind} record: {e}"),
+            })
+    }
+
+    async fn require<T: DeserializeOwned>(
+        &self,
+        resource: &str,
+        kind: &str,
+        id: &str,
+    ) -> Result<T, AcmeServerError> {
+        self.load(kind, id)
+            .await?
+            .ok_or_else(|| not_found(resource))
+    }
+
+    async fn store<T: Serialize + ?Sized>(
+        &self,
+        kind: &str,
+        id: &str,
+        value: &T,
+    ) -> Result<(), AcmeServerError> {
+        let data = serde_json::to_vec(value).map_err(|e| AcmeServerError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(&self.key(kind, id), &data).await?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for AcmeServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AcmeServer")
+            .field("prefix", &self.prefix)
+            .field("api_path", &self.api_path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An RFC 7807 problem document of ACME type `kind`.
+pub fn problem(kind: &str, detail: &str) -> Value {
+    json!({ "type": format!("{PROBLEM_PREFIX}{kind}"), "detail": detail })
+}
+
+fn malformed(reason: &str) -> AcmeServerError {
+    AcmeServerError::Malformed {
+        reason: reason.to_owned(),
+    }
+}
+
+fn not_found(resource: &str) -> AcmeServerError {
+    AcmeServerError::NotFound {
+        resource: resource.to_owned(),
+    }
+}
+
+fn bad_csr(error: PkiError) -> AcmeServerError {
+    match error {
+        PkiError::InvalidRequest { reason } => AcmeServerError::BadCsr { reason },
+        other => AcmeServerError::Pki(other),
+    }
+}
+
+fn owned_by(account: &Account, owner: &str) -> Result<(), AcmeServerError> {
+    if account.id == owner {
+        Ok(())
+    } else {
+        Err(AcmeServerError::Unauthorized {
+            reason: "the resource belongs to another account".to_owned(),
+        })
+    }
+}
+
+/// The `contact` URLs of an account payload; only `mailto:` is accepted.
+fn contacts(payload: &Value) -> Result<Vec<String>, AcmeServerError> {
+    let Some(contact) = payload.get("contact") else {
+        return Ok(Vec::new());
+    };
+    let contact: Vec<String> = serde_json::from_value(contact.clone())
+        .map_err(|_| malformed("contact must be a list of URLs"))?;
+    if let Some(bad) = contact.iter().find(|c| !c.starts_with("mailto:")) {
+        return Err(AcmeServerError::Malformed {
+            reason: format!("unsupported contact '{bad}', only mailto: is allowed"),
+        });
+    }
+    Ok(contact)
+}
+
+/// ID of a stored certificate: the SHA-256 of its DER, so revocation
+/// requests can find it.
+fn certificate_id(pem: &str) -> Result<String, AcmeServerError> {
+    let body: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+    let der = BASE64.decode(body).map_err(|e| AcmeServerError::Internal {
+        reason: format!("issued certificate is not valid PEM: {e}"),
+    })?;
+    Ok(hex::encode(Sha256::digest(der)))
+}
+
+fn decode_json<T: DeserializeOwned>(encoded: &str) -> Option<T> {
+    let bytes = BASE64URL.decode(encoded).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn jwk_field(jwk: &Value, name: &str) -> Result<Vec<u8>, AcmeServerError> {
+    jwk[name]
+        .as_str()
+        .and_then(|v| BASE64URL.decode(v).ok())
+        .ok_or_else(|| malformed(&format!("jwk is missing '{name}'")))
+}
+
+/// The RFC 7638 thumbprint of an EC, RSA, or Ed25519 JWK.
+fn thumbprint(jwk: &Value) -> Result<String, AcmeServerError> {
+    let field = |name: &str| {
+        jwk[name]
+            .as_str()
+            .ok_or_else(|| malformed(&format!("jwk is missing '{name}'")))
+    };
+    // The required members, in lexicographic order, without whitespace.
+    let canonical = match jwk["kty"].as_str() {
+        Some("EC") => format!(
+            r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#,
+            field("crv")?,
+            field("x")?,
+            field("y")?
+        ),
+        Some("RSA") => format!(
+            r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#,
+            field("e")?,
+            field("n")?
+        ),
+        Some("OKP") => format!(
+            r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#,
+            field("crv")?,
+            field("x")?
+        ),
+        _ => return Err(malformed("jwk kty must be EC, RSA, or OKP")),
+    };
+    Ok(BASE64URL.encode(Sha256::digest(canonical.as_bytes())))
+}
+
+/// A random 128-bit ID, used for nonces, object IDs, and challenge tokens.
+///
+/// Fails rather than falling back to a predictable value if the OS RNG is
+/// unavailable.
+fn random_id() -> Result<String, AcmeServerError> {
+    let mut bytes = [0u8; 16];
+    OsRng
+        .try_fill_bytes(&mut bytes)
+        .map_err(|e| AcmeServerError::Internal {
+            reason: format!("OS RNG unavailable: {e}"),
+        })?;
+    Ok(BASE64URL.encode(bytes))
+}
+
+/// Verify a JWS signature made with `alg` by the key `jwk`.
+fn verify_signature(
+    alg: &str,
+    jwk: &Value,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), AcmeServerError> {
+    use p256::ecdsa::signature::Verifier as _;
+
+    let curve = |crv: &str, len: usize| -> Result<Vec<u8>, AcmeServerError> {
+        if jwk["kty"] != "EC" || jwk["crv"] != crv {
+            return Err(malformed(&format!("{alg} needs a {crv} EC key")));
+        }
+        let (x, y) = (jwk_field(jwk, "x")?, jwk_field(jwk, "y")?);
+        if x.len() != len || y.len() != len {
+            return Err(malformed("invalid EC key coordinates"));
+        }
+        Ok([&[0x04][..], &x, &y].concat())
+    };
+    let invalid_key = || malformed("invalid JWK");
+    let bad_signature = || malformed("JWS signature does not verify");
+
+    match alg {
+        "ES256" => {
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&curve("P-256", 32)?)
+                .map_err(|_| invalid_key())?;
+            let signature =
+                p256::ecdsa::Signature::from_slice(signature).map_err(|_| bad_signature())?;
+            key.verify(message, &signature).map_err(|_| bad_signature())
+        }
+        "ES384" => {
+            let key = p384::ecdsa::VerifyingKey::from_sec1_bytes(&curve("P-384", 48)?)
+                .map_err(|_| invalid_key())?;
+            let signature =
+                p384::ecdsa::Signature::from_slice(signature).map_err(|_| bad_signature())?;
+            key.verify(message, &signature).map_err(|_| bad_signature())
+        }
+        "RS256" => {
+            if jwk["kty"] != "RSA" {
+                return Err(malformed("RS256 needs an RSA key"));
+            }
+            let n = rsa::BigUint::from_bytes_be(&jwk_field(jwk, "n")?);
+            let e = rsa::BigUint::from_bytes_be(&jwk_field(jwk, "e")?);
+            if !(MIN_RSA_BITS..=MAX_RSA_BITS).contains(&n.bits()) {
+                return Err(malformed("RSA keys must be 2048 to 8192 bits"));
+            }
+            let key = rsa::RsaPublicKey::new_with_max_size(n, e, MAX_RSA_BITS)
+                .map_err(|_| invalid_key())?;
+            let signature =
+                rsa::pkcs1v15::Signature::try_from(signature).map_err(|_| bad_signature())?;
+            rsa::pkcs1v15::VerifyingKey::<Sha256>::new(key)
+                .verify(message, &signature)
+                .map_err(|_| bad_signature())
+        }
+        "EdDSA" => {
+            if jwk["kty"] != "OKP" || jwk["crv"] != "Ed25519" {
+                return Err(malformed("EdDSA needs an Ed25519 key"));
+            }
+            let key = <[u8; 32]>::try_from(jwk_field(jwk, "x")?.as_slice())
+                .ok()
+                .and_then(|x| ed25519_dalek::VerifyingKey::from_bytes(&x).ok())
+                .ok_or_else(invalid_key)?;
+            let signature =
+                ed25519_dalek::Signature::from_slice(signature).map_err(|_| bad_signature())?;
+            key.verify(message, &signature).map_err(|_| bad_signature())
+        }
+        _ => Err(AcmeServerError::BadSignatureAlgorithm {
+            alg: alg.to_owned(),
+        }),
+    }
+}
+
+/// Check one challenge of `domain`, returning a problem document on failure.
+async fn check_challenge(
+    config: &AcmeServerConfig,
+    domain: &str,
+    kind: &str,
+    token: &str,
+    key_authorization: &str,
+) -> Result<(), Value> {
+    match kind {
+        "http-01" => {
+            let url = format!("http://{domain}/.well-known/acme-challenge/{token}");
+            let client = reqwest::Client::builder()
+                .timeout(VALIDATION_TIMEOUT)
+                .redirect(reqwest::redirect::Policy::limited(10))
+                .build()
+                .map_err(|e| problem("serverInternal", &e.to_string()))?;
+            let response = client
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| problem("connection", &format!("fetching {url}: {e}")))?;
+            if !response.status().is_success() {
+                return Err(problem(
+                    "unauthorized",
+                    &format!("{url} returned {}", response.status()),
+                ));
+            }
+            let body = response
+                .text()
+                .await
+                .map_err(|e| problem("connection", &format!("reading {url}: {e}")))?;
+            if body.trim() != key_authorization {
+                return Err(problem(
+                    "incorrectResponse",
+                    &format!("{url} did not return the key authorization"),
+                ));
+            }
+            Ok(())
+        }
+        "dns-01" => {
+            let name = format!("_acme-challenge.{domain}");
+            let resolver = match &config.dns_resolver {
+                Some(resolver) => parse_resolver(resolver),
+                None => system_resolver(),
+            }
+            .map_err(|e| problem("serverInternal", &e.to_string()))?;
+            let records = lookup_txt(resolver, &name)
+                .await
+                .map_err(|e| problem("dns", &format!("looking up TXT {name}: {e}")))?;
+            let digest = BASE64URL.encode(Sha256::digest(key_authorization.as_bytes()));
+            if records.contains(&digest) {
+                Ok(())
+            } else {
+                Err(problem(
+                    "incorrectResponse",
+                    &format!("no TXT record of {name} has the expected digest"),
+                ))
+            }
+        }
+        _ => Err(problem("malformed", &format!("unknown challenge '{kind}'"))),
+    }
+}
+
+/// A resolver address; a bare IP means port 53.
+fn parse_resolver(resolver: &str) -> Result<SocketAddr, AcmeServerError> {
+    resolver
+        .parse::<SocketAddr>()
+        .or_else(|_| resolver.parse().map(|ip| SocketAddr::new(ip, 53)))
+        .map_err(|_| {
+            malformed(&format!(
+                "invalid dns_resolver '{resolver}', expected ip:port"
+            ))
+        })
+}
+
+/// The first `nameserver` of `/etc/resolv.conf`.
+fn system_resolver() -> Result<SocketAddr, AcmeServerError> {
+    let conf = std::fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
+    conf.lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .find_map(|ip| parse_resolver(ip.trim()).ok())
+        .ok_or_else(|| AcmeServerError::Internal {
+            reason: "no dns_resolver configured and none in /etc/resolv.conf".to_owned(),
+        })
+}
+
+/// The TXT records of `name`, asked of `resolver` over UDP.
+async fn lookup_txt(resolver: SocketAddr, name: &str) -> Result<Vec<String>, String> {
+    let mut id = [0u8; 2];
+    OsRng
+        .try_fill_bytes(&mut id)
+        .map_err(|_| "no randomness for the query ID".to_owned())?;
+
+    // Header: ID, recursion desired, one question.
+    let mut query = id.to_vec();
+    query.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.trim_end_matches('.').split('.') {
+        let len = u8::try_from(label.len())
+            .ok()
+            .filter(|len| (1..64).contains(len))
+            .ok_or_else(|| format!("invalid DNS name '{name}'"))?;
+        query.push(len);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.extend_from_slice(&[0, 0, 16, 0, 1]); // root, TXT, IN
+
+    let local = if resolver.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(local).await.map_err(|e| e.to_string())?;
+    socket.connect(resolver).await.map_err(|e| e.to_string())?;
+    socket.send(&query).await.map_err(|e| e.to_string())?;
+    let mut buf = [0u8; 4096];
+    let len = tokio::time::timeout(VALIDATION_TIMEOUT, socket.recv(&mut buf))
+        .await
+        .map_err(|_| format!("{resolver} did not answer"))?
+        .map_err(|e| e.to_string())?;
+    parse_txt_response(&buf[..len], id)
+}
+
+/// The TXT records in the answer section of a DNS response.
+fn parse_txt_response(message: &[u8], id: [u8; 2]) -> Result<Vec<String>, String> {
+    let malformed = || "malformed DNS response".to_owned();
+    let header = message.get(..12).ok_or_else(malformed)?;
+    if header[..2] != id {
+        return Err("DNS response ID does not match the query".to_owned());
+    }
+    if header[2] & 0x02 != 0 {
+        return Err("DNS response was truncated".to_owned());
+    }
+    match header[3] & 0x0F {
+        0 => {}
+        3 => return Ok(Vec::new()), // NXDOMAIN
+        rcode => return Err(format!("DNS server answered with rcode {rcode}")),
+    }
+    let questions = u16::from_be_bytes([header[4], header[5]]);
+    let answers = u16::from_be_bytes([header[6], header[7]]);
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(message, pos).ok_or_else(malformed)? + 4;
+    }
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(message, pos).ok_or_else(malformed)?;
+        let fixed = message.get(pos..pos + 10).ok_or_else(malformed)?;
+        let kind = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let len = usize::from(u16::from_be_bytes([fixed[8], fixed[9]]));
+        pos += 10;
+        let mut data = message.get(pos..pos + len).ok_or_else(malformed)?;
+        pos += len;
+        if kind != 16 {
+            continue;
+        }
+        // A TXT record is one or more length-prefixed strings, concatenated.
+        let mut text = Vec::new();
+        while let Some((&len, rest)) = data.split_first() {
+            let len = usize::from(len);
+            text.extend_from_slice(rest.get(..len).ok_or_else(malformed)?);
+            data = &rest[len..];
+        }
+        records.push(String::from_utf8_lossy(&text).into_owned());
+    }
+    Ok(records)
+}
+
+/// The position after the (possibly compressed) DNS name at `pos`.
+fn skip_name(message: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        match *message.get(pos)? {
+            0 => return Some(pos + 1),
+            len if len & 0xC0 == 0xC0 => return Some(pos + 2),
+            len => pos += usize::from(len) + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use p256::ecdsa::SigningKey;
+    use p256::ecdsa::signature::Signer as _;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use crate::pki::PkiRole;
+
+    const BASE: &str = "https://vault.test/v1/pki/acme";
+
+    /// Answers TXT queries from `records`; names it does not know get an
+    /// empty answer.
+    async fn fake_dns(records: Arc<Mutex<HashMap<String, String>>>) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let query = &buf[..len];
+                let end = skip_name(query, 12).unwrap();
+                let mut labels = Vec::new();
+                let mut pos = 12;
+                while query[pos] != 0 {
+                    let len = usize::from(query[pos]);
+                    labels.push(String::from_utf8_lossy(&query[pos + 1..=pos + len]).into_owned());
+                    pos += len + 1;
+                }
+                let txt = records.lock().await.get(&labels.join(".")).cloned();
+
+                let mut reply = query[..2].to_vec();
+                reply.extend_from_slice(&[
+                    0x81,
+                    0x80,
+                    0,
+                    1,
+                    0,
+                    u8::from(txt.is_some()),
+                    0,
+                    0,
+                    0,
+                    0,
+                ]);
+                reply.extend_from_slice(&query[12..end + 4]);
+                if let Some(txt) = txt {
+                    // Split in two strings to exercise concatenation.
+                    let (a, b) = txt.as_bytes().split_at(txt.len() / 2);
+                    let rdata = [
+                        &[u8::try_from(a.len()).unwrap()][..],
+                        a,
+                        &[u8::try_from(b.len()).unwrap()],
+                        b,
+                    ]
+                    .concat();
+                    reply.extend_from_slice(&[0xC0, 12, 0, 16, 0, 1, 0, 0, 0, 0]);
+                    reply.extend_from_slice(&u16::try_from(rdata.len()).unwrap().to_be_bytes());
+                    reply.extend_from_slice(&rdata);
+                }
+                let _ = socket.send_to(&reply, peer).await;
+            }
+        });
+        addr
+    }
+
+    struct Setup {
+        acme: Arc<AcmeServer>,
+        pki: Arc<PkiEngine>,
+        dns: Arc<Mutex<HashMap<String, String>>>,
+    }
+
+    async fn setup() -> Setup {
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Arc::new(Barrier::new(storage));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let pki = Arc::new(PkiEngine::new(Arc::clone(&barrier), "pki/".to_owned()));
+        pki.generate_root("Test CA", 24).await.unwrap();
+        pki.create_role(PkiRole {
+            name: "acme".to_owned(),
+            allowed_domains: vec!["example.com".to_owned()],
+            allow_subdomains: true,
+            max_ttl_hours: 24,
+            generate_key: false,
+            key_type: "any".to_owned(),
+            key_bits: 0,
+            allow_wildcard_certificates: true,
+            allow_ip_sans: false,
+            allowed_uri_sans: Vec::new(),
+            not_before_duration_secs: 30,
+        })
+        .await
+        .unwrap();
+
+        let dns = Arc::new(Mutex::new(HashMap::new()));
+        let resolver = fake_dns(Arc::clone(&dns)).await;
+        let mut acme = AcmeServer::new(
+            Arc::clone(&pki),
+            barrier,
+            "pki/acme/".to_owned(),
+            "/v1/pki/acme",
+        );
+        acme.retry_delay = Duration::ZERO;
+        acme.configure(AcmeServerConfig {
+            enabled: true,
+            base_url: "https://vault.test/".to_owned(),
+            role: "acme".to_owned(),
+            dns_resolver: Some(resolver.to_string()),
+        })
+        .await
+        .unwrap();
+        Setup {
+            acme: Arc::new(acme),
+            pki,
+            dns,
+        }
+    }
+
+    /// An ACME client key and, once registered, its account URL.
+    struct Client {
+        key: SigningKey,
+        jwk: Value,
+        kid: Option<String>,
+    }
+
+    impl Client {
+        fn new() -> Self {
+            let key = SigningKey::random(&mut OsRng);
+            let point = key.verifying_key().to_encoded_point(false);
+            let (x, y) = point.as_bytes()[1..].split_at(32);
+            let jwk = json!({
+                "kty": "EC", "crv": "P-256", "x": BASE64URL.encode(x), "y": BASE64URL.encode(y),
+            });
+            Self {
+                key,
+                jwk,
+                kid: None,
+            }
+        }
+
+        fn thumbprint(&self) -> String {
+            thumbprint(&self.jwk).unwrap()
+        }
+
+        async fn sign(&self, acme: &AcmeServer, path: &str, payload: Option<Value>) -> Vec<u8> {
+            let mut protected = json!({
+                "alg": "ES256",
+                "nonce": acme.new_nonce().await.unwrap(),
+                "url": format!("{BASE}/{path}"),
+            });
+            match &self.kid {
+                Some(kid) => protected["kid"] = json!(kid),
+                None => protected["jwk"] = self.jwk.clone(),
+            }
+            let protected = BASE64URL.encode(protected.to_string());
+            let payload = payload.map_or_else(String::new, |p| BASE64URL.encode(p.to_string()));
+            let signature: p256::ecdsa::Signature =
+                self.key.sign(format!("{protected}.{payload}").as_bytes());
+            json!({
+                "protected": protected,
+                "payload": payload,
+                "signature": BASE64URL.encode(signature.to_bytes()),
+            })
+            .to_string()
+            .into_bytes()
+        }
+
+        async fn register(&mut self, acme: &AcmeServer) {
+            let body = self
+                .sign(
+                    acme,
+                    "new-account",
+                    Some(json!({ "termsOfServiceAgreed": true })),
+                )
+                .await;
+            self.kid = acme.new_account(&body).await.unwrap().location;
+        }
+    }
+
+    fn id_of(url: &str) -> &str {
+        url.rsplit('/').next().unwrap()
+    }
+
+    /// A CSR for `names` with the first as its common name, as certbot
+    /// sends.
+    fn csr(names: &[&str]) -> String {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let owned: Vec<String> = names.iter().map(|n| (*n).to_owned()).collect();
+        let mut params = rcgen::CertificateParams::new(owned).unwrap();
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, names[0]);
+        BASE64URL.encode(params.serialize_request(&key).unwrap().der())
+    }
+
+    /// Publish the dns-01 record of each authorization, respond, and wait
+    /// for the authorizations to settle.
+    async fn solve(
+        setup: &Setup,
+        client: &Client,
+        order: &Value,
+        digest_of: impl Fn(&str) -> String,
+    ) -> Vec<String> {
+        let mut statuses = Vec::new();
+        for authz_url in order["authorizations"].as_array().unwrap() {
+            let path = format!("authz/{}", id_of(authz_url.as_str().unwrap()));
+            let body = client.sign(&setup.acme, &path, None).await;
+            let authz = setup
+                .acme
+                .authorization(id_of(&path), &body)
+                .await
+                .unwrap()
+                .body;
+            let challenge = authz["challenges"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|c| c["type"] == "dns-01")
+                .unwrap();
+            let token = challenge["token"].as_str().unwrap();
+            let key_authorization = format!("{token}.{}", client.thumbprint());
+            setup.dns.lock().await.insert(
+                format!(
+                    "_acme-challenge.{}",
+                    authz["identifier"]["value"].as_str().unwrap()
+                ),
+                digest_of(&key_authorization),
+            );
+
+            let chall_path = challenge["url"]
+                .as_str()
+                .unwrap()
+                .strip_prefix(&format!("{BASE}/"))
+                .unwrap()
+                .to_owned();
+            let body = client.sign(&setup.acme, &chall_path, Some(json!({}))).await;
+            let authz_id = id_of(&path).to_owned();
+            let response = setup
+                .acme
+                .challenge(&authz_id, "dns-01", &body)
+                .await
+                .unwrap();
+            assert_eq!(response.body["status"], "processing");
+
+            let mut status = String::new();
+            for _ in 0..200 {
+                let body = client.sign(&setup.acme, &path, None).await;
+                let authz = setup
+                    .acme
+                    .authorization(&authz_id, &body)
+                    .await
+                    .unwrap()
+                    .body;
+                status = authz["status"].as_str().unwrap().to_owned();
+                if status != "pending" {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            statuses.push(status);
+        }
+        statuses
+    }
+
+    fn digest(key_authorization: &str) -> String {
+        BASE64URL.encode(Sha256::digest(key_authorization.as_bytes()))
+    }
+
+    #[tokio::test]
+    async fn dns01_order_is_issued_and_revocable() {
+        let setup = setup().await;
+        let acme = &setup.acme;
+        let mut client = Client::new();
+
+        let body = client
+            .sign(
+                acme,
+                "new-account",
+                Some(json!({ "contact": ["mailto:ops@example.com"] })),
+            )
+            .await;
+        let created = acme.new_account(&body).await.unwrap();
+        assert!(created.created);
+        let body = client.sign(acme, "new-account", Some(json!({}))).await;
+        let existing = acme.new_account(&body).await.unwrap();
+        assert!(!existing.created);
+        assert_eq!(existing.location, created.location);
+        client.kid = created.location;
+
+        let identifiers = json!([
+            { "type": "dns", "value": "API.example.com" },
+            { "type": "dns", "value": "*.example.com" },
+        ]);
+        let body = client
+            .sign(
+                acme,
+                "new-order",
+                Some(json!({ "identifiers": identifiers })),
+            )
+            .await;
+        let response = acme.new_order(&body).await.unwrap();
+        let order = response.body;
+        let order_id = id_of(response.location.as_deref().unwrap()).to_owned();
+        assert_eq!(order["status"], "pending");
+        assert_eq!(order["authorizations"].as_array().unwrap().len(), 2);
+
+        let body = client
+            .sign(
+                acme,
+                &format!("order/{order_id}/finalize"),
+                Some(json!({ "csr": csr(&["api.example.com"]) })),
+            )
+            .await;
+        assert!(matches!(
+            acme.finalize(&order_id, &body).await,
+            Err(AcmeServerError::OrderNotReady { .. })
+        ));
+
+        assert_eq!(
+            solve(&setup, &client, &order, digest).await,
+            ["valid", "valid"]
+        );
+        let body = client.sign(acme, &format!("order/{order_id}"), None).await;
+        assert_eq!(
+            acme.order(&order_id, &body).await.unwrap().body["status"],
+            "ready"
+        );
+
+        // The CSR must name exactly the order's identifiers.
+        let body = client
+            .sign(
+                acme,
+                &format!("order/{order_id}/finalize"),
+                Some(json!({ "csr": csr(&["api.example.com"]) })),
+            )
+            .await;
+        assert!(matches!(
+            acme.finalize(&order_id, &body).await,
+            Err(AcmeServerError::BadCsr { .. })
+        ));
+
+        let body = client
+            .sign(
+                acme,
+                &format!("order/{order_id}/finalize"),
+                Some(json!({ "csr": csr(&["api.example.com", "*.example.com"]) })),
+            )
+            .await;
+        let (response, issued) = acme.finalize(&order_id, &body).await.unwrap();
+        assert_eq!(response.body["status"], "valid");
+        assert!(issued.private_key_pem.is_none());
+        let cert_url = response.body["certificate"].as_str().unwrap();
+
+        let cert_id = id_of(cert_url);
+        let body = client.sign(acme, &format!("cert/{cert_id}"), None).await;
+        let chain = acme.certificate(cert_id, &body).await.unwrap();
+        assert_eq!(chain.matches("BEGIN CERTIFICATE").count(), 2);
+
+        let leaf: String = issued
+            .certificate_pem
+            .lines()
+            .filter(|l| !l.starts_with("-----"))
+            .collect();
+        let der = BASE64.decode(leaf).unwrap();
+        let body = client
+            .sign(
+                acme,
+                "revoke-cert",
+                Some(json!({ "certificate": BASE64URL.encode(der) })),
+            )
+            .await;
+        acme.revoke(&body).await.unwrap();
+        assert_eq!(
+            setup.pki.list_revoked().await.unwrap(),
+            [issued.serial_number]
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_dns_record_invalidates_the_order() {
+        let setup = setup().await;
+        let acme = &setup.acme;
+        let mut client = Client::new();
+        client.register(acme).await;
+
+        let body = client
+            .sign(
+                acme,
+                "new-order",
+                Some(json!({ "identifiers": [{ "type": "dns", "value": "web.example.com" }] })),
+            )
+            .await;
+        let response = acme.new_order(&body).await.unwrap();
+        let order_id = id_of(response.location.as_deref().unwrap()).to_owned();
+
+        let statuses = solve(&setup, &client, &response.body, |_| "wrong".to_owned()).await;
+        assert_eq!(statuses, ["invalid"]);
+        let body = client.sign(acme, &format!("order/{order_id}"), None).await;
+        let order = acme.order(&order_id, &body).await.unwrap().body;
+        assert_eq!(order["status"], "invalid");
+        assert!(
+            order["error"]["type"]
+                .as_str()
+                .unwrap()
+                .starts_with(PROBLEM_PREFIX)
+        );
+    }
+
+    #[tokio::test]
+    async fn requests_are_checked_before_they_act() {
+        let setup = setup().await;
+        let acme = &setup.acme;
+        let mut alice = Client::new();
+        alice.register(acme).await;
+        let mut mallory = Client::new();
+        mallory.register(acme).await;
+
+        // A nonce is good for one request.
+        let body = alice
+            .sign(
+                acme,
+                "new-order",
+                Some(json!({ "identifiers": [{ "type": "dns", "value": "a.example.com" }] })),
+            )
+            .await;
+        let response = acme.new_order(&body).await.unwrap();
+        assert!(matches!(
+            acme.new_order(&body).await,
+            Err(AcmeServerError::BadNonce)
+        ));
+        let order_id = id_of(response.location.as_deref().unwrap()).to_owned();
+
+        // The signed URL must be the one requested.
+        let body = alice.sign(acme, "new-account", None).await;
+        assert!(matches!(
+            acme.order(&order_id, &body).await,
+            Err(AcmeServerError::Unauthorized { .. })
+        ));
+
+        // Another account cannot read the order.
+        let body = mallory.sign(acme, &format!("order/{order_id}"), None).await;
+        assert!(matches!(
+            acme.order(&order_id, &body).await,
+            Err(AcmeServerError::Unauthorized { .. })
+        ));
+
+        // The role decides which names can be ordered.
+        let body = alice
+            .sign(
+                acme,
+                "new-order",
+                Some(json!({ "identifiers": [{ "type": "dns", "value": "example.org" }] })),
+            )
+            .await;
+        assert!(matches!(
+            acme.new_order(&body).await,
+            Err(AcmeServerError::RejectedIdentifier { .. })
+        ));
+
+        // Orders need an account, not just a key.
+        let stranger = Client::new();
+        let body = stranger
+            .sign(
+                acme,
+                "new-order",
+                Some(json!({ "identifiers": [{ "type": "dns", "value": "a.example.com" }] })),
+            )
+            .await;
+        assert!(matches!(
+            acme.new_order(&body).await,
+            Err(AcmeServerError::Malformed { .. })
+        ));
+        let body = stranger
+            .sign(
+                acme,
+                "new-account",
+                Some(json!({ "onlyReturnExisting": true })),
+            )
+            .await;
+        assert!(matches!(
+            acme.new_account(&body).await,
+            Err(AcmeServerError::AccountDoesNotExist)
+        ));
+
+        // A tampered payload fails the signature check.
+        let body = alice
+            .sign(
+                acme,
+                "new-order",
+                Some(json!({ "identifiers": [{ "type": "dns", "value": "a.example.com" }] })),
+            )
+            .await;
+        let mut jws: Value = serde_json::from_slice(&body).unwrap();
+        jws["payload"] =
+            json!(BASE64URL.encode(r#"{"identifiers":[{"type":"dns","value":"b.example.com"}]}"#));
+        let body = jws.to_string().into_bytes();
+        assert!(matches!(
+            acme.new_order(&body).await,
+            Err(AcmeServerError::Malformed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn disabled_acme_answers_nothing() {
+        let setup = setup().await;
+        let mut config = setup.acme.config().await.unwrap().unwrap();
+        config.enabled = false;
+        setup.acme.configure(config).await.unwrap();
+        assert!(matches!(
+            setup.acme.directory().await,
+            Err(AcmeServerError::Disabled)
+        ));
+        assert!(setup.acme.directory_url().await.is_none());
+    }
+}
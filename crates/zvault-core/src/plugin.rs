@@ -0,0 +1,1282 @@
+//! External secrets engine plugins for `ZVault`.
+//!
+//! Third parties ship engines (`RabbitMQ` users, Azure AD app passwords, ...)
+//! as WebAssembly modules instead of patches to this crate. A plugin is
+//! registered in the [`PluginCatalog`] and mounted like a built-in engine;
+//! requests under its mount are dispatched to it through the
+//! [`SecretsEngine`] trait.
+//!
+//! [`ExternalPlugin`] runs the module in a wasmtime sandbox: the plugin has
+//! no filesystem, environment, or network access apart from the host calls
+//! below, its memory is capped, and every call is metered with fuel and
+//! bounded by a timeout. Plugins can be written in any language that
+//! compiles to `wasm32-unknown-unknown`. ABI version 1:
+//!
+//! - The module exports `memory`, `zvault_abi_version() -> i32` (which must
+//!   return `1`), `zvault_alloc(len: i32) -> i32`, and
+//!   `zvault_call(ptr: i32, len: i32) -> i64`.
+//! - The host writes each request into a buffer from `zvault_alloc` and
+//!   passes it to `zvault_call`, which returns its response buffer packed
+//!   as `ptr << 32 | len`. All buffers live in, and are owned by, the guest.
+//! - Requests are JSON `{method, params}`; responses are `{result}` or
+//!   `{error: {code, message}}`.
+//!
+//! Methods the host calls:
+//!
+//! - `initialize` `{abi_version, mount, config}`, once after instantiation.
+//! - `handle` `{operation, path, data}` → `{data, lease}`, where `operation`
+//!   is `read`, `create`, `update`, `delete`, or `list`, `path` is relative to
+//!   the mount, and the optional `lease` is `{ttl_secs, max_ttl_secs,
+//!   renewable, secret}`.
+//! - `revoke` `{lease_id, path, secret}`, when a lease the plugin issued
+//!   expires or is revoked. `secret` is the value from the lease.
+//!
+//! While a call is in flight the plugin may call back into the host through
+//! its `zvault.host_call(ptr: i32, len: i32) -> i64` import, using the same
+//! message shapes; the reply is written to a buffer from `zvault_alloc`:
+//!
+//! - `storage.get` `{key}` → `{value}`, `storage.put` `{key, value}`,
+//!   `storage.delete` `{key}`, and `storage.list` `{prefix}` → `{keys}`. Keys
+//!   are scoped to `plugins/<mount>` in the barrier, so plugin state is
+//!   encrypted at rest and a plugin cannot reach anything outside its mount.
+//! - `http.request` `{method, url, headers, body}` → `{status, body}`, to
+//!   the hosts in the catalog entry's `allowed_hosts` only. Redirects are
+//!   not followed.
+//! - `log` `{message}`, written to the server log.
+//!
+//! Error code `400` is an invalid request and `404` is not found, in both
+//! directions; any other code from a plugin is a plugin failure. The host
+//! refuses hosts outside `allowed_hosts` with `403` and reports its own
+//! failures as `500`.
+//!
+//! Catalog entries name a module relative to the plugin directory
+//! (`ZVAULT_PLUGIN_DIR`) together with its SHA-256, which is checked on
+//! registration and again before every instantiation. Without a plugin
+//! directory, plugins are disabled. A plugin that traps, runs out of fuel,
+//! or times out is discarded and instantiated afresh on the next request.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, OnceCell};
+use tracing::{info, warn};
+use wasmtime::{
+    AsContext, AsContextMut, Caller, Config, Extern, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap, TypedFunc,
+};
+
+use crate::barrier::Barrier;
+use crate::error::{LeaseError, PluginError};
+use crate::lease::{Lease, RevocationHandler};
+use crate::policy::Capability;
+use crate::registry::Engine;
+
+/// Plugin ABI version implemented by this host.
+pub const ABI_VERSION: i32 = 1;
+
+/// Mount table engine type of plugin mounts; the mount config's
+/// `plugin_name` names the catalog entry.
+pub const PLUGIN_ENGINE_TYPE: &str = "plugin";
+
+/// Storage prefix for catalog entries.
+const CATALOG_PREFIX: &str = "sys/plugins/catalog/";
+
+/// Storage prefix for plugin state; each mount gets `plugins/<mount>`.
+const STORAGE_PREFIX: &str = "plugins/";
+
+/// Default time a plugin has to answer one call, including its host calls.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest plugin name.
+const MAX_NAME_LEN: usize = 64;
+
+/// Largest linear memory a plugin may grow to.
+const MAX_MEMORY: usize = 64 << 20;
+
+/// Largest message read out of a plugin.
+const MAX_MESSAGE_LEN: usize = 16 << 20;
+
+/// Fuel one call may burn, roughly one unit per WebAssembly instruction.
+const FUEL_PER_CALL: u64 = 10_000_000_000;
+
+/// Fuel burned between yields to the async runtime, so a busy plugin
+/// cannot hold a worker thread past its timeout.
+const FUEL_YIELD_INTERVAL: u64 = 100_000;
+
+/// Error code a plugin uses to reject a request as invalid.
+pub const ERROR_INVALID_REQUEST: i64 = 400;
+
+/// Error code a plugin uses when nothing exists at a path.
+pub const ERROR_NOT_FOUND: i64 = 404;
+
+/// Error code the host answers `http.request` with for hosts outside the
+/// plugin's `allowed_hosts`.
+pub const ERROR_FORBIDDEN: i64 = 403;
+
+/// Error code the host answers a host call with when it fails itself.
+pub const ERROR_HOST: i64 = 500;
+
+/// A registered plugin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginEntry {
+    /// Catalog name, referenced by mounts.
+    pub name: String,
+    /// WebAssembly module path, relative to the plugin directory.
+    pub module: String,
+    /// Hex SHA-256 of the module.
+    pub sha256: String,
+    /// Hosts the plugin may reach through `http.request`.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// Optional description.
+    #[serde(default)]
+    pub description: String,
+    /// When the entry was last registered.
+    pub registered_at: DateTime<Utc>,
+}
+
+/// Parameters for registering a plugin.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RegisterPluginParams {
+    /// WebAssembly module path, relative to the plugin directory.
+    pub module: String,
+    /// Hex SHA-256 of the module.
+    pub sha256: String,
+    /// Hosts the plugin may reach through `http.request`.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// Optional description.
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Registered plugins, persisted through the barrier at
+/// `sys/plugins/catalog/<name>`.
+pub struct PluginCatalog {
+    barrier: Arc<Barrier>,
+    plugin_dir: Option<PathBuf>,
+    runtime: OnceCell<wasmtime::Engine>,
+}
+
+impl PluginCatalog {
+    /// Create a catalog whose modules live in `plugin_dir`.
+    ///
+    /// With no directory, entries can still be listed and deleted, but
+    /// registering and instantiating plugins fails with
+    /// [`PluginError::Disabled`].
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>, plugin_dir: Option<PathBuf>) -> Self {
+        Self {
+            barrier,
+            plugin_dir,
+            runtime: OnceCell::new(),
+        }
+    }
+
+    /// The configured plugin directory.
+    #[must_use]
+    pub fn plugin_dir(&self) -> Option<&Path> {
+        self.plugin_dir.as_deref()
+    }
+
+    /// Register a plugin, replacing any entry with the same name.
+    ///
+    /// # Errors
+    ///
+    /// - [`PluginError::Disabled`] if no plugin directory is configured.
+    /// - [`PluginError::Invalid`] if the name, module, checksum, or allowed
+    ///   hosts are malformed, or the module is not a valid WebAssembly
+    ///   module in the plugin directory.
+    /// - [`PluginError::ChecksumMismatch`] if the module's SHA-256 differs.
+    /// - [`PluginError::Barrier`] if storage fails.
+    pub async fn register(
+        &self,
+        name: &str,
+        params: RegisterPluginParams,
+    ) -> Result<PluginEntry, PluginError> {
+        validate_name(name)?;
+        let sha256 = params.sha256.trim().to_ascii_lowercase();
+        if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(PluginError::Invalid {
+                reason: "sha256 must be 64 hex characters".to_owned(),
+            });
+        }
+        let mut allowed_hosts = Vec::with_capacity(params.allowed_hosts.len());
+        for host in params.allowed_hosts {
+            let host = host.trim().to_ascii_lowercase();
+            if host.is_empty() || host.contains(['/', ':', '@']) {
+                return Err(PluginError::Invalid {
+                    reason: format!("allowed host '{host}' must be a bare host name"),
+                });
+            }
+            allowed_hosts.push(host);
+        }
+
+        let entry = PluginEntry {
+            name: name.to_owned(),
+            module: params.module,
+            sha256,
+            allowed_hosts,
+            description: params.description,
+            registered_at: Utc::now(),
+        };
+        self.module(&entry).await?;
+
+        let bytes = serde_json::to_vec(&entry).map_err(|e| PluginError::Invalid {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier
+            .put(&format!("{CATALOG_PREFIX}{name}"), &bytes)
+            .await?;
+
+        info!(plugin = %name, module = %entry.module, "plugin registered");
+        Ok(entry)
+    }
+
+    /// Look up a plugin by name.
+    ///
+    /// # Errors
+    ///
+    /// - [`PluginError::NotFound`] if no plugin has this name.
+    /// - [`PluginError::Barrier`] if storage fails.
+    pub async fn get(&self, name: &str) -> Result<PluginEntry, PluginError> {
+        let data = self
+            .barrier
+            .get(&format!("{CATALOG_PREFIX}{name}"))
+            .await?
+            .ok_or_else(|| PluginError::NotFound {
+                name: name.to_owned(),
+            })?;
+        serde_json::from_slice(&data).map_err(|e| PluginError::Invalid {
+            reason: format!("corrupt catalog entry '{name}': {e}"),
+        })
+    }
+
+    /// List all registered plugins, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::Barrier`] if storage fails.
+    pub async fn list(&self) -> Result<Vec<PluginEntry>, PluginError> {
+        let mut entries = Vec::new();
+        for key in self.barrier.list(CATALOG_PREFIX).await? {
+            let name = key.strip_prefix(CATALOG_PREFIX).unwrap_or(&key);
+            match self.get(name).await {
+                Ok(entry) => entries.push(entry),
+                Err(PluginError::NotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    /// Remove a plugin from the catalog.
+    ///
+    /// # Errors
+    ///
+    /// - [`PluginError::NotFound`] if no plugin has this name.
+    /// - [`PluginError::Barrier`] if storage fails.
+    pub async fn delete(&self, name: &str) -> Result<(), PluginError> {
+        self.get(name).await?;
+        self.barrier
+            .delete(&format!("{CATALOG_PREFIX}{name}"))
+            .await?;
+        info!(plugin = %name, "plugin deregistered");
+        Ok(())
+    }
+
+    /// Read an entry's module, check it against the registered SHA-256, and
+    /// compile it.
+    ///
+    /// # Errors
+    ///
+    /// - [`PluginError::Disabled`] if no plugin directory is configured.
+    /// - [`PluginError::Invalid`] if the module path escapes the plugin
+    ///   directory, is not a readable file, or does not compile.
+    /// - [`PluginError::ChecksumMismatch`] if the digest differs.
+    pub async fn module(&self, entry: &PluginEntry) -> Result<Module, PluginError> {
+        let dir = self.plugin_dir.as_deref().ok_or(PluginError::Disabled)?;
+        let relative = Path::new(&entry.module);
+        if entry.module.is_empty()
+            || !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(PluginError::Invalid {
+                reason: format!(
+                    "module '{}' must be a path inside the plugin directory",
+                    entry.module
+                ),
+            });
+        }
+
+        let bytes =
+            tokio::fs::read(dir.join(relative))
+                .await
+                .map_err(|e| PluginError::Invalid {
+                    reason: format!("cannot read plugin '{}': {e}", entry.module),
+                })?;
+        if hex::encode(Sha256::digest(&bytes)) != entry.sha256 {
+            return Err(PluginError::ChecksumMismatch {
+                name: entry.name.clone(),
+            });
+        }
+
+        // The digest covers the bytes that are compiled, so a module
+        // swapped after the check is never run.
+        let runtime = self.runtime().await?.clone();
+        let invalid = |reason: String| PluginError::Invalid {
+            reason: format!("plugin '{}' is not a valid module: {reason}", entry.module),
+        };
+        tokio::task::spawn_blocking(move || Module::new(&runtime, &bytes))
+            .await
+            .map_err(|e| invalid(e.to_string()))?
+            .map_err(|e| invalid(format!("{e:#}")))
+    }
+
+    /// The wasmtime engine shared by every plugin, created on first use.
+    async fn runtime(&self) -> Result<&wasmtime::Engine, PluginError> {
+        self.runtime
+            .get_or_try_init(|| async {
+                let mut config = Config::new();
+                config.async_support(true).consume_fuel(true);
+                wasmtime::Engine::new(&config).map_err(|e| PluginError::Invalid {
+                    reason: format!("cannot create the WebAssembly runtime: {e:#}"),
+                })
+            })
+            .await
+    }
+}
+
+/// Plugin names are lowercase letters, digits, `-` and `_`.
+fn validate_name(name: &str) -> Result<(), PluginError> {
+    if name.is_empty()
+        || name.len() > MAX_NAME_LEN
+        || !name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+    {
+        return Err(PluginError::Invalid {
+            reason: format!(
+                "plugin name must be 1-{MAX_NAME_LEN} lowercase letters, digits, '-' or '_'"
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Operation a request performs on a plugin path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginOperation {
+    /// Read a path.
+    Read,
+    /// Write a path that may not exist yet (`POST`).
+    Create,
+    /// Write an existing path (`PUT`).
+    Update,
+    /// Delete a path.
+    Delete,
+    /// List keys under a path.
+    List,
+}
+
+impl PluginOperation {
+    /// The policy capability the operation needs on its path.
+    #[must_use]
+    pub fn capability(self) -> Capability {
+        match self {
+            Self::Read => Capability::Read,
+            Self::Create => Capability::Create,
+            Self::Update => Capability::Update,
+            Self::Delete => Capability::Delete,
+            Self::List => Capability::List,
+        }
+    }
+}
+
+/// A request dispatched to a plugin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRequest {
+    /// What to do.
+    pub operation: PluginOperation,
+    /// Path relative to the mount (e.g., `creds/readonly`).
+    pub path: String,
+    /// Request body, for writes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// A plugin's answer to a request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginResponse {
+    /// Response data returned to the client.
+    #[serde(default)]
+    pub data: Option<Value>,
+    /// Set when the response holds a credential the plugin must revoke later.
+    #[serde(default)]
+    pub lease: Option<PluginLease>,
+}
+
+/// Lease terms for a credential a plugin issued.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginLease {
+    /// Time-to-live in seconds.
+    pub ttl_secs: i64,
+    /// Upper bound across renewals. `0` means no cap.
+    #[serde(default)]
+    pub max_ttl_secs: i64,
+    /// Whether the lease can be renewed.
+    #[serde(default)]
+    pub renewable: bool,
+    /// Opaque data handed back to the plugin on revocation; never shown to
+    /// clients.
+    #[serde(default)]
+    pub secret: Value,
+}
+
+/// A secrets engine mounted from the plugin catalog.
+#[async_trait::async_trait]
+pub trait SecretsEngine: Send + Sync {
+    /// Handle one request under the engine's mount.
+    ///
+    /// # Errors
+    ///
+    /// - [`PluginError::InvalidRequest`] or [`PluginError::PathNotFound`]
+    ///   if the engine rejects the request.
+    /// - Any other [`PluginError`] if the engine could not be reached.
+    async fn handle(&self, request: &PluginRequest) -> Result<PluginResponse, PluginError>;
+
+    /// Revoke a credential issued with a [`PluginLease`].
+    ///
+    /// Must be idempotent: failed revocations are retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PluginError`] if the credential could not be revoked.
+    async fn revoke(&self, lease_id: &str, path: &str, secret: &Value) -> Result<(), PluginError>;
+}
+
+/// A message in either direction across the plugin boundary.
+#[derive(Serialize, Deserialize)]
+struct Call {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Parameters of an `http.request` host call.
+#[derive(Deserialize)]
+struct HttpRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// What a plugin's store carries: everything its host calls may touch.
+struct HostState {
+    barrier: Arc<Barrier>,
+    plugin: String,
+    mount: String,
+    allowed_hosts: Vec<String>,
+    http: reqwest::Client,
+    limits: StoreLimits,
+}
+
+impl HostState {
+    fn new(
+        barrier: Arc<Barrier>,
+        plugin: &str,
+        mount: &str,
+        allowed_hosts: Vec<String>,
+        timeout: Duration,
+    ) -> Result<Self, reqwest::Error> {
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(timeout)
+            .build()?;
+        Ok(Self {
+            barrier,
+            plugin: plugin.to_owned(),
+            mount: mount.to_owned(),
+            allowed_hosts,
+            http,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY)
+                .instances(1)
+                .build(),
+        })
+    }
+
+    /// Serve one host call from the plugin.
+    async fn host_call(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "storage.get" | "storage.put" | "storage.delete" | "storage.list" => {
+                self.storage(method, params).await
+            }
+            "http.request" => self.http_request(params).await,
+            "log" => {
+                let message = params
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                info!(plugin = %self.plugin, mount = %self.mount, "{message}");
+                Ok(Value::Null)
+            }
+            other => Err((ERROR_INVALID_REQUEST, format!("unknown method '{other}'"))),
+        }
+    }
+
+    /// Serve a `storage.*` call against the mount's barrier prefix.
+    async fn storage(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        let prefix = format!("{STORAGE_PREFIX}{}", self.mount);
+        let key = |field: &str| -> Result<String, (i64, String)> {
+            let key = params
+                .get(field)
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            if key.starts_with('/') || key.split('/').any(|part| part == "..") {
+                return Err((
+                    ERROR_INVALID_REQUEST,
+                    format!("invalid storage {field} '{key}'"),
+                ));
+            }
+            Ok(format!("{prefix}{key}"))
+        };
+        let failed = |e: crate::error::BarrierError| (ERROR_HOST, e.to_string());
+
+        match method {
+            "storage.get" => {
+                let value = match self.barrier.get(&key("key")?).await.map_err(failed)? {
+                    Some(bytes) => serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+                    None => Value::Null,
+                };
+                Ok(json!({ "value": value }))
+            }
+            "storage.put" => {
+                let value = params.get("value").cloned().unwrap_or(Value::Null);
+                self.barrier
+                    .put(&key("key")?, value.to_string().as_bytes())
+                    .await
+                    .map_err(failed)?;
+                Ok(Value::Null)
+            }
+            "storage.delete" => {
+                self.barrier.delete(&key("key")?).await.map_err(failed)?;
+                Ok(Value::Null)
+            }
+            _ => {
+                let keys: Vec<String> = self
+                    .barrier
+                    .list(&key("prefix")?)
+                    .await
+                    .map_err(failed)?
+                    .into_iter()
+                    .filter_map(|k| k.strip_prefix(&prefix).map(str::to_owned))
+                    .collect();
+                Ok(json!({ "keys": keys }))
+            }
+        }
+    }
+
+    /// Serve an `http.request` call to one of the plugin's allowed hosts.
+    async fn http_request(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let invalid = |reason: String| (ERROR_INVALID_REQUEST, reason);
+        let request: HttpRequest = serde_json::from_value(params.clone())
+            .map_err(|e| invalid(format!("invalid http.request: {e}")))?;
+        let url = url::Url::parse(&request.url)
+            .map_err(|e| invalid(format!("invalid url '{}': {e}", request.url)))?;
+        let host = url.host_str().unwrap_or_default();
+        if !matches!(url.scheme(), "http" | "https")
+            || !self
+                .allowed_hosts
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(host))
+        {
+            return Err((
+                ERROR_FORBIDDEN,
+                format!("'{}' is not an allowed host of this plugin", request.url),
+            ));
+        }
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .map_err(|e| invalid(format!("invalid method '{}': {e}", request.method)))?;
+
+        let mut builder = self.http.request(method, url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| (ERROR_HOST, e.to_string()))?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| (ERROR_HOST, e.to_string()))?;
+        Ok(json!({ "status": status, "body": body }))
+    }
+}
+
+/// An instantiated plugin module and the guest exports the host calls.
+struct PluginInstance {
+    store: Store<HostState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    call: TypedFunc<(i32, i32), i64>,
+}
+
+impl PluginInstance {
+    /// Pass one request to `zvault_call` and read back the response.
+    async fn invoke(&mut self, request: &[u8]) -> wasmtime::Result<Vec<u8>> {
+        let (ptr, len) = write_guest(&mut self.store, self.memory, &self.alloc, request).await?;
+        let packed = self.call.call_async(&mut self.store, (ptr, len)).await?;
+        let (ptr, len) = unpack(packed)?;
+        read_guest(&self.store, self.memory, ptr, len)
+    }
+}
+
+/// Copy `bytes` into a fresh guest buffer.
+async fn write_guest(
+    mut store: impl AsContextMut<Data = HostState>,
+    memory: Memory,
+    alloc: &TypedFunc<i32, i32>,
+    bytes: &[u8],
+) -> wasmtime::Result<(i32, i32)> {
+    let len = i32::try_from(bytes.len())?;
+    let ptr = alloc.call_async(&mut store, len).await?;
+    memory.write(&mut store, usize::try_from(ptr)?, bytes)?;
+    Ok((ptr, len))
+}
+
+/// Copy a guest buffer out of linear memory.
+fn read_guest(
+    store: &impl AsContext<Data = HostState>,
+    memory: Memory,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<Vec<u8>> {
+    let start = usize::try_from(ptr)?;
+    let len = usize::try_from(len)?;
+    if len > MAX_MESSAGE_LEN {
+        return Err(wasmtime::Error::msg(format!(
+            "message of {len} bytes exceeds the {MAX_MESSAGE_LEN}-byte limit"
+        )));
+    }
+    start
+        .checked_add(len)
+        .and_then(|end| memory.data(store).get(start..end))
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| wasmtime::Error::msg("message lies outside guest memory"))
+}
+
+fn pack(ptr: i32, len: i32) -> i64 {
+    (i64::from(ptr) << 32) | i64::from(len)
+}
+
+fn unpack(packed: i64) -> wasmtime::Result<(i32, i32)> {
+    Ok((
+        i32::try_from(packed >> 32)?,
+        i32::try_from(packed & 0xFFFF_FFFF)?,
+    ))
+}
+
+/// The `zvault.host_call` import: decode the plugin's call, serve it, and
+/// hand the reply back in a guest buffer.
+async fn host_call(mut caller: Caller<'_, HostState>, ptr: i32, len: i32) -> wasmtime::Result<i64> {
+    let export = |caller: &mut Caller<'_, HostState>, name: &str| {
+        caller
+            .get_export(name)
+            .ok_or_else(|| wasmtime::Error::msg(format!("plugin does not export '{name}'")))
+    };
+    let memory = export(&mut caller, "memory")?
+        .into_memory()
+        .ok_or_else(|| wasmtime::Error::msg("'memory' is not a memory"))?;
+    let alloc = export(&mut caller, "zvault_alloc")?
+        .into_func()
+        .ok_or_else(|| wasmtime::Error::msg("'zvault_alloc' is not a function"))?
+        .typed::<i32, i32>(&caller)?;
+
+    let request = read_guest(&caller, memory, ptr, len)?;
+    let reply = match serde_json::from_slice::<Call>(&request) {
+        Ok(call) => caller.data().host_call(&call.method, &call.params).await,
+        Err(e) => Err((ERROR_INVALID_REQUEST, format!("invalid host call: {e}"))),
+    };
+    let reply = match reply {
+        Ok(result) => json!({ "result": result }),
+        Err((code, message)) => json!({ "error": { "code": code, "message": message } }),
+    };
+    let (ptr, len) = write_guest(&mut caller, memory, &alloc, reply.to_string().as_bytes()).await?;
+    Ok(pack(ptr, len))
+}
+
+/// A plugin mounted at one path, run in its own wasmtime store.
+///
+/// Calls are serialized per mount. The module is instantiated on first use
+/// and instantiated afresh after it traps, runs out of fuel, or times out.
+pub struct ExternalPlugin {
+    catalog: Arc<PluginCatalog>,
+    barrier: Arc<Barrier>,
+    name: String,
+    mount: String,
+    config: Value,
+    timeout: Duration,
+    instance: Mutex<Option<PluginInstance>>,
+}
+
+impl ExternalPlugin {
+    /// Create the engine for catalog plugin `name` mounted at `mount`.
+    ///
+    /// `config` is the mount's configuration, passed to the plugin in
+    /// `initialize`. Nothing is instantiated until [`start`](Self::start) or
+    /// the first request.
+    #[must_use]
+    pub fn new(
+        catalog: Arc<PluginCatalog>,
+        barrier: Arc<Barrier>,
+        name: &str,
+        mount: &str,
+        config: Value,
+    ) -> Self {
+        Self {
+            catalog,
+            barrier,
+            name: name.to_owned(),
+            mount: mount.to_owned(),
+            config,
+            timeout: DEFAULT_TIMEOUT,
+            instance: Mutex::new(None),
+        }
+    }
+
+    /// Override how long the plugin has to answer one call.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The catalog name of the plugin.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The mount path the plugin serves.
+    #[must_use]
+    pub fn mount(&self) -> &str {
+        &self.mount
+    }
+
+    /// Instantiate the plugin now, so a broken plugin fails at mount time
+    /// rather than on its first request.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PluginError`] if the plugin cannot be verified,
+    /// instantiated, or initialized.
+    pub async fn start(&self) -> Result<(), PluginError> {
+        let mut guard = self.instance.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.launch().await?);
+        }
+        Ok(())
+    }
+
+    /// Drop the plugin instance. The next request instantiates it again,
+    /// picking up a re-registered module.
+    pub async fn stop(&self) {
+        self.instance.lock().await.take();
+    }
+
+    /// Verify, instantiate, and initialize the plugin.
+    async fn launch(&self) -> Result<PluginInstance, PluginError> {
+        let entry = self.catalog.get(&self.name).await?;
+        let module = self.catalog.module(&entry).await?;
+        let mut instance = tokio::time::timeout(self.timeout, self.instantiate(&entry, &module))
+            .await
+            .map_err(|_| self.timed_out())?
+            .map_err(|e| self.launch_failed(&e))?;
+
+        self.exchange(
+            &mut instance,
+            "initialize",
+            json!({
+                "abi_version": ABI_VERSION,
+                "mount": self.mount,
+                "config": self.config,
+            }),
+        )
+        .await
+        .map_err(|e| PluginError::Launch {
+            name: self.name.clone(),
+            reason: e.to_string(),
+        })?;
+
+        info!(plugin = %self.name, mount = %self.mount, "plugin started");
+        Ok(instance)
+    }
+
+    /// Create the plugin's store, link its imports, and check its ABI.
+    async fn instantiate(
+        &self,
+        entry: &PluginEntry,
+        module: &Module,
+    ) -> wasmtime::Result<PluginInstance> {
+        let state = HostState::new(
+            Arc::clone(&self.barrier),
+            &self.name,
+            &self.mount,
+            entry.allowed_hosts.clone(),
+            self.timeout,
+        )?;
+        let mut store = Store::new(module.engine(), state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+        store.fuel_async_yield_interval(Some(FUEL_YIELD_INTERVAL))?;
+
+        let mut linker = Linker::new(module.engine());
+        linker.func_wrap_async(
+            "zvault",
+            "host_call",
+            |caller: Caller<'_, HostState>, (ptr, len): (i32, i32)| {
+                Box::new(host_call(caller, ptr, len))
+            },
+        )?;
+        let instance = linker.instantiate_async(&mut store, module).await?;
+
+        let version = instance
+            .get_typed_func::<(), i32>(&mut store, "zvault_abi_version")?
+            .call_async(&mut store, ())
+            .await?;
+        if version != ABI_VERSION {
+            return Err(wasmtime::Error::msg(format!(
+                "unsupported ABI version {version}, expected {ABI_VERSION}"
+            )));
+        }
+        let memory = instance
+            .get_export(&mut store, "memory")
+            .and_then(Extern::into_memory)
+            .ok_or_else(|| wasmtime::Error::msg("module does not export 'memory'"))?;
+        let alloc = instance.get_typed_func(&mut store, "zvault_alloc")?;
+        let call = instance.get_typed_func(&mut store, "zvault_call")?;
+        Ok(PluginInstance {
+            store,
+            memory,
+            alloc,
+            call,
+        })
+    }
+
+    /// Call `method` on the plugin, instantiating it first if needed.
+    async fn call(&self, method: &str, params: Value) -> Result<Value, PluginError> {
+        let mut guard = self.instance.lock().await;
+        let mut instance = match guard.take() {
+            Some(instance) => instance,
+            None => self.launch().await?,
+        };
+
+        let result = self.exchange(&mut instance, method, params).await;
+        match &result {
+            Err(PluginError::Protocol { .. } | PluginError::Timeout { .. }) => {
+                warn!(plugin = %self.name, mount = %self.mount, "discarding misbehaving plugin");
+            }
+            _ => *guard = Some(instance),
+        }
+        result
+    }
+
+    /// Send one request, serving the plugin's host calls until it answers.
+    async fn exchange(
+        &self,
+        instance: &mut PluginInstance,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, PluginError> {
+        let request = serde_json::to_vec(&Call {
+            method: method.to_owned(),
+            params,
+        })
+        .map_err(|e| self.protocol(e.to_string()))?;
+        instance
+            .store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| self.protocol(format!("{e:#}")))?;
+
+        let response = tokio::time::timeout(self.timeout, instance.invoke(&request))
+            .await
+            .map_err(|_| self.timed_out())?
+            .map_err(|e| self.trapped(&e))?;
+        let response: Value = serde_json::from_slice(&response)
+            .map_err(|e| self.protocol(format!("invalid message: {e}")))?;
+        if let Some(error) = response.get("error") {
+            return Err(self.rpc_error(error));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    fn protocol(&self, reason: String) -> PluginError {
+        PluginError::Protocol {
+            name: self.name.clone(),
+            reason,
+        }
+    }
+
+    fn timed_out(&self) -> PluginError {
+        PluginError::Timeout {
+            name: self.name.clone(),
+        }
+    }
+
+    fn launch_failed(&self, error: &wasmtime::Error) -> PluginError {
+        PluginError::Launch {
+            name: self.name.clone(),
+            reason: format!("{error:#}"),
+        }
+    }
+
+    /// Map a failed call into the guest. Running out of fuel is a timeout;
+    /// any other trap breaks the protocol.
+    fn trapped(&self, error: &wasmtime::Error) -> PluginError {
+        if error.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) {
+            return self.timed_out();
+        }
+        self.protocol(format!("{error:#}"))
+    }
+
+    /// Map an error object from the plugin.
+    fn rpc_error(&self, error: &Value) -> PluginError {
+        let reason = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error")
+            .to_owned();
+        match error.get("code").and_then(Value::as_i64) {
+            Some(ERROR_INVALID_REQUEST) => PluginError::InvalidRequest { reason },
+            Some(ERROR_NOT_FOUND) => PluginError::PathNotFound { reason },
+            _ => PluginError::Failed {
+                name: self.name.clone(),
+                reason,
+            },
+        }
+    }
+}
+
+impl Engine for ExternalPlugin {
+    fn engine_type(&self) -> &'static str {
+        PLUGIN_ENGINE_TYPE
+    }
+
+    fn storage_prefix(&self) -> String {
+        format!("{STORAGE_PREFIX}{}", self.mount)
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl SecretsEngine for ExternalPlugin {
+    async fn handle(&self, request: &PluginRequest) -> Result<PluginResponse, PluginError> {
+        let params = serde_json::to_value(request).map_err(|e| self.protocol(e.to_string()))?;
+        let result = self.call("handle", params).await?;
+        let response: PluginResponse = serde_json::from_value(result)
+            .map_err(|e| self.protocol(format!("invalid handle result: {e}")))?;
+        if response
+            .lease
+            .as_ref()
+            .is_some_and(|lease| lease.ttl_secs <= 0)
+        {
+            return Err(self.protocol("lease ttl_secs must be positive".to_owned()));
+        }
+        Ok(response)
+    }
+
+    async fn revoke(&self, lease_id: &str, path: &str, secret: &Value) -> Result<(), PluginError> {
+        self.call(
+            "revoke",
+            json!({ "lease_id": lease_id, "path": path, "secret": secret }),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl RevocationHandler for ExternalPlugin {
+    async fn revoke_lease(&self, lease: &Lease) -> Result<(), LeaseError> {
+        let path = lease
+            .engine_path
+            .strip_prefix(&self.mount)
+            .unwrap_or(&lease.engine_path);
+        let secret = lease.data.get("secret").cloned().unwrap_or(Value::Null);
+        self.revoke(&lease.id, path, &secret)
+            .await
+            .map_err(|e| LeaseError::RevocationFailed {
+                lease_id: lease.id.clone(),
+                reason: e.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fmt::Write as _;
+
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    /// Byte strings the test plugin keeps in its data segment.
+    const SEGMENTS: [&str; 6] = [
+        r#"{"result":null}"#,
+        r#"{"error":{"code":404,"message":"no such role"}}"#,
+        r#"{"method":"storage.put","params":{"key":"seen","value":"yes"}}"#,
+        r#"{"result":{"data":{"user":"u1"},"lease":{"ttl_secs":60,"secret":{"user":"u1"}}}}"#,
+        "missing",
+        "spin",
+    ];
+
+    /// A plugin in the WebAssembly text format. It answers everything but
+    /// `handle` with a null result. `handle` stores `seen` through
+    /// `storage.put` and issues a leased credential, except that paths
+    /// containing `missing` are not found and `spin` never returns.
+    fn plugin_wat() -> String {
+        let mut data = String::new();
+        let mut at = Vec::new();
+        let mut offset = 0;
+        for text in SEGMENTS {
+            let _ = writeln!(
+                data,
+                r#"  (data (i32.const {offset}) "{}")"#,
+                text.replace('"', "\\\"")
+            );
+            at.push(format!("(i32.const {offset}) (i32.const {})", text.len()));
+            offset += text.len();
+        }
+        let [null, not_found, store_seen, issued, missing, spin] = at.as_slice() else {
+            return String::new();
+        };
+
+        format!(
+            r#"(module
+  (import "zvault" "host_call" (func $host_call (param i32 i32) (result i64)))
+  (memory (export "memory") 2)
+  (global $heap (mut i32) (i32.const 4096))
+{data}
+  (func (export "zvault_abi_version") (result i32) (i32.const 1))
+  (func (export "zvault_alloc") (param $len i32) (result i32)
+    (global.get $heap)
+    (global.set $heap (i32.add (global.get $heap) (local.get $len))))
+  (func $reply (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len))))
+  (func $contains (param $p i32) (param $n i32) (param $q i32) (param $m i32) (result i32)
+    (local $i i32) (local $j i32)
+    (block $none
+      (loop $outer
+        (br_if $none (i32.gt_u (i32.add (local.get $i) (local.get $m)) (local.get $n)))
+        (local.set $j (i32.const 0))
+        (block $mismatch
+          (loop $inner
+            (if (i32.eq (local.get $j) (local.get $m)) (then (return (i32.const 1))))
+            (br_if $mismatch
+              (i32.ne
+                (i32.load8_u (i32.add (local.get $p) (i32.add (local.get $i) (local.get $j))))
+                (i32.load8_u (i32.add (local.get $q) (local.get $j)))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (br $inner)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $outer)))
+    (i32.const 0))
+  (func (export "zvault_call") (param $ptr i32) (param $len i32) (result i64)
+    ;; Requests start with {{"method":" so the method name begins at 11.
+    (if (i32.ne (i32.load8_u offset=11 (local.get $ptr)) (i32.const 104))
+      (then (return (call $reply {null}))))
+    (if (call $contains (local.get $ptr) (local.get $len) {missing})
+      (then (return (call $reply {not_found}))))
+    (if (call $contains (local.get $ptr) (local.get $len) {spin})
+      (then (loop $forever (br $forever))))
+    (drop (call $host_call {store_seen}))
+    (call $reply {issued})))
+"#
+        )
+    }
+
+    async fn setup() -> (Arc<PluginCatalog>, Arc<Barrier>, PathBuf, String) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let dir = std::env::temp_dir().join(format!("zvault-plugins-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let module = plugin_wat();
+        std::fs::write(dir.join("echo.wat"), &module).unwrap();
+        let digest = hex::encode(Sha256::digest(module.as_bytes()));
+        let catalog = Arc::new(PluginCatalog::new(Arc::clone(&barrier), Some(dir.clone())));
+        (catalog, barrier, dir, digest)
+    }
+
+    fn params(sha256: String) -> RegisterPluginParams {
+        RegisterPluginParams {
+            module: "echo.wat".to_owned(),
+            sha256,
+            ..RegisterPluginParams::default()
+        }
+    }
+
+    fn read(path: &str) -> PluginRequest {
+        PluginRequest {
+            operation: PluginOperation::Read,
+            path: path.to_owned(),
+            data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn register_checks_module_and_checksum() {
+        let (catalog, _barrier, dir, digest) = setup().await;
+
+        let wrong = catalog.register("echo", params("0".repeat(64))).await;
+        assert!(matches!(wrong, Err(PluginError::ChecksumMismatch { .. })));
+
+        let mut escape = params(digest.clone());
+        escape.module = "../echo.wat".to_owned();
+        assert!(matches!(
+            catalog.register("echo", escape).await,
+            Err(PluginError::Invalid { .. })
+        ));
+        assert!(matches!(
+            catalog.register("Bad/Name", params(digest.clone())).await,
+            Err(PluginError::Invalid { .. })
+        ));
+        let mut url_host = params(digest.clone());
+        url_host.allowed_hosts = vec!["https://rabbitmq.internal".to_owned()];
+        assert!(matches!(
+            catalog.register("echo", url_host).await,
+            Err(PluginError::Invalid { .. })
+        ));
+
+        // A file that matches its digest but is not a module is refused.
+        std::fs::write(dir.join("junk.wasm"), b"not wasm").unwrap();
+        let junk = RegisterPluginParams {
+            module: "junk.wasm".to_owned(),
+            sha256: hex::encode(Sha256::digest(b"not wasm")),
+            ..RegisterPluginParams::default()
+        };
+        assert!(matches!(
+            catalog.register("junk", junk).await,
+            Err(PluginError::Invalid { .. })
+        ));
+
+        catalog.register("echo", params(digest)).await.unwrap();
+        assert_eq!(catalog.list().await.unwrap().len(), 1);
+
+        // A swapped module is refused before it is compiled.
+        std::fs::write(dir.join("echo.wat"), format!("{}\n", plugin_wat())).unwrap();
+        let entry = catalog.get("echo").await.unwrap();
+        assert!(matches!(
+            catalog.module(&entry).await,
+            Err(PluginError::ChecksumMismatch { .. })
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn disabled_without_plugin_dir() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let catalog = PluginCatalog::new(barrier, None);
+        let result = catalog.register("echo", params("0".repeat(64))).await;
+        assert!(matches!(result, Err(PluginError::Disabled)));
+    }
+
+    #[tokio::test]
+    async fn handles_requests_with_scoped_storage() {
+        let (catalog, barrier, dir, digest) = setup().await;
+        catalog.register("echo", params(digest)).await.unwrap();
+
+        let engine = ExternalPlugin::new(
+            catalog,
+            Arc::clone(&barrier),
+            "echo",
+            "rabbitmq/",
+            Value::Null,
+        )
+        .with_timeout(Duration::from_secs(5));
+        engine.start().await.unwrap();
+
+        let response = engine.handle(&read("creds/app")).await.unwrap();
+        assert_eq!(response.data, Some(json!({ "user": "u1" })));
+        assert_eq!(response.lease.unwrap().ttl_secs, 60);
+        assert_eq!(
+            barrier.get("plugins/rabbitmq/seen").await.unwrap().unwrap(),
+            b"\"yes\""
+        );
+
+        let missing = engine.handle(&read("missing")).await;
+        assert!(matches!(missing, Err(PluginError::PathNotFound { .. })));
+
+        // A stopped plugin is instantiated again on the next call.
+        engine.stop().await;
+        engine
+            .revoke("lease-1", "creds/app", &json!({ "user": "u1" }))
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn runaway_plugins_time_out_and_are_replaced() {
+        let (catalog, barrier, dir, digest) = setup().await;
+        catalog.register("echo", params(digest)).await.unwrap();
+        let engine = ExternalPlugin::new(catalog, barrier, "echo", "rabbitmq/", Value::Null)
+            .with_timeout(Duration::from_millis(200));
+
+        let spin = engine.handle(&read("spin")).await;
+        assert!(matches!(spin, Err(PluginError::Timeout { .. })));
+        assert!(engine.handle(&read("creds/app")).await.is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn host_calls_stay_inside_the_sandbox() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let state = HostState::new(
+            barrier,
+            "echo",
+            "rabbitmq/",
+            vec!["rabbitmq.internal".to_owned()],
+            DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+
+        for url in ["https://example.com/", "file:///etc/passwd"] {
+            let request = json!({ "method": "GET", "url": url });
+            let (code, _) = state.host_call("http.request", &request).await.unwrap_err();
+            assert_eq!(code, ERROR_FORBIDDEN);
+        }
+        let escape = json!({ "key": "../../sys/token/root" });
+        let (code, _) = state.host_call("storage.get", &escape).await.unwrap_err();
+        assert_eq!(code, ERROR_INVALID_REQUEST);
+        let (code, _) = state.host_call("exec", &Value::Null).await.unwrap_err();
+        assert_eq!(code, ERROR_INVALID_REQUEST);
+    }
+}
@@ -11,9 +11,26 @@
 //!
 //! `deny` always wins over other capabilities.
 //!
-//! Two built-in policies exist:
+//! Delegated admin: `sudo` on a mount's subtree (e.g. `transit-teamA/**`)
+//! lets the holder manage that mount — its keys, roles, and configs, and its
+//! mount entry — without any `sys/` grant. See [`PolicyStore::check_mount`].
+//!
+//! Control groups: a rule may carry a `control_group` block. A request that
+//! such a rule grants is refused with [`PolicyError::ControlGroupRequired`]
+//! until enough approvers sign off on it; see [`crate::control_group`]. The
+//! block applies even when another rule grants the same request freely.
+//!
+//! Step-up MFA: a rule may list `mfa_methods`. A request that such a rule
+//! grants is refused with [`PolicyError::MfaRequired`] unless each method was
+//! validated for it (see [`crate::mfa`]). Like control groups, the
+//! requirement holds even when another rule grants the request freely.
+//!
+//! Three built-in policies exist:
 //! - `root`: grants all capabilities on all paths (attached to root token).
-//! - `default`: grants basic self-management (token lookup/renew).
+//! - `default`: grants basic self-management (token lookup/renew) and use
+//!   of the token's own cubbyhole.
+//! - `mcp-audit`: opt-in; lets a client such as the MCP server report the
+//!   actions it takes to the audit log (`sys/audit-log/external`).
 
 use std::sync::Arc;
 
@@ -21,11 +38,16 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use crate::barrier::Barrier;
+use crate::control_group;
 use crate::error::PolicyError;
+use crate::mfa;
 
 /// Storage prefix for policy documents.
 const POLICY_PREFIX: &str = "sys/policies/";
 
+/// Policies every vault has, which cannot be written or deleted.
+const BUILTIN_POLICIES: [&str; 3] = ["root", "default", "mcp-audit"];
+
 /// A policy document containing access rules.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Policy {
@@ -42,6 +64,37 @@ pub struct PolicyRule {
     pub path: String,
     /// Allowed capabilities on this path.
     pub capabilities: Vec<Capability>,
+    /// Approvals required before a request this rule grants may run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control_group: Option<ControlGroup>,
+    /// MFA methods that must be validated for requests this rule grants.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mfa_methods: Vec<String>,
+}
+
+/// Multi-party approval required by a [`PolicyRule`].
+///
+/// An approver qualifies by holding one of `approver_policies` or by being
+/// one of the `approver_identities` entities. The requester never counts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlGroup {
+    /// Number of distinct approvers needed.
+    pub approvals: u32,
+    /// Policies whose holders may approve.
+    #[serde(default)]
+    pub approver_policies: Vec<String>,
+    /// Identity entity IDs that may approve.
+    #[serde(default)]
+    pub approver_identities: Vec<String>,
+}
+
+impl ControlGroup {
+    /// Whether an approver with these policies and entity qualifies.
+    #[must_use]
+    pub fn accepts(&self, policies: &[String], entity_id: Option<&str>) -> bool {
+        policies.iter().any(|p| self.approver_policies.contains(p))
+            || entity_id.is_some_and(|id| self.approver_identities.iter().any(|a| a == id))
+    }
 }
 
 /// An access capability.
@@ -80,11 +133,12 @@ impl PolicyStore {
     ///
     /// # Errors
     ///
-    /// - [`PolicyError::BuiltIn`] if trying to modify `root` or `default`.
-    /// - [`PolicyError::Invalid`] if the policy has no rules.
+    /// - [`PolicyError::BuiltIn`] if trying to modify a built-in policy.
+    /// - [`PolicyError::Invalid`] if the policy has no rules or a control
+    ///   group names no approvers.
     /// - [`PolicyError::Barrier`] if storage fails.
     pub async fn put(&self, policy: &Policy) -> Result<(), PolicyError> {
-        if policy.name == "root" || policy.name == "default" {
+        if BUILTIN_POLICIES.contains(&policy.name.as_str()) {
             return Err(PolicyError::BuiltIn {
                 name: policy.name.clone(),
             });
@@ -96,6 +150,24 @@ impl PolicyStore {
             });
         }
 
+        for rule in &policy.rules {
+            if let Some(group) = &rule.control_group {
+                if group.approvals == 0 {
+                    return Err(PolicyError::Invalid {
+                        reason: format!(
+                            "control group on '{}' needs at least 1 approval",
+                            rule.path
+                        ),
+                    });
+                }
+                if group.approver_policies.is_empty() && group.approver_identities.is_empty() {
+                    return Err(PolicyError::Invalid {
+                        reason: format!("control group on '{}' names no approvers", rule.path),
+                    });
+                }
+            }
+        }
+
         let bytes = serde_json::to_vec(policy).map_err(|e| PolicyError::Invalid {
             reason: format!("serialization failed: {e}"),
         })?;
@@ -110,7 +182,7 @@ impl PolicyStore {
 
     /// Read a policy by name.
     ///
-    /// Returns built-in policies for `root` and `default` without storage lookup.
+    /// Returns built-in policies without storage lookup.
     ///
     /// # Errors
     ///
@@ -124,6 +196,9 @@ impl PolicyStore {
         if name == "default" {
             return Ok(default_policy());
         }
+        if name == "mcp-audit" {
+            return Ok(mcp_audit_policy());
+        }
 
         let key = format!("{POLICY_PREFIX}{name}");
         let data = self
@@ -143,10 +218,10 @@ impl PolicyStore {
     ///
     /// # Errors
     ///
-    /// - [`PolicyError::BuiltIn`] if trying to delete `root` or `default`.
+    /// - [`PolicyError::BuiltIn`] if trying to delete a built-in policy.
     /// - [`PolicyError::Barrier`] if storage fails.
     pub async fn delete(&self, name: &str) -> Result<(), PolicyError> {
-        if name == "root" || name == "default" {
+        if BUILTIN_POLICIES.contains(&name) {
             return Err(PolicyError::BuiltIn {
                 name: name.to_owned(),
             });
@@ -162,7 +237,7 @@ impl PolicyStore {
 
     /// List all policy names.
     ///
-    /// Always includes `root` and `default`.
+    /// Always includes the built-in policies.
     ///
     /// # Errors
     ///
@@ -175,11 +250,10 @@ impl PolicyStore {
             .collect();
 
         // Always include built-ins.
-        if !names.contains(&"root".to_owned()) {
-            names.push("root".to_owned());
-        }
-        if !names.contains(&"default".to_owned()) {
-            names.push("default".to_owned());
+        for builtin in BUILTIN_POLICIES {
+            if !names.iter().any(|name| name == builtin) {
+                names.push(builtin.to_owned());
+            }
         }
 
         names.sort();
@@ -194,6 +268,8 @@ impl PolicyStore {
     /// # Errors
     ///
     /// - [`PolicyError::Denied`] if no policy grants the capability.
+    /// - [`PolicyError::ControlGroupRequired`] if a granting rule carries a
+    ///   control group and the request has not been approved.
     /// - [`PolicyError::Barrier`] if loading policies fails.
     pub async fn check(
         &self,
@@ -201,7 +277,119 @@ impl PolicyStore {
         path: &str,
         capability: &Capability,
     ) -> Result<(), PolicyError> {
+        match self.evaluate(policy_names, path, capability).await? {
+            Decision::Granted(grant) => grant.check(path),
+            Decision::Denied | Decision::NotGranted => Err(denied(path, capability)),
+        }
+    }
+
+    /// The control groups that must approve a request needing any of
+    /// `capabilities` on `path` before it runs.
+    ///
+    /// Empty if no rule granting one of them carries a control group, if the
+    /// request has already been approved, or if `path` is denied — the
+    /// handler's own check then decides. Unlike [`Self::check`], a control
+    /// group on any of the capabilities counts, even when another is granted
+    /// freely.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PolicyError::Barrier`] if loading policies fails.
+    pub async fn control_groups(
+        &self,
+        policy_names: &[String],
+        path: &str,
+        capabilities: &[Capability],
+    ) -> Result<Vec<ControlGroup>, PolicyError> {
+        let mut groups: Vec<ControlGroup> = Vec::new();
+        if control_group::is_approved() {
+            return Ok(groups);
+        }
+        for capability in capabilities {
+            match self.evaluate(policy_names, path, capability).await? {
+                Decision::Granted(grant) => {
+                    for group in grant.control_groups {
+                        if !groups.contains(&group) {
+                            groups.push(group);
+                        }
+                    }
+                }
+                Decision::Denied => return Ok(Vec::new()),
+                Decision::NotGranted => {}
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Check a management operation on a mount, honouring delegated admin.
+    ///
+    /// Succeeds if `capability` is granted on `path`, or if the policies
+    /// grant `sudo` on the mount root (e.g. a `transit-teamA/**` rule for
+    /// mount `transit-teamA/`). An explicit `deny` on `path` still wins.
+    ///
+    /// # Errors
+    ///
+    /// - [`PolicyError::Denied`] if neither grant applies.
+    /// - [`PolicyError::ControlGroupRequired`] if the applicable grant
+    ///   carries a control group and the request has not been approved.
+    /// - [`PolicyError::Barrier`] if loading policies fails.
+    pub async fn check_mount(
+        &self,
+        policy_names: &[String],
+        mount: &str,
+        path: &str,
+        capability: &Capability,
+    ) -> Result<(), PolicyError> {
+        match self.evaluate(policy_names, path, capability).await? {
+            Decision::Granted(grant) => grant.check(path),
+            Decision::Denied => Err(denied(path, capability)),
+            Decision::NotGranted => match self.mount_admin(policy_names, mount).await? {
+                Decision::Granted(grant) => grant.check(path),
+                Decision::Denied | Decision::NotGranted => Err(denied(path, capability)),
+            },
+        }
+    }
+
+    /// Whether the policies grant delegated admin (`sudo`) over `mount`.
+    ///
+    /// System paths (`sys/`, `auth/`) can never be delegated this way. A
+    /// grant held behind a control group counts only once approved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PolicyError::Barrier`] if loading policies fails.
+    pub async fn is_mount_admin(
+        &self,
+        policy_names: &[String],
+        mount: &str,
+    ) -> Result<bool, PolicyError> {
+        Ok(match self.mount_admin(policy_names, mount).await? {
+            Decision::Granted(grant) => grant.check("").is_ok(),
+            Decision::Denied | Decision::NotGranted => false,
+        })
+    }
+
+    /// Evaluate delegated admin (`sudo`) over `mount`.
+    async fn mount_admin(
+        &self,
+        policy_names: &[String],
+        mount: &str,
+    ) -> Result<Decision, PolicyError> {
+        if mount.is_empty() || mount.starts_with("sys/") || mount.starts_with("auth/") {
+            return Ok(Decision::NotGranted);
+        }
+        self.evaluate(policy_names, mount, &Capability::Sudo).await
+    }
+
+    /// Evaluate every rule of every policy against `path`.
+    async fn evaluate(
+        &self,
+        policy_names: &[String],
+        path: &str,
+        capability: &Capability,
+    ) -> Result<Decision, PolicyError> {
         let mut granted = false;
+        let mut grant = Grant::default();
 
         for name in policy_names {
             let policy = match self.get(name).await {
@@ -214,29 +402,82 @@ impl PolicyStore {
                 if path_matches(&rule.path, path) {
                     // Deny always wins.
                     if rule.capabilities.contains(&Capability::Deny) {
-                        return Err(PolicyError::Denied {
-                            path: path.to_owned(),
-                            capability: format!("{capability:?}"),
-                        });
+                        return Ok(Decision::Denied);
                     }
                     if rule.capabilities.contains(capability) {
                         granted = true;
+                        if let Some(group) = &rule.control_group {
+                            if !grant.control_groups.contains(group) {
+                                grant.control_groups.push(group.clone());
+                            }
+                        }
+                        for method in &rule.mfa_methods {
+                            if !grant.mfa_methods.contains(method) {
+                                grant.mfa_methods.push(method.clone());
+                            }
+                        }
                     }
                 }
             }
         }
 
-        if granted {
+        Ok(if granted {
+            Decision::Granted(grant)
+        } else {
+            Decision::NotGranted
+        })
+    }
+}
+
+/// Outcome of evaluating policies for one path and capability.
+#[derive(Debug, PartialEq, Eq)]
+enum Decision {
+    /// Granted, subject to the conditions of the granting rules.
+    Granted(Grant),
+    /// A matching rule explicitly denies the path.
+    Denied,
+    NotGranted,
+}
+
+/// Conditions the granting rules attach to a request.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Grant {
+    control_groups: Vec<ControlGroup>,
+    mfa_methods: Vec<String>,
+}
+
+impl Grant {
+    /// Let the request through unless it still needs MFA or approval.
+    fn check(self, path: &str) -> Result<(), PolicyError> {
+        let missing: Vec<String> = self
+            .mfa_methods
+            .into_iter()
+            .filter(|m| !mfa::is_verified(m))
+            .collect();
+        if !missing.is_empty() {
+            return Err(PolicyError::MfaRequired {
+                path: path.to_owned(),
+                methods: missing,
+            });
+        }
+        if self.control_groups.is_empty() || control_group::is_approved() {
             Ok(())
         } else {
-            Err(PolicyError::Denied {
+            Err(PolicyError::ControlGroupRequired {
                 path: path.to_owned(),
-                capability: format!("{capability:?}"),
+                control_groups: self.control_groups,
             })
         }
     }
 }
 
+fn denied(path: &str, capability: &Capability) -> PolicyError {
+    PolicyError::Denied {
+        path: path.to_owned(),
+        capability: format!("{capability:?}"),
+    }
+}
+
 /// The built-in `root` policy — grants everything on all paths.
 #[must_use]
 pub fn root_policy() -> Policy {
@@ -252,6 +493,8 @@ pub fn root_policy() -> Policy {
                 Capability::Delete,
                 Capability::Sudo,
             ],
+            control_group: None,
+            mfa_methods: Vec::new(),
         }],
     }
 }
@@ -265,15 +508,53 @@ pub fn default_policy() -> Policy {
             PolicyRule {
                 path: "auth/token/lookup-self".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             },
             PolicyRule {
                 path: "auth/token/renew-self".to_owned(),
                 capabilities: vec![Capability::Update],
+                control_group: None,
+                mfa_methods: Vec::new(),
+            },
+            PolicyRule {
+                path: "sys/access-requests".to_owned(),
+                capabilities: vec![Capability::Create],
+                control_group: None,
+                mfa_methods: Vec::new(),
+            },
+            PolicyRule {
+                path: "cubbyhole/**".to_owned(),
+                capabilities: vec![
+                    Capability::Read,
+                    Capability::List,
+                    Capability::Create,
+                    Capability::Update,
+                    Capability::Delete,
+                ],
+                control_group: None,
+                mfa_methods: Vec::new(),
             },
         ],
     }
 }
 
+/// The built-in `mcp-audit` policy — reporting actions taken on the token's
+/// behalf to the audit log. Not part of `default`, since any holder can
+/// write `external/<source>/<action>` entries with it.
+#[must_use]
+pub fn mcp_audit_policy() -> Policy {
+    Policy {
+        name: "mcp-audit".to_owned(),
+        rules: vec![PolicyRule {
+            path: "sys/audit-log/external".to_owned(),
+            capabilities: vec![Capability::Create],
+            control_group: None,
+            mfa_methods: Vec::new(),
+        }],
+    }
+}
+
 /// Match a path against a pattern supporting `*` (one segment) and `**` (recursive).
 fn path_matches(pattern: &str, path: &str) -> bool {
     glob_match::glob_match(pattern, path)
@@ -299,7 +580,7 @@ mod tests {
     async fn make_policy_store() -> PolicyStore {
         let storage = Arc::new(MemoryBackend::new());
         let barrier = Arc::new(Barrier::new(storage));
-        barrier.unseal(EncryptionKey::generate()).await;
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
         PolicyStore::new(barrier)
     }
 
@@ -320,6 +601,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/data/dev/*".to_owned(),
                 capabilities: vec![Capability::Read, Capability::List],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
 
@@ -345,6 +628,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/*".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
 
@@ -380,7 +665,31 @@ mod tests {
         let store = make_policy_store().await;
         let default = store.get("default").await.unwrap();
         assert_eq!(default.name, "default");
-        assert_eq!(default.rules.len(), 2);
+        assert_eq!(default.rules.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn only_mcp_audit_grants_external_audit_entries() {
+        let store = make_policy_store().await;
+        let err = store
+            .check(
+                &["default".to_owned()],
+                "sys/audit-log/external",
+                &Capability::Create,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PolicyError::Denied { .. }));
+        store
+            .check(
+                &["default".to_owned(), "mcp-audit".to_owned()],
+                "sys/audit-log/external",
+                &Capability::Create,
+            )
+            .await
+            .unwrap();
+        let err = store.delete("mcp-audit").await.unwrap_err();
+        assert!(matches!(err, PolicyError::BuiltIn { .. }));
     }
 
     #[tokio::test]
@@ -391,6 +700,8 @@ mod tests {
             vec![PolicyRule {
                 path: "**".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         let err = store.put(&policy).await.unwrap_err();
@@ -405,6 +716,8 @@ mod tests {
             vec![PolicyRule {
                 path: "**".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         let err = store.put(&policy).await.unwrap_err();
@@ -433,6 +746,7 @@ mod tests {
         let names = store.list().await.unwrap();
         assert!(names.contains(&"root".to_owned()));
         assert!(names.contains(&"default".to_owned()));
+        assert!(names.contains(&"mcp-audit".to_owned()));
     }
 
     #[tokio::test]
@@ -443,6 +757,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/*".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         store.put(&policy).await.unwrap();
@@ -463,6 +779,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/data/prod/db-password".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         store.put(&policy).await.unwrap();
@@ -485,6 +803,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/data/prod/db-password".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         store.put(&policy).await.unwrap();
@@ -509,6 +829,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/data/dev/*".to_owned(),
                 capabilities: vec![Capability::Read, Capability::Create],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         store.put(&policy).await.unwrap();
@@ -534,6 +856,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/**".to_owned(),
                 capabilities: vec![Capability::Read, Capability::Create, Capability::Delete],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         store.put(&policy).await.unwrap();
@@ -560,10 +884,14 @@ mod tests {
                 PolicyRule {
                     path: "secret/**".to_owned(),
                     capabilities: vec![Capability::Read],
+                    control_group: None,
+                    mfa_methods: Vec::new(),
                 },
                 PolicyRule {
                     path: "secret/data/prod/*".to_owned(),
                     capabilities: vec![Capability::Deny],
+                    control_group: None,
+                    mfa_methods: Vec::new(),
                 },
             ],
         );
@@ -589,6 +917,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/**".to_owned(),
                 capabilities: vec![Capability::Read, Capability::Create],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         let deny_policy = test_policy(
@@ -596,6 +926,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/data/prod/*".to_owned(),
                 capabilities: vec![Capability::Deny],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         store.put(&grant_policy).await.unwrap();
@@ -622,6 +954,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/data/shared/*".to_owned(),
                 capabilities: vec![Capability::Read],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         let write_policy = test_policy(
@@ -629,6 +963,8 @@ mod tests {
             vec![PolicyRule {
                 path: "secret/data/shared/*".to_owned(),
                 capabilities: vec![Capability::Create],
+                control_group: None,
+                mfa_methods: Vec::new(),
             }],
         );
         store.put(&read_policy).await.unwrap();
@@ -714,4 +1050,164 @@ mod tests {
             .await;
         assert!(matches!(result, Err(PolicyError::Denied { .. })));
     }
+
+    // ── Delegated mount admin ────────────────────────────────────────
+
+    fn team_lead_policy() -> Policy {
+        test_policy(
+            "team-a-admin",
+            vec![
+                PolicyRule {
+                    path: "transit-teamA/**".to_owned(),
+                    capabilities: vec![Capability::Sudo],
+                    control_group: None,
+                    mfa_methods: Vec::new(),
+                },
+                PolicyRule {
+                    path: "transit-teamA/keys/frozen".to_owned(),
+                    capabilities: vec![Capability::Deny],
+                    control_group: None,
+                    mfa_methods: Vec::new(),
+                },
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn subtree_sudo_grants_mount_management() {
+        let store = make_policy_store().await;
+        store.put(&team_lead_policy()).await.unwrap();
+        let names = vec!["team-a-admin".to_owned()];
+
+        store
+            .check_mount(
+                &names,
+                "transit-teamA/",
+                "transit-teamA/keys/payments",
+                &Capability::Create,
+            )
+            .await
+            .unwrap();
+        store
+            .check_mount(&names, "transit-teamA/", "sys/mounts", &Capability::Update)
+            .await
+            .unwrap();
+
+        // Plain checks and other mounts are unaffected.
+        assert!(
+            store
+                .check(&names, "transit-teamA/keys/payments", &Capability::Create)
+                .await
+                .is_err()
+        );
+        assert!(
+            store
+                .check_mount(&names, "transit/", "transit/keys/x", &Capability::Create)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn subtree_sudo_respects_explicit_deny() {
+        let store = make_policy_store().await;
+        store.put(&team_lead_policy()).await.unwrap();
+        let names = vec!["team-a-admin".to_owned()];
+
+        let err = store
+            .check_mount(
+                &names,
+                "transit-teamA/",
+                "transit-teamA/keys/frozen",
+                &Capability::Update,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PolicyError::Denied { .. }));
+    }
+
+    #[tokio::test]
+    async fn subtree_sudo_is_scoped_to_its_database_mount() {
+        let store = make_policy_store().await;
+        store
+            .put(&test_policy(
+                "db-team-a-admin",
+                vec![PolicyRule {
+                    path: "database-teamA/**".to_owned(),
+                    capabilities: vec![Capability::Sudo],
+                    control_group: None,
+                    mfa_methods: Vec::new(),
+                }],
+            ))
+            .await
+            .unwrap();
+        let names = vec!["db-team-a-admin".to_owned()];
+
+        // Handlers check `{mount}config/{name}` for the mount they serve.
+        for (mount, allowed) in [("database-teamA/", true), ("database-teamB/", false)] {
+            let result = store
+                .check_mount(
+                    &names,
+                    mount,
+                    &format!("{mount}config/orders"),
+                    &Capability::Create,
+                )
+                .await;
+            assert_eq!(result.is_ok(), allowed, "{mount}");
+        }
+    }
+
+    #[tokio::test]
+    async fn system_paths_cannot_be_delegated() {
+        let store = make_policy_store().await;
+        let names = vec!["root".to_owned()];
+        assert!(!store.is_mount_admin(&names, "sys/").await.unwrap());
+        assert!(!store.is_mount_admin(&names, "auth/token/").await.unwrap());
+        assert!(store.is_mount_admin(&names, "secret/").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn mfa_methods_require_step_up() {
+        let store = make_policy_store().await;
+        let rule = |path: &str, mfa: &[&str]| PolicyRule {
+            path: path.to_owned(),
+            capabilities: vec![Capability::Delete],
+            control_group: None,
+            mfa_methods: mfa.iter().map(|m| (*m).to_owned()).collect(),
+        };
+        store
+            .put(&test_policy(
+                "policy-admin",
+                vec![
+                    rule("sys/policies/*", &["totp"]),
+                    rule("sys/policies/**", &[]),
+                ],
+            ))
+            .await
+            .unwrap();
+        let names = vec!["policy-admin".to_owned()];
+
+        let err = store
+            .check(&names, "sys/policies/ci", &Capability::Delete)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, PolicyError::MfaRequired { ref methods, .. } if methods == &["totp"])
+        );
+
+        let verified = mfa::run_verified(vec!["totp".to_owned()], async {
+            store
+                .check(&names, "sys/policies/ci", &Capability::Delete)
+                .await
+        })
+        .await;
+        assert!(verified.is_ok());
+        let wrong_method = mfa::run_verified(vec!["other".to_owned()], async {
+            store
+                .check(&names, "sys/policies/ci", &Capability::Delete)
+                .await
+        })
+        .await;
+        assert!(wrong_method.is_err());
+    }
 }
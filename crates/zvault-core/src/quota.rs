@@ -0,0 +1,511 @@
+//! Resource quotas for `ZVault`.
+//!
+//! Two kinds of quota protect the server from runaway clients:
+//!
+//! - **Rate limit quotas** cap requests per second with a token bucket. A
+//!   quota applies globally (empty `path`) or to every request under a path
+//!   prefix such as `secret/`; with `per_token` each valid client token
+//!   (or, for requests without one, each client address) gets its own
+//!   bucket instead of sharing one. The most specific quota matching a request is
+//!   the only one applied.
+//! - **Lease count quotas** cap the active leases under a path prefix such
+//!   as `database/`, enforced by [`crate::lease::LeaseManager`] when a lease
+//!   is created.
+//!
+//! Quotas are stored through the barrier at `sys/quotas/` and cached in
+//! memory so rate checks never touch storage; [`QuotaManager::load`]
+//! refreshes the cache after an unseal. Buckets live in memory only, so a
+//! restart refills them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::barrier::Barrier;
+use crate::error::QuotaError;
+
+/// Storage prefix for rate limit quotas.
+const RATE_LIMIT_PREFIX: &str = "sys/quotas/rate-limit/";
+
+/// Storage prefix for lease count quotas.
+const LEASE_COUNT_PREFIX: &str = "sys/quotas/lease-count/";
+
+/// Bucket count above which idle (full) per-token buckets are dropped.
+const MAX_BUCKETS: usize = 10_000;
+
+/// A token-bucket request rate limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitQuota {
+    /// Quota name.
+    #[serde(default)]
+    pub name: String,
+    /// Path prefix the quota applies to (e.g. `secret/`); empty for all
+    /// requests.
+    #[serde(default)]
+    pub path: String,
+    /// Requests allowed per `interval_secs`.
+    pub rate: f64,
+    /// Window `rate` is measured over, in seconds.
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    /// Requests allowed in a burst (default: `rate`, at least 1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+    /// Give each client token its own bucket instead of sharing one.
+    #[serde(default)]
+    pub per_token: bool,
+}
+
+fn default_interval() -> u64 {
+    1
+}
+
+impl RateLimitQuota {
+    fn capacity(&self) -> f64 {
+        self.burst.map_or(self.rate.ceil(), f64::from).max(1.0)
+    }
+
+    /// Bucket refill rate in requests per second.
+    fn refill_per_sec(&self) -> f64 {
+        // Precision loss only matters for windows beyond 2^52 seconds.
+        #[allow(clippy::cast_precision_loss)]
+        let interval = self.interval_secs as f64;
+        self.rate / interval
+    }
+}
+
+/// A cap on the active leases under a path prefix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaseCountQuota {
+    /// Quota name.
+    #[serde(default)]
+    pub name: String,
+    /// Lease path prefix the quota applies to (e.g. `database/`); empty for
+    /// all leases.
+    #[serde(default)]
+    pub path: String,
+    /// Maximum active leases.
+    pub max_leases: u64,
+}
+
+/// Token bucket state.
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Refill for the time elapsed since the last update.
+    fn refill(&mut self, quota: &RateLimitQuota, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = elapsed
+            .mul_add(quota.refill_per_sec(), self.tokens)
+            .min(quota.capacity());
+        self.updated = now;
+    }
+}
+
+/// Stores quotas and enforces rate limits.
+pub struct QuotaManager {
+    barrier: Arc<Barrier>,
+    rate_limits: RwLock<Vec<RateLimitQuota>>,
+    lease_counts: RwLock<Vec<LeaseCountQuota>>,
+    /// Buckets keyed by quota name and client (empty for shared buckets).
+    buckets: Mutex<HashMap<(String, String), Bucket>>,
+}
+
+impl QuotaManager {
+    /// Create a manager backed by the barrier. Call [`Self::load`] once the
+    /// barrier is unsealed.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            barrier,
+            rate_limits: RwLock::new(Vec::new()),
+            lease_counts: RwLock::new(Vec::new()),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reload all quotas from storage into the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::Barrier`] if the barrier is sealed.
+    pub async fn load(&self) -> Result<(), QuotaError> {
+        let rate_limits = self.read_all(RATE_LIMIT_PREFIX).await?;
+        let lease_counts = self.read_all(LEASE_COUNT_PREFIX).await?;
+        *self.rate_limits.write().await = rate_limits;
+        *self.lease_counts.write().await = lease_counts;
+        self.buckets.lock().await.clear();
+        Ok(())
+    }
+
+    async fn read_all<T: serde::de::DeserializeOwned>(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<T>, QuotaError> {
+        let mut items = Vec::new();
+        for key in self.barrier.list(prefix).await? {
+            if let Some(data) = self.barrier.get(&key).await? {
+                items.push(
+                    serde_json::from_slice(&data).map_err(|e| QuotaError::Internal {
+                        reason: format!("corrupt quota {key}: {e}"),
+                    })?,
+                );
+            }
+        }
+        Ok(items)
+    }
+
+    async fn put<T: Serialize>(&self, key: &str, value: &T) -> Result<(), QuotaError> {
+        let data = serde_json::to_vec(value).map_err(|e| QuotaError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        self.barrier.put(key, &data).await?;
+        Ok(())
+    }
+
+    // ── Rate limit quotas ────────────────────────────────────────────
+
+    /// Create or replace a rate limit quota. Its buckets start full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::InvalidConfig`] if the name is empty, the rate
+    /// is not positive, or the interval is zero.
+    pub async fn put_rate_limit(&self, quota: RateLimitQuota) -> Result<(), QuotaError> {
+        validate_name(&quota.name)?;
+        if !(quota.rate.is_finite() && quota.rate > 0.0) {
+            return Err(invalid_config("rate must be a positive number"));
+        }
+        if quota.interval_secs == 0 {
+            return Err(invalid_config("interval must be at least one second"));
+        }
+        if quota.burst == Some(0) {
+            return Err(invalid_config("burst must be at least 1"));
+        }
+        self.put(&format!("{RATE_LIMIT_PREFIX}{}", quota.name), &quota)
+            .await?;
+
+        self.buckets
+            .lock()
+            .await
+            .retain(|(name, _), _| *name != quota.name);
+        let mut cache = self.rate_limits.write().await;
+        cache.retain(|q| q.name != quota.name);
+        cache.push(quota);
+        Ok(())
+    }
+
+    /// Get a rate limit quota.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::NotFound`] if it does not exist.
+    pub async fn get_rate_limit(&self, name: &str) -> Result<RateLimitQuota, QuotaError> {
+        self.rate_limits
+            .read()
+            .await
+            .iter()
+            .find(|q| q.name == name)
+            .cloned()
+            .ok_or_else(|| QuotaError::NotFound {
+                name: name.to_owned(),
+            })
+    }
+
+    /// List rate limit quotas, sorted by name.
+    pub async fn list_rate_limits(&self) -> Vec<RateLimitQuota> {
+        let mut quotas = self.rate_limits.read().await.clone();
+        quotas.sort_by(|a, b| a.name.cmp(&b.name));
+        quotas
+    }
+
+    /// Delete a rate limit quota.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::NotFound`] if it does not exist.
+    pub async fn delete_rate_limit(&self, name: &str) -> Result<(), QuotaError> {
+        self.get_rate_limit(name).await?;
+        self.barrier
+            .delete(&format!("{RATE_LIMIT_PREFIX}{name}"))
+            .await?;
+        self.rate_limits.write().await.retain(|q| q.name != name);
+        self.buckets.lock().await.retain(|(n, _), _| n != name);
+        Ok(())
+    }
+
+    /// Whether the quota applied to `path` keeps a bucket per client, so the
+    /// caller must identify the client before [`Self::check_rate`].
+    pub async fn is_per_token(&self, path: &str) -> bool {
+        most_specific(&self.rate_limits.read().await, path, |q| &q.path)
+            .is_some_and(|quota| quota.per_token)
+    }
+
+    /// Take one request from the bucket of the most specific quota matching
+    /// `path`. `client` identifies the caller for `per_token` quotas: the
+    /// hash of a token the caller has validated, or the client address.
+    /// An unvalidated token must never be used — each made-up value would
+    /// get a fresh, full bucket.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::RateLimited`] with the seconds until a request
+    /// would be allowed if the bucket is empty.
+    pub async fn check_rate(&self, path: &str, client: &str) -> Result<(), QuotaError> {
+        let Some(quota) = most_specific(&self.rate_limits.read().await, path, |q| &q.path) else {
+            return Ok(());
+        };
+        let key = (
+            quota.name.clone(),
+            if quota.per_token {
+                client.to_owned()
+            } else {
+                String::new()
+            },
+        );
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().await;
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(&key) {
+            let quotas = self.rate_limits.read().await;
+            buckets.retain(|(name, _), bucket| {
+                quotas.iter().find(|q| q.name == *name).is_some_and(|q| {
+                    bucket.refill(q, now);
+                    bucket.tokens < q.capacity()
+                })
+            });
+        }
+        let bucket = buckets.entry(key).or_insert_with(|| Bucket {
+            tokens: quota.capacity(),
+            updated: now,
+        });
+        bucket.refill(&quota, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let wait = (1.0 - bucket.tokens) / quota.refill_per_sec();
+        Err(QuotaError::RateLimited {
+            quota: quota.name,
+            // Saturating float-to-int cast of a small positive wait.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            retry_after_secs: wait.ceil().max(1.0) as u64,
+        })
+    }
+
+    // ── Lease count quotas ───────────────────────────────────────────
+
+    /// Create or replace a lease count quota.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::InvalidConfig`] if the name is empty.
+    pub async fn put_lease_count(&self, quota: LeaseCountQuota) -> Result<(), QuotaError> {
+        validate_name(&quota.name)?;
+        self.put(&format!("{LEASE_COUNT_PREFIX}{}", quota.name), &quota)
+            .await?;
+        let mut cache = self.lease_counts.write().await;
+        cache.retain(|q| q.name != quota.name);
+        cache.push(quota);
+        Ok(())
+    }
+
+    /// Get a lease count quota.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::NotFound`] if it does not exist.
+    pub async fn get_lease_count(&self, name: &str) -> Result<LeaseCountQuota, QuotaError> {
+        self.lease_counts
+            .read()
+            .await
+            .iter()
+            .find(|q| q.name == name)
+            .cloned()
+            .ok_or_else(|| QuotaError::NotFound {
+                name: name.to_owned(),
+            })
+    }
+
+    /// List lease count quotas, sorted by name.
+    pub async fn list_lease_counts(&self) -> Vec<LeaseCountQuota> {
+        let mut quotas = self.lease_counts.read().await.clone();
+        quotas.sort_by(|a, b| a.name.cmp(&b.name));
+        quotas
+    }
+
+    /// Delete a lease count quota.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::NotFound`] if it does not exist.
+    pub async fn delete_lease_count(&self, name: &str) -> Result<(), QuotaError> {
+        self.get_lease_count(name).await?;
+        self.barrier
+            .delete(&format!("{LEASE_COUNT_PREFIX}{name}"))
+            .await?;
+        self.lease_counts.write().await.retain(|q| q.name != name);
+        Ok(())
+    }
+
+    /// The most specific lease count quota covering lease path `path`.
+    pub async fn lease_count_for(&self, path: &str) -> Option<LeaseCountQuota> {
+        most_specific(&self.lease_counts.read().await, path, |q| &q.path)
+    }
+}
+
+impl std::fmt::Debug for QuotaManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuotaManager").finish_non_exhaustive()
+    }
+}
+
+/// The quota with the longest path prefix of `path`.
+fn most_specific<T: Clone>(quotas: &[T], path: &str, prefix: impl Fn(&T) -> &str) -> Option<T> {
+    quotas
+        .iter()
+        .filter(|q| path.starts_with(prefix(q)))
+        .max_by_key(|q| prefix(q).len())
+        .cloned()
+}
+
+fn validate_name(name: &str) -> Result<(), QuotaError> {
+    if name.is_empty() || name.contains('/') {
+        return Err(invalid_config(
+            "quota name must be non-empty and contain no '/'",
+        ));
+    }
+    Ok(())
+}
+
+fn invalid_config(reason: &str) -> QuotaError {
+    QuotaError::InvalidConfig {
+        reason: reason.to_owned(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn manager() -> (QuotaManager, Arc<Barrier>) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        (QuotaManager::new(Arc::clone(&barrier)), barrier)
+    }
+
+    fn rate_limit(name: &str, path: &str, rate: f64, per_token: bool) -> RateLimitQuota {
+        RateLimitQuota {
+            name: name.to_owned(),
+            path: path.to_owned(),
+            rate,
+            interval_secs: 1,
+            burst: None,
+            per_token,
+        }
+    }
+
+    #[tokio::test]
+    async fn bucket_allows_burst_then_limits() {
+        let (quotas, _) = manager().await;
+        quotas
+            .put_rate_limit(rate_limit("kv", "secret/", 3.0, false))
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            quotas.check_rate("secret/data/a", "t1").await.unwrap();
+        }
+        let err = quotas.check_rate("secret/data/a", "t2").await.unwrap_err();
+        assert!(matches!(
+            err,
+            QuotaError::RateLimited { ref quota, retry_after_secs: 1 } if quota == "kv"
+        ));
+        // Other paths are not covered by the quota.
+        quotas.check_rate("transit/encrypt/k", "t1").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn per_token_buckets_and_most_specific_quota() {
+        let (quotas, _) = manager().await;
+        quotas
+            .put_rate_limit(rate_limit("global", "", 1.0, false))
+            .await
+            .unwrap();
+        quotas
+            .put_rate_limit(rate_limit("kv", "secret/", 1.0, true))
+            .await
+            .unwrap();
+
+        // Each token has its own bucket under secret/.
+        assert!(quotas.is_per_token("secret/data/a").await);
+        assert!(!quotas.is_per_token("sys/mounts").await);
+        quotas.check_rate("secret/data/a", "t1").await.unwrap();
+        quotas.check_rate("secret/data/a", "t2").await.unwrap();
+        assert!(quotas.check_rate("secret/data/a", "t1").await.is_err());
+
+        // Everything else shares the global bucket.
+        quotas.check_rate("sys/mounts", "t1").await.unwrap();
+        assert!(quotas.check_rate("sys/policies", "t2").await.is_err());
+
+        quotas.delete_rate_limit("global").await.unwrap();
+        quotas.check_rate("sys/policies", "t2").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn quotas_survive_reload() {
+        let (quotas, barrier) = manager().await;
+        quotas
+            .put_rate_limit(rate_limit("kv", "secret/", 5.0, false))
+            .await
+            .unwrap();
+        quotas
+            .put_lease_count(LeaseCountQuota {
+                name: "db".to_owned(),
+                path: "database/".to_owned(),
+                max_leases: 2,
+            })
+            .await
+            .unwrap();
+
+        let reloaded = QuotaManager::new(barrier);
+        reloaded.load().await.unwrap();
+        assert_eq!(reloaded.list_rate_limits().await.len(), 1);
+        let quota = reloaded.lease_count_for("database/creds/ro").await.unwrap();
+        assert_eq!(quota.max_leases, 2);
+        assert!(reloaded.lease_count_for("pki/issue/web").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_quotas() {
+        let (quotas, _) = manager().await;
+        for bad in [
+            rate_limit("", "", 1.0, false),
+            rate_limit("a/b", "", 1.0, false),
+            rate_limit("zero", "", 0.0, false),
+            RateLimitQuota {
+                interval_secs: 0,
+                ..rate_limit("interval", "", 1.0, false)
+            },
+        ] {
+            assert!(matches!(
+                quotas.put_rate_limit(bad).await,
+                Err(QuotaError::InvalidConfig { .. })
+            ));
+        }
+        assert!(matches!(
+            quotas.delete_rate_limit("missing").await,
+            Err(QuotaError::NotFound { .. })
+        ));
+    }
+}
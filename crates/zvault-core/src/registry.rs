@@ -0,0 +1,178 @@
+//! Registry of mounted secrets engine instances for `ZVault`.
+//!
+//! The mount table ([`crate::mount`]) records *what* is mounted where; the
+//! registry holds the live engine behind each mount. Every engine type
+//! implements [`Engine`], and handlers look an engine up by mount path and
+//! concrete type, so a mount created at runtime is served as soon as its
+//! engine is inserted and stops being served when it is removed.
+//!
+//! Each engine keeps all of its data under one barrier prefix, which
+//! [`purge`] deletes when a mount is disabled for good.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::barrier::Barrier;
+use crate::error::BarrierError;
+
+/// A secrets engine instance that can be mounted.
+pub trait Engine: Send + Sync + 'static {
+    /// Engine type as recorded in the mount table (e.g., `kv`, `transit`).
+    fn engine_type(&self) -> &'static str;
+
+    /// Barrier prefix holding all of this engine's data.
+    fn storage_prefix(&self) -> String;
+
+    /// Convert to [`Any`] so the registry can hand out the concrete type.
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+/// Live engines keyed by mount path (e.g., `secret/`).
+#[derive(Default)]
+pub struct EngineRegistry {
+    engines: RwLock<HashMap<String, Arc<dyn Engine>>>,
+}
+
+impl EngineRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `mount` with `engine`, returning the engine it replaces.
+    pub async fn insert(
+        &self,
+        mount: impl Into<String>,
+        engine: Arc<dyn Engine>,
+    ) -> Option<Arc<dyn Engine>> {
+        self.engines.write().await.insert(mount.into(), engine)
+    }
+
+    /// Stop serving `mount`, returning its engine.
+    pub async fn remove(&self, mount: &str) -> Option<Arc<dyn Engine>> {
+        self.engines.write().await.remove(mount)
+    }
+
+    /// The engine at `mount`, if one is mounted there and has type `T`.
+    pub async fn get<T: Engine>(&self, mount: &str) -> Option<Arc<T>> {
+        let engine = self.engines.read().await.get(mount).cloned()?;
+        engine.into_any().downcast::<T>().ok()
+    }
+
+    /// The engine at `mount`, whatever its type.
+    pub async fn get_any(&self, mount: &str) -> Option<Arc<dyn Engine>> {
+        self.engines.read().await.get(mount).cloned()
+    }
+
+    /// Every engine of type `T` with its mount path, sorted by path.
+    pub async fn all<T: Engine>(&self) -> Vec<(String, Arc<T>)> {
+        let mut engines: Vec<_> = self
+            .engines
+            .read()
+            .await
+            .iter()
+            .filter_map(|(mount, engine)| {
+                Arc::clone(engine)
+                    .into_any()
+                    .downcast::<T>()
+                    .ok()
+                    .map(|engine| (mount.clone(), engine))
+            })
+            .collect();
+        engines.sort_by(|a, b| a.0.cmp(&b.0));
+        engines
+    }
+
+    /// Whether any engine of type `T` is mounted.
+    pub async fn has<T: Engine>(&self) -> bool {
+        self.engines
+            .read()
+            .await
+            .values()
+            .any(|engine| Arc::clone(engine).into_any().is::<T>())
+    }
+}
+
+impl std::fmt::Debug for EngineRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EngineRegistry").finish_non_exhaustive()
+    }
+}
+
+/// Delete every barrier entry under `engine`'s storage prefix, returning
+/// how many were removed.
+///
+/// # Errors
+///
+/// Returns [`BarrierError`] if the vault is sealed or storage fails; entries
+/// deleted before the failure stay deleted.
+pub async fn purge(barrier: &Barrier, engine: &dyn Engine) -> Result<usize, BarrierError> {
+    let prefix = engine.storage_prefix();
+    let keys = barrier.list(&prefix).await?;
+    for key in &keys {
+        barrier.delete(key).await?;
+    }
+    info!(prefix = %prefix, deleted = keys.len(), "engine storage purged");
+    Ok(keys.len())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use crate::engine::KvEngine;
+    use crate::transit::TransitEngine;
+    use zvault_storage::MemoryBackend;
+
+    async fn make_barrier() -> Arc<Barrier> {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        barrier
+    }
+
+    #[tokio::test]
+    async fn get_checks_engine_type() {
+        let barrier = make_barrier().await;
+        let registry = EngineRegistry::new();
+        registry
+            .insert(
+                "secret/",
+                Arc::new(KvEngine::new(Arc::clone(&barrier), "kv/secret/".to_owned())),
+            )
+            .await;
+        registry
+            .insert(
+                "transit/",
+                Arc::new(TransitEngine::new(barrier, "transit/transit/".to_owned())),
+            )
+            .await;
+
+        assert!(registry.get::<KvEngine>("secret/").await.is_some());
+        assert!(registry.get::<TransitEngine>("secret/").await.is_none());
+        assert!(registry.get::<KvEngine>("missing/").await.is_none());
+        assert_eq!(registry.all::<KvEngine>().await.len(), 1);
+        assert!(registry.has::<TransitEngine>().await);
+
+        registry.remove("transit/").await;
+        assert!(!registry.has::<TransitEngine>().await);
+    }
+
+    #[tokio::test]
+    async fn purge_removes_only_the_engine_prefix() {
+        let barrier = make_barrier().await;
+        let team_a = KvEngine::new(Arc::clone(&barrier), "kv/team-a/".to_owned());
+        barrier.put("kv/team-a/data/x", b"1").await.unwrap();
+        barrier.put("kv/team-a/meta/x", b"2").await.unwrap();
+        barrier.put("kv/team-ab/data/y", b"3").await.unwrap();
+
+        assert_eq!(purge(&barrier, &team_a).await.unwrap(), 2);
+        assert!(barrier.list("kv/team-a/").await.unwrap().is_empty());
+        assert!(barrier.exists("kv/team-ab/data/y").await.unwrap());
+    }
+}
@@ -0,0 +1,296 @@
+//! Change log for primary → replica replication.
+//!
+//! On a primary, [`ReplicationLog`] wraps the storage backend underneath the
+//! barrier and records every successful write and delete, in order, under a
+//! sequence index. Values are recorded exactly as stored — barrier
+//! ciphertext — so the log is no more readable than the storage itself.
+//!
+//! The log is kept in memory and holds the last `capacity` changes. A
+//! replica bootstraps from [`ReplicationLog::snapshot_at`] and then follows
+//! the log with [`ReplicationLog::since`]. A replica that falls further
+//! behind than the log reaches, or whose primary restarted (and so has a new
+//! [`epoch`](ReplicationLog::epoch)), must take a fresh snapshot.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::sync::{Mutex, watch};
+use zvault_storage::{StorageBackend, StorageError};
+
+use crate::error::ReplicationError;
+
+/// Default number of changes a primary keeps for replicas to catch up from.
+pub const DEFAULT_LOG_CAPACITY: usize = 10_000;
+
+/// One recorded change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Sequence index, starting at 1 for each epoch.
+    pub index: u64,
+    /// Storage key.
+    pub key: String,
+    /// Base64 ciphertext as written, or `None` for a delete.
+    pub value: Option<String>,
+}
+
+impl LogEntry {
+    /// Decode the value, if this entry is a write.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplicationError::Invalid`] if the value is not base64.
+    pub fn decode_value(&self) -> Result<Option<Vec<u8>>, ReplicationError> {
+        self.value
+            .as_deref()
+            .map(|v| {
+                BASE64.decode(v).map_err(|e| ReplicationError::Invalid {
+                    reason: format!("value of '{}': {e}", self.key),
+                })
+            })
+            .transpose()
+    }
+}
+
+/// A [`StorageBackend`] wrapper that records writes and deletes for replicas.
+pub struct ReplicationLog {
+    inner: Arc<dyn StorageBackend>,
+    epoch: String,
+    capacity: usize,
+    /// Held across each write so log order matches storage order.
+    entries: Mutex<VecDeque<LogEntry>>,
+    last_index: watch::Sender<u64>,
+}
+
+impl ReplicationLog {
+    /// Wrap `inner`, keeping the last `capacity` changes.
+    #[must_use]
+    pub fn new(inner: Arc<dyn StorageBackend>, capacity: usize) -> Self {
+        Self {
+            inner,
+            epoch: uuid::Uuid::new_v4().to_string(),
+            capacity: capacity.max(1),
+            entries: Mutex::new(VecDeque::new()),
+            last_index: watch::Sender::new(0),
+        }
+    }
+
+    /// Identifies this log. Changes whenever the primary restarts, since
+    /// indexes from a previous run mean nothing to the new log.
+    #[must_use]
+    pub fn epoch(&self) -> &str {
+        &self.epoch
+    }
+
+    /// Index of the most recent change (0 if none yet).
+    #[must_use]
+    pub fn last_index(&self) -> u64 {
+        *self.last_index.borrow()
+    }
+
+    /// Every storage entry, and the index the snapshot is current as of.
+    ///
+    /// Changes after that index may or may not be in the snapshot already;
+    /// replaying them on top is harmless because each entry carries the
+    /// full value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] if the backend cannot take the snapshot.
+    pub async fn snapshot_at(&self) -> Result<(u64, Vec<(String, Vec<u8>)>), StorageError> {
+        let index = {
+            let _entries = self.entries.lock().await;
+            self.last_index()
+        };
+        Ok((index, self.inner.snapshot().await?))
+    }
+
+    /// Up to `limit` changes after index `after`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplicationError::ResyncRequired`] if changes after `after`
+    /// have already been dropped from the log, or `after` is ahead of it.
+    pub async fn since(&self, after: u64, limit: usize) -> Result<Vec<LogEntry>, ReplicationError> {
+        let entries = self.entries.lock().await;
+        let last = self.last_index();
+        if after > last {
+            return Err(ReplicationError::ResyncRequired {
+                reason: format!("index {after} is ahead of the log ({last})"),
+            });
+        }
+        let oldest = entries.front().map_or(last + 1, |e| e.index);
+        if after + 1 < oldest {
+            return Err(ReplicationError::ResyncRequired {
+                reason: format!("index {after} is older than the log ({oldest})"),
+            });
+        }
+        Ok(entries
+            .iter()
+            .filter(|e| e.index > after)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    /// Wait up to `timeout` for a change after index `after`.
+    pub async fn wait_for_change(&self, after: u64, timeout: Duration) {
+        let mut rx = self.last_index.subscribe();
+        let _ = tokio::time::timeout(timeout, rx.wait_for(|&last| last > after)).await;
+    }
+
+    fn append(&self, entries: &mut VecDeque<LogEntry>, key: &str, value: Option<&[u8]>) {
+        let index = self.last_index() + 1;
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(LogEntry {
+            index,
+            key: key.to_owned(),
+            value: value.map(|v| BASE64.encode(v)),
+        });
+        self.last_index.send_replace(index);
+    }
+}
+
+impl std::fmt::Debug for ReplicationLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplicationLog")
+            .field("epoch", &self.epoch)
+            .field("last_index", &self.last_index())
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for ReplicationLog {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        self.inner.get(key).await
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        let mut entries = self.entries.lock().await;
+        self.inner.put(key, value).await?;
+        self.append(&mut entries, key, Some(value));
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let mut entries = self.entries.lock().await;
+        self.inner.delete(key).await?;
+        self.append(&mut entries, key, None);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        self.inner.list(prefix).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        self.inner.exists(key).await
+    }
+
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        self.inner.snapshot().await
+    }
+
+    async fn acquire_lock(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<String, StorageError> {
+        self.inner.acquire_lock(name, holder, ttl).await
+    }
+
+    async fn lock_holder(&self, name: &str) -> Result<Option<String>, StorageError> {
+        self.inner.lock_holder(name).await
+    }
+
+    async fn release_lock(&self, name: &str, holder: &str) -> Result<(), StorageError> {
+        self.inner.release_lock(name, holder).await
+    }
+}
+
+/// Compare a presented replication secret against the configured one in
+/// constant time.
+#[must_use]
+pub fn secret_matches(expected: &str, presented: &str) -> bool {
+    !expected.is_empty() && bool::from(expected.as_bytes().ct_eq(presented.as_bytes()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use zvault_storage::MemoryBackend;
+
+    fn make_log(capacity: usize) -> ReplicationLog {
+        ReplicationLog::new(Arc::new(MemoryBackend::new()), capacity)
+    }
+
+    #[tokio::test]
+    async fn records_writes_and_deletes_in_order() {
+        let log = make_log(10);
+        log.put("a", b"1").await.unwrap();
+        log.put("b", b"2").await.unwrap();
+        log.delete("a").await.unwrap();
+
+        let entries = log.since(0, 100).await.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].index, 1);
+        assert_eq!(entries[1].decode_value().unwrap(), Some(b"2".to_vec()));
+        assert_eq!(entries[2].key, "a");
+        assert_eq!(entries[2].value, None);
+        assert_eq!(log.since(2, 100).await.unwrap().len(), 1);
+        assert!(log.since(3, 100).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn falling_behind_the_log_requires_resync() {
+        let log = make_log(2);
+        for key in ["a", "b", "c"] {
+            log.put(key, b"x").await.unwrap();
+        }
+        assert!(matches!(
+            log.since(0, 100).await,
+            Err(ReplicationError::ResyncRequired { .. })
+        ));
+        assert_eq!(log.since(1, 100).await.unwrap().len(), 2);
+        assert!(matches!(
+            log.since(4, 100).await,
+            Err(ReplicationError::ResyncRequired { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn snapshot_reports_its_index() {
+        let log = make_log(10);
+        log.put("a", b"1").await.unwrap();
+        let (index, entries) = log.snapshot_at().await.unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(entries, vec![("a".to_owned(), b"1".to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn wait_returns_on_change() {
+        let log = Arc::new(make_log(10));
+        let writer = Arc::clone(&log);
+        tokio::spawn(async move {
+            writer.put("a", b"1").await.unwrap();
+        });
+        log.wait_for_change(0, Duration::from_secs(5)).await;
+        assert_eq!(log.last_index(), 1);
+    }
+
+    #[test]
+    fn secret_comparison() {
+        assert!(secret_matches("s3cret", "s3cret"));
+        assert!(!secret_matches("s3cret", "s3cre"));
+        assert!(!secret_matches("", ""));
+    }
+}
@@ -0,0 +1,989 @@
+//! Secret rotation engine for `ZVault`.
+//!
+//! A [`RotationPolicy`] binds one field of a KV secret to a [`Rotator`] that
+//! produces new values and a [`Schedule`] saying when to run it: a fixed
+//! interval or a five-field cron expression evaluated in UTC. Each rotation
+//! writes a new KV version with only the rotated field changed, using
+//! check-and-set so a concurrent write is never overwritten.
+//!
+//! Rotators:
+//! - `random` — a random string from a chosen character set.
+//! - `database` — rotates a static role's password through the database
+//!   engine and stores the new password.
+//! - `webhook` — `POST`s the secret's coordinates to a custom endpoint and
+//!   stores the `value` it answers with. Requests carry the same
+//!   `X-ZVault-Timestamp` / `X-ZVault-Signature` headers as notifications.
+//!
+//! Failed rotations leave the secret unchanged, record the error on the
+//! policy, and are retried with backoff. Policies are stored through the
+//! barrier under `sys/rotation/policies/`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::barrier::Barrier;
+use crate::database::DatabaseEngine;
+use crate::engine::{EngineRequest, KvEngine, Operation};
+use crate::error::{EngineError, RotationError};
+use crate::notify::sign;
+
+/// Storage prefix for rotation policies.
+const POLICY_PREFIX: &str = "sys/rotation/policies/";
+
+/// Shortest allowed interval between rotations, in seconds.
+pub const MIN_INTERVAL_SECS: u64 = 60;
+
+/// Delay before retrying a failed rotation; doubled after each failure.
+const RETRY_BASE: chrono::Duration = chrono::Duration::minutes(1);
+
+/// Longest delay before retrying a failed rotation.
+const RETRY_MAX: chrono::Duration = chrono::Duration::hours(1);
+
+/// Timeout for one webhook rotator request.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Bounds for generated random values.
+const MIN_LENGTH: usize = 8;
+const MAX_LENGTH: usize = 1024;
+
+/// Characters a `random` rotator draws from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Charset {
+    /// `A-Z`, `a-z`, and `0-9`.
+    #[default]
+    Alphanumeric,
+    /// Lowercase hexadecimal digits.
+    Hex,
+    /// Printable ASCII, excluding space, quotes, and backslash.
+    Ascii,
+}
+
+impl Charset {
+    fn chars(self) -> &'static [u8] {
+        match self {
+            Self::Alphanumeric => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+            Self::Hex => b"0123456789abcdef",
+            Self::Ascii => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!#$%&()*+,-./:;<=>?@[]^_{|}~"
+            }
+        }
+    }
+}
+
+/// How new values are produced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Rotator {
+    /// A random string.
+    Random {
+        /// Length in characters.
+        #[serde(default = "default_length")]
+        length: usize,
+        /// Character set.
+        #[serde(default)]
+        charset: Charset,
+    },
+    /// The password of a database engine static role, rotated in the
+    /// database first.
+    Database {
+        /// Mount path of the database engine.
+        #[serde(default = "default_database_mount")]
+        mount: String,
+        /// Static role whose password is rotated.
+        role: String,
+    },
+    /// A custom endpoint that returns `{"value": "..."}`.
+    Webhook {
+        /// Endpoint receiving `POST` requests.
+        url: String,
+        /// HMAC key for `X-ZVault-Signature`, if signing is enabled.
+        #[serde(default)]
+        secret: Option<String>,
+    },
+}
+
+fn default_length() -> usize {
+    32
+}
+
+fn default_database_mount() -> String {
+    "database/".to_owned()
+}
+
+/// When a policy rotates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Schedule {
+    /// Every this many seconds after the last rotation.
+    Interval(u64),
+    /// A five-field cron expression (`minute hour day month weekday`) or
+    /// one of `@hourly`, `@daily`, `@weekly`, `@monthly`, in UTC.
+    Cron(String),
+}
+
+impl Schedule {
+    /// The first rotation time strictly after `after`, or `None` if the
+    /// schedule never fires (e.g. `0 0 30 2 *`).
+    #[must_use]
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Interval(secs) => {
+                let secs = i64::try_from(*secs).ok()?;
+                after.checked_add_signed(chrono::Duration::seconds(secs))
+            }
+            Self::Cron(expression) => Cron::parse(expression).ok()?.next_after(after),
+        }
+    }
+
+    fn validate(&self) -> Result<(), RotationError> {
+        match self {
+            Self::Interval(secs) if *secs < MIN_INTERVAL_SECS => Err(invalid(format!(
+                "interval must be at least {MIN_INTERVAL_SECS} seconds"
+            ))),
+            Self::Interval(_) => Ok(()),
+            Self::Cron(expression) => {
+                let cron = Cron::parse(expression).map_err(invalid)?;
+                if cron.next_after(Utc::now()).is_none() {
+                    return Err(invalid(format!("cron '{expression}' never fires")));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A rotation policy for one field of a KV secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationPolicy {
+    /// KV mount path, e.g. `secret/`.
+    pub mount: String,
+    /// Secret path within the mount.
+    pub path: String,
+    /// Field of the secret that receives the new value.
+    pub field: String,
+    /// How new values are produced.
+    pub rotator: Rotator,
+    /// When rotations run.
+    pub schedule: Schedule,
+    /// When the policy was created.
+    pub created_at: DateTime<Utc>,
+    /// When the policy was last changed.
+    pub updated_at: DateTime<Utc>,
+    /// When the next rotation (or retry) is due.
+    pub next_rotation: DateTime<Utc>,
+    /// When the secret was last rotated successfully.
+    #[serde(default)]
+    pub last_rotated: Option<DateTime<Utc>>,
+    /// KV version written by the last successful rotation.
+    #[serde(default)]
+    pub last_version: Option<u64>,
+    /// Consecutive failed attempts.
+    #[serde(default)]
+    pub failures: u32,
+    /// Why the last attempt failed, cleared on success.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl RotationPolicy {
+    /// The policy's identifier: `{mount}{path}`.
+    #[must_use]
+    pub fn id(&self) -> String {
+        format!("{}{}", self.mount, self.path)
+    }
+}
+
+/// Parameters for creating or replacing a rotation policy.
+#[derive(Debug, Clone)]
+pub struct RotationParams {
+    /// Field to rotate (default `value`).
+    pub field: Option<String>,
+    /// How new values are produced.
+    pub rotator: Rotator,
+    /// When rotations run.
+    pub schedule: Schedule,
+}
+
+/// Outcome of a successful rotation.
+#[derive(Debug, Clone, Serialize)]
+pub struct Rotation {
+    /// KV mount path.
+    pub mount: String,
+    /// Secret path within the mount.
+    pub path: String,
+    /// The new KV version.
+    pub version: u64,
+    /// When the rotation happened.
+    pub rotated_at: DateTime<Utc>,
+    /// When the next rotation is due.
+    pub next_rotation: DateTime<Utc>,
+}
+
+/// Stores rotation policies and performs rotations.
+pub struct RotationManager {
+    barrier: Arc<Barrier>,
+    client: reqwest::Client,
+    /// Serializes rotations so the worker and manual triggers never rotate
+    /// the same secret twice at once.
+    rotate_lock: Mutex<()>,
+}
+
+impl RotationManager {
+    /// Create a manager backed by the barrier.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RotationError::Internal`] if the HTTP client cannot be built.
+    pub fn new(barrier: Arc<Barrier>) -> Result<Self, RotationError> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| RotationError::Internal {
+                reason: format!("failed to build http client: {e}"),
+            })?;
+        Ok(Self {
+            barrier,
+            client,
+            rotate_lock: Mutex::new(()),
+        })
+    }
+
+    /// Create or replace the policy for `path` on the KV mount `mount`.
+    ///
+    /// The next rotation is scheduled from now; rotation history is kept.
+    ///
+    /// # Errors
+    ///
+    /// - [`RotationError::Invalid`] if the path, field, rotator, or schedule
+    ///   is invalid.
+    /// - [`RotationError::Barrier`] if storage fails.
+    pub async fn put(
+        &self,
+        mount: &str,
+        path: &str,
+        params: RotationParams,
+    ) -> Result<RotationPolicy, RotationError> {
+        validate_path(mount, path)?;
+        let field = params.field.unwrap_or_else(|| "value".to_owned());
+        if field.is_empty() || field.len() > 128 || field.chars().any(char::is_control) {
+            return Err(invalid("field must be 1-128 printable characters"));
+        }
+        validate_rotator(&params.rotator)?;
+        params.schedule.validate()?;
+
+        let now = Utc::now();
+        let id = format!("{mount}{path}");
+        let existing = self.load(&id).await?;
+        let policy = RotationPolicy {
+            mount: mount.to_owned(),
+            path: path.to_owned(),
+            field,
+            rotator: params.rotator,
+            next_rotation: params.schedule.next_after(now).unwrap_or(now),
+            schedule: params.schedule,
+            created_at: existing.as_ref().map_or(now, |p| p.created_at),
+            updated_at: now,
+            last_rotated: existing.as_ref().and_then(|p| p.last_rotated),
+            last_version: existing.as_ref().and_then(|p| p.last_version),
+            failures: 0,
+            last_error: None,
+        };
+        self.store(&policy).await?;
+        Ok(policy)
+    }
+
+    /// Read the policy `id` (`{mount}{path}`).
+    ///
+    /// # Errors
+    ///
+    /// - [`RotationError::NotFound`] if it does not exist.
+    /// - [`RotationError::Barrier`] if storage fails.
+    pub async fn get(&self, id: &str) -> Result<RotationPolicy, RotationError> {
+        self.load(id).await?.ok_or_else(|| RotationError::NotFound {
+            path: id.to_owned(),
+        })
+    }
+
+    /// List all policies, sorted by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RotationError::Barrier`] if storage fails.
+    pub async fn list(&self) -> Result<Vec<RotationPolicy>, RotationError> {
+        let mut policies = Vec::new();
+        for key in self.barrier.list(POLICY_PREFIX).await? {
+            let Some(data) = self.barrier.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice::<RotationPolicy>(&data) {
+                Ok(policy) => policies.push(policy),
+                Err(e) => warn!(key = %key, error = %e, "skipping corrupt rotation policy"),
+            }
+        }
+        policies.sort_by_key(RotationPolicy::id);
+        Ok(policies)
+    }
+
+    /// Delete the policy `id`. The secret itself is left alone.
+    ///
+    /// # Errors
+    ///
+    /// - [`RotationError::NotFound`] if it does not exist.
+    /// - [`RotationError::Barrier`] if storage fails.
+    pub async fn delete(&self, id: &str) -> Result<(), RotationError> {
+        self.get(id).await?;
+        self.barrier.delete(&format!("{POLICY_PREFIX}{id}")).await?;
+        Ok(())
+    }
+
+    /// Policies whose next rotation is due by `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RotationError::Barrier`] if storage fails.
+    pub async fn due(&self, now: DateTime<Utc>) -> Result<Vec<RotationPolicy>, RotationError> {
+        let mut due: Vec<_> = self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|p| p.next_rotation <= now)
+            .collect();
+        due.sort_by_key(|p| p.next_rotation);
+        Ok(due)
+    }
+
+    /// Rotate the secret `id` now, writing a new version to `kv`.
+    ///
+    /// `kv` must be the engine mounted at the policy's mount; `database` the
+    /// engine a `database` rotator names, if it is mounted. On failure the
+    /// error is recorded on the policy and a retry is scheduled.
+    ///
+    /// # Errors
+    ///
+    /// - [`RotationError::NotFound`] if the policy does not exist.
+    /// - [`RotationError::Failed`] if no new value could be produced or
+    ///   stored; the secret is unchanged.
+    /// - [`RotationError::Barrier`] if storage fails.
+    pub async fn rotate(
+        &self,
+        id: &str,
+        kv: &KvEngine,
+        database: Option<&DatabaseEngine>,
+    ) -> Result<Rotation, RotationError> {
+        let _rotating = self.rotate_lock.lock().await;
+        // Re-read under the lock: the policy may have changed or been deleted.
+        let mut policy = self.get(id).await?;
+        let now = Utc::now();
+
+        match self.rotate_secret(&policy, kv, database).await {
+            Ok(version) => {
+                let next_rotation = policy.schedule.next_after(now).unwrap_or(now);
+                policy.last_rotated = Some(now);
+                policy.last_version = Some(version);
+                policy.next_rotation = next_rotation;
+                policy.failures = 0;
+                policy.last_error = None;
+                self.store(&policy).await?;
+                info!(secret = %id, version, "secret rotated");
+                Ok(Rotation {
+                    mount: policy.mount,
+                    path: policy.path,
+                    version,
+                    rotated_at: now,
+                    next_rotation,
+                })
+            }
+            Err(reason) => {
+                policy.failures = policy.failures.saturating_add(1);
+                let retry = now + backoff(policy.failures);
+                policy.next_rotation = policy
+                    .schedule
+                    .next_after(now)
+                    .map_or(retry, |next| next.min(retry));
+                policy.last_error = Some(reason.clone());
+                self.store(&policy).await?;
+                warn!(secret = %id, failures = policy.failures, error = %reason, "secret rotation failed, will retry");
+                Err(RotationError::Failed {
+                    path: id.to_owned(),
+                    reason,
+                })
+            }
+        }
+    }
+
+    // ── Private helpers ──────────────────────────────────────────────
+
+    /// Produce a new value and write it as a new KV version, returning the
+    /// version or the failure reason.
+    async fn rotate_secret(
+        &self,
+        policy: &RotationPolicy,
+        kv: &KvEngine,
+        database: Option<&DatabaseEngine>,
+    ) -> Result<u64, String> {
+        // Pin the version first so the write fails if someone else writes
+        // while the new value is being produced.
+        let cas = match kv.metadata(&policy.path).await {
+            Ok(meta) => meta.current_version,
+            Err(EngineError::NotFound { .. }) => 0,
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut data = match kv
+            .handle(&EngineRequest {
+                operation: Operation::Read,
+                path: policy.path.clone(),
+                data: None,
+            })
+            .await
+        {
+            Ok(response) => response
+                .data
+                .and_then(|d| d.get("data").and_then(|v| v.as_object().cloned()))
+                .unwrap_or_default(),
+            Err(EngineError::NotFound { .. }) => serde_json::Map::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let value = self.generate(policy, cas, database).await?;
+        data.insert(policy.field.clone(), serde_json::Value::String(value));
+
+        let response = kv
+            .write_cas(
+                &policy.path,
+                Some(serde_json::Value::Object(data)),
+                Some(cas),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        response
+            .data
+            .as_ref()
+            .and_then(|d| d.get("version"))
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| "write returned no version".to_owned())
+    }
+
+    async fn generate(
+        &self,
+        policy: &RotationPolicy,
+        version: u32,
+        database: Option<&DatabaseEngine>,
+    ) -> Result<String, String> {
+        match &policy.rotator {
+            Rotator::Random { length, charset } => random_string(*length, *charset),
+            Rotator::Database { mount, role } => {
+                let engine =
+                    database.ok_or_else(|| format!("no database engine mounted at '{mount}'"))?;
+                engine
+                    .rotate_static_role(role)
+                    .await
+                    .map(|creds| creds.password)
+                    .map_err(|e| e.to_string())
+            }
+            Rotator::Webhook { url, secret } => {
+                self.call_webhook(policy, version, url, secret.as_deref())
+                    .await
+            }
+        }
+    }
+
+    /// Ask a custom endpoint for the new value.
+    async fn call_webhook(
+        &self,
+        policy: &RotationPolicy,
+        version: u32,
+        url: &str,
+        secret: Option<&str>,
+    ) -> Result<String, String> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "mount": policy.mount,
+            "path": policy.path,
+            "field": policy.field,
+            "version": version,
+        }))
+        .map_err(|e| e.to_string())?;
+        let timestamp = Utc::now().timestamp().to_string();
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-ZVault-Timestamp", &timestamp);
+        if let Some(secret) = secret {
+            request = request.header(
+                "X-ZVault-Signature",
+                format!("sha256={}", sign(secret, &timestamp, &body)),
+            );
+        }
+        let response: serde_json::Value = request
+            .body(body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| format!("invalid rotator response: {e}"))?;
+        match response.get("value").and_then(serde_json::Value::as_str) {
+            Some(value) if !value.is_empty() => Ok(value.to_owned()),
+            _ => Err("rotator response has no \"value\" string".to_owned()),
+        }
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<RotationPolicy>, RotationError> {
+        let Some(data) = self.barrier.get(&format!("{POLICY_PREFIX}{id}")).await? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| RotationError::Internal {
+                reason: format!("corrupt rotation policy '{id}': {e}"),
+            })
+    }
+
+    async fn store(&self, policy: &RotationPolicy) -> Result<(), RotationError> {
+        let data = serde_json::to_vec(policy).map_err(|e| RotationError::Internal {
+            reason: format!("failed to serialize rotation policy: {e}"),
+        })?;
+        self.barrier
+            .put(&format!("{POLICY_PREFIX}{}", policy.id()), &data)
+            .await?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for RotationManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RotationManager").finish_non_exhaustive()
+    }
+}
+
+/// A random string of `length` characters from `charset`.
+fn random_string(length: usize, charset: Charset) -> Result<String, String> {
+    let chars = charset.chars();
+    // Reject bytes past the largest multiple of the alphabet size so every
+    // character is equally likely.
+    let limit = 256 - 256 % chars.len();
+    let mut out = String::with_capacity(length);
+    let mut buf = [0_u8; 64];
+    while out.len() < length {
+        OsRng
+            .try_fill_bytes(&mut buf)
+            .map_err(|_| "system random generator failed".to_owned())?;
+        for &b in &buf {
+            if usize::from(b) < limit && out.len() < length {
+                out.push(char::from(chars[usize::from(b) % chars.len()]));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Delay before retrying after `failures` consecutive failures.
+fn backoff(failures: u32) -> chrono::Duration {
+    let factor = 1_i32 << failures.saturating_sub(1).min(16);
+    (RETRY_BASE * factor).min(RETRY_MAX)
+}
+
+fn validate_path(mount: &str, path: &str) -> Result<(), RotationError> {
+    let safe = |s: &str| {
+        s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'/')
+    };
+    if mount.len() < 2 || !mount.ends_with('/') || !safe(mount) {
+        return Err(invalid(format!("invalid mount '{mount}'")));
+    }
+    if path.is_empty()
+        || path.starts_with('/')
+        || path.ends_with('/')
+        || path.contains("//")
+        || !safe(path)
+        || path.split('/').count() > 10
+    {
+        return Err(invalid(format!("invalid secret path '{path}'")));
+    }
+    Ok(())
+}
+
+fn validate_rotator(rotator: &Rotator) -> Result<(), RotationError> {
+    match rotator {
+        Rotator::Random { length, .. } if !(MIN_LENGTH..=MAX_LENGTH).contains(length) => {
+            Err(invalid(format!(
+                "length must be between {MIN_LENGTH} and {MAX_LENGTH}"
+            )))
+        }
+        Rotator::Random { .. } => Ok(()),
+        Rotator::Database { mount, role } => {
+            if mount.is_empty() || role.is_empty() {
+                return Err(invalid("database rotator needs a mount and a role"));
+            }
+            Ok(())
+        }
+        Rotator::Webhook { url, .. } => {
+            let parsed =
+                reqwest::Url::parse(url).map_err(|e| invalid(format!("webhook url: {e}")))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(invalid("webhook url must use http or https"));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> RotationError {
+    RotationError::Invalid {
+        reason: reason.into(),
+    }
+}
+
+// ── Cron ─────────────────────────────────────────────────────────────
+
+/// A parsed five-field cron expression. Each field is a bit set of the
+/// values it matches.
+#[derive(Debug)]
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether day-of-month / day-of-week were `*`. When both are
+    /// restricted a day matching either one fires, as in classic cron.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "cron '{expression}' must have 5 fields: minute hour day month weekday"
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // Both 0 and 7 mean Sunday.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first matching minute strictly after `after`.
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut t = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(chrono::Duration::minutes(1))?;
+        // Five years covers every satisfiable expression, leap days included.
+        let limit = after.checked_add_signed(chrono::Duration::days(5 * 366))?;
+        while t <= limit {
+            let date = t.date_naive();
+            if self.months & (1 << date.month()) == 0 {
+                let (year, month) = if date.month() == 12 {
+                    (date.year().checked_add(1)?, 1)
+                } else {
+                    (date.year(), date.month().saturating_add(1))
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !self.day_matches(date) {
+                t = date.succ_opt()?.and_hms_opt(0, 0, 0)?.and_utc();
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + chrono::Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += chrono::Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+/// Parse one cron field (`*`, `5`, `1-5`, `*/15`, `10-40/10`, or a comma
+/// list of those) into a bit set of values in `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let number = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|n| (min..=max).contains(n))
+            .ok_or_else(|| format!("cron value '{s}' is not in {min}-{max}"))
+    };
+    let mut bits = 0_u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid cron step in '{part}'"))?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (number(a)?, number(b)?),
+                // `5/15` means every 15 starting at 5.
+                None if part.contains('/') => (number(range)?, max),
+                None => {
+                    let n = number(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("invalid cron range '{range}'"));
+        }
+        let mut value = start;
+        while value <= end {
+            bits |= 1 << value;
+            value = value.saturating_add(step);
+        }
+    }
+    Ok(bits)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn setup() -> (RotationManager, KvEngine) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let kv = KvEngine::new(Arc::clone(&barrier), "kv/secret/".to_owned());
+        (RotationManager::new(barrier).unwrap(), kv)
+    }
+
+    fn random(schedule: Schedule) -> RotationParams {
+        RotationParams {
+            field: Some("password".to_owned()),
+            rotator: Rotator::Random {
+                length: 24,
+                charset: Charset::Hex,
+            },
+            schedule,
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    async fn read(kv: &KvEngine, path: &str) -> serde_json::Value {
+        kv.handle(&EngineRequest {
+            operation: Operation::Read,
+            path: path.to_owned(),
+            data: None,
+        })
+        .await
+        .unwrap()
+        .data
+        .unwrap()
+    }
+
+    #[test]
+    fn cron_next_after() {
+        let daily = Schedule::Cron("30 3 * * *".to_owned());
+        assert_eq!(
+            daily.next_after(at("2026-03-01T03:30:00Z")),
+            Some(at("2026-03-02T03:30:00Z"))
+        );
+        let quarter = Schedule::Cron("*/15 * * * *".to_owned());
+        assert_eq!(
+            quarter.next_after(at("2026-03-01T10:07:42Z")),
+            Some(at("2026-03-01T10:15:00Z"))
+        );
+        // Mondays (2026-03-02 is a Monday), via the 1-5 range.
+        let weekdays = Schedule::Cron("0 9 * * 1-5".to_owned());
+        assert_eq!(
+            weekdays.next_after(at("2026-02-28T12:00:00Z")),
+            Some(at("2026-03-02T09:00:00Z"))
+        );
+        // Year rollover and `@monthly`.
+        assert_eq!(
+            Schedule::Cron("@monthly".to_owned()).next_after(at("2026-12-15T00:00:00Z")),
+            Some(at("2027-01-01T00:00:00Z"))
+        );
+        assert_eq!(
+            Schedule::Cron("0 0 29 2 *".to_owned()).next_after(at("2026-03-01T00:00:00Z")),
+            Some(at("2028-02-29T00:00:00Z"))
+        );
+        assert_eq!(
+            Schedule::Cron("0 0 30 2 *".to_owned()).next_after(at("2026-03-01T00:00:00Z")),
+            None
+        );
+    }
+
+    #[test]
+    fn cron_rejects_bad_expressions() {
+        for bad in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(Cron::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn random_string_uses_charset() {
+        let value = random_string(40, Charset::Hex).unwrap();
+        assert_eq!(value.len(), 40);
+        assert!(value.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_ne!(value, random_string(40, Charset::Hex).unwrap());
+    }
+
+    #[tokio::test]
+    async fn put_validates() {
+        let (mgr, _kv) = setup().await;
+        let short = mgr
+            .put("secret/", "app/db", random(Schedule::Interval(5)))
+            .await;
+        assert!(matches!(short, Err(RotationError::Invalid { .. })));
+        let traversal = mgr
+            .put("secret/", "../sys", random(Schedule::Interval(3600)))
+            .await;
+        assert!(matches!(traversal, Err(RotationError::Invalid { .. })));
+        let never = mgr
+            .put(
+                "secret/",
+                "app/db",
+                random(Schedule::Cron("0 0 31 4 *".to_owned())),
+            )
+            .await;
+        assert!(matches!(never, Err(RotationError::Invalid { .. })));
+    }
+
+    #[tokio::test]
+    async fn rotate_keeps_other_fields_and_bumps_version() {
+        let (mgr, kv) = setup().await;
+        kv.write_cas(
+            "app/db",
+            Some(serde_json::json!({ "username": "app", "password": "old" })),
+            None,
+        )
+        .await
+        .unwrap();
+        let policy = mgr
+            .put("secret/", "app/db", random(Schedule::Interval(3600)))
+            .await
+            .unwrap();
+        assert_eq!(policy.id(), "secret/app/db");
+        assert!(mgr.due(Utc::now()).await.unwrap().is_empty());
+
+        let rotation = mgr.rotate("secret/app/db", &kv, None).await.unwrap();
+        assert_eq!(rotation.version, 2);
+
+        let data = read(&kv, "app/db").await;
+        assert_eq!(data["data"]["username"], "app");
+        let password = data["data"]["password"].as_str().unwrap();
+        assert_eq!(password.len(), 24);
+        assert_ne!(password, "old");
+
+        let policy = mgr.get("secret/app/db").await.unwrap();
+        assert_eq!(policy.last_version, Some(2));
+        assert!(policy.last_rotated.is_some());
+        assert_eq!(mgr.due(policy.next_rotation).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rotate_creates_missing_secret() {
+        let (mgr, kv) = setup().await;
+        mgr.put("secret/", "new/key", random(Schedule::Interval(3600)))
+            .await
+            .unwrap();
+        let rotation = mgr.rotate("secret/new/key", &kv, None).await.unwrap();
+        assert_eq!(rotation.version, 1);
+        assert!(read(&kv, "new/key").await["data"]["password"].is_string());
+    }
+
+    #[tokio::test]
+    async fn failed_rotation_is_recorded_and_retried() {
+        let (mgr, kv) = setup().await;
+        kv.write_cas("app/db", Some(serde_json::json!({ "value": "old" })), None)
+            .await
+            .unwrap();
+        mgr.put(
+            "secret/",
+            "app/db",
+            RotationParams {
+                field: None,
+                rotator: Rotator::Database {
+                    mount: "database/".to_owned(),
+                    role: "app".to_owned(),
+                },
+                schedule: Schedule::Interval(86_400),
+            },
+        )
+        .await
+        .unwrap();
+
+        let err = mgr.rotate("secret/app/db", &kv, None).await.unwrap_err();
+        assert!(matches!(err, RotationError::Failed { .. }));
+        assert_eq!(read(&kv, "app/db").await["data"]["value"], "old");
+
+        let policy = mgr.get("secret/app/db").await.unwrap();
+        assert_eq!(policy.failures, 1);
+        assert!(policy.last_error.unwrap().contains("no database engine"));
+        // Retried after the backoff, well before the daily schedule.
+        assert!(policy.next_rotation <= Utc::now() + RETRY_BASE);
+        assert!(policy.last_rotated.is_none());
+    }
+
+    #[tokio::test]
+    async fn delete_removes_policy() {
+        let (mgr, _kv) = setup().await;
+        mgr.put("secret/", "app/db", random(Schedule::Interval(3600)))
+            .await
+            .unwrap();
+        assert_eq!(mgr.list().await.unwrap().len(), 1);
+        mgr.delete("secret/app/db").await.unwrap();
+        assert!(mgr.list().await.unwrap().is_empty());
+        assert!(matches!(
+            mgr.delete("secret/app/db").await,
+            Err(RotationError::NotFound { .. })
+        ));
+    }
+}
@@ -13,6 +13,25 @@
 //!
 //! 3. **Seal**: Zeroize the root key from memory, seal the barrier.
 //!
+//! With a [`SealWrapper`] configured (auto-unseal), the root key is encrypted
+//! by the KMS instead, [`SealManager::auto_unseal`] replaces share submission,
+//! and operators receive Shamir shares of a *recovery key* that authorizes
+//! migrating back to unseal shares. Existing vaults move between the two
+//! modes with [`SealManager::migrate_to_auto`] and
+//! [`SealManager::migrate_to_shamir`].
+//!
+//! 4. **Rekey**: While unsealed, an operator starts a rekey with a new share
+//!    count and threshold. Holders of the current shares submit them against
+//!    the rekey's nonce; once the current threshold is reached the root key
+//!    is re-encrypted under a fresh unseal key and the new shares are
+//!    returned. With auto-unseal, the recovery key is rekeyed instead.
+//!
+//! 5. **Generate root**: While unsealed, an operator starts an attempt and
+//!    receives a one-time pad. Shareholders submit current shares against
+//!    the attempt's nonce; at the threshold a new root token is minted and
+//!    returned only XOR-ed with the pad, so whoever holds the pad — not the
+//!    shareholders or anyone watching the responses — learns the token.
+//!
 //! # Security model
 //!
 //! - The unseal key is never stored. It exists only as Shamir shares held by
@@ -20,19 +39,28 @@
 //! - The root key is stored encrypted by the unseal key at `sys/seal/root_key`.
 //! - Seal config (threshold, share count) is stored at `sys/seal/config`.
 //! - Shares are shown once at init time and never persisted by the server.
+//! - The recovery key is stored encrypted by the root key at
+//!   `sys/seal/recovery_key`, so it can only be checked once the KMS has
+//!   released the root key.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
 use base64::Engine;
-use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
 use serde::{Deserialize, Serialize};
 use sharks::{Share, Sharks};
+use subtle::ConstantTimeEq;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
 
 use crate::barrier::Barrier;
 use crate::crypto::{self, EncryptionKey};
-use crate::error::SealError;
+use crate::error::{BarrierError, SealError};
+use crate::kms::SealWrapper;
 
 /// Storage key for the encrypted root key.
 const ROOT_KEY_PATH: &str = "sys/seal/root_key";
@@ -40,6 +68,12 @@ const ROOT_KEY_PATH: &str = "sys/seal/root_key";
 /// Storage key for the seal configuration.
 const SEAL_CONFIG_PATH: &str = "sys/seal/config";
 
+/// Storage key for the recovery key (auto-unseal only), encrypted by the root key.
+const RECOVERY_KEY_PATH: &str = "sys/seal/recovery_key";
+
+/// Seal type reported for vaults unsealed with Shamir shares.
+pub const SEAL_TYPE_SHAMIR: &str = "shamir";
+
 /// Persisted seal configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SealConfig {
@@ -47,13 +81,20 @@ pub struct SealConfig {
     pub shares: u8,
     /// Minimum shares required to reconstruct the unseal key.
     pub threshold: u8,
+    /// Name of the [`SealWrapper`] holding the root key, or `None` for Shamir.
+    /// With a wrapper, `shares` and `threshold` describe the recovery key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapper: Option<String>,
 }
 
 /// Result of a successful vault initialization.
 #[derive(Debug)]
 pub struct InitResult {
     /// Base64-encoded unseal key shares. Shown once, never stored.
+    /// Empty with auto-unseal.
     pub unseal_shares: Vec<String>,
+    /// Base64-encoded recovery key shares. Empty with Shamir unseal.
+    pub recovery_shares: Vec<String>,
     /// The root token for initial authentication.
     pub root_token: String,
 }
@@ -67,6 +108,113 @@ pub struct UnsealProgress {
     pub submitted: u8,
 }
 
+/// Progress of an ongoing rekey.
+#[derive(Debug, Clone, Serialize)]
+pub struct RekeyStatus {
+    /// Identifies this rekey; every share submission must quote it.
+    pub nonce: String,
+    /// Share count of the new key.
+    pub shares: u8,
+    /// Threshold of the new key.
+    pub threshold: u8,
+    /// Current shares submitted so far.
+    pub progress: u8,
+    /// Current shares needed to authorize the rekey.
+    pub required: u8,
+}
+
+/// Outcome of submitting a rekey share.
+#[derive(Debug)]
+pub enum RekeyUpdate {
+    /// More current shares are needed.
+    Pending(RekeyStatus),
+    /// The rekey finished. These shares (unseal shares, or recovery shares
+    /// with auto-unseal) replace the old ones, which stop working.
+    Complete(Vec<String>),
+}
+
+/// Progress of an ongoing root token generation.
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerateRootStatus {
+    /// Identifies this attempt; every share submission must quote it.
+    pub nonce: String,
+    /// Current shares submitted so far.
+    pub progress: u8,
+    /// Current shares needed to mint the token.
+    pub required: u8,
+    /// Length of the one-time pad, which matches the encoded token's.
+    pub otp_length: usize,
+    /// The one-time pad. Only returned when the attempt starts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otp: Option<String>,
+}
+
+/// Outcome of submitting a share towards a new root token.
+#[derive(Debug)]
+pub enum GenerateRootUpdate {
+    /// More current shares are needed.
+    Pending(GenerateRootStatus),
+    /// The shares were verified and a root token minted.
+    Complete(GeneratedRoot),
+}
+
+/// A freshly minted root token.
+pub struct GeneratedRoot {
+    /// The token itself, for the caller to register. Never returned to clients.
+    pub root_token: Zeroizing<String>,
+    /// Base64 of the token XOR-ed with the attempt's one-time pad.
+    pub encoded_token: String,
+}
+
+impl std::fmt::Debug for GeneratedRoot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeneratedRoot")
+            .field("encoded_token", &self.encoded_token)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A root token generation awaiting current shares.
+struct PendingGenerateRoot {
+    nonce: String,
+    otp: Zeroizing<String>,
+    required: u8,
+    submitted: Vec<Zeroizing<String>>,
+}
+
+impl PendingGenerateRoot {
+    fn status(&self) -> GenerateRootStatus {
+        GenerateRootStatus {
+            nonce: self.nonce.clone(),
+            progress: u8::try_from(self.submitted.len()).unwrap_or(u8::MAX),
+            required: self.required,
+            otp_length: self.otp.len(),
+            otp: None,
+        }
+    }
+}
+
+/// A rekey awaiting current shares.
+struct PendingRekey {
+    nonce: String,
+    shares: u8,
+    threshold: u8,
+    required: u8,
+    submitted: Vec<Zeroizing<String>>,
+}
+
+impl PendingRekey {
+    fn status(&self) -> RekeyStatus {
+        RekeyStatus {
+            nonce: self.nonce.clone(),
+            shares: self.shares,
+            threshold: self.threshold,
+            progress: u8::try_from(self.submitted.len()).unwrap_or(u8::MAX),
+            required: self.required,
+        }
+    }
+}
+
 /// Manages the seal/unseal lifecycle.
 ///
 /// Holds the barrier, accumulated unseal shares, and seal configuration.
@@ -75,6 +223,15 @@ pub struct SealManager {
     barrier: Arc<Barrier>,
     /// Accumulated raw share bytes during unseal. Cleared after success or seal.
     pending_shares: Mutex<Vec<Vec<u8>>>,
+    /// KMS for auto-unseal, if configured.
+    wrapper: Option<Arc<dyn SealWrapper>>,
+    /// The rekey in progress, if any. Cleared on completion, failure, or seal.
+    rekey: Mutex<Option<PendingRekey>>,
+    /// The root token generation in progress, if any. Cleared on completion,
+    /// failure, or seal.
+    generate_root: Mutex<Option<PendingGenerateRoot>>,
+    /// Unseal attempts that failed since the last successful unseal.
+    failed_unseals: AtomicU32,
 }
 
 impl SealManager {
@@ -84,9 +241,23 @@ impl SealManager {
         Self {
             barrier,
             pending_shares: Mutex::new(Vec::new()),
+            wrapper: None,
+            rekey: Mutex::new(None),
+            generate_root: Mutex::new(None),
+            failed_unseals: AtomicU32::new(0),
         }
     }
 
+    /// Use `wrapper` for auto-unseal.
+    ///
+    /// New vaults are initialized with it; existing Shamir vaults keep using
+    /// shares until migrated with [`migrate_to_auto`](Self::migrate_to_auto).
+    #[must_use]
+    pub fn with_wrapper(mut self, wrapper: Arc<dyn SealWrapper>) -> Self {
+        self.wrapper = Some(wrapper);
+        self
+    }
+
     /// Initialize a new vault.
     ///
     /// Generates a root key and unseal key, encrypts the root key with the
@@ -96,6 +267,10 @@ impl SealManager {
     /// The vault is left in a **sealed** state after init. The operator must
     /// unseal it using the returned shares.
     ///
+    /// With a seal wrapper, the root key is encrypted by the KMS instead and
+    /// the shares returned are recovery shares; unseal with
+    /// [`auto_unseal`](Self::auto_unseal).
+    ///
     /// # Errors
     ///
     /// - [`SealError::AlreadyInitialized`] if the vault has already been initialized.
@@ -114,36 +289,32 @@ impl SealManager {
         // Generate root key (256-bit, will encrypt all vault data).
         let root_key = EncryptionKey::generate();
 
-        // Generate unseal key (256-bit, will be split into Shamir shares).
-        let unseal_key = EncryptionKey::generate();
-
-        // Encrypt root key with unseal key.
-        let encrypted_root = crypto::encrypt(&unseal_key, root_key.as_bytes())?;
-
-        // Split unseal key into Shamir shares.
-        let shamir = Sharks(threshold);
-        let dealer = shamir.dealer(unseal_key.as_bytes());
-        let share_vec: Vec<Share> = dealer.take(usize::from(shares)).collect();
-        let encoded_shares: Vec<String> = share_vec
-            .iter()
-            .map(|s| BASE64.encode(Vec::from(s)))
-            .collect();
-
-        // Store encrypted root key (raw — it's already encrypted by unseal key).
-        self.barrier
-            .put_raw(ROOT_KEY_PATH, &encrypted_root)
-            .await
-            .map_err(SealError::Barrier)?;
+        let (unseal_shares, recovery_shares) = if let Some(wrapper) = &self.wrapper {
+            // Store the KMS-wrapped root key; operators get recovery shares.
+            let recovery_shares = self
+                .store_recovery_key(&root_key, shares, threshold)
+                .await?;
+            let wrapped_root = wrapper.wrap(root_key.as_bytes()).await?;
+            self.barrier
+                .put_raw(ROOT_KEY_PATH, &wrapped_root)
+                .await
+                .map_err(SealError::Barrier)?;
+            (Vec::new(), recovery_shares)
+        } else {
+            (
+                self.store_shamir_root_key(&root_key, shares, threshold)
+                    .await?,
+                Vec::new(),
+            )
+        };
 
         // Store seal config (raw — not sensitive, but stored before barrier is unsealed).
-        let config = SealConfig { shares, threshold };
-        let config_bytes = serde_json::to_vec(&config).map_err(|e| SealError::InvalidConfig {
-            reason: format!("failed to serialize seal config: {e}"),
-        })?;
-        self.barrier
-            .put_raw(SEAL_CONFIG_PATH, &config_bytes)
-            .await
-            .map_err(SealError::Barrier)?;
+        self.save_config(&SealConfig {
+            shares,
+            threshold,
+            wrapper: self.wrapper.as_ref().map(|w| w.name().to_owned()),
+        })
+        .await?;
 
         // Generate root token (UUID v4).
         let root_token = uuid::Uuid::new_v4().to_string();
@@ -151,7 +322,8 @@ impl SealManager {
         info!(shares = shares, threshold = threshold, "vault initialized");
 
         Ok(InitResult {
-            unseal_shares: encoded_shares,
+            unseal_shares,
+            recovery_shares,
             root_token,
         })
     }
@@ -165,6 +337,7 @@ impl SealManager {
     ///
     /// - [`SealError::NotInitialized`] if the vault hasn't been initialized.
     /// - [`SealError::AlreadyUnsealed`] if the vault is already unsealed.
+    /// - [`SealError::InvalidConfig`] if the vault uses auto-unseal.
     /// - [`SealError::InvalidShare`] if the share is malformed.
     /// - [`SealError::RecoveryFailed`] if share reconstruction fails.
     /// - [`SealError::RootKeyDecryption`] if the reconstructed key can't decrypt the root key.
@@ -182,15 +355,18 @@ impl SealManager {
             return Err(SealError::AlreadyUnsealed);
         }
 
-        // Decode the share.
-        let share_bytes = BASE64
-            .decode(share_b64)
-            .map_err(|e| SealError::InvalidShare {
-                reason: format!("base64 decode failed: {e}"),
-            })?;
-
         // Load config to know the threshold.
         let config = self.load_config().await?;
+        if let Some(wrapper) = &config.wrapper {
+            return Err(SealError::InvalidConfig {
+                reason: format!(
+                    "vault uses auto-unseal ({wrapper}); unseal shares are not accepted"
+                ),
+            });
+        }
+
+        // Decode the share.
+        let share_bytes = decode_share(share_b64).map_err(|e| self.unseal_failed(e))?;
 
         // Accumulate the share.
         let mut pending = self.pending_shares.lock().await;
@@ -206,65 +382,413 @@ impl SealManager {
         }
 
         // We have enough shares — attempt reconstruction.
-        let shamir = Sharks(config.threshold);
-        let parsed_shares: Result<Vec<Share>, SealError> = pending
-            .iter()
-            .map(|bytes| {
-                Share::try_from(bytes.as_slice()).map_err(|e| SealError::InvalidShare {
-                    reason: format!("share deserialization failed: {e}"),
-                })
-            })
-            .collect();
-        let parsed_shares = parsed_shares?;
-
-        let unseal_key_bytes =
-            shamir
-                .recover(&parsed_shares)
-                .map_err(|e| SealError::RecoveryFailed {
-                    reason: e.to_string(),
-                })?;
+        let recovered = recover_key(config.threshold, &pending);
 
         // Clear pending shares immediately.
         pending.clear();
         drop(pending);
 
-        // Reconstruct the unseal key.
-        let unseal_key_array: [u8; 32] =
-            unseal_key_bytes
-                .try_into()
-                .map_err(|_| SealError::RecoveryFailed {
-                    reason: "recovered key is not 32 bytes".to_owned(),
-                })?;
-        let unseal_key = EncryptionKey::from_bytes(unseal_key_array);
+        let root_key = match recovered {
+            Ok(key) => self.decrypt_shamir_root_key(&key).await,
+            Err(e) => Err(e),
+        }
+        .map_err(|e| self.unseal_failed(e))?;
 
-        // Load and decrypt the root key.
-        let encrypted_root = self
-            .barrier
-            .get_raw(ROOT_KEY_PATH)
+        // Unseal the barrier.
+        self.barrier
+            .unseal(root_key)
             .await
-            .map_err(SealError::Barrier)?
-            .ok_or(SealError::NotInitialized)?;
+            .map_err(SealError::Barrier)?;
+        self.failed_unseals.store(0, Ordering::Relaxed);
 
-        let root_key_bytes = crypto::decrypt(&unseal_key, &encrypted_root).map_err(|e| {
-            SealError::RootKeyDecryption {
-                reason: e.to_string(),
-            }
-        })?;
+        info!("vault unsealed");
 
-        let root_key_array: [u8; 32] =
-            root_key_bytes
-                .try_into()
-                .map_err(|_| SealError::RootKeyDecryption {
-                    reason: "decrypted root key is not 32 bytes".to_owned(),
-                })?;
-        let root_key = EncryptionKey::from_bytes(root_key_array);
+        Ok(None)
+    }
 
-        // Unseal the barrier.
-        self.barrier.unseal(root_key).await;
+    /// Unseal the vault with the configured seal wrapper.
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::NotInitialized`] if the vault hasn't been initialized.
+    /// - [`SealError::AlreadyUnsealed`] if the vault is already unsealed.
+    /// - [`SealError::InvalidConfig`] if the vault uses Shamir unseal shares.
+    /// - [`SealError::Wrapper`] if the vault's wrapper is not configured or
+    ///   the KMS cannot decrypt the root key.
+    pub async fn auto_unseal(&self) -> Result<(), SealError> {
+        if !self.is_initialized().await? {
+            return Err(SealError::NotInitialized);
+        }
+        if self.barrier.is_unsealed().await {
+            return Err(SealError::AlreadyUnsealed);
+        }
 
-        info!("vault unsealed");
+        let config = self.load_config().await?;
+        let wrapper = self.wrapper_for(&config)?;
+        let root_key = self
+            .unwrap_root_key(wrapper.as_ref())
+            .await
+            .map_err(|e| self.unseal_failed(e))?;
+        self.barrier
+            .unseal(root_key)
+            .await
+            .map_err(SealError::Barrier)?;
+        self.failed_unseals.store(0, Ordering::Relaxed);
 
-        Ok(None)
+        info!(seal = wrapper.name(), "vault auto-unsealed");
+
+        Ok(())
+    }
+
+    /// Check recovery shares against the stored recovery key.
+    ///
+    /// Needs at least `threshold` shares and the vault's seal wrapper, which
+    /// releases the root key that encrypts the recovery key.
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::InvalidConfig`] if the vault uses Shamir unseal shares.
+    /// - [`SealError::InvalidShare`] if a share is malformed.
+    /// - [`SealError::RecoveryFailed`] if the shares do not reconstruct the
+    ///   recovery key.
+    /// - [`SealError::Wrapper`] if the seal wrapper is unavailable.
+    pub async fn verify_recovery_shares(&self, shares: &[String]) -> Result<(), SealError> {
+        self.recovery_root_key(shares).await.map(drop)
+    }
+
+    /// Move an unsealed Shamir vault to auto-unseal.
+    ///
+    /// `unseal_shares` must reconstruct the current unseal key. The root key
+    /// is re-encrypted by the configured wrapper and a recovery key is split
+    /// with the existing share count and threshold; the vault stays unsealed.
+    /// Returns the new recovery shares; the old unseal shares stop working,
+    /// and a rekey in progress is cancelled.
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::NotInitialized`] if the vault hasn't been initialized.
+    /// - [`SealError::Barrier`] if the vault is sealed.
+    /// - [`SealError::InvalidConfig`] if the vault already uses auto-unseal
+    ///   or no wrapper is configured.
+    /// - [`SealError::InvalidShare`], [`SealError::RecoveryFailed`], or
+    ///   [`SealError::RootKeyDecryption`] if the shares are wrong.
+    pub async fn migrate_to_auto(
+        &self,
+        unseal_shares: &[String],
+    ) -> Result<Vec<String>, SealError> {
+        self.ensure_unsealed().await?;
+        let config = self.load_config().await?;
+        if let Some(current) = &config.wrapper {
+            return Err(SealError::InvalidConfig {
+                reason: format!("vault already uses auto-unseal ({current})"),
+            });
+        }
+        let wrapper = self
+            .wrapper
+            .clone()
+            .ok_or_else(|| SealError::InvalidConfig {
+                reason: "no seal wrapper is configured to migrate to".to_owned(),
+            })?;
+
+        let decoded = unseal_shares
+            .iter()
+            .map(|s| decode_share(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        let unseal_key = recover_key(config.threshold, &decoded)?;
+        let root_key = self.decrypt_shamir_root_key(&unseal_key).await?;
+
+        let recovery_shares = self
+            .store_recovery_key(&root_key, config.shares, config.threshold)
+            .await?;
+        let wrapped_root = wrapper.wrap(root_key.as_bytes()).await?;
+        self.barrier
+            .put_raw(ROOT_KEY_PATH, &wrapped_root)
+            .await
+            .map_err(SealError::Barrier)?;
+        self.save_config(&SealConfig {
+            wrapper: Some(wrapper.name().to_owned()),
+            ..config
+        })
+        .await?;
+        *self.rekey.lock().await = None;
+
+        info!(seal = wrapper.name(), "vault migrated to auto-unseal");
+
+        Ok(recovery_shares)
+    }
+
+    /// Move an unsealed auto-unseal vault back to Shamir unseal shares.
+    ///
+    /// `recovery_shares` must reconstruct the recovery key, and the vault's
+    /// wrapper must still be configured to release the root key. A new unseal
+    /// key is split with the existing share count and threshold and the
+    /// recovery key is removed; the vault stays unsealed. Returns the new
+    /// unseal shares; a rekey in progress is cancelled.
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::NotInitialized`] if the vault hasn't been initialized.
+    /// - [`SealError::Barrier`] if the vault is sealed.
+    /// - [`SealError::InvalidConfig`] if the vault already uses unseal shares.
+    /// - [`SealError::InvalidShare`] or [`SealError::RecoveryFailed`] if the
+    ///   recovery shares are wrong.
+    /// - [`SealError::Wrapper`] if the seal wrapper is unavailable.
+    pub async fn migrate_to_shamir(
+        &self,
+        recovery_shares: &[String],
+    ) -> Result<Vec<String>, SealError> {
+        self.ensure_unsealed().await?;
+        let (config, root_key) = self.recovery_root_key(recovery_shares).await?;
+
+        let unseal_shares = self
+            .store_shamir_root_key(&root_key, config.shares, config.threshold)
+            .await?;
+        self.save_config(&SealConfig {
+            wrapper: None,
+            ..config
+        })
+        .await?;
+        self.barrier
+            .delete(RECOVERY_KEY_PATH)
+            .await
+            .map_err(SealError::Barrier)?;
+        *self.rekey.lock().await = None;
+
+        info!("vault migrated to Shamir unseal shares");
+
+        Ok(unseal_shares)
+    }
+
+    /// Start a rekey to `shares` new shares with `threshold`.
+    ///
+    /// The vault must be unsealed. Nothing changes until holders of the
+    /// current shares authorize the rekey with [`rekey_update`](Self::rekey_update).
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::NotInitialized`] if the vault hasn't been initialized.
+    /// - [`SealError::Barrier`] if the vault is sealed.
+    /// - [`SealError::InvalidConfig`] if the new share count or threshold are
+    ///   out of bounds.
+    /// - [`SealError::RekeyInProgress`] if a rekey is already in progress.
+    pub async fn rekey_init(&self, shares: u8, threshold: u8) -> Result<RekeyStatus, SealError> {
+        validate_config(shares, threshold)?;
+        self.ensure_unsealed().await?;
+        let config = self.load_config().await?;
+
+        let mut rekey = self.rekey.lock().await;
+        if rekey.is_some() {
+            return Err(SealError::RekeyInProgress);
+        }
+        let pending = PendingRekey {
+            nonce: uuid::Uuid::new_v4().to_string(),
+            shares,
+            threshold,
+            required: config.threshold,
+            submitted: Vec::new(),
+        };
+        let status = pending.status();
+        *rekey = Some(pending);
+
+        info!(shares = shares, threshold = threshold, "rekey started");
+
+        Ok(status)
+    }
+
+    /// Submit a current share towards the rekey identified by `nonce`.
+    ///
+    /// When the current threshold is reached the shares are verified, the
+    /// root key (or, with auto-unseal, a new recovery key) is split into the
+    /// new shares, and the rekey ends. If verification fails the rekey is
+    /// cancelled and must be started again.
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::NoRekeyInProgress`] if no rekey is in progress.
+    /// - [`SealError::RekeyNonceMismatch`] if `nonce` is not the current one.
+    /// - [`SealError::InvalidShare`] if the share is malformed or repeated.
+    /// - [`SealError::RecoveryFailed`] or [`SealError::RootKeyDecryption`] if
+    ///   the shares do not reconstruct the current key.
+    pub async fn rekey_update(
+        &self,
+        nonce: &str,
+        share_b64: &str,
+    ) -> Result<RekeyUpdate, SealError> {
+        self.ensure_unsealed().await?;
+
+        let mut rekey = self.rekey.lock().await;
+        let pending = rekey.as_mut().ok_or(SealError::NoRekeyInProgress)?;
+        if !bool::from(pending.nonce.as_bytes().ct_eq(nonce.as_bytes())) {
+            return Err(SealError::RekeyNonceMismatch);
+        }
+        decode_share(share_b64)?;
+        if pending.submitted.iter().any(|s| s.as_str() == share_b64) {
+            return Err(SealError::InvalidShare {
+                reason: "share was already submitted for this rekey".to_owned(),
+            });
+        }
+        pending.submitted.push(Zeroizing::new(share_b64.to_owned()));
+        if pending.submitted.len() < usize::from(pending.required) {
+            return Ok(RekeyUpdate::Pending(pending.status()));
+        }
+
+        // Threshold reached: the rekey ends here whether or not it succeeds.
+        let Some(pending) = rekey.take() else {
+            return Err(SealError::NoRekeyInProgress);
+        };
+        let current: Vec<String> = pending.submitted.iter().map(|s| s.to_string()).collect();
+        let new_shares = self
+            .rekey_with(&current, pending.shares, pending.threshold)
+            .await?;
+
+        info!(
+            shares = pending.shares,
+            threshold = pending.threshold,
+            "rekey completed"
+        );
+
+        Ok(RekeyUpdate::Complete(new_shares))
+    }
+
+    /// Cancel the rekey in progress, discarding submitted shares.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SealError::NoRekeyInProgress`] if no rekey is in progress.
+    pub async fn rekey_cancel(&self) -> Result<(), SealError> {
+        self.rekey
+            .lock()
+            .await
+            .take()
+            .map(drop)
+            .ok_or(SealError::NoRekeyInProgress)?;
+        info!("rekey cancelled");
+        Ok(())
+    }
+
+    /// The rekey in progress, if any.
+    pub async fn rekey_status(&self) -> Option<RekeyStatus> {
+        self.rekey.lock().await.as_ref().map(PendingRekey::status)
+    }
+
+    /// Start generating a new root token.
+    ///
+    /// The returned status carries the attempt's one-time pad, which is
+    /// shown only here and is needed to decode the finished token.
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::NotInitialized`] if the vault hasn't been initialized.
+    /// - [`SealError::Barrier`] if the vault is sealed.
+    /// - [`SealError::GenerateRootInProgress`] if an attempt is already in
+    ///   progress.
+    pub async fn generate_root_init(&self) -> Result<GenerateRootStatus, SealError> {
+        self.ensure_unsealed().await?;
+        let config = self.load_config().await?;
+
+        let mut generate_root = self.generate_root.lock().await;
+        if generate_root.is_some() {
+            return Err(SealError::GenerateRootInProgress);
+        }
+        let pending = PendingGenerateRoot {
+            nonce: uuid::Uuid::new_v4().to_string(),
+            otp: generate_otp(),
+            required: config.threshold,
+            submitted: Vec::new(),
+        };
+        let mut status = pending.status();
+        status.otp = Some(pending.otp.to_string());
+        *generate_root = Some(pending);
+
+        info!("root token generation started");
+
+        Ok(status)
+    }
+
+    /// Submit a current share towards the root token generation identified
+    /// by `nonce`.
+    ///
+    /// When the current threshold is reached the shares are verified and a
+    /// new root token is minted; the caller must register
+    /// [`GeneratedRoot::root_token`] with the token store. The attempt ends
+    /// there whether or not verification succeeds.
+    ///
+    /// # Errors
+    ///
+    /// - [`SealError::NoGenerateRootInProgress`] if no attempt is in progress.
+    /// - [`SealError::GenerateRootNonceMismatch`] if `nonce` is not the
+    ///   current one.
+    /// - [`SealError::InvalidShare`] if the share is malformed or repeated.
+    /// - [`SealError::RecoveryFailed`] or [`SealError::RootKeyDecryption`] if
+    ///   the shares do not reconstruct the current key.
+    pub async fn generate_root_update(
+        &self,
+        nonce: &str,
+        share_b64: &str,
+    ) -> Result<GenerateRootUpdate, SealError> {
+        self.ensure_unsealed().await?;
+
+        let mut generate_root = self.generate_root.lock().await;
+        let pending = generate_root
+            .as_mut()
+            .ok_or(SealError::NoGenerateRootInProgress)?;
+        if !bool::from(pending.nonce.as_bytes().ct_eq(nonce.as_bytes())) {
+            return Err(SealError::GenerateRootNonceMismatch);
+        }
+        decode_share(share_b64)?;
+        if pending.submitted.iter().any(|s| s.as_str() == share_b64) {
+            return Err(SealError::InvalidShare {
+                reason: "share was already submitted for this root token generation".to_owned(),
+            });
+        }
+        pending.submitted.push(Zeroizing::new(share_b64.to_owned()));
+        if pending.submitted.len() < usize::from(pending.required) {
+            return Ok(GenerateRootUpdate::Pending(pending.status()));
+        }
+
+        // Threshold reached: the attempt ends here whether or not it succeeds.
+        let Some(pending) = generate_root.take() else {
+            return Err(SealError::NoGenerateRootInProgress);
+        };
+        let current: Vec<String> = pending.submitted.iter().map(|s| s.to_string()).collect();
+        self.authorized_root_key(&current).await?;
+
+        let root_token = Zeroizing::new(uuid::Uuid::new_v4().to_string());
+        let encoded_token = encode_root_token(&root_token, &pending.otp)?;
+
+        info!("root token generated");
+
+        Ok(GenerateRootUpdate::Complete(GeneratedRoot {
+            root_token,
+            encoded_token,
+        }))
+    }
+
+    /// Cancel the root token generation in progress, discarding submitted
+    /// shares.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SealError::NoGenerateRootInProgress`] if no attempt is in
+    /// progress.
+    pub async fn generate_root_cancel(&self) -> Result<(), SealError> {
+        self.generate_root
+            .lock()
+            .await
+            .take()
+            .map(drop)
+            .ok_or(SealError::NoGenerateRootInProgress)?;
+        info!("root token generation cancelled");
+        Ok(())
+    }
+
+    /// The root token generation in progress, if any.
+    pub async fn generate_root_status(&self) -> Option<GenerateRootStatus> {
+        self.generate_root
+            .lock()
+            .await
+            .as_ref()
+            .map(PendingGenerateRoot::status)
     }
 
     /// Seal the vault, zeroizing the root key from memory.
@@ -277,8 +801,10 @@ impl SealManager {
             return Err(SealError::AlreadySealed);
         }
 
-        // Clear any pending shares.
+        // Clear any pending shares and abandon a rekey or root generation.
         self.pending_shares.lock().await.clear();
+        self.rekey.lock().await.take();
+        self.generate_root.lock().await.take();
 
         // Seal the barrier (zeroizes root key).
         self.barrier.seal().await;
@@ -288,6 +814,16 @@ impl SealManager {
         Ok(())
     }
 
+    /// Unseal attempts that failed since the last successful unseal.
+    ///
+    /// An attempt fails when a share is malformed, when a full set of shares
+    /// does not reconstruct the unseal key, or when the KMS cannot release
+    /// the root key.
+    #[must_use]
+    pub fn failed_unseal_attempts(&self) -> u32 {
+        self.failed_unseals.load(Ordering::Relaxed)
+    }
+
     /// Check whether the vault has been initialized (root key exists in storage).
     ///
     /// # Errors
@@ -312,13 +848,14 @@ impl SealManager {
         let initialized = self.is_initialized().await?;
         let sealed = !self.barrier.is_unsealed().await;
 
-        let (threshold, shares, progress) = if initialized {
+        let (threshold, shares, progress, wrapper) = if initialized {
             let config = self.load_config().await?;
             let pending = self.pending_shares.lock().await;
             let submitted = u8::try_from(pending.len()).unwrap_or(u8::MAX);
-            (config.threshold, config.shares, submitted)
+            (config.threshold, config.shares, submitted, config.wrapper)
         } else {
-            (0, 0, 0)
+            let wrapper = self.wrapper.as_ref().map(|w| w.name().to_owned());
+            (0, 0, 0, wrapper)
         };
 
         Ok(SealStatus {
@@ -327,9 +864,20 @@ impl SealManager {
             threshold,
             shares,
             progress,
+            seal_type: wrapper.unwrap_or_else(|| SEAL_TYPE_SHAMIR.to_owned()),
         })
     }
 
+    /// Count a failed unseal attempt, passing its error through.
+    fn unseal_failed(&self, error: SealError) -> SealError {
+        let attempts = self
+            .failed_unseals
+            .fetch_add(1, Ordering::Relaxed)
+            .saturating_add(1);
+        warn!(attempts, error = %error, "unseal attempt failed");
+        error
+    }
+
     /// Load the seal configuration from storage.
     async fn load_config(&self) -> Result<SealConfig, SealError> {
         let config_bytes = self
@@ -343,6 +891,189 @@ impl SealManager {
             reason: format!("failed to deserialize seal config: {e}"),
         })
     }
+
+    /// Persist the seal configuration.
+    async fn save_config(&self, config: &SealConfig) -> Result<(), SealError> {
+        let config_bytes = serde_json::to_vec(config).map_err(|e| SealError::InvalidConfig {
+            reason: format!("failed to serialize seal config: {e}"),
+        })?;
+        self.barrier
+            .put_raw(SEAL_CONFIG_PATH, &config_bytes)
+            .await
+            .map_err(SealError::Barrier)
+    }
+
+    /// Fail unless the vault is initialized and unsealed.
+    async fn ensure_unsealed(&self) -> Result<(), SealError> {
+        if !self.is_initialized().await? {
+            return Err(SealError::NotInitialized);
+        }
+        if !self.barrier.is_unsealed().await {
+            return Err(SealError::Barrier(BarrierError::Sealed));
+        }
+        Ok(())
+    }
+
+    /// Verify `current` shares and replace them with a new split.
+    async fn rekey_with(
+        &self,
+        current: &[String],
+        shares: u8,
+        threshold: u8,
+    ) -> Result<Vec<String>, SealError> {
+        let (config, root_key) = self.authorized_root_key(current).await?;
+        let new_shares = if config.wrapper.is_some() {
+            self.store_recovery_key(&root_key, shares, threshold)
+                .await?
+        } else {
+            self.store_shamir_root_key(&root_key, shares, threshold)
+                .await?
+        };
+        self.save_config(&SealConfig {
+            shares,
+            threshold,
+            ..config
+        })
+        .await?;
+        Ok(new_shares)
+    }
+
+    /// Verify `current` shares — unseal shares, or recovery shares with
+    /// auto-unseal — returning the seal config and root key.
+    async fn authorized_root_key(
+        &self,
+        current: &[String],
+    ) -> Result<(SealConfig, EncryptionKey), SealError> {
+        let config = self.load_config().await?;
+        if config.wrapper.is_some() {
+            return self.recovery_root_key(current).await;
+        }
+        let decoded = current
+            .iter()
+            .map(|s| decode_share(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        let unseal_key = recover_key(config.threshold, &decoded)?;
+        let root_key = self.decrypt_shamir_root_key(&unseal_key).await?;
+        Ok((config, root_key))
+    }
+
+    /// The configured wrapper, if it is the one that sealed this vault.
+    fn wrapper_for(&self, config: &SealConfig) -> Result<Arc<dyn SealWrapper>, SealError> {
+        let Some(name) = &config.wrapper else {
+            return Err(SealError::InvalidConfig {
+                reason: "vault uses Shamir unseal shares".to_owned(),
+            });
+        };
+        match &self.wrapper {
+            Some(wrapper) if wrapper.name() == name => Ok(Arc::clone(wrapper)),
+            _ => Err(SealError::Wrapper {
+                reason: format!("vault is sealed by '{name}' but that seal is not configured"),
+            }),
+        }
+    }
+
+    /// Encrypt `root_key` under a fresh unseal key and return its shares.
+    async fn store_shamir_root_key(
+        &self,
+        root_key: &EncryptionKey,
+        shares: u8,
+        threshold: u8,
+    ) -> Result<Vec<String>, SealError> {
+        // Generate unseal key (256-bit, will be split into Shamir shares).
+        let unseal_key = EncryptionKey::generate();
+        let encrypted_root = crypto::encrypt(&unseal_key, root_key.as_bytes())?;
+
+        // Store encrypted root key (raw — it's already encrypted by unseal key).
+        self.barrier
+            .put_raw(ROOT_KEY_PATH, &encrypted_root)
+            .await
+            .map_err(SealError::Barrier)?;
+
+        Ok(split_key(&unseal_key, shares, threshold))
+    }
+
+    /// Generate a recovery key, store it encrypted by `root_key`, and return
+    /// its shares.
+    async fn store_recovery_key(
+        &self,
+        root_key: &EncryptionKey,
+        shares: u8,
+        threshold: u8,
+    ) -> Result<Vec<String>, SealError> {
+        let recovery_key = EncryptionKey::generate();
+        let encrypted = crypto::encrypt(root_key, recovery_key.as_bytes())?;
+        self.barrier
+            .put_raw(RECOVERY_KEY_PATH, &encrypted)
+            .await
+            .map_err(SealError::Barrier)?;
+
+        Ok(split_key(&recovery_key, shares, threshold))
+    }
+
+    /// Decrypt the stored root key with a reconstructed unseal key.
+    async fn decrypt_shamir_root_key(
+        &self,
+        unseal_key: &EncryptionKey,
+    ) -> Result<EncryptionKey, SealError> {
+        let encrypted_root = self
+            .barrier
+            .get_raw(ROOT_KEY_PATH)
+            .await
+            .map_err(SealError::Barrier)?
+            .ok_or(SealError::NotInitialized)?;
+
+        let root_key_bytes = crypto::decrypt(unseal_key, &encrypted_root).map_err(|e| {
+            SealError::RootKeyDecryption {
+                reason: e.to_string(),
+            }
+        })?;
+        root_key_from_bytes(root_key_bytes)
+    }
+
+    /// Decrypt the stored root key with the seal wrapper.
+    async fn unwrap_root_key(&self, wrapper: &dyn SealWrapper) -> Result<EncryptionKey, SealError> {
+        let wrapped_root = self
+            .barrier
+            .get_raw(ROOT_KEY_PATH)
+            .await
+            .map_err(SealError::Barrier)?
+            .ok_or(SealError::NotInitialized)?;
+
+        root_key_from_bytes(wrapper.unwrap(&wrapped_root).await?)
+    }
+
+    /// Verify recovery shares, returning the seal config and root key.
+    async fn recovery_root_key(
+        &self,
+        shares: &[String],
+    ) -> Result<(SealConfig, EncryptionKey), SealError> {
+        let config = self.load_config().await?;
+        let wrapper = self.wrapper_for(&config)?;
+
+        let decoded = shares
+            .iter()
+            .map(|s| decode_share(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        let submitted = recover_key(config.threshold, &decoded)?;
+
+        let root_key = self.unwrap_root_key(wrapper.as_ref()).await?;
+        let encrypted = self
+            .barrier
+            .get_raw(RECOVERY_KEY_PATH)
+            .await
+            .map_err(SealError::Barrier)?
+            .ok_or_else(|| SealError::RecoveryFailed {
+                reason: "no recovery key is stored".to_owned(),
+            })?;
+        let stored = Zeroizing::new(crypto::decrypt(&root_key, &encrypted)?);
+
+        if !bool::from(stored.as_slice().ct_eq(submitted.as_bytes())) {
+            return Err(SealError::RecoveryFailed {
+                reason: "recovery shares do not match the recovery key".to_owned(),
+            });
+        }
+        Ok((config, root_key))
+    }
 }
 
 /// Current seal status of the vault.
@@ -358,6 +1089,8 @@ pub struct SealStatus {
     pub shares: u8,
     /// Number of shares submitted so far in the current unseal attempt.
     pub progress: u8,
+    /// `shamir`, or the name of the auto-unseal wrapper (e.g. `devkms`).
+    pub seal_type: String,
 }
 
 impl std::fmt::Debug for SealManager {
@@ -368,6 +1101,104 @@ impl std::fmt::Debug for SealManager {
     }
 }
 
+/// Split `key` into base64-encoded Shamir shares.
+fn split_key(key: &EncryptionKey, shares: u8, threshold: u8) -> Vec<String> {
+    let dealer = Sharks(threshold).dealer(key.as_bytes());
+    dealer
+        .take(usize::from(shares))
+        .map(|s: Share| BASE64.encode(Vec::from(&s)))
+        .collect()
+}
+
+/// A one-time pad as long as a root token (a UUID), as base64url text.
+fn generate_otp() -> Zeroizing<String> {
+    let mut bytes = Zeroizing::new([0u8; 27]);
+    OsRng.fill_bytes(bytes.as_mut());
+    Zeroizing::new(URL_SAFE_NO_PAD.encode(bytes.as_ref()))
+}
+
+/// Base64 of `token` XOR-ed with `otp`, which must be the same length.
+///
+/// # Errors
+///
+/// Returns [`SealError::InvalidConfig`] if the lengths differ.
+pub fn encode_root_token(token: &str, otp: &str) -> Result<String, SealError> {
+    if token.len() != otp.len() {
+        return Err(SealError::InvalidConfig {
+            reason: "one-time pad length does not match the token".to_owned(),
+        });
+    }
+    let xored: Vec<u8> = token.bytes().zip(otp.bytes()).map(|(t, o)| t ^ o).collect();
+    Ok(BASE64.encode(xored))
+}
+
+/// Recover a root token from [`encode_root_token`] output and its pad.
+///
+/// # Errors
+///
+/// Returns [`SealError::InvalidConfig`] if the encoding is malformed or does
+/// not match the pad.
+pub fn decode_root_token(encoded: &str, otp: &str) -> Result<Zeroizing<String>, SealError> {
+    let invalid = |reason: &str| SealError::InvalidConfig {
+        reason: reason.to_owned(),
+    };
+    let xored = BASE64
+        .decode(encoded.trim())
+        .map_err(|_| invalid("encoded token is not valid base64"))?;
+    if xored.len() != otp.len() {
+        return Err(invalid(
+            "one-time pad length does not match the encoded token",
+        ));
+    }
+    let token: Vec<u8> = xored.iter().zip(otp.bytes()).map(|(x, o)| x ^ o).collect();
+    String::from_utf8(token)
+        .map(Zeroizing::new)
+        .map_err(|_| invalid("encoded token does not decode with this one-time pad"))
+}
+
+/// Decode a base64 share.
+fn decode_share(share_b64: &str) -> Result<Vec<u8>, SealError> {
+    BASE64
+        .decode(share_b64)
+        .map_err(|e| SealError::InvalidShare {
+            reason: format!("base64 decode failed: {e}"),
+        })
+}
+
+/// Reconstruct a 256-bit key from raw share bytes.
+fn recover_key(threshold: u8, shares: &[Vec<u8>]) -> Result<EncryptionKey, SealError> {
+    let parsed_shares = shares
+        .iter()
+        .map(|bytes| {
+            Share::try_from(bytes.as_slice()).map_err(|e| SealError::InvalidShare {
+                reason: format!("share deserialization failed: {e}"),
+            })
+        })
+        .collect::<Result<Vec<Share>, _>>()?;
+
+    let key_bytes =
+        Sharks(threshold)
+            .recover(&parsed_shares)
+            .map_err(|e| SealError::RecoveryFailed {
+                reason: e.to_string(),
+            })?;
+
+    let key_array: [u8; 32] = key_bytes
+        .try_into()
+        .map_err(|_| SealError::RecoveryFailed {
+            reason: "recovered key is not 32 bytes".to_owned(),
+        })?;
+    Ok(EncryptionKey::from_bytes(key_array))
+}
+
+/// Turn decrypted root key bytes into a key.
+fn root_key_from_bytes(bytes: Vec<u8>) -> Result<EncryptionKey, SealError> {
+    let root_key_array: [u8; 32] = bytes.try_into().map_err(|_| SealError::RootKeyDecryption {
+        reason: "decrypted root key is not 32 bytes".to_owned(),
+    })?;
+    Ok(EncryptionKey::from_bytes(root_key_array))
+}
+
 /// Validate Shamir configuration parameters.
 fn validate_config(share_count: u8, threshold: u8) -> Result<(), SealError> {
     if !(1..=10).contains(&share_count) {
@@ -375,9 +1206,11 @@ fn validate_config(share_count: u8, threshold: u8) -> Result<(), SealError> {
             reason: format!("share count must be 1-10, got {share_count}"),
         });
     }
-    if threshold < 2 {
+    // A single share is its own threshold (dev mode); otherwise no one
+    // share may unseal the vault alone.
+    if threshold < 2 && !(share_count == 1 && threshold == 1) {
         return Err(SealError::InvalidConfig {
-            reason: format!("threshold must be at least 2, got {threshold}"),
+            reason: format!("threshold must be at least 2 with several shares, got {threshold}"),
         });
     }
     if threshold > share_count {
@@ -397,6 +1230,7 @@ mod tests {
 
     use super::*;
     use crate::barrier::Barrier;
+    use crate::kms::DevKms;
 
     fn make_seal_manager() -> SealManager {
         let storage = Arc::new(MemoryBackend::new());
@@ -412,6 +1246,7 @@ mod tests {
         assert!(validate_config(3, 2).is_ok());
         assert!(validate_config(10, 10).is_ok());
         assert!(validate_config(2, 2).is_ok());
+        assert!(validate_config(1, 1).is_ok());
     }
 
     #[test]
@@ -448,6 +1283,20 @@ mod tests {
         assert!(!result.root_token.is_empty());
     }
 
+    #[tokio::test]
+    async fn single_share_unseals() {
+        let mgr = make_seal_manager();
+        let result = mgr.init(1, 1).await.unwrap();
+        assert_eq!(result.unseal_shares.len(), 1);
+
+        let progress = mgr
+            .submit_unseal_share(&result.unseal_shares[0])
+            .await
+            .unwrap();
+        assert!(progress.is_none());
+        assert!(mgr.barrier.is_unsealed().await);
+    }
+
     #[tokio::test]
     async fn init_leaves_vault_sealed() {
         let mgr = make_seal_manager();
@@ -568,6 +1417,32 @@ mod tests {
         assert!(matches!(err, SealError::InvalidShare { .. }));
     }
 
+    #[tokio::test]
+    async fn failed_unseals_are_counted_until_success() {
+        let mgr = make_seal_manager();
+        let result = mgr.init(3, 2).await.unwrap();
+        let other = make_seal_manager().init(3, 2).await.unwrap();
+
+        mgr.submit_unseal_share("not-valid-base64!!!")
+            .await
+            .unwrap_err();
+        mgr.submit_unseal_share(&result.unseal_shares[0])
+            .await
+            .unwrap();
+        mgr.submit_unseal_share(&other.unseal_shares[1])
+            .await
+            .unwrap_err();
+        assert_eq!(mgr.failed_unseal_attempts(), 2);
+
+        mgr.submit_unseal_share(&result.unseal_shares[0])
+            .await
+            .unwrap();
+        mgr.submit_unseal_share(&result.unseal_shares[1])
+            .await
+            .unwrap();
+        assert_eq!(mgr.failed_unseal_attempts(), 0);
+    }
+
     // ── seal ─────────────────────────────────────────────────────────
 
     #[tokio::test]
@@ -609,7 +1484,7 @@ mod tests {
             .unwrap();
 
         // Manually unseal the barrier to allow seal() to work.
-        mgr.barrier.unseal(EncryptionKey::generate()).await;
+        mgr.barrier.unseal(EncryptionKey::generate()).await.unwrap();
         mgr.seal().await.unwrap();
 
         // Pending shares should be cleared — status shows 0 progress.
@@ -724,6 +1599,357 @@ mod tests {
         assert_eq!(val, Some(b"hello".to_vec()));
     }
 
+    // ── auto-unseal ──────────────────────────────────────────────────
+
+    async fn dev_kms() -> (Arc<dyn SealWrapper>, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("zvault-seal-{}", uuid::Uuid::new_v4()));
+        let kms = DevKms::open(dir.join("dev.key")).await.unwrap();
+        (Arc::new(kms), dir)
+    }
+
+    #[tokio::test]
+    async fn auto_init_returns_recovery_shares_and_auto_unseals() {
+        let (kms, dir) = dev_kms().await;
+        let mgr = make_seal_manager().with_wrapper(kms);
+
+        let result = mgr.init(3, 2).await.unwrap();
+        assert!(result.unseal_shares.is_empty());
+        assert_eq!(result.recovery_shares.len(), 3);
+        assert_eq!(mgr.status().await.unwrap().seal_type, "devkms");
+
+        let err = mgr
+            .submit_unseal_share(&result.recovery_shares[0])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SealError::InvalidConfig { .. }));
+
+        mgr.auto_unseal().await.unwrap();
+        assert!(mgr.barrier.is_unsealed().await);
+        mgr.verify_recovery_shares(&result.recovery_shares[1..])
+            .await
+            .unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn auto_unseal_requires_the_vaults_wrapper() {
+        let (kms, dir) = dev_kms().await;
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Arc::new(Barrier::new(storage));
+        SealManager::new(Arc::clone(&barrier))
+            .with_wrapper(kms)
+            .init(2, 2)
+            .await
+            .unwrap();
+
+        let without = SealManager::new(Arc::clone(&barrier));
+        let err = without.auto_unseal().await.unwrap_err();
+        assert!(matches!(err, SealError::Wrapper { .. }));
+
+        let (other, other_dir) = dev_kms().await;
+        let err = SealManager::new(barrier)
+            .with_wrapper(other)
+            .auto_unseal()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SealError::Wrapper { .. }));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_dir_all(&other_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn migrate_shamir_to_auto_and_back_keeps_data() {
+        let (kms, dir) = dev_kms().await;
+        let storage = Arc::new(MemoryBackend::new());
+        let barrier = Arc::new(Barrier::new(storage));
+
+        let shamir = SealManager::new(Arc::clone(&barrier));
+        let init = shamir.init(3, 2).await.unwrap();
+        shamir
+            .submit_unseal_share(&init.unseal_shares[0])
+            .await
+            .unwrap();
+        shamir
+            .submit_unseal_share(&init.unseal_shares[1])
+            .await
+            .unwrap();
+        barrier.put("test/key", b"kept").await.unwrap();
+
+        let auto = SealManager::new(Arc::clone(&barrier)).with_wrapper(kms);
+        let err = auto
+            .migrate_to_auto(&init.unseal_shares[..1])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SealError::RecoveryFailed { .. }));
+        auto.seal().await.unwrap();
+        let err = auto
+            .migrate_to_auto(&init.unseal_shares[1..])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SealError::Barrier(BarrierError::Sealed)));
+        auto.submit_unseal_share(&init.unseal_shares[0])
+            .await
+            .unwrap();
+        auto.submit_unseal_share(&init.unseal_shares[1])
+            .await
+            .unwrap();
+
+        let recovery = auto
+            .migrate_to_auto(&init.unseal_shares[1..])
+            .await
+            .unwrap();
+        assert_eq!(recovery.len(), 3);
+        assert_eq!(barrier.get("test/key").await.unwrap().unwrap(), b"kept");
+
+        auto.seal().await.unwrap();
+        auto.auto_unseal().await.unwrap();
+
+        let err = auto
+            .migrate_to_shamir(&init.unseal_shares[..2])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SealError::RecoveryFailed { .. }));
+
+        let unseal = auto.migrate_to_shamir(&recovery[..2]).await.unwrap();
+        assert_eq!(auto.status().await.unwrap().seal_type, SEAL_TYPE_SHAMIR);
+        auto.seal().await.unwrap();
+
+        auto.submit_unseal_share(&unseal[0]).await.unwrap();
+        auto.submit_unseal_share(&unseal[2]).await.unwrap();
+        assert_eq!(barrier.get("test/key").await.unwrap().unwrap(), b"kept");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // ── rekey ────────────────────────────────────────────────────────
+
+    async fn unsealed(shares: u8, threshold: u8) -> (SealManager, InitResult) {
+        let mgr = make_seal_manager();
+        let init = mgr.init(shares, threshold).await.unwrap();
+        for share in &init.unseal_shares[..usize::from(threshold)] {
+            mgr.submit_unseal_share(share).await.unwrap();
+        }
+        (mgr, init)
+    }
+
+    /// The new shares of a completed rekey, or none if it is still pending.
+    fn completed(update: RekeyUpdate) -> Vec<String> {
+        match update {
+            RekeyUpdate::Complete(shares) => shares,
+            RekeyUpdate::Pending(_) => Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn rekey_replaces_unseal_shares() {
+        let (mgr, init) = unsealed(3, 2).await;
+        mgr.barrier.put("test/key", b"kept").await.unwrap();
+
+        let status = mgr.rekey_init(5, 3).await.unwrap();
+        assert_eq!((status.progress, status.required), (0, 2));
+        let update = mgr
+            .rekey_update(&status.nonce, &init.unseal_shares[0])
+            .await
+            .unwrap();
+        assert!(matches!(update, RekeyUpdate::Pending(ref s) if s.progress == 1));
+        let new_shares = completed(
+            mgr.rekey_update(&status.nonce, &init.unseal_shares[2])
+                .await
+                .unwrap(),
+        );
+        assert_eq!(new_shares.len(), 5);
+        assert!(mgr.rekey_status().await.is_none());
+        let after = mgr.status().await.unwrap();
+        assert_eq!((after.shares, after.threshold), (5, 3));
+
+        mgr.seal().await.unwrap();
+        mgr.submit_unseal_share(&init.unseal_shares[0])
+            .await
+            .unwrap();
+        mgr.submit_unseal_share(&init.unseal_shares[1])
+            .await
+            .unwrap();
+        assert!(
+            mgr.submit_unseal_share(&init.unseal_shares[2])
+                .await
+                .is_err()
+        );
+        assert!(!mgr.barrier.is_unsealed().await);
+
+        for share in &new_shares[1..4] {
+            mgr.submit_unseal_share(share).await.unwrap();
+        }
+        assert_eq!(mgr.barrier.get("test/key").await.unwrap().unwrap(), b"kept");
+    }
+
+    #[tokio::test]
+    async fn rekey_checks_nonce_duplicates_and_cancel() {
+        let (mgr, init) = unsealed(3, 2).await;
+        let status = mgr.rekey_init(2, 2).await.unwrap();
+        assert!(matches!(
+            mgr.rekey_init(4, 2).await.unwrap_err(),
+            SealError::RekeyInProgress
+        ));
+        assert!(matches!(
+            mgr.rekey_update("wrong", &init.unseal_shares[0])
+                .await
+                .unwrap_err(),
+            SealError::RekeyNonceMismatch
+        ));
+
+        mgr.rekey_update(&status.nonce, &init.unseal_shares[0])
+            .await
+            .unwrap();
+        assert!(matches!(
+            mgr.rekey_update(&status.nonce, &init.unseal_shares[0])
+                .await
+                .unwrap_err(),
+            SealError::InvalidShare { .. }
+        ));
+
+        mgr.rekey_cancel().await.unwrap();
+        assert!(matches!(
+            mgr.rekey_cancel().await.unwrap_err(),
+            SealError::NoRekeyInProgress
+        ));
+        assert!(matches!(
+            mgr.rekey_update(&status.nonce, &init.unseal_shares[1])
+                .await
+                .unwrap_err(),
+            SealError::NoRekeyInProgress
+        ));
+    }
+
+    #[tokio::test]
+    async fn rekey_requires_unsealed_vault() {
+        let mgr = make_seal_manager();
+        mgr.init(3, 2).await.unwrap();
+        let err = mgr.rekey_init(3, 2).await.unwrap_err();
+        assert!(matches!(err, SealError::Barrier(BarrierError::Sealed)));
+
+        let (mgr, _) = unsealed(3, 2).await;
+        mgr.rekey_init(3, 2).await.unwrap();
+        mgr.seal().await.unwrap();
+        assert!(mgr.rekey_status().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn rekey_auto_unseal_replaces_recovery_shares() {
+        let (kms, dir) = dev_kms().await;
+        let mgr = make_seal_manager().with_wrapper(kms);
+        let init = mgr.init(3, 2).await.unwrap();
+        mgr.auto_unseal().await.unwrap();
+
+        let status = mgr.rekey_init(4, 3).await.unwrap();
+        mgr.rekey_update(&status.nonce, &init.recovery_shares[0])
+            .await
+            .unwrap();
+        let recovery = completed(
+            mgr.rekey_update(&status.nonce, &init.recovery_shares[1])
+                .await
+                .unwrap(),
+        );
+        assert_eq!(recovery.len(), 4);
+        mgr.verify_recovery_shares(&recovery[..3]).await.unwrap();
+        assert!(
+            mgr.verify_recovery_shares(&init.recovery_shares[..2])
+                .await
+                .is_err()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// The minted token of a completed root generation.
+    fn generated(update: GenerateRootUpdate) -> Option<GeneratedRoot> {
+        match update {
+            GenerateRootUpdate::Complete(generated) => Some(generated),
+            GenerateRootUpdate::Pending(_) => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn generate_root_returns_token_under_otp() {
+        let (mgr, init) = unsealed(3, 2).await;
+        let status = mgr.generate_root_init().await.unwrap();
+        let otp = status.otp.unwrap();
+        assert_eq!((status.progress, status.required), (0, 2));
+        assert_eq!(otp.len(), status.otp_length);
+        assert!(mgr.generate_root_status().await.unwrap().otp.is_none());
+
+        let update = mgr
+            .generate_root_update(&status.nonce, &init.unseal_shares[1])
+            .await
+            .unwrap();
+        assert!(matches!(update, GenerateRootUpdate::Pending(ref s) if s.progress == 1));
+        let generated = generated(
+            mgr.generate_root_update(&status.nonce, &init.unseal_shares[2])
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_ne!(*generated.root_token, init.root_token);
+        assert_eq!(
+            *decode_root_token(&generated.encoded_token, &otp).unwrap(),
+            *generated.root_token
+        );
+        assert!(decode_root_token(&generated.encoded_token, "short").is_err());
+        assert!(mgr.generate_root_status().await.is_none());
+
+        // Shares of a different vault are rejected and end the attempt.
+        let (_, other) = unsealed(3, 2).await;
+        let status = mgr.generate_root_init().await.unwrap();
+        for share in &other.unseal_shares[..1] {
+            mgr.generate_root_update(&status.nonce, share)
+                .await
+                .unwrap();
+        }
+        assert!(
+            mgr.generate_root_update(&status.nonce, &other.unseal_shares[1])
+                .await
+                .is_err()
+        );
+        assert!(mgr.generate_root_status().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn generate_root_checks_nonce_and_clears_on_seal() {
+        let (mgr, init) = unsealed(3, 2).await;
+        let status = mgr.generate_root_init().await.unwrap();
+        assert!(matches!(
+            mgr.generate_root_init().await.unwrap_err(),
+            SealError::GenerateRootInProgress
+        ));
+        assert!(matches!(
+            mgr.generate_root_update("wrong", &init.unseal_shares[0])
+                .await
+                .unwrap_err(),
+            SealError::GenerateRootNonceMismatch
+        ));
+
+        mgr.generate_root_cancel().await.unwrap();
+        assert!(matches!(
+            mgr.generate_root_cancel().await.unwrap_err(),
+            SealError::NoGenerateRootInProgress
+        ));
+        assert!(matches!(
+            mgr.generate_root_update(&status.nonce, &init.unseal_shares[0])
+                .await
+                .unwrap_err(),
+            SealError::NoGenerateRootInProgress
+        ));
+
+        mgr.generate_root_init().await.unwrap();
+        mgr.seal().await.unwrap();
+        assert!(mgr.generate_root_status().await.is_none());
+        assert!(matches!(
+            mgr.generate_root_init().await.unwrap_err(),
+            SealError::Barrier(BarrierError::Sealed)
+        ));
+    }
+
     // ── SealManager Debug ────────────────────────────────────────────
 
     #[test]
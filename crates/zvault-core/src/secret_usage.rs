@@ -0,0 +1,302 @@
+//! Secret usage analytics for `ZVault`.
+//!
+//! Counts reads and distinct readers per KV secret so owners can find
+//! secrets nobody reads (candidates for deletion) and secrets read by many
+//! clients (candidates for tighter policies). Readers are identified by the
+//! same client ID as [`crate::activity`], so raw token hashes never reach
+//! storage.
+//!
+//! Reads are aggregated in memory and written through the barrier at
+//! `sys/counters/secrets/{mount}{path}` by [`SecretUsageLog::flush`], which
+//! the server calls periodically. Counts not yet flushed are included in
+//! [`SecretUsageLog::stats`], but are lost if the process dies.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::barrier::Barrier;
+use crate::error::SecretUsageError;
+
+/// Storage prefix for per-secret usage records.
+const USAGE_PREFIX: &str = "sys/counters/secrets/";
+
+/// Usage of one secret.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretUsage {
+    /// Mount-qualified secret path (e.g. `secret/app/db`).
+    pub path: String,
+    /// Total successful reads.
+    pub reads: u64,
+    /// Distinct clients that read the secret.
+    pub distinct_readers: u64,
+    /// First counted read, if any.
+    pub first_read: Option<DateTime<Utc>>,
+    /// Most recent counted read, if any.
+    pub last_read: Option<DateTime<Utc>>,
+}
+
+impl SecretUsage {
+    /// A secret with no recorded reads.
+    #[must_use]
+    pub fn unread(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            reads: 0,
+            distinct_readers: 0,
+            first_read: None,
+            last_read: None,
+        }
+    }
+}
+
+/// Stored (and pending) counters for one secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UsageRecord {
+    reads: u64,
+    readers: BTreeSet<String>,
+    first_read: DateTime<Utc>,
+    last_read: DateTime<Utc>,
+}
+
+impl UsageRecord {
+    fn merge(&mut self, other: &Self) {
+        self.reads = self.reads.saturating_add(other.reads);
+        self.readers.extend(other.readers.iter().cloned());
+        self.first_read = self.first_read.min(other.first_read);
+        self.last_read = self.last_read.max(other.last_read);
+    }
+
+    fn to_usage(&self, path: &str) -> SecretUsage {
+        SecretUsage {
+            path: path.to_owned(),
+            reads: self.reads,
+            distinct_readers: self.readers.len() as u64,
+            first_read: Some(self.first_read),
+            last_read: Some(self.last_read),
+        }
+    }
+}
+
+/// Records and reports per-secret read counts.
+pub struct SecretUsageLog {
+    barrier: Arc<Barrier>,
+    pending: Mutex<HashMap<String, UsageRecord>>,
+}
+
+impl SecretUsageLog {
+    /// Create a usage log storing counters through the barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            barrier,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a read of `path` by `reader_id`. Only touches memory.
+    pub async fn record(&self, path: &str, reader_id: &str) {
+        let now = Utc::now();
+        let mut pending = self.pending.lock().await;
+        let record = pending
+            .entry(path.to_owned())
+            .or_insert_with(|| UsageRecord {
+                reads: 0,
+                readers: BTreeSet::new(),
+                first_read: now,
+                last_read: now,
+            });
+        record.reads = record.reads.saturating_add(1);
+        record.readers.insert(reader_id.to_owned());
+        record.last_read = now;
+    }
+
+    /// Merge pending counts into storage. Returns the number of secrets
+    /// written.
+    ///
+    /// Counts that could not be written stay pending for the next flush.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SecretUsageError::Barrier` if a record cannot be read or
+    /// written (including while sealed), and `SecretUsageError::Internal`
+    /// if a stored record is corrupt.
+    pub async fn flush(&self) -> Result<usize, SecretUsageError> {
+        let batch = std::mem::take(&mut *self.pending.lock().await);
+        let mut remaining = batch.into_iter();
+        let mut written = 0;
+
+        while let Some((path, delta)) = remaining.next() {
+            if let Err(e) = self.merge_into_storage(&path, &delta).await {
+                let mut pending = self.pending.lock().await;
+                for (path, delta) in std::iter::once((path, delta)).chain(remaining) {
+                    match pending.get_mut(&path) {
+                        Some(newer) => newer.merge(&delta),
+                        None => {
+                            pending.insert(path, delta);
+                        }
+                    }
+                }
+                return Err(e);
+            }
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Usage of every secret under `prefix` that has been read, sorted by
+    /// path, including counts not yet flushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SecretUsageError::Barrier` on storage failure and
+    /// `SecretUsageError::Internal` if a stored record is corrupt.
+    pub async fn stats(&self, prefix: &str) -> Result<Vec<SecretUsage>, SecretUsageError> {
+        let mut records: BTreeMap<String, UsageRecord> = BTreeMap::new();
+        for key in self
+            .barrier
+            .list(&format!("{USAGE_PREFIX}{prefix}"))
+            .await?
+        {
+            let Some(path) = key.strip_prefix(USAGE_PREFIX) else {
+                continue;
+            };
+            if let Some(record) = self.load(path).await? {
+                records.insert(path.to_owned(), record);
+            }
+        }
+
+        for (path, delta) in self.pending.lock().await.iter() {
+            if !path.starts_with(prefix) {
+                continue;
+            }
+            match records.get_mut(path) {
+                Some(record) => record.merge(delta),
+                None => {
+                    records.insert(path.clone(), delta.clone());
+                }
+            }
+        }
+
+        Ok(records
+            .iter()
+            .map(|(path, record)| record.to_usage(path))
+            .collect())
+    }
+
+    /// Forget all counters for `path`, e.g. after the secret is destroyed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SecretUsageError::Barrier` if the record cannot be deleted.
+    pub async fn remove(&self, path: &str) -> Result<(), SecretUsageError> {
+        self.pending.lock().await.remove(path);
+        self.barrier
+            .delete(&format!("{USAGE_PREFIX}{path}"))
+            .await?;
+        Ok(())
+    }
+
+    async fn merge_into_storage(
+        &self,
+        path: &str,
+        delta: &UsageRecord,
+    ) -> Result<(), SecretUsageError> {
+        let record = match self.load(path).await? {
+            Some(mut stored) => {
+                stored.merge(delta);
+                stored
+            }
+            None => delta.clone(),
+        };
+        let bytes = serde_json::to_vec(&record).map_err(|e| SecretUsageError::Internal {
+            reason: format!("usage serialization failed: {e}"),
+        })?;
+        self.barrier
+            .put(&format!("{USAGE_PREFIX}{path}"), &bytes)
+            .await?;
+        Ok(())
+    }
+
+    async fn load(&self, path: &str) -> Result<Option<UsageRecord>, SecretUsageError> {
+        match self.barrier.get(&format!("{USAGE_PREFIX}{path}")).await? {
+            Some(bytes) => {
+                serde_json::from_slice(&bytes)
+                    .map(Some)
+                    .map_err(|e| SecretUsageError::Internal {
+                        reason: format!("corrupt usage record for {path}: {e}"),
+                    })
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl std::fmt::Debug for SecretUsageLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretUsageLog").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn barrier() -> Arc<Barrier> {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        barrier
+    }
+
+    #[tokio::test]
+    async fn counts_reads_and_distinct_readers_across_flushes() {
+        let log = SecretUsageLog::new(barrier().await);
+
+        log.record("secret/app/db", "client-a").await;
+        log.record("secret/app/db", "client-a").await;
+        log.record("secret/app/db", "client-b").await;
+        log.record("secret/other/key", "client-a").await;
+        assert_eq!(log.flush().await.unwrap(), 2);
+
+        log.record("secret/app/db", "client-c").await;
+        log.record("secret/app/api", "client-a").await;
+
+        let stats = log.stats("secret/app/").await.unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].path, "secret/app/api");
+        assert_eq!(stats[0].reads, 1);
+        assert_eq!(stats[1].path, "secret/app/db");
+        assert_eq!(stats[1].reads, 4);
+        assert_eq!(stats[1].distinct_readers, 3);
+
+        log.flush().await.unwrap();
+        assert_eq!(log.stats("secret/app/db").await.unwrap()[0].reads, 4);
+
+        log.remove("secret/app/db").await.unwrap();
+        assert_eq!(log.stats("secret/").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_flush_keeps_counts_pending() {
+        let barrier = barrier().await;
+        let log = SecretUsageLog::new(Arc::clone(&barrier));
+        log.record("secret/app/db", "client-a").await;
+
+        barrier.seal().await;
+        assert!(log.flush().await.is_err());
+        log.record("secret/app/db", "client-b").await;
+
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        log.flush().await.unwrap();
+        let stats = log.stats("secret/").await.unwrap();
+        assert_eq!(stats[0].reads, 2);
+        assert_eq!(stats[0].distinct_readers, 2);
+    }
+}
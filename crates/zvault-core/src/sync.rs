@@ -0,0 +1,1252 @@
+//! Secret sync to GitHub Actions for `ZVault`.
+//!
+//! A [`GithubSync`] pushes fields of KV secrets to the Actions secrets of a
+//! GitHub repository, or of one of its environments. Each [`SyncMapping`]
+//! names a KV secret and either one field, pushed as one GitHub secret, or
+//! every field, each pushed as its upper-cased name behind an optional
+//! prefix. Values leave the vault only as libsodium sealed boxes for the
+//! repository's public key, which is what the GitHub API expects.
+//!
+//! The server syncs every [`GithubSync`] covering a secret when it is
+//! written, and retries failed syncs with backoff. GitHub never returns
+//! secret values, so drift is judged from what was pushed: the SHA-256 of
+//! each value and GitHub's `updated_at` right after the push. A secret whose
+//! `updated_at` moved since was changed outside the vault, and is pushed
+//! again on the next sync. [`SyncManager::drift`] reports each secret's
+//! [`DriftStatus`] without changing anything. Syncs are stored through the
+//! barrier under `sys/sync/github/`.
+//!
+//! Mappings, KV reads, and drift reports are shared with Kubernetes Secret
+//! syncs in [`crate::sync_kubernetes`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use crypto_box::aead::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::barrier::Barrier;
+use crate::engine::{EngineRequest, KvEngine, Operation, unwrap_data_envelope};
+use crate::error::{EngineError, SyncError};
+
+/// Storage prefix for GitHub syncs.
+const SYNC_PREFIX: &str = "sys/sync/github/";
+
+/// GitHub REST API used when a sync does not name another (GitHub
+/// Enterprise Server).
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// GitHub REST API version sent with every request.
+const API_VERSION: &str = "2022-11-28";
+
+/// Secrets per page when listing a repository's secrets.
+const PAGE_SIZE: usize = 100;
+
+/// Delay before retrying a failed sync; doubled after each failure.
+const RETRY_BASE: chrono::Duration = chrono::Duration::minutes(1);
+
+/// Longest delay before retrying a failed sync.
+const RETRY_MAX: chrono::Duration = chrono::Duration::hours(1);
+
+/// Timeout for one GitHub API request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One KV secret, or one field of it, to sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncMapping {
+    /// KV mount, e.g. `secret/`.
+    pub mount: String,
+    /// Secret path within the mount, e.g. `ci/deploy`.
+    pub path: String,
+    /// Field to push; `None` pushes every field of the secret.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// Name `field` is pushed as (default: derived from the field name), or
+    /// the prefix for every field's name when `field` is `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl SyncMapping {
+    /// The secret as `<mount><path>`, e.g. `secret/ci/deploy`.
+    #[must_use]
+    pub fn source(&self) -> String {
+        format!("{}{}", self.mount, self.path)
+    }
+
+    /// The GitHub secret name `field` of this mapping's secret is pushed as.
+    #[must_use]
+    pub fn secret_name(&self, field: &str) -> String {
+        Target::Github.key(self, field)
+    }
+}
+
+/// Where mapped secrets are pushed, which decides how their fields are
+/// named there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Target {
+    /// GitHub Actions secrets: fields upper-cased, e.g. `DB_URL`.
+    Github,
+    /// Keys of a Kubernetes Secret: fields kept as they are.
+    Kubernetes,
+}
+
+impl Target {
+    /// The name `field` of `mapping`'s secret is pushed as.
+    pub(crate) fn key(self, mapping: &SyncMapping, field: &str) -> String {
+        let prefix = match (&mapping.field, &mapping.name) {
+            (Some(_), Some(name)) => return name.clone(),
+            (Some(_), None) => "",
+            (None, prefix) => prefix.as_deref().unwrap_or_default(),
+        };
+        let field: String = field
+            .chars()
+            .map(|c| match self {
+                Self::Github if c.is_ascii_alphanumeric() => c.to_ascii_uppercase(),
+                Self::Kubernetes if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') => c,
+                _ => '_',
+            })
+            .collect();
+        format!("{prefix}{field}")
+    }
+
+    /// Check a name a value is pushed as.
+    pub(crate) fn validate_key(self, key: &str) -> Result<(), SyncError> {
+        let valid = match self {
+            // Letters, digits, and `_`, not starting with a digit or `GITHUB_`.
+            Self::Github => {
+                !key.is_empty()
+                    && !key.starts_with(|c: char| c.is_ascii_digit())
+                    && !key.to_ascii_uppercase().starts_with("GITHUB_")
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            Self::Kubernetes => {
+                !key.is_empty()
+                    && key.len() <= 253
+                    && key
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(invalid(format!("'{key}' is not a valid {}", self.noun())))
+        }
+    }
+
+    fn noun(self) -> &'static str {
+        match self {
+            Self::Github => "GitHub secret name",
+            Self::Kubernetes => "Kubernetes Secret key",
+        }
+    }
+}
+
+/// A repository or environment whose Actions secrets are kept in sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubSync {
+    /// Unique name.
+    pub name: String,
+    /// `owner/repo`.
+    pub repository: String,
+    /// Deployment environment whose secrets are synced instead of the
+    /// repository's.
+    #[serde(default)]
+    pub environment: Option<String>,
+    /// GitHub REST API base URL.
+    pub api_url: String,
+    /// Token allowed to write the repository's secrets.
+    pub token: String,
+    /// Secrets to push.
+    pub mappings: Vec<SyncMapping>,
+    /// Disabled syncs keep their config but are not run on writes.
+    pub enabled: bool,
+    /// Secrets pushed so far, by GitHub secret name.
+    #[serde(default)]
+    pub pushed: BTreeMap<String, PushedSecret>,
+    /// When the sync was created.
+    pub created_at: DateTime<Utc>,
+    /// When the sync was last changed.
+    pub updated_at: DateTime<Utc>,
+    /// When the last successful sync finished.
+    #[serde(default)]
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Why the last sync failed, cleared by a successful one.
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Consecutive failed syncs.
+    #[serde(default)]
+    pub failures: u32,
+    /// When a failed sync is retried.
+    #[serde(default)]
+    pub next_retry_at: Option<DateTime<Utc>>,
+}
+
+impl GithubSync {
+    /// Whether a write to `path` on the KV mount `mount` concerns this sync.
+    #[must_use]
+    pub fn covers(&self, mount: &str, path: &str) -> bool {
+        self.mappings
+            .iter()
+            .any(|m| m.mount == mount && m.path == path)
+    }
+
+    /// The Actions secrets endpoint, with `segments` appended.
+    fn endpoint(&self, segments: &[&str]) -> Result<reqwest::Url, String> {
+        let mut url = reqwest::Url::parse(&self.api_url)
+            .map_err(|e| format!("invalid api_url '{}': {e}", self.api_url))?;
+        let (owner, repo) = self
+            .repository
+            .split_once('/')
+            .ok_or_else(|| format!("invalid repository '{}'", self.repository))?;
+        {
+            let mut path = url
+                .path_segments_mut()
+                .map_err(|()| format!("invalid api_url '{}'", self.api_url))?;
+            path.pop_if_empty().extend(["repos", owner, repo]);
+            match &self.environment {
+                Some(environment) => path.extend(["environments", environment, "secrets"]),
+                None => path.extend(["actions", "secrets"]),
+            };
+            path.extend(segments);
+        }
+        Ok(url)
+    }
+}
+
+/// A secret as last pushed to GitHub.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushedSecret {
+    /// KV secret it came from, `<mount><path>#<field>`.
+    pub source: String,
+    /// Hex SHA-256 of the pushed value.
+    pub sha256: String,
+    /// When it was pushed.
+    pub pushed_at: DateTime<Utc>,
+    /// GitHub's `updated_at` for the secret right after the push.
+    #[serde(default)]
+    pub github_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Parameters for creating or replacing a GitHub sync.
+#[derive(Debug, Clone, Default)]
+pub struct GithubSyncParams {
+    /// `owner/repo`.
+    pub repository: String,
+    /// Sync an environment's secrets instead of the repository's.
+    pub environment: Option<String>,
+    /// GitHub REST API base URL (default [`DEFAULT_API_URL`]).
+    pub api_url: Option<String>,
+    /// GitHub token. `None` keeps the current one; required on create.
+    pub token: Option<String>,
+    /// Secrets to push; at least one.
+    pub mappings: Vec<SyncMapping>,
+    /// Whether the sync is enabled (default `true`).
+    pub enabled: Option<bool>,
+}
+
+/// Outcome of one sync.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    /// GitHub secrets written.
+    pub pushed: Vec<String>,
+    /// GitHub secrets already up to date.
+    pub unchanged: usize,
+}
+
+/// How a GitHub secret compares with the vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftStatus {
+    /// GitHub holds the value last pushed, which is the vault's value.
+    InSync,
+    /// The vault value changed, or was never pushed, since the last sync.
+    Pending,
+    /// The secret does not exist on GitHub.
+    Missing,
+    /// The secret was changed on GitHub after it was pushed.
+    Modified,
+    /// The secret is on GitHub, but its vault secret or field is gone.
+    SourceMissing,
+}
+
+/// One GitHub secret in a drift report.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftEntry {
+    /// GitHub secret name.
+    pub name: String,
+    /// KV secret it comes from, `<mount><path>#<field>`.
+    pub source: String,
+    pub status: DriftStatus,
+}
+
+/// A value to be pushed.
+pub(crate) struct Desired {
+    /// KV secret it comes from, `<mount><path>#<field>`.
+    pub(crate) source: String,
+    /// KV version it was read from.
+    pub(crate) version: Option<u64>,
+    pub(crate) value: Zeroizing<String>,
+}
+
+/// A repository's (or environment's) public key for sealing secrets.
+#[derive(Deserialize)]
+struct RepoKey {
+    key_id: String,
+    key: String,
+}
+
+/// Stores GitHub syncs and pushes secrets to GitHub.
+pub struct SyncManager {
+    barrier: Arc<Barrier>,
+    client: reqwest::Client,
+    /// Serializes syncs so a write and a retry never push the same sync at
+    /// once.
+    sync_lock: Mutex<()>,
+}
+
+impl SyncManager {
+    /// Create a manager backed by the barrier.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::Internal`] if the HTTP client cannot be built.
+    pub fn new(barrier: Arc<Barrier>) -> Result<Self, SyncError> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("zvault/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| SyncError::Internal {
+                reason: format!("failed to build http client: {e}"),
+            })?;
+        Ok(Self {
+            barrier,
+            client,
+            sync_lock: Mutex::new(()),
+        })
+    }
+
+    /// Create or replace the GitHub sync `name`.
+    ///
+    /// Pointing a sync at another repository or environment forgets what
+    /// was pushed, so the next sync pushes everything.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::Invalid`] if the name, repository, a mapping, or the
+    ///   token is invalid or missing.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn put(&self, name: &str, params: GithubSyncParams) -> Result<GithubSync, SyncError> {
+        validate_name(name)?;
+        validate_repository(&params.repository)?;
+        if params
+            .environment
+            .as_deref()
+            .is_some_and(|e| e.is_empty() || e.len() > 255)
+        {
+            return Err(invalid("environment must be 1-255 characters"));
+        }
+        let api_url = params
+            .api_url
+            .unwrap_or_else(|| DEFAULT_API_URL.to_owned())
+            .trim_end_matches('/')
+            .to_owned();
+        let parsed = reqwest::Url::parse(&api_url).map_err(|e| invalid(format!("api_url: {e}")))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(invalid("api_url must use http or https"));
+        }
+        let mappings = validate_mappings(params.mappings, Target::Github)?;
+
+        let now = Utc::now();
+        let existing = self.load(name).await?;
+        let token = match (params.token, &existing) {
+            (Some(token), _) if !token.is_empty() => token,
+            (None, Some(existing)) => existing.token.clone(),
+            _ => return Err(invalid("a GitHub token is required")),
+        };
+        let same_target = existing.as_ref().is_some_and(|s| {
+            s.repository == params.repository
+                && s.environment == params.environment
+                && s.api_url == api_url
+        });
+        let pushed = match &existing {
+            Some(existing) if same_target => existing
+                .pushed
+                .iter()
+                .filter(|(_, p)| {
+                    mappings
+                        .iter()
+                        .any(|m| p.source.starts_with(&format!("{}#", m.source())))
+                })
+                .map(|(name, p)| (name.clone(), p.clone()))
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+
+        let sync = GithubSync {
+            name: name.to_owned(),
+            repository: params.repository,
+            environment: params.environment,
+            api_url,
+            token,
+            mappings,
+            enabled: params.enabled.unwrap_or(true),
+            pushed,
+            created_at: existing.as_ref().map_or(now, |s| s.created_at),
+            updated_at: now,
+            last_synced_at: existing.as_ref().and_then(|s| s.last_synced_at),
+            last_error: None,
+            failures: 0,
+            next_retry_at: None,
+        };
+        self.store(&sync).await?;
+        Ok(sync)
+    }
+
+    /// Read the GitHub sync `name`.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if it does not exist.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn get(&self, name: &str) -> Result<GithubSync, SyncError> {
+        self.load(name).await?.ok_or_else(|| SyncError::NotFound {
+            name: name.to_owned(),
+        })
+    }
+
+    /// List all GitHub syncs, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::Barrier`] if storage fails.
+    pub async fn list(&self) -> Result<Vec<GithubSync>, SyncError> {
+        let mut syncs = Vec::new();
+        for key in self.barrier.list(SYNC_PREFIX).await? {
+            let Some(data) = self.barrier.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice::<GithubSync>(&data) {
+                Ok(sync) => syncs.push(sync),
+                Err(e) => warn!(key = %key, error = %e, "skipping corrupt sync record"),
+            }
+        }
+        syncs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(syncs)
+    }
+
+    /// Delete the GitHub sync `name`. Secrets already pushed stay on GitHub.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if it does not exist.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn delete(&self, name: &str) -> Result<(), SyncError> {
+        self.get(name).await?;
+        self.barrier.delete(&format!("{SYNC_PREFIX}{name}")).await?;
+        Ok(())
+    }
+
+    /// Names of the enabled syncs covering `path` on the KV mount `mount`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::Barrier`] if storage fails.
+    pub async fn covering(&self, mount: &str, path: &str) -> Result<Vec<String>, SyncError> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|s| s.enabled && s.covers(mount, path))
+            .map(|s| s.name)
+            .collect())
+    }
+
+    /// Names of the enabled syncs whose retry is due by `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::Barrier`] if storage fails.
+    pub async fn due(&self, now: DateTime<Utc>) -> Result<Vec<String>, SyncError> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|s| s.enabled && s.next_retry_at.is_some_and(|at| at <= now))
+            .map(|s| s.name)
+            .collect())
+    }
+
+    /// Push the secrets of sync `name` whose GitHub copy is not in sync,
+    /// or all of them with `force`. `kv` maps mount paths to KV engines.
+    ///
+    /// The outcome is recorded on the sync; a failure schedules a retry.
+    /// Secrets pushed before a failure stay recorded as pushed.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if the sync does not exist.
+    /// - [`SyncError::Failed`] if a secret cannot be read or GitHub
+    ///   refuses a request.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn sync(
+        &self,
+        name: &str,
+        kv: &HashMap<String, Arc<KvEngine>>,
+        force: bool,
+    ) -> Result<SyncReport, SyncError> {
+        let _syncing = self.sync_lock.lock().await;
+        let mut sync = self.get(name).await?;
+        let result = self.push(&mut sync, kv, force).await;
+
+        let now = Utc::now();
+        match &result {
+            Ok(report) => {
+                if !report.pushed.is_empty() {
+                    info!(sync = %name, pushed = report.pushed.len(), "secrets synced to GitHub");
+                }
+                sync.last_synced_at = Some(now);
+                sync.last_error = None;
+                sync.failures = 0;
+                sync.next_retry_at = None;
+            }
+            Err(reason) => {
+                warn!(sync = %name, error = %reason, "GitHub sync failed, will retry");
+                sync.failures = sync.failures.saturating_add(1);
+                sync.last_error = Some(reason.clone());
+                sync.next_retry_at = Some(now + backoff(sync.failures));
+            }
+        }
+        // Keep config changes made while the sync was running.
+        if let Some(current) = self.load(name).await? {
+            sync.token = current.token;
+            sync.mappings = current.mappings;
+            sync.enabled = current.enabled;
+            sync.updated_at = current.updated_at;
+            if current.repository != sync.repository
+                || current.environment != sync.environment
+                || current.api_url != sync.api_url
+            {
+                return result.map_err(|reason| SyncError::Failed {
+                    name: name.to_owned(),
+                    reason,
+                });
+            }
+        } else {
+            return Err(SyncError::NotFound {
+                name: name.to_owned(),
+            });
+        }
+        self.store(&sync).await?;
+
+        result.map_err(|reason| SyncError::Failed {
+            name: name.to_owned(),
+            reason,
+        })
+    }
+
+    /// Compare the secrets of sync `name` on GitHub with the vault, without
+    /// changing either. `kv` maps mount paths to KV engines.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if the sync does not exist.
+    /// - [`SyncError::Failed`] if a secret cannot be read or GitHub
+    ///   refuses a request.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn drift(
+        &self,
+        name: &str,
+        kv: &HashMap<String, Arc<KvEngine>>,
+    ) -> Result<Vec<DriftEntry>, SyncError> {
+        let sync = self.get(name).await?;
+        let failed = |reason| SyncError::Failed {
+            name: name.to_owned(),
+            reason,
+        };
+        let desired = desired_values(&sync.mappings, kv, Target::Github)
+            .await
+            .map_err(failed)?;
+        let remote = self.remote_secrets(&sync).await.map_err(failed)?;
+
+        let mut entries: Vec<DriftEntry> = desired
+            .iter()
+            .map(|(secret, want)| DriftEntry {
+                name: secret.clone(),
+                source: want.source.clone(),
+                status: drift_status(
+                    sync.pushed.get(secret),
+                    Some(&sha256_hex(&want.value)),
+                    remote.get(secret).copied(),
+                ),
+            })
+            .collect();
+        for (secret, pushed) in &sync.pushed {
+            if !desired.contains_key(secret) && remote.contains_key(secret) {
+                entries.push(DriftEntry {
+                    name: secret.clone(),
+                    source: pushed.source.clone(),
+                    status: DriftStatus::SourceMissing,
+                });
+            }
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    // ── Private helpers ──────────────────────────────────────────────
+
+    /// Push what is out of sync, recording each push on `sync`.
+    async fn push(
+        &self,
+        sync: &mut GithubSync,
+        kv: &HashMap<String, Arc<KvEngine>>,
+        force: bool,
+    ) -> Result<SyncReport, String> {
+        let desired = desired_values(&sync.mappings, kv, Target::Github).await?;
+        let remote = self.remote_secrets(sync).await?;
+
+        let mut report = SyncReport::default();
+        let mut repo_key: Option<RepoKey> = None;
+        for (secret, want) in &desired {
+            let sha256 = sha256_hex(&want.value);
+            let status = drift_status(
+                sync.pushed.get(secret),
+                Some(&sha256),
+                remote.get(secret).copied(),
+            );
+            if status == DriftStatus::InSync && !force {
+                report.unchanged += 1;
+                continue;
+            }
+            let key = match repo_key {
+                Some(ref key) => key,
+                None => repo_key.insert(self.public_key(sync).await?),
+            };
+            let encrypted_value = seal(&key.key, want.value.as_bytes())?;
+            let url = sync.endpoint(&[secret])?;
+            self.github(sync, reqwest::Method::PUT, url)
+                .json(&serde_json::json!({
+                    "encrypted_value": encrypted_value,
+                    "key_id": key.key_id,
+                }))
+                .send()
+                .await
+                .map_err(|e| format!("GitHub request failed: {e}"))
+                .and_then(check_status)
+                .map_err(|e| format!("{secret}: {e}"))?;
+            sync.pushed.insert(
+                secret.clone(),
+                PushedSecret {
+                    source: want.source.clone(),
+                    sha256,
+                    pushed_at: Utc::now(),
+                    github_updated_at: None,
+                },
+            );
+            report.pushed.push(secret.clone());
+        }
+
+        if !report.pushed.is_empty() {
+            let remote = self.remote_secrets(sync).await?;
+            for secret in &report.pushed {
+                if let Some(pushed) = sync.pushed.get_mut(secret) {
+                    pushed.github_updated_at = remote.get(secret).copied();
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Every secret on GitHub with its `updated_at`.
+    async fn remote_secrets(
+        &self,
+        sync: &GithubSync,
+    ) -> Result<BTreeMap<String, DateTime<Utc>>, String> {
+        #[derive(Deserialize)]
+        struct Page {
+            secrets: Vec<RemoteSecret>,
+        }
+        #[derive(Deserialize)]
+        struct RemoteSecret {
+            name: String,
+            updated_at: DateTime<Utc>,
+        }
+
+        let mut secrets = BTreeMap::new();
+        for page in 1.. {
+            let mut url = sync.endpoint(&[])?;
+            url.query_pairs_mut()
+                .append_pair("per_page", &PAGE_SIZE.to_string())
+                .append_pair("page", &page.to_string());
+            let response = self
+                .github(sync, reqwest::Method::GET, url)
+                .send()
+                .await
+                .map_err(|e| format!("GitHub request failed: {e}"))
+                .and_then(check_status)?;
+            let page: Page = response
+                .json()
+                .await
+                .map_err(|e| format!("invalid GitHub secrets list: {e}"))?;
+            let count = page.secrets.len();
+            secrets.extend(page.secrets.into_iter().map(|s| (s.name, s.updated_at)));
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(secrets)
+    }
+
+    async fn public_key(&self, sync: &GithubSync) -> Result<RepoKey, String> {
+        self.github(sync, reqwest::Method::GET, sync.endpoint(&["public-key"])?)
+            .send()
+            .await
+            .map_err(|e| format!("GitHub request failed: {e}"))
+            .and_then(check_status)?
+            .json()
+            .await
+            .map_err(|e| format!("invalid GitHub public key: {e}"))
+    }
+
+    fn github(
+        &self,
+        sync: &GithubSync,
+        method: reqwest::Method,
+        url: reqwest::Url,
+    ) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&sync.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", API_VERSION)
+    }
+
+    async fn load(&self, name: &str) -> Result<Option<GithubSync>, SyncError> {
+        let Some(data) = self.barrier.get(&format!("{SYNC_PREFIX}{name}")).await? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| SyncError::Internal {
+                reason: format!("corrupt sync '{name}': {e}"),
+            })
+    }
+
+    async fn store(&self, sync: &GithubSync) -> Result<(), SyncError> {
+        let data = serde_json::to_vec(sync).map_err(|e| SyncError::Internal {
+            reason: format!("failed to serialize sync '{}': {e}", sync.name),
+        })?;
+        self.barrier
+            .put(&format!("{SYNC_PREFIX}{}", sync.name), &data)
+            .await?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SyncManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncManager").finish_non_exhaustive()
+    }
+}
+
+/// The values `mappings` push to `target`, by the name they are pushed as.
+///
+/// Secrets or fields missing from the vault are left out.
+pub(crate) async fn desired_values(
+    mappings: &[SyncMapping],
+    kv: &HashMap<String, Arc<KvEngine>>,
+    target: Target,
+) -> Result<BTreeMap<String, Desired>, String> {
+    let mut desired: BTreeMap<String, Desired> = BTreeMap::new();
+    for mapping in mappings {
+        let engine = kv
+            .get(&mapping.mount)
+            .ok_or_else(|| format!("no KV engine mounted at '{}'", mapping.mount))?;
+        let (data, version) = match engine
+            .handle(&EngineRequest {
+                operation: Operation::Read,
+                path: mapping.path.clone(),
+                data: None,
+            })
+            .await
+        {
+            Ok(response) => {
+                let response = response.data.unwrap_or_default();
+                let data = response
+                    .get("data")
+                    .and_then(|v| v.as_object().cloned())
+                    .unwrap_or_default();
+                (
+                    unwrap_data_envelope(data),
+                    response
+                        .pointer("/metadata/version")
+                        .and_then(serde_json::Value::as_u64),
+                )
+            }
+            Err(EngineError::NotFound { .. }) => continue,
+            Err(e) => return Err(format!("{}: {e}", mapping.source())),
+        };
+
+        for (field, value) in &data {
+            if mapping.field.as_ref().is_some_and(|f| f != field) {
+                continue;
+            }
+            let secret = target.key(mapping, field);
+            let source = format!("{}#{field}", mapping.source());
+            if let Some(other) = desired.get(&secret) {
+                return Err(format!(
+                    "{secret} is mapped from both {} and {source}",
+                    other.source
+                ));
+            }
+            if target.validate_key(&secret).is_err() {
+                return Err(format!(
+                    "{source} would be pushed as '{secret}', which is not a valid {}",
+                    target.noun()
+                ));
+            }
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            desired.insert(
+                secret,
+                Desired {
+                    source,
+                    version,
+                    value: Zeroizing::new(value),
+                },
+            );
+        }
+    }
+    Ok(desired)
+}
+
+/// Compare a GitHub secret with the vault. `desired` is the SHA-256 of the
+/// vault value, `remote` GitHub's `updated_at` for the secret.
+fn drift_status(
+    pushed: Option<&PushedSecret>,
+    desired: Option<&str>,
+    remote: Option<DateTime<Utc>>,
+) -> DriftStatus {
+    let Some(desired) = desired else {
+        return DriftStatus::SourceMissing;
+    };
+    let Some(remote) = remote else {
+        return DriftStatus::Missing;
+    };
+    match pushed {
+        None => DriftStatus::Pending,
+        Some(p) if p.github_updated_at.is_none_or(|at| remote > at) => DriftStatus::Modified,
+        Some(p) if p.sha256 != desired => DriftStatus::Pending,
+        Some(_) => DriftStatus::InSync,
+    }
+}
+
+/// Encrypt `value` in a libsodium sealed box for a base64 Curve25519
+/// public key, as the GitHub secrets API expects.
+fn seal(public_key: &str, value: &[u8]) -> Result<String, String> {
+    let key: [u8; crypto_box::KEY_SIZE] = BASE64
+        .decode(public_key)
+        .ok()
+        .and_then(|k| k.try_into().ok())
+        .ok_or_else(|| "GitHub returned an invalid public key".to_owned())?;
+    let sealed = crypto_box::PublicKey::from(key)
+        .seal(&mut OsRng, value)
+        .map_err(|e| format!("failed to encrypt secret: {e}"))?;
+    Ok(BASE64.encode(sealed))
+}
+
+/// Turn an error response into its status and GitHub's message.
+fn check_status(response: reqwest::Response) -> Result<reqwest::Response, String> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    Err(match status.as_u16() {
+        401 => "GitHub rejected the token (401)".to_owned(),
+        403 => "the token may not manage this repository's secrets (403)".to_owned(),
+        404 => "repository or environment not found, or not visible to the token (404)".to_owned(),
+        _ => format!("GitHub returned {status}"),
+    })
+}
+
+pub(crate) fn sha256_hex(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
+
+/// Delay before retrying after `failures` consecutive failures.
+pub(crate) fn backoff(failures: u32) -> chrono::Duration {
+    let factor = 1_i32 << failures.saturating_sub(1).min(16);
+    (RETRY_BASE * factor).min(RETRY_MAX)
+}
+
+pub(crate) fn validate_name(name: &str) -> Result<(), SyncError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid(
+            "sync name must be 1-64 letters, digits, '-' or '_'",
+        ))
+    }
+}
+
+fn validate_repository(repository: &str) -> Result<(), SyncError> {
+    let part = |s: &str| {
+        !s.is_empty()
+            && s.len() <= 100
+            && s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+    };
+    match repository.split_once('/') {
+        Some((owner, repo)) if part(owner) && part(repo) => Ok(()),
+        _ => Err(invalid(format!(
+            "repository must be owner/repo, got '{repository}'"
+        ))),
+    }
+}
+
+/// Normalize mounts to end in `/` and check names and duplicates.
+pub(crate) fn validate_mappings(
+    mappings: Vec<SyncMapping>,
+    target: Target,
+) -> Result<Vec<SyncMapping>, SyncError> {
+    if mappings.is_empty() {
+        return Err(invalid("at least one mapping is required"));
+    }
+    let mut normalized: Vec<SyncMapping> = Vec::with_capacity(mappings.len());
+    for mut mapping in mappings {
+        if !mapping.mount.ends_with('/') {
+            mapping.mount.push('/');
+        }
+        let safe = |s: &str| {
+            s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'/')
+        };
+        if mapping.mount.len() < 2 || !safe(&mapping.mount) {
+            return Err(invalid(format!("invalid mount '{}'", mapping.mount)));
+        }
+        if mapping.path.is_empty()
+            || mapping.path.starts_with('/')
+            || mapping.path.ends_with('/')
+            || mapping.path.contains("//")
+            || !safe(&mapping.path)
+        {
+            return Err(invalid(format!("invalid secret path '{}'", mapping.path)));
+        }
+        match &mapping.field {
+            Some(field) if field.is_empty() => return Err(invalid("field must not be empty")),
+            Some(field) => target.validate_key(&target.key(&mapping, field))?,
+            None => {
+                if let Some(prefix) = &mapping.name {
+                    target.validate_key(&format!("{prefix}X"))?;
+                }
+            }
+        }
+        if normalized.contains(&mapping) {
+            return Err(invalid(format!(
+                "{} is mapped more than once",
+                mapping.source()
+            )));
+        }
+        if let Some(field) = &mapping.field {
+            let secret = target.key(&mapping, field);
+            if normalized
+                .iter()
+                .any(|m| m.field.as_ref().is_some_and(|f| target.key(m, f) == secret))
+            {
+                return Err(invalid(format!("{secret} is mapped twice")));
+            }
+        }
+        normalized.push(mapping);
+    }
+    Ok(normalized)
+}
+
+pub(crate) fn invalid(reason: impl Into<String>) -> SyncError {
+    SyncError::Invalid {
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn setup() -> (SyncManager, HashMap<String, Arc<KvEngine>>) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let kv = Arc::new(KvEngine::new(Arc::clone(&barrier), "kv/secret/".to_owned()));
+        let engines = HashMap::from([("secret/".to_owned(), kv)]);
+        (SyncManager::new(barrier).unwrap(), engines)
+    }
+
+    async fn write(kv: &HashMap<String, Arc<KvEngine>>, path: &str, data: serde_json::Value) {
+        kv["secret/"]
+            .handle(&EngineRequest {
+                operation: Operation::Write,
+                path: path.to_owned(),
+                data: Some(data),
+            })
+            .await
+            .unwrap();
+    }
+
+    fn mapping(path: &str, field: Option<&str>, name: Option<&str>) -> SyncMapping {
+        SyncMapping {
+            mount: "secret".to_owned(),
+            path: path.to_owned(),
+            field: field.map(str::to_owned),
+            name: name.map(str::to_owned),
+        }
+    }
+
+    fn params(api_url: &str, mappings: Vec<SyncMapping>) -> GithubSyncParams {
+        GithubSyncParams {
+            repository: "acme/api".to_owned(),
+            api_url: Some(api_url.to_owned()),
+            token: Some("ghp_test".to_owned()),
+            mappings,
+            ..GithubSyncParams::default()
+        }
+    }
+
+    /// A URL on a port that refuses connections.
+    fn dead_url() -> String {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        format!("http://127.0.0.1:{port}")
+    }
+
+    fn pushed(sha256: &str, github_updated_at: &str) -> PushedSecret {
+        PushedSecret {
+            source: "secret/ci#token".to_owned(),
+            sha256: sha256.to_owned(),
+            pushed_at: Utc::now(),
+            github_updated_at: Some(at(github_updated_at)),
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn secret_names_from_mappings() {
+        assert_eq!(
+            mapping("ci", Some("token"), None).secret_name("token"),
+            "TOKEN"
+        );
+        assert_eq!(
+            mapping("ci", Some("token"), Some("DEPLOY_TOKEN")).secret_name("token"),
+            "DEPLOY_TOKEN"
+        );
+        assert_eq!(
+            mapping("ci", None, Some("PROD_")).secret_name("db-url"),
+            "PROD_DB_URL"
+        );
+        assert_eq!(mapping("ci", None, None).secret_name("api.key"), "API_KEY");
+    }
+
+    #[test]
+    fn endpoints_for_repository_and_environment() {
+        let mut sync = GithubSync {
+            name: "ci".to_owned(),
+            repository: "acme/api".to_owned(),
+            environment: None,
+            api_url: DEFAULT_API_URL.to_owned(),
+            token: String::new(),
+            mappings: Vec::new(),
+            enabled: true,
+            pushed: BTreeMap::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_synced_at: None,
+            last_error: None,
+            failures: 0,
+            next_retry_at: None,
+        };
+        assert_eq!(
+            sync.endpoint(&["public-key"]).unwrap().as_str(),
+            "https://api.github.com/repos/acme/api/actions/secrets/public-key"
+        );
+        sync.environment = Some("prod east".to_owned());
+        sync.api_url = "https://ghe.example.com/api/v3".to_owned();
+        assert_eq!(
+            sync.endpoint(&["TOKEN"]).unwrap().as_str(),
+            "https://ghe.example.com/api/v3/repos/acme/api/environments/prod%20east/secrets/TOKEN"
+        );
+    }
+
+    #[test]
+    fn drift_statuses() {
+        let hash = sha256_hex("v1");
+        let synced = pushed(&hash, "2026-01-01T00:00:00Z");
+        let same = Some(at("2026-01-01T00:00:00Z"));
+        assert_eq!(
+            drift_status(Some(&synced), Some(&hash), same),
+            DriftStatus::InSync
+        );
+        assert_eq!(
+            drift_status(Some(&synced), Some(&sha256_hex("v2")), same),
+            DriftStatus::Pending
+        );
+        assert_eq!(
+            drift_status(Some(&synced), Some(&hash), Some(at("2026-01-02T00:00:00Z"))),
+            DriftStatus::Modified
+        );
+        assert_eq!(
+            drift_status(Some(&synced), Some(&hash), None),
+            DriftStatus::Missing
+        );
+        assert_eq!(drift_status(None, Some(&hash), same), DriftStatus::Pending);
+        assert_eq!(
+            drift_status(Some(&synced), None, same),
+            DriftStatus::SourceMissing
+        );
+    }
+
+    #[test]
+    fn sealed_value_opens_with_secret_key() {
+        let secret = crypto_box::SecretKey::generate(&mut OsRng);
+        let public = BASE64.encode(secret.public_key().as_bytes());
+        let sealed = BASE64.decode(seal(&public, b"hunter2").unwrap()).unwrap();
+        assert_eq!(secret.unseal(&sealed).unwrap(), b"hunter2");
+        assert!(seal("not-a-key", b"x").is_err());
+    }
+
+    #[tokio::test]
+    async fn put_validates_and_keeps_token() {
+        let (mgr, _) = setup().await;
+        let url = DEFAULT_API_URL;
+        assert!(mgr.put("ci", params(url, Vec::new())).await.is_err());
+        let mut bad_repo = params(url, vec![mapping("ci", None, None)]);
+        bad_repo.repository = "acme".to_owned();
+        assert!(mgr.put("ci", bad_repo).await.is_err());
+        let reserved = params(url, vec![mapping("ci", Some("t"), Some("GITHUB_TOKEN"))]);
+        assert!(mgr.put("ci", reserved).await.is_err());
+        let twice = params(
+            url,
+            vec![
+                mapping("a", Some("token"), None),
+                mapping("b", Some("token"), None),
+            ],
+        );
+        assert!(mgr.put("ci", twice).await.is_err());
+
+        let sync = mgr
+            .put("ci", params(url, vec![mapping("ci", Some("token"), None)]))
+            .await
+            .unwrap();
+        assert_eq!(sync.mappings[0].mount, "secret/");
+        assert!(sync.covers("secret/", "ci"));
+
+        let mut without_token = params(url, vec![mapping("ci", None, None)]);
+        without_token.token = None;
+        let updated = mgr.put("ci", without_token).await.unwrap();
+        assert_eq!(updated.token, "ghp_test");
+        assert_eq!(updated.created_at, sync.created_at);
+
+        let mut new_sync = params(url, vec![mapping("ci", None, None)]);
+        new_sync.token = None;
+        assert!(mgr.put("other", new_sync).await.is_err());
+
+        assert_eq!(mgr.covering("secret/", "ci").await.unwrap(), ["ci"]);
+        assert!(mgr.covering("secret/", "app").await.unwrap().is_empty());
+        mgr.delete("ci").await.unwrap();
+        assert!(matches!(
+            mgr.get("ci").await,
+            Err(SyncError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn desired_values_follow_mappings() {
+        let (mgr, kv) = setup().await;
+        write(
+            &kv,
+            "ci",
+            serde_json::json!({ "token": "t0k", "db-url": "postgres://", "port": 5432 }),
+        )
+        .await;
+        let sync = mgr
+            .put(
+                "ci",
+                params(
+                    DEFAULT_API_URL,
+                    vec![
+                        mapping("ci", Some("token"), Some("DEPLOY_TOKEN")),
+                        mapping("ci", None, Some("APP_")),
+                        mapping("gone", None, None),
+                    ],
+                ),
+            )
+            .await
+            .unwrap();
+
+        let desired = desired_values(&sync.mappings, &kv, Target::Github)
+            .await
+            .unwrap();
+        let names: Vec<_> = desired.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            ["APP_DB_URL", "APP_PORT", "APP_TOKEN", "DEPLOY_TOKEN"]
+        );
+        assert_eq!(*desired["APP_PORT"].value, "5432");
+        assert_eq!(desired["DEPLOY_TOKEN"].source, "secret/ci#token");
+
+        write(&kv, "ci", serde_json::json!({ "data": { "token": "t1k" } })).await;
+        let desired = desired_values(&sync.mappings, &kv, Target::Github)
+            .await
+            .unwrap();
+        assert_eq!(*desired["DEPLOY_TOKEN"].value, "t1k");
+    }
+
+    #[tokio::test]
+    async fn failed_sync_is_recorded_and_retried() {
+        let (mgr, kv) = setup().await;
+        write(&kv, "ci", serde_json::json!({ "token": "t0k" })).await;
+        mgr.put("ci", params(&dead_url(), vec![mapping("ci", None, None)]))
+            .await
+            .unwrap();
+
+        let err = mgr.sync("ci", &kv, false).await.unwrap_err();
+        assert!(matches!(err, SyncError::Failed { .. }));
+        let sync = mgr.get("ci").await.unwrap();
+        assert_eq!(sync.failures, 1);
+        assert!(sync.last_error.is_some());
+        let retry_at = sync.next_retry_at.unwrap();
+
+        assert!(mgr.due(Utc::now()).await.unwrap().is_empty());
+        assert_eq!(mgr.due(retry_at).await.unwrap(), ["ci"]);
+        assert!(mgr.drift("ci", &kv).await.is_err());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        assert_eq!(backoff(1), chrono::Duration::minutes(1));
+        assert_eq!(backoff(2), chrono::Duration::minutes(2));
+        assert_eq!(backoff(20), RETRY_MAX);
+    }
+}
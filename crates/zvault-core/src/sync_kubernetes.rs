@@ -0,0 +1,1104 @@
+//! Secret sync to Kubernetes Secrets for `ZVault`.
+//!
+//! A [`KubernetesSync`] keeps one Kubernetes Secret in line with KV secrets.
+//! Its [`SyncMapping`]s name KV secrets, and optionally one field of each;
+//! fields keep their names as Secret keys unless the mapping renames them.
+//!
+//! Secrets are written with server-side apply under the `zvault` field
+//! manager, so keys dropped from the vault or the mappings are removed
+//! while labels and annotations added by other tools are left alone. Every
+//! write annotates the Secret with the SHA-256 of its data
+//! ([`ANNOTATION_HASH`]) and the KV versions it came from
+//! ([`ANNOTATION_VERSIONS`]): copying the hash into a pod template, or
+//! pointing a reloader at the Secret, rolls workloads when it changes. A
+//! Secret that exists without this sync's [`ANNOTATION_SYNC`] is never
+//! overwritten.
+//!
+//! The server reaches the cluster it runs in through its service account,
+//! unless a sync names another API server with a token and CA certificate.
+//! Syncs run when a mapped secret is written, retry with backoff like
+//! GitHub syncs (see [`crate::sync`]), and are stored through the barrier
+//! under `sys/sync/kubernetes/`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::barrier::Barrier;
+use crate::engine::KvEngine;
+use crate::error::SyncError;
+use crate::sync::{
+    Desired, DriftEntry, DriftStatus, SyncMapping, SyncReport, Target, backoff, desired_values,
+    invalid, sha256_hex, validate_mappings, validate_name,
+};
+
+/// Storage prefix for Kubernetes syncs.
+const SYNC_PREFIX: &str = "sys/sync/kubernetes/";
+
+/// Field manager for server-side apply.
+const FIELD_MANAGER: &str = "zvault";
+
+/// Annotation naming the sync that owns a Secret.
+pub const ANNOTATION_SYNC: &str = "zvault.io/sync";
+
+/// Annotation holding the hex SHA-256 of a Secret's data.
+pub const ANNOTATION_HASH: &str = "zvault.io/secret-hash";
+
+/// Annotation listing the KV versions a Secret was built from, as
+/// `secret/app/db=3,secret/app/api=12`.
+pub const ANNOTATION_VERSIONS: &str = "zvault.io/source-versions";
+
+/// Label marking Secrets written by `ZVault`.
+const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+
+/// Service account files mounted into every pod.
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Timeout for one Kubernetes API request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A Kubernetes Secret kept in sync with KV secrets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KubernetesSync {
+    /// Unique name.
+    pub name: String,
+    /// Namespace of the Secret.
+    pub namespace: String,
+    /// Name of the Secret.
+    pub secret_name: String,
+    /// API server URL; `None` uses the cluster the server runs in.
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Bearer token for `api_url`.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// PEM CA certificate for `api_url`, if not publicly trusted.
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    /// Extra labels set on the Secret.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Secrets to push.
+    pub mappings: Vec<SyncMapping>,
+    /// Disabled syncs keep their config but are not run on writes.
+    pub enabled: bool,
+    /// Hex SHA-256 of each key's value as last applied.
+    #[serde(default)]
+    pub applied: BTreeMap<String, String>,
+    /// [`ANNOTATION_HASH`] as last applied.
+    #[serde(default)]
+    pub secret_hash: Option<String>,
+    /// When the sync was created.
+    pub created_at: DateTime<Utc>,
+    /// When the sync was last changed.
+    pub updated_at: DateTime<Utc>,
+    /// When the last successful sync finished.
+    #[serde(default)]
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Why the last sync failed, cleared by a successful one.
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Consecutive failed syncs.
+    #[serde(default)]
+    pub failures: u32,
+    /// When a failed sync is retried.
+    #[serde(default)]
+    pub next_retry_at: Option<DateTime<Utc>>,
+}
+
+impl KubernetesSync {
+    /// Whether a write to `path` on the KV mount `mount` concerns this sync.
+    #[must_use]
+    pub fn covers(&self, mount: &str, path: &str) -> bool {
+        self.mappings
+            .iter()
+            .any(|m| m.mount == mount && m.path == path)
+    }
+
+    /// The Secret as `<namespace>/<name>`.
+    #[must_use]
+    pub fn secret(&self) -> String {
+        format!("{}/{}", self.namespace, self.secret_name)
+    }
+}
+
+/// Parameters for creating or replacing a Kubernetes sync.
+#[derive(Debug, Clone, Default)]
+pub struct KubernetesSyncParams {
+    /// Namespace of the Secret.
+    pub namespace: String,
+    /// Name of the Secret.
+    pub secret_name: String,
+    /// API server URL; `None` uses the cluster the server runs in.
+    pub api_url: Option<String>,
+    /// Bearer token for `api_url`. `None` keeps the current one; required
+    /// with `api_url`.
+    pub token: Option<String>,
+    /// PEM CA certificate for `api_url`. `None` keeps the current one, `""`
+    /// removes it.
+    pub ca_cert: Option<String>,
+    /// Extra labels set on the Secret.
+    pub labels: BTreeMap<String, String>,
+    /// Secrets to push; at least one.
+    pub mappings: Vec<SyncMapping>,
+    /// Whether the sync is enabled (default `true`).
+    pub enabled: Option<bool>,
+}
+
+/// A Secret as read from the API server.
+struct RemoteSecret {
+    data: BTreeMap<String, Vec<u8>>,
+    /// Value of [`ANNOTATION_SYNC`].
+    owner: Option<String>,
+}
+
+/// An API server connection.
+struct Cluster {
+    client: reqwest::Client,
+    base: reqwest::Url,
+    token: Zeroizing<String>,
+}
+
+/// Stores Kubernetes syncs and writes their Secrets.
+pub struct KubernetesSyncManager {
+    barrier: Arc<Barrier>,
+    /// Serializes syncs so a write and a retry never apply the same Secret
+    /// at once.
+    sync_lock: Mutex<()>,
+}
+
+impl KubernetesSyncManager {
+    /// Create a manager backed by the barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            barrier,
+            sync_lock: Mutex::new(()),
+        }
+    }
+
+    /// Create or replace the Kubernetes sync `name`.
+    ///
+    /// Pointing a sync at another Secret or cluster forgets what was
+    /// applied; the old Secret is left as it is.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::Invalid`] if the name, Secret, labels, a mapping, or
+    ///   the cluster settings are invalid.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn put(
+        &self,
+        name: &str,
+        params: KubernetesSyncParams,
+    ) -> Result<KubernetesSync, SyncError> {
+        validate_name(name)?;
+        if !is_dns_label(&params.namespace) {
+            return Err(invalid(format!(
+                "namespace '{}' must be a DNS label (lowercase letters, digits, '-')",
+                params.namespace
+            )));
+        }
+        if !is_dns_subdomain(&params.secret_name) {
+            return Err(invalid(format!(
+                "secret name '{}' must be a DNS subdomain (lowercase letters, digits, '-', '.')",
+                params.secret_name
+            )));
+        }
+        for (key, value) in &params.labels {
+            validate_label(key, value)?;
+        }
+        let api_url = params
+            .api_url
+            .filter(|u| !u.is_empty())
+            .map(|u| u.trim_end_matches('/').to_owned());
+        if let Some(url) = &api_url {
+            let parsed = reqwest::Url::parse(url).map_err(|e| invalid(format!("api_url: {e}")))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(invalid("api_url must use http or https"));
+            }
+        }
+        let mappings = validate_mappings(params.mappings, Target::Kubernetes)?;
+
+        let now = Utc::now();
+        let existing = self.load(name).await?;
+        let keep = |new: Option<String>, old: Option<&String>| match new {
+            Some(new) if new.is_empty() => None,
+            Some(new) => Some(new),
+            None => old.cloned(),
+        };
+        let token = keep(
+            params.token,
+            existing.as_ref().and_then(|s| s.token.as_ref()),
+        );
+        let ca_cert = keep(
+            params.ca_cert,
+            existing.as_ref().and_then(|s| s.ca_cert.as_ref()),
+        );
+        if api_url.is_some() && token.is_none() {
+            return Err(invalid("a token is required with api_url"));
+        }
+        if let Some(pem) = &ca_cert {
+            let certs = reqwest::Certificate::from_pem_bundle(pem.as_bytes())
+                .map_err(|e| invalid(format!("ca_cert: {e}")))?;
+            if certs.is_empty() {
+                return Err(invalid("ca_cert has no PEM certificates"));
+            }
+        }
+        let same_target = existing.as_ref().is_some_and(|s| {
+            s.namespace == params.namespace
+                && s.secret_name == params.secret_name
+                && s.api_url == api_url
+        });
+        let (applied, secret_hash) = match &existing {
+            Some(existing) if same_target => {
+                (existing.applied.clone(), existing.secret_hash.clone())
+            }
+            _ => (BTreeMap::new(), None),
+        };
+
+        let sync = KubernetesSync {
+            name: name.to_owned(),
+            namespace: params.namespace,
+            secret_name: params.secret_name,
+            api_url,
+            token,
+            ca_cert,
+            labels: params.labels,
+            mappings,
+            enabled: params.enabled.unwrap_or(true),
+            applied,
+            secret_hash,
+            created_at: existing.as_ref().map_or(now, |s| s.created_at),
+            updated_at: now,
+            last_synced_at: existing.as_ref().and_then(|s| s.last_synced_at),
+            last_error: None,
+            failures: 0,
+            next_retry_at: None,
+        };
+        self.store(&sync).await?;
+        Ok(sync)
+    }
+
+    /// Read the Kubernetes sync `name`.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if it does not exist.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn get(&self, name: &str) -> Result<KubernetesSync, SyncError> {
+        self.load(name).await?.ok_or_else(|| SyncError::NotFound {
+            name: name.to_owned(),
+        })
+    }
+
+    /// List all Kubernetes syncs, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::Barrier`] if storage fails.
+    pub async fn list(&self) -> Result<Vec<KubernetesSync>, SyncError> {
+        let mut syncs = Vec::new();
+        for key in self.barrier.list(SYNC_PREFIX).await? {
+            let Some(data) = self.barrier.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice::<KubernetesSync>(&data) {
+                Ok(sync) => syncs.push(sync),
+                Err(e) => warn!(key = %key, error = %e, "skipping corrupt sync record"),
+            }
+        }
+        syncs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(syncs)
+    }
+
+    /// Delete the Kubernetes sync `name`. Its Secret stays in the cluster.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if it does not exist.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn delete(&self, name: &str) -> Result<(), SyncError> {
+        self.get(name).await?;
+        self.barrier.delete(&format!("{SYNC_PREFIX}{name}")).await?;
+        Ok(())
+    }
+
+    /// Names of the enabled syncs covering `path` on the KV mount `mount`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::Barrier`] if storage fails.
+    pub async fn covering(&self, mount: &str, path: &str) -> Result<Vec<String>, SyncError> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|s| s.enabled && s.covers(mount, path))
+            .map(|s| s.name)
+            .collect())
+    }
+
+    /// Names of the enabled syncs whose retry is due by `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::Barrier`] if storage fails.
+    pub async fn due(&self, now: DateTime<Utc>) -> Result<Vec<String>, SyncError> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|s| s.enabled && s.next_retry_at.is_some_and(|at| at <= now))
+            .map(|s| s.name)
+            .collect())
+    }
+
+    /// Apply the Secret of sync `name` if any key differs from the vault,
+    /// or unconditionally with `force`. `kv` maps mount paths to KV
+    /// engines.
+    ///
+    /// The outcome is recorded on the sync; a failure schedules a retry.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if the sync does not exist.
+    /// - [`SyncError::Failed`] if a secret cannot be read, the Secret
+    ///   belongs to something else, or the API server refuses a request.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn sync(
+        &self,
+        name: &str,
+        kv: &HashMap<String, Arc<KvEngine>>,
+        force: bool,
+    ) -> Result<SyncReport, SyncError> {
+        let _syncing = self.sync_lock.lock().await;
+        let mut sync = self.get(name).await?;
+        let result = apply(&mut sync, kv, force).await;
+
+        let now = Utc::now();
+        match &result {
+            Ok(report) => {
+                if !report.pushed.is_empty() {
+                    info!(sync = %name, secret = %sync.secret(), keys = report.pushed.len(), "Kubernetes Secret synced");
+                }
+                sync.last_synced_at = Some(now);
+                sync.last_error = None;
+                sync.failures = 0;
+                sync.next_retry_at = None;
+            }
+            Err(reason) => {
+                warn!(sync = %name, error = %reason, "Kubernetes sync failed, will retry");
+                sync.failures = sync.failures.saturating_add(1);
+                sync.last_error = Some(reason.clone());
+                sync.next_retry_at = Some(now + backoff(sync.failures));
+            }
+        }
+        let failed = |reason| SyncError::Failed {
+            name: name.to_owned(),
+            reason,
+        };
+        // Keep config changes made while the sync was running, unless they
+        // point it at another Secret.
+        let Some(current) = self.load(name).await? else {
+            return Err(SyncError::NotFound {
+                name: name.to_owned(),
+            });
+        };
+        if current.namespace != sync.namespace
+            || current.secret_name != sync.secret_name
+            || current.api_url != sync.api_url
+        {
+            return result.map_err(failed);
+        }
+        sync.token = current.token;
+        sync.ca_cert = current.ca_cert;
+        sync.labels = current.labels;
+        sync.mappings = current.mappings;
+        sync.enabled = current.enabled;
+        sync.updated_at = current.updated_at;
+        self.store(&sync).await?;
+
+        result.map_err(failed)
+    }
+
+    /// Compare the keys of the Secret of sync `name` with the vault,
+    /// without changing either. `kv` maps mount paths to KV engines.
+    ///
+    /// # Errors
+    ///
+    /// - [`SyncError::NotFound`] if the sync does not exist.
+    /// - [`SyncError::Failed`] if a secret cannot be read or the API server
+    ///   refuses a request.
+    /// - [`SyncError::Barrier`] if storage fails.
+    pub async fn drift(
+        &self,
+        name: &str,
+        kv: &HashMap<String, Arc<KvEngine>>,
+    ) -> Result<Vec<DriftEntry>, SyncError> {
+        let sync = self.get(name).await?;
+        let failed = |reason| SyncError::Failed {
+            name: name.to_owned(),
+            reason,
+        };
+        let desired = desired_values(&sync.mappings, kv, Target::Kubernetes)
+            .await
+            .map_err(failed)?;
+        let cluster = connect(&sync).await.map_err(failed)?;
+        let remote = cluster
+            .read_secret(&sync)
+            .await
+            .map_err(failed)?
+            .map(|r| r.data)
+            .unwrap_or_default();
+        Ok(drift_entries(&sync, &desired, &remote))
+    }
+
+    // ── Private helpers ──────────────────────────────────────────────
+
+    async fn load(&self, name: &str) -> Result<Option<KubernetesSync>, SyncError> {
+        let Some(data) = self.barrier.get(&format!("{SYNC_PREFIX}{name}")).await? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| SyncError::Internal {
+                reason: format!("corrupt sync '{name}': {e}"),
+            })
+    }
+
+    async fn store(&self, sync: &KubernetesSync) -> Result<(), SyncError> {
+        let data = serde_json::to_vec(sync).map_err(|e| SyncError::Internal {
+            reason: format!("failed to serialize sync '{}': {e}", sync.name),
+        })?;
+        self.barrier
+            .put(&format!("{SYNC_PREFIX}{}", sync.name), &data)
+            .await?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for KubernetesSyncManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KubernetesSyncManager")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Apply `sync`'s Secret if it differs from the vault, recording what was
+/// applied on `sync`.
+async fn apply(
+    sync: &mut KubernetesSync,
+    kv: &HashMap<String, Arc<KvEngine>>,
+    force: bool,
+) -> Result<SyncReport, String> {
+    let desired = desired_values(&sync.mappings, kv, Target::Kubernetes).await?;
+    let cluster = connect(sync).await?;
+    let remote = cluster.read_secret(sync).await?;
+    if let Some(owner) = remote.as_ref().map(|r| r.owner.as_deref()) {
+        if owner != Some(sync.name.as_str()) {
+            return Err(format!(
+                "Secret {} exists and is not managed by this sync ({ANNOTATION_SYNC} is {})",
+                sync.secret(),
+                owner.unwrap_or("not set")
+            ));
+        }
+    }
+
+    let hash = secret_hash(&desired);
+    let remote_data = remote.map(|r| r.data).unwrap_or_default();
+    let changed: Vec<String> = desired
+        .iter()
+        .filter(|(key, want)| {
+            force || remote_data.get(*key).map(Vec::as_slice) != Some(want.value.as_bytes())
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    let removed = sync
+        .applied
+        .keys()
+        .any(|key| !desired.contains_key(key) && remote_data.contains_key(key));
+    let current = sync.secret_hash.as_deref() == Some(hash.as_str());
+    if changed.is_empty() && !removed && current {
+        return Ok(SyncReport {
+            pushed: Vec::new(),
+            unchanged: desired.len(),
+        });
+    }
+
+    cluster
+        .apply_secret(sync, &manifest(sync, &desired, &hash))
+        .await?;
+    sync.applied = desired
+        .iter()
+        .map(|(key, want)| (key.clone(), sha256_hex(&want.value)))
+        .collect();
+    sync.secret_hash = Some(hash);
+    Ok(SyncReport {
+        unchanged: desired.len().saturating_sub(changed.len()),
+        pushed: changed,
+    })
+}
+
+/// The server-side apply manifest for `sync`'s Secret.
+fn manifest(
+    sync: &KubernetesSync,
+    desired: &BTreeMap<String, Desired>,
+    hash: &str,
+) -> serde_json::Value {
+    let mut labels = sync.labels.clone();
+    labels.insert(MANAGED_BY_LABEL.to_owned(), FIELD_MANAGER.to_owned());
+
+    let mut versions: BTreeMap<&str, u64> = BTreeMap::new();
+    for want in desired.values() {
+        let secret = want
+            .source
+            .split_once('#')
+            .map_or(&*want.source, |(s, _)| s);
+        if let Some(version) = want.version {
+            versions.insert(secret, version);
+        }
+    }
+    let versions: Vec<String> = versions
+        .iter()
+        .map(|(secret, version)| format!("{secret}={version}"))
+        .collect();
+
+    let data: BTreeMap<&str, String> = desired
+        .iter()
+        .map(|(key, want)| (key.as_str(), BASE64.encode(want.value.as_bytes())))
+        .collect();
+    serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": {
+            "name": sync.secret_name,
+            "namespace": sync.namespace,
+            "labels": labels,
+            "annotations": {
+                ANNOTATION_SYNC: sync.name,
+                ANNOTATION_HASH: hash,
+                ANNOTATION_VERSIONS: versions.join(","),
+            },
+        },
+        "type": "Opaque",
+        "data": data,
+    })
+}
+
+/// Each key's drift, plus keys this sync applied whose source is gone.
+fn drift_entries(
+    sync: &KubernetesSync,
+    desired: &BTreeMap<String, Desired>,
+    remote: &BTreeMap<String, Vec<u8>>,
+) -> Vec<DriftEntry> {
+    let mut entries: Vec<DriftEntry> = desired
+        .iter()
+        .map(|(key, want)| {
+            let status = match remote.get(key) {
+                None => DriftStatus::Missing,
+                Some(value) if value.as_slice() == want.value.as_bytes() => DriftStatus::InSync,
+                Some(value) if Some(&bytes_hex(value)) == sync.applied.get(key) => {
+                    DriftStatus::Pending
+                }
+                Some(_) => DriftStatus::Modified,
+            };
+            DriftEntry {
+                name: key.clone(),
+                source: want.source.clone(),
+                status,
+            }
+        })
+        .collect();
+    for key in sync.applied.keys() {
+        if !desired.contains_key(key) && remote.contains_key(key) {
+            entries.push(DriftEntry {
+                name: key.clone(),
+                source: String::new(),
+                status: DriftStatus::SourceMissing,
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+/// Hex SHA-256 over every key and value, in key order.
+fn secret_hash(desired: &BTreeMap<String, Desired>) -> String {
+    let mut hasher = Sha256::new();
+    for (key, want) in desired {
+        hasher.update(key.as_bytes());
+        hasher.update([0]);
+        hasher.update(want.value.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn bytes_hex(value: &[u8]) -> String {
+    hex::encode(Sha256::digest(value))
+}
+
+/// Connect to `sync`'s API server, or to the cluster the server runs in.
+async fn connect(sync: &KubernetesSync) -> Result<Cluster, String> {
+    let (base, token, ca_cert) = match (&sync.api_url, &sync.token) {
+        (Some(url), Some(token)) => (url.clone(), token.clone(), sync.ca_cert.clone()),
+        (Some(_), None) => return Err("no token configured for api_url".to_owned()),
+        (None, _) => {
+            let host = std::env::var("KUBERNETES_SERVICE_HOST").map_err(|_| {
+                "not running in a Kubernetes cluster; set api_url and token".to_owned()
+            })?;
+            let port =
+                std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_owned());
+            let host = if host.contains(':') {
+                format!("[{host}]")
+            } else {
+                host
+            };
+            let read = |file: &str| {
+                let path = format!("{SERVICE_ACCOUNT_DIR}/{file}");
+                async move {
+                    tokio::fs::read_to_string(&path)
+                        .await
+                        .map_err(|e| format!("failed to read {path}: {e}"))
+                }
+            };
+            let token = read("token").await?.trim().to_owned();
+            (
+                format!("https://{host}:{port}"),
+                token,
+                Some(read("ca.crt").await?),
+            )
+        }
+    };
+
+    let mut builder = reqwest::Client::builder().timeout(REQUEST_TIMEOUT);
+    if let Some(pem) = ca_cert {
+        for cert in reqwest::Certificate::from_pem_bundle(pem.as_bytes())
+            .map_err(|e| format!("invalid CA certificate: {e}"))?
+        {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    let client = builder
+        .build()
+        .map_err(|e| format!("failed to build http client: {e}"))?;
+    let base = reqwest::Url::parse(&base).map_err(|e| format!("invalid api_url '{base}': {e}"))?;
+    Ok(Cluster {
+        client,
+        base,
+        token: Zeroizing::new(token),
+    })
+}
+
+impl Cluster {
+    fn secret_url(&self, sync: &KubernetesSync) -> Result<reqwest::Url, String> {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .map_err(|()| format!("invalid api_url '{}'", self.base))?
+            .pop_if_empty()
+            .extend([
+                "api",
+                "v1",
+                "namespaces",
+                &sync.namespace,
+                "secrets",
+                &sync.secret_name,
+            ]);
+        Ok(url)
+    }
+
+    /// Read the Secret, or `None` if it does not exist.
+    async fn read_secret(&self, sync: &KubernetesSync) -> Result<Option<RemoteSecret>, String> {
+        #[derive(Deserialize)]
+        struct Secret {
+            #[serde(default)]
+            metadata: Metadata,
+            #[serde(default)]
+            data: BTreeMap<String, String>,
+        }
+        #[derive(Default, Deserialize)]
+        struct Metadata {
+            #[serde(default)]
+            annotations: BTreeMap<String, String>,
+        }
+
+        let response = self
+            .client
+            .get(self.secret_url(sync)?)
+            .bearer_auth(&*self.token)
+            .send()
+            .await
+            .map_err(|e| format!("Kubernetes request failed: {e}"))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let secret: Secret = check_status(response, sync, "read")
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("invalid Secret from the API server: {e}"))?;
+        let data = secret
+            .data
+            .into_iter()
+            .map(|(key, value)| {
+                BASE64
+                    .decode(value)
+                    .map(|value| (key.clone(), value))
+                    .map_err(|e| format!("invalid data for key {key}: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Some(RemoteSecret {
+            data,
+            owner: secret.metadata.annotations.get(ANNOTATION_SYNC).cloned(),
+        }))
+    }
+
+    /// Create or update the Secret with server-side apply.
+    async fn apply_secret(
+        &self,
+        sync: &KubernetesSync,
+        manifest: &serde_json::Value,
+    ) -> Result<(), String> {
+        let mut url = self.secret_url(sync)?;
+        url.query_pairs_mut()
+            .append_pair("fieldManager", FIELD_MANAGER)
+            .append_pair("force", "true");
+        let body =
+            serde_json::to_vec(manifest).map_err(|e| format!("failed to serialize Secret: {e}"))?;
+        let response = self
+            .client
+            .patch(url)
+            .bearer_auth(&*self.token)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                "application/apply-patch+yaml",
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("Kubernetes request failed: {e}"))?;
+        check_status(response, sync, "write").await.map(drop)
+    }
+}
+
+/// Turn an error response into its status and the API server's message.
+async fn check_status(
+    response: reqwest::Response,
+    sync: &KubernetesSync,
+    verb: &str,
+) -> Result<reqwest::Response, String> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|body| body.get("message")?.as_str().map(str::to_owned));
+    Err(match (status.as_u16(), message) {
+        (401, _) => "the API server rejected the token (401)".to_owned(),
+        (403, _) => format!(
+            "not allowed to {verb} Secrets in namespace {} (403); grant the service account get and patch on secrets",
+            sync.namespace
+        ),
+        (_, Some(message)) => format!("API server returned {status}: {message}"),
+        (_, None) => format!("API server returned {status}"),
+    })
+}
+
+/// A DNS-1123 label, as namespaces are.
+fn is_dns_label(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+}
+
+/// A DNS-1123 subdomain, as Secret names are.
+fn is_dns_subdomain(name: &str) -> bool {
+    name.len() <= 253 && name.split('.').all(is_dns_label)
+}
+
+/// Labels: an optional DNS subdomain prefix and a name of up to 63
+/// alphanumerics, `-`, `_`, or `.`; values are the same or empty.
+fn validate_label(key: &str, value: &str) -> Result<(), SyncError> {
+    let segment = |s: &str| {
+        s.len() <= 63
+            && s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+            && !s.starts_with(|c: char| !c.is_ascii_alphanumeric())
+            && !s.ends_with(|c: char| !c.is_ascii_alphanumeric())
+    };
+    let (prefix, name) = key
+        .rsplit_once('/')
+        .map_or((None, key), |(p, n)| (Some(p), n));
+    if name.is_empty() || !segment(name) || prefix.is_some_and(|p| !is_dns_subdomain(p)) {
+        return Err(invalid(format!("invalid label key '{key}'")));
+    }
+    if key == MANAGED_BY_LABEL {
+        return Err(invalid(format!(
+            "label {MANAGED_BY_LABEL} is set by the sync"
+        )));
+    }
+    if !segment(value) {
+        return Err(invalid(format!("invalid value for label {key}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use crate::engine::{EngineRequest, Operation};
+
+    async fn setup() -> (KubernetesSyncManager, HashMap<String, Arc<KvEngine>>) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let kv = Arc::new(KvEngine::new(Arc::clone(&barrier), "kv/secret/".to_owned()));
+        let engines = HashMap::from([("secret/".to_owned(), kv)]);
+        (KubernetesSyncManager::new(barrier), engines)
+    }
+
+    fn mapping(path: &str, field: Option<&str>, name: Option<&str>) -> SyncMapping {
+        SyncMapping {
+            mount: "secret/".to_owned(),
+            path: path.to_owned(),
+            field: field.map(str::to_owned),
+            name: name.map(str::to_owned),
+        }
+    }
+
+    fn params(mappings: Vec<SyncMapping>) -> KubernetesSyncParams {
+        KubernetesSyncParams {
+            namespace: "prod".to_owned(),
+            secret_name: "api-secrets".to_owned(),
+            api_url: Some("https://k8s.example.com:6443".to_owned()),
+            token: Some("sa-token".to_owned()),
+            mappings,
+            ..KubernetesSyncParams::default()
+        }
+    }
+
+    fn desired(pairs: &[(&str, &str)]) -> BTreeMap<String, Desired> {
+        pairs
+            .iter()
+            .map(|(key, value)| {
+                (
+                    (*key).to_owned(),
+                    Desired {
+                        source: format!("secret/app#{key}"),
+                        version: Some(2),
+                        value: Zeroizing::new((*value).to_owned()),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn names_and_labels() {
+        assert!(is_dns_label("prod-east"));
+        assert!(!is_dns_label("Prod"));
+        assert!(!is_dns_label("-prod"));
+        assert!(is_dns_subdomain("api.secrets"));
+        assert!(!is_dns_subdomain("api..secrets"));
+        assert!(validate_label("app.kubernetes.io/name", "api").is_ok());
+        assert!(validate_label("team", "").is_ok());
+        assert!(validate_label("bad key", "x").is_err());
+        assert!(validate_label(MANAGED_BY_LABEL, "me").is_err());
+    }
+
+    #[test]
+    fn keys_keep_field_names() {
+        let m = mapping("app", None, Some("db."));
+        assert_eq!(Target::Kubernetes.key(&m, "url"), "db.url");
+        assert_eq!(Target::Kubernetes.key(&m, "pass word"), "db.pass_word");
+        let m = mapping("app", Some("token"), None);
+        assert_eq!(Target::Kubernetes.key(&m, "token"), "token");
+    }
+
+    #[test]
+    fn manifest_is_annotated() {
+        let sync = KubernetesSync {
+            name: "api".to_owned(),
+            namespace: "prod".to_owned(),
+            secret_name: "api-secrets".to_owned(),
+            api_url: None,
+            token: None,
+            ca_cert: None,
+            labels: BTreeMap::from([("team".to_owned(), "core".to_owned())]),
+            mappings: Vec::new(),
+            enabled: true,
+            applied: BTreeMap::new(),
+            secret_hash: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_synced_at: None,
+            last_error: None,
+            failures: 0,
+            next_retry_at: None,
+        };
+        let want = desired(&[("password", "hunter2"), ("user", "app")]);
+        let hash = secret_hash(&want);
+        let manifest = manifest(&sync, &want, &hash);
+        assert_eq!(manifest["data"]["password"], BASE64.encode("hunter2"));
+        assert_eq!(manifest["metadata"]["labels"][MANAGED_BY_LABEL], "zvault");
+        assert_eq!(manifest["metadata"]["labels"]["team"], "core");
+        let annotations = &manifest["metadata"]["annotations"];
+        assert_eq!(annotations[ANNOTATION_SYNC], "api");
+        assert_eq!(annotations[ANNOTATION_HASH], hash.as_str());
+        assert_eq!(annotations[ANNOTATION_VERSIONS], "secret/app=2");
+
+        assert_ne!(
+            hash,
+            secret_hash(&desired(&[("password", "hunter3"), ("user", "app")]))
+        );
+        // Keys and values cannot run together into the same hash.
+        assert_ne!(
+            secret_hash(&desired(&[("a", "bc")])),
+            secret_hash(&desired(&[("ab", "c")]))
+        );
+    }
+
+    #[test]
+    fn drift_by_key() {
+        let mut sync = KubernetesSync {
+            name: "api".to_owned(),
+            namespace: "prod".to_owned(),
+            secret_name: "api-secrets".to_owned(),
+            api_url: None,
+            token: None,
+            ca_cert: None,
+            labels: BTreeMap::new(),
+            mappings: Vec::new(),
+            enabled: true,
+            applied: BTreeMap::new(),
+            secret_hash: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_synced_at: None,
+            last_error: None,
+            failures: 0,
+            next_retry_at: None,
+        };
+        for key in ["same", "vault", "cluster", "gone"] {
+            sync.applied.insert(key.to_owned(), sha256_hex("old"));
+        }
+        let want = desired(&[
+            ("same", "old"),
+            ("vault", "new"),
+            ("cluster", "old"),
+            ("missing", "x"),
+        ]);
+        let remote: BTreeMap<String, Vec<u8>> = [
+            ("same", "old"),
+            ("vault", "old"),
+            ("cluster", "edited"),
+            ("gone", "old"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_owned(), v.as_bytes().to_vec()))
+        .collect();
+
+        let statuses: Vec<(String, DriftStatus)> = drift_entries(&sync, &want, &remote)
+            .into_iter()
+            .map(|e| (e.name, e.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("cluster".to_owned(), DriftStatus::Modified),
+                ("gone".to_owned(), DriftStatus::SourceMissing),
+                ("missing".to_owned(), DriftStatus::Missing),
+                ("same".to_owned(), DriftStatus::InSync),
+                ("vault".to_owned(), DriftStatus::Pending),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn put_validates_and_keeps_credentials() {
+        let (mgr, _) = setup().await;
+        let mut bad = params(vec![mapping("app", None, None)]);
+        bad.namespace = "Prod".to_owned();
+        assert!(mgr.put("api", bad).await.is_err());
+        let mut no_token = params(vec![mapping("app", None, None)]);
+        no_token.token = None;
+        assert!(mgr.put("api", no_token).await.is_err());
+        let mut bad_ca = params(vec![mapping("app", None, None)]);
+        bad_ca.ca_cert = Some("not a certificate".to_owned());
+        assert!(mgr.put("api", bad_ca).await.is_err());
+        let bad_key = params(vec![mapping("app", Some("token"), Some("a/b"))]);
+        assert!(mgr.put("api", bad_key).await.is_err());
+
+        let sync = mgr
+            .put("api", params(vec![mapping("app", Some("token"), None)]))
+            .await
+            .unwrap();
+        assert!(sync.covers("secret/", "app"));
+        assert_eq!(sync.secret(), "prod/api-secrets");
+
+        let mut update = params(vec![mapping("app", None, None)]);
+        update.token = None;
+        let updated = mgr.put("api", update).await.unwrap();
+        assert_eq!(updated.token.as_deref(), Some("sa-token"));
+
+        let mut in_cluster = params(vec![mapping("app", None, None)]);
+        in_cluster.api_url = None;
+        in_cluster.token = Some(String::new());
+        let in_cluster = mgr.put("api", in_cluster).await.unwrap();
+        assert!(in_cluster.api_url.is_none() && in_cluster.token.is_none());
+
+        assert_eq!(mgr.covering("secret/", "app").await.unwrap(), ["api"]);
+        mgr.delete("api").await.unwrap();
+        assert!(matches!(
+            mgr.get("api").await,
+            Err(SyncError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_sync_is_recorded_and_retried() {
+        let (mgr, kv) = setup().await;
+        kv["secret/"]
+            .handle(&EngineRequest {
+                operation: Operation::Write,
+                path: "app".to_owned(),
+                data: Some(serde_json::json!({ "token": "t0k" })),
+            })
+            .await
+            .unwrap();
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let mut dead = params(vec![mapping("app", None, None)]);
+        dead.api_url = Some(format!("http://127.0.0.1:{port}"));
+        mgr.put("api", dead).await.unwrap();
+
+        let err = mgr.sync("api", &kv, false).await.unwrap_err();
+        assert!(matches!(err, SyncError::Failed { .. }));
+        let sync = mgr.get("api").await.unwrap();
+        assert_eq!(sync.failures, 1);
+        assert!(sync.last_error.is_some());
+        assert_eq!(mgr.due(sync.next_retry_at.unwrap()).await.unwrap(), ["api"]);
+    }
+}
@@ -15,16 +15,44 @@
 //! - Token comparison uses `subtle::ConstantTimeEq`.
 //! - Tokens have TTLs and optional max TTLs.
 //! - Revoking a parent token revokes all children (tree revocation).
-
+//! - Revoking a token destroys its cubbyhole.
+//! - A token with bound CIDRs is only usable from clients in those networks.
+//! - Audit logs identify a token by its accessor, derived from the hash, which
+//!   cannot be used to authenticate. An index from accessor to hash lets
+//!   operators look up and revoke tokens without holding their values.
+//!
+//! # Batch tokens
+//!
+//! Batch tokens (`b.` prefix) are not stored: policies, TTL, and parent are
+//! encrypted into the token itself with AES-256-GCM and HMAC-SHA256-signed,
+//! under keys derived from one random key kept in the barrier. Creating one
+//! writes nothing, so CI fan-out can mint thousands cheaply. They cannot be
+//! renewed, revoked individually, or create child tokens; they stop working
+//! when they expire or their parent token is revoked, and have no accessor
+//! or cubbyhole.
+
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use tracing::info;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+use zeroize::Zeroize;
 
 use crate::barrier::Barrier;
-use crate::error::TokenError;
+use crate::crypto::{self, EncryptionKey};
+use crate::cubbyhole::Cubbyhole;
+use crate::error::{BarrierError, CryptoError, TokenError};
+use crate::metrics;
+
+type HmacSha256 = Hmac<Sha256>;
 
 /// Storage prefix for token entries.
 const TOKEN_PREFIX: &str = "sys/tokens/";
@@ -32,6 +60,21 @@ const TOKEN_PREFIX: &str = "sys/tokens/";
 /// Storage prefix for parent→children index.
 const TOKEN_CHILDREN_PREFIX: &str = "sys/token-children/";
 
+/// Storage prefix for the accessor→hash index.
+const TOKEN_ACCESSOR_PREFIX: &str = "sys/token-accessors/";
+
+/// Prefix of batch tokens.
+pub const BATCH_TOKEN_PREFIX: &str = "b.";
+
+/// Storage key of the key batch tokens are sealed with.
+const BATCH_KEY_PATH: &str = "sys/token-batch-key";
+
+/// TTL of batch tokens created without one.
+pub const BATCH_DEFAULT_TTL_SECS: i64 = 3600;
+
+/// Length of the HMAC-SHA256 tag ending a batch token.
+const BATCH_TAG_LEN: usize = 32;
+
 /// A stored token entry (persisted through the barrier).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenEntry {
@@ -53,6 +96,29 @@ pub struct TokenEntry {
     pub metadata: std::collections::HashMap<String, String>,
     /// Display name for audit logs.
     pub display_name: String,
+    /// Networks the token may be used from (empty = anywhere).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bound_cidrs: Vec<String>,
+    /// Whether this is a batch token, decoded from the token rather than
+    /// read from storage.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub batch: bool,
+}
+
+impl TokenEntry {
+    /// The token's accessor. Batch tokens have none: they are not indexed,
+    /// so no accessor could find them.
+    #[must_use]
+    pub fn accessor(&self) -> Option<String> {
+        (!self.batch).then(|| token_accessor(&self.token_hash))
+    }
+
+    /// Whether a client at `ip` may use the token. A token with bound CIDRs
+    /// refuses clients whose address is unknown.
+    #[must_use]
+    pub fn allows_client(&self, ip: Option<IpAddr>) -> bool {
+        cidrs_allow(&self.bound_cidrs, ip)
+    }
 }
 
 /// Parameters for creating a new token.
@@ -71,18 +137,42 @@ pub struct CreateTokenParams {
     pub metadata: std::collections::HashMap<String, String>,
     /// Display name for audit logs.
     pub display_name: String,
+    /// Networks the token may be used from (empty = anywhere).
+    pub bound_cidrs: Vec<String>,
+}
+
+/// What a batch token carries, encrypted.
+#[derive(Serialize, Deserialize)]
+struct BatchPayload {
+    policies: Vec<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    created_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    expires_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent_hash: Option<String>,
+    display_name: String,
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    metadata: std::collections::HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    bound_cidrs: Vec<String>,
 }
 
 /// Manages token creation, lookup, renewal, and revocation.
 pub struct TokenStore {
     barrier: Arc<Barrier>,
+    /// Serializes creation of the batch token key.
+    batch_key_lock: Mutex<()>,
 }
 
 impl TokenStore {
     /// Create a new token store backed by the given barrier.
     #[must_use]
     pub fn new(barrier: Arc<Barrier>) -> Self {
-        Self { barrier }
+        Self {
+            barrier,
+            batch_key_lock: Mutex::new(()),
+        }
     }
 
     /// Create a new token and persist its hash.
@@ -91,8 +181,10 @@ impl TokenStore {
     ///
     /// # Errors
     ///
-    /// Returns [`TokenError::Barrier`] if storage fails.
+    /// - [`TokenError::InvalidBoundCidr`] if a bound CIDR doesn't parse.
+    /// - [`TokenError::Barrier`] if storage fails.
     pub async fn create(&self, params: CreateTokenParams) -> Result<String, TokenError> {
+        validate_cidrs(&params.bound_cidrs)?;
         let plaintext_token = uuid::Uuid::new_v4().to_string();
         let token_hash = hash_token(&plaintext_token);
         let now = Utc::now();
@@ -109,6 +201,8 @@ impl TokenStore {
             parent_hash: params.parent_hash.clone(),
             metadata: params.metadata,
             display_name: params.display_name,
+            bound_cidrs: params.bound_cidrs,
+            batch: false,
         };
 
         let entry_bytes = serde_json::to_vec(&entry).map_err(|e| {
@@ -121,6 +215,7 @@ impl TokenStore {
 
         let key = format!("{TOKEN_PREFIX}{token_hash}");
         self.barrier.put(&key, &entry_bytes).await?;
+        self.index_accessor(&token_hash).await?;
 
         // Index parent→child relationship for tree revocation.
         if let Some(ref parent) = params.parent_hash {
@@ -128,11 +223,67 @@ impl TokenStore {
             self.barrier.put(&child_key, b"1").await?;
         }
 
+        metrics::global()
+            .tokens_created
+            .fetch_add(1, Ordering::Relaxed);
         info!(display_name = %entry.display_name, "token created");
 
         Ok(plaintext_token)
     }
 
+    /// Create a batch token. Nothing is stored; `ttl` defaults to an hour
+    /// and is cut short to the parent's remaining TTL, and `renewable` and
+    /// `max_ttl` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenError::InvalidBoundCidr`] if a bound CIDR doesn't parse.
+    /// - [`TokenError::NotFound`] if the parent token doesn't exist.
+    /// - [`TokenError::Barrier`] if the batch token key cannot be read or
+    ///   created, or encryption fails.
+    pub async fn create_batch(&self, params: CreateTokenParams) -> Result<String, TokenError> {
+        validate_cidrs(&params.bound_cidrs)?;
+        let now = Utc::now();
+        let ttl = params
+            .ttl
+            .unwrap_or_else(|| Duration::seconds(BATCH_DEFAULT_TTL_SECS));
+        let mut expires_at = now + ttl;
+        if let Some(parent) = &params.parent_hash
+            && let Some(parent_expires_at) = self.parent(parent).await?.expires_at
+        {
+            expires_at = expires_at.min(parent_expires_at);
+        }
+        let payload = BatchPayload {
+            policies: params.policies,
+            created_at: now,
+            expires_at,
+            parent_hash: params.parent_hash,
+            display_name: params.display_name,
+            metadata: params.metadata,
+            bound_cidrs: params.bound_cidrs,
+        };
+
+        let plaintext = serde_json::to_vec(&payload).map_err(|e| {
+            TokenError::Barrier(BarrierError::Crypto(CryptoError::Encryption {
+                reason: format!("token serialization failed: {e}"),
+            }))
+        })?;
+        let (enc_key, mac_key) = self.batch_keys().await?;
+        let mut sealed = crypto::encrypt(&enc_key, &plaintext).map_err(BarrierError::Crypto)?;
+        let tag = batch_mac(&mac_key)?.chain_update(&sealed).finalize();
+        sealed.extend_from_slice(&tag.into_bytes());
+
+        metrics::global()
+            .tokens_created
+            .fetch_add(1, Ordering::Relaxed);
+        debug!(display_name = %payload.display_name, "batch token created");
+
+        Ok(format!(
+            "{BATCH_TOKEN_PREFIX}{}",
+            URL_SAFE_NO_PAD.encode(&sealed)
+        ))
+    }
+
     /// Create a token with a specific plaintext value and persist its hash.
     ///
     /// Used during vault initialization to store the root token generated by
@@ -141,12 +292,14 @@ impl TokenStore {
     ///
     /// # Errors
     ///
-    /// Returns [`TokenError::Barrier`] if storage fails.
+    /// - [`TokenError::InvalidBoundCidr`] if a bound CIDR doesn't parse.
+    /// - [`TokenError::Barrier`] if storage fails.
     pub async fn create_with_token(
         &self,
         plaintext_token: &str,
         params: CreateTokenParams,
     ) -> Result<(), TokenError> {
+        validate_cidrs(&params.bound_cidrs)?;
         let token_hash = hash_token(plaintext_token);
         let now = Utc::now();
 
@@ -162,6 +315,8 @@ impl TokenStore {
             parent_hash: params.parent_hash.clone(),
             metadata: params.metadata,
             display_name: params.display_name,
+            bound_cidrs: params.bound_cidrs,
+            batch: false,
         };
 
         let entry_bytes = serde_json::to_vec(&entry).map_err(|e| {
@@ -174,12 +329,16 @@ impl TokenStore {
 
         let key = format!("{TOKEN_PREFIX}{token_hash}");
         self.barrier.put(&key, &entry_bytes).await?;
+        self.index_accessor(&token_hash).await?;
 
         if let Some(ref parent) = params.parent_hash {
             let child_key = format!("{TOKEN_CHILDREN_PREFIX}{parent}/{token_hash}");
             self.barrier.put(&child_key, b"1").await?;
         }
 
+        metrics::global()
+            .tokens_created
+            .fetch_add(1, Ordering::Relaxed);
         info!(display_name = %entry.display_name, "token created (pre-generated)");
 
         Ok(())
@@ -195,6 +354,10 @@ impl TokenStore {
     /// - [`TokenError::Expired`] if the token's TTL has passed.
     /// - [`TokenError::Barrier`] if storage fails.
     pub async fn lookup(&self, plaintext_token: &str) -> Result<TokenEntry, TokenError> {
+        if let Some(encoded) = plaintext_token.strip_prefix(BATCH_TOKEN_PREFIX) {
+            return self.lookup_batch(plaintext_token, encoded).await;
+        }
+
         let token_hash = hash_token(plaintext_token);
         let key = format!("{TOKEN_PREFIX}{token_hash}");
 
@@ -220,6 +383,60 @@ impl TokenStore {
         Ok(entry)
     }
 
+    /// Look up a token by its accessor.
+    ///
+    /// Unlike [`Self::lookup`], an expired token that is still stored is
+    /// returned, so operators can see everything outstanding.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenError::NotFound`] if no stored token has this accessor.
+    /// - [`TokenError::Barrier`] if storage fails.
+    pub async fn lookup_accessor(&self, accessor: &str) -> Result<TokenEntry, TokenError> {
+        if accessor.len() != 32 || !accessor.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(TokenError::NotFound);
+        }
+
+        let index_key = format!("{TOKEN_ACCESSOR_PREFIX}{accessor}");
+        if let Some(hash) = self.barrier.get(&index_key).await? {
+            let key = format!("{TOKEN_PREFIX}{}", String::from_utf8_lossy(&hash));
+            let data = self.barrier.get(&key).await?.ok_or(TokenError::NotFound)?;
+            return serde_json::from_slice(&data).map_err(|e| {
+                TokenError::Barrier(crate::error::BarrierError::Crypto(
+                    crate::error::CryptoError::Decryption {
+                        reason: format!("token deserialization failed: {e}"),
+                    },
+                ))
+            });
+        }
+
+        // Tokens created before the index existed: find by scan, then index.
+        let entry = self
+            .list_all()
+            .await?
+            .into_iter()
+            .find(|entry| entry.accessor().as_deref() == Some(accessor))
+            .ok_or(TokenError::NotFound)?;
+        self.index_accessor(&entry.token_hash).await?;
+        Ok(entry)
+    }
+
+    /// Accessors of all stored tokens, sorted. Expired tokens are included.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::Barrier`] if storage fails.
+    pub async fn list_accessors(&self) -> Result<Vec<String>, TokenError> {
+        let mut accessors: Vec<String> = self
+            .list_all()
+            .await?
+            .iter()
+            .filter_map(TokenEntry::accessor)
+            .collect();
+        accessors.sort();
+        Ok(accessors)
+    }
+
     /// Renew a token, extending its TTL.
     ///
     /// # Errors
@@ -235,6 +452,7 @@ impl TokenStore {
     ) -> Result<TokenEntry, TokenError> {
         let mut entry = self.lookup(plaintext_token).await?;
 
+        // Batch tokens are never renewable.
         if !entry.renewable {
             return Err(TokenError::NotRenewable);
         }
@@ -271,16 +489,67 @@ impl TokenStore {
         Ok(entry)
     }
 
+    /// Set one metadata entry on a token.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenError::NotFound`] if the token doesn't exist.
+    /// - [`TokenError::Expired`] if the token's TTL has passed.
+    /// - [`TokenError::Barrier`] if storage fails.
+    pub async fn set_metadata(
+        &self,
+        plaintext_token: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<TokenEntry, TokenError> {
+        let mut entry = self.lookup(plaintext_token).await?;
+        if entry.batch {
+            return Err(TokenError::BatchUnsupported {
+                operation: "metadata update",
+            });
+        }
+        entry.metadata.insert(key.to_owned(), value.to_owned());
+
+        let entry_bytes = serde_json::to_vec(&entry).map_err(|e| {
+            TokenError::Barrier(crate::error::BarrierError::Crypto(
+                crate::error::CryptoError::Encryption {
+                    reason: format!("token serialization failed: {e}"),
+                },
+            ))
+        })?;
+
+        let key = format!("{TOKEN_PREFIX}{}", entry.token_hash);
+        self.barrier.put(&key, &entry_bytes).await?;
+
+        Ok(entry)
+    }
+
     /// Revoke a token and all its children (tree revocation).
     ///
     /// # Errors
     ///
     /// Returns [`TokenError::Barrier`] if storage fails.
     pub async fn revoke(&self, plaintext_token: &str) -> Result<(), TokenError> {
+        if plaintext_token.starts_with(BATCH_TOKEN_PREFIX) {
+            return Err(TokenError::BatchUnsupported {
+                operation: "revocation",
+            });
+        }
         let token_hash = hash_token(plaintext_token);
         self.revoke_by_hash(&token_hash).await
     }
 
+    /// Revoke the token with `accessor` and all its children.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenError::NotFound`] if no stored token has this accessor.
+    /// - [`TokenError::Barrier`] if storage fails.
+    pub async fn revoke_accessor(&self, accessor: &str) -> Result<(), TokenError> {
+        let entry = self.lookup_accessor(accessor).await?;
+        self.revoke_by_hash(&entry.token_hash).await
+    }
+
     /// List all stored token entries (metadata only, no plaintext tokens).
     ///
     /// Returns token entries with their hashes, policies, and expiry info.
@@ -314,6 +583,100 @@ impl TokenStore {
         Ok(keys.len())
     }
 
+    /// Decode and check batch token `token`, whose part after the prefix is
+    /// `encoded`. Anything that does not verify is reported as not found.
+    async fn lookup_batch(&self, token: &str, encoded: &str) -> Result<TokenEntry, TokenError> {
+        let raw = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|_| TokenError::NotFound)?;
+        let Some(split) = raw.len().checked_sub(BATCH_TAG_LEN) else {
+            return Err(TokenError::NotFound);
+        };
+        let (sealed, tag) = raw.split_at(split);
+        let (enc_key, mac_key) = self.batch_keys().await?;
+        batch_mac(&mac_key)?
+            .chain_update(sealed)
+            .verify_slice(tag)
+            .map_err(|_| TokenError::NotFound)?;
+        let plaintext = crypto::decrypt(&enc_key, sealed).map_err(|_| TokenError::NotFound)?;
+        let payload: BatchPayload =
+            serde_json::from_slice(&plaintext).map_err(|_| TokenError::NotFound)?;
+
+        if Utc::now() > payload.expires_at {
+            return Err(TokenError::Expired {
+                expired_at: payload.expires_at.to_rfc3339(),
+            });
+        }
+        // A batch token lives only as long as its parent.
+        if let Some(parent) = &payload.parent_hash {
+            let parent = self.parent(parent).await?;
+            if parent.expires_at.is_some_and(|at| Utc::now() > at) {
+                return Err(TokenError::NotFound);
+            }
+        }
+
+        Ok(TokenEntry {
+            token_hash: hash_token(token),
+            policies: payload.policies,
+            created_at: payload.created_at,
+            expires_at: Some(payload.expires_at),
+            renewable: false,
+            max_ttl: None,
+            parent_hash: payload.parent_hash,
+            metadata: payload.metadata,
+            display_name: payload.display_name,
+            bound_cidrs: payload.bound_cidrs,
+            batch: true,
+        })
+    }
+
+    /// The stored parent of a batch token, expired or not.
+    async fn parent(&self, parent_hash: &str) -> Result<TokenEntry, TokenError> {
+        let data = self
+            .barrier
+            .get(&format!("{TOKEN_PREFIX}{parent_hash}"))
+            .await?
+            .ok_or(TokenError::NotFound)?;
+        serde_json::from_slice(&data).map_err(|_| TokenError::NotFound)
+    }
+
+    /// The encryption and HMAC keys for batch tokens, derived from the key
+    /// stored at [`BATCH_KEY_PATH`], which is created on first use.
+    async fn batch_keys(&self) -> Result<(EncryptionKey, EncryptionKey), TokenError> {
+        let mut stored = if let Some(stored) = self.barrier.get(BATCH_KEY_PATH).await? {
+            stored
+        } else {
+            let _guard = self.batch_key_lock.lock().await;
+            if let Some(stored) = self.barrier.get(BATCH_KEY_PATH).await? {
+                stored
+            } else {
+                let key = EncryptionKey::generate();
+                self.barrier.put(BATCH_KEY_PATH, key.as_bytes()).await?;
+                info!("batch token key created");
+                key.as_bytes().to_vec()
+            }
+        };
+        let bytes: [u8; 32] = stored.as_slice().try_into().map_err(|_| {
+            TokenError::Barrier(BarrierError::Crypto(CryptoError::Decryption {
+                reason: "batch token key has the wrong length".to_owned(),
+            }))
+        })?;
+        stored.zeroize();
+        let root = EncryptionKey::from_bytes(bytes);
+        let enc_key = crypto::derive_key(&root, None, b"zvault-batch-token-enc-v1")
+            .map_err(BarrierError::Crypto)?;
+        let mac_key = crypto::derive_key(&root, None, b"zvault-batch-token-mac-v1")
+            .map_err(BarrierError::Crypto)?;
+        Ok((enc_key, mac_key))
+    }
+
+    /// Record the accessor of the token with `token_hash` in the index.
+    async fn index_accessor(&self, token_hash: &str) -> Result<(), TokenError> {
+        let key = format!("{TOKEN_ACCESSOR_PREFIX}{}", token_accessor(token_hash));
+        self.barrier.put(&key, token_hash.as_bytes()).await?;
+        Ok(())
+    }
+
     /// Revoke a token by its hash, recursively revoking children.
     async fn revoke_by_hash(&self, token_hash: &str) -> Result<(), TokenError> {
         // First, revoke all children.
@@ -330,10 +693,18 @@ impl TokenStore {
             self.barrier.delete(child_key).await?;
         }
 
-        // Delete the token itself.
+        // Delete the token itself, its accessor, then its cubbyhole.
         let key = format!("{TOKEN_PREFIX}{token_hash}");
         self.barrier.delete(&key).await?;
-
+        let accessor_key = format!("{TOKEN_ACCESSOR_PREFIX}{}", token_accessor(token_hash));
+        self.barrier.delete(&accessor_key).await?;
+        Cubbyhole::new(Arc::clone(&self.barrier))
+            .destroy(token_hash)
+            .await?;
+
+        metrics::global()
+            .tokens_revoked
+            .fetch_add(1, Ordering::Relaxed);
         info!(
             token_hash_prefix = &token_hash[..8.min(token_hash.len())],
             "token revoked"
@@ -343,6 +714,15 @@ impl TokenStore {
     }
 }
 
+/// HMAC-SHA256 keyed with `key`, for batch token tags.
+fn batch_mac(key: &EncryptionKey) -> Result<HmacSha256, TokenError> {
+    <HmacSha256 as Mac>::new_from_slice(key.as_bytes()).map_err(|e| {
+        TokenError::Barrier(BarrierError::Crypto(CryptoError::Encryption {
+            reason: format!("batch token MAC key rejected: {e}"),
+        }))
+    })
+}
+
 /// Hash a plaintext token with SHA-256, returning hex-encoded hash.
 ///
 /// This is a one-way operation. The plaintext token cannot be recovered.
@@ -352,8 +732,264 @@ pub fn hash_token(plaintext: &str) -> String {
     hex::encode(digest)
 }
 
+/// Accessor of the token with hash `token_hash`: identifies the token in
+/// audit logs and lookups, and stays the same across restarts, but cannot be
+/// turned back into the hash or the token.
+#[must_use]
+pub fn token_accessor(token_hash: &str) -> String {
+    let digest = Sha256::digest(format!("accessor:{token_hash}").as_bytes());
+    hex::encode(&digest[..16])
+}
+
+/// Parse a CIDR block, or a single address as a block of one.
+#[must_use]
+pub fn parse_cidr(value: &str) -> Option<IpNet> {
+    let value = value.trim();
+    value
+        .parse::<IpNet>()
+        .ok()
+        .or_else(|| value.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+/// Check that every entry of `cidrs` parses with [`parse_cidr`].
+///
+/// # Errors
+///
+/// Returns [`TokenError::InvalidBoundCidr`] naming the first bad entry.
+pub fn validate_cidrs(cidrs: &[String]) -> Result<(), TokenError> {
+    match cidrs.iter().find(|cidr| parse_cidr(cidr).is_none()) {
+        Some(cidr) => Err(TokenError::InvalidBoundCidr { cidr: cidr.clone() }),
+        None => Ok(()),
+    }
+}
+
+/// Whether a client at `ip` is inside one of `cidrs`. An empty list allows
+/// every client, even one whose address is unknown.
+#[must_use]
+pub fn cidrs_allow(cidrs: &[String], ip: Option<IpAddr>) -> bool {
+    if cidrs.is_empty() {
+        return true;
+    }
+    let Some(ip) = ip.map(|ip| ip.to_canonical()) else {
+        return false;
+    };
+    cidrs
+        .iter()
+        .filter_map(|cidr| parse_cidr(cidr))
+        .any(|net| net.contains(&ip))
+}
+
+/// Whether every network in `inner` lies inside one of `outer`. Anything is
+/// inside an empty `outer`; an empty `inner` is only inside an empty `outer`.
+#[must_use]
+pub fn cidrs_within(inner: &[String], outer: &[String]) -> bool {
+    if outer.is_empty() {
+        return true;
+    }
+    let outer: Vec<IpNet> = outer.iter().filter_map(|cidr| parse_cidr(cidr)).collect();
+    !inner.is_empty()
+        && inner
+            .iter()
+            .all(|cidr| parse_cidr(cidr).is_some_and(|net| outer.iter().any(|o| o.contains(&net))))
+}
+
 impl std::fmt::Debug for TokenStore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TokenStore").finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use zvault_storage::MemoryBackend;
+
+    fn cidrs(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| (*v).to_owned()).collect()
+    }
+
+    fn params(parent_hash: Option<String>) -> CreateTokenParams {
+        CreateTokenParams {
+            policies: vec!["default".to_owned()],
+            ttl: None,
+            max_ttl: None,
+            renewable: true,
+            parent_hash,
+            metadata: std::collections::HashMap::new(),
+            display_name: "test".to_owned(),
+            bound_cidrs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn bound_cidrs_limit_clients() {
+        let bound = cidrs(&["10.0.0.0/8", "192.168.1.7", "fd00::/8"]);
+        let ip = |s: &str| Some(s.parse::<IpAddr>().unwrap());
+
+        assert!(cidrs_allow(&bound, ip("10.1.2.3")));
+        assert!(cidrs_allow(&bound, ip("192.168.1.7")));
+        assert!(cidrs_allow(&bound, ip("::ffff:10.1.2.3")));
+        assert!(cidrs_allow(&bound, ip("fd12::1")));
+        assert!(!cidrs_allow(&bound, ip("192.168.1.8")));
+        assert!(!cidrs_allow(&bound, None));
+        assert!(cidrs_allow(&[], None));
+
+        assert!(validate_cidrs(&bound).is_ok());
+        let err = validate_cidrs(&cidrs(&["10.0.0.0/8", "10.0.0.0/33"])).unwrap_err();
+        assert!(matches!(err, TokenError::InvalidBoundCidr { cidr } if cidr == "10.0.0.0/33"));
+    }
+
+    #[test]
+    fn child_cidrs_within_parent() {
+        let parent = cidrs(&["10.0.0.0/8"]);
+        assert!(cidrs_within(&cidrs(&["10.1.0.0/16", "10.2.3.4"]), &parent));
+        assert!(!cidrs_within(&cidrs(&["10.1.0.0/16", "11.0.0.1"]), &parent));
+        assert!(!cidrs_within(&cidrs(&["0.0.0.0/0"]), &parent));
+        assert!(!cidrs_within(&[], &parent));
+        assert!(cidrs_within(&[], &[]));
+    }
+
+    #[test]
+    fn accessor_is_stable_and_not_the_hash() {
+        let hash = hash_token("s.token");
+        assert_eq!(token_accessor(&hash), token_accessor(&hash));
+        assert_eq!(token_accessor(&hash).len(), 32);
+        assert!(!hash.contains(&token_accessor(&hash)));
+    }
+
+    #[tokio::test]
+    async fn lookup_and_revoke_by_accessor() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let store = TokenStore::new(Arc::clone(&barrier));
+
+        let parent = store.create(params(None)).await.unwrap();
+        let parent_hash = hash_token(&parent);
+        let child = store
+            .create(params(Some(parent_hash.clone())))
+            .await
+            .unwrap();
+        let parent_accessor = token_accessor(&parent_hash);
+        let child_accessor = token_accessor(&hash_token(&child));
+
+        let mut expected = vec![parent_accessor.clone(), child_accessor.clone()];
+        expected.sort();
+        assert_eq!(store.list_accessors().await.unwrap(), expected);
+        let entry = store.lookup_accessor(&parent_accessor).await.unwrap();
+        assert_eq!(entry.token_hash, parent_hash);
+
+        // A token stored before the index existed is found by scanning.
+        barrier
+            .delete(&format!("{TOKEN_ACCESSOR_PREFIX}{child_accessor}"))
+            .await
+            .unwrap();
+        assert!(store.lookup_accessor(&child_accessor).await.is_ok());
+
+        store.revoke_accessor(&parent_accessor).await.unwrap();
+        assert!(matches!(
+            store.lookup(&child).await,
+            Err(TokenError::NotFound)
+        ));
+        for accessor in [&parent_accessor, &child_accessor, &"../x".to_owned()] {
+            assert!(matches!(
+                store.lookup_accessor(accessor).await,
+                Err(TokenError::NotFound)
+            ));
+        }
+        assert!(
+            barrier
+                .list(TOKEN_ACCESSOR_PREFIX)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_tokens_are_not_stored() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let store = TokenStore::new(Arc::clone(&barrier));
+
+        let parent = store.create(params(None)).await.unwrap();
+        let mut batch_params = params(Some(hash_token(&parent)));
+        batch_params.ttl = Some(Duration::minutes(5));
+        batch_params.bound_cidrs = cidrs(&["10.0.0.0/8"]);
+        let batch = store.create_batch(batch_params).await.unwrap();
+        assert!(batch.starts_with(BATCH_TOKEN_PREFIX));
+        assert_eq!(store.count().await.unwrap(), 1);
+
+        let entry = store.lookup(&batch).await.unwrap();
+        assert!(entry.batch && !entry.renewable);
+        assert_eq!(entry.policies, vec!["default".to_owned()]);
+        assert_eq!(entry.bound_cidrs, cidrs(&["10.0.0.0/8"]));
+        assert!(matches!(
+            store.renew(&batch, Duration::hours(1)).await,
+            Err(TokenError::NotRenewable)
+        ));
+        assert!(matches!(
+            store.revoke(&batch).await,
+            Err(TokenError::BatchUnsupported { .. })
+        ));
+
+        // Any change to the token breaks its tag.
+        let mut tampered = batch.clone().into_bytes();
+        let last = tampered.len() - 10;
+        tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+        let tampered = String::from_utf8(tampered).unwrap();
+        assert!(matches!(
+            store.lookup(&tampered).await,
+            Err(TokenError::NotFound)
+        ));
+
+        let mut expired_params = params(None);
+        expired_params.ttl = Some(Duration::seconds(-1));
+        let expired = store.create_batch(expired_params).await.unwrap();
+        assert!(matches!(
+            store.lookup(&expired).await,
+            Err(TokenError::Expired { .. })
+        ));
+
+        // Revoking the parent ends the batch token.
+        store.revoke(&parent).await.unwrap();
+        assert!(matches!(
+            store.lookup(&batch).await,
+            Err(TokenError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn batch_tokens_expire_with_their_parent() {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        let store = TokenStore::new(barrier);
+
+        let mut parent_params = params(None);
+        parent_params.ttl = Some(Duration::minutes(10));
+        let parent = store.create(parent_params).await.unwrap();
+        let parent_expires_at = store.lookup(&parent).await.unwrap().expires_at;
+
+        let mut batch_params = params(Some(hash_token(&parent)));
+        batch_params.ttl = Some(Duration::hours(1));
+        let batch = store.create_batch(batch_params).await.unwrap();
+        // Batch tokens keep whole seconds.
+        let expires_at = store.lookup(&batch).await.unwrap().expires_at.unwrap();
+        let parent_expires_at = parent_expires_at.unwrap();
+        assert!(expires_at <= parent_expires_at);
+        assert!(expires_at > parent_expires_at - Duration::seconds(1));
+
+        // A shorter TTL is kept, and a missing parent is refused.
+        let mut batch_params = params(Some(hash_token(&parent)));
+        batch_params.ttl = Some(Duration::minutes(1));
+        let batch = store.create_batch(batch_params).await.unwrap();
+        assert!(store.lookup(&batch).await.unwrap().expires_at.unwrap() < expires_at);
+        assert!(matches!(
+            store
+                .create_batch(params(Some(hash_token("missing"))))
+                .await,
+            Err(TokenError::NotFound)
+        ));
+    }
+}
@@ -5,28 +5,104 @@
 //! Keys are named, versioned, and stored through the barrier.
 //!
 //! Supported operations:
-//! - `encrypt` / `decrypt` — AES-256-GCM
+//! - `encrypt` / `decrypt` — AES-256-GCM, singly or in batches of up to
+//!   [`MAX_BATCH_ITEMS`] processed concurrently with per-item results
 //! - `rewrap` — re-encrypt ciphertext under the latest key version
+//! - `config` — set `min_decryption_version` / `min_encryption_version`
 //! - `datakey` — generate a data encryption key (returned wrapped + plaintext)
+//! - `import` — bring your own key, wrapped for the engine's RSA wrapping key
+//! - `export` — return key material, only for keys created as `exportable`
+//! - `tokenize` / `detokenize` — swap a value for a random token and back;
+//!   the engine keeps the value encrypted under the named key, with caller
+//!   metadata. Convergent tokens map equal values to one token, which lets
+//!   `lookup` find a value's token without revealing the value.
 //!
 //! # Security model
 //!
 //! - Named keys are derived from the root key via HKDF with unique info.
-//! - Key versions allow rotation without re-encrypting all data.
+//! - Key versions allow rotation without re-encrypting all data. Once old
+//!   ciphertexts are rewrapped, raising `min_decryption_version` retires the
+//!   old versions; `min_encryption_version` stops callers pinning an old
+//!   version for new ciphertexts.
 //! - Ciphertext is prefixed with `vault:v{version}:` for version tracking.
+//! - Keys with [`KeyBackend::Pkcs11`] keep their material in an HSM slot;
+//!   only the per-version object label, which names the mount, is stored
+//!   (see [`crate::hsm`]). Deleting a key destroys its HSM objects.
+//! - Imported keys arrive as `RSA-OAEP-SHA256(ephemeral) || KWP(ephemeral, key)`
+//!   (the format cloud KMS tooling emits); the RSA-4096 wrapping key is
+//!   generated on first use and never leaves the barrier.
+//! - Exportability is fixed at creation/import time and cannot be turned on
+//!   later, so a key created non-exportable never leaves the engine.
+//! - Tokens are random and carry no information about their value. The
+//!   convergent index is keyed by an HMAC of the value under a per-mount
+//!   secret that never leaves the barrier.
 
+use std::any::Any;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
 use base64::Engine;
-use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
 use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rsa::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
 use serde::{Deserialize, Serialize};
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use sha2::Sha256;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::barrier::Barrier;
 use crate::crypto::{self, EncryptionKey};
 use crate::error::EngineError;
+use crate::hsm::HsmProvider;
+
+/// Size of the RSA key used to wrap imported keys.
+const WRAPPING_KEY_BITS: usize = 4096;
+
+/// Export type for AES encryption keys (the only kind transit holds).
+pub const EXPORT_ENCRYPTION_KEY: &str = "encryption-key";
+
+/// Largest batch accepted by [`TransitEngine::encrypt_batch`] and
+/// [`TransitEngine::decrypt_batch`].
+pub const MAX_BATCH_ITEMS: usize = 1000;
+
+/// Batch items processed at once. Bounds HSM sessions as well as CPU use.
+const BATCH_CONCURRENCY: usize = 16;
+
+/// Prefix of every token issued by [`TransitEngine::tokenize`].
+pub const TOKEN_PREFIX: &str = "tok_";
+
+/// Random bytes in a token (encoded as unpadded base64url after the prefix).
+const TOKEN_BYTES: usize = 24;
+
+/// Where a transit key's material lives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyBackend {
+    /// Generated by `ZVault` and stored encrypted through the barrier.
+    #[default]
+    Internal,
+    /// Generated inside and never leaves the configured PKCS#11 slot.
+    Pkcs11,
+}
+
+impl std::str::FromStr for KeyBackend {
+    type Err = EngineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "internal" => Ok(Self::Internal),
+            "pkcs11" => Ok(Self::Pkcs11),
+            other => Err(EngineError::InvalidRequest {
+                reason: format!("unknown key backend '{other}', expected 'internal' or 'pkcs11'"),
+            }),
+        }
+    }
+}
 
 /// A named transit key with version history.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,12 +115,83 @@ pub struct TransitKey {
     pub latest_version: u32,
     /// Minimum version allowed for decryption (for key rotation enforcement).
     pub min_decryption_version: u32,
+    /// Minimum version allowed for encryption. `0` means only the latest
+    /// version may be used.
+    #[serde(default)]
+    pub min_encryption_version: u32,
     /// Whether this key supports encryption.
     pub supports_encryption: bool,
     /// Whether this key supports decryption.
     pub supports_decryption: bool,
     /// When the key was created.
     pub created_at: DateTime<Utc>,
+    /// Where the key material lives.
+    #[serde(default)]
+    pub backend: KeyBackend,
+    /// Whether key material may be returned by [`TransitEngine::export_key`].
+    #[serde(default)]
+    pub exportable: bool,
+}
+
+/// Options for [`TransitEngine::create_key_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CreateKeyOptions {
+    /// Where the key material lives.
+    pub backend: KeyBackend,
+    /// Allow the key material to be exported. Cannot be changed later.
+    pub exportable: bool,
+}
+
+/// One plaintext of a [`TransitEngine::encrypt_batch`] call.
+#[derive(Debug, Clone)]
+pub struct BatchEncryptItem {
+    /// Data to encrypt.
+    pub plaintext: Vec<u8>,
+    /// Key version to encrypt with. Defaults to the latest.
+    pub key_version: Option<u32>,
+}
+
+/// Options for [`TransitEngine::tokenize`].
+#[derive(Debug, Clone, Default)]
+pub struct TokenizeOptions {
+    /// Issue one token per distinct value, so equal values share a token.
+    pub convergent: bool,
+    /// Caller-supplied metadata stored with the token.
+    pub metadata: HashMap<String, String>,
+}
+
+/// A token and what the engine stores about it (never the value itself).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenInfo {
+    /// The token.
+    pub token: String,
+    /// Whether equal values map to this same token.
+    pub convergent: bool,
+    /// Caller-supplied metadata.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Key version the value is encrypted under.
+    pub key_version: u32,
+    /// When the token was issued.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Stored token record: the public info plus the encrypted value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TokenRecord {
+    #[serde(flatten)]
+    info: TokenInfo,
+    ciphertext: String,
+}
+
+/// Changes for [`TransitEngine::update_key_config`]. `None` leaves a field as is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyConfigUpdate {
+    /// New minimum version allowed for decryption (`1..=latest_version`).
+    pub min_decryption_version: Option<u32>,
+    /// New minimum version allowed for encryption (`0`, or
+    /// `min_decryption_version..=latest_version`).
+    pub min_encryption_version: Option<u32>,
 }
 
 /// A single version of a transit key.
@@ -55,9 +202,13 @@ pub struct TransitKey {
 pub struct TransitKeyVersion {
     /// The raw key material (32 bytes, stored encrypted through barrier).
     /// Zeroized on drop to prevent key material from lingering in memory.
+    /// Empty for HSM-backed versions.
     pub key_material: ZeroizingKeyMaterial,
     /// When this version was created.
     pub created_at: DateTime<Utc>,
+    /// Label of the HSM key object, for HSM-backed versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hsm_label: Option<String>,
 }
 
 /// Wrapper around `Vec<u8>` that zeroizes key material on drop.
@@ -104,66 +255,196 @@ pub struct TransitEngine {
     barrier: Arc<Barrier>,
     /// Storage prefix for transit keys.
     prefix: String,
+    /// HSM used for keys with [`KeyBackend::Pkcs11`], if configured.
+    hsm: Option<Arc<dyn HsmProvider>>,
+    /// Serializes lazy creation of the import wrapping key.
+    wrapping_key_lock: Mutex<()>,
+    /// Serializes convergent tokenization so a value gets a single token.
+    token_lock: Mutex<()>,
 }
 
 impl TransitEngine {
     /// Create a new transit engine with the given barrier and mount prefix.
     #[must_use]
     pub fn new(barrier: Arc<Barrier>, prefix: String) -> Self {
-        Self { barrier, prefix }
+        Self {
+            barrier,
+            prefix,
+            hsm: None,
+            wrapping_key_lock: Mutex::new(()),
+            token_lock: Mutex::new(()),
+        }
     }
 
-    /// Create a new named encryption key.
+    /// Attach an HSM so keys can be created with [`KeyBackend::Pkcs11`].
+    #[must_use]
+    pub fn with_hsm(mut self, hsm: Arc<dyn HsmProvider>) -> Self {
+        self.hsm = Some(hsm);
+        self
+    }
+
+    /// Create a new named encryption key with internal key material.
     ///
     /// # Errors
     ///
     /// Returns [`EngineError`] if the key already exists or storage fails.
     pub async fn create_key(&self, name: &str) -> Result<(), EngineError> {
-        let storage_key = format!("{}keys/{}", self.prefix, name);
-
-        if self
-            .barrier
-            .get(&storage_key)
+        self.create_key_with_options(name, CreateKeyOptions::default())
             .await
-            .map_err(EngineError::Barrier)?
-            .is_some()
-        {
+    }
+
+    /// Create a new named encryption key with the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if the key already exists, the backend is
+    /// `pkcs11` but no HSM is configured (or the key is marked exportable),
+    /// or storage or the HSM fails.
+    pub async fn create_key_with_options(
+        &self,
+        name: &str,
+        options: CreateKeyOptions,
+    ) -> Result<(), EngineError> {
+        if options.exportable && options.backend == KeyBackend::Pkcs11 {
             return Err(EngineError::InvalidRequest {
-                reason: format!("key '{name}' already exists"),
+                reason: "pkcs11-backed keys cannot be exportable".to_owned(),
             });
         }
+        self.ensure_absent(name).await?;
 
-        let key_material = EncryptionKey::generate();
-        let now = Utc::now();
+        let version = self.new_version(name, options.backend, 1).await?;
+        self.save_key(&new_key(name, version, options)).await
+    }
 
-        let mut versions = HashMap::new();
-        versions.insert(
-            1,
-            TransitKeyVersion {
-                key_material: ZeroizingKeyMaterial::new(key_material.as_bytes().to_vec()),
-                created_at: now,
-            },
+    /// PEM-encoded public half of the RSA key used to wrap imported keys.
+    ///
+    /// The key pair is generated on first use and stored through the barrier.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if key generation or storage fails.
+    pub async fn wrapping_key(&self) -> Result<String, EngineError> {
+        let private = self.load_wrapping_key().await?;
+        RsaPublicKey::from(&private)
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| EngineError::Internal {
+                reason: format!("failed to encode wrapping key: {e}"),
+            })
+    }
+
+    /// Import an externally generated AES-256 key as version 1 of `name`.
+    ///
+    /// `ciphertext` is base64 of the RSA-OAEP-SHA256 encryption of an
+    /// ephemeral AES-256 key under [`wrapping_key`](Self::wrapping_key),
+    /// followed by the target key wrapped with that ephemeral key using
+    /// AES key wrap with padding (RFC 5649).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::InvalidRequest`] if the key already exists, the
+    /// ciphertext is malformed or does not unwrap, or the imported key is not
+    /// 32 bytes.
+    pub async fn import_key(
+        &self,
+        name: &str,
+        ciphertext: &str,
+        exportable: bool,
+    ) -> Result<(), EngineError> {
+        self.ensure_absent(name).await?;
+
+        let blob = BASE64
+            .decode(ciphertext.trim())
+            .map_err(|e| EngineError::InvalidRequest {
+                reason: format!("invalid base64 ciphertext: {e}"),
+            })?;
+        let rsa_len = WRAPPING_KEY_BITS / 8;
+        if blob.len() <= rsa_len {
+            return Err(EngineError::InvalidRequest {
+                reason: "ciphertext too short for a wrapped key".to_owned(),
+            });
+        }
+        let (wrapped_ephemeral, wrapped_key) = blob.split_at(rsa_len);
+
+        let private = self.load_wrapping_key().await?;
+        let ephemeral = Zeroizing::new(
+            private
+                .decrypt(Oaep::new::<Sha256>(), wrapped_ephemeral)
+                .map_err(|_| EngineError::InvalidRequest {
+                    reason: "failed to decrypt ephemeral key with the wrapping key".to_owned(),
+                })?,
         );
+        let material =
+            Zeroizing::new(crypto::unwrap_key(&ephemeral, wrapped_key).map_err(|e| {
+                EngineError::InvalidRequest {
+                    reason: format!("failed to unwrap imported key: {e}"),
+                }
+            })?);
+        if material.len() != 32 {
+            return Err(EngineError::InvalidRequest {
+                reason: format!("imported key must be 32 bytes, got {}", material.len()),
+            });
+        }
 
-        let transit_key = TransitKey {
-            name: name.to_owned(),
-            versions,
-            latest_version: 1,
-            min_decryption_version: 1,
-            supports_encryption: true,
-            supports_decryption: true,
-            created_at: now,
+        let version = TransitKeyVersion {
+            key_material: ZeroizingKeyMaterial::new(material.to_vec()),
+            created_at: Utc::now(),
+            hsm_label: None,
         };
+        let options = CreateKeyOptions {
+            backend: KeyBackend::Internal,
+            exportable,
+        };
+        self.save_key(&new_key(name, version, options)).await
+    }
 
-        let bytes = serde_json::to_vec(&transit_key).map_err(|e| EngineError::Internal {
-            reason: format!("serialization failed: {e}"),
-        })?;
-        self.barrier
-            .put(&storage_key, &bytes)
-            .await
-            .map_err(EngineError::Barrier)?;
+    /// Export key material for an exportable key.
+    ///
+    /// `version` is `None` or `"all"` for every version, `"latest"`, or a
+    /// version number. Returns base64 key material keyed by version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::InvalidRequest`] if the key is not exportable or
+    /// `export_type` is unsupported, and [`EngineError::NotFound`] if the key
+    /// or version does not exist.
+    pub async fn export_key(
+        &self,
+        name: &str,
+        export_type: &str,
+        version: Option<&str>,
+    ) -> Result<HashMap<u32, String>, EngineError> {
+        if export_type != EXPORT_ENCRYPTION_KEY {
+            return Err(EngineError::InvalidRequest {
+                reason: format!(
+                    "unsupported export type '{export_type}', expected '{EXPORT_ENCRYPTION_KEY}'"
+                ),
+            });
+        }
 
-        Ok(())
+        let key = self.load_key(name).await?;
+        if !key.exportable {
+            return Err(EngineError::InvalidRequest {
+                reason: format!("key '{name}' is not exportable"),
+            });
+        }
+
+        let selected: Vec<u32> = match version {
+            None | Some("all") => key.versions.keys().copied().collect(),
+            Some("latest") => vec![key.latest_version],
+            Some(v) => vec![v.parse().map_err(|_| EngineError::InvalidRequest {
+                reason: format!("invalid version '{v}'"),
+            })?],
+        };
+
+        selected
+            .into_iter()
+            .map(|v| {
+                let kv = key.versions.get(&v).ok_or_else(|| EngineError::NotFound {
+                    path: format!("transit/keys/{name}/v{v}"),
+                })?;
+                Ok((v, BASE64.encode(kv.key_material.as_bytes())))
+            })
+            .collect()
     }
 
     /// Rotate a named key, creating a new version.
@@ -174,16 +455,9 @@ impl TransitEngine {
     pub async fn rotate_key(&self, name: &str) -> Result<u32, EngineError> {
         let mut key = self.load_key(name).await?;
 
-        let new_material = EncryptionKey::generate();
         let new_version = key.latest_version.saturating_add(1);
-
-        key.versions.insert(
-            new_version,
-            TransitKeyVersion {
-                key_material: ZeroizingKeyMaterial::new(new_material.as_bytes().to_vec()),
-                created_at: Utc::now(),
-            },
-        );
+        let version = self.new_version(name, key.backend, new_version).await?;
+        key.versions.insert(new_version, version);
         key.latest_version = new_version;
 
         self.save_key(&key).await?;
@@ -191,6 +465,64 @@ impl TransitEngine {
         Ok(new_version)
     }
 
+    /// Delete a named key with all its versions and tokens, destroying the
+    /// HSM objects of HSM-backed versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if the key doesn't exist, storage fails, or
+    /// the HSM fails to destroy a version (the key is deleted regardless).
+    pub async fn delete_key(&self, name: &str) -> Result<(), EngineError> {
+        let key = self.load_key(name).await?;
+
+        for prefix in [
+            format!("{}tokens/{name}/", self.prefix),
+            format!("{}token-index/{name}/", self.prefix),
+        ] {
+            let entries = self
+                .barrier
+                .list(&prefix)
+                .await
+                .map_err(EngineError::Barrier)?;
+            for entry in &entries {
+                self.barrier
+                    .delete(entry)
+                    .await
+                    .map_err(EngineError::Barrier)?;
+            }
+        }
+        self.barrier
+            .delete(&format!("{}keys/{name}", self.prefix))
+            .await
+            .map_err(EngineError::Barrier)?;
+
+        let labels: Vec<&String> = key
+            .versions
+            .values()
+            .filter_map(|v| v.hsm_label.as_ref())
+            .collect();
+        if labels.is_empty() {
+            return Ok(());
+        }
+        let hsm = require_hsm(self.hsm.as_ref())?;
+        let mut failed = Vec::new();
+        for label in labels {
+            if let Err(e) = hsm.destroy_key(label).await {
+                failed.push(format!("{label}: {e}"));
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineError::Hsm {
+                reason: format!(
+                    "key '{name}' deleted, but destroying HSM objects failed: {}",
+                    failed.join("; ")
+                ),
+            })
+        }
+    }
+
     /// Encrypt plaintext using the latest version of a named key.
     ///
     /// Returns ciphertext in the format `vault:v{version}:{base64_ciphertext}`.
@@ -200,29 +532,25 @@ impl TransitEngine {
     /// Returns [`EngineError`] if the key doesn't exist, doesn't support
     /// encryption, or a crypto operation fails.
     pub async fn encrypt(&self, key_name: &str, plaintext: &[u8]) -> Result<String, EngineError> {
-        let key = self.load_key(key_name).await?;
-
-        if !key.supports_encryption {
-            return Err(EngineError::InvalidRequest {
-                reason: format!("key '{key_name}' does not support encryption"),
-            });
-        }
-
-        let version = key.latest_version;
-        let key_version = key
-            .versions
-            .get(&version)
-            .ok_or_else(|| EngineError::Internal {
-                reason: format!("key version {version} missing"),
-            })?;
-
-        let enc_key = Self::material_to_key(key_version.key_material.as_bytes())?;
-        let ciphertext =
-            crypto::encrypt(&enc_key, plaintext).map_err(|e| EngineError::Internal {
-                reason: format!("encryption failed: {e}"),
-            })?;
+        self.encrypt_with_version(key_name, plaintext, None).await
+    }
 
-        Ok(format!("vault:v{version}:{}", BASE64.encode(&ciphertext)))
+    /// Encrypt plaintext using a specific version of a named key, or the
+    /// latest when `version` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if the key doesn't exist, doesn't support
+    /// encryption, the version is below `min_encryption_version` or beyond
+    /// the latest version, or a crypto operation fails.
+    pub async fn encrypt_with_version(
+        &self,
+        key_name: &str,
+        plaintext: &[u8],
+        version: Option<u32>,
+    ) -> Result<String, EngineError> {
+        let key = self.load_key(key_name).await?;
+        encrypt_with_key(&key, self.hsm.as_ref(), plaintext, version).await
     }
 
     /// Decrypt ciphertext that was encrypted by this transit engine.
@@ -233,45 +561,132 @@ impl TransitEngine {
     /// is invalid, the version is below `min_decryption_version`, or decryption fails.
     pub async fn decrypt(&self, key_name: &str, ciphertext: &str) -> Result<Vec<u8>, EngineError> {
         let key = self.load_key(key_name).await?;
+        decrypt_with_key(&key, self.hsm.as_ref(), ciphertext).await
+    }
 
-        if !key.supports_decryption {
+    /// Encrypt many plaintexts with one key, concurrently.
+    ///
+    /// The key is loaded once; each item succeeds or fails on its own and
+    /// results are returned in input order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if the key doesn't exist or the batch is empty
+    /// or larger than [`MAX_BATCH_ITEMS`]. Per-item failures are reported in
+    /// the returned results.
+    pub async fn encrypt_batch(
+        &self,
+        key_name: &str,
+        items: Vec<BatchEncryptItem>,
+    ) -> Result<Vec<Result<String, EngineError>>, EngineError> {
+        check_batch_size(items.len())?;
+        let key = Arc::new(self.load_key(key_name).await?);
+        let hsm = self.hsm.clone();
+
+        Ok(
+            run_batch(items, |item| {
+                let key = Arc::clone(&key);
+                let hsm = hsm.clone();
+                async move {
+                    encrypt_with_key(&key, hsm.as_ref(), &item.plaintext, item.key_version).await
+                }
+            })
+            .await,
+        )
+    }
+
+    /// Decrypt many ciphertexts with one key, concurrently.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if the key doesn't exist or the batch is empty
+    /// or larger than [`MAX_BATCH_ITEMS`]. Per-item failures are reported in
+    /// the returned results.
+    pub async fn decrypt_batch(
+        &self,
+        key_name: &str,
+        ciphertexts: Vec<String>,
+    ) -> Result<Vec<Result<Vec<u8>, EngineError>>, EngineError> {
+        check_batch_size(ciphertexts.len())?;
+        let key = Arc::new(self.load_key(key_name).await?);
+        let hsm = self.hsm.clone();
+
+        Ok(run_batch(ciphertexts, |ciphertext| {
+            let key = Arc::clone(&key);
+            let hsm = hsm.clone();
+            async move { decrypt_with_key(&key, hsm.as_ref(), &ciphertext).await }
+        })
+        .await)
+    }
+
+    /// Re-wrap ciphertext under the latest key version (or `version`, when
+    /// given) without revealing plaintext to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if the ciphertext cannot be decrypted or the
+    /// target version cannot be used for encryption.
+    pub async fn rewrap(
+        &self,
+        key_name: &str,
+        ciphertext: &str,
+        version: Option<u32>,
+    ) -> Result<String, EngineError> {
+        let key = self.load_key(key_name).await?;
+        let plaintext =
+            Zeroizing::new(decrypt_with_key(&key, self.hsm.as_ref(), ciphertext).await?);
+        encrypt_with_key(&key, self.hsm.as_ref(), &plaintext, version).await
+    }
+
+    /// Update the version limits of a named key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::InvalidRequest`] if `min_decryption_version` is
+    /// outside `1..=latest_version`, or `min_encryption_version` is neither
+    /// `0` nor within `min_decryption_version..=latest_version`.
+    pub async fn update_key_config(
+        &self,
+        name: &str,
+        update: KeyConfigUpdate,
+    ) -> Result<TransitKeyInfo, EngineError> {
+        let mut key = self.load_key(name).await?;
+
+        let min_decryption = update
+            .min_decryption_version
+            .unwrap_or(key.min_decryption_version);
+        let min_encryption = update
+            .min_encryption_version
+            .unwrap_or(key.min_encryption_version);
+
+        if min_decryption == 0 || min_decryption > key.latest_version {
             return Err(EngineError::InvalidRequest {
-                reason: format!("key '{key_name}' does not support decryption"),
+                reason: format!(
+                    "min_decryption_version must be between 1 and the latest version ({})",
+                    key.latest_version
+                ),
             });
         }
-
-        let (version, raw_ct) = parse_ciphertext(ciphertext)?;
-
-        if version < key.min_decryption_version {
+        if min_encryption > key.latest_version {
             return Err(EngineError::InvalidRequest {
                 reason: format!(
-                    "ciphertext version {version} is below minimum decryption version {}",
-                    key.min_decryption_version
+                    "min_encryption_version must not exceed the latest version ({})",
+                    key.latest_version
                 ),
             });
         }
+        if min_encryption != 0 && min_encryption < min_decryption {
+            return Err(EngineError::InvalidRequest {
+                reason: "min_encryption_version must not be below min_decryption_version"
+                    .to_owned(),
+            });
+        }
 
-        let key_version = key
-            .versions
-            .get(&version)
-            .ok_or_else(|| EngineError::NotFound {
-                path: format!("{key_name}/v{version}"),
-            })?;
-
-        let enc_key = Self::material_to_key(key_version.key_material.as_bytes())?;
-        crypto::decrypt(&enc_key, &raw_ct).map_err(|e| EngineError::Internal {
-            reason: format!("decryption failed: {e}"),
-        })
-    }
+        key.min_decryption_version = min_decryption;
+        key.min_encryption_version = min_encryption;
+        self.save_key(&key).await?;
 
-    /// Re-wrap ciphertext under the latest key version without revealing plaintext.
-    ///
-    /// # Errors
-    ///
-    /// Returns [`EngineError`] on any failure.
-    pub async fn rewrap(&self, key_name: &str, ciphertext: &str) -> Result<String, EngineError> {
-        let plaintext = self.decrypt(key_name, ciphertext).await?;
-        self.encrypt(key_name, &plaintext).await
+        Ok(key_info(key))
     }
 
     /// Generate a new data encryption key, returned both as plaintext and
@@ -316,21 +731,302 @@ impl TransitEngine {
     ///
     /// Returns [`EngineError::NotFound`] if the key doesn't exist.
     pub async fn key_info(&self, name: &str) -> Result<TransitKeyInfo, EngineError> {
-        let key = self.load_key(name).await?;
+        Ok(key_info(self.load_key(name).await?))
+    }
 
-        Ok(TransitKeyInfo {
-            name: key.name,
-            latest_version: key.latest_version,
-            min_decryption_version: key.min_decryption_version,
-            supports_encryption: key.supports_encryption,
-            supports_decryption: key.supports_decryption,
-            version_count: u32::try_from(key.versions.len()).unwrap_or(u32::MAX),
-            created_at: key.created_at,
-        })
+    /// Replace `plaintext` with a random token, storing the value encrypted
+    /// under the latest version of `key_name`.
+    ///
+    /// With `options.convergent`, a value tokenized convergently before gets
+    /// its existing token back (with its original metadata).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError`] if the key doesn't exist, doesn't support
+    /// encryption, or storage fails.
+    pub async fn tokenize(
+        &self,
+        key_name: &str,
+        plaintext: &[u8],
+        options: TokenizeOptions,
+    ) -> Result<TokenInfo, EngineError> {
+        let key = self.load_key(key_name).await?;
+        if !options.convergent {
+            return self.issue_token(&key, plaintext, options).await;
+        }
+
+        let _guard = self.token_lock.lock().await;
+        let index_key = self.token_index_key(key_name, plaintext).await?;
+        if let Some(token) = self
+            .barrier
+            .get(&index_key)
+            .await
+            .map_err(EngineError::Barrier)?
+        {
+            let token = String::from_utf8(token).map_err(|e| EngineError::Internal {
+                reason: format!("corrupt token index: {e}"),
+            })?;
+            return Ok(self.load_token(key_name, &token).await?.info);
+        }
+        let info = self.issue_token(&key, plaintext, options).await?;
+        self.barrier
+            .put(&index_key, info.token.as_bytes())
+            .await
+            .map_err(EngineError::Barrier)?;
+        Ok(info)
+    }
+
+    /// Return the value behind `token`, with the token's metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::NotFound`] if `key_name` issued no such token,
+    /// or [`EngineError`] if the key no longer decrypts it (e.g. its
+    /// version is below `min_decryption_version`).
+    pub async fn detokenize(
+        &self,
+        key_name: &str,
+        token: &str,
+    ) -> Result<(Zeroizing<Vec<u8>>, TokenInfo), EngineError> {
+        let record = self.load_token(key_name, token).await?;
+        let key = self.load_key(key_name).await?;
+        let plaintext = decrypt_with_key(&key, self.hsm.as_ref(), &record.ciphertext).await?;
+        Ok((Zeroizing::new(plaintext), record.info))
+    }
+
+    /// Read what the engine stores about `token`, without its value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::NotFound`] if `key_name` issued no such token.
+    pub async fn lookup_token(
+        &self,
+        key_name: &str,
+        token: &str,
+    ) -> Result<TokenInfo, EngineError> {
+        Ok(self.load_token(key_name, token).await?.info)
+    }
+
+    /// Find the convergent token issued for `plaintext`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EngineError::NotFound`] if the value has no convergent
+    /// token under `key_name`.
+    pub async fn lookup_value(
+        &self,
+        key_name: &str,
+        plaintext: &[u8],
+    ) -> Result<TokenInfo, EngineError> {
+        self.load_key(key_name).await?;
+        let index_key = self.token_index_key(key_name, plaintext).await?;
+        let token = self
+            .barrier
+            .get(&index_key)
+            .await
+            .map_err(EngineError::Barrier)?
+            .ok_or_else(|| EngineError::NotFound {
+                path: format!("transit/tokens/{key_name}"),
+            })?;
+        let token = String::from_utf8(token).map_err(|e| EngineError::Internal {
+            reason: format!("corrupt token index: {e}"),
+        })?;
+        self.lookup_token(key_name, &token).await
     }
 
     // ── Internal helpers ─────────────────────────────────────────────
 
+    /// Generate material for version `version` of key `name`.
+    async fn new_version(
+        &self,
+        name: &str,
+        backend: KeyBackend,
+        version: u32,
+    ) -> Result<TransitKeyVersion, EngineError> {
+        match backend {
+            KeyBackend::Internal => {
+                let material = EncryptionKey::generate();
+                Ok(TransitKeyVersion {
+                    key_material: ZeroizingKeyMaterial::new(material.as_bytes().to_vec()),
+                    created_at: Utc::now(),
+                    hsm_label: None,
+                })
+            }
+            KeyBackend::Pkcs11 => {
+                let hsm = self
+                    .hsm
+                    .as_ref()
+                    .ok_or_else(|| EngineError::InvalidRequest {
+                        reason: "no HSM is configured for pkcs11-backed keys".to_owned(),
+                    })?;
+                let label = format!("zvault-transit-{}{name}-v{version}", self.mount());
+                hsm.generate_key(&label).await?;
+                Ok(TransitKeyVersion {
+                    key_material: ZeroizingKeyMaterial::new(Vec::new()),
+                    created_at: Utc::now(),
+                    hsm_label: Some(label),
+                })
+            }
+        }
+    }
+
+    /// Mount path this engine serves, e.g. `transit/`, recovered from its
+    /// storage prefix (empty for a prefix not under `transit/`).
+    fn mount(&self) -> &str {
+        self.prefix.strip_prefix("transit/").unwrap_or_default()
+    }
+
+    async fn ensure_absent(&self, name: &str) -> Result<(), EngineError> {
+        let storage_key = format!("{}keys/{}", self.prefix, name);
+        if self
+            .barrier
+            .get(&storage_key)
+            .await
+            .map_err(EngineError::Barrier)?
+            .is_some()
+        {
+            return Err(EngineError::InvalidRequest {
+                reason: format!("key '{name}' already exists"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Encrypt `plaintext` and store it under a new random token.
+    async fn issue_token(
+        &self,
+        key: &TransitKey,
+        plaintext: &[u8],
+        options: TokenizeOptions,
+    ) -> Result<TokenInfo, EngineError> {
+        let ciphertext = encrypt_with_key(key, self.hsm.as_ref(), plaintext, None).await?;
+        let mut bytes = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        let record = TokenRecord {
+            info: TokenInfo {
+                token: format!("{TOKEN_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes)),
+                convergent: options.convergent,
+                metadata: options.metadata,
+                key_version: key.latest_version,
+                created_at: Utc::now(),
+            },
+            ciphertext,
+        };
+        let bytes = serde_json::to_vec(&record).map_err(|e| EngineError::Internal {
+            reason: format!("token serialization failed: {e}"),
+        })?;
+        self.barrier
+            .put(
+                &format!("{}tokens/{}/{}", self.prefix, key.name, record.info.token),
+                &bytes,
+            )
+            .await
+            .map_err(EngineError::Barrier)?;
+        Ok(record.info)
+    }
+
+    async fn load_token(&self, key_name: &str, token: &str) -> Result<TokenRecord, EngineError> {
+        let not_found = || EngineError::NotFound {
+            path: format!("transit/tokens/{key_name}"),
+        };
+        let well_formed = token.strip_prefix(TOKEN_PREFIX).is_some_and(|rest| {
+            !rest.is_empty()
+                && rest
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        });
+        if !well_formed {
+            return Err(not_found());
+        }
+        let data = self
+            .barrier
+            .get(&format!("{}tokens/{key_name}/{token}", self.prefix))
+            .await
+            .map_err(EngineError::Barrier)?
+            .ok_or_else(not_found)?;
+        serde_json::from_slice(&data).map_err(|e| EngineError::Internal {
+            reason: format!("token deserialization failed: {e}"),
+        })
+    }
+
+    /// Storage key of the convergent index entry for `plaintext`.
+    async fn token_index_key(
+        &self,
+        key_name: &str,
+        plaintext: &[u8],
+    ) -> Result<String, EngineError> {
+        let secret = self.load_index_secret().await?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&secret).map_err(|e| EngineError::Internal {
+                reason: format!("invalid token index secret: {e}"),
+            })?;
+        mac.update(key_name.as_bytes());
+        mac.update(&[0]);
+        mac.update(plaintext);
+        let digest = hex::encode(mac.finalize().into_bytes());
+        Ok(format!("{}token-index/{key_name}/{digest}", self.prefix))
+    }
+
+    /// Load the convergent index HMAC secret, generating it on first use.
+    async fn load_index_secret(&self) -> Result<Zeroizing<Vec<u8>>, EngineError> {
+        let storage_key = format!("{}token-index-secret", self.prefix);
+        let _guard = self.wrapping_key_lock.lock().await;
+        if let Some(secret) = self
+            .barrier
+            .get(&storage_key)
+            .await
+            .map_err(EngineError::Barrier)?
+        {
+            return Ok(Zeroizing::new(secret));
+        }
+        let secret = Zeroizing::new(EncryptionKey::generate().as_bytes().to_vec());
+        self.barrier
+            .put(&storage_key, &secret)
+            .await
+            .map_err(EngineError::Barrier)?;
+        Ok(secret)
+    }
+
+    /// Load the import wrapping key, generating it on first use.
+    async fn load_wrapping_key(&self) -> Result<RsaPrivateKey, EngineError> {
+        let storage_key = format!("{}wrapping-key", self.prefix);
+        let _guard = self.wrapping_key_lock.lock().await;
+
+        if let Some(der) = self
+            .barrier
+            .get(&storage_key)
+            .await
+            .map_err(EngineError::Barrier)?
+        {
+            let der = Zeroizing::new(der);
+            return RsaPrivateKey::from_pkcs8_der(&der).map_err(|e| EngineError::Internal {
+                reason: format!("stored wrapping key is invalid: {e}"),
+            });
+        }
+
+        // RSA key generation is CPU-bound and takes a while; keep it off the
+        // async workers.
+        let private = tokio::task::spawn_blocking(|| {
+            RsaPrivateKey::new(&mut rsa::rand_core::OsRng, WRAPPING_KEY_BITS)
+        })
+        .await
+        .map_err(|e| EngineError::Internal {
+            reason: format!("wrapping key generation task failed: {e}"),
+        })?
+        .map_err(|e| EngineError::Internal {
+            reason: format!("wrapping key generation failed: {e}"),
+        })?;
+
+        let der = private.to_pkcs8_der().map_err(|e| EngineError::Internal {
+            reason: format!("failed to encode wrapping key: {e}"),
+        })?;
+        self.barrier
+            .put(&storage_key, der.as_bytes())
+            .await
+            .map_err(EngineError::Barrier)?;
+        Ok(private)
+    }
+
     async fn load_key(&self, name: &str) -> Result<TransitKey, EngineError> {
         let storage_key = format!("{}keys/{}", self.prefix, name);
         let data = self
@@ -367,6 +1063,188 @@ impl TransitEngine {
     }
 }
 
+/// Encrypt with `version` of a loaded key (latest when `None`).
+async fn encrypt_with_key(
+    key: &TransitKey,
+    hsm: Option<&Arc<dyn HsmProvider>>,
+    plaintext: &[u8],
+    version: Option<u32>,
+) -> Result<String, EngineError> {
+    if !key.supports_encryption {
+        return Err(EngineError::InvalidRequest {
+            reason: format!("key '{}' does not support encryption", key.name),
+        });
+    }
+
+    let version = version.unwrap_or(key.latest_version);
+    if version > key.latest_version {
+        return Err(EngineError::InvalidRequest {
+            reason: format!(
+                "version {version} exceeds the latest version {}",
+                key.latest_version
+            ),
+        });
+    }
+    let min_encryption = if key.min_encryption_version == 0 {
+        key.latest_version
+    } else {
+        key.min_encryption_version
+    };
+    if version < min_encryption {
+        return Err(EngineError::InvalidRequest {
+            reason: format!(
+                "version {version} is below minimum encryption version {min_encryption}"
+            ),
+        });
+    }
+
+    let key_version = key
+        .versions
+        .get(&version)
+        .ok_or_else(|| EngineError::Internal {
+            reason: format!("key version {version} missing"),
+        })?;
+
+    let ciphertext = if let Some(label) = &key_version.hsm_label {
+        require_hsm(hsm)?.encrypt(label, plaintext).await?
+    } else {
+        let enc_key = TransitEngine::material_to_key(key_version.key_material.as_bytes())?;
+        crypto::encrypt(&enc_key, plaintext).map_err(|e| EngineError::Internal {
+            reason: format!("encryption failed: {e}"),
+        })?
+    };
+
+    Ok(format!("vault:v{version}:{}", BASE64.encode(&ciphertext)))
+}
+
+/// Decrypt `vault:v{N}:` ciphertext with a loaded key.
+async fn decrypt_with_key(
+    key: &TransitKey,
+    hsm: Option<&Arc<dyn HsmProvider>>,
+    ciphertext: &str,
+) -> Result<Vec<u8>, EngineError> {
+    if !key.supports_decryption {
+        return Err(EngineError::InvalidRequest {
+            reason: format!("key '{}' does not support decryption", key.name),
+        });
+    }
+
+    let (version, raw_ct) = parse_ciphertext(ciphertext)?;
+
+    if version < key.min_decryption_version {
+        return Err(EngineError::InvalidRequest {
+            reason: format!(
+                "ciphertext version {version} is below minimum decryption version {}",
+                key.min_decryption_version
+            ),
+        });
+    }
+
+    let key_version = key
+        .versions
+        .get(&version)
+        .ok_or_else(|| EngineError::NotFound {
+            path: format!("{}/v{version}", key.name),
+        })?;
+
+    if let Some(label) = &key_version.hsm_label {
+        return require_hsm(hsm)?.decrypt(label, &raw_ct).await;
+    }
+
+    let enc_key = TransitEngine::material_to_key(key_version.key_material.as_bytes())?;
+    crypto::decrypt(&enc_key, &raw_ct).map_err(|e| EngineError::Internal {
+        reason: format!("decryption failed: {e}"),
+    })
+}
+
+fn check_batch_size(len: usize) -> Result<(), EngineError> {
+    if len == 0 || len > MAX_BATCH_ITEMS {
+        return Err(EngineError::InvalidRequest {
+            reason: format!("batch must contain between 1 and {MAX_BATCH_ITEMS} items"),
+        });
+    }
+    Ok(())
+}
+
+/// Run `op` over `items` on up to [`BATCH_CONCURRENCY`] tasks, returning
+/// results in input order.
+async fn run_batch<I, T, F, Fut>(items: Vec<I>, op: F) -> Vec<Result<T, EngineError>>
+where
+    T: Send + 'static,
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = Result<T, EngineError>> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(BATCH_CONCURRENCY));
+    let mut results: Vec<Option<Result<T, EngineError>>> = items.iter().map(|_| None).collect();
+    let mut tasks = JoinSet::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let task = op(item);
+        let semaphore = Arc::clone(&semaphore);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (index, task.await)
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined
+            && let Some(slot) = results.get_mut(index)
+        {
+            *slot = Some(result);
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                Err(EngineError::Internal {
+                    reason: "batch item task failed".to_owned(),
+                })
+            })
+        })
+        .collect()
+}
+
+/// The configured HSM, for HSM-backed key versions.
+fn require_hsm(hsm: Option<&Arc<dyn HsmProvider>>) -> Result<&Arc<dyn HsmProvider>, EngineError> {
+    hsm.ok_or_else(|| EngineError::Hsm {
+        reason: "key is HSM-backed but no HSM is configured".to_owned(),
+    })
+}
+
+/// A fresh key record holding `version` as version 1.
+fn new_key(name: &str, version: TransitKeyVersion, options: CreateKeyOptions) -> TransitKey {
+    TransitKey {
+        name: name.to_owned(),
+        versions: HashMap::from([(1, version)]),
+        latest_version: 1,
+        min_decryption_version: 1,
+        min_encryption_version: 0,
+        supports_encryption: true,
+        supports_decryption: true,
+        created_at: Utc::now(),
+        backend: options.backend,
+        exportable: options.exportable,
+    }
+}
+
+/// Public metadata of a key record.
+fn key_info(key: TransitKey) -> TransitKeyInfo {
+    TransitKeyInfo {
+        version_count: u32::try_from(key.versions.len()).unwrap_or(u32::MAX),
+        name: key.name,
+        latest_version: key.latest_version,
+        min_decryption_version: key.min_decryption_version,
+        min_encryption_version: key.min_encryption_version,
+        supports_encryption: key.supports_encryption,
+        supports_decryption: key.supports_decryption,
+        created_at: key.created_at,
+        backend: key.backend,
+        exportable: key.exportable,
+    }
+}
+
 /// Response from `generate_data_key`.
 #[derive(Debug, Serialize)]
 pub struct DataKeyResponse {
@@ -382,10 +1260,13 @@ pub struct TransitKeyInfo {
     pub name: String,
     pub latest_version: u32,
     pub min_decryption_version: u32,
+    pub min_encryption_version: u32,
     pub supports_encryption: bool,
     pub supports_decryption: bool,
     pub version_count: u32,
     pub created_at: DateTime<Utc>,
+    pub backend: KeyBackend,
+    pub exportable: bool,
 }
 
 /// Parse `vault:v{version}:{base64}` ciphertext format.
@@ -418,10 +1299,336 @@ fn parse_ciphertext(ct: &str) -> Result<(u32, Vec<u8>), EngineError> {
     Ok((version, raw))
 }
 
+impl crate::registry::Engine for TransitEngine {
+    fn engine_type(&self) -> &'static str {
+        "transit"
+    }
+
+    fn storage_prefix(&self) -> String {
+        self.prefix.clone()
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
 impl std::fmt::Debug for TransitEngine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TransitEngine")
             .field("prefix", &self.prefix)
+            .field("hsm", &self.hsm.as_ref().map(|h| h.name()))
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use rsa::pkcs8::DecodePublicKey;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+
+    async fn engine() -> TransitEngine {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        TransitEngine::new(barrier, "transit/".to_owned())
+    }
+
+    /// Wrap `key` for import the way client tooling does.
+    fn wrap_for_import(wrapping_key_pem: &str, key: &[u8]) -> String {
+        let public = RsaPublicKey::from_public_key_pem(wrapping_key_pem).unwrap();
+        let ephemeral = EncryptionKey::generate();
+        let mut blob = public
+            .encrypt(
+                &mut rsa::rand_core::OsRng,
+                Oaep::new::<Sha256>(),
+                ephemeral.as_bytes(),
+            )
+            .unwrap();
+        blob.extend(crypto::wrap_key(ephemeral.as_bytes(), key).unwrap());
+        BASE64.encode(blob)
+    }
+
+    #[tokio::test]
+    async fn imported_key_decrypts_external_ciphertext() {
+        let engine = engine().await;
+        let pem = engine.wrapping_key().await.unwrap();
+        assert_eq!(pem, engine.wrapping_key().await.unwrap());
+
+        let key = EncryptionKey::generate();
+        let external = crypto::encrypt(&key, b"byok").unwrap();
+        engine
+            .import_key("byok", &wrap_for_import(&pem, key.as_bytes()), true)
+            .await
+            .unwrap();
+
+        let ct = format!("vault:v1:{}", BASE64.encode(external));
+        assert_eq!(engine.decrypt("byok", &ct).await.unwrap(), b"byok");
+
+        let exported = engine
+            .export_key("byok", EXPORT_ENCRYPTION_KEY, Some("latest"))
+            .await
+            .unwrap();
+        assert_eq!(exported[&1], BASE64.encode(key.as_bytes()));
+
+        let err = engine
+            .import_key("byok", &wrap_for_import(&pem, key.as_bytes()), false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineError::InvalidRequest { .. }));
+    }
+
+    #[tokio::test]
+    async fn import_rejects_bad_key_material() {
+        let engine = engine().await;
+        let pem = engine.wrapping_key().await.unwrap();
+
+        let short = wrap_for_import(&pem, &[7u8; 16]);
+        assert!(engine.import_key("short", &short, false).await.is_err());
+
+        let mut blob = BASE64.decode(wrap_for_import(&pem, &[7u8; 32])).unwrap();
+        let last = blob.len() - 1;
+        blob[last] ^= 1;
+        let tampered = BASE64.encode(blob);
+        assert!(
+            engine
+                .import_key("tampered", &tampered, false)
+                .await
+                .is_err()
+        );
+        assert!(engine.list_keys().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn export_requires_exportable_key() {
+        let engine = engine().await;
+        engine.create_key("locked").await.unwrap();
+        let err = engine
+            .export_key("locked", EXPORT_ENCRYPTION_KEY, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineError::InvalidRequest { .. }));
+
+        let options = CreateKeyOptions {
+            exportable: true,
+            ..CreateKeyOptions::default()
+        };
+        engine
+            .create_key_with_options("open", options)
+            .await
+            .unwrap();
+        engine.rotate_key("open").await.unwrap();
+        let all = engine
+            .export_key("open", EXPORT_ENCRYPTION_KEY, Some("all"))
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(
+            engine
+                .export_key("open", "signing-key", None)
+                .await
+                .is_err()
+        );
+        assert!(engine.key_info("open").await.unwrap().exportable);
+    }
+
+    #[tokio::test]
+    async fn rewrap_and_version_limits() {
+        let engine = engine().await;
+        engine.create_key("k").await.unwrap();
+        let v1 = engine.encrypt("k", b"data").await.unwrap();
+        engine.rotate_key("k").await.unwrap();
+        engine.rotate_key("k").await.unwrap();
+
+        let v3 = engine.rewrap("k", &v1, None).await.unwrap();
+        assert!(v3.starts_with("vault:v3:"));
+        assert_eq!(engine.decrypt("k", &v3).await.unwrap(), b"data");
+
+        // Pinning an old version needs min_encryption_version.
+        assert!(engine.rewrap("k", &v1, Some(2)).await.is_err());
+        let update = KeyConfigUpdate {
+            min_encryption_version: Some(2),
+            ..KeyConfigUpdate::default()
+        };
+        engine.update_key_config("k", update).await.unwrap();
+        let v2 = engine.rewrap("k", &v1, Some(2)).await.unwrap();
+        assert!(v2.starts_with("vault:v2:"));
+        assert!(
+            engine
+                .encrypt_with_version("k", b"data", Some(1))
+                .await
+                .is_err()
+        );
+
+        let update = KeyConfigUpdate {
+            min_decryption_version: Some(3),
+            min_encryption_version: Some(0),
+        };
+        let info = engine.update_key_config("k", update).await.unwrap();
+        assert_eq!(info.min_decryption_version, 3);
+        assert!(engine.decrypt("k", &v1).await.is_err());
+        assert!(engine.rewrap("k", &v2, None).await.is_err());
+        assert_eq!(engine.decrypt("k", &v3).await.unwrap(), b"data");
+
+        for update in [
+            KeyConfigUpdate {
+                min_decryption_version: Some(4),
+                ..KeyConfigUpdate::default()
+            },
+            KeyConfigUpdate {
+                min_encryption_version: Some(2),
+                ..KeyConfigUpdate::default()
+            },
+        ] {
+            let err = engine.update_key_config("k", update).await.unwrap_err();
+            assert!(matches!(err, EngineError::InvalidRequest { .. }));
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_results_are_per_item_and_ordered() {
+        let engine = engine().await;
+        engine.create_key("k").await.unwrap();
+
+        let items: Vec<_> = (0..40u8)
+            .map(|i| BatchEncryptItem {
+                plaintext: vec![i; 8],
+                key_version: (i == 7).then_some(2),
+            })
+            .collect();
+        let encrypted = engine.encrypt_batch("k", items).await.unwrap();
+        assert_eq!(encrypted.len(), 40);
+        assert!(encrypted[7].is_err());
+
+        let mut ciphertexts: Vec<String> = encrypted
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|_| "vault:v1:AAAA".to_owned()))
+            .collect();
+        ciphertexts.push("garbage".to_owned());
+        let decrypted = engine.decrypt_batch("k", ciphertexts).await.unwrap();
+        for (i, result) in decrypted.iter().enumerate() {
+            match i {
+                7 | 40 => assert!(result.is_err()),
+                _ => assert_eq!(result.as_ref().unwrap(), &vec![u8::try_from(i).unwrap(); 8]),
+            }
+        }
+
+        assert!(engine.encrypt_batch("k", Vec::new()).await.is_err());
+        assert!(
+            engine
+                .decrypt_batch("missing", vec!["vault:v1:AAAA".to_owned()])
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn tokenize_round_trips_with_metadata() {
+        let engine = engine().await;
+        engine.create_key("k").await.unwrap();
+
+        let options = TokenizeOptions {
+            convergent: false,
+            metadata: HashMap::from([("field".to_owned(), "ssn".to_owned())]),
+        };
+        let a = engine.tokenize("k", b"123-45-6789", options).await.unwrap();
+        let b = engine
+            .tokenize("k", b"123-45-6789", TokenizeOptions::default())
+            .await
+            .unwrap();
+        assert!(a.token.starts_with(TOKEN_PREFIX));
+        assert_ne!(a.token, b.token);
+
+        let (value, info) = engine.detokenize("k", &a.token).await.unwrap();
+        assert_eq!(value.as_slice(), b"123-45-6789");
+        assert_eq!(info.metadata["field"], "ssn");
+        assert_eq!(
+            engine
+                .lookup_token("k", &b.token)
+                .await
+                .unwrap()
+                .key_version,
+            1
+        );
+
+        // Tokens are scoped to the key that issued them.
+        engine.create_key("other").await.unwrap();
+        assert!(engine.detokenize("other", &a.token).await.is_err());
+        for bad in ["tok_", "tok_../keys/k", "nope"] {
+            let err = engine.lookup_token("k", bad).await.unwrap_err();
+            assert!(matches!(err, EngineError::NotFound { .. }));
+        }
+        // Non-convergent tokens are not indexed by value.
+        assert!(engine.lookup_value("k", b"123-45-6789").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn deleting_a_key_removes_its_tokens() {
+        let engine = engine().await;
+        engine.create_key("k").await.unwrap();
+        engine.create_key("kept").await.unwrap();
+        let convergent = || TokenizeOptions {
+            convergent: true,
+            ..TokenizeOptions::default()
+        };
+        engine.tokenize("k", b"value", convergent()).await.unwrap();
+        let kept = engine
+            .tokenize("kept", b"value", convergent())
+            .await
+            .unwrap();
+
+        engine.delete_key("k").await.unwrap();
+        assert_eq!(engine.list_keys().await.unwrap(), ["kept"]);
+        assert!(matches!(
+            engine.delete_key("k").await.unwrap_err(),
+            EngineError::NotFound { .. }
+        ));
+
+        // A key recreated under the name starts without the old tokens.
+        engine.create_key("k").await.unwrap();
+        assert!(engine.lookup_value("k", b"value").await.is_err());
+        assert_eq!(
+            engine.lookup_value("kept", b"value").await.unwrap().token,
+            kept.token
+        );
+    }
+
+    #[tokio::test]
+    async fn convergent_tokens_are_shared_and_searchable() {
+        let engine = engine().await;
+        engine.create_key("k").await.unwrap();
+        engine.create_key("other").await.unwrap();
+
+        let convergent = || TokenizeOptions {
+            convergent: true,
+            ..TokenizeOptions::default()
+        };
+        let a = engine.tokenize("k", b"value", convergent()).await.unwrap();
+        let b = engine.tokenize("k", b"value", convergent()).await.unwrap();
+        let c = engine
+            .tokenize("k", b"different", convergent())
+            .await
+            .unwrap();
+        let d = engine
+            .tokenize("other", b"value", convergent())
+            .await
+            .unwrap();
+        assert_eq!(a.token, b.token);
+        assert_ne!(a.token, c.token);
+        assert_ne!(a.token, d.token);
+        assert!(a.convergent);
+
+        assert_eq!(
+            engine.lookup_value("k", b"value").await.unwrap().token,
+            a.token
+        );
+        assert!(engine.lookup_value("k", b"unknown").await.is_err());
+
+        // Rotation doesn't break existing tokens.
+        engine.rotate_key("k").await.unwrap();
+        let (value, _) = engine.detokenize("k", &a.token).await.unwrap();
+        assert_eq!(value.as_slice(), b"value");
+    }
+}
@@ -0,0 +1,336 @@
+//! Response wrapping for `ZVault`.
+//!
+//! Instead of returning a response directly, the server can store it in the
+//! cubbyhole of a new single-use wrapping token and return only that token.
+//! Whoever holds the token can unwrap the response exactly once, so a secret
+//! handed to a machine through an intermediary (an `AppRole` secret ID passed
+//! along by a CI system, say) is never visible to the intermediary, and a
+//! stolen token shows up as a failed unwrap for the real recipient.
+//!
+//! Wrapping tokens carry only the [`WRAPPING_POLICY`] pseudo-policy, are not
+//! renewable, and should be accepted by nothing but the `sys/wrapping/*`
+//! endpoints.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::barrier::Barrier;
+use crate::cubbyhole::Cubbyhole;
+use crate::error::{TokenError, WrappingError};
+use crate::token::{CreateTokenParams, TokenEntry, TokenStore, hash_token};
+
+/// Sole policy attached to wrapping tokens.
+pub const WRAPPING_POLICY: &str = "response-wrapping";
+
+/// Longest a response may stay wrapped.
+pub const MAX_WRAP_TTL_SECS: i64 = 30 * 24 * 3600;
+
+/// Cubbyhole key holding the wrapped response.
+const RESPONSE_KEY: &str = "response";
+
+/// A wrapped response as stored in the wrapping token's cubbyhole.
+#[derive(Debug, Serialize, Deserialize)]
+struct WrappedResponse {
+    creation_path: String,
+    creation_time: DateTime<Utc>,
+    ttl_secs: i64,
+    response: serde_json::Value,
+}
+
+/// Returned in place of a wrapped response.
+#[derive(Debug, Clone, Serialize)]
+pub struct WrapInfo {
+    /// The single-use wrapping token.
+    pub token: String,
+    /// Seconds until the wrapping token expires.
+    pub ttl: i64,
+    /// When the response was wrapped.
+    pub creation_time: DateTime<Utc>,
+    /// Request path that produced the response.
+    pub creation_path: String,
+}
+
+/// Metadata about a wrapping token, readable without unwrapping it.
+#[derive(Debug, Clone, Serialize)]
+pub struct WrapLookup {
+    /// TTL the response was wrapped with, in seconds.
+    pub creation_ttl: i64,
+    /// When the response was wrapped.
+    pub creation_time: DateTime<Utc>,
+    /// Request path that produced the response.
+    pub creation_path: String,
+}
+
+/// Whether `entry` is a wrapping token.
+#[must_use]
+pub fn is_wrapping_token(entry: &TokenEntry) -> bool {
+    matches!(entry.policies.as_slice(), [policy] if policy == WRAPPING_POLICY)
+}
+
+/// Wraps responses into single-use tokens and unwraps them.
+pub struct ResponseWrapper {
+    cubbyhole: Cubbyhole,
+    /// Serializes unwrap and rewrap so a token is consumed at most once.
+    consume: Mutex<()>,
+}
+
+impl ResponseWrapper {
+    /// Create a response wrapper backed by the given barrier.
+    #[must_use]
+    pub fn new(barrier: Arc<Barrier>) -> Self {
+        Self {
+            cubbyhole: Cubbyhole::new(barrier),
+            consume: Mutex::new(()),
+        }
+    }
+
+    /// Wrap `response` into a new token that expires after `ttl`.
+    ///
+    /// # Errors
+    ///
+    /// - [`WrappingError::InvalidTtl`] if `ttl` is not positive or exceeds
+    ///   [`MAX_WRAP_TTL_SECS`].
+    /// - [`WrappingError::Token`] or [`WrappingError::Barrier`] if storing
+    ///   the token or response fails.
+    pub async fn wrap(
+        &self,
+        tokens: &TokenStore,
+        response: serde_json::Value,
+        ttl: Duration,
+        creation_path: &str,
+    ) -> Result<WrapInfo, WrappingError> {
+        let ttl_secs = ttl.num_seconds();
+        if ttl_secs <= 0 || ttl_secs > MAX_WRAP_TTL_SECS {
+            return Err(WrappingError::InvalidTtl {
+                reason: format!("must be between 1 and {MAX_WRAP_TTL_SECS} seconds"),
+            });
+        }
+
+        let token = tokens
+            .create(CreateTokenParams {
+                policies: vec![WRAPPING_POLICY.to_owned()],
+                ttl: Some(ttl),
+                max_ttl: Some(ttl),
+                renewable: false,
+                parent_hash: None,
+                metadata: std::collections::HashMap::new(),
+                display_name: WRAPPING_POLICY.to_owned(),
+                bound_cidrs: Vec::new(),
+            })
+            .await?;
+
+        let wrapped = WrappedResponse {
+            creation_path: creation_path.to_owned(),
+            creation_time: Utc::now(),
+            ttl_secs,
+            response,
+        };
+        let data = serde_json::to_vec(&wrapped).map_err(|e| WrappingError::Internal {
+            reason: format!("serialization failed: {e}"),
+        })?;
+        if let Err(e) = self
+            .cubbyhole
+            .put(&hash_token(&token), RESPONSE_KEY, &data)
+            .await
+        {
+            let _ = tokens.revoke(&token).await;
+            return Err(e.into());
+        }
+
+        Ok(WrapInfo {
+            token,
+            ttl: ttl_secs,
+            creation_time: wrapped.creation_time,
+            creation_path: wrapped.creation_path,
+        })
+    }
+
+    /// Read a wrapping token's metadata without consuming it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WrappingError::InvalidToken`] if `token` is not a live
+    /// wrapping token.
+    pub async fn lookup(
+        &self,
+        tokens: &TokenStore,
+        token: &str,
+    ) -> Result<WrapLookup, WrappingError> {
+        let wrapped = self.load(tokens, token).await?;
+        Ok(WrapLookup {
+            creation_ttl: wrapped.ttl_secs,
+            creation_time: wrapped.creation_time,
+            creation_path: wrapped.creation_path,
+        })
+    }
+
+    /// Return the wrapped response and revoke the token.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WrappingError::InvalidToken`] if `token` is not a live
+    /// wrapping token, including one that was already unwrapped.
+    pub async fn unwrap(
+        &self,
+        tokens: &TokenStore,
+        token: &str,
+    ) -> Result<serde_json::Value, WrappingError> {
+        let _guard = self.consume.lock().await;
+        let wrapped = self.load(tokens, token).await?;
+        tokens.revoke(token).await?;
+        Ok(wrapped.response)
+    }
+
+    /// Move the wrapped response to a new token with the original TTL,
+    /// revoking the old one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WrappingError::InvalidToken`] if `token` is not a live
+    /// wrapping token.
+    pub async fn rewrap(
+        &self,
+        tokens: &TokenStore,
+        token: &str,
+    ) -> Result<WrapInfo, WrappingError> {
+        let _guard = self.consume.lock().await;
+        let wrapped = self.load(tokens, token).await?;
+        let info = self
+            .wrap(
+                tokens,
+                wrapped.response,
+                Duration::seconds(wrapped.ttl_secs),
+                &wrapped.creation_path,
+            )
+            .await?;
+        tokens.revoke(token).await?;
+        Ok(info)
+    }
+
+    /// Load the response wrapped by a live wrapping token.
+    async fn load(
+        &self,
+        tokens: &TokenStore,
+        token: &str,
+    ) -> Result<WrappedResponse, WrappingError> {
+        let entry = match tokens.lookup(token).await {
+            Ok(entry) => entry,
+            Err(TokenError::NotFound) => return Err(WrappingError::InvalidToken),
+            Err(TokenError::Expired { .. }) => {
+                // Expired tokens are never used again; drop the response now.
+                tokens.revoke(token).await?;
+                return Err(WrappingError::InvalidToken);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !is_wrapping_token(&entry) {
+            return Err(WrappingError::InvalidToken);
+        }
+
+        let data = self
+            .cubbyhole
+            .get(&entry.token_hash, RESPONSE_KEY)
+            .await?
+            .ok_or(WrappingError::InvalidToken)?;
+        serde_json::from_slice(&data).map_err(|e| WrappingError::Internal {
+            reason: format!("corrupt wrapped response: {e}"),
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use serde_json::json;
+    use zvault_storage::MemoryBackend;
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+
+    async fn setup() -> (ResponseWrapper, TokenStore) {
+        let barrier = Arc::new(Barrier::new(Arc::new(MemoryBackend::new())));
+        barrier.unseal(EncryptionKey::generate()).await.unwrap();
+        (
+            ResponseWrapper::new(Arc::clone(&barrier)),
+            TokenStore::new(barrier),
+        )
+    }
+
+    #[tokio::test]
+    async fn unwraps_exactly_once() {
+        let (wrapper, tokens) = setup().await;
+        let info = wrapper
+            .wrap(
+                &tokens,
+                json!({"secret_id": "s3cret"}),
+                Duration::minutes(5),
+                "auth/approle/role/ci/secret-id",
+            )
+            .await
+            .unwrap();
+        assert_eq!(info.ttl, 300);
+
+        let lookup = wrapper.lookup(&tokens, &info.token).await.unwrap();
+        assert_eq!(lookup.creation_path, "auth/approle/role/ci/secret-id");
+
+        let response = wrapper.unwrap(&tokens, &info.token).await.unwrap();
+        assert_eq!(response["secret_id"], "s3cret");
+        assert!(matches!(
+            wrapper.unwrap(&tokens, &info.token).await,
+            Err(WrappingError::InvalidToken)
+        ));
+        assert!(tokens.lookup(&info.token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rewrap_invalidates_old_token() {
+        let (wrapper, tokens) = setup().await;
+        let old = wrapper
+            .wrap(
+                &tokens,
+                json!({"n": 1}),
+                Duration::hours(1),
+                "secret/data/x",
+            )
+            .await
+            .unwrap();
+
+        let new = wrapper.rewrap(&tokens, &old.token).await.unwrap();
+        assert_ne!(new.token, old.token);
+        assert_eq!(new.ttl, 3600);
+        assert!(wrapper.lookup(&tokens, &old.token).await.is_err());
+        assert_eq!(wrapper.unwrap(&tokens, &new.token).await.unwrap()["n"], 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_non_wrapping_tokens_and_bad_ttls() {
+        let (wrapper, tokens) = setup().await;
+        let token = tokens
+            .create(CreateTokenParams {
+                policies: vec!["default".to_owned()],
+                ttl: None,
+                max_ttl: None,
+                renewable: false,
+                parent_hash: None,
+                metadata: std::collections::HashMap::new(),
+                display_name: "test".to_owned(),
+                bound_cidrs: Vec::new(),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(
+            wrapper.unwrap(&tokens, &token).await,
+            Err(WrappingError::InvalidToken)
+        ));
+
+        for ttl in [Duration::zero(), Duration::seconds(MAX_WRAP_TTL_SECS + 1)] {
+            assert!(matches!(
+                wrapper.wrap(&tokens, json!({}), ttl, "x").await,
+                Err(WrappingError::InvalidTtl { .. })
+            ));
+        }
+    }
+}
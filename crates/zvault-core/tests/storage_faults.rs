@@ -0,0 +1,182 @@
+//! Chaos tests: the barrier, KV engine, and lease expiry under storage faults.
+//!
+//! Each test runs against a [`FaultInjectingBackend`] with a fixed seed, so
+//! failures replay deterministically.
+
+#![allow(clippy::unwrap_used)]
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use zvault_core::barrier::Barrier;
+use zvault_core::crypto::EncryptionKey;
+use zvault_core::engine::{EngineRequest, KvEngine, Operation};
+use zvault_core::error::{BarrierError, EngineError};
+use zvault_core::lease::{Lease, LeaseManager};
+use zvault_storage::{FaultInjectingBackend, FaultPlan, MemoryBackend, StorageBackend};
+
+async fn faulty_barrier(
+    plan: FaultPlan,
+) -> (Arc<Barrier>, Arc<FaultInjectingBackend<MemoryBackend>>) {
+    let storage = Arc::new(FaultInjectingBackend::new(MemoryBackend::new(), plan));
+    storage.set_enabled(false);
+    let barrier = Arc::new(Barrier::new(Arc::clone(&storage) as Arc<dyn StorageBackend>));
+    barrier.unseal(EncryptionKey::generate()).await.unwrap();
+    (barrier, storage)
+}
+
+fn write(path: &str, value: u32) -> EngineRequest {
+    EngineRequest {
+        operation: Operation::Write,
+        path: path.to_owned(),
+        data: Some(serde_json::json!({ "value": value })),
+    }
+}
+
+fn read(path: &str) -> EngineRequest {
+    EngineRequest {
+        operation: Operation::Read,
+        path: path.to_owned(),
+        data: None,
+    }
+}
+
+fn read_value(resp: &zvault_core::engine::EngineResponse) -> u64 {
+    resp.data.as_ref().unwrap()["data"]["value"]
+        .as_u64()
+        .unwrap()
+}
+
+// ── Barrier ──────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn barrier_write_fault_keeps_previous_value() {
+    let (barrier, storage) = faulty_barrier(FaultPlan::new(1).with_write_errors(1.0)).await;
+    barrier.put("sys/config", b"v1").await.unwrap();
+
+    storage.set_enabled(true);
+    let err = barrier.put("sys/config", b"v2").await.unwrap_err();
+    assert!(matches!(err, BarrierError::Storage(_)));
+
+    storage.set_enabled(false);
+    assert_eq!(
+        barrier.get("sys/config").await.unwrap(),
+        Some(b"v1".to_vec())
+    );
+}
+
+#[tokio::test]
+async fn barrier_rejects_torn_writes_instead_of_returning_garbage() {
+    let (barrier, storage) = faulty_barrier(FaultPlan::new(2).with_torn_writes(1.0)).await;
+
+    storage.set_enabled(true);
+    assert!(barrier.put("sys/config", b"payload").await.is_err());
+    assert_eq!(storage.stats().torn_writes, 1);
+
+    storage.set_enabled(false);
+    let err = barrier.get("sys/config").await.unwrap_err();
+    assert!(matches!(err, BarrierError::Crypto(_)));
+}
+
+// ── KV engine ────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn kv_write_fault_keeps_last_version() {
+    let (barrier, storage) =
+        faulty_barrier(FaultPlan::new(3).with_write_errors(1.0).only_prefix("kv/")).await;
+    let engine = KvEngine::new(barrier, "kv/secret/".to_owned());
+    engine.handle(&write("app", 1)).await.unwrap();
+
+    storage.set_enabled(true);
+    let err = engine.handle(&write("app", 2)).await.unwrap_err();
+    assert!(matches!(
+        err,
+        EngineError::Barrier(BarrierError::Storage(_))
+    ));
+
+    storage.set_enabled(false);
+    let resp = engine.handle(&read("app")).await.unwrap();
+    assert_eq!(read_value(&resp), 1);
+}
+
+#[tokio::test]
+async fn kv_never_returns_unacknowledged_data_under_random_faults() {
+    let plan = FaultPlan::new(0x5EED)
+        .with_read_errors(0.2)
+        .with_write_errors(0.2)
+        .with_torn_writes(0.05)
+        .with_latency(
+            std::time::Duration::ZERO,
+            std::time::Duration::from_micros(200),
+        )
+        .only_prefix("kv/");
+    let (barrier, storage) = faulty_barrier(plan).await;
+    let engine = KvEngine::new(barrier, "kv/secret/".to_owned());
+    storage.set_enabled(true);
+
+    let mut acknowledged: Option<u64> = None;
+    for i in 0..200 {
+        if engine.handle(&write("app", i)).await.is_ok() {
+            acknowledged = Some(u64::from(i));
+        }
+        // A read may fail, but must never surface anything other than the
+        // last acknowledged write.
+        if let Ok(resp) = engine.handle(&read("app")).await {
+            assert_eq!(Some(read_value(&resp)), acknowledged);
+        }
+    }
+
+    let stats = storage.stats();
+    assert!(stats.errors > 0);
+    assert!(stats.delays > 0);
+}
+
+// ── Lease expiry ─────────────────────────────────────────────────────
+
+fn expired_lease(id: &str) -> Lease {
+    Lease {
+        id: id.to_owned(),
+        engine_path: "database/creds/ro".to_owned(),
+        issued_at: Utc::now() - Duration::hours(2),
+        ttl_secs: 60,
+        max_ttl_secs: 0,
+        renewable: true,
+        data: serde_json::json!({}),
+        token_hash: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn lease_expiry_scan_reports_list_faults() {
+    let (barrier, storage) = faulty_barrier(FaultPlan::new(4).with_list_errors(1.0)).await;
+    let leases = LeaseManager::new(barrier);
+    leases.create(&expired_lease("l1")).await.unwrap();
+
+    storage.set_enabled(true);
+    assert!(leases.find_expired().await.is_err());
+
+    storage.set_enabled(false);
+    assert_eq!(leases.find_expired().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn failed_lease_revocation_is_retried_after_recovery() {
+    let (barrier, storage) = faulty_barrier(FaultPlan::new(5).with_delete_errors(1.0)).await;
+    let leases = LeaseManager::new(barrier);
+    leases.create(&expired_lease("l1")).await.unwrap();
+    leases.create(&expired_lease("l2")).await.unwrap();
+
+    storage.set_enabled(true);
+    for lease in leases.find_expired().await.unwrap() {
+        assert!(leases.revoke(&lease.id).await.is_err());
+    }
+
+    // Nothing was lost: the next scan still sees both leases and cleans up.
+    storage.set_enabled(false);
+    let expired = leases.find_expired().await.unwrap();
+    assert_eq!(expired.len(), 2);
+    for lease in expired {
+        leases.revoke(&lease.id).await.unwrap();
+    }
+    assert!(leases.find_expired().await.unwrap().is_empty());
+}
@@ -0,0 +1,441 @@
+//! Scheduled backups.
+//!
+//! With `ZVAULT_BACKUP_INTERVAL` set, a background worker writes a backup
+//! file every interval to a local directory or an S3-compatible bucket.
+//! Each file holds the same JSON as `GET /v1/sys/backup` — a point-in-time
+//! snapshot of barrier ciphertext — so `zvault restore <file>` restores it.
+//!
+//! After every successful backup, older files are pruned: the newest backup
+//! of each of the last `keep_daily` days and of each of the last
+//! `keep_weekly` ISO weeks is kept, everything else is deleted. Files whose
+//! names the scheduler did not generate are never touched.
+//!
+//! The outcome of the last run is exposed at `GET /v1/sys/backup/status`,
+//! to tokens with `read` on `sys/backup`.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use zvault_core::barrier::Barrier;
+
+use crate::config::{BackupConfig, BackupDestination};
+use crate::error::AppError;
+use crate::routes::sys::create_backup;
+
+/// Backup file names are `<prefix><timestamp><suffix>`.
+const FILE_PREFIX: &str = "zvault-backup-";
+const FILE_SUFFIX: &str = ".json";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Outcome of scheduled backups, for `GET /v1/sys/backup/status`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackupStatus {
+    /// Whether scheduled backups are configured.
+    pub enabled: bool,
+    /// Seconds between backups.
+    pub interval_secs: Option<u64>,
+    /// Where backups are written.
+    pub destination: Option<String>,
+    /// Days a daily backup is kept for.
+    pub keep_daily: Option<usize>,
+    /// Weeks a weekly backup is kept for.
+    pub keep_weekly: Option<usize>,
+    /// When the last backup was attempted.
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    /// When the last backup succeeded.
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// File written by the last successful backup.
+    pub last_file: Option<String>,
+    /// Entries in the last successful backup.
+    pub last_entry_count: Option<usize>,
+    /// Error of the last attempt, if it failed.
+    pub last_error: Option<String>,
+    /// Attempts that failed since the last success.
+    pub consecutive_failures: u32,
+    /// Backups kept after the last pruning.
+    pub retained: Option<usize>,
+}
+
+/// Where backup files go.
+enum Destination {
+    Dir(PathBuf),
+    #[cfg(feature = "s3-backend")]
+    S3 {
+        bucket: String,
+        backend: Box<zvault_storage::S3Backend>,
+    },
+}
+
+impl Destination {
+    fn open(config: &BackupDestination) -> anyhow::Result<Self> {
+        match config {
+            BackupDestination::Dir { path } => Ok(Self::Dir(PathBuf::from(path))),
+            #[cfg(feature = "s3-backend")]
+            BackupDestination::S3 {
+                bucket,
+                region,
+                endpoint,
+                prefix,
+                path_style,
+            } => {
+                let mut s3_config = zvault_storage::S3Config::new(
+                    bucket,
+                    region,
+                    std::env::var("AWS_ACCESS_KEY_ID").context("AWS_ACCESS_KEY_ID is not set")?,
+                    std::env::var("AWS_SECRET_ACCESS_KEY")
+                        .context("AWS_SECRET_ACCESS_KEY is not set")?,
+                );
+                if let Some(endpoint) = endpoint {
+                    s3_config.endpoint.clone_from(endpoint);
+                }
+                s3_config.prefix.clone_from(prefix);
+                s3_config.path_style = *path_style;
+                let backend = zvault_storage::S3Backend::new(s3_config)
+                    .context("failed to configure backup bucket")?;
+                Ok(Self::S3 {
+                    bucket: format!("s3://{bucket}/{prefix}"),
+                    backend: Box::new(backend),
+                })
+            }
+            #[cfg(not(feature = "s3-backend"))]
+            BackupDestination::S3 { .. } => {
+                anyhow::bail!("S3 backup destination requires feature 's3-backend'")
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Dir(path) => path.display().to_string(),
+            #[cfg(feature = "s3-backend")]
+            Self::S3 { bucket, .. } => bucket.clone(),
+        }
+    }
+
+    async fn write(&self, name: &str, data: &[u8]) -> anyhow::Result<()> {
+        match self {
+            Self::Dir(dir) => {
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .with_context(|| format!("failed to create {}", dir.display()))?;
+                // Write then rename, so a crash never leaves a truncated file
+                // with a valid backup name.
+                let tmp = dir.join(format!(".{name}.tmp"));
+                tokio::fs::write(&tmp, data)
+                    .await
+                    .with_context(|| format!("failed to write {}", tmp.display()))?;
+                tokio::fs::rename(&tmp, dir.join(name))
+                    .await
+                    .with_context(|| format!("failed to move backup into {}", dir.display()))
+            }
+            #[cfg(feature = "s3-backend")]
+            Self::S3 { backend, .. } => {
+                use zvault_storage::StorageBackend;
+                backend.put(name, data).await.map_err(Into::into)
+            }
+        }
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<String>> {
+        match self {
+            Self::Dir(dir) => {
+                let mut names = Vec::new();
+                let mut entries = tokio::fs::read_dir(dir)
+                    .await
+                    .with_context(|| format!("failed to read {}", dir.display()))?;
+                while let Some(entry) = entries.next_entry().await? {
+                    if let Some(name) = entry.file_name().to_str() {
+                        names.push(name.to_owned());
+                    }
+                }
+                Ok(names)
+            }
+            #[cfg(feature = "s3-backend")]
+            Self::S3 { backend, .. } => {
+                use zvault_storage::StorageBackend;
+                backend.list(FILE_PREFIX).await.map_err(Into::into)
+            }
+        }
+    }
+
+    async fn remove(&self, name: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Dir(dir) => tokio::fs::remove_file(dir.join(name))
+                .await
+                .with_context(|| format!("failed to delete {name}")),
+            #[cfg(feature = "s3-backend")]
+            Self::S3 { backend, .. } => {
+                use zvault_storage::StorageBackend;
+                backend.delete(name).await.map_err(Into::into)
+            }
+        }
+    }
+}
+
+/// Writes backups on a schedule and prunes old ones.
+pub struct BackupScheduler {
+    destination: Destination,
+    interval_secs: u64,
+    keep_daily: usize,
+    keep_weekly: usize,
+    status: RwLock<BackupStatus>,
+}
+
+impl std::fmt::Debug for BackupScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackupScheduler")
+            .field("destination", &self.destination.describe())
+            .field("interval_secs", &self.interval_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BackupScheduler {
+    /// Create a scheduler for `config`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the destination cannot be configured (e.g. S3 credentials
+    /// are missing).
+    pub fn new(config: &BackupConfig) -> anyhow::Result<Self> {
+        let destination = Destination::open(&config.destination)?;
+        let status = BackupStatus {
+            enabled: true,
+            interval_secs: Some(config.interval_secs),
+            destination: Some(destination.describe()),
+            keep_daily: Some(config.keep_daily),
+            keep_weekly: Some(config.keep_weekly),
+            ..BackupStatus::default()
+        };
+        Ok(Self {
+            destination,
+            interval_secs: config.interval_secs,
+            keep_daily: config.keep_daily,
+            keep_weekly: config.keep_weekly,
+            status: RwLock::new(status),
+        })
+    }
+
+    /// Seconds between backups.
+    #[must_use]
+    pub fn interval_secs(&self) -> u64 {
+        self.interval_secs
+    }
+
+    /// Current status.
+    pub async fn status(&self) -> BackupStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Take one backup and prune old ones, recording the outcome. Skipped
+    /// while the vault is sealed.
+    pub async fn run(&self, barrier: &Barrier) {
+        let now = Utc::now();
+        let backup = match create_backup(barrier).await {
+            Ok(backup) => backup,
+            Err(AppError::Sealed) => return,
+            Err(AppError::Internal(reason)) => {
+                self.record_failure(now, reason).await;
+                return;
+            }
+            Err(e) => {
+                self.record_failure(now, format!("{e:?}")).await;
+                return;
+            }
+        };
+
+        let name = file_name(now);
+        let written = match serde_json::to_vec_pretty(&backup) {
+            Ok(data) => self.destination.write(&name, &data).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = written {
+            self.record_failure(now, format!("{e:#}")).await;
+            return;
+        }
+        info!(file = %name, entries = backup.entry_count, "scheduled backup written");
+
+        let retained = match self.prune().await {
+            Ok(retained) => Some(retained),
+            Err(e) => {
+                warn!(error = %format!("{e:#}"), "failed to prune old backups");
+                None
+            }
+        };
+
+        let mut status = self.status.write().await;
+        status.last_attempt_at = Some(now);
+        status.last_success_at = Some(now);
+        status.last_file = Some(name);
+        status.last_entry_count = Some(backup.entry_count);
+        status.last_error = None;
+        status.consecutive_failures = 0;
+        if retained.is_some() {
+            status.retained = retained;
+        }
+    }
+
+    async fn record_failure(&self, at: DateTime<Utc>, error: String) {
+        warn!(error = %error, "scheduled backup failed");
+        let mut status = self.status.write().await;
+        status.last_attempt_at = Some(at);
+        status.last_error = Some(error);
+        status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+    }
+
+    /// Delete backups outside the retention window. Returns how many remain.
+    async fn prune(&self) -> anyhow::Result<usize> {
+        let backups: Vec<(String, DateTime<Utc>)> = self
+            .destination
+            .list()
+            .await?
+            .into_iter()
+            .filter_map(|name| parse_file_name(&name).map(|at| (name, at)))
+            .collect();
+        let expired = expired_backups(&backups, self.keep_daily, self.keep_weekly);
+        for name in &expired {
+            self.destination.remove(name).await?;
+            info!(file = %name, "pruned old backup");
+        }
+        Ok(backups.len() - expired.len())
+    }
+}
+
+fn file_name(at: DateTime<Utc>) -> String {
+    format!("{FILE_PREFIX}{}{FILE_SUFFIX}", at.format(TIMESTAMP_FORMAT))
+}
+
+fn parse_file_name(name: &str) -> Option<DateTime<Utc>> {
+    let timestamp = name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?;
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Backups to delete: all but the newest of each of the `keep_daily` most
+/// recent days and of each of the `keep_weekly` most recent ISO weeks.
+fn expired_backups(
+    backups: &[(String, DateTime<Utc>)],
+    keep_daily: usize,
+    keep_weekly: usize,
+) -> Vec<String> {
+    let mut newest_first: Vec<&(String, DateTime<Utc>)> = backups.iter().collect();
+    newest_first.sort_by_key(|(_, at)| std::cmp::Reverse(*at));
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    let mut expired = Vec::new();
+    for (name, at) in newest_first {
+        let week = at.iso_week();
+        let daily = days.len() < keep_daily && days.insert(at.date_naive());
+        let weekly = weeks.len() < keep_weekly && weeks.insert((week.year(), week.week()));
+        if !daily && !weekly {
+            expired.push(name.clone());
+        }
+    }
+    expired
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        timestamp.parse().unwrap()
+    }
+
+    fn backups(timestamps: &[&str]) -> Vec<(String, DateTime<Utc>)> {
+        timestamps
+            .iter()
+            .map(|t| (file_name(at(t)), at(t)))
+            .collect()
+    }
+
+    #[test]
+    fn keeping_one_day_keeps_only_the_newest_backup() {
+        let all = backups(&[
+            "2025-03-10T08:00:00Z",
+            "2025-03-10T20:00:00Z",
+            "2025-03-11T08:00:00Z",
+            "2025-03-12T08:00:00Z",
+        ]);
+        let mut expired = expired_backups(&all, 1, 0);
+        expired.sort();
+        assert_eq!(
+            expired,
+            [&all[0].0, &all[1].0, &all[2].0].map(String::clone)
+        );
+    }
+
+    #[test]
+    fn fewer_backups_than_retained_are_all_kept() {
+        let all = backups(&["2025-03-10T08:00:00Z", "2025-03-11T08:00:00Z"]);
+        assert!(expired_backups(&all, 7, 4).is_empty());
+        assert!(expired_backups(&[], 7, 4).is_empty());
+    }
+
+    #[test]
+    fn input_order_does_not_matter() {
+        let sorted = backups(&[
+            "2025-02-20T08:00:00Z",
+            "2025-03-01T08:00:00Z",
+            "2025-03-10T08:00:00Z",
+            "2025-03-10T20:00:00Z",
+            "2025-03-11T08:00:00Z",
+        ]);
+        let mut shuffled = sorted.clone();
+        shuffled.swap(0, 3);
+        shuffled.swap(1, 4);
+        shuffled.reverse();
+
+        let mut expected = expired_backups(&sorted, 2, 2);
+        let mut actual = expired_backups(&shuffled, 2, 2);
+        expected.sort();
+        actual.sort();
+        // 03-11 and 03-10 20:00 are the daily ones; 03-01 is the newest of
+        // the second week.
+        assert_eq!(expected, [&sorted[0].0, &sorted[2].0].map(String::clone));
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn pruning_leaves_foreign_files_alone() {
+        let dir = std::env::temp_dir().join(format!("zvault-backups-{}", uuid::Uuid::new_v4()));
+        let scheduler = BackupScheduler::new(&BackupConfig {
+            interval_secs: 3600,
+            destination: BackupDestination::Dir {
+                path: dir.to_string_lossy().into_owned(),
+            },
+            keep_daily: 1,
+            keep_weekly: 0,
+        })
+        .unwrap();
+        let old = file_name(at("2025-03-10T08:00:00Z"));
+        let new = file_name(at("2025-03-11T08:00:00Z"));
+        let foreign = [
+            "notes.txt",
+            "zvault-backup-latest.json",
+            "zvault-backup-20250301T080000Z.json.bak",
+        ];
+        for name in [old.as_str(), new.as_str()].iter().chain(&foreign) {
+            scheduler.destination.write(name, b"{}").await.unwrap();
+        }
+
+        let retained = scheduler.prune().await.unwrap();
+
+        let mut left = scheduler.destination.list().await.unwrap();
+        left.sort();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(retained, 1);
+        let mut expected: Vec<String> = foreign.iter().map(|&n| n.to_owned()).collect();
+        expected.push(new);
+        expected.sort();
+        assert_eq!(left, expected);
+    }
+}
@@ -0,0 +1,25 @@
+//! Build metadata embedded at compile time by `build.rs`.
+
+/// Crate version (`CARGO_PKG_VERSION`).
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short git revision the binary was built from, or `unknown`.
+pub const GIT_SHA: &str = env!("ZVAULT_GIT_SHA");
+
+/// Target triple (e.g. `x86_64-unknown-linux-musl`).
+pub const TARGET: &str = env!("ZVAULT_BUILD_TARGET");
+
+/// Cargo profile (`release` or `debug`).
+pub const PROFILE: &str = env!("ZVAULT_BUILD_PROFILE");
+
+/// `--version` output: `0.2.0 (abc123def456 x86_64-unknown-linux-gnu release)`.
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("ZVAULT_GIT_SHA"),
+    " ",
+    env!("ZVAULT_BUILD_TARGET"),
+    " ",
+    env!("ZVAULT_BUILD_PROFILE"),
+    ")"
+);
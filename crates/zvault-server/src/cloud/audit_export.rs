@@ -0,0 +1,327 @@
+//! Cloud audit log retention and scheduled export.
+//!
+//! A background worker calls [`run`] every minute. Each run deletes
+//! audit entries older than the org tier's retention (or an export
+//! config's shorter `retention_days`), then delivers new entries for every
+//! export config whose interval has elapsed.
+//!
+//! Exports are newline-delimited JSON, one [`AuditEntry`] per line, in
+//! batches of [`BATCH_SIZE`]. A batch goes to an S3-compatible bucket as
+//! `<prefix><org_id>/<yyyy>/<mm>/<dd>/<time>-<last entry id>.ndjson`, or is
+//! `POST`ed to an HTTPS endpoint with the configured headers. Each org's
+//! export position only advances after a batch is delivered, and entries
+//! past it are never pruned, so a failing destination delays deletion
+//! rather than losing entries.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::error::CloudError;
+use super::kms;
+use super::models::{AuditEntry, AuditExportConfig, Tier};
+use super::repository;
+use super::routes::secrets::decrypt_secret;
+
+/// Seconds between retention and export runs.
+pub const RUN_INTERVAL_SECS: u64 = 60;
+/// Entries per exported object or request.
+pub const BATCH_SIZE: i64 = 1000;
+/// Batches one export run delivers per org; the rest wait for the next run.
+const MAX_BATCHES_PER_RUN: usize = 100;
+/// Timeout for a single HTTPS push.
+const PUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where an org's audit log is exported, without credentials.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum ExportDestination {
+    /// An S3-compatible bucket.
+    S3 {
+        bucket: String,
+        region: String,
+        /// Service endpoint for non-AWS buckets (e.g. `MinIO`, R2).
+        #[serde(default)]
+        endpoint: Option<String>,
+        /// Prefix for object keys, e.g. `zvault-audit/`.
+        #[serde(default)]
+        prefix: String,
+        /// Address the bucket as `<endpoint>/<bucket>`.
+        #[serde(default)]
+        path_style: bool,
+    },
+    /// An HTTP(S) endpoint receiving `POST`ed NDJSON, e.g. a SIEM collector.
+    Https { url: String },
+}
+
+/// Secrets for an export destination, stored encrypted with the org key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportCredentials {
+    /// S3 access key ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+    /// S3 secret access key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    /// Extra HTTPS request headers (e.g., an authorization header for the SIEM).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Check that a destination and its credentials can be exported to.
+///
+/// # Errors
+///
+/// Returns `CloudError::BadRequest` describing the first problem found.
+pub fn validate(
+    destination: &ExportDestination,
+    credentials: &ExportCredentials,
+) -> Result<(), CloudError> {
+    match destination {
+        ExportDestination::S3 { bucket, region, .. } => {
+            if !cfg!(feature = "s3-backend") {
+                return Err(CloudError::BadRequest(
+                    "S3 audit export is not available on this server".to_owned(),
+                ));
+            }
+            if bucket.is_empty() || region.is_empty() {
+                return Err(CloudError::BadRequest(
+                    "S3 export requires bucket and region".to_owned(),
+                ));
+            }
+            if credentials.access_key_id.is_none() || credentials.secret_access_key.is_none() {
+                return Err(CloudError::BadRequest(
+                    "S3 export requires access_key_id and secret_access_key credentials".to_owned(),
+                ));
+            }
+        }
+        ExportDestination::Https { url } => {
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| CloudError::BadRequest(format!("invalid export url '{url}': {e}")))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(CloudError::BadRequest(format!(
+                    "export url must be http or https, got '{}'",
+                    parsed.scheme()
+                )));
+            }
+            for (name, value) in &credentials.headers {
+                if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err()
+                    || reqwest::header::HeaderValue::from_str(value).is_err()
+                {
+                    return Err(CloudError::BadRequest(format!(
+                        "invalid export header '{name}'"
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prune expired audit entries, then run every due export.
+pub async fn run(pool: &PgPool) {
+    prune(pool).await;
+
+    let configs = match repository::claim_due_audit_exports(pool).await {
+        Ok(configs) => configs,
+        Err(e) => {
+            warn!(error = %e, "failed to list due audit exports");
+            return;
+        }
+    };
+    for config in configs {
+        let result = export(pool, &config).await;
+        let error = result.as_ref().err().map(ToString::to_string);
+        match &result {
+            Ok(0) => {}
+            Ok(exported) => info!(org_id = %config.org_id, exported, "exported audit entries"),
+            Err(e) => warn!(org_id = %config.org_id, error = %e, "audit export failed"),
+        }
+        if let Err(e) = repository::finish_audit_export(pool, config.org_id, error.as_deref()).await
+        {
+            warn!(org_id = %config.org_id, error = %e, "failed to record audit export run");
+        }
+    }
+}
+
+/// Delete audit entries past their tier's retention and past any export
+/// config's `retention_days`.
+async fn prune(pool: &PgPool) {
+    for tier in [Tier::Free, Tier::Pro, Tier::Team, Tier::Business] {
+        let days = tier.audit_retention_days();
+        match repository::prune_audit_log(pool, &tier.to_string(), days).await {
+            Ok(0) => {}
+            Ok(deleted) => info!(%tier, deleted, "pruned expired audit entries"),
+            Err(e) => warn!(%tier, error = %e, "failed to prune audit log"),
+        }
+    }
+    match repository::prune_exported_audit_log(pool).await {
+        Ok(0) => {}
+        Ok(deleted) => info!(deleted, "pruned exported audit entries"),
+        Err(e) => warn!(error = %e, "failed to prune exported audit entries"),
+    }
+}
+
+/// Deliver an org's entries past its export position, returning how many
+/// were delivered.
+async fn export(pool: &PgPool, config: &AuditExportConfig) -> Result<usize, CloudError> {
+    let org = repository::get_org(pool, config.org_id).await?;
+    let tier: Tier = org.tier.parse().map_err(CloudError::Internal)?;
+    if !tier.audit_export() {
+        return Err(CloudError::Forbidden(format!(
+            "audit export is not included in the {tier} tier"
+        )));
+    }
+
+    let destination: ExportDestination = serde_json::from_value(config.destination.clone())
+        .map_err(|e| CloudError::Internal(format!("invalid export destination: {e}")))?;
+    let data_key = kms::org_data_key(pool, &org).await?;
+    let credentials: ExportCredentials = serde_json::from_str(&decrypt_secret(
+        &data_key,
+        &config.encrypted_credentials,
+        &config.nonce,
+    )?)
+    .map_err(|e| CloudError::Internal(format!("invalid export credentials: {e}")))?;
+    let sink = Sink::open(&destination, &credentials)?;
+
+    let mut position = config.exported_until.zip(config.exported_until_id);
+    let mut exported = 0;
+    for _ in 0..MAX_BATCHES_PER_RUN {
+        let entries =
+            repository::audit_entries_after(pool, config.org_id, position, BATCH_SIZE).await?;
+        let Some(last) = entries.last() else {
+            break;
+        };
+        sink.deliver(config.org_id, &entries).await?;
+        repository::advance_audit_export(pool, config.org_id, last.created_at, last.id).await?;
+        position = Some((last.created_at, last.id));
+        exported += entries.len();
+        if entries.len() < usize::try_from(BATCH_SIZE).unwrap_or(usize::MAX) {
+            break;
+        }
+    }
+    Ok(exported)
+}
+
+/// An opened export destination.
+enum Sink {
+    #[cfg(feature = "s3-backend")]
+    S3(Box<zvault_storage::S3Backend>),
+    Https {
+        client: reqwest::Client,
+        url: String,
+        headers: BTreeMap<String, String>,
+    },
+}
+
+impl Sink {
+    fn open(
+        destination: &ExportDestination,
+        credentials: &ExportCredentials,
+    ) -> Result<Self, CloudError> {
+        validate(destination, credentials)?;
+        match destination {
+            #[cfg(feature = "s3-backend")]
+            ExportDestination::S3 {
+                bucket,
+                region,
+                endpoint,
+                prefix,
+                path_style,
+            } => {
+                let mut s3_config = zvault_storage::S3Config::new(
+                    bucket,
+                    region,
+                    credentials.access_key_id.clone().unwrap_or_default(),
+                    credentials.secret_access_key.clone().unwrap_or_default(),
+                );
+                if let Some(endpoint) = endpoint {
+                    s3_config.endpoint.clone_from(endpoint);
+                }
+                s3_config.prefix.clone_from(prefix);
+                s3_config.path_style = *path_style;
+                let backend = zvault_storage::S3Backend::new(s3_config)
+                    .map_err(|e| CloudError::BadRequest(e.to_string()))?;
+                Ok(Self::S3(Box::new(backend)))
+            }
+            #[cfg(not(feature = "s3-backend"))]
+            ExportDestination::S3 { .. } => Err(CloudError::BadRequest(
+                "S3 audit export is not available on this server".to_owned(),
+            )),
+            ExportDestination::Https { url } => {
+                let client = reqwest::Client::builder()
+                    .timeout(PUSH_TIMEOUT)
+                    .build()
+                    .map_err(|e| {
+                        CloudError::Internal(format!("failed to build http client: {e}"))
+                    })?;
+                Ok(Self::Https {
+                    client,
+                    url: url.clone(),
+                    headers: credentials.headers.clone(),
+                })
+            }
+        }
+    }
+
+    /// Deliver one batch of entries.
+    async fn deliver(&self, org_id: Uuid, entries: &[AuditEntry]) -> Result<(), CloudError> {
+        let mut body = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut body, entry)
+                .map_err(|e| CloudError::Internal(format!("failed to encode audit entry: {e}")))?;
+            body.push(b'\n');
+        }
+
+        match self {
+            #[cfg(feature = "s3-backend")]
+            Self::S3(backend) => {
+                use zvault_storage::StorageBackend;
+                let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+                    return Ok(());
+                };
+                let name = format!(
+                    "{org_id}/{}/{}-{}.ndjson",
+                    first.created_at.format("%Y/%m/%d"),
+                    first.created_at.format("%Y%m%dT%H%M%SZ"),
+                    last.id
+                );
+                backend
+                    .put(&name, &body)
+                    .await
+                    .map_err(|e| CloudError::Internal(format!("S3 upload failed: {e}")))
+            }
+            Self::Https {
+                client,
+                url,
+                headers,
+            } => {
+                let mut request = client
+                    .post(url)
+                    .header("content-type", "application/x-ndjson")
+                    .header("x-zvault-org-id", org_id.to_string());
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                let response = request
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(|e| CloudError::Internal(format!("export push failed: {e}")))?;
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(CloudError::Internal(format!(
+                        "export endpoint returned {}",
+                        response.status()
+                    )))
+                }
+            }
+        }
+    }
+}
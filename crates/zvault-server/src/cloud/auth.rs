@@ -9,8 +9,13 @@
 //!    passed as `Authorization: Bearer zvt_<token>`. Scoped to project + env.
 //!
 //! Service tokens are SHA-256 hashed before storage (never stored plaintext).
+//! Every request re-checks the token against the database, so revocation and
+//! expiry take effect immediately. The middleware also enforces the token's
+//! scope before any handler runs: read-only tokens are refused on mutating
+//! methods, and project-scoped tokens outside their project.
 
-use axum::extract::{Request, State};
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::Method;
 use axum::middleware::Next;
 use axum::response::Response;
 use sha2::{Digest, Sha256};
@@ -19,6 +24,7 @@ use uuid::Uuid;
 
 use super::error::CloudError;
 use super::repository;
+use crate::tls::TlsConnectInfo;
 
 /// Identity of the authenticated caller.
 #[derive(Debug, Clone)]
@@ -155,12 +161,18 @@ fn base64_url_decode(input: &str) -> Result<Vec<u8>, CloudError> {
 
 /// Authenticate a request from the `Authorization: Bearer <token>` header.
 ///
-/// Tries service token first (prefix `zvt_`), then Clerk JWT.
+/// Tries service token first (prefix `zvt_`), then Clerk JWT. `client_ip`
+/// is recorded as a service token's last-used address.
 ///
 /// # Errors
 ///
-/// Returns `CloudError::Unauthorized` if no valid token is found.
-pub async fn authenticate(pool: &PgPool, token: &str) -> Result<CloudIdentity, CloudError> {
+/// Returns `CloudError::Unauthorized` if no valid token is found, or if a
+/// service token has been revoked or has expired.
+pub async fn authenticate(
+    pool: &PgPool,
+    token: &str,
+    client_ip: Option<String>,
+) -> Result<CloudIdentity, CloudError> {
     if token.starts_with("zvt_") {
         // Service token — hash and look up.
         let token_hash = hash_token(token);
@@ -170,7 +182,8 @@ pub async fn authenticate(pool: &PgPool, token: &str) -> Result<CloudIdentity, C
         let pool_clone = pool.clone();
         let token_id = st.id;
         tokio::spawn(async move {
-            let _ = repository::touch_service_token(&pool_clone, token_id).await;
+            let _ =
+                repository::touch_service_token(&pool_clone, token_id, client_ip.as_deref()).await;
         });
 
         Ok(CloudIdentity::ServiceToken {
@@ -232,8 +245,66 @@ pub async fn cloud_auth_middleware(
             CloudError::Unauthorized("Authorization header must use Bearer scheme".to_owned())
         })?;
 
-    let identity = authenticate(&pool, token).await?;
+    let identity = authenticate(&pool, token, client_ip(&req)).await?;
+    check_token_scope(&identity, req.method(), req.uri().path())?;
     req.extensions_mut().insert(identity);
 
     Ok(next.run(req).await)
 }
+
+/// Refuse requests outside a service token's scope.
+///
+/// Read-only tokens may only use safe methods, and a token may only reach
+/// its own project. Environment scope needs the environment's ID, so the
+/// secret routes check it when they resolve the environment.
+fn check_token_scope(
+    identity: &CloudIdentity,
+    method: &Method,
+    path: &str,
+) -> Result<(), CloudError> {
+    let CloudIdentity::ServiceToken {
+        project_id,
+        permissions,
+        ..
+    } = identity
+    else {
+        return Ok(());
+    };
+
+    let read_only = !permissions.iter().any(|p| p == "write");
+    if read_only && !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Err(CloudError::Forbidden(
+            "service token is read-only".to_owned(),
+        ));
+    }
+
+    let mut segments = path.split('/');
+    while let Some(segment) = segments.next() {
+        if segment == "projects" {
+            if let Some(requested) = segments.next() {
+                if requested.parse::<Uuid>().ok() != Some(*project_id) {
+                    return Err(CloudError::Forbidden(
+                        "service token is not scoped to this project".to_owned(),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Best-effort client address: the first proxy hop, else the peer address.
+fn client_ip(req: &Request) -> Option<String> {
+    req.headers()
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|v| v.trim().to_owned())
+        .filter(|v| !v.is_empty())
+        .or_else(|| {
+            req.extensions()
+                .get::<ConnectInfo<TlsConnectInfo>>()
+                .map(|ConnectInfo(info)| info.remote_addr.ip().to_string())
+        })
+}
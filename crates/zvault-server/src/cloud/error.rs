@@ -34,6 +34,11 @@ pub enum CloudError {
     #[error("limit exceeded: {0}")]
     LimitExceeded(String),
 
+    /// The org's data key cannot be unwrapped by its customer-managed KMS key
+    /// (access revoked, key disabled or deleted, KMS unreachable).
+    #[error("key unavailable: {0}")]
+    KeyUnavailable(String),
+
     /// Internal error (database, crypto, etc.).
     #[error("internal error: {0}")]
     Internal(String),
@@ -55,6 +60,7 @@ impl IntoResponse for CloudError {
             Self::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg),
             Self::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg),
             Self::LimitExceeded(msg) => (StatusCode::TOO_MANY_REQUESTS, "limit_exceeded", msg),
+            Self::KeyUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, "key_unavailable", msg),
             Self::Internal(msg) => {
                 tracing::error!(error = %msg, "cloud internal error");
                 (
@@ -0,0 +1,574 @@
+//! Customer-managed KMS keys for cloud orgs (bring your own key).
+//!
+//! Every org's secrets are encrypted with its own AES-256-GCM data key. By
+//! default that key is stored in `organizations.encryption_key`. An
+//! Enterprise org can instead have it wrapped by a key in its own AWS KMS or
+//! GCP Cloud KMS: the wrapped key is kept in `org_kms_keys` and the plaintext
+//! column is emptied, so the org's secrets are only readable while the
+//! customer lets this deployment use their key.
+//!
+//! [`org_data_key`] returns an org's data key either way. Unwrapped keys are
+//! cached in memory for [`CACHE_TTL`], which bounds both the KMS request
+//! rate and how long access continues after the customer revokes it. When
+//! the KMS refuses to unwrap, requests fail with
+//! `CloudError::KeyUnavailable` and the key's status is recorded as
+//! `unavailable` with the KMS's reason until an unwrap succeeds again.
+//!
+//! The data key itself never changes: rotating the KMS key only needs the
+//! data key re-encrypted under the new key material ([`wrap`] after
+//! [`unwrap`]), not the org's secrets re-encrypted.
+//!
+//! Credentials come from the environment: `AWS_ACCESS_KEY_ID`,
+//! `AWS_SECRET_ACCESS_KEY` and optionally `AWS_SESSION_TOKEN` for AWS;
+//! `GOOGLE_OAUTH_ACCESS_TOKEN`, or else the GCE metadata server, for GCP.
+//! `ZVAULT_CLOUD_AWS_KMS_ENDPOINT` and `ZVAULT_CLOUD_GCP_KMS_ENDPOINT`
+//! override the service endpoints (e.g. for VPC endpoints).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+use zvault_storage::sigv4::{self, Signer};
+
+use super::error::CloudError;
+use super::models::{OrgKmsKey, Organization};
+use super::repository;
+
+/// How long an unwrapped data key is reused before asking the KMS again.
+pub const CACHE_TTL: Duration = Duration::from_secs(300);
+/// Timeout for a single KMS request.
+const KMS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An unwrapped data key and when it was unwrapped.
+type CachedKey = (Instant, Zeroizing<Vec<u8>>);
+
+/// Unwrapped data keys by org.
+static KEY_CACHE: LazyLock<Mutex<HashMap<Uuid, CachedKey>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+/// GCP access token from the metadata server, with when it expires.
+static GCP_TOKEN: LazyLock<Mutex<Option<(Instant, String)>>> = LazyLock::new(|| Mutex::new(None));
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+/// A KMS service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KmsProvider {
+    Aws,
+    Gcp,
+}
+
+impl fmt::Display for KmsProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Aws => write!(f, "aws"),
+            Self::Gcp => write!(f, "gcp"),
+        }
+    }
+}
+
+impl std::str::FromStr for KmsProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "aws" => Ok(Self::Aws),
+            "gcp" => Ok(Self::Gcp),
+            other => Err(format!("unknown KMS provider: {other}")),
+        }
+    }
+}
+
+/// A customer-managed KMS key.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KmsKeySpec {
+    pub provider: KmsProvider,
+    /// AWS key ARN, or key ID / alias with `region`; GCP crypto key name
+    /// (`projects/.../locations/.../keyRings/.../cryptoKeys/...`).
+    pub key_id: String,
+    /// AWS region, when `key_id` is not an ARN.
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+impl KmsKeySpec {
+    /// The key an org's data key is currently wrapped by.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CloudError::Internal` if the stored provider is unknown.
+    pub fn of(key: &OrgKmsKey) -> Result<Self, CloudError> {
+        Ok(Self {
+            provider: key.provider.parse().map_err(CloudError::Internal)?,
+            key_id: key.key_id.clone(),
+            region: key.region.clone(),
+        })
+    }
+
+    /// Check that the key is well formed, without contacting the KMS.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CloudError::BadRequest` describing the problem.
+    pub fn validate(&self) -> Result<(), CloudError> {
+        match self.provider {
+            KmsProvider::Aws => {
+                if self.key_id.is_empty() {
+                    return Err(CloudError::BadRequest("key_id is required".to_owned()));
+                }
+                if let (Some(region), Some(arn_region)) = (&self.region, arn_region(&self.key_id))
+                    && region != arn_region
+                {
+                    return Err(CloudError::BadRequest(format!(
+                        "region '{region}' does not match the key ARN's region '{arn_region}'"
+                    )));
+                }
+                let region = self.aws_region().ok_or_else(|| {
+                    CloudError::BadRequest(
+                        "region is required unless key_id is a key ARN".to_owned(),
+                    )
+                })?;
+                if region.is_empty()
+                    || !region
+                        .bytes()
+                        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+                {
+                    return Err(CloudError::BadRequest(format!(
+                        "invalid AWS region '{region}'"
+                    )));
+                }
+            }
+            KmsProvider::Gcp => {
+                if self.region.is_some() {
+                    return Err(CloudError::BadRequest(
+                        "region is not used for GCP keys; the location is part of key_id"
+                            .to_owned(),
+                    ));
+                }
+                let parts: Vec<&str> = self.key_id.split('/').collect();
+                let well_formed = parts.len() == 8
+                    && ["projects", "locations", "keyRings", "cryptoKeys"]
+                        .iter()
+                        .zip(parts.chunks(2))
+                        .all(|(label, pair)| {
+                            pair[0] == *label
+                                && !pair[1].is_empty()
+                                && pair[1]
+                                    .bytes()
+                                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+                        });
+                if !well_formed {
+                    return Err(CloudError::BadRequest(
+                        "GCP key_id must be projects/<project>/locations/<location>/keyRings/<ring>/cryptoKeys/<key>"
+                            .to_owned(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn aws_region(&self) -> Option<&str> {
+        arn_region(&self.key_id).or(self.region.as_deref())
+    }
+}
+
+impl fmt::Display for KmsKeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} KMS key {}", self.provider, self.key_id)
+    }
+}
+
+/// The region of an AWS KMS key ARN (`arn:aws:kms:<region>:<account>:key/<id>`).
+fn arn_region(key_id: &str) -> Option<&str> {
+    let mut parts = key_id.strip_prefix("arn:")?.split(':');
+    let (_partition, service, region) = (parts.next()?, parts.next()?, parts.next()?);
+    (service == "kms").then_some(region)
+}
+
+/// Get an org's data key, unwrapping it with the org's KMS key if it has one.
+///
+/// # Errors
+///
+/// Returns `CloudError::KeyUnavailable` if the org's KMS key cannot be used,
+/// or `CloudError::Internal` on database failure.
+pub async fn org_data_key(
+    pool: &PgPool,
+    org: &Organization,
+) -> Result<Zeroizing<Vec<u8>>, CloudError> {
+    if !org.encryption_key.is_empty() {
+        return Ok(Zeroizing::new(org.encryption_key.clone()));
+    }
+    if let Some((unwrapped_at, key)) = KEY_CACHE.lock().await.get(&org.id)
+        && unwrapped_at.elapsed() < CACHE_TTL
+    {
+        return Ok(key.clone());
+    }
+
+    let Some(kms_key) = repository::get_org_kms_key(pool, org.id).await? else {
+        // The customer-managed key was removed after `org` was read.
+        let org = repository::get_org(pool, org.id).await?;
+        if org.encryption_key.is_empty() {
+            return Err(CloudError::Internal(
+                "organization has no data key".to_owned(),
+            ));
+        }
+        return Ok(Zeroizing::new(org.encryption_key));
+    };
+    let spec = KmsKeySpec::of(&kms_key)?;
+
+    match unwrap(org.id, &spec, &kms_key.wrapped_key).await {
+        Ok(key) => {
+            if kms_key.status != "active"
+                && let Err(e) = repository::record_org_kms_status(pool, org.id, None).await
+            {
+                warn!(org_id = %org.id, error = %e, "failed to record KMS key status");
+            }
+            remember(org.id, &key).await;
+            Ok(key)
+        }
+        Err(reason) => {
+            warn!(org_id = %org.id, %reason, "failed to unwrap org data key");
+            if let Err(e) = repository::record_org_kms_status(pool, org.id, Some(&reason)).await {
+                warn!(org_id = %org.id, error = %e, "failed to record KMS key status");
+            }
+            Err(CloudError::KeyUnavailable(format!(
+                "the organization's encryption key cannot be unwrapped: {reason}"
+            )))
+        }
+    }
+}
+
+/// Cache an org's unwrapped data key.
+pub async fn remember(org_id: Uuid, key: &[u8]) {
+    KEY_CACHE
+        .lock()
+        .await
+        .insert(org_id, (Instant::now(), Zeroizing::new(key.to_vec())));
+}
+
+/// Drop an org's cached data key.
+pub async fn forget(org_id: Uuid) {
+    KEY_CACHE.lock().await.remove(&org_id);
+}
+
+/// Encrypt an org's data key with a KMS key, binding it to the org.
+///
+/// # Errors
+///
+/// Returns the KMS's reason for refusing.
+pub async fn wrap(org_id: Uuid, spec: &KmsKeySpec, data_key: &[u8]) -> Result<Vec<u8>, String> {
+    let plaintext = BASE64.encode(data_key);
+    let ciphertext = match spec.provider {
+        KmsProvider::Aws => {
+            let response = aws_call(
+                spec,
+                "Encrypt",
+                &serde_json::json!({
+                    "KeyId": spec.key_id,
+                    "Plaintext": plaintext,
+                    "EncryptionContext": { "zvault:org_id": org_id.to_string() },
+                }),
+            )
+            .await?;
+            response_field(&response, "CiphertextBlob")?
+        }
+        KmsProvider::Gcp => {
+            let response = gcp_call(
+                spec,
+                "encrypt",
+                &serde_json::json!({
+                    "plaintext": plaintext,
+                    "additionalAuthenticatedData": BASE64.encode(org_id.to_string()),
+                }),
+            )
+            .await?;
+            response_field(&response, "ciphertext")?
+        }
+    };
+    BASE64
+        .decode(ciphertext)
+        .map_err(|e| format!("{spec} returned invalid ciphertext: {e}"))
+}
+
+/// Decrypt an org's data key with the KMS key it was wrapped by.
+///
+/// # Errors
+///
+/// Returns the KMS's reason for refusing.
+pub async fn unwrap(
+    org_id: Uuid,
+    spec: &KmsKeySpec,
+    wrapped_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, String> {
+    let ciphertext = BASE64.encode(wrapped_key);
+    let plaintext = match spec.provider {
+        KmsProvider::Aws => {
+            let response = aws_call(
+                spec,
+                "Decrypt",
+                &serde_json::json!({
+                    "KeyId": spec.key_id,
+                    "CiphertextBlob": ciphertext,
+                    "EncryptionContext": { "zvault:org_id": org_id.to_string() },
+                }),
+            )
+            .await?;
+            Zeroizing::new(response_field(&response, "Plaintext")?)
+        }
+        KmsProvider::Gcp => {
+            let response = gcp_call(
+                spec,
+                "decrypt",
+                &serde_json::json!({
+                    "ciphertext": ciphertext,
+                    "additionalAuthenticatedData": BASE64.encode(org_id.to_string()),
+                }),
+            )
+            .await?;
+            Zeroizing::new(response_field(&response, "plaintext")?)
+        }
+    };
+    let key = Zeroizing::new(
+        BASE64
+            .decode(plaintext.as_bytes())
+            .map_err(|e| format!("{spec} returned invalid plaintext: {e}"))?,
+    );
+    if key.len() != 32 {
+        return Err(format!("{spec} returned a {}-byte data key", key.len()));
+    }
+    Ok(key)
+}
+
+/// A string field of a KMS JSON response.
+fn response_field(response: &serde_json::Value, field: &str) -> Result<String, String> {
+    response
+        .get(field)
+        .and_then(serde_json::Value::as_str)
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| format!("KMS response is missing {field}"))
+}
+
+// ── AWS KMS ──────────────────────────────────────────────────────────
+
+/// Call an AWS KMS API action with a `SigV4`-signed JSON request.
+async fn aws_call(
+    spec: &KmsKeySpec,
+    action: &str,
+    payload: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let region = spec
+        .aws_region()
+        .ok_or_else(|| format!("{spec} has no region"))?;
+    let access_key_id = std::env::var("AWS_ACCESS_KEY_ID")
+        .map_err(|_| "AWS credentials are not configured on this server".to_owned())?;
+    let secret_access_key = Zeroizing::new(
+        std::env::var("AWS_SECRET_ACCESS_KEY")
+            .map_err(|_| "AWS credentials are not configured on this server".to_owned())?,
+    );
+    let session_token = std::env::var("AWS_SESSION_TOKEN").ok();
+
+    let endpoint = std::env::var("ZVAULT_CLOUD_AWS_KMS_ENDPOINT")
+        .unwrap_or_else(|_| format!("https://kms.{region}.amazonaws.com"));
+    let url = reqwest::Url::parse(&endpoint)
+        .map_err(|e| format!("invalid AWS KMS endpoint '{endpoint}': {e}"))?;
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_owned(),
+        (None, _) => return Err(format!("invalid AWS KMS endpoint '{endpoint}'")),
+    };
+
+    let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+    let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let target = format!("TrentService.{action}");
+    let mut headers = vec![
+        ("content-type", "application/x-amz-json-1.1"),
+        ("host", host.as_str()),
+        ("x-amz-date", amz_date.as_str()),
+    ];
+    if let Some(token) = &session_token {
+        headers.push(("x-amz-security-token", token.as_str()));
+    }
+    headers.push(("x-amz-target", target.as_str()));
+
+    let signer = Signer {
+        access_key_id: &access_key_id,
+        secret_access_key: &secret_access_key,
+        region,
+        service: "kms",
+    };
+    let authorization = signer.authorization(
+        &amz_date,
+        &sigv4::canonical_request(
+            "POST",
+            url.path(),
+            "",
+            &headers,
+            &sigv4::payload_hash(&body),
+        ),
+        &headers,
+    );
+
+    let mut request = HTTP
+        .post(url)
+        .timeout(KMS_TIMEOUT)
+        .header("authorization", authorization);
+    for (name, value) in &headers {
+        if *name != "host" {
+            request = request.header(*name, *value);
+        }
+    }
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("AWS KMS request failed: {e}"))?;
+    let status = response.status();
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("AWS KMS returned {status} with an unreadable body: {e}"))?;
+    if status.is_success() {
+        return Ok(json);
+    }
+
+    let error_type = json
+        .get("__type")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    let error_type = error_type.rsplit('#').next().unwrap_or(error_type);
+    let message = json
+        .get("message")
+        .or_else(|| json.get("Message"))
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    Err(match error_type {
+        "AccessDeniedException" => format!(
+            "access to {spec} was denied; its key policy must allow kms:Encrypt and kms:Decrypt for this deployment ({message})"
+        ),
+        "DisabledException" => format!("{spec} is disabled"),
+        "KMSInvalidStateException" => format!("{spec} is pending deletion or unusable ({message})"),
+        "NotFoundException" => format!("{spec} was not found"),
+        "InvalidCiphertextException" | "IncorrectKeyException" => {
+            format!("the data key was not wrapped by {spec}")
+        }
+        "UnrecognizedClientException" | "InvalidSignatureException" => {
+            format!("this deployment's AWS credentials were rejected ({message})")
+        }
+        _ => format!("AWS KMS returned {status}: {error_type} {message}"),
+    })
+}
+
+// ── GCP Cloud KMS ────────────────────────────────────────────────────
+
+/// Call a Cloud KMS crypto key method (`encrypt` or `decrypt`).
+async fn gcp_call(
+    spec: &KmsKeySpec,
+    method: &str,
+    payload: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let endpoint = std::env::var("ZVAULT_CLOUD_GCP_KMS_ENDPOINT")
+        .unwrap_or_else(|_| "https://cloudkms.googleapis.com".to_owned());
+    let token = gcp_access_token().await?;
+
+    let response = HTTP
+        .post(format!(
+            "{}/v1/{}:{method}",
+            endpoint.trim_end_matches('/'),
+            spec.key_id
+        ))
+        .timeout(KMS_TIMEOUT)
+        .bearer_auth(token.as_str())
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| format!("Cloud KMS request failed: {e}"))?;
+    let status = response.status();
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Cloud KMS returned {status} with an unreadable body: {e}"))?;
+    if status.is_success() {
+        return Ok(json);
+    }
+
+    let error = json.get("error");
+    let error_status = error
+        .and_then(|e| e.get("status"))
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    let message = error
+        .and_then(|e| e.get("message"))
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    Err(match error_status {
+        "PERMISSION_DENIED" => format!(
+            "access to {spec} was denied; this deployment needs the Cloud KMS CryptoKey Encrypter/Decrypter role on it ({message})"
+        ),
+        "NOT_FOUND" => format!("{spec} was not found"),
+        "FAILED_PRECONDITION" => format!("{spec} is disabled or destroyed ({message})"),
+        "UNAUTHENTICATED" => {
+            format!("this deployment's Google credentials were rejected ({message})")
+        }
+        "INVALID_ARGUMENT" if method == "decrypt" => {
+            format!("the data key was not wrapped by {spec} ({message})")
+        }
+        _ => format!("Cloud KMS returned {status}: {error_status} {message}"),
+    })
+}
+
+/// A Google OAuth access token: `GOOGLE_OAUTH_ACCESS_TOKEN`, or one from
+/// the GCE metadata server, cached until shortly before it expires.
+async fn gcp_access_token() -> Result<Zeroizing<String>, String> {
+    if let Ok(token) = std::env::var("GOOGLE_OAUTH_ACCESS_TOKEN") {
+        return Ok(Zeroizing::new(token));
+    }
+
+    let mut cached = GCP_TOKEN.lock().await;
+    if let Some((expires, token)) = cached.as_ref()
+        && Instant::now() < *expires
+    {
+        return Ok(Zeroizing::new(token.clone()));
+    }
+
+    let host = std::env::var("GCE_METADATA_HOST")
+        .unwrap_or_else(|_| "metadata.google.internal".to_owned());
+    let response = HTTP
+        .get(format!(
+            "http://{host}/computeMetadata/v1/instance/service-accounts/default/token"
+        ))
+        .timeout(KMS_TIMEOUT)
+        .header("metadata-flavor", "Google")
+        .send()
+        .await
+        .map_err(|e| format!("Google credentials are not available on this server: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Google metadata server returned {}",
+            response.status()
+        ));
+    }
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("invalid Google metadata token response: {e}"))?;
+    let token = response_field(&json, "access_token")?;
+    let expires_in = json
+        .get("expires_in")
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(0);
+    *cached = Some((
+        Instant::now() + Duration::from_secs(expires_in.saturating_sub(60)),
+        token.clone(),
+    ));
+    Ok(Zeroizing::new(token))
+}
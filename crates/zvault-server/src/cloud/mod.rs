@@ -22,14 +22,24 @@
 //!   ├── orgs (organization CRUD + members)
 //!   ├── projects (project CRUD + environments)
 //!   ├── secrets (per-environment secret CRUD, AES-256-GCM encrypted)
-//!   └── tokens (service token management)
+//!   ├── tokens (service token management)
+//!   ├── audit (audit log listing + export configuration)
+//!   ├── kms (customer-managed key configuration + rewrap)
+//!   └── vault-link (confirm a self-hosted vault for token exchange)
 //! ```
 //!
+//! A background worker prunes the audit log by tier retention and runs
+//! scheduled audit exports (see [`audit_export`]).
+//!
 //! All secret values are encrypted with per-org AES-256-GCM keys before
-//! storage. Nonces are generated fresh for every write via `OsRng`.
+//! storage. Nonces are generated fresh for every write via `OsRng`. An
+//! Enterprise org can have its key wrapped by its own AWS or GCP KMS key
+//! (see [`kms`]).
 
+pub mod audit_export;
 pub mod auth;
 pub mod error;
+pub mod kms;
 pub mod models;
 pub mod repository;
 pub mod routes;
@@ -34,6 +34,43 @@ impl Tier {
         }
     }
 
+    /// Days of secret version history kept for this tier. Each secret's
+    /// current version is kept regardless.
+    #[must_use]
+    pub const fn secret_history_days(&self) -> u32 {
+        match self {
+            Self::Free => 7,
+            Self::Pro => 30,
+            Self::Team => 90,
+            Self::Business => 365,
+            Self::Enterprise => u32::MAX,
+        }
+    }
+
+    /// Days audit log entries are kept for this tier.
+    #[must_use]
+    pub const fn audit_retention_days(&self) -> u32 {
+        match self {
+            Self::Free => 7,
+            Self::Pro => 30,
+            Self::Team => 90,
+            Self::Business => 365,
+            Self::Enterprise => u32::MAX,
+        }
+    }
+
+    /// Whether this tier may export its audit log on a schedule.
+    #[must_use]
+    pub const fn audit_export(&self) -> bool {
+        matches!(self, Self::Business | Self::Enterprise)
+    }
+
+    /// Whether this tier may wrap its data key with its own KMS key.
+    #[must_use]
+    pub const fn customer_managed_keys(&self) -> bool {
+        matches!(self, Self::Enterprise)
+    }
+
     /// Maximum API requests per month for this tier.
     #[must_use]
     pub const fn max_api_requests_per_month(&self) -> u64 {
@@ -82,12 +119,33 @@ pub struct Organization {
     pub slug: String,
     pub owner_id: Uuid,
     pub tier: String,
+    /// The org's AES-256-GCM data key; empty while it is wrapped by a
+    /// customer-managed KMS key (see [`OrgKmsKey`]).
     #[serde(skip)]
     pub encryption_key: Vec<u8>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// An org's data key wrapped by a customer-managed KMS key.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct OrgKmsKey {
+    pub org_id: Uuid,
+    /// `aws` or `gcp`.
+    pub provider: String,
+    pub key_id: String,
+    pub region: Option<String>,
+    #[serde(skip)]
+    pub wrapped_key: Vec<u8>,
+    /// `active`, or `unavailable` after the KMS refused to unwrap the key.
+    pub status: String,
+    pub last_error: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub rewrapped_at: DateTime<Utc>,
+    pub configured_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Organization member role.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -144,6 +202,8 @@ pub struct Environment {
     pub slug: String,
     pub sort_order: i32,
     pub created_at: DateTime<Utc>,
+    /// Environment this one inherits secrets from.
+    pub parent_id: Option<Uuid>,
 }
 
 // ── Secrets ──────────────────────────────────────────────────────────
@@ -173,6 +233,42 @@ pub struct SecretEntry {
     pub comment: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Slug of the ancestor environment the value is inherited from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inherited_from: Option<String>,
+}
+
+/// A secret looked up through environment inheritance.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ResolvedSecret {
+    #[sqlx(flatten)]
+    pub secret: EncryptedSecret,
+    /// Slug of the ancestor environment holding the secret; `None` if the
+    /// environment itself does.
+    pub inherited_from: Option<String>,
+}
+
+/// One recorded version of a secret, encrypted.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EncryptedSecretVersion {
+    pub key: String,
+    pub version: i32,
+    pub encrypted_value: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub comment: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Secret version listing (no values).
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SecretVersion {
+    pub version: i32,
+    pub comment: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    /// Whether this version is the secret's current value.
+    pub current: bool,
 }
 
 /// Secret key listing (no values).
@@ -182,6 +278,10 @@ pub struct SecretKey {
     pub version: i32,
     pub comment: String,
     pub updated_at: DateTime<Utc>,
+    /// Slug of the ancestor environment the key is inherited from.
+    pub inherited_from: Option<String>,
+    /// Whether the environment sets this key itself and an ancestor does too.
+    pub overrides: bool,
 }
 
 // ── Service Tokens ───────────────────────────────────────────────────
@@ -199,9 +299,25 @@ pub struct ServiceToken {
     pub permissions: Vec<String>,
     pub expires_at: Option<DateTime<Utc>>,
     pub last_used_at: Option<DateTime<Utc>>,
+    /// Client address of the most recent use.
+    pub last_used_ip: Option<String>,
     pub created_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<Uuid>,
+}
+
+// ── Vault Links ──────────────────────────────────────────────────────
+
+/// An org admin's confirmation that a self-hosted vault may exchange this
+/// org's service tokens.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct VaultLink {
+    pub org_id: Uuid,
+    /// Link ID reported by the self-hosted vault.
+    pub link_id: String,
+    pub confirmed_by: Uuid,
+    pub created_at: DateTime<Utc>,
 }
 
 // ── Users & Sessions ─────────────────────────────────────────────────
@@ -262,3 +378,30 @@ pub struct AuditEntry {
     pub user_agent: Option<String>,
     pub created_at: DateTime<Utc>,
 }
+
+/// An org's scheduled audit log export.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AuditExportConfig {
+    pub org_id: Uuid,
+    /// `{"type": "s3", ...}` or `{"type": "https", ...}`, without credentials.
+    pub destination: serde_json::Value,
+    #[serde(skip)]
+    pub encrypted_credentials: Vec<u8>,
+    #[serde(skip)]
+    pub nonce: Vec<u8>,
+    pub interval_secs: i32,
+    pub enabled: bool,
+    /// Days exported entries are kept, if shorter than the tier's retention.
+    pub retention_days: Option<i32>,
+    /// Entries up to this time have been delivered.
+    pub exported_until: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    pub exported_until_id: Option<Uuid>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: i32,
+    pub updated_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
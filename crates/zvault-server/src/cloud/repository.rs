@@ -9,8 +9,9 @@ use uuid::Uuid;
 
 use super::error::CloudError;
 use super::models::{
-    AuditEntry, CloudUser, EncryptedSecret, Environment, OrgMember, Organization,
-    Project, SecretKey, ServiceToken,
+    AuditEntry, AuditExportConfig, CloudUser, EncryptedSecret, EncryptedSecretVersion, Environment,
+    OrgKmsKey, OrgMember, Organization, Project, ResolvedSecret, SecretKey, SecretVersion,
+    ServiceToken, VaultLink,
 };
 
 // ── Organizations ────────────────────────────────────────────────────
@@ -62,10 +63,7 @@ pub async fn get_org(pool: &PgPool, org_id: Uuid) -> Result<Organization, CloudE
 /// # Errors
 ///
 /// Returns `CloudError::Internal` on database failure.
-pub async fn list_user_orgs(
-    pool: &PgPool,
-    user_id: Uuid,
-) -> Result<Vec<Organization>, CloudError> {
+pub async fn list_user_orgs(pool: &PgPool, user_id: Uuid) -> Result<Vec<Organization>, CloudError> {
     let orgs = sqlx::query_as::<_, Organization>(
         r"SELECT o.* FROM organizations o
           WHERE o.owner_id = $1
@@ -117,11 +115,12 @@ pub async fn add_member(
 ///
 /// Returns `CloudError::Internal` on database failure.
 pub async fn list_members(pool: &PgPool, org_id: Uuid) -> Result<Vec<OrgMember>, CloudError> {
-    let members =
-        sqlx::query_as::<_, OrgMember>("SELECT * FROM org_members WHERE org_id = $1 ORDER BY invited_at")
-            .bind(org_id)
-            .fetch_all(pool)
-            .await?;
+    let members = sqlx::query_as::<_, OrgMember>(
+        "SELECT * FROM org_members WHERE org_id = $1 ORDER BY invited_at",
+    )
+    .bind(org_id)
+    .fetch_all(pool)
+    .await?;
 
     Ok(members)
 }
@@ -140,7 +139,10 @@ pub async fn create_project(
     slug: &str,
     description: &str,
 ) -> Result<Project, CloudError> {
-    let mut tx = pool.begin().await.map_err(|e| CloudError::Internal(e.to_string()))?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
 
     let project = sqlx::query_as::<_, Project>(
         r"INSERT INTO projects (org_id, name, slug, description)
@@ -174,7 +176,9 @@ pub async fn create_project(
         .await?;
     }
 
-    tx.commit().await.map_err(|e| CloudError::Internal(e.to_string()))?;
+    tx.commit()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
 
     Ok(project)
 }
@@ -254,7 +258,7 @@ pub async fn get_environment_by_slug(
     .ok_or_else(|| CloudError::NotFound(format!("environment '{slug}' not found")))
 }
 
-/// Create a custom environment.
+/// Create a custom environment, optionally inheriting from `parent_id`.
 ///
 /// # Errors
 ///
@@ -264,35 +268,121 @@ pub async fn create_environment(
     project_id: Uuid,
     name: &str,
     slug: &str,
+    parent_id: Option<Uuid>,
 ) -> Result<Environment, CloudError> {
     // Get next sort order.
-    let max_order: Option<i32> = sqlx::query_scalar(
-        "SELECT MAX(sort_order) FROM environments WHERE project_id = $1",
-    )
-    .bind(project_id)
-    .fetch_one(pool)
-    .await?;
+    let max_order: Option<i32> =
+        sqlx::query_scalar("SELECT MAX(sort_order) FROM environments WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_one(pool)
+            .await?;
 
     let sort_order = max_order.unwrap_or(0).saturating_add(1);
 
     let env = sqlx::query_as::<_, Environment>(
-        r"INSERT INTO environments (project_id, name, slug, sort_order)
-          VALUES ($1, $2, $3, $4)
+        r"INSERT INTO environments (project_id, name, slug, sort_order, parent_id)
+          VALUES ($1, $2, $3, $4, $5)
           RETURNING *",
     )
     .bind(project_id)
     .bind(name)
     .bind(slug)
     .bind(sort_order)
+    .bind(parent_id)
     .fetch_one(pool)
     .await?;
 
     Ok(env)
 }
 
+/// Most environments in one inheritance chain, the environment included.
+pub const MAX_INHERITANCE_DEPTH: usize = 5;
+
+/// `WITH RECURSIVE chain (id, parent_id, slug, depth)`: environment `$1` at
+/// depth 0 and its ancestors, nearest first.
+const ENV_CHAIN: &str = r"WITH RECURSIVE chain AS (
+    SELECT id, parent_id, slug, 0 AS depth FROM environments WHERE id = $1
+    UNION ALL
+    SELECT e.id, e.parent_id, e.slug, c.depth + 1
+    FROM environments e JOIN chain c ON e.id = c.parent_id
+    WHERE c.depth < 16
+  )";
+
+/// An environment and its ancestors, nearest first.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn environment_chain(
+    pool: &PgPool,
+    environment_id: Uuid,
+) -> Result<Vec<Environment>, CloudError> {
+    let envs = sqlx::query_as::<_, Environment>(&format!(
+        "{ENV_CHAIN} SELECT e.* FROM environments e JOIN chain c ON e.id = c.id ORDER BY c.depth"
+    ))
+    .bind(environment_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(envs)
+}
+
+/// Make an environment inherit from `parent_id`, or from nothing.
+///
+/// # Errors
+///
+/// Returns `CloudError::BadRequest` if the parent would create a cycle or a
+/// chain longer than [`MAX_INHERITANCE_DEPTH`].
+pub async fn set_environment_parent(
+    pool: &PgPool,
+    environment_id: Uuid,
+    parent_id: Option<Uuid>,
+) -> Result<Environment, CloudError> {
+    if let Some(parent_id) = parent_id {
+        let ancestors = environment_chain(pool, parent_id).await?;
+        if ancestors.iter().any(|env| env.id == environment_id) {
+            return Err(CloudError::BadRequest(
+                "an environment cannot inherit from itself or its descendants".to_owned(),
+            ));
+        }
+        // Levels below this environment, which move down with it.
+        let below: Option<i32> = sqlx::query_scalar(
+            r"WITH RECURSIVE below AS (
+                SELECT id, 0 AS depth FROM environments WHERE id = $1
+                UNION ALL
+                SELECT e.id, b.depth + 1
+                FROM environments e JOIN below b ON e.parent_id = b.id
+                WHERE b.depth < 16
+              )
+              SELECT MAX(depth) FROM below",
+        )
+        .bind(environment_id)
+        .fetch_one(pool)
+        .await?;
+        if ancestors.len() + 1 + usize::try_from(below.unwrap_or(0)).unwrap_or_default()
+            > MAX_INHERITANCE_DEPTH
+        {
+            return Err(CloudError::BadRequest(format!(
+                "inheritance chains are limited to {MAX_INHERITANCE_DEPTH} environments"
+            )));
+        }
+    }
+
+    sqlx::query_as::<_, Environment>(
+        "UPDATE environments SET parent_id = $2 WHERE id = $1 RETURNING *",
+    )
+    .bind(environment_id)
+    .bind(parent_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| CloudError::NotFound("environment not found".to_owned()))
+}
+
 // ── Secrets ──────────────────────────────────────────────────────────
 
-/// Upsert an encrypted secret (insert or update).
+/// Upsert an encrypted secret (insert or update) and record the new version.
+///
+/// A secret created again after a delete continues its old version numbers.
 ///
 /// # Errors
 ///
@@ -306,9 +396,14 @@ pub async fn upsert_secret(
     comment: &str,
     actor_id: Option<Uuid>,
 ) -> Result<EncryptedSecret, CloudError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
+
     let secret = sqlx::query_as::<_, EncryptedSecret>(
-        r"INSERT INTO cloud_secrets (environment_id, key, encrypted_value, nonce, comment, created_by, updated_by)
-          VALUES ($1, $2, $3, $4, $5, $6, $6)
+        r"INSERT INTO cloud_secrets (environment_id, key, encrypted_value, nonce, comment, created_by, updated_by, version)
+          VALUES ($1, $2, $3, $4, $5, $6, $6, COALESCE((SELECT MAX(version) FROM cloud_secret_versions WHERE environment_id = $1 AND key = $2), 0) + 1)
           ON CONFLICT (environment_id, key) DO UPDATE SET
             encrypted_value = EXCLUDED.encrypted_value,
             nonce = EXCLUDED.nonce,
@@ -324,12 +419,144 @@ pub async fn upsert_secret(
     .bind(nonce)
     .bind(comment)
     .bind(actor_id)
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await?;
+    record_version(&mut tx, &secret).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
 
     Ok(secret)
 }
 
+/// Add a just-written secret to its version history.
+async fn record_version(
+    conn: &mut sqlx::PgConnection,
+    secret: &EncryptedSecret,
+) -> Result<(), CloudError> {
+    sqlx::query(
+        r"INSERT INTO cloud_secret_versions
+            (environment_id, key, version, encrypted_value, nonce, comment, created_by, created_at)
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(secret.environment_id)
+    .bind(&secret.key)
+    .bind(secret.version)
+    .bind(&secret.encrypted_value)
+    .bind(&secret.nonce)
+    .bind(&secret.comment)
+    .bind(secret.updated_by)
+    .bind(secret.updated_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// One write in a bulk upsert.
+pub struct SecretWrite<'a> {
+    pub key: &'a str,
+    pub encrypted_value: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub comment: &'a str,
+    /// Version the secret must be at for the write to apply; `0` means it
+    /// must not exist yet. `None` writes unconditionally.
+    pub expected_version: Option<i32>,
+}
+
+/// Create or update several secrets in one transaction, recording each new
+/// version.
+///
+/// If any write's expected version does not match, nothing is written.
+///
+/// # Errors
+///
+/// Returns `CloudError::Conflict` if an expected version does not match.
+pub async fn upsert_secrets(
+    pool: &PgPool,
+    environment_id: Uuid,
+    writes: &[SecretWrite<'_>],
+    actor_id: Option<Uuid>,
+) -> Result<Vec<EncryptedSecret>, CloudError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
+
+    let mut secrets = Vec::with_capacity(writes.len());
+    for write in writes {
+        let query = match write.expected_version {
+            None => {
+                r"INSERT INTO cloud_secrets (environment_id, key, encrypted_value, nonce, comment, created_by, updated_by, version)
+                  VALUES ($1, $2, $3, $4, $5, $6, $6, COALESCE((SELECT MAX(version) FROM cloud_secret_versions WHERE environment_id = $1 AND key = $2), 0) + 1)
+                  ON CONFLICT (environment_id, key) DO UPDATE SET
+                    encrypted_value = EXCLUDED.encrypted_value,
+                    nonce = EXCLUDED.nonce,
+                    version = cloud_secrets.version + 1,
+                    comment = EXCLUDED.comment,
+                    updated_by = EXCLUDED.updated_by,
+                    updated_at = now()
+                  RETURNING *"
+            }
+            Some(0) => {
+                r"INSERT INTO cloud_secrets (environment_id, key, encrypted_value, nonce, comment, created_by, updated_by, version)
+                  VALUES ($1, $2, $3, $4, $5, $6, $6, COALESCE((SELECT MAX(version) FROM cloud_secret_versions WHERE environment_id = $1 AND key = $2), 0) + 1)
+                  ON CONFLICT (environment_id, key) DO NOTHING
+                  RETURNING *"
+            }
+            Some(_) => {
+                r"UPDATE cloud_secrets SET
+                    encrypted_value = $3,
+                    nonce = $4,
+                    version = version + 1,
+                    comment = $5,
+                    updated_by = $6,
+                    updated_at = now()
+                  WHERE environment_id = $1 AND key = $2 AND version = $7
+                  RETURNING *"
+            }
+        };
+        let mut query = sqlx::query_as::<_, EncryptedSecret>(query)
+            .bind(environment_id)
+            .bind(write.key)
+            .bind(&write.encrypted_value)
+            .bind(&write.nonce)
+            .bind(write.comment)
+            .bind(actor_id);
+        if let Some(version) = write.expected_version.filter(|v| *v != 0) {
+            query = query.bind(version);
+        }
+        let secret = query.fetch_optional(&mut *tx).await?;
+
+        // Dropping the transaction rolls back the writes so far.
+        let secret = match (secret, write.expected_version) {
+            (Some(secret), _) => secret,
+            (None, Some(0)) => {
+                return Err(CloudError::Conflict(format!(
+                    "secret '{}' already exists",
+                    write.key
+                )));
+            }
+            (None, version) => {
+                return Err(CloudError::Conflict(format!(
+                    "secret '{}' is not at version {}",
+                    write.key,
+                    version.unwrap_or_default()
+                )));
+            }
+        };
+        record_version(&mut tx, &secret).await?;
+        secrets.push(secret);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
+
+    Ok(secrets)
+}
+
 /// Get a single encrypted secret by key.
 ///
 /// # Errors
@@ -369,7 +596,34 @@ pub async fn list_secrets(
     Ok(secrets)
 }
 
-/// List secret keys (no values) for an environment.
+/// Get a secret from an environment or, failing that, its nearest ancestor
+/// that has it.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if no environment in the chain has it.
+pub async fn get_resolved_secret(
+    pool: &PgPool,
+    environment_id: Uuid,
+    key: &str,
+) -> Result<ResolvedSecret, CloudError> {
+    sqlx::query_as::<_, ResolvedSecret>(&format!(
+        r"{ENV_CHAIN}
+          SELECT s.*, CASE WHEN c.depth = 0 THEN NULL ELSE c.slug END AS inherited_from
+          FROM cloud_secrets s JOIN chain c ON s.environment_id = c.id
+          WHERE s.key = $2
+          ORDER BY c.depth
+          LIMIT 1"
+    ))
+    .bind(environment_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| CloudError::NotFound(format!("secret '{key}' not found")))
+}
+
+/// List secret keys (no values) for an environment, including those it
+/// inherits, each from the nearest environment that sets it.
 ///
 /// # Errors
 ///
@@ -378,12 +632,17 @@ pub async fn list_secret_keys(
     pool: &PgPool,
     environment_id: Uuid,
 ) -> Result<Vec<SecretKey>, CloudError> {
-    let keys = sqlx::query_as::<_, SecretKey>(
-        r"SELECT key, version, comment, updated_at
-          FROM cloud_secrets
-          WHERE environment_id = $1
-          ORDER BY key",
-    )
+    let keys = sqlx::query_as::<_, SecretKey>(&format!(
+        r"{ENV_CHAIN}
+          SELECT DISTINCT ON (s.key) s.key, s.version, s.comment, s.updated_at,
+            CASE WHEN c.depth = 0 THEN NULL ELSE c.slug END AS inherited_from,
+            c.depth = 0 AND EXISTS (
+              SELECT 1 FROM cloud_secrets a JOIN chain ac ON a.environment_id = ac.id
+              WHERE ac.depth > 0 AND a.key = s.key
+            ) AS overrides
+          FROM cloud_secrets s JOIN chain c ON s.environment_id = c.id
+          ORDER BY s.key, c.depth"
+    ))
     .bind(environment_id)
     .fetch_all(pool)
     .await?;
@@ -401,19 +660,130 @@ pub async fn delete_secret(
     environment_id: Uuid,
     key: &str,
 ) -> Result<(), CloudError> {
-    let result = sqlx::query(
-        "DELETE FROM cloud_secrets WHERE environment_id = $1 AND key = $2",
+    let result = sqlx::query("DELETE FROM cloud_secrets WHERE environment_id = $1 AND key = $2")
+        .bind(environment_id)
+        .bind(key)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(CloudError::NotFound(format!("secret '{key}' not found")));
+    }
+
+    Ok(())
+}
+
+/// List a secret's recorded versions (no values), newest first. Includes
+/// versions of a deleted secret until they are pruned.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if the secret has no recorded versions.
+pub async fn list_secret_versions(
+    pool: &PgPool,
+    environment_id: Uuid,
+    key: &str,
+) -> Result<Vec<SecretVersion>, CloudError> {
+    let versions = sqlx::query_as::<_, SecretVersion>(
+        r"SELECT v.version, v.comment, v.created_by, v.created_at, s.id IS NOT NULL AS current
+          FROM cloud_secret_versions v
+          LEFT JOIN cloud_secrets s
+            ON s.environment_id = v.environment_id AND s.key = v.key AND s.version = v.version
+          WHERE v.environment_id = $1 AND v.key = $2
+          ORDER BY v.version DESC",
     )
     .bind(environment_id)
     .bind(key)
-    .execute(pool)
+    .fetch_all(pool)
     .await?;
 
-    if result.rows_affected() == 0 {
+    if versions.is_empty() {
         return Err(CloudError::NotFound(format!("secret '{key}' not found")));
     }
 
-    Ok(())
+    Ok(versions)
+}
+
+/// Get one recorded version of a secret.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if that version was never written or has
+/// been pruned.
+pub async fn get_secret_version(
+    pool: &PgPool,
+    environment_id: Uuid,
+    key: &str,
+    version: i32,
+) -> Result<EncryptedSecretVersion, CloudError> {
+    sqlx::query_as::<_, EncryptedSecretVersion>(
+        r"SELECT key, version, encrypted_value, nonce, comment, created_by, created_at
+          FROM cloud_secret_versions
+          WHERE environment_id = $1 AND key = $2 AND version = $3",
+    )
+    .bind(environment_id)
+    .bind(key)
+    .bind(version)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| CloudError::NotFound(format!("secret '{key}' has no version {version}")))
+}
+
+/// Get the version of a secret that was current at `at`.
+///
+/// Deletes are not recorded, so a secret deleted before `at` yields the
+/// value it had when deleted.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if no retained version was written by `at`.
+pub async fn get_secret_version_at(
+    pool: &PgPool,
+    environment_id: Uuid,
+    key: &str,
+    at: DateTime<Utc>,
+) -> Result<EncryptedSecretVersion, CloudError> {
+    sqlx::query_as::<_, EncryptedSecretVersion>(
+        r"SELECT key, version, encrypted_value, nonce, comment, created_by, created_at
+          FROM cloud_secret_versions
+          WHERE environment_id = $1 AND key = $2 AND created_at <= $3
+          ORDER BY version DESC
+          LIMIT 1",
+    )
+    .bind(environment_id)
+    .bind(key)
+    .bind(at)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| CloudError::NotFound(format!("secret '{key}' has no version from {at}")))
+}
+
+/// Delete an environment's recorded versions older than `retention_days`,
+/// except each secret's current version.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn prune_secret_versions(
+    pool: &PgPool,
+    environment_id: Uuid,
+    retention_days: u32,
+) -> Result<u64, CloudError> {
+    let result = sqlx::query(
+        r"DELETE FROM cloud_secret_versions v
+          WHERE v.environment_id = $1
+            AND v.created_at < now() - make_interval(days => $2)
+            AND NOT EXISTS (
+              SELECT 1 FROM cloud_secrets s
+              WHERE s.environment_id = v.environment_id AND s.key = v.key AND s.version = v.version
+            )",
+    )
+    .bind(environment_id)
+    .bind(i32::try_from(retention_days).unwrap_or(i32::MAX))
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
 }
 
 // ── Service Tokens ───────────────────────────────────────────────────
@@ -458,33 +828,62 @@ pub async fn create_service_token(
 ///
 /// # Errors
 ///
-/// Returns `CloudError::NotFound` if the token does not exist or is revoked/expired.
+/// Returns `CloudError::Unauthorized` if the token does not exist, has been
+/// revoked, or has expired.
 pub async fn lookup_service_token(
     pool: &PgPool,
     token_hash: &str,
 ) -> Result<ServiceToken, CloudError> {
-    sqlx::query_as::<_, ServiceToken>(
-        r"SELECT * FROM service_tokens
-          WHERE token_hash = $1
-            AND revoked_at IS NULL
-            AND (expires_at IS NULL OR expires_at > now())",
-    )
-    .bind(token_hash)
-    .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| CloudError::Unauthorized("invalid or expired service token".to_owned()))
+    let token =
+        sqlx::query_as::<_, ServiceToken>("SELECT * FROM service_tokens WHERE token_hash = $1")
+            .bind(token_hash)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| CloudError::Unauthorized("invalid service token".to_owned()))?;
+
+    if let Some(revoked_at) = token.revoked_at {
+        return Err(CloudError::Unauthorized(format!(
+            "service token was revoked at {}",
+            revoked_at.to_rfc3339()
+        )));
+    }
+    if let Some(expires_at) = token.expires_at {
+        if expires_at <= Utc::now() {
+            return Err(CloudError::Unauthorized(format!(
+                "service token expired at {}",
+                expires_at.to_rfc3339()
+            )));
+        }
+    }
+
+    Ok(token)
 }
 
-/// Update `last_used_at` for a service token.
+/// Record a use of a service token.
+///
+/// `last_used_at` is written at most once a minute per token unless the
+/// client address changes, so busy tokens don't cost a write per request.
 ///
 /// # Errors
 ///
 /// Returns `CloudError::Internal` on database failure.
-pub async fn touch_service_token(pool: &PgPool, token_id: Uuid) -> Result<(), CloudError> {
-    sqlx::query("UPDATE service_tokens SET last_used_at = now() WHERE id = $1")
-        .bind(token_id)
-        .execute(pool)
-        .await?;
+pub async fn touch_service_token(
+    pool: &PgPool,
+    token_id: Uuid,
+    client_ip: Option<&str>,
+) -> Result<(), CloudError> {
+    sqlx::query(
+        r"UPDATE service_tokens
+          SET last_used_at = now(), last_used_ip = COALESCE($2, last_used_ip)
+          WHERE id = $1
+            AND (last_used_at IS NULL
+                 OR last_used_at < now() - interval '1 minute'
+                 OR ($2 IS NOT NULL AND last_used_ip IS DISTINCT FROM $2))",
+    )
+    .bind(token_id)
+    .bind(client_ip)
+    .execute(pool)
+    .await?;
 
     Ok(())
 }
@@ -508,7 +907,8 @@ pub async fn list_service_tokens(
     Ok(tokens)
 }
 
-/// Revoke a service token.
+/// Revoke a service token. Revoking an already revoked token keeps the
+/// original revocation time and revoker.
 ///
 /// # Errors
 ///
@@ -517,12 +917,17 @@ pub async fn revoke_service_token(
     pool: &PgPool,
     token_id: Uuid,
     project_id: Uuid,
+    revoked_by: Uuid,
 ) -> Result<(), CloudError> {
     let result = sqlx::query(
-        "UPDATE service_tokens SET revoked_at = now() WHERE id = $1 AND project_id = $2",
+        r"UPDATE service_tokens
+          SET revoked_at = COALESCE(revoked_at, now()),
+              revoked_by = COALESCE(revoked_by, $3)
+          WHERE id = $1 AND project_id = $2",
     )
     .bind(token_id)
     .bind(project_id)
+    .bind(revoked_by)
     .execute(pool)
     .await?;
 
@@ -533,6 +938,69 @@ pub async fn revoke_service_token(
     Ok(())
 }
 
+// ── Vault Links ──────────────────────────────────────────────────────
+
+/// Confirm (or replace) the self-hosted vault link for an org.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn upsert_vault_link(
+    pool: &PgPool,
+    org_id: Uuid,
+    link_id: &str,
+    confirmed_by: Uuid,
+) -> Result<VaultLink, CloudError> {
+    let link = sqlx::query_as::<_, VaultLink>(
+        r"INSERT INTO vault_links (org_id, link_id, confirmed_by)
+          VALUES ($1, $2, $3)
+          ON CONFLICT (org_id) DO UPDATE
+            SET link_id = EXCLUDED.link_id,
+                confirmed_by = EXCLUDED.confirmed_by,
+                created_at = now()
+          RETURNING *",
+    )
+    .bind(org_id)
+    .bind(link_id)
+    .bind(confirmed_by)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(link)
+}
+
+/// Get the confirmed vault link for an org, if any.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn get_vault_link(pool: &PgPool, org_id: Uuid) -> Result<Option<VaultLink>, CloudError> {
+    let link = sqlx::query_as::<_, VaultLink>("SELECT * FROM vault_links WHERE org_id = $1")
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(link)
+}
+
+/// Remove the vault link for an org.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if the org has no vault link.
+pub async fn delete_vault_link(pool: &PgPool, org_id: Uuid) -> Result<(), CloudError> {
+    let result = sqlx::query("DELETE FROM vault_links WHERE org_id = $1")
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(CloudError::NotFound("vault link not found".to_owned()));
+    }
+
+    Ok(())
+}
+
 // ── Users (Clerk-synced) ──────────────────────────────────────────────
 
 /// Upsert a cloud user from Clerk JWT claims.
@@ -572,10 +1040,7 @@ pub async fn upsert_clerk_user(
 /// # Errors
 ///
 /// Returns `CloudError::NotFound` if the user does not exist.
-pub async fn get_user_by_clerk_id(
-    pool: &PgPool,
-    clerk_id: &str,
-) -> Result<CloudUser, CloudError> {
+pub async fn get_user_by_clerk_id(pool: &PgPool, clerk_id: &str) -> Result<CloudUser, CloudError> {
     sqlx::query_as::<_, CloudUser>("SELECT * FROM cloud_users WHERE clerk_id = $1")
         .bind(clerk_id)
         .fetch_optional(pool)
@@ -663,6 +1128,411 @@ pub async fn list_audit(
     Ok(entries)
 }
 
+/// Delete audit entries older than `days` for orgs on `tier`. Entries an
+/// enabled export has not delivered yet are kept.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn prune_audit_log(pool: &PgPool, tier: &str, days: u32) -> Result<u64, CloudError> {
+    let result = sqlx::query(
+        r"DELETE FROM cloud_audit_log a
+          USING organizations o
+          WHERE a.org_id = o.id
+            AND o.tier = $1
+            AND a.created_at < now() - make_interval(days => $2)
+            AND NOT EXISTS (
+                SELECT 1 FROM cloud_audit_export_configs c
+                WHERE c.org_id = a.org_id
+                  AND c.enabled
+                  AND (c.exported_until IS NULL
+                       OR (a.created_at, a.id) > (c.exported_until, c.exported_until_id)))",
+    )
+    .bind(tier)
+    .bind(i32::try_from(days).unwrap_or(i32::MAX))
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Delete exported audit entries older than their export config's
+/// `retention_days`.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn prune_exported_audit_log(pool: &PgPool) -> Result<u64, CloudError> {
+    let result = sqlx::query(
+        r"DELETE FROM cloud_audit_log a
+          USING cloud_audit_export_configs c
+          WHERE c.org_id = a.org_id
+            AND c.retention_days IS NOT NULL
+            AND a.created_at < now() - make_interval(days => c.retention_days)
+            AND (a.created_at, a.id) <= (c.exported_until, c.exported_until_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Get an org's audit export config, if any.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn get_audit_export_config(
+    pool: &PgPool,
+    org_id: Uuid,
+) -> Result<Option<AuditExportConfig>, CloudError> {
+    let config = sqlx::query_as::<_, AuditExportConfig>(
+        "SELECT * FROM cloud_audit_export_configs WHERE org_id = $1",
+    )
+    .bind(org_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(config)
+}
+
+/// Create or replace an org's audit export config. The export position is
+/// kept, so changing the destination does not re-export old entries.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+#[allow(clippy::too_many_arguments)]
+pub async fn upsert_audit_export_config(
+    pool: &PgPool,
+    org_id: Uuid,
+    destination: &serde_json::Value,
+    encrypted_credentials: &[u8],
+    nonce: &[u8],
+    interval_secs: i32,
+    enabled: bool,
+    retention_days: Option<i32>,
+    updated_by: Uuid,
+) -> Result<AuditExportConfig, CloudError> {
+    let config = sqlx::query_as::<_, AuditExportConfig>(
+        r"INSERT INTO cloud_audit_export_configs
+            (org_id, destination, encrypted_credentials, nonce, interval_secs, enabled, retention_days, updated_by)
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+          ON CONFLICT (org_id) DO UPDATE
+            SET destination = EXCLUDED.destination,
+                encrypted_credentials = EXCLUDED.encrypted_credentials,
+                nonce = EXCLUDED.nonce,
+                interval_secs = EXCLUDED.interval_secs,
+                enabled = EXCLUDED.enabled,
+                retention_days = EXCLUDED.retention_days,
+                updated_by = EXCLUDED.updated_by,
+                last_error = NULL,
+                consecutive_failures = 0,
+                updated_at = now()
+          RETURNING *",
+    )
+    .bind(org_id)
+    .bind(destination)
+    .bind(encrypted_credentials)
+    .bind(nonce)
+    .bind(interval_secs)
+    .bind(enabled)
+    .bind(retention_days)
+    .bind(updated_by)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(config)
+}
+
+/// Delete an org's audit export config.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if the org has no export config.
+pub async fn delete_audit_export_config(pool: &PgPool, org_id: Uuid) -> Result<(), CloudError> {
+    let result = sqlx::query("DELETE FROM cloud_audit_export_configs WHERE org_id = $1")
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(CloudError::NotFound(
+            "audit export config not found".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Claim the enabled audit export configs whose interval has elapsed since
+/// their last run, marking them as run now so no other server picks them up.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn claim_due_audit_exports(pool: &PgPool) -> Result<Vec<AuditExportConfig>, CloudError> {
+    let configs = sqlx::query_as::<_, AuditExportConfig>(
+        r"UPDATE cloud_audit_export_configs
+          SET last_run_at = now()
+          WHERE enabled
+            AND (last_run_at IS NULL
+                 OR last_run_at < now() - make_interval(secs => interval_secs))
+          RETURNING *",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(configs)
+}
+
+/// The next audit entries of an org after an export position, oldest first.
+///
+/// Entries from the last minute are left for the next run, so a write that
+/// commits late cannot land behind the export position.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn audit_entries_after(
+    pool: &PgPool,
+    org_id: Uuid,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+) -> Result<Vec<AuditEntry>, CloudError> {
+    let (after_time, after_id) = after.unzip();
+    let entries = sqlx::query_as::<_, AuditEntry>(
+        r"SELECT * FROM cloud_audit_log
+          WHERE org_id = $1
+            AND ($2::timestamptz IS NULL OR (created_at, id) > ($2, $3))
+            AND created_at < now() - interval '1 minute'
+          ORDER BY created_at, id
+          LIMIT $4",
+    )
+    .bind(org_id)
+    .bind(after_time)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+/// Advance an org's export position after delivering entries up to
+/// `(until, until_id)`.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn advance_audit_export(
+    pool: &PgPool,
+    org_id: Uuid,
+    until: DateTime<Utc>,
+    until_id: Uuid,
+) -> Result<(), CloudError> {
+    sqlx::query(
+        r"UPDATE cloud_audit_export_configs
+          SET exported_until = $2, exported_until_id = $3
+          WHERE org_id = $1",
+    )
+    .bind(org_id)
+    .bind(until)
+    .bind(until_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record the outcome of an export run: `error` is `None` on success.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn finish_audit_export(
+    pool: &PgPool,
+    org_id: Uuid,
+    error: Option<&str>,
+) -> Result<(), CloudError> {
+    sqlx::query(
+        r"UPDATE cloud_audit_export_configs
+          SET last_success_at = CASE WHEN $2::text IS NULL THEN now() ELSE last_success_at END,
+              last_error = $2,
+              consecutive_failures = CASE WHEN $2::text IS NULL THEN 0 ELSE consecutive_failures + 1 END
+          WHERE org_id = $1",
+    )
+    .bind(org_id)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// ── Customer-managed KMS keys ────────────────────────────────────────
+
+/// Get an org's customer-managed KMS key, if it has one.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn get_org_kms_key(pool: &PgPool, org_id: Uuid) -> Result<Option<OrgKmsKey>, CloudError> {
+    let key = sqlx::query_as::<_, OrgKmsKey>("SELECT * FROM org_kms_keys WHERE org_id = $1")
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(key)
+}
+
+/// Store an org's data key wrapped by a (new) customer-managed KMS key and
+/// drop the plaintext data key, in one transaction.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn set_org_kms_key(
+    pool: &PgPool,
+    org_id: Uuid,
+    provider: &str,
+    key_id: &str,
+    region: Option<&str>,
+    wrapped_key: &[u8],
+    configured_by: Uuid,
+) -> Result<OrgKmsKey, CloudError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
+
+    let key = sqlx::query_as::<_, OrgKmsKey>(
+        r"INSERT INTO org_kms_keys
+            (org_id, provider, key_id, region, wrapped_key, configured_by, last_checked_at)
+          VALUES ($1, $2, $3, $4, $5, $6, now())
+          ON CONFLICT (org_id) DO UPDATE
+            SET provider = EXCLUDED.provider,
+                key_id = EXCLUDED.key_id,
+                region = EXCLUDED.region,
+                wrapped_key = EXCLUDED.wrapped_key,
+                configured_by = EXCLUDED.configured_by,
+                status = 'active',
+                last_error = NULL,
+                last_checked_at = now(),
+                rewrapped_at = now()
+          RETURNING *",
+    )
+    .bind(org_id)
+    .bind(provider)
+    .bind(key_id)
+    .bind(region)
+    .bind(wrapped_key)
+    .bind(configured_by)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE organizations SET encryption_key = ''::bytea, updated_at = now() WHERE id = $1",
+    )
+    .bind(org_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
+    Ok(key)
+}
+
+/// Replace an org's wrapped data key after re-encrypting it with the current
+/// version of the same KMS key.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if the org has no customer-managed key.
+pub async fn rewrap_org_kms_key(
+    pool: &PgPool,
+    org_id: Uuid,
+    wrapped_key: &[u8],
+) -> Result<OrgKmsKey, CloudError> {
+    sqlx::query_as::<_, OrgKmsKey>(
+        r"UPDATE org_kms_keys
+          SET wrapped_key = $2, status = 'active', last_error = NULL,
+              last_checked_at = now(), rewrapped_at = now()
+          WHERE org_id = $1
+          RETURNING *",
+    )
+    .bind(org_id)
+    .bind(wrapped_key)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| CloudError::NotFound("customer-managed key not configured".to_owned()))
+}
+
+/// Remove an org's customer-managed KMS key, storing its data key in the
+/// platform-managed form again, in one transaction.
+///
+/// # Errors
+///
+/// Returns `CloudError::NotFound` if the org has no customer-managed key.
+pub async fn remove_org_kms_key(
+    pool: &PgPool,
+    org_id: Uuid,
+    data_key: &[u8],
+) -> Result<(), CloudError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
+
+    let result = sqlx::query("DELETE FROM org_kms_keys WHERE org_id = $1")
+        .bind(org_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(CloudError::NotFound(
+            "customer-managed key not configured".to_owned(),
+        ));
+    }
+
+    sqlx::query("UPDATE organizations SET encryption_key = $2, updated_at = now() WHERE id = $1")
+        .bind(org_id)
+        .bind(data_key)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CloudError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+/// Record the outcome of unwrapping an org's data key: `error` marks the
+/// key unavailable, `None` marks it active again.
+///
+/// # Errors
+///
+/// Returns `CloudError::Internal` on database failure.
+pub async fn record_org_kms_status(
+    pool: &PgPool,
+    org_id: Uuid,
+    error: Option<&str>,
+) -> Result<(), CloudError> {
+    sqlx::query(
+        r"UPDATE org_kms_keys
+          SET status = CASE WHEN $2::text IS NULL THEN 'active' ELSE 'unavailable' END,
+              last_error = $2,
+              last_checked_at = now()
+          WHERE org_id = $1",
+    )
+    .bind(org_id)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 // ── Helpers ──────────────────────────────────────────────────────────
 
 /// Check if a user has access to an organization (owner or accepted member).
@@ -676,13 +1546,12 @@ pub async fn check_org_access(
     user_id: Uuid,
 ) -> Result<String, CloudError> {
     // Check if owner.
-    let is_owner: Option<bool> = sqlx::query_scalar(
-        "SELECT true FROM organizations WHERE id = $1 AND owner_id = $2",
-    )
-    .bind(org_id)
-    .bind(user_id)
-    .fetch_optional(pool)
-    .await?;
+    let is_owner: Option<bool> =
+        sqlx::query_scalar("SELECT true FROM organizations WHERE id = $1 AND owner_id = $2")
+            .bind(org_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
 
     if is_owner.is_some() {
         return Ok("admin".to_owned());
@@ -706,11 +1575,139 @@ pub async fn check_org_access(
 ///
 /// Returns `CloudError::Internal` on database failure.
 pub async fn count_environments(pool: &PgPool, project_id: Uuid) -> Result<i64, CloudError> {
-    let count: i64 =
-        sqlx::query_scalar("SELECT COUNT(*) FROM environments WHERE project_id = $1")
-            .bind(project_id)
-            .fetch_one(pool)
-            .await?;
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM environments WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(pool)
+        .await?;
 
     Ok(count)
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+
+    use super::*;
+
+    /// Connect to `ZVAULT_TEST_DATABASE_URL` and apply the migrations in a
+    /// fresh schema, so runs never see each other's rows.
+    async fn test_pool() -> PgPool {
+        let url = std::env::var("ZVAULT_TEST_DATABASE_URL").unwrap();
+        let schema = format!("zvault_test_{}", Uuid::new_v4().simple());
+        let admin = PgPool::connect(&url).await.unwrap();
+        sqlx::query(&format!("CREATE SCHEMA {schema}"))
+            .execute(&admin)
+            .await
+            .unwrap();
+
+        let options = PgConnectOptions::from_str(&url)
+            .unwrap()
+            .options([("search_path", schema.as_str())]);
+        let pool = PgPoolOptions::new().connect_with(options).await.unwrap();
+        let mut migrations: Vec<_> =
+            std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/../../migrations"))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect();
+        migrations.sort();
+        for migration in migrations {
+            let sql = std::fs::read_to_string(migration).unwrap();
+            sqlx::raw_sql(&sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    async fn environment(pool: &PgPool) -> Uuid {
+        let org = create_org(pool, "Acme", "acme", Uuid::new_v4(), "pro", b"key")
+            .await
+            .unwrap();
+        let project = create_project(pool, org.id, "API", "api", "")
+            .await
+            .unwrap();
+        get_environment_by_slug(pool, project.id, "development")
+            .await
+            .unwrap()
+            .id
+    }
+
+    fn write<'a>(key: &'a str, value: &[u8], expected_version: Option<i32>) -> SecretWrite<'a> {
+        SecretWrite {
+            key,
+            encrypted_value: value.to_vec(),
+            nonce: vec![0; 12],
+            comment: "",
+            expected_version,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PostgreSQL in ZVAULT_TEST_DATABASE_URL"]
+    async fn secret_versions_continue_across_deletes() {
+        let pool = test_pool().await;
+        let env = environment(&pool).await;
+
+        upsert_secret(&pool, env, "API_KEY", b"v1", &[0; 12], "", None)
+            .await
+            .unwrap();
+        upsert_secret(&pool, env, "API_KEY", b"v2", &[0; 12], "", None)
+            .await
+            .unwrap();
+        delete_secret(&pool, env, "API_KEY").await.unwrap();
+        let secret = upsert_secret(&pool, env, "API_KEY", b"v3", &[0; 12], "", None)
+            .await
+            .unwrap();
+        assert_eq!(secret.version, 3);
+
+        let versions = list_secret_versions(&pool, env, "API_KEY").await.unwrap();
+        let numbers: Vec<_> = versions.iter().map(|v| (v.version, v.current)).collect();
+        assert_eq!(numbers, [(3, true), (2, false), (1, false)]);
+        let old = get_secret_version(&pool, env, "API_KEY", 1).await.unwrap();
+        assert_eq!(old.encrypted_value, b"v1");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PostgreSQL in ZVAULT_TEST_DATABASE_URL"]
+    async fn bulk_writes_apply_all_or_nothing() {
+        let pool = test_pool().await;
+        let env = environment(&pool).await;
+
+        let written = upsert_secrets(&pool, env, &[write("A", b"a1", Some(0))], None)
+            .await
+            .unwrap();
+        assert_eq!(written[0].version, 1);
+
+        // The stale write to A rolls back the new B.
+        let err = upsert_secrets(
+            &pool,
+            env,
+            &[write("B", b"b1", Some(0)), write("A", b"a2", Some(2))],
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, CloudError::Conflict(_)));
+        assert!(matches!(
+            get_secret(&pool, env, "B").await,
+            Err(CloudError::NotFound(_))
+        ));
+
+        let err = upsert_secrets(&pool, env, &[write("A", b"a2", Some(0))], None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CloudError::Conflict(_)));
+
+        upsert_secrets(
+            &pool,
+            env,
+            &[write("A", b"a2", Some(1)), write("B", b"b1", None)],
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(get_secret(&pool, env, "A").await.unwrap().version, 2);
+        assert_eq!(get_secret(&pool, env, "B").await.unwrap().version, 1);
+    }
+}
@@ -1,6 +1,7 @@
 //! Cloud audit log routes.
 //!
-//! Read-only access to the cloud audit log for a project.
+//! Read-only access to the cloud audit log for a project, and an org's
+//! scheduled audit export configuration (see [`crate::cloud::audit_export`]).
 //! All routes require cloud authentication (Clerk JWT).
 
 use axum::extract::{Path, Query, State};
@@ -10,10 +11,18 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::cloud::audit_export::{self, ExportCredentials, ExportDestination};
 use crate::cloud::auth::CloudIdentity;
 use crate::cloud::error::CloudError;
-use crate::cloud::models::AuditEntry;
+use crate::cloud::kms;
+use crate::cloud::models::{AuditEntry, AuditExportConfig, Tier};
 use crate::cloud::repository;
+use crate::cloud::routes::secrets::{decrypt_secret, encrypt_secret};
+use crate::routes::auth::parse_duration;
+
+/// Shortest and longest time between scheduled exports.
+const MIN_EXPORT_INTERVAL_SECS: i64 = 300;
+const MAX_EXPORT_INTERVAL_SECS: i64 = 86_400;
 
 /// Query parameters for audit listing.
 #[derive(Debug, Deserialize)]
@@ -34,12 +43,43 @@ pub struct AuditListResponse {
     pub entries: Vec<AuditEntry>,
 }
 
+/// Request body for configuring audit export.
+#[derive(Debug, Deserialize)]
+pub struct ExportConfigRequest {
+    pub destination: ExportDestination,
+    /// S3 keys or HTTPS headers. Omit to keep the stored credentials.
+    pub credentials: Option<ExportCredentials>,
+    /// Time between exports (e.g. `"1h"`, `"15m"`).
+    #[serde(default = "default_export_interval")]
+    pub interval: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Delete exported entries after this many days, sooner than the tier's
+    /// retention.
+    pub retention_days: Option<i32>,
+}
+
+fn default_export_interval() -> String {
+    "1h".to_owned()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
 /// Build the audit router.
 pub fn router() -> Router<PgPool> {
-    Router::new().route(
-        "/orgs/{org_id}/projects/{project_id}/audit",
-        get(list_audit),
-    )
+    Router::new()
+        .route(
+            "/orgs/{org_id}/projects/{project_id}/audit",
+            get(list_audit),
+        )
+        .route(
+            "/orgs/{org_id}/audit/export-config",
+            get(get_export_config)
+                .put(put_export_config)
+                .delete(delete_export_config),
+        )
 }
 
 /// `GET /v1/cloud/orgs/{org_id}/projects/{project_id}/audit`
@@ -64,3 +104,161 @@ async fn list_audit(
 
     Ok(Json(entries))
 }
+
+/// `GET /v1/cloud/orgs/{org_id}/audit/export-config` — show the export
+/// configuration and the outcome of its last run. Credentials are never
+/// returned.
+async fn get_export_config(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<AuditExportConfig>, CloudError> {
+    require_admin(&pool, &identity, org_id).await?;
+
+    repository::get_audit_export_config(&pool, org_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| CloudError::NotFound("audit export config not found".to_owned()))
+}
+
+/// `PUT /v1/cloud/orgs/{org_id}/audit/export-config` — create or replace
+/// the export configuration.
+async fn put_export_config(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+    Json(body): Json<ExportConfigRequest>,
+) -> Result<Json<AuditExportConfig>, CloudError> {
+    let user_id = require_admin(&pool, &identity, org_id).await?;
+
+    let org = repository::get_org(&pool, org_id).await?;
+    let tier: Tier = org.tier.parse().map_err(CloudError::Internal)?;
+    if !tier.audit_export() {
+        return Err(CloudError::Forbidden(format!(
+            "audit export requires the business or enterprise tier (org is on {tier})"
+        )));
+    }
+
+    let interval_secs = parse_duration(&body.interval)
+        .map_err(|_| {
+            CloudError::BadRequest(format!(
+                "invalid interval '{}' — use e.g. 15m or 1h",
+                body.interval
+            ))
+        })?
+        .num_seconds();
+    if !(MIN_EXPORT_INTERVAL_SECS..=MAX_EXPORT_INTERVAL_SECS).contains(&interval_secs) {
+        return Err(CloudError::BadRequest(
+            "interval must be between 5m and 24h".to_owned(),
+        ));
+    }
+
+    if let Some(days) = body.retention_days {
+        let max = tier.audit_retention_days();
+        if days < 1 || u32::try_from(days).is_ok_and(|d| d > max) {
+            return Err(CloudError::BadRequest(format!(
+                "retention_days must be between 1 and the {tier} tier's retention"
+            )));
+        }
+    }
+
+    let data_key = kms::org_data_key(&pool, &org).await?;
+    let existing = repository::get_audit_export_config(&pool, org_id).await?;
+    let credentials = match (body.credentials, &existing) {
+        (Some(credentials), _) => credentials,
+        (None, Some(existing)) => serde_json::from_str(&decrypt_secret(
+            &data_key,
+            &existing.encrypted_credentials,
+            &existing.nonce,
+        )?)
+        .map_err(|e| CloudError::Internal(format!("invalid export credentials: {e}")))?,
+        (None, None) => ExportCredentials::default(),
+    };
+    audit_export::validate(&body.destination, &credentials)?;
+
+    let credentials_json =
+        serde_json::to_string(&credentials).map_err(|e| CloudError::Internal(e.to_string()))?;
+    let (ciphertext, nonce) = encrypt_secret(&data_key, &credentials_json)?;
+    let destination =
+        serde_json::to_value(&body.destination).map_err(|e| CloudError::Internal(e.to_string()))?;
+
+    let config = repository::upsert_audit_export_config(
+        &pool,
+        org_id,
+        &destination,
+        &ciphertext,
+        &nonce,
+        i32::try_from(interval_secs).unwrap_or(i32::MAX),
+        body.enabled,
+        body.retention_days,
+        user_id,
+    )
+    .await?;
+
+    repository::write_audit(
+        &pool,
+        org_id,
+        None,
+        None,
+        Some(user_id),
+        identity.actor_type(),
+        "audit_export.configure",
+        "audit/export-config",
+        &serde_json::json!({
+            "destination": destination,
+            "enabled": body.enabled,
+            "interval_secs": interval_secs,
+        }),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(config))
+}
+
+/// `DELETE /v1/cloud/orgs/{org_id}/audit/export-config` — stop exporting.
+async fn delete_export_config(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, CloudError> {
+    let user_id = require_admin(&pool, &identity, org_id).await?;
+    repository::delete_audit_export_config(&pool, org_id).await?;
+
+    repository::write_audit(
+        &pool,
+        org_id,
+        None,
+        None,
+        Some(user_id),
+        identity.actor_type(),
+        "audit_export.delete",
+        "audit/export-config",
+        &serde_json::json!({}),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+/// Require a user who is an admin of `org_id`, returning their ID.
+async fn require_admin(
+    pool: &PgPool,
+    identity: &CloudIdentity,
+    org_id: Uuid,
+) -> Result<Uuid, CloudError> {
+    let CloudIdentity::User { user_id, .. } = identity else {
+        return Err(CloudError::Forbidden(
+            "service tokens cannot manage audit export".to_owned(),
+        ));
+    };
+    if repository::check_org_access(pool, org_id, *user_id).await? != "admin" {
+        return Err(CloudError::Forbidden(
+            "only org admins can manage audit export".to_owned(),
+        ));
+    }
+    Ok(*user_id)
+}
@@ -0,0 +1,218 @@
+//! Customer-managed KMS key routes.
+//!
+//! Let an org admin wrap the org's data key with their own AWS or GCP KMS
+//! key, switch to another key, re-wrap it after rotating the key, or go
+//! back to a platform-managed key (see [`crate::cloud::kms`]). All routes
+//! require cloud authentication (Clerk JWT).
+
+use axum::extract::{Path, State};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::cloud::auth::CloudIdentity;
+use crate::cloud::error::CloudError;
+use crate::cloud::kms::{self, KmsKeySpec};
+use crate::cloud::models::{OrgKmsKey, Tier};
+use crate::cloud::repository;
+
+/// Build the KMS key router.
+pub fn router() -> Router<PgPool> {
+    Router::new()
+        .route(
+            "/orgs/{org_id}/kms",
+            get(get_kms_key).put(put_kms_key).delete(delete_kms_key),
+        )
+        .route("/orgs/{org_id}/kms/rewrap", post(rewrap_kms_key))
+}
+
+/// `GET /v1/cloud/orgs/{org_id}/kms` — show the org's customer-managed key
+/// and whether it could be used the last time it was needed.
+async fn get_kms_key(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<OrgKmsKey>, CloudError> {
+    require_admin(&pool, &identity, org_id).await?;
+
+    repository::get_org_kms_key(&pool, org_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| CloudError::NotFound("customer-managed key not configured".to_owned()))
+}
+
+/// `PUT /v1/cloud/orgs/{org_id}/kms` — wrap the org's data key with a
+/// customer-managed key, replacing the platform-managed or previous key.
+///
+/// The new key must encrypt and then decrypt the data key before the
+/// change is saved, so a misconfigured key policy is rejected up front.
+async fn put_kms_key(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+    Json(spec): Json<KmsKeySpec>,
+) -> Result<Json<OrgKmsKey>, CloudError> {
+    let user_id = require_admin(&pool, &identity, org_id).await?;
+
+    let org = repository::get_org(&pool, org_id).await?;
+    let tier: Tier = org.tier.parse().map_err(CloudError::Internal)?;
+    if !tier.customer_managed_keys() {
+        return Err(CloudError::Forbidden(format!(
+            "customer-managed keys require the enterprise tier (org is on {tier})"
+        )));
+    }
+    spec.validate()?;
+
+    let previous = repository::get_org_kms_key(&pool, org_id).await?;
+    let data_key = kms::org_data_key(&pool, &org).await?;
+
+    let wrapped = kms::wrap(org_id, &spec, &data_key)
+        .await
+        .map_err(|e| CloudError::BadRequest(format!("cannot encrypt with {spec}: {e}")))?;
+    let unwrapped = kms::unwrap(org_id, &spec, &wrapped)
+        .await
+        .map_err(|e| CloudError::BadRequest(format!("cannot decrypt with {spec}: {e}")))?;
+    if *unwrapped != *data_key {
+        return Err(CloudError::BadRequest(format!(
+            "{spec} did not return the data key it encrypted"
+        )));
+    }
+
+    let key = repository::set_org_kms_key(
+        &pool,
+        org_id,
+        &spec.provider.to_string(),
+        &spec.key_id,
+        spec.region.as_deref(),
+        &wrapped,
+        user_id,
+    )
+    .await?;
+    kms::remember(org_id, &data_key).await;
+
+    repository::write_audit(
+        &pool,
+        org_id,
+        None,
+        None,
+        Some(user_id),
+        identity.actor_type(),
+        "kms.configure",
+        "kms",
+        &serde_json::json!({
+            "provider": key.provider,
+            "key_id": key.key_id,
+            "previous_key_id": previous.map(|p| p.key_id),
+        }),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(key))
+}
+
+/// `POST /v1/cloud/orgs/{org_id}/kms/rewrap` — re-encrypt the data key with
+/// the current version of the org's KMS key, e.g. after rotating it.
+///
+/// Always asks the KMS, so it also confirms access was restored after the
+/// key was reported unavailable.
+async fn rewrap_kms_key(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<OrgKmsKey>, CloudError> {
+    let user_id = require_admin(&pool, &identity, org_id).await?;
+
+    let current = repository::get_org_kms_key(&pool, org_id)
+        .await?
+        .ok_or_else(|| CloudError::NotFound("customer-managed key not configured".to_owned()))?;
+    let spec = KmsKeySpec::of(&current)?;
+
+    let data_key = match kms::unwrap(org_id, &spec, &current.wrapped_key).await {
+        Ok(data_key) => data_key,
+        Err(reason) => {
+            repository::record_org_kms_status(&pool, org_id, Some(&reason)).await?;
+            kms::forget(org_id).await;
+            return Err(CloudError::KeyUnavailable(format!(
+                "the organization's encryption key cannot be unwrapped: {reason}"
+            )));
+        }
+    };
+    let wrapped = kms::wrap(org_id, &spec, &data_key)
+        .await
+        .map_err(|e| CloudError::KeyUnavailable(format!("cannot encrypt with {spec}: {e}")))?;
+
+    let key = repository::rewrap_org_kms_key(&pool, org_id, &wrapped).await?;
+    kms::remember(org_id, &data_key).await;
+
+    repository::write_audit(
+        &pool,
+        org_id,
+        None,
+        None,
+        Some(user_id),
+        identity.actor_type(),
+        "kms.rewrap",
+        "kms",
+        &serde_json::json!({ "provider": key.provider, "key_id": key.key_id }),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(key))
+}
+
+/// `DELETE /v1/cloud/orgs/{org_id}/kms` — stop using the customer-managed
+/// key and store the data key as a platform-managed key again. Needs the
+/// customer-managed key to still be usable.
+async fn delete_kms_key(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, CloudError> {
+    let user_id = require_admin(&pool, &identity, org_id).await?;
+
+    let org = repository::get_org(&pool, org_id).await?;
+    let data_key = kms::org_data_key(&pool, &org).await?;
+    repository::remove_org_kms_key(&pool, org_id, &data_key).await?;
+    kms::forget(org_id).await;
+
+    repository::write_audit(
+        &pool,
+        org_id,
+        None,
+        None,
+        Some(user_id),
+        identity.actor_type(),
+        "kms.remove",
+        "kms",
+        &serde_json::json!({}),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+/// Require a user who is an admin of `org_id`, returning their ID.
+async fn require_admin(
+    pool: &PgPool,
+    identity: &CloudIdentity,
+    org_id: Uuid,
+) -> Result<Uuid, CloudError> {
+    let CloudIdentity::User { user_id, .. } = identity else {
+        return Err(CloudError::Forbidden(
+            "service tokens cannot manage encryption keys".to_owned(),
+        ));
+    };
+    if repository::check_org_access(pool, org_id, *user_id).await? != "admin" {
+        return Err(CloudError::Forbidden(
+            "only org admins can manage encryption keys".to_owned(),
+        ));
+    }
+    Ok(*user_id)
+}
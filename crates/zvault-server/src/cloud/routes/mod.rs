@@ -9,10 +9,12 @@
 
 pub mod audit;
 pub mod auth_routes;
+pub mod kms;
 pub mod orgs;
 pub mod projects;
 pub mod secrets;
 pub mod tokens;
+pub mod vault_link;
 
 use axum::middleware as axum_mw;
 use axum::Router;
@@ -35,6 +37,8 @@ pub fn cloud_router(pool: PgPool) -> Router {
         .merge(secrets::router())
         .merge(tokens::router())
         .merge(audit::router())
+        .merge(kms::router())
+        .merge(vault_link::router())
         .route_layer(axum_mw::from_fn_with_state(
             pool.clone(),
             cloud_auth_middleware,
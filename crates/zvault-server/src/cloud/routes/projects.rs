@@ -1,10 +1,12 @@
 //! Project and environment management routes.
 //!
 //! Create and list projects within an organization. Manage environments
-//! per project with tier-based limits on environment count.
+//! per project with tier-based limits on environment count. An environment
+//! may inherit the secrets of another environment in the project, such as a
+//! shared base, and override them key by key.
 
 use axum::extract::{Path, State};
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
 use axum::{Extension, Json, Router};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
@@ -29,6 +31,16 @@ pub struct CreateProjectRequest {
 pub struct CreateEnvironmentRequest {
     pub name: String,
     pub slug: String,
+    /// Slug of an environment to inherit secrets from.
+    #[serde(default)]
+    pub inherits_from: Option<String>,
+}
+
+/// Request body for changing what an environment inherits from.
+#[derive(Debug, Deserialize)]
+pub struct SetParentRequest {
+    /// Slug of the environment to inherit from; `null` to stop inheriting.
+    pub inherits_from: Option<String>,
 }
 
 /// Response for project listing.
@@ -55,6 +67,10 @@ pub fn router() -> Router<PgPool> {
             "/orgs/{org_id}/projects/{project_id}/environments",
             post(create_environment).get(list_environments),
         )
+        .route(
+            "/orgs/{org_id}/projects/{project_id}/environments/{env_slug}/parent",
+            put(set_environment_parent),
+        )
 }
 
 /// `POST /v1/cloud/orgs/{org_id}/projects` — create a new project.
@@ -200,8 +216,65 @@ async fn create_environment(
         ));
     }
 
-    let env =
-        repository::create_environment(&pool, project_id, &body.name, &body.slug).await?;
+    let parent_id = match &body.inherits_from {
+        Some(parent) => {
+            let parent = repository::get_environment_by_slug(&pool, project_id, parent).await?;
+            if repository::environment_chain(&pool, parent.id).await?.len()
+                >= repository::MAX_INHERITANCE_DEPTH
+            {
+                return Err(CloudError::BadRequest(format!(
+                    "inheritance chains are limited to {} environments",
+                    repository::MAX_INHERITANCE_DEPTH
+                )));
+            }
+            Some(parent.id)
+        }
+        None => None,
+    };
+
+    let env = repository::create_environment(&pool, project_id, &body.name, &body.slug, parent_id)
+        .await?;
+
+    Ok(Json(env))
+}
+
+/// `PUT /v1/cloud/orgs/{org_id}/projects/{project_id}/environments/{env_slug}/parent`
+///
+/// Change which environment this one inherits secrets from. Keys the
+/// environment sets itself keep overriding the inherited ones.
+async fn set_environment_parent(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path((org_id, project_id, env_slug)): Path<(Uuid, Uuid, String)>,
+    Json(body): Json<SetParentRequest>,
+) -> Result<Json<Environment>, CloudError> {
+    let CloudIdentity::User { user_id, .. } = identity else {
+        return Err(CloudError::Forbidden(
+            "service tokens cannot change environments".to_owned(),
+        ));
+    };
+
+    let role = repository::check_org_access(&pool, org_id, user_id).await?;
+    if role == "viewer" {
+        return Err(CloudError::Forbidden(
+            "viewers cannot change environments".to_owned(),
+        ));
+    }
+
+    // Verify project belongs to org.
+    repository::get_project(&pool, project_id, org_id).await?;
+
+    let env = repository::get_environment_by_slug(&pool, project_id, &env_slug).await?;
+    let parent_id = match &body.inherits_from {
+        Some(parent) => Some(
+            repository::get_environment_by_slug(&pool, project_id, parent)
+                .await?
+                .id,
+        ),
+        None => None,
+    };
+
+    let env = repository::set_environment_parent(&pool, env.id, parent_id).await?;
 
     Ok(Json(env))
 }
@@ -3,17 +3,26 @@
 //! CRUD operations on secrets within a project environment. Secret values
 //! are encrypted with per-org AES-256-GCM keys before storage and decrypted
 //! on read. Nonces are generated fresh for every write via `OsRng`.
+//!
+//! A bulk `PUT` on the collection writes many secrets in one transaction,
+//! optionally checking each one's current version first.
+//!
+//! Every write is also kept in the secret's version history, for as many
+//! days as the org's tier allows. Restoring a version, or the version that
+//! was current at a given time, writes its value as a new version.
 
 use axum::extract::{Path, State};
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::cloud::auth::CloudIdentity;
 use crate::cloud::error::CloudError;
-use crate::cloud::models::{SecretEntry, SecretKey};
+use crate::cloud::kms;
+use crate::cloud::models::{Organization, SecretEntry, SecretKey, SecretVersion, Tier};
 use crate::cloud::repository;
 
 /// Request body for setting a secret.
@@ -24,12 +33,55 @@ pub struct SetSecretRequest {
     pub comment: String,
 }
 
+/// One entry of a bulk set request.
+#[derive(Debug, Deserialize)]
+pub struct BulkSecretWrite {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub comment: String,
+    /// Version the secret must currently be at; `0` means it must not
+    /// exist yet. Omit to write unconditionally.
+    #[serde(default)]
+    pub expected_version: Option<i32>,
+}
+
+/// Request body for setting several secrets at once.
+#[derive(Debug, Deserialize)]
+pub struct BulkSetSecretsRequest {
+    pub secrets: Vec<BulkSecretWrite>,
+}
+
+/// Response for several secrets.
+#[derive(Debug, Serialize)]
+pub struct SecretsResponse {
+    pub secrets: Vec<SecretEntry>,
+}
+
 /// Response for a single secret.
 #[derive(Debug, Serialize)]
 pub struct SecretResponse {
     pub secret: SecretEntry,
 }
 
+/// Request body for restoring a secret: exactly one of `version` or `at`.
+#[derive(Debug, Deserialize)]
+pub struct RestoreSecretRequest {
+    /// Version to restore.
+    #[serde(default)]
+    pub version: Option<i32>,
+    /// Restore the version that was current at this time.
+    #[serde(default)]
+    pub at: Option<DateTime<Utc>>,
+}
+
+/// Response for a secret's version history (no values).
+#[derive(Debug, Serialize)]
+pub struct SecretVersionsResponse {
+    pub key: String,
+    pub versions: Vec<SecretVersion>,
+}
+
 /// Response for secret key listing (no values).
 #[derive(Debug, Serialize)]
 pub struct SecretKeysResponse {
@@ -41,14 +93,29 @@ pub fn router() -> Router<PgPool> {
     Router::new()
         .route(
             "/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets",
-            get(list_secrets),
+            get(list_secrets).put(bulk_set_secrets),
         )
         .route(
             "/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}",
             get(get_secret).put(set_secret).delete(delete_secret),
         )
+        .route(
+            "/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}/versions",
+            get(list_secret_versions),
+        )
+        .route(
+            "/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}/versions/{version}",
+            get(get_secret_version),
+        )
+        .route(
+            "/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}/restore",
+            post(restore_secret),
+        )
 }
 
+/// Most secrets one bulk set request may write.
+const MAX_BULK_SECRETS: usize = 1000;
+
 /// Encrypt a secret value with the org's AES-256-GCM key.
 ///
 /// Returns `(ciphertext, nonce)`. Nonce is generated fresh via `OsRng`.
@@ -56,7 +123,7 @@ pub fn router() -> Router<PgPool> {
 /// # Errors
 ///
 /// Returns `CloudError::Internal` if encryption fails.
-fn encrypt_secret(org_key: &[u8], plaintext: &str) -> Result<(Vec<u8>, Vec<u8>), CloudError> {
+pub(crate) fn encrypt_secret(org_key: &[u8], plaintext: &str) -> Result<(Vec<u8>, Vec<u8>), CloudError> {
     use aes_gcm::aead::{Aead, OsRng};
     use aes_gcm::aead::rand_core::RngCore;
     use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
@@ -87,7 +154,7 @@ fn encrypt_secret(org_key: &[u8], plaintext: &str) -> Result<(Vec<u8>, Vec<u8>),
 /// # Errors
 ///
 /// Returns `CloudError::Internal` if decryption fails.
-fn decrypt_secret(
+pub(crate) fn decrypt_secret(
     org_key: &[u8],
     ciphertext: &[u8],
     nonce_bytes: &[u8],
@@ -119,6 +186,46 @@ fn decrypt_secret(
         .map_err(|e| CloudError::Internal(format!("decrypted value is not valid UTF-8: {e}")))
 }
 
+/// Validate a secret's key format and value size (max 1MB).
+fn validate_secret(key: &str, value: &str) -> Result<(), CloudError> {
+    if key.is_empty() || key.len() > 256 {
+        return Err(CloudError::BadRequest(
+            "secret key must be 1-256 characters".to_owned(),
+        ));
+    }
+    if value.len() > 1_048_576 {
+        return Err(CloudError::BadRequest(
+            "secret value must be under 1MB".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// The user or service token recorded as a secret's author.
+fn actor_id(identity: &CloudIdentity) -> Uuid {
+    match identity {
+        CloudIdentity::User { user_id, .. } => *user_id,
+        CloudIdentity::ServiceToken { token_id, .. } => *token_id,
+    }
+}
+
+/// Drop an environment's secret versions older than the org's tier keeps.
+async fn prune_history(
+    pool: &PgPool,
+    org: &Organization,
+    environment_id: Uuid,
+) -> Result<(), CloudError> {
+    let tier: Tier = org
+        .tier
+        .parse()
+        .map_err(|e: String| CloudError::Internal(e))?;
+    let days = tier.secret_history_days();
+    if days != u32::MAX {
+        repository::prune_secret_versions(pool, environment_id, days).await?;
+    }
+    Ok(())
+}
+
 /// Resolve org + project + environment from path params.
 ///
 /// Returns `(org, environment)` after verifying access.
@@ -168,7 +275,8 @@ async fn resolve_env(
 
 /// `GET /v1/cloud/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets`
 ///
-/// List secret keys (no values) for an environment.
+/// List secret keys (no values) for an environment, with the keys it
+/// inherits and where each comes from.
 async fn list_secrets(
     State(pool): State<PgPool>,
     Extension(identity): Extension<CloudIdentity>,
@@ -182,16 +290,19 @@ async fn list_secrets(
 
 /// `GET /v1/cloud/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}`
 ///
-/// Get a single secret (decrypted).
+/// Get a single secret (decrypted), inherited if the environment does not
+/// set it itself.
 async fn get_secret(
     State(pool): State<PgPool>,
     Extension(identity): Extension<CloudIdentity>,
     Path((org_id, project_id, env_slug, key)): Path<(Uuid, Uuid, String, String)>,
 ) -> Result<Json<SecretResponse>, CloudError> {
     let (org, env) = resolve_env(&pool, &identity, org_id, project_id, &env_slug).await?;
-    let encrypted = repository::get_secret(&pool, env.id, &key).await?;
+    let resolved = repository::get_resolved_secret(&pool, env.id, &key).await?;
+    let encrypted = resolved.secret;
 
-    let value = decrypt_secret(&org.encryption_key, &encrypted.encrypted_value, &encrypted.nonce)?;
+    let data_key = kms::org_data_key(&pool, &org).await?;
+    let value = decrypt_secret(&data_key, &encrypted.encrypted_value, &encrypted.nonce)?;
 
     Ok(Json(SecretResponse {
         secret: SecretEntry {
@@ -201,6 +312,7 @@ async fn get_secret(
             comment: encrypted.comment,
             created_at: encrypted.created_at,
             updated_at: encrypted.updated_at,
+            inherited_from: resolved.inherited_from,
         },
     }))
 }
@@ -224,54 +336,222 @@ async fn set_secret(
     }
 
     let (org, env) = resolve_env(&pool, &identity, org_id, project_id, &env_slug).await?;
+    validate_secret(&key, &body.value)?;
 
-    // Validate key format.
-    if key.is_empty() || key.len() > 256 {
-        return Err(CloudError::BadRequest(
-            "secret key must be 1-256 characters".to_owned(),
-        ));
+    let data_key = kms::org_data_key(&pool, &org).await?;
+    let (ciphertext, nonce) = encrypt_secret(&data_key, &body.value)?;
+
+    let actor_id = Some(actor_id(&identity));
+
+    let encrypted = repository::upsert_secret(
+        &pool,
+        env.id,
+        &key,
+        &ciphertext,
+        &nonce,
+        &body.comment,
+        actor_id,
+    )
+    .await?;
+    prune_history(&pool, &org, env.id).await?;
+
+    Ok(Json(SecretResponse {
+        secret: SecretEntry {
+            key: encrypted.key,
+            value: body.value,
+            version: encrypted.version,
+            comment: encrypted.comment,
+            created_at: encrypted.created_at,
+            updated_at: encrypted.updated_at,
+            inherited_from: None,
+        },
+    }))
+}
+
+/// `PUT /v1/cloud/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets`
+///
+/// Set several secrets in one transaction. Entries may carry the version
+/// they expect to replace (optimistic concurrency); if any does not match,
+/// nothing is written and the request fails with 409.
+async fn bulk_set_secrets(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path((org_id, project_id, env_slug)): Path<(Uuid, Uuid, String)>,
+    Json(body): Json<BulkSetSecretsRequest>,
+) -> Result<Json<SecretsResponse>, CloudError> {
+    // Check write permission for service tokens.
+    if let CloudIdentity::ServiceToken { permissions, .. } = &identity {
+        if !permissions.contains(&"write".to_owned()) {
+            return Err(CloudError::Forbidden(
+                "service token does not have write permission".to_owned(),
+            ));
+        }
     }
 
-    // Validate value size (max 1MB).
-    if body.value.len() > 1_048_576 {
-        return Err(CloudError::BadRequest(
-            "secret value must be under 1MB".to_owned(),
-        ));
+    let (org, env) = resolve_env(&pool, &identity, org_id, project_id, &env_slug).await?;
+
+    if body.secrets.is_empty() || body.secrets.len() > MAX_BULK_SECRETS {
+        return Err(CloudError::BadRequest(format!(
+            "secrets must hold 1-{MAX_BULK_SECRETS} entries"
+        )));
+    }
+    let data_key = kms::org_data_key(&pool, &org).await?;
+    let mut writes = Vec::with_capacity(body.secrets.len());
+    for (i, secret) in body.secrets.iter().enumerate() {
+        validate_secret(&secret.key, &secret.value)?;
+        if body.secrets[..i].iter().any(|s| s.key == secret.key) {
+            return Err(CloudError::BadRequest(format!(
+                "secret '{}' appears more than once",
+                secret.key
+            )));
+        }
+        if secret.expected_version.is_some_and(|v| v < 0) {
+            return Err(CloudError::BadRequest(
+                "expected_version must not be negative".to_owned(),
+            ));
+        }
+        let (encrypted_value, nonce) = encrypt_secret(&data_key, &secret.value)?;
+        writes.push(repository::SecretWrite {
+            key: &secret.key,
+            encrypted_value,
+            nonce,
+            comment: &secret.comment,
+            expected_version: secret.expected_version,
+        });
     }
 
-    let (ciphertext, nonce) = encrypt_secret(&org.encryption_key, &body.value)?;
+    let encrypted =
+        repository::upsert_secrets(&pool, env.id, &writes, Some(actor_id(&identity))).await?;
+    prune_history(&pool, &org, env.id).await?;
+
+    let secrets = encrypted
+        .into_iter()
+        .zip(body.secrets)
+        .map(|(encrypted, secret)| SecretEntry {
+            key: encrypted.key,
+            value: secret.value,
+            version: encrypted.version,
+            comment: encrypted.comment,
+            created_at: encrypted.created_at,
+            updated_at: encrypted.updated_at,
+            inherited_from: None,
+        })
+        .collect();
+
+    Ok(Json(SecretsResponse { secrets }))
+}
+
+/// `GET /v1/cloud/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}/versions`
+///
+/// List a secret's retained versions (no values), newest first. A deleted
+/// secret keeps its history until the versions age out.
+async fn list_secret_versions(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path((org_id, project_id, env_slug, key)): Path<(Uuid, Uuid, String, String)>,
+) -> Result<Json<SecretVersionsResponse>, CloudError> {
+    let (org, env) = resolve_env(&pool, &identity, org_id, project_id, &env_slug).await?;
+    prune_history(&pool, &org, env.id).await?;
+    let versions = repository::list_secret_versions(&pool, env.id, &key).await?;
+
+    Ok(Json(SecretVersionsResponse { key, versions }))
+}
+
+/// `GET /v1/cloud/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}/versions/{version}`
+///
+/// Get one version of a secret (decrypted).
+async fn get_secret_version(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path((org_id, project_id, env_slug, key, version)): Path<(Uuid, Uuid, String, String, i32)>,
+) -> Result<Json<SecretResponse>, CloudError> {
+    let (org, env) = resolve_env(&pool, &identity, org_id, project_id, &env_slug).await?;
+    prune_history(&pool, &org, env.id).await?;
+    let encrypted = repository::get_secret_version(&pool, env.id, &key, version).await?;
 
-    let actor_id = match &identity {
-        CloudIdentity::User { user_id, .. } => Some(*user_id),
-        CloudIdentity::ServiceToken { token_id, .. } => Some(*token_id),
+    let data_key = kms::org_data_key(&pool, &org).await?;
+    let value = decrypt_secret(&data_key, &encrypted.encrypted_value, &encrypted.nonce)?;
+
+    Ok(Json(SecretResponse {
+        secret: SecretEntry {
+            key: encrypted.key,
+            value,
+            version: encrypted.version,
+            comment: encrypted.comment,
+            created_at: encrypted.created_at,
+            updated_at: encrypted.created_at,
+            inherited_from: None,
+        },
+    }))
+}
+
+/// `POST /v1/cloud/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}/restore`
+///
+/// Write an earlier version's value as the secret's new version. Works for
+/// deleted secrets too, as long as their history is retained.
+async fn restore_secret(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path((org_id, project_id, env_slug, key)): Path<(Uuid, Uuid, String, String)>,
+    Json(body): Json<RestoreSecretRequest>,
+) -> Result<Json<SecretResponse>, CloudError> {
+    // Check write permission for service tokens.
+    if let CloudIdentity::ServiceToken { permissions, .. } = &identity {
+        if !permissions.contains(&"write".to_owned()) {
+            return Err(CloudError::Forbidden(
+                "service token does not have write permission".to_owned(),
+            ));
+        }
+    }
+
+    let (org, env) = resolve_env(&pool, &identity, org_id, project_id, &env_slug).await?;
+    prune_history(&pool, &org, env.id).await?;
+
+    let old = match (body.version, body.at) {
+        (Some(version), None) => {
+            repository::get_secret_version(&pool, env.id, &key, version).await?
+        }
+        (None, Some(at)) => repository::get_secret_version_at(&pool, env.id, &key, at).await?,
+        _ => {
+            return Err(CloudError::BadRequest(
+                "pass exactly one of version or at".to_owned(),
+            ));
+        }
     };
 
+    // Re-encrypt so the restored version gets a fresh nonce like any write.
+    let data_key = kms::org_data_key(&pool, &org).await?;
+    let value = decrypt_secret(&data_key, &old.encrypted_value, &old.nonce)?;
+    let (ciphertext, nonce) = encrypt_secret(&data_key, &value)?;
+    let comment = format!("restored from version {}", old.version);
+
     let encrypted = repository::upsert_secret(
         &pool,
         env.id,
         &key,
         &ciphertext,
         &nonce,
-        &body.comment,
-        actor_id,
+        &comment,
+        Some(actor_id(&identity)),
     )
     .await?;
 
     Ok(Json(SecretResponse {
         secret: SecretEntry {
             key: encrypted.key,
-            value: body.value,
+            value,
             version: encrypted.version,
             comment: encrypted.comment,
             created_at: encrypted.created_at,
             updated_at: encrypted.updated_at,
+            inherited_from: None,
         },
     }))
 }
 
 /// `DELETE /v1/cloud/orgs/{org_id}/projects/{project_id}/envs/{env_slug}/secrets/{key}`
 ///
-/// Delete a secret.
+/// Delete a secret. Its version history is kept, so it can be restored.
 async fn delete_secret(
     State(pool): State<PgPool>,
     Extension(identity): Extension<CloudIdentity>,
@@ -287,7 +567,17 @@ async fn delete_secret(
     }
 
     let (_org, env) = resolve_env(&pool, &identity, org_id, project_id, &env_slug).await?;
-    repository::delete_secret(&pool, env.id, &key).await?;
+    if let Err(err) = repository::delete_secret(&pool, env.id, &key).await {
+        // Say where an inherited key lives instead of just "not found".
+        if let Ok(resolved) = repository::get_resolved_secret(&pool, env.id, &key).await {
+            if let Some(parent) = resolved.inherited_from {
+                return Err(CloudError::BadRequest(format!(
+                    "secret '{key}' is inherited from '{parent}'; delete it there"
+                )));
+            }
+        }
+        return Err(err);
+    }
 
     Ok(Json(serde_json::json!({ "ok": true })))
 }
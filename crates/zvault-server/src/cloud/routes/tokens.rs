@@ -3,11 +3,15 @@
 //! Create, list, and revoke service tokens scoped to a project
 //! (optionally to a specific environment). Tokens are SHA-256 hashed
 //! before storage — the plaintext is returned only once at creation.
+//!
+//! Tokens expire after 90 days unless created with an explicit `ttl` or
+//! `expires_at` (at most a year out). Only org admins may create tokens
+//! that never expire.
 
 use axum::extract::{Path, State};
-use axum::routing::post;
+use axum::routing::{delete, post};
 use axum::{Extension, Json, Router};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -16,15 +20,29 @@ use crate::cloud::auth::{generate_service_token, hash_token, token_prefix, Cloud
 use crate::cloud::error::CloudError;
 use crate::cloud::models::ServiceToken;
 use crate::cloud::repository;
+use crate::routes::auth::parse_duration;
+
+/// Lifetime of a token created without `ttl`, `expires_at`, or `never_expires`.
+const DEFAULT_TOKEN_TTL_DAYS: i64 = 90;
+/// Longest lifetime a token may be created with, short of never expiring.
+const MAX_TOKEN_TTL_DAYS: i64 = 365;
 
 /// Request body for creating a service token.
 #[derive(Debug, Deserialize)]
 pub struct CreateTokenRequest {
     pub name: String,
+    /// Environment scope by ID.
     pub environment_id: Option<Uuid>,
+    /// Environment scope by slug, as an alternative to `environment_id`.
+    pub environment: Option<String>,
     #[serde(default = "default_permissions")]
     pub permissions: Vec<String>,
     pub expires_at: Option<DateTime<Utc>>,
+    /// Lifetime from now (e.g. `"30d"`, `"12h"`), instead of `expires_at`.
+    pub ttl: Option<String>,
+    /// Create a token without an expiry. Admins only.
+    #[serde(default)]
+    pub never_expires: bool,
 }
 
 fn default_permissions() -> Vec<String> {
@@ -52,6 +70,10 @@ pub fn router() -> Router<PgPool> {
             "/orgs/{org_id}/projects/{project_id}/tokens",
             post(create_token).get(list_tokens),
         )
+        .route(
+            "/orgs/{org_id}/projects/{project_id}/tokens/{token_id}",
+            delete(revoke_token),
+        )
         .route(
             "/orgs/{org_id}/projects/{project_id}/tokens/{token_id}/revoke",
             post(revoke_token),
@@ -89,6 +111,11 @@ async fn create_token(
     }
 
     // Validate permissions.
+    if body.permissions.is_empty() {
+        return Err(CloudError::BadRequest(
+            "at least one permission is required".to_owned(),
+        ));
+    }
     let valid_perms = ["read", "write"];
     for perm in &body.permissions {
         if !valid_perms.contains(&perm.as_str()) {
@@ -100,14 +127,29 @@ async fn create_token(
     }
 
     // If scoped to an environment, verify it exists.
-    if let Some(env_id) = body.environment_id {
-        let envs = repository::list_environments(&pool, project_id).await?;
-        if !envs.iter().any(|e| e.id == env_id) {
-            return Err(CloudError::NotFound(
-                "environment not found in this project".to_owned(),
+    let environment_id = match (body.environment_id, body.environment.as_deref()) {
+        (Some(_), Some(_)) => {
+            return Err(CloudError::BadRequest(
+                "set only one of environment_id and environment".to_owned(),
             ));
         }
-    }
+        (Some(env_id), None) => {
+            let envs = repository::list_environments(&pool, project_id).await?;
+            if !envs.iter().any(|e| e.id == env_id) {
+                return Err(CloudError::NotFound(
+                    "environment not found in this project".to_owned(),
+                ));
+            }
+            Some(env_id)
+        }
+        (None, Some(slug)) => {
+            let env = repository::get_environment_by_slug(&pool, project_id, slug).await?;
+            Some(env.id)
+        }
+        (None, None) => None,
+    };
+
+    let expires_at = token_expiry(&body, &role)?;
 
     // Generate token.
     let plaintext = generate_service_token();
@@ -117,12 +159,12 @@ async fn create_token(
     let token = repository::create_service_token(
         &pool,
         project_id,
-        body.environment_id,
+        environment_id,
         &body.name,
         &hash,
         &prefix,
         &body.permissions,
-        body.expires_at,
+        expires_at,
         Some(user_id),
     )
     .await?;
@@ -133,6 +175,50 @@ async fn create_token(
     }))
 }
 
+/// When a new token expires: `expires_at`, `ttl` from now, never (admins
+/// only), or by default [`DEFAULT_TOKEN_TTL_DAYS`] from now.
+fn token_expiry(
+    body: &CreateTokenRequest,
+    role: &str,
+) -> Result<Option<DateTime<Utc>>, CloudError> {
+    let now = Utc::now();
+    let expires_at = match (body.expires_at, body.ttl.as_deref(), body.never_expires) {
+        (None, None, false) => now + Duration::days(DEFAULT_TOKEN_TTL_DAYS),
+        (Some(at), None, false) => at,
+        (None, Some(ttl), false) => {
+            let ttl = parse_duration(ttl).map_err(|_| {
+                CloudError::BadRequest(format!("invalid ttl '{ttl}' — use e.g. 30d or 12h"))
+            })?;
+            now + ttl
+        }
+        (None, None, true) => {
+            if role != "admin" {
+                return Err(CloudError::Forbidden(
+                    "only admins can create service tokens that never expire".to_owned(),
+                ));
+            }
+            return Ok(None);
+        }
+        _ => {
+            return Err(CloudError::BadRequest(
+                "set only one of expires_at, ttl, and never_expires".to_owned(),
+            ));
+        }
+    };
+
+    if expires_at <= now {
+        return Err(CloudError::BadRequest(
+            "token expiry must be in the future".to_owned(),
+        ));
+    }
+    if expires_at > now + Duration::days(MAX_TOKEN_TTL_DAYS) {
+        return Err(CloudError::BadRequest(format!(
+            "token lifetime must be at most {MAX_TOKEN_TTL_DAYS} days — use never_expires for a permanent token"
+        )));
+    }
+    Ok(Some(expires_at))
+}
+
 /// `GET /v1/cloud/orgs/{org_id}/projects/{project_id}/tokens` — list service tokens.
 async fn list_tokens(
     State(pool): State<PgPool>,
@@ -154,8 +240,10 @@ async fn list_tokens(
 }
 
 /// `POST /v1/cloud/orgs/{org_id}/projects/{project_id}/tokens/{token_id}/revoke`
+/// (or `DELETE .../tokens/{token_id}`)
 ///
-/// Revoke a service token. Revoked tokens cannot be used for authentication.
+/// Revoke a service token. Revoked tokens are refused from the next request
+/// on; the token stays listed with who revoked it and when.
 async fn revoke_token(
     State(pool): State<PgPool>,
     Extension(identity): Extension<CloudIdentity>,
@@ -175,7 +263,7 @@ async fn revoke_token(
     }
 
     repository::get_project(&pool, project_id, org_id).await?;
-    repository::revoke_service_token(&pool, token_id, project_id).await?;
+    repository::revoke_service_token(&pool, token_id, project_id, user_id).await?;
 
     Ok(Json(serde_json::json!({ "ok": true })))
 }
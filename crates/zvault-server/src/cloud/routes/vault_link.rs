@@ -0,0 +1,131 @@
+//! Vault link routes.
+//!
+//! Let an org admin confirm the link ID of a self-hosted vault, so that
+//! vault can exchange this org's service tokens for its own tokens and mint
+//! scoped service tokens from vault tokens. The vault side configures its
+//! half at `/v1/sys/cloud-link`; exchange only works while both agree.
+
+use axum::extract::{Path, State};
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::cloud::auth::CloudIdentity;
+use crate::cloud::error::CloudError;
+use crate::cloud::models::VaultLink;
+use crate::cloud::repository;
+
+/// Request body for confirming a vault link.
+#[derive(Debug, Deserialize)]
+pub struct ConfirmLinkRequest {
+    /// Link ID shown by the self-hosted vault at `GET /v1/sys/cloud-link`.
+    pub link_id: String,
+}
+
+/// Build the vault link router.
+pub fn router() -> Router<PgPool> {
+    Router::new().route(
+        "/orgs/{org_id}/vault-link",
+        post(confirm_link).get(get_link).delete(delete_link),
+    )
+}
+
+/// `POST /v1/cloud/orgs/{org_id}/vault-link` — confirm a self-hosted vault.
+async fn confirm_link(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+    Json(body): Json<ConfirmLinkRequest>,
+) -> Result<Json<VaultLink>, CloudError> {
+    let user_id = require_admin(&pool, &identity, org_id).await?;
+
+    if body.link_id.trim().is_empty() {
+        return Err(CloudError::BadRequest("link_id is required".to_owned()));
+    }
+
+    let link = repository::upsert_vault_link(&pool, org_id, body.link_id.trim(), user_id).await?;
+
+    repository::write_audit(
+        &pool,
+        org_id,
+        None,
+        None,
+        Some(user_id),
+        identity.actor_type(),
+        "vault_link.confirm",
+        &format!("vault-link/{}", link.link_id),
+        &serde_json::json!({}),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(link))
+}
+
+/// `GET /v1/cloud/orgs/{org_id}/vault-link` — show the confirmed link.
+async fn get_link(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<VaultLink>, CloudError> {
+    let CloudIdentity::User { user_id, .. } = identity else {
+        return Err(CloudError::Forbidden(
+            "service tokens cannot read vault links".to_owned(),
+        ));
+    };
+    repository::check_org_access(&pool, org_id, user_id).await?;
+
+    repository::get_vault_link(&pool, org_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| CloudError::NotFound("vault link not found".to_owned()))
+}
+
+/// `DELETE /v1/cloud/orgs/{org_id}/vault-link` — stop token exchange.
+async fn delete_link(
+    State(pool): State<PgPool>,
+    Extension(identity): Extension<CloudIdentity>,
+    Path(org_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, CloudError> {
+    let user_id = require_admin(&pool, &identity, org_id).await?;
+    repository::delete_vault_link(&pool, org_id).await?;
+
+    repository::write_audit(
+        &pool,
+        org_id,
+        None,
+        None,
+        Some(user_id),
+        identity.actor_type(),
+        "vault_link.delete",
+        "vault-link",
+        &serde_json::json!({}),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+/// Require a user who is an admin of `org_id`, returning their ID.
+async fn require_admin(
+    pool: &PgPool,
+    identity: &CloudIdentity,
+    org_id: Uuid,
+) -> Result<Uuid, CloudError> {
+    let CloudIdentity::User { user_id, .. } = identity else {
+        return Err(CloudError::Forbidden(
+            "service tokens cannot manage vault links".to_owned(),
+        ));
+    };
+    if repository::check_org_access(pool, org_id, *user_id).await? != "admin" {
+        return Err(CloudError::Forbidden(
+            "only org admins can manage vault links".to_owned(),
+        ));
+    }
+    Ok(*user_id)
+}
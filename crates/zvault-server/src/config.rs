@@ -1,12 +1,19 @@
 //! Server configuration for `ZVault`.
 //!
 //! Loads configuration from environment variables with sensible defaults.
-//! All settings can be overridden via `ZVAULT_*` environment variables.
+//! All settings can be overridden via `ZVAULT_*` environment variables, and
+//! a configuration file (see [`crate::config_file`]) can supply them instead.
 
+use std::collections::HashMap;
+use std::env::VarError;
 use std::net::SocketAddr;
 
+use zvault_core::acme::{ChallengeType, LETS_ENCRYPT_DIRECTORY};
+use zvault_core::hsm::Pkcs11Config;
+
 /// Server configuration.
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ServerConfig {
     /// Address to bind the HTTP listener to.
     pub bind_addr: SocketAddr,
@@ -20,12 +27,159 @@ pub struct ServerConfig {
     pub enable_transit: bool,
     /// Lease expiry scan interval in seconds.
     pub lease_scan_interval_secs: u64,
+    /// How often aggregated secret read counts are persisted, in seconds.
+    pub secret_usage_flush_interval_secs: u64,
     /// Whether to skip `mlock` (for development without root/`CAP_IPC_LOCK`).
     pub disable_mlock: bool,
     /// Spring OAuth configuration (optional — enables "Sign in with Spring").
     pub spring_oauth: Option<SpringOAuthConfig>,
     /// Cloud `PostgreSQL` URL (optional — enables cloud API at `/v1/cloud/*`).
     pub cloud_database_url: Option<String>,
+    /// PKCS#11 slot for HSM-backed transit keys (optional).
+    pub hsm: Option<Pkcs11Config>,
+    /// Key file for the development KMS seal (`ZVAULT_SEAL=devkms`).
+    pub dev_kms_key_path: Option<String>,
+    /// Slack-compatible webhook notified of access request changes (optional).
+    pub access_request_webhook: Option<String>,
+    /// TLS from certificate files (optional — serves HTTPS on `bind_addr`).
+    pub tls: Option<TlsFileConfig>,
+    /// Automatic TLS via ACME (optional — serves HTTPS on `bind_addr`).
+    pub acme: Option<AcmeServerConfig>,
+    /// HTTPS URL whose `Date` header the startup self-test compares the
+    /// local clock against (default: the ACME directory, if configured).
+    pub clock_reference_url: Option<String>,
+    /// Whether `/v1/sys/metrics` requires a token with `read` on `sys/metrics`.
+    pub metrics_require_auth: bool,
+    /// Whether TLS listeners ask clients for a certificate, which `cert`
+    /// auth logins present.
+    pub tls_client_certs: bool,
+    /// Scheduled backups (optional — enabled by `ZVAULT_BACKUP_INTERVAL`).
+    pub backup: Option<BackupConfig>,
+    /// Primary → replica replication (optional — enabled by `ZVAULT_REPLICATION_MODE`).
+    pub replication: Option<ReplicationConfig>,
+    /// Active/standby high availability (optional — enabled by `ZVAULT_HA_ENABLED`).
+    pub ha: Option<HaConfig>,
+    /// CIDR blocks of proxies whose `X-Forwarded-For` is trusted.
+    pub trusted_proxies: Vec<String>,
+    /// Dev mode: initialize and unseal an in-memory vault at startup (see
+    /// [`ServerConfig::into_dev`]). Never for production.
+    pub dev: bool,
+    /// Directory holding external secrets engine plugins (optional —
+    /// plugins are disabled without it).
+    pub plugin_dir: Option<String>,
+}
+
+/// Configuration for active/standby high availability.
+#[derive(Debug, Clone)]
+pub struct HaConfig {
+    /// Base URL other servers reach this one at (e.g. `https://vault-0.vault:8200`).
+    pub api_addr: String,
+    /// Seconds the active node's lock lasts without renewal.
+    pub lock_ttl_secs: u64,
+}
+
+/// Configuration for primary → replica replication.
+#[derive(Debug, Clone)]
+pub struct ReplicationConfig {
+    /// Whether this server is the primary or a replica.
+    pub role: ReplicationRole,
+    /// Shared secret replicas present to the primary.
+    pub secret: String,
+}
+
+/// The part this server plays in replication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplicationRole {
+    /// Records changes for replicas to stream.
+    Primary {
+        /// Changes kept for replicas to catch up from.
+        log_size: usize,
+    },
+    /// Streams changes from a primary, serves reads, and forwards writes.
+    Replica {
+        /// Base URL of the primary (e.g. `https://vault-1.internal:8200`).
+        primary_addr: String,
+    },
+}
+
+/// Configuration for scheduled backups.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    /// Seconds between backups.
+    pub interval_secs: u64,
+    /// Where backup files are written.
+    pub destination: BackupDestination,
+    /// Keep the newest backup of each of this many most recent days.
+    pub keep_daily: usize,
+    /// Keep the newest backup of each of this many most recent ISO weeks.
+    pub keep_weekly: usize,
+}
+
+/// Where scheduled backups are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupDestination {
+    /// A local directory.
+    Dir { path: String },
+    /// An S3-compatible bucket. Credentials are read from
+    /// `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY`.
+    S3 {
+        bucket: String,
+        region: String,
+        /// Custom endpoint; AWS S3 for the region if unset.
+        endpoint: Option<String>,
+        prefix: String,
+        path_style: bool,
+    },
+}
+
+/// Configuration for serving TLS from a certificate and key on disk.
+#[derive(Debug, Clone)]
+pub struct TlsFileConfig {
+    /// PEM certificate chain, leaf first.
+    pub cert_path: String,
+    /// PEM private key.
+    pub key_path: String,
+    /// Generate a certificate from a new internal CA if the files are missing.
+    pub bootstrap: bool,
+    /// Hostnames and IP addresses a bootstrapped certificate covers.
+    pub hostnames: Vec<String>,
+}
+
+/// Configuration for obtaining the server's TLS certificate via ACME.
+#[derive(Debug, Clone)]
+pub struct AcmeServerConfig {
+    /// Domains the certificate covers.
+    pub domains: Vec<String>,
+    /// Contact email registered with the ACME account.
+    pub email: Option<String>,
+    /// ACME directory URL (default: Let's Encrypt production).
+    pub directory_url: String,
+    /// Challenge used to prove control of the domains.
+    pub challenge: ChallengeType,
+    /// Plain HTTP listener for `http-01` challenges and HTTPS redirects.
+    pub http_addr: SocketAddr,
+    /// Renew once the certificate has fewer than this many days left.
+    pub renew_before_days: i64,
+    /// DNS provider for `dns-01` challenges.
+    pub dns_provider: Option<AcmeDnsProvider>,
+}
+
+/// DNS providers supported for `dns-01` challenges.
+#[derive(Clone)]
+pub enum AcmeDnsProvider {
+    /// Cloudflare, via an API token with `Zone.DNS` edit permission.
+    Cloudflare { api_token: String, zone_id: String },
+}
+
+impl std::fmt::Debug for AcmeDnsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cloudflare { zone_id, .. } => f
+                .debug_struct("Cloudflare")
+                .field("zone_id", zone_id)
+                .finish_non_exhaustive(),
+        }
+    }
 }
 
 /// Configuration for Spring OAuth 2.0 / OIDC integration.
@@ -56,89 +210,224 @@ pub enum StorageBackendType {
     Redb { path: String },
     /// `PostgreSQL` persistent storage (recommended for Railway / cloud).
     Postgres { url: String },
+    /// S3-compatible bucket (AWS S3, R2, `MinIO`). Credentials are read from
+    /// `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` when the backend opens.
+    S3 {
+        bucket: String,
+        region: String,
+        /// Custom endpoint; AWS S3 for the region if unset.
+        endpoint: Option<String>,
+        prefix: String,
+        path_style: bool,
+        /// Local write-through cache path, if any.
+        cache_path: Option<String>,
+    },
+}
+
+/// Where settings are read from: the environment first, then the values of
+/// a configuration file.
+#[derive(Clone, Default)]
+pub struct Settings {
+    file: HashMap<String, String>,
+}
+
+impl Settings {
+    /// Settings from the environment alone.
+    #[must_use]
+    pub fn env() -> Self {
+        Self::default()
+    }
+
+    /// Settings from the environment, falling back to `file`, keyed by
+    /// environment variable name.
+    #[must_use]
+    pub fn with_file(file: HashMap<String, String>) -> Self {
+        Self { file }
+    }
+
+    /// The value of environment variable `name`, or of the file setting
+    /// standing for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VarError`] if neither is set, or the variable is not
+    /// valid Unicode.
+    pub fn var(&self, name: &str) -> Result<String, VarError> {
+        match std::env::var(name) {
+            Err(VarError::NotPresent) => self.file.get(name).cloned().ok_or(VarError::NotPresent),
+            result => result,
+        }
+    }
+}
+
+impl std::fmt::Debug for Settings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Settings").finish_non_exhaustive()
+    }
 }
 
 impl ServerConfig {
     /// Load configuration from environment variables.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::env())
+    }
+
+    /// Load configuration from `settings`.
     ///
-    /// Environment variables:
+    /// Environment variables (or the configuration file settings standing for them):
     /// - `PORT` — port to bind on (Railway convention, binds to `0.0.0.0`)
     /// - `ZVAULT_BIND_ADDR` — full bind address (overrides `PORT`, default: `127.0.0.1:8200`)
-    /// - `ZVAULT_STORAGE` — `memory`, `rocksdb`, `redb`, or `postgres` (default: `memory`)
+    /// - `ZVAULT_STORAGE` — `memory`, `rocksdb`, `redb`, `postgres`, or `s3` (default: `memory`)
     /// - `ZVAULT_STORAGE_PATH` — path for persistent backends (default: `./data`)
     /// - `DATABASE_URL` — `PostgreSQL` connection string (required when `ZVAULT_STORAGE=postgres`)
+    /// - `ZVAULT_S3_BUCKET` / `ZVAULT_S3_REGION` — bucket and signing region for `s3` (region default: `us-east-1`)
+    /// - `ZVAULT_S3_ENDPOINT` — S3-compatible endpoint, e.g. R2 or `MinIO` (default: AWS S3)
+    /// - `ZVAULT_S3_PREFIX` — object key prefix (optional)
+    /// - `ZVAULT_S3_PATH_STYLE` — address the bucket as `<endpoint>/<bucket>` (default: `false`)
+    /// - `ZVAULT_S3_CACHE_PATH` — `RocksDB` write-through cache for the bucket (optional)
     /// - `ZVAULT_STORAGE_PATH` — path for persistent backends (default: `./data`)
     /// - `ZVAULT_LOG_LEVEL` — log filter (default: `info`)
     /// - `ZVAULT_AUDIT_FILE` — path to audit log file (optional)
     /// - `ZVAULT_ENABLE_TRANSIT` — enable transit engine (default: `true`)
-    /// - `ZVAULT_LEASE_SCAN_INTERVAL` — seconds between lease scans (default: `60`)
+    /// - `ZVAULT_LEASE_SCAN_INTERVAL` — seconds between lease and KV version expiry scans (default: `60`)
     /// - `ZVAULT_DISABLE_MLOCK` — skip `mlockall` for dev environments (default: `false`)
+    /// - `ZVAULT_HSM_MODULE` — PKCS#11 module path; enables `pkcs11` transit keys (optional)
+    /// - `ZVAULT_HSM_SLOT` — PKCS#11 slot ID (default: `0`)
+    /// - `ZVAULT_HSM_PIN` — PKCS#11 user PIN
+    /// - `ZVAULT_HSM_TOOL` — `pkcs11-tool` executable (default: `pkcs11-tool`)
+    /// - `ZVAULT_SEAL` — `shamir` (default) or `devkms` for file-based auto-unseal
+    /// - `ZVAULT_DEV_KMS_KEY` — dev KMS key file (default: `<storage path>/dev-kms.key`)
+    /// - `ZVAULT_ACCESS_REQUEST_WEBHOOK` — Slack incoming webhook for access requests (optional)
+    /// - `ZVAULT_TLS_CERT` / `ZVAULT_TLS_KEY` — PEM certificate and key files; enables HTTPS (optional)
+    /// - `ZVAULT_TLS_BOOTSTRAP` — create a self-signed certificate if the files are missing (default: `false`)
+    /// - `ZVAULT_TLS_HOSTNAMES` — comma-separated names for a bootstrapped certificate (default: `localhost,127.0.0.1`)
+    /// - `ZVAULT_ACME_DOMAINS` — comma-separated domains; enables HTTPS via ACME (optional)
+    /// - `ZVAULT_ACME_EMAIL` — ACME account contact email (optional)
+    /// - `ZVAULT_ACME_DIRECTORY` — ACME directory URL (default: Let's Encrypt production)
+    /// - `ZVAULT_ACME_CHALLENGE` — `http-01` (default) or `dns-01`
+    /// - `ZVAULT_ACME_HTTP_ADDR` — challenge and redirect listener (default: `0.0.0.0:80`)
+    /// - `ZVAULT_ACME_RENEW_BEFORE_DAYS` — renewal window in days (default: `30`)
+    /// - `ZVAULT_ACME_DNS_PROVIDER` — `cloudflare`, for `dns-01`
+    /// - `ZVAULT_ACME_CLOUDFLARE_API_TOKEN` / `ZVAULT_ACME_CLOUDFLARE_ZONE_ID` — Cloudflare credentials
+    /// - `ZVAULT_CLOCK_REFERENCE_URL` — clock skew reference for the startup self-test (optional)
+    /// - `ZVAULT_METRICS_REQUIRE_AUTH` — require a token to scrape `/v1/sys/metrics` (default: `false`)
+    /// - `ZVAULT_BACKUP_INTERVAL` — seconds between scheduled backups; enables them (optional)
+    /// - `ZVAULT_BACKUP_DIR` — backup directory (default: `<storage path>/backups`)
+    /// - `ZVAULT_BACKUP_S3_BUCKET` — write backups to this bucket instead of a directory (optional)
+    /// - `ZVAULT_BACKUP_S3_REGION` / `ZVAULT_BACKUP_S3_ENDPOINT` / `ZVAULT_BACKUP_S3_PREFIX` / `ZVAULT_BACKUP_S3_PATH_STYLE` — as for `s3` storage
+    /// - `ZVAULT_BACKUP_KEEP_DAILY` / `ZVAULT_BACKUP_KEEP_WEEKLY` — days and weeks to keep a backup for (default: `7` / `4`)
+    /// - `ZVAULT_REPLICATION_MODE` — `primary` or `replica`; enables replication (optional)
+    /// - `ZVAULT_REPLICATION_SECRET` — shared secret between primary and replicas (required with a mode)
+    /// - `ZVAULT_REPLICATION_PRIMARY_ADDR` — primary's base URL, on a replica
+    /// - `ZVAULT_REPLICATION_LOG_SIZE` — changes a primary keeps for lagging replicas (default: `10000`)
+    /// - `ZVAULT_HA_ENABLED` — elect one active node among servers sharing storage (default: `false`)
+    /// - `ZVAULT_API_ADDR` — base URL standbys forward requests to this server at (required with HA)
+    /// - `ZVAULT_HA_LOCK_TTL` — seconds the active node's lock lasts without renewal (default: `15`)
+    /// - `ZVAULT_TRUSTED_PROXIES` — comma-separated CIDRs of proxies whose `X-Forwarded-For` names the client (default: none)
+    /// - `ZVAULT_PLUGIN_DIR` — directory of plugin WebAssembly modules; enables the plugin catalog (optional)
+    /// - `ZVAULT_DEV` — run a throwaway dev vault, like `--dev`; see [`Self::into_dev`] (default: `false`)
     #[must_use]
-    pub fn from_env() -> Self {
+    #[allow(clippy::too_many_lines)]
+    pub fn from_settings(settings: &Settings) -> Self {
         // Priority: ZVAULT_BIND_ADDR > PORT (Railway) > default 127.0.0.1:8200
-        let bind_addr = if let Ok(addr) = std::env::var("ZVAULT_BIND_ADDR") {
+        let bind_addr = if let Ok(addr) = settings.var("ZVAULT_BIND_ADDR") {
             addr.parse()
                 .unwrap_or_else(|_| SocketAddr::from(([127, 0, 0, 1], 8200)))
-        } else if let Ok(port_str) = std::env::var("PORT") {
+        } else if let Ok(port_str) = settings.var("PORT") {
             let port: u16 = port_str.parse().unwrap_or(8200);
             SocketAddr::from(([0, 0, 0, 0], port))
         } else {
             SocketAddr::from(([127, 0, 0, 1], 8200))
         };
 
-        let storage_path =
-            std::env::var("ZVAULT_STORAGE_PATH").unwrap_or_else(|_| "./data".to_owned());
-
-        let storage_backend = match std::env::var("ZVAULT_STORAGE")
-            .unwrap_or_else(|_| "memory".to_owned())
-            .to_lowercase()
-            .as_str()
-        {
-            "rocksdb" => StorageBackendType::RocksDb { path: storage_path },
-            "redb" => StorageBackendType::Redb { path: storage_path },
-            "postgres" | "postgresql" => {
-                let url = std::env::var("DATABASE_URL")
-                    .unwrap_or_else(|_| "postgres://localhost/zvault".to_owned());
-                StorageBackendType::Postgres { url }
-            }
-            _ => StorageBackendType::Memory,
-        };
+        let storage_path = settings
+            .var("ZVAULT_STORAGE_PATH")
+            .unwrap_or_else(|_| "./data".to_owned());
+
+        // Dev KMS seal — enabled when ZVAULT_SEAL=devkms.
+        let dev_kms_key_path = settings
+            .var("ZVAULT_SEAL")
+            .is_ok_and(|v| v.eq_ignore_ascii_case("devkms"))
+            .then(|| {
+                settings
+                    .var("ZVAULT_DEV_KMS_KEY")
+                    .unwrap_or_else(|_| format!("{storage_path}/dev-kms.key"))
+            });
+
+        let backup = backup_from_env(settings, &storage_path);
+        let storage_backend = storage_from_env(settings, storage_path);
+
+        let log_level = settings
+            .var("ZVAULT_LOG_LEVEL")
+            .unwrap_or_else(|_| "info".to_owned());
 
-        let log_level = std::env::var("ZVAULT_LOG_LEVEL").unwrap_or_else(|_| "info".to_owned());
+        let audit_file_path = settings.var("ZVAULT_AUDIT_FILE").ok();
 
-        let audit_file_path = std::env::var("ZVAULT_AUDIT_FILE").ok();
+        let enable_transit = settings
+            .var("ZVAULT_ENABLE_TRANSIT")
+            .map_or(true, |v| v != "false" && v != "0");
 
-        let enable_transit = std::env::var("ZVAULT_ENABLE_TRANSIT")
-            .map(|v| v != "false" && v != "0")
-            .unwrap_or(true);
+        let lease_scan_interval_secs = settings
+            .var("ZVAULT_LEASE_SCAN_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
 
-        let lease_scan_interval_secs = std::env::var("ZVAULT_LEASE_SCAN_INTERVAL")
+        let secret_usage_flush_interval_secs = settings
+            .var("ZVAULT_SECRET_USAGE_FLUSH_INTERVAL")
             .ok()
             .and_then(|v| v.parse().ok())
+            .filter(|&secs| secs > 0)
             .unwrap_or(60);
 
-        let disable_mlock = std::env::var("ZVAULT_DISABLE_MLOCK")
-            .map(|v| v == "true" || v == "1")
-            .unwrap_or(false);
+        let disable_mlock = settings
+            .var("ZVAULT_DISABLE_MLOCK")
+            .is_ok_and(|v| v == "true" || v == "1");
 
         // Spring OAuth — enabled when SPRING_AUTH_URL is set.
-        let spring_oauth =
-            std::env::var("SPRING_AUTH_URL")
-                .ok()
-                .map(|auth_url| SpringOAuthConfig {
-                    auth_url,
-                    client_id: std::env::var("SPRING_CLIENT_ID")
-                        .unwrap_or_else(|_| "zvault-dashboard".to_owned()),
-                    client_secret: std::env::var("SPRING_CLIENT_SECRET").unwrap_or_default(),
-                    redirect_uri: std::env::var("SPRING_REDIRECT_URI").ok(),
-                    default_policy: std::env::var("SPRING_DEFAULT_POLICY")
-                        .unwrap_or_else(|_| "default".to_owned()),
-                    admin_policy: std::env::var("SPRING_ADMIN_POLICY")
-                        .unwrap_or_else(|_| "root".to_owned()),
-                });
+        let spring_oauth = settings
+            .var("SPRING_AUTH_URL")
+            .ok()
+            .map(|auth_url| SpringOAuthConfig {
+                auth_url,
+                client_id: settings
+                    .var("SPRING_CLIENT_ID")
+                    .unwrap_or_else(|_| "zvault-dashboard".to_owned()),
+                client_secret: settings.var("SPRING_CLIENT_SECRET").unwrap_or_default(),
+                redirect_uri: settings.var("SPRING_REDIRECT_URI").ok(),
+                default_policy: settings
+                    .var("SPRING_DEFAULT_POLICY")
+                    .unwrap_or_else(|_| "default".to_owned()),
+                admin_policy: settings
+                    .var("SPRING_ADMIN_POLICY")
+                    .unwrap_or_else(|_| "root".to_owned()),
+            });
 
         // Cloud API — enabled when CLOUD_DATABASE_URL is set.
-        let cloud_database_url = std::env::var("CLOUD_DATABASE_URL").ok();
+        let cloud_database_url = settings.var("CLOUD_DATABASE_URL").ok();
+
+        // HSM — enabled when ZVAULT_HSM_MODULE is set.
+        let hsm = settings
+            .var("ZVAULT_HSM_MODULE")
+            .ok()
+            .map(|module_path| Pkcs11Config {
+                module_path,
+                slot: settings
+                    .var("ZVAULT_HSM_SLOT")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0),
+                pin: settings.var("ZVAULT_HSM_PIN").unwrap_or_default(),
+                tool_path: settings
+                    .var("ZVAULT_HSM_TOOL")
+                    .unwrap_or_else(|_| "pkcs11-tool".to_owned()),
+            });
+
+        let access_request_webhook = settings
+            .var("ZVAULT_ACCESS_REQUEST_WEBHOOK")
+            .ok()
+            .filter(|v| !v.is_empty());
 
         Self {
             bind_addr,
@@ -147,9 +436,277 @@ impl ServerConfig {
             audit_file_path,
             enable_transit,
             lease_scan_interval_secs,
+            secret_usage_flush_interval_secs,
             disable_mlock,
             spring_oauth,
             cloud_database_url,
+            hsm,
+            dev_kms_key_path,
+            access_request_webhook,
+            tls: tls_from_env(settings),
+            acme: acme_from_env(settings),
+            clock_reference_url: settings
+                .var("ZVAULT_CLOCK_REFERENCE_URL")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            metrics_require_auth: settings
+                .var("ZVAULT_METRICS_REQUIRE_AUTH")
+                .is_ok_and(|v| v == "true" || v == "1"),
+            tls_client_certs: !settings
+                .var("ZVAULT_TLS_DISABLE_CLIENT_CERTS")
+                .is_ok_and(|v| v == "true" || v == "1"),
+            backup,
+            replication: replication_from_env(settings),
+            ha: ha_from_env(settings),
+            trusted_proxies: settings
+                .var("ZVAULT_TRUSTED_PROXIES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|cidr| cidr.trim().to_owned())
+                .filter(|cidr| !cidr.is_empty())
+                .collect(),
+            plugin_dir: settings
+                .var("ZVAULT_PLUGIN_DIR")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            dev: settings
+                .var("ZVAULT_DEV")
+                .is_ok_and(|v| v == "true" || v == "1"),
+        }
+    }
+
+    /// Switch to dev mode: in-memory storage, no `mlock`, plain HTTP, and a
+    /// Shamir seal, with HA, replication, and scheduled backups off. The
+    /// server initializes and unseals the vault itself at startup.
+    #[must_use]
+    pub fn into_dev(self) -> Self {
+        Self {
+            dev: true,
+            storage_backend: StorageBackendType::Memory,
+            disable_mlock: true,
+            tls: None,
+            acme: None,
+            dev_kms_key_path: None,
+            backup: None,
+            replication: None,
+            ha: None,
+            ..self
+        }
+    }
+}
+
+/// Storage backend selected by `ZVAULT_STORAGE`.
+fn storage_from_env(settings: &Settings, storage_path: String) -> StorageBackendType {
+    match settings
+        .var("ZVAULT_STORAGE")
+        .unwrap_or_else(|_| "memory".to_owned())
+        .to_lowercase()
+        .as_str()
+    {
+        "rocksdb" => StorageBackendType::RocksDb { path: storage_path },
+        "redb" => StorageBackendType::Redb { path: storage_path },
+        "postgres" | "postgresql" => {
+            let url = settings
+                .var("DATABASE_URL")
+                .unwrap_or_else(|_| "postgres://localhost/zvault".to_owned());
+            StorageBackendType::Postgres { url }
         }
+        "s3" => StorageBackendType::S3 {
+            bucket: settings.var("ZVAULT_S3_BUCKET").unwrap_or_default(),
+            region: settings
+                .var("ZVAULT_S3_REGION")
+                .unwrap_or_else(|_| "us-east-1".to_owned()),
+            endpoint: settings.var("ZVAULT_S3_ENDPOINT").ok(),
+            prefix: settings.var("ZVAULT_S3_PREFIX").unwrap_or_default(),
+            path_style: settings
+                .var("ZVAULT_S3_PATH_STYLE")
+                .is_ok_and(|v| v == "true" || v == "1"),
+            cache_path: settings.var("ZVAULT_S3_CACHE_PATH").ok(),
+        },
+        _ => StorageBackendType::Memory,
+    }
+}
+
+/// Backup settings — enabled when `ZVAULT_BACKUP_INTERVAL` is a positive
+/// number of seconds.
+fn backup_from_env(settings: &Settings, storage_path: &str) -> Option<BackupConfig> {
+    let interval_secs = settings
+        .var("ZVAULT_BACKUP_INTERVAL")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&secs| secs > 0)?;
+    let destination = match settings
+        .var("ZVAULT_BACKUP_S3_BUCKET")
+        .ok()
+        .filter(|v| !v.is_empty())
+    {
+        Some(bucket) => BackupDestination::S3 {
+            bucket,
+            region: settings
+                .var("ZVAULT_BACKUP_S3_REGION")
+                .unwrap_or_else(|_| "us-east-1".to_owned()),
+            endpoint: settings.var("ZVAULT_BACKUP_S3_ENDPOINT").ok(),
+            prefix: settings.var("ZVAULT_BACKUP_S3_PREFIX").unwrap_or_default(),
+            path_style: settings
+                .var("ZVAULT_BACKUP_S3_PATH_STYLE")
+                .is_ok_and(|v| v == "true" || v == "1"),
+        },
+        None => BackupDestination::Dir {
+            path: settings
+                .var("ZVAULT_BACKUP_DIR")
+                .unwrap_or_else(|_| format!("{storage_path}/backups")),
+        },
+    };
+    let keep = |var: &str, default: usize| {
+        settings
+            .var(var)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    };
+    Some(BackupConfig {
+        interval_secs,
+        destination,
+        // The newest backup is always kept.
+        keep_daily: keep("ZVAULT_BACKUP_KEEP_DAILY", 7).max(1),
+        keep_weekly: keep("ZVAULT_BACKUP_KEEP_WEEKLY", 4),
+    })
+}
+
+/// Replication settings — enabled when `ZVAULT_REPLICATION_MODE` is
+/// `primary` or `replica`. A missing secret or primary address is reported
+/// by the startup self-test.
+fn replication_from_env(settings: &Settings) -> Option<ReplicationConfig> {
+    let role = match settings
+        .var("ZVAULT_REPLICATION_MODE")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "primary" => ReplicationRole::Primary {
+            log_size: settings
+                .var("ZVAULT_REPLICATION_LOG_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(zvault_core::replication::DEFAULT_LOG_CAPACITY),
+        },
+        "replica" => ReplicationRole::Replica {
+            primary_addr: settings
+                .var("ZVAULT_REPLICATION_PRIMARY_ADDR")
+                .unwrap_or_default()
+                .trim_end_matches('/')
+                .to_owned(),
+        },
+        _ => return None,
+    };
+    Some(ReplicationConfig {
+        role,
+        secret: settings
+            .var("ZVAULT_REPLICATION_SECRET")
+            .unwrap_or_default(),
+    })
+}
+
+/// HA settings — enabled when `ZVAULT_HA_ENABLED` is `true`.
+fn ha_from_env(settings: &Settings) -> Option<HaConfig> {
+    if !settings
+        .var("ZVAULT_HA_ENABLED")
+        .is_ok_and(|v| v == "true" || v == "1")
+    {
+        return None;
     }
+    Some(HaConfig {
+        api_addr: settings
+            .var("ZVAULT_API_ADDR")
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_owned(),
+        lock_ttl_secs: settings
+            .var("ZVAULT_HA_LOCK_TTL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(15),
+    })
+}
+
+/// File TLS settings — enabled when both `ZVAULT_TLS_CERT` and `ZVAULT_TLS_KEY` are set.
+fn tls_from_env(settings: &Settings) -> Option<TlsFileConfig> {
+    let cert_path = settings
+        .var("ZVAULT_TLS_CERT")
+        .ok()
+        .filter(|v| !v.is_empty())?;
+    let key_path = settings
+        .var("ZVAULT_TLS_KEY")
+        .ok()
+        .filter(|v| !v.is_empty())?;
+    let hostnames: Vec<String> = settings
+        .var("ZVAULT_TLS_HOSTNAMES")
+        .unwrap_or_else(|_| "localhost,127.0.0.1".to_owned())
+        .split(',')
+        .map(|h| h.trim().to_lowercase())
+        .filter(|h| !h.is_empty())
+        .collect();
+    Some(TlsFileConfig {
+        cert_path,
+        key_path,
+        bootstrap: settings
+            .var("ZVAULT_TLS_BOOTSTRAP")
+            .is_ok_and(|v| v == "true" || v == "1"),
+        hostnames,
+    })
+}
+
+/// ACME settings — enabled when `ZVAULT_ACME_DOMAINS` lists at least one domain.
+fn acme_from_env(settings: &Settings) -> Option<AcmeServerConfig> {
+    let domains: Vec<String> = settings
+        .var("ZVAULT_ACME_DOMAINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|d| d.trim().to_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect();
+    if domains.is_empty() {
+        return None;
+    }
+
+    let dns_provider = settings
+        .var("ZVAULT_ACME_DNS_PROVIDER")
+        .is_ok_and(|v| v.eq_ignore_ascii_case("cloudflare"))
+        .then(|| AcmeDnsProvider::Cloudflare {
+            api_token: settings
+                .var("ZVAULT_ACME_CLOUDFLARE_API_TOKEN")
+                .unwrap_or_default(),
+            zone_id: settings
+                .var("ZVAULT_ACME_CLOUDFLARE_ZONE_ID")
+                .unwrap_or_default(),
+        });
+
+    Some(AcmeServerConfig {
+        domains,
+        email: settings
+            .var("ZVAULT_ACME_EMAIL")
+            .ok()
+            .filter(|v| !v.is_empty()),
+        directory_url: settings
+            .var("ZVAULT_ACME_DIRECTORY")
+            .unwrap_or_else(|_| LETS_ENCRYPT_DIRECTORY.to_owned()),
+        challenge: settings
+            .var("ZVAULT_ACME_CHALLENGE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(ChallengeType::Http01),
+        http_addr: settings
+            .var("ZVAULT_ACME_HTTP_ADDR")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 80))),
+        renew_before_days: settings
+            .var("ZVAULT_ACME_RENEW_BEFORE_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&days| days > 0)
+            .unwrap_or(30),
+        dns_provider,
+    })
 }
@@ -0,0 +1,436 @@
+//! Server configuration file for `ZVault`.
+//!
+//! `zvault-server --config zvault.toml` (or `ZVAULT_CONFIG`) reads settings
+//! from a TOML file, from HCL when the name ends in `.hcl`, or from JSON when
+//! it ends in `.json`. Every setting
+//! stands for one of the documented environment variables, grouped by section
+//! (`[listener] address` is `ZVAULT_BIND_ADDR`), and an environment variable
+//! that is set still wins over the file. Unknown keys are rejected so a typo
+//! cannot silently fall back to a default. Lists are joined with commas.
+//!
+//! Two sections have no environment equivalent: `[[audit]]` declares audit
+//! devices enabled at startup, and `[[mount]]` declares secrets engines
+//! created on unseal when missing. In HCL these are repeated `audit { ... }`
+//! and `mount { ... }` blocks.
+//!
+//! On `SIGHUP` the server re-reads the file and applies its log level, audit
+//! devices, and mounts; TLS certificates are reloaded by the listener.
+//! Everything else takes effect on restart.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, anyhow, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use zvault_core::audit::AuditDevice;
+
+/// Settings keys and the environment variables they stand for.
+const SETTINGS: &[(&str, &str)] = &[
+    ("log_level", "ZVAULT_LOG_LEVEL"),
+    ("audit_file", "ZVAULT_AUDIT_FILE"),
+    ("disable_mlock", "ZVAULT_DISABLE_MLOCK"),
+    ("enable_transit", "ZVAULT_ENABLE_TRANSIT"),
+    ("lease_scan_interval", "ZVAULT_LEASE_SCAN_INTERVAL"),
+    (
+        "secret_usage_flush_interval",
+        "ZVAULT_SECRET_USAGE_FLUSH_INTERVAL",
+    ),
+    ("plugin_dir", "ZVAULT_PLUGIN_DIR"),
+    ("access_request_webhook", "ZVAULT_ACCESS_REQUEST_WEBHOOK"),
+    ("clock_reference_url", "ZVAULT_CLOCK_REFERENCE_URL"),
+    ("listener.address", "ZVAULT_BIND_ADDR"),
+    ("listener.tls_cert", "ZVAULT_TLS_CERT"),
+    ("listener.tls_key", "ZVAULT_TLS_KEY"),
+    ("listener.tls_bootstrap", "ZVAULT_TLS_BOOTSTRAP"),
+    ("listener.tls_hostnames", "ZVAULT_TLS_HOSTNAMES"),
+    (
+        "listener.tls_disable_client_certs",
+        "ZVAULT_TLS_DISABLE_CLIENT_CERTS",
+    ),
+    ("listener.trusted_proxies", "ZVAULT_TRUSTED_PROXIES"),
+    ("storage.type", "ZVAULT_STORAGE"),
+    ("storage.path", "ZVAULT_STORAGE_PATH"),
+    ("storage.url", "DATABASE_URL"),
+    ("storage.bucket", "ZVAULT_S3_BUCKET"),
+    ("storage.region", "ZVAULT_S3_REGION"),
+    ("storage.endpoint", "ZVAULT_S3_ENDPOINT"),
+    ("storage.prefix", "ZVAULT_S3_PREFIX"),
+    ("storage.path_style", "ZVAULT_S3_PATH_STYLE"),
+    ("storage.cache_path", "ZVAULT_S3_CACHE_PATH"),
+    ("seal.type", "ZVAULT_SEAL"),
+    ("seal.key_file", "ZVAULT_DEV_KMS_KEY"),
+    (
+        "telemetry.metrics_require_auth",
+        "ZVAULT_METRICS_REQUIRE_AUTH",
+    ),
+    ("hsm.module", "ZVAULT_HSM_MODULE"),
+    ("hsm.slot", "ZVAULT_HSM_SLOT"),
+    ("hsm.pin", "ZVAULT_HSM_PIN"),
+    ("hsm.tool", "ZVAULT_HSM_TOOL"),
+    ("ha.enabled", "ZVAULT_HA_ENABLED"),
+    ("ha.api_addr", "ZVAULT_API_ADDR"),
+    ("ha.lock_ttl", "ZVAULT_HA_LOCK_TTL"),
+    ("replication.mode", "ZVAULT_REPLICATION_MODE"),
+    ("replication.secret", "ZVAULT_REPLICATION_SECRET"),
+    (
+        "replication.primary_addr",
+        "ZVAULT_REPLICATION_PRIMARY_ADDR",
+    ),
+    ("replication.log_size", "ZVAULT_REPLICATION_LOG_SIZE"),
+    ("backup.interval", "ZVAULT_BACKUP_INTERVAL"),
+    ("backup.dir", "ZVAULT_BACKUP_DIR"),
+    ("backup.s3_bucket", "ZVAULT_BACKUP_S3_BUCKET"),
+    ("backup.s3_region", "ZVAULT_BACKUP_S3_REGION"),
+    ("backup.s3_endpoint", "ZVAULT_BACKUP_S3_ENDPOINT"),
+    ("backup.s3_prefix", "ZVAULT_BACKUP_S3_PREFIX"),
+    ("backup.s3_path_style", "ZVAULT_BACKUP_S3_PATH_STYLE"),
+    ("backup.keep_daily", "ZVAULT_BACKUP_KEEP_DAILY"),
+    ("backup.keep_weekly", "ZVAULT_BACKUP_KEEP_WEEKLY"),
+    ("acme.domains", "ZVAULT_ACME_DOMAINS"),
+    ("acme.email", "ZVAULT_ACME_EMAIL"),
+    ("acme.directory", "ZVAULT_ACME_DIRECTORY"),
+    ("acme.challenge", "ZVAULT_ACME_CHALLENGE"),
+    ("acme.http_addr", "ZVAULT_ACME_HTTP_ADDR"),
+    ("acme.renew_before_days", "ZVAULT_ACME_RENEW_BEFORE_DAYS"),
+    ("acme.dns_provider", "ZVAULT_ACME_DNS_PROVIDER"),
+    (
+        "acme.cloudflare_api_token",
+        "ZVAULT_ACME_CLOUDFLARE_API_TOKEN",
+    ),
+    ("acme.cloudflare_zone_id", "ZVAULT_ACME_CLOUDFLARE_ZONE_ID"),
+    ("spring_oauth.auth_url", "SPRING_AUTH_URL"),
+    ("spring_oauth.client_id", "SPRING_CLIENT_ID"),
+    ("spring_oauth.client_secret", "SPRING_CLIENT_SECRET"),
+    ("spring_oauth.redirect_uri", "SPRING_REDIRECT_URI"),
+    ("spring_oauth.default_policy", "SPRING_DEFAULT_POLICY"),
+    ("spring_oauth.admin_policy", "SPRING_ADMIN_POLICY"),
+    ("cloud.database_url", "CLOUD_DATABASE_URL"),
+];
+
+/// A parsed configuration file.
+#[derive(Clone, Default)]
+pub struct ConfigFile {
+    /// Setting values keyed by the environment variable they stand for.
+    pub settings: HashMap<String, String>,
+    /// Audit devices to enable at startup.
+    pub audit: Vec<AuditDevice>,
+    /// Secrets engines to mount on unseal when missing.
+    pub mounts: Vec<MountDecl>,
+}
+
+/// A `[[mount]]` entry.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MountDecl {
+    /// Mount path (e.g., `team-a/`).
+    pub path: String,
+    /// Engine type: `kv`, or a plugin from the catalog.
+    #[serde(rename = "type")]
+    pub engine_type: String,
+    /// Human-readable description.
+    #[serde(default)]
+    pub description: String,
+    /// Engine-specific configuration, as for `POST /v1/sys/mounts/{path}`.
+    #[serde(default)]
+    pub config: Value,
+}
+
+impl std::fmt::Debug for ConfigFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Values may hold credentials (replication secret, HSM PIN).
+        let mut keys: Vec<&String> = self.settings.keys().collect();
+        keys.sort();
+        f.debug_struct("ConfigFile")
+            .field("settings", &keys)
+            .field("audit", &self.audit.len())
+            .field("mounts", &self.mounts)
+            .finish()
+    }
+}
+
+/// Read and parse the configuration file at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, does not parse, or contains
+/// an unknown or malformed setting.
+pub fn load(path: &Path) -> anyhow::Result<ConfigFile> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let format = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Format::Json,
+        Some("hcl") => Format::Hcl,
+        _ => Format::Toml,
+    };
+    parse(&text, format).with_context(|| format!("invalid configuration in {}", path.display()))
+}
+
+/// Syntax of a configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// TOML, the default.
+    Toml,
+    /// HCL, for files ending in `.hcl`.
+    Hcl,
+    /// JSON, for files ending in `.json`.
+    Json,
+}
+
+/// Parse configuration `text` written in `format`.
+///
+/// # Errors
+///
+/// Returns an error if the text does not parse or contains an unknown or
+/// malformed setting.
+pub fn parse(text: &str, format: Format) -> anyhow::Result<ConfigFile> {
+    let value = match format {
+        Format::Json => serde_json::from_str(text)?,
+        Format::Hcl => hcl::from_str(text)?,
+        Format::Toml => {
+            let document: toml_edit::DocumentMut = text.parse()?;
+            table_to_json(document.as_table())
+        }
+    };
+    let Value::Object(root) = value else {
+        bail!("the configuration must be a table");
+    };
+
+    let mut file = ConfigFile::default();
+    for (key, value) in root {
+        match key.as_str() {
+            "audit" => {
+                file.audit = serde_json::from_value(blocks(value)).context("invalid [[audit]]")?;
+            }
+            "mount" => {
+                file.mounts = serde_json::from_value(blocks(value)).context("invalid [[mount]]")?;
+            }
+            _ => match value {
+                Value::Object(section) => {
+                    for (name, value) in section {
+                        file.set(&format!("{key}.{name}"), value)?;
+                    }
+                }
+                value => file.set(&key, value)?,
+            },
+        }
+    }
+    Ok(file)
+}
+
+impl ConfigFile {
+    fn set(&mut self, key: &str, value: Value) -> anyhow::Result<()> {
+        let (_, var) = SETTINGS
+            .iter()
+            .find(|(name, _)| *name == key)
+            .ok_or_else(|| anyhow!("unknown setting '{key}'"))?;
+        let text = match value {
+            Value::Array(items) => items
+                .into_iter()
+                .map(|item| scalar(key, item))
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .join(","),
+            value => scalar(key, value)?,
+        };
+        self.settings.insert((*var).to_owned(), text);
+        Ok(())
+    }
+}
+
+/// A list of blocks. HCL gives a lone block as a table rather than a list
+/// of one.
+fn blocks(value: Value) -> Value {
+    match value {
+        Value::Object(_) => Value::Array(vec![value]),
+        value => value,
+    }
+}
+
+fn scalar(key: &str, value: Value) -> anyhow::Result<String> {
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        _ => bail!("setting '{key}' must be a string, number, boolean, or list of those"),
+    }
+}
+
+fn table_to_json(table: &toml_edit::Table) -> Value {
+    let mut object = serde_json::Map::new();
+    for (key, item) in table {
+        let value = match item {
+            toml_edit::Item::None => continue,
+            toml_edit::Item::Value(value) => value_to_json(value),
+            toml_edit::Item::Table(table) => table_to_json(table),
+            toml_edit::Item::ArrayOfTables(tables) => {
+                Value::Array(tables.iter().map(table_to_json).collect())
+            }
+        };
+        object.insert(key.to_owned(), value);
+    }
+    Value::Object(object)
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+        toml_edit::Value::Array(items) => Value::Array(items.iter().map(value_to_json).collect()),
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_owned(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+log_level = "debug"
+
+[listener]
+address = "0.0.0.0:8200"
+trusted_proxies = ["10.0.0.0/8", "192.168.0.0/16"]
+
+[ha]
+enabled = true
+lock_ttl = 15
+
+[[audit]]
+path = "file"
+type = "file"
+options = { file_path = "/var/log/zvault/audit.log" }
+
+[[mount]]
+path = "team-a"
+type = "kv"
+
+[[mount]]
+path = "team-b"
+type = "kv"
+description = "Team B"
+"#;
+
+    const HCL: &str = r#"
+log_level = "debug"
+
+listener {
+  address         = "0.0.0.0:8200"
+  trusted_proxies = ["10.0.0.0/8", "192.168.0.0/16"]
+}
+
+ha {
+  enabled  = true
+  lock_ttl = 15
+}
+
+audit {
+  path    = "file"
+  type    = "file"
+  options = { file_path = "/var/log/zvault/audit.log" }
+}
+
+mount {
+  path = "team-a"
+  type = "kv"
+}
+
+mount {
+  path        = "team-b"
+  type        = "kv"
+  description = "Team B"
+}
+"#;
+
+    fn setting<'a>(file: &'a ConfigFile, var: &str) -> &'a str {
+        file.settings.get(var).map_or("", String::as_str)
+    }
+
+    #[test]
+    fn sections_map_to_their_variables() {
+        let file = parse(TOML, Format::Toml).unwrap();
+        assert_eq!(setting(&file, "ZVAULT_LOG_LEVEL"), "debug");
+        assert_eq!(setting(&file, "ZVAULT_BIND_ADDR"), "0.0.0.0:8200");
+        assert_eq!(file.audit.len(), 1);
+        let mounts: Vec<&str> = file.mounts.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(mounts, ["team-a", "team-b"]);
+        assert_eq!(file.mounts[1].description, "Team B");
+    }
+
+    #[test]
+    fn values_are_coerced_to_strings() {
+        let file = parse(TOML, Format::Toml).unwrap();
+        assert_eq!(setting(&file, "ZVAULT_HA_ENABLED"), "true");
+        assert_eq!(setting(&file, "ZVAULT_HA_LOCK_TTL"), "15");
+        assert_eq!(
+            setting(&file, "ZVAULT_TRUSTED_PROXIES"),
+            "10.0.0.0/8,192.168.0.0/16"
+        );
+    }
+
+    #[test]
+    fn hcl_and_json_read_like_toml() {
+        let toml = parse(TOML, Format::Toml).unwrap();
+        let hcl = parse(HCL, Format::Hcl).unwrap();
+        assert_eq!(hcl.settings, toml.settings);
+        assert_eq!(hcl.audit.len(), 1);
+        assert_eq!(hcl.mounts.len(), 2);
+
+        let json = r#"{"storage": {"type": "memory"}, "mount": [{"path": "kv", "type": "kv"}]}"#;
+        let json = parse(json, Format::Json).unwrap();
+        assert_eq!(setting(&json, "ZVAULT_STORAGE"), "memory");
+        assert_eq!(json.mounts.len(), 1);
+    }
+
+    #[test]
+    fn a_single_hcl_block_is_a_list_of_one() {
+        let file = parse(
+            "mount {\n  path = \"kv\"\n  type = \"kv\"\n}\n",
+            Format::Hcl,
+        )
+        .unwrap();
+        assert_eq!(file.mounts.len(), 1);
+        assert_eq!(file.mounts[0].path, "kv");
+    }
+
+    #[test]
+    fn bad_files_are_rejected() {
+        for (text, format, message) in [
+            (
+                "log_levle = \"info\"",
+                Format::Toml,
+                "unknown setting 'log_levle'",
+            ),
+            (
+                "[listener]\nport = 8200",
+                Format::Toml,
+                "unknown setting 'listener.port'",
+            ),
+            (
+                "[listener]\naddress = { host = \"a\" }",
+                Format::Toml,
+                "must be a string",
+            ),
+            ("log_level = [[1]]", Format::Toml, "must be a string"),
+            (
+                "[[mount]]\npath = \"kv\"",
+                Format::Toml,
+                "invalid [[mount]]",
+            ),
+            ("log_level = ", Format::Toml, ""),
+            ("listener {", Format::Hcl, ""),
+            ("[]", Format::Json, "must be a table"),
+        ] {
+            let err = parse(text, format).unwrap_err();
+            assert!(format!("{err:#}").contains(message), "{text}: {err:#}");
+        }
+    }
+}
@@ -1,16 +1,27 @@
 //! HTTP error types for `VaultRS` server.
 //!
 //! Maps domain errors from `zvault-core` into appropriate HTTP responses.
-//! Every error variant produces a JSON body with a machine-readable `error`
-//! field and a human-readable `message`.
+//! Every failure, whichever route produced it, has the same JSON body:
+//!
+//! ```json
+//! {"errors": ["permission denied"], "code": "permission_denied",
+//!  "message": "permission denied", "request_id": "…"}
+//! ```
+//!
+//! `code` is one of the [`ErrorCode`]s and fixes the status: a sealed vault
+//! is always `503 sealed`, a policy denial `403 permission_denied`, a missing
+//! resource `404 not_found`, and invalid input `400 invalid_request`.
 
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
 
 use zvault_core::error::{
-    AppRoleError, BarrierError, DatabaseError, EngineError, LeaseError, MountError, PkiError,
-    PolicyError, SealError, TokenError,
+    AccessRequestError, AcmeServerError, ActivityError, AppRoleError, AuditError, BarrierError,
+    CertAuthError, ControlGroupError, DatabaseError, EngineError, IdentityError, JwtAuthError,
+    LeaseError, LicenseError, MfaError, MigrateError, MountError, MountTransferError, NotifyError,
+    PkiError, PluginError, PolicyError, QuotaError, ReplicationError, RotationError, SealError,
+    SecretUsageError, SyncError, TokenError, WrappingError,
 };
 
 /// Application-level error returned from HTTP handlers.
@@ -30,56 +41,235 @@ pub enum AppError {
     Conflict(String),
     /// Internal server error.
     Internal(String),
+    /// The request cannot be served right now (e.g. no HA active node).
+    Unavailable(String),
+    /// The active license does not include a gated feature.
+    FeatureNotLicensed {
+        feature: String,
+        required_tier: String,
+        message: String,
+    },
+    /// The request needs valid MFA codes in the `X-Vault-MFA` header.
+    MfaRequired(String),
+    /// Policy grants the request once a control group approves it.
+    ControlGroupRequired(String),
+    /// A quota rejected the request; retry after the given delay if known.
+    RateLimited {
+        message: String,
+        retry_after_secs: Option<u64>,
+    },
+}
+
+impl std::fmt::Display for AppError {
+    /// The human-readable `message` of the error's response body.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sealed => write!(f, "vault is sealed"),
+            Self::Unauthorized(msg)
+            | Self::Forbidden(msg)
+            | Self::NotFound(msg)
+            | Self::BadRequest(msg)
+            | Self::Conflict(msg)
+            | Self::Internal(msg)
+            | Self::Unavailable(msg)
+            | Self::MfaRequired(msg)
+            | Self::FeatureNotLicensed { message: msg, .. }
+            | Self::ControlGroupRequired(msg)
+            | Self::RateLimited { message: msg, .. } => write!(f, "{msg}"),
+        }
+    }
+}
+
+/// Machine-readable error code of an error response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The vault is sealed (503).
+    Sealed,
+    /// No token, or an invalid or expired one (401).
+    Unauthenticated,
+    /// Policy denies the operation (403).
+    PermissionDenied,
+    /// The resource does not exist (404).
+    NotFound,
+    /// The request is malformed or its input invalid (400).
+    InvalidRequest,
+    /// The request conflicts with current state (409).
+    Conflict,
+    /// The server failed (500).
+    Internal,
+    /// The request cannot be served right now (503).
+    Unavailable,
+    /// The license does not include the feature (403).
+    FeatureNotLicensed,
+    /// Valid MFA codes are required (403).
+    MfaRequired,
+    /// A control group must approve the request (403).
+    ControlGroupRequired,
+    /// A quota rejected the request (429).
+    RateLimited,
+}
+
+impl ErrorCode {
+    /// HTTP status of responses with this code.
+    #[must_use]
+    pub fn status(self) -> StatusCode {
+        match self {
+            Self::Sealed | Self::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Unauthenticated => StatusCode::UNAUTHORIZED,
+            Self::PermissionDenied
+            | Self::FeatureNotLicensed
+            | Self::MfaRequired
+            | Self::ControlGroupRequired => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::InvalidRequest => StatusCode::BAD_REQUEST,
+            Self::Conflict => StatusCode::CONFLICT,
+            Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// The code for a failure with `status` that did not come with one,
+    /// such as a request the router or an extractor rejected.
+    #[must_use]
+    pub fn for_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Unauthenticated,
+            StatusCode::FORBIDDEN => Self::PermissionDenied,
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::CONFLICT => Self::Conflict,
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+            StatusCode::SERVICE_UNAVAILABLE => Self::Unavailable,
+            s if s.is_client_error() => Self::InvalidRequest,
+            _ => Self::Internal,
+        }
+    }
+}
+
+impl AppError {
+    /// The error's machine-readable code.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Sealed => ErrorCode::Sealed,
+            Self::Unauthorized(_) => ErrorCode::Unauthenticated,
+            Self::Forbidden(_) => ErrorCode::PermissionDenied,
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::BadRequest(_) => ErrorCode::InvalidRequest,
+            Self::Conflict(_) => ErrorCode::Conflict,
+            Self::Internal(_) => ErrorCode::Internal,
+            Self::Unavailable(_) => ErrorCode::Unavailable,
+            Self::FeatureNotLicensed { .. } => ErrorCode::FeatureNotLicensed,
+            Self::MfaRequired(_) => ErrorCode::MfaRequired,
+            Self::ControlGroupRequired(_) => ErrorCode::ControlGroupRequired,
+            Self::RateLimited { .. } => ErrorCode::RateLimited,
+        }
+    }
 }
 
 /// JSON error response body.
 #[derive(Serialize)]
 struct ErrorBody {
-    error: &'static str,
+    /// Human-readable messages, most important first.
+    errors: Vec<String>,
+    code: ErrorCode,
+    /// The first of `errors`.
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    required_tier: Option<String>,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_type, message) = match self {
-            Self::Sealed => (
-                StatusCode::SERVICE_UNAVAILABLE,
-                "sealed",
-                "vault is sealed".to_owned(),
-            ),
-            Self::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "unauthorized", msg),
-            Self::Forbidden(msg) => (StatusCode::FORBIDDEN, "forbidden", msg),
-            Self::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", msg),
-            Self::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg),
-            Self::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg),
-            Self::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", msg),
-        };
+        let code = self.code();
+        let message = self.to_string();
+        let mut feature = None;
+        let mut required_tier = None;
+        let mut retry_after = None;
+
+        match self {
+            Self::FeatureNotLicensed {
+                feature: f,
+                required_tier: t,
+                ..
+            } => {
+                feature = Some(f);
+                required_tier = Some(t);
+            }
+            Self::RateLimited {
+                retry_after_secs, ..
+            } => retry_after = retry_after_secs,
+            _ => {}
+        }
 
         let body = ErrorBody {
-            error: error_type,
+            errors: vec![message.clone()],
+            code,
             message,
+            request_id: crate::middleware::current_request_id(),
+            feature,
+            required_tier,
         };
 
-        (status, axum::Json(body)).into_response()
+        let mut response = (code.status(), axum::Json(body)).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(axum::http::header::RETRY_AFTER, secs.into());
+        }
+        response
     }
 }
 
+/// The error response for a failure with `status` that produced no body of
+/// its own, or only `message` as plain text.
+#[must_use]
+pub fn status_response(status: StatusCode, message: &str) -> Response {
+    let message = if message.trim().is_empty() {
+        status.canonical_reason().unwrap_or("error").to_lowercase()
+    } else {
+        message.trim().to_owned()
+    };
+    let body = ErrorBody {
+        errors: vec![message.clone()],
+        code: ErrorCode::for_status(status),
+        message,
+        request_id: crate::middleware::current_request_id(),
+        feature: None,
+        required_tier: None,
+    };
+    (status, axum::Json(body)).into_response()
+}
+
 impl From<SealError> for AppError {
     fn from(err: SealError) -> Self {
         match err {
             SealError::AlreadyInitialized
             | SealError::AlreadyUnsealed
-            | SealError::AlreadySealed => Self::Conflict(err.to_string()),
+            | SealError::AlreadySealed
+            | SealError::RekeyInProgress
+            | SealError::GenerateRootInProgress => Self::Conflict(err.to_string()),
 
             SealError::NotInitialized
             | SealError::InvalidConfig { .. }
             | SealError::InvalidShare { .. }
             | SealError::RecoveryFailed { .. }
-            | SealError::RootKeyDecryption { .. } => Self::BadRequest(err.to_string()),
+            | SealError::RootKeyDecryption { .. }
+            | SealError::NoRekeyInProgress
+            | SealError::RekeyNonceMismatch
+            | SealError::NoGenerateRootInProgress
+            | SealError::GenerateRootNonceMismatch => Self::BadRequest(err.to_string()),
 
-            SealError::Crypto(_) | SealError::Barrier(_) | SealError::Storage(_) => {
-                Self::Internal(err.to_string())
-            }
+            SealError::Barrier(BarrierError::Sealed) => Self::Sealed,
+
+            SealError::Crypto(_)
+            | SealError::Barrier(_)
+            | SealError::Storage(_)
+            | SealError::Wrapper { .. } => Self::Internal(err.to_string()),
         }
     }
 }
@@ -88,7 +278,9 @@ impl From<BarrierError> for AppError {
     fn from(err: BarrierError) -> Self {
         match err {
             BarrierError::Sealed => Self::Sealed,
-            BarrierError::Crypto(_) | BarrierError::Storage(_) => Self::Internal(err.to_string()),
+            BarrierError::Crypto(_) | BarrierError::Storage(_) | BarrierError::Keyring { .. } => {
+                Self::Internal(err.to_string())
+            }
         }
     }
 }
@@ -98,15 +290,11 @@ impl From<TokenError> for AppError {
         match err {
             TokenError::NotFound => Self::Unauthorized("invalid token".to_owned()),
             TokenError::Expired { .. } => Self::Unauthorized(err.to_string()),
-            TokenError::NotRenewable | TokenError::MaxTtlExceeded { .. } => {
-                Self::BadRequest(err.to_string())
-            }
-            TokenError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
-            },
+            TokenError::NotRenewable
+            | TokenError::MaxTtlExceeded { .. }
+            | TokenError::InvalidBoundCidr { .. }
+            | TokenError::BatchUnsupported { .. } => Self::BadRequest(err.to_string()),
+            TokenError::Barrier(inner) => inner.into(),
         }
     }
 }
@@ -119,12 +307,138 @@ impl From<PolicyError> for AppError {
             PolicyError::BuiltIn { .. } | PolicyError::Denied { .. } => {
                 Self::Forbidden(err.to_string())
             }
-            PolicyError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
-            },
+            PolicyError::ControlGroupRequired { .. } => Self::ControlGroupRequired(err.to_string()),
+            PolicyError::MfaRequired { .. } => Self::MfaRequired(err.to_string()),
+            PolicyError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<AccessRequestError> for AppError {
+    fn from(err: AccessRequestError) -> Self {
+        match err {
+            AccessRequestError::NotFound { .. } => Self::NotFound(err.to_string()),
+            AccessRequestError::InvalidRequest { .. } => Self::BadRequest(err.to_string()),
+            AccessRequestError::InvalidState { .. } => Self::Conflict(err.to_string()),
+            AccessRequestError::SelfApproval => Self::Forbidden(err.to_string()),
+            AccessRequestError::Internal { .. } => Self::Internal(err.to_string()),
+            AccessRequestError::Policy(inner) => inner.into(),
+            AccessRequestError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<ControlGroupError> for AppError {
+    fn from(err: ControlGroupError) -> Self {
+        match err {
+            ControlGroupError::NotFound { .. } => Self::NotFound(err.to_string()),
+            ControlGroupError::NotAuthorized { .. } | ControlGroupError::SelfApproval => {
+                Self::Forbidden(err.to_string())
+            }
+            ControlGroupError::NotApproved { .. } => Self::Conflict(err.to_string()),
+            ControlGroupError::Mismatch { .. } => Self::BadRequest(err.to_string()),
+            ControlGroupError::Internal { .. } => Self::Internal(err.to_string()),
+            ControlGroupError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<IdentityError> for AppError {
+    fn from(err: IdentityError) -> Self {
+        match err {
+            IdentityError::EntityNotFound { .. }
+            | IdentityError::AliasNotFound { .. }
+            | IdentityError::GroupNotFound { .. } => Self::NotFound(err.to_string()),
+            IdentityError::NameInUse { .. } | IdentityError::AliasInUse { .. } => {
+                Self::Conflict(err.to_string())
+            }
+            IdentityError::EntityDisabled { .. } => Self::Forbidden(err.to_string()),
+            IdentityError::Invalid { .. } => Self::BadRequest(err.to_string()),
+            IdentityError::Internal { .. } => Self::Internal(err.to_string()),
+            IdentityError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<NotifyError> for AppError {
+    fn from(err: NotifyError) -> Self {
+        match err {
+            NotifyError::NotFound { .. } => Self::NotFound(err.to_string()),
+            NotifyError::Invalid { .. } | NotifyError::Delivery { .. } => {
+                Self::BadRequest(err.to_string())
+            }
+            NotifyError::Internal { .. } => Self::Internal(err.to_string()),
+            NotifyError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<RotationError> for AppError {
+    fn from(err: RotationError) -> Self {
+        match err {
+            RotationError::NotFound { .. } => Self::NotFound(err.to_string()),
+            RotationError::Invalid { .. } | RotationError::Failed { .. } => {
+                Self::BadRequest(err.to_string())
+            }
+            RotationError::Internal { .. } => Self::Internal(err.to_string()),
+            RotationError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<SyncError> for AppError {
+    fn from(err: SyncError) -> Self {
+        match err {
+            SyncError::NotFound { .. } => Self::NotFound(err.to_string()),
+            SyncError::Invalid { .. } | SyncError::Failed { .. } => {
+                Self::BadRequest(err.to_string())
+            }
+            SyncError::Internal { .. } => Self::Internal(err.to_string()),
+            SyncError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<MigrateError> for AppError {
+    fn from(err: MigrateError) -> Self {
+        match err {
+            MigrateError::Invalid { .. } | MigrateError::Aws { .. } => {
+                Self::BadRequest(err.to_string())
+            }
+            MigrateError::Conflict { .. } => Self::Conflict(err.to_string()),
+            MigrateError::Engine(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<MfaError> for AppError {
+    fn from(err: MfaError) -> Self {
+        match err {
+            MfaError::MethodNotFound { .. } | MfaError::EnforcementNotFound { .. } => {
+                Self::NotFound(err.to_string())
+            }
+            MfaError::NotEnrolled { .. }
+            | MfaError::InvalidCode { .. }
+            | MfaError::Required { .. } => Self::MfaRequired(err.to_string()),
+            MfaError::AlreadyEnrolled { .. } | MfaError::InUse { .. } => {
+                Self::Conflict(err.to_string())
+            }
+            MfaError::Invalid { .. } => Self::BadRequest(err.to_string()),
+            MfaError::Internal { .. } => Self::Internal(err.to_string()),
+            MfaError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<AuditError> for AppError {
+    fn from(err: AuditError) -> Self {
+        match err {
+            AuditError::DeviceExists { .. } => Self::Conflict(err.to_string()),
+            AuditError::DeviceNotFound { .. } => Self::NotFound(err.to_string()),
+            AuditError::InvalidConfig { .. } => Self::BadRequest(err.to_string()),
+            AuditError::AllBackendsFailed
+            | AuditError::BackendFailure { .. }
+            | AuditError::Serialization { .. } => Self::Internal(err.to_string()),
         }
     }
 }
@@ -137,12 +451,7 @@ impl From<MountError> for AppError {
             MountError::InvalidPath { .. } | MountError::UnknownEngineType { .. } => {
                 Self::BadRequest(err.to_string())
             }
-            MountError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
-            },
+            MountError::Barrier(inner) => inner.into(),
         }
     }
 }
@@ -152,13 +461,13 @@ impl From<EngineError> for AppError {
         match err {
             EngineError::NotFound { .. } => Self::NotFound(err.to_string()),
             EngineError::InvalidRequest { .. } => Self::BadRequest(err.to_string()),
-            EngineError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
-            },
-            EngineError::Internal { .. } => Self::Internal(err.to_string()),
+            EngineError::CasMismatch { .. } | EngineError::AlreadyExists { .. } => {
+                Self::Conflict(err.to_string())
+            }
+            EngineError::Barrier(inner) => inner.into(),
+            EngineError::Internal { .. } | EngineError::Hsm { .. } => {
+                Self::Internal(err.to_string())
+            }
         }
     }
 }
@@ -167,15 +476,80 @@ impl From<LeaseError> for AppError {
     fn from(err: LeaseError) -> Self {
         match err {
             LeaseError::NotFound { .. } => Self::NotFound(err.to_string()),
-            LeaseError::Expired { .. } | LeaseError::NotRenewable { .. } => {
+            LeaseError::Expired { .. }
+            | LeaseError::NotRenewable { .. }
+            | LeaseError::MaxTtlReached { .. } => Self::BadRequest(err.to_string()),
+            LeaseError::RevocationFailed { .. } => Self::Internal(err.to_string()),
+            LeaseError::QuotaExceeded { .. } => Self::RateLimited {
+                message: err.to_string(),
+                retry_after_secs: None,
+            },
+            LeaseError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<LicenseError> for AppError {
+    fn from(err: LicenseError) -> Self {
+        match err {
+            LicenseError::Invalid { .. } | LicenseError::Expired { .. } => {
                 Self::BadRequest(err.to_string())
             }
-            LeaseError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
+            LicenseError::FeatureNotLicensed {
+                feature,
+                required_tier,
+                ..
+            } => Self::FeatureNotLicensed {
+                feature: feature.to_string(),
+                required_tier: required_tier.to_string(),
+                message: err.to_string(),
             },
+            LicenseError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<ActivityError> for AppError {
+    fn from(err: ActivityError) -> Self {
+        match err {
+            ActivityError::InvalidRange { .. } => Self::BadRequest(err.to_string()),
+            ActivityError::Internal { .. } => Self::Internal(err.to_string()),
+            ActivityError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<SecretUsageError> for AppError {
+    fn from(err: SecretUsageError) -> Self {
+        match err {
+            SecretUsageError::Internal { .. } => Self::Internal(err.to_string()),
+            SecretUsageError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<WrappingError> for AppError {
+    fn from(err: WrappingError) -> Self {
+        match err {
+            WrappingError::InvalidToken | WrappingError::InvalidTtl { .. } => {
+                Self::BadRequest(err.to_string())
+            }
+            WrappingError::Internal { .. } => Self::Internal(err.to_string()),
+            WrappingError::Token(inner) => inner.into(),
+            WrappingError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<MountTransferError> for AppError {
+    fn from(err: MountTransferError) -> Self {
+        match err {
+            MountTransferError::InvalidTransferKey { .. }
+            | MountTransferError::InvalidBundle { .. } => Self::BadRequest(err.to_string()),
+            MountTransferError::TargetNotEmpty { .. } => Self::Conflict(err.to_string()),
+            MountTransferError::Internal { .. } => Self::Internal(err.to_string()),
+            MountTransferError::Mount(inner) => inner.into(),
+            MountTransferError::Barrier(inner) => inner.into(),
         }
     }
 }
@@ -183,17 +557,16 @@ impl From<LeaseError> for AppError {
 impl From<DatabaseError> for AppError {
     fn from(err: DatabaseError) -> Self {
         match err {
-            DatabaseError::NotFound { .. } | DatabaseError::RoleNotFound { .. } => {
-                Self::NotFound(err.to_string())
+            DatabaseError::NotFound { .. }
+            | DatabaseError::RoleNotFound { .. }
+            | DatabaseError::StaticRoleNotFound { .. } => Self::NotFound(err.to_string()),
+            DatabaseError::InvalidConfig { .. } | DatabaseError::Statement { .. } => {
+                Self::BadRequest(err.to_string())
             }
-            DatabaseError::InvalidConfig { .. } => Self::BadRequest(err.to_string()),
-            DatabaseError::Internal { .. } => Self::Internal(err.to_string()),
-            DatabaseError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
-            },
+            DatabaseError::Connection { .. } | DatabaseError::Internal { .. } => {
+                Self::Internal(err.to_string())
+            }
+            DatabaseError::Barrier(inner) => inner.into(),
         }
     }
 }
@@ -206,12 +579,52 @@ impl From<PkiError> for AppError {
             PkiError::CertGeneration { .. } | PkiError::Internal { .. } => {
                 Self::Internal(err.to_string())
             }
-            PkiError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
-            },
+            PkiError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<AcmeServerError> for AppError {
+    fn from(err: AcmeServerError) -> Self {
+        match err {
+            AcmeServerError::Pki(inner) => inner.into(),
+            AcmeServerError::Barrier(BarrierError::Sealed) => Self::Sealed,
+            AcmeServerError::Disabled | AcmeServerError::NotFound { .. } => {
+                Self::NotFound(err.to_string())
+            }
+            AcmeServerError::Unauthorized { .. } => Self::Forbidden(err.to_string()),
+            AcmeServerError::Internal { .. } | AcmeServerError::Barrier(_) => {
+                Self::Internal(err.to_string())
+            }
+            _ => Self::BadRequest(err.to_string()),
+        }
+    }
+}
+
+impl From<JwtAuthError> for AppError {
+    fn from(err: JwtAuthError) -> Self {
+        match err {
+            JwtAuthError::RoleNotFound { .. } | JwtAuthError::NotConfigured => {
+                Self::NotFound(err.to_string())
+            }
+            JwtAuthError::InvalidToken { .. } => Self::Unauthorized(err.to_string()),
+            JwtAuthError::InvalidConfig { .. } => Self::BadRequest(err.to_string()),
+            JwtAuthError::Jwks { .. } | JwtAuthError::Internal { .. } => {
+                Self::Internal(err.to_string())
+            }
+            JwtAuthError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<CertAuthError> for AppError {
+    fn from(err: CertAuthError) -> Self {
+        match err {
+            CertAuthError::RoleNotFound { .. } => Self::NotFound(err.to_string()),
+            CertAuthError::InvalidCertificate { .. } => Self::Unauthorized(err.to_string()),
+            CertAuthError::InvalidConfig { .. } => Self::BadRequest(err.to_string()),
+            CertAuthError::Internal { .. } => Self::Internal(err.to_string()),
+            CertAuthError::Barrier(inner) => inner.into(),
         }
     }
 }
@@ -221,14 +634,72 @@ impl From<AppRoleError> for AppError {
         match err {
             AppRoleError::RoleNotFound { .. } => Self::NotFound(err.to_string()),
             AppRoleError::InvalidSecretId { .. } => Self::Unauthorized(err.to_string()),
+            AppRoleError::ClientNotAllowed { .. } => Self::Forbidden(err.to_string()),
             AppRoleError::InvalidConfig { .. } => Self::BadRequest(err.to_string()),
             AppRoleError::Internal { .. } => Self::Internal(err.to_string()),
-            AppRoleError::Barrier(ref inner) => match inner {
-                BarrierError::Sealed => Self::Sealed,
-                BarrierError::Crypto(_) | BarrierError::Storage(_) => {
-                    Self::Internal(err.to_string())
-                }
+            AppRoleError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<QuotaError> for AppError {
+    fn from(err: QuotaError) -> Self {
+        match err {
+            QuotaError::NotFound { .. } => Self::NotFound(err.to_string()),
+            QuotaError::InvalidConfig { .. } => Self::BadRequest(err.to_string()),
+            QuotaError::RateLimited {
+                retry_after_secs, ..
+            } => Self::RateLimited {
+                message: err.to_string(),
+                retry_after_secs: Some(retry_after_secs),
             },
+            QuotaError::Internal { .. } => Self::Internal(err.to_string()),
+            QuotaError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+impl From<ReplicationError> for AppError {
+    fn from(err: ReplicationError) -> Self {
+        match err {
+            ReplicationError::ResyncRequired { .. } => Self::Conflict(err.to_string()),
+            ReplicationError::Invalid { .. } => Self::BadRequest(err.to_string()),
+            ReplicationError::Storage(_) => Self::Internal(err.to_string()),
+        }
+    }
+}
+
+impl From<PluginError> for AppError {
+    fn from(err: PluginError) -> Self {
+        match err {
+            PluginError::NotFound { .. } | PluginError::PathNotFound { .. } => {
+                Self::NotFound(err.to_string())
+            }
+            PluginError::Invalid { .. }
+            | PluginError::Disabled
+            | PluginError::ChecksumMismatch { .. }
+            | PluginError::InvalidRequest { .. } => Self::BadRequest(err.to_string()),
+            PluginError::Timeout { .. } => Self::Unavailable(err.to_string()),
+            PluginError::Launch { .. }
+            | PluginError::Protocol { .. }
+            | PluginError::Failed { .. } => Self::Internal(err.to_string()),
+            PluginError::Barrier(inner) => inner.into(),
+        }
+    }
+}
+
+#[cfg(feature = "cloud")]
+impl From<crate::cloud::error::CloudError> for AppError {
+    fn from(err: crate::cloud::error::CloudError) -> Self {
+        use crate::cloud::error::CloudError;
+        match err {
+            CloudError::Unauthorized(msg) => Self::Unauthorized(msg),
+            CloudError::Forbidden(msg) | CloudError::LimitExceeded(msg) => Self::Forbidden(msg),
+            CloudError::NotFound(msg) => Self::NotFound(msg),
+            CloudError::BadRequest(msg) => Self::BadRequest(msg),
+            CloudError::Conflict(msg) => Self::Conflict(msg),
+            CloudError::KeyUnavailable(msg) => Self::Unavailable(msg),
+            CloudError::Internal(msg) => Self::Internal(msg),
         }
     }
 }
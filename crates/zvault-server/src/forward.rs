@@ -0,0 +1,105 @@
+//! Request forwarding to another server.
+//!
+//! A replica forwards requests that may write to its primary, and an HA
+//! standby forwards them to the active node. That includes reads whose
+//! handlers write, such as KV secret reads, which count the read and may
+//! issue a lease. Either way the request is
+//! replayed as-is — headers (token included) and body — and the upstream
+//! response is relayed back, so the caller cannot tell it was forwarded.
+//! `X-Forwarded-For` is replaced by the client address this server settled
+//! on, which the upstream believes if it lists this server as a trusted
+//! proxy.
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{HeaderName, Method, header};
+use axum::response::{IntoResponse, Response};
+use tracing::warn;
+
+use zvault_core::mount::MountManager;
+
+use crate::error::AppError;
+use crate::middleware::RequestInfo;
+use crate::routes::sys::MAX_SNAPSHOT_BODY;
+
+/// Largest request body forwarded: that of a restore or mount import.
+const MAX_FORWARD_BODY: usize = MAX_SNAPSHOT_BODY;
+
+/// Headers that describe a single connection and are not forwarded.
+const HOP_BY_HOP: [HeaderName; 5] = [
+    header::CONNECTION,
+    header::CONTENT_LENGTH,
+    header::HOST,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+/// Whether a request to `/v1/<path>` is handled locally rather than
+/// forwarded: reads, and the init, seal, health, leader, and replication
+/// endpoints, which describe this server itself. (An uninitialized cluster
+/// has no active node to forward `sys/init` to.)
+#[must_use]
+pub fn serves_locally(method: &Method, path: &str) -> bool {
+    method == Method::GET
+        || method == Method::HEAD
+        || method == Method::OPTIONS
+        || matches!(
+            path,
+            "sys/init" | "sys/seal" | "sys/unseal" | "sys/health" | "sys/leader"
+        )
+        || path.starts_with("sys/replication/")
+}
+
+/// Whether a read of `/v1/<path>` writes to storage, and so must be
+/// forwarded like a write: KV secret reads record the read for usage
+/// analytics and, on mounts with `lease_reads`, issue a read lease.
+pub async fn read_writes(mounts: &MountManager, path: &str) -> bool {
+    mounts
+        .resolve(path)
+        .await
+        .is_some_and(|(mount, rest)| mount.engine_type == "kv" && rest.starts_with("data/"))
+}
+
+/// Send `req` to the server at `addr` (a base URL) and relay its response.
+/// `target` names the server in logs and errors.
+pub async fn forward(client: &reqwest::Client, addr: &str, target: &str, req: Request) -> Response {
+    match try_forward(client, addr, req).await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!(error = %format!("{e:#}"), addr, "failed to forward request to {target}");
+            AppError::Internal(format!("failed to forward request to the {target}")).into_response()
+        }
+    }
+}
+
+async fn try_forward(
+    client: &reqwest::Client,
+    addr: &str,
+    req: Request,
+) -> anyhow::Result<Response> {
+    let client_ip = RequestInfo::of(&req).client_ip;
+    let (parts, body) = req.into_parts();
+    let path = parts.uri.path_and_query().map_or("/", |p| p.as_str());
+    let body = axum::body::to_bytes(body, MAX_FORWARD_BODY).await?;
+
+    let mut request = client
+        .request(parts.method, format!("{addr}{path}"))
+        .body(body);
+    for (name, value) in &parts.headers {
+        if !HOP_BY_HOP.contains(name) && name != "x-forwarded-for" {
+            request = request.header(name, value);
+        }
+    }
+    if let Some(ip) = client_ip {
+        request = request.header("x-forwarded-for", ip.to_string());
+    }
+    let upstream = request.send().await?;
+
+    let mut response = Response::builder().status(upstream.status());
+    for (name, value) in upstream.headers() {
+        if !HOP_BY_HOP.contains(name) {
+            response = response.header(name, value);
+        }
+    }
+    Ok(response.body(Body::from(upstream.bytes().await?))?)
+}
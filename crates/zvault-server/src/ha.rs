@@ -0,0 +1,277 @@
+//! Active/standby high availability.
+//!
+//! With `ZVAULT_HA_ENABLED=true`, several servers share one storage backend
+//! and elect a single active node through a lock held in that storage (see
+//! [`StorageBackend::acquire_lock`]). The active node renews the lock every
+//! third of `ZVAULT_HA_LOCK_TTL`; if it dies, a standby takes the lock once
+//! it expires. Only unsealed servers compete for the lock, and an active
+//! node that is sealed gives it up at once.
+//!
+//! Standbys read the shared storage themselves and serve reads locally; any
+//! other request to `/v1/` is forwarded to the active node at the address it
+//! advertises in the lock (`ZVAULT_API_ADDR`). Lease expiry, access grant
+//! expiry, rotation, and scheduled backups run on the active node only.
+//! `GET /v1/sys/leader` reports which node is active.
+//!
+//! HA needs an Enterprise license ([`Feature::Ha`]). The license is read
+//! from the barrier, so it is checked on every lock check rather than at
+//! startup: an unlicensed server competes for the lock no more than a
+//! sealed one does.
+//!
+//! A standby reloads the barrier keyring on every check, so it can read
+//! data encrypted under a key the active node rotated in. A standby that
+//! takes over reloads cached configuration (quotas, license) as it would
+//! on unseal.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, watch};
+use tracing::{info, warn};
+use zvault_core::license::Feature;
+use zvault_storage::{StorageBackend, StorageError};
+
+use crate::config::HaConfig;
+use crate::error::AppError;
+use crate::forward;
+use crate::state::AppState;
+
+/// Name of the storage lock the active node holds.
+pub const LOCK_NAME: &str = "ha/active";
+
+/// What the active node records as the lock's holder.
+#[derive(Debug, Serialize, Deserialize)]
+struct Holder {
+    /// Identifies the server process; changes on restart.
+    id: String,
+    /// Base URL standbys forward requests to.
+    addr: String,
+}
+
+/// Response body for `GET /v1/sys/leader`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LeaderStatus {
+    /// Whether HA is configured on this server.
+    pub ha_enabled: bool,
+    /// Whether this server is the active node.
+    pub is_self: bool,
+    /// Base URL of the active node, if there is one.
+    pub leader_address: Option<String>,
+    /// This server's HA node ID.
+    pub node_id: Option<String>,
+    /// When this server last became the active node, while it is.
+    pub active_since: Option<DateTime<Utc>>,
+    /// Error of the last lock check, if it failed.
+    pub last_error: Option<String>,
+}
+
+/// Competes for the active node's lock and forwards requests to the winner.
+pub struct HaCoordinator {
+    node_id: String,
+    holder: String,
+    lock_ttl: Duration,
+    storage: Arc<dyn StorageBackend>,
+    client: reqwest::Client,
+    active: AtomicBool,
+    /// Whether the last lock check found HA licensed, so the warning is
+    /// logged once per change.
+    licensed: AtomicBool,
+    /// When the lock was last taken or renewed, while active.
+    renewed_at: RwLock<Option<Instant>>,
+    status: RwLock<LeaderStatus>,
+}
+
+impl std::fmt::Debug for HaCoordinator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HaCoordinator")
+            .field("node_id", &self.node_id)
+            .field("active", &self.is_active())
+            .finish_non_exhaustive()
+    }
+}
+
+impl HaCoordinator {
+    /// Create a coordinator taking the lock in `storage`, which must be the
+    /// backend every server in the cluster shares.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the HTTP client cannot be built.
+    pub fn new(config: &HaConfig, storage: Arc<dyn StorageBackend>) -> anyhow::Result<Self> {
+        let node_id = uuid::Uuid::new_v4().to_string();
+        let holder = serde_json::to_string(&Holder {
+            id: node_id.clone(),
+            addr: config.api_addr.clone(),
+        })?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()?;
+        Ok(Self {
+            status: RwLock::new(LeaderStatus {
+                ha_enabled: true,
+                node_id: Some(node_id.clone()),
+                ..LeaderStatus::default()
+            }),
+            node_id,
+            holder,
+            lock_ttl: Duration::from_secs(config.lock_ttl_secs),
+            storage,
+            client,
+            active: AtomicBool::new(false),
+            licensed: AtomicBool::new(true),
+            renewed_at: RwLock::new(None),
+        })
+    }
+
+    /// Whether this server is the active node.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Current leader status, for `GET /v1/sys/leader`.
+    pub async fn status(&self) -> LeaderStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Compete for the lock until `shutdown` fires, then give it up if
+    /// held so a standby can take over without waiting for it to expire.
+    pub async fn run(&self, state: &AppState, shutdown: &mut watch::Receiver<bool>) {
+        info!(node_id = %self.node_id, lock_ttl = ?self.lock_ttl, "HA coordinator started");
+        loop {
+            self.check(state).await;
+            tokio::select! {
+                () = tokio::time::sleep(self.lock_ttl / 3) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+        if self.is_active() {
+            if let Err(e) = self.storage.release_lock(LOCK_NAME, &self.holder).await {
+                warn!(error = %e, "failed to release the active node lock");
+            }
+            self.active.store(false, Ordering::SeqCst);
+        }
+        info!("HA coordinator shutting down");
+    }
+
+    /// Take or renew the lock if unsealed and licensed (releasing it
+    /// otherwise), then record who holds it.
+    async fn check(&self, state: &AppState) {
+        let unsealed = state.barrier.is_unsealed().await;
+        let licensed = unsealed && self.licensed(state).await;
+        let result = if licensed {
+            self.storage
+                .acquire_lock(LOCK_NAME, &self.holder, self.lock_ttl)
+                .await
+                .map(Some)
+        } else {
+            self.give_up().await
+        };
+        match result {
+            Ok(holder) => self.record_holder(state, holder.as_deref(), unsealed).await,
+            Err(e) => self.record_error(&e).await,
+        }
+    }
+
+    /// Whether the active license unlocks HA, warning when it stops doing so.
+    async fn licensed(&self, state: &AppState) -> bool {
+        let result = state.license_manager.check(Feature::Ha).await;
+        let was_licensed = self.licensed.swap(result.is_ok(), Ordering::SeqCst);
+        if let Err(e) = &result {
+            if was_licensed {
+                warn!(error = %e, "HA is not licensed, not competing for the active node lock");
+            }
+        }
+        result.is_ok()
+    }
+
+    /// Release the lock if held, and return whoever holds it now.
+    async fn give_up(&self) -> Result<Option<String>, StorageError> {
+        if self.is_active() {
+            self.storage.release_lock(LOCK_NAME, &self.holder).await?;
+        }
+        self.storage.lock_holder(LOCK_NAME).await
+    }
+
+    async fn record_holder(&self, state: &AppState, holder: Option<&str>, unsealed: bool) {
+        let is_self = holder == Some(self.holder.as_str());
+        let was_active = self.active.swap(is_self, Ordering::SeqCst);
+        *self.renewed_at.write().await = is_self.then(Instant::now);
+
+        if is_self && !was_active {
+            info!(node_id = %self.node_id, "this server is now the active node");
+            reload_cached_state(state).await;
+        } else if !is_self && was_active {
+            warn!(node_id = %self.node_id, "this server is no longer the active node");
+        }
+        if !is_self && unsealed {
+            if let Err(e) = state.barrier.reload_keyring().await {
+                warn!(error = %e, "standby failed to reload the barrier keyring");
+            }
+        }
+
+        let leader_address = holder.map(|holder| {
+            serde_json::from_str::<Holder>(holder).map_or_else(|_| holder.to_owned(), |h| h.addr)
+        });
+        let mut status = self.status.write().await;
+        if leader_address != status.leader_address {
+            info!(leader = ?leader_address, "active node changed");
+        }
+        status.is_self = is_self;
+        status.leader_address = leader_address;
+        if !is_self {
+            status.active_since = None;
+        } else if !was_active {
+            status.active_since = Some(Utc::now());
+        }
+        status.last_error = None;
+    }
+
+    /// Record a failed lock check. An active node that has not renewed the
+    /// lock for a full TTL steps down: a standby may already hold it.
+    async fn record_error(&self, error: &StorageError) {
+        warn!(error = %error, "HA lock check failed");
+        let expired = self
+            .renewed_at
+            .read()
+            .await
+            .is_none_or(|at| at.elapsed() >= self.lock_ttl);
+        let mut status = self.status.write().await;
+        if expired && self.active.swap(false, Ordering::SeqCst) {
+            warn!(node_id = %self.node_id, "could not renew the active node lock, stepping down");
+            status.is_self = false;
+            status.leader_address = None;
+            status.active_since = None;
+        }
+        status.last_error = Some(error.to_string());
+    }
+
+    /// Send `req` to the active node and relay its response.
+    pub async fn forward(&self, req: Request) -> Response {
+        let leader = self.status.read().await.leader_address.clone();
+        match leader {
+            Some(addr) => forward::forward(&self.client, &addr, "active node", req).await,
+            None => AppError::Unavailable("no active node to forward the request to".to_owned())
+                .into_response(),
+        }
+    }
+}
+
+/// Reload state a standby may have cached before the previous active node
+/// changed it.
+async fn reload_cached_state(state: &AppState) {
+    if let Err(e) = state.barrier.reload_keyring().await {
+        warn!(error = %e, "failed to reload the barrier keyring");
+    }
+    if let Err(e) = state.license_manager.load().await {
+        warn!(error = %e, "failed to load license");
+    }
+    if let Err(e) = state.quotas.load().await {
+        warn!(error = %e, "failed to load quotas");
+    }
+}
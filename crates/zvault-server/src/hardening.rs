@@ -9,7 +9,19 @@
 //!    all current and future memory pages, preventing the OS from swapping
 //!    sensitive data (unseal keys, root keys, transit keys) to disk.
 //!
-//! Both functions are no-ops on non-Unix platforms.
+//! Where a measure does not exist — Windows, or `mlockall` on macOS, whose
+//! libc exports it but always fails — the functions return
+//! [`Hardening::Unsupported`] instead of an error, so the server starts
+//! cleanly and logs a note rather than a failure.
+
+/// Result of applying a hardening measure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hardening {
+    /// The measure is in effect.
+    Applied,
+    /// The platform does not provide the measure.
+    Unsupported,
+}
 
 /// Disable core dumps by setting `RLIMIT_CORE` to 0.
 ///
@@ -21,7 +33,7 @@
 ///
 /// Returns an error string if the `setrlimit` syscall fails.
 #[cfg(unix)]
-pub fn disable_core_dumps() -> Result<(), String> {
+pub fn disable_core_dumps() -> Result<Hardening, String> {
     // SAFETY: `setrlimit` is a POSIX syscall that sets resource limits for
     // the current process. We pass a valid `rlimit` struct with both fields
     // set to 0. This is a well-defined operation with no memory safety
@@ -37,19 +49,20 @@ pub fn disable_core_dumps() -> Result<(), String> {
     };
 
     if result == 0 {
-        Ok(())
+        Ok(Hardening::Applied)
     } else {
-        Err(format!(
-            "setrlimit(RLIMIT_CORE, 0) failed with errno {}",
-            std::io::Error::last_os_error()
-        ))
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::ENOSYS) {
+            return Ok(Hardening::Unsupported);
+        }
+        Err(format!("setrlimit(RLIMIT_CORE, 0) failed with errno {err}"))
     }
 }
 
-/// No-op on non-Unix platforms.
+/// No core dump limit to set on non-Unix platforms.
 #[cfg(not(unix))]
-pub fn disable_core_dumps() -> Result<(), String> {
-    Ok(())
+pub fn disable_core_dumps() -> Result<Hardening, String> {
+    Ok(Hardening::Unsupported)
 }
 
 /// Pin all current and future memory pages with `mlockall`.
@@ -61,9 +74,10 @@ pub fn disable_core_dumps() -> Result<(), String> {
 ///
 /// # Errors
 ///
-/// Returns an error string if the `mlockall` syscall fails.
-#[cfg(unix)]
-pub fn lock_memory() -> Result<(), String> {
+/// Returns an error string if the `mlockall` syscall fails for a reason
+/// other than being unimplemented (typically missing privileges).
+#[cfg(all(unix, not(target_vendor = "apple")))]
+pub fn lock_memory() -> Result<Hardening, String> {
     // SAFETY: `mlockall` is a POSIX syscall that locks all current and
     // future mapped pages into RAM. We pass `MCL_CURRENT | MCL_FUTURE`
     // which are well-defined flags. The call has no memory safety
@@ -73,17 +87,20 @@ pub fn lock_memory() -> Result<(), String> {
     let result = unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) };
 
     if result == 0 {
-        Ok(())
+        Ok(Hardening::Applied)
     } else {
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::ENOSYS) {
+            return Ok(Hardening::Unsupported);
+        }
         Err(format!(
-            "mlockall(MCL_CURRENT | MCL_FUTURE) failed with errno {}",
-            std::io::Error::last_os_error()
+            "mlockall(MCL_CURRENT | MCL_FUTURE) failed with errno {err}"
         ))
     }
 }
 
-/// No-op on non-Unix platforms.
-#[cfg(not(unix))]
-pub fn lock_memory() -> Result<(), String> {
-    Ok(())
+/// `mlockall` is a stub on Apple platforms and Windows has no equivalent.
+#[cfg(any(not(unix), target_vendor = "apple"))]
+pub fn lock_memory() -> Result<Hardening, String> {
+    Ok(Hardening::Unsupported)
 }
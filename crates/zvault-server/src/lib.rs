@@ -4,11 +4,19 @@
 //! running Axum server. Serves both the JSON API at `/v1/*` and the web UI
 //! at `/`.
 
+pub mod backup;
+pub mod build_info;
 #[cfg(feature = "cloud")]
 pub mod cloud;
 pub mod config;
+pub mod config_file;
 pub mod error;
+pub mod forward;
+pub mod ha;
 pub mod hardening;
 pub mod middleware;
+pub mod preflight;
+pub mod replication;
 pub mod routes;
 pub mod state;
+pub mod tls;
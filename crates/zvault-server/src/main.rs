@@ -1,103 +1,350 @@
 //! `ZVault` server entry point.
 //!
 //! Bootstraps the storage backend, barrier, seal manager, and all subsystems,
-//! then starts the Axum HTTP server with graceful shutdown. A background
-//! lease expiry worker runs alongside the server and is cancelled on shutdown.
+//! runs the startup self-test (see [`zvault_server::preflight`]; `--check-config`
+//! stops after printing it), then starts the Axum HTTP server with graceful
+//! shutdown. Background lease
+//! and access grant expiry workers, the secret usage flusher, notification
+//! delivery, secret rotation, GitHub and Kubernetes secret sync, scheduled
+//! backups, replication, HA leader election, and configuration file reload
+//! (see [`zvault_server::config_file`]) run alongside the server
+//! and are cancelled on shutdown. Replicas leave lease expiry, access grant
+//! expiry, rotation, and sync to their primary; HA standbys leave those and
+//! scheduled backups to the active node.
 
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
-use axum::Router;
 use axum::http::HeaderValue;
 use axum::middleware as axum_mw;
+use axum::{Extension, Router};
 use tokio::net::TcpListener;
-use tokio::sync::{RwLock, watch};
+use tokio::sync::{RwLock, broadcast, watch};
 use tracing::{info, warn};
 
+use zvault_core::access_request::AccessRequestStore;
+use zvault_core::acme::{self, AcmeClient, Http01Responder};
+use zvault_core::activity::ActivityLog;
 use zvault_core::approle::AppRoleStore;
 use zvault_core::audit::AuditManager;
 use zvault_core::audit_file::FileAuditBackend;
 use zvault_core::barrier::Barrier;
+use zvault_core::certauth::CertAuthStore;
+use zvault_core::control_group::ControlGroupStore;
+use zvault_core::cubbyhole::{CUBBYHOLE_MOUNT, Cubbyhole};
 use zvault_core::database::DatabaseEngine;
 use zvault_core::engine::KvEngine;
-use zvault_core::lease::LeaseManager;
+use zvault_core::error::{
+    AccessRequestError, AcmeError, BarrierError, ControlGroupError, EngineError, NotifyError,
+    RotationError, SecretUsageError, SyncError,
+};
+use zvault_core::events::{EventBus, TOPIC_KV_WRITE, TOPIC_LEASE_EXPIRED, VaultEvent};
+use zvault_core::hsm::{HsmProvider, Pkcs11Provider};
+use zvault_core::identity::IdentityStore;
+use zvault_core::jwt_auth::JwtAuthStore;
+use zvault_core::kms::DevKms;
+use zvault_core::lease::{LeaseManager, RevocationHandler};
+use zvault_core::license::LicenseManager;
+use zvault_core::metrics::MeteredBackend;
+use zvault_core::mfa::MfaStore;
 use zvault_core::mount::{MountEntry, MountManager};
+use zvault_core::notify::NotificationManager;
 use zvault_core::pki::PkiEngine;
+use zvault_core::pki_acme::AcmeServer;
+use zvault_core::plugin::PluginCatalog;
 use zvault_core::policy::PolicyStore;
+use zvault_core::quota::QuotaManager;
+use zvault_core::registry::EngineRegistry;
+use zvault_core::replication::ReplicationLog;
+use zvault_core::rotation::RotationManager;
 use zvault_core::seal::SealManager;
-use zvault_core::token::TokenStore;
+use zvault_core::secret_usage::SecretUsageLog;
+use zvault_core::sync::SyncManager;
+use zvault_core::sync_kubernetes::KubernetesSyncManager;
+use zvault_core::token::{TokenStore, parse_cidr};
 use zvault_core::transit::TransitEngine;
+use zvault_core::wrapping::ResponseWrapper;
 use zvault_storage::MemoryBackend;
 
-use zvault_server::config::{ServerConfig, StorageBackendType};
+use zvault_server::backup::BackupScheduler;
+use zvault_server::build_info;
 #[cfg(feature = "cloud")]
 use zvault_server::cloud;
-use zvault_server::hardening;
-use zvault_server::middleware::auth_middleware;
+use zvault_server::config::{
+    ReplicationRole, ServerConfig, Settings, StorageBackendType, TlsFileConfig,
+};
+use zvault_server::config_file::{self, ConfigFile};
+use zvault_server::ha::HaCoordinator;
+use zvault_server::middleware::{
+    auth_middleware, forward_middleware, http_metrics_middleware, login_audit_middleware,
+    quota_middleware, request_info_middleware, wrap_middleware,
+};
+use zvault_server::preflight;
+use zvault_server::replication::{Replication, Replicator};
 use zvault_server::routes;
+use zvault_server::routes::database::DatabaseMount;
+use zvault_server::routes::secrets::KvMount;
+use zvault_server::routes::transit::TransitMount;
 use zvault_server::state::AppState;
+use zvault_server::tls::{self, CertResolver, TlsConnectInfo, TlsListener};
 
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::set_header::SetResponseHeaderLayer;
 use tower_http::trace::TraceLayer;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::reload;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Load configuration from environment.
-    let config = ServerConfig::from_env();
+    if print_version() {
+        return Ok(());
+    }
+
+    let check_only = check_config_requested();
+
+    // Load configuration from the environment and the configuration file.
+    let config_path = config_path();
+    let config_file = match &config_path {
+        Some(path) => config_file::load(path)?,
+        None => ConfigFile::default(),
+    };
+    let settings = Settings::with_file(config_file.settings.clone());
+    let mut config = ServerConfig::from_settings(&settings);
+    if config.dev || dev_requested() {
+        config = config.into_dev();
+    }
 
-    // Production hardening: disable core dumps (always) and lock memory (unless disabled).
-    // These run before logging is initialized, so we use eprintln for warnings.
-    apply_hardening(&config);
+    // Production hardening: disable core dumps (always) and lock memory (unless
+    // disabled), before any key material is loaded. Reported by the self-test.
+    let hardening = preflight::apply_hardening(&config);
 
-    // Initialize structured logging.
-    tracing_subscriber::fmt()
+    // Initialize structured logging. `--check-config` keeps stdout for the report.
+    let writer = if check_only {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    // The filter is reloadable so `SIGHUP` can apply a new log level.
+    let subscriber = tracing_subscriber::fmt()
         .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&config.log_level)),
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.log_level)),
         )
+        .with_writer(writer)
         .json()
-        .init();
+        .with_filter_reloading();
+    let log_filter = subscriber.reload_handle();
+    subscriber.init();
 
     info!(storage = ?config.storage_backend, "ZVault starting");
+    if let Some(path) = &config_path {
+        info!(path = %path.display(), "configuration file loaded");
+    }
+
+    let (state, lease_manager) = build_app_state(&config, config_file).await?;
 
-    let (state, lease_manager) = build_app_state(&config).await?;
+    // Auto-unseal vaults whose root key is held by a configured KMS.
+    if routes::sys::try_auto_unseal(&state).await {
+        info!("vault auto-unsealed at startup");
+    }
+
+    // A dev vault starts empty, so initialize and unseal it right away.
+    let dev = if config.dev {
+        let credentials = routes::sys::init_dev(&state)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to initialize dev vault: {e}"))?;
+        warn!("dev mode: in-memory vault without TLS or mlock — never use in production");
+        Some(credentials)
+    } else {
+        None
+    };
+
+    // Self-test before binding anything: refuse to start on hard failures.
+    let report = preflight::run(&config, &settings, &state, hardening).await;
+    if check_only {
+        print_report(&report);
+        anyhow::ensure!(!report.failed(), "configuration check failed");
+        return Ok(());
+    }
+    report.log();
+    anyhow::ensure!(
+        !report.failed(),
+        "startup self-test failed — run `zvault-server --check-config` for the full report"
+    );
 
     // Shutdown signal channel.
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
-    // Spawn lease expiry background worker.
-    let lease_worker_handle = {
+    let mut workers = spawn_workers(&config, &state, lease_manager, &shutdown_rx);
+    if let Some(path) = config_path {
+        let reload_state = Arc::clone(&state);
+        let mut rx = shutdown_rx.clone();
+        workers.push(tokio::spawn(async move {
+            config_reload_worker(path, reload_state, log_filter, &mut rx).await;
+        }));
+    }
+
+    let app = build_router(Arc::clone(&state), config.metrics_require_auth);
+
+    if let Some(credentials) = &dev {
+        print_dev_banner(&config, credentials);
+    }
+
+    // Bind and serve.
+    serve(&config, app, &state, shutdown_tx, &shutdown_rx).await?;
+
+    // Wait for background workers to finish (with timeout).
+    info!("waiting for background workers to stop");
+    for handle in workers {
+        let _ = tokio::time::timeout(Duration::from_secs(10), handle).await;
+    }
+
+    info!("ZVault server stopped");
+    Ok(())
+}
+
+/// Spawn the background workers; each stops when `shutdown` fires.
+#[allow(clippy::too_many_lines)]
+fn spawn_workers(
+    config: &ServerConfig,
+    state: &Arc<AppState>,
+    lease_manager: Arc<LeaseManager>,
+    shutdown_rx: &watch::Receiver<bool>,
+) -> Vec<tokio::task::JoinHandle<()>> {
+    // Replicas leave expiry and rotation to the primary and stream the
+    // results; running them here as well would revoke and rotate twice.
+    // HA standbys run these workers but idle until they become active.
+    let is_primary = !matches!(state.replication, Some(Replication::Replica(_)));
+
+    // Spawn lease expiry background worker, which also purges expired KV versions.
+    let lease_worker_handle = is_primary.then(|| {
         let lm = lease_manager;
+        let event_bus = Arc::clone(&state.event_bus);
+        let kv_state = Arc::clone(state);
+        let mut rx = shutdown_rx.clone();
+        let interval_secs = config.lease_scan_interval_secs;
+        tokio::spawn(async move {
+            lease_expiry_worker(lm, event_bus, kv_state, &mut rx, interval_secs).await;
+        })
+    });
+
+    // Spawn access grant and control group expiry worker.
+    let access_worker_handle = is_primary.then(|| {
+        let store = Arc::clone(&state.access_requests);
+        let control_groups = Arc::clone(&state.control_groups);
+        let ha = state.ha.clone();
         let mut rx = shutdown_rx.clone();
         let interval_secs = config.lease_scan_interval_secs;
         tokio::spawn(async move {
-            lease_expiry_worker(lm, &mut rx, interval_secs).await;
+            access_grant_worker(store, control_groups, ha, &mut rx, interval_secs).await;
+        })
+    });
+
+    // Spawn secret usage flusher.
+    let usage_worker_handle = {
+        let log = Arc::clone(&state.secret_usage);
+        let mut rx = shutdown_rx.clone();
+        let interval_secs = config.secret_usage_flush_interval_secs;
+        tokio::spawn(async move {
+            secret_usage_worker(log, &mut rx, interval_secs).await;
         })
     };
 
-    let app = build_router(Arc::clone(&state));
+    // Spawn webhook notification delivery and retry worker.
+    let notify_worker_handle = {
+        let notifications = Arc::clone(&state.notifications);
+        let events = state.event_bus.subscribe();
+        let mut rx = shutdown_rx.clone();
+        let interval_secs = config.lease_scan_interval_secs;
+        tokio::spawn(async move {
+            notification_worker(notifications, events, &mut rx, interval_secs).await;
+        })
+    };
 
-    // Bind and serve.
-    let listener = TcpListener::bind(config.bind_addr)
-        .await
-        .with_context(|| format!("failed to bind to {}", config.bind_addr))?;
+    // Spawn secret rotation worker.
+    let rotation_worker_handle = is_primary.then(|| {
+        let rotation_state = Arc::clone(state);
+        let mut rx = shutdown_rx.clone();
+        let interval_secs = config.lease_scan_interval_secs;
+        tokio::spawn(async move {
+            rotation_worker(rotation_state, &mut rx, interval_secs).await;
+        })
+    });
 
-    info!(addr = %config.bind_addr, "ZVault server listening");
+    // Spawn GitHub and Kubernetes secret sync worker.
+    let sync_worker_handle = is_primary.then(|| {
+        let sync_state = Arc::clone(state);
+        let events = state.event_bus.subscribe();
+        let mut rx = shutdown_rx.clone();
+        let interval_secs = config.lease_scan_interval_secs;
+        tokio::spawn(async move {
+            sync_worker(sync_state, events, &mut rx, interval_secs).await;
+        })
+    });
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(shutdown_tx))
-        .await
-        .context("server error")?;
+    // Spawn scheduled backup worker, if configured.
+    let backup_worker_handle = state.backups.clone().map(|scheduler| {
+        let barrier = Arc::clone(&state.barrier);
+        let ha = state.ha.clone();
+        let mut rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            backup_worker(scheduler, barrier, ha, &mut rx).await;
+        })
+    });
 
-    // Wait for background workers to finish (with timeout).
-    info!("waiting for background workers to stop");
-    let _ = tokio::time::timeout(Duration::from_secs(10), lease_worker_handle).await;
+    // Spawn the cloud audit retention and export worker, in cloud mode.
+    #[cfg(feature = "cloud")]
+    let cloud_audit_pool = state.cloud_pg_pool.clone().filter(|_| is_primary);
+    #[cfg(feature = "cloud")]
+    let cloud_audit_worker_handle = cloud_audit_pool.map(|pool| {
+        let ha = state.ha.clone();
+        let mut rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            cloud_audit_worker(pool, ha, &mut rx).await;
+        })
+    });
+    #[cfg(not(feature = "cloud"))]
+    let cloud_audit_worker_handle = None;
+
+    // Spawn the replication worker on a replica.
+    let replication_worker_handle = match &state.replication {
+        Some(Replication::Replica(replicator)) => {
+            let replicator = Arc::clone(replicator);
+            let replica_state = Arc::clone(state);
+            let mut rx = shutdown_rx.clone();
+            Some(tokio::spawn(async move {
+                replicator.run(&replica_state, &mut rx).await;
+            }))
+        }
+        _ => None,
+    };
 
-    info!("ZVault server stopped");
-    Ok(())
+    // Spawn the HA leader election worker.
+    let ha_worker_handle = state.ha.clone().map(|ha| {
+        let ha_state = Arc::clone(state);
+        let mut rx = shutdown_rx.clone();
+        tokio::spawn(async move { ha.run(&ha_state, &mut rx).await })
+    });
+
+    [
+        lease_worker_handle,
+        access_worker_handle,
+        Some(usage_worker_handle),
+        Some(notify_worker_handle),
+        rotation_worker_handle,
+        sync_worker_handle,
+        backup_worker_handle,
+        cloud_audit_worker_handle,
+        replication_worker_handle,
+        ha_worker_handle,
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
 }
 
 /// Create the storage backend based on configuration.
@@ -147,6 +394,59 @@ async fn create_storage_backend(
                 "PostgreSQL backend requested but feature 'postgres-backend' is not enabled"
             );
         }
+        #[cfg(feature = "s3-backend")]
+        StorageBackendType::S3 {
+            bucket,
+            region,
+            endpoint,
+            prefix,
+            path_style,
+            cache_path,
+        } => {
+            anyhow::ensure!(
+                !bucket.is_empty(),
+                "ZVAULT_S3_BUCKET is required for S3 storage"
+            );
+            let mut s3_config = zvault_storage::S3Config::new(
+                bucket,
+                region,
+                std::env::var("AWS_ACCESS_KEY_ID").context("AWS_ACCESS_KEY_ID is not set")?,
+                std::env::var("AWS_SECRET_ACCESS_KEY")
+                    .context("AWS_SECRET_ACCESS_KEY is not set")?,
+            );
+            if let Some(endpoint) = endpoint {
+                s3_config.endpoint.clone_from(endpoint);
+            }
+            s3_config.prefix.clone_from(prefix);
+            s3_config.path_style = *path_style;
+            info!(bucket = %bucket, endpoint = %s3_config.endpoint, "using S3 storage");
+            let mut backend = zvault_storage::S3Backend::new(s3_config)
+                .context("failed to configure S3 storage")?;
+            if let Some(cache_path) = cache_path {
+                backend = backend.with_cache(open_s3_cache(cache_path)?);
+            }
+            Ok(Arc::new(backend) as Arc<dyn zvault_storage::StorageBackend>)
+        }
+        #[cfg(not(feature = "s3-backend"))]
+        StorageBackendType::S3 { .. } => {
+            anyhow::bail!("S3 backend requested but feature 's3-backend' is not enabled");
+        }
+    }
+}
+
+/// Open the local `RocksDB` write-through cache for S3 storage.
+#[cfg(feature = "s3-backend")]
+fn open_s3_cache(path: &str) -> anyhow::Result<Arc<dyn zvault_storage::StorageBackend>> {
+    #[cfg(feature = "rocksdb-backend")]
+    {
+        info!(path = %path, "caching S3 storage in RocksDB");
+        Ok(Arc::new(
+            zvault_storage::RocksDbBackend::open(path).context("failed to open S3 cache")?,
+        ))
+    }
+    #[cfg(not(feature = "rocksdb-backend"))]
+    {
+        anyhow::bail!("S3 cache at '{path}' requires feature 'rocksdb-backend'");
     }
 }
 
@@ -155,16 +455,13 @@ async fn register_default_engines(
     config: &ServerConfig,
     barrier: &Arc<Barrier>,
     mount_manager: &Arc<MountManager>,
-) -> (
-    HashMap<String, Arc<KvEngine>>,
-    HashMap<String, Arc<TransitEngine>>,
-    HashMap<String, Arc<DatabaseEngine>>,
-    HashMap<String, Arc<PkiEngine>>,
-) {
+    hsm: Option<&Arc<dyn HsmProvider>>,
+) -> EngineRegistry {
+    let engines = EngineRegistry::new();
+
     // KV engine.
     let default_kv = Arc::new(KvEngine::new(Arc::clone(barrier), "kv/secret/".to_owned()));
-    let mut kv_engines = HashMap::new();
-    kv_engines.insert("secret/".to_owned(), default_kv);
+    engines.insert("secret/", default_kv).await;
 
     let _ = mount_manager
         .mount(MountEntry {
@@ -175,14 +472,23 @@ async fn register_default_engines(
         })
         .await;
 
+    // Cubbyhole engine (built in; data lives in each token's cubbyhole).
+    let _ = mount_manager
+        .mount(MountEntry {
+            path: CUBBYHOLE_MOUNT.to_owned(),
+            engine_type: "cubbyhole".to_owned(),
+            description: "Per-token private storage".to_owned(),
+            config: serde_json::Value::Null,
+        })
+        .await;
+
     // Transit engine.
-    let mut transit_engines = HashMap::new();
     if config.enable_transit {
-        let transit = Arc::new(TransitEngine::new(
-            Arc::clone(barrier),
-            "transit/transit/".to_owned(),
-        ));
-        transit_engines.insert("transit/".to_owned(), transit);
+        let mut transit = TransitEngine::new(Arc::clone(barrier), "transit/transit/".to_owned());
+        if let Some(hsm) = hsm {
+            transit = transit.with_hsm(Arc::clone(hsm));
+        }
+        engines.insert("transit/", Arc::new(transit)).await;
 
         let _ = mount_manager
             .mount(MountEntry {
@@ -197,12 +503,11 @@ async fn register_default_engines(
     }
 
     // Database engine.
-    let mut database_engines = HashMap::new();
     let db_engine = Arc::new(DatabaseEngine::new(
         Arc::clone(barrier),
         "db/database/".to_owned(),
     ));
-    database_engines.insert("database/".to_owned(), db_engine);
+    engines.insert("database/", db_engine).await;
 
     let _ = mount_manager
         .mount(MountEntry {
@@ -216,9 +521,8 @@ async fn register_default_engines(
     info!("database engine mounted at database/");
 
     // PKI engine.
-    let mut pki_engines = HashMap::new();
     let pki_engine = Arc::new(PkiEngine::new(Arc::clone(barrier), "pki/pki/".to_owned()));
-    pki_engines.insert("pki/".to_owned(), pki_engine);
+    engines.insert("pki/", pki_engine).await;
 
     let _ = mount_manager
         .mount(MountEntry {
@@ -231,35 +535,87 @@ async fn register_default_engines(
 
     info!("PKI engine mounted at pki/");
 
-    (kv_engines, transit_engines, database_engines, pki_engines)
+    engines
+}
+
+/// Engines with external side effects clean up when their leases end.
+async fn register_revocation_handlers(lease_manager: &LeaseManager, engines: &EngineRegistry) {
+    for (path, engine) in engines.all::<DatabaseEngine>().await {
+        lease_manager
+            .register_handler(&path, engine as Arc<dyn RevocationHandler>)
+            .await;
+    }
+    for (path, engine) in engines.all::<PkiEngine>().await {
+        lease_manager
+            .register_handler(&path, engine as Arc<dyn RevocationHandler>)
+            .await;
+    }
+}
+
+/// A random 32-byte HMAC key for audit field hashing.
+///
+/// This ensures audit HMACs are unique per server instance. In production,
+/// this should be persisted through the barrier so HMACs are consistent
+/// across restarts (TODO: store at `sys/audit/hmac_key` on first init).
+fn audit_hmac_key() -> Vec<u8> {
+    // Two UUID v4s = 32 bytes of OS CSPRNG randomness.
+    let a = uuid::Uuid::new_v4();
+    let b = uuid::Uuid::new_v4();
+    let mut key = Vec::with_capacity(32);
+    key.extend_from_slice(a.as_bytes());
+    key.extend_from_slice(b.as_bytes());
+    key
 }
 
 /// Build the shared application state and return it along with the lease manager.
+#[allow(clippy::too_many_lines)]
 async fn build_app_state(
     config: &ServerConfig,
+    config_file: ConfigFile,
 ) -> anyhow::Result<(Arc<AppState>, Arc<LeaseManager>)> {
-    let storage = create_storage_backend(&config.storage_backend).await?;
+    let storage = Arc::new(MeteredBackend::new(
+        create_storage_backend(&config.storage_backend).await?,
+    ));
+    let ha = ha_coordinator(config, &storage)?;
 
     // Build core subsystems.
-    let barrier = Arc::new(Barrier::new(storage));
-    let seal_manager = Arc::new(SealManager::new(Arc::clone(&barrier)));
+    let (barrier, replication) = replicated_barrier(config, storage)?;
+    let mut seal_manager = SealManager::new(Arc::clone(&barrier));
+    if let Some(ref key_path) = config.dev_kms_key_path {
+        let kms = DevKms::open(key_path)
+            .await
+            .context("failed to open dev KMS key")?;
+        seal_manager = seal_manager.with_wrapper(Arc::new(kms));
+        warn!(path = %key_path, "dev KMS seal enabled — not for production use");
+    }
+    let seal_manager = Arc::new(seal_manager);
     let token_store = Arc::new(TokenStore::new(Arc::clone(&barrier)));
     let policy_store = Arc::new(PolicyStore::new(Arc::clone(&barrier)));
-    // Generate a random 32-byte HMAC key for audit field hashing.
-    // This ensures audit HMACs are unique per server instance. In production,
-    // this should be persisted through the barrier so HMACs are consistent
-    // across restarts (TODO: store at sys/audit/hmac_key on first init).
-    let hmac_key: Vec<u8> = {
-        // Two UUID v4s = 32 bytes of OS CSPRNG randomness.
-        let a = uuid::Uuid::new_v4();
-        let b = uuid::Uuid::new_v4();
-        let mut key = Vec::with_capacity(32);
-        key.extend_from_slice(a.as_bytes());
-        key.extend_from_slice(b.as_bytes());
-        key
-    };
-    let audit_manager = Arc::new(AuditManager::new(hmac_key));
-    let lease_manager = Arc::new(LeaseManager::new(Arc::clone(&barrier)));
+    // Mount manager — starts empty when sealed, reloads on unseal.
+    let mount_manager = Arc::new(match MountManager::new(Arc::clone(&barrier)).await {
+        Ok(mgr) => mgr,
+        Err(_) => MountManager::empty(Arc::clone(&barrier)),
+    });
+
+    let audit_manager =
+        Arc::new(AuditManager::new(audit_hmac_key()).with_mounts(Arc::clone(&mount_manager)));
+    let quotas = Arc::new(QuotaManager::new(Arc::clone(&barrier)));
+    let lease_manager =
+        Arc::new(LeaseManager::new(Arc::clone(&barrier)).with_quotas(Arc::clone(&quotas)));
+    let activity_log = Arc::new(ActivityLog::new(Arc::clone(&barrier)));
+    let license_manager = Arc::new(
+        LicenseManager::new(Arc::clone(&barrier)).context("failed to initialize license manager")?,
+    );
+    let event_bus = Arc::new(EventBus::new());
+    let mut access_requests =
+        AccessRequestStore::new(Arc::clone(&barrier), Arc::clone(&policy_store))
+            .with_event_bus(Arc::clone(&event_bus));
+    if let Some(ref url) = config.access_request_webhook {
+        access_requests = access_requests
+            .with_webhook(url)
+            .context("invalid ZVAULT_ACCESS_REQUEST_WEBHOOK")?;
+        info!("access request webhook notifications enabled");
+    }
 
     // Register file audit backend if configured.
     if let Some(ref audit_path) = config.audit_file_path {
@@ -268,14 +624,21 @@ async fn build_app_state(
         info!(path = %audit_path, "file audit backend registered");
     }
 
-    // Mount manager — starts empty when sealed, reloads on unseal.
-    let mount_manager = Arc::new(match MountManager::new(Arc::clone(&barrier)).await {
-        Ok(mgr) => mgr,
-        Err(_) => MountManager::empty(Arc::clone(&barrier)),
+    let hsm = config.hsm.as_ref().map(|hsm| {
+        info!(module = %hsm.module_path, slot = hsm.slot, "transit HSM backend enabled");
+        Arc::new(Pkcs11Provider::new(hsm.clone())) as Arc<dyn HsmProvider>
+    });
+    let engines = register_default_engines(config, &barrier, &mount_manager, hsm.as_ref()).await;
+
+    register_revocation_handlers(&lease_manager, &engines).await;
+    let pki_acme = engines.get::<PkiEngine>("pki/").await.map(|pki| {
+        Arc::new(AcmeServer::new(
+            pki,
+            Arc::clone(&barrier),
+            "pki/pki/acme/".to_owned(),
+            "/v1/pki/acme",
+        ))
     });
-
-    let (kv_engines, transit_engines, database_engines, pki_engines) =
-        register_default_engines(config, &barrier, &mount_manager).await;
 
     // Initialize AppRole auth store.
     let approle_store = Arc::new(AppRoleStore::new(
@@ -285,63 +648,228 @@ async fn build_app_state(
 
     info!("AppRole auth method enabled");
 
+    if let Some(ref dir) = config.plugin_dir {
+        info!(dir = %dir, "plugin catalog enabled");
+    }
+
+    let trusted_proxies = config
+        .trusted_proxies
+        .iter()
+        .map(|cidr| {
+            parse_cidr(cidr)
+                .with_context(|| format!("invalid ZVAULT_TRUSTED_PROXIES entry '{cidr}'"))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
     let state = Arc::new(AppState {
-        barrier,
+        barrier: Arc::clone(&barrier),
         seal_manager,
         token_store,
         policy_store,
         mount_manager,
         audit_manager,
         lease_manager: Arc::clone(&lease_manager),
-        kv_engines: RwLock::new(kv_engines),
-        transit_engines: RwLock::new(transit_engines),
-        database_engines: RwLock::new(database_engines),
-        pki_engines: RwLock::new(pki_engines),
+        quotas,
+        license_manager,
+        activity_log,
+        secret_usage: Arc::new(SecretUsageLog::new(Arc::clone(&barrier))),
+        cubbyhole: Arc::new(Cubbyhole::new(Arc::clone(&barrier))),
+        response_wrapper: Arc::new(ResponseWrapper::new(Arc::clone(&barrier))),
+        event_bus,
+        notifications: Arc::new(
+            NotificationManager::new(Arc::clone(&barrier))
+                .context("failed to initialize notifications")?
+                .with_source(
+                    config
+                        .ha
+                        .as_ref()
+                        .map_or_else(|| config.bind_addr.to_string(), |ha| ha.api_addr.clone()),
+                ),
+        ),
+        rotation: Arc::new(
+            RotationManager::new(Arc::clone(&barrier))
+                .context("failed to initialize secret rotation")?,
+        ),
+        sync: Arc::new(
+            SyncManager::new(Arc::clone(&barrier)).context("failed to initialize secret sync")?,
+        ),
+        kubernetes_sync: Arc::new(KubernetesSyncManager::new(Arc::clone(&barrier))),
+        backups: backup_scheduler(config)?,
+        replication,
+        ha,
+        access_requests: Arc::new(access_requests),
+        control_groups: Arc::new(ControlGroupStore::new(Arc::clone(&barrier))),
+        mfa: Arc::new(MfaStore::new(Arc::clone(&barrier))),
+        identity: Arc::new(IdentityStore::new(Arc::clone(&barrier))),
+        engines,
+        hsm,
+        plugin_catalog: Arc::new(PluginCatalog::new(
+            Arc::clone(&barrier),
+            config.plugin_dir.as_ref().map(PathBuf::from),
+        )),
+        pki_acme,
         approle_store: Some(approle_store),
+        cert_auth: Arc::new(CertAuthStore::new(
+            Arc::clone(&barrier),
+            "sys/cert/".to_owned(),
+        )),
+        jwt_auth: Arc::new(JwtAuthStore::new(barrier, "sys/jwt/".to_owned())),
+        mcp_sessions: RwLock::new(HashMap::new()),
         spring_oauth: config.spring_oauth.clone(),
         audit_file_path: config.audit_file_path.clone(),
+        trusted_proxies,
+        config_file: RwLock::new(config_file),
         #[cfg(feature = "cloud")]
-        cloud_pg_pool: {
-            if let Some(ref db_url) = config.cloud_database_url {
-                let pool = sqlx::PgPool::connect(db_url)
-                    .await
-                    .context("failed to connect to cloud database")?;
-                info!("cloud PostgreSQL pool connected");
-                Some(pool)
-            } else {
-                None
-            }
-        },
+        cloud_pg_pool: connect_cloud_pool(config).await?,
     });
 
+    // Audit devices declared in the configuration file.
+    routes::audit::apply_config_devices(&state, &[]).await;
+
     Ok((state, lease_manager))
 }
 
-/// Build the Axum router with all routes and middleware.
-fn build_router(state: Arc<AppState>) -> Router {
-    // Authenticated routes go through the auth middleware layer.
-    let authenticated_routes = Router::new()
+/// Set up HA leader election over the shared `storage`, if configured.
+fn ha_coordinator(
+    config: &ServerConfig,
+    storage: &Arc<MeteredBackend>,
+) -> anyhow::Result<Option<Arc<HaCoordinator>>> {
+    let Some(ha) = &config.ha else {
+        return Ok(None);
+    };
+    let coordinator =
+        HaCoordinator::new(ha, Arc::clone(storage) as _).context("failed to configure HA")?;
+    info!(api_addr = %ha.api_addr, lock_ttl_secs = ha.lock_ttl_secs, "HA enabled");
+    Ok(Some(Arc::new(coordinator)))
+}
+
+/// Build the barrier over `storage` and set up the configured replication
+/// role: on a primary, the barrier writes through the change log replicas
+/// stream from; a replica's replicator writes to `storage` directly.
+fn replicated_barrier(
+    config: &ServerConfig,
+    storage: Arc<dyn zvault_storage::StorageBackend>,
+) -> anyhow::Result<(Arc<Barrier>, Option<Replication>)> {
+    let Some(replication) = &config.replication else {
+        return Ok((Arc::new(Barrier::new(storage)), None));
+    };
+    match &replication.role {
+        ReplicationRole::Primary { log_size } => {
+            let log = Arc::new(ReplicationLog::new(storage, *log_size));
+            info!(epoch = %log.epoch(), log_size, "replication primary enabled");
+            let barrier = Arc::new(Barrier::new(Arc::clone(&log) as _));
+            Ok((
+                barrier,
+                Some(Replication::Primary {
+                    log,
+                    secret: replication.secret.clone(),
+                }),
+            ))
+        }
+        ReplicationRole::Replica { primary_addr } => {
+            let barrier = Arc::new(Barrier::new(Arc::clone(&storage)));
+            let replicator = Replicator::new(
+                primary_addr,
+                &replication.secret,
+                storage,
+                Arc::clone(&barrier),
+            )
+            .context("failed to configure replication")?;
+            info!(primary = %primary_addr, "replica mode enabled");
+            Ok((barrier, Some(Replication::Replica(Arc::new(replicator)))))
+        }
+    }
+}
+
+/// Connect the cloud `PostgreSQL` pool, if cloud mode is configured.
+#[cfg(feature = "cloud")]
+async fn connect_cloud_pool(config: &ServerConfig) -> anyhow::Result<Option<sqlx::PgPool>> {
+    let Some(ref db_url) = config.cloud_database_url else {
+        return Ok(None);
+    };
+    let pool = sqlx::PgPool::connect(db_url)
+        .await
+        .context("failed to connect to cloud database")?;
+    info!("cloud PostgreSQL pool connected");
+    Ok(Some(pool))
+}
+
+/// Login routes: no token required, but still audited.
+fn login_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    let router = Router::new()
+        .nest("/v1/auth/approle", routes::approle::login_router())
+        .nest("/v1/auth/jwt", routes::jwt::login_router())
+        .nest("/v1/auth/cert", routes::cert::login_router());
+    #[cfg(feature = "cloud")]
+    let router = router.nest("/v1/auth/cloud", routes::cloud_link::login_router());
+    router.route_layer(axum_mw::from_fn_with_state(
+        Arc::clone(state),
+        login_audit_middleware,
+    ))
+}
+
+/// Routes that require a token; middleware is layered on by the caller.
+fn authenticated_routes() -> Router<Arc<AppState>> {
+    Router::new()
         .nest("/v1/auth/token", routes::auth::router())
         .nest("/v1/auth/approle", routes::approle::router())
+        .nest("/v1/auth/jwt", routes::jwt::router())
+        .nest("/v1/auth/cert", routes::cert::router())
         .nest("/v1/sys/policies", routes::policy::router())
         .nest("/v1/sys/mounts", routes::mounts::router())
         .nest("/v1/sys/leases", routes::leases::router())
-        .nest("/v1/secret", routes::secrets::router())
-        .nest("/v1/transit", routes::transit::router())
-        .nest("/v1/database", routes::database::router())
+        .nest("/v1/sys", routes::sys::authenticated_router())
+        .nest("/v1/sys/audit", routes::audit::router())
+        .nest("/v1/sys/audit-settings", routes::audit::settings_router())
+        .nest(
+            "/v1/sys/audit-log/external",
+            routes::audit::external_router(),
+        )
+        .nest("/v1/sys/rotate", routes::keyring::router())
+        .nest("/v1/sys/reencrypt", routes::keyring::reencrypt_router())
+        .nest("/v1/sys/key-status", routes::keyring::status_router())
+        .nest("/v1/sys/access-requests", routes::access_requests::router())
+        .nest("/v1/sys/control-group", routes::control_group::router())
+        .nest("/v1/sys/mfa", routes::mfa::router())
+        .nest("/v1/sys/identity", routes::identity::router())
+        .nest("/v1/sys/license", routes::license::router())
+        .nest("/v1/sys/wrapping", routes::wrapping::router())
+        .nest("/v1/sys/events", routes::events::router())
+        .nest("/v1/sys/notifications", routes::notifications::router())
+        .nest("/v1/sys/rotation", routes::rotation::router())
+        .nest("/v1/sys/sync", routes::sync::router())
+        .nest("/v1/sys/migrate", routes::migrate::router())
+        .nest("/v1/sys/quotas", routes::quotas::router())
+        .nest(
+            "/v1/sys/internal/counters/activity",
+            routes::activity::router(),
+        )
+        .nest(
+            "/v1/sys/internal/counters/secrets",
+            routes::secret_usage::router(),
+        )
+        .nest(
+            "/v1/secret",
+            routes::secrets::router().layer(Extension(KvMount::default())),
+        )
+        .nest("/v1/cubbyhole", routes::cubbyhole::router())
+        .nest(
+            "/v1/transit",
+            routes::transit::router().layer(Extension(TransitMount::default())),
+        )
+        .nest(
+            "/v1/database",
+            routes::database::router().layer(Extension(DatabaseMount::default())),
+        )
         .nest("/v1/pki", routes::pki::router())
-        .route_layer(axum_mw::from_fn_with_state(
-            Arc::clone(&state),
-            auth_middleware,
-        ));
-
-    // Concurrency-limit the sys routes (init/unseal) to prevent resource exhaustion.
-    let sys_routes = Router::new()
-        .nest("/v1/sys", routes::sys::router())
-        .layer(tower::limit::ConcurrencyLimitLayer::new(10));
+        .nest("/v1/sys/plugins", routes::plugins::router())
+        .merge(routes::mounts::mount_router())
+}
 
-    // CORS — restrictive defaults, allow dashboard dev server.
-    let cors = CorsLayer::new()
+/// CORS — restrictive defaults, allow dashboard dev server.
+fn cors_layer() -> CorsLayer {
+    let request_id = axum::http::HeaderName::from_static("x-request-id");
+    CorsLayer::new()
         .allow_origin(Any)
         .allow_methods([
             axum::http::Method::GET,
@@ -353,15 +881,64 @@ fn build_router(state: Arc<AppState>) -> Router {
             axum::http::header::CONTENT_TYPE,
             axum::http::header::AUTHORIZATION,
             axum::http::HeaderName::from_static("x-vault-token"),
-        ]);
+            axum::http::HeaderName::from_static("x-vault-wrap-ttl"),
+            request_id.clone(),
+        ])
+        .expose_headers([request_id])
+}
+
+/// Build the Axum router with all routes and middleware.
+fn build_router(state: Arc<AppState>, metrics_require_auth: bool) -> Router {
+    // Authenticated routes go through the auth middleware layer.
+    let mut authenticated_routes = authenticated_routes();
+    if metrics_require_auth {
+        authenticated_routes =
+            authenticated_routes.nest("/v1/sys/metrics", routes::metrics::router());
+    }
+    #[cfg(feature = "cloud")]
+    let authenticated_routes =
+        authenticated_routes.nest("/v1/sys/cloud-link", routes::cloud_link::router());
+    let authenticated_routes = authenticated_routes.nest(
+        "/v1/mcp",
+        routes::mcp::router().route_layer(axum_mw::from_fn_with_state(
+            (Arc::clone(&state), zvault_core::license::Feature::Mcp),
+            zvault_server::middleware::license_middleware,
+        )),
+    );
+    let authenticated_routes = authenticated_routes.nest(
+        "/v1/sys/replication/status",
+        routes::replication::status_router().route_layer(axum_mw::from_fn_with_state(
+            (Arc::clone(&state), zvault_core::license::Feature::Ha),
+            zvault_server::middleware::license_middleware,
+        )),
+    );
+    let authenticated_routes = authenticated_routes
+        .route_layer(axum_mw::from_fn_with_state(
+            Arc::clone(&state),
+            wrap_middleware,
+        ))
+        .route_layer(axum_mw::from_fn_with_state(
+            Arc::clone(&state),
+            auth_middleware,
+        ));
+
+    // Concurrency-limit the sys routes (init/unseal) to prevent resource exhaustion.
+    let sys_routes = Router::new()
+        .nest("/v1/sys", routes::sys::router())
+        .layer(tower::limit::ConcurrencyLimitLayer::new(10));
 
     // OIDC login routes (unauthenticated — these are the login flow).
     #[cfg(feature = "spring-oauth")]
-    let oidc_routes = Router::new().nest("/v1/auth/oidc", routes::oidc::router());
+    let oidc_routes = Router::new()
+        .nest("/v1/auth/oidc", routes::oidc::router())
+        .route_layer(axum_mw::from_fn_with_state(
+            (Arc::clone(&state), zvault_core::license::Feature::Sso),
+            zvault_server::middleware::license_middleware,
+        ));
 
     let mut app = Router::new()
         .merge(sys_routes)
-        .nest("/v1/auth/approle", routes::approle::login_router())
+        .merge(login_routes(&state))
         .merge(authenticated_routes);
 
     #[cfg(feature = "spring-oauth")]
@@ -369,18 +946,58 @@ fn build_router(state: Arc<AppState>) -> Router {
         app = app.merge(oidc_routes);
     }
 
-    // Metrics endpoint (unauthenticated — Prometheus scrapes this).
-    app = app.nest("/v1/sys/metrics", routes::metrics::router());
+    // Metrics endpoint (unauthenticated unless configured otherwise —
+    // Prometheus scrapes this).
+    if !metrics_require_auth {
+        app = app.nest("/v1/sys/metrics", routes::metrics::router());
+    }
+
+    // Discovery document (unauthenticated — clients read it before login).
+    app = app.merge(routes::well_known::router());
+
+    // ACME (JWS-signed by the account key, not a token).
+    app = app.nest("/v1/pki/acme", routes::pki_acme::router());
+
+    // Replication snapshot and stream (replication secret, not a token).
+    app = app.nest(
+        "/v1/sys/replication",
+        routes::replication::router().route_layer(axum_mw::from_fn_with_state(
+            (Arc::clone(&state), zvault_core::license::Feature::Ha),
+            zvault_server::middleware::license_middleware,
+        )),
+    );
 
     // Capture cloud pool before state is moved into with_state().
     #[cfg(feature = "cloud")]
     let cloud_pool = state.cloud_pg_pool.clone();
 
+    // Rate limit quotas, ahead of auth so floods never reach the token store.
+    app = app.route_layer(axum_mw::from_fn_with_state(
+        Arc::clone(&state),
+        quota_middleware,
+    ));
+
+    // Request counts and latency per matched route.
+    app = app.route_layer(axum_mw::from_fn(http_metrics_middleware));
+
+    // On a replica or HA standby, writes go to the primary or active node
+    // before anything else runs.
+    app = app.layer(axum_mw::from_fn_with_state(
+        Arc::clone(&state),
+        forward_middleware,
+    ));
+
+    // Client address and request ID, ahead of everything that records them.
+    app = app.layer(axum_mw::from_fn_with_state(
+        Arc::clone(&state),
+        request_info_middleware,
+    ));
+
     let mut final_app = app
         .merge(routes::ui::router())
         .merge(routes::docs::router())
         .layer(TraceLayer::new_for_http())
-        .layer(cors)
+        .layer(cors_layer())
         .layer(SetResponseHeaderLayer::overriding(
             axum::http::header::X_CONTENT_TYPE_OPTIONS,
             HeaderValue::from_static("nosniff"),
@@ -407,31 +1024,338 @@ fn build_router(state: Arc<AppState>) -> Router {
     final_app
 }
 
-/// Maximum retries per tick when the storage backend is unreachable.
-const LEASE_SCAN_MAX_RETRIES: u32 = 3;
+/// Serve the API until shutdown — over TLS with an ACME certificate when
+/// `ZVAULT_ACME_DOMAINS` is set, plain HTTP otherwise.
+async fn serve(
+    config: &ServerConfig,
+    app: Router,
+    state: &Arc<AppState>,
+    shutdown_tx: watch::Sender<bool>,
+    shutdown_rx: &watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    if let Some(tls) = &config.tls {
+        anyhow::ensure!(
+            config.acme.is_none(),
+            "ZVAULT_TLS_CERT and ZVAULT_ACME_DOMAINS are mutually exclusive"
+        );
+        return serve_tls_files(config, tls, app, shutdown_tx, shutdown_rx).await;
+    }
+    let Some(acme) = &config.acme else {
+        let listener = TcpListener::bind(config.bind_addr)
+            .await
+            .with_context(|| format!("failed to bind to {}", config.bind_addr))?;
+        info!(addr = %config.bind_addr, "ZVault server listening");
+        return axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown_signal(shutdown_tx))
+        .await
+        .context("server error");
+    };
 
-/// Background worker that periodically scans for expired leases and revokes them.
-///
-/// If the storage backend (DB) is unreachable during cleanup, the worker retries
-/// with exponential backoff (1s, 2s, 4s) before giving up on that tick. A
-/// consecutive-failure counter escalates log severity so operators notice
-/// persistent issues without being spammed on transient blips.
-async fn lease_expiry_worker(
-    lease_manager: Arc<LeaseManager>,
+    let http01 = Arc::new(Http01Responder::default());
+    let client = Arc::new(tls::acme_client(
+        acme,
+        Arc::clone(&state.barrier),
+        Arc::clone(&http01),
+    )?);
+    // Until the vault is unsealed the ACME certificate is unreadable.
+    let resolver = Arc::new(CertResolver::new(&acme::self_signed(&acme.domains)?)?);
+
+    let http_listener = TcpListener::bind(acme.http_addr)
+        .await
+        .with_context(|| format!("failed to bind to {}", acme.http_addr))?;
+    let challenges = tls::challenge_router(http01, config.bind_addr.port());
+    let mut rx = shutdown_rx.clone();
+    tokio::spawn(async move {
+        let stopped = async move {
+            let _ = rx.changed().await;
+        };
+        if let Err(e) = axum::serve(http_listener, challenges)
+            .with_graceful_shutdown(stopped)
+            .await
+        {
+            warn!(error = %e, "ACME challenge listener failed");
+        }
+    });
+
+    let mut rx = shutdown_rx.clone();
+    let worker_resolver = Arc::clone(&resolver);
+    tokio::spawn(async move {
+        acme_worker(client, worker_resolver, &mut rx).await;
+    });
+
+    let listener = TlsListener::bind(config.bind_addr, resolver, config.tls_client_certs).await?;
+    info!(
+        addr = %config.bind_addr,
+        http_addr = %acme.http_addr,
+        domains = ?acme.domains,
+        "ZVault server listening with TLS"
+    );
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(shutdown_tx))
+    .await
+    .context("server error")
+}
+
+/// Serve HTTPS with the configured certificate files, creating them first if
+/// bootstrapping is enabled, and reload them when they change.
+async fn serve_tls_files(
+    config: &ServerConfig,
+    tls: &TlsFileConfig,
+    app: Router,
+    shutdown_tx: watch::Sender<bool>,
+    shutdown_rx: &watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    if tls::bootstrap_files(tls).await? {
+        warn!(
+            cert = %tls.cert_path,
+            ca = %tls::bootstrap_ca_path(tls).display(),
+            hostnames = ?tls.hostnames,
+            "bootstrapped a self-signed TLS certificate — clients must trust the CA file"
+        );
+    }
+    let resolver = Arc::new(CertResolver::from_files(tls)?);
+
+    let mut rx = shutdown_rx.clone();
+    let worker_tls = tls.clone();
+    let worker_resolver = Arc::clone(&resolver);
+    tokio::spawn(async move {
+        tls_reload_worker(&worker_tls, &worker_resolver, &mut rx).await;
+    });
+
+    let listener = TlsListener::bind(config.bind_addr, resolver, config.tls_client_certs).await?;
+    info!(
+        addr = %config.bind_addr,
+        cert = %tls.cert_path,
+        "ZVault server listening with TLS"
+    );
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(shutdown_tx))
+    .await
+    .context("server error")
+}
+
+/// How often the TLS reload worker checks the certificate files for changes.
+const TLS_FILE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Background worker that reloads the TLS certificate files on `SIGHUP` or
+/// when their modification time changes. A certificate that fails to load
+/// is logged and the current one kept; a changed file is retried on every
+/// poll until it loads.
+async fn tls_reload_worker(
+    tls: &TlsFileConfig,
+    resolver: &CertResolver,
     shutdown: &mut watch::Receiver<bool>,
-    interval_secs: u64,
 ) {
-    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
-    let mut consecutive_failures: u32 = 0;
-    info!(interval_secs, "lease expiry worker started");
+    info!(cert = %tls.cert_path, "TLS certificate reload worker started");
+    let mut loaded = tls::files_modified(tls);
+    let mut poll = tokio::time::interval(TLS_FILE_POLL_INTERVAL);
+    #[cfg(unix)]
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+    #[cfg(not(unix))]
+    let mut hangup = None;
 
     loop {
-        tokio::select! {
-            _ = interval.tick() => {
-                let scan_result = retry_scan(&lease_manager, shutdown).await;
-
-                match scan_result {
-                    Ok(None) => {
+        let reason = tokio::select! {
+            _ = poll.tick() => {
+                if tls::files_modified(tls) == loaded {
+                    continue;
+                }
+                "files changed"
+            }
+            () = recv_hangup(&mut hangup) => "SIGHUP",
+            _ = shutdown.changed() => {
+                info!("TLS certificate reload worker shutting down");
+                return;
+            }
+        };
+        let modified = tls::files_modified(tls);
+        match resolver.reload_files(tls) {
+            Ok(()) => {
+                loaded = modified;
+                info!(reason, cert = %tls.cert_path, "TLS certificate reloaded");
+            }
+            Err(e) => warn!(
+                reason,
+                error = %format!("{e:#}"),
+                "TLS certificate reload failed, keeping the current certificate"
+            ),
+        }
+    }
+}
+
+/// Background worker that re-reads the configuration file on `SIGHUP` and
+/// applies what can change at runtime: the log level (unless `RUST_LOG` is
+/// set), the audit devices it declares, and, once unsealed, its mounts. A
+/// file that no longer loads is logged and the running configuration kept.
+async fn config_reload_worker<S>(
+    path: PathBuf,
+    state: Arc<AppState>,
+    log_filter: reload::Handle<EnvFilter, S>,
+    shutdown: &mut watch::Receiver<bool>,
+) {
+    #[cfg(unix)]
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+    #[cfg(not(unix))]
+    let mut hangup = None;
+
+    loop {
+        tokio::select! {
+            () = recv_hangup(&mut hangup) => {}
+            _ = shutdown.changed() => return,
+        }
+
+        let file = match config_file::load(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!(
+                    error = %format!("{e:#}"),
+                    "configuration reload failed, keeping the current configuration"
+                );
+                continue;
+            }
+        };
+
+        if std::env::var_os("RUST_LOG").is_none() {
+            let level = Settings::with_file(file.settings.clone())
+                .var("ZVAULT_LOG_LEVEL")
+                .unwrap_or_else(|_| "info".to_owned());
+            if let Err(e) = log_filter.reload(EnvFilter::new(&level)) {
+                warn!(error = %e, "failed to apply the configured log level");
+            }
+        }
+
+        let previous = std::mem::replace(&mut *state.config_file.write().await, file);
+        routes::audit::apply_config_devices(&state, &previous.audit).await;
+        if state.barrier.is_unsealed().await {
+            routes::mounts::ensure_config_mounts(&state).await;
+        }
+        info!(path = %path.display(), "configuration reloaded");
+    }
+}
+
+/// Wait for the next `SIGHUP`; never resolves if the handler is missing.
+#[cfg(unix)]
+async fn recv_hangup(signal: &mut Option<tokio::signal::unix::Signal>) {
+    if let Some(signal) = signal
+        && signal.recv().await.is_some()
+    {
+        return;
+    }
+    std::future::pending::<()>().await;
+}
+
+/// `SIGHUP` does not exist off Unix.
+#[cfg(not(unix))]
+async fn recv_hangup(_signal: &mut Option<()>) {
+    std::future::pending::<()>().await;
+}
+
+/// How often the ACME worker checks whether the certificate is due for renewal.
+const ACME_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Delay before retrying a failed ACME issuance, to stay under CA rate limits.
+const ACME_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How often the ACME worker checks whether the vault has been unsealed.
+const ACME_SEALED_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Background worker that keeps the TLS certificate current: once the vault
+/// is unsealed it installs the stored ACME certificate, obtaining one first
+/// if there is none, and renews it when it nears expiry.
+async fn acme_worker(
+    client: Arc<AcmeClient>,
+    resolver: Arc<CertResolver>,
+    shutdown: &mut watch::Receiver<bool>,
+) {
+    info!(domains = ?client.config().domains, "ACME certificate worker started");
+    let mut delay = Duration::ZERO;
+
+    loop {
+        tokio::select! {
+            () = tokio::time::sleep(delay) => {
+                delay = match client.ensure_certificate().await {
+                    Ok(certificate) => match resolver.install(&certificate) {
+                        Ok(()) => ACME_CHECK_INTERVAL,
+                        Err(e) => {
+                            warn!(error = %e, "failed to install ACME certificate, will retry");
+                            ACME_RETRY_INTERVAL
+                        }
+                    },
+                    Err(AcmeError::Barrier(BarrierError::Sealed)) => ACME_SEALED_INTERVAL,
+                    Err(e) => {
+                        warn!(error = %e, "ACME certificate renewal failed, will retry");
+                        ACME_RETRY_INTERVAL
+                    }
+                };
+            }
+            _ = shutdown.changed() => {
+                info!("ACME certificate worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// Permanently delete KV secret versions past their `delete_version_after`.
+async fn sweep_kv_versions(state: &AppState) {
+    let engines = state.engines.all::<KvEngine>().await;
+    let now = chrono::Utc::now();
+    for (_, engine) in engines {
+        match engine.sweep_expired_versions(now).await {
+            Ok(0) | Err(EngineError::Barrier(BarrierError::Sealed)) => {}
+            Ok(deleted) => info!(
+                mount = engine.prefix(),
+                deleted, "purged expired secret versions"
+            ),
+            Err(e) => warn!(mount = engine.prefix(), error = %e, "secret version sweep failed"),
+        }
+    }
+}
+
+/// Maximum retries per tick when the storage backend is unreachable.
+const LEASE_SCAN_MAX_RETRIES: u32 = 3;
+
+/// Background worker that periodically scans for expired leases and revokes them.
+///
+/// Each revoked lease is published as a `lease.expired` event so consumers
+/// holding the leased secret know to re-fetch it. On the same tick it purges
+/// KV secret versions past their `delete_version_after`. Idle on an HA
+/// standby.
+///
+/// If the storage backend (DB) is unreachable during cleanup, the worker retries
+/// with exponential backoff (1s, 2s, 4s) before giving up on that tick. A
+/// consecutive-failure counter escalates log severity so operators notice
+/// persistent issues without being spammed on transient blips.
+async fn lease_expiry_worker(
+    lease_manager: Arc<LeaseManager>,
+    event_bus: Arc<EventBus>,
+    state: Arc<AppState>,
+    shutdown: &mut watch::Receiver<bool>,
+    interval_secs: u64,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    let mut consecutive_failures: u32 = 0;
+    info!(interval_secs, "lease expiry worker started");
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if state.is_standby() {
+                    continue;
+                }
+                let scan_result = retry_scan(&lease_manager, shutdown).await;
+
+                match scan_result {
+                    Ok(None) => {
                         // Shutdown requested during retry — exit.
                         info!("lease expiry worker shutting down");
                         return;
@@ -447,7 +1371,16 @@ async fn lease_expiry_worker(
                         let mut failed = 0u32;
                         for lease in &expired {
                             match lease_manager.revoke(&lease.id).await {
-                                Ok(()) => { revoked = revoked.saturating_add(1); }
+                                Ok(()) => {
+                                    revoked = revoked.saturating_add(1);
+                                    event_bus.publish(
+                                        TOPIC_LEASE_EXPIRED,
+                                        serde_json::json!({
+                                            "lease_id": lease.id,
+                                            "engine_path": lease.engine_path,
+                                        }),
+                                    );
+                                }
                                 Err(e) => {
                                     failed = failed.saturating_add(1);
                                     warn!(
@@ -478,6 +1411,7 @@ async fn lease_expiry_worker(
                         }
                     }
                 }
+                sweep_kv_versions(&state).await;
             }
             _ = shutdown.changed() => {
                 info!("lease expiry worker shutting down");
@@ -487,6 +1421,309 @@ async fn lease_expiry_worker(
     }
 }
 
+/// Background worker that ends access request grants past their expiry,
+/// deleting the policies they attached, and discards expired control group
+/// requests. Idle on an HA standby.
+async fn access_grant_worker(
+    store: Arc<AccessRequestStore>,
+    control_groups: Arc<ControlGroupStore>,
+    ha: Option<Arc<HaCoordinator>>,
+    shutdown: &mut watch::Receiver<bool>,
+    interval_secs: u64,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    info!(interval_secs, "access grant expiry worker started");
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if ha.as_ref().is_some_and(|ha| !ha.is_active()) {
+                    continue;
+                }
+                match store.expire(chrono::Utc::now()).await {
+                    Ok(_) | Err(AccessRequestError::Barrier(BarrierError::Sealed)) => {}
+                    Err(e) => warn!(error = %e, "access grant expiry scan failed, will retry next tick"),
+                }
+                match control_groups.expire(chrono::Utc::now()).await {
+                    Ok(_) | Err(ControlGroupError::Barrier(BarrierError::Sealed)) => {}
+                    Err(e) => warn!(error = %e, "control group expiry scan failed, will retry next tick"),
+                }
+            }
+            _ = shutdown.changed() => {
+                info!("access grant expiry worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// Background worker that persists aggregated secret read counts, with a
+/// final flush on shutdown.
+async fn secret_usage_worker(
+    log: Arc<SecretUsageLog>,
+    shutdown: &mut watch::Receiver<bool>,
+    interval_secs: u64,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    info!(interval_secs, "secret usage flusher started");
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => flush_secret_usage(&log).await,
+            _ = shutdown.changed() => {
+                flush_secret_usage(&log).await;
+                info!("secret usage flusher shutting down");
+                return;
+            }
+        }
+    }
+}
+
+async fn flush_secret_usage(log: &SecretUsageLog) {
+    match log.flush().await {
+        Ok(_) | Err(SecretUsageError::Barrier(BarrierError::Sealed)) => {}
+        Err(e) => warn!(error = %e, "secret usage flush failed, will retry next tick"),
+    }
+}
+
+/// Background task that hands published events to notification webhooks and
+/// retries failed deliveries every `interval_secs`.
+///
+/// Each event is delivered on its own task so a slow webhook does not hold
+/// up the event stream.
+async fn notification_worker(
+    notifications: Arc<NotificationManager>,
+    mut events: broadcast::Receiver<VaultEvent>,
+    shutdown: &mut watch::Receiver<bool>,
+    interval_secs: u64,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    info!(interval_secs, "notification worker started");
+
+    loop {
+        tokio::select! {
+            received = events.recv() => match received {
+                Ok(event) => {
+                    let notifications = Arc::clone(&notifications);
+                    tokio::spawn(async move {
+                        let result = notifications.dispatch(&event).await.map(drop);
+                        log_notify_error(result, "notification dispatch failed");
+                    });
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!(missed, "notification worker fell behind, events not delivered");
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = interval.tick() => {
+                let result = notifications.retry_due(chrono::Utc::now()).await.map(drop);
+                log_notify_error(result, "notification retry failed, will retry next tick");
+            }
+            _ = shutdown.changed() => {
+                info!("notification worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+fn log_notify_error(result: Result<(), NotifyError>, message: &str) {
+    match result {
+        Ok(()) | Err(NotifyError::Barrier(BarrierError::Sealed)) => {}
+        Err(e) => warn!(error = %e, "{message}"),
+    }
+}
+
+/// Background worker that rotates secrets whose rotation policy is due.
+///
+/// Rotations run one at a time; a failed rotation is recorded on its policy
+/// and retried on a later tick. Idle on an HA standby.
+async fn rotation_worker(
+    state: Arc<AppState>,
+    shutdown: &mut watch::Receiver<bool>,
+    interval_secs: u64,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    info!(interval_secs, "secret rotation worker started");
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if state.is_standby() {
+                    continue;
+                }
+                let due = match state.rotation.due(chrono::Utc::now()).await {
+                    Ok(due) => due,
+                    Err(RotationError::Barrier(BarrierError::Sealed)) => continue,
+                    Err(e) => {
+                        warn!(error = %e, "rotation scan failed, will retry next tick");
+                        continue;
+                    }
+                };
+                for policy in due {
+                    // Failures are logged and recorded on the policy.
+                    let _ = routes::rotation::rotate(&state, &policy.id()).await;
+                }
+            }
+            _ = shutdown.changed() => {
+                info!("secret rotation worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// Background worker that syncs secrets to GitHub and Kubernetes when they
+/// are written, and retries failed syncs every `interval_secs`.
+///
+/// Each sync runs on its own task; syncs are serialized by their manager.
+/// Idle on an HA standby.
+async fn sync_worker(
+    state: Arc<AppState>,
+    mut events: broadcast::Receiver<VaultEvent>,
+    shutdown: &mut watch::Receiver<bool>,
+    interval_secs: u64,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    info!(interval_secs, "secret sync worker started");
+
+    loop {
+        tokio::select! {
+            received = events.recv() => match received {
+                Ok(event) if event.topic == TOPIC_KV_WRITE && !state.is_standby() => {
+                    let result = sync_written(&state, &event).await;
+                    log_sync_error(result, "sync lookup failed, secret not synced");
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!(missed, "sync worker fell behind, some writes not synced until retried");
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = interval.tick() => {
+                if state.is_standby() {
+                    continue;
+                }
+                let result = retry_due_syncs(&state).await;
+                log_sync_error(result, "sync retry scan failed, will retry next tick");
+            }
+            _ = shutdown.changed() => {
+                info!("secret sync worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// Start every enabled sync covering the secret a `kv.write` event names.
+async fn sync_written(state: &Arc<AppState>, event: &VaultEvent) -> Result<(), SyncError> {
+    let (Some(mount), Some(path)) = (event.data["mount"].as_str(), event.data["path"].as_str())
+    else {
+        return Ok(());
+    };
+    let Some(path) = path.strip_prefix(&format!("{mount}data/")) else {
+        return Ok(());
+    };
+    // Failures are logged and recorded on the sync.
+    for name in state.sync.covering(mount, path).await? {
+        let state = Arc::clone(state);
+        tokio::spawn(async move {
+            let _ = routes::sync::run(&state, &name, false).await;
+        });
+    }
+    for name in state.kubernetes_sync.covering(mount, path).await? {
+        let state = Arc::clone(state);
+        tokio::spawn(async move {
+            let _ = routes::sync::run_kubernetes(&state, &name, false).await;
+        });
+    }
+    Ok(())
+}
+
+async fn retry_due_syncs(state: &AppState) -> Result<(), SyncError> {
+    let now = chrono::Utc::now();
+    for name in state.sync.due(now).await? {
+        let _ = routes::sync::run(state, &name, false).await;
+    }
+    for name in state.kubernetes_sync.due(now).await? {
+        let _ = routes::sync::run_kubernetes(state, &name, false).await;
+    }
+    Ok(())
+}
+
+fn log_sync_error(result: Result<(), SyncError>, message: &str) {
+    match result {
+        Ok(()) | Err(SyncError::Barrier(BarrierError::Sealed)) => {}
+        Err(e) => warn!(error = %e, "{message}"),
+    }
+}
+
+/// Create the scheduled backup scheduler, if backups are configured.
+fn backup_scheduler(config: &ServerConfig) -> anyhow::Result<Option<Arc<BackupScheduler>>> {
+    let Some(backup) = &config.backup else {
+        return Ok(None);
+    };
+    let scheduler =
+        BackupScheduler::new(backup).context("failed to configure scheduled backups")?;
+    info!(?scheduler, "scheduled backups enabled");
+    Ok(Some(Arc::new(scheduler)))
+}
+
+/// Background worker that takes a scheduled backup every interval, unless
+/// this server is an HA standby.
+async fn backup_worker(
+    scheduler: Arc<BackupScheduler>,
+    barrier: Arc<Barrier>,
+    ha: Option<Arc<HaCoordinator>>,
+    shutdown: &mut watch::Receiver<bool>,
+) {
+    let interval_secs = scheduler.interval_secs();
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    // The first tick fires immediately; the vault is usually still sealed.
+    interval.tick().await;
+    info!(interval_secs, "scheduled backup worker started");
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if ha.as_ref().is_none_or(|ha| ha.is_active()) {
+                    scheduler.run(&barrier).await;
+                }
+            }
+            _ = shutdown.changed() => {
+                info!("scheduled backup worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
+/// Prune the cloud audit log and run scheduled audit exports.
+#[cfg(feature = "cloud")]
+async fn cloud_audit_worker(
+    pool: sqlx::PgPool,
+    ha: Option<Arc<HaCoordinator>>,
+    shutdown: &mut watch::Receiver<bool>,
+) {
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(cloud::audit_export::RUN_INTERVAL_SECS));
+    info!("cloud audit retention and export worker started");
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if ha.as_ref().is_none_or(|ha| ha.is_active()) {
+                    cloud::audit_export::run(&pool).await;
+                }
+            }
+            _ = shutdown.changed() => {
+                info!("cloud audit worker shutting down");
+                return;
+            }
+        }
+    }
+}
+
 /// Attempt `find_expired()` with exponential backoff. Returns:
 /// - `Ok(Some(leases))` on success
 /// - `Ok(None)` if shutdown was signalled during retry
@@ -558,20 +1795,1037 @@ async fn shutdown_signal(shutdown_tx: watch::Sender<bool>) {
     let _ = shutdown_tx.send(true);
 }
 
-/// Apply production hardening before logging is initialized.
-///
-/// Uses `eprintln` because structured logging is not yet available.
-#[allow(clippy::print_stderr)]
-fn apply_hardening(config: &ServerConfig) {
-    if let Err(e) = hardening::disable_core_dumps() {
-        eprintln!("WARNING: failed to disable core dumps: {e}");
+/// Whether `--check-config` was passed: run the self-test and exit.
+fn check_config_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--check-config")
+}
+
+/// Whether `--dev` (or Vault's `-dev`) was passed.
+/// The configuration file named by `--config <path>`, `--config=<path>`, or
+/// `ZVAULT_CONFIG`.
+fn config_path() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" || arg == "-config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    std::env::var_os("ZVAULT_CONFIG")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+fn dev_requested() -> bool {
+    std::env::args()
+        .skip(1)
+        .any(|arg| arg == "--dev" || arg == "-dev")
+}
+
+/// Tell the developer how to reach the dev vault.
+#[allow(clippy::print_stdout)]
+fn print_dev_banner(config: &ServerConfig, credentials: &routes::sys::DevCredentials) {
+    let addr = format!("http://{}", config.bind_addr);
+    println!(
+        "
+ZVault is running in dev mode: storage is in memory, the vault is already
+unsealed, and every secret is lost when the server stops. Never use dev
+mode in production.
+
+    export VAULT_ADDR='{addr}'
+    export VAULT_TOKEN='{root_token}'
+
+Unseal Key: {unseal_key}
+Root Token: {root_token}
+",
+        root_token = credentials.root_token,
+        unseal_key = credentials.unseal_key,
+    );
+}
+
+/// Print the `--check-config` report.
+#[allow(clippy::print_stdout)]
+fn print_report(report: &preflight::Report) {
+    print!("{report}");
+}
+
+/// Handle `--version` / `-V`. Returns `true` if the version was printed.
+#[allow(clippy::print_stdout)]
+fn print_version() -> bool {
+    let requested = std::env::args()
+        .skip(1)
+        .any(|arg| arg == "--version" || arg == "-V");
+    if requested {
+        println!("zvault-server {}", build_info::LONG_VERSION);
+    }
+    requested
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+    use zvault_core::token::CreateTokenParams;
+
+    use super::*;
+
+    /// The router and state of a fresh dev vault, and its credentials.
+    async fn dev_vault() -> (Router, Arc<AppState>, routes::sys::DevCredentials) {
+        let config = ServerConfig::from_settings(&Settings::env()).into_dev();
+        let (state, _) = build_app_state(&config, ConfigFile::default())
+            .await
+            .unwrap();
+        let credentials = routes::sys::init_dev(&state).await.unwrap();
+        let app = build_router(Arc::clone(&state), false);
+        (app, state, credentials)
     }
 
-    if config.disable_mlock {
-        eprintln!(
-            "WARNING: mlock disabled via ZVAULT_DISABLE_MLOCK — secrets may be swapped to disk"
+    /// A token with only the `default` policy.
+    async fn default_token(state: &AppState) -> String {
+        state
+            .token_store
+            .create(CreateTokenParams {
+                policies: vec!["default".to_owned()],
+                ttl: None,
+                max_ttl: None,
+                renewable: false,
+                parent_hash: None,
+                metadata: HashMap::new(),
+                display_name: "test".to_owned(),
+                bound_cidrs: Vec::new(),
+            })
+            .await
+            .unwrap()
+    }
+
+    /// Send a request with an optional token and JSON body.
+    async fn send(
+        app: &Router,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut req = Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            req = req.header("X-Vault-Token", token);
+        }
+        let req = match body.map(|body| body.to_string()) {
+            Some(body) => req
+                .header("Content-Type", "application/json")
+                .header("Content-Length", body.len())
+                .body(Body::from(body)),
+            None => req.body(Body::empty()),
+        }
+        .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn audit_log_requires_sudo() {
+        let (app, state, credentials) = dev_vault().await;
+        let root = credentials.root_token;
+        let token = default_token(&state).await;
+
+        for path in ["/v1/sys/audit-log", "/v1/sys/audit-log/count"] {
+            let (status, _) = send(&app, "GET", path, None, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{path}");
+            let (status, _) = send(&app, "GET", path, Some(&token), None).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{path}");
+            let (status, _) = send(&app, "GET", path, Some(&root), None).await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn replication_routes_require_an_enterprise_license() {
+        let (app, _state, credentials) = dev_vault().await;
+        let root = Some(credentials.root_token.as_str());
+
+        for (path, token) in [
+            ("/v1/sys/replication/status", root),
+            ("/v1/sys/replication/snapshot", None),
+            ("/v1/sys/replication/stream?epoch=0&after=0", None),
+        ] {
+            let (status, body) = send(&app, "GET", path, token, None).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{path}");
+            assert_eq!(body["feature"], "ha", "{path}: {body}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rekey_requires_sudo() {
+        let (app, state, credentials) = dev_vault().await;
+        let root = credentials.root_token;
+        let token = default_token(&state).await;
+        let init = serde_json::json!({ "shares": 3, "threshold": 2 });
+
+        let (status, _) = send(&app, "POST", "/v1/sys/rekey/init", None, Some(init.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/rekey/init",
+            Some(&token),
+            Some(init.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, body) =
+            send(&app, "POST", "/v1/sys/rekey/init", Some(&root), Some(init)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["started"], true);
+
+        for token in [None, Some(token.as_str())] {
+            let (status, _) = send(&app, "GET", "/v1/sys/rekey/init", token, None).await;
+            assert_ne!(status, StatusCode::OK);
+            let (status, _) = send(&app, "POST", "/v1/sys/rekey/cancel", token, None).await;
+            assert_ne!(status, StatusCode::NO_CONTENT);
+        }
+        let (_, body) = send(&app, "GET", "/v1/sys/rekey/init", Some(&root), None).await;
+        assert_eq!(body["started"], true, "the rekey survives refused cancels");
+    }
+
+    #[tokio::test]
+    async fn generate_root_returns_only_the_encoded_token() {
+        let (app, state, credentials) = dev_vault().await;
+        let token = default_token(&state).await;
+        let attempt = "/v1/sys/generate-root/attempt";
+
+        // Seeing or cancelling an attempt takes sudo.
+        for method in ["GET", "DELETE"] {
+            let (status, _) = send(&app, method, attempt, None, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{method}");
+            let (status, _) = send(&app, method, attempt, Some(&token), None).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{method}");
+        }
+
+        // Starting one and submitting shares do not: the shares authorize it.
+        let (status, started) = send(&app, "POST", attempt, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let otp = started["otp"].as_str().unwrap().to_owned();
+        let update = serde_json::json!({
+            "nonce": started["nonce"],
+            "share": credentials.unseal_key,
+        });
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/v1/sys/generate-root/update",
+            None,
+            Some(update),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["complete"], true);
+        let encoded = body["encoded_token"].as_str().unwrap();
+
+        let root = zvault_core::seal::decode_root_token(encoded, &otp).unwrap();
+        assert!(!body.to_string().contains(root.as_str()));
+        let (status, _) = send(&app, "GET", attempt, Some(&root), None).await;
+        assert_eq!(status, StatusCode::OK, "the decoded token is a root token");
+    }
+
+    #[tokio::test]
+    async fn seal_migration_requires_sudo() {
+        let (app, state, credentials) = dev_vault().await;
+        let token = default_token(&state).await;
+        let body = serde_json::json!({ "to": "auto", "shares": [credentials.unseal_key] });
+
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/seal/migrate",
+            None,
+            Some(body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/seal/migrate",
+            Some(&token),
+            Some(body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        // Past the policy check, a dev vault has no KMS to migrate to.
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/seal/migrate",
+            Some(&credentials.root_token),
+            Some(body),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn restore_requires_sudo() {
+        let (app, state, credentials) = dev_vault().await;
+        let token = default_token(&state).await;
+        let (_, backup) = send(&app, "GET", "/v1/sys/backup", None, None).await;
+        let body = serde_json::json!({ "snapshot": backup["snapshot"], "prefix": "kv/secret/" });
+
+        let (status, _) = send(&app, "POST", "/v1/sys/restore", None, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/restore",
+            Some(&token),
+            Some(body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, restored) = send(
+            &app,
+            "POST",
+            "/v1/sys/restore",
+            Some(&credentials.root_token),
+            Some(body),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(restored["success"], true);
+    }
+
+    #[tokio::test]
+    async fn restore_accepts_snapshots_over_two_mib() {
+        let (app, state, credentials) = dev_vault().await;
+        let root = Some(credentials.root_token.as_str());
+        let log = std::env::temp_dir().join(format!("zvault-audit-{}.log", uuid::Uuid::new_v4()));
+        for (path, body) in [
+            (
+                "/v1/sys/audit/file",
+                serde_json::json!({"type": "file", "options": {"file_path": log.to_string_lossy()}}),
+            ),
+            (
+                "/v1/sys/audit-settings",
+                serde_json::json!({"log_request_data": true}),
+            ),
+        ] {
+            let (status, body) = send(&app, "POST", path, root, Some(body)).await;
+            assert!(status.is_success(), "{path}: {status} {body}");
+        }
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        let blob = vec![7u8; 3 * 1024 * 1024];
+        let entries = serde_json::json!([
+            {"key": "restored/blob", "value": base64::Engine::encode(&engine, &blob)}
+        ]);
+        let body = serde_json::json!({
+            "snapshot": base64::Engine::encode(&engine, entries.to_string()),
+            "prefix": "restored/",
+        });
+        let (status, restored) = send(&app, "POST", "/v1/sys/restore", root, Some(body)).await;
+        assert_eq!(status, StatusCode::OK, "{restored}");
+        assert_eq!(
+            state.barrier.get_raw("restored/blob").await.unwrap(),
+            Some(blob)
         );
-    } else if let Err(e) = hardening::lock_memory() {
-        eprintln!("WARNING: failed to lock memory: {e} (set ZVAULT_DISABLE_MLOCK=true for dev)");
+        let _ = std::fs::remove_file(&log);
+    }
+
+    #[tokio::test]
+    async fn control_group_requests_are_parked_before_the_handler_runs() {
+        let (app, state, credentials) = dev_vault().await;
+        state
+            .policy_store
+            .put(&zvault_core::policy::Policy {
+                name: "gated".to_owned(),
+                rules: vec![zvault_core::policy::PolicyRule {
+                    path: "secret/data/gated".to_owned(),
+                    capabilities: vec![
+                        zvault_core::policy::Capability::Create,
+                        zvault_core::policy::Capability::Update,
+                    ],
+                    control_group: Some(zvault_core::policy::ControlGroup {
+                        approvals: 1,
+                        approver_policies: vec!["root".to_owned()],
+                        approver_identities: Vec::new(),
+                    }),
+                    mfa_methods: Vec::new(),
+                }],
+            })
+            .await
+            .unwrap();
+        let token = state
+            .token_store
+            .create(CreateTokenParams {
+                policies: vec!["gated".to_owned()],
+                ttl: None,
+                max_ttl: None,
+                renewable: false,
+                parent_hash: None,
+                metadata: HashMap::new(),
+                display_name: "gated".to_owned(),
+                bound_cidrs: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        let write = serde_json::json!({"data": {"pw": "hunter2"}});
+        let (status, parked) = send(
+            &app,
+            "POST",
+            "/v1/secret/data/gated",
+            Some(&token),
+            Some(write),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert!(parked["control_group"]["accessor"].is_string());
+        let (status, _) = send(
+            &app,
+            "GET",
+            "/v1/secret/data/gated",
+            Some(&credentials.root_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn backup_status_requires_read() {
+        let (app, state, credentials) = dev_vault().await;
+        let token = default_token(&state).await;
+
+        let (status, _) = send(&app, "GET", "/v1/sys/backup/status", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&app, "GET", "/v1/sys/backup/status", Some(&token), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, body) = send(
+            &app,
+            "GET",
+            "/v1/sys/backup/status",
+            Some(&credentials.root_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["enabled"], false);
+    }
+
+    #[tokio::test]
+    async fn runtime_kv_mount_values_are_not_audited() {
+        let (app, _state, credentials) = dev_vault().await;
+        let root = Some(credentials.root_token.as_str());
+        let log = std::env::temp_dir().join(format!("zvault-audit-{}.log", uuid::Uuid::new_v4()));
+        let secret = format!("value-{}", uuid::Uuid::new_v4());
+
+        for (path, body) in [
+            (
+                "/v1/sys/audit/file",
+                serde_json::json!({"type": "file", "options": {"file_path": log.to_string_lossy()}}),
+            ),
+            (
+                "/v1/sys/audit-settings",
+                serde_json::json!({"log_request_data": true}),
+            ),
+            (
+                "/v1/sys/mounts/kv2-team-a",
+                serde_json::json!({"engine_type": "kv"}),
+            ),
+            (
+                "/v1/kv2-team-a/data/app",
+                serde_json::json!({"data": {"pw": secret}}),
+            ),
+        ] {
+            let (status, body) = send(&app, "POST", path, root, Some(body)).await;
+            assert!(status.is_success(), "{path}: {status} {body}");
+        }
+        let (status, _) = send(&app, "DELETE", "/v1/sys/audit/file", root, None).await;
+        assert!(status.is_success());
+
+        let audited = std::fs::read_to_string(&log).unwrap();
+        let _ = std::fs::remove_file(&log);
+        assert!(audited.contains("kv2-team-a/data/app"));
+        assert!(!audited.contains(&secret));
+    }
+
+    #[tokio::test]
+    async fn audit_response_entries_pair_with_their_requests() {
+        let (app, _state, credentials) = dev_vault().await;
+        let root = Some(credentials.root_token.as_str());
+        let log = std::env::temp_dir().join(format!("zvault-audit-{}.log", uuid::Uuid::new_v4()));
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/audit/file",
+            root,
+            Some(serde_json::json!({"type": "file", "options": {"file_path": log.to_string_lossy()}})),
+        )
+        .await;
+        assert!(status.is_success());
+        for _ in 0..3 {
+            let (status, _) = send(&app, "GET", "/v1/sys/mounts", root, None).await;
+            assert!(status.is_success());
+        }
+
+        let audited = std::fs::read_to_string(&log).unwrap();
+        let _ = std::fs::remove_file(&log);
+        let entries: Vec<serde_json::Value> = audited
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let (requests, responses): (Vec<_>, Vec<_>) =
+            entries.iter().partition(|e| e["response"].is_null());
+        assert_eq!(requests.len(), 3);
+        assert_eq!(responses.len(), 3);
+        for response in responses {
+            let paired: Vec<_> = requests
+                .iter()
+                .filter(|r| r["id"] == response["id"])
+                .collect();
+            assert_eq!(paired.len(), 1, "{response}");
+            assert_eq!(
+                paired[0]["request"]["request_id"],
+                response["request"]["request_id"]
+            );
+        }
+    }
+
+    #[cfg(feature = "cloud")]
+    #[tokio::test]
+    async fn cloud_logins_are_audited() {
+        let (app, _state, credentials) = dev_vault().await;
+        let root = Some(credentials.root_token.as_str());
+        let log = std::env::temp_dir().join(format!("zvault-audit-{}.log", uuid::Uuid::new_v4()));
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/audit/file",
+            root,
+            Some(serde_json::json!({"type": "file", "options": {"file_path": log.to_string_lossy()}})),
+        )
+        .await;
+        assert!(status.is_success());
+
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/auth/cloud/login",
+            None,
+            Some(serde_json::json!({"token": "not-a-service-token"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let audited = std::fs::read_to_string(&log).unwrap();
+        let _ = std::fs::remove_file(&log);
+        let logins: Vec<serde_json::Value> = audited
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|e| e["request"]["path"] == "auth/cloud/login")
+            .collect();
+        assert_eq!(logins.len(), 2, "{audited}");
+        assert!(logins.iter().all(|e| e["request"]["operation"] == "login"));
+        assert!(logins.iter().any(|e| e["response"]["status_code"] == 401));
+    }
+
+    #[tokio::test]
+    async fn disabling_an_audit_device_fails_closed() {
+        let (app, state, credentials) = dev_vault().await;
+        // A directory cannot be opened for appending, so every write fails.
+        state
+            .audit_manager
+            .enable(
+                "broken/",
+                Arc::new(FileAuditBackend::new(std::env::temp_dir())),
+            )
+            .await
+            .unwrap();
+
+        let (status, _) = send(
+            &app,
+            "DELETE",
+            "/v1/sys/audit/broken",
+            Some(&credentials.root_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(state.audit_manager.devices().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn runtime_transit_mount_keys_can_be_deleted() {
+        let (app, _state, credentials) = dev_vault().await;
+        let root = Some(credentials.root_token.as_str());
+        for (method, path, body) in [
+            (
+                "POST",
+                "/v1/sys/mounts/transit-team",
+                Some(serde_json::json!({"engine_type": "transit"})),
+            ),
+            ("POST", "/v1/transit-team/keys/app", None),
+            ("GET", "/v1/transit-team/keys/app", None),
+            ("DELETE", "/v1/transit-team/keys/app", None),
+        ] {
+            let (status, body) = send(&app, method, path, root, body).await;
+            assert!(status.is_success(), "{method} {path}: {status} {body}");
+        }
+
+        let (status, _) = send(&app, "GET", "/v1/transit-team/keys/app", root, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&app, "DELETE", "/v1/transit-team/keys/app", root, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn per_token_quotas_bucket_only_valid_tokens() {
+        let (app, state, credentials) = dev_vault().await;
+        state
+            .quotas
+            .put_rate_limit(zvault_core::quota::RateLimitQuota {
+                name: "per-token".to_owned(),
+                path: "secret/".to_owned(),
+                rate: 1.0,
+                interval_secs: 3600,
+                burst: Some(1),
+                per_token: true,
+            })
+            .await
+            .unwrap();
+        let default = default_token(&state).await;
+        let path = "/v1/secret/data/app";
+
+        let (status, _) = send(&app, "GET", path, Some(&credentials.root_token), None).await;
+        assert_ne!(status, StatusCode::TOO_MANY_REQUESTS);
+        let (status, _) = send(&app, "GET", path, Some(&credentials.root_token), None).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        let (status, _) = send(&app, "GET", path, Some(&default), None).await;
+        assert_ne!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        // Made-up tokens share the client address's bucket.
+        let (status, _) = send(&app, "GET", path, Some("made-up"), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&app, "GET", path, Some("also-made-up"), None).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn kv_secret_reads_are_forwarded() {
+        let (app, state, credentials) = dev_vault().await;
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/mounts/team",
+            Some(&credentials.root_token),
+            Some(serde_json::json!({"engine_type": "kv"})),
+        )
+        .await;
+        assert!(status.is_success());
+
+        for (path, writes) in [
+            ("secret/data/app", true),
+            ("team/data/app/db", true),
+            ("secret/metadata/app", false),
+            ("team/list", false),
+            ("transit/keys/app", false),
+            ("sys/mounts", false),
+        ] {
+            assert_eq!(
+                zvault_server::forward::read_writes(&state.mount_manager, path).await,
+                writes,
+                "{path}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn replicated_changes_reload_cached_state() {
+        let (_app, state, _credentials) = dev_vault().await;
+
+        // Write as the replicator does: to storage, behind the managers' backs.
+        MountManager::new(Arc::clone(&state.barrier))
+            .await
+            .unwrap()
+            .mount(MountEntry {
+                path: "replicated/".to_owned(),
+                engine_type: "kv".to_owned(),
+                description: String::new(),
+                config: serde_json::Value::Null,
+            })
+            .await
+            .unwrap();
+        QuotaManager::new(Arc::clone(&state.barrier))
+            .put_rate_limit(zvault_core::quota::RateLimitQuota {
+                name: "replicated".to_owned(),
+                path: String::new(),
+                rate: 100.0,
+                interval_secs: 1,
+                burst: None,
+                per_token: false,
+            })
+            .await
+            .unwrap();
+        let settings = zvault_core::audit::AuditSettings {
+            log_request_data: true,
+            ..zvault_core::audit::AuditSettings::default()
+        };
+        state
+            .barrier
+            .put(
+                "sys/audit/settings",
+                &serde_json::to_vec(&settings).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        zvault_server::replication::reload_changed(&state, ["sys/seal/keyring"].into_iter()).await;
+        assert!(state.engines.get_any("replicated/").await.is_none());
+        assert!(state.quotas.list_rate_limits().await.is_empty());
+        assert!(!state.audit_manager.settings().await.log_request_data);
+
+        let keys = [
+            "sys/mounts",
+            "sys/quotas/rate-limit/replicated",
+            "sys/audit/settings",
+        ];
+        zvault_server::replication::reload_changed(&state, keys.into_iter()).await;
+        assert!(state.engines.get_any("replicated/").await.is_some());
+        assert_eq!(state.quotas.list_rate_limits().await.len(), 1);
+        assert!(state.audit_manager.settings().await.log_request_data);
+    }
+
+    #[tokio::test]
+    async fn batch_tokens_have_no_accessor() {
+        let (app, _state, credentials) = dev_vault().await;
+        let root = credentials.root_token;
+        let create = "/v1/auth/token/create";
+
+        let (status, service) = send(
+            &app,
+            "POST",
+            create,
+            Some(&root),
+            Some(serde_json::json!({})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(service["accessor"].is_string());
+
+        let (status, batch) = send(
+            &app,
+            "POST",
+            create,
+            Some(&root),
+            Some(serde_json::json!({"type": "batch", "policies": ["default"]})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(batch["accessor"].is_null(), "{batch}");
+
+        let token = batch["client_token"].as_str().unwrap();
+        let (status, lookup) = send(
+            &app,
+            "POST",
+            "/v1/auth/token/lookup-self",
+            Some(token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(lookup["type"], "batch");
+        assert!(lookup["accessor"].is_null(), "{lookup}");
+    }
+
+    #[tokio::test]
+    async fn lease_reads_publish_lease_events() {
+        let (app, state, credentials) = dev_vault().await;
+        let root = credentials.root_token;
+        let mut events = state.event_bus.subscribe();
+
+        let tune = serde_json::json!({"config": {"lease_reads": true, "read_lease_ttl_secs": 60}});
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/mounts/secret/tune",
+            Some(&root),
+            Some(tune),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let secret = serde_json::json!({"password": "hunter2"});
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/secret/data/app",
+            Some(&root),
+            Some(secret),
+        )
+        .await;
+        assert!(status.is_success());
+        let (status, read) = send(&app, "GET", "/v1/secret/data/app", Some(&root), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(read["lease_duration"], 60);
+        let lease_id = read["lease_id"].as_str().unwrap().to_owned();
+
+        let revoke = serde_json::json!({"lease_id": lease_id});
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/v1/sys/leases/revoke",
+            Some(&root),
+            Some(revoke),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        // Tuning and reading publish nothing; the write and the revocation do.
+        let write = events.try_recv().unwrap();
+        assert_eq!(write.topic, TOPIC_KV_WRITE);
+        assert_eq!(write.data["path"], "secret/data/app");
+        let revoked = events.try_recv().unwrap();
+        assert_eq!(revoked.topic, zvault_core::events::TOPIC_LEASE_REVOKED);
+        assert_eq!(revoked.data["lease_id"], lease_id.as_str());
+        assert_eq!(revoked.data["engine_path"], "secret/data/app");
+        assert!(events.try_recv().is_err());
+    }
+
+    /// A dev vault whose license manager trusts a test key, with a Pro
+    /// license activated.
+    async fn licensed_dev_vault() -> (Router, Arc<AppState>, routes::sys::DevCredentials) {
+        use base64::Engine as _;
+        use ed25519_dalek::Signer as _;
+
+        let config = ServerConfig::from_settings(&Settings::env()).into_dev();
+        let (mut state, _) = build_app_state(&config, ConfigFile::default())
+            .await
+            .unwrap();
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let license_manager = Arc::new(LicenseManager::with_verifying_key(
+            Arc::clone(&state.barrier),
+            signing_key.verifying_key(),
+        ));
+        Arc::get_mut(&mut state).unwrap().license_manager = license_manager;
+        let credentials = routes::sys::init_dev(&state).await.unwrap();
+
+        let payload = serde_json::json!({
+            "tier": "pro",
+            "email": "ops@example.com",
+            "issued_at": "2025-01-01T00:00:00Z",
+            "expires_at": "2099-01-01T00:00:00Z",
+            "license_id": "lic_test",
+        });
+        let payload = base64::engine::general_purpose::STANDARD.encode(payload.to_string());
+        let signature = signing_key.sign(payload.as_bytes());
+        let key = format!(
+            "{payload}.{}",
+            base64::engine::general_purpose::STANDARD.encode(signature.to_bytes())
+        );
+        state.license_manager.activate(&key).await.unwrap();
+
+        let app = build_router(Arc::clone(&state), false);
+        (app, state, credentials)
+    }
+
+    /// A token with the given policies.
+    async fn token_with(state: &AppState, policies: &[&str]) -> String {
+        state
+            .token_store
+            .create(CreateTokenParams {
+                policies: policies.iter().map(|&p| p.to_owned()).collect(),
+                ttl: None,
+                max_ttl: None,
+                renewable: false,
+                parent_hash: None,
+                metadata: HashMap::new(),
+                display_name: "test".to_owned(),
+                bound_cidrs: Vec::new(),
+            })
+            .await
+            .unwrap()
+    }
+
+    /// Open an MCP SSE session, returning its ID and event stream, or the
+    /// status it was refused with.
+    async fn open_mcp_session(
+        app: &Router,
+        token: &str,
+    ) -> Result<(String, axum::body::BodyDataStream), StatusCode> {
+        let req = Request::builder()
+            .method("GET")
+            .uri("/v1/mcp")
+            .header("X-Vault-Token", token)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        if !response.status().is_success() {
+            return Err(response.status());
+        }
+        let mut events = response.into_body().into_data_stream();
+        let endpoint = next_event(&mut events).await.unwrap();
+        let id = endpoint
+            .split("session_id=")
+            .nth(1)
+            .and_then(|rest| rest.lines().next())
+            .unwrap()
+            .to_owned();
+        Ok((id, events))
+    }
+
+    /// The next SSE event, or `None` once the stream ends.
+    async fn next_event(events: &mut axum::body::BodyDataStream) -> Option<String> {
+        use futures_util::StreamExt as _;
+        let chunk = events.next().await?.unwrap();
+        Some(String::from_utf8(chunk.to_vec()).unwrap())
+    }
+
+    fn rpc(id: u64, method: &str, params: &serde_json::Value) -> serde_json::Value {
+        serde_json::json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    }
+
+    #[tokio::test]
+    async fn mcp_requires_a_pro_license() {
+        let (app, _state, credentials) = dev_vault().await;
+        let ping = rpc(1, "ping", &serde_json::json!({}));
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/v1/mcp",
+            Some(&credentials.root_token),
+            Some(ping),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["feature"], "mcp", "{body}");
+    }
+
+    #[tokio::test]
+    async fn mcp_sessions_belong_to_the_token_that_opened_them() {
+        let (app, state, credentials) = licensed_dev_vault().await;
+        let owner = credentials.root_token;
+        let other = token_with(&state, &["root"]).await;
+        let (id, mut events) = open_mcp_session(&app, &owner).await.unwrap();
+        let path = format!("/v1/mcp/messages?session_id={id}");
+
+        let ping = rpc(1, "ping", &serde_json::json!({}));
+        let (status, _) = send(&app, "POST", &path, Some(&other), Some(ping.clone())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&app, "POST", &path, None, Some(ping.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&app, "POST", &path, Some(&owner), Some(ping)).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let reply = next_event(&mut events).await.unwrap();
+        assert!(reply.starts_with("event: message"), "{reply}");
+        assert!(reply.contains(r#""id":1"#), "{reply}");
+
+        // The other token gets a session of its own, not a view of this one.
+        let (other_id, _other_events) = open_mcp_session(&app, &other).await.unwrap();
+        assert_ne!(other_id, id);
+    }
+
+    #[tokio::test]
+    async fn mcp_sessions_are_limited_per_token() {
+        let (app, state, credentials) = licensed_dev_vault().await;
+        let root = credentials.root_token;
+
+        // `MAX_SESSIONS_PER_TOKEN` sessions open; the next is refused.
+        let mut open = Vec::new();
+        for _ in 0..16 {
+            open.push(open_mcp_session(&app, &root).await.unwrap());
+        }
+        assert_eq!(
+            open_mcp_session(&app, &root).await.unwrap_err(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // The limit is per token.
+        let other = token_with(&state, &["root"]).await;
+        assert!(open_mcp_session(&app, &other).await.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn mcp_sessions_end_once_their_token_is_revoked() {
+        let (app, state, _credentials) = licensed_dev_vault().await;
+        let token = token_with(&state, &["root"]).await;
+        let (id, mut events) = open_mcp_session(&app, &token).await.unwrap();
+
+        state.token_store.revoke(&token).await.unwrap();
+        let path = format!("/v1/mcp/messages?session_id={id}");
+        let ping = rpc(1, "ping", &serde_json::json!({}));
+        let (status, _) = send(&app, "POST", &path, Some(&token), Some(ping)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // The idle stream rechecks the token and ends; only keep-alive
+        // comments come before.
+        while let Some(event) = next_event(&mut events).await {
+            assert!(event.starts_with(':'), "{event}");
+        }
+    }
+
+    #[tokio::test]
+    async fn mcp_tools_run_under_the_token_policy() {
+        let (app, state, credentials) = licensed_dev_vault().await;
+        state
+            .policy_store
+            .put(&zvault_core::policy::Policy {
+                name: "reader".to_owned(),
+                rules: vec![zvault_core::policy::PolicyRule {
+                    path: "secret/**".to_owned(),
+                    capabilities: vec![
+                        zvault_core::policy::Capability::Read,
+                        zvault_core::policy::Capability::List,
+                    ],
+                    control_group: None,
+                    mfa_methods: Vec::new(),
+                }],
+            })
+            .await
+            .unwrap();
+        let reader = token_with(&state, &["reader"]).await;
+
+        let list = rpc(
+            1,
+            "tools/call",
+            &serde_json::json!({"name": "zvault_list_secrets", "arguments": {"path": "app"}}),
+        );
+        let (status, body) = send(&app, "POST", "/v1/mcp", Some(&reader), Some(list)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["result"]["isError"].is_null(), "{body}");
+
+        let set = rpc(
+            2,
+            "tools/call",
+            &serde_json::json!({
+                "name": "zvault_set_secret",
+                "arguments": {"path": "app/db", "value": "hunter2"},
+            }),
+        );
+        let (status, body) = send(&app, "POST", "/v1/mcp", Some(&reader), Some(set)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["result"]["isError"], true, "{body}");
+        let text = body["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("permission denied"), "{text}");
+        assert!(!body.to_string().contains("hunter2"));
+
+        let (status, _) = send(
+            &app,
+            "GET",
+            "/v1/secret/data/app/db",
+            Some(&credentials.root_token),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
     }
 }
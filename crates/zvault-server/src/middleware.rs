@@ -1,17 +1,102 @@
-//! Authentication middleware for `ZVault`.
+//! Request middleware for `ZVault`.
 //!
-//! Extracts the `X-Vault-Token` header, validates it against the token store,
-//! and injects the token entry into the request extensions for downstream
-//! handlers to use for policy checks.
+//! Every `/v1/` request passes through these layers, outermost first:
+//!
+//! 1. [`request_info_middleware`] — client address and request ID.
+//! 2. [`forward_middleware`] — writes on a replica or HA standby go to the
+//!    primary or active node.
+//! 3. [`http_metrics_middleware`] — status and latency per route.
+//! 4. [`quota_middleware`] — rate limit quotas.
+//! 5. [`auth_middleware`] — token validation, audit, MFA, and control
+//!    groups; login routes get [`login_audit_middleware`] instead.
+//! 6. [`wrap_middleware`] and [`license_middleware`] — response wrapping
+//!    and license gates, on the routes that use them.
 
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Instant;
 
-use axum::extract::{Request, State};
-use axum::http::StatusCode;
+use axum::body::Bytes;
+use axum::extract::{ConnectInfo, FromRequest, MatchedPath, Request, State};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode, header};
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
+use ipnet::IpNet;
+use zvault_core::access_request::Actor;
+use zvault_core::activity::{ActivityLog, ROOT_NAMESPACE};
+use zvault_core::audit::{AuditAuth, AuditEntry, AuditRequest, AuditResponse};
+use zvault_core::control_group::{ParkedRequest, run_approved};
+use zvault_core::error::{AuditError, IdentityError, TokenError};
+use zvault_core::identity::ENTITY_ID_METADATA;
+use zvault_core::license::Feature;
+use zvault_core::metrics;
+use zvault_core::mfa::{self, run_verified};
+use zvault_core::policy::Capability;
+use zvault_core::token::{TokenEntry, hash_token, token_accessor};
+use zvault_core::wrapping::{MAX_WRAP_TTL_SECS, is_wrapping_token};
 
+use crate::error::AppError;
+use crate::forward;
+use crate::replication::Replication;
+use crate::routes::auth::parse_duration;
+use crate::routes::mfa::verify_credentials;
 use crate::state::AppState;
+use crate::tls::TlsConnectInfo;
+
+/// Header carrying the request ID, in both directions.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Largest plain-text error body [`request_info_middleware`] turns into a
+/// JSON error response; anything longer is replaced by the status reason.
+const MAX_TEXT_ERROR_BYTES: usize = 4096;
+
+/// Largest request body recorded in an audit entry.
+const MAX_AUDITED_BODY: u64 = 2 * 1024 * 1024;
+
+tokio::task_local! {
+    /// ID of the request being served.
+    static REQUEST_ID: String;
+}
+
+/// The ID of the request being served, inside [`request_info_middleware`].
+#[must_use]
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// A token [`quota_middleware`] has looked up, reused by [`auth_middleware`]
+/// so a request costs one token store lookup.
+#[derive(Debug, Clone)]
+struct LookedUpToken {
+    token: String,
+    entry: TokenEntry,
+}
+
+/// Where a request came from and what it is called, injected into request
+/// extensions by [`request_info_middleware`].
+#[derive(Debug, Clone, Default)]
+pub struct RequestInfo {
+    /// Client address, if known.
+    pub client_ip: Option<IpAddr>,
+    /// Request ID: the client's `X-Request-Id`, if usable, else a new UUID.
+    pub request_id: String,
+}
+
+impl RequestInfo {
+    /// The client address as recorded in audit entries.
+    #[must_use]
+    pub fn remote_addr(&self) -> String {
+        self.client_ip
+            .map_or_else(|| "unknown".to_owned(), |ip| ip.to_string())
+    }
+
+    /// The request's info, or an empty one outside [`request_info_middleware`].
+    #[must_use]
+    pub fn of(req: &Request) -> Self {
+        req.extensions().get::<Self>().cloned().unwrap_or_default()
+    }
+}
 
 /// Authentication context injected into request extensions.
 #[derive(Debug, Clone)]
@@ -22,11 +107,41 @@ pub struct AuthContext {
     pub policies: Vec<String>,
     /// Display name for audit.
     pub display_name: String,
+    /// Identity entity the token belongs to, if any.
+    pub entity_id: Option<String>,
+    /// Networks the token may be used from (empty = anywhere).
+    pub bound_cidrs: Vec<String>,
+    /// Whether the token is a batch token, which has no stored entry.
+    pub batch: bool,
+}
+
+impl AuthContext {
+    /// The caller as a requester or approver.
+    #[must_use]
+    pub fn actor(&self) -> Actor<'_> {
+        Actor {
+            entity_id: self.entity_id.as_deref(),
+            token_hash: &self.token_hash,
+            display_name: &self.display_name,
+        }
+    }
 }
 
 /// Middleware that validates the `X-Vault-Token` header.
 ///
-/// Skips auth for health and seal-status endpoints.
+/// Skips auth for health and seal-status endpoints. The token's
+/// [`AuthContext`] is injected for handlers' policy checks, with the
+/// policies of its identity entity and groups and of approved access
+/// requests added; tokens of a disabled entity, tokens with bound CIDRs
+/// used from another network, and wrapping tokens outside
+/// `sys/wrapping/*` are refused. MFA codes in `X-Vault-MFA` are validated
+/// against the token's identity, so policy rules with `mfa_methods` see
+/// them; a bad code fails the request.
+///
+/// The request is counted towards client activity and recorded in the
+/// audit log before the handler runs — fail-closed: a request no audit
+/// device accepts is refused, even one disabling an audit device. Its
+/// response is recorded under the same entry and request IDs.
 pub async fn auth_middleware(
     State(state): State<Arc<AppState>>,
     mut req: Request,
@@ -39,6 +154,7 @@ pub async fn auth_middleware(
         || path == "/v1/sys/seal-status"
         || path == "/v1/sys/init"
         || path == "/v1/sys/unseal"
+        || path == "/v1/sys/version"
         || path.starts_with("/app/")
         || path == "/app"
         || path == "/"
@@ -53,28 +169,681 @@ pub async fn auth_middleware(
         .map(String::from);
 
     let Some(token) = token else {
-        return (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(serde_json::json!({"error": "unauthorized", "message": "missing X-Vault-Token header"})),
-        ).into_response();
+        return AppError::Unauthorized("missing X-Vault-Token header".to_owned()).into_response();
     };
 
-    match state.token_store.lookup(&token).await {
+    let info = RequestInfo::of(&req);
+    let looked_up = req
+        .extensions_mut()
+        .remove::<LookedUpToken>()
+        .filter(|looked_up| looked_up.token == token);
+    let lookup = match looked_up {
+        Some(looked_up) => Ok(looked_up.entry),
+        None => state.token_store.lookup(&token).await,
+    };
+    match lookup {
+        Ok(entry) if !entry.allows_client(info.client_ip) => {
+            AppError::Forbidden(format!("token may not be used from {}", info.remote_addr()))
+                .into_response()
+        }
+        Ok(entry) if is_wrapping_token(&entry) && !path.starts_with("/v1/sys/wrapping/") => {
+            AppError::Forbidden("wrapping tokens can only be used to unwrap".to_owned())
+                .into_response()
+        }
         Ok(entry) => {
-            let ctx = AuthContext {
+            let mut ctx = AuthContext {
                 token_hash: entry.token_hash.clone(),
                 policies: entry.policies.clone(),
                 display_name: entry.display_name.clone(),
+                entity_id: entry.metadata.get(ENTITY_ID_METADATA).cloned(),
+                bound_cidrs: entry.bound_cidrs.clone(),
+                batch: entry.batch,
+            };
+            if let Err(e) = attach_identity_policies(&state, &mut ctx).await {
+                return e.into_response();
+            }
+            attach_access_grants(&state, &mut ctx).await;
+            let identity = mfa::identity(ctx.entity_id.as_deref(), &ctx.display_name);
+            let verified = match verify_credentials(&state, identity, req.headers()).await {
+                Ok(verified) => verified,
+                Err(e) => return e.into_response(),
+            };
+            let method = req.method().clone();
+            req.extensions_mut().insert(ctx.clone());
+            record_activity(&state, &ctx, &path).await;
+            let (req, data) = match capture_request_data(&state, req).await {
+                Ok(captured) => captured,
+                Err(e) => return e.into_response(),
+            };
+            // Fail closed: a request no audit device recorded is not served.
+            let entry = match audit_request(&state, Some(&ctx), &method, &path, &info, data).await {
+                Ok(entry) => entry,
+                Err(e) => return audit_failure(&path, &e),
             };
-            req.extensions_mut().insert(ctx);
-            next.run(req).await
+            let response = run_verified(verified, run_controlled(&state, &ctx, req, next)).await;
+            if let Err(e) = audit_response(&state, entry, response.status()).await {
+                return audit_failure(&path, &e);
+            }
+
+            response
         }
-        Err(_) => (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(
-                serde_json::json!({"error": "unauthorized", "message": "invalid or expired token"}),
-            ),
+        // A sealed vault or failing storage is not the caller's fault.
+        Err(e @ TokenError::Barrier(_)) => AppError::from(e).into_response(),
+        Err(_) => AppError::Unauthorized("invalid or expired token".to_owned()).into_response(),
+    }
+}
+
+/// Add the policies of the token's entity and its groups to `ctx`.
+///
+/// A token whose entity has since been deleted keeps only its own policies.
+async fn attach_identity_policies(state: &AppState, ctx: &mut AuthContext) -> Result<(), AppError> {
+    let Some(entity_id) = ctx.entity_id.as_deref() else {
+        return Ok(());
+    };
+    match state.identity.policies(entity_id).await {
+        Ok(policies) => {
+            for policy in policies {
+                if !ctx.policies.contains(&policy) {
+                    ctx.policies.push(policy);
+                }
+            }
+            Ok(())
+        }
+        Err(IdentityError::EntityNotFound { .. }) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Add policies from approved, unexpired access requests to `ctx`.
+///
+/// A failed lookup only means no temporary access is granted for this
+/// request; the token's own policies still apply.
+async fn attach_access_grants(state: &AppState, ctx: &mut AuthContext) {
+    let requester = ActivityLog::client_id(ctx.entity_id.as_deref(), &ctx.token_hash).0;
+    match state.access_requests.active_policies(&requester).await {
+        Ok(policies) => ctx.policies.extend(policies),
+        Err(e) => tracing::warn!(error = %e, "failed to load access grants"),
+    }
+}
+
+/// Run the request, parking it instead if a control group must approve it
+/// first, or replaying the parked request named by `X-Vault-Control-Group`.
+///
+/// Parking is decided from the token's policies and the request's method
+/// and path alone, so the handler never sees a request awaiting approval
+/// and only parked requests have their body buffered. A parked request is
+/// answered with `202 Accepted` and an accessor; once approved, the caller
+/// repeats the method and path with the accessor in the header, and the
+/// parked request runs with its original body.
+async fn run_controlled(state: &AppState, ctx: &AuthContext, req: Request, next: Next) -> Response {
+    let actor = ctx.actor();
+    let accessor = req
+        .headers()
+        .get("X-Vault-Control-Group")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+    if let Some(accessor) = accessor {
+        return replay_parked(state, actor, &accessor, req, next).await;
+    }
+
+    let policy_path = req.uri().path();
+    let policy_path = policy_path.strip_prefix("/v1/").unwrap_or(policy_path);
+    let control_groups = match state
+        .policy_store
+        .control_groups(
+            &ctx.policies,
+            policy_path,
+            method_capabilities(req.method()),
+        )
+        .await
+    {
+        Ok(groups) if groups.is_empty() => return next.run(req).await,
+        Ok(groups) => groups,
+        Err(e) => return AppError::from(e).into_response(),
+    };
+
+    let method = req.method().to_string();
+    let path = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| req.uri().path().to_owned(), ToString::to_string);
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+    let body = match buffer_body(req).await {
+        Ok((_, body)) => body,
+        Err(response) => return response,
+    };
+    let parked = ParkedRequest {
+        method,
+        path,
+        content_type,
+        body: body.to_vec(),
+        control_groups,
+    };
+    match state.control_groups.park(actor, parked).await {
+        Ok(parked) => (
+            StatusCode::ACCEPTED,
+            axum::Json(serde_json::json!({
+                "control_group": {
+                    "accessor": parked.accessor,
+                    "path": parked.path,
+                    "created_at": parked.created_at,
+                    "expires_at": parked.expires_at,
+                    "control_groups": parked.control_groups,
+                }
+            })),
         )
             .into_response(),
+        Err(e) => AppError::from(e).into_response(),
+    }
+}
+
+/// Capabilities a request with `method` may be checked for, any of which
+/// puts it behind the control groups of the rules granting it.
+fn method_capabilities(method: &Method) -> &'static [Capability] {
+    match *method {
+        Method::GET | Method::HEAD => &[Capability::Read, Capability::List, Capability::Sudo],
+        Method::POST | Method::PUT | Method::PATCH => {
+            &[Capability::Create, Capability::Update, Capability::Sudo]
+        }
+        Method::DELETE => &[Capability::Delete, Capability::Sudo],
+        _ => &[],
+    }
+}
+
+/// Replay the approved request parked under `accessor` in place of `req`.
+///
+/// Only the method and path of `req` are used; the parked query string,
+/// body, and `Content-Type` replace its own.
+async fn replay_parked(
+    state: &AppState,
+    actor: Actor<'_>,
+    accessor: &str,
+    req: Request,
+    next: Next,
+) -> Response {
+    let parked = match state
+        .control_groups
+        .claim(accessor, actor, req.method().as_str(), req.uri().path())
+        .await
+    {
+        Ok(parked) => parked,
+        Err(e) => return AppError::from(e).into_response(),
+    };
+    let body = match parked.body_bytes() {
+        Ok(body) => body,
+        Err(e) => return AppError::from(e).into_response(),
+    };
+
+    let (mut parts, _) = req.into_parts();
+    if let Ok(uri) = parked.path.parse() {
+        parts.uri = uri;
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    match parked
+        .content_type
+        .as_deref()
+        .and_then(|v| HeaderValue::from_str(v).ok())
+    {
+        Some(value) => parts.headers.insert(header::CONTENT_TYPE, value),
+        None => parts.headers.remove(header::CONTENT_TYPE),
+    };
+    let req = Request::from_parts(parts, axum::body::Body::from(body));
+    run_approved(next.run(req)).await
+}
+
+/// Route layer that rejects requests unless the active license unlocks `feature`.
+///
+/// Applied with `from_fn_with_state((state, Feature::X), license_middleware)`
+/// on routers serving paid features.
+pub async fn license_middleware(
+    State((state, feature)): State<(Arc<AppState>, Feature)>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Err(e) = state.license_manager.check(feature).await {
+        return AppError::from(e).into_response();
+    }
+    next.run(req).await
+}
+
+/// Route layer that wraps the response when the request sets
+/// `X-Vault-Wrap-TTL` (`300`, `5m`, `1h`, ...).
+///
+/// A successful JSON response is stored in the cubbyhole of a new single-use
+/// wrapping token and replaced by `{"wrap_info": {...}}`. Error responses are
+/// returned unwrapped so the caller can see why the request failed.
+pub async fn wrap_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(header) = req.headers().get("X-Vault-Wrap-TTL") else {
+        return next.run(req).await;
+    };
+    let ttl = match header
+        .to_str()
+        .map_err(|_| AppError::BadRequest("invalid X-Vault-Wrap-TTL header".to_owned()))
+        .and_then(parse_duration)
+    {
+        Ok(ttl) if ttl.num_seconds() > 0 && ttl.num_seconds() <= MAX_WRAP_TTL_SECS => ttl,
+        Ok(_) => {
+            return AppError::BadRequest(format!(
+                "X-Vault-Wrap-TTL must be between 1 and {MAX_WRAP_TTL_SECS} seconds"
+            ))
+            .into_response();
+        }
+        Err(e) => return e.into_response(),
+    };
+    let path = req.uri().path().trim_start_matches("/v1/").to_owned();
+
+    let response = next.run(req).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return AppError::Internal(format!("failed to read response: {e}")).into_response();
+        }
+    };
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        // Nothing to wrap (e.g. 204 No Content): pass the response through.
+        return Response::from_parts(parts, axum::body::Body::from(bytes));
+    };
+
+    match state
+        .response_wrapper
+        .wrap(&state.token_store, value, ttl, &path)
+        .await
+    {
+        Ok(info) => axum::Json(serde_json::json!({ "wrap_info": info })).into_response(),
+        Err(e) => AppError::from(e).into_response(),
+    }
+}
+
+/// Layer that applies rate limit quotas to every `/v1/` request.
+///
+/// Requests are keyed by the hash of their `X-Vault-Token` once the token
+/// store has validated it, and otherwise by client address; the validated
+/// entry is handed on to [`auth_middleware`] rather than looked up again.
+/// It runs ahead of auth so floods never reach the rest of the chain; a
+/// request over its quota gets `429 Too Many Requests` and `Retry-After`.
+/// Health checks and the quota endpoints themselves are exempt so an
+/// operator can always undo a bad quota.
+pub async fn quota_middleware(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(path) = req.uri().path().strip_prefix("/v1/") else {
+        return next.run(req).await;
+    };
+    if path == "sys/health" || path.starts_with("sys/quotas/") {
+        return next.run(req).await;
+    }
+    let path = path.to_owned();
+    let token = req
+        .headers()
+        .get("X-Vault-Token")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+    // Only a token the store knows gets its own bucket; anything else counts
+    // against the client address, so made-up tokens cannot dodge the quota.
+    let mut client = None;
+    if let Some(token) = token
+        && state.quotas.is_per_token(&path).await
+        && let Ok(entry) = state.token_store.lookup(&token).await
+    {
+        client = Some(hash_token(&token));
+        req.extensions_mut().insert(LookedUpToken { token, entry });
+    }
+    let client = client.unwrap_or_else(|| RequestInfo::of(&req).remote_addr());
+    if let Err(e) = state.quotas.check_rate(&path, &client).await {
+        return AppError::from(e).into_response();
+    }
+    next.run(req).await
+}
+
+/// Layer that forwards every `/v1/` request that may write to the server
+/// that takes writes: the primary, on a replica, or the active node, on an
+/// HA standby. Reads that do not write and this server's own seal, leader,
+/// and replication endpoints are served locally. Forwarded requests are
+/// authenticated and audited by the server they are forwarded to.
+pub async fn forward_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let replicator = match &state.replication {
+        Some(Replication::Replica(replicator)) => Some(replicator),
+        _ => None,
+    };
+    let standby = state.ha.as_ref().filter(|ha| !ha.is_active());
+    if replicator.is_none() && standby.is_none() {
+        return next.run(req).await;
+    }
+
+    let forwards = match req.uri().path().strip_prefix("/v1/") {
+        Some(path) if forward::serves_locally(req.method(), path) => {
+            forward::read_writes(&state.mount_manager, path).await
+        }
+        Some(_) => true,
+        None => false,
+    };
+    match (forwards, replicator, standby) {
+        (true, Some(replicator), _) => replicator.forward(req).await,
+        (true, None, Some(ha)) => ha.forward(req).await,
+        _ => next.run(req).await,
+    }
+}
+
+/// Route layer that records each request's status and latency in the
+/// metrics registry, labelled with the matched route template so secret
+/// paths never become label values.
+pub async fn http_metrics_middleware(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_owned(), |p| p.as_str().to_owned());
+    let start = Instant::now();
+    let response = next.run(req).await;
+    metrics::global()
+        .observe_http(&method, &route, response.status().as_u16(), start.elapsed())
+        .await;
+    response
+}
+
+/// Route layer that audits login requests, which carry no token.
+///
+/// As with authenticated requests, a login no audit device records is
+/// refused before it is served (fail-closed).
+pub async fn login_audit_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().to_owned();
+    let method = req.method().clone();
+    let info = RequestInfo::of(&req);
+    let (req, data) = match capture_request_data(&state, req).await {
+        Ok(captured) => captured,
+        Err(e) => return e.into_response(),
+    };
+    let entry = match audit_request(&state, None, &method, &path, &info, data).await {
+        Ok(entry) => entry,
+        Err(e) => return audit_failure(&path, &e),
+    };
+    let response = next.run(req).await;
+    if let Err(e) = audit_response(&state, entry, response.status()).await {
+        return audit_failure(&path, &e);
+    }
+
+    response
+}
+
+/// Buffer the JSON request body for the audit entry, if request data is
+/// logged and there are devices to log it to. The audit manager applies
+/// the redaction rules. Bodies declared larger than [`MAX_AUDITED_BODY`],
+/// such as restored snapshots, are not recorded.
+async fn capture_request_data(
+    state: &AppState,
+    req: Request,
+) -> Result<(Request, Option<serde_json::Value>), Response> {
+    let declared_len = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if matches!(*req.method(), Method::GET | Method::HEAD)
+        || declared_len.is_some_and(|len| len > MAX_AUDITED_BODY)
+        || !state.audit_manager.has_backends().await
+        || !state.audit_manager.logs_request_data().await
+    {
+        return Ok((req, None));
+    }
+    let (parts, bytes) = buffer_body(req).await?;
+    let data = serde_json::from_slice(&bytes).ok();
+    Ok((
+        Request::from_parts(parts, axum::body::Body::from(bytes)),
+        data,
+    ))
+}
+
+/// Buffer a request body within axum's body limit — 2 MiB unless a
+/// `DefaultBodyLimit` layer sets another — so middleware never holds more
+/// than the `Json` extractor would accept. Larger bodies get a 413.
+async fn buffer_body(req: Request) -> Result<(Parts, Bytes), Response> {
+    let (parts, body) = req.into_parts();
+    let bytes = Bytes::from_request(Request::from_parts(parts.clone(), body), &())
+        .await
+        .map_err(|rejection| {
+            crate::error::status_response(rejection.status(), &rejection.body_text())
+        })?;
+    Ok((parts, bytes))
+}
+
+/// Response for a request whose audit entry no device accepted.
+fn audit_failure(path: &str, err: &AuditError) -> Response {
+    tracing::error!(error = %err, path = %path, "audit logging failed, denying request");
+    AppError::Internal("audit logging failed".to_owned()).into_response()
+}
+
+/// Record a request in the audit log before it is served, returning the
+/// entry for [`audit_response`], or `None` without audit devices. Requests
+/// without `ctx` are logins.
+async fn audit_request(
+    state: &AppState,
+    ctx: Option<&AuthContext>,
+    method: &Method,
+    path: &str,
+    info: &RequestInfo,
+    data: Option<serde_json::Value>,
+) -> Result<Option<AuditEntry>, AuditError> {
+    if !state.audit_manager.has_backends().await {
+        return Ok(None);
+    }
+
+    let operation = match (ctx, method) {
+        (None, _) => "login",
+        (_, &Method::GET | &Method::HEAD) => "read",
+        (_, &Method::DELETE) => "delete",
+        _ => "write",
+    };
+
+    let entry = AuditEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now(),
+        request: AuditRequest {
+            operation: operation.to_owned(),
+            path: path.trim_start_matches("/v1/").to_owned(),
+            data,
+            remote_addr: info.remote_addr(),
+            request_id: info.request_id.clone(),
+        },
+        response: None,
+        auth: ctx.map_or_else(
+            || AuditAuth {
+                token_id: String::new(),
+                accessor: String::new(),
+                policies: Vec::new(),
+                metadata: std::collections::HashMap::new(),
+            },
+            |ctx| audit_auth(state, ctx),
+        ),
+    };
+
+    state.audit_manager.log(&entry).await?;
+    Ok(Some(entry))
+}
+
+/// Record the response to a request [`audit_request`] logged as `entry`.
+/// The response entry keeps the request entry's `id` and request ID, so
+/// the two pair up in the log.
+async fn audit_response(
+    state: &AppState,
+    entry: Option<AuditEntry>,
+    status: StatusCode,
+) -> Result<(), AuditError> {
+    let Some(mut entry) = entry else {
+        return Ok(());
+    };
+    entry.timestamp = chrono::Utc::now();
+    entry.response = Some(AuditResponse {
+        status_code: status.as_u16(),
+        error: (status.is_client_error() || status.is_server_error())
+            .then(|| status.canonical_reason().unwrap_or("error").to_owned()),
+    });
+    state.audit_manager.log(&entry).await
+}
+
+/// The auth portion of an audit entry for the caller in `ctx`.
+pub(crate) fn audit_auth(state: &AppState, ctx: &AuthContext) -> AuditAuth {
+    AuditAuth {
+        token_id: state.audit_manager.hmac_field(&ctx.token_hash),
+        accessor: if ctx.batch {
+            String::new()
+        } else {
+            token_accessor(&ctx.token_hash)
+        },
+        policies: ctx.policies.clone(),
+        metadata: std::collections::HashMap::from([(
+            "display_name".to_owned(),
+            ctx.display_name.clone(),
+        )]),
+    }
+}
+
+/// Count the request towards monthly client activity.
+///
+/// Activity is informational, so failures are logged rather than surfaced.
+async fn record_activity(state: &AppState, ctx: &AuthContext, path: &str) {
+    let (client_id, client_type) =
+        ActivityLog::client_id(ctx.entity_id.as_deref(), &ctx.token_hash);
+    let mount = ActivityLog::mount_for_path(path);
+    if let Err(e) = state
+        .activity_log
+        .record(&client_id, client_type, ROOT_NAMESPACE, &mount)
+        .await
+    {
+        tracing::warn!(error = %e, "failed to record client activity");
+    }
+}
+
+/// Layer that gives every request a [`RequestInfo`] and returns its ID in
+/// the `X-Request-Id` response header.
+///
+/// The client address is the connection's peer, or the address it
+/// forwarded for when the peer is a trusted proxy.
+/// The ID is also set on the request, so a forwarded request keeps it, and
+/// in error response bodies. Failures without a JSON body, such as requests
+/// the router or an extractor rejected, get the standard error body (see
+/// [`crate::error`]).
+pub async fn request_info_middleware(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<TlsConnectInfo>>()
+        .map(|ConnectInfo(info)| info.remote_addr.ip())
+        .or_else(|| {
+            req.extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip())
+        });
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic()))
+        .map_or_else(|| uuid::Uuid::new_v4().to_string(), str::to_owned);
+    let info = RequestInfo {
+        client_ip: client_ip(peer, req.headers(), &state.trusted_proxies),
+        request_id,
+    };
+    let header = HeaderValue::from_str(&info.request_id).ok();
+    if let Some(value) = &header {
+        req.headers_mut().insert(REQUEST_ID_HEADER, value.clone());
+    }
+    let request_id = info.request_id.clone();
+    req.extensions_mut().insert(info);
+
+    let mut response = REQUEST_ID
+        .scope(request_id.clone(), async move {
+            let response = next.run(req).await;
+            if is_bare_error(&response) {
+                json_error(response).await
+            } else {
+                response
+            }
+        })
+        .await;
+    if let Some(value) = header {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Whether `response` is a failure with no body or a plain-text one.
+fn is_bare_error(response: &Response) -> bool {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return false;
+    }
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.starts_with("text/plain"))
+}
+
+/// Replace a bare failure with the standard error body, keeping its status
+/// and headers and using its text as the message.
+async fn json_error(response: Response) -> Response {
+    let (parts, body) = response.into_parts();
+    let message = match axum::body::to_bytes(body, MAX_TEXT_ERROR_BYTES).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(_) => String::new(),
+    };
+    let mut json = crate::error::status_response(parts.status, &message);
+    for (name, value) in &parts.headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            json.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+    json
+}
+
+/// The client behind a connection from `peer`. `X-Forwarded-For` is only
+/// believed from a trusted proxy: the client is the last hop added before
+/// the request reached the first of a chain of trusted proxies.
+fn client_ip(peer: Option<IpAddr>, headers: &HeaderMap, trusted: &[IpNet]) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted.iter().any(|net| net.contains(ip));
+    let mut client = peer?.to_canonical();
+    if !is_trusted(&client) {
+        return Some(client);
+    }
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect();
+    for hop in hops.iter().rev() {
+        let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip.to_canonical();
+        if !is_trusted(&client) {
+            break;
+        }
     }
+    Some(client)
 }
@@ -0,0 +1,753 @@
+//! Startup self-test and configuration linting.
+//!
+//! The same checks back `zvault-server --check-config`, which prints the
+//! report and exits, and every normal boot, which logs the report and refuses
+//! to bind a listener if any check fails. They run once storage is open and
+//! auto-unseal has been attempted, so they see exactly what the server would:
+//!
+//! - **config** — settings that are legal but risky, and environment values
+//!   that [`ServerConfig::from_env`] silently replaces with a default.
+//! - **storage** — a raw write, read, and delete of a probe key.
+//! - **seal** — seal status, the KMS key round trip, and the HSM module.
+//! - **clock** — skew against the `Date` header of a reference server.
+//! - **core dumps** / **mlock** — the process hardening from [`crate::hardening`].
+//! - **tls** — the certificate files, or the ACME placeholder certificate
+//!   and, once unsealed, the stored ACME certificate chain.
+//!
+//! Warnings never block startup; failures always do.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use zvault_core::acme::{self, Http01Responder};
+use zvault_core::kms::{DevKms, SealWrapper};
+use zvault_core::seal::SEAL_TYPE_SHAMIR;
+
+use crate::config::{
+    AcmeDnsProvider, ReplicationRole, ServerConfig, Settings, StorageBackendType, TlsFileConfig,
+};
+use crate::hardening::{self, Hardening};
+use crate::state::AppState;
+use crate::tls;
+
+/// Raw storage key written and removed by the storage probe.
+const STORAGE_PROBE_KEY: &str = "sys/preflight/probe";
+
+/// Time allowed for the clock reference server to answer.
+const CLOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Skew above which the clock check warns.
+const CLOCK_SKEW_WARN: chrono::Duration = chrono::Duration::seconds(5);
+
+/// Skew above which tokens, TOTP codes, and certificates stop validating.
+const CLOCK_SKEW_FAIL: chrono::Duration = chrono::Duration::seconds(60);
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The check passed.
+    Pass,
+    /// The server can start, but the operator should look at this.
+    Warn,
+    /// The server must not start.
+    Fail,
+    /// The check does not apply to this configuration.
+    Skip,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+            Self::Skip => "SKIP",
+        })
+    }
+}
+
+/// Result of one check.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// Which subsystem was checked (e.g. `storage`).
+    pub name: &'static str,
+    /// Outcome.
+    pub status: Status,
+    /// Human-readable explanation.
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// All checks from one self-test run, in the order they ran.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    /// Individual check results.
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Whether any check failed.
+    #[must_use]
+    pub fn failed(&self) -> bool {
+        self.checks.iter().any(|c| c.status == Status::Fail)
+    }
+
+    /// Log every check at a level matching its status.
+    pub fn log(&self) {
+        for check in &self.checks {
+            let (name, detail) = (check.name, check.detail.as_str());
+            match check.status {
+                Status::Pass | Status::Skip => {
+                    info!(check = name, status = %check.status, detail, "self-test");
+                }
+                Status::Warn => warn!(check = name, detail, "self-test warning"),
+                Status::Fail => error!(check = name, detail, "self-test failed"),
+            }
+        }
+    }
+}
+
+/// Aligned text, one check per line, followed by a summary line.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+        for check in &self.checks {
+            writeln!(
+                f,
+                "[{}] {:width$}  {}",
+                check.status, check.name, check.detail
+            )?;
+        }
+        let count = |status| self.checks.iter().filter(|c| c.status == status).count();
+        writeln!(
+            f,
+            "{} passed, {} warnings, {} failed, {} skipped",
+            count(Status::Pass),
+            count(Status::Warn),
+            count(Status::Fail),
+            count(Status::Skip)
+        )
+    }
+}
+
+/// Disable core dumps and lock memory, reporting what took effect.
+///
+/// Runs before anything else so no key material is ever loaded into memory
+/// that could be dumped or swapped. Unsupported platforms and missing
+/// privileges are warnings, so development machines still boot.
+#[must_use]
+pub fn apply_hardening(config: &ServerConfig) -> Vec<Check> {
+    let os = std::env::consts::OS;
+    let core_dumps = match hardening::disable_core_dumps() {
+        Ok(Hardening::Applied) => Check::new("core dumps", Status::Pass, "disabled"),
+        Ok(Hardening::Unsupported) => Check::new(
+            "core dumps",
+            Status::Warn,
+            format!("core dump suppression is not supported on {os}"),
+        ),
+        Err(e) => Check::new("core dumps", Status::Warn, format!("not disabled: {e}")),
+    };
+
+    let mlock = if config.disable_mlock {
+        Check::new(
+            "mlock",
+            Status::Warn,
+            "disabled via ZVAULT_DISABLE_MLOCK — secrets may be swapped to disk",
+        )
+    } else {
+        match hardening::lock_memory() {
+            Ok(Hardening::Applied) => Check::new("mlock", Status::Pass, "all memory locked"),
+            Ok(Hardening::Unsupported) => Check::new(
+                "mlock",
+                Status::Warn,
+                format!("memory locking is not supported on {os} — secrets may be swapped to disk"),
+            ),
+            Err(e) => Check::new(
+                "mlock",
+                Status::Warn,
+                format!("{e} (grant CAP_IPC_LOCK, or set ZVAULT_DISABLE_MLOCK=true for dev)"),
+            ),
+        }
+    };
+
+    vec![core_dumps, mlock]
+}
+
+/// Run every check against a fully built server state.
+///
+/// `settings` is where `config` was loaded from, so raw values are linted
+/// whether they come from the environment or the configuration file.
+/// `hardening` carries the results of [`apply_hardening`], which has to run
+/// before the state is built.
+pub async fn run(
+    config: &ServerConfig,
+    settings: &Settings,
+    state: &AppState,
+    hardening: Vec<Check>,
+) -> Report {
+    let mut checks = lint(config, |name| settings.var(name).ok());
+    checks.push(storage(config, state).await);
+    checks.push(seal(config, state).await);
+    if let Some(check) = hsm(config) {
+        checks.push(check);
+    }
+    checks.push(clock(config).await);
+    checks.extend(hardening);
+    checks.push(tls_chain(config, state).await);
+    Report { checks }
+}
+
+/// Lint `config`, reading raw environment values through `env` to catch
+/// the ones `from_env` ignored.
+fn lint(config: &ServerConfig, env: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut advise = |detail: String| checks.push(Check::new("config", Status::Warn, detail));
+
+    if config.dev {
+        advise("dev mode — in-memory, auto-unsealed vault; never use in production".to_owned());
+    } else if config.storage_backend == StorageBackendType::Memory {
+        advise("in-memory storage — all data is lost on restart".to_owned());
+    }
+    if config.tls.is_none() && config.acme.is_none() && !config.bind_addr.ip().is_loopback() {
+        advise(format!(
+            "listening on {} without TLS — terminate TLS in front of ZVault, set ZVAULT_TLS_CERT and ZVAULT_TLS_KEY, or set ZVAULT_ACME_DOMAINS",
+            config.bind_addr
+        ));
+    }
+    if config.dev_kms_key_path.is_some() {
+        advise("dev KMS seal — the root key is only as safe as the key file".to_owned());
+    }
+    if config
+        .spring_oauth
+        .as_ref()
+        .is_some_and(|s| s.client_secret.is_empty())
+    {
+        advise("SPRING_AUTH_URL is set but SPRING_CLIENT_SECRET is empty".to_owned());
+    }
+
+    checks.extend(lint_tls(config, &env));
+    checks.extend(lint_acme(config));
+    checks.extend(lint_replication(config));
+    checks.extend(lint_ha(config));
+    checks.extend(lint_env(&env));
+
+    if config.lease_scan_interval_secs == 0 {
+        checks.push(Check::new(
+            "config",
+            Status::Fail,
+            "ZVAULT_LEASE_SCAN_INTERVAL must be at least 1 second",
+        ));
+    }
+    if let Some(path) = &config.audit_file_path {
+        let dir = Path::new(path)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        if !dir.is_dir() {
+            checks.push(Check::new(
+                "config",
+                Status::Fail,
+                format!(
+                    "ZVAULT_AUDIT_FILE directory {} does not exist",
+                    dir.display()
+                ),
+            ));
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(Check::new("config", Status::Pass, "no issues found"));
+    }
+    checks
+}
+
+/// Lint the certificate file settings.
+fn lint_tls(config: &ServerConfig, env: &impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let fail = |detail: &str| vec![Check::new("config", Status::Fail, detail.to_owned())];
+    let Some(tls) = &config.tls else {
+        let cert = env("ZVAULT_TLS_CERT").is_some_and(|v| !v.is_empty());
+        let key = env("ZVAULT_TLS_KEY").is_some_and(|v| !v.is_empty());
+        if cert != key {
+            return fail("ZVAULT_TLS_CERT and ZVAULT_TLS_KEY must be set together");
+        }
+        return Vec::new();
+    };
+    if config.acme.is_some() {
+        return fail("ZVAULT_TLS_CERT and ZVAULT_ACME_DOMAINS are mutually exclusive");
+    }
+    if tls.bootstrap && tls.hostnames.is_empty() {
+        return fail("ZVAULT_TLS_HOSTNAMES must list at least one name to bootstrap a certificate");
+    }
+    Vec::new()
+}
+
+/// Lint the ACME settings.
+fn lint_acme(config: &ServerConfig) -> Vec<Check> {
+    let Some(acme) = &config.acme else {
+        return Vec::new();
+    };
+    let mut checks = Vec::new();
+    let mut push = |status, detail: String| checks.push(Check::new("config", status, detail));
+
+    if acme.email.is_none() {
+        push(
+            Status::Warn,
+            "ZVAULT_ACME_EMAIL is unset — the CA cannot warn you about expiring certificates"
+                .to_owned(),
+        );
+    }
+    if !acme.directory_url.starts_with("https://") {
+        push(
+            Status::Warn,
+            format!("ACME directory {} is not HTTPS", acme.directory_url),
+        );
+    }
+    if addrs_conflict(acme.http_addr, config.bind_addr) {
+        push(
+            Status::Fail,
+            format!(
+                "ZVAULT_ACME_HTTP_ADDR {} collides with ZVAULT_BIND_ADDR {}",
+                acme.http_addr, config.bind_addr
+            ),
+        );
+    }
+    match &acme.dns_provider {
+        None if acme.challenge == acme::ChallengeType::Dns01 => push(
+            Status::Fail,
+            "dns-01 requires ZVAULT_ACME_DNS_PROVIDER".to_owned(),
+        ),
+        Some(AcmeDnsProvider::Cloudflare { api_token, zone_id })
+            if api_token.is_empty() || zone_id.is_empty() =>
+        {
+            push(
+                Status::Fail,
+                "Cloudflare DNS needs ZVAULT_ACME_CLOUDFLARE_API_TOKEN and ZVAULT_ACME_CLOUDFLARE_ZONE_ID"
+                    .to_owned(),
+            );
+        }
+        _ => {}
+    }
+    checks
+}
+
+/// Lint the replication settings.
+fn lint_replication(config: &ServerConfig) -> Vec<Check> {
+    let Some(replication) = &config.replication else {
+        return Vec::new();
+    };
+    let mut checks = Vec::new();
+    let mut push = |status, detail: String| checks.push(Check::new("config", status, detail));
+
+    if replication.secret.is_empty() {
+        push(
+            Status::Fail,
+            "ZVAULT_REPLICATION_MODE requires ZVAULT_REPLICATION_SECRET".to_owned(),
+        );
+    } else if replication.secret.len() < 32 {
+        push(
+            Status::Warn,
+            "ZVAULT_REPLICATION_SECRET is shorter than 32 characters".to_owned(),
+        );
+    }
+    if let ReplicationRole::Replica { primary_addr } = &replication.role {
+        if primary_addr.is_empty() {
+            push(
+                Status::Fail,
+                "a replica requires ZVAULT_REPLICATION_PRIMARY_ADDR".to_owned(),
+            );
+        } else if !primary_addr.starts_with("https://") {
+            push(
+                Status::Warn,
+                format!("replicating from {primary_addr} without TLS"),
+            );
+        }
+    }
+    checks
+}
+
+/// Lint the HA settings.
+fn lint_ha(config: &ServerConfig) -> Vec<Check> {
+    let Some(ha) = &config.ha else {
+        return Vec::new();
+    };
+    let mut checks = Vec::new();
+    let mut push = |status, detail: String| checks.push(Check::new("config", status, detail));
+
+    if ha.api_addr.is_empty() {
+        push(
+            Status::Fail,
+            "ZVAULT_HA_ENABLED requires ZVAULT_API_ADDR".to_owned(),
+        );
+    } else if !ha.api_addr.starts_with("https://") {
+        push(
+            Status::Warn,
+            format!(
+                "standbys would forward requests to {} without TLS",
+                ha.api_addr
+            ),
+        );
+    }
+    match config.storage_backend {
+        StorageBackendType::Postgres { .. } => {}
+        StorageBackendType::Memory => push(
+            Status::Warn,
+            "HA over memory storage elects this server alone".to_owned(),
+        ),
+        _ => push(
+            Status::Fail,
+            "HA requires storage shared by every server: ZVAULT_STORAGE=postgres".to_owned(),
+        ),
+    }
+    if config
+        .replication
+        .as_ref()
+        .is_some_and(|r| matches!(r.role, ReplicationRole::Replica { .. }))
+    {
+        push(
+            Status::Fail,
+            "a replication replica cannot also be an HA node".to_owned(),
+        );
+    }
+    checks
+}
+
+/// Whether two listeners would try to bind the same port.
+fn addrs_conflict(a: SocketAddr, b: SocketAddr) -> bool {
+    a.port() == b.port() && (a.ip() == b.ip() || a.ip().is_unspecified() || b.ip().is_unspecified())
+}
+
+/// Flag environment values that `from_env` silently replaced with a default.
+fn lint_env(env: &impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut invalid = |name: &str, value: &str, expected: &str| {
+        checks.push(Check::new(
+            "config",
+            Status::Fail,
+            format!("{name}={value:?} is not {expected}; the default would be used instead"),
+        ));
+    };
+
+    if let Some(v) = env("ZVAULT_BIND_ADDR").filter(|v| v.parse::<SocketAddr>().is_err()) {
+        invalid("ZVAULT_BIND_ADDR", &v, "a socket address");
+    } else if let Some(v) = env("PORT").filter(|v| v.parse::<u16>().is_err()) {
+        invalid("PORT", &v, "a port number");
+    }
+    if let Some(v) = env("ZVAULT_STORAGE").filter(|v| {
+        !matches!(
+            v.to_lowercase().as_str(),
+            "memory" | "rocksdb" | "redb" | "postgres" | "postgresql"
+        )
+    }) {
+        invalid("ZVAULT_STORAGE", &v, "memory, rocksdb, redb, or postgres");
+    }
+    if let Some(v) = env("ZVAULT_SEAL")
+        .filter(|v| !v.eq_ignore_ascii_case(SEAL_TYPE_SHAMIR) && !v.eq_ignore_ascii_case("devkms"))
+    {
+        invalid("ZVAULT_SEAL", &v, "shamir or devkms");
+    }
+    if let Some(v) =
+        env("ZVAULT_ACME_CHALLENGE").filter(|v| v.parse::<acme::ChallengeType>().is_err())
+    {
+        invalid("ZVAULT_ACME_CHALLENGE", &v, "http-01 or dns-01");
+    }
+    if let Some(v) = env("ZVAULT_ACME_HTTP_ADDR").filter(|v| v.parse::<SocketAddr>().is_err()) {
+        invalid("ZVAULT_ACME_HTTP_ADDR", &v, "a socket address");
+    }
+    if let Some(v) =
+        env("ZVAULT_ACME_DNS_PROVIDER").filter(|v| !v.eq_ignore_ascii_case("cloudflare"))
+    {
+        invalid("ZVAULT_ACME_DNS_PROVIDER", &v, "cloudflare");
+    }
+    checks
+}
+
+/// Write, read back, and delete a probe key below the barrier.
+async fn storage(config: &ServerConfig, state: &AppState) -> Check {
+    let backend = match &config.storage_backend {
+        StorageBackendType::Memory => "memory",
+        StorageBackendType::RocksDb { .. } => "rocksdb",
+        StorageBackendType::Redb { .. } => "redb",
+        StorageBackendType::Postgres { .. } => "postgres",
+        StorageBackendType::S3 { .. } => "s3",
+    };
+    let probe = uuid::Uuid::new_v4();
+    let started = Instant::now();
+    let result = async {
+        state
+            .barrier
+            .put_raw(STORAGE_PROBE_KEY, probe.as_bytes())
+            .await?;
+        let read = state.barrier.get_raw(STORAGE_PROBE_KEY).await?;
+        state.barrier.delete_raw(STORAGE_PROBE_KEY).await?;
+        Ok::<_, zvault_core::error::BarrierError>(read)
+    }
+    .await;
+    match result {
+        Ok(Some(read)) if read == probe.as_bytes() => Check::new(
+            "storage",
+            Status::Pass,
+            format!(
+                "{backend}: read/write probe ok in {} ms",
+                started.elapsed().as_millis()
+            ),
+        ),
+        Ok(_) => Check::new(
+            "storage",
+            Status::Fail,
+            format!("{backend}: probe read back different data than was written"),
+        ),
+        Err(e) => Check::new("storage", Status::Fail, format!("{backend}: {e}")),
+    }
+}
+
+/// Check that the seal can be read and, for auto-unseal, that the KMS
+/// unsealed the vault.
+async fn seal(config: &ServerConfig, state: &AppState) -> Check {
+    if let Some(path) = &config.dev_kms_key_path {
+        if let Err(e) = kms_round_trip(path).await {
+            return Check::new("seal", Status::Fail, format!("dev KMS {path}: {e}"));
+        }
+    }
+    let status = match state.seal_manager.status().await {
+        Ok(status) => status,
+        Err(e) => return Check::new("seal", Status::Fail, format!("unreadable: {e}")),
+    };
+    let seal_type = &status.seal_type;
+    if !status.initialized {
+        Check::new(
+            "seal",
+            Status::Warn,
+            "vault is not initialized — run `zvault init`",
+        )
+    } else if !status.sealed {
+        Check::new("seal", Status::Pass, format!("{seal_type}: unsealed"))
+    } else if seal_type == SEAL_TYPE_SHAMIR {
+        Check::new(
+            "seal",
+            Status::Pass,
+            format!(
+                "{seal_type}: sealed, awaiting {} of {} unseal keys",
+                status.threshold, status.shares
+            ),
+        )
+    } else {
+        Check::new(
+            "seal",
+            Status::Fail,
+            format!(
+                "{seal_type}: auto-unseal failed — the KMS is unreachable or holds a different key"
+            ),
+        )
+    }
+}
+
+/// Encrypt and decrypt a probe value with the dev KMS key.
+async fn kms_round_trip(path: &str) -> Result<(), String> {
+    let kms = DevKms::open(path).await.map_err(|e| e.to_string())?;
+    let probe = uuid::Uuid::new_v4();
+    let wrapped = kms
+        .wrap(probe.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    let unwrapped = kms.unwrap(&wrapped).await.map_err(|e| e.to_string())?;
+    if unwrapped == probe.as_bytes() {
+        Ok(())
+    } else {
+        Err("round trip returned different data".to_owned())
+    }
+}
+
+/// Check that the PKCS#11 module for HSM-backed transit keys exists.
+fn hsm(config: &ServerConfig) -> Option<Check> {
+    let hsm = config.hsm.as_ref()?;
+    Some(if !Path::new(&hsm.module_path).is_file() {
+        Check::new(
+            "hsm",
+            Status::Fail,
+            format!("PKCS#11 module {} not found", hsm.module_path),
+        )
+    } else if hsm.pin.is_empty() {
+        Check::new(
+            "hsm",
+            Status::Warn,
+            format!("slot {}: ZVAULT_HSM_PIN is empty", hsm.slot),
+        )
+    } else {
+        Check::new(
+            "hsm",
+            Status::Pass,
+            format!("{} slot {}", hsm.module_path, hsm.slot),
+        )
+    })
+}
+
+/// Compare the local clock with the `Date` header of a reference server.
+async fn clock(config: &ServerConfig) -> Check {
+    let Some(url) = config
+        .clock_reference_url
+        .as_deref()
+        .or_else(|| config.acme.as_ref().map(|a| a.directory_url.as_str()))
+    else {
+        return Check::new(
+            "clock",
+            Status::Skip,
+            "no reference server (set ZVAULT_CLOCK_REFERENCE_URL)",
+        );
+    };
+    let remote = match reference_time(url).await {
+        Ok(remote) => remote,
+        Err(e) => return Check::new("clock", Status::Warn, format!("{url}: {e}")),
+    };
+    let skew = Utc::now() - remote;
+    let detail = format!("{} ms skew against {url}", skew.num_milliseconds());
+    let abs = skew.abs();
+    if abs > CLOCK_SKEW_FAIL {
+        Check::new("clock", Status::Fail, detail)
+    } else if abs > CLOCK_SKEW_WARN {
+        Check::new("clock", Status::Warn, detail)
+    } else {
+        Check::new("clock", Status::Pass, detail)
+    }
+}
+
+/// The time reported by `url` in its `Date` response header.
+async fn reference_time(url: &str) -> Result<DateTime<Utc>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(CLOCK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client.head(url).send().await.map_err(|e| e.to_string())?;
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|v| v.to_str().ok())
+        .ok_or("response has no Date header")?;
+    DateTime::parse_from_rfc2822(date)
+        .map(|d| d.with_timezone(&Utc))
+        .map_err(|e| format!("unparsable Date header {date:?}: {e}"))
+}
+
+/// Check the certificate files, or the placeholder certificate and, if the
+/// barrier is unsealed, the stored ACME certificate.
+async fn tls_chain(config: &ServerConfig, state: &AppState) -> Check {
+    if let Some(tls_config) = &config.tls {
+        return tls_files(tls_config);
+    }
+    let Some(acme_config) = &config.acme else {
+        return Check::new(
+            "tls",
+            Status::Skip,
+            "neither ZVAULT_TLS_CERT nor ZVAULT_ACME_DOMAINS is set",
+        );
+    };
+    let placeholder = acme::self_signed(&acme_config.domains)
+        .map_err(anyhow::Error::from)
+        .and_then(|cert| tls::validate_certificate(&cert));
+    if let Err(e) = placeholder {
+        return Check::new(
+            "tls",
+            Status::Fail,
+            format!("placeholder certificate: {e:#}"),
+        );
+    }
+    let client = match tls::acme_client(
+        acme_config,
+        Arc::clone(&state.barrier),
+        Arc::new(Http01Responder::default()),
+    ) {
+        Ok(client) => client,
+        Err(e) => return Check::new("tls", Status::Fail, format!("{e:#}")),
+    };
+    if !state.barrier.is_unsealed().await {
+        return Check::new(
+            "tls",
+            Status::Skip,
+            "sealed — the ACME certificate is checked once it is readable",
+        );
+    }
+    let certificate = match client.certificate().await {
+        Ok(Some(certificate)) => certificate,
+        Ok(None) => {
+            return Check::new(
+                "tls",
+                Status::Pass,
+                "no certificate yet — one will be issued after startup",
+            );
+        }
+        Err(e) => return Check::new("tls", Status::Fail, format!("stored certificate: {e}")),
+    };
+    if let Err(e) = tls::validate_certificate(&certificate) {
+        return Check::new("tls", Status::Fail, format!("stored certificate: {e:#}"));
+    }
+    let renew_before = chrono::Duration::days(acme_config.renew_before_days);
+    let now = Utc::now();
+    let (status, note) = if certificate.not_after <= now {
+        (Status::Warn, "expired — renewing after startup")
+    } else if certificate.needs_renewal(&acme_config.domains, renew_before, now) {
+        (Status::Warn, "due for renewal after startup")
+    } else {
+        (Status::Pass, "current")
+    };
+    Check::new(
+        "tls",
+        status,
+        format!(
+            "chain for {} valid until {} ({note})",
+            certificate.domains.join(", "),
+            certificate.not_after.to_rfc3339()
+        ),
+    )
+}
+
+/// Check that the certificate files load, or will be bootstrapped.
+fn tls_files(tls_config: &TlsFileConfig) -> Check {
+    let cert_exists = Path::new(&tls_config.cert_path).exists();
+    let key_exists = Path::new(&tls_config.key_path).exists();
+    if !cert_exists && !key_exists {
+        return if tls_config.bootstrap {
+            Check::new(
+                "tls",
+                Status::Warn,
+                format!(
+                    "{} is missing — a self-signed certificate for {} will be bootstrapped",
+                    tls_config.cert_path,
+                    tls_config.hostnames.join(", ")
+                ),
+            )
+        } else {
+            Check::new(
+                "tls",
+                Status::Fail,
+                format!(
+                    "{} is missing and ZVAULT_TLS_BOOTSTRAP is off",
+                    tls_config.cert_path
+                ),
+            )
+        };
+    }
+    match tls::load_files(tls_config) {
+        Ok(_) => Check::new(
+            "tls",
+            Status::Pass,
+            format!("{} matches its key", tls_config.cert_path),
+        ),
+        Err(e) => Check::new("tls", Status::Fail, format!("{e:#}")),
+    }
+}
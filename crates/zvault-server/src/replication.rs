@@ -0,0 +1,358 @@
+//! Primary → replica replication.
+//!
+//! With `ZVAULT_REPLICATION_MODE=primary`, the server records every storage
+//! change in a [`ReplicationLog`] and serves it at `/v1/sys/replication/*`
+//! to replicas presenting the shared `ZVAULT_REPLICATION_SECRET`.
+//!
+//! With `ZVAULT_REPLICATION_MODE=replica`, a background [`Replicator`]
+//! bootstraps from a snapshot of the primary's storage, then long-polls the
+//! primary for changes and applies them to local storage as-is. Everything
+//! crosses the wire as barrier ciphertext: a replica is unsealed with the
+//! primary's unseal keys (or auto-unseal KMS), and can replicate while
+//! sealed. Reads are served locally; any other request to `/v1/`, and KV
+//! secret reads, which record usage and may issue a lease, are forwarded to
+//! the primary, so a write is visible on the replica only once it has been
+//! streamed back. Replication is asynchronous — a replica may
+//! lag the primary by up to a poll.
+//!
+//! Mount tables and cached configuration (quotas, license) are loaded when a
+//! replica starts or is unsealed, as on a primary, and the mount table,
+//! audit devices, and quotas are reloaded whenever a batch or snapshot
+//! changes them.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::response::Response;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, watch};
+use tracing::{info, warn};
+use zvault_core::barrier::Barrier;
+use zvault_core::error::ReplicationError;
+use zvault_core::replication::{LogEntry, ReplicationLog};
+use zvault_storage::StorageBackend;
+
+use crate::forward;
+use crate::routes::{audit, mounts};
+use crate::state::AppState;
+
+/// Header replicas authenticate to the primary with.
+pub const SECRET_HEADER: &str = "x-vault-replication-secret";
+
+/// How long the primary holds a stream request open waiting for changes.
+pub const MAX_WAIT_SECS: u64 = 30;
+
+/// Most changes returned by one stream request.
+pub const MAX_BATCH: usize = 1000;
+
+/// Pause after a failed poll before trying again.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Storage keys whose change means the barrier keyring must be reloaded.
+const SEAL_PREFIX: &str = "sys/seal/";
+
+/// Storage key of the mount table.
+const MOUNTS_KEY: &str = "sys/mounts";
+
+/// Storage keys of the audit device table and settings.
+const AUDIT_PREFIX: &str = "sys/audit/";
+
+/// Storage keys of the rate limit and lease count quotas.
+const QUOTAS_PREFIX: &str = "sys/quotas/";
+
+/// The role this server plays in replication.
+#[derive(Debug)]
+pub enum Replication {
+    /// Records changes for replicas.
+    Primary {
+        log: Arc<ReplicationLog>,
+        secret: String,
+    },
+    /// Follows a primary.
+    Replica(Arc<Replicator>),
+}
+
+impl Replication {
+    /// Current replication status, for `GET /v1/sys/replication/status`.
+    pub async fn status(&self) -> ReplicationStatus {
+        match self {
+            Self::Primary { log, .. } => ReplicationStatus::Primary {
+                epoch: log.epoch().to_owned(),
+                last_index: log.last_index(),
+            },
+            Self::Replica(replicator) => {
+                ReplicationStatus::Replica(replicator.status.read().await.clone())
+            }
+        }
+    }
+}
+
+/// Response body for `GET /v1/sys/replication/status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ReplicationStatus {
+    /// Replication is not configured.
+    Disabled,
+    /// This server is a primary.
+    Primary {
+        /// Identifies the primary's change log; changes on restart.
+        epoch: String,
+        /// Index of the most recent change.
+        last_index: u64,
+    },
+    /// This server is a replica.
+    Replica(ReplicaStatus),
+}
+
+/// Progress of a replica.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplicaStatus {
+    /// The primary's base URL.
+    pub primary_addr: String,
+    /// Epoch of the primary's log being followed, once bootstrapped.
+    pub epoch: Option<String>,
+    /// Index of the last change applied.
+    pub last_index: u64,
+    /// When the replica last heard from the primary.
+    pub last_contact_at: Option<DateTime<Utc>>,
+    /// Error of the last poll, if it failed.
+    pub last_error: Option<String>,
+    /// Full snapshots taken, including the initial one.
+    pub resyncs: u64,
+}
+
+/// Response body for `GET /v1/sys/replication/snapshot`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotResponse {
+    /// Epoch of the primary's log.
+    pub epoch: String,
+    /// Log index the snapshot is current as of.
+    pub index: u64,
+    /// Every storage entry, values base64 ciphertext.
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// One entry of a replication snapshot.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Storage key.
+    pub key: String,
+    /// Base64 ciphertext.
+    pub value: String,
+}
+
+/// Response body for `GET /v1/sys/replication/stream`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamResponse {
+    /// Epoch of the primary's log.
+    pub epoch: String,
+    /// Changes after the requested index, oldest first.
+    pub entries: Vec<LogEntry>,
+}
+
+/// Streams changes from a primary into local storage and forwards writes.
+pub struct Replicator {
+    primary_addr: String,
+    secret: String,
+    client: reqwest::Client,
+    storage: Arc<dyn StorageBackend>,
+    barrier: Arc<Barrier>,
+    status: RwLock<ReplicaStatus>,
+}
+
+impl std::fmt::Debug for Replicator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Replicator")
+            .field("primary_addr", &self.primary_addr)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Replicator {
+    /// Create a replicator writing raw entries to `storage`, the same
+    /// backend `barrier` reads from.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the HTTP client cannot be built.
+    pub fn new(
+        primary_addr: &str,
+        secret: &str,
+        storage: Arc<dyn StorageBackend>,
+        barrier: Arc<Barrier>,
+    ) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            // Long enough for a long poll plus a slow forwarded request.
+            .timeout(Duration::from_secs(MAX_WAIT_SECS * 3))
+            .build()?;
+        Ok(Self {
+            primary_addr: primary_addr.trim_end_matches('/').to_owned(),
+            secret: secret.to_owned(),
+            client,
+            storage,
+            barrier,
+            status: RwLock::new(ReplicaStatus {
+                primary_addr: primary_addr.to_owned(),
+                ..ReplicaStatus::default()
+            }),
+        })
+    }
+
+    /// Follow the primary until `shutdown` fires, reloading the parts of
+    /// `state` cached from storage as their keys change.
+    ///
+    /// A poll interrupted by shutdown leaves its index unrecorded, so its
+    /// changes are applied again on the next run; applying a change twice
+    /// is harmless.
+    pub async fn run(&self, state: &AppState, shutdown: &mut watch::Receiver<bool>) {
+        info!(primary = %self.primary_addr, "replication worker started");
+        loop {
+            let result = tokio::select! {
+                result = self.poll(state) => result,
+                _ = shutdown.changed() => break,
+            };
+            let Err(e) = result else {
+                continue;
+            };
+            let error = format!("{e:#}");
+            warn!(error = %error, "replication poll failed");
+            self.status.write().await.last_error = Some(error);
+            tokio::select! {
+                () = tokio::time::sleep(RETRY_DELAY) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+        info!("replication worker shutting down");
+    }
+
+    /// Apply one batch of changes, or take a snapshot if the replica has
+    /// not bootstrapped or cannot catch up from the primary's log.
+    async fn poll(&self, state: &AppState) -> anyhow::Result<()> {
+        let (epoch, after) = {
+            let status = self.status.read().await;
+            (status.epoch.clone(), status.last_index)
+        };
+        let Some(epoch) = epoch else {
+            return self.resync(state).await;
+        };
+
+        let response = self
+            .client
+            .get(format!("{}/v1/sys/replication/stream", self.primary_addr))
+            .header(SECRET_HEADER, &self.secret)
+            .query(&[
+                ("epoch", epoch.as_str()),
+                ("after", &after.to_string()),
+                ("wait", &MAX_WAIT_SECS.to_string()),
+            ])
+            .send()
+            .await?;
+        if response.status() == StatusCode::CONFLICT {
+            info!("replica fell behind the primary's log, resyncing");
+            return self.resync(state).await;
+        }
+        let batch: StreamResponse = response.error_for_status()?.json().await?;
+
+        let mut reload_keyring = false;
+        let mut last_index = after;
+        for entry in &batch.entries {
+            match entry.decode_value()? {
+                Some(value) => self.storage.put(&entry.key, &value).await?,
+                None => self.storage.delete(&entry.key).await?,
+            }
+            reload_keyring |= entry.key.starts_with(SEAL_PREFIX);
+            last_index = entry.index;
+        }
+        if reload_keyring {
+            self.barrier.reload_keyring().await?;
+        }
+        reload_changed(state, batch.entries.iter().map(|e| e.key.as_str())).await;
+
+        let mut status = self.status.write().await;
+        status.last_index = last_index;
+        status.last_contact_at = Some(Utc::now());
+        status.last_error = None;
+        Ok(())
+    }
+
+    /// Replace local storage with a snapshot of the primary's.
+    async fn resync(&self, state: &AppState) -> anyhow::Result<()> {
+        let snapshot: SnapshotResponse = self
+            .client
+            .get(format!("{}/v1/sys/replication/snapshot", self.primary_addr))
+            .header(SECRET_HEADER, &self.secret)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        // Decode everything before writing anything.
+        let mut entries = Vec::with_capacity(snapshot.entries.len());
+        for entry in snapshot.entries {
+            let value =
+                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &entry.value)
+                    .map_err(|e| ReplicationError::Invalid {
+                        reason: format!("value of '{}': {e}", entry.key),
+                    })?;
+            entries.push((entry.key, value));
+        }
+        let keys: HashSet<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        for key in self.storage.list("").await? {
+            if !keys.contains(key.as_str()) {
+                self.storage.delete(&key).await?;
+            }
+        }
+        for (key, value) in &entries {
+            self.storage.put(key, value).await?;
+        }
+        self.barrier.reload_keyring().await?;
+        reload_changed(state, entries.iter().map(|(key, _)| key.as_str())).await;
+        info!(
+            epoch = %snapshot.epoch,
+            index = snapshot.index,
+            entries = entries.len(),
+            "replica synced from primary snapshot"
+        );
+
+        let mut status = self.status.write().await;
+        status.epoch = Some(snapshot.epoch);
+        status.last_index = snapshot.index;
+        status.last_contact_at = Some(Utc::now());
+        status.last_error = None;
+        status.resyncs = status.resyncs.saturating_add(1);
+        Ok(())
+    }
+
+    /// Send `req` to the primary and relay its response.
+    pub async fn forward(&self, req: Request) -> Response {
+        forward::forward(&self.client, &self.primary_addr, "primary", req).await
+    }
+}
+
+/// Reload the mount table, audit devices, and quotas if any of `keys`
+/// belongs to them. Nothing is reloaded while sealed; unsealing loads it all.
+pub async fn reload_changed<'a>(state: &AppState, keys: impl Iterator<Item = &'a str>) {
+    let (mut mount_table, mut audit_devices, mut quotas) = (false, false, false);
+    for key in keys {
+        mount_table |= key == MOUNTS_KEY;
+        audit_devices |= key.starts_with(AUDIT_PREFIX);
+        quotas |= key.starts_with(QUOTAS_PREFIX);
+    }
+    if !(mount_table || audit_devices || quotas) || !state.barrier.is_unsealed().await {
+        return;
+    }
+    if mount_table {
+        mounts::restore_mounts(state).await;
+    }
+    if audit_devices {
+        audit::restore_devices(state).await;
+    }
+    if quotas && let Err(e) = state.quotas.load().await {
+        warn!(error = %e, "failed to reload replicated quotas");
+    }
+}
@@ -0,0 +1,234 @@
+//! Access request routes: `/v1/sys/access-requests/*`
+//!
+//! Just-in-time access: a client requests temporary capabilities on a path,
+//! an approver grants or denies it, and the grant expires on its own.
+//!
+//! Anyone holding `create` on `sys/access-requests` (part of the `default`
+//! policy) may submit a request and can always read, list, and revoke their
+//! own. Approvers need `update` on `sys/access-requests/{id}` and must
+//! themselves hold every requested capability on the requested path, so an
+//! approval can never widen access beyond what the approver has.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::routes::auth::parse_duration;
+use crate::state::AppState;
+use zvault_core::access_request::{AccessRequest, NewAccessRequest};
+use zvault_core::policy::Capability;
+
+/// Policy path for submitting and listing requests.
+const REQUESTS_PATH: &str = "sys/access-requests";
+
+/// Build the `/v1/sys/access-requests` router.
+///
+/// Paths:
+/// - `POST /v1/sys/access-requests` — submit a request
+/// - `GET  /v1/sys/access-requests` — list requests (own requests without `list`)
+/// - `GET  /v1/sys/access-requests/{id}` — read a request and its history
+/// - `POST /v1/sys/access-requests/{id}/approve` — approve and attach the grant
+/// - `POST /v1/sys/access-requests/{id}/deny` — deny
+/// - `POST /v1/sys/access-requests/{id}/revoke` — end an active grant early
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_requests).post(create_request))
+        .route("/{id}", get(read_request))
+        .route("/{id}/approve", post(approve_request))
+        .route("/{id}/deny", post(deny_request))
+        .route("/{id}/revoke", post(revoke_request))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAccessRequest {
+    /// Path pattern to request access to (e.g. `secret/data/prod/**`).
+    pub path: String,
+    /// Requested capabilities.
+    pub capabilities: Vec<Capability>,
+    /// Why access is needed; shown to approvers.
+    pub reason: String,
+    /// Grant duration (e.g. `"30m"`, `"4h"`). Defaults to 1h, at most 24h.
+    #[serde(default)]
+    pub ttl: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DecisionRequest {
+    /// Optional note recorded in the request history.
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccessRequestListResponse {
+    pub requests: Vec<AccessRequest>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Submit a new access request.
+async fn create_request(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<CreateAccessRequest>,
+) -> Result<Json<AccessRequest>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, REQUESTS_PATH, &Capability::Create)
+        .await?;
+
+    let ttl = body
+        .ttl
+        .as_deref()
+        .map(parse_duration)
+        .transpose()?
+        .map(|d| {
+            d.to_std()
+                .map_err(|_| AppError::BadRequest("ttl must be positive".to_owned()))
+        })
+        .transpose()?;
+
+    let request = state
+        .access_requests
+        .create(
+            auth.actor(),
+            NewAccessRequest {
+                path: body.path,
+                capabilities: body.capabilities,
+                reason: body.reason,
+                ttl,
+            },
+        )
+        .await?;
+
+    Ok(Json(request))
+}
+
+/// List access requests. Callers without `list` see only their own.
+async fn list_requests(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<AccessRequestListResponse>, AppError> {
+    let can_list = state
+        .policy_store
+        .check(&auth.policies, REQUESTS_PATH, &Capability::List)
+        .await
+        .is_ok();
+
+    let mut requests = state.access_requests.list().await?;
+    if !can_list {
+        let me = auth.actor().requester_id();
+        requests.retain(|r| r.requester == me);
+    }
+
+    Ok(Json(AccessRequestListResponse { requests }))
+}
+
+/// Read one access request, including its decision history.
+async fn read_request(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<AccessRequest>, AppError> {
+    let request = state.access_requests.get(&id).await?;
+    if request.requester != auth.actor().requester_id() {
+        state
+            .policy_store
+            .check(
+                &auth.policies,
+                &format!("{REQUESTS_PATH}/{id}"),
+                &Capability::Read,
+            )
+            .await?;
+    }
+
+    Ok(Json(request))
+}
+
+/// Approve a pending request.
+async fn approve_request(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+    body: Option<Json<DecisionRequest>>,
+) -> Result<Json<AccessRequest>, AppError> {
+    check_approver(&state, &auth, &id).await?;
+
+    let request = state.access_requests.get(&id).await?;
+    for capability in &request.capabilities {
+        state
+            .policy_store
+            .check(&auth.policies, &request.path, capability)
+            .await?;
+    }
+
+    let comment = body.and_then(|Json(b)| b.comment);
+    let request = state
+        .access_requests
+        .approve(&id, auth.actor(), comment)
+        .await?;
+
+    Ok(Json(request))
+}
+
+/// Deny a pending request.
+async fn deny_request(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+    body: Option<Json<DecisionRequest>>,
+) -> Result<Json<AccessRequest>, AppError> {
+    check_approver(&state, &auth, &id).await?;
+
+    let comment = body.and_then(|Json(b)| b.comment);
+    let request = state
+        .access_requests
+        .deny(&id, auth.actor(), comment)
+        .await?;
+
+    Ok(Json(request))
+}
+
+/// End an approved grant before it expires. Requesters may give up their
+/// own access; anyone else needs approver rights.
+async fn revoke_request(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+    body: Option<Json<DecisionRequest>>,
+) -> Result<Json<AccessRequest>, AppError> {
+    let request = state.access_requests.get(&id).await?;
+    if request.requester != auth.actor().requester_id() {
+        check_approver(&state, &auth, &id).await?;
+    }
+
+    let comment = body.and_then(|Json(b)| b.comment);
+    let request = state
+        .access_requests
+        .revoke(&id, auth.actor(), comment)
+        .await?;
+
+    Ok(Json(request))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// Require approver rights on request `id`.
+async fn check_approver(state: &AppState, auth: &AuthContext, id: &str) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{REQUESTS_PATH}/{id}"),
+            &Capability::Update,
+        )
+        .await?;
+    Ok(())
+}
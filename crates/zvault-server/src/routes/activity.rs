@@ -0,0 +1,145 @@
+//! Client activity routes: `/v1/sys/internal/counters/activity`
+//!
+//! Report distinct clients per month, namespace, and mount, and export the
+//! underlying per-client rows for chargeback.
+
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::activity::{ActivityExportRecord, ActivityReport, ClientType};
+use zvault_core::policy::Capability;
+
+/// Build the `/v1/sys/internal/counters/activity` router.
+///
+/// Paths:
+/// - `GET /v1/sys/internal/counters/activity` — activity report
+/// - `GET /v1/sys/internal/counters/activity/export` — per-client rows (JSON or CSV)
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(activity_report))
+        .route("/export", get(activity_export))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    /// First month (`YYYY-MM`). Defaults to eleven months before `end_time`.
+    pub start_time: Option<String>,
+    /// Last month (`YYYY-MM`). Defaults to the current month.
+    pub end_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityExportQuery {
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    /// Only export clients in this namespace.
+    pub namespace: Option<String>,
+    /// Only export rows for this mount (e.g. `secret/`).
+    pub mount: Option<String>,
+    /// `json` (default) or `csv`.
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Distinct client counts over a month range.
+async fn activity_report(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<ActivityReport>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            "sys/internal/counters/activity",
+            &Capability::Read,
+        )
+        .await?;
+
+    let report = state
+        .activity_log
+        .report(query.start_time.as_deref(), query.end_time.as_deref())
+        .await?;
+    Ok(Json(report))
+}
+
+/// Export per-client activity rows.
+async fn activity_export(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<ActivityExportQuery>,
+) -> Result<Response, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            "sys/internal/counters/activity",
+            &Capability::Read,
+        )
+        .await?;
+
+    let rows = state
+        .activity_log
+        .export(
+            query.start_time.as_deref(),
+            query.end_time.as_deref(),
+            query.namespace.as_deref(),
+            query.mount.as_deref(),
+        )
+        .await?;
+
+    match query.format.as_deref().unwrap_or("json") {
+        "json" => Ok(Json(serde_json::json!({ "records": rows })).into_response()),
+        "csv" => Ok((
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            to_csv(&rows),
+        )
+            .into_response()),
+        other => Err(AppError::BadRequest(format!(
+            "unsupported export format '{other}', expected 'json' or 'csv'"
+        ))),
+    }
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+fn to_csv(rows: &[ActivityExportRecord]) -> String {
+    let mut out = String::from("month,client_id,client_type,namespace,mount\n");
+    for row in rows {
+        let client_type = match row.client_type {
+            ClientType::Entity => "entity",
+            ClientType::NonEntityToken => "non_entity_token",
+        };
+        let _ = writeln!(
+            out,
+            "{},{},{client_type},{},{}",
+            row.month,
+            csv_field(&row.client_id),
+            csv_field(&row.namespace),
+            csv_field(&row.mount),
+        );
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
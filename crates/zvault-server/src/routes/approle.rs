@@ -7,18 +7,24 @@
 //! - `GET  /v1/auth/approle/role` — list all roles
 //! - `GET  /v1/auth/approle/role/:name/role-id` — get role ID
 //! - `POST /v1/auth/approle/role/:name/secret-id` — generate secret ID
-//! - `POST /v1/auth/approle/login` — login with `role_id` + `secret_id`
+//! - `POST /v1/auth/approle/login` — login with `role_id` + `secret_id` (plus
+//!   an `X-Vault-MFA` header when a login enforcement names `approle`)
 
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
+use axum::http::HeaderMap;
 use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
 use serde::Deserialize;
 
 use zvault_core::approle::AppRole;
+use zvault_core::identity::ENTITY_ID_METADATA;
 
 use crate::error::AppError;
+use crate::middleware::RequestInfo;
+use crate::routes::identity::bind_login;
+use crate::routes::mfa::enforce_login;
 use crate::state::AppState;
 
 /// Build the `AppRole` auth router (authenticated — role management).
@@ -51,6 +57,8 @@ struct CreateRoleRequest {
     secret_id_num_uses: u32,
     #[serde(default)]
     secret_id_ttl_secs: i64,
+    #[serde(default)]
+    bound_cidrs: Vec<String>,
 }
 
 fn default_ttl() -> i64 {
@@ -82,6 +90,7 @@ async fn create_role(
             bind_secret_id: body.bind_secret_id,
             secret_id_num_uses: body.secret_id_num_uses,
             secret_id_ttl_secs: body.secret_id_ttl_secs,
+            bound_cidrs: body.bound_cidrs,
         })
         .await
         .map_err(AppError::from)?;
@@ -108,6 +117,8 @@ async fn get_role(
         "token_max_ttl_secs": role.token_max_ttl_secs,
         "bind_secret_id": role.bind_secret_id,
         "secret_id_num_uses": role.secret_id_num_uses,
+        "secret_id_ttl_secs": role.secret_id_ttl_secs,
+        "bound_cidrs": role.bound_cidrs,
     })))
 }
 
@@ -169,26 +180,38 @@ struct LoginRequest {
 
 async fn login(
     State(state): State<Arc<AppState>>,
+    Extension(request): Extension<RequestInfo>,
+    headers: HeaderMap,
     Json(body): Json<LoginRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let store = state
         .approle_store
         .as_ref()
         .ok_or_else(|| AppError::NotFound("AppRole auth not enabled".to_owned()))?;
-    let (plaintext_token, token_entry) = store
-        .login(&body.role_id, &body.secret_id, &state.token_store)
+    let (plaintext_token, _) = store
+        .login(
+            &body.role_id,
+            &body.secret_id,
+            request.client_ip,
+            &state.token_store,
+        )
         .await
         .map_err(AppError::from)?;
 
+    let token_entry = bind_login(&state, "approle", &plaintext_token).await?;
+    enforce_login(&state, "approle", &headers, &plaintext_token, &token_entry).await?;
+
     let ttl_secs = token_entry
         .expires_at
         .map_or(0, |exp| (exp - chrono::Utc::now()).num_seconds().max(0));
 
     Ok(Json(serde_json::json!({
         "client_token": plaintext_token,
+        "accessor": token_entry.accessor(),
         "token_hash": token_entry.token_hash,
         "policies": token_entry.policies,
         "ttl": ttl_secs,
         "renewable": token_entry.renewable,
+        "entity_id": token_entry.metadata.get(ENTITY_ID_METADATA),
     })))
 }
@@ -0,0 +1,482 @@
+//! Audit device routes: `/v1/sys/audit/*`
+//!
+//! Enable, disable, and list audit devices (file, syslog, webhook), and
+//! configure request data logging and redaction (`/v1/sys/audit-settings`).
+//! Device configurations and settings are persisted through the barrier and
+//! restored on unseal. Devices declared in the configuration file are enabled
+//! at startup and follow the file on reload, without being persisted.
+//!
+//! Clients that act on the caller's behalf, such as the MCP server, report
+//! those actions to `/v1/sys/audit-log/external` so they reach the same
+//! audit devices as direct API access.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::middleware::{self, AuthContext, RequestInfo};
+use crate::state::AppState;
+use zvault_core::audit::{
+    AuditDevice, AuditDeviceConfig, AuditEntry, AuditRequest, AuditResponse, AuditSettings,
+};
+use zvault_core::policy::Capability;
+
+/// Storage key for the persisted audit device table.
+const AUDIT_DEVICES_KEY: &str = "sys/audit/devices";
+
+/// Storage key for the persisted audit settings.
+const AUDIT_SETTINGS_KEY: &str = "sys/audit/settings";
+
+/// Build the `/v1/sys/audit` router.
+///
+/// Paths:
+/// - `GET    /v1/sys/audit` — list enabled devices
+/// - `POST   /v1/sys/audit/{path}` — enable a device
+/// - `DELETE /v1/sys/audit/{path}` — disable a device
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(list_devices)).route(
+        "/{path}",
+        axum::routing::post(enable_device).delete(disable_device),
+    )
+}
+
+/// Build the `/v1/sys/audit-settings` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/audit-settings` — read request data logging and redaction rules
+/// - `POST /v1/sys/audit-settings` — replace them
+pub fn settings_router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(read_settings).post(write_settings))
+}
+
+/// Build the `/v1/sys/audit-log/external` router.
+///
+/// Paths:
+/// - `POST /v1/sys/audit-log/external` — record an action a client took
+pub fn external_router() -> Router<Arc<AppState>> {
+    Router::new().route("/", post(log_external))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct EnableAuditRequest {
+    pub description: Option<String>,
+    /// Device type and options, e.g. `{"type": "syslog", "options": {...}}`.
+    #[serde(flatten)]
+    pub config: AuditDeviceConfig,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditDeviceResponse {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub device_type: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditDeviceListResponse {
+    pub devices: Vec<AuditDeviceResponse>,
+}
+
+/// An action a client took on the caller's behalf.
+#[derive(Debug, Deserialize)]
+pub struct ExternalAuditRequest {
+    /// What took the action, e.g. `mcp`.
+    pub source: String,
+    /// The action, e.g. an MCP tool name.
+    pub action: String,
+    /// `read`, `write`, `delete`, or `execute` (the default).
+    #[serde(default = "default_external_operation")]
+    pub operation: String,
+    /// Details such as the tool arguments, with secret values already
+    /// removed by the client.
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+    /// How the action ended.
+    pub outcome: ExternalOutcome,
+    /// Error message for a failed or denied action.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+fn default_external_operation() -> String {
+    "execute".to_owned()
+}
+
+/// Outcome of an externally reported action.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalOutcome {
+    Success,
+    Error,
+    Denied,
+}
+
+impl ExternalOutcome {
+    /// The status code recorded for the outcome, as if the action were an
+    /// API request.
+    fn status_code(self) -> u16 {
+        match self {
+            Self::Success => 200,
+            Self::Denied => 403,
+            Self::Error => 500,
+        }
+    }
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// List enabled audit devices.
+///
+/// Options are not echoed back — webhook headers may carry credentials.
+async fn list_devices(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<AuditDeviceListResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/audit", &Capability::Sudo)
+        .await?;
+
+    let persisted = load_devices(&state).await?;
+    let declared = state.config_file.read().await.audit.clone();
+    let devices = state
+        .audit_manager
+        .devices()
+        .await
+        .into_iter()
+        .map(|(path, device_type)| {
+            let description = persisted
+                .iter()
+                .chain(&declared)
+                .find(|d| normalize_path(&d.path) == path)
+                .map(|d| d.description.clone())
+                .unwrap_or_default();
+            AuditDeviceResponse {
+                path,
+                device_type,
+                description,
+            }
+        })
+        .collect();
+
+    Ok(Json(AuditDeviceListResponse { devices }))
+}
+
+/// Enable an audit device at the given path.
+async fn enable_device(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+    Json(body): Json<EnableAuditRequest>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/audit", &Capability::Sudo)
+        .await?;
+
+    let path = normalize_path(&path);
+    let backend = body.config.build()?;
+    state.audit_manager.enable(&path, backend).await?;
+
+    let mut devices = load_devices(&state).await?;
+    devices.retain(|d| d.path != path);
+    devices.push(AuditDevice {
+        path: path.clone(),
+        description: body.description.unwrap_or_default(),
+        config: body.config,
+    });
+
+    if let Err(e) = save_devices(&state, &devices).await {
+        // Keep the in-memory and persisted tables consistent.
+        let _ = state.audit_manager.disable(&path).await;
+        return Err(e);
+    }
+
+    info!(path = %path, "audit device enabled");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Disable the audit device at the given path.
+async fn disable_device(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/audit", &Capability::Sudo)
+        .await?;
+
+    let path = normalize_path(&path);
+    state.audit_manager.disable(&path).await?;
+
+    let mut devices = load_devices(&state).await?;
+    devices.retain(|d| d.path != path);
+    save_devices(&state, &devices).await?;
+
+    info!(path = %path, "audit device disabled");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Read request data logging and redaction settings.
+async fn read_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<AuditSettings>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/audit", &Capability::Sudo)
+        .await?;
+
+    Ok(Json(state.audit_manager.settings().await))
+}
+
+/// Replace request data logging and redaction settings.
+///
+/// Omitting `rules` restores the built-in rules.
+async fn write_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<AuditSettings>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/audit", &Capability::Sudo)
+        .await?;
+
+    let bytes = serde_json::to_vec(&body)
+        .map_err(|e| AppError::Internal(format!("audit settings serialization failed: {e}")))?;
+    let previous = state.audit_manager.settings().await;
+    state.audit_manager.set_settings(body.clone()).await?;
+    if let Err(e) = state.barrier.put(AUDIT_SETTINGS_KEY, &bytes).await {
+        // Keep the in-memory and persisted settings consistent.
+        let _ = state.audit_manager.set_settings(previous).await;
+        return Err(e.into());
+    }
+
+    info!(
+        log_request_data = body.log_request_data,
+        rules = body.rules.len(),
+        "audit settings updated"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Record an action a client took on the caller's behalf.
+///
+/// The entry's path is `external/<source>/<action>` and it carries the
+/// caller's token and policies. Its data is kept even when request data
+/// logging is off, with the redaction rules applied. As for API requests,
+/// an entry no audit device accepts is an error.
+async fn log_external(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(request): Extension<RequestInfo>,
+    Json(body): Json<ExternalAuditRequest>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            "sys/audit-log/external",
+            &Capability::Create,
+        )
+        .await?;
+
+    for (field, value) in [("source", &body.source), ("action", &body.action)] {
+        let valid = !value.is_empty()
+            && value.len() <= 128
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid {
+            return Err(AppError::BadRequest(format!(
+                "{field} must be 1-128 letters, digits, '_', '-', or '.'"
+            )));
+        }
+    }
+    if !matches!(
+        body.operation.as_str(),
+        "read" | "write" | "delete" | "execute"
+    ) {
+        return Err(AppError::BadRequest(format!(
+            "unknown operation '{}', expected read, write, delete, or execute",
+            body.operation
+        )));
+    }
+
+    record_external(&state, &auth, &request, body).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Write the audit entry for an already validated external action, as
+/// taken by `auth` in `request`.
+pub(crate) async fn record_external(
+    state: &AppState,
+    auth: &AuthContext,
+    request: &RequestInfo,
+    action: ExternalAuditRequest,
+) -> Result<(), AppError> {
+    let mut audit_auth = middleware::audit_auth(state, auth);
+    audit_auth
+        .metadata
+        .insert("source".to_owned(), action.source.clone());
+    let entry = AuditEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now(),
+        request: AuditRequest {
+            operation: action.operation,
+            path: format!("external/{}/{}", action.source, action.action),
+            data: action.data,
+            remote_addr: request.remote_addr(),
+            request_id: request.request_id.clone(),
+        },
+        response: Some(AuditResponse {
+            status_code: action.outcome.status_code(),
+            error: action.error,
+        }),
+        auth: audit_auth,
+    };
+    state.audit_manager.log_external(&entry).await?;
+    Ok(())
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// Re-enable persisted audit devices and settings. Called once the vault is
+/// unsealed.
+///
+/// Devices that fail to build are logged and skipped so a misconfigured
+/// collector cannot block unseal.
+pub async fn restore_devices(state: &AppState) {
+    restore_settings(state).await;
+
+    let devices = match load_devices(state).await {
+        Ok(d) => d,
+        Err(e) => {
+            warn!(error = ?e, "failed to load audit device table");
+            return;
+        }
+    };
+
+    let enabled: Vec<String> = state
+        .audit_manager
+        .devices()
+        .await
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+
+    for device in devices {
+        if enabled.contains(&device.path) {
+            continue;
+        }
+        let result = match device.config.build() {
+            Ok(backend) => state.audit_manager.enable(&device.path, backend).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => info!(path = %device.path, "audit device restored"),
+            Err(e) => warn!(path = %device.path, error = %e, "failed to restore audit device"),
+        }
+    }
+}
+
+/// Bring the audit devices declared in the configuration file in line with
+/// it, given the devices the previous version of the file declared (none at
+/// startup).
+///
+/// Devices dropped from the file are disabled, changed ones are rebuilt, and
+/// new ones are enabled; unchanged devices keep running. Failures are logged
+/// and skipped, as in [`restore_devices`].
+pub async fn apply_config_devices(state: &AppState, previous: &[AuditDevice]) {
+    let declared = state.config_file.read().await.audit.clone();
+    let unchanged = |a: &AuditDevice, b: &AuditDevice| {
+        normalize_path(&a.path) == normalize_path(&b.path)
+            && serde_json::to_value(&a.config).ok() == serde_json::to_value(&b.config).ok()
+    };
+
+    for device in previous {
+        if declared.iter().any(|d| unchanged(d, device)) {
+            continue;
+        }
+        let path = normalize_path(&device.path);
+        match state.audit_manager.disable(&path).await {
+            Ok(()) => info!(path = %path, "configured audit device disabled"),
+            Err(e) => warn!(path = %path, error = %e, "failed to disable configured audit device"),
+        }
+    }
+
+    for device in &declared {
+        if previous.iter().any(|d| unchanged(d, device)) {
+            continue;
+        }
+        let path = normalize_path(&device.path);
+        let result = match device.config.build() {
+            Ok(backend) => state.audit_manager.enable(&path, backend).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => info!(path = %path, "configured audit device enabled"),
+            Err(e) => warn!(path = %path, error = %e, "failed to enable configured audit device"),
+        }
+    }
+}
+
+/// Apply persisted audit settings, keeping the defaults if none are stored.
+async fn restore_settings(state: &AppState) {
+    let settings = match state.barrier.get(AUDIT_SETTINGS_KEY).await {
+        Ok(Some(bytes)) => {
+            serde_json::from_slice::<AuditSettings>(&bytes).map_err(|e| e.to_string())
+        }
+        Ok(None) => return,
+        Err(e) => Err(e.to_string()),
+    };
+    let result = match settings {
+        Ok(settings) => state
+            .audit_manager
+            .set_settings(settings)
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!(error = %e, "failed to restore audit settings");
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    if path.ends_with('/') {
+        path.to_owned()
+    } else {
+        format!("{path}/")
+    }
+}
+
+async fn load_devices(state: &AppState) -> Result<Vec<AuditDevice>, AppError> {
+    match state.barrier.get(AUDIT_DEVICES_KEY).await? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| AppError::Internal(format!("corrupt audit device table: {e}"))),
+        None => Ok(Vec::new()),
+    }
+}
+
+async fn save_devices(state: &AppState, devices: &[AuditDevice]) -> Result<(), AppError> {
+    let bytes = serde_json::to_vec(devices)
+        .map_err(|e| AppError::Internal(format!("audit device serialization failed: {e}")))?;
+    state.barrier.put(AUDIT_DEVICES_KEY, &bytes).await?;
+    Ok(())
+}
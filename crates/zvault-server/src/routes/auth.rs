@@ -1,13 +1,16 @@
 //! Token authentication routes: `/v1/auth/token/*`
 //!
-//! Handles token creation, lookup, renewal, and revocation.
+//! Handles token creation, lookup, renewal, and revocation. Operators can
+//! also list, look up, and revoke tokens by accessor, without the token.
+//! `"type": "batch"` creates a batch token, which is never stored and has
+//! no accessor (see [`zvault_core::token`]).
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::routing::post;
+use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
 use chrono::Duration;
 use serde::{Deserialize, Serialize};
@@ -15,8 +18,9 @@ use serde::{Deserialize, Serialize};
 use crate::error::AppError;
 use crate::middleware::AuthContext;
 use crate::state::AppState;
+use zvault_core::error::TokenError;
 use zvault_core::policy::Capability;
-use zvault_core::token::CreateTokenParams;
+use zvault_core::token::{CreateTokenParams, TokenEntry, cidrs_within, hash_token, token_accessor};
 
 /// Build the `/v1/auth/token` router.
 pub fn router() -> Router<Arc<AppState>> {
@@ -24,6 +28,9 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/create", post(create_token))
         .route("/lookup", post(lookup_token))
         .route("/lookup-self", post(lookup_self))
+        .route("/accessors", get(list_accessors))
+        .route("/lookup-accessor", post(lookup_accessor))
+        .route("/revoke-accessor", post(revoke_accessor))
         .route("/renew", post(renew_token))
         .route("/renew-self", post(renew_self))
         .route("/revoke", post(revoke_token))
@@ -39,11 +46,31 @@ pub struct CreateTokenRequest {
     pub display_name: Option<String>,
     pub renewable: Option<bool>,
     pub metadata: Option<HashMap<String, String>>,
+    /// Networks the token may be used from; defaults to the parent's.
+    pub bound_cidrs: Option<Vec<String>>,
+    #[serde(rename = "type", default)]
+    pub token_type: TokenType,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    #[default]
+    Service,
+    Batch,
+}
+
+impl TokenType {
+    fn of(batch: bool) -> Self {
+        if batch { Self::Batch } else { Self::Service }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct TokenResponse {
     pub client_token: String,
+    /// `None` for batch tokens, which have no accessor.
+    pub accessor: Option<String>,
     pub policies: Vec<String>,
     pub renewable: bool,
     pub lease_duration: Option<i64>,
@@ -52,11 +79,16 @@ pub struct TokenResponse {
 #[derive(Debug, Serialize)]
 pub struct TokenLookupResponse {
     pub token_hash: String,
+    /// `None` for batch tokens, which have no accessor.
+    pub accessor: Option<String>,
     pub policies: Vec<String>,
     pub display_name: String,
     pub renewable: bool,
     pub created_at: String,
     pub expires_at: Option<String>,
+    pub bound_cidrs: Vec<String>,
+    #[serde(rename = "type")]
+    pub token_type: TokenType,
 }
 
 #[derive(Debug, Deserialize)]
@@ -64,6 +96,11 @@ pub struct TokenLookupRequest {
     pub token: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TokenAccessorRequest {
+    pub accessor: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TokenRenewRequest {
     pub token: Option<String>,
@@ -87,31 +124,61 @@ async fn create_token(
         .policy_store
         .check(&auth.policies, "auth/token/create", &Capability::Sudo)
         .await?;
+    // Children of a batch token could not be revoked with it.
+    if auth.batch {
+        return Err(AppError::Forbidden(
+            "batch tokens cannot create tokens".to_owned(),
+        ));
+    }
 
     let ttl = body.ttl.as_deref().map(parse_duration).transpose()?;
     let policies = body.policies.unwrap_or_else(|| vec!["default".to_owned()]);
-
-    let token = state
-        .token_store
-        .create(CreateTokenParams {
-            policies: policies.clone(),
-            ttl,
-            max_ttl: None,
-            renewable: body.renewable.unwrap_or(true),
-            parent_hash: Some(auth.token_hash),
-            metadata: body.metadata.unwrap_or_default(),
-            display_name: body.display_name.unwrap_or_else(|| "token".to_owned()),
-        })
-        .await?;
-
-    let lease_duration = ttl.map(|d| d.num_seconds());
+    // A child is bound at least as tightly as its parent.
+    let bound_cidrs = match body.bound_cidrs {
+        Some(cidrs) if !cidrs_within(&cidrs, &auth.bound_cidrs) => {
+            return Err(AppError::Forbidden(
+                "bound_cidrs must lie within the parent token's bound CIDRs".to_owned(),
+            ));
+        }
+        Some(cidrs) => cidrs,
+        None => auth.bound_cidrs.clone(),
+    };
+
+    let batch = body.token_type == TokenType::Batch;
+    let renewable = !batch && body.renewable.unwrap_or(true);
+    let params = CreateTokenParams {
+        policies: policies.clone(),
+        ttl,
+        max_ttl: None,
+        renewable,
+        parent_hash: Some(auth.token_hash),
+        metadata: body.metadata.unwrap_or_default(),
+        display_name: body.display_name.unwrap_or_else(|| "token".to_owned()),
+        bound_cidrs,
+    };
+    let token = if batch {
+        state.token_store.create_batch(params).await?
+    } else {
+        state.token_store.create(params).await?
+    };
+
+    // A batch token's TTL may have been cut short to the parent's.
+    let lease_duration = if batch {
+        let entry = state.token_store.lookup(&token).await?;
+        entry
+            .expires_at
+            .map(|at| (at - chrono::Utc::now()).num_seconds().max(0))
+    } else {
+        ttl.map(|ttl| ttl.num_seconds())
+    };
 
     Ok((
         StatusCode::OK,
         Json(TokenResponse {
+            accessor: (!batch).then(|| token_accessor(&hash_token(&token))),
             client_token: token,
             policies,
-            renewable: body.renewable.unwrap_or(true),
+            renewable,
             lease_duration,
         }),
     ))
@@ -130,14 +197,7 @@ async fn lookup_token(
 
     let entry = state.token_store.lookup(&body.token).await?;
 
-    Ok(Json(TokenLookupResponse {
-        token_hash: entry.token_hash,
-        policies: entry.policies,
-        display_name: entry.display_name,
-        renewable: entry.renewable,
-        created_at: entry.created_at.to_rfc3339(),
-        expires_at: entry.expires_at.map(|t| t.to_rfc3339()),
-    }))
+    Ok(Json(lookup_response(entry)))
 }
 
 /// Look up the caller's own token (allowed by default policy).
@@ -154,15 +214,80 @@ async fn lookup_self(
     // reconstruct the response from the auth context. For a full lookup
     // we'd need the plaintext token, so we return what we know.
     Ok(Json(TokenLookupResponse {
+        accessor: (!auth.batch).then(|| token_accessor(&auth.token_hash)),
         token_hash: auth.token_hash,
         policies: auth.policies,
         display_name: auth.display_name,
         renewable: false, // We don't have this from AuthContext; safe default
         created_at: String::new(),
         expires_at: None,
+        bound_cidrs: auth.bound_cidrs,
+        token_type: TokenType::of(auth.batch),
     }))
 }
 
+/// List the accessors of all stored tokens (requires sudo).
+async fn list_accessors(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "auth/token/accessors", &Capability::Sudo)
+        .await?;
+
+    let accessors = state.token_store.list_accessors().await?;
+    Ok(Json(serde_json::json!({"keys": accessors})))
+}
+
+/// Look up a token by its accessor (requires sudo).
+async fn lookup_accessor(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<TokenAccessorRequest>,
+) -> Result<Json<TokenLookupResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            "auth/token/lookup-accessor",
+            &Capability::Sudo,
+        )
+        .await?;
+
+    let entry = state
+        .token_store
+        .lookup_accessor(&body.accessor)
+        .await
+        .map_err(|e| accessor_error(e, &body.accessor))?;
+
+    Ok(Json(lookup_response(entry)))
+}
+
+/// Revoke the token with an accessor and all its children (requires sudo).
+async fn revoke_accessor(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<TokenAccessorRequest>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            "auth/token/revoke-accessor",
+            &Capability::Sudo,
+        )
+        .await?;
+
+    state
+        .token_store
+        .revoke_accessor(&body.accessor)
+        .await
+        .map_err(|e| accessor_error(e, &body.accessor))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Renew a specific token (requires sudo).
 async fn renew_token(
     State(state): State<Arc<AppState>>,
@@ -187,14 +312,7 @@ async fn renew_token(
 
     let entry = state.token_store.renew(&token, increment).await?;
 
-    Ok(Json(TokenLookupResponse {
-        token_hash: entry.token_hash,
-        policies: entry.policies,
-        display_name: entry.display_name,
-        renewable: entry.renewable,
-        created_at: entry.created_at.to_rfc3339(),
-        expires_at: entry.expires_at.map(|t| t.to_rfc3339()),
-    }))
+    Ok(Json(lookup_response(entry)))
 }
 
 /// Renew the caller's own token (allowed by default policy).
@@ -263,12 +381,34 @@ async fn revoke_self(
 
 // ── Helpers ──────────────────────────────────────────────────────────
 
+fn lookup_response(entry: TokenEntry) -> TokenLookupResponse {
+    TokenLookupResponse {
+        accessor: entry.accessor(),
+        token_hash: entry.token_hash,
+        policies: entry.policies,
+        display_name: entry.display_name,
+        renewable: entry.renewable,
+        created_at: entry.created_at.to_rfc3339(),
+        expires_at: entry.expires_at.map(|t| t.to_rfc3339()),
+        bound_cidrs: entry.bound_cidrs,
+        token_type: TokenType::of(entry.batch),
+    }
+}
+
+/// An unknown accessor is a missing object, not a bad credential.
+fn accessor_error(err: TokenError, accessor: &str) -> AppError {
+    match err {
+        TokenError::NotFound => AppError::NotFound(format!("no token with accessor '{accessor}'")),
+        err => err.into(),
+    }
+}
+
 /// Parse a human-readable duration string like `"1h"`, `"30m"`, `"3600s"`, `"24h"`.
 ///
 /// # Errors
 ///
 /// Returns [`AppError::BadRequest`] if the format is unrecognized.
-fn parse_duration(s: &str) -> Result<Duration, AppError> {
+pub(crate) fn parse_duration(s: &str) -> Result<Duration, AppError> {
     let s = s.trim();
     if s.is_empty() {
         return Err(AppError::BadRequest("empty duration string".to_owned()));
@@ -0,0 +1,151 @@
+//! HTTP route handlers for the TLS certificate auth method.
+//!
+//! Clients log in with the certificate they presented during the TLS
+//! handshake, so login only works on a TLS listener (file TLS or ACME) that
+//! asks for client certificates.
+//!
+//! Endpoints:
+//! - `POST /v1/auth/cert/role/:name` — create or replace a role
+//! - `GET  /v1/auth/cert/role/:name` — read a role
+//! - `DELETE /v1/auth/cert/role/:name` — delete a role
+//! - `GET  /v1/auth/cert/role` — list all roles
+//! - `POST /v1/auth/cert/login` — exchange the client certificate for a
+//!   token (plus an `X-Vault-MFA` header when a login enforcement names
+//!   `cert`)
+
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, Path, State};
+use axum::http::HeaderMap;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+
+use zvault_core::certauth::CertRole;
+use zvault_core::identity::ENTITY_ID_METADATA;
+use zvault_core::policy::Capability;
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::routes::identity::bind_login;
+use crate::routes::mfa::enforce_login;
+use crate::state::AppState;
+use crate::tls::TlsConnectInfo;
+
+/// Build the cert auth router (authenticated — role management).
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/role", get(list_roles)).route(
+        "/role/{name}",
+        post(write_role).get(get_role).delete(delete_role),
+    )
+}
+
+/// Build the public cert login router (no auth required).
+pub fn login_router() -> Router<Arc<AppState>> {
+    Router::new().route("/login", post(login))
+}
+
+async fn write_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(mut body): Json<CertRole>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_role(&state, &auth, &name, Capability::Update).await?;
+    body.name = name;
+    state.cert_auth.write_role(body).await?;
+    Ok(Json(serde_json::json!({"status": "ok"})))
+}
+
+async fn get_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<CertRole>, AppError> {
+    check_role(&state, &auth, &name, Capability::Read).await?;
+    Ok(Json(state.cert_auth.get_role(&name).await?))
+}
+
+async fn delete_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_role(&state, &auth, &name, Capability::Delete).await?;
+    state.cert_auth.delete_role(&name).await?;
+    Ok(Json(serde_json::json!({"status": "deleted"})))
+}
+
+async fn list_roles(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "auth/cert/role", &Capability::List)
+        .await?;
+    let names = state.cert_auth.list_roles().await?;
+    Ok(Json(serde_json::json!({"keys": names})))
+}
+
+#[derive(Default, Deserialize)]
+struct LoginRequest {
+    /// Role to log in with; any role accepting the certificate if omitted.
+    #[serde(default)]
+    role: Option<String>,
+}
+
+async fn login(
+    State(state): State<Arc<AppState>>,
+    connect_info: Option<Extension<ConnectInfo<TlsConnectInfo>>>,
+    headers: HeaderMap,
+    body: Option<Json<LoginRequest>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let Some(Extension(ConnectInfo(tls))) = connect_info else {
+        return Err(AppError::BadRequest(
+            "cert login requires a TLS connection (ZVAULT_TLS_CERT or ZVAULT_ACME_DOMAINS)"
+                .to_owned(),
+        ));
+    };
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+
+    let (plaintext_token, _) = state
+        .cert_auth
+        .login(body.role.as_deref(), &tls.client_chain, &state.token_store)
+        .await?;
+
+    let token_entry = bind_login(&state, "cert", &plaintext_token).await?;
+    enforce_login(&state, "cert", &headers, &plaintext_token, &token_entry).await?;
+
+    let ttl_secs = token_entry
+        .expires_at
+        .map_or(0, |exp| (exp - chrono::Utc::now()).num_seconds().max(0));
+
+    Ok(Json(serde_json::json!({
+        "client_token": plaintext_token,
+        "accessor": token_entry.accessor(),
+        "token_hash": token_entry.token_hash,
+        "policies": token_entry.policies,
+        "ttl": ttl_secs,
+        "renewable": token_entry.renewable,
+        "entity_id": token_entry.metadata.get(ENTITY_ID_METADATA),
+    })))
+}
+
+/// Require `capability` on role `name`.
+async fn check_role(
+    state: &AppState,
+    auth: &AuthContext,
+    name: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("auth/cert/role/{name}"),
+            &capability,
+        )
+        .await?;
+    Ok(())
+}
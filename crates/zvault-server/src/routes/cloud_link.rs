@@ -0,0 +1,466 @@
+//! Cloud link routes: bridge cloud service tokens and vault tokens.
+//!
+//! Hybrid deployments run workloads against both the cloud API and this
+//! vault. Instead of distributing two sets of credentials, a vault linked to
+//! a cloud org exchanges in both directions:
+//!
+//! - A `zvt_` service token from the linked org logs in at
+//!   `/v1/auth/cloud/login` and gets a vault token carrying the link's
+//!   policies, never outliving the service token.
+//! - A vault token mints a short-lived `zvt_` service token for one project
+//!   of the linked org at `/v1/sys/cloud-link/service-token`, gated by
+//!   policy on `sys/cloud-link/projects/{project_id}`.
+//!
+//! Linking takes both sides: a vault operator with `sudo` on
+//! `sys/cloud-link` names the org and gets a link ID, and an org admin
+//! confirms that ID at `/v1/cloud/orgs/{org_id}/vault-link`. Exchange stops
+//! as soon as either side removes its half.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::cloud::auth::{generate_service_token, hash_token, token_prefix};
+use crate::cloud::models::ServiceToken;
+use crate::cloud::repository;
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::routes::auth::parse_duration;
+use crate::routes::mfa::enforce_login;
+use crate::state::AppState;
+use zvault_core::policy::Capability;
+use zvault_core::token::CreateTokenParams;
+
+/// Barrier key for the vault half of the link.
+const LINK_KEY: &str = "sys/cloud-link/config";
+
+/// Policy path for managing the link.
+const LINK_PATH: &str = "sys/cloud-link";
+
+/// Default lifetime of vault tokens issued for service tokens.
+const DEFAULT_TOKEN_TTL_SECS: i64 = 3600;
+
+/// Default and upper bound on minted service token lifetimes.
+const DEFAULT_SERVICE_TOKEN_TTL_SECS: i64 = 3600;
+const MAX_SERVICE_TOKEN_TTL_SECS: i64 = 86400;
+
+/// Build the `/v1/sys/cloud-link` router.
+///
+/// Paths:
+/// - `POST   /v1/sys/cloud-link` — link to a cloud org (returns the link ID)
+/// - `GET    /v1/sys/cloud-link` — show the link and whether the org confirmed it
+/// - `DELETE /v1/sys/cloud-link` — unlink
+/// - `POST   /v1/sys/cloud-link/service-token` — mint a `zvt_` token from a vault token
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(write_link).get(read_link).delete(delete_link))
+        .route("/service-token", post(mint_service_token))
+}
+
+/// Build the public `/v1/auth/cloud` router (no vault token required).
+///
+/// Paths:
+/// - `POST /v1/auth/cloud/login` — exchange a `zvt_` token for a vault token
+pub fn login_router() -> Router<Arc<AppState>> {
+    Router::new().route("/login", post(login))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+/// The vault half of a link, stored through the barrier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LinkConfig {
+    link_id: String,
+    org_id: Uuid,
+    policies: Vec<String>,
+    token_ttl_secs: i64,
+    max_service_token_ttl_secs: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WriteLinkRequest {
+    /// Cloud organization to link to.
+    pub org_id: Uuid,
+    /// Policies attached to vault tokens issued for the org's service tokens.
+    pub policies: Vec<String>,
+    /// Lifetime of those vault tokens (e.g. `"1h"`). Defaults to 1h.
+    #[serde(default)]
+    pub token_ttl: Option<String>,
+    /// Longest service token a vault token may mint (e.g. `"4h"`). Defaults
+    /// to 1h, at most 24h.
+    #[serde(default)]
+    pub max_service_token_ttl: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkResponse {
+    /// Give this to an org admin to confirm the link.
+    pub link_id: String,
+    pub org_id: Uuid,
+    pub policies: Vec<String>,
+    pub token_ttl_secs: i64,
+    pub max_service_token_ttl_secs: i64,
+    /// Whether an org admin has confirmed this link ID.
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MintServiceTokenRequest {
+    pub project_id: Uuid,
+    #[serde(default)]
+    pub environment_id: Option<Uuid>,
+    /// `read` and/or `write`. Defaults to `read`.
+    #[serde(default = "default_permissions")]
+    pub permissions: Vec<String>,
+    /// Token lifetime (e.g. `"30m"`). Defaults to 1h, capped by the link.
+    #[serde(default)]
+    pub ttl: Option<String>,
+    /// Display name. Defaults to `vault:<caller display name>`.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+fn default_permissions() -> Vec<String> {
+    vec!["read".to_owned()]
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintServiceTokenResponse {
+    pub token: ServiceToken,
+    /// The plaintext token. It cannot be retrieved again.
+    pub plaintext_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloudLoginRequest {
+    /// A `zvt_` service token from the linked org.
+    pub token: String,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Link this vault to a cloud org. Re-linking to the same org keeps the
+/// link ID, so changing policies or TTLs does not need a new confirmation.
+async fn write_link(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<WriteLinkRequest>,
+) -> Result<Json<LinkResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, LINK_PATH, &Capability::Sudo)
+        .await?;
+    let pool = cloud_pool(&state)?;
+
+    if body.policies.is_empty() {
+        return Err(AppError::BadRequest(
+            "at least one policy is required".to_owned(),
+        ));
+    }
+    let token_ttl_secs = ttl_secs(body.token_ttl.as_deref(), DEFAULT_TOKEN_TTL_SECS)?;
+    let max_service_token_ttl_secs = ttl_secs(
+        body.max_service_token_ttl.as_deref(),
+        DEFAULT_SERVICE_TOKEN_TTL_SECS,
+    )?;
+    if max_service_token_ttl_secs > MAX_SERVICE_TOKEN_TTL_SECS {
+        return Err(AppError::BadRequest(
+            "max_service_token_ttl must be at most 24h".to_owned(),
+        ));
+    }
+
+    repository::get_org(pool, body.org_id).await?;
+
+    let link_id = match load_link(&state).await? {
+        Some(existing) if existing.org_id == body.org_id => existing.link_id,
+        _ => Uuid::new_v4().simple().to_string(),
+    };
+    let config = LinkConfig {
+        link_id,
+        org_id: body.org_id,
+        policies: body.policies,
+        token_ttl_secs,
+        max_service_token_ttl_secs,
+    };
+    let data = serde_json::to_vec(&config)
+        .map_err(|e| AppError::Internal(format!("serialization failed: {e}")))?;
+    state.barrier.put(LINK_KEY, &data).await?;
+
+    let confirmed = is_confirmed(pool, &config).await?;
+    Ok(Json(link_response(config, confirmed)))
+}
+
+/// Show the link and whether the org has confirmed it.
+async fn read_link(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<LinkResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, LINK_PATH, &Capability::Read)
+        .await?;
+    let pool = cloud_pool(&state)?;
+
+    let config = load_link(&state)
+        .await?
+        .ok_or_else(|| AppError::NotFound("cloud link not configured".to_owned()))?;
+    let confirmed = is_confirmed(pool, &config).await?;
+    Ok(Json(link_response(config, confirmed)))
+}
+
+/// Unlink. Vault tokens already issued keep their (short) TTLs.
+async fn delete_link(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, LINK_PATH, &Capability::Sudo)
+        .await?;
+    state.barrier.delete(LINK_KEY).await?;
+    Ok(Json(serde_json::json!({"status": "deleted"})))
+}
+
+/// Mint a `zvt_` service token for a project of the linked org.
+async fn mint_service_token(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<MintServiceTokenRequest>,
+) -> Result<Json<MintServiceTokenResponse>, AppError> {
+    let project_path = format!("{LINK_PATH}/projects/{}", body.project_id);
+    if body.permissions.is_empty() {
+        return Err(AppError::BadRequest(
+            "at least one permission is required".to_owned(),
+        ));
+    }
+    for permission in &body.permissions {
+        let capability = match permission.as_str() {
+            "read" => Capability::Read,
+            "write" => Capability::Update,
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "invalid permission '{other}' — must be one of: read, write"
+                )));
+            }
+        };
+        state
+            .policy_store
+            .check(&auth.policies, &project_path, &capability)
+            .await?;
+    }
+
+    let (pool, config) = confirmed_link(&state).await?;
+    repository::get_project(pool, body.project_id, config.org_id).await?;
+    if let Some(env_id) = body.environment_id {
+        let envs = repository::list_environments(pool, body.project_id).await?;
+        if !envs.iter().any(|e| e.id == env_id) {
+            return Err(AppError::NotFound(
+                "environment not found in this project".to_owned(),
+            ));
+        }
+    }
+
+    let ttl_secs = ttl_secs(body.ttl.as_deref(), DEFAULT_SERVICE_TOKEN_TTL_SECS)?
+        .min(config.max_service_token_ttl_secs);
+    let expires_at = Utc::now() + Duration::seconds(ttl_secs);
+    let name = body
+        .name
+        .unwrap_or_else(|| format!("vault:{}", auth.display_name));
+
+    let plaintext = generate_service_token();
+    let token = repository::create_service_token(
+        pool,
+        body.project_id,
+        body.environment_id,
+        &name,
+        &hash_token(&plaintext),
+        &token_prefix(&plaintext),
+        &body.permissions,
+        Some(expires_at),
+        None,
+    )
+    .await?;
+
+    repository::write_audit(
+        pool,
+        config.org_id,
+        Some(body.project_id),
+        None,
+        Some(token.id),
+        "service_token",
+        "service_token.mint_from_vault",
+        &format!("tokens/{}", token.id),
+        &serde_json::json!({
+            "link_id": config.link_id,
+            "vault_actor": auth.display_name,
+            "permissions": body.permissions,
+        }),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(MintServiceTokenResponse {
+        token,
+        plaintext_token: plaintext,
+    }))
+}
+
+/// Exchange a `zvt_` service token from the linked org for a vault token.
+async fn login(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<CloudLoginRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if !body.token.starts_with("zvt_") {
+        return Err(AppError::Unauthorized(
+            "expected a zvt_ service token".to_owned(),
+        ));
+    }
+    let (pool, config) = confirmed_link(&state).await?;
+
+    let service_token = repository::lookup_service_token(pool, &hash_token(&body.token)).await?;
+    if repository::get_project(pool, service_token.project_id, config.org_id)
+        .await
+        .is_err()
+    {
+        return Err(AppError::Forbidden(
+            "service token belongs to an organization not linked to this vault".to_owned(),
+        ));
+    }
+    repository::touch_service_token(pool, service_token.id, None).await?;
+
+    let ttl = token_ttl(&config, service_token.expires_at);
+    let mut metadata = HashMap::from([
+        ("cloud_org_id".to_owned(), config.org_id.to_string()),
+        (
+            "cloud_project_id".to_owned(),
+            service_token.project_id.to_string(),
+        ),
+        ("cloud_token_id".to_owned(), service_token.id.to_string()),
+        (
+            "cloud_permissions".to_owned(),
+            service_token.permissions.join(","),
+        ),
+    ]);
+    if let Some(env_id) = service_token.environment_id {
+        metadata.insert("cloud_environment_id".to_owned(), env_id.to_string());
+    }
+
+    let plaintext_token = state
+        .token_store
+        .create(CreateTokenParams {
+            policies: config.policies.clone(),
+            ttl: Some(ttl),
+            max_ttl: Some(ttl),
+            renewable: false,
+            parent_hash: None,
+            metadata,
+            display_name: format!("cloud-{}", service_token.token_prefix),
+            bound_cidrs: Vec::new(),
+        })
+        .await?;
+    let token_entry = state.token_store.lookup(&plaintext_token).await?;
+    enforce_login(&state, "cloud", &headers, &plaintext_token, &token_entry).await?;
+
+    repository::write_audit(
+        pool,
+        config.org_id,
+        Some(service_token.project_id),
+        None,
+        Some(service_token.id),
+        "service_token",
+        "service_token.exchange_for_vault",
+        &format!("tokens/{}", service_token.id),
+        &serde_json::json!({ "link_id": config.link_id }),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({
+        "client_token": plaintext_token,
+        "accessor": token_entry.accessor(),
+        "token_hash": token_entry.token_hash,
+        "policies": token_entry.policies,
+        "ttl": ttl.num_seconds(),
+        "renewable": token_entry.renewable,
+    })))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+fn cloud_pool(state: &AppState) -> Result<&PgPool, AppError> {
+    state
+        .cloud_pg_pool
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("cloud mode is not enabled".to_owned()))
+}
+
+async fn load_link(state: &AppState) -> Result<Option<LinkConfig>, AppError> {
+    state
+        .barrier
+        .get(LINK_KEY)
+        .await?
+        .map(|data| {
+            serde_json::from_slice(&data)
+                .map_err(|e| AppError::Internal(format!("corrupt cloud link config: {e}")))
+        })
+        .transpose()
+}
+
+/// Whether an org admin has confirmed this vault's link ID.
+async fn is_confirmed(pool: &PgPool, config: &LinkConfig) -> Result<bool, AppError> {
+    Ok(repository::get_vault_link(pool, config.org_id)
+        .await?
+        .is_some_and(|link| link.link_id == config.link_id))
+}
+
+/// The link, if both sides have agreed to it.
+async fn confirmed_link(state: &AppState) -> Result<(&PgPool, LinkConfig), AppError> {
+    let pool = cloud_pool(state)?;
+    let config = load_link(state)
+        .await?
+        .ok_or_else(|| AppError::NotFound("cloud link not configured".to_owned()))?;
+    if !is_confirmed(pool, &config).await? {
+        return Err(AppError::Forbidden(
+            "cloud link has not been confirmed by an org admin".to_owned(),
+        ));
+    }
+    Ok((pool, config))
+}
+
+/// Vault token lifetime: the link's TTL, cut short by the service token's expiry.
+fn token_ttl(config: &LinkConfig, expires_at: Option<DateTime<Utc>>) -> Duration {
+    let ttl = Duration::seconds(config.token_ttl_secs);
+    expires_at.map_or(ttl, |exp| ttl.min(exp - Utc::now()))
+}
+
+fn ttl_secs(raw: Option<&str>, default: i64) -> Result<i64, AppError> {
+    let secs = raw
+        .map(parse_duration)
+        .transpose()?
+        .map_or(default, |d| d.num_seconds());
+    if secs <= 0 {
+        return Err(AppError::BadRequest("ttl must be positive".to_owned()));
+    }
+    Ok(secs)
+}
+
+fn link_response(config: LinkConfig, confirmed: bool) -> LinkResponse {
+    LinkResponse {
+        link_id: config.link_id,
+        org_id: config.org_id,
+        policies: config.policies,
+        token_ttl_secs: config.token_ttl_secs,
+        max_service_token_ttl_secs: config.max_service_token_ttl_secs,
+        confirmed,
+    }
+}
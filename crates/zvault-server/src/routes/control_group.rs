@@ -0,0 +1,128 @@
+//! Control group routes: `/v1/sys/control-group/*`
+//!
+//! A request that policy grants only behind a control group is parked by the
+//! auth middleware and answered with an accessor. Approvers sign off here;
+//! their qualification comes from the control group itself (approver
+//! policies or identities), not from a `sys/` grant. The requester polls the
+//! status and, once approved, repeats the request with the
+//! `X-Vault-Control-Group` header to receive the result.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::control_group::ControlGroupRequest;
+use zvault_core::policy::ControlGroup;
+
+/// Build the `/v1/sys/control-group` router.
+///
+/// Paths:
+/// - `POST /v1/sys/control-group/authorize` — approve a parked request
+/// - `POST /v1/sys/control-group/request` — read a parked request's status
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/authorize", post(authorize))
+        .route("/request", post(status))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct AccessorRequest {
+    /// Accessor returned when the request was parked.
+    pub accessor: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ControlGroupStatusResponse {
+    pub accessor: String,
+    pub approved: bool,
+    pub method: String,
+    pub path: String,
+    pub requester_name: String,
+    pub control_groups: Vec<ControlGroupProgress>,
+    pub authorizations: Vec<AuthorizationResponse>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ControlGroupProgress {
+    #[serde(flatten)]
+    pub group: ControlGroup,
+    /// Qualifying approvals collected so far.
+    pub approved_by: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthorizationResponse {
+    pub approver_name: String,
+    pub approved_at: DateTime<Utc>,
+}
+
+impl From<ControlGroupRequest> for ControlGroupStatusResponse {
+    fn from(request: ControlGroupRequest) -> Self {
+        Self {
+            approved: request.is_approved(),
+            control_groups: request
+                .control_groups
+                .iter()
+                .map(|group| ControlGroupProgress {
+                    approved_by: request.approvals_for(group),
+                    group: group.clone(),
+                })
+                .collect(),
+            authorizations: request
+                .authorizations
+                .into_iter()
+                .map(|a| AuthorizationResponse {
+                    approver_name: a.approver_name,
+                    approved_at: a.approved_at,
+                })
+                .collect(),
+            accessor: request.accessor,
+            method: request.method,
+            path: request.path,
+            requester_name: request.requester_name,
+            created_at: request.created_at,
+            expires_at: request.expires_at,
+        }
+    }
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Approve a parked request. The caller must qualify for one of its
+/// control groups and must not be the requester.
+async fn authorize(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<AccessorRequest>,
+) -> Result<Json<ControlGroupStatusResponse>, AppError> {
+    let request = state
+        .control_groups
+        .authorize(&body.accessor, auth.actor(), &auth.policies)
+        .await?;
+    Ok(Json(request.into()))
+}
+
+/// Read a parked request's approval status. Open to the requester and to
+/// anyone who could approve it.
+async fn status(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<AccessorRequest>,
+) -> Result<Json<ControlGroupStatusResponse>, AppError> {
+    let request = state
+        .control_groups
+        .status(&body.accessor, auth.actor(), &auth.policies)
+        .await?;
+    Ok(Json(request.into()))
+}
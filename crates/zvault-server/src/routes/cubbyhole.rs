@@ -0,0 +1,143 @@
+//! Cubbyhole routes: `/v1/cubbyhole/*`
+//!
+//! Private key/value storage scoped to the calling token. Every request
+//! addresses the cubbyhole of the token that made it, so no token can see
+//! another's entries, and the whole cubbyhole is destroyed when the token is
+//! revoked. Useful for per-workflow scratch secrets that should not outlive
+//! the job's token.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::routes::secrets::validate_secret_path;
+use crate::state::AppState;
+use zvault_core::cubbyhole::CUBBYHOLE_MOUNT;
+use zvault_core::policy::Capability;
+
+/// Build the `/v1/cubbyhole` router.
+///
+/// Paths:
+/// - `GET    /v1/cubbyhole/data/{*path}` — read
+/// - `POST   /v1/cubbyhole/data/{*path}` — write
+/// - `DELETE /v1/cubbyhole/data/{*path}` — delete
+/// - `GET    /v1/cubbyhole/list` — list all keys
+/// - `GET    /v1/cubbyhole/list/{*path}` — list keys under a prefix
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/data/{*path}",
+            get(read_entry).post(write_entry).delete(delete_entry),
+        )
+        .route("/list", get(list_root))
+        .route("/list/{*path}", get(list_entries))
+}
+
+/// Read an entry from the caller's cubbyhole.
+async fn read_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    validate_secret_path(&path)?;
+    check(&state, &auth, &format!("data/{path}"), &Capability::Read).await?;
+
+    let bytes = state
+        .cubbyhole
+        .get(&auth.token_hash, &path)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("no cubbyhole entry at {path}")))?;
+    let data: serde_json::Value = serde_json::from_slice(&bytes)
+        .map_err(|e| AppError::Internal(format!("corrupt cubbyhole entry: {e}")))?;
+
+    Ok(Json(serde_json::json!({ "data": data })))
+}
+
+/// Write an entry to the caller's cubbyhole, replacing any existing value.
+async fn write_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+    Json(body): Json<HashMap<String, serde_json::Value>>,
+) -> Result<StatusCode, AppError> {
+    validate_secret_path(&path)?;
+    check(&state, &auth, &format!("data/{path}"), &Capability::Create).await?;
+
+    let bytes = serde_json::to_vec(&body)
+        .map_err(|e| AppError::Internal(format!("serialization failed: {e}")))?;
+    state.cubbyhole.put(&auth.token_hash, &path, &bytes).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete an entry from the caller's cubbyhole.
+async fn delete_entry(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+) -> Result<StatusCode, AppError> {
+    validate_secret_path(&path)?;
+    check(&state, &auth, &format!("data/{path}"), &Capability::Delete).await?;
+
+    state.cubbyhole.delete(&auth.token_hash, &path).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List every key in the caller's cubbyhole.
+async fn list_root(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check(&state, &auth, "list", &Capability::List).await?;
+
+    let keys = state.cubbyhole.list(&auth.token_hash, "").await?;
+    Ok(Json(serde_json::json!({ "data": { "keys": keys } })))
+}
+
+/// List keys under a prefix in the caller's cubbyhole.
+async fn list_entries(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    validate_secret_path(&path)?;
+    check(&state, &auth, &format!("list/{path}"), &Capability::List).await?;
+
+    let prefix = if path.ends_with('/') {
+        path
+    } else {
+        format!("{path}/")
+    };
+    let keys = state.cubbyhole.list(&auth.token_hash, &prefix).await?;
+    Ok(Json(serde_json::json!({ "data": { "keys": keys } })))
+}
+
+/// Check `capability` on `cubbyhole/{path}` for the caller.
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: &Capability,
+) -> Result<(), AppError> {
+    if auth.batch {
+        return Err(AppError::BadRequest(
+            "batch tokens have no cubbyhole".to_owned(),
+        ));
+    }
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{CUBBYHOLE_MOUNT}{path}"),
+            capability,
+        )
+        .await?;
+    Ok(())
+}
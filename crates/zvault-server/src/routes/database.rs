@@ -10,19 +10,50 @@
 //! - `DELETE /v1/database/roles/:name` — delete a role
 //! - `GET  /v1/database/roles` — list all roles
 //! - `GET  /v1/database/creds/:name` — generate credentials
+//! - `POST /v1/database/rotate-root/:name` — rotate a connection's admin password
+//! - `POST /v1/database/static-roles/:name` — create a static role
+//! - `GET  /v1/database/static-roles/:name` — read a static role
+//! - `DELETE /v1/database/static-roles/:name` — delete a static role
+//! - `GET  /v1/database/static-roles` — list all static roles
+//! - `GET  /v1/database/static-creds/:name` — read a static role's password
+//! - `POST /v1/database/rotate-role/:name` — rotate a static role's password
+//!
+//! The same routes serve every database mount: other mounts (e.g.
+//! `database-teamA/`) are forwarded here by
+//! [`super::mounts::mount_router`], and handlers read the mount from the
+//! [`DatabaseMount`] request extension.
+//!
+//! Every endpoint is policy-checked against its path under the mount.
+//! Management endpoints (configs, roles, static roles, rotation) also accept
+//! delegated admin — `sudo` on `{mount}**` — while `creds` and
+//! `static-creds` always need an explicit `read` grant.
 
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
 use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
 use serde::Deserialize;
 
-use zvault_core::database::{DatabaseConfig, DatabaseRole};
+use zvault_core::database::{
+    DatabaseConfig, DatabaseEngine, DatabaseRole, DatabaseStaticRole, StaticCredentials,
+};
+use zvault_core::policy::Capability;
 
 use crate::error::AppError;
+use crate::middleware::AuthContext;
 use crate::state::AppState;
 
+/// The database mount a request is served from, e.g. `database/`.
+#[derive(Debug, Clone)]
+pub struct DatabaseMount(pub String);
+
+impl Default for DatabaseMount {
+    fn default() -> Self {
+        Self("database/".to_owned())
+    }
+}
+
 /// Build the database engine router.
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
@@ -37,6 +68,16 @@ pub fn router() -> Router<Arc<AppState>> {
             post(create_role).get(get_role).delete(delete_role),
         )
         .route("/creds/{name}", get(generate_creds))
+        .route("/rotate-root/{name}", post(rotate_root))
+        .route("/static-roles", get(list_static_roles))
+        .route(
+            "/static-roles/{name}",
+            post(create_static_role)
+                .get(get_static_role)
+                .delete(delete_static_role),
+        )
+        .route("/static-creds/{name}", get(get_static_creds))
+        .route("/rotate-role/{name}", post(rotate_static_role))
 }
 
 #[derive(Deserialize)]
@@ -47,6 +88,12 @@ struct ConfigureRequest {
     max_open_connections: u32,
     #[serde(default)]
     allowed_roles: Vec<String>,
+    /// Admin user for `{{username}}` in `connection_url`.
+    #[serde(default)]
+    username: Option<String>,
+    /// Admin password for `{{password}}` in `connection_url`.
+    #[serde(default)]
+    password: Option<String>,
 }
 
 fn default_max_conn() -> u32 {
@@ -55,13 +102,20 @@ fn default_max_conn() -> u32 {
 
 async fn configure(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
     Path(name): Path<String>,
     Json(body): Json<ConfigureRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}config/{name}"),
+        &Capability::Create,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     engine
         .configure(DatabaseConfig {
             name,
@@ -69,6 +123,9 @@ async fn configure(
             connection_url: body.connection_url,
             max_open_connections: body.max_open_connections,
             allowed_roles: body.allowed_roles,
+            username: body.username,
+            password: body.password,
+            root_rotated_at: None,
         })
         .await
         .map_err(AppError::from)?;
@@ -77,42 +134,70 @@ async fn configure(
 
 async fn get_config(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
     Path(name): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}config/{name}"),
+        &Capability::Read,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     let config = engine.get_config(&name).await.map_err(AppError::from)?;
-    // Redact connection_url in response.
+    // Redact connection_url in response unless the credentials are templated out of it.
+    let connection_url = if config.is_templated() {
+        config.connection_url
+    } else {
+        "***".to_owned()
+    };
     Ok(Json(serde_json::json!({
         "name": config.name,
         "plugin": config.plugin,
-        "connection_url": "***",
+        "connection_url": connection_url,
+        "username": config.username,
         "max_open_connections": config.max_open_connections,
         "allowed_roles": config.allowed_roles,
+        "root_rotated_at": config.root_rotated_at,
     })))
 }
 
 async fn delete_config(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
     Path(name): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}config/{name}"),
+        &Capability::Delete,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     engine.delete_config(&name).await.map_err(AppError::from)?;
     Ok(Json(serde_json::json!({"status": "deleted"})))
 }
 
 async fn list_configs(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}config"),
+        &Capability::List,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     let names = engine.list_configs().await.map_err(AppError::from)?;
     Ok(Json(serde_json::json!({"keys": names})))
 }
@@ -138,13 +223,20 @@ fn default_max_ttl() -> i64 {
 
 async fn create_role(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
     Path(name): Path<String>,
     Json(body): Json<CreateRoleRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}roles/{name}"),
+        &Capability::Create,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     engine
         .create_role(DatabaseRole {
             name,
@@ -161,47 +253,75 @@ async fn create_role(
 
 async fn get_role(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
     Path(name): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}roles/{name}"),
+        &Capability::Read,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     let role = engine.get_role(&name).await.map_err(AppError::from)?;
     Ok(Json(serde_json::to_value(role).unwrap_or_default()))
 }
 
 async fn delete_role(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
     Path(name): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}roles/{name}"),
+        &Capability::Delete,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     engine.delete_role(&name).await.map_err(AppError::from)?;
     Ok(Json(serde_json::json!({"status": "deleted"})))
 }
 
 async fn list_roles(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}roles"),
+        &Capability::List,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
     let names = engine.list_roles().await.map_err(AppError::from)?;
     Ok(Json(serde_json::json!({"keys": names})))
 }
 
 async fn generate_creds(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
     Path(name): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.database_engines.read().await;
-    let engine = engines
-        .get("database/")
-        .ok_or_else(|| AppError::NotFound("database engine not mounted".to_owned()))?;
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount}creds/{name}"),
+            &Capability::Read,
+        )
+        .await?;
+    let engine = database_engine(&state, &mount).await?;
     let (creds, role) = engine
         .generate_credentials(&name)
         .await
@@ -210,11 +330,12 @@ async fn generate_creds(
     // Create a lease for the credentials.
     let lease = zvault_core::lease::Lease {
         id: uuid::Uuid::new_v4().to_string(),
-        engine_path: format!("database/creds/{name}"),
+        engine_path: format!("{mount}creds/{name}"),
         issued_at: chrono::Utc::now(),
         ttl_secs: role.default_ttl_secs,
+        max_ttl_secs: role.max_ttl_secs,
         renewable: true,
-        data: serde_json::json!({"username": creds.username}),
+        data: serde_json::json!({"username": creds.username, "role": name}),
         token_hash: String::new(),
     };
     let lease_id = state
@@ -231,3 +352,200 @@ async fn generate_creds(
         "renewable": true,
     })))
 }
+
+async fn rotate_root(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}rotate-root/{name}"),
+        &Capability::Update,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
+    let rotation = engine.rotate_root(&name).await.map_err(AppError::from)?;
+    Ok(Json(serde_json::json!({
+        "status": "rotated",
+        "username": rotation.username,
+        "rotated_at": rotation.rotated_at,
+    })))
+}
+
+#[derive(Deserialize)]
+struct CreateStaticRoleRequest {
+    db_name: String,
+    username: String,
+    #[serde(default)]
+    rotation_statements: Vec<String>,
+}
+
+async fn create_static_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
+    Path(name): Path<String>,
+    Json(body): Json<CreateStaticRoleRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}static-roles/{name}"),
+        &Capability::Create,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
+    let creds = engine
+        .create_static_role(DatabaseStaticRole {
+            name,
+            db_name: body.db_name,
+            username: body.username,
+            rotation_statements: body.rotation_statements,
+        })
+        .await
+        .map_err(AppError::from)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "username": creds.username,
+        "last_rotated": creds.last_rotated,
+    })))
+}
+
+async fn get_static_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}static-roles/{name}"),
+        &Capability::Read,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
+    let role = engine
+        .get_static_role(&name)
+        .await
+        .map_err(AppError::from)?;
+    Ok(Json(serde_json::to_value(role).unwrap_or_default()))
+}
+
+async fn delete_static_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}static-roles/{name}"),
+        &Capability::Delete,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
+    engine
+        .delete_static_role(&name)
+        .await
+        .map_err(AppError::from)?;
+    Ok(Json(serde_json::json!({"status": "deleted"})))
+}
+
+async fn list_static_roles(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}static-roles"),
+        &Capability::List,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
+    let names = engine.list_static_roles().await.map_err(AppError::from)?;
+    Ok(Json(serde_json::json!({"keys": names})))
+}
+
+async fn get_static_creds(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
+    Path(name): Path<String>,
+) -> Result<Json<StaticCredentials>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount}static-creds/{name}"),
+            &Capability::Read,
+        )
+        .await?;
+    let engine = database_engine(&state, &mount).await?;
+    let creds = engine
+        .get_static_credentials(&name)
+        .await
+        .map_err(AppError::from)?;
+    Ok(Json(creds))
+}
+
+async fn rotate_static_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(DatabaseMount(mount)): Extension<DatabaseMount>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_admin(
+        &state,
+        &auth,
+        &mount,
+        &format!("{mount}rotate-role/{name}"),
+        &Capability::Update,
+    )
+    .await?;
+    let engine = database_engine(&state, &mount).await?;
+    let creds = engine
+        .rotate_static_role(&name)
+        .await
+        .map_err(AppError::from)?;
+    Ok(Json(serde_json::json!({
+        "status": "rotated",
+        "username": creds.username,
+        "last_rotated": creds.last_rotated,
+    })))
+}
+
+/// The database engine serving `mount`.
+async fn database_engine(state: &AppState, mount: &str) -> Result<Arc<DatabaseEngine>, AppError> {
+    state
+        .engines
+        .get::<DatabaseEngine>(mount)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("no database engine mounted at '{mount}'")))
+}
+
+/// Check a management operation, accepting delegated admin over the mount.
+async fn check_admin(
+    state: &AppState,
+    auth: &AuthContext,
+    mount: &str,
+    path: &str,
+    capability: &Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check_mount(&auth.policies, mount, path, capability)
+        .await?;
+    Ok(())
+}
@@ -283,7 +283,10 @@ cargo build --release --package zvault-server
 cargo build --release --package zvault-cli</code></pre>
 
 <h2>Run the Server</h2>
-<pre><code># In-memory storage (development)
+<pre><code># Dev mode: in-memory, initialized and unsealed, prints the root token
+./target/release/zvault-server --dev
+
+# In-memory storage (development)
 ./target/release/zvault-server
 
 # RocksDB storage (production)
@@ -292,6 +295,11 @@ ZVAULT_STORAGE=rocksdb ZVAULT_STORAGE_PATH=/var/lib/zvault \
 
 <p>The server starts on <code>http://127.0.0.1:8200</code> by default. Open the web UI or use the CLI.</p>
 
+<p>Dev mode (<code>--dev</code> or <code>ZVAULT_DEV=1</code>) skips the steps below: it forces in-memory storage, turns off
+TLS, mlock, HA, replication, and backups, initializes the vault with a single unseal share, unseals it, and prints the
+unseal key, the root token, and <code>export VAULT_ADDR=… VAULT_TOKEN=…</code> lines for the CLI. All data is lost when
+the server stops; never use it in production.</p>
+
 <h2>Step 1: Initialize</h2>
 <p>Initialization generates the root encryption key and splits the unseal key into Shamir shares.
 This can only be done once per storage backend.</p>
@@ -373,6 +381,10 @@ Storage layer (ciphertext only)</code></pre>
         ├──► Encrypts engine configuration
         ├──► Encrypts transit key material
         │
+        ├──► Encrypts: Keyring (term 2+ keys, after /v1/sys/rotate)
+        │     The active term encrypts new writes in place of the root key;
+        │     every entry is tagged with the term that encrypted it
+        │
         └──► Per-Engine Keys (derived via HKDF-SHA256)
               Each engine gets its own derived key</code></pre>
 
@@ -401,7 +413,10 @@ zvault-cli (standalone, HTTP client only — no internal deps)</code></pre>
 sys/mounts              → engine mount table
 sys/policies/&lt;name&gt;     → policy definitions
 sys/tokens/&lt;hash&gt;       → token metadata
+sys/token-accessors/&lt;accessor&gt; → token hash by accessor
 sys/leases/&lt;id&gt;         → lease data
+sys/plugins/catalog/&lt;name&gt; → plugin catalog entries
+plugins/&lt;mount&gt;/       → plugin-owned state
 kv/&lt;mount&gt;/data/&lt;path&gt;  → KV secret data
 transit/&lt;mount&gt;/keys/   → transit key material</code></pre>
 
@@ -422,6 +437,28 @@ const API_REFERENCE: &str = r#"
 <p>All API endpoints are prefixed with <code>/v1</code>. Authenticated endpoints require
 an <code>X-Vault-Token</code> header.</p>
 
+<h2>Errors</h2>
+<p>Every failed request returns the same JSON body. <code>code</code> is stable and determines the status;
+<code>request_id</code> matches the <code>X-Request-Id</code> response header and the request's audit entry.</p>
+<pre><code>{"errors": ["permission denied: ..."], "code": "permission_denied", "message": "permission denied: ...", "request_id": "..."}</code></pre>
+<table>
+  <thead><tr><th>Code</th><th>Status</th><th>Meaning</th></tr></thead>
+  <tbody>
+    <tr><td><code>invalid_request</code></td><td>400</td><td>Malformed request or invalid input</td></tr>
+    <tr><td><code>unauthenticated</code></td><td>401</td><td>Missing, invalid, or expired token or login credentials</td></tr>
+    <tr><td><code>permission_denied</code></td><td>403</td><td>Policy denies the operation</td></tr>
+    <tr><td><code>mfa_required</code></td><td>403</td><td>Valid codes are required in <code>X-Vault-MFA</code></td></tr>
+    <tr><td><code>control_group_required</code></td><td>403</td><td>A control group must approve the request</td></tr>
+    <tr><td><code>feature_not_licensed</code></td><td>403</td><td>The license lacks the feature (body adds <code>feature</code> and <code>required_tier</code>)</td></tr>
+    <tr><td><code>not_found</code></td><td>404</td><td>The resource does not exist</td></tr>
+    <tr><td><code>conflict</code></td><td>409</td><td>The request conflicts with current state (already exists, CAS mismatch)</td></tr>
+    <tr><td><code>rate_limited</code></td><td>429</td><td>A quota rejected the request; see <code>Retry-After</code></td></tr>
+    <tr><td><code>internal</code></td><td>500</td><td>The server failed</td></tr>
+    <tr><td><code>sealed</code></td><td>503</td><td>The vault is sealed</td></tr>
+    <tr><td><code>unavailable</code></td><td>503</td><td>Temporarily unable to serve, e.g. no HA active node</td></tr>
+  </tbody>
+</table>
+
 <h2>System</h2>
 <p>System endpoints manage vault lifecycle. Init and health do not require authentication.</p>
 
@@ -441,21 +478,99 @@ Response: {"sealed": true, "progress": 2, "threshold": 3}
 
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/seal-status</code></div>
 <p>Get current seal status. No authentication required.</p>
-<pre><code>Response: {"initialized": true, "sealed": false, "threshold": 3, "shares": 5}</code></pre>
+<pre><code>Response: {"initialized": true, "sealed": false, "threshold": 3, "shares": 5, "seal_type": "shamir"}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/seal/migrate</code></div>
+<p>Move an unsealed vault between unseal shares and auto-unseal. Requires <code>sudo</code> on
+<code>sys/seal/migrate</code>. Migrating to <code>auto</code> takes the current unseal shares and returns recovery shares;
+migrating to <code>shamir</code> takes recovery shares and returns new unseal shares. The vault stays unsealed, the
+submitted shares stop working, and a rekey in progress is cancelled.</p>
+<pre><code>Request:  {"to": "auto", "shares": ["...", "..."]}
+Response: {"seal_type": "devkms", "recovery_shares": ["...", ...]}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/rekey/init</code></div>
+<p>Start replacing the unseal shares with a new share count and threshold, without re-initializing. The vault must be
+unsealed. <code>GET</code> on the same path reports the rekey in progress. With auto-unseal the recovery shares are
+rekeyed instead. Every rekey endpoint requires <code>sudo</code> on its path (<code>sys/rekey/init</code>,
+<code>sys/rekey/update</code>, <code>sys/rekey/cancel</code>).</p>
+<pre><code>Request:  {"shares": 7, "threshold": 4}
+Response: {"started": true, "nonce": "...", "shares": 7, "threshold": 4, "progress": 0, "required": 3}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/rekey/update</code></div>
+<p>Submit a current share against the rekey's nonce. Once the current threshold is reached the response carries the new
+shares and the old ones stop working. Shares that fail to reconstruct the key cancel the rekey.</p>
+<pre><code>Request:  {"nonce": "...", "share": "base64-encoded-share"}
+Response: {"started": true, "complete": false, "progress": 1, "required": 3, ...}
+          {"complete": true, "unseal_shares": ["...", ...]}  // when threshold reached</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/rekey/cancel</code></div>
+<p>Cancel the rekey in progress and discard submitted shares. Sealing the vault also cancels it.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/generate-root/attempt</code></div>
+<p>Start minting a new root token from a quorum of unseal shares (recovery shares with auto-unseal), for when the
+root token is lost. The vault must be unsealed. No token is needed. The response carries a one-time pad, shown only
+here. <code>GET</code> reports the attempt in progress and <code>DELETE</code> cancels it; both require <code>sudo</code>
+on <code>sys/generate-root/attempt</code>. Sealing the vault also cancels it.</p>
+<pre><code>Response: {"started": true, "nonce": "...", "progress": 0, "required": 3, "otp_length": 36, "otp": "..."}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/generate-root/update</code></div>
+<p>Submit a current share against the attempt's nonce. Once the threshold is reached a new root token is stored and
+returned XOR-ed with the one-time pad and base64-encoded, so only the holder of the pad can read it; the token itself
+never leaves the server. No token is needed. Shares that fail to reconstruct the key cancel the attempt.</p>
+<pre><code>Request:  {"nonce": "...", "share": "base64-encoded-share"}
+Response: {"started": true, "complete": false, "progress": 1, "required": 3, ...}
+          {"complete": true, "encoded_token": "..."}  // when threshold reached</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/rotate</code></div>
+<p>Rotate the barrier encryption key. Requires <code>sudo</code> on <code>sys/rotate</code>. A new key becomes the active
+term for all writes; entries written under earlier terms stay readable. With <code>reencrypt</code>, every entry is
+rewritten under the new term before the response (other requests wait meanwhile). Unseal shares are unaffected.</p>
+<pre><code>Request:  {"reencrypt": true}
+Response: {"term": 2, "install_time": "2026-01-01T00:00:00Z", "reencrypted": 412}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/reencrypt</code></div>
+<p>Rewrite the entries under a storage prefix with the active term, one prefix at a time after a rotation. An empty
+<code>prefix</code> covers everything. Entries written before term tagging are tagged on the way. Requires
+<code>sudo</code> on <code>sys/reencrypt</code>.</p>
+<pre><code>Request:  {"prefix": "kv/"}
+Response: {"term": 2, "prefix": "kv/", "reencrypted": 37}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/key-status</code></div>
+<p>Active key term and when it was installed (<code>null</code> for the original key). With <code>?prefix=</code>, also counts
+the entries under the prefix by the term that encrypts them; <code>untagged</code> entries predate tagging and belong to
+term 1. Requires <code>read</code> on <code>sys/key-status</code>.</p>
+<pre><code>Response: {"term": 2, "install_time": "2026-01-01T00:00:00Z"}
+          {"term": 2, "install_time": "...", "usage": {"terms": {"1": 3, "2": 409}, "untagged": 0}}  // ?prefix=</code></pre>
+
+<h3>Dev KMS Seal</h3>
+<p>With <code>ZVAULT_SEAL=devkms</code> the root key is encrypted by a local key file instead of Shamir shares, and the
+server unseals itself on start. <code>/v1/sys/init</code> returns <code>recovery_shares</code> and leaves the vault unsealed;
+<code>/v1/sys/unseal</code> ignores the share and asks the KMS. This exercises the same auto-unseal, recovery, and
+migration paths as a cloud KMS without credentials. Anyone who can read the key file can unseal the vault — use it for
+development and tests only.</p>
 
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/health</code></div>
 <p>Health check. Returns 200 if unsealed, 503 if sealed, 501 if not initialized.</p>
 
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/metrics</code></div>
+<p>Prometheus text format metrics: seal status, lease counts (total and per engine mount), barrier encrypt/decrypt and
+storage operation latency histograms with error counts, token creations and revocations, and HTTP request counts and
+latency per route template. No authentication required unless <code>ZVAULT_METRICS_REQUIRE_AUTH=true</code>, in which case
+the token needs <code>read</code> on <code>sys/metrics</code>.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/.well-known/zvault-configuration</code></div>
+<p>Discovery document for SDKs, agents, and the CLI. No authentication required. Lists API base paths, enabled auth methods, the OIDC issuer (when SSO is licensed and configured), the MCP endpoint (when licensed), feature flags, and the minimum CLI version.</p>
+
 <h2>Secrets (KV v2)</h2>
-<p>Read and write versioned key-value secrets. All endpoints require authentication.</p>
+<p>Read and write versioned key-value secrets. All endpoints require authentication. <code>secret/</code> is the default mount; every other KV mount serves the same endpoints under its own path (e.g. <code>/v1/team-a/data/:path</code>), with policies written against that path (<code>team-a/data/*</code>).</p>
 
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/secret/data/:path</code></div>
 <p>Read the latest version of a secret.</p>
 <pre><code>Response: {"data": {"key": "value"}, "metadata": {"version": 3, "created_time": "..."}}</code></pre>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/secret/data/:path</code></div>
-<p>Write a new version of a secret.</p>
-<pre><code>Request:  {"data": {"username": "admin", "password": "s3cret"}}
+<p>Write a new version of a secret. With <code>options.cas</code> the write only succeeds if that is the current version (<code>0</code> for a secret that does not exist yet); otherwise it fails with 409. Secrets with <code>cas_required</code> reject writes without it.</p>
+<pre><code>Request:  {"data": {"username": "admin", "password": "s3cret"}, "options": {"cas": 3}}
 Response: {"version": 4, "created_time": "..."}</code></pre>
 
 <div class="endpoint"><span class="method method-delete">DELETE</span> <code>/v1/secret/data/:path</code></div>
@@ -467,10 +582,37 @@ Response: {"version": 4, "created_time": "..."}</code></pre>
 
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/secret/metadata/:path</code></div>
 <p>Read version history and metadata for a secret.</p>
+<pre><code>Response: {"current_version": 4, "version_count": 4, "max_versions": 10, "cas_required": true,
+           "delete_version_after_secs": 7776000,
+           "custom_metadata": {"owner": "alice", "team": "payments"}, "created_at": "...", "updated_at": "..."}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/secret/metadata/:path</code></div>
+<p>Update a secret's metadata (requires <code>update</code> on <code>secret/metadata/:path</code>). Omitted fields are unchanged; <code>custom_metadata</code> replaces all tags (up to 64 keys, 512-byte values). Lowering <code>max_versions</code> prunes the oldest versions. Metadata can be set before the first write.</p>
+<p><code>delete_version_after</code> (e.g. <code>"90d"</code>, <code>"2160h"</code>; <code>"0"</code> disables) makes each version unreadable once it is that old; the server permanently deletes such versions on every lease scan (<code>ZVAULT_LEASE_SCAN_INTERVAL</code>). A secret whose current version has expired reads as not found until it is written again.</p>
+<pre><code>Request: {"custom_metadata": {"owner": "alice", "team": "payments"}, "max_versions": 5, "cas_required": true,
+          "delete_version_after": "90d"}</code></pre>
+
+<div class="endpoint"><span class="method method-delete">DELETE</span> <code>/v1/secret/metadata/:path</code></div>
+<p>Permanently destroy a secret and all of its versions.</p>
 
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/secret/list/:prefix</code></div>
 <p>List secret keys under a prefix.</p>
 
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/secret/list</code></div>
+<p>List every secret key in the mount.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/secret/copy</code></div>
+<p>Copy a secret, or every secret under a prefix ending in <code>/</code>, with all versions and metadata. Fails with 409 if a destination exists unless <code>overwrite</code> is set.</p>
+<pre><code>Request: {"from": "myapp/", "to": "myapp-v2/", "overwrite": false}
+Response: {"secrets": [{"from": "myapp/db", "to": "myapp-v2/db"}]}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/secret/move</code></div>
+<p>Like copy, then permanently remove the sources. Requires <code>delete</code> on each source's metadata path.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/secret/hashes/:prefix</code></div>
+<p>HMAC-SHA256 of every value of every secret under a prefix (or the whole mount at <code>/v1/secret/hashes</code>), keyed like audit log HMACs. Equal values have equal hashes, so two subtrees can be diffed without reading them. Requires <code>list</code> on the prefix and <code>read</code> on each secret.</p>
+<pre><code>Response: {"secrets": {"db": {"password": "9f2c…"}, "api/key": {"value": "41ab…"}}}</code></pre>
+
 <h2>Transit (Encryption as a Service)</h2>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/keys/:name</code></div>
@@ -480,15 +622,44 @@ Response: {"version": 4, "created_time": "..."}</code></pre>
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/transit/keys/:name</code></div>
 <p>Read key metadata (type, versions, creation time). Key material is never returned.</p>
 
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/keys/:name/config</code></div>
+<p>Set the oldest key versions allowed for decryption and encryption. <code>min_encryption_version</code> of <code>0</code> (the default) allows only the latest version.</p>
+<pre><code>Request:  {"min_decryption_version": 3, "min_encryption_version": 0}
+Response: key metadata</code></pre>
+
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/encrypt/:name</code></div>
-<p>Encrypt plaintext with a named key.</p>
+<p>Encrypt plaintext with a named key. <code>key_version</code> is optional and must not be below <code>min_encryption_version</code>.</p>
 <pre><code>Request:  {"plaintext": "base64-encoded-data"}
 Response: {"ciphertext": "vault:v1:base64-ciphertext"}</code></pre>
+<p>Send <code>batch_input</code> instead of <code>plaintext</code> to encrypt up to 1000 items in one call. Items are processed concurrently; <code>batch_results</code> keeps input order and a failed item carries an <code>error</code> without failing the rest.</p>
+<pre><code>Request:  {"batch_input": [{"plaintext": "aGVsbG8="}, {"plaintext": "d29ybGQ=", "key_version": 2}]}
+Response: {"batch_results": [{"ciphertext": "vault:v3:..."}, {"ciphertext": "vault:v2:..."}]}</code></pre>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/decrypt/:name</code></div>
-<p>Decrypt ciphertext.</p>
+<p>Decrypt ciphertext. Also accepts <code>batch_input</code>, with per-item results as for encrypt.</p>
 <pre><code>Request:  {"ciphertext": "vault:v1:base64-ciphertext"}
-Response: {"plaintext": "base64-encoded-data"}</code></pre>
+Response: {"plaintext": "base64-encoded-data"}
+
+Request:  {"batch_input": [{"ciphertext": "vault:v1:..."}, {"ciphertext": "garbage"}]}
+Response: {"batch_results": [{"plaintext": "aGVsbG8="}, {"error": "invalid engine request: invalid ciphertext format, expected vault:v{N}:{base64}"}]}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/rewrap/:name</code></div>
+<p>Re-encrypt ciphertext under the latest key version (or <code>key_version</code>). The plaintext is never returned.</p>
+<pre><code>Request:  {"ciphertext": "vault:v1:base64-ciphertext"}
+Response: {"ciphertext": "vault:v3:base64-ciphertext"}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/tokenize/:name</code></div>
+<p>Replace a value with a random token; the engine keeps the value encrypted under the key, with optional <code>metadata</code>. With <code>"convergent": true</code>, an equal value gets its existing token back.</p>
+<pre><code>Request:  {"plaintext": "base64-encoded-data", "convergent": true, "metadata": {"field": "ssn"}}
+Response: {"token": "tok_...", "convergent": true, "metadata": {"field": "ssn"}, "key_version": 1, "created_at": "..."}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/detokenize/:name</code></div>
+<p>Return the value behind a token, alongside its metadata.</p>
+<pre><code>Request:  {"token": "tok_..."}
+Response: {"token": "tok_...", ..., "plaintext": "base64-encoded-data"}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/lookup/:name</code></div>
+<p>Describe a token without revealing its value (<code>{"token": ...}</code>), or find the convergent token for a value (<code>{"plaintext": ...}</code>).</p>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/transit/sign/:name</code></div>
 <p>Sign data with a named key (Ed25519 or ECDSA).</p>
@@ -505,12 +676,14 @@ Response: {"plaintext": "base64-encoded-data"}</code></pre>
 <h2>Auth Tokens</h2>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/token/create</code></div>
-<p>Create a new token with specified policies and TTL.</p>
-<pre><code>Request:  {"policies": ["app-readonly"], "ttl": "1h"}
-Response: {"token": "hvs.xxx", "policies": ["app-readonly"], "ttl": 3600}</code></pre>
+<p>Create a new token with specified policies and TTL. <code>bound_cidrs</code> limits the networks it may be used from, and defaults to the parent token's; a child of a bound token must stay inside its parent's networks.</p>
+<pre><code>Request:  {"policies": ["app-readonly"], "ttl": "1h", "bound_cidrs": ["10.0.0.0/8"]}
+Response: {"client_token": "hvs.xxx", "accessor": "9f2c...", "policies": ["app-readonly"], "lease_duration": 3600}</code></pre>
+<p>Logins (AppRole, JWT, certificate) also return the new token's <code>accessor</code>.</p>
+<p><code>"type": "batch"</code> creates a batch token (<code>b.</code> prefix) instead: its policies and TTL are encrypted and HMAC-signed into the token, and nothing is written to storage, so batch tokens are cheap to create in bulk. They default to a 1h TTL, cannot be renewed or revoked on their own, and stop working when their parent token is revoked. They cannot create tokens and have no cubbyhole or accessor: <code>accessor</code> is <code>null</code> in their create and lookup responses and empty in their audit entries. Lookups report <code>"type": "batch"</code>.</p>
 
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/auth/token/lookup</code></div>
-<p>Look up metadata for the current token.</p>
+<p>Look up metadata for the current token, including its <code>accessor</code> — the non-secret ID audit entries record — and <code>bound_cidrs</code>.</p>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/token/renew</code></div>
 <p>Renew the current token's TTL.</p>
@@ -518,6 +691,109 @@ Response: {"token": "hvs.xxx", "policies": ["app-readonly"], "ttl": 3600}</code>
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/token/revoke</code></div>
 <p>Revoke a token and all its child tokens and leases.</p>
 
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/auth/token/accessors</code></div>
+<p>List the accessors of all stored tokens, including expired ones not yet removed. Requires <code>sudo</code> on <code>auth/token/accessors</code>.</p>
+<pre><code>Response: {"keys": ["0b6e...", "9f2c..."]}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/token/lookup-accessor</code></div>
+<p>Look up a token's metadata by accessor, without the token. Requires <code>sudo</code>; an unknown accessor returns <code>404</code>.</p>
+<pre><code>Request: {"accessor": "9f2c..."}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/token/revoke-accessor</code></div>
+<p>Revoke the token with an accessor and all its child tokens. Requires <code>sudo</code>.</p>
+
+<h2>JWT Auth</h2>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/jwt/config</code></div>
+<p>Set where signing keys come from: a <code>jwks_url</code> or a list of PEM <code>jwt_validation_pubkeys</code> (exactly one), plus an optional <code>bound_issuer</code>. Requires <code>update</code> on <code>auth/jwt/config</code>.</p>
+<pre><code>Request: {"jwks_url": "https://token.actions.githubusercontent.com/.well-known/jwks",
+          "bound_issuer": "https://token.actions.githubusercontent.com"}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/jwt/role/:name</code></div>
+<p>Create or replace a role. <code>bound_audiences</code> and <code>policies</code> are required; <code>bound_claims</code> values are exact strings, or globs with <code>"bound_claims_type": "glob"</code>. Claim keys starting with <code>/</code> are JSON pointers into nested claims. The optional <code>groups_claim</code> names the claim listing the caller's groups, for external identity groups.</p>
+<pre><code>Request: {"bound_audiences": ["https://github.com/acme"],
+          "bound_claims": {"repository": ["acme/api"], "ref": ["refs/heads/main"]},
+          "user_claim": "repository", "policies": ["deploy"], "token_ttl_secs": 900}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/auth/jwt/role</code></div>
+<p>List role names. <code>GET</code> and <code>DELETE</code> on <code>/v1/auth/jwt/role/:name</code> read and remove one role.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/jwt/login</code></div>
+<p>Exchange a JWT for a token (no auth required). The signature, <code>exp</code>/<code>nbf</code> (60s leeway), audience, issuer, and every bound claim must check out; HMAC-signed tokens are rejected.</p>
+<pre><code>Request:  {"role": "deploy", "jwt": "eyJhbGciOiJSUzI1NiIs..."}
+Response: {"client_token": "...", "policies": ["deploy"], "ttl": 900, "renewable": true}</code></pre>
+
+<h2>Cert Auth</h2>
+<p>Log in with a TLS client certificate. Works on a TLS listener (certificate files or ACME), which asks every client for a certificate unless <code>ZVAULT_TLS_DISABLE_CLIENT_CERTS=true</code>; connections without one are still accepted.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/cert/role/:name</code></div>
+<p>Create or replace a role. <code>certificate</code> (PEM CA certificates the client's chain must lead to) and <code>policies</code> are required. <code>allowed_common_names</code>, <code>allowed_dns_sans</code>, <code>allowed_email_sans</code>, <code>allowed_uri_sans</code>, and <code>allowed_organizational_units</code> are globs; every non-empty list must match the certificate.</p>
+<pre><code>Request: {"certificate": "-----BEGIN CERTIFICATE-----\n...",
+          "allowed_common_names": ["web-*"], "allowed_organizational_units": ["platform"],
+          "policies": ["web"], "token_ttl_secs": 900}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/auth/cert/role</code></div>
+<p>List role names. <code>GET</code> and <code>DELETE</code> on <code>/v1/auth/cert/role/:name</code> read and remove one role.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/cert/login</code></div>
+<p>Exchange the client certificate presented on this connection for a token (no auth required). The chain must be valid now and lead to the role's CAs. Without <code>role</code>, the first role by name that accepts the certificate is used. The token's identity alias is the certificate's common name.</p>
+<pre><code>Request:  {"role": "web"}
+Response: {"client_token": "...", "policies": ["web"], "ttl": 900, "renewable": true}</code></pre>
+
+<h2>Cloud Link</h2>
+<p>Hybrid deployments can use one credential on both sides: a vault linked to a cloud org exchanges that org's <code>zvt_</code> service tokens for vault tokens, and mints service tokens from vault tokens. Requires cloud mode (<code>CLOUD_DATABASE_URL</code>). Linking takes both sides: the vault names the org, and an org admin confirms the returned link ID.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/cloud-link</code></div>
+<p>Link to an org. Requires <code>sudo</code> on <code>sys/cloud-link</code>. <code>token_ttl</code> (default 1h) bounds exchanged vault tokens; <code>max_service_token_ttl</code> (default 1h, at most 24h) bounds minted service tokens. <code>GET</code> shows the link and whether it is confirmed; <code>DELETE</code> unlinks.</p>
+<pre><code>Request:  {"org_id": "6cd3abc0-...", "policies": ["ci-readonly"], "token_ttl": "30m"}
+Response: {"link_id": "492c66e6...", "confirmed": false, ...}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/cloud/orgs/:org_id/vault-link</code></div>
+<p>Confirm a link ID (org admins only, Clerk session). <code>DELETE</code> stops all exchange for the org.</p>
+<pre><code>Request: {"link_id": "492c66e6..."}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/auth/cloud/login</code></div>
+<p>Exchange a <code>zvt_</code> service token from the linked org for a non-renewable vault token with the link's policies, expiring no later than the service token.</p>
+<pre><code>Request:  {"token": "zvt_86ad89ea..."}
+Response: {"client_token": "...", "policies": ["ci-readonly"], "ttl": 1800, "renewable": false}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/cloud-link/service-token</code></div>
+<p>Mint a <code>zvt_</code> service token for a project of the linked org. Each requested permission needs a capability on <code>sys/cloud-link/projects/:project_id</code>: <code>read</code> needs <code>read</code>, <code>write</code> needs <code>update</code>.</p>
+<pre><code>Request:  {"project_id": "6be12c8f-...", "permissions": ["read"], "ttl": "10m"}
+Response: {"token": {...}, "plaintext_token": "zvt_d33ebd22..."}</code></pre>
+
+<h2>Response Wrapping</h2>
+<p>Send <code>X-Vault-Wrap-TTL</code> (<code>60</code>, <code>5m</code>, <code>1h</code>; at most 30 days) on any authenticated request to get a single-use wrapping token instead of the response. The response is kept in the token's cubbyhole until it is unwrapped or expires. Errors are never wrapped. Wrapping tokens are rejected everywhere except the endpoints below, which take the wrapping token in <code>token</code> or, failing that, <code>X-Vault-Token</code>.</p>
+<pre><code>curl -H "X-Vault-Token: $TOKEN" -H "X-Vault-Wrap-TTL: 5m" \
+  -X POST $VAULT_ADDR/v1/auth/approle/role/ci/secret-id
+Response: {"wrap_info": {"token": "...", "ttl": 300, "creation_time": "...", "creation_path": "auth/approle/role/ci/secret-id"}}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/wrapping/unwrap</code></div>
+<p>Return the wrapped response and revoke the wrapping token. A second unwrap fails, so an intercepted token is noticed by the intended recipient.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/wrapping/lookup</code></div>
+<p>Show <code>creation_path</code>, <code>creation_time</code>, and <code>creation_ttl</code> without unwrapping.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/wrapping/rewrap</code></div>
+<p>Move the response to a new wrapping token with the original TTL and revoke the old one.</p>
+
+<h2>Cubbyhole</h2>
+<p>Private storage for the calling token, mounted at <code>cubbyhole/</code>. Each token sees only its own entries, and they are destroyed when the token is revoked — scratch space for a CI job's short-lived secrets. The <code>default</code> policy grants full access to <code>cubbyhole/**</code>.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/cubbyhole/data/:path</code></div>
+<p>Read an entry.</p>
+<pre><code>Response: {"data": {"key": "abc"}}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/cubbyhole/data/:path</code></div>
+<p>Write an entry, replacing any existing value. The body is a flat JSON object.</p>
+
+<div class="endpoint"><span class="method method-delete">DELETE</span> <code>/v1/cubbyhole/data/:path</code></div>
+<p>Delete an entry.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/cubbyhole/list/:path</code></div>
+<p>List keys under a prefix; <code>/v1/cubbyhole/list</code> lists the whole cubbyhole.</p>
+<pre><code>Response: {"data": {"keys": ["ci/deploy-key"]}}</code></pre>
+
 <h2>Policies</h2>
 
 <div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/policies</code></div>
@@ -538,10 +814,115 @@ Response: {"token": "hvs.xxx", "policies": ["app-readonly"], "ttl": 3600}</code>
 <p>List all engine mounts.</p>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mounts/:path</code></div>
-<p>Mount a new secrets engine at the given path.</p>
+<p>Mount a new secrets engine at the given path. <code>engine_type</code> is <code>kv</code>, <code>transit</code>, <code>database</code>, or the name of a plugin in the catalog (equivalently <code>plugin</code> with <code>config.plugin_name</code>); a plugin is started before it is mounted and receives <code>config</code> on start. The mount is served at <code>/v1/:path/</code> as soon as this returns; a KV mount keeps its data apart from every other mount under <code>kv/:path/</code>. <code>sys/</code>, <code>auth/</code>, and the built-in engine paths are reserved.</p>
+<pre><code>Request:  {"engine_type": "rabbitmq", "description": "RabbitMQ users", "config": {"host": "mq.internal"}}</code></pre>
 
 <div class="endpoint"><span class="method method-delete">DELETE</span> <code>/v1/sys/mounts/:path</code></div>
-<p>Unmount an engine and revoke all its leases.</p>
+<p>Unmount an engine and revoke all its leases. The engine's stored data is kept, and mounting the same type at the same path again brings it back; pass <code>?purge=true</code> to delete it.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mounts/:path/tune</code></div>
+<p>Update KV mount options. Set <code>lease_reads</code> and <code>read_lease_ttl_secs</code> to attach a lease to every read.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mounts/:path/export</code></div>
+<p>Export a KV mount's entry and data, decrypted from this vault's barrier and re-encrypted under a 256-bit transfer key, so one team's engine can move to another cluster without a full backup. Requires <code>sudo</code> on <code>sys/mounts</code> or delegated admin over the mount. Omit <code>transfer_key</code> to have one generated and returned; send it separately from the bundle.</p>
+<pre><code>Request:  {"transfer_key": "base64 (optional)"}
+Response: {"bundle": {"version": 1, "source_path": "team-a/", "engine_type": "kv", "entry_count": 42, "created_at": "...", "ciphertext": "..."}, "transfer_key": "..."}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mounts/:path/import</code></div>
+<p>Mount an exported bundle at <code>:path</code> with the source mount's description and config. The path must be unmounted and hold no data; leases and read counters are not transferred. Bundles up to 256 MiB are accepted, as are snapshots sent to <code>/v1/sys/restore</code>; other endpoints take bodies up to 2 MiB.</p>
+<pre><code>Request:  {"bundle": {...}, "transfer_key": "..."}
+Response: {"path": "team-a/", "source_path": "team-a/", "entry_count": 42}</code></pre>
+
+<h2>Plugins</h2>
+<p>External secrets engines are WebAssembly modules in <code>ZVAULT_PLUGIN_DIR</code>, run in a wasmtime sandbox with no filesystem, environment, or network access of their own, a 64 MiB memory cap, and a fuel budget and timeout per call. A module exports <code>memory</code>, <code>zvault_abi_version() -&gt; i32</code> (ABI version <code>1</code>), <code>zvault_alloc(len) -&gt; ptr</code>, and <code>zvault_call(ptr, len) -&gt; i64</code>, which takes a JSON <code>{method, params}</code> request and returns a <code>{result}</code> or <code>{error: {code, message}}</code> response packed as <code>ptr &lt;&lt; 32 | len</code>. The server calls <code>initialize</code> <code>{abi_version: 1, mount, config}</code> once, then <code>handle</code> <code>{operation, path, data}</code> for each request (<code>read</code>, <code>create</code>, <code>update</code>, <code>delete</code>, <code>list</code>) and <code>revoke</code> <code>{lease_id, path, secret}</code> when a lease it issued ends. While handling a call, the plugin may call its <code>zvault.host_call</code> import with <code>storage.get</code>, <code>storage.put</code>, <code>storage.delete</code>, and <code>storage.list</code>, scoped to <code>plugins/&lt;mount&gt;</code> in the barrier; <code>http.request</code> <code>{method, url, headers, body}</code> to the hosts in its <code>allowed_hosts</code>; and <code>log</code> <code>{message}</code>. Errors with code <code>400</code> or <code>404</code> are returned to the client as such.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/plugins/catalog</code></div>
+<p>List registered plugins with the mounts that use them.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/plugins/catalog/:name</code></div>
+<p>Register or replace a plugin. Requires <code>sudo</code> on <code>sys/plugins/catalog/:name</code>. <code>module</code> is relative to the plugin directory; its SHA-256 is checked now and before every instantiation, and it must compile. <code>allowed_hosts</code> lists the only hosts <code>http.request</code> may reach.</p>
+<pre><code>Request:  {"module": "rabbitmq.wasm", "sha256": "9f86d0…", "allowed_hosts": ["rabbitmq.internal"], "description": "RabbitMQ users"}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/plugins/catalog/:name</code></div>
+<p>Read a catalog entry.</p>
+
+<div class="endpoint"><span class="method method-delete">DELETE</span> <code>/v1/sys/plugins/catalog/:name</code></div>
+<p>Remove a plugin. Refused with <code>409</code> while it is mounted.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/plugins/reload/:name</code></div>
+<p>Discard every instance of the plugin and instantiate it again, picking up a re-registered module. Requires <code>sudo</code> on <code>sys/plugins/reload</code>.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/:mount/*path</code></div>
+<p>Requests under a plugin mount go to the plugin: <code>GET</code> reads (<code>?list=true</code> lists), <code>POST</code> creates, <code>PUT</code> updates, <code>DELETE</code> deletes, each checked against the matching capability on the full path. A response with a lease carries <code>lease_id</code>, <code>lease_duration</code>, and <code>renewable</code>.</p>
+
+<h2>Audit Devices</h2>
+<p>Each request is recorded twice, with the same <code>id</code> and <code>request_id</code>: once without a
+<code>response</code> before it is served, and once with the response status after. A request no enabled device
+accepts is refused with <code>500</code> before it reaches its handler, <code>DELETE /v1/sys/audit/:path</code>
+included; to replace a failing device, enable a working one first.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/audit</code></div>
+<p>List enabled audit devices.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/audit/:path</code></div>
+<p>Enable an audit device. Body: <code>{"type": "file"|"syslog"|"webhook", "options": {...}}</code>.</p>
+
+<div class="endpoint"><span class="method method-delete">DELETE</span> <code>/v1/sys/audit/:path</code></div>
+<p>Disable an audit device, flushing any buffered entries.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/audit-settings</code></div>
+<p>Read request data logging and redaction rules.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/audit-settings</code></div>
+<p>Replace them. Request bodies are recorded only with <code>log_request_data</code>; bodies declared
+larger than 2 MiB (restores, mount imports) are served but not recorded. Every rule whose <code>path</code>
+pattern (<code>*</code> one segment, <code>**</code> any) matches the request applies: <code>hmac</code> fields are
+replaced by <code>hmac-sha256:&lt;hex&gt;</code> and <code>remove</code> fields are deleted, at any depth, and
+<code>drop_body</code> drops the body. Omit <code>rules</code> to restore the built-in ones, which HMAC credentials
+(<code>token</code>, <code>secret_id</code>, <code>password</code>, ...) everywhere. Whatever the rules say, bodies sent to
+KV and cubbyhole mounts and to transit encrypt/decrypt are never recorded; the mount is found in the mount
+table, so this covers every mount of those engines, including ones added at runtime. AppRole and JWT
+logins are audited with operation <code>login</code>.</p>
+<pre><code>Request: {"log_request_data": true, "rules": [
+  {"path": "auth/approle/login", "hmac": ["secret_id"]},
+  {"path": "database/config/*", "drop_body": true}
+]}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/audit-log</code></div>
+<p>Read entries from the file audit device. Without <code>cursor</code>, returns the most recent <code>limit</code> entries (default 100, max 1000), newest first. With <code>cursor</code> (start at <code>0</code>), pages through the whole log oldest first; pass each response's <code>next_cursor</code> until it is absent. Requires <code>sudo</code> on <code>sys/audit-log</code>, as does the count below.</p>
+<pre><code>GET /v1/sys/audit-log?cursor=0&amp;limit=1000
+Response: {"entries": [...], "count": 1000, "next_cursor": 482113}</code></pre>
+<p>Both modes take filters, applied on the server: <code>start_time</code> and <code>end_time</code> (RFC 3339, or a duration such as <code>30d</code> meaning that long ago), <code>path_prefix</code>, <code>actor</code> (display name), <code>token_hash</code> or <code>token_id</code> (the HMAC in the log), <code>accessor</code> (as shown by token lookup), <code>operation</code>, and <code>status</code> (<code>success</code>, <code>error</code>, or a status code). A filtered page may scan past many entries before it fills.</p>
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/audit-log/count</code></div>
+<p>Count the entries that pass the same filters.</p>
+<pre><code>GET /v1/sys/audit-log/count?path_prefix=secret/data/db&amp;operation=read&amp;start_time=30d
+Response: {"count": 42}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/audit-log/external</code></div>
+<p>Record an action a client took on the caller's behalf, such as an MCP tool call, in the audit devices. The entry's path is <code>external/&lt;source&gt;/&lt;action&gt;</code> and it carries the caller's token and policies; its <code>data</code> is kept even when request data logging is off, with the redaction rules applied. <code>operation</code> is <code>read</code>, <code>write</code>, <code>delete</code>, or <code>execute</code> (default); <code>outcome</code> (<code>success</code>, <code>error</code>, <code>denied</code>) is recorded as status 200, 500, or 403. Requires <code>create</code> on <code>sys/audit-log/external</code>, which the built-in <code>mcp-audit</code> policy grants; attach it to the tokens of clients that report here.</p>
+<pre><code>Request: {"source": "mcp", "action": "zvault_set_secret", "operation": "write",
+          "data": {"arguments": {"path": "app/db", "value": "[redacted]"}}, "outcome": "success"}</code></pre>
+
+<h2>License</h2>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/license</code></div>
+<p>Show the active license and effective tier.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/license</code></div>
+<p>Activate a signed license key. Body: <code>{"key": "..."}</code>. Gated routes (MCP, SSO, namespaces, HA) return <code>403 feature_not_licensed</code> with <code>feature</code> and <code>required_tier</code> fields when the tier is too low.</p>
+
+<h2>Client Activity</h2>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/internal/counters/activity</code></div>
+<p>Distinct clients per month, namespace, and mount. Query: <code>start_time</code>, <code>end_time</code> (<code>YYYY-MM</code>, default the last 12 months). Tokens bound to an entity count once per entity.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/internal/counters/activity/export</code></div>
+<p>One row per client, mount, and month for chargeback. Filter with <code>namespace</code> and <code>mount</code>; <code>format=csv</code> returns CSV.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/internal/counters/secrets</code></div>
+<p>Read count and distinct readers for every KV secret under <code>prefix</code> (e.g. <code>secret/myapp/</code>). Secrets that were never read are listed with zero counts. Reads are aggregated in memory and persisted every <code>ZVAULT_SECRET_USAGE_FLUSH_INTERVAL</code> seconds.</p>
+<pre><code>Response: {"secrets": [{"path": "secret/myapp/db", "reads": 42, "distinct_readers": 3,
+                        "first_read": "2026-10-01T09:00:00Z", "last_read": "2026-10-16T08:12:45Z"}]}</code></pre>
 
 <h2>Leases</h2>
 
@@ -549,10 +930,293 @@ Response: {"token": "hvs.xxx", "policies": ["app-readonly"], "ttl": 3600}</code>
 <p>List active leases.</p>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/leases/renew</code></div>
-<p>Renew a lease by ID.</p>
+<p>Renew a lease by ID. Body: <code>{"lease_id": "...", "increment": 3600}</code>. The TTL is capped at the lease's <code>max_ttl_secs</code>.</p>
 
 <div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/leases/revoke</code></div>
-<p>Revoke a lease by ID.</p>
+<p>Revoke a lease by ID. Database leases drop the generated user and PKI leases tombstone the certificate before the lease is removed.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/leases/revoke-prefix/:prefix</code></div>
+<p>Revoke every lease whose engine path starts with the prefix (e.g., <code>database/</code>). Leases whose engine cleanup fails are kept.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/leases/revoke-force/:prefix</code></div>
+<p>Like <code>revoke-prefix</code>, but removes leases even when engine cleanup fails. Use only when the backing system is gone.</p>
+
+<h2>Access Requests</h2>
+<p>Just-in-time access: request temporary capabilities on a path, have an approver grant them, and let them expire.
+An approved request writes the policy <code>access-request-{id}</code> and attaches it to the requester's entity (or token,
+for tokens without one) until <code>expires_at</code>. Every change is kept in the request's <code>history</code>, published as
+an <code>access_request.*</code> event, and posted to <code>ZVAULT_ACCESS_REQUEST_WEBHOOK</code> when set.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/access-requests</code></div>
+<p>Submit a request. Needs <code>create</code> on <code>sys/access-requests</code>, which the <code>default</code> policy grants. <code>ttl</code> defaults to <code>1h</code> and may not exceed <code>24h</code>.</p>
+<pre><code>Request: {"path": "secret/data/prod/**", "capabilities": ["read"], "reason": "INC-42", "ttl": "2h"}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/access-requests</code></div>
+<p>List requests, newest first. Without <code>list</code> on <code>sys/access-requests</code>, only your own requests are returned.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/access-requests/:id</code></div>
+<p>Read a request and its history. Requesters can always read their own.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/access-requests/:id/approve</code></div>
+<p>Approve a pending request. Needs <code>update</code> on <code>sys/access-requests/:id</code>, and the approver must hold every requested capability on the path. Requesters cannot approve their own requests. Optional body: <code>{"comment": "..."}</code>.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/access-requests/:id/deny</code></div>
+<p>Deny a pending request. Same permissions as approve.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/access-requests/:id/revoke</code></div>
+<p>End an approved grant early and delete its policy. Requesters may revoke their own grants.</p>
+
+<h2>Control Groups</h2>
+<p>A request granted by a policy rule with a <code>control_group</code> block is not run. It is parked for 24 hours and
+answered with <code>202 Accepted</code>:</p>
+<pre><code>{"control_group": {"accessor": "9f1c…", "path": "/v1/secret/data/prod/root", "expires_at": "...",
+                   "control_groups": [{"approvals": 2, "approver_policies": ["security"], "approver_identities": []}]}}</code></pre>
+<p>Once approved, the requester repeats the same method and path with <code>X-Vault-Control-Group: &lt;accessor&gt;</code>. The
+parked request — including its original body — runs and its response is returned. Each approval runs the request once.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/control-group/authorize</code></div>
+<p>Approve a parked request. The caller must hold one of a control group's <code>approver_policies</code> or be one of its
+<code>approver_identities</code>; requesters cannot approve their own requests.</p>
+<pre><code>Request: {"accessor": "9f1c…"}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/control-group/request</code></div>
+<p>Read a parked request: method, path, requester, approvals per control group, and whether it is <code>approved</code>.
+Open to the requester and anyone who could approve it.</p>
+
+<h2>Events</h2>
+<p>Vault events stream as Server-Sent Events, so services can reload secrets when they change instead of polling.
+Topics: <code>kv.write</code>, <code>kv.delete</code>, <code>kv.destroy</code>, <code>kv.metadata</code>,
+<code>policy.write</code>, <code>policy.delete</code>, <code>lease.expired</code>, <code>lease.revoked</code>,
+<code>sys.initialized</code>, <code>sys.sealed</code>, <code>sys.unsealed</code>, and <code>sys.unseal_failed</code> (published
+after every third failed unseal attempt in a row). Events carry paths and IDs, never secret values.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/events/subscribe/:pattern</code></div>
+<p>Stream events whose topic matches the pattern; <code>*</code> matches anything and <code>/</code> reads as <code>.</code>,
+so <code>kv/*</code> covers every KV topic. Needs <code>read</code> on <code>sys/events/subscribe/:pattern</code>. An event about
+a path is only sent if the token can <code>read</code> that path (<code>sys/policies</code> for policy events). The stream
+ends when the token is revoked or expires, and after <code>sys.sealed</code>. A subscriber that falls behind receives a
+<code>lagged</code> event with the number of events it missed.</p>
+<pre><code>event: kv.write
+id: 6c1e…
+data: {"id": "6c1e…", "topic": "kv.write", "timestamp": "...",
+       "data": {"mount": "secret/", "path": "secret/data/myapp/db", "version": 4}}</code></pre>
+
+<h2>MCP</h2>
+<p>The Model Context Protocol served over HTTP, so remote AI agents and hosted assistants can connect with a vault token
+instead of running <code>zvault mcp-server</code> locally. Requires a Pro license. Tools: <code>zvault_list_secrets</code>,
+<code>zvault_describe_secret</code>, <code>zvault_generate_env_template</code>, <code>zvault_set_secret</code>,
+<code>zvault_delete_secret</code>, and <code>zvault_vault_status</code>. Each runs with the same policy check as the matching
+<code>/v1/secret</code> request, no tool returns a secret value, and every call is audited at <code>external/mcp/:tool</code>.
+Tools that read local files, connect to services, or run commands are only available from the CLI.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/mcp</code></div>
+<p>Handle one JSON-RPC 2.0 message (<code>initialize</code>, <code>ping</code>, <code>tools/list</code>, <code>tools/call</code>) and return its response.
+Notifications are answered with <code>202</code> and no body.</p>
+<pre><code>{"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+ "params": {"name": "zvault_list_secrets", "arguments": {"path": "env/myapp"}}}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/mcp</code></div>
+<p>Open a Server-Sent Events session. The first event is <code>endpoint</code>, whose data is the URL to post messages to;
+responses arrive as <code>message</code> events. At most 16 sessions per token; the stream ends when the token is revoked
+or expires.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/mcp/messages?session_id=:id</code></div>
+<p>Send a JSON-RPC message to a session and get <code>202</code>. Only the token that opened the session may post to it.</p>
+
+<h2>Notifications</h2>
+<p>Webhooks receive vault events matching their topic patterns (same syntax as event subscriptions). Slack and Discord
+webhooks get a one-line message; generic webhooks get the event JSON. PagerDuty webhooks send Events API v2 alerts:
+<code>sys.sealed</code> and <code>sys.unseal_failed</code> trigger a <code>critical</code> incident that the next
+<code>sys.unsealed</code> resolves, and other topics open an <code>info</code> alert each. In Slack those two events mention
+<code>@channel</code>. Webhooks are kept in memory, so seal events still go out while the vault is sealed; a delivery that
+fails then is logged but cannot be queued. Every delivery carries <code>X-ZVault-Event</code>,
+<code>X-ZVault-Delivery</code>, and <code>X-ZVault-Timestamp</code>; webhooks with a <code>secret</code> also get
+<code>X-ZVault-Signature: sha256=&lt;hex&gt;</code>, an HMAC-SHA256 of <code>{timestamp}.{body}</code>. Failed deliveries
+are retried with exponential backoff (30s doubling, up to 1h) and dropped after 8 attempts.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/notifications/webhooks/:name</code></div>
+<p>Create or replace a webhook. <code>format</code> is <code>generic</code>, <code>slack</code>, <code>discord</code>, or
+<code>pagerduty</code>, and is detected from the URL when omitted. Empty <code>events</code> means every topic. Omitting
+<code>secret</code> keeps the current one; <code>""</code> removes it. <code>pagerduty</code> webhooks need the integration's
+<code>routing_key</code>, which is set and removed the same way. Secrets and routing keys are never returned — responses carry
+<code>signed</code> and <code>has_routing_key</code> instead.
+<code>GET</code> reads a webhook, <code>DELETE</code> removes it and its queued deliveries.</p>
+<pre><code>Request: {"url": "https://hooks.slack.com/services/…", "events": ["kv.*", "lease.expired"], "secret": "…"}
+Request: {"url": "https://events.pagerduty.com/v2/enqueue", "events": ["sys.*"], "routing_key": "…"}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/notifications/webhooks</code></div>
+<p>List webhooks.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/notifications/webhooks/:name/test</code></div>
+<p>Send a <code>notify.test</code> event right away, ignoring the webhook's filter. Returns <code>400</code> with the reason
+if the endpoint does not accept it.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/notifications/queue</code></div>
+<p>Deliveries waiting to be retried, with their attempt count, next attempt time, and last error.</p>
+
+<h2>Secret Rotation</h2>
+<p>Rotation policies rotate one field of a KV secret on an interval or a cron schedule (five fields, UTC). Each rotation
+writes a new version with only that field changed, using check-and-set, and publishes <code>kv.write</code> and
+<code>kv.rotate</code>. Rotators: <code>random</code> (<code>length</code> 8–1024, <code>charset</code>
+<code>alphanumeric</code>, <code>hex</code>, or <code>ascii</code>), <code>database</code> (rotates a static role's password
+through the database engine at <code>mount</code>), and <code>webhook</code> (POSTs <code>{mount, path, field, version}</code>
+to <code>url</code>, signed like notifications when <code>secret</code> is set, and stores the <code>value</code> it returns).
+A failed rotation leaves the secret unchanged, is recorded in <code>last_error</code>, and is retried with backoff.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/rotation/policies/:mount/*path</code></div>
+<p>Create or replace the policy for a secret, e.g. <code>secret/app/db</code>. Needs <code>update</code> on
+<code>sys/rotation/policies/…</code> and <code>create</code> on the secret's <code>data/</code> path. Give exactly one of
+<code>interval</code> (e.g. <code>24h</code>), <code>cron</code>, or <code>schedule</code>. <code>GET</code> reads it with
+its status; <code>DELETE</code> removes it and keeps the secret.</p>
+<pre><code>Request: {"field": "password", "rotator": {"type": "random", "length": 40}, "cron": "0 3 * * *"}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/rotation/policies</code></div>
+<p>List policies with <code>next_rotation</code>, <code>last_rotated</code>, <code>last_version</code>, and <code>last_error</code>.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/rotation/rotate/:mount/*path</code></div>
+<p>Rotate now and return the new <code>version</code>. Returns <code>400</code> with the reason if the rotator fails.</p>
+
+<h2>Secret Sync</h2>
+<p>GitHub syncs push KV secrets to a repository's Actions secrets, or to one of its deployment environments. A mapping
+names a secret and a <code>field</code>, pushed as <code>name</code> (default: the field upper-cased), or omits the field
+to push every field with <code>name</code> as a prefix. Values are encrypted with the repository's public key before they
+leave the server. Syncs covering a secret run when it is written; a failed sync is recorded in <code>last_error</code> and
+retried with backoff. GitHub never returns secret values, so drift is judged from what was pushed: a secret changed on
+GitHub after the last push is <code>modified</code>, and is pushed again on the next run.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/sync/github/:name</code></div>
+<p>Create or replace a sync. Needs <code>update</code> on <code>sys/sync/github/…</code> and <code>read</code> on each
+mapped secret's <code>data/</code> path. The token needs write access to the repository's secrets; it is never returned,
+and omitting it on update keeps the current one. <code>GET</code> reads a sync with the secrets pushed so far;
+<code>DELETE</code> removes it and leaves the secrets on GitHub.</p>
+<pre><code>Request: {"repository": "acme/api", "environment": "production", "token": "ghp_…",
+          "mappings": [{"mount": "secret/", "path": "ci/deploy", "field": "token", "name": "DEPLOY_TOKEN"}]}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/sync/github</code></div>
+<p>List syncs with <code>last_synced_at</code>, <code>last_error</code>, and <code>next_retry_at</code>.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/sync/github/:name/run</code></div>
+<p>Push secrets that are not in sync now, or all of them with <code>{"force": true}</code>. Returns the
+<code>pushed</code> names and the <code>unchanged</code> count, or <code>400</code> with GitHub's reason.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/sync/github/:name/drift</code></div>
+<p>Each GitHub secret's status: <code>in_sync</code>, <code>pending</code> (changed in the vault), <code>missing</code>
+(not on GitHub), <code>modified</code> (changed on GitHub), or <code>source_missing</code> (gone from the vault).</p>
+
+<p>Kubernetes syncs keep one Secret in line with the mapped KV secrets. Fields keep their names as Secret keys unless a
+mapping renames them. The Secret is written with server-side apply under the <code>zvault</code> field manager, so keys
+that leave the mappings are removed and labels or annotations set by other tools are kept. Each write sets
+<code>zvault.io/secret-hash</code> (SHA-256 of the data) and <code>zvault.io/source-versions</code> (the KV versions
+applied); copy the hash into a pod template annotation, or point a reloader at the Secret, to roll workloads when it
+changes. A Secret that already exists without this sync's <code>zvault.io/sync</code> annotation is never
+overwritten. The server uses its service account in the cluster it runs in, unless the sync sets
+<code>api_url</code> with a <code>token</code> and, for a private CA, a PEM <code>ca_cert</code>.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/sync/kubernetes/:name</code></div>
+<p>Create or replace a sync. Needs <code>update</code> on <code>sys/sync/kubernetes/…</code> and <code>read</code> on each
+mapped secret's <code>data/</code> path. The service account, or <code>token</code>, needs <code>get</code> and
+<code>patch</code> on Secrets in the namespace. The token is never returned; omitting it or <code>ca_cert</code> on update
+keeps the current one. <code>GET</code> reads a sync with the keys applied so far and <code>secret_hash</code>;
+<code>DELETE</code> removes it and leaves the Secret in the cluster. <code>GET /v1/sys/sync/kubernetes</code> lists them.</p>
+<pre><code>Request: {"namespace": "prod", "secret_name": "api-env", "labels": {"app": "api"},
+          "mappings": [{"mount": "secret/", "path": "prod/api"}, {"mount": "secret/", "path": "prod/db", "field": "url", "name": "DATABASE_URL"}]}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/sync/kubernetes/:name/run</code></div>
+<p>Apply the Secret now if any key differs from the vault, or always with <code>{"force": true}</code>. Returns the
+changed keys as <code>pushed</code> and the <code>unchanged</code> count, or <code>400</code> with the API server's reason.</p>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/sync/kubernetes/:name/drift</code></div>
+<p>Each key's status, compared with the Secret's live data: <code>in_sync</code>, <code>pending</code>,
+<code>missing</code>, <code>modified</code> (edited in the cluster), or <code>source_missing</code>.</p>
+
+<h2>AWS Migration</h2>
+<p>Bulk copies between a KV mount and AWS Secrets Manager or SSM Parameter Store (<code>"store": "secrets-manager"</code>
+or <code>"parameter-store"</code>), keeping the hierarchy under a name prefix. A Secrets Manager secret becomes one KV
+secret: a JSON object value is split into fields, any other value is stored as <code>value</code>. SSM parameters
+become fields of the KV secret at their parent path, so <code>/prod/api/db/user</code> under <code>/prod</code> is the
+<code>user</code> field of <code>api/db</code>. Characters KV paths do not allow become <code>_</code>. AWS tags and KV
+custom metadata are copied both ways. Each target is reported as <code>create</code>, <code>update</code>,
+<code>unchanged</code>, <code>skip</code>, or <code>failed</code> with a <code>reason</code>. A target that exists with a
+different value is a conflict: <code>"conflict": "skip"</code> (default) leaves it, <code>"overwrite"</code> replaces
+it, and <code>"fail"</code> returns <code>409</code> before anything is written. <code>"dry_run": true</code> returns
+the plan without writing. Requests are signed with the <code>credentials</code> given, or the server's
+<code>AWS_*</code> environment; <code>ZVAULT_AWS_SECRETSMANAGER_ENDPOINT</code> and <code>ZVAULT_AWS_SSM_ENDPOINT</code>
+override the endpoints.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/migrate/aws/import</code></div>
+<p>Copy the AWS secrets under <code>prefix</code> into <code>path</code> of <code>mount</code> (default
+<code>secret/</code>). Needs <code>update</code> on <code>sys/migrate/aws/import</code>, <code>create</code> on each
+written secret's <code>data/</code> path, and <code>update</code> on the <code>metadata/</code> path of each secret
+that gets tags. The credentials need <code>secretsmanager:ListSecrets</code> and <code>GetSecretValue</code>, or
+<code>ssm:GetParametersByPath</code> and <code>ListTagsForResource</code>.</p>
+<pre><code>Request:  {"store": "secrets-manager", "region": "us-east-1", "prefix": "prod/", "path": "imported",
+           "credentials": {"access_key_id": "AKIA…", "secret_access_key": "…"}, "conflict": "skip", "dry_run": true}
+Response: {"store": "secrets-manager", "dry_run": true,
+           "entries": [{"kv_path": "imported/api/db", "aws_name": "prod/api/db", "action": "create"}]}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/migrate/aws/export</code></div>
+<p>Copy the KV secrets under <code>path</code> to AWS under <code>prefix</code>: one Secrets Manager secret per KV
+secret, holding its fields as JSON, or one <code>SecureString</code> parameter per field. <code>kms_key_id</code>
+encrypts new secrets and parameters with that key. Needs <code>update</code> on <code>sys/migrate/aws/export</code>
+and <code>read</code> on each exported secret's <code>data/</code> path.</p>
+
+<h2>MFA</h2>
+<p>TOTP multi-factor authentication. Codes are sent in the <code>X-Vault-MFA</code> header as <code>method:code</code>,
+comma separated for several methods. Each code is accepted once. An identity is a token's entity ID, or its display
+name (e.g. <code>approle-ci</code>) when it has none. Codes are required at login by login enforcements, and on
+individual requests by policy rules with <code>mfa_methods</code>; a missing or wrong code returns
+<code>403 mfa_required</code>.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mfa/method/totp/:name</code></div>
+<p>Create or update a TOTP method. <code>digits</code> is 6 or 8, <code>period_secs</code> 15–300, <code>skew</code> (extra
+periods accepted either side) 0–2, and <code>algorithm</code> <code>sha1</code>, <code>sha256</code>, or <code>sha512</code>.
+<code>GET</code> reads a method; <code>DELETE</code> removes it and every enrollment, unless a login enforcement uses it.</p>
+<pre><code>Request: {"issuer": "ZVault", "period_secs": 30, "digits": 6, "algorithm": "sha1", "skew": 1}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mfa/method/totp/:name/generate</code></div>
+<p>Enroll the calling token's identity. Returns the base32 <code>secret</code> and an <code>otpauth://</code> <code>url</code> for
+authenticator apps. Fails with <code>409</code> if the identity is already enrolled.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mfa/method/totp/:name/admin-generate</code></div>
+<p>Enroll another identity, such as an AppRole or JWT login, before its logins require MFA.
+<code>admin-destroy</code> takes the same body and removes the identity's secret.</p>
+<pre><code>Request: {"identity": "approle-ci"}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/mfa/login-enforcement/:name</code></div>
+<p>Require MFA methods on logins through auth methods (<code>approle</code>, <code>jwt</code>, <code>cert</code>, <code>cloud</code>). A login without valid codes
+returns <code>403</code> and the token it would have issued is revoked. <code>GET</code> and <code>DELETE</code> manage an
+enforcement; <code>GET /v1/sys/mfa/login-enforcement</code> lists them.</p>
+<pre><code>Request: {"mfa_methods": ["totp"], "auth_methods": ["approle"]}</code></pre>
+
+<h2>Identity</h2>
+<p>An entity is one client however it logs in. Aliases map a login — an <code>approle</code> role name, a <code>jwt</code>
+user claim, an <code>oidc</code> subject, a <code>cert</code> common name — to an entity, and every login token carries its entity's ID
+(<code>entity_id</code> in the login response). Each request gets the policies of the token, its entity, and every group
+containing the entity, directly or through nested groups. A login through an alias no one created gets a fresh entity.
+Tokens of a disabled entity are refused with <code>403</code>. Policy paths are <code>sys/identity/entity/id/:id</code>,
+<code>sys/identity/entity-alias/id/:id</code>, and <code>sys/identity/group/id/:id</code>.</p>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/identity/entity</code></div>
+<p>Create an entity. <code>POST</code> on <code>/v1/sys/identity/entity/id/:id</code> updates one (omitted fields are kept),
+<code>GET</code> reads it with its <code>aliases</code> and <code>group_ids</code>, and <code>DELETE</code> removes it and its aliases.
+<code>GET /v1/sys/identity/entity/name/:name</code> reads by name.</p>
+<pre><code>Request: {"name": "alice", "policies": ["dev"], "metadata": {"team": "payments"}, "disabled": false}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/identity/entity-alias</code></div>
+<p>Attach an alias to an entity. An alias belongs to one entity; <code>409</code> if it is taken.
+<code>GET</code> and <code>DELETE</code> on <code>/v1/sys/identity/entity-alias/id/:id</code> read and remove one.</p>
+<pre><code>Request: {"entity_id": "…", "auth_method": "jwt", "name": "alice@example.com"}</code></pre>
+
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/sys/identity/group</code></div>
+<p>Create a group. Internal groups list <code>member_entity_ids</code> and <code>member_group_ids</code> (cycles are rejected).
+External groups (<code>"type": "external"</code>) mirror a provider group through their <code>alias</code>; an entity is a member while its
+latest login through that auth method presented the group — a JWT role's <code>groups_claim</code>, or OIDC roles.
+<code>/v1/sys/identity/group/id/:id</code> and <code>/v1/sys/identity/group/name/:name</code> work like their entity counterparts.</p>
+<pre><code>Request: {"name": "ops", "type": "external", "policies": ["ops"],
+          "alias": {"auth_method": "jwt", "name": "platform-ops"}}</code></pre>
+
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/sys/identity/lookup/self</code></div>
+<p>The calling token's <code>entity_id</code> and effective <code>policies</code>. Needs no policy.</p>
 "#;
 
 /// CLI reference documentation.
@@ -566,6 +1230,9 @@ over HTTP. It has no internal dependencies on the server crates.</p>
   <tbody>
     <tr><td><code>--addr</code></td><td><code>VAULT_ADDR</code></td><td>Server address (default: <code>http://127.0.0.1:8200</code>)</td></tr>
     <tr><td><code>--token</code></td><td><code>VAULT_TOKEN</code></td><td>Authentication token</td></tr>
+    <tr><td><code>--mfa</code></td><td><code>VAULT_MFA</code></td><td>MFA codes sent as <code>X-Vault-MFA</code>, e.g. <code>totp:123456</code></td></tr>
+    <tr><td><code>--ca-cert</code></td><td><code>VAULT_CACERT</code></td><td>PEM CA certificate to trust for HTTPS, e.g. a bootstrapped server CA</td></tr>
+    <tr><td><code>--client-cert</code>, <code>--client-key</code></td><td><code>VAULT_CLIENT_CERT</code>, <code>VAULT_CLIENT_KEY</code></td><td>PEM client certificate and key presented over HTTPS, for <code>cert</code> auth</td></tr>
   </tbody>
 </table>
 
@@ -591,15 +1258,40 @@ over HTTP. It has no internal dependencies on the server crates.</p>
 <h3><code>zvault-cli seal</code></h3>
 <p>Seal the vault. Requires authentication.</p>
 
+<h3><code>zvault-cli rekey --shares 7 --threshold 4</code></h3>
+<p>Replace the unseal shares. Prompts for current shares one at a time (or reads them from stdin, one per line) until the
+current threshold is met, then prints the new shares. Re-running resumes a rekey in progress; <code>--cancel</code>
+abandons it. Requires a token with <code>sudo</code> on <code>sys/rekey/*</code>.</p>
+
+<h3><code>zvault-cli generate-root</code></h3>
+<p>Mint a new root token. Prints the attempt's one-time pad, prompts for current shares (or reads them from stdin) until
+the threshold is met, then prints the decoded token. <code>--otp</code> resumes an attempt started elsewhere,
+<code>--decode &lt;encoded&gt; --otp &lt;pad&gt;</code> decodes a token offline, and <code>--cancel</code> abandons
+the attempt. Resuming with <code>--otp</code> and <code>--cancel</code> need a token with <code>sudo</code> on
+<code>sys/generate-root/attempt</code>; without a token the command starts a new attempt.</p>
+
+<h3><code>zvault-cli mount export &lt;path&gt; -o bundle.json</code></h3>
+<p>Write a KV mount's data, encrypted under a transfer key, to a file. Pass <code>--transfer-key</code> (or <code>ZVAULT_TRANSFER_KEY</code>) to use your own key; otherwise one is generated and printed.</p>
+
+<h3><code>zvault-cli mount import &lt;path&gt; -f bundle.json --transfer-key &lt;key&gt;</code></h3>
+<p>Recreate the exported mount at a new, empty path on this vault.</p>
+
 <h2>KV Commands</h2>
 
 <h3><code>zvault-cli kv get &lt;path&gt;</code></h3>
-<p>Read a secret at the given path.</p>
-<pre><code>zvault-cli kv get secret/myapp/db</code></pre>
+<p>Read a secret at the given path. Every <code>kv</code> command takes <code>--mount &lt;path&gt;</code> to work on a KV mount other than <code>secret/</code>.</p>
+<pre><code>zvault-cli kv get secret/myapp/db
+zvault-cli kv get myapp/db --mount team-a</code></pre>
 
 <h3><code>zvault-cli kv put &lt;path&gt; [key=value ...]</code></h3>
-<p>Write key-value pairs to a secret path.</p>
-<pre><code>zvault-cli kv put secret/myapp/db username=admin password=s3cret</code></pre>
+<p>Write key-value pairs to a secret path. <code>--cas &lt;version&gt;</code> only writes if that is still the current version (<code>0</code>: the secret must not exist yet).</p>
+<pre><code>zvault-cli kv put secret/myapp/db username=admin password=s3cret
+zvault-cli kv put myapp/db password=rotated --cas 4</code></pre>
+
+<h3><code>zvault-cli kv metadata get|put &lt;path&gt;</code></h3>
+<p>Show or edit a secret's custom metadata and write settings. <code>--custom key=value</code> (repeatable) replaces the tags, <code>--clear-custom</code> removes them, <code>--max-versions</code> / <code>--cas-required true|false</code> change the limits, and <code>--delete-version-after 90d</code> purges versions once they reach that age (<code>0</code> turns it off).</p>
+<pre><code>zvault-cli kv metadata put myapp/db --custom owner=alice --custom team=payments --cas-required true
+zvault-cli kv metadata get myapp/db</code></pre>
 
 <h3><code>zvault-cli kv delete &lt;path&gt;</code></h3>
 <p>Soft-delete a secret (recoverable).</p>
@@ -608,6 +1300,76 @@ over HTTP. It has no internal dependencies on the server crates.</p>
 <p>List secrets under a prefix.</p>
 <pre><code>zvault-cli kv list secret/myapp/</code></pre>
 
+<h3><code>zvault-cli kv stats [prefix]</code></h3>
+<p>Show read counts and distinct readers per secret. <code>--unread</code> lists only secrets nobody has read; <code>--sort reads|readers</code> puts the busiest or most widely shared first.</p>
+<pre><code>zvault-cli kv stats myapp/ --sort readers</code></pre>
+
+<h2>Terraform Output</h2>
+
+<h3><code>zvault-cli tf-output &lt;prefix&gt;</code></h3>
+<p>Print every field of every secret under a prefix as one flat JSON object of strings, the format Terraform's <code>external</code> data source reads. Names join the secret path and field (<code>db/main</code> + <code>password</code> → <code>db_main_password</code>); <code>--separator</code>, <code>--case upper|lower</code>, and <code>--field-only</code> change them. <code>--key</code> keeps only matching <code>&lt;path&gt;/&lt;field&gt;</code> keys (<code>*</code> matches anything; repeatable). <code>--hcl</code> prints <code>name = "value"</code> lines for a <code>.tfvars</code> file instead. Without a prefix, Terraform's <code>query</code> is read from stdin: <code>prefix</code>, and <code>key</code> as comma-separated patterns. <code>tf</code> is a short alias.</p>
+<pre><code>zvault-cli tf-output myapp/ --key 'db/*'
+zvault-cli tf-output myapp/ --hcl &gt; secrets.auto.tfvars
+
+data "external" "db" {
+  program = ["zvault-cli", "tf"]
+  query   = { prefix = "myapp/", key = "db/*" }
+}
+# data.external.db.result.db_main_password</code></pre>
+
+<h2>Docker Credential Helper</h2>
+
+<h3><code>zvault-cli docker-credential-helper get | store | erase | list</code></h3>
+<p>Implements the Docker credential helper protocol, so <code>docker login</code> saves registry credentials in the vault and <code>docker pull</code> reads them from there instead of <code>~/.docker/config.json</code>. Docker runs it as <code>docker-credential-zvault</code>: link that name to the CLI and set <code>credsStore</code> (or <code>credHelpers</code> for some registries). Each registry is a secret under <code>secret/docker/</code> named after its host, e.g. <code>secret/docker/ghcr_io</code>; <code>ZVAULT_DOCKER_PREFIX</code> picks another folder. The helper uses <code>VAULT_ADDR</code> and <code>VAULT_TOKEN</code> from Docker's environment. <code>dch</code> is a short alias.</p>
+<pre><code>ln -s "$(command -v zvault-cli)" /usr/local/bin/docker-credential-zvault
+# in ~/.docker/config.json: "credsStore": "zvault"
+docker login ghcr.io
+echo ghcr.io | zvault-cli dch get</code></pre>
+
+<h2>AWS Migration</h2>
+
+<h3><code>zvault-cli import-aws &lt;prefix&gt;</code> / <code>zvault-cli export-aws &lt;path&gt;</code></h3>
+<p>Move secrets from AWS Secrets Manager or SSM Parameter Store (<code>--store parameter-store</code>) into the vault,
+or back. <code>import-aws</code> copies everything under an AWS name prefix into <code>--path</code>;
+<code>export-aws</code> copies a KV subtree under <code>--prefix</code>, with <code>--kms-key-id</code> for new
+secrets. <code>--conflict skip|overwrite|fail</code> decides what happens to targets that already hold a different
+value, and <code>--dry-run</code> prints the plan. The server does the copying with the <code>AWS_ACCESS_KEY_ID</code>,
+<code>AWS_SECRET_ACCESS_KEY</code>, <code>AWS_SESSION_TOKEN</code>, and <code>AWS_REGION</code> of the CLI's environment,
+or its own with <code>--server-credentials</code>.</p>
+<pre><code>zvault-cli import-aws prod/ --path imported --dry-run
+zvault-cli import-aws /prod/api --store parameter-store --path api --conflict overwrite
+zvault-cli export-aws myapp/ --prefix prod/myapp --conflict fail</code></pre>
+
+<h2>HashiCorp Vault Migration</h2>
+
+<h3><code>zvault-cli migrate-vault --addr &lt;url&gt; --token &lt;token&gt;</code></h3>
+<p>Copy a HashiCorp Vault KV v2 mount (<code>--mount</code>, default <code>secret</code>; <code>--path</code> for a subtree)
+into <code>secret/</code>, and with <code>--policies</code> and <code>--approles</code> its ACL policies and AppRole roles.
+The latest version of each secret is copied with its custom metadata, <code>max_versions</code>, <code>cas_required</code>,
+and <code>delete_version_after</code>. <code>--map from=to</code> (repeatable, longest prefix wins) moves paths, in
+secrets and in the translated policies alike. Policy rules ZVault cannot enforce the same way (parameter constraints,
+wrapping TTLs, control groups) and roles bound to CIDRs or use counts are left out rather than loosened; the report
+lists them with everything else that did not carry over, such as version history and secret IDs. Migrated roles get
+new role IDs. <code>--namespace</code> reads from a Vault Enterprise namespace, <code>--conflict skip|overwrite|fail</code>
+and <code>--dry-run</code> work as for AWS, and <code>ZVAULT_MIGRATE_VAULT_ADDR</code> /
+<code>ZVAULT_MIGRATE_VAULT_TOKEN</code> can stand in for the flags.</p>
+<pre><code>zvault-cli migrate-vault --addr https://vault.example.com:8200 --token "$VAULT_SOURCE_TOKEN" --dry-run
+zvault-cli migrate-vault --addr https://vault.example.com:8200 --token "$VAULT_SOURCE_TOKEN" \
+  --map apps/legacy=legacy --policies --approles</code></pre>
+
+<h2>Password Manager Import</h2>
+
+<h3><code>zvault-cli import-passwords &lt;file&gt;</code></h3>
+<p>Import a 1Password export (<code>.1pux</code>) or an unencrypted Bitwarden JSON export. Each item becomes one secret
+at <code>&lt;prefix&gt;/&lt;vault or folder&gt;/&lt;item&gt;</code> (<code>--prefix</code>, default <code>imported</code>)
+whose keys are its username, password, one-time password seed, URLs, notes, and custom fields. On a terminal you are
+asked where each vault or folder should go, and <code>-</code> leaves it out; <code>--map Work=team/work</code> answers
+ahead of time and <code>--yes</code> accepts every default. <code>--dry-run</code> lists the secrets and their keys but
+never the values. Existing secrets are only replaced with <code>--overwrite</code>. Attachments, passkeys, linked fields,
+and archived 1Password items (unless <code>--include-archived</code>) are listed as not imported.</p>
+<pre><code>zvault-cli import-passwords 1PasswordExport.1pux --dry-run
+zvault-cli import-passwords bitwarden_export.json --prefix team --map "Work/AWS=cloud/aws" --yes</code></pre>
+
 <h2>Transit Commands</h2>
 
 <h3><code>zvault-cli transit create-key &lt;name&gt;</code></h3>
@@ -621,18 +1383,79 @@ over HTTP. It has no internal dependencies on the server crates.</p>
 <h3><code>zvault-cli transit decrypt &lt;key&gt;</code></h3>
 <p>Decrypt ciphertext with a named key.</p>
 
+<h3><code>zvault-cli transit tokenize &lt;key&gt; &lt;b64&gt;</code></h3>
+<p>Replace a value with a token. <code>--convergent</code> reuses the token of an equal value; <code>--metadata k=v</code> is repeatable.
+<code>detokenize &lt;key&gt; &lt;token&gt;</code> reverses it, and <code>lookup-token &lt;key&gt; --token|--plaintext</code> describes a token or finds a convergent one.</p>
+<pre><code>zvault-cli transit tokenize pii MTIzLTQ1LTY3ODk= --convergent --metadata field=ssn</code></pre>
+
 <h2>Token Commands</h2>
 
 <h3><code>zvault-cli token create</code></h3>
 <p>Create a new token.</p>
-<pre><code>zvault-cli token create --policies app-readonly --ttl 1h</code></pre>
+<pre><code>zvault-cli token create --policies app-readonly --ttl 1h --bound-cidrs 10.0.0.0/8</code></pre>
+<p><code>--batch</code> creates a batch token, which is not stored and ends with its TTL or its parent.</p>
 
 <h3><code>zvault-cli token lookup</code></h3>
 <p>Look up the current token's metadata.</p>
 
+<h3><code>zvault-cli token accessors</code></h3>
+<p>List the accessors of all stored tokens. <code>token lookup-accessor &lt;accessor&gt;</code> shows a token's metadata and <code>token revoke-accessor &lt;accessor&gt;</code> revokes it and its children, without the token value.</p>
+
 <h3><code>zvault-cli token revoke &lt;token&gt;</code></h3>
 <p>Revoke a token and all its children.</p>
 
+<h2>JWT Commands</h2>
+
+<h3><code>zvault-cli jwt config</code></h3>
+<p>Configure the key source with <code>--jwks-url</code> or one or more <code>--pubkey-file</code>, and an optional <code>--bound-issuer</code>.</p>
+
+<h3><code>zvault-cli jwt create-role &lt;name&gt;</code></h3>
+<p>Create a role. <code>--bound-claim claim=value</code> is repeatable; <code>--glob</code> matches values as globs.</p>
+<pre><code>zvault-cli jwt create-role deploy --policies deploy \
+  --bound-audiences https://github.com/acme \
+  --bound-claim repository=acme/api --bound-claim ref=refs/heads/main</code></pre>
+
+<h3><code>zvault-cli jwt login --role &lt;name&gt;</code></h3>
+<p>Exchange the JWT in <code>--jwt</code> (or <code>ZVAULT_JWT</code>) for a token. <code>read-role</code>, <code>list-roles</code>, and <code>delete-role</code> manage roles.</p>
+
+<h2>Cert Commands</h2>
+
+<h3><code>zvault-cli cert create-role &lt;name&gt; --ca-file &lt;pem&gt;</code></h3>
+<p>Create a role trusting the CA certificates in <code>--ca-file</code>. <code>--allowed-common-names</code>, <code>--allowed-dns-sans</code>, <code>--allowed-email-sans</code>, <code>--allowed-uri-sans</code>, and <code>--allowed-ous</code> take comma-separated globs.</p>
+<pre><code>zvault-cli cert create-role web --ca-file clients-ca.pem --policies web \
+  --allowed-common-names 'web-*' --allowed-ous platform</code></pre>
+
+<h3><code>zvault-cli cert login [--role &lt;name&gt;]</code></h3>
+<p>Exchange the certificate given with the global <code>--client-cert</code> and <code>--client-key</code> for a token. <code>read-role</code>, <code>list-roles</code>, and <code>delete-role</code> manage roles.</p>
+<pre><code>zvault-cli --addr https://vault:8200 --client-cert web-1.crt --client-key web-1.key cert login</code></pre>
+
+<h2>Identity Commands</h2>
+
+<h3><code>zvault-cli identity create-entity &lt;name&gt; --policies dev</code></h3>
+<p>Create an entity. <code>read-entity</code>, <code>list-entities</code>, <code>disable-entity [--enable]</code>, and
+<code>delete-entity</code> manage entities by name.</p>
+
+<h3><code>zvault-cli identity add-alias &lt;entity&gt; --auth-method jwt --name &lt;user&gt;</code></h3>
+<p>Map an auth method login to the entity. <code>delete-alias &lt;id&gt;</code> removes one.</p>
+
+<h3><code>zvault-cli identity create-group &lt;name&gt;</code></h3>
+<p>Create an internal group with <code>--member-entities</code> and <code>--member-groups</code>, or an external one with
+<code>--external jwt:platform-ops</code>. <code>read-group</code>, <code>list-groups</code>, and <code>delete-group</code> manage groups;
+<code>whoami</code> shows the calling token's entity and effective policies.</p>
+
+<h2>Wrapping Commands</h2>
+
+<h3><code>zvault-cli approle secret-id &lt;name&gt; --wrap-ttl 5m</code></h3>
+<p>Generate a secret ID wrapped in a single-use token, safe to pass through CI or a deploy tool.</p>
+
+<h3><code>zvault-cli wrapping unwrap &lt;token&gt;</code></h3>
+<p>Print the wrapped response. <code>wrapping lookup</code> shows where the token came from; <code>wrapping rewrap</code> swaps it for a fresh one.</p>
+
+<h2>Cubbyhole Commands</h2>
+
+<h3><code>zvault-cli cubbyhole put &lt;path&gt; key=value ...</code></h3>
+<p>Write an entry to the current token's cubbyhole. <code>get</code>, <code>delete</code>, and <code>list [prefix]</code> work like their <code>kv</code> counterparts; everything disappears with the token.</p>
+
 <h2>Policy Commands</h2>
 
 <h3><code>zvault-cli policy list</code></h3>
@@ -647,6 +1470,106 @@ over HTTP. It has no internal dependencies on the server crates.</p>
 
 <h3><code>zvault-cli policy delete &lt;name&gt;</code></h3>
 <p>Delete a policy.</p>
+
+<h2>Event Commands</h2>
+
+<h3><code>zvault-cli events subscribe &lt;pattern&gt;</code></h3>
+<p>Print vault events matching the pattern as they happen, until the server ends the stream. <code>--json</code> prints each event as a JSON line.</p>
+<pre><code>zvault-cli events subscribe 'kv/*'
+zvault-cli events subscribe '*' --json</code></pre>
+
+<h2>Notification Commands</h2>
+
+<h3><code>zvault-cli notify set-webhook &lt;url&gt;</code></h3>
+<p>Create or update a server-side webhook. <code>--name</code> defaults to <code>default</code>, <code>--events</code> takes comma-separated topic patterns (default <code>kv.*,lease.expired</code>), <code>--format</code> overrides URL detection, <code>--secret</code> signs deliveries, and <code>--disabled</code> pauses it.</p>
+<pre><code>zvault-cli notify set-webhook https://hooks.slack.com/services/… --events 'kv.*,policy.*'
+zvault-cli notify test</code></pre>
+
+<h3><code>zvault-cli notify get-webhook | remove-webhook | test | queue</code></h3>
+<p>List webhooks (or one with <code>--name</code>), remove one, send it a test event, or show deliveries waiting to be retried.</p>
+
+<h2>Rotation Commands</h2>
+
+<h3><code>zvault-cli rotate set-policy &lt;mount/path&gt;</code></h3>
+<p>Create or replace a server-side rotation policy. <code>--field</code> defaults to <code>value</code>; <code>--rotator</code> is <code>random</code> (<code>--length</code>, <code>--charset</code>), <code>database</code> (<code>--role</code>, <code>--db-mount</code>), or <code>webhook</code> (<code>--url</code>, <code>--secret</code>). Schedule with <code>--interval</code> or <code>--cron</code>.</p>
+<pre><code>zvault-cli rotate set-policy secret/app/db --field password --interval 30d
+zvault-cli rotate set-policy secret/app/pg --rotator database --role app --cron '0 3 * * 0'</code></pre>
+
+<h3><code>zvault-cli rotate get-policy | list-policies | remove-policy | trigger | status</code></h3>
+<p>Show one policy, list them with their next rotation, remove one, rotate a secret now, or show the last rotation and any failure of each.</p>
+
+<h2>Sync Commands</h2>
+
+<h3><code>zvault-cli sync github set &lt;name&gt; --repo &lt;owner/repo&gt; --map &lt;secret&gt;</code></h3>
+<p>Create or replace a GitHub sync. Each <code>--map</code> is <code>&lt;mount&gt;/&lt;path&gt;[#field][=NAME]</code>; without a field every field is pushed, with <code>NAME</code> as a prefix. <code>--token</code> defaults to <code>GITHUB_TOKEN</code>; <code>--environment</code> syncs an environment's secrets and <code>--api-url</code> points at GitHub Enterprise Server.</p>
+<pre><code>zvault-cli sync github set api --repo acme/api --map secret/ci/deploy#token=DEPLOY_TOKEN
+zvault-cli sync github set api-prod --repo acme/api --environment production --map secret/prod/api=PROD_</code></pre>
+
+<h3><code>zvault-cli sync github get | remove | run | drift</code></h3>
+<p>Show syncs and their last result, remove one, push changed secrets now (<code>--force</code> pushes all), or show which secrets drifted from the vault.</p>
+
+<h3><code>zvault-cli sync kubernetes set &lt;name&gt; --namespace &lt;ns&gt; --secret &lt;name&gt; --map &lt;secret&gt;</code></h3>
+<p>Create or replace a Kubernetes sync. <code>--map</code> works as for GitHub, with <code>KEY</code> naming the Secret key; <code>--label key=value</code> labels the Secret. Without <code>--api-url</code> the server writes to the cluster it runs in; otherwise <code>--token</code> (default <code>KUBERNETES_TOKEN</code>) and <code>--ca-cert &lt;file&gt;</code> reach another cluster.</p>
+<pre><code>zvault-cli sync kubernetes set api --namespace prod --secret api-env --map secret/prod/api --label app=api
+zvault-cli sync kubernetes set api-db --namespace prod --secret api-db --map secret/prod/db#url=DATABASE_URL \
+  --api-url https://k8s.example.com:6443 --ca-cert ca.pem</code></pre>
+
+<h3><code>zvault-cli sync kubernetes get | remove | run | drift</code></h3>
+<p>Show syncs with their applied keys and hash, remove one, apply the Secret now (<code>--force</code> even when in sync), or show which keys drifted from the vault.</p>
+
+<h2>Audit Commands</h2>
+
+<h3><code>zvault-cli audit-export</code></h3>
+<p>Stream the audit log page by page. <code>--format</code> is <code>json</code>, <code>ndjson</code>, <code>csv</code>, or <code>parquet</code>; <code>--gzip</code> compresses text formats on the fly; <code>--limit</code> caps the entry count (default: everything).</p>
+<pre><code>zvault-cli audit-export --format ndjson --gzip --output audit.ndjson.gz
+zvault-cli audit-export --format parquet --output audit.parquet</code></pre>
+<p>Filter with <code>--since</code>/<code>--until</code> (RFC 3339 or a duration ago), <code>--path-prefix</code>, <code>--actor</code>, <code>--token-hash</code>, <code>--accessor</code>, <code>--operation</code>, and <code>--status</code>; <code>--count</code> prints the number of matching entries instead.</p>
+<pre><code># Who read the database password in the last month?
+zvault-cli audit-export --format csv --path-prefix secret/data/db/password --operation read --since 30d</code></pre>
+
+<h2>Raw API Access</h2>
+
+<h3><code>zvault-cli api &lt;method&gt; &lt;path&gt;</code></h3>
+<p>Call any endpoint with the CLI's address and token, for features that have no subcommand yet. The path may omit <code>/v1/</code>; <code>--data</code> takes inline JSON, <code>@file</code>, or <code>@-</code> for stdin. The response JSON goes to stdout. A failed request prints the error body (see the API reference) with its <code>status</code> to stdout and exits non-zero.</p>
+<pre><code>zvault-cli api GET sys/health
+echo '{"data": {"k": "v"}}' | zvault-cli api POST secret/data/app --data @-</code></pre>
+
+<h2>Declarative Configuration</h2>
+
+<h3><code>zvault-cli apply</code></h3>
+<p>Reconcile policies, KV mounts, AppRole roles, PKI roles, rotation policies, and Kubernetes syncs with a YAML file kept in Git. The plan (<code>+</code> create, <code>~</code> update, <code>-</code> delete) is printed before anything changes; <code>--dry-run</code> stops there.</p>
+<pre><code>version: 1
+policies:
+  ci-read:
+    rules:
+      - path: "secret/data/ci/*"
+        capabilities: [read, list]
+mounts:
+  team-a:
+    description: Team A secrets
+    config: {read_lease_ttl_secs: 300}
+approles:
+  ci:
+    policies: [ci-read]
+    token_ttl_secs: 600
+pki_roles:
+  web:
+    allowed_domains: [example.com]
+    allow_subdomains: true
+rotation_policies:
+  secret/db/password:
+    rotator: {type: random, length: 40}
+    interval: 24h
+kubernetes_syncs:
+  api:
+    namespace: prod
+    secret_name: api-env
+    mappings: [secret/prod/api, "secret/prod/db#url=DATABASE_URL"]
+    labels: {app: api}</code></pre>
+<pre><code>zvault-cli apply -f vault-config.yaml --dry-run
+zvault-cli apply -f vault-config.yaml
+zvault-cli apply -f vault-config.yaml --prune --confirm vault-config.yaml</code></pre>
+<p>Only sections present in the file are managed. <code>--prune</code> deletes resources in those sections that the file does not declare, after confirmation. Built-in policies, mounts, and PKI roles are never deleted. Updating an AppRole keeps its <code>role_id</code>. Kubernetes sync tokens are never read from the file: set them once with <code>sync kubernetes set</code>.</p>
 "#;
 
 /// Security model documentation.
@@ -697,6 +1620,7 @@ This page documents the threat model, cryptographic choices, and hardening measu
   <li>Tokens are hashed with SHA-256 before storage — plaintext tokens are never persisted</li>
   <li>Token comparison uses <code>subtle::ConstantTimeEq</code> to prevent timing attacks</li>
   <li>Failed auth attempts take the same time as successful ones</li>
+  <li>Tokens and AppRole roles with <code>bound_cidrs</code> are refused from other networks; <code>X-Forwarded-For</code> is only believed from <code>ZVAULT_TRUSTED_PROXIES</code></li>
 </ul>
 
 <h2>Audit System</h2>
@@ -704,6 +1628,7 @@ This page documents the threat model, cryptographic choices, and hardening measu
   <li>Every API request generates an audit entry <strong>before</strong> the response is sent</li>
   <li>If all audit backends fail, the request is denied (fail-closed)</li>
   <li>Sensitive fields are HMAC'd with a per-backend key</li>
+  <li>Entries record the client address, the request ID returned in <code>X-Request-Id</code>, and the token's accessor</li>
   <li>Audit log is append-only — no update or delete operations</li>
 </ul>
 
@@ -724,6 +1649,7 @@ Cache-Control: no-store</code></pre>
 <ul>
   <li>Use RocksDB or redb storage (not in-memory)</li>
   <li>Keep <code>ZVAULT_DISABLE_MLOCK=false</code> (enable memory locking)</li>
+  <li>Run <code>zvault-server --check-config</code> after every configuration change</li>
   <li>Enable audit logging to a persistent file</li>
   <li>Use scoped tokens — revoke the root token after initial setup</li>
   <li>Run as a non-root user with <code>CAP_IPC_LOCK</code> capability</li>
@@ -733,7 +1659,7 @@ Cache-Control: no-store</code></pre>
 
 /// Configuration documentation.
 const CONFIGURATION: &str = r"
-<p>ZVault is configured entirely through environment variables. No config files required.</p>
+<p>ZVault is configured through environment variables, optionally backed by a configuration file (see below).</p>
 
 <h2>Environment Variables</h2>
 <table>
@@ -777,16 +1703,141 @@ const CONFIGURATION: &str = r"
     <tr>
       <td><code>ZVAULT_LEASE_SCAN_INTERVAL</code></td>
       <td><code>60</code></td>
-      <td>Seconds between lease expiry scans.</td>
+      <td>Seconds between lease expiry scans, which also purge KV versions past <code>delete_version_after</code>.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_SECRET_USAGE_FLUSH_INTERVAL</code></td>
+      <td><code>60</code></td>
+      <td>Seconds between writes of aggregated secret read counts.</td>
     </tr>
     <tr>
       <td><code>ZVAULT_DISABLE_MLOCK</code></td>
       <td><code>false</code></td>
       <td>Skip <code>mlockall</code>. Set to <code>true</code> in containers without <code>CAP_IPC_LOCK</code>.</td>
     </tr>
+    <tr>
+      <td><code>ZVAULT_DEV</code></td>
+      <td><code>false</code></td>
+      <td>Dev mode, like <code>--dev</code>: in-memory storage without TLS or mlock, initialized with one unseal share, unsealed, and the root token printed.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_SEAL</code></td>
+      <td><code>shamir</code></td>
+      <td>Seal type. <code>devkms</code> auto-unseals with a local key file (development only).</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_DEV_KMS_KEY</code></td>
+      <td><code>&lt;storage path&gt;/dev-kms.key</code></td>
+      <td>Dev KMS key file. Generated with mode <code>0600</code> if missing.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACCESS_REQUEST_WEBHOOK</code></td>
+      <td>—</td>
+      <td>Slack-compatible incoming webhook notified when access requests are created, decided, revoked, or expire.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_TLS_CERT</code> / <code>ZVAULT_TLS_KEY</code></td>
+      <td>—</td>
+      <td>PEM certificate chain and private key. When both are set, the server serves HTTPS with them and reloads them on <code>SIGHUP</code> or when the files change.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_TLS_BOOTSTRAP</code></td>
+      <td><code>false</code></td>
+      <td>Create the certificate and key on first start from a new internal CA if they do not exist.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_TLS_HOSTNAMES</code></td>
+      <td><code>localhost,127.0.0.1</code></td>
+      <td>Comma-separated DNS names and IP addresses a bootstrapped certificate covers.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_TLS_DISABLE_CLIENT_CERTS</code></td>
+      <td><code>false</code></td>
+      <td>Stop asking TLS clients for a certificate, which disables <code>cert</code> auth logins.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACME_DOMAINS</code></td>
+      <td>—</td>
+      <td>Comma-separated domains. When set, the server serves HTTPS with a certificate obtained via ACME.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACME_EMAIL</code></td>
+      <td>—</td>
+      <td>Contact email registered with the ACME account.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACME_DIRECTORY</code></td>
+      <td>Let's Encrypt production</td>
+      <td>ACME directory URL, e.g. Let's Encrypt staging for testing.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACME_CHALLENGE</code></td>
+      <td><code>http-01</code></td>
+      <td><code>http-01</code> or <code>dns-01</code>. Wildcard domains require <code>dns-01</code>.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACME_HTTP_ADDR</code></td>
+      <td><code>0.0.0.0:80</code></td>
+      <td>Plain HTTP listener for <code>http-01</code> challenges; redirects everything else to HTTPS.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACME_RENEW_BEFORE_DAYS</code></td>
+      <td><code>30</code></td>
+      <td>Renew once the certificate has fewer than this many days left.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_ACME_DNS_PROVIDER</code></td>
+      <td>—</td>
+      <td><code>cloudflare</code>, with <code>ZVAULT_ACME_CLOUDFLARE_API_TOKEN</code> and <code>ZVAULT_ACME_CLOUDFLARE_ZONE_ID</code>, for <code>dns-01</code>.</td>
+    </tr>
+    <tr>
+      <td><code>ZVAULT_CLOCK_REFERENCE_URL</code></td>
+      <td>ACME directory</td>
+      <td>HTTPS URL whose <code>Date</code> header the startup self-test measures clock skew against. Without it (or ACME) the clock check is skipped.</td>
+    </tr>
   </tbody>
 </table>
 
+<h2>Configuration File</h2>
+<p><code>zvault-server --config /etc/zvault/zvault.toml</code> (or <code>ZVAULT_CONFIG</code>) reads the same settings from a
+TOML file, HCL if the name ends in <code>.hcl</code>, or JSON if it ends in <code>.json</code>. Each key stands for one of the variables above,
+grouped by section: top-level keys drop the <code>ZVAULT_</code> prefix (<code>log_level</code>, <code>audit_file</code>,
+<code>plugin_dir</code>, …), <code>[listener]</code> has <code>address</code>, <code>tls_cert</code>, <code>tls_key</code>,
+<code>tls_hostnames</code>, and <code>trusted_proxies</code>, <code>[storage]</code> has <code>type</code>, <code>path</code>,
+<code>url</code>, and the S3 options, <code>[seal]</code> has <code>type</code> and <code>key_file</code>, and
+<code>[telemetry]</code> has <code>metrics_require_auth</code>; <code>[hsm]</code>, <code>[ha]</code>,
+<code>[replication]</code>, <code>[backup]</code>, and <code>[acme]</code> follow their variable names. A variable set in the
+environment wins over the file, and an unknown key stops the server from starting.</p>
+<pre><code>log_level = 'info'
+
+[listener]
+address = '0.0.0.0:8200'
+tls_cert = '/etc/zvault/tls/cert.pem'
+tls_key = '/etc/zvault/tls/key.pem'
+trusted_proxies = ['10.0.0.0/8']
+
+[storage]
+type = 'rocksdb'
+path = '/var/lib/zvault/data'
+
+[[audit]]
+path = 'file'
+type = 'file'
+options = { file_path = '/var/log/zvault/audit.log' }
+
+[[mount]]
+path = 'team-a'
+type = 'kv'
+description = 'Team A secrets'</code></pre>
+<p><code>[[audit]]</code> entries take the body of <code>POST /v1/sys/audit/{path}</code> and are enabled at startup without
+being stored in the vault. <code>[[mount]]</code> entries take the body of <code>POST /v1/sys/mounts/{path}</code> and are
+created on unseal if the path is not mounted yet; existing mounts are never changed or removed. In HCL, sections are
+blocks (<code>listener { address = &quot;0.0.0.0:8200&quot; }</code>) and each audit device or mount is its own
+<code>audit { ... }</code> or <code>mount { ... }</code> block.</p>
+<p>On <code>SIGHUP</code> the server re-reads the file and applies the log level (unless <code>RUST_LOG</code> is set), audit
+devices (removed ones are disabled, changed ones rebuilt), and new mounts, and reloads the TLS certificate files. Other
+settings take effect on restart. A file that fails to load is logged and the running configuration kept.</p>
+
 <h2>Priority Order</h2>
 <p>For the bind address, the priority is:</p>
 <ol>
@@ -814,6 +1865,60 @@ where cross-compilation matters. Enable with the <code>redb-backend</code> featu
 <pre><code>ZVAULT_STORAGE=redb
 ZVAULT_STORAGE_PATH=/var/lib/zvault/data</code></pre>
 
+<h2>TLS from Certificate Files</h2>
+<p>Set <code>ZVAULT_TLS_CERT</code> and <code>ZVAULT_TLS_KEY</code> to PEM files and the listener on
+<code>ZVAULT_BIND_ADDR</code> speaks TLS only. Replace the files and send <code>SIGHUP</code> (or wait
+a few seconds — the server polls their modification time) to switch certificates without dropping
+connections; a certificate that fails to load is logged and the current one kept.</p>
+<pre><code>ZVAULT_BIND_ADDR=0.0.0.0:8200
+ZVAULT_TLS_CERT=/etc/zvault/tls/server.crt
+ZVAULT_TLS_KEY=/etc/zvault/tls/server.key</code></pre>
+<p>With <code>ZVAULT_TLS_BOOTSTRAP=true</code> and no files yet, the server creates a root CA and a
+one-year certificate for <code>ZVAULT_TLS_HOSTNAMES</code> on first start. The key is written with
+mode <code>0600</code>, the CA certificate beside the certificate (<code>server.ca.pem</code> above),
+and the CA key is discarded. Point clients at the CA:</p>
+<pre><code>zvault --ca-cert /etc/zvault/tls/server.ca.pem --addr https://localhost:8200 status</code></pre>
+<p>File TLS and ACME are mutually exclusive.</p>
+
+<h2>Automatic TLS (ACME)</h2>
+<p>Set <code>ZVAULT_ACME_DOMAINS</code> and the server obtains and renews its own certificate from
+Let's Encrypt (or any ACME CA), so small installs get HTTPS without a reverse proxy. The
+listener on <code>ZVAULT_BIND_ADDR</code> then speaks TLS only.</p>
+<pre><code>ZVAULT_BIND_ADDR=0.0.0.0:443
+ZVAULT_ACME_DOMAINS=vault.example.com
+ZVAULT_ACME_EMAIL=ops@example.com</code></pre>
+<p>The ACME account key and the certificate's private key are stored through the barrier, so
+until the vault is unsealed the server presents a self-signed placeholder. Once unsealed it loads
+the stored certificate, or requests one, and checks for renewal twice a day. Failed attempts are
+retried hourly.</p>
+<p>With <code>http-01</code> (the default) the CA connects to port 80, so
+<code>ZVAULT_ACME_HTTP_ADDR</code> must be reachable from the internet. With <code>dns-01</code> the
+server publishes a TXT record through the configured DNS provider instead, which also covers
+wildcard domains and servers without a public port 80:</p>
+<pre><code>ZVAULT_ACME_DOMAINS=vault.example.com,*.vault.example.com
+ZVAULT_ACME_CHALLENGE=dns-01
+ZVAULT_ACME_DNS_PROVIDER=cloudflare
+ZVAULT_ACME_CLOUDFLARE_API_TOKEN=...
+ZVAULT_ACME_CLOUDFLARE_ZONE_ID=...</code></pre>
+
+<h2>Startup Self-Test</h2>
+<p>Before binding a listener the server checks its own setup and prints each result as
+<code>PASS</code>, <code>WARN</code>, <code>FAIL</code>, or <code>SKIP</code>. Warnings are logged;
+any failure stops startup.</p>
+<ul>
+  <li><strong>config</strong> — risky settings (in-memory storage, plain HTTP on a public address,
+  dev KMS) and environment values that would silently fall back to a default, such as
+  <code>ZVAULT_STORAGE=rockdb</code></li>
+  <li><strong>storage</strong> — writes, reads back, and deletes a probe key</li>
+  <li><strong>seal</strong> — seal status, the dev KMS key round trip, and whether auto-unseal worked</li>
+  <li><strong>hsm</strong> — the PKCS#11 module exists</li>
+  <li><strong>clock</strong> — skew against <code>ZVAULT_CLOCK_REFERENCE_URL</code>; over 5 s warns, over 60 s fails</li>
+  <li><strong>core dumps</strong>, <strong>mlock</strong> — process hardening took effect</li>
+  <li><strong>tls</strong> — the ACME certificate chain parses, matches its key, and is current</li>
+</ul>
+<p>Run the same checks without starting the server; the exit status is non-zero on failure:</p>
+<pre><code>zvault-server --check-config</code></pre>
+
 <h2>Deployment Examples</h2>
 
 <h3>Railway</h3>
@@ -902,9 +2007,47 @@ Applications send data to ZVault and get encrypted/signed results back.</p>
 </table>
 
 <h3>Key Versioning</h3>
-<p>Each named key supports multiple versions. Encryption always uses the latest version.
-Decryption tries all versions (ciphertext includes a version prefix). Old versions can
-be disabled or destroyed for key rotation.</p>
+<p>Each named key supports multiple versions. Encryption uses the latest version unless the
+key's <code>min_encryption_version</code> allows pinning an older one. The ciphertext prefix selects the version
+for decryption. To retire old versions after a rotation, rewrap stored ciphertexts and then raise
+<code>min_decryption_version</code>:</p>
+<pre><code>curl -X POST http://127.0.0.1:8200/v1/transit/rewrap/my-app-key \
+  -H "X-Vault-Token: $TOKEN" \
+  -d '{"ciphertext": "vault:v1:..."}'
+
+curl -X POST http://127.0.0.1:8200/v1/transit/keys/my-app-key/config \
+  -H "X-Vault-Token: $TOKEN" \
+  -d '{"min_decryption_version": 2}'</code></pre>
+
+<h3>HSM-Backed Keys</h3>
+<p>With <code>ZVAULT_HSM_MODULE</code> (plus <code>ZVAULT_HSM_SLOT</code> and <code>ZVAULT_HSM_PIN</code>) set, keys created with
+<code>{"backend": "pkcs11"}</code> are generated inside the HSM slot and never leave it. Encrypt and decrypt calls are
+delegated to the HSM through OpenSC's <code>pkcs11-tool</code> (0.24+); ZVault still handles versioning, rotation, and policy.
+Key info reports the backend of each key.</p>
+<pre><code>curl -X POST http://127.0.0.1:8200/v1/transit/keys/payments \
+  -H "X-Vault-Token: $TOKEN" \
+  -d '{"backend": "pkcs11"}'</code></pre>
+
+<h3>Importing and Exporting Keys</h3>
+<p>To bring your own key, fetch the engine's RSA-4096 wrapping key from <code>GET /v1/transit/wrapping_key</code>, encrypt a
+fresh AES-256 ephemeral key to it with RSA-OAEP (SHA-256), wrap your 32-byte key with the ephemeral key using AES key wrap
+with padding (RFC 5649), and send the base64 of both concatenated. Ciphertexts produced elsewhere with that key decrypt as
+version 1.</p>
+<pre><code>curl -X POST http://127.0.0.1:8200/v1/transit/keys/migrated/import \
+  -H "X-Vault-Token: $TOKEN" \
+  -d '{"ciphertext": "&lt;base64&gt;", "exportable": true}'</code></pre>
+<p>Keys created or imported with <code>"exportable": true</code> can be read back with
+<code>GET /v1/transit/export/encryption-key/{name}</code> (all versions) or <code>.../{name}/{version}</code>
+(a number or <code>latest</code>). Exportability cannot be enabled after creation, and HSM-backed keys are never exportable.</p>
+
+<h3>Tokenization</h3>
+<p>Tokenize swaps a sensitive value for an opaque <code>tok_</code> token that downstream systems can store in its
+place. Unlike a ciphertext, a token carries nothing about the value: ZVault keeps the encrypted value, so every
+detokenize goes through policy and the audit log. Tokens belong to the key that issued them and survive key rotation.
+Convergent tokens map equal values to one token, which keeps joins and deduplication working, and let
+<code>lookup</code> find a value's token; the value index is keyed by an HMAC under a per-mount secret.
+Grant <code>transit/tokenize/{name}</code>, <code>transit/detokenize/{name}</code> and
+<code>transit/lookup/{name}</code> separately, since few callers should detokenize.</p>
 
 <h3>Usage</h3>
 <pre><code># Create a key
@@ -923,14 +2066,82 @@ curl -X POST http://127.0.0.1:8200/v1/transit/decrypt/my-app-key \
   -d '{"ciphertext": "vault:v1:..."}'</code></pre>
 
 <h2>Database (Dynamic Credentials)</h2>
-<div class="callout callout-warn">Planned — not yet implemented.</div>
 <p>Generates short-lived database credentials on demand. Connects to a target database,
-creates temporary users with a TTL, and revokes them on lease expiry.</p>
+creates temporary users with a TTL, and revokes them on lease expiry. Supported plugins:
+<code>postgresql</code>, <code>mysql</code>, <code>mariadb</code>, and <code>mssql</code>
+(ADO.NET connection string). Creation statements may use <code>{{name}}</code>,
+<code>{{password}}</code>, and <code>{{expiration}}</code>. A placeholder inside quotes or brackets
+is escaped for that delimiter; an unquoted one only accepts plain tokens.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/database/config/:name</code></div>
+<p>Configure a connection: <code>plugin</code>, <code>connection_url</code>, <code>allowed_roles</code>. The URL may use
+<code>{{username}}</code> and <code>{{password}}</code> placeholders filled from the <code>username</code> and
+<code>password</code> fields, so the admin password is never part of the URL. Reads never return the password.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/database/rotate-root/:name</code></div>
+<p>Rotate the password of the user the connection authenticates as and return <code>username</code> and <code>rotated_at</code>.
+The new password is stored only in the barrier — rotate right after configuring so the bootstrap password stops working.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/database/roles/:name</code></div>
+<p>Create a dynamic role: <code>db_name</code>, <code>creation_statements</code>, <code>revocation_statements</code>, TTLs.</p>
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/database/creds/:role</code></div>
+<p>Create a database user for the role and return it with a lease.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/database/static-roles/:name</code></div>
+<p>Manage an existing user (<code>db_name</code>, <code>username</code>, optional <code>rotation_statements</code>). Its password is rotated immediately.</p>
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/database/static-creds/:name</code></div>
+<p>Read the current password of a static role.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/database/rotate-role/:name</code></div>
+<p>Rotate a static role's password now.</p>
 
 <h2>PKI (Certificate Authority)</h2>
-<div class="callout callout-warn">Planned — not yet implemented.</div>
-<p>Acts as an internal certificate authority. Generates X.509 certificates on demand
-with configurable SANs, TTL, and key usage.</p>
+<p>Acts as an internal certificate authority. Generates X.509 certificates on demand;
+roles decide which names, key types, and lifetimes a certificate may have.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/pki/roles/:name</code></div>
+<p>Create a role: <code>allowed_domains</code>, <code>allow_subdomains</code>, <code>max_ttl_hours</code>,
+<code>key_type</code> (<code>rsa</code>, <code>ec</code>, <code>ed25519</code>, or <code>any</code> to let requests choose) and
+<code>key_bits</code> (2048/3072/4096 for RSA, 256/384 for EC). <code>allow_wildcard_certificates</code> permits
+<code>*.domain</code> names (with <code>allow_subdomains</code>), <code>allow_ip_sans</code> permits IP SANs, and
+<code>allowed_uri_sans</code> lists glob patterns URI SANs must match. <code>not_before_duration_secs</code>
+(default 30, at most one day) backdates <code>not_before</code> to absorb clock skew.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/pki/issue/:role</code></div>
+<p>Issue a certificate: <code>common_name</code>, optional <code>ttl_hours</code>, and extra SANs in <code>alt_names</code>,
+<code>ip_sans</code>, and <code>uri_sans</code>. <code>key_type</code>/<code>key_bits</code> choose the key when the role's
+type is <code>any</code>; <code>not_before</code> (<code>30s</code>, <code>5m</code>) overrides the role's backdating.</p>
+<pre><code>curl -X POST http://127.0.0.1:8200/v1/pki/issue/web \
+  -H "X-Vault-Token: $TOKEN" \
+  -d '{"common_name": "api.example.com", "alt_names": ["www.example.com"],
+       "ip_sans": ["10.0.0.5"], "uri_sans": ["spiffe://cluster.local/ns/prod/sa/api"]}'</code></pre>
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/pki/certs</code></div>
+<p>List issued certificates with their common name, expiry, and revocation status, in pages of
+<code>limit</code> (default 100, max 1000). Filter with <code>common_name</code> (substring),
+<code>expires_within</code> (<code>72h</code>), or <code>expires_after</code>/<code>expires_before</code>
+(RFC 3339), and pass the response's <code>next_cursor</code> as <code>cursor</code> for the next page.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/pki/tidy</code></div>
+<p>Delete certificates (<code>tidy_cert_store</code>) and revocation entries (<code>tidy_revoked_certs</code>)
+more than <code>safety_buffer</code> (default <code>72h</code>) past expiry. Both are on by default;
+the response counts what was deleted.</p>
+<pre><code>curl -X POST http://127.0.0.1:8200/v1/pki/tidy \
+  -H "X-Vault-Token: $TOKEN" -d '{"safety_buffer": "168h"}'</code></pre>
+
+<h3>ACME</h3>
+<p>cert-manager, Caddy, certbot, and other ACME (RFC 8555) clients can obtain certificates without a
+vault token. Clients prove control of each name with <code>http-01</code> or <code>dns-01</code>
+(the only challenge for wildcards); ZVault checks <code>dns-01</code> TXT records on the configured
+resolver, so internal zones work. Every certificate is issued under one role and gets a lease like
+<code>pki/issue</code>.</p>
+<div class="endpoint"><span class="method method-post">POST</span> <code>/v1/pki/config/acme</code></div>
+<p>Enable ACME: <code>enabled</code>, <code>base_url</code> (the URL clients reach ZVault at), <code>role</code>,
+and optional <code>dns_resolver</code> (<code>ip</code> or <code>ip:port</code>; defaults to the first
+<code>nameserver</code> in <code>/etc/resolv.conf</code>). <code>GET</code> reads it back with the directory URL.</p>
+<pre><code>curl -X POST http://127.0.0.1:8200/v1/pki/config/acme \
+  -H "X-Vault-Token: $TOKEN" \
+  -d '{"enabled": true, "base_url": "https://vault.internal:8200", "role": "web",
+       "dns_resolver": "10.0.0.2"}'
+
+certbot certonly --server https://vault.internal:8200/v1/pki/acme/directory \
+  --manual --preferred-challenges dns -d api.internal</code></pre>
+<div class="endpoint"><span class="method method-get">GET</span> <code>/v1/pki/acme/directory</code></div>
+<p>The ACME directory. The <code>new-nonce</code>, <code>new-account</code>, <code>new-order</code>,
+<code>order</code>, <code>authz</code>, <code>chall</code>, <code>cert</code>, and <code>revoke-cert</code>
+resources below it take JWS requests signed with the account key (ES256, ES384, RS256, or EdDSA).
+Finalize CSRs must name exactly the order's identifiers.</p>
 "#;
 
 /// Policies and auth documentation.
@@ -976,12 +2187,58 @@ policies that define what paths it can access and what operations it can perform
 </ul>
 <p><code>deny</code> always takes precedence over other capabilities, regardless of other policies.</p>
 
+<h2>Delegated Mount Admin</h2>
+<p><code>sudo</code> on a mount's subtree makes the holder an admin of that mount only: they can manage its keys,
+roles, and configs, and mount, tune, or unmount it through <code>/v1/sys/mounts</code> — no <code>sys/</code> grant
+needed. Data operations (encrypt, decrypt, credentials) still need explicit capabilities, and <code>deny</code> rules
+still win. <code>sys/</code> and <code>auth/</code> paths can never be delegated. Give each team its own
+<code>database-team-a/</code> or <code>transit-team-a/</code> mount to delegate it without the shared defaults.</p>
+<pre><code>{
+  "name": "team-a-lead",
+  "rules": [
+    { "path": "database-team-a/**", "capabilities": ["sudo"] },
+    { "path": "transit-team-a/**", "capabilities": ["sudo"] }
+  ]
+}</code></pre>
+
+<h2>Control Groups</h2>
+<p>A rule may require multi-party approval. Requests the rule grants are parked until <code>approvals</code> distinct
+approvers — holders of an <code>approver_policies</code> policy or one of the <code>approver_identities</code> entities —
+sign off with <code>zvault control-group authorize</code>. The requester then runs the request with
+<code>zvault control-group retrieve</code>. The control group applies even if another policy grants the same request.
+Whether a request is parked is decided from its method and path before anything runs: a rule with a control group
+that grants any capability the method may need (<code>read</code>/<code>list</code> for GET,
+<code>create</code>/<code>update</code> for POST, <code>delete</code> for DELETE, or <code>sudo</code>) on the path parks it.
+Parked bodies are limited to 2 MiB.</p>
+<pre><code>{
+  "name": "break-glass",
+  "rules": [
+    {
+      "path": "secret/data/prod/**",
+      "capabilities": ["read"],
+      "control_group": { "approvals": 2, "approver_policies": ["security"] }
+    }
+  ]
+}</code></pre>
+
+<h2>Step-up MFA</h2>
+<p>A rule with <code>mfa_methods</code> grants its requests only when each listed TOTP method's code is sent in the
+<code>X-Vault-MFA</code> header (<code>zvault --mfa totp:123456 ...</code>). Like control groups, the requirement applies
+even if another policy grants the same request. Each method must exist when the policy is written.</p>
+<pre><code>{
+  "name": "policy-admin",
+  "rules": [
+    { "path": "sys/policies", "capabilities": ["list", "read", "create"] },
+    { "path": "sys/policies", "capabilities": ["delete"], "mfa_methods": ["totp"] }
+  ]
+}</code></pre>
+
 <h2>Built-in Policies</h2>
 <table>
   <thead><tr><th>Policy</th><th>Description</th></tr></thead>
   <tbody>
     <tr><td><code>root</code></td><td>Unrestricted access to everything. Assigned to the root token.</td></tr>
-    <tr><td><code>default</code></td><td>Minimal access — token self-lookup and renewal.</td></tr>
+    <tr><td><code>default</code></td><td>Minimal access — token self-lookup and renewal, plus the token's own cubbyhole.</td></tr>
   </tbody>
 </table>
 
@@ -1010,6 +2267,16 @@ zvault-cli token create --policies app-readonly --ttl 1h</code></pre>
 <p>Machine-to-machine authentication using a role ID (public) and secret ID (private, single-use).
 Designed for CI/CD pipelines and automated systems.</p>
 
+<h3>JWT</h3>
+<p>CI systems exchange the OIDC token issued to each job (GitHub Actions, GitLab) for a short-lived
+vault token, so no long-lived secret ID has to live in CI settings. Roles bind audiences, the subject,
+and claims such as repository or branch to policies. Keys come from a JWKS URL (cached for five minutes)
+or static PEM public keys.</p>
+<pre><code># GitHub Actions step (job needs `permissions: id-token: write`)
+export ZVAULT_JWT=$(curl -sH "Authorization: bearer $ACTIONS_ID_TOKEN_REQUEST_TOKEN" \
+  "$ACTIONS_ID_TOKEN_REQUEST_URL&amp;audience=https://github.com/acme" | jq -r .value)
+zvault-cli jwt login --role deploy</code></pre>
+
 <h3>OIDC (planned)</h3>
 <p>Authenticate via an external OpenID Connect provider (Okta, Auth0, Keycloak, Spring).
 Maps OIDC claims to ZVault policies.</p>
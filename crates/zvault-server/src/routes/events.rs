@@ -0,0 +1,160 @@
+//! Event streaming routes: `/v1/sys/events/*`
+//!
+//! Stream vault events to subscribers as Server-Sent Events. A subscription
+//! names a topic pattern (`kv/*`, `policy.write`, `*`) and needs `read` on
+//! `sys/events/subscribe/<pattern>`.
+//!
+//! Each event is authorized again before it is sent: events about a path
+//! are only delivered when the subscriber's policies allow reading that
+//! path, and the stream ends once the token is revoked or expires. A
+//! `sys.sealed` event is delivered and then ends every stream.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::get;
+use axum::{Extension, Router};
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::events::{TOPIC_SEALED, VaultEvent, topic_matches};
+use zvault_core::policy::Capability;
+
+/// SSE event name sent when a subscriber fell behind and missed events.
+const LAGGED_EVENT: &str = "lagged";
+
+/// Build the `/v1/sys/events` router.
+///
+/// Paths:
+/// - `GET /v1/sys/events/subscribe/{pattern}` — stream matching events (SSE)
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/subscribe/{*pattern}", get(subscribe))
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Stream events whose topic matches `pattern`.
+///
+/// Each SSE message carries the topic as its event name, the event ID as
+/// its ID, and the [`VaultEvent`] as JSON data.
+async fn subscribe(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    headers: HeaderMap,
+    Path(pattern): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("sys/events/subscribe/{pattern}"),
+            &Capability::Read,
+        )
+        .await?;
+
+    let token = headers
+        .get("X-Vault-Token")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+        .ok_or_else(|| AppError::Unauthorized("missing X-Vault-Token header".to_owned()))?;
+
+    let subscription = Subscription {
+        rx: state.event_bus.subscribe(),
+        state,
+        policies: auth.policies,
+        token,
+        pattern,
+        closed: false,
+    };
+    let events = stream::unfold(subscription, |mut sub| async move {
+        sub.next().await.map(|event| (Ok(event), sub))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// One subscriber's view of the event bus.
+struct Subscription {
+    state: Arc<AppState>,
+    rx: Receiver<VaultEvent>,
+    policies: Vec<String>,
+    token: String,
+    pattern: String,
+    closed: bool,
+}
+
+impl Subscription {
+    /// The next event this subscriber may see, or `None` to end the stream.
+    async fn next(&mut self) -> Option<Event> {
+        if self.closed {
+            return None;
+        }
+        loop {
+            let event = match self.rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    return Event::default()
+                        .event(LAGGED_EVENT)
+                        .json_data(serde_json::json!({ "missed": missed }))
+                        .ok();
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            if !topic_matches(&self.pattern, &event.topic) {
+                continue;
+            }
+
+            // Nothing behind the barrier can be checked once sealed.
+            if event.topic == TOPIC_SEALED {
+                self.closed = true;
+            } else if self.state.token_store.lookup(&self.token).await.is_err() {
+                return None;
+            } else if !self.may_read(&event).await {
+                continue;
+            }
+
+            return Event::default()
+                .event(&event.topic)
+                .id(&event.id)
+                .json_data(&event)
+                .ok();
+        }
+    }
+
+    /// Whether the subscriber's policies allow reading what `event` is about.
+    async fn may_read(&self, event: &VaultEvent) -> bool {
+        let Some(path) = acl_path(event) else {
+            return true;
+        };
+        self.state
+            .policy_store
+            .check(&self.policies, &path, &Capability::Read)
+            .await
+            .is_ok()
+    }
+}
+
+/// The ACL path an event is about, if any.
+///
+/// Policy events map to `sys/policies`; KV and lease events carry their
+/// path in the payload. Seal status events concern no path.
+fn acl_path(event: &VaultEvent) -> Option<String> {
+    if event.topic.starts_with("policy.") {
+        return Some("sys/policies".to_owned());
+    }
+    event
+        .data
+        .get("path")
+        .or_else(|| event.data.get("engine_path"))
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_owned)
+}
@@ -0,0 +1,372 @@
+//! Identity routes: `/v1/sys/identity/*`
+//!
+//! Entities, their aliases, and groups. Login endpoints bind every new token
+//! to the entity of its alias through [`bind_login`], and the auth
+//! middleware adds the entity's and its groups' policies on each request.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::identity::{
+    ALIAS_METADATA, ENTITY_ID_METADATA, Entity, EntityAlias, EntityParams, GROUPS_METADATA, Group,
+    GroupParams,
+};
+use zvault_core::policy::Capability;
+use zvault_core::token::TokenEntry;
+
+/// Build the `/v1/sys/identity` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/identity/entity` — list entity IDs
+/// - `POST /v1/sys/identity/entity` — create an entity
+/// - `GET|POST|DELETE /v1/sys/identity/entity/id/{id}` — manage one entity
+/// - `GET  /v1/sys/identity/entity/name/{name}` — read an entity by name
+/// - `GET  /v1/sys/identity/entity-alias` — list aliases
+/// - `POST /v1/sys/identity/entity-alias` — attach `{entity_id, auth_method, name}`
+/// - `GET|DELETE /v1/sys/identity/entity-alias/id/{id}` — manage one alias
+/// - `GET  /v1/sys/identity/group` — list group IDs
+/// - `POST /v1/sys/identity/group` — create a group
+/// - `GET|POST|DELETE /v1/sys/identity/group/id/{id}` — manage one group
+/// - `GET  /v1/sys/identity/group/name/{name}` — read a group by name
+/// - `GET  /v1/sys/identity/lookup/self` — the caller's entity and policies
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/entity", get(list_entities).post(create_entity))
+        .route(
+            "/entity/id/{id}",
+            get(read_entity).post(update_entity).delete(delete_entity),
+        )
+        .route("/entity/name/{name}", get(read_entity_by_name))
+        .route("/entity-alias", get(list_aliases).post(create_alias))
+        .route(
+            "/entity-alias/id/{id}",
+            get(read_alias).delete(delete_alias),
+        )
+        .route("/group", get(list_groups).post(create_group))
+        .route(
+            "/group/id/{id}",
+            get(read_group).post(update_group).delete(delete_group),
+        )
+        .route("/group/name/{name}", get(read_group_by_name))
+        .route("/lookup/self", get(lookup_self))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct AliasRequest {
+    pub entity_id: String,
+    /// `approle`, `jwt`, or `oidc`.
+    pub auth_method: String,
+    /// Role name, user claim, or OIDC subject.
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntityResponse {
+    #[serde(flatten)]
+    pub entity: Entity,
+    pub aliases: Vec<EntityAlias>,
+    /// Groups containing the entity, directly or through nesting.
+    pub group_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LookupSelfResponse {
+    pub entity_id: Option<String>,
+    /// The token's policies plus those from its entity and groups.
+    pub policies: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListResponse {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AliasListResponse {
+    pub aliases: Vec<EntityAlias>,
+}
+
+// ── Entities ─────────────────────────────────────────────────────────
+
+async fn list_entities(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<ListResponse>, AppError> {
+    check(&state, &auth, "sys/identity/entity", Capability::List).await?;
+    let keys = state.identity.list_entities().await?;
+    Ok(Json(ListResponse { keys }))
+}
+
+async fn create_entity(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<EntityParams>,
+) -> Result<Json<EntityResponse>, AppError> {
+    check(&state, &auth, "sys/identity/entity", Capability::Create).await?;
+    let entity = state.identity.create_entity(body).await?;
+    entity_response(&state, entity).await.map(Json)
+}
+
+async fn read_entity(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<EntityResponse>, AppError> {
+    check(&state, &auth, &entity_path(&id), Capability::Read).await?;
+    let entity = state.identity.get_entity(&id).await?;
+    entity_response(&state, entity).await.map(Json)
+}
+
+async fn read_entity_by_name(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<EntityResponse>, AppError> {
+    let entity = state.identity.entity_by_name(&name).await?;
+    check(&state, &auth, &entity_path(&entity.id), Capability::Read).await?;
+    entity_response(&state, entity).await.map(Json)
+}
+
+async fn update_entity(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+    Json(body): Json<EntityParams>,
+) -> Result<Json<EntityResponse>, AppError> {
+    check(&state, &auth, &entity_path(&id), Capability::Update).await?;
+    let entity = state.identity.update_entity(&id, body).await?;
+    entity_response(&state, entity).await.map(Json)
+}
+
+async fn delete_entity(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &entity_path(&id), Capability::Delete).await?;
+    state.identity.delete_entity(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Aliases ──────────────────────────────────────────────────────────
+
+async fn list_aliases(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<AliasListResponse>, AppError> {
+    check(&state, &auth, "sys/identity/entity-alias", Capability::List).await?;
+    let aliases = state.identity.list_aliases(None).await?;
+    Ok(Json(AliasListResponse { aliases }))
+}
+
+async fn create_alias(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<AliasRequest>,
+) -> Result<Json<EntityAlias>, AppError> {
+    check(
+        &state,
+        &auth,
+        "sys/identity/entity-alias",
+        Capability::Create,
+    )
+    .await?;
+    let alias = state
+        .identity
+        .create_alias(&body.entity_id, &body.auth_method, &body.name)
+        .await?;
+    Ok(Json(alias))
+}
+
+async fn read_alias(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<EntityAlias>, AppError> {
+    check(&state, &auth, &alias_path(&id), Capability::Read).await?;
+    Ok(Json(state.identity.get_alias(&id).await?))
+}
+
+async fn delete_alias(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &alias_path(&id), Capability::Delete).await?;
+    state.identity.delete_alias(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Groups ───────────────────────────────────────────────────────────
+
+async fn list_groups(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<ListResponse>, AppError> {
+    check(&state, &auth, "sys/identity/group", Capability::List).await?;
+    let keys = state.identity.list_groups().await?;
+    Ok(Json(ListResponse { keys }))
+}
+
+async fn create_group(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<GroupParams>,
+) -> Result<Json<Group>, AppError> {
+    check(&state, &auth, "sys/identity/group", Capability::Create).await?;
+    Ok(Json(state.identity.create_group(body).await?))
+}
+
+async fn read_group(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<Group>, AppError> {
+    check(&state, &auth, &group_path(&id), Capability::Read).await?;
+    Ok(Json(state.identity.get_group(&id).await?))
+}
+
+async fn read_group_by_name(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<Group>, AppError> {
+    let group = state.identity.group_by_name(&name).await?;
+    check(&state, &auth, &group_path(&group.id), Capability::Read).await?;
+    Ok(Json(group))
+}
+
+async fn update_group(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+    Json(body): Json<GroupParams>,
+) -> Result<Json<Group>, AppError> {
+    check(&state, &auth, &group_path(&id), Capability::Update).await?;
+    Ok(Json(state.identity.update_group(&id, body).await?))
+}
+
+async fn delete_group(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &group_path(&id), Capability::Delete).await?;
+    state.identity.delete_group(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The caller's own entity and effective policies. Needs no policy.
+async fn lookup_self(Extension(auth): Extension<AuthContext>) -> Json<LookupSelfResponse> {
+    Json(LookupSelfResponse {
+        entity_id: auth.entity_id,
+        policies: auth.policies,
+    })
+}
+
+// ── Logins ───────────────────────────────────────────────────────────
+
+/// Bind a freshly issued login token to the entity of its alias.
+///
+/// The auth method records the alias name (and any external group names)
+/// in the token's metadata; the entity is resolved — or created — from
+/// them and its ID is stored on the token. A login whose entity is
+/// disabled has its new token revoked before the error is returned.
+///
+/// # Errors
+///
+/// Returns 403 if the entity is disabled, or 500 if the token carries no
+/// alias or storage fails.
+pub async fn bind_login(
+    state: &AppState,
+    auth_method: &str,
+    token: &str,
+) -> Result<TokenEntry, AppError> {
+    let result = bind_entity(state, auth_method, token).await;
+    if result.is_err() {
+        if let Err(e) = state.token_store.revoke(token).await {
+            tracing::warn!(error = %e, "failed to revoke token after failed identity binding");
+        }
+    }
+    result
+}
+
+async fn bind_entity(
+    state: &AppState,
+    auth_method: &str,
+    token: &str,
+) -> Result<TokenEntry, AppError> {
+    let entry = state.token_store.lookup(token).await?;
+    let alias = entry
+        .metadata
+        .get(ALIAS_METADATA)
+        .ok_or_else(|| AppError::Internal(format!("{auth_method} login set no alias")))?;
+    let groups: Vec<String> = entry
+        .metadata
+        .get(GROUPS_METADATA)
+        .map(|g| {
+            g.split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default();
+    let entity = state.identity.login(auth_method, alias, &groups).await?;
+    Ok(state
+        .token_store
+        .set_metadata(token, ENTITY_ID_METADATA, &entity.id)
+        .await?)
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+async fn entity_response(state: &AppState, entity: Entity) -> Result<EntityResponse, AppError> {
+    let aliases = state.identity.list_aliases(Some(&entity.id)).await?;
+    let group_ids = state
+        .identity
+        .groups_of(&entity.id)
+        .await?
+        .into_iter()
+        .map(|g| g.id)
+        .collect();
+    Ok(EntityResponse {
+        entity,
+        aliases,
+        group_ids,
+    })
+}
+
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &capability)
+        .await?;
+    Ok(())
+}
+
+fn entity_path(id: &str) -> String {
+    format!("sys/identity/entity/id/{id}")
+}
+
+fn alias_path(id: &str) -> String {
+    format!("sys/identity/entity-alias/id/{id}")
+}
+
+fn group_path(id: &str) -> String {
+    format!("sys/identity/group/id/{id}")
+}
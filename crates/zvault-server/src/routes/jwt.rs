@@ -0,0 +1,170 @@
+//! HTTP route handlers for the JWT auth method.
+//!
+//! CI systems exchange the OIDC token they are issued per job (GitHub
+//! Actions, GitLab) for a short-lived vault token, with no stored secret.
+//!
+//! Endpoints:
+//! - `POST /v1/auth/jwt/config` — set the JWKS URL or static public keys
+//! - `GET  /v1/auth/jwt/config` — read the config
+//! - `POST /v1/auth/jwt/role/:name` — create or replace a role
+//! - `GET  /v1/auth/jwt/role/:name` — read a role
+//! - `DELETE /v1/auth/jwt/role/:name` — delete a role
+//! - `GET  /v1/auth/jwt/role` — list all roles
+//! - `POST /v1/auth/jwt/login` — exchange a JWT for a token (plus an
+//!   `X-Vault-MFA` header when a login enforcement names `jwt`)
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+
+use zvault_core::identity::ENTITY_ID_METADATA;
+use zvault_core::jwt_auth::{JwtConfig, JwtRole};
+use zvault_core::policy::Capability;
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::routes::identity::bind_login;
+use crate::routes::mfa::enforce_login;
+use crate::state::AppState;
+
+/// Policy path for the key source config.
+const CONFIG_PATH: &str = "auth/jwt/config";
+
+/// Build the JWT auth router (authenticated — config and role management).
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/config", get(read_config).post(write_config))
+        .route("/role", get(list_roles))
+        .route(
+            "/role/{name}",
+            post(write_role).get(get_role).delete(delete_role),
+        )
+}
+
+/// Build the public JWT login router (no auth required).
+pub fn login_router() -> Router<Arc<AppState>> {
+    Router::new().route("/login", post(login))
+}
+
+async fn write_config(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<JwtConfig>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, CONFIG_PATH, &Capability::Update)
+        .await?;
+    state.jwt_auth.write_config(body).await?;
+    Ok(Json(serde_json::json!({"status": "ok"})))
+}
+
+async fn read_config(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<JwtConfig>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, CONFIG_PATH, &Capability::Read)
+        .await?;
+    Ok(Json(state.jwt_auth.read_config().await?))
+}
+
+async fn write_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(mut body): Json<JwtRole>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_role(&state, &auth, &name, Capability::Update).await?;
+    body.name = name;
+    state.jwt_auth.write_role(body).await?;
+    Ok(Json(serde_json::json!({"status": "ok"})))
+}
+
+async fn get_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<JwtRole>, AppError> {
+    check_role(&state, &auth, &name, Capability::Read).await?;
+    Ok(Json(state.jwt_auth.get_role(&name).await?))
+}
+
+async fn delete_role(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_role(&state, &auth, &name, Capability::Delete).await?;
+    state.jwt_auth.delete_role(&name).await?;
+    Ok(Json(serde_json::json!({"status": "deleted"})))
+}
+
+async fn list_roles(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "auth/jwt/role", &Capability::List)
+        .await?;
+    let names = state.jwt_auth.list_roles().await?;
+    Ok(Json(serde_json::json!({"keys": names})))
+}
+
+#[derive(Deserialize)]
+struct LoginRequest {
+    role: String,
+    jwt: String,
+}
+
+async fn login(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<LoginRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (plaintext_token, _) = state
+        .jwt_auth
+        .login(&body.role, &body.jwt, &state.token_store)
+        .await?;
+
+    let token_entry = bind_login(&state, "jwt", &plaintext_token).await?;
+    enforce_login(&state, "jwt", &headers, &plaintext_token, &token_entry).await?;
+
+    let ttl_secs = token_entry
+        .expires_at
+        .map_or(0, |exp| (exp - chrono::Utc::now()).num_seconds().max(0));
+
+    Ok(Json(serde_json::json!({
+        "client_token": plaintext_token,
+        "accessor": token_entry.accessor(),
+        "token_hash": token_entry.token_hash,
+        "policies": token_entry.policies,
+        "ttl": ttl_secs,
+        "renewable": token_entry.renewable,
+        "entity_id": token_entry.metadata.get(ENTITY_ID_METADATA),
+    })))
+}
+
+/// Require `capability` on role `name`.
+async fn check_role(
+    state: &AppState,
+    auth: &AuthContext,
+    name: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("auth/jwt/role/{name}"),
+            &capability,
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Barrier keyring routes: `/v1/sys/rotate`, `/v1/sys/reencrypt`, and
+//! `/v1/sys/key-status`.
+//!
+//! Rotation installs a new data encryption key as the active term. Entries
+//! written before stay readable under their old term; pass `reencrypt` to
+//! rewrite them under the new one straight away, or re-encrypt one prefix at
+//! a time later. `key-status?prefix=` counts entries by term so an audit can
+//! confirm nothing is left under a retired key.
+
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::barrier::{KeyStatus, TermUsage};
+use zvault_core::policy::Capability;
+
+/// Build the `/v1/sys/rotate` router.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/", post(rotate))
+}
+
+/// Build the `/v1/sys/reencrypt` router.
+pub fn reencrypt_router() -> Router<Arc<AppState>> {
+    Router::new().route("/", post(reencrypt))
+}
+
+/// Build the `/v1/sys/key-status` router.
+pub fn status_router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(key_status))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+/// Request body for `POST /v1/sys/rotate`.
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateRequest {
+    /// Rewrite existing entries under the new key before returning.
+    #[serde(default)]
+    pub reencrypt: bool,
+}
+
+/// Response body for `POST /v1/sys/rotate`.
+#[derive(Debug, Serialize)]
+pub struct RotateResponse {
+    /// The new active term.
+    #[serde(flatten)]
+    pub status: KeyStatus,
+    /// Entries rewritten under the new term, if `reencrypt` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reencrypted: Option<usize>,
+}
+
+/// Request body for `POST /v1/sys/reencrypt`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ReencryptRequest {
+    /// Storage prefix to rewrite; empty for everything.
+    #[serde(default)]
+    pub prefix: String,
+}
+
+/// Response body for `POST /v1/sys/reencrypt`.
+#[derive(Debug, Serialize)]
+pub struct ReencryptResponse {
+    /// The active term entries were rewritten under.
+    pub term: u32,
+    pub prefix: String,
+    /// Entries rewritten.
+    pub reencrypted: usize,
+}
+
+/// Query parameters for `GET /v1/sys/key-status`.
+#[derive(Debug, Default, Deserialize)]
+pub struct KeyStatusQuery {
+    /// Count entries under this storage prefix by key term.
+    pub prefix: Option<String>,
+}
+
+/// Response body for `GET /v1/sys/key-status`.
+#[derive(Debug, Serialize)]
+pub struct KeyStatusResponse {
+    #[serde(flatten)]
+    pub status: KeyStatus,
+    /// Entries by key term, if a `prefix` was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TermUsage>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Rotate the barrier encryption key.
+async fn rotate(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    body: Option<Json<RotateRequest>>,
+) -> Result<Json<RotateResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/rotate", &Capability::Sudo)
+        .await?;
+
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let status = state.barrier.rotate().await?;
+    info!(term = status.term, "barrier key rotated");
+
+    let reencrypted = if body.reencrypt {
+        let count = state.barrier.reencrypt("").await?;
+        info!(term = status.term, entries = count, "entries re-encrypted");
+        Some(count)
+    } else {
+        None
+    };
+
+    Ok(Json(RotateResponse {
+        status,
+        reencrypted,
+    }))
+}
+
+/// Rewrite the entries under a prefix with the active key term.
+async fn reencrypt(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    body: Option<Json<ReencryptRequest>>,
+) -> Result<Json<ReencryptResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/reencrypt", &Capability::Sudo)
+        .await?;
+
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let reencrypted = state.barrier.reencrypt(&body.prefix).await?;
+    let term = state.barrier.key_status().await?.term;
+    info!(term, prefix = %body.prefix, entries = reencrypted, "entries re-encrypted");
+
+    Ok(Json(ReencryptResponse {
+        term,
+        prefix: body.prefix,
+        reencrypted,
+    }))
+}
+
+/// Report the active key term and when it was installed, and with
+/// `?prefix=` how many entries under the prefix each term encrypts.
+async fn key_status(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<KeyStatusQuery>,
+) -> Result<Json<KeyStatusResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/key-status", &Capability::Read)
+        .await?;
+
+    let status = state.barrier.key_status().await?;
+    let usage = match query.prefix {
+        Some(prefix) => Some(state.barrier.term_usage(&prefix).await?),
+        None => None,
+    };
+    Ok(Json(KeyStatusResponse { status, usage }))
+}
@@ -4,7 +4,7 @@
 
 use std::sync::Arc;
 
-use axum::extract::State;
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
@@ -13,6 +13,8 @@ use serde::{Deserialize, Serialize};
 use crate::error::AppError;
 use crate::middleware::AuthContext;
 use crate::state::AppState;
+use zvault_core::events::TOPIC_LEASE_REVOKED;
+use zvault_core::lease::Lease;
 use zvault_core::policy::Capability;
 
 /// Build the `/v1/sys/leases` router.
@@ -22,6 +24,8 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/lookup", post(lookup_lease))
         .route("/renew", post(renew_lease))
         .route("/revoke", post(revoke_lease))
+        .route("/revoke-prefix/{*prefix}", post(revoke_prefix))
+        .route("/revoke-force/{*prefix}", post(revoke_force))
 }
 
 // ── Request / Response types ─────────────────────────────────────────
@@ -37,6 +41,8 @@ pub struct LeaseResponse {
     pub engine_path: String,
     pub issued_at: String,
     pub ttl_secs: i64,
+    /// Cap on `ttl_secs` across renewals; `0` means uncapped.
+    pub max_ttl_secs: i64,
     pub renewable: bool,
     pub expired: bool,
 }
@@ -52,6 +58,14 @@ pub struct LeaseRevokeRequest {
     pub lease_id: String,
 }
 
+/// Response body for prefix revocation.
+#[derive(Debug, Serialize)]
+pub struct LeaseRevokePrefixResponse {
+    pub prefix: String,
+    pub revoked: usize,
+    pub lease_ids: Vec<String>,
+}
+
 /// Response body for `GET /v1/sys/leases`.
 #[derive(Debug, Serialize)]
 pub struct LeaseListResponse {
@@ -81,6 +95,7 @@ async fn list_leases(
                 engine_path: lease.engine_path.clone(),
                 issued_at: lease.issued_at.to_rfc3339(),
                 ttl_secs: lease.ttl_secs,
+                max_ttl_secs: lease.max_ttl_secs,
                 renewable: lease.renewable,
                 expired,
             }
@@ -110,12 +125,13 @@ async fn lookup_lease(
         engine_path: lease.engine_path,
         issued_at: lease.issued_at.to_rfc3339(),
         ttl_secs: lease.ttl_secs,
+        max_ttl_secs: lease.max_ttl_secs,
         renewable: lease.renewable,
         expired,
     }))
 }
 
-/// Renew a lease.
+/// Renew a lease. The increment is clamped to the lease's max TTL.
 async fn renew_lease(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
@@ -135,6 +151,7 @@ async fn renew_lease(
         engine_path: lease.engine_path,
         issued_at: lease.issued_at.to_rfc3339(),
         ttl_secs: lease.ttl_secs,
+        max_ttl_secs: lease.max_ttl_secs,
         renewable: lease.renewable,
         expired,
     }))
@@ -151,7 +168,81 @@ async fn revoke_lease(
         .check(&auth.policies, "sys/leases/revoke", &Capability::Sudo)
         .await?;
 
+    // Revocation stays idempotent; only publish for leases that existed.
+    let lease = state.lease_manager.lookup(&body.lease_id).await.ok();
     state.lease_manager.revoke(&body.lease_id).await?;
 
+    if let Some(lease) = lease {
+        state.event_bus.publish(
+            TOPIC_LEASE_REVOKED,
+            serde_json::json!({
+                "lease_id": lease.id,
+                "engine_path": lease.engine_path,
+            }),
+        );
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Revoke every lease whose engine path starts with the prefix.
+///
+/// Leases whose engine cleanup fails are kept; use `revoke-force` to drop them.
+async fn revoke_prefix(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(prefix): Path<String>,
+) -> Result<Json<LeaseRevokePrefixResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            "sys/leases/revoke-prefix",
+            &Capability::Sudo,
+        )
+        .await?;
+
+    let revoked = state.lease_manager.revoke_prefix(&prefix).await?;
+    Ok(Json(publish_revoked(&state, prefix, &revoked)))
+}
+
+/// Revoke every lease under the prefix, discarding leases whose engine
+/// cleanup fails.
+async fn revoke_force(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(prefix): Path<String>,
+) -> Result<Json<LeaseRevokePrefixResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/leases/revoke-force", &Capability::Sudo)
+        .await?;
+
+    let revoked = state.lease_manager.revoke_force(&prefix).await?;
+    Ok(Json(publish_revoked(&state, prefix, &revoked)))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// Publish a revocation event per lease and build the response body.
+fn publish_revoked(
+    state: &AppState,
+    prefix: String,
+    revoked: &[Lease],
+) -> LeaseRevokePrefixResponse {
+    for lease in revoked {
+        state.event_bus.publish(
+            TOPIC_LEASE_REVOKED,
+            serde_json::json!({
+                "lease_id": lease.id,
+                "engine_path": lease.engine_path,
+            }),
+        );
+    }
+
+    LeaseRevokePrefixResponse {
+        prefix,
+        revoked: revoked.len(),
+        lease_ids: revoked.iter().map(|l| l.id.clone()).collect(),
+    }
+}
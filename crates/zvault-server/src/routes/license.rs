@@ -0,0 +1,87 @@
+//! License routes: `/v1/sys/license`
+//!
+//! Activate a signed license and inspect the effective tier. The license is
+//! verified and stored server-side; gated routes consult it on every request.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::license::{License, Tier};
+use zvault_core::policy::Capability;
+
+/// Build the `/v1/sys/license` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/license` — current license status
+/// - `POST /v1/sys/license` — activate a license key
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(license_status).post(activate_license))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct ActivateLicenseRequest {
+    pub key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LicenseStatusResponse {
+    /// Effective tier (`free` when unlicensed or expired).
+    pub tier: Tier,
+    pub license_id: Option<String>,
+    pub email: Option<String>,
+    pub expires_at: Option<String>,
+    pub expired: bool,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Show the active license.
+async fn license_status(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<LicenseStatusResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/license", &Capability::Read)
+        .await?;
+
+    let tier = state.license_manager.tier().await;
+    let license = state.license_manager.current().await;
+    Ok(Json(status_response(tier, license.as_ref())))
+}
+
+/// Verify and activate a license key.
+async fn activate_license(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ActivateLicenseRequest>,
+) -> Result<Json<LicenseStatusResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/license", &Capability::Sudo)
+        .await?;
+
+    let license = state.license_manager.activate(&body.key).await?;
+    Ok(Json(status_response(license.payload.tier, Some(&license))))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+fn status_response(tier: Tier, license: Option<&License>) -> LicenseStatusResponse {
+    LicenseStatusResponse {
+        tier,
+        license_id: license.map(|l| l.payload.license_id.clone()),
+        email: license.map(|l| l.payload.email.clone()),
+        expires_at: license.map(|l| l.payload.expires_at.clone()),
+        expired: license.is_some_and(License::is_expired),
+    }
+}
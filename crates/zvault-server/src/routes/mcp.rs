@@ -0,0 +1,651 @@
+//! MCP routes: `/v1/mcp`
+//!
+//! Serves the Model Context Protocol over HTTP, so remote AI agents and
+//! hosted assistants can use the vault with a vault token instead of running
+//! `zvault mcp-server` next to them. Both transports share one JSON-RPC
+//! handler:
+//!
+//! - `POST /v1/mcp` answers a JSON-RPC message in the response body.
+//! - `GET /v1/mcp` opens a Server-Sent Events session. Its first `endpoint`
+//!   event names `/v1/mcp/messages?session_id=...`; messages posted there are
+//!   answered with `202 Accepted`, and their responses arrive as `message`
+//!   events. Only the token that opened a session may post to it, and the
+//!   stream ends once that token is revoked or expires.
+//!
+//! Tools run as the calling token: each goes through the same handler, and
+//! so the same policy check, as the equivalent `/v1/secret` request. Every
+//! call is recorded as an external `mcp` action in the audit log, with the
+//! value of a secret being set redacted.
+//!
+//! Only the vault tools are served. The CLI tools that read local files,
+//! connect to databases and services with stored credentials, or run
+//! commands would act from the vault host, so they stay in the CLI. As
+//! there, no tool returns a secret value.
+
+use std::convert::Infallible;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+use crate::error::AppError;
+use crate::middleware::{AuthContext, RequestInfo};
+use crate::routes::audit::{self, ExternalAuditRequest, ExternalOutcome};
+use crate::routes::secrets::{self, KvMount};
+use crate::state::AppState;
+
+/// MCP protocol revision this server speaks.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Open SSE sessions allowed per token.
+const MAX_SESSIONS_PER_TOKEN: usize = 16;
+
+/// Responses queued for an SSE session before posting to it blocks.
+const SESSION_QUEUE: usize = 32;
+
+/// How often an idle SSE session checks that its token is still valid.
+const TOKEN_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Tools served over HTTP.
+const TOOLS: [&str; 6] = [
+    "zvault_list_secrets",
+    "zvault_describe_secret",
+    "zvault_generate_env_template",
+    "zvault_set_secret",
+    "zvault_delete_secret",
+    "zvault_vault_status",
+];
+
+/// Build the `/v1/mcp` router.
+///
+/// Paths:
+/// - `POST /v1/mcp` — handle one JSON-RPC message
+/// - `GET  /v1/mcp` — open an SSE session
+/// - `POST /v1/mcp/messages?session_id=...` — send a message to an SSE session
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(open_session).post(post_message))
+        .route("/messages", post(post_session_message))
+}
+
+// ── Request types ────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionQuery {
+    pub session_id: String,
+}
+
+/// An open SSE session, keyed by its ID in [`AppState::mcp_sessions`].
+#[derive(Debug)]
+pub struct McpSession {
+    /// Hash of the token that opened the session.
+    token_hash: String,
+    /// Responses to send as `message` events.
+    tx: mpsc::Sender<Value>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Handle a JSON-RPC message and return its response; notifications are
+/// answered with `202 Accepted` and no body.
+async fn post_message(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(request): Extension<RequestInfo>,
+    Json(message): Json<Value>,
+) -> Response {
+    match handle_message(&state, &auth, &request, message).await {
+        Some(reply) => Json(reply).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+/// Open an SSE session for the calling token.
+async fn open_session(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let token = headers
+        .get("X-Vault-Token")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+        .ok_or_else(|| AppError::Unauthorized("missing X-Vault-Token header".to_owned()))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = mpsc::channel(SESSION_QUEUE);
+    {
+        let mut sessions = state.mcp_sessions.write().await;
+        let open = sessions
+            .values()
+            .filter(|s| s.token_hash == auth.token_hash)
+            .count();
+        if open >= MAX_SESSIONS_PER_TOKEN {
+            return Err(AppError::RateLimited {
+                message: format!("at most {MAX_SESSIONS_PER_TOKEN} MCP sessions per token"),
+                retry_after_secs: None,
+            });
+        }
+        sessions.insert(
+            id.clone(),
+            McpSession {
+                token_hash: auth.token_hash,
+                tx,
+            },
+        );
+    }
+
+    let session = SessionStream {
+        state,
+        rx,
+        token,
+        id,
+        endpoint_sent: false,
+    };
+    let events = stream::unfold(session, |mut session| async move {
+        session.next().await.map(|event| (Ok(event), session))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Handle a JSON-RPC message for an SSE session, sending its response on
+/// the session's stream.
+async fn post_session_message(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(request): Extension<RequestInfo>,
+    Query(query): Query<SessionQuery>,
+    Json(message): Json<Value>,
+) -> Result<StatusCode, AppError> {
+    let not_found = || AppError::NotFound(format!("no MCP session '{}'", query.session_id));
+    let tx = state
+        .mcp_sessions
+        .read()
+        .await
+        .get(&query.session_id)
+        .filter(|s| s.token_hash == auth.token_hash)
+        .map(|s| s.tx.clone())
+        .ok_or_else(not_found)?;
+
+    if let Some(reply) = handle_message(&state, &auth, &request, message).await {
+        tx.send(reply).await.map_err(|_| not_found())?;
+    }
+    Ok(StatusCode::ACCEPTED)
+}
+
+// ── JSON-RPC ─────────────────────────────────────────────────────────
+
+/// The response to a JSON-RPC message, or `None` for a notification.
+async fn handle_message(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    request: &RequestInfo,
+    message: Value,
+) -> Option<Value> {
+    let req: JsonRpcRequest = match serde_json::from_value(message) {
+        Ok(req) => req,
+        Err(e) => {
+            return Some(rpc_err(
+                &Value::Null,
+                -32600,
+                &format!("invalid request: {e}"),
+            ));
+        }
+    };
+    let id = req.id.clone().unwrap_or(Value::Null);
+
+    match req.method.as_str() {
+        "initialize" => Some(rpc_ok(
+            &id,
+            &json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "zvault-mcp",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            }),
+        )),
+        "ping" => Some(rpc_ok(&id, &json!({}))),
+        "tools/list" => Some(rpc_ok(&id, &json!({ "tools": tool_definitions() }))),
+        "tools/call" => {
+            let params = req.params.unwrap_or(Value::Null);
+            let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+            let arguments = params
+                .get("arguments")
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+            match call_tool(state, auth, request, name, &arguments).await {
+                Ok(result) => Some(rpc_ok(&id, &result)),
+                Err(e) => Some(rpc_err(&id, -32603, &e.to_string())),
+            }
+        }
+        // Notifications (no id) get no response, known or not.
+        _ if req.id.is_none() => None,
+        method => Some(rpc_err(&id, -32601, &format!("method not found: {method}"))),
+    }
+}
+
+/// Run a tool and audit the call, returning its MCP result.
+///
+/// A tool that fails produces an `isError` result; only a call the audit
+/// log refuses fails the JSON-RPC request, and its result is withheld.
+async fn call_tool(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    request: &RequestInfo,
+    name: &str,
+    arguments: &Value,
+) -> Result<Value, AppError> {
+    if !TOOLS.contains(&name) {
+        return Ok(tool_result(&format!("Error: unknown tool: {name}"), true));
+    }
+
+    let result = run_tool(state, auth, name, arguments).await;
+    let (outcome, error) = match &result {
+        Ok(_) => (ExternalOutcome::Success, None),
+        Err(
+            e @ (AppError::Forbidden(_)
+            | AppError::MfaRequired(_)
+            | AppError::ControlGroupRequired(_)),
+        ) => (ExternalOutcome::Denied, Some(e.to_string())),
+        Err(e) => (ExternalOutcome::Error, Some(e.to_string())),
+    };
+    let action = ExternalAuditRequest {
+        source: "mcp".to_owned(),
+        action: name.to_owned(),
+        operation: operation(name).to_owned(),
+        data: Some(json!({ "arguments": redact(arguments) })),
+        outcome,
+        error,
+    };
+    audit::record_external(state, auth, request, action).await?;
+
+    Ok(match result {
+        Ok(text) => tool_result(&text, false),
+        Err(e) => tool_result(&format!("Error: {e}"), true),
+    })
+}
+
+fn rpc_ok(id: &Value, result: &Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_err(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn tool_result(text: &str, is_error: bool) -> Value {
+    let mut result = json!({ "content": [{ "type": "text", "text": text }] });
+    if is_error {
+        result["isError"] = Value::Bool(true);
+    }
+    result
+}
+
+// ── Tools ────────────────────────────────────────────────────────────
+
+/// Definitions of [`TOOLS`], as the CLI server describes them.
+fn tool_definitions() -> Value {
+    let path = |description: &str| json!({ "type": "string", "description": description });
+    json!([
+        {
+            "name": "zvault_list_secrets",
+            "description": "List secret key names under a path. Returns paths only, never values.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path("Path prefix to list (e.g. 'env/myapp'). Use empty string for root.")
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "zvault_describe_secret",
+            "description": "Get metadata about a secret (version, created_at, keys). Never returns actual values.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path("Full secret path (e.g. 'env/myapp/DATABASE_URL')")
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "zvault_generate_env_template",
+            "description": "Generate a .env.zvault template from secrets stored under a project path.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": path("Project name / path prefix (e.g. 'myapp')")
+                },
+                "required": ["project"]
+            }
+        },
+        {
+            "name": "zvault_set_secret",
+            "description": "Store a secret value in the vault. Use this when the user asks to save a new secret.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path("Secret path (e.g. 'env/myapp/API_KEY')"),
+                    "value": path("The secret value to store")
+                },
+                "required": ["path", "value"]
+            }
+        },
+        {
+            "name": "zvault_delete_secret",
+            "description": "Delete a secret from the vault.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path("Secret path to delete")
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "zvault_vault_status",
+            "description": "Check vault health: sealed/unsealed, initialized, version.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }
+    ])
+}
+
+async fn run_tool(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    name: &str,
+    args: &Value,
+) -> Result<String, AppError> {
+    match name {
+        "zvault_list_secrets" => {
+            let path = args.get("path").and_then(Value::as_str).unwrap_or("");
+            tool_list_secrets(state, auth, path.trim_matches('/')).await
+        }
+        "zvault_describe_secret" => {
+            tool_describe_secret(state, auth, required(args, "path")?).await
+        }
+        "zvault_generate_env_template" => {
+            tool_generate_env_template(state, auth, required(args, "project")?).await
+        }
+        "zvault_set_secret" => {
+            let path = required(args, "path")?;
+            tool_set_secret(state, auth, path, required(args, "value")?).await
+        }
+        "zvault_delete_secret" => tool_delete_secret(state, auth, required(args, "path")?).await,
+        "zvault_vault_status" => tool_vault_status(state).await,
+        _ => Err(AppError::BadRequest(format!("unknown tool: {name}"))),
+    }
+}
+
+async fn tool_list_secrets(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    path: &str,
+) -> Result<String, AppError> {
+    let keys = list_keys(state, auth, path).await?;
+    if keys.is_empty() {
+        return Ok(format!("No secrets found under '{path}'."));
+    }
+
+    let mut out = format!("Secrets under '{path}' ({} keys):\n", keys.len());
+    for key in &keys {
+        let _ = writeln!(out, "  • {key}");
+    }
+    Ok(out)
+}
+
+async fn tool_describe_secret(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    path: &str,
+) -> Result<String, AppError> {
+    // Read the secret for its key names only; the values are never returned.
+    let secret = secrets::read_secret(
+        State(Arc::clone(state)),
+        Extension(auth.clone()),
+        Extension(KvMount::default()),
+        Path(path.to_owned()),
+    )
+    .await?
+    .0;
+    let key_names: Vec<&str> = secret
+        .data
+        .as_ref()
+        .and_then(|d| d.get("data"))
+        .and_then(Value::as_object)
+        .map(|obj| obj.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    let keys_display = if key_names.is_empty() {
+        "(none)".to_owned()
+    } else {
+        key_names.join(", ")
+    };
+
+    let mut out = format!("Secret: {path}\n");
+    let _ = writeln!(out, "  Keys: {keys_display}");
+
+    // Metadata needs its own capability; describe what the token may see.
+    if let Ok(Json(meta)) = secrets::get_metadata(
+        State(Arc::clone(state)),
+        Extension(auth.clone()),
+        Extension(KvMount::default()),
+        Path(path.to_owned()),
+    )
+    .await
+    {
+        let _ = writeln!(out, "  Current version: {}", meta.current_version);
+        let _ = writeln!(out, "  Created: {}", meta.created_at);
+        let _ = writeln!(out, "  Updated: {}", meta.updated_at);
+    }
+
+    out.push_str("  Values: [REDACTED — use `zvault run` to inject at runtime]\n");
+    Ok(out)
+}
+
+async fn tool_generate_env_template(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    project: &str,
+) -> Result<String, AppError> {
+    let keys = list_keys(state, auth, &format!("env/{project}")).await?;
+    if keys.is_empty() {
+        return Ok(format!("No secrets found under 'env/{project}'."));
+    }
+
+    let mut out = format!(
+        "# Generated .env.zvault template for project '{project}'\n\
+         # Safe to commit — contains only vault references, no real values.\n\n"
+    );
+    for key in &keys {
+        let clean = key.trim_end_matches('/');
+        let _ = writeln!(out, "{clean}=zvault://env/{project}/{clean}");
+    }
+    Ok(out)
+}
+
+async fn tool_set_secret(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    path: &str,
+    value: &str,
+) -> Result<String, AppError> {
+    let key_name = path.rsplit('/').next().unwrap_or("value");
+    let _ = secrets::write_secret(
+        State(Arc::clone(state)),
+        Extension(auth.clone()),
+        Extension(KvMount::default()),
+        Path(path.to_owned()),
+        Json(json!({ key_name: value })),
+    )
+    .await?;
+
+    // SECURITY: Confirm storage without echoing the value.
+    Ok(format!(
+        "Secret stored at '{path}' (key: {key_name}). Value: [REDACTED]"
+    ))
+}
+
+async fn tool_delete_secret(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    path: &str,
+) -> Result<String, AppError> {
+    secrets::delete_secret(
+        State(Arc::clone(state)),
+        Extension(auth.clone()),
+        Extension(KvMount::default()),
+        Path(path.to_owned()),
+    )
+    .await?;
+    Ok(format!("Secret at '{path}' deleted."))
+}
+
+async fn tool_vault_status(state: &AppState) -> Result<String, AppError> {
+    let status = state.seal_manager.status().await?;
+
+    let mut out = String::from("ZVault Status:\n");
+    let _ = writeln!(out, "  Initialized: {}", status.initialized);
+    let _ = writeln!(out, "  Sealed: {}", status.sealed);
+    let _ = writeln!(
+        out,
+        "  Shares: {}, Threshold: {}",
+        status.shares, status.threshold
+    );
+    let _ = writeln!(out, "  Seal type: {}", status.seal_type);
+    let _ = writeln!(out, "  Version: {}", env!("CARGO_PKG_VERSION"));
+    Ok(out)
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// The keys under `path` (the whole mount when empty), relative to it.
+async fn list_keys(
+    state: &Arc<AppState>,
+    auth: &AuthContext,
+    path: &str,
+) -> Result<Vec<String>, AppError> {
+    let listing = if path.is_empty() {
+        secrets::list_all_secrets(
+            State(Arc::clone(state)),
+            Extension(auth.clone()),
+            Extension(KvMount::default()),
+        )
+        .await?
+    } else {
+        secrets::list_secrets(
+            State(Arc::clone(state)),
+            Extension(auth.clone()),
+            Extension(KvMount::default()),
+            Path(path.to_owned()),
+        )
+        .await?
+    };
+    Ok(listing
+        .0
+        .data
+        .as_ref()
+        .and_then(|d| d.get("keys"))
+        .and_then(Value::as_array)
+        .map(|keys| {
+            keys.iter()
+                .filter_map(Value::as_str)
+                .map(|key| key.trim_start_matches('/').to_owned())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// A required string argument.
+fn required<'a>(args: &'a Value, name: &str) -> Result<&'a str, AppError> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| AppError::BadRequest(format!("missing required parameter: {name}")))
+}
+
+/// The audit operation of a tool call.
+fn operation(tool: &str) -> &'static str {
+    match tool {
+        "zvault_set_secret" => "write",
+        "zvault_delete_secret" => "delete",
+        _ => "read",
+    }
+}
+
+/// Tool arguments with the value of a secret being set replaced.
+fn redact(args: &Value) -> Value {
+    let mut args = args.clone();
+    if let Some(value) = args.get_mut("value") {
+        *value = "[redacted]".into();
+    }
+    args
+}
+
+/// One SSE session's stream: the `endpoint` event, then a `message` event
+/// per response.
+struct SessionStream {
+    state: Arc<AppState>,
+    rx: mpsc::Receiver<Value>,
+    token: String,
+    id: String,
+    endpoint_sent: bool,
+}
+
+impl SessionStream {
+    /// The next event to send, or `None` to end the stream.
+    async fn next(&mut self) -> Option<Event> {
+        if !self.endpoint_sent {
+            self.endpoint_sent = true;
+            return Some(
+                Event::default()
+                    .event("endpoint")
+                    .data(format!("/v1/mcp/messages?session_id={}", self.id)),
+            );
+        }
+        loop {
+            tokio::select! {
+                reply = self.rx.recv() => {
+                    return Event::default().event("message").json_data(reply?).ok();
+                }
+                () = tokio::time::sleep(TOKEN_CHECK_INTERVAL) => {
+                    if self.state.token_store.lookup(&self.token).await.is_err() {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SessionStream {
+    /// Forget the session once its client disconnects.
+    fn drop(&mut self) {
+        let state = Arc::clone(&self.state);
+        let id = std::mem::take(&mut self.id);
+        tokio::spawn(async move {
+            state.mcp_sessions.write().await.remove(&id);
+        });
+    }
+}
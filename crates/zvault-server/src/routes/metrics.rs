@@ -1,16 +1,24 @@
 //! Prometheus metrics endpoint: `/v1/sys/metrics`
 //!
 //! Exposes vault health and operational metrics in Prometheus text format.
-//! No authentication required — designed for Prometheus scraping.
+//! No authentication required by default — designed for Prometheus
+//! scraping. With `ZVAULT_METRICS_REQUIRE_AUTH=true` the route sits behind
+//! the auth middleware and the token needs `read` on `sys/metrics`.
 
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
 use std::sync::Arc;
 
-use axum::Router;
 use axum::extract::State;
 use axum::http::header;
 use axum::response::IntoResponse;
 use axum::routing::get;
+use axum::{Extension, Router};
+use zvault_core::metrics::{self, escape, header as help};
+use zvault_core::policy::Capability;
 
+use crate::error::AppError;
+use crate::middleware::AuthContext;
 use crate::state::AppState;
 
 /// Build the `/v1/sys/metrics` router.
@@ -24,11 +32,24 @@ pub fn router() -> Router<Arc<AppState>> {
 /// - `zvault_sealed` (gauge): 1 if sealed, 0 if unsealed
 /// - `zvault_initialized` (gauge): 1 if initialized
 /// - `zvault_lease_count` (gauge): total active leases
+/// - `zvault_lease_engine_count` (gauge): active leases by `engine` mount
 /// - `zvault_lease_expired_count` (gauge): expired leases pending cleanup
 /// - `zvault_mount_count` (gauge): number of mounted engines
 /// - `zvault_info` (gauge): build info label
-async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let mut lines = Vec::with_capacity(32);
+/// - barrier, storage, token, and HTTP request metrics from
+///   [`zvault_core::metrics`]
+async fn prometheus_metrics(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(Extension(auth)) = auth {
+        state
+            .policy_store
+            .check(&auth.policies, "sys/metrics", &Capability::Read)
+            .await?;
+    }
+
+    let mut out = String::with_capacity(16 * 1024);
 
     // Seal status.
     let (initialized, sealed) = match state.seal_manager.status().await {
@@ -36,58 +57,98 @@ async fn prometheus_metrics(State(state): State<Arc<AppState>>) -> impl IntoResp
         Err(_) => (false, true),
     };
 
-    lines.push("# HELP zvault_initialized Whether the vault has been initialized.".to_owned());
-    lines.push("# TYPE zvault_initialized gauge".to_owned());
-    lines.push(format!("zvault_initialized {}", u8::from(initialized)));
-
-    lines.push("# HELP zvault_sealed Whether the vault is currently sealed.".to_owned());
-    lines.push("# TYPE zvault_sealed gauge".to_owned());
-    lines.push(format!("zvault_sealed {}", u8::from(sealed)));
-
-    // Lease counts (only if unsealed).
+    help(
+        &mut out,
+        "zvault_initialized",
+        "gauge",
+        "Whether the vault has been initialized.",
+    );
+    let _ = writeln!(out, "zvault_initialized {}", u8::from(initialized));
+    help(
+        &mut out,
+        "zvault_sealed",
+        "gauge",
+        "Whether the vault is currently sealed.",
+    );
+    let _ = writeln!(out, "zvault_sealed {}", u8::from(sealed));
+
+    // Lease and mount counts (only if unsealed).
     if !sealed {
-        let (total, expired) = match state.lease_manager.list_all().await {
-            Ok(leases) => {
-                let expired = leases.iter().filter(|l| l.is_expired()).count();
-                (leases.len(), expired)
-            }
-            Err(_) => (0, 0),
-        };
-
-        lines.push("# HELP zvault_lease_count Total number of active leases.".to_owned());
-        lines.push("# TYPE zvault_lease_count gauge".to_owned());
-        lines.push(format!("zvault_lease_count {total}"));
+        render_leases(&state, &mut out).await;
 
-        lines.push(
-            "# HELP zvault_lease_expired_count Number of expired leases pending cleanup."
-                .to_owned(),
-        );
-        lines.push("# TYPE zvault_lease_expired_count gauge".to_owned());
-        lines.push(format!("zvault_lease_expired_count {expired}"));
-
-        // Mount count.
         let mount_count = state.mount_manager.list().await.len();
-
-        lines.push("# HELP zvault_mount_count Number of mounted secret engines.".to_owned());
-        lines.push("# TYPE zvault_mount_count gauge".to_owned());
-        lines.push(format!("zvault_mount_count {mount_count}"));
+        help(
+            &mut out,
+            "zvault_mount_count",
+            "gauge",
+            "Number of mounted secret engines.",
+        );
+        let _ = writeln!(out, "zvault_mount_count {mount_count}");
     }
 
+    metrics::global().render(&mut out).await;
+
     // Build info.
-    lines.push("# HELP zvault_info ZVault build information.".to_owned());
-    lines.push("# TYPE zvault_info gauge".to_owned());
-    lines.push(format!(
+    help(
+        &mut out,
+        "zvault_info",
+        "gauge",
+        "ZVault build information.",
+    );
+    let _ = writeln!(
+        out,
         "zvault_info{{version=\"{}\"}} 1",
         env!("CARGO_PKG_VERSION")
-    ));
-
-    let body = lines.join("\n") + "\n";
+    );
 
-    (
+    Ok((
         [(
             header::CONTENT_TYPE,
             "text/plain; version=0.0.4; charset=utf-8",
         )],
-        body,
-    )
+        out,
+    ))
+}
+
+/// Lease totals, and counts per engine mount (the first segment of the
+/// lease's engine path, e.g. `database/`).
+async fn render_leases(state: &AppState, out: &mut String) {
+    let leases = state.lease_manager.list_all().await.unwrap_or_default();
+    let expired = leases.iter().filter(|l| l.is_expired()).count();
+    let mut by_engine: BTreeMap<&str, usize> = BTreeMap::new();
+    for lease in &leases {
+        let engine = lease
+            .engine_path
+            .find('/')
+            .map_or(lease.engine_path.as_str(), |i| &lease.engine_path[..=i]);
+        *by_engine.entry(engine).or_default() += 1;
+    }
+
+    help(
+        out,
+        "zvault_lease_count",
+        "gauge",
+        "Total number of active leases.",
+    );
+    let _ = writeln!(out, "zvault_lease_count {}", leases.len());
+    help(
+        out,
+        "zvault_lease_engine_count",
+        "gauge",
+        "Number of active leases by engine mount.",
+    );
+    for (engine, count) in by_engine {
+        let _ = writeln!(
+            out,
+            "zvault_lease_engine_count{{engine=\"{}\"}} {count}",
+            escape(engine)
+        );
+    }
+    help(
+        out,
+        "zvault_lease_expired_count",
+        "gauge",
+        "Number of expired leases pending cleanup.",
+    );
+    let _ = writeln!(out, "zvault_lease_expired_count {expired}");
 }
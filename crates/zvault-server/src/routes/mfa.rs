@@ -0,0 +1,332 @@
+//! MFA routes: `/v1/sys/mfa/*`
+//!
+//! Configures TOTP methods and login enforcements, and enrolls identities.
+//! An identity is a token's entity ID, or its display name when it has none.
+//! Self-enrollment (`generate`) is for the calling token's identity;
+//! `admin-generate` and `admin-destroy` act on any identity, which is how an
+//! `AppRole` or JWT identity gets its secret before its logins require MFA.
+//!
+//! Codes are supplied in the `X-Vault-MFA` header (`method:code`, comma
+//! separated). The auth middleware validates the header on authenticated
+//! requests for policy step-up, and the approle and jwt login handlers call
+//! [`enforce_login`].
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::error::MfaError;
+use zvault_core::identity::ENTITY_ID_METADATA;
+use zvault_core::mfa::{
+    self, LoginEnforcement, MFA_HEADER, TotpAlgorithm, TotpKey, TotpMethod, parse_credentials,
+};
+use zvault_core::policy::Capability;
+use zvault_core::token::TokenEntry;
+
+/// Build the `/v1/sys/mfa` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/mfa/method/totp` — list TOTP methods
+/// - `POST /v1/sys/mfa/method/totp/{name}` — create or update a TOTP method
+/// - `GET  /v1/sys/mfa/method/totp/{name}` — read a TOTP method
+/// - `DELETE /v1/sys/mfa/method/totp/{name}` — delete a method and its enrollments
+/// - `POST /v1/sys/mfa/method/totp/{name}/generate` — enroll the calling identity
+/// - `POST /v1/sys/mfa/method/totp/{name}/admin-generate` — enroll `{"identity"}`
+/// - `POST /v1/sys/mfa/method/totp/{name}/admin-destroy` — remove `{"identity"}`'s secret
+/// - `GET  /v1/sys/mfa/login-enforcement` — list login enforcements
+/// - `POST|GET|DELETE /v1/sys/mfa/login-enforcement/{name}` — manage one
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/method/totp", get(list_methods))
+        .route(
+            "/method/totp/{name}",
+            post(put_method).get(get_method).delete(delete_method),
+        )
+        .route("/method/totp/{name}/generate", post(generate))
+        .route("/method/totp/{name}/admin-generate", post(admin_generate))
+        .route("/method/totp/{name}/admin-destroy", post(admin_destroy))
+        .route("/login-enforcement", get(list_enforcements))
+        .route(
+            "/login-enforcement/{name}",
+            post(put_enforcement)
+                .get(get_enforcement)
+                .delete(delete_enforcement),
+        )
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct TotpMethodRequest {
+    /// Issuer shown by authenticator apps.
+    pub issuer: String,
+    #[serde(default)]
+    pub period_secs: Option<u64>,
+    #[serde(default)]
+    pub digits: Option<u32>,
+    #[serde(default)]
+    pub algorithm: Option<TotpAlgorithm>,
+    #[serde(default)]
+    pub skew: Option<u32>,
+    #[serde(default)]
+    pub key_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdentityRequest {
+    /// Entity ID or token display name, e.g. `approle-ci`.
+    pub identity: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnforcementRequest {
+    pub mfa_methods: Vec<String>,
+    pub auth_methods: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListResponse {
+    pub keys: Vec<String>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+async fn list_methods(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<ListResponse>, AppError> {
+    check(&state, &auth, "sys/mfa/method/totp", Capability::List).await?;
+    let keys = state.mfa.list_totp_methods().await?;
+    Ok(Json(ListResponse { keys }))
+}
+
+async fn put_method(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<TotpMethodRequest>,
+) -> Result<Json<TotpMethod>, AppError> {
+    check(&state, &auth, &method_path(&name), Capability::Update).await?;
+    let mut method = TotpMethod::new(&name, &body.issuer);
+    if let Ok(existing) = state.mfa.get_totp_method(&name).await {
+        method.created_at = existing.created_at;
+    }
+    method.period_secs = body.period_secs.unwrap_or(method.period_secs);
+    method.digits = body.digits.unwrap_or(method.digits);
+    method.algorithm = body.algorithm.unwrap_or(method.algorithm);
+    method.skew = body.skew.unwrap_or(method.skew);
+    method.key_size = body.key_size.unwrap_or(method.key_size);
+    state.mfa.put_totp_method(&method).await?;
+    Ok(Json(method))
+}
+
+async fn get_method(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<TotpMethod>, AppError> {
+    check(&state, &auth, &method_path(&name), Capability::Read).await?;
+    Ok(Json(state.mfa.get_totp_method(&name).await?))
+}
+
+async fn delete_method(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &method_path(&name), Capability::Delete).await?;
+    state.mfa.delete_totp_method(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Enroll the calling token's identity.
+async fn generate(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<TotpKey>, AppError> {
+    let path = format!("{}/generate", method_path(&name));
+    check(&state, &auth, &path, Capability::Update).await?;
+    let identity = mfa::identity(auth.entity_id.as_deref(), &auth.display_name);
+    Ok(Json(state.mfa.generate_totp(&name, identity).await?))
+}
+
+async fn admin_generate(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<IdentityRequest>,
+) -> Result<Json<TotpKey>, AppError> {
+    let path = format!("{}/admin-generate", method_path(&name));
+    check(&state, &auth, &path, Capability::Update).await?;
+    Ok(Json(state.mfa.generate_totp(&name, &body.identity).await?))
+}
+
+async fn admin_destroy(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<IdentityRequest>,
+) -> Result<StatusCode, AppError> {
+    let path = format!("{}/admin-destroy", method_path(&name));
+    check(&state, &auth, &path, Capability::Update).await?;
+    state.mfa.destroy_totp(&name, &body.identity).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_enforcements(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<ListResponse>, AppError> {
+    check(&state, &auth, "sys/mfa/login-enforcement", Capability::List).await?;
+    let keys = state.mfa.list_login_enforcements().await?;
+    Ok(Json(ListResponse { keys }))
+}
+
+async fn put_enforcement(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<EnforcementRequest>,
+) -> Result<Json<LoginEnforcement>, AppError> {
+    check(&state, &auth, &enforcement_path(&name), Capability::Update).await?;
+    let enforcement = LoginEnforcement {
+        name,
+        mfa_methods: body.mfa_methods,
+        auth_methods: body.auth_methods,
+    };
+    state.mfa.put_login_enforcement(&enforcement).await?;
+    Ok(Json(enforcement))
+}
+
+async fn get_enforcement(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<LoginEnforcement>, AppError> {
+    check(&state, &auth, &enforcement_path(&name), Capability::Read).await?;
+    Ok(Json(state.mfa.get_login_enforcement(&name).await?))
+}
+
+async fn delete_enforcement(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &enforcement_path(&name), Capability::Delete).await?;
+    state.mfa.delete_login_enforcement(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Enforcement ──────────────────────────────────────────────────────
+
+/// Validate every credential in the `X-Vault-MFA` header for `identity`,
+/// returning the methods that passed. No header means no methods.
+///
+/// # Errors
+///
+/// Fails on a malformed header or any wrong, reused, or unenrolled code.
+pub async fn verify_credentials(
+    state: &AppState,
+    identity: &str,
+    headers: &HeaderMap,
+) -> Result<Vec<String>, AppError> {
+    let Some(header) = headers.get(MFA_HEADER) else {
+        return Ok(Vec::new());
+    };
+    let header = header
+        .to_str()
+        .map_err(|_| AppError::BadRequest(format!("{MFA_HEADER} must be ASCII")))?;
+    let mut verified = Vec::new();
+    for (method, code) in parse_credentials(header)? {
+        state
+            .mfa
+            .validate(&method, identity, &code, Utc::now())
+            .await?;
+        verified.push(method);
+    }
+    Ok(verified)
+}
+
+/// Require the MFA that login enforcements set for `auth_method`.
+///
+/// Runs once the credentials have been accepted, so the identity is known.
+/// A login that fails MFA has its new token revoked before the error is
+/// returned; the token never reaches the caller.
+///
+/// # Errors
+///
+/// Returns 403 `mfa_required` if a required method's code is missing or
+/// invalid.
+pub async fn enforce_login(
+    state: &AppState,
+    auth_method: &str,
+    headers: &HeaderMap,
+    token: &str,
+    entry: &TokenEntry,
+) -> Result<(), AppError> {
+    let result = check_login(state, auth_method, headers, entry).await;
+    if result.is_err() {
+        if let Err(e) = state.token_store.revoke(token).await {
+            tracing::warn!(error = %e, "failed to revoke token after failed login MFA");
+        }
+    }
+    result
+}
+
+async fn check_login(
+    state: &AppState,
+    auth_method: &str,
+    headers: &HeaderMap,
+    entry: &TokenEntry,
+) -> Result<(), AppError> {
+    let required = state.mfa.login_methods(auth_method).await?;
+    if required.is_empty() {
+        return Ok(());
+    }
+    let identity = mfa::identity(
+        entry.metadata.get(ENTITY_ID_METADATA).map(String::as_str),
+        &entry.display_name,
+    );
+    let verified = verify_credentials(state, identity, headers).await?;
+    let missing: Vec<String> = required
+        .into_iter()
+        .filter(|m| !verified.contains(m))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(MfaError::Required { methods: missing }.into())
+    }
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &capability)
+        .await?;
+    Ok(())
+}
+
+fn method_path(name: &str) -> String {
+    format!("sys/mfa/method/totp/{name}")
+}
+
+fn enforcement_path(name: &str) -> String {
+    format!("sys/mfa/login-enforcement/{name}")
+}
@@ -0,0 +1,207 @@
+//! Migration routes: `/v1/sys/migrate/*`
+//!
+//! Bulk import of AWS Secrets Manager secrets or SSM parameters into a KV
+//! mount, and export of KV secrets to them. Every KV path a migration
+//! touches is checked against the caller's policies before anything is
+//! read out or written, and `dry_run` returns the plan without writing.
+//! AWS credentials in the request are used for that request only; without
+//! them the server's own `AWS_*` environment is used.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::routes::secrets::publish_kv_event;
+use crate::state::AppState;
+use zvault_core::aws_migrate::{
+    self, AwsConnection, AwsCredentials, AwsStore, ConflictStrategy, ExportParams, ImportParams,
+    MigrationAction, MigrationReport,
+};
+use zvault_core::engine::KvEngine;
+use zvault_core::events::TOPIC_KV_WRITE;
+use zvault_core::policy::Capability;
+
+/// Build the `/v1/sys/migrate` router.
+///
+/// Paths:
+/// - `POST /v1/sys/migrate/aws/import` — copy AWS secrets into KV
+/// - `POST /v1/sys/migrate/aws/export` — copy KV secrets to AWS
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/aws/import", post(import_aws))
+        .route("/aws/export", post(export_aws))
+}
+
+// ── Request types ────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct AwsMigrateRequest {
+    pub store: AwsStore,
+    /// AWS region. Omit to use the server's `AWS_REGION`.
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Omit to use the server's AWS credentials.
+    #[serde(default)]
+    pub credentials: Option<AwsCredentials>,
+    /// KV mount (default `secret/`).
+    #[serde(default)]
+    pub mount: Option<String>,
+    /// AWS name prefix to import from or export under.
+    #[serde(default)]
+    pub prefix: String,
+    /// KV path to import under or export from.
+    #[serde(default)]
+    pub path: String,
+    /// KMS key for exported secrets and parameters.
+    #[serde(default)]
+    pub kms_key_id: Option<String>,
+    #[serde(default)]
+    pub conflict: ConflictStrategy,
+    /// Return the plan without writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+async fn import_aws(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<AwsMigrateRequest>,
+) -> Result<Json<MigrationReport>, AppError> {
+    check(&state, &auth, "sys/migrate/aws/import", Capability::Update).await?;
+    let (mount, engine) = kv_engine(&state, body.mount.as_deref()).await?;
+    let connection = connection(&body)?;
+
+    let plan = aws_migrate::plan_import(
+        &connection,
+        &engine,
+        &ImportParams {
+            prefix: body.prefix,
+            path: body.path,
+            conflict: body.conflict,
+        },
+    )
+    .await?;
+    for entry in plan.entries() {
+        if matches!(
+            entry.action,
+            MigrationAction::Create | MigrationAction::Update
+        ) {
+            let path = format!("{mount}data/{}", entry.kv_path);
+            check(&state, &auth, &path, Capability::Create).await?;
+        }
+    }
+    for kv_path in plan.tagged_paths() {
+        let path = format!("{mount}metadata/{kv_path}");
+        check(&state, &auth, &path, Capability::Update).await?;
+    }
+    if body.dry_run {
+        return Ok(Json(plan.report()));
+    }
+
+    let report = plan.execute(&engine).await;
+    for entry in &report.entries {
+        if matches!(
+            entry.action,
+            MigrationAction::Create | MigrationAction::Update
+        ) {
+            publish_kv_event(&state, TOPIC_KV_WRITE, &mount, &entry.kv_path, None);
+        }
+    }
+    Ok(Json(report))
+}
+
+async fn export_aws(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<AwsMigrateRequest>,
+) -> Result<Json<MigrationReport>, AppError> {
+    check(&state, &auth, "sys/migrate/aws/export", Capability::Update).await?;
+    let (mount, engine) = kv_engine(&state, body.mount.as_deref()).await?;
+    let connection = connection(&body)?;
+
+    let plan = aws_migrate::plan_export(
+        &connection,
+        &engine,
+        &ExportParams {
+            path: body.path,
+            prefix: body.prefix,
+            kms_key_id: body.kms_key_id,
+            conflict: body.conflict,
+        },
+    )
+    .await?;
+    // Planning compares values, so every secret read needs `read`, even
+    // those left alone.
+    let read: BTreeSet<&str> = plan.entries().iter().map(|e| e.kv_path.as_str()).collect();
+    for kv_path in read {
+        let path = format!("{mount}data/{kv_path}");
+        check(&state, &auth, &path, Capability::Read).await?;
+    }
+    if body.dry_run {
+        return Ok(Json(plan.report()));
+    }
+    Ok(Json(plan.execute(&engine).await))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &capability)
+        .await?;
+    Ok(())
+}
+
+/// The KV engine at `mount` (default `secret/`), with the mount path.
+async fn kv_engine(
+    state: &AppState,
+    mount: Option<&str>,
+) -> Result<(String, Arc<KvEngine>), AppError> {
+    let mount = format!("{}/", mount.unwrap_or("secret").trim_matches('/'));
+    let engine = state
+        .engines
+        .get::<KvEngine>(&mount)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("no KV engine mounted at '{mount}'")))?;
+    Ok((mount, engine))
+}
+
+/// The store, region, and credentials of a request, falling back to the
+/// server's environment.
+fn connection(body: &AwsMigrateRequest) -> Result<AwsConnection, AppError> {
+    let region = body
+        .region
+        .clone()
+        .filter(|r| !r.is_empty())
+        .or_else(|| std::env::var("AWS_REGION").ok())
+        .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok())
+        .ok_or_else(|| AppError::BadRequest("an AWS region is required".to_owned()))?;
+    let credentials = body
+        .credentials
+        .clone()
+        .or_else(AwsCredentials::from_env)
+        .ok_or_else(|| {
+            AppError::BadRequest(
+                "no AWS credentials were given and none are configured on this server".to_owned(),
+            )
+        })?;
+    Ok(AwsConnection {
+        store: body.store,
+        region,
+        credentials,
+    })
+}
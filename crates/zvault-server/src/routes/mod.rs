@@ -2,26 +2,76 @@
 //!
 //! Routes are organized by subsystem:
 //! - `sys`: System operations (init, seal, unseal, health)
+//! - `access_requests`: Just-in-time access requests
+//! - `activity`: Client activity counters
+//! - `audit`: Audit device management
 //! - `auth`: Token authentication (create, lookup, renew, revoke)
+//! - `cert`: TLS client certificate auth
+//! - `cloud_link`: Service token exchange with a linked cloud org
+//! - `control_group`: Multi-party approval of parked requests
+//! - `cubbyhole`: Per-token private storage
+//! - `events`: Server-Sent Events subscriptions to vault events
+//! - `identity`: Entities, aliases, and groups
+//! - `jwt`: JWT auth for CI/OIDC token login
+//! - `keyring`: Barrier encryption key rotation and status
+//! - `mcp`: Model Context Protocol over HTTP and SSE for remote AI agents
 //! - `policy`: Policy CRUD
+//! - `quotas`: Rate limit and lease count quotas
+//! - `replication`: Change streaming from a primary to read replicas
 //! - `mounts`: Engine mount management
+//! - `notifications`: Webhook notifications of vault events
 //! - `leases`: Lease lifecycle
+//! - `license`: License activation and feature gating status
+//! - `mfa`: MFA methods, enrollment, and login enforcement
+//! - `migrate`: Bulk import and export with AWS Secrets Manager and SSM Parameter Store
+//! - `plugins`: External secrets engine plugin catalog and plugin mounts
+//! - `rotation`: Scheduled and on-demand secret rotation
+//! - `secret_usage`: Per-secret read counters
 //! - `secrets`: Secret read/write through mounted engines
+//! - `sync`: Secret sync to GitHub Actions secrets and Kubernetes Secrets, with drift detection
 //! - `ui`: Landing page and web UI
+//! - `well_known`: Discovery document for SDK/agent/CLI autoconfiguration
+//! - `wrapping`: Unwrap, look up, and rewrap response-wrapping tokens
 //! - `dashboard`: Page content constants for the dashboard app
 
+pub mod access_requests;
+pub mod activity;
 pub mod approle;
+pub mod audit;
 pub mod auth;
+pub mod cert;
+#[cfg(feature = "cloud")]
+pub mod cloud_link;
+pub mod control_group;
+pub mod cubbyhole;
 pub mod database;
 pub mod docs;
+pub mod events;
+pub mod identity;
+pub mod jwt;
+pub mod keyring;
 pub mod leases;
+pub mod license;
+pub mod mcp;
 pub mod metrics;
+pub mod mfa;
+pub mod migrate;
 pub mod mounts;
+pub mod notifications;
 #[cfg(feature = "spring-oauth")]
 pub mod oidc;
 pub mod pki;
+pub mod pki_acme;
+pub mod plugins;
 pub mod policy;
+pub mod quotas;
+pub mod replication;
+pub mod rotation;
+pub mod secret_usage;
 pub mod secrets;
+pub mod sync;
 pub mod sys;
 pub mod transit;
 pub mod ui;
+pub mod well_known;
+pub mod wrapping;
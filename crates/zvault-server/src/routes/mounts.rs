@@ -1,21 +1,72 @@
 //! Engine mount management routes: `/v1/sys/mounts/*`
 //!
-//! Mount, unmount, and list secrets engines.
+//! Mount, unmount, and list secrets engines, and move a single KV mount's
+//! data between vaults with export/import (see
+//! [`zvault_core::mount_transfer`]).
+//!
+//! `kv`, `transit`, and `database` mount a built-in engine, so a team can
+//! get its own `transit-team-a/` or `database-team-a/` next to the default
+//! mounts. Any other engine type mounts an external plugin from the plugin
+//! catalog (see [`super::plugins`]).
+//!
+//! Every mount is served at `/v1/{mount}/...` as soon as it is created: the
+//! engine instance goes into the state's engine registry, and requests
+//! without a more specific route are resolved through the mount table by
+//! [`mount_router`]. Unmounting revokes the mount's leases and, with
+//! `?purge=true`, deletes everything the engine stored.
+//!
+//! Besides global grants on `sys/mounts`, a token with `sudo` on a mount's
+//! subtree (delegated admin) may mount, tune, and unmount that path, and sees
+//! it when listing.
+//!
+//! Mounts declared in the configuration file are created on unseal, and on
+//! reload, when they do not exist yet.
 
 use std::sync::Arc;
 
-use axum::extract::{Path, State};
+use axum::extract::{DefaultBodyLimit, Path, Query, Request, State};
+use axum::handler::Handler;
 use axum::http::StatusCode;
-use axum::routing::{delete, get, post};
+use axum::response::Response;
+use axum::routing::{any, delete, get, post};
 use axum::{Extension, Json, Router};
 use serde::{Deserialize, Serialize};
+use tower::ServiceExt;
 
 use crate::error::AppError;
 use crate::middleware::AuthContext;
+use crate::replication::Replication;
+use crate::routes::database::DatabaseMount;
+use crate::routes::secrets::KvMount;
+use crate::routes::sys::MAX_SNAPSHOT_BODY;
+use crate::routes::transit::TransitMount;
 use crate::state::AppState;
-use zvault_core::engine::KvEngine;
+use zvault_core::database::DatabaseEngine;
+use zvault_core::engine::{KvEngine, KvMountConfig};
+use zvault_core::error::PluginError;
+use zvault_core::lease::RevocationHandler;
 use zvault_core::mount::MountEntry;
+use zvault_core::mount_transfer::{
+    MountBundle, MountTransfer, encode_transfer_key, generate_transfer_key, parse_transfer_key,
+};
+use zvault_core::plugin::{ExternalPlugin, PLUGIN_ENGINE_TYPE};
 use zvault_core::policy::Capability;
+use zvault_core::registry::{self, Engine};
+use zvault_core::transit::TransitEngine;
+
+/// Engine types served by built-in routers rather than a plugin.
+const BUILTIN_ENGINE_TYPES: &[&str] = &["kv", "transit", "database"];
+
+/// Mount paths taken by built-in routes; an engine mounted there would never
+/// see a request.
+const RESERVED_MOUNTS: &[&str] = &[
+    "sys/",
+    "auth/",
+    "cubbyhole/",
+    "transit/",
+    "database/",
+    "pki/",
+];
 
 /// Build the `/v1/sys/mounts` router.
 pub fn router() -> Router<Arc<AppState>> {
@@ -23,6 +74,23 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/", get(list_mounts))
         .route("/{path}", post(mount_engine))
         .route("/{path}", delete(unmount_engine))
+        .route("/{path}/tune", post(tune_engine))
+        .route("/{path}/export", post(export_mount))
+        .route(
+            "/{path}/import",
+            post(import_mount).layer(DefaultBodyLimit::max(MAX_SNAPSHOT_BODY)),
+        )
+}
+
+/// Build the router serving mounts at `/v1/{mount}/{path}`.
+///
+/// Built-in engines have their own, more specific routes; everything else
+/// under `/v1/` lands here, is resolved through the mount table, and goes to
+/// the mount's engine: KV, transit, and database mounts to the
+/// [`super::secrets`], [`super::transit`], and [`super::database`] routers,
+/// and plugin mounts to [`super::plugins`].
+pub fn mount_router() -> Router<Arc<AppState>> {
+    Router::new().route("/v1/{*path}", any(dispatch))
 }
 
 // ── Request / Response types ─────────────────────────────────────────
@@ -37,6 +105,7 @@ pub struct MountEntryResponse {
     pub path: String,
     pub engine_type: String,
     pub description: String,
+    pub config: serde_json::Value,
 }
 
 #[derive(Debug, Deserialize)]
@@ -46,6 +115,45 @@ pub struct MountRequest {
     pub config: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct UnmountQuery {
+    /// Also delete all data the engine stored.
+    #[serde(default)]
+    pub purge: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TuneRequest {
+    pub config: serde_json::Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportRequest {
+    /// Base64 256-bit transfer key; generated when omitted.
+    pub transfer_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportResponse {
+    pub bundle: MountBundle,
+    /// The generated transfer key, present only when none was supplied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportRequest {
+    pub bundle: MountBundle,
+    pub transfer_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResponse {
+    pub path: String,
+    pub source_path: String,
+    pub entry_count: usize,
+}
+
 // ── Handlers ─────────────────────────────────────────────────────────
 
 /// List all mounted engines.
@@ -53,12 +161,30 @@ async fn list_mounts(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
 ) -> Result<Json<MountListResponse>, AppError> {
-    state
+    let entries = match state
         .policy_store
         .check(&auth.policies, "sys/mounts", &Capability::List)
-        .await?;
-
-    let entries = state.mount_manager.list().await;
+        .await
+    {
+        Ok(()) => state.mount_manager.list().await,
+        Err(err) => {
+            // Delegated admins only see the mounts they administer.
+            let mut visible = Vec::new();
+            for entry in state.mount_manager.list().await {
+                if state
+                    .policy_store
+                    .is_mount_admin(&auth.policies, &entry.path)
+                    .await?
+                {
+                    visible.push(entry);
+                }
+            }
+            if visible.is_empty() {
+                return Err(err.into());
+            }
+            visible
+        }
+    };
 
     let mounts = entries
         .into_iter()
@@ -66,6 +192,7 @@ async fn list_mounts(
             path: e.path,
             engine_type: e.engine_type,
             description: e.description,
+            config: e.config,
         })
         .collect();
 
@@ -79,68 +206,511 @@ async fn mount_engine(
     Path(path): Path<String>,
     Json(body): Json<MountRequest>,
 ) -> Result<StatusCode, AppError> {
+    let mount_path = if path.ends_with('/') {
+        path.clone()
+    } else {
+        format!("{path}/")
+    };
+
     state
         .policy_store
-        .check(&auth.policies, "sys/mounts", &Capability::Create)
+        .check_mount(
+            &auth.policies,
+            &mount_path,
+            "sys/mounts",
+            &Capability::Create,
+        )
         .await?;
 
-    // Validate engine type.
-    if body.engine_type != "kv" {
+    create_mount(
+        &state,
+        mount_path,
+        body.engine_type,
+        body.description.unwrap_or_default(),
+        body.config.unwrap_or(serde_json::Value::Null),
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Mount `engine_type` at `mount_path` (already ending in `/`) and serve it.
+async fn create_mount(
+    state: &AppState,
+    mount_path: String,
+    engine_type: String,
+    description: String,
+    config: serde_json::Value,
+) -> Result<(), AppError> {
+    if RESERVED_MOUNTS.contains(&mount_path.as_str()) {
         return Err(AppError::BadRequest(format!(
-            "unsupported engine type '{}', only 'kv' is supported",
-            body.engine_type
+            "'{mount_path}' is reserved for a built-in route"
         )));
     }
 
+    // Anything but a built-in engine must be a plugin, named directly or via
+    // `plugin_name`.
+    if !BUILTIN_ENGINE_TYPES.contains(&engine_type.as_str()) {
+        let config = plugin_config(state, &engine_type, config).await?;
+        let entry = MountEntry {
+            path: mount_path,
+            engine_type: PLUGIN_ENGINE_TYPE.to_owned(),
+            description,
+            config,
+        };
+        return super::plugins::mount(state, entry).await;
+    }
+
+    let entry = MountEntry {
+        path: mount_path,
+        engine_type,
+        description,
+        config,
+    };
+    let engine = builtin_engine(state, &entry)?;
+
+    state.mount_manager.mount(entry.clone()).await?;
+
+    // Register the engine instance; it is served right away.
+    serve_builtin(state, entry.path, engine).await;
+    Ok(())
+}
+
+/// Unmount a secrets engine, deleting its data with `?purge=true`.
+async fn unmount_engine(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+    Query(query): Query<UnmountQuery>,
+) -> Result<StatusCode, AppError> {
     let mount_path = if path.ends_with('/') {
         path.clone()
     } else {
         format!("{path}/")
     };
 
-    let entry = MountEntry {
-        path: mount_path.clone(),
-        engine_type: body.engine_type,
-        description: body.description.unwrap_or_default(),
-        config: body.config.unwrap_or(serde_json::Value::Null),
-    };
+    state
+        .policy_store
+        .check_mount(
+            &auth.policies,
+            &mount_path,
+            "sys/mounts",
+            &Capability::Delete,
+        )
+        .await?;
 
-    state.mount_manager.mount(entry).await?;
+    state.mount_manager.unmount(&mount_path).await?;
+    let engine = state.engines.remove(&mount_path).await;
 
-    // Create and register the KV engine instance.
-    let engine = Arc::new(KvEngine::new(
-        Arc::clone(&state.barrier),
-        format!("kv/{mount_path}"),
-    ));
-    state.kv_engines.write().await.insert(mount_path, engine);
+    // Revoke all leases for this mount while the engine can still clean up
+    // after them, then stop routing revocations to it.
+    let _ = state.lease_manager.revoke_prefix(&mount_path).await;
+    state.lease_manager.unregister_handler(&mount_path).await;
+
+    let Some(engine) = engine else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
+    if let Ok(plugin) = Arc::clone(&engine).into_any().downcast::<ExternalPlugin>() {
+        plugin.stop().await;
+    }
+    if query.purge {
+        // Transit keys may live in the HSM; destroy those objects first.
+        if let Ok(transit) = Arc::clone(&engine).into_any().downcast::<TransitEngine>() {
+            for name in transit.list_keys().await? {
+                transit.delete_key(&name).await?;
+            }
+        }
+        registry::purge(&state.barrier, engine.as_ref()).await?;
+    }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Unmount a secrets engine.
-async fn unmount_engine(
+/// Update the configuration of a mounted KV engine (e.g., enable read leases).
+async fn tune_engine(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
     Path(path): Path<String>,
+    Json(body): Json<TuneRequest>,
 ) -> Result<StatusCode, AppError> {
+    let mount_path = if path.ends_with('/') {
+        path.clone()
+    } else {
+        format!("{path}/")
+    };
+
+    state
+        .policy_store
+        .check_mount(
+            &auth.policies,
+            &mount_path,
+            "sys/mounts",
+            &Capability::Update,
+        )
+        .await?;
+
+    let kv_config = parse_kv_config(&body.config)?;
+
+    let existing = state
+        .engines
+        .get::<KvEngine>(&mount_path)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("no KV engine mounted at '{mount_path}'")))?;
+
+    state.mount_manager.tune(&mount_path, body.config).await?;
+
+    // Engines are immutable once shared, so swap in a reconfigured instance
+    // over the same storage prefix.
+    let engine = Arc::new(
+        KvEngine::new(Arc::clone(&state.barrier), existing.prefix().to_owned())
+            .with_config(kv_config),
+    );
+    state.engines.insert(mount_path, engine).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Export a KV mount's data sealed under a transfer key.
+///
+/// Exporting hands out every secret in the mount, so it needs `sudo` on
+/// `sys/mounts` or delegated admin over the mount.
+async fn export_mount(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+    body: Option<Json<ExportRequest>>,
+) -> Result<Json<ExportResponse>, AppError> {
+    let mount_path = if path.ends_with('/') {
+        path.clone()
+    } else {
+        format!("{path}/")
+    };
+
     state
         .policy_store
-        .check(&auth.policies, "sys/mounts", &Capability::Delete)
+        .check_mount(&auth.policies, &mount_path, "sys/mounts", &Capability::Sudo)
         .await?;
 
+    let prefix = state
+        .engines
+        .get::<KvEngine>(&mount_path)
+        .await
+        .map(|engine| engine.prefix().to_owned())
+        .ok_or_else(|| AppError::NotFound(format!("no KV engine mounted at '{mount_path}'")))?;
+    let entry = state
+        .mount_manager
+        .list()
+        .await
+        .into_iter()
+        .find(|e| e.path == mount_path)
+        .ok_or_else(|| AppError::NotFound(format!("no mount at '{mount_path}'")))?;
+
+    let Json(body) = body.unwrap_or_default();
+    let (key, generated) = if let Some(encoded) = body.transfer_key {
+        (parse_transfer_key(&encoded)?, None)
+    } else {
+        let key = generate_transfer_key();
+        let encoded = encode_transfer_key(&key);
+        (key, Some(encoded))
+    };
+
+    let bundle = MountTransfer::new(Arc::clone(&state.barrier))
+        .export(&entry, &prefix, &key)
+        .await?;
+
+    Ok(Json(ExportResponse {
+        bundle,
+        transfer_key: generated,
+    }))
+}
+
+/// Import an exported KV mount at a new, empty path.
+async fn import_mount(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+    Json(body): Json<ImportRequest>,
+) -> Result<Json<ImportResponse>, AppError> {
     let mount_path = if path.ends_with('/') {
         path.clone()
     } else {
         format!("{path}/")
     };
 
-    state.mount_manager.unmount(&mount_path).await?;
+    state
+        .policy_store
+        .check_mount(
+            &auth.policies,
+            &mount_path,
+            "sys/mounts",
+            &Capability::Create,
+        )
+        .await?;
 
-    // Remove the KV engine instance.
-    state.kv_engines.write().await.remove(&mount_path);
+    let key = parse_transfer_key(&body.transfer_key)?;
+    let opened = MountTransfer::open(&body.bundle, &key)?;
+    if opened.mount.engine_type != "kv" {
+        return Err(AppError::BadRequest(format!(
+            "unsupported engine type '{}', only 'kv' mounts can be imported",
+            opened.mount.engine_type
+        )));
+    }
+    let kv_config = parse_kv_config(&opened.mount.config)?;
+    let source_path = opened.mount.path.clone();
+    let entry_count = opened.entry_count();
 
-    // Revoke all leases for this mount.
-    let _ = state.lease_manager.revoke_prefix(&mount_path).await;
+    let prefix = format!("kv/{mount_path}");
+    MountTransfer::new(Arc::clone(&state.barrier))
+        .import(&state.mount_manager, opened, &mount_path, &prefix)
+        .await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    let engine = Arc::new(KvEngine::new(Arc::clone(&state.barrier), prefix).with_config(kv_config));
+    state.engines.insert(mount_path.clone(), engine).await;
+
+    Ok(Json(ImportResponse {
+        path: mount_path,
+        source_path,
+        entry_count,
+    }))
+}
+
+/// Bring back the mounts persisted before the server started sealed:
+/// built-in mounts get their engine again and plugin mounts are
+/// re-registered.
+pub(crate) async fn restore_mounts(state: &AppState) {
+    let restored = match state.mount_manager.reload().await {
+        Ok(restored) => restored,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to restore mounts");
+            return;
+        }
+    };
+
+    for entry in restored
+        .iter()
+        .filter(|e| BUILTIN_ENGINE_TYPES.contains(&e.engine_type.as_str()))
+    {
+        // The default transit and database engines are set up at startup,
+        // possibly with an HSM, and keep serving as they are.
+        if entry.engine_type != "kv" && state.engines.get_any(&entry.path).await.is_some() {
+            continue;
+        }
+        let Ok(engine) = builtin_engine(state, entry) else {
+            tracing::warn!(path = %entry.path, "invalid mount config, skipping");
+            continue;
+        };
+        serve_builtin(state, entry.path.clone(), engine).await;
+    }
+
+    super::plugins::restore(state, &restored).await;
+}
+
+/// Create the mounts declared in the configuration file that do not exist
+/// yet. Existing mounts are left as they are, and mounts dropped from the
+/// file are never removed. Replicas get their mounts from the primary.
+pub async fn ensure_config_mounts(state: &AppState) {
+    if matches!(state.replication, Some(Replication::Replica(_))) {
+        return;
+    }
+    let declared = state.config_file.read().await.mounts.clone();
+    let existing: Vec<String> = state
+        .mount_manager
+        .list()
+        .await
+        .into_iter()
+        .map(|e| e.path)
+        .collect();
+
+    for mount in declared {
+        let path = if mount.path.ends_with('/') {
+            mount.path
+        } else {
+            format!("{}/", mount.path)
+        };
+        if existing.contains(&path) {
+            continue;
+        }
+        match create_mount(
+            state,
+            path.clone(),
+            mount.engine_type,
+            mount.description,
+            mount.config,
+        )
+        .await
+        {
+            Ok(()) => tracing::info!(path = %path, "mount from configuration file created"),
+            Err(e) => tracing::warn!(path = %path, error = %e, "failed to create configured mount"),
+        }
+    }
+}
+
+/// Serve a request under a mount that has no routes of its own.
+async fn dispatch(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(path): Path<String>,
+    request: Request,
+) -> Result<Response, AppError> {
+    let not_found = || AppError::NotFound(format!("no handler for route '{path}'"));
+    let (entry, _) = state
+        .mount_manager
+        .resolve(&path)
+        .await
+        .ok_or_else(not_found)?;
+
+    match entry.engine_type.as_str() {
+        "kv" => {
+            let router = super::secrets::router();
+            forward(
+                state,
+                auth,
+                KvMount(entry.path.clone()),
+                &entry.path,
+                router,
+                request,
+            )
+            .await
+        }
+        "transit" => {
+            let router = super::transit::router();
+            let mount = TransitMount(entry.path.clone());
+            forward(state, auth, mount, &entry.path, router, request).await
+        }
+        "database" => {
+            let router = super::database::router();
+            let mount = DatabaseMount(entry.path.clone());
+            forward(state, auth, mount, &entry.path, router, request).await
+        }
+        PLUGIN_ENGINE_TYPE => Ok(super::plugins::dispatch.call(request, state).await),
+        _ => Err(not_found()),
+    }
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// Hand a request under `mount` to the engine `router`.
+///
+/// The request is rebuilt relative to the mount with only the caller's
+/// identity and the mount extension (e.g. [`KvMount`]) attached, so the
+/// inner router matches its own path parameters.
+async fn forward<M: Clone + Send + Sync + 'static>(
+    state: Arc<AppState>,
+    auth: AuthContext,
+    extension: M,
+    mount: &str,
+    router: Router<Arc<AppState>>,
+    request: Request,
+) -> Result<Response, AppError> {
+    let (parts, body) = request.into_parts();
+    let relative = parts
+        .uri
+        .path()
+        .strip_prefix("/v1/")
+        .and_then(|path| path.strip_prefix(mount))
+        .ok_or_else(|| AppError::NotFound(format!("no handler for route '{}'", parts.uri)))?;
+    let uri = match parts.uri.query() {
+        Some(query) => format!("/{relative}?{query}"),
+        None => format!("/{relative}"),
+    };
+
+    let mut forwarded = Request::new(body);
+    *forwarded.method_mut() = parts.method;
+    *forwarded.uri_mut() = uri
+        .parse()
+        .map_err(|_| AppError::BadRequest(format!("invalid request path '{uri}'")))?;
+    *forwarded.headers_mut() = parts.headers;
+    forwarded.extensions_mut().insert(auth);
+    forwarded.extensions_mut().insert(extension);
+
+    let Ok(response) = router.with_state(state).oneshot(forwarded).await;
+    Ok(response)
+}
+
+/// Mount config for plugin `engine_type`: `plugin` expects
+/// `config.plugin_name`; any other type must be a registered plugin name.
+async fn plugin_config(
+    state: &AppState,
+    engine_type: &str,
+    config: serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+    if engine_type == PLUGIN_ENGINE_TYPE {
+        return Ok(config);
+    }
+
+    match state.plugin_catalog.get(engine_type).await {
+        Ok(_) => {}
+        Err(PluginError::NotFound { .. }) => {
+            return Err(AppError::BadRequest(format!(
+                "unsupported engine type '{engine_type}', expected 'kv', 'plugin', or a registered plugin name"
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let mut config = match config {
+        serde_json::Value::Null => serde_json::Map::new(),
+        serde_json::Value::Object(map) => map,
+        _ => {
+            return Err(AppError::BadRequest(
+                "plugin mount config must be an object".to_owned(),
+            ));
+        }
+    };
+    config.insert("plugin_name".to_owned(), engine_type.into());
+    Ok(config.into())
+}
+
+/// A new engine instance for a built-in mount. Each type keeps its data
+/// under a prefix derived from the mount path, so the default `transit/`
+/// and `database/` mounts land on the prefixes they always used.
+fn builtin_engine(state: &AppState, entry: &MountEntry) -> Result<Arc<dyn Engine>, AppError> {
+    let barrier = Arc::clone(&state.barrier);
+    let path = &entry.path;
+    match entry.engine_type.as_str() {
+        "kv" => Ok(Arc::new(
+            KvEngine::new(barrier, format!("kv/{path}"))
+                .with_config(parse_kv_config(&entry.config)?),
+        )),
+        "transit" => {
+            let mut transit = TransitEngine::new(barrier, format!("transit/{path}"));
+            if let Some(hsm) = &state.hsm {
+                transit = transit.with_hsm(Arc::clone(hsm));
+            }
+            Ok(Arc::new(transit))
+        }
+        "database" => Ok(Arc::new(DatabaseEngine::new(barrier, format!("db/{path}")))),
+        other => Err(AppError::BadRequest(format!(
+            "'{other}' is not a built-in engine type"
+        ))),
+    }
+}
+
+/// Serve `path` with a built-in `engine`, routing lease revocations to it
+/// when it has external side effects.
+async fn serve_builtin(state: &AppState, path: String, engine: Arc<dyn Engine>) {
+    if let Ok(database) = Arc::clone(&engine).into_any().downcast::<DatabaseEngine>() {
+        state
+            .lease_manager
+            .register_handler(&path, database as Arc<dyn RevocationHandler>)
+            .await;
+    }
+    state.engines.insert(path, engine).await;
+}
+
+/// Parse KV mount options, treating a missing config as the defaults.
+fn parse_kv_config(config: &serde_json::Value) -> Result<KvMountConfig, AppError> {
+    if config.is_null() {
+        return Ok(KvMountConfig::default());
+    }
+
+    let parsed: KvMountConfig = serde_json::from_value(config.clone())
+        .map_err(|e| AppError::BadRequest(format!("invalid kv mount config: {e}")))?;
+
+    if parsed.read_lease_ttl_secs <= 0 {
+        return Err(AppError::BadRequest(
+            "read_lease_ttl_secs must be positive".to_owned(),
+        ));
+    }
+
+    Ok(parsed)
 }
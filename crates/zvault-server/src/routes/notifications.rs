@@ -0,0 +1,206 @@
+//! Notification routes: `/v1/sys/notifications/*`
+//!
+//! Manages webhooks that receive vault events (Slack, Discord, `PagerDuty`,
+//! or generic JSON receivers) and shows deliveries waiting to be retried.
+//! Delivery itself runs in the server's notification worker. Signing
+//! secrets and `PagerDuty` routing keys are write-only: responses only say
+//! whether one is set.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::notify::{Delivery, Webhook, WebhookFormat, WebhookParams};
+use zvault_core::policy::Capability;
+
+/// Build the `/v1/sys/notifications` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/notifications/webhooks` — list webhooks
+/// - `POST|GET|DELETE /v1/sys/notifications/webhooks/{name}` — manage one
+/// - `POST /v1/sys/notifications/webhooks/{name}/test` — send a test event
+/// - `GET  /v1/sys/notifications/queue` — deliveries waiting to be retried
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/webhooks", get(list_webhooks))
+        .route(
+            "/webhooks/{name}",
+            post(put_webhook).get(get_webhook).delete(delete_webhook),
+        )
+        .route("/webhooks/{name}/test", post(test_webhook))
+        .route("/queue", get(queue))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookRequest {
+    pub url: String,
+    /// `generic`, `slack`, `discord`, or `pagerduty`; detected from the URL
+    /// if omitted.
+    #[serde(default)]
+    pub format: Option<WebhookFormat>,
+    /// Topic patterns, e.g. `["kv.*", "lease.expired"]`. Empty means all.
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// HMAC signing secret. Omit to keep the current one, `""` to remove it.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// `PagerDuty` integration key. Omit to keep the current one, `""` to
+    /// remove it.
+    #[serde(default)]
+    pub routing_key: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub name: String,
+    pub url: String,
+    pub format: WebhookFormat,
+    pub events: Vec<String>,
+    /// Whether deliveries carry `X-ZVault-Signature`.
+    pub signed: bool,
+    /// Whether a `PagerDuty` routing key is set.
+    pub has_routing_key: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(w: Webhook) -> Self {
+        Self {
+            signed: w.secret.is_some(),
+            has_routing_key: w.routing_key.is_some(),
+            name: w.name,
+            url: w.url,
+            format: w.format,
+            events: w.events,
+            enabled: w.enabled,
+            created_at: w.created_at,
+            updated_at: w.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookListResponse {
+    pub webhooks: Vec<WebhookResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueResponse {
+    pub deliveries: Vec<Delivery>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+async fn list_webhooks(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<WebhookListResponse>, AppError> {
+    check(
+        &state,
+        &auth,
+        "sys/notifications/webhooks",
+        Capability::List,
+    )
+    .await?;
+    let webhooks = state.notifications.list().await?;
+    Ok(Json(WebhookListResponse {
+        webhooks: webhooks.into_iter().map(Into::into).collect(),
+    }))
+}
+
+async fn put_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<WebhookRequest>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    check(&state, &auth, &webhook_path(&name), Capability::Update).await?;
+    let webhook = state
+        .notifications
+        .put(
+            &name,
+            WebhookParams {
+                url: body.url,
+                format: body.format,
+                events: body.events,
+                secret: body.secret,
+                routing_key: body.routing_key,
+                enabled: body.enabled,
+            },
+        )
+        .await?;
+    Ok(Json(webhook.into()))
+}
+
+async fn get_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    check(&state, &auth, &webhook_path(&name), Capability::Read).await?;
+    Ok(Json(state.notifications.get(&name).await?.into()))
+}
+
+async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &webhook_path(&name), Capability::Delete).await?;
+    state.notifications.delete(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Send a `notify.test` event to the webhook and report the outcome.
+async fn test_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let path = format!("{}/test", webhook_path(&name));
+    check(&state, &auth, &path, Capability::Update).await?;
+    state.notifications.test(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn queue(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<QueueResponse>, AppError> {
+    check(&state, &auth, "sys/notifications/queue", Capability::Read).await?;
+    let deliveries = state.notifications.pending().await?;
+    Ok(Json(QueueResponse { deliveries }))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &capability)
+        .await?;
+    Ok(())
+}
+
+fn webhook_path(name: &str) -> String {
+    format!("sys/notifications/webhooks/{name}")
+}
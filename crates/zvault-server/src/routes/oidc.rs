@@ -18,7 +18,9 @@ use sha2::{Digest, Sha256};
 use tracing::{info, warn};
 
 use crate::error::AppError;
+use crate::routes::identity::bind_login;
 use crate::state::AppState;
+use zvault_core::identity::{ALIAS_METADATA, GROUPS_METADATA};
 use zvault_core::token::CreateTokenParams;
 
 /// Build the `/v1/auth/oidc` router (no auth required — these are login endpoints).
@@ -288,6 +290,8 @@ async fn oidc_callback(
     let mut metadata = HashMap::new();
     metadata.insert("auth_method".to_owned(), "oidc".to_owned());
     metadata.insert("oidc_sub".to_owned(), userinfo.sub.clone());
+    metadata.insert(ALIAS_METADATA.to_owned(), userinfo.sub.clone());
+    metadata.insert(GROUPS_METADATA.to_owned(), userinfo.roles.join(","));
     if let Some(ref email) = userinfo.email {
         metadata.insert("email".to_owned(), email.clone());
     }
@@ -302,9 +306,11 @@ async fn oidc_callback(
             parent_hash: None,
             metadata,
             display_name: display_name.clone(),
+            bound_cidrs: Vec::new(),
         })
         .await
         .map_err(|e| AppError::Internal(format!("failed to create vault token: {e}")))?;
+    bind_login(&state, "oidc", &vault_token).await?;
 
     info!(
         sub = %userinfo.sub,
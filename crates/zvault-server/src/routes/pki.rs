@@ -7,18 +7,35 @@
 //! - `GET  /v1/pki/roles/:name` — read a PKI role
 //! - `GET  /v1/pki/roles` — list all roles
 //! - `POST /v1/pki/issue/:role` — issue a certificate
-//! - `GET  /v1/pki/certs` — list issued certificates
+//! - `GET  /v1/pki/certs` — list issued certificates, paged and filtered
+//! - `POST /v1/pki/tidy` — prune expired certificates and revocation entries
+//! - `GET  /v1/pki/config/acme` — read the ACME configuration
+//! - `POST /v1/pki/config/acme` — enable and configure ACME
+//!
+//! Issue requests may add DNS (`alt_names`), IP (`ip_sans`) and URI
+//! (`uri_sans`) SANs, pick a key type and size when the role's `key_type` is
+//! `"any"`, and override the role's `not_before` backdating. Each is checked
+//! against the role.
+//!
+//! The ACME protocol endpoints themselves live in `pki_acme` under
+//! `/v1/pki/acme` and need no token.
 
 use std::sync::Arc;
 
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::Deserialize;
+use tracing::info;
 
-use zvault_core::pki::PkiRole;
+use zvault_core::pki::{
+    CertListQuery, DEFAULT_NOT_BEFORE_SECS, IssueRequest, IssuedCertificate, PkiEngine, PkiRole,
+    TidyRequest,
+};
+use zvault_core::pki_acme::AcmeServerConfig;
 
 use crate::error::AppError;
+use crate::routes::auth::parse_duration;
 use crate::state::AppState;
 
 /// Build the PKI engine router.
@@ -30,6 +47,8 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/roles/{name}", post(create_role).get(get_role))
         .route("/issue/{role}", post(issue_cert))
         .route("/certs", get(list_certs))
+        .route("/tidy", post(tidy))
+        .route("/config/acme", get(get_acme_config).post(set_acme_config))
 }
 
 #[derive(Deserialize)]
@@ -47,9 +66,10 @@ async fn generate_root(
     State(state): State<Arc<AppState>>,
     Json(body): Json<GenerateRootRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.pki_engines.read().await;
-    let engine = engines
-        .get("pki/")
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
         .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
     let ca = engine
         .generate_root(&body.common_name, body.ttl_hours)
@@ -63,9 +83,10 @@ async fn generate_root(
 }
 
 async fn get_ca(State(state): State<Arc<AppState>>) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.pki_engines.read().await;
-    let engine = engines
-        .get("pki/")
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
         .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
     let ca = engine.get_ca().await.map_err(AppError::from)?;
     Ok(Json(serde_json::json!({
@@ -76,6 +97,7 @@ async fn get_ca(State(state): State<Arc<AppState>>) -> Result<Json<serde_json::V
 }
 
 #[derive(Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 struct CreatePkiRoleRequest {
     allowed_domains: Vec<String>,
     #[serde(default)]
@@ -88,6 +110,14 @@ struct CreatePkiRoleRequest {
     key_type: String,
     #[serde(default = "default_key_bits")]
     key_bits: u32,
+    #[serde(default)]
+    allow_wildcard_certificates: bool,
+    #[serde(default)]
+    allow_ip_sans: bool,
+    #[serde(default)]
+    allowed_uri_sans: Vec<String>,
+    #[serde(default = "default_not_before")]
+    not_before_duration_secs: u64,
 }
 
 fn default_role_ttl() -> u64 {
@@ -102,15 +132,19 @@ fn default_key_type() -> String {
 fn default_key_bits() -> u32 {
     256
 }
+fn default_not_before() -> u64 {
+    DEFAULT_NOT_BEFORE_SECS
+}
 
 async fn create_role(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
     Json(body): Json<CreatePkiRoleRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.pki_engines.read().await;
-    let engine = engines
-        .get("pki/")
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
         .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
     engine
         .create_role(PkiRole {
@@ -121,6 +155,10 @@ async fn create_role(
             generate_key: body.generate_key,
             key_type: body.key_type,
             key_bits: body.key_bits,
+            allow_wildcard_certificates: body.allow_wildcard_certificates,
+            allow_ip_sans: body.allow_ip_sans,
+            allowed_uri_sans: body.allowed_uri_sans,
+            not_before_duration_secs: body.not_before_duration_secs,
         })
         .await
         .map_err(AppError::from)?;
@@ -131,9 +169,10 @@ async fn get_role(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.pki_engines.read().await;
-    let engine = engines
-        .get("pki/")
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
         .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
     let role = engine.get_role(&name).await.map_err(AppError::from)?;
     Ok(Json(serde_json::to_value(role).unwrap_or_default()))
@@ -142,9 +181,10 @@ async fn get_role(
 async fn list_roles(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.pki_engines.read().await;
-    let engine = engines
-        .get("pki/")
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
         .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
     let names = engine.list_roles().await.map_err(AppError::from)?;
     Ok(Json(serde_json::json!({"keys": names})))
@@ -154,6 +194,16 @@ async fn list_roles(
 struct IssueCertRequest {
     common_name: String,
     ttl_hours: Option<u64>,
+    #[serde(default)]
+    alt_names: Vec<String>,
+    #[serde(default)]
+    ip_sans: Vec<String>,
+    #[serde(default)]
+    uri_sans: Vec<String>,
+    key_type: Option<String>,
+    key_bits: Option<u32>,
+    /// Backdating as seconds or a duration string (`"5m"`); overrides the role.
+    not_before: Option<String>,
 }
 
 async fn issue_cert(
@@ -161,30 +211,226 @@ async fn issue_cert(
     Path(role): Path<String>,
     Json(body): Json<IssueCertRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.pki_engines.read().await;
-    let engine = engines
-        .get("pki/")
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
         .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
+    let not_before_secs = body
+        .not_before
+        .as_deref()
+        .map(parse_duration)
+        .transpose()?
+        .map(|d| u64::try_from(d.num_seconds()))
+        .transpose()
+        .map_err(|_| AppError::BadRequest("not_before must not be negative".to_owned()))?;
+    let request = IssueRequest {
+        common_name: body.common_name,
+        alt_names: body.alt_names,
+        ip_sans: body.ip_sans,
+        uri_sans: body.uri_sans,
+        ttl_hours: body.ttl_hours,
+        key_type: body.key_type,
+        key_bits: body.key_bits,
+        not_before_secs,
+    };
     let cert = engine
-        .issue(&role, &body.common_name, body.ttl_hours)
+        .issue(&role, &request)
         .await
         .map_err(AppError::from)?;
+
+    let (lease_id, ttl_secs) = track_certificate(&state, &role, &cert).await?;
+
     Ok(Json(serde_json::json!({
         "certificate": cert.certificate_pem,
         "private_key": cert.private_key_pem,
         "ca_chain": cert.ca_chain_pem,
         "serial_number": cert.serial_number,
         "expiration": cert.expiration,
+        "lease_id": lease_id,
+        "lease_duration": ttl_secs,
     })))
 }
 
+/// Track an issued certificate with a lease so expiry/revocation tombstones
+/// it. Returns the lease ID and duration in seconds.
+pub(crate) async fn track_certificate(
+    state: &AppState,
+    role: &str,
+    cert: &IssuedCertificate,
+) -> Result<(String, i64), AppError> {
+    let issued_at = chrono::Utc::now();
+    let ttl_secs = chrono::DateTime::parse_from_rfc3339(&cert.expiration).map_or(0, |exp| {
+        (exp.with_timezone(&chrono::Utc) - issued_at)
+            .num_seconds()
+            .max(0)
+    });
+    let lease = zvault_core::lease::Lease {
+        id: uuid::Uuid::new_v4().to_string(),
+        engine_path: format!("pki/issue/{role}"),
+        issued_at,
+        ttl_secs,
+        max_ttl_secs: ttl_secs,
+        renewable: false,
+        data: serde_json::json!({"serial_number": cert.serial_number}),
+        token_hash: String::new(),
+    };
+    let lease_id = state
+        .lease_manager
+        .create(&lease)
+        .await
+        .map_err(AppError::from)?;
+    Ok((lease_id, ttl_secs))
+}
+
+#[derive(Deserialize)]
+struct ListCertsQuery {
+    /// Only certificates whose common name contains this.
+    common_name: Option<String>,
+    /// Only certificates expiring within this long from now (e.g. `72h`).
+    expires_within: Option<String>,
+    /// Only certificates expiring at or after this time (RFC 3339).
+    expires_after: Option<String>,
+    /// Only certificates expiring at or before this time (RFC 3339).
+    expires_before: Option<String>,
+    /// Page size (default: 100, max: 1000).
+    limit: Option<usize>,
+    /// The previous page's `next_cursor`.
+    cursor: Option<String>,
+}
+
 async fn list_certs(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ListCertsQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
+        .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
+    let mut expires_before = query
+        .expires_before
+        .as_deref()
+        .map(parse_time)
+        .transpose()?;
+    if let Some(within) = query.expires_within.as_deref() {
+        let until = chrono::Utc::now() + parse_duration(within)?;
+        expires_before = Some(expires_before.map_or(until, |before| before.min(until)));
+    }
+    let page = engine
+        .list_cert_page(&CertListQuery {
+            common_name: query.common_name,
+            expires_after: query.expires_after.as_deref().map(parse_time).transpose()?,
+            expires_before,
+            after: query.cursor,
+            limit: query.limit.unwrap_or(100).clamp(1, 1000),
+        })
+        .await
+        .map_err(AppError::from)?;
+    let keys: Vec<&str> = page
+        .certificates
+        .iter()
+        .map(|c| c.serial_number.as_str())
+        .collect();
+    Ok(Json(serde_json::json!({
+        "keys": keys,
+        "certificates": page.certificates,
+        "next_cursor": page.next_cursor,
+    })))
+}
+
+fn parse_time(at: &str) -> Result<chrono::DateTime<chrono::Utc>, AppError> {
+    chrono::DateTime::parse_from_rfc3339(at)
+        .map(|at| at.with_timezone(&chrono::Utc))
+        .map_err(|_| AppError::BadRequest(format!("'{at}' is not an RFC 3339 timestamp")))
+}
+
+#[derive(Deserialize)]
+struct TidyCertsRequest {
+    #[serde(default = "default_true")]
+    tidy_cert_store: bool,
+    #[serde(default = "default_true")]
+    tidy_revoked_certs: bool,
+    /// How long past expiry entries are kept (default `72h`).
+    safety_buffer: Option<String>,
+}
+
+async fn tidy(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<TidyCertsRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let engine = state
+        .engines
+        .get::<PkiEngine>("pki/")
+        .await
+        .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
+    let mut request = TidyRequest {
+        tidy_cert_store: body.tidy_cert_store,
+        tidy_revoked_certs: body.tidy_revoked_certs,
+        ..TidyRequest::default()
+    };
+    if let Some(buffer) = body.safety_buffer.as_deref() {
+        request.safety_buffer = parse_duration(buffer)?;
+        if request.safety_buffer < chrono::Duration::zero() {
+            return Err(AppError::BadRequest(
+                "safety_buffer must not be negative".to_owned(),
+            ));
+        }
+    }
+    let result = engine.tidy(&request).await.map_err(AppError::from)?;
+    info!(
+        certs_deleted = result.certs_deleted,
+        revoked_deleted = result.revoked_deleted,
+        "PKI tidy finished"
+    );
+    Ok(Json(serde_json::to_value(result).unwrap_or_default()))
+}
+
+#[derive(Deserialize)]
+struct AcmeConfigRequest {
+    enabled: bool,
+    base_url: String,
+    role: String,
+    dns_resolver: Option<String>,
+}
+
+async fn get_acme_config(
+    State(state): State<Arc<AppState>>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let engines = state.pki_engines.read().await;
-    let engine = engines
-        .get("pki/")
+    let acme = state
+        .pki_acme
+        .as_ref()
         .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
-    let serials = engine.list_certs().await.map_err(AppError::from)?;
-    Ok(Json(serde_json::json!({"keys": serials})))
+    let config = acme
+        .config()
+        .await?
+        .ok_or_else(|| AppError::NotFound("ACME is not configured".to_owned()))?;
+    Ok(Json(serde_json::json!({
+        "enabled": config.enabled,
+        "base_url": config.base_url,
+        "role": config.role,
+        "dns_resolver": config.dns_resolver,
+        "directory": acme.directory_url().await,
+    })))
+}
+
+async fn set_acme_config(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<AcmeConfigRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let acme = state
+        .pki_acme
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("PKI engine not mounted".to_owned()))?;
+    acme.configure(AcmeServerConfig {
+        enabled: body.enabled,
+        base_url: body.base_url,
+        role: body.role,
+        dns_resolver: body.dns_resolver,
+    })
+    .await?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "directory": acme.directory_url().await,
+    })))
 }
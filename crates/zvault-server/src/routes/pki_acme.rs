@@ -0,0 +1,296 @@
+//! ACME (RFC 8555) endpoints of the PKI engine.
+//!
+//! Endpoints, below `/v1/pki/acme`:
+//! - `GET  /directory` — the directory ACME clients are pointed at
+//! - `HEAD /new-nonce`, `GET /new-nonce` — a fresh `Replay-Nonce`
+//! - `POST /new-account` — create or look up an account
+//! - `POST /account/:id`, `POST /account/:id/orders` — account and its orders
+//! - `POST /new-order` — order a certificate for DNS identifiers
+//! - `POST /order/:id`, `POST /order/:id/finalize` — poll and finalize
+//! - `POST /authz/:id` — an authorization
+//! - `POST /chall/:authz/:type` — start an `http-01` or `dns-01` challenge
+//! - `POST /cert/:id` — download the certificate chain
+//! - `POST /revoke-cert` — revoke a certificate
+//!
+//! No vault token is required: every `POST` is a JWS signed by the account
+//! key, and issuance is bounded by the role in `/v1/pki/config/acme`.
+//! Errors are RFC 7807 problem documents.
+
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use tracing::warn;
+
+use zvault_core::error::{AcmeServerError, BarrierError, PkiError};
+use zvault_core::pki_acme::{AcmeResponse, AcmeServer, problem};
+
+use crate::routes::pki::track_certificate;
+use crate::state::AppState;
+
+/// Build the ACME router.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/directory", get(directory))
+        .route("/new-nonce", get(new_nonce).head(new_nonce))
+        .route("/new-account", post(new_account))
+        .route("/account/{id}", post(account))
+        .route("/account/{id}/orders", post(account_orders))
+        .route("/new-order", post(new_order))
+        .route("/order/{id}", post(order))
+        .route("/order/{id}/finalize", post(finalize))
+        .route("/authz/{id}", post(authorization))
+        .route("/chall/{authz}/{kind}", post(challenge))
+        .route("/cert/{id}", post(certificate))
+        .route("/revoke-cert", post(revoke))
+}
+
+async fn directory(State(state): State<Arc<AppState>>) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    match acme.directory().await {
+        Ok(directory) => Json(directory).into_response(),
+        Err(e) => problem_response(&e),
+    }
+}
+
+async fn new_nonce(State(state): State<Arc<AppState>>, method: Method) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    if let Err(e) = acme.directory().await {
+        return problem_response(&e);
+    }
+    let status = if method == Method::HEAD {
+        StatusCode::OK
+    } else {
+        StatusCode::NO_CONTENT
+    };
+    let mut response = status.into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    with_nonce(acme, response).await
+}
+
+async fn new_account(State(state): State<Arc<AppState>>, body: Bytes) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    reply(acme, acme.new_account(&body).await).await
+}
+
+async fn account(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    reply(acme, acme.account(&id, &body).await).await
+}
+
+async fn account_orders(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    reply(acme, acme.account_orders(&id, &body).await).await
+}
+
+async fn new_order(State(state): State<Arc<AppState>>, body: Bytes) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    reply(acme, acme.new_order(&body).await).await
+}
+
+async fn order(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    reply(acme, acme.order(&id, &body).await).await
+}
+
+async fn finalize(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    let result = match acme.finalize(&id, &body).await {
+        Ok((response, cert)) => {
+            // Track the certificate with a lease like `pki/issue` does. The
+            // order is already valid, so a failure here is only logged.
+            let role = acme.config().await.ok().flatten().map(|c| c.role);
+            if let Err(e) =
+                track_certificate(&state, role.as_deref().unwrap_or_default(), &cert).await
+            {
+                warn!(order = %id, error = %e, "failed to create lease for ACME certificate");
+            }
+            Ok(response)
+        }
+        Err(e) => Err(e),
+    };
+    reply(acme, result).await
+}
+
+async fn authorization(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    reply(acme, acme.authorization(&id, &body).await).await
+}
+
+async fn challenge(
+    State(state): State<Arc<AppState>>,
+    Path((authz, kind)): Path<(String, String)>,
+    body: Bytes,
+) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    reply(acme, acme.challenge(&authz, &kind, &body).await).await
+}
+
+async fn certificate(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    let response = match acme.certificate(&id, &body).await {
+        Ok(chain) => (
+            [(header::CONTENT_TYPE, "application/pem-certificate-chain")],
+            chain,
+        )
+            .into_response(),
+        Err(e) => problem_response(&e),
+    };
+    with_nonce(acme, response).await
+}
+
+async fn revoke(State(state): State<Arc<AppState>>, body: Bytes) -> Response {
+    let Some(acme) = &state.pki_acme else {
+        return disabled();
+    };
+    let response = match acme.revoke(&body).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => problem_response(&e),
+    };
+    with_nonce(acme, response).await
+}
+
+// ── Responses ────────────────────────────────────────────────────────
+
+/// Turn an ACME result into a response with its `Location`, `Link`, and
+/// `Replay-Nonce` headers.
+async fn reply(acme: &AcmeServer, result: Result<AcmeResponse, AcmeServerError>) -> Response {
+    let response = match result {
+        Ok(resource) => {
+            let status = if resource.created {
+                StatusCode::CREATED
+            } else {
+                StatusCode::OK
+            };
+            let mut response = (status, Json(resource.body)).into_response();
+            let headers = response.headers_mut();
+            if let Some(value) = resource
+                .location
+                .and_then(|url| HeaderValue::from_str(&url).ok())
+            {
+                headers.insert(header::LOCATION, value);
+            }
+            if let Some(value) = resource
+                .up
+                .and_then(|url| HeaderValue::from_str(&format!("<{url}>;rel=\"up\"")).ok())
+            {
+                headers.append(header::LINK, value);
+            }
+            response
+        }
+        Err(e) => problem_response(&e),
+    };
+    with_nonce(acme, response).await
+}
+
+/// Add a fresh `Replay-Nonce` and the directory `Link` to `response`. If no
+/// nonce can be generated, the response is replaced by the error.
+async fn with_nonce(acme: &AcmeServer, mut response: Response) -> Response {
+    let nonce = match acme.new_nonce().await {
+        Ok(nonce) => nonce,
+        Err(e) => return problem_response(&e),
+    };
+    let headers = response.headers_mut();
+    if let Ok(nonce) = HeaderValue::from_str(&nonce) {
+        headers.insert("replay-nonce", nonce);
+    }
+    if let Some(value) = acme
+        .directory_url()
+        .await
+        .and_then(|url| HeaderValue::from_str(&format!("<{url}>;rel=\"index\"")).ok())
+    {
+        headers.append(header::LINK, value);
+    }
+    response
+}
+
+fn disabled() -> Response {
+    problem_response(&AcmeServerError::Disabled)
+}
+
+/// An `application/problem+json` response for `err`.
+fn problem_response(err: &AcmeServerError) -> Response {
+    let (status, kind) = match err {
+        AcmeServerError::Disabled | AcmeServerError::NotFound { .. } => {
+            (StatusCode::NOT_FOUND, "malformed")
+        }
+        AcmeServerError::Malformed { .. } => (StatusCode::BAD_REQUEST, "malformed"),
+        AcmeServerError::BadNonce => (StatusCode::BAD_REQUEST, "badNonce"),
+        AcmeServerError::BadSignatureAlgorithm { .. } => {
+            (StatusCode::BAD_REQUEST, "badSignatureAlgorithm")
+        }
+        AcmeServerError::AccountDoesNotExist => (StatusCode::BAD_REQUEST, "accountDoesNotExist"),
+        AcmeServerError::Unauthorized { .. } => (StatusCode::FORBIDDEN, "unauthorized"),
+        AcmeServerError::RejectedIdentifier { .. } => {
+            (StatusCode::BAD_REQUEST, "rejectedIdentifier")
+        }
+        AcmeServerError::OrderNotReady { .. } => (StatusCode::FORBIDDEN, "orderNotReady"),
+        AcmeServerError::BadCsr { .. } => (StatusCode::BAD_REQUEST, "badCSR"),
+        AcmeServerError::Barrier(BarrierError::Sealed)
+        | AcmeServerError::Pki(PkiError::Barrier(BarrierError::Sealed)) => {
+            (StatusCode::SERVICE_UNAVAILABLE, "serverInternal")
+        }
+        AcmeServerError::Internal { .. }
+        | AcmeServerError::Pki(_)
+        | AcmeServerError::Barrier(_) => (StatusCode::INTERNAL_SERVER_ERROR, "serverInternal"),
+    };
+    let body = problem(kind, &err.to_string());
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/problem+json")],
+        body.to_string(),
+    )
+        .into_response()
+}
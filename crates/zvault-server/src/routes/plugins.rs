@@ -0,0 +1,362 @@
+//! External plugin routes: `/v1/sys/plugins/*` and plugin mounts.
+//!
+//! Registers WebAssembly modules in the plugin catalog (see
+//! [`zvault_core::plugin`]) and serves requests that
+//! [`super::mounts::mount_router`] resolves to a mount of engine type
+//! `plugin`: `GET` reads (`?list=true` or `LIST` lists), `POST`
+//! creates, `PUT` updates, and `DELETE` deletes, each checked against the
+//! token's policies on the full request path. Credentials a plugin issues
+//! with a lease are revoked through the plugin when the lease ends.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::lease::{Lease, RevocationHandler};
+use zvault_core::mount::MountEntry;
+use zvault_core::plugin::{
+    ExternalPlugin, PLUGIN_ENGINE_TYPE, PluginEntry, PluginOperation, PluginRequest,
+    RegisterPluginParams, SecretsEngine,
+};
+use zvault_core::policy::Capability;
+
+/// Build the `/v1/sys/plugins` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/plugins/catalog` — list registered plugins
+/// - `POST|GET|DELETE /v1/sys/plugins/catalog/{name}` — manage one
+/// - `POST /v1/sys/plugins/reload/{name}` — re-instantiate the plugin
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/catalog", get(list_plugins))
+        .route(
+            "/catalog/{name}",
+            post(register_plugin).get(get_plugin).delete(delete_plugin),
+        )
+        .route("/reload/{name}", post(reload_plugin))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct PluginResponse {
+    pub name: String,
+    pub module: String,
+    pub sha256: String,
+    pub allowed_hosts: Vec<String>,
+    pub description: String,
+    pub registered_at: DateTime<Utc>,
+    /// Mount paths served by this plugin.
+    pub mounts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginListResponse {
+    pub plugins: Vec<PluginResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReloadResponse {
+    pub mounts: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DispatchQuery {
+    #[serde(default)]
+    pub list: bool,
+}
+
+// ── Catalog handlers ─────────────────────────────────────────────────
+
+/// List registered plugins.
+async fn list_plugins(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<PluginListResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/plugins/catalog", &Capability::List)
+        .await?;
+
+    let mut plugins = Vec::new();
+    for entry in state.plugin_catalog.list().await? {
+        plugins.push(to_response(&state, entry).await);
+    }
+    Ok(Json(PluginListResponse { plugins }))
+}
+
+/// Register or replace a plugin. Running mounts keep the old module until
+/// they are reloaded.
+async fn register_plugin(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<RegisterPluginParams>,
+) -> Result<Json<PluginResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("sys/plugins/catalog/{name}"),
+            &Capability::Sudo,
+        )
+        .await?;
+
+    let entry = state.plugin_catalog.register(&name, body).await?;
+    Ok(Json(to_response(&state, entry).await))
+}
+
+/// Read a plugin's catalog entry.
+async fn get_plugin(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<PluginResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("sys/plugins/catalog/{name}"),
+            &Capability::Read,
+        )
+        .await?;
+
+    let entry = state.plugin_catalog.get(&name).await?;
+    Ok(Json(to_response(&state, entry).await))
+}
+
+/// Remove a plugin that no mount uses.
+async fn delete_plugin(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("sys/plugins/catalog/{name}"),
+            &Capability::Sudo,
+        )
+        .await?;
+
+    let mounts = plugin_mounts(&state, &name).await;
+    if !mounts.is_empty() {
+        return Err(AppError::Conflict(format!(
+            "plugin '{name}' is mounted at {}; unmount it first",
+            mounts.join(", ")
+        )));
+    }
+
+    state.plugin_catalog.delete(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Drop the plugin's instances so the next request instantiates them from
+/// the current catalog entry.
+async fn reload_plugin(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<ReloadResponse>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/plugins/reload", &Capability::Sudo)
+        .await?;
+
+    state.plugin_catalog.get(&name).await?;
+    let engines = state.engines.all::<ExternalPlugin>().await;
+
+    let mut mounts = Vec::new();
+    for (_, engine) in engines.into_iter().filter(|(_, e)| e.name() == name) {
+        engine.stop().await;
+        engine.start().await?;
+        mounts.push(engine.mount().to_owned());
+    }
+    mounts.sort();
+    Ok(Json(ReloadResponse { mounts }))
+}
+
+// ── Plugin mounts ────────────────────────────────────────────────────
+
+/// Serve a request under a plugin mount.
+pub(crate) async fn dispatch(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    method: Method,
+    Path(path): Path<String>,
+    Query(query): Query<DispatchQuery>,
+    body: Option<Json<serde_json::Value>>,
+) -> Result<Response, AppError> {
+    let not_found = || AppError::NotFound(format!("no handler for route '{path}'"));
+    let (entry, relative) = state
+        .mount_manager
+        .resolve(&path)
+        .await
+        .ok_or_else(not_found)?;
+    if entry.engine_type != PLUGIN_ENGINE_TYPE {
+        return Err(not_found());
+    }
+    let engine = state
+        .engines
+        .get::<ExternalPlugin>(&entry.path)
+        .await
+        .ok_or_else(not_found)?;
+
+    let operation = match method.as_str() {
+        "GET" if query.list => PluginOperation::List,
+        "LIST" => PluginOperation::List,
+        "GET" => PluginOperation::Read,
+        "POST" => PluginOperation::Create,
+        "PUT" => PluginOperation::Update,
+        "DELETE" => PluginOperation::Delete,
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "unsupported method '{other}' on a plugin mount"
+            )));
+        }
+    };
+
+    state
+        .policy_store
+        .check(&auth.policies, &path, &operation.capability())
+        .await?;
+
+    let response = engine
+        .handle(&PluginRequest {
+            operation,
+            path: relative,
+            data: body.map(|Json(data)| data),
+        })
+        .await?;
+
+    let Some(lease) = response.lease else {
+        return Ok(match response.data {
+            Some(data) => Json(serde_json::json!({ "data": data })).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        });
+    };
+
+    let lease = Lease {
+        id: uuid::Uuid::new_v4().to_string(),
+        engine_path: path,
+        issued_at: Utc::now(),
+        ttl_secs: lease.ttl_secs,
+        max_ttl_secs: lease.max_ttl_secs,
+        renewable: lease.renewable,
+        data: serde_json::json!({ "plugin": engine.name(), "secret": lease.secret }),
+        token_hash: auth.token_hash,
+    };
+    let lease_id = state.lease_manager.create(&lease).await?;
+
+    Ok(Json(serde_json::json!({
+        "data": response.data,
+        "lease_id": lease_id,
+        "lease_duration": lease.ttl_secs,
+        "renewable": lease.renewable,
+    }))
+    .into_response())
+}
+
+/// Mount catalog plugin `config.plugin_name` at `entry.path`.
+///
+/// The plugin is instantiated first, so a plugin that fails to start is
+/// never mounted.
+pub(crate) async fn mount(state: &AppState, entry: MountEntry) -> Result<(), AppError> {
+    let name = plugin_name(&entry.config)
+        .ok_or_else(|| AppError::BadRequest("plugin mounts need config.plugin_name".to_owned()))?;
+    state.plugin_catalog.get(name).await?;
+
+    let engine = Arc::new(ExternalPlugin::new(
+        Arc::clone(&state.plugin_catalog),
+        Arc::clone(&state.barrier),
+        name,
+        &entry.path,
+        entry.config.clone(),
+    ));
+    engine.start().await?;
+
+    let path = entry.path.clone();
+    if let Err(e) = state.mount_manager.mount(entry).await {
+        engine.stop().await;
+        return Err(e.into());
+    }
+    register(state, path, engine).await;
+    Ok(())
+}
+
+/// Recreate the engines of plugin mounts restored from storage. Plugins
+/// are instantiated on their first request.
+pub(crate) async fn restore(state: &AppState, entries: &[MountEntry]) {
+    for entry in entries
+        .iter()
+        .filter(|e| e.engine_type == PLUGIN_ENGINE_TYPE)
+    {
+        let Some(name) = plugin_name(&entry.config) else {
+            warn!(path = %entry.path, "plugin mount has no plugin_name, skipping");
+            continue;
+        };
+        let engine = Arc::new(ExternalPlugin::new(
+            Arc::clone(&state.plugin_catalog),
+            Arc::clone(&state.barrier),
+            name,
+            &entry.path,
+            entry.config.clone(),
+        ));
+        register(state, entry.path.clone(), engine).await;
+    }
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// Serve `path` with `engine` and route its lease revocations to it.
+async fn register(state: &AppState, path: String, engine: Arc<ExternalPlugin>) {
+    state
+        .lease_manager
+        .register_handler(&path, Arc::clone(&engine) as Arc<dyn RevocationHandler>)
+        .await;
+    state.engines.insert(path, engine).await;
+}
+
+fn plugin_name(config: &serde_json::Value) -> Option<&str> {
+    config
+        .get("plugin_name")
+        .and_then(serde_json::Value::as_str)
+}
+
+/// Mount paths served by plugin `name`, sorted.
+async fn plugin_mounts(state: &AppState, name: &str) -> Vec<String> {
+    let mut mounts: Vec<String> = state
+        .mount_manager
+        .list()
+        .await
+        .into_iter()
+        .filter(|e| e.engine_type == PLUGIN_ENGINE_TYPE && plugin_name(&e.config) == Some(name))
+        .map(|e| e.path)
+        .collect();
+    mounts.sort();
+    mounts
+}
+
+async fn to_response(state: &AppState, entry: PluginEntry) -> PluginResponse {
+    let mounts = plugin_mounts(state, &entry.name).await;
+    PluginResponse {
+        name: entry.name,
+        module: entry.module,
+        sha256: entry.sha256,
+        allowed_hosts: entry.allowed_hosts,
+        description: entry.description,
+        registered_at: entry.registered_at,
+        mounts,
+    }
+}
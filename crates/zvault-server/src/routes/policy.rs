@@ -1,6 +1,8 @@
 //! Policy management routes: `/v1/sys/policies/*`
 //!
-//! CRUD operations for access control policies.
+//! CRUD operations for access control policies. Rules may carry a control
+//! group and a list of step-up `mfa_methods`, which must name configured
+//! MFA methods. Writes and deletes publish `policy.*` events.
 
 use std::sync::Arc;
 
@@ -13,7 +15,8 @@ use serde::{Deserialize, Serialize};
 use crate::error::AppError;
 use crate::middleware::AuthContext;
 use crate::state::AppState;
-use zvault_core::policy::{Capability, Policy, PolicyRule};
+use zvault_core::events::{TOPIC_POLICY_DELETE, TOPIC_POLICY_WRITE};
+use zvault_core::policy::{Capability, ControlGroup, Policy, PolicyRule};
 
 /// Build the `/v1/sys/policies` router.
 pub fn router() -> Router<Arc<AppState>> {
@@ -41,6 +44,10 @@ pub struct PolicyResponse {
 pub struct PolicyRuleResponse {
     pub path: String,
     pub capabilities: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_group: Option<ControlGroup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mfa_methods: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -52,6 +59,11 @@ pub struct PutPolicyRequest {
 pub struct PutPolicyRule {
     pub path: String,
     pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub control_group: Option<ControlGroup>,
+    /// MFA methods to validate for requests this rule grants.
+    #[serde(default)]
+    pub mfa_methods: Vec<String>,
 }
 
 // ── Handlers ─────────────────────────────────────────────────────────
@@ -90,6 +102,8 @@ async fn get_policy(
         .map(|r| PolicyRuleResponse {
             path: r.path.clone(),
             capabilities: r.capabilities.iter().map(|c| format!("{c:?}")).collect(),
+            control_group: r.control_group.clone(),
+            mfa_methods: r.mfa_methods.clone(),
         })
         .collect();
 
@@ -111,6 +125,11 @@ async fn put_policy(
         .check(&auth.policies, "sys/policies", &Capability::Create)
         .await?;
 
+    // An unknown method could never be satisfied, locking the rule.
+    for method in body.rules.iter().flat_map(|r| &r.mfa_methods) {
+        state.mfa.get_totp_method(method).await?;
+    }
+
     let rules: Result<Vec<PolicyRule>, AppError> = body
         .rules
         .into_iter()
@@ -120,6 +139,8 @@ async fn put_policy(
             Ok(PolicyRule {
                 path: r.path,
                 capabilities: capabilities?,
+                control_group: r.control_group,
+                mfa_methods: r.mfa_methods,
             })
         })
         .collect();
@@ -130,6 +151,10 @@ async fn put_policy(
     };
 
     state.policy_store.put(&policy).await?;
+    state.event_bus.publish(
+        TOPIC_POLICY_WRITE,
+        serde_json::json!({ "name": policy.name }),
+    );
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -146,6 +171,9 @@ async fn delete_policy(
         .await?;
 
     state.policy_store.delete(&name).await?;
+    state
+        .event_bus
+        .publish(TOPIC_POLICY_DELETE, serde_json::json!({ "name": name }));
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,177 @@
+//! HTTP route handlers for resource quotas.
+//!
+//! Endpoints:
+//! - `POST /v1/sys/quotas/rate-limit/:name` — create or replace a rate limit
+//!   quota
+//! - `GET  /v1/sys/quotas/rate-limit/:name` — read a rate limit quota
+//! - `DELETE /v1/sys/quotas/rate-limit/:name` — delete a rate limit quota
+//! - `GET  /v1/sys/quotas/rate-limit` — list rate limit quotas
+//! - `POST /v1/sys/quotas/lease-count/:name` — create or replace a lease
+//!   count quota
+//! - `GET  /v1/sys/quotas/lease-count/:name` — read a lease count quota
+//! - `DELETE /v1/sys/quotas/lease-count/:name` — delete a lease count quota
+//! - `GET  /v1/sys/quotas/lease-count` — list lease count quotas
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+
+use zvault_core::policy::Capability;
+use zvault_core::quota::{LeaseCountQuota, RateLimitQuota};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+
+/// Build the `/v1/sys/quotas` router.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/rate-limit", get(list_rate_limits))
+        .route(
+            "/rate-limit/{name}",
+            get(get_rate_limit)
+                .post(put_rate_limit)
+                .delete(delete_rate_limit),
+        )
+        .route("/lease-count", get(list_lease_counts))
+        .route(
+            "/lease-count/{name}",
+            get(get_lease_count)
+                .post(put_lease_count)
+                .delete(delete_lease_count),
+        )
+}
+
+async fn put_rate_limit(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(mut body): Json<RateLimitQuota>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("sys/quotas/rate-limit/{name}"),
+        Capability::Update,
+    )
+    .await?;
+    body.name = name;
+    state.quotas.put_rate_limit(body).await?;
+    Ok(Json(serde_json::json!({"status": "ok"})))
+}
+
+async fn get_rate_limit(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<RateLimitQuota>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("sys/quotas/rate-limit/{name}"),
+        Capability::Read,
+    )
+    .await?;
+    Ok(Json(state.quotas.get_rate_limit(&name).await?))
+}
+
+async fn delete_rate_limit(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("sys/quotas/rate-limit/{name}"),
+        Capability::Delete,
+    )
+    .await?;
+    state.quotas.delete_rate_limit(&name).await?;
+    Ok(Json(serde_json::json!({"status": "deleted"})))
+}
+
+async fn list_rate_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check(&state, &auth, "sys/quotas/rate-limit", Capability::List).await?;
+    let quotas = state.quotas.list_rate_limits().await;
+    let keys: Vec<&str> = quotas.iter().map(|q| q.name.as_str()).collect();
+    Ok(Json(serde_json::json!({"keys": keys, "quotas": quotas})))
+}
+
+async fn put_lease_count(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(mut body): Json<LeaseCountQuota>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("sys/quotas/lease-count/{name}"),
+        Capability::Update,
+    )
+    .await?;
+    body.name = name;
+    state.quotas.put_lease_count(body).await?;
+    Ok(Json(serde_json::json!({"status": "ok"})))
+}
+
+async fn get_lease_count(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<LeaseCountQuota>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("sys/quotas/lease-count/{name}"),
+        Capability::Read,
+    )
+    .await?;
+    Ok(Json(state.quotas.get_lease_count(&name).await?))
+}
+
+async fn delete_lease_count(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("sys/quotas/lease-count/{name}"),
+        Capability::Delete,
+    )
+    .await?;
+    state.quotas.delete_lease_count(&name).await?;
+    Ok(Json(serde_json::json!({"status": "deleted"})))
+}
+
+async fn list_lease_counts(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check(&state, &auth, "sys/quotas/lease-count", Capability::List).await?;
+    let quotas = state.quotas.list_lease_counts().await;
+    let keys: Vec<&str> = quotas.iter().map(|q| q.name.as_str()).collect();
+    Ok(Json(serde_json::json!({"keys": keys, "quotas": quotas})))
+}
+
+/// Require `capability` on `path`.
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &capability)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Replication routes: `/v1/sys/replication/*`.
+//!
+//! Endpoints:
+//! - `GET /v1/sys/replication/status` — this server's replication role and
+//!   progress (token with `read` on `sys/replication/status`)
+//! - `GET /v1/sys/replication/snapshot` — the primary's storage as of a log
+//!   index (replication secret)
+//! - `GET /v1/sys/replication/stream?epoch=&after=&wait=` — changes after a
+//!   log index, held open up to `wait` seconds for new ones (replication
+//!   secret)
+//!
+//! All three need an Enterprise license (`Feature::Ha`).
+//!
+//! Snapshot and stream carry barrier ciphertext only, and are authenticated
+//! by the shared secret in the `X-Vault-Replication-Secret` header rather
+//! than a token, so replicas can bootstrap before they have any tokens.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+
+use zvault_core::error::ReplicationError;
+use zvault_core::policy::Capability;
+use zvault_core::replication::{ReplicationLog, secret_matches};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::replication::{
+    MAX_BATCH, MAX_WAIT_SECS, Replication, ReplicationStatus, SECRET_HEADER, SnapshotEntry,
+    SnapshotResponse, StreamResponse,
+};
+use crate::state::AppState;
+
+/// Build the `/v1/sys/replication` router (replication secret auth).
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/snapshot", get(snapshot))
+        .route("/stream", get(stream))
+}
+
+/// Build the `/v1/sys/replication/status` router (token auth).
+pub fn status_router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(status))
+}
+
+/// Query parameters for `GET /v1/sys/replication/stream`.
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Epoch of the log the replica is following.
+    pub epoch: String,
+    /// Return changes after this index.
+    #[serde(default)]
+    pub after: u64,
+    /// Seconds to wait for a change if there is none yet (capped at 30).
+    #[serde(default)]
+    pub wait: u64,
+}
+
+/// The primary's change log, if this server is a primary and `headers`
+/// carry its replication secret.
+fn primary_log<'a>(
+    state: &'a AppState,
+    headers: &HeaderMap,
+) -> Result<&'a ReplicationLog, AppError> {
+    let Some(Replication::Primary { log, secret }) = &state.replication else {
+        return Err(AppError::NotFound(
+            "this server is not a replication primary".to_owned(),
+        ));
+    };
+    let presented = headers
+        .get(SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !secret_matches(secret, presented) {
+        return Err(AppError::Unauthorized(
+            "invalid replication secret".to_owned(),
+        ));
+    }
+    Ok(log)
+}
+
+/// Snapshot the primary's storage for a replica to bootstrap from.
+async fn snapshot(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<SnapshotResponse>, AppError> {
+    let log = primary_log(&state, &headers)?;
+    let (index, entries) = log.snapshot_at().await.map_err(ReplicationError::from)?;
+    let entries = entries
+        .into_iter()
+        .map(|(key, value)| SnapshotEntry {
+            key,
+            value: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &value),
+        })
+        .collect();
+    Ok(Json(SnapshotResponse {
+        epoch: log.epoch().to_owned(),
+        index,
+        entries,
+    }))
+}
+
+/// Changes after `after`, waiting up to `wait` seconds for one.
+async fn stream(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+) -> Result<Json<StreamResponse>, AppError> {
+    let log = primary_log(&state, &headers)?;
+    if query.epoch != log.epoch() {
+        return Err(ReplicationError::ResyncRequired {
+            reason: "the primary has restarted".to_owned(),
+        }
+        .into());
+    }
+    if query.wait > 0 && log.last_index() == query.after {
+        let wait = Duration::from_secs(query.wait.min(MAX_WAIT_SECS));
+        log.wait_for_change(query.after, wait).await;
+    }
+    let entries = log.since(query.after, MAX_BATCH).await?;
+    Ok(Json(StreamResponse {
+        epoch: log.epoch().to_owned(),
+        entries,
+    }))
+}
+
+/// This server's replication role and progress.
+async fn status(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<ReplicationStatus>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/replication/status", &Capability::Read)
+        .await?;
+    let status = match &state.replication {
+        Some(replication) => replication.status().await,
+        None => ReplicationStatus::Disabled,
+    };
+    Ok(Json(status))
+}
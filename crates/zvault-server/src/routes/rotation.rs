@@ -0,0 +1,281 @@
+//! Secret rotation routes: `/v1/sys/rotation/*`
+//!
+//! Manages rotation policies for KV secrets and triggers rotations on
+//! demand. Scheduled rotations run in the server's rotation worker through
+//! [`rotate`]. Policies are addressed by `{mount}{path}`, e.g.
+//! `secret/app/db`. Webhook rotator secrets are write-only: responses only
+//! say whether the webhook is signed.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::routes::auth::parse_duration;
+use crate::routes::secrets::validate_secret_path;
+use crate::state::AppState;
+use zvault_core::database::DatabaseEngine;
+use zvault_core::engine::KvEngine;
+use zvault_core::events::{TOPIC_KV_ROTATE, TOPIC_KV_WRITE};
+use zvault_core::policy::Capability;
+use zvault_core::rotation::{Rotation, RotationParams, RotationPolicy, Rotator, Schedule};
+
+/// Build the `/v1/sys/rotation` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/rotation/policies` — list policies with their status
+/// - `POST|GET|DELETE /v1/sys/rotation/policies/{mount}/{*path}` — manage one
+/// - `POST /v1/sys/rotation/rotate/{mount}/{*path}` — rotate now
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/policies", get(list_policies))
+        .route(
+            "/policies/{*id}",
+            post(put_policy).get(get_policy).delete(delete_policy),
+        )
+        .route("/rotate/{*id}", post(rotate_now))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyRequest {
+    /// Secret field that receives new values (default `value`).
+    #[serde(default)]
+    pub field: Option<String>,
+    /// `{"type": "random" | "database" | "webhook", ...}`.
+    pub rotator: Rotator,
+    /// Duration between rotations, e.g. `24h` or `30d`.
+    #[serde(default)]
+    pub interval: Option<String>,
+    /// Cron expression in UTC, e.g. `0 3 * * *`.
+    #[serde(default)]
+    pub cron: Option<String>,
+    /// The `schedule` object as returned by `GET`, an alternative to
+    /// `interval` and `cron`.
+    #[serde(default)]
+    pub schedule: Option<Schedule>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PolicyResponse {
+    pub id: String,
+    pub mount: String,
+    pub path: String,
+    pub field: String,
+    pub rotator: serde_json::Value,
+    pub schedule: Schedule,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub next_rotation: DateTime<Utc>,
+    pub last_rotated: Option<DateTime<Utc>>,
+    pub last_version: Option<u64>,
+    pub failures: u32,
+    pub last_error: Option<String>,
+}
+
+impl From<RotationPolicy> for PolicyResponse {
+    fn from(p: RotationPolicy) -> Self {
+        let mut rotator = serde_json::to_value(&p.rotator).unwrap_or_default();
+        if let Rotator::Webhook { secret, .. } = &p.rotator {
+            rotator["signed"] = serde_json::Value::Bool(secret.is_some());
+            if let Some(obj) = rotator.as_object_mut() {
+                obj.remove("secret");
+            }
+        }
+        Self {
+            id: p.id(),
+            mount: p.mount,
+            path: p.path,
+            field: p.field,
+            rotator,
+            schedule: p.schedule,
+            created_at: p.created_at,
+            updated_at: p.updated_at,
+            next_rotation: p.next_rotation,
+            last_rotated: p.last_rotated,
+            last_version: p.last_version,
+            failures: p.failures,
+            last_error: p.last_error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PolicyListResponse {
+    pub policies: Vec<PolicyResponse>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+async fn list_policies(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<PolicyListResponse>, AppError> {
+    check(&state, &auth, "sys/rotation/policies", Capability::List).await?;
+    let policies = state.rotation.list().await?;
+    Ok(Json(PolicyListResponse {
+        policies: policies.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Create or replace a policy. The caller must also be allowed to write
+/// the secret, since the server will write it on their behalf.
+async fn put_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+    Json(body): Json<PolicyRequest>,
+) -> Result<Json<PolicyResponse>, AppError> {
+    let (mount, path) = split_id(&id)?;
+    check(&state, &auth, &policy_path(&id), Capability::Update).await?;
+    check(
+        &state,
+        &auth,
+        &format!("{mount}data/{path}"),
+        Capability::Create,
+    )
+    .await?;
+    if state.engines.get::<KvEngine>(mount).await.is_none() {
+        return Err(AppError::NotFound(format!(
+            "no KV engine mounted at '{mount}'"
+        )));
+    }
+
+    let schedule = match (body.interval, body.cron, body.schedule) {
+        (Some(interval), None, None) => {
+            let secs = parse_duration(&interval)?.num_seconds();
+            Schedule::Interval(
+                u64::try_from(secs)
+                    .map_err(|_| AppError::BadRequest(format!("invalid interval: {interval}")))?,
+            )
+        }
+        (None, Some(cron), None) => Schedule::Cron(cron),
+        (None, None, Some(schedule)) => schedule,
+        _ => {
+            return Err(AppError::BadRequest(
+                "exactly one of 'interval', 'cron', or 'schedule' is required".to_owned(),
+            ));
+        }
+    };
+    let policy = state
+        .rotation
+        .put(
+            mount,
+            path,
+            RotationParams {
+                field: body.field,
+                rotator: body.rotator,
+                schedule,
+            },
+        )
+        .await?;
+    Ok(Json(policy.into()))
+}
+
+async fn get_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<PolicyResponse>, AppError> {
+    check(&state, &auth, &policy_path(&id), Capability::Read).await?;
+    Ok(Json(state.rotation.get(&id).await?.into()))
+}
+
+async fn delete_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &policy_path(&id), Capability::Delete).await?;
+    state.rotation.delete(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn rotate_now(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<String>,
+) -> Result<Json<Rotation>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("sys/rotation/rotate/{id}"),
+        Capability::Update,
+    )
+    .await?;
+    Ok(Json(rotate(&state, &id).await?))
+}
+
+// ── Rotation ─────────────────────────────────────────────────────────
+
+/// Rotate the secret of policy `id` and publish `kv.write` and `kv.rotate`
+/// events for the new version. Used by the rotate endpoint and the
+/// rotation worker.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the policy or its KV mount is gone, and
+/// `AppError::BadRequest` if the rotation failed (recorded on the policy).
+pub async fn rotate(state: &AppState, id: &str) -> Result<Rotation, AppError> {
+    let policy = state.rotation.get(id).await?;
+    let kv = state
+        .engines
+        .get::<KvEngine>(&policy.mount)
+        .await
+        .ok_or_else(|| {
+            tracing::warn!(secret = %id, mount = %policy.mount, "rotation skipped, KV mount is gone");
+            AppError::NotFound(format!("no KV engine mounted at '{}'", policy.mount))
+        })?;
+    let database = match &policy.rotator {
+        Rotator::Database { mount, .. } => state.engines.get::<DatabaseEngine>(mount).await,
+        _ => None,
+    };
+
+    let rotation = state.rotation.rotate(id, &kv, database.as_deref()).await?;
+    let data = serde_json::json!({
+        "mount": rotation.mount,
+        "path": format!("{}data/{}", rotation.mount, rotation.path),
+        "version": rotation.version,
+    });
+    state.event_bus.publish(TOPIC_KV_WRITE, data.clone());
+    state.event_bus.publish(TOPIC_KV_ROTATE, data);
+    Ok(rotation)
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &capability)
+        .await?;
+    Ok(())
+}
+
+/// Split `secret/app/db` into the mount `secret/` and the path `app/db`.
+fn split_id(id: &str) -> Result<(&str, &str), AppError> {
+    let Some(slash) = id.find('/') else {
+        return Err(AppError::BadRequest(format!(
+            "'{id}' must be <mount>/<path>, e.g. secret/app/db"
+        )));
+    };
+    let (mount, path) = id.split_at(slash.saturating_add(1));
+    validate_secret_path(path)?;
+    Ok((mount, path))
+}
+
+fn policy_path(id: &str) -> String {
+    format!("sys/rotation/policies/{id}")
+}
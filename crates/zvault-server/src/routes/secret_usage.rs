@@ -0,0 +1,114 @@
+//! Secret usage routes: `/v1/sys/internal/counters/secrets`
+//!
+//! Report read counts and distinct readers per KV secret under a prefix.
+//! Secrets that exist but were never read are included with zero counts, so
+//! unused secrets stand out as readily as over-shared ones.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::engine::{EngineRequest, KvEngine, Operation};
+use zvault_core::policy::Capability;
+use zvault_core::secret_usage::SecretUsage;
+
+/// Build the `/v1/sys/internal/counters/secrets` router.
+///
+/// Paths:
+/// - `GET /v1/sys/internal/counters/secrets?prefix=secret/app/` — usage per secret
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(secret_usage))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct SecretUsageQuery {
+    /// Mount-qualified path prefix (e.g. `secret/app/`). Defaults to all secrets.
+    #[serde(default)]
+    pub prefix: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecretUsageResponse {
+    /// Usage per secret, sorted by path.
+    pub secrets: Vec<SecretUsage>,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Read counts and distinct readers per secret under a prefix.
+async fn secret_usage(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<SecretUsageQuery>,
+) -> Result<Json<SecretUsageResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            "sys/internal/counters/secrets",
+            &Capability::Read,
+        )
+        .await?;
+
+    let prefix = query.prefix.trim_start_matches('/');
+    let mut secrets: BTreeMap<String, SecretUsage> = existing_secrets(&state, prefix)
+        .await?
+        .into_iter()
+        .map(|path| {
+            let usage = SecretUsage::unread(&path);
+            (path, usage)
+        })
+        .collect();
+    for usage in state.secret_usage.stats(prefix).await? {
+        secrets.insert(usage.path.clone(), usage);
+    }
+
+    Ok(Json(SecretUsageResponse {
+        secrets: secrets.into_values().collect(),
+    }))
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// Mount-qualified paths of every KV secret under `prefix`.
+async fn existing_secrets(state: &AppState, prefix: &str) -> Result<Vec<String>, AppError> {
+    let engines = state.engines.all::<KvEngine>().await;
+
+    let mut paths = Vec::new();
+    for (mount, engine) in engines {
+        let relative = if let Some(rest) = prefix.strip_prefix(mount.as_str()) {
+            rest
+        } else if mount.starts_with(prefix) {
+            ""
+        } else {
+            continue;
+        };
+
+        let response = engine
+            .handle(&EngineRequest {
+                operation: Operation::List,
+                path: relative.to_owned(),
+                data: None,
+            })
+            .await?;
+        let keys = response
+            .data
+            .as_ref()
+            .and_then(|d| d.get("keys"))
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(serde_json::Value::as_str);
+        paths.extend(keys.map(|key| format!("{mount}{relative}{key}")));
+    }
+    Ok(paths)
+}
@@ -1,22 +1,48 @@
 //! Secrets routes: `/v1/{mount_path}/*`
 //!
 //! Routes requests to the appropriate KV engine based on the mount table.
-//! Supports read, write, delete, list, and metadata operations.
+//! Supports read, write, delete, list, and metadata operations. Successful
+//! reads are counted towards per-secret usage analytics.
+//!
+//! Writes accept `{"options": {"cas": N}}` alongside the secret data for
+//! check-and-set; the `options` key is stripped before the data is stored.
+//!
+//! Writes, deletes, destroys, and metadata updates publish `kv.*` events
+//! carrying the secret's ACL path — never its data.
+//!
+//! Copy and move work on one secret or, for a path ending in `/`, a whole
+//! subtree, and carry every version and all metadata across. Nothing is
+//! written unless the caller may read each source and create each
+//! destination (and, for a move, destroy each source).
+//!
+//! `hashes` returns an HMAC of every value under a prefix (keyed like the
+//! audit log's), so two subtrees can be compared without downloading them.
 
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::error::AppError;
 use crate::middleware::AuthContext;
+use crate::routes::auth::parse_duration;
 use crate::state::AppState;
-use zvault_core::engine::{EngineRequest, Operation};
+use zvault_core::activity::ActivityLog;
+use zvault_core::engine::{
+    EngineRequest, KvEngine, KvMetadata, KvMetadataUpdate, Operation, unwrap_data_envelope,
+};
+use zvault_core::error::EngineError;
+use zvault_core::events::{TOPIC_KV_DELETE, TOPIC_KV_DESTROY, TOPIC_KV_METADATA, TOPIC_KV_WRITE};
+use zvault_core::lease::Lease;
 use zvault_core::policy::Capability;
 
+/// Mount path of the built-in KV engine served at `/v1/secret`.
+pub const DEFAULT_KV_MOUNT: &str = "secret/";
+
 /// Validate a secret path against security rules.
 ///
 /// - Only alphanumeric, `_`, `-`, `/` characters allowed.
@@ -24,7 +50,7 @@ use zvault_core::policy::Capability;
 /// - No null bytes.
 /// - Maximum 10 path segments.
 /// - Path must not be empty.
-fn validate_secret_path(path: &str) -> Result<(), AppError> {
+pub(crate) fn validate_secret_path(path: &str) -> Result<(), AppError> {
     if path.is_empty() {
         return Err(AppError::BadRequest(
             "secret path must not be empty".to_owned(),
@@ -63,22 +89,79 @@ fn validate_secret_path(path: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Build the `/v1/secret` router for the default KV mount.
+/// Build the router serving one KV mount.
+///
+/// Nested at `/v1/secret` for the default mount; other KV mounts are
+/// forwarded here by [`super::mounts::mount_router`]. Handlers read the
+/// mount from the [`KvMount`] request extension.
 ///
 /// Paths:
-/// - `GET    /v1/secret/data/{*path}` — read
-/// - `POST   /v1/secret/data/{*path}` — write
-/// - `DELETE  /v1/secret/data/{*path}` — delete
-/// - `GET    /v1/secret/metadata/{*path}` — metadata
-/// - `GET    /v1/secret/list/{*path}` — list keys
+/// - `GET    /v1/{mount}/data/{*path}` — read
+/// - `POST   /v1/{mount}/data/{*path}` — write
+/// - `DELETE  /v1/{mount}/data/{*path}` — delete
+/// - `GET    /v1/{mount}/metadata/{*path}` — metadata
+/// - `POST   /v1/{mount}/metadata/{*path}` — update custom metadata, limits, and expiry
+/// - `DELETE /v1/{mount}/metadata/{*path}` — destroy all versions
+/// - `GET    /v1/{mount}/list/{*path}` — list keys
+/// - `GET    /v1/{mount}/list` — list every key in the mount
+/// - `POST   /v1/{mount}/copy` — copy a secret or subtree with its history
+/// - `POST   /v1/{mount}/move` — move a secret or subtree with its history
+/// - `GET    /v1/{mount}/hashes/{*path}` — value HMACs of every secret under a prefix
+/// - `GET    /v1/{mount}/hashes` — value HMACs of every secret in the mount
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route(
             "/data/{*path}",
             get(read_secret).post(write_secret).delete(delete_secret),
         )
-        .route("/metadata/{*path}", get(get_metadata))
+        .route(
+            "/metadata/{*path}",
+            get(get_metadata)
+                .post(update_metadata)
+                .delete(destroy_secret),
+        )
+        .route("/list", get(list_all_secrets))
         .route("/list/{*path}", get(list_secrets))
+        .route("/copy", post(copy_secrets))
+        .route("/move", post(move_secrets))
+        .route("/hashes", get(all_value_hashes))
+        .route("/hashes/{*path}", get(value_hashes))
+}
+
+/// The KV mount a request is served from, e.g. `secret/`.
+#[derive(Debug, Clone)]
+pub struct KvMount(pub String);
+
+impl Default for KvMount {
+    fn default() -> Self {
+        Self(DEFAULT_KV_MOUNT.to_owned())
+    }
+}
+
+// ── Request types ────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMetadataRequest {
+    #[serde(default)]
+    pub custom_metadata: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub max_versions: Option<u32>,
+    #[serde(default)]
+    pub cas_required: Option<bool>,
+    /// Duration such as `2160h` or `90d`; `0` disables expiry.
+    #[serde(default)]
+    pub delete_version_after: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CopyRequest {
+    /// A secret path, or a prefix ending in `/` for every secret under it.
+    pub from: String,
+    /// Destination path or prefix.
+    pub to: String,
+    /// Replace secrets that already exist at the destination.
+    #[serde(default)]
+    pub overwrite: bool,
 }
 
 // ── Response types ───────────────────────────────────────────────────
@@ -98,6 +181,24 @@ pub struct MetadataResponse {
     pub updated_at: String,
     pub version_count: u32,
     pub max_versions: u32,
+    pub custom_metadata: HashMap<String, String>,
+    pub cas_required: bool,
+    pub delete_version_after_secs: i64,
+}
+
+impl From<KvMetadata> for MetadataResponse {
+    fn from(meta: KvMetadata) -> Self {
+        Self {
+            current_version: meta.current_version,
+            created_at: meta.created_at.to_rfc3339(),
+            updated_at: meta.updated_at.to_rfc3339(),
+            version_count: meta.version_count,
+            max_versions: meta.max_versions,
+            custom_metadata: meta.custom_metadata,
+            cas_required: meta.cas_required,
+            delete_version_after_secs: meta.delete_version_after_secs,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -105,16 +206,33 @@ pub struct ListResponse {
     pub keys: Vec<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct CopiedSecret {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CopyResponse {
+    pub secrets: Vec<CopiedSecret>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HashesResponse {
+    /// Secret path relative to the prefix → key → hex HMAC of the value.
+    pub secrets: BTreeMap<String, BTreeMap<String, String>>,
+}
+
 // ── Handlers ─────────────────────────────────────────────────────────
 
 /// Read a secret from the KV engine.
-async fn read_secret(
+pub(crate) async fn read_secret(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
     Path(path): Path<String>,
 ) -> Result<Json<SecretResponse>, AppError> {
     validate_secret_path(&path)?;
-    let mount_path = resolve_mount(&path);
 
     state
         .policy_store
@@ -135,6 +253,43 @@ async fn read_secret(
         })
         .await?;
 
+    let (reader_id, _) = ActivityLog::client_id(auth.entity_id.as_deref(), &auth.token_hash);
+    state
+        .secret_usage
+        .record(&format!("{mount_path}{path}"), &reader_id)
+        .await;
+
+    // Mounts with `lease_reads` hand every reader a lease so that rotating
+    // the secret and revoking outstanding leases notifies consumers.
+    let config = engine.config();
+    if config.lease_reads {
+        let version = response
+            .data
+            .as_ref()
+            .and_then(|d| d.pointer("/metadata/version"))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+
+        let lease = Lease {
+            id: uuid::Uuid::new_v4().to_string(),
+            engine_path: format!("{mount_path}data/{path}"),
+            issued_at: chrono::Utc::now(),
+            ttl_secs: config.read_lease_ttl_secs,
+            max_ttl_secs: 0,
+            renewable: true,
+            data: serde_json::json!({ "path": path, "version": version }),
+            token_hash: auth.token_hash,
+        };
+        let lease_id = state.lease_manager.create(&lease).await?;
+
+        return Ok(Json(SecretResponse {
+            data: response.data,
+            lease_id: Some(lease_id),
+            lease_duration: Some(lease.ttl_secs),
+            renewable: true,
+        }));
+    }
+
     Ok(Json(SecretResponse {
         data: response.data,
         lease_id: response.lease_id,
@@ -144,14 +299,14 @@ async fn read_secret(
 }
 
 /// Write a secret to the KV engine.
-async fn write_secret(
+pub(crate) async fn write_secret(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
     Path(path): Path<String>,
-    Json(body): Json<serde_json::Value>,
+    Json(mut body): Json<serde_json::Value>,
 ) -> Result<(StatusCode, Json<SecretResponse>), AppError> {
     validate_secret_path(&path)?;
-    let mount_path = resolve_mount(&path);
 
     state
         .policy_store
@@ -162,15 +317,21 @@ async fn write_secret(
         )
         .await?;
 
+    let cas = take_cas_option(&mut body)?;
     let engine = get_engine(&state, &mount_path).await?;
 
-    let response = engine
-        .handle(&EngineRequest {
-            operation: Operation::Write,
-            path: path.clone(),
-            data: Some(body),
-        })
-        .await?;
+    let response = engine.write_cas(&path, Some(body), cas).await?;
+    publish_kv_event(
+        &state,
+        TOPIC_KV_WRITE,
+        &mount_path,
+        &path,
+        response
+            .data
+            .as_ref()
+            .and_then(|d| d.get("version"))
+            .and_then(serde_json::Value::as_u64),
+    );
 
     Ok((
         StatusCode::OK,
@@ -184,13 +345,13 @@ async fn write_secret(
 }
 
 /// Delete a secret from the KV engine (soft delete).
-async fn delete_secret(
+pub(crate) async fn delete_secret(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
     Path(path): Path<String>,
 ) -> Result<StatusCode, AppError> {
     validate_secret_path(&path)?;
-    let mount_path = resolve_mount(&path);
 
     state
         .policy_store
@@ -210,18 +371,19 @@ async fn delete_secret(
             data: None,
         })
         .await?;
+    publish_kv_event(&state, TOPIC_KV_DELETE, &mount_path, &path, None);
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 /// Get metadata about a secret.
-async fn get_metadata(
+pub(crate) async fn get_metadata(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
     Path(path): Path<String>,
 ) -> Result<Json<MetadataResponse>, AppError> {
     validate_secret_path(&path)?;
-    let mount_path = resolve_mount(&path);
 
     state
         .policy_store
@@ -236,24 +398,120 @@ async fn get_metadata(
 
     let meta = engine.metadata(&path).await?;
 
-    Ok(Json(MetadataResponse {
-        current_version: meta.current_version,
-        created_at: meta.created_at.to_rfc3339(),
-        updated_at: meta.updated_at.to_rfc3339(),
-        version_count: meta.version_count,
-        max_versions: meta.max_versions,
-    }))
+    Ok(Json(meta.into()))
+}
+
+/// Update a secret's custom metadata, version limit, CAS requirement, or
+/// version expiry.
+async fn update_metadata(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
+    Path(path): Path<String>,
+    Json(body): Json<UpdateMetadataRequest>,
+) -> Result<Json<MetadataResponse>, AppError> {
+    validate_secret_path(&path)?;
+    let delete_version_after_secs = body
+        .delete_version_after
+        .as_deref()
+        .map(parse_duration)
+        .transpose()?
+        .map(|d| d.num_seconds());
+
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount_path}metadata/{path}"),
+            &Capability::Update,
+        )
+        .await?;
+
+    let engine = get_engine(&state, &mount_path).await?;
+
+    let meta = engine
+        .update_metadata(
+            &path,
+            KvMetadataUpdate {
+                custom_metadata: body.custom_metadata,
+                max_versions: body.max_versions,
+                cas_required: body.cas_required,
+                delete_version_after_secs,
+            },
+        )
+        .await?;
+    publish_kv_event(
+        &state,
+        TOPIC_KV_METADATA,
+        &mount_path,
+        &path,
+        Some(u64::from(meta.current_version)),
+    );
+
+    Ok(Json(meta.into()))
+}
+
+/// Permanently destroy a secret and its version history.
+async fn destroy_secret(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
+    Path(path): Path<String>,
+) -> Result<StatusCode, AppError> {
+    validate_secret_path(&path)?;
+
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount_path}metadata/{path}"),
+            &Capability::Delete,
+        )
+        .await?;
+
+    let engine = get_engine(&state, &mount_path).await?;
+    engine.destroy(&path).await?;
+    publish_kv_event(&state, TOPIC_KV_DESTROY, &mount_path, &path, None);
+
+    if let Err(e) = state
+        .secret_usage
+        .remove(&format!("{mount_path}{path}"))
+        .await
+    {
+        tracing::warn!(error = %e, "failed to clear usage counters for destroyed secret");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// List secret keys under a prefix.
-async fn list_secrets(
+pub(crate) async fn list_secrets(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
     Path(path): Path<String>,
 ) -> Result<Json<SecretResponse>, AppError> {
     validate_secret_path(&path)?;
-    let mount_path = resolve_mount(&path);
+    list_keys(&state, &auth, &mount_path, path).await
+}
 
+/// List every secret key in the mount.
+pub(crate) async fn list_all_secrets(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
+) -> Result<Json<SecretResponse>, AppError> {
+    list_keys(&state, &auth, &mount_path, String::new()).await
+}
+
+/// List the keys under `path` (the whole mount when empty), checking
+/// `list` on it.
+async fn list_keys(
+    state: &AppState,
+    auth: &AuthContext,
+    mount_path: &str,
+    path: String,
+) -> Result<Json<SecretResponse>, AppError> {
     state
         .policy_store
         .check(
@@ -263,12 +521,12 @@ async fn list_secrets(
         )
         .await?;
 
-    let engine = get_engine(&state, &mount_path).await?;
+    let engine = get_engine(state, mount_path).await?;
 
     let response = engine
         .handle(&EngineRequest {
             operation: Operation::List,
-            path: path.clone(),
+            path,
             data: None,
         })
         .await?;
@@ -281,26 +539,234 @@ async fn list_secrets(
     }))
 }
 
+/// Copy a secret or subtree, keeping its versions and metadata.
+async fn copy_secrets(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
+    Json(body): Json<CopyRequest>,
+) -> Result<Json<CopyResponse>, AppError> {
+    transfer_secrets(&state, &auth, &mount_path, body, false).await
+}
+
+/// Move a secret or subtree, keeping its versions and metadata.
+async fn move_secrets(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
+    Json(body): Json<CopyRequest>,
+) -> Result<Json<CopyResponse>, AppError> {
+    transfer_secrets(&state, &auth, &mount_path, body, true).await
+}
+
+/// Copy, or with `remove_source` move, the secrets `body` names after
+/// checking the caller's access to every one of them.
+async fn transfer_secrets(
+    state: &AppState,
+    auth: &AuthContext,
+    mount_path: &str,
+    body: CopyRequest,
+    remove_source: bool,
+) -> Result<Json<CopyResponse>, AppError> {
+    validate_secret_path(&body.from)?;
+    validate_secret_path(&body.to)?;
+    let engine = get_engine(state, mount_path).await?;
+
+    let pairs = engine.copy_targets(&body.from, &body.to).await?;
+    for (from, to) in &pairs {
+        validate_secret_path(to)?;
+        let checks = [
+            (format!("{mount_path}data/{from}"), Capability::Read),
+            (format!("{mount_path}data/{to}"), Capability::Create),
+        ];
+        for (path, capability) in &checks {
+            state
+                .policy_store
+                .check(&auth.policies, path, capability)
+                .await?;
+        }
+        if remove_source {
+            state
+                .policy_store
+                .check(
+                    &auth.policies,
+                    &format!("{mount_path}metadata/{from}"),
+                    &Capability::Delete,
+                )
+                .await?;
+        }
+    }
+
+    engine.copy(&pairs, body.overwrite, remove_source).await?;
+
+    for (from, to) in &pairs {
+        publish_kv_event(state, TOPIC_KV_WRITE, mount_path, to, None);
+        if remove_source {
+            publish_kv_event(state, TOPIC_KV_DESTROY, mount_path, from, None);
+            if let Err(e) = state
+                .secret_usage
+                .remove(&format!("{mount_path}{from}"))
+                .await
+            {
+                tracing::warn!(error = %e, "failed to clear usage counters for moved secret");
+            }
+        }
+    }
+
+    Ok(Json(CopyResponse {
+        secrets: pairs
+            .into_iter()
+            .map(|(from, to)| CopiedSecret { from, to })
+            .collect(),
+    }))
+}
+
+/// HMAC every value of every secret under a prefix.
+async fn value_hashes(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
+    Path(path): Path<String>,
+) -> Result<Json<HashesResponse>, AppError> {
+    validate_secret_path(&path)?;
+    hash_tree(&state, &auth, &mount_path, &path).await
+}
+
+/// HMAC every value of every secret in the mount.
+async fn all_value_hashes(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(KvMount(mount_path)): Extension<KvMount>,
+) -> Result<Json<HashesResponse>, AppError> {
+    hash_tree(&state, &auth, &mount_path, "").await
+}
+
+/// HMAC the values under `path`, checking `list` on it and `read` on each
+/// secret. Deleted secrets are left out.
+async fn hash_tree(
+    state: &AppState,
+    auth: &AuthContext,
+    mount_path: &str,
+    path: &str,
+) -> Result<Json<HashesResponse>, AppError> {
+    let dir = match path.trim_matches('/') {
+        "" => String::new(),
+        trimmed => format!("{trimmed}/"),
+    };
+
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount_path}list/{dir}"),
+            &Capability::List,
+        )
+        .await?;
+
+    let engine = get_engine(state, mount_path).await?;
+    let listing = engine
+        .handle(&EngineRequest {
+            operation: Operation::List,
+            path: dir.clone(),
+            data: None,
+        })
+        .await?;
+    let keys: Vec<String> = listing
+        .data
+        .as_ref()
+        .and_then(|d| d.get("keys"))
+        .and_then(serde_json::Value::as_array)
+        .map(|keys| {
+            keys.iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut secrets = BTreeMap::new();
+    for key in keys {
+        let secret_path = format!("{dir}{key}");
+        state
+            .policy_store
+            .check(
+                &auth.policies,
+                &format!("{mount_path}data/{secret_path}"),
+                &Capability::Read,
+            )
+            .await?;
+        let read = engine
+            .handle(&EngineRequest {
+                operation: Operation::Read,
+                path: secret_path,
+                data: None,
+            })
+            .await;
+        let data = match read {
+            Ok(response) => response.data,
+            Err(EngineError::NotFound { .. }) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let Some(values) = data.as_ref().and_then(|d| d.get("data")) else {
+            continue;
+        };
+        let hashes = values
+            .as_object()
+            .map(|map| {
+                unwrap_data_envelope(map.clone())
+                    .iter()
+                    .map(|(k, v)| (k.clone(), state.audit_manager.hmac_field(&v.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        secrets.insert(key, hashes);
+    }
+
+    Ok(Json(HashesResponse { secrets }))
+}
+
 // ── Helpers ──────────────────────────────────────────────────────────
 
-/// Resolve the mount path for a given secret path.
-///
-/// For now, all secrets go through the default `secret/` mount.
-/// A full implementation would use the mount table to resolve dynamically.
-fn resolve_mount(_path: &str) -> String {
-    "secret/".to_owned()
+/// Remove the write `options` from a request body, returning its `cas`.
+fn take_cas_option(body: &mut serde_json::Value) -> Result<Option<u32>, AppError> {
+    let Some(options) = body.as_object_mut().and_then(|m| m.remove("options")) else {
+        return Ok(None);
+    };
+    match options.get("cas") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(cas) => cas
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                AppError::BadRequest("options.cas must be a non-negative version number".to_owned())
+            }),
+    }
 }
 
-/// Get the KV engine for a mount path.
-async fn get_engine(
+/// Publish a `kv.*` event for a secret, keyed by its `data/` ACL path.
+pub(crate) fn publish_kv_event(
     state: &AppState,
+    topic: &str,
     mount_path: &str,
-) -> Result<Arc<zvault_core::engine::KvEngine>, AppError> {
+    path: &str,
+    version: Option<u64>,
+) {
+    state.event_bus.publish(
+        topic,
+        serde_json::json!({
+            "mount": mount_path,
+            "path": format!("{mount_path}data/{path}"),
+            "version": version,
+        }),
+    );
+}
+
+/// Get the KV engine for a mount path.
+async fn get_engine(state: &AppState, mount_path: &str) -> Result<Arc<KvEngine>, AppError> {
     state
-        .kv_engines
-        .read()
+        .engines
+        .get::<KvEngine>(mount_path)
         .await
-        .get(mount_path)
-        .cloned()
         .ok_or_else(|| AppError::NotFound(format!("no engine mounted at '{mount_path}'")))
 }
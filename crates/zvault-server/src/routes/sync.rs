@@ -0,0 +1,488 @@
+//! Secret sync routes: `/v1/sys/sync/*`
+//!
+//! Manages syncs that push KV secrets to GitHub Actions repository or
+//! environment secrets, or to Kubernetes Secrets, runs them on demand, and
+//! reports drift. Syncs also run from the server's sync worker through
+//! [`run`] and [`run_kubernetes`] when a mapped secret is written. GitHub
+//! and Kubernetes tokens are write-only: responses never include them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+use zvault_core::engine::KvEngine;
+use zvault_core::policy::Capability;
+use zvault_core::sync::{
+    DriftEntry, DriftStatus, GithubSync, GithubSyncParams, SyncMapping, SyncReport,
+};
+use zvault_core::sync_kubernetes::{KubernetesSync, KubernetesSyncParams};
+
+/// Build the `/v1/sys/sync` router.
+///
+/// Paths:
+/// - `GET  /v1/sys/sync/github` — list GitHub syncs with their status
+/// - `POST|GET|DELETE /v1/sys/sync/github/{name}` — manage one
+/// - `POST /v1/sys/sync/github/{name}/run` — push changed secrets now
+/// - `GET  /v1/sys/sync/github/{name}/drift` — compare GitHub with the vault
+/// - `GET  /v1/sys/sync/kubernetes` — list Kubernetes syncs with their status
+/// - `POST|GET|DELETE /v1/sys/sync/kubernetes/{name}` — manage one
+/// - `POST /v1/sys/sync/kubernetes/{name}/run` — apply the Secret now
+/// - `GET  /v1/sys/sync/kubernetes/{name}/drift` — compare the Secret with the vault
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/github", get(list_syncs))
+        .route(
+            "/github/{name}",
+            post(put_sync).get(get_sync).delete(delete_sync),
+        )
+        .route("/github/{name}/run", post(run_now))
+        .route("/github/{name}/drift", get(drift))
+        .route("/kubernetes", get(list_kubernetes))
+        .route(
+            "/kubernetes/{name}",
+            post(put_kubernetes)
+                .get(get_kubernetes)
+                .delete(delete_kubernetes),
+        )
+        .route("/kubernetes/{name}/run", post(run_kubernetes_now))
+        .route("/kubernetes/{name}/drift", get(kubernetes_drift))
+}
+
+// ── Request / Response types ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct SyncRequest {
+    /// `owner/repo`.
+    pub repository: String,
+    /// Sync this deployment environment's secrets instead of the
+    /// repository's.
+    #[serde(default)]
+    pub environment: Option<String>,
+    /// GitHub REST API URL, for GitHub Enterprise Server.
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Token allowed to write the repository's secrets. Omit to keep the
+    /// current one.
+    #[serde(default)]
+    pub token: Option<String>,
+    pub mappings: Vec<SyncMapping>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KubernetesSyncRequest {
+    pub namespace: String,
+    pub secret_name: String,
+    /// API server URL. Omit to use the cluster the server runs in.
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Bearer token for `api_url`. Omit to keep the current one.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// PEM CA certificate for `api_url`. Omit to keep the current one, `""`
+    /// to remove it.
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    pub mappings: Vec<SyncMapping>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RunRequest {
+    /// Push every secret, even those already in sync.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncResponse {
+    pub name: String,
+    pub repository: String,
+    pub environment: Option<String>,
+    pub api_url: String,
+    pub mappings: Vec<SyncMapping>,
+    pub enabled: bool,
+    /// GitHub secret names pushed so far.
+    pub secrets: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub failures: u32,
+    pub next_retry_at: Option<DateTime<Utc>>,
+}
+
+impl From<GithubSync> for SyncResponse {
+    fn from(s: GithubSync) -> Self {
+        Self {
+            name: s.name,
+            repository: s.repository,
+            environment: s.environment,
+            api_url: s.api_url,
+            mappings: s.mappings,
+            enabled: s.enabled,
+            secrets: s.pushed.into_keys().collect(),
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+            last_synced_at: s.last_synced_at,
+            last_error: s.last_error,
+            failures: s.failures,
+            next_retry_at: s.next_retry_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KubernetesSyncResponse {
+    pub name: String,
+    pub namespace: String,
+    pub secret_name: String,
+    /// `None` when syncing to the cluster the server runs in.
+    pub api_url: Option<String>,
+    /// Whether a CA certificate is set for `api_url`.
+    pub custom_ca: bool,
+    pub labels: BTreeMap<String, String>,
+    pub mappings: Vec<SyncMapping>,
+    pub enabled: bool,
+    /// Secret keys applied so far.
+    pub keys: Vec<String>,
+    /// `zvault.io/secret-hash` as last applied.
+    pub secret_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub failures: u32,
+    pub next_retry_at: Option<DateTime<Utc>>,
+}
+
+impl From<KubernetesSync> for KubernetesSyncResponse {
+    fn from(s: KubernetesSync) -> Self {
+        Self {
+            custom_ca: s.ca_cert.is_some(),
+            name: s.name,
+            namespace: s.namespace,
+            secret_name: s.secret_name,
+            api_url: s.api_url,
+            labels: s.labels,
+            mappings: s.mappings,
+            enabled: s.enabled,
+            keys: s.applied.into_keys().collect(),
+            secret_hash: s.secret_hash,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+            last_synced_at: s.last_synced_at,
+            last_error: s.last_error,
+            failures: s.failures,
+            next_retry_at: s.next_retry_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncListResponse {
+    pub syncs: Vec<SyncResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KubernetesSyncListResponse {
+    pub syncs: Vec<KubernetesSyncResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DriftResponse {
+    pub name: String,
+    /// Whether every secret is in sync.
+    pub in_sync: bool,
+    pub secrets: Vec<DriftEntry>,
+}
+
+impl DriftResponse {
+    fn new(name: String, secrets: Vec<DriftEntry>) -> Self {
+        Self {
+            in_sync: secrets.iter().all(|s| s.status == DriftStatus::InSync),
+            name,
+            secrets,
+        }
+    }
+}
+
+// ── GitHub handlers ──────────────────────────────────────────────────
+
+async fn list_syncs(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<SyncListResponse>, AppError> {
+    check(&state, &auth, "sys/sync/github", Capability::List).await?;
+    let syncs = state.sync.list().await?;
+    Ok(Json(SyncListResponse {
+        syncs: syncs.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Create or replace a sync. The caller must also be allowed to read every
+/// mapped secret, since the server will copy it to GitHub on their behalf.
+async fn put_sync(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<SyncRequest>,
+) -> Result<Json<SyncResponse>, AppError> {
+    check(&state, &auth, &sync_path(&name), Capability::Update).await?;
+    check_mappings(&state, &auth, &body.mappings).await?;
+
+    let sync = state
+        .sync
+        .put(
+            &name,
+            GithubSyncParams {
+                repository: body.repository,
+                environment: body.environment,
+                api_url: body.api_url,
+                token: body.token,
+                mappings: body.mappings,
+                enabled: body.enabled,
+            },
+        )
+        .await?;
+    Ok(Json(sync.into()))
+}
+
+async fn get_sync(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<SyncResponse>, AppError> {
+    check(&state, &auth, &sync_path(&name), Capability::Read).await?;
+    Ok(Json(state.sync.get(&name).await?.into()))
+}
+
+async fn delete_sync(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &sync_path(&name), Capability::Delete).await?;
+    state.sync.delete(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn run_now(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    body: Option<Json<RunRequest>>,
+) -> Result<Json<SyncReport>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("{}/run", sync_path(&name)),
+        Capability::Update,
+    )
+    .await?;
+    let force = body.is_some_and(|Json(b)| b.force);
+    Ok(Json(run(&state, &name, force).await?))
+}
+
+async fn drift(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<DriftResponse>, AppError> {
+    check(&state, &auth, &sync_path(&name), Capability::Read).await?;
+    let engines = kv_engines(&state).await;
+    let secrets = state.sync.drift(&name, &engines).await?;
+    Ok(Json(DriftResponse::new(name, secrets)))
+}
+
+// ── Kubernetes handlers ──────────────────────────────────────────────
+
+async fn list_kubernetes(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<KubernetesSyncListResponse>, AppError> {
+    check(&state, &auth, "sys/sync/kubernetes", Capability::List).await?;
+    let syncs = state.kubernetes_sync.list().await?;
+    Ok(Json(KubernetesSyncListResponse {
+        syncs: syncs.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Create or replace a Kubernetes sync. As for GitHub syncs, the caller
+/// must be allowed to read every mapped secret.
+async fn put_kubernetes(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    Json(body): Json<KubernetesSyncRequest>,
+) -> Result<Json<KubernetesSyncResponse>, AppError> {
+    check(&state, &auth, &kubernetes_path(&name), Capability::Update).await?;
+    check_mappings(&state, &auth, &body.mappings).await?;
+
+    let sync = state
+        .kubernetes_sync
+        .put(
+            &name,
+            KubernetesSyncParams {
+                namespace: body.namespace,
+                secret_name: body.secret_name,
+                api_url: body.api_url,
+                token: body.token,
+                ca_cert: body.ca_cert,
+                labels: body.labels,
+                mappings: body.mappings,
+                enabled: body.enabled,
+            },
+        )
+        .await?;
+    Ok(Json(sync.into()))
+}
+
+async fn get_kubernetes(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<KubernetesSyncResponse>, AppError> {
+    check(&state, &auth, &kubernetes_path(&name), Capability::Read).await?;
+    Ok(Json(state.kubernetes_sync.get(&name).await?.into()))
+}
+
+async fn delete_kubernetes(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check(&state, &auth, &kubernetes_path(&name), Capability::Delete).await?;
+    state.kubernetes_sync.delete(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn run_kubernetes_now(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+    body: Option<Json<RunRequest>>,
+) -> Result<Json<SyncReport>, AppError> {
+    check(
+        &state,
+        &auth,
+        &format!("{}/run", kubernetes_path(&name)),
+        Capability::Update,
+    )
+    .await?;
+    let force = body.is_some_and(|Json(b)| b.force);
+    Ok(Json(run_kubernetes(&state, &name, force).await?))
+}
+
+async fn kubernetes_drift(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(name): Path<String>,
+) -> Result<Json<DriftResponse>, AppError> {
+    check(&state, &auth, &kubernetes_path(&name), Capability::Read).await?;
+    let engines = kv_engines(&state).await;
+    let secrets = state.kubernetes_sync.drift(&name, &engines).await?;
+    Ok(Json(DriftResponse::new(name, secrets)))
+}
+
+// ── Sync ─────────────────────────────────────────────────────────────
+
+/// Push the changed secrets of sync `name` to GitHub, or all of them with
+/// `force`. Used by the run endpoint and the sync worker.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the sync is gone, and
+/// `AppError::BadRequest` if the sync failed (recorded on the sync).
+pub async fn run(state: &AppState, name: &str, force: bool) -> Result<SyncReport, AppError> {
+    let engines = kv_engines(state).await;
+    Ok(state.sync.sync(name, &engines, force).await?)
+}
+
+/// Apply the Secret of Kubernetes sync `name` if it differs from the vault,
+/// or unconditionally with `force`. Used by the run endpoint and the sync
+/// worker.
+///
+/// # Errors
+///
+/// Returns `AppError::NotFound` if the sync is gone, and
+/// `AppError::BadRequest` if the sync failed (recorded on the sync).
+pub async fn run_kubernetes(
+    state: &AppState,
+    name: &str,
+    force: bool,
+) -> Result<SyncReport, AppError> {
+    let engines = kv_engines(state).await;
+    Ok(state.kubernetes_sync.sync(name, &engines, force).await?)
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+async fn check(
+    state: &AppState,
+    auth: &AuthContext,
+    path: &str,
+    capability: Capability,
+) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &capability)
+        .await?;
+    Ok(())
+}
+
+/// Require a KV mount for every mapping and `read` on each mapped secret.
+async fn check_mappings(
+    state: &AppState,
+    auth: &AuthContext,
+    mappings: &[SyncMapping],
+) -> Result<(), AppError> {
+    let engines = kv_engines(state).await;
+    for mapping in mappings {
+        let mount = if mapping.mount.ends_with('/') {
+            mapping.mount.clone()
+        } else {
+            format!("{}/", mapping.mount)
+        };
+        if !engines.contains_key(&mount) {
+            return Err(AppError::NotFound(format!(
+                "no KV engine mounted at '{mount}'"
+            )));
+        }
+        check(
+            state,
+            auth,
+            &format!("{mount}data/{}", mapping.path),
+            Capability::Read,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Every mounted KV engine keyed by mount path, as the sync managers take
+/// them.
+async fn kv_engines(state: &AppState) -> HashMap<String, Arc<KvEngine>> {
+    state.engines.all::<KvEngine>().await.into_iter().collect()
+}
+
+fn sync_path(name: &str) -> String {
+    format!("sys/sync/github/{name}")
+}
+
+fn kubernetes_path(name: &str) -> String {
+    format!("sys/sync/kubernetes/{name}")
+}
@@ -1,33 +1,94 @@
 //! System routes: `/v1/sys/*`
 //!
-//! Handles vault initialization, seal/unseal lifecycle, and health checks.
+//! Handles vault initialization, seal/unseal lifecycle (including
+//! auto-unseal, seal migration, rekeying, and root token generation), health
+//! checks, and the HA leader status.
 //! These endpoints are the first to come online and the last to go down.
+//! Those that need a token — seal migration, rekeying, the status and
+//! cancellation of a root token generation, reading the audit log, and
+//! restoring a backup — are in [`authenticated_router`], behind the auth
+//! middleware, and require `sudo` on their path. The scheduled backup
+//! status is there too and requires `read` on `sys/backup`. Starting a root token generation and submitting shares to
+//! it take no token, since it is how a lost root token is replaced: the
+//! shares authorize it, and the new token leaves the server only encoded
+//! with the one-time pad handed to whoever started the attempt.
 
 use std::sync::Arc;
 
-use axum::extract::State;
+use axum::extract::{DefaultBodyLimit, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
 use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt};
 
+use crate::backup::BackupStatus;
+use crate::build_info;
 use crate::error::AppError;
+use crate::ha::LeaderStatus;
+use crate::middleware::AuthContext;
 use crate::state::AppState;
+use zvault_core::audit::{AuditEntry, AuditFilter, AuditStatus};
+use zvault_core::barrier::Barrier;
+use zvault_core::events::{TOPIC_INITIALIZED, TOPIC_SEALED, TOPIC_UNSEAL_FAILED, TOPIC_UNSEALED};
+use zvault_core::policy::Capability;
+use zvault_core::seal::{
+    GenerateRootStatus, GenerateRootUpdate, RekeyStatus, RekeyUpdate, SEAL_TYPE_SHAMIR,
+};
 use zvault_core::token::CreateTokenParams;
 
+/// Failed unseal attempts in a row that publish `sys.unseal_failed`; each
+/// further run of this many failures publishes it again.
+const UNSEAL_FAILURE_ALERT: u32 = 3;
+
 /// Build the `/v1/sys` router.
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/init", post(init))
         .route("/unseal", post(unseal))
         .route("/seal", post(seal))
+        .route("/generate-root/attempt", post(generate_root_init))
+        .route("/generate-root/update", post(generate_root_update))
         .route("/seal-status", get(seal_status))
         .route("/health", get(health))
-        .route("/audit-log", get(audit_log))
-        .route("/license", get(license_status))
+        .route("/leader", get(leader))
+        .route("/version", get(version))
         .route("/backup", get(backup))
-        .route("/restore", post(restore))
+}
+
+/// Build the `/v1/sys` routes that require a token, nested behind the auth
+/// middleware.
+pub fn authenticated_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/seal/migrate", post(migrate_seal))
+        .route("/rekey/init", get(rekey_status).post(rekey_init))
+        .route("/rekey/update", post(rekey_update))
+        .route("/rekey/cancel", post(rekey_cancel))
+        .route(
+            "/generate-root/attempt",
+            get(generate_root_status).delete(generate_root_cancel),
+        )
+        .route("/audit-log", get(audit_log))
+        .route("/audit-log/count", get(audit_log_count))
+        .route("/backup/status", get(backup_status))
+        .route(
+            "/restore",
+            post(restore).layer(DefaultBodyLimit::max(MAX_SNAPSHOT_BODY)),
+        )
+}
+
+/// Largest request body accepted by restore and mount import, which carry
+/// whole snapshots; other endpoints keep axum's 2 MiB limit.
+pub const MAX_SNAPSHOT_BODY: usize = 256 * 1024 * 1024;
+
+/// Refuse the caller unless policy grants `sudo` on `path`.
+async fn require_sudo(state: &AppState, auth: &AuthContext, path: &str) -> Result<(), AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, path, &Capability::Sudo)
+        .await?;
+    Ok(())
 }
 
 // ── Request / Response types ─────────────────────────────────────────
@@ -44,8 +105,11 @@ pub struct InitRequest {
 /// Response body for `POST /v1/sys/init`.
 #[derive(Debug, Serialize)]
 pub struct InitResponse {
-    /// Base64-encoded unseal key shares (shown once).
+    /// Base64-encoded unseal key shares (shown once). Empty with auto-unseal.
     pub unseal_shares: Vec<String>,
+    /// Base64-encoded recovery key shares (shown once, auto-unseal only).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recovery_shares: Vec<String>,
     /// Root token for initial authentication.
     pub root_token: String,
 }
@@ -53,10 +117,116 @@ pub struct InitResponse {
 /// Request body for `POST /v1/sys/unseal`.
 #[derive(Debug, Deserialize)]
 pub struct UnsealRequest {
-    /// Base64-encoded unseal key share.
+    /// Base64-encoded unseal key share. Ignored with auto-unseal.
+    #[serde(default)]
     pub share: String,
 }
 
+/// Request body for `POST /v1/sys/seal/migrate`.
+#[derive(Debug, Deserialize)]
+pub struct MigrateSealRequest {
+    /// `auto` to move to the configured KMS, `shamir` to move back.
+    pub to: String,
+    /// Current unseal shares (to `auto`) or recovery shares (to `shamir`).
+    pub shares: Vec<String>,
+}
+
+/// Response body for `POST /v1/sys/seal/migrate`.
+#[derive(Debug, Serialize)]
+pub struct MigrateSealResponse {
+    /// Seal type after migration.
+    pub seal_type: String,
+    /// New unseal shares (shown once, migration to `shamir`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unseal_shares: Vec<String>,
+    /// New recovery shares (shown once, migration to `auto`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recovery_shares: Vec<String>,
+}
+
+/// Request body for `POST /v1/sys/rekey/init`.
+#[derive(Debug, Deserialize)]
+pub struct RekeyInitRequest {
+    /// Number of new shares to generate (1-10).
+    pub shares: u8,
+    /// Minimum new shares required (2..=shares).
+    pub threshold: u8,
+}
+
+/// Request body for `POST /v1/sys/rekey/update`.
+#[derive(Debug, Deserialize)]
+pub struct RekeyUpdateRequest {
+    /// Nonce returned by `POST /v1/sys/rekey/init`.
+    pub nonce: String,
+    /// A current unseal share (recovery share with auto-unseal).
+    pub share: String,
+}
+
+/// Response body for `GET /v1/sys/rekey/init` and the rekey endpoints.
+#[derive(Debug, Serialize)]
+pub struct RekeyResponse {
+    /// Whether a rekey is in progress.
+    pub started: bool,
+    /// Whether the rekey just completed.
+    pub complete: bool,
+    /// Progress of the rekey in progress.
+    #[serde(flatten)]
+    pub status: Option<RekeyStatus>,
+    /// New unseal shares (shown once, Shamir seal).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unseal_shares: Vec<String>,
+    /// New recovery shares (shown once, auto-unseal).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recovery_shares: Vec<String>,
+}
+
+impl RekeyResponse {
+    fn pending(status: Option<RekeyStatus>) -> Self {
+        Self {
+            started: status.is_some(),
+            complete: false,
+            status,
+            unseal_shares: Vec::new(),
+            recovery_shares: Vec::new(),
+        }
+    }
+}
+
+/// Request body for `POST /v1/sys/generate-root/update`.
+#[derive(Debug, Deserialize)]
+pub struct GenerateRootUpdateRequest {
+    /// Nonce returned by `POST /v1/sys/generate-root/attempt`.
+    pub nonce: String,
+    /// A current unseal share (recovery share with auto-unseal).
+    pub share: String,
+}
+
+/// Response body for the `/v1/sys/generate-root` endpoints.
+#[derive(Debug, Serialize)]
+pub struct GenerateRootResponse {
+    /// Whether an attempt is in progress.
+    pub started: bool,
+    /// Whether the attempt just completed.
+    pub complete: bool,
+    /// Progress of the attempt in progress.
+    #[serde(flatten)]
+    pub status: Option<GenerateRootStatus>,
+    /// The new root token XOR-ed with the attempt's one-time pad, base64.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoded_token: Option<String>,
+}
+
+impl GenerateRootResponse {
+    fn pending(status: Option<GenerateRootStatus>) -> Self {
+        Self {
+            started: status.is_some(),
+            complete: false,
+            status,
+            encoded_token: None,
+        }
+    }
+}
+
 /// Response body for `POST /v1/sys/unseal`.
 #[derive(Debug, Serialize)]
 pub struct UnsealResponse {
@@ -81,6 +251,8 @@ pub struct SealStatusResponse {
     pub shares: u8,
     /// Shares submitted in current unseal attempt.
     pub progress: u8,
+    /// `shamir` or the auto-unseal KMS (e.g. `devkms`).
+    pub seal_type: String,
 }
 
 // ── Handlers ─────────────────────────────────────────────────────────
@@ -89,15 +261,22 @@ pub struct SealStatusResponse {
 ///
 /// Generates a root key, splits the unseal key into Shamir shares, and
 /// returns the shares + root token. The vault is left sealed.
+///
+/// With auto-unseal, returns recovery shares instead and leaves the vault
+/// unsealed.
 async fn init(
     State(state): State<Arc<AppState>>,
     Json(body): Json<InitRequest>,
 ) -> Result<(StatusCode, Json<InitResponse>), AppError> {
     let result = state.seal_manager.init(body.shares, body.threshold).await?;
+    let auto = result.unseal_shares.is_empty();
 
     // The vault is sealed after init. We need to temporarily unseal it to
     // persist the root token in the TokenStore (which goes through the barrier).
     // We have all shares at this point, so we can reconstruct the unseal key.
+    if auto {
+        state.seal_manager.auto_unseal().await?;
+    }
     for share in &result.unseal_shares {
         let progress = state.seal_manager.submit_unseal_share(share).await?;
         if progress.is_none() {
@@ -105,11 +284,68 @@ async fn init(
         }
     }
 
-    // Store the root token in the TokenStore so auth middleware can find it.
+    store_root_token(&state, &result.root_token).await?;
+
+    // Re-seal a Shamir vault; the operator must unseal it using the shares.
+    if auto {
+        after_unseal(&state).await;
+    } else {
+        state.seal_manager.seal().await?;
+    }
+    state.event_bus.publish(
+        TOPIC_INITIALIZED,
+        serde_json::json!({ "shares": body.shares, "threshold": body.threshold }),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(InitResponse {
+            unseal_shares: result.unseal_shares,
+            recovery_shares: result.recovery_shares,
+            root_token: result.root_token,
+        }),
+    ))
+}
+
+/// Unseal key and root token of a dev-mode vault.
+#[derive(Debug)]
+pub struct DevCredentials {
+    pub unseal_key: String,
+    pub root_token: String,
+}
+
+/// Initialize a dev-mode vault with a single unseal share and unseal it.
+///
+/// # Errors
+///
+/// Returns [`AppError`] if the vault is already initialized or storage
+/// fails.
+pub async fn init_dev(state: &AppState) -> Result<DevCredentials, AppError> {
+    let result = state.seal_manager.init(1, 1).await?;
+    let unseal_key = result
+        .unseal_shares
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::Internal("init returned no unseal share".to_owned()))?;
+    state.seal_manager.submit_unseal_share(&unseal_key).await?;
+    store_root_token(state, &result.root_token).await?;
+    after_unseal(state).await;
+    state.event_bus.publish(
+        TOPIC_INITIALIZED,
+        serde_json::json!({ "shares": 1, "threshold": 1, "dev": true }),
+    );
+    Ok(DevCredentials {
+        unseal_key,
+        root_token: result.root_token,
+    })
+}
+
+/// Store the root token in the `TokenStore` so auth middleware can find it.
+async fn store_root_token(state: &AppState, root_token: &str) -> Result<(), AppError> {
     state
         .token_store
         .create_with_token(
-            &result.root_token,
+            root_token,
             CreateTokenParams {
                 policies: vec!["root".to_owned()],
                 ttl: None,
@@ -118,50 +354,294 @@ async fn init(
                 parent_hash: None,
                 metadata: std::collections::HashMap::new(),
                 display_name: "root".to_owned(),
+                bound_cidrs: Vec::new(),
             },
         )
         .await
         .map_err(|e| AppError::Internal(format!("failed to store root token: {e}")))?;
-
-    // Re-seal the vault. The operator must unseal it using the shares.
-    state.seal_manager.seal().await?;
-
-    Ok((
-        StatusCode::OK,
-        Json(InitResponse {
-            unseal_shares: result.unseal_shares,
-            root_token: result.root_token,
-        }),
-    ))
+    Ok(())
 }
 
 /// Submit an unseal key share.
 ///
 /// Returns progress if more shares are needed, or unseals the vault when
-/// the threshold is reached.
+/// the threshold is reached. With auto-unseal the share is ignored and the
+/// KMS unseals the vault.
 async fn unseal(
     State(state): State<Arc<AppState>>,
     Json(body): Json<UnsealRequest>,
 ) -> Result<Json<UnsealResponse>, AppError> {
-    let progress = state.seal_manager.submit_unseal_share(&body.share).await?;
+    let status = state.seal_manager.status().await?;
+    let failed_before = state.seal_manager.failed_unseal_attempts();
+    let result = if status.seal_type == SEAL_TYPE_SHAMIR {
+        state.seal_manager.submit_unseal_share(&body.share).await
+    } else {
+        state.seal_manager.auto_unseal().await.map(|()| None)
+    };
+    let progress = match result {
+        Ok(progress) => progress,
+        Err(e) => {
+            alert_failed_unseal(&state, failed_before, &status.seal_type);
+            return Err(e.into());
+        }
+    };
 
-    match progress {
-        Some(p) => Ok(Json(UnsealResponse {
+    if let Some(p) = progress {
+        return Ok(Json(UnsealResponse {
             sealed: true,
             threshold: p.threshold,
             progress: p.submitted,
-        })),
-        None => Ok(Json(UnsealResponse {
-            sealed: false,
-            threshold: 0,
-            progress: 0,
-        })),
+        }));
+    }
+
+    after_unseal(&state).await;
+
+    Ok(Json(UnsealResponse {
+        sealed: false,
+        threshold: 0,
+        progress: 0,
+    }))
+}
+
+/// Publish `sys.unseal_failed` when the attempt that just failed completes
+/// a run of [`UNSEAL_FAILURE_ALERT`] failures.
+fn alert_failed_unseal(state: &AppState, failed_before: u32, seal_type: &str) {
+    let attempts = state.seal_manager.failed_unseal_attempts();
+    if attempts > failed_before && attempts % UNSEAL_FAILURE_ALERT == 0 {
+        state.event_bus.publish(
+            TOPIC_UNSEAL_FAILED,
+            serde_json::json!({ "attempts": attempts, "seal_type": seal_type }),
+        );
+    }
+}
+
+/// Migrate an unsealed vault between Shamir shares and auto-unseal.
+///
+/// Requires `sudo` on `sys/seal/migrate` as well as the current shares. The
+/// shares submitted stop working; the response carries their replacements.
+async fn migrate_seal(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<MigrateSealRequest>,
+) -> Result<Json<MigrateSealResponse>, AppError> {
+    require_sudo(&state, &auth, "sys/seal/migrate").await?;
+    let (unseal_shares, recovery_shares) = match body.to.as_str() {
+        "auto" => (
+            Vec::new(),
+            state.seal_manager.migrate_to_auto(&body.shares).await?,
+        ),
+        "shamir" => (
+            state.seal_manager.migrate_to_shamir(&body.shares).await?,
+            Vec::new(),
+        ),
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "unknown seal type '{other}', expected 'auto' or 'shamir'"
+            )));
+        }
+    };
+
+    let status = state.seal_manager.status().await?;
+    Ok(Json(MigrateSealResponse {
+        seal_type: status.seal_type,
+        unseal_shares,
+        recovery_shares,
+    }))
+}
+
+/// Report the rekey in progress, if any.
+async fn rekey_status(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<RekeyResponse>, AppError> {
+    require_sudo(&state, &auth, "sys/rekey/init").await?;
+    Ok(Json(RekeyResponse::pending(
+        state.seal_manager.rekey_status().await,
+    )))
+}
+
+/// Start a rekey to a new share count and threshold.
+///
+/// Nothing changes until current shareholders submit enough shares to
+/// `POST /v1/sys/rekey/update`.
+async fn rekey_init(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<RekeyInitRequest>,
+) -> Result<Json<RekeyResponse>, AppError> {
+    require_sudo(&state, &auth, "sys/rekey/init").await?;
+    let status = state
+        .seal_manager
+        .rekey_init(body.shares, body.threshold)
+        .await?;
+    Ok(Json(RekeyResponse::pending(Some(status))))
+}
+
+/// Submit a current share towards the rekey in progress.
+///
+/// At the current threshold the rekey completes: the response carries the
+/// new shares and the old ones stop working.
+async fn rekey_update(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<RekeyUpdateRequest>,
+) -> Result<Json<RekeyResponse>, AppError> {
+    require_sudo(&state, &auth, "sys/rekey/update").await?;
+    let shares = match state
+        .seal_manager
+        .rekey_update(&body.nonce, &body.share)
+        .await?
+    {
+        RekeyUpdate::Pending(status) => return Ok(Json(RekeyResponse::pending(Some(status)))),
+        RekeyUpdate::Complete(shares) => shares,
+    };
+
+    let status = state.seal_manager.status().await?;
+    let (unseal_shares, recovery_shares) = if status.seal_type == SEAL_TYPE_SHAMIR {
+        (shares, Vec::new())
+    } else {
+        (Vec::new(), shares)
+    };
+
+    Ok(Json(RekeyResponse {
+        started: false,
+        complete: true,
+        status: None,
+        unseal_shares,
+        recovery_shares,
+    }))
+}
+
+/// Cancel the rekey in progress, discarding submitted shares.
+async fn rekey_cancel(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<StatusCode, AppError> {
+    require_sudo(&state, &auth, "sys/rekey/cancel").await?;
+    state.seal_manager.rekey_cancel().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Report the root token generation in progress, if any.
+async fn generate_root_status(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<GenerateRootResponse>, AppError> {
+    require_sudo(&state, &auth, "sys/generate-root/attempt").await?;
+    Ok(Json(GenerateRootResponse::pending(
+        state.seal_manager.generate_root_status().await,
+    )))
+}
+
+/// Start generating a new root token.
+///
+/// The response carries the one-time pad, shown only here; keep it to
+/// decode the token once shareholders complete the attempt.
+async fn generate_root_init(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<GenerateRootResponse>, AppError> {
+    let status = state.seal_manager.generate_root_init().await?;
+    Ok(Json(GenerateRootResponse::pending(Some(status))))
+}
+
+/// Submit a current share towards the root token generation in progress.
+///
+/// At the current threshold a new root token is stored and returned encoded
+/// with the attempt's one-time pad. The token itself is only registered with
+/// the token store: the encoded form is all that leaves the server, so
+/// submitting the last share reveals nothing without the pad.
+async fn generate_root_update(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<GenerateRootUpdateRequest>,
+) -> Result<Json<GenerateRootResponse>, AppError> {
+    let generated = match state
+        .seal_manager
+        .generate_root_update(&body.nonce, &body.share)
+        .await?
+    {
+        GenerateRootUpdate::Pending(status) => {
+            return Ok(Json(GenerateRootResponse::pending(Some(status))));
+        }
+        GenerateRootUpdate::Complete(generated) => generated,
+    };
+
+    state
+        .token_store
+        .create_with_token(
+            &generated.root_token,
+            CreateTokenParams {
+                policies: vec!["root".to_owned()],
+                ttl: None,
+                max_ttl: None,
+                renewable: false,
+                parent_hash: None,
+                metadata: std::collections::HashMap::new(),
+                display_name: "root".to_owned(),
+                bound_cidrs: Vec::new(),
+            },
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("failed to store root token: {e}")))?;
+
+    Ok(Json(GenerateRootResponse {
+        started: false,
+        complete: true,
+        status: None,
+        encoded_token: Some(generated.encoded_token),
+    }))
+}
+
+/// Cancel the root token generation in progress, discarding submitted shares.
+///
+/// Without a token that may cancel, sealing the vault also clears it.
+async fn generate_root_cancel(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<StatusCode, AppError> {
+    require_sudo(&state, &auth, "sys/generate-root/attempt").await?;
+    state.seal_manager.generate_root_cancel().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Auto-unseal at startup if the vault's KMS is configured.
+///
+/// Returns whether the vault was unsealed. Failures are logged, leaving the
+/// vault sealed for an operator to investigate.
+pub async fn try_auto_unseal(state: &AppState) -> bool {
+    match state.seal_manager.status().await {
+        Ok(s) if s.initialized && s.sealed && s.seal_type != SEAL_TYPE_SHAMIR => {}
+        _ => return false,
+    }
+    if let Err(e) = state.seal_manager.auto_unseal().await {
+        tracing::warn!(error = %e, "auto-unseal failed");
+        return false;
     }
+    after_unseal(state).await;
+    true
+}
+
+/// Reload state that lives behind the barrier after an unseal.
+async fn after_unseal(state: &AppState) {
+    super::audit::restore_devices(state).await;
+    super::mounts::restore_mounts(state).await;
+    super::mounts::ensure_config_mounts(state).await;
+    if let Err(e) = state.license_manager.load().await {
+        tracing::warn!(error = %e, "failed to load license");
+    }
+    if let Err(e) = state.quotas.load().await {
+        tracing::warn!(error = %e, "failed to load quotas");
+    }
+    state
+        .event_bus
+        .publish(TOPIC_UNSEALED, serde_json::json!({ "sealed": false }));
 }
 
 /// Seal the vault, zeroizing all key material from memory.
 async fn seal(State(state): State<Arc<AppState>>) -> Result<StatusCode, AppError> {
     state.seal_manager.seal().await?;
+    state
+        .event_bus
+        .publish(TOPIC_SEALED, serde_json::json!({ "sealed": true }));
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -176,6 +656,7 @@ async fn seal_status(
         threshold: status.threshold,
         shares: status.shares,
         progress: status.progress,
+        seal_type: status.seal_type,
     }))
 }
 
@@ -193,6 +674,7 @@ async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
                 threshold: 0,
                 shares: 0,
                 progress: 0,
+                seal_type: s.seal_type,
             };
             (StatusCode::NOT_IMPLEMENTED, Json(body))
         }
@@ -203,6 +685,7 @@ async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
                 threshold: s.threshold,
                 shares: s.shares,
                 progress: s.progress,
+                seal_type: s.seal_type,
             };
             (StatusCode::SERVICE_UNAVAILABLE, Json(body))
         }
@@ -213,6 +696,7 @@ async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
                 threshold: s.threshold,
                 shares: s.shares,
                 progress: s.progress,
+                seal_type: s.seal_type,
             };
             (StatusCode::OK, Json(body))
         }
@@ -223,12 +707,23 @@ async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
                 threshold: 0,
                 shares: 0,
                 progress: 0,
+                seal_type: SEAL_TYPE_SHAMIR.to_owned(),
             };
             (StatusCode::INTERNAL_SERVER_ERROR, Json(body))
         }
     }
 }
 
+/// Which server is the HA active node. No auth required, so load balancers
+/// and clients can find it.
+async fn leader(State(state): State<Arc<AppState>>) -> Json<LeaderStatus> {
+    let status = match &state.ha {
+        Some(ha) => ha.status().await,
+        None => LeaderStatus::default(),
+    };
+    Json(status)
+}
+
 // ── Audit log read endpoint ──────────────────────────────────────────
 
 /// Query parameters for `GET /v1/sys/audit-log`.
@@ -236,36 +731,133 @@ async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
 pub struct AuditLogQuery {
     /// Maximum number of entries to return (default: 100, max: 1000).
     pub limit: Option<usize>,
+    /// Page through the whole log oldest-first, starting at this cursor.
+    /// Pass `0` for the first page, then each response's `next_cursor`.
+    pub cursor: Option<u64>,
+    #[serde(flatten)]
+    pub filter: AuditFilterQuery,
+}
+
+/// Entry filters shared by `GET /v1/sys/audit-log` and its `/count`.
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditFilterQuery {
+    /// Only entries at or after this time: RFC 3339, or a duration such
+    /// as `30d` meaning that long ago.
+    pub start_time: Option<String>,
+    /// Only entries before this time, in the same forms as `start_time`.
+    pub end_time: Option<String>,
+    /// Only entries whose path starts with this, e.g. `secret/data/db`.
+    pub path_prefix: Option<String>,
+    /// Only entries whose caller has this display name.
+    pub actor: Option<String>,
+    /// Only entries made with the token of this hash (see token lookup).
+    pub token_hash: Option<String>,
+    /// Only entries with this HMAC'd token ID, as it appears in the log.
+    pub token_id: Option<String>,
+    /// Only entries made with the token of this accessor.
+    pub accessor: Option<String>,
+    /// Only entries with this operation (`read`, `write`, `delete`, ...).
+    pub operation: Option<String>,
+    /// `success`, `error`, or an HTTP status code.
+    pub status: Option<String>,
+}
+
+impl AuditFilterQuery {
+    fn to_filter(&self, state: &AppState) -> Result<AuditFilter, AppError> {
+        let time = |at: &Option<String>| at.as_deref().map(parse_time).transpose();
+        let token_id = match (&self.token_id, &self.token_hash) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::BadRequest(
+                    "pass token_id or token_hash, not both".to_owned(),
+                ));
+            }
+            (Some(id), None) => Some(id.clone()),
+            (None, Some(hash)) => Some(state.audit_manager.hmac_field(hash)),
+            (None, None) => None,
+        };
+        let status = self
+            .status
+            .as_deref()
+            .map(|status| {
+                AuditStatus::parse(status).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "invalid status '{status}', expected success, error, or a status code"
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(AuditFilter {
+            start_time: time(&self.start_time)?,
+            end_time: time(&self.end_time)?,
+            path_prefix: self.path_prefix.clone(),
+            actor: self.actor.clone(),
+            token_id,
+            accessor: self.accessor.clone(),
+            operation: self.operation.clone(),
+            status,
+        })
+    }
+}
+
+/// An RFC 3339 timestamp, or a duration (`30d`, `12h`) before now.
+fn parse_time(at: &str) -> Result<chrono::DateTime<chrono::Utc>, AppError> {
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(at) {
+        return Ok(at.with_timezone(&chrono::Utc));
+    }
+    crate::routes::auth::parse_duration(at)
+        .map(|ago| chrono::Utc::now() - ago)
+        .map_err(|_| {
+            AppError::BadRequest(format!(
+                "'{at}' is neither an RFC 3339 timestamp nor a duration"
+            ))
+        })
+}
+
+/// Whether a parsed audit log line passes `filter`.
+fn entry_matches(filter: &AuditFilter, entry: &serde_json::Value) -> bool {
+    filter.is_empty() || AuditEntry::deserialize(entry).is_ok_and(|e| filter.matches(&e))
 }
 
 /// Response body for `GET /v1/sys/audit-log`.
 #[derive(Debug, Serialize)]
 pub struct AuditLogResponse {
-    /// Audit log entries (most recent first).
+    /// Audit log entries (most recent first, or oldest first when paging).
     pub entries: Vec<serde_json::Value>,
     /// Total number of entries returned.
     pub count: usize,
+    /// Cursor for the next page; absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<u64>,
 }
 
 /// Read recent audit log entries from the file backend.
 ///
-/// Returns the most recent entries in reverse chronological order.
-/// No auth required on this endpoint since it's under `/v1/sys` which
-/// is not behind the auth middleware — but the audit file only contains
-/// HMAC'd sensitive fields, so no secrets are exposed.
+/// Returns the most recent entries in reverse chronological order, or with
+/// `cursor`, one page of the whole log in file order. Filters apply to both.
+/// Requires `sudo` on `sys/audit-log`: the filters answer who did what.
 async fn audit_log(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     axum::extract::Query(query): axum::extract::Query<AuditLogQuery>,
 ) -> Result<Json<AuditLogResponse>, AppError> {
-    let limit = query.limit.unwrap_or(100).min(1000);
+    require_sudo(&state, &auth, "sys/audit-log").await?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let filter = query.filter.to_filter(&state)?;
 
     let Some(ref audit_path) = state.audit_file_path else {
         return Ok(Json(AuditLogResponse {
             entries: Vec::new(),
             count: 0,
+            next_cursor: None,
         }));
     };
 
+    if let Some(cursor) = query.cursor {
+        return audit_log_page(audit_path, cursor, limit, &filter)
+            .await
+            .map(Json);
+    }
+
     // Read the audit file. If it doesn't exist yet, return empty.
     let content = match tokio::fs::read_to_string(audit_path).await {
         Ok(c) => c,
@@ -273,6 +865,7 @@ async fn audit_log(
             return Ok(Json(AuditLogResponse {
                 entries: Vec::new(),
                 count: 0,
+                next_cursor: None,
             }));
         }
         Err(e) => {
@@ -285,6 +878,7 @@ async fn audit_log(
         .lines()
         .filter(|line| !line.trim().is_empty())
         .filter_map(|line| serde_json::from_str(line).ok())
+        .filter(|entry| entry_matches(&filter, entry))
         .collect();
 
     // Most recent last in file → reverse to get most recent first.
@@ -292,40 +886,148 @@ async fn audit_log(
     entries.truncate(limit);
 
     let count = entries.len();
-    Ok(Json(AuditLogResponse { entries, count }))
+    Ok(Json(AuditLogResponse {
+        entries,
+        count,
+        next_cursor: None,
+    }))
+}
+
+/// Read up to `limit` matching entries starting at byte offset `cursor`.
+///
+/// The cursor is the byte offset of the next unread line, so each page
+/// costs one seek no matter how large the log is. A trailing line without
+/// a newline is still being written and is left for the next page.
+async fn audit_log_page(
+    audit_path: &str,
+    cursor: u64,
+    limit: usize,
+    filter: &AuditFilter,
+) -> Result<AuditLogResponse, AppError> {
+    let file = match tokio::fs::File::open(audit_path).await {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(AuditLogResponse {
+                entries: Vec::new(),
+                count: 0,
+                next_cursor: None,
+            });
+        }
+        Err(e) => return Err(AppError::Internal(format!("failed to read audit log: {e}"))),
+    };
+    let len = file
+        .metadata()
+        .await
+        .map_err(|e| AppError::Internal(format!("failed to read audit log: {e}")))?
+        .len();
+    if cursor > len {
+        return Err(AppError::BadRequest(format!(
+            "cursor {cursor} is past the end of the audit log"
+        )));
+    }
+
+    let mut reader = tokio::io::BufReader::new(file);
+    reader
+        .seek(std::io::SeekFrom::Start(cursor))
+        .await
+        .map_err(|e| AppError::Internal(format!("failed to seek audit log: {e}")))?;
+
+    let mut entries = Vec::new();
+    let mut offset = cursor;
+    let mut line = String::new();
+    while entries.len() < limit {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| AppError::Internal(format!("failed to read audit log: {e}")))?;
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        offset += read as u64;
+        if let Ok(entry) = serde_json::from_str(line.trim())
+            && entry_matches(filter, &entry)
+        {
+            entries.push(entry);
+        }
+    }
+
+    let count = entries.len();
+    Ok(AuditLogResponse {
+        entries,
+        count,
+        next_cursor: (count == limit).then_some(offset),
+    })
+}
+
+/// Response body for `GET /v1/sys/audit-log/count`.
+#[derive(Debug, Serialize)]
+pub struct AuditLogCountResponse {
+    /// Entries in the whole log that pass the filters.
+    pub count: usize,
+}
+
+/// Count the audit log entries that pass the filters, without returning them.
+async fn audit_log_count(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    axum::extract::Query(query): axum::extract::Query<AuditFilterQuery>,
+) -> Result<Json<AuditLogCountResponse>, AppError> {
+    require_sudo(&state, &auth, "sys/audit-log").await?;
+    let filter = query.to_filter(&state)?;
+    let Some(ref audit_path) = state.audit_file_path else {
+        return Ok(Json(AuditLogCountResponse { count: 0 }));
+    };
+    let file = match tokio::fs::File::open(audit_path).await {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Json(AuditLogCountResponse { count: 0 }));
+        }
+        Err(e) => return Err(AppError::Internal(format!("failed to read audit log: {e}"))),
+    };
+
+    let mut lines = tokio::io::BufReader::new(file).lines();
+    let mut count = 0;
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| AppError::Internal(format!("failed to read audit log: {e}")))?
+    {
+        if let Ok(entry) = serde_json::from_str(line.trim())
+            && entry_matches(&filter, &entry)
+        {
+            count += 1;
+        }
+    }
+    Ok(Json(AuditLogCountResponse { count }))
 }
 
-// ── License status endpoint ──────────────────────────────────────────
+// ── Version endpoint ─────────────────────────────────────────────────
 
-/// Response body for `GET /v1/sys/license`.
+/// Oldest CLI release that speaks this server's API.
+///
+/// Bump when an API change breaks older clients.
+pub(crate) const MIN_CLI_VERSION: &str = "0.2.0";
+
+/// Response body for `GET /v1/sys/version`.
 #[derive(Debug, Serialize)]
-pub struct LicenseResponse {
-    /// Current license tier.
-    pub tier: String,
-    /// Whether a license is installed.
-    pub licensed: bool,
-    /// License ID (if any).
-    pub license_id: Option<String>,
-    /// Licensee email (if any).
-    pub email: Option<String>,
-    /// Expiration date (if any).
-    pub expires_at: Option<String>,
-}
-
-/// Get the current license status.
+pub struct VersionResponse {
+    /// Server version.
+    pub version: String,
+    /// Git revision the server was built from.
+    pub revision: String,
+    /// Minimum supported CLI version.
+    pub min_cli_version: String,
+}
+
+/// Report the server version and the minimum supported CLI version.
 ///
-/// No auth required — returns only tier info, no secrets.
-async fn license_status(State(_state): State<Arc<AppState>>) -> Json<LicenseResponse> {
-    // The license is a CLI-side concept stored in ~/.zvault/license.key.
-    // The server doesn't have direct access to it, so we return a basic
-    // "server edition" response. The dashboard can also check the CLI
-    // license separately.
-    Json(LicenseResponse {
-        tier: "community".to_owned(),
-        licensed: false,
-        license_id: None,
-        email: None,
-        expires_at: None,
+/// No auth required — used by `zvault version --check`.
+async fn version() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        version: build_info::VERSION.to_owned(),
+        revision: build_info::GIT_SHA.to_owned(),
+        min_cli_version: MIN_CLI_VERSION.to_owned(),
     })
 }
 
@@ -362,18 +1064,28 @@ struct BackupEntry {
 /// without the unseal key. Still, this should be protected in production
 /// (e.g., via network policy or reverse proxy auth).
 async fn backup(State(state): State<Arc<AppState>>) -> Result<Json<BackupResponse>, AppError> {
-    // List all keys in the barrier.
-    let keys = state.barrier.list("").await?;
-
-    let mut entries = Vec::with_capacity(keys.len());
-    for key in &keys {
-        if let Ok(Some(data)) = state.barrier.get_raw(key).await {
-            entries.push(BackupEntry {
-                key: key.clone(),
-                value: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data),
-            });
-        }
-    }
+    Ok(Json(create_backup(&state.barrier).await?))
+}
+
+/// Take a backup of all barrier data. Shared by `GET /v1/sys/backup` and
+/// scheduled backups.
+///
+/// # Errors
+///
+/// Returns [`AppError::Sealed`] if the vault is sealed, or
+/// [`AppError::Internal`] if storage or serialization fails.
+pub async fn create_backup(barrier: &Barrier) -> Result<BackupResponse, AppError> {
+    // One point-in-time read, so the backup never mixes entries from before
+    // and after a concurrent write.
+    let entries: Vec<BackupEntry> = barrier
+        .snapshot_raw()
+        .await?
+        .into_iter()
+        .map(|(key, data)| BackupEntry {
+            key,
+            value: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data),
+        })
+        .collect();
 
     let entry_count = entries.len();
     let snapshot_json = serde_json::to_vec(&entries)
@@ -384,11 +1096,29 @@ async fn backup(State(state): State<Arc<AppState>>) -> Result<Json<BackupRespons
 
     let created_at = chrono::Utc::now().to_rfc3339();
 
-    Ok(Json(BackupResponse {
+    Ok(BackupResponse {
         snapshot,
         entry_count,
         created_at,
         version: env!("CARGO_PKG_VERSION").to_owned(),
+    })
+}
+
+/// `GET /v1/sys/backup/status` — Scheduled backup status.
+///
+/// Reports `enabled: false` when `ZVAULT_BACKUP_INTERVAL` is not set.
+/// Requires `read` on `sys/backup`: the status names the backup directory.
+async fn backup_status(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<BackupStatus>, AppError> {
+    state
+        .policy_store
+        .check(&auth.policies, "sys/backup", &Capability::Read)
+        .await?;
+    Ok(Json(match &state.backups {
+        Some(scheduler) => scheduler.status().await,
+        None => BackupStatus::default(),
     }))
 }
 
@@ -397,6 +1127,10 @@ async fn backup(State(state): State<Arc<AppState>>) -> Result<Json<BackupRespons
 pub struct RestoreRequest {
     /// Base64-encoded snapshot (from a previous backup).
     pub snapshot: String,
+    /// Restore only entries whose storage key starts with this prefix
+    /// (e.g. `kv/secret/`). Everything else in the vault is left as is.
+    #[serde(default)]
+    pub prefix: Option<String>,
 }
 
 /// Response body for `POST /v1/sys/restore`.
@@ -404,6 +1138,8 @@ pub struct RestoreRequest {
 pub struct RestoreResponse {
     /// Number of entries restored.
     pub entry_count: usize,
+    /// Number of snapshot entries skipped by the prefix filter.
+    pub skipped: usize,
     /// Whether the restore was successful.
     pub success: bool,
 }
@@ -411,11 +1147,17 @@ pub struct RestoreResponse {
 /// `POST /v1/sys/restore` — Restore barrier data from an encrypted snapshot.
 ///
 /// Overwrites existing data. The vault should be sealed after restore
-/// and re-unsealed to pick up the restored state.
+/// and re-unsealed to pick up the restored state. With `prefix`, only the
+/// matching entries are written, which recovers e.g. one deleted secret
+/// tree without rolling back the rest of the vault; the entries must be
+/// encrypted under a keyring term this vault still has. Requires `sudo` on
+/// `sys/restore`.
 async fn restore(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Json(body): Json<RestoreRequest>,
 ) -> Result<Json<RestoreResponse>, AppError> {
+    require_sudo(&state, &auth, "sys/restore").await?;
     let snapshot_bytes =
         base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &body.snapshot)
             .map_err(|_| AppError::BadRequest("invalid base64 snapshot".to_owned()))?;
@@ -423,20 +1165,32 @@ async fn restore(
     let entries: Vec<BackupEntry> = serde_json::from_slice(&snapshot_bytes)
         .map_err(|e| AppError::BadRequest(format!("invalid snapshot format: {e}")))?;
 
-    let entry_count = entries.len();
+    let prefix = body.prefix.as_deref().unwrap_or_default();
+    let (selected, skipped): (Vec<BackupEntry>, Vec<BackupEntry>) = entries
+        .into_iter()
+        .partition(|entry| entry.key.starts_with(prefix));
 
-    for entry in &entries {
+    // Decode everything before writing anything, so a bad entry cannot
+    // leave a half-applied restore behind.
+    let mut decoded = Vec::with_capacity(selected.len());
+    for entry in selected {
         let value =
             base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &entry.value)
                 .map_err(|_| {
                     AppError::BadRequest(format!("invalid base64 value for key: {}", entry.key))
                 })?;
+        decoded.push((entry.key, value));
+    }
 
-        state.barrier.put_raw(&entry.key, &value).await?;
+    for (key, value) in &decoded {
+        state.barrier.put_raw(key, value).await?;
     }
+    // The snapshot may carry a different keyring.
+    state.barrier.reload_keyring().await?;
 
     Ok(Json(RestoreResponse {
-        entry_count,
+        entry_count: decoded.len(),
+        skipped: skipped.len(),
         success: true,
     }))
 }
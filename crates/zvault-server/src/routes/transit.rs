@@ -2,7 +2,23 @@
 //!
 //! Encryption-as-a-service: create named keys, encrypt/decrypt data,
 //! rotate keys, rewrap ciphertext, and generate data encryption keys.
+//! Per-key version limits (`min_decryption_version`,
+//! `min_encryption_version`) retire old versions after a rewrap.
+//! Keys can also be imported (BYOK) and, when created exportable, exported.
+//! Encrypt and decrypt accept a `batch_input` array for bulk workloads; each
+//! item gets its own result or error.
+//! Tokenization swaps a value for a random token the engine can later
+//! reverse; convergent tokens map equal values to one token and can be
+//! found by value through `lookup`.
+//!
+//! Other transit mounts (e.g. `transit-teamA/`) are forwarded to the same
+//! router by [`super::mounts::mount_router`]; handlers read the mount from
+//! the [`TransitMount`] request extension.
+//!
+//! Key management (create, rotate, list, read) also accepts delegated admin:
+//! `sudo` on `{mount}**`. Encrypt/decrypt always need an explicit grant.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
@@ -17,45 +33,162 @@ use crate::error::AppError;
 use crate::middleware::AuthContext;
 use crate::state::AppState;
 use zvault_core::policy::Capability;
-use zvault_core::transit::TransitEngine;
+use zvault_core::transit::{
+    BatchEncryptItem, CreateKeyOptions, KeyBackend, KeyConfigUpdate, TokenInfo, TokenizeOptions,
+    TransitEngine, TransitKeyInfo,
+};
+
+/// The transit mount a request is served from, e.g. `transit/`.
+#[derive(Debug, Clone)]
+pub struct TransitMount(pub String);
+
+impl Default for TransitMount {
+    fn default() -> Self {
+        Self("transit/".to_owned())
+    }
+}
 
 /// Build the `/v1/transit` router.
 ///
 /// Paths:
-/// - `POST /v1/transit/keys/{name}` — create key
+/// - `POST /v1/transit/keys/{name}` — create key (optional body `{"backend": "pkcs11", "exportable": true}`)
 /// - `POST /v1/transit/keys/{name}/rotate` — rotate key
-/// - `POST /v1/transit/encrypt/{name}` — encrypt
-/// - `POST /v1/transit/decrypt/{name}` — decrypt
+/// - `POST /v1/transit/keys/{name}/config` — set minimum encryption/decryption versions
+/// - `POST /v1/transit/keys/{name}/import` — import a wrapped key
+/// - `GET  /v1/transit/wrapping_key` — RSA public key for wrapping imports
+/// - `GET  /v1/transit/export/{type}/{name}[/{version}]` — export an exportable key
+/// - `POST /v1/transit/encrypt/{name}` — encrypt (`plaintext` or `batch_input`)
+/// - `POST /v1/transit/decrypt/{name}` — decrypt (`ciphertext` or `batch_input`)
 /// - `POST /v1/transit/rewrap/{name}` — rewrap
 /// - `POST /v1/transit/datakey/{name}` — generate data key
+/// - `POST /v1/transit/tokenize/{name}` — tokenize a value (optionally convergent, with metadata)
+/// - `POST /v1/transit/detokenize/{name}` — return the value behind a token
+/// - `POST /v1/transit/lookup/{name}` — token info by `token`, or a convergent token by `plaintext`
 /// - `GET  /v1/transit/keys` — list keys
 /// - `GET  /v1/transit/keys/{name}` — key info
+/// - `DELETE /v1/transit/keys/{name}` — delete key, its tokens, and its HSM objects
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/keys", get(list_keys))
-        .route("/keys/{name}", get(key_info).post(create_key))
+        .route(
+            "/keys/{name}",
+            get(key_info).post(create_key).delete(delete_key),
+        )
         .route("/keys/{name}/rotate", post(rotate_key))
+        .route("/keys/{name}/config", post(update_key_config))
+        .route("/keys/{name}/import", post(import_key))
+        .route("/wrapping_key", get(wrapping_key))
+        .route("/export/{export_type}/{name}", get(export_key))
+        .route(
+            "/export/{export_type}/{name}/{version}",
+            get(export_key_version),
+        )
         .route("/encrypt/{name}", post(encrypt))
         .route("/decrypt/{name}", post(decrypt))
         .route("/rewrap/{name}", post(rewrap))
         .route("/datakey/{name}", post(generate_data_key))
+        .route("/tokenize/{name}", post(tokenize))
+        .route("/detokenize/{name}", post(detokenize))
+        .route("/lookup/{name}", post(lookup))
 }
 
 // ── Request / Response types ─────────────────────────────────────────
 
+#[derive(Debug, Deserialize)]
+pub struct CreateKeyRequest {
+    /// `internal` (default) or `pkcs11` to keep the key in the configured HSM.
+    #[serde(default)]
+    pub backend: Option<String>,
+    /// Allow the key to be exported. Fixed at creation.
+    #[serde(default)]
+    pub exportable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeyConfigRequest {
+    /// Oldest version ciphertexts may be decrypted (and rewrapped) with.
+    #[serde(default)]
+    pub min_decryption_version: Option<u32>,
+    /// Oldest version callers may encrypt with; `0` allows only the latest.
+    #[serde(default)]
+    pub min_encryption_version: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportKeyRequest {
+    /// Base64 of `RSA-OAEP-SHA256(ephemeral) || KWP(ephemeral, key)`.
+    pub ciphertext: String,
+    /// Allow the imported key to be exported. Fixed at import.
+    #[serde(default)]
+    pub exportable: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WrappingKeyResponse {
+    /// PEM-encoded RSA-4096 public key.
+    pub public_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportKeyResponse {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub export_type: String,
+    /// Base64 key material by version.
+    pub keys: HashMap<u32, String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct EncryptRequest {
+    /// Base64-encoded plaintext. Mutually exclusive with `batch_input`.
+    #[serde(default)]
+    pub plaintext: Option<String>,
+    /// Key version to encrypt with. Defaults to the latest.
+    #[serde(default)]
+    pub key_version: Option<u32>,
+    /// Items to encrypt in one call.
+    #[serde(default)]
+    pub batch_input: Option<Vec<BatchEncryptInput>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchEncryptInput {
     /// Base64-encoded plaintext.
     pub plaintext: String,
+    /// Key version to encrypt with. Defaults to the latest.
+    #[serde(default)]
+    pub key_version: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct EncryptResponse {
-    pub ciphertext: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ciphertext: Option<String>,
+    /// Per-item results, in input order, for `batch_input` requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_results: Option<Vec<BatchEncryptResult>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchEncryptResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ciphertext: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DecryptRequest {
+    /// Ciphertext in `vault:v{N}:{base64}` format. Mutually exclusive with `batch_input`.
+    #[serde(default)]
+    pub ciphertext: Option<String>,
+    /// Items to decrypt in one call.
+    #[serde(default)]
+    pub batch_input: Option<Vec<BatchDecryptInput>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchDecryptInput {
     /// Ciphertext in `vault:v{N}:{base64}` format.
     pub ciphertext: String,
 }
@@ -63,13 +196,29 @@ pub struct DecryptRequest {
 #[derive(Debug, Serialize)]
 pub struct DecryptResponse {
     /// Base64-encoded plaintext.
-    pub plaintext: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plaintext: Option<String>,
+    /// Per-item results, in input order, for `batch_input` requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_results: Option<Vec<BatchDecryptResult>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchDecryptResult {
+    /// Base64-encoded plaintext.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plaintext: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RewrapRequest {
     /// Ciphertext to re-wrap under the latest key version.
     pub ciphertext: String,
+    /// Key version to re-wrap to. Defaults to the latest.
+    #[serde(default)]
+    pub key_version: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -85,6 +234,58 @@ pub struct DataKeyResponse {
     pub ciphertext: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TokenizeRequest {
+    /// Base64-encoded value to tokenize.
+    pub plaintext: String,
+    /// Reuse the existing token for an equal value.
+    #[serde(default)]
+    pub convergent: bool,
+    /// Metadata stored with the token.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetokenizeRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LookupRequest {
+    /// Token to describe. Mutually exclusive with `plaintext`.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Base64 value whose convergent token to find.
+    #[serde(default)]
+    pub plaintext: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub token: String,
+    pub convergent: bool,
+    pub metadata: HashMap<String, String>,
+    pub key_version: u32,
+    pub created_at: String,
+    /// Base64-encoded value, only on detokenize.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plaintext: Option<String>,
+}
+
+impl From<TokenInfo> for TokenResponse {
+    fn from(info: TokenInfo) -> Self {
+        Self {
+            token: info.token,
+            convergent: info.convergent,
+            metadata: info.metadata,
+            key_version: info.key_version,
+            created_at: info.created_at.to_rfc3339(),
+            plaintext: None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct KeyListResponse {
     pub keys: Vec<String>,
@@ -95,10 +296,30 @@ pub struct KeyInfoResponse {
     pub name: String,
     pub latest_version: u32,
     pub min_decryption_version: u32,
+    pub min_encryption_version: u32,
     pub supports_encryption: bool,
     pub supports_decryption: bool,
     pub version_count: u32,
     pub created_at: String,
+    pub backend: KeyBackend,
+    pub exportable: bool,
+}
+
+impl From<TransitKeyInfo> for KeyInfoResponse {
+    fn from(info: TransitKeyInfo) -> Self {
+        Self {
+            name: info.name,
+            latest_version: info.latest_version,
+            min_decryption_version: info.min_decryption_version,
+            min_encryption_version: info.min_encryption_version,
+            supports_encryption: info.supports_encryption,
+            supports_decryption: info.supports_decryption,
+            version_count: info.version_count,
+            created_at: info.created_at.to_rfc3339(),
+            backend: info.backend,
+            exportable: info.exportable,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -112,48 +333,171 @@ pub struct RotateResponse {
 async fn create_key(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
     Path(name): Path<String>,
+    body: Option<Json<CreateKeyRequest>>,
 ) -> Result<StatusCode, AppError> {
     state
         .policy_store
-        .check(
+        .check_mount(
             &auth.policies,
-            &format!("transit/keys/{name}"),
+            &mount,
+            &format!("{mount}keys/{name}"),
             &Capability::Create,
         )
         .await?;
 
-    let engine = get_transit_engine(&state).await?;
-    engine.create_key(&name).await?;
+    let (backend, exportable) = match body {
+        Some(Json(b)) => (b.backend, b.exportable),
+        None => (None, false),
+    };
+    let backend = match backend {
+        Some(backend) => backend.parse::<KeyBackend>()?,
+        None => KeyBackend::Internal,
+    };
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    engine
+        .create_key_with_options(
+            &name,
+            CreateKeyOptions {
+                backend,
+                exportable,
+            },
+        )
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Import an externally generated key, wrapped for the engine's wrapping key.
+async fn import_key(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path(name): Path<String>,
+    Json(body): Json<ImportKeyRequest>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check_mount(
+            &auth.policies,
+            &mount,
+            &format!("{mount}keys/{name}/import"),
+            &Capability::Update,
+        )
+        .await?;
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    engine
+        .import_key(&name, &body.ciphertext, body.exportable)
+        .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Return the RSA public key clients wrap imported keys for.
+async fn wrapping_key(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+) -> Result<Json<WrappingKeyResponse>, AppError> {
+    state
+        .policy_store
+        .check_mount(
+            &auth.policies,
+            &mount,
+            &format!("{mount}wrapping_key"),
+            &Capability::Read,
+        )
+        .await?;
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    let public_key = engine.wrapping_key().await?;
+
+    Ok(Json(WrappingKeyResponse { public_key }))
+}
+
+/// Export every version of an exportable key.
+async fn export_key(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path((export_type, name)): Path<(String, String)>,
+) -> Result<Json<ExportKeyResponse>, AppError> {
+    export(&state, &auth, &mount, export_type, name, None).await
+}
+
+/// Export one version (a number or `latest`) of an exportable key.
+async fn export_key_version(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path((export_type, name, version)): Path<(String, String, String)>,
+) -> Result<Json<ExportKeyResponse>, AppError> {
+    export(&state, &auth, &mount, export_type, name, Some(version)).await
+}
+
 /// Rotate a named transit key.
 async fn rotate_key(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
     Path(name): Path<String>,
 ) -> Result<Json<RotateResponse>, AppError> {
     state
         .policy_store
-        .check(
+        .check_mount(
             &auth.policies,
-            &format!("transit/keys/{name}"),
+            &mount,
+            &format!("{mount}keys/{name}"),
             &Capability::Update,
         )
         .await?;
 
-    let engine = get_transit_engine(&state).await?;
+    let engine = get_transit_engine(&state, &mount).await?;
     let new_version = engine.rotate_key(&name).await?;
 
     Ok(Json(RotateResponse { new_version }))
 }
 
+/// Update the version limits of a named transit key.
+async fn update_key_config(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path(name): Path<String>,
+    Json(body): Json<KeyConfigRequest>,
+) -> Result<Json<KeyInfoResponse>, AppError> {
+    state
+        .policy_store
+        .check_mount(
+            &auth.policies,
+            &mount,
+            &format!("{mount}keys/{name}/config"),
+            &Capability::Update,
+        )
+        .await?;
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    let info = engine
+        .update_key_config(
+            &name,
+            KeyConfigUpdate {
+                min_decryption_version: body.min_decryption_version,
+                min_encryption_version: body.min_encryption_version,
+            },
+        )
+        .await?;
+
+    Ok(Json(info.into()))
+}
+
 /// Encrypt plaintext using a named transit key.
 async fn encrypt(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
     Path(name): Path<String>,
     Json(body): Json<EncryptRequest>,
 ) -> Result<Json<EncryptResponse>, AppError> {
@@ -161,22 +505,77 @@ async fn encrypt(
         .policy_store
         .check(
             &auth.policies,
-            &format!("transit/encrypt/{name}"),
+            &format!("{mount}encrypt/{name}"),
             &Capability::Update,
         )
         .await?;
 
-    let plaintext_bytes = base64_decode(&body.plaintext)?;
-    let engine = get_transit_engine(&state).await?;
-    let ciphertext = engine.encrypt(&name, &plaintext_bytes).await?;
-
-    Ok(Json(EncryptResponse { ciphertext }))
+    let engine = get_transit_engine(&state, &mount).await?;
+
+    match (body.plaintext, body.batch_input) {
+        (Some(plaintext), None) => {
+            let plaintext_bytes = base64_decode(&plaintext)?;
+            let ciphertext = engine
+                .encrypt_with_version(&name, &plaintext_bytes, body.key_version)
+                .await?;
+            Ok(Json(EncryptResponse {
+                ciphertext: Some(ciphertext),
+                batch_results: None,
+            }))
+        }
+        (None, Some(batch)) => {
+            // Items with invalid base64 fail on their own, like engine errors.
+            let mut results: Vec<Option<BatchEncryptResult>> = Vec::with_capacity(batch.len());
+            let mut items = Vec::with_capacity(batch.len());
+            for input in batch {
+                match BASE64.decode(&input.plaintext) {
+                    Ok(plaintext) => {
+                        results.push(None);
+                        items.push(BatchEncryptItem {
+                            plaintext,
+                            key_version: input.key_version,
+                        });
+                    }
+                    Err(e) => results.push(Some(batch_error(format!("invalid base64 input: {e}")))),
+                }
+            }
+
+            let mut encrypted = if items.is_empty() {
+                Vec::new()
+            } else {
+                engine.encrypt_batch(&name, items).await?
+            }
+            .into_iter();
+            let batch_results = results
+                .into_iter()
+                .map(|result| {
+                    result.unwrap_or_else(|| match encrypted.next() {
+                        Some(Ok(ciphertext)) => BatchEncryptResult {
+                            ciphertext: Some(ciphertext),
+                            error: None,
+                        },
+                        Some(Err(e)) => batch_error(e.to_string()),
+                        None => batch_error("missing batch result".to_owned()),
+                    })
+                })
+                .collect();
+
+            Ok(Json(EncryptResponse {
+                ciphertext: None,
+                batch_results: Some(batch_results),
+            }))
+        }
+        _ => Err(AppError::BadRequest(
+            "exactly one of 'plaintext' or 'batch_input' is required".to_owned(),
+        )),
+    }
 }
 
 /// Decrypt ciphertext using a named transit key.
 async fn decrypt(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
     Path(name): Path<String>,
     Json(body): Json<DecryptRequest>,
 ) -> Result<Json<DecryptResponse>, AppError> {
@@ -184,25 +583,55 @@ async fn decrypt(
         .policy_store
         .check(
             &auth.policies,
-            &format!("transit/decrypt/{name}"),
+            &format!("{mount}decrypt/{name}"),
             &Capability::Update,
         )
         .await?;
 
-    let engine = get_transit_engine(&state).await?;
-    let plaintext = engine.decrypt(&name, &body.ciphertext).await?;
-
-    let plaintext_b64 = BASE64.encode(&plaintext);
-
-    Ok(Json(DecryptResponse {
-        plaintext: plaintext_b64,
-    }))
+    let engine = get_transit_engine(&state, &mount).await?;
+
+    match (body.ciphertext, body.batch_input) {
+        (Some(ciphertext), None) => {
+            let plaintext = engine.decrypt(&name, &ciphertext).await?;
+            Ok(Json(DecryptResponse {
+                plaintext: Some(BASE64.encode(&plaintext)),
+                batch_results: None,
+            }))
+        }
+        (None, Some(batch)) => {
+            let ciphertexts = batch.into_iter().map(|input| input.ciphertext).collect();
+            let batch_results = engine
+                .decrypt_batch(&name, ciphertexts)
+                .await?
+                .into_iter()
+                .map(|result| match result {
+                    Ok(plaintext) => BatchDecryptResult {
+                        plaintext: Some(BASE64.encode(&plaintext)),
+                        error: None,
+                    },
+                    Err(e) => BatchDecryptResult {
+                        plaintext: None,
+                        error: Some(e.to_string()),
+                    },
+                })
+                .collect();
+
+            Ok(Json(DecryptResponse {
+                plaintext: None,
+                batch_results: Some(batch_results),
+            }))
+        }
+        _ => Err(AppError::BadRequest(
+            "exactly one of 'ciphertext' or 'batch_input' is required".to_owned(),
+        )),
+    }
 }
 
 /// Re-wrap ciphertext under the latest key version.
 async fn rewrap(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
     Path(name): Path<String>,
     Json(body): Json<RewrapRequest>,
 ) -> Result<Json<RewrapResponse>, AppError> {
@@ -210,13 +639,15 @@ async fn rewrap(
         .policy_store
         .check(
             &auth.policies,
-            &format!("transit/rewrap/{name}"),
+            &format!("{mount}rewrap/{name}"),
             &Capability::Update,
         )
         .await?;
 
-    let engine = get_transit_engine(&state).await?;
-    let ciphertext = engine.rewrap(&name, &body.ciphertext).await?;
+    let engine = get_transit_engine(&state, &mount).await?;
+    let ciphertext = engine
+        .rewrap(&name, &body.ciphertext, body.key_version)
+        .await?;
 
     Ok(Json(RewrapResponse { ciphertext }))
 }
@@ -225,18 +656,19 @@ async fn rewrap(
 async fn generate_data_key(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
     Path(name): Path<String>,
 ) -> Result<Json<DataKeyResponse>, AppError> {
     state
         .policy_store
         .check(
             &auth.policies,
-            &format!("transit/datakey/{name}"),
+            &format!("{mount}datakey/{name}"),
             &Capability::Update,
         )
         .await?;
 
-    let engine = get_transit_engine(&state).await?;
+    let engine = get_transit_engine(&state, &mount).await?;
     let dk = engine.generate_data_key(&name).await?;
 
     Ok(Json(DataKeyResponse {
@@ -245,17 +677,112 @@ async fn generate_data_key(
     }))
 }
 
+/// Replace a value with a token.
+async fn tokenize(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path(name): Path<String>,
+    Json(body): Json<TokenizeRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount}tokenize/{name}"),
+            &Capability::Update,
+        )
+        .await?;
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    let plaintext = base64_decode(&body.plaintext)?;
+    let options = TokenizeOptions {
+        convergent: body.convergent,
+        metadata: body.metadata,
+    };
+    let info = engine.tokenize(&name, &plaintext, options).await?;
+
+    Ok(Json(info.into()))
+}
+
+/// Return the value behind a token.
+async fn detokenize(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path(name): Path<String>,
+    Json(body): Json<DetokenizeRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount}detokenize/{name}"),
+            &Capability::Update,
+        )
+        .await?;
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    let (plaintext, info) = engine.detokenize(&name, &body.token).await?;
+
+    Ok(Json(TokenResponse {
+        plaintext: Some(BASE64.encode(&*plaintext)),
+        ..info.into()
+    }))
+}
+
+/// Describe a token, or find the convergent token for a value.
+async fn lookup(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path(name): Path<String>,
+    Json(body): Json<LookupRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    state
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount}lookup/{name}"),
+            &Capability::Update,
+        )
+        .await?;
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    let info = match (body.token, body.plaintext) {
+        (Some(token), None) => engine.lookup_token(&name, &token).await?,
+        (None, Some(plaintext)) => {
+            engine
+                .lookup_value(&name, &base64_decode(&plaintext)?)
+                .await?
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "exactly one of 'token' or 'plaintext' is required".to_owned(),
+            ));
+        }
+    };
+
+    Ok(Json(info.into()))
+}
+
 /// List all transit key names.
 async fn list_keys(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
 ) -> Result<Json<KeyListResponse>, AppError> {
     state
         .policy_store
-        .check(&auth.policies, "transit/keys", &Capability::List)
+        .check_mount(
+            &auth.policies,
+            &mount,
+            &format!("{mount}keys"),
+            &Capability::List,
+        )
         .await?;
 
-    let engine = get_transit_engine(&state).await?;
+    let engine = get_transit_engine(&state, &mount).await?;
     let keys = engine.list_keys().await?;
 
     Ok(Json(KeyListResponse { keys }))
@@ -265,42 +792,96 @@ async fn list_keys(
 async fn key_info(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
     Path(name): Path<String>,
 ) -> Result<Json<KeyInfoResponse>, AppError> {
     state
         .policy_store
-        .check(
+        .check_mount(
             &auth.policies,
-            &format!("transit/keys/{name}"),
+            &mount,
+            &format!("{mount}keys/{name}"),
             &Capability::Read,
         )
         .await?;
 
-    let engine = get_transit_engine(&state).await?;
+    let engine = get_transit_engine(&state, &mount).await?;
     let info = engine.key_info(&name).await?;
 
-    Ok(Json(KeyInfoResponse {
-        name: info.name,
-        latest_version: info.latest_version,
-        min_decryption_version: info.min_decryption_version,
-        supports_encryption: info.supports_encryption,
-        supports_decryption: info.supports_decryption,
-        version_count: info.version_count,
-        created_at: info.created_at.to_rfc3339(),
-    }))
+    Ok(Json(info.into()))
+}
+
+/// Delete a named transit key with all its versions and tokens.
+async fn delete_key(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(TransitMount(mount)): Extension<TransitMount>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    state
+        .policy_store
+        .check_mount(
+            &auth.policies,
+            &mount,
+            &format!("{mount}keys/{name}"),
+            &Capability::Delete,
+        )
+        .await?;
+
+    let engine = get_transit_engine(&state, &mount).await?;
+    engine.delete_key(&name).await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // ── Helpers ──────────────────────────────────────────────────────────
 
-/// Get the default transit engine from state.
-async fn get_transit_engine(state: &AppState) -> Result<Arc<TransitEngine>, AppError> {
+/// Shared body of the export handlers. Export is a plain read of key
+/// material, so it is not covered by delegated mount admin.
+async fn export(
+    state: &AppState,
+    auth: &AuthContext,
+    mount: &str,
+    export_type: String,
+    name: String,
+    version: Option<String>,
+) -> Result<Json<ExportKeyResponse>, AppError> {
     state
-        .transit_engines
-        .read()
+        .policy_store
+        .check(
+            &auth.policies,
+            &format!("{mount}export/{export_type}/{name}"),
+            &Capability::Read,
+        )
+        .await?;
+
+    let engine = get_transit_engine(state, mount).await?;
+    let keys = engine
+        .export_key(&name, &export_type, version.as_deref())
+        .await?;
+
+    Ok(Json(ExportKeyResponse {
+        name,
+        export_type,
+        keys,
+    }))
+}
+
+/// The transit engine serving `mount`.
+async fn get_transit_engine(state: &AppState, mount: &str) -> Result<Arc<TransitEngine>, AppError> {
+    state
+        .engines
+        .get::<TransitEngine>(mount)
         .await
-        .get("transit/")
-        .cloned()
-        .ok_or_else(|| AppError::NotFound("no transit engine mounted".to_owned()))
+        .ok_or_else(|| AppError::NotFound(format!("no transit engine mounted at '{mount}'")))
+}
+
+/// A failed batch encryption item.
+fn batch_error(error: String) -> BatchEncryptResult {
+    BatchEncryptResult {
+        ciphertext: None,
+        error: Some(error),
+    }
 }
 
 /// Decode base64 input, returning a user-friendly error.
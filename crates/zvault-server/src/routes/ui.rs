@@ -122,6 +122,7 @@ async fn spring_oauth_callback(
             parent_hash: None,
             metadata,
             display_name,
+            bound_cidrs: Vec::new(),
         })
         .await
     {
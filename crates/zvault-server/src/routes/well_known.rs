@@ -0,0 +1,193 @@
+//! Discovery route: `/.well-known/zvault-configuration`
+//!
+//! Describes the server to clients that only know its URL: API base paths,
+//! enabled auth methods, the OIDC issuer, and feature flags. The SDK, agent,
+//! and CLI read it to auto-configure against cloud or self-hosted servers.
+//!
+//! No auth required. The document carries no secrets — only what a client
+//! needs before it has a token.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+
+use crate::state::AppState;
+use zvault_core::database::DatabaseEngine;
+use zvault_core::license::{Feature, Tier};
+use zvault_core::pki::PkiEngine;
+use zvault_core::transit::TransitEngine;
+
+/// Build the `/.well-known` router.
+///
+/// Paths:
+/// - `GET /.well-known/zvault-configuration` — server discovery document
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/.well-known/zvault-configuration", get(configuration))
+}
+
+// ── Response types ───────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct ConfigurationResponse {
+    /// Server version.
+    pub server_version: String,
+    /// Oldest CLI release that speaks this server's API.
+    pub min_cli_version: String,
+    /// Prefix of every API path.
+    pub api_base_path: String,
+    /// Well-known API locations.
+    pub endpoints: Endpoints,
+    /// Auth methods a client can log in with.
+    pub auth_methods: Vec<AuthMethod>,
+    /// OIDC provider details, when SSO is enabled.
+    pub oidc: Option<OidcInfo>,
+    /// What this server supports.
+    pub features: Features,
+    /// Whether the vault is currently sealed.
+    pub sealed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Endpoints {
+    pub health: String,
+    pub seal_status: String,
+    pub version: String,
+    pub secrets: String,
+    pub leases: String,
+    pub transit: Option<String>,
+    pub database: Option<String>,
+    pub pki: Option<String>,
+    pub cloud: Option<String>,
+    pub mcp: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthMethod {
+    /// Method type (`token`, `approle`, `oidc`).
+    #[serde(rename = "type")]
+    pub method_type: String,
+    /// Mount path of the method.
+    pub path: String,
+    /// Where to exchange credentials for a token, if the method has a login.
+    pub login_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OidcInfo {
+    /// Base URL of the identity provider.
+    pub issuer: String,
+    /// Provider name.
+    pub provider: String,
+    /// Public OAuth client ID.
+    pub client_id: String,
+    /// Browser entry point for the login flow.
+    pub login_url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Features {
+    /// Effective license tier.
+    pub license_tier: Tier,
+    pub transit: bool,
+    pub database: bool,
+    pub pki: bool,
+    pub sso: bool,
+    pub mcp: bool,
+    pub namespaces: bool,
+    pub ha: bool,
+    pub cloud: bool,
+}
+
+// ── Handlers ─────────────────────────────────────────────────────────
+
+/// Return the discovery document.
+async fn configuration(State(state): State<Arc<AppState>>) -> Json<ConfigurationResponse> {
+    let transit = state.engines.has::<TransitEngine>().await;
+    let database = state.engines.has::<DatabaseEngine>().await;
+    let pki = state.engines.has::<PkiEngine>().await;
+    let cloud = cloud_enabled(&state);
+
+    let sso_licensed = state.license_manager.check(Feature::Sso).await.is_ok();
+    let mcp = state.license_manager.check(Feature::Mcp).await.is_ok();
+    let oidc = state
+        .spring_oauth
+        .as_ref()
+        .filter(|_| sso_licensed)
+        .map(|cfg| OidcInfo {
+            issuer: cfg.auth_url.clone(),
+            provider: "spring".to_owned(),
+            client_id: cfg.client_id.clone(),
+            login_url: "/v1/auth/oidc/login".to_owned(),
+        });
+
+    let mut auth_methods = vec![
+        AuthMethod {
+            method_type: "token".to_owned(),
+            path: "/v1/auth/token".to_owned(),
+            login_path: None,
+        },
+        AuthMethod {
+            method_type: "approle".to_owned(),
+            path: "/v1/auth/approle".to_owned(),
+            login_path: Some("/v1/auth/approle/login".to_owned()),
+        },
+    ];
+    if oidc.is_some() {
+        auth_methods.push(AuthMethod {
+            method_type: "oidc".to_owned(),
+            path: "/v1/auth/oidc".to_owned(),
+            login_path: Some("/v1/auth/oidc/login".to_owned()),
+        });
+    }
+
+    // Namespaces and HA are licensed features the server does not ship yet.
+    let features = Features {
+        license_tier: state.license_manager.tier().await,
+        transit,
+        database,
+        pki,
+        sso: oidc.is_some(),
+        mcp,
+        namespaces: false,
+        ha: false,
+        cloud,
+    };
+
+    Json(ConfigurationResponse {
+        server_version: env!("CARGO_PKG_VERSION").to_owned(),
+        min_cli_version: super::sys::MIN_CLI_VERSION.to_owned(),
+        api_base_path: "/v1".to_owned(),
+        endpoints: Endpoints {
+            health: "/v1/sys/health".to_owned(),
+            seal_status: "/v1/sys/seal-status".to_owned(),
+            version: "/v1/sys/version".to_owned(),
+            secrets: "/v1/secret".to_owned(),
+            leases: "/v1/sys/leases".to_owned(),
+            transit: transit.then(|| "/v1/transit".to_owned()),
+            database: database.then(|| "/v1/database".to_owned()),
+            pki: pki.then(|| "/v1/pki".to_owned()),
+            cloud: cloud.then(|| "/v1/cloud".to_owned()),
+            mcp: mcp.then(|| "/v1/mcp".to_owned()),
+        },
+        auth_methods,
+        oidc,
+        features,
+        sealed: !state.barrier.is_unsealed().await,
+    })
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+#[cfg(feature = "cloud")]
+fn cloud_enabled(state: &AppState) -> bool {
+    state.cloud_pg_pool.is_some()
+}
+
+#[cfg(not(feature = "cloud"))]
+fn cloud_enabled(_state: &AppState) -> bool {
+    false
+}
@@ -0,0 +1,94 @@
+//! HTTP route handlers for response wrapping.
+//!
+//! Any authenticated request can ask for its response to be wrapped by
+//! sending `X-Vault-Wrap-TTL` (see [`crate::middleware::wrap_middleware`]).
+//! These endpoints consume the resulting wrapping tokens. The wrapping token
+//! is taken from the `token` body field, or else from `X-Vault-Token` — a
+//! wrapping token may authenticate these endpoints and nothing else.
+//! Holding the token is the only authorization they require.
+//!
+//! Endpoints:
+//! - `POST /v1/sys/wrapping/unwrap` — return the wrapped response, once
+//! - `POST /v1/sys/wrapping/lookup` — read creation metadata without unwrapping
+//! - `POST /v1/sys/wrapping/rewrap` — move the response to a fresh token
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use zvault_core::wrapping::{WrapInfo, WrapLookup};
+
+use crate::error::AppError;
+use crate::state::AppState;
+
+/// Build the `/v1/sys/wrapping` router.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/unwrap", post(unwrap))
+        .route("/lookup", post(lookup))
+        .route("/rewrap", post(rewrap))
+}
+
+#[derive(Deserialize)]
+struct WrappingTokenRequest {
+    token: Option<String>,
+}
+
+async fn unwrap(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<WrappingTokenRequest>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let token = wrapping_token(&headers, body)?;
+    let response = state
+        .response_wrapper
+        .unwrap(&state.token_store, &token)
+        .await?;
+    Ok(Json(response))
+}
+
+async fn lookup(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<WrappingTokenRequest>>,
+) -> Result<Json<WrapLookup>, AppError> {
+    let token = wrapping_token(&headers, body)?;
+    Ok(Json(
+        state
+            .response_wrapper
+            .lookup(&state.token_store, &token)
+            .await?,
+    ))
+}
+
+async fn rewrap(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<WrappingTokenRequest>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let token = wrapping_token(&headers, body)?;
+    let info: WrapInfo = state
+        .response_wrapper
+        .rewrap(&state.token_store, &token)
+        .await?;
+    Ok(Json(serde_json::json!({ "wrap_info": info })))
+}
+
+/// The wrapping token from the body, falling back to `X-Vault-Token`.
+fn wrapping_token(
+    headers: &HeaderMap,
+    body: Option<Json<WrappingTokenRequest>>,
+) -> Result<String, AppError> {
+    body.and_then(|Json(b)| b.token)
+        .or_else(|| {
+            headers
+                .get("X-Vault-Token")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned)
+        })
+        .ok_or_else(|| AppError::BadRequest("wrapping token is required".to_owned()))
+}
@@ -7,22 +7,45 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use ipnet::IpNet;
 use tokio::sync::RwLock;
 
+use zvault_core::access_request::AccessRequestStore;
+use zvault_core::activity::ActivityLog;
 use zvault_core::approle::AppRoleStore;
 use zvault_core::audit::AuditManager;
 use zvault_core::barrier::Barrier;
-use zvault_core::database::DatabaseEngine;
-use zvault_core::engine::KvEngine;
+use zvault_core::certauth::CertAuthStore;
+use zvault_core::control_group::ControlGroupStore;
+use zvault_core::cubbyhole::Cubbyhole;
+use zvault_core::events::EventBus;
+use zvault_core::hsm::HsmProvider;
+use zvault_core::identity::IdentityStore;
+use zvault_core::jwt_auth::JwtAuthStore;
 use zvault_core::lease::LeaseManager;
+use zvault_core::license::LicenseManager;
+use zvault_core::mfa::MfaStore;
 use zvault_core::mount::MountManager;
-use zvault_core::pki::PkiEngine;
+use zvault_core::notify::NotificationManager;
+use zvault_core::pki_acme::AcmeServer;
+use zvault_core::plugin::PluginCatalog;
 use zvault_core::policy::PolicyStore;
+use zvault_core::quota::QuotaManager;
+use zvault_core::registry::EngineRegistry;
+use zvault_core::rotation::RotationManager;
 use zvault_core::seal::SealManager;
+use zvault_core::secret_usage::SecretUsageLog;
+use zvault_core::sync::SyncManager;
+use zvault_core::sync_kubernetes::KubernetesSyncManager;
 use zvault_core::token::TokenStore;
-use zvault_core::transit::TransitEngine;
+use zvault_core::wrapping::ResponseWrapper;
 
+use crate::backup::BackupScheduler;
 use crate::config::SpringOAuthConfig;
+use crate::config_file::ConfigFile;
+use crate::ha::HaCoordinator;
+use crate::replication::Replication;
+use crate::routes::mcp::McpSession;
 
 /// Shared application state passed to all HTTP handlers.
 pub struct AppState {
@@ -40,25 +63,83 @@ pub struct AppState {
     pub audit_manager: Arc<AuditManager>,
     /// Lease lifecycle manager.
     pub lease_manager: Arc<LeaseManager>,
-    /// Registered KV engines keyed by mount path.
-    pub kv_engines: RwLock<HashMap<String, Arc<KvEngine>>>,
-    /// Registered transit engines keyed by mount path.
-    pub transit_engines: RwLock<HashMap<String, Arc<TransitEngine>>>,
-    /// Registered database engines keyed by mount path.
-    pub database_engines: RwLock<HashMap<String, Arc<DatabaseEngine>>>,
-    /// Registered PKI engines keyed by mount path.
-    pub pki_engines: RwLock<HashMap<String, Arc<PkiEngine>>>,
+    /// Rate limit and lease count quotas.
+    pub quotas: Arc<QuotaManager>,
+    /// Active license and feature gating.
+    pub license_manager: Arc<LicenseManager>,
+    /// Distinct client activity per month.
+    pub activity_log: Arc<ActivityLog>,
+    /// Read counts and distinct readers per KV secret.
+    pub secret_usage: Arc<SecretUsageLog>,
+    /// In-process event bus (secret, policy, lease, and seal events).
+    pub event_bus: Arc<EventBus>,
+    /// Notification webhooks and their retry queue.
+    pub notifications: Arc<NotificationManager>,
+    /// Secret rotation policies; the rotation worker runs the due ones.
+    pub rotation: Arc<RotationManager>,
+    /// Secret syncs to GitHub Actions; the sync worker runs them on writes.
+    pub sync: Arc<SyncManager>,
+    /// Kubernetes Secret syncs; the sync worker runs them on writes.
+    pub kubernetes_sync: Arc<KubernetesSyncManager>,
+    /// Scheduled backups (None if `ZVAULT_BACKUP_INTERVAL` is not set).
+    pub backups: Option<Arc<BackupScheduler>>,
+    /// Replication role (None if `ZVAULT_REPLICATION_MODE` is not set).
+    pub replication: Option<Replication>,
+    /// Active/standby HA (None if `ZVAULT_HA_ENABLED` is not set).
+    pub ha: Option<Arc<HaCoordinator>>,
+    /// Just-in-time access requests and their grants.
+    pub access_requests: Arc<AccessRequestStore>,
+    /// Requests parked for control group approval.
+    pub control_groups: Arc<ControlGroupStore>,
+    /// MFA methods, enrollments, and login enforcements.
+    pub mfa: Arc<MfaStore>,
+    /// Identity entities, aliases, and groups.
+    pub identity: Arc<IdentityStore>,
+    /// Mounted engine instances (KV, transit, database, PKI, plugins)
+    /// keyed by mount path.
+    pub engines: EngineRegistry,
+    /// HSM for `pkcs11`-backed transit keys on every transit mount (None if
+    /// `ZVAULT_HSM_MODULE` is not set).
+    pub hsm: Option<Arc<dyn HsmProvider>>,
+    /// Registered external secrets engine plugins.
+    pub plugin_catalog: Arc<PluginCatalog>,
+    /// ACME server of the `pki/` mount (None if PKI is not mounted).
+    pub pki_acme: Option<Arc<AcmeServer>>,
     /// `AppRole` auth store (None if not enabled).
     pub approle_store: Option<Arc<AppRoleStore>>,
+    /// JWT auth store for CI/OIDC token login.
+    pub jwt_auth: Arc<JwtAuthStore>,
+    /// TLS client certificate auth store.
+    pub cert_auth: Arc<CertAuthStore>,
+    /// Per-token private storage behind the `cubbyhole/` mount.
+    pub cubbyhole: Arc<Cubbyhole>,
+    /// Response wrapping into single-use tokens.
+    pub response_wrapper: Arc<ResponseWrapper>,
+    /// Open MCP Server-Sent Events sessions keyed by session ID.
+    pub mcp_sessions: RwLock<HashMap<String, McpSession>>,
     /// Spring OAuth configuration (None if not configured).
     pub spring_oauth: Option<SpringOAuthConfig>,
     /// Path to the audit log file (for reading audit entries via API).
     pub audit_file_path: Option<String>,
+    /// Proxies whose `X-Forwarded-For` header names the client.
+    pub trusted_proxies: Vec<IpNet>,
+    /// Audit devices and mounts declared in the configuration file, replaced
+    /// on reload (empty without a file).
+    pub config_file: RwLock<ConfigFile>,
     /// `PostgreSQL` pool for cloud API (None if cloud mode is not enabled).
     #[cfg(feature = "cloud")]
     pub cloud_pg_pool: Option<sqlx::PgPool>,
 }
 
+impl AppState {
+    /// Whether this server is an HA standby, which leaves writes and
+    /// background maintenance to the active node.
+    #[must_use]
+    pub fn is_standby(&self) -> bool {
+        self.ha.as_ref().is_some_and(|ha| !ha.is_active())
+    }
+}
+
 impl std::fmt::Debug for AppState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AppState").finish_non_exhaustive()
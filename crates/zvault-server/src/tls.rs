@@ -0,0 +1,536 @@
+//! Built-in TLS for `ZVault`, with certificates from files or ACME.
+//!
+//! When `ZVAULT_TLS_CERT` and `ZVAULT_TLS_KEY` are set the API listener
+//! serves that certificate and reloads it on `SIGHUP` or when either file
+//! changes. With `ZVAULT_TLS_BOOTSTRAP` the files are created on first start
+//! from a new internal CA (see [`zvault_core::pki::bootstrap_certificate`]);
+//! the CA certificate is written next to them for clients to trust.
+//!
+//! When `ZVAULT_ACME_DOMAINS` is set the API listener speaks TLS. The
+//! certificate comes from [`zvault_core::acme::AcmeClient`], which keeps it
+//! behind the barrier — so until the vault is unsealed the listener presents
+//! a self-signed placeholder, and the renewal worker swaps in the real
+//! certificate once it can read (or obtain) one.
+//!
+//! A plain HTTP listener answers `http-01` challenges and redirects every
+//! other request to HTTPS.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use axum::Router;
+use axum::extract::connect_info::Connected;
+use axum::extract::{Path, State};
+use axum::http::uri::Authority;
+use axum::http::{StatusCode, Uri, header};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::get;
+use axum::serve::IncomingStream;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::client::danger::HandshakeSignatureValid;
+use tokio_rustls::rustls::crypto::ring::sign::any_supported_type;
+use tokio_rustls::rustls::crypto::{
+    CryptoProvider, verify_tls12_signature, verify_tls13_signature,
+};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, UnixTime};
+use tokio_rustls::rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::{
+    DigitallySignedStruct, DistinguishedName, Error as RustlsError, InconsistentKeys, ServerConfig,
+    SignatureScheme, crypto::ring::default_provider,
+};
+use tokio_rustls::server::TlsStream;
+use tracing::{debug, warn};
+use zeroize::Zeroizing;
+
+use zvault_core::acme::{
+    AcmeCertificate, AcmeClient, AcmeConfig, CloudflareDns, DnsProvider, Http01Responder,
+};
+use zvault_core::barrier::Barrier;
+use zvault_core::pki;
+
+use crate::config::{AcmeDnsProvider, AcmeServerConfig, TlsFileConfig};
+
+/// Time allowed for a client to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handshaken connections waiting for `axum::serve` to pick them up.
+const ACCEPT_BACKLOG: usize = 128;
+
+/// Serves whichever certificate was installed last.
+pub struct CertResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl CertResolver {
+    /// Create a resolver serving `initial`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate or key cannot be parsed.
+    pub fn new(initial: &AcmeCertificate) -> anyhow::Result<Self> {
+        Ok(Self {
+            current: RwLock::new(certified_key(initial)?),
+        })
+    }
+
+    /// Create a resolver serving the configured certificate files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files cannot be loaded (see [`load_files`]).
+    pub fn from_files(tls: &TlsFileConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            current: RwLock::new(load_files(tls)?),
+        })
+    }
+
+    /// Serve `certificate` on all new connections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate or key cannot be parsed.
+    pub fn install(&self, certificate: &AcmeCertificate) -> anyhow::Result<()> {
+        self.replace(certified_key(certificate)?);
+        Ok(())
+    }
+
+    /// Reload the certificate files and serve them on all new connections.
+    /// On error the current certificate stays in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files cannot be loaded (see [`load_files`]).
+    pub fn reload_files(&self, tls: &TlsFileConfig) -> anyhow::Result<()> {
+        self.replace(load_files(tls)?);
+        Ok(())
+    }
+
+    fn replace(&self, key: Arc<CertifiedKey>) {
+        if let Ok(mut current) = self.current.write() {
+            *current = key;
+        }
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.current.read().ok().map(|current| Arc::clone(&current))
+    }
+}
+
+impl fmt::Debug for CertResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertResolver").finish_non_exhaustive()
+    }
+}
+
+fn certified_key(certificate: &AcmeCertificate) -> anyhow::Result<Arc<CertifiedKey>> {
+    certified_key_from_pem(&certificate.certificate_pem, &certificate.private_key_pem)
+}
+
+fn certified_key_from_pem(
+    certificate_pem: &str,
+    private_key_pem: &str,
+) -> anyhow::Result<Arc<CertifiedKey>> {
+    let chain = CertificateDer::pem_slice_iter(certificate_pem.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .context("invalid certificate chain")?;
+    anyhow::ensure!(!chain.is_empty(), "certificate chain is empty");
+    let key = PrivateKeyDer::from_pem_slice(private_key_pem.as_bytes())
+        .context("invalid certificate private key")?;
+    let signing_key = any_supported_type(&key).context("unsupported certificate key type")?;
+    Ok(Arc::new(CertifiedKey::new(chain, signing_key)))
+}
+
+/// Check that `certificate` can be served: the chain and key parse, and the
+/// key belongs to the leaf certificate.
+///
+/// # Errors
+///
+/// Returns an error describing the first problem found.
+pub fn validate_certificate(certificate: &AcmeCertificate) -> anyhow::Result<()> {
+    check_keys_match(&*certified_key(certificate)?)
+}
+
+fn check_keys_match(key: &CertifiedKey) -> anyhow::Result<()> {
+    match key.keys_match() {
+        // The signing key cannot report its public half; nothing to compare.
+        Ok(()) | Err(RustlsError::InconsistentKeys(InconsistentKeys::Unknown)) => Ok(()),
+        Err(e) => Err(anyhow::anyhow!(
+            "certificate does not match its private key: {e}"
+        )),
+    }
+}
+
+// ── Certificate files ────────────────────────────────────────────────
+
+/// Validity of a bootstrapped certificate.
+const BOOTSTRAP_TTL_HOURS: u64 = 365 * 24;
+
+/// Read, parse, and check the configured certificate and key files.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read, does not parse, or the key
+/// does not belong to the certificate.
+pub fn load_files(tls: &TlsFileConfig) -> anyhow::Result<Arc<CertifiedKey>> {
+    let certificate_pem = std::fs::read_to_string(&tls.cert_path)
+        .with_context(|| format!("failed to read {}", tls.cert_path))?;
+    let private_key_pem = Zeroizing::new(
+        std::fs::read_to_string(&tls.key_path)
+            .with_context(|| format!("failed to read {}", tls.key_path))?,
+    );
+    let key = certified_key_from_pem(&certificate_pem, &private_key_pem)?;
+    check_keys_match(&key)?;
+    Ok(key)
+}
+
+/// Where a bootstrapped CA certificate is written: beside the certificate,
+/// e.g. `tls/server.crt` gives `tls/server.ca.pem`.
+#[must_use]
+pub fn bootstrap_ca_path(tls: &TlsFileConfig) -> PathBuf {
+    FsPath::new(&tls.cert_path).with_extension("ca.pem")
+}
+
+/// Create the certificate and key files from a new internal CA, unless
+/// both already exist. Returns whether they were created.
+///
+/// # Errors
+///
+/// Returns an error if only one of the files exists, bootstrapping is
+/// disabled and the files are missing, or the files cannot be written.
+pub async fn bootstrap_files(tls: &TlsFileConfig) -> anyhow::Result<bool> {
+    let cert_exists = FsPath::new(&tls.cert_path).exists();
+    let key_exists = FsPath::new(&tls.key_path).exists();
+    match (cert_exists, key_exists) {
+        (true, true) => return Ok(false),
+        (true, false) | (false, true) => anyhow::bail!(
+            "only one of {} and {} exists — remove it to bootstrap a new certificate",
+            tls.cert_path,
+            tls.key_path
+        ),
+        (false, false) if !tls.bootstrap => anyhow::bail!(
+            "{} does not exist — provide a certificate or set ZVAULT_TLS_BOOTSTRAP=true",
+            tls.cert_path
+        ),
+        (false, false) => {}
+    }
+
+    let issued = pki::bootstrap_certificate(&tls.hostnames, BOOTSTRAP_TTL_HOURS)?;
+    let private_key_pem = Zeroizing::new(issued.private_key_pem.unwrap_or_default());
+    write_file(&tls.key_path, private_key_pem.as_bytes(), true).await?;
+    let chain = format!("{}{}", issued.certificate_pem, issued.ca_chain_pem);
+    write_file(&tls.cert_path, chain.as_bytes(), false).await?;
+    let ca_path = bootstrap_ca_path(tls);
+    write_file(
+        &ca_path.to_string_lossy(),
+        issued.ca_chain_pem.as_bytes(),
+        false,
+    )
+    .await?;
+    Ok(true)
+}
+
+async fn write_file(path: &str, contents: &[u8], private: bool) -> anyhow::Result<()> {
+    if let Some(parent) = FsPath::new(path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+    {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(if private { 0o600 } else { 0o644 });
+    #[cfg(not(unix))]
+    let _ = private;
+    let mut file = options
+        .open(path)
+        .await
+        .with_context(|| format!("failed to create {path}"))?;
+    file.write_all(contents)
+        .await
+        .with_context(|| format!("failed to write {path}"))
+}
+
+/// Latest modification time of the certificate and key files, used to
+/// notice when they are replaced.
+#[must_use]
+pub fn files_modified(tls: &TlsFileConfig) -> Option<SystemTime> {
+    [&tls.cert_path, &tls.key_path]
+        .into_iter()
+        .filter_map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+/// Build the ACME client for `acme`, storing certificates behind `barrier`.
+///
+/// # Errors
+///
+/// Returns an error if the DNS provider or ACME configuration is invalid.
+pub fn acme_client(
+    acme: &AcmeServerConfig,
+    barrier: Arc<Barrier>,
+    http01: Arc<Http01Responder>,
+) -> anyhow::Result<AcmeClient> {
+    let dns: Option<Arc<dyn DnsProvider>> = match &acme.dns_provider {
+        Some(AcmeDnsProvider::Cloudflare { api_token, zone_id }) => Some(Arc::new(
+            CloudflareDns::new(api_token.clone(), zone_id.clone())?,
+        )),
+        None => None,
+    };
+    Ok(AcmeClient::new(
+        barrier,
+        AcmeConfig {
+            directory_url: acme.directory_url.clone(),
+            contact_email: acme.email.clone(),
+            domains: acme.domains.clone(),
+            challenge: acme.challenge,
+            renew_before: chrono::Duration::days(acme.renew_before_days),
+        },
+        http01,
+        dns,
+    )?)
+}
+
+/// A TCP listener that completes TLS handshakes before handing connections
+/// to `axum::serve`.
+///
+/// Handshakes run on their own tasks so a slow client cannot stall accepts.
+pub struct TlsListener {
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    /// Bind `addr` and serve certificates from `resolver`. With
+    /// `request_client_certs`, clients are asked for (but not required to
+    /// present) a certificate, which handlers see through [`TlsConnectInfo`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(
+        addr: SocketAddr,
+        resolver: Arc<CertResolver>,
+        request_client_certs: bool,
+    ) -> anyhow::Result<Self> {
+        let provider = Arc::new(default_provider());
+        let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .context("failed to configure TLS")?;
+        let builder = if request_client_certs {
+            builder.with_client_cert_verifier(Arc::new(OptionalClientCert { provider }))
+        } else {
+            builder.with_no_client_auth()
+        };
+        let mut config = builder.with_cert_resolver(resolver);
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("failed to bind to {addr}"))?;
+        let local_addr = listener.local_addr()?;
+        let (tx, incoming) = mpsc::channel(ACCEPT_BACKLOG);
+        tokio::spawn(accept_loop(listener, acceptor, tx));
+        Ok(Self {
+            incoming,
+            local_addr,
+        })
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    tx: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!(error = %e, "failed to accept connection");
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                continue;
+            }
+        };
+        if tx.is_closed() {
+            return;
+        }
+        let acceptor = acceptor.clone();
+        let handshaken = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(tls)) => {
+                    let _ = handshaken.send((tls, peer)).await;
+                }
+                Ok(Err(e)) => debug!(%peer, error = %e, "TLS handshake failed"),
+                Err(_) => debug!(%peer, "TLS handshake timed out"),
+            }
+        });
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(conn) => conn,
+            // The accept loop only exits once this listener is dropped.
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Connection info for requests on a [`TlsListener`]: the peer address and
+/// the certificate chain the client presented, if any.
+#[derive(Debug, Clone)]
+pub struct TlsConnectInfo {
+    /// Client socket address.
+    pub remote_addr: SocketAddr,
+    /// Client certificate chain, leaf first; empty if none was presented.
+    pub client_chain: Arc<[CertificateDer<'static>]>,
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for TlsConnectInfo {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        let client_chain = stream
+            .io()
+            .get_ref()
+            .1
+            .peer_certificates()
+            .map(|chain| chain.iter().map(|c| c.clone().into_owned()).collect())
+            .unwrap_or_default();
+        Self {
+            remote_addr: *stream.remote_addr(),
+            client_chain,
+        }
+    }
+}
+
+/// Asks for a client certificate but accepts connections without one.
+///
+/// Only possession of the key is checked here (the handshake signature);
+/// whether the chain is trusted is up to `cert` auth roles at login.
+#[derive(Debug)]
+struct OptionalClientCert {
+    provider: Arc<CryptoProvider>,
+}
+
+impl ClientCertVerifier for OptionalClientCert {
+    fn client_auth_mandatory(&self) -> bool {
+        false
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, RustlsError> {
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, RustlsError> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, RustlsError> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Router for the plain HTTP listener: answers `http-01` challenges and
+/// redirects everything else to HTTPS on `https_port`.
+pub fn challenge_router(responder: Arc<Http01Responder>, https_port: u16) -> Router {
+    Router::new()
+        .route(
+            "/.well-known/acme-challenge/{token}",
+            get(challenge_response),
+        )
+        .fallback(move |headers: axum::http::HeaderMap, uri: Uri| async move {
+            redirect_to_https(&headers, &uri, https_port)
+        })
+        .with_state(responder)
+}
+
+async fn challenge_response(
+    State(responder): State<Arc<Http01Responder>>,
+    Path(token): Path<String>,
+) -> Response {
+    match responder.key_authorization(&token).await {
+        Some(key_authorization) => (
+            [(header::CONTENT_TYPE, "application/octet-stream")],
+            key_authorization,
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+fn redirect_to_https(headers: &axum::http::HeaderMap, uri: &Uri, https_port: u16) -> Response {
+    let Some(host) = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|h| h.parse::<Authority>().ok())
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let path = uri.path_and_query().map_or("/", |p| p.as_str());
+    let host = host.host();
+    let location = if https_port == 443 {
+        format!("https://{host}{path}")
+    } else {
+        format!("https://{host}:{https_port}{path}")
+    };
+    Redirect::permanent(&location).into_response()
+}
@@ -34,6 +34,14 @@ pub enum StorageError {
     #[error("transaction failed: {reason}")]
     Transaction { reason: String },
 
+    /// Failed to take a point-in-time snapshot.
+    #[error("snapshot failed: {reason}")]
+    Snapshot { reason: String },
+
+    /// Failed to acquire, renew, or release a lock.
+    #[error("lock '{name}' failed: {reason}")]
+    Lock { name: String, reason: String },
+
     /// A storage key contained invalid UTF-8.
     #[error("invalid key encoding: {reason}")]
     InvalidKey { reason: String },
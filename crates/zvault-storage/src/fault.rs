@@ -0,0 +1,391 @@
+//! Fault-injecting storage wrapper for chaos testing.
+//!
+//! [`FaultInjectingBackend`] wraps any [`StorageBackend`] and, according to a
+//! [`FaultPlan`], delays operations, fails them, or tears writes (persists a
+//! truncated value and then reports failure — what a crash mid-write leaves
+//! behind). Decisions come from a seeded PRNG, so a failing test replays
+//! identically with the same seed and the same operation sequence.
+//!
+//! Only compiled with the `testing` feature.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::{StorageBackend, StorageError};
+
+/// Weyl sequence increment for the splitmix64 generator.
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// What to inject and how often.
+///
+/// Rates are probabilities in `0.0..=1.0`, checked independently per call.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use zvault_storage::FaultPlan;
+/// let plan = FaultPlan::new(42)
+///     .with_write_errors(0.1)
+///     .with_torn_writes(0.05)
+///     .with_latency(Duration::from_millis(1), Duration::from_millis(5))
+///     .only_prefix("kv/");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultPlan {
+    seed: u64,
+    read_errors: u64,
+    write_errors: u64,
+    torn_writes: u64,
+    delete_errors: u64,
+    list_errors: u64,
+    latency: Option<(Duration, Duration)>,
+    prefix: Option<String>,
+}
+
+impl FaultPlan {
+    /// An empty plan (no faults) with the given PRNG seed.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Self::default()
+        }
+    }
+
+    /// Fail `get` and `exists` with [`StorageError::Read`] at `rate`.
+    #[must_use]
+    pub fn with_read_errors(mut self, rate: f64) -> Self {
+        self.read_errors = threshold(rate);
+        self
+    }
+
+    /// Fail `put` with [`StorageError::Write`] at `rate`, leaving the old value.
+    #[must_use]
+    pub fn with_write_errors(mut self, rate: f64) -> Self {
+        self.write_errors = threshold(rate);
+        self
+    }
+
+    /// Tear `put` at `rate`: store the first half of the value, then fail.
+    #[must_use]
+    pub fn with_torn_writes(mut self, rate: f64) -> Self {
+        self.torn_writes = threshold(rate);
+        self
+    }
+
+    /// Fail `delete` with [`StorageError::Delete`] at `rate`.
+    #[must_use]
+    pub fn with_delete_errors(mut self, rate: f64) -> Self {
+        self.delete_errors = threshold(rate);
+        self
+    }
+
+    /// Fail `list` with [`StorageError::List`] at `rate`.
+    #[must_use]
+    pub fn with_list_errors(mut self, rate: f64) -> Self {
+        self.list_errors = threshold(rate);
+        self
+    }
+
+    /// Delay every operation by a random duration in `min..=max`.
+    #[must_use]
+    pub fn with_latency(mut self, min: Duration, max: Duration) -> Self {
+        self.latency = Some((min, max.max(min)));
+        self
+    }
+
+    /// Only inject faults for keys (or list prefixes) starting with `prefix`.
+    #[must_use]
+    pub fn only_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_owned());
+        self
+    }
+}
+
+/// Counts of faults injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    /// Operations failed outright.
+    pub errors: u64,
+    /// Writes that persisted a truncated value before failing.
+    pub torn_writes: u64,
+    /// Operations that were delayed.
+    pub delays: u64,
+}
+
+/// A [`StorageBackend`] wrapper that injects faults according to a [`FaultPlan`].
+///
+/// Faults can be switched off with [`set_enabled`](Self::set_enabled) to set
+/// up fixtures or to verify recovery once storage is healthy again.
+#[derive(Debug)]
+pub struct FaultInjectingBackend<B> {
+    inner: B,
+    plan: FaultPlan,
+    rng: AtomicU64,
+    enabled: AtomicBool,
+    errors: AtomicU64,
+    torn_writes: AtomicU64,
+    delays: AtomicU64,
+}
+
+impl<B: StorageBackend> FaultInjectingBackend<B> {
+    /// Wrap `inner`, with faults enabled.
+    #[must_use]
+    pub fn new(inner: B, plan: FaultPlan) -> Self {
+        Self {
+            inner,
+            rng: AtomicU64::new(plan.seed),
+            plan,
+            enabled: AtomicBool::new(true),
+            errors: AtomicU64::new(0),
+            torn_writes: AtomicU64::new(0),
+            delays: AtomicU64::new(0),
+        }
+    }
+
+    /// Turn fault injection on or off.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Faults injected so far.
+    #[must_use]
+    pub fn stats(&self) -> FaultStats {
+        FaultStats {
+            errors: self.errors.load(Ordering::SeqCst),
+            torn_writes: self.torn_writes.load(Ordering::SeqCst),
+            delays: self.delays.load(Ordering::SeqCst),
+        }
+    }
+
+    /// The wrapped backend, for inspecting state without faults.
+    #[must_use]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Next value of the splitmix64 sequence.
+    fn next_u64(&self) -> u64 {
+        let mut z = self
+            .rng
+            .fetch_add(GOLDEN_GAMMA, Ordering::SeqCst)
+            .wrapping_add(GOLDEN_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Whether faults apply to `key` right now.
+    fn targets(&self, key: &str) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+            && self
+                .plan
+                .prefix
+                .as_deref()
+                .is_none_or(|prefix| key.starts_with(prefix))
+    }
+
+    /// Roll against a threshold produced by [`threshold`].
+    fn roll(&self, threshold: u64) -> bool {
+        threshold > 0 && self.next_u64() < threshold
+    }
+
+    /// Sleep for the planned latency, if any.
+    async fn delay(&self) {
+        let Some((min, max)) = self.plan.latency else {
+            return;
+        };
+        let spread = u64::try_from(max.saturating_sub(min).as_micros()).unwrap_or(u64::MAX);
+        let extra = if spread == 0 {
+            0
+        } else {
+            self.next_u64() % spread.saturating_add(1)
+        };
+        self.delays.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(min + Duration::from_micros(extra)).await;
+    }
+
+    /// Apply latency and decide whether to fail, for an operation on `key`.
+    async fn inject(&self, key: &str, threshold: u64) -> bool {
+        if !self.targets(key) {
+            return false;
+        }
+        self.delay().await;
+        let fail = self.roll(threshold);
+        if fail {
+            self.errors.fetch_add(1, Ordering::SeqCst);
+        }
+        fail
+    }
+}
+
+#[async_trait::async_trait]
+impl<B: StorageBackend> StorageBackend for FaultInjectingBackend<B> {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        if self.inject(key, self.plan.read_errors).await {
+            return Err(StorageError::Read {
+                key: key.to_owned(),
+                reason: "injected read fault".to_owned(),
+            });
+        }
+        self.inner.get(key).await
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        if self.inject(key, self.plan.write_errors).await {
+            return Err(StorageError::Write {
+                key: key.to_owned(),
+                reason: "injected write fault".to_owned(),
+            });
+        }
+        if self.targets(key) && self.roll(self.plan.torn_writes) {
+            self.torn_writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.put(key, &value[..value.len() / 2]).await?;
+            return Err(StorageError::Write {
+                key: key.to_owned(),
+                reason: "injected torn write".to_owned(),
+            });
+        }
+        self.inner.put(key, value).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        if self.inject(key, self.plan.delete_errors).await {
+            return Err(StorageError::Delete {
+                key: key.to_owned(),
+                reason: "injected delete fault".to_owned(),
+            });
+        }
+        self.inner.delete(key).await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        if self.inject(prefix, self.plan.list_errors).await {
+            return Err(StorageError::List {
+                prefix: prefix.to_owned(),
+                reason: "injected list fault".to_owned(),
+            });
+        }
+        self.inner.list(prefix).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        Ok(self.get(key).await?.is_some())
+    }
+
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        if self.inject("", self.plan.list_errors).await {
+            return Err(StorageError::Snapshot {
+                reason: "injected snapshot fault".to_owned(),
+            });
+        }
+        self.inner.snapshot().await
+    }
+
+    async fn acquire_lock(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<String, StorageError> {
+        self.inner.acquire_lock(name, holder, ttl).await
+    }
+
+    async fn lock_holder(&self, name: &str) -> Result<Option<String>, StorageError> {
+        self.inner.lock_holder(name).await
+    }
+
+    async fn release_lock(&self, name: &str, holder: &str) -> Result<(), StorageError> {
+        self.inner.release_lock(name, holder).await
+    }
+}
+
+/// Map a probability onto the `u64` range the PRNG output is compared with.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn threshold(rate: f64) -> u64 {
+    if rate >= 1.0 {
+        u64::MAX
+    } else if rate > 0.0 {
+        (rate * u64::MAX as f64) as u64
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::MemoryBackend;
+
+    #[tokio::test]
+    async fn empty_plan_passes_through() {
+        let backend = FaultInjectingBackend::new(MemoryBackend::new(), FaultPlan::new(1));
+        backend.put("a", b"1").await.unwrap();
+        assert_eq!(backend.get("a").await.unwrap(), Some(b"1".to_vec()));
+        assert_eq!(backend.stats(), FaultStats::default());
+    }
+
+    #[tokio::test]
+    async fn certain_faults_always_fire() {
+        let plan = FaultPlan::new(1)
+            .with_read_errors(1.0)
+            .with_list_errors(1.0);
+        let backend = FaultInjectingBackend::new(MemoryBackend::new(), plan);
+        assert!(matches!(
+            backend.get("a").await,
+            Err(StorageError::Read { .. })
+        ));
+        assert!(matches!(
+            backend.list("").await,
+            Err(StorageError::List { .. })
+        ));
+        assert_eq!(backend.stats().errors, 2);
+
+        backend.set_enabled(false);
+        assert_eq!(backend.get("a").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn torn_write_keeps_half_the_value() {
+        let plan = FaultPlan::new(7).with_torn_writes(1.0);
+        let backend = FaultInjectingBackend::new(MemoryBackend::new(), plan);
+        assert!(backend.put("a", b"abcdef").await.is_err());
+        assert_eq!(
+            backend.inner().get("a").await.unwrap(),
+            Some(b"abc".to_vec())
+        );
+        assert_eq!(backend.stats().torn_writes, 1);
+    }
+
+    #[tokio::test]
+    async fn prefix_limits_faults() {
+        let plan = FaultPlan::new(3).with_write_errors(1.0).only_prefix("kv/");
+        let backend = FaultInjectingBackend::new(MemoryBackend::new(), plan);
+        backend.put("sys/config", b"ok").await.unwrap();
+        assert!(backend.put("kv/data/a", b"no").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn same_seed_replays_same_faults() {
+        async fn outcomes(seed: u64) -> Vec<bool> {
+            let plan = FaultPlan::new(seed).with_write_errors(0.5);
+            let backend = FaultInjectingBackend::new(MemoryBackend::new(), plan);
+            let mut out = Vec::new();
+            for i in 0..32 {
+                out.push(backend.put(&format!("k{i}"), b"v").await.is_ok());
+            }
+            out
+        }
+
+        let first = outcomes(99).await;
+        assert_eq!(first, outcomes(99).await);
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+}
@@ -5,13 +5,27 @@
 //! encryption barrier in `zvault-core` wraps a storage backend to ensure all
 //! data is encrypted before it reaches this layer.
 //!
-//! Three implementations are provided:
+//! The provided implementations are:
 //!
 //! - [`RocksDbBackend`] — production default, backed by `RocksDB` (feature `rocksdb-backend`)
 //! - [`RedbBackend`] — pure-Rust alternative, backed by redb (feature `redb-backend`)
+//! - [`PostgresBackend`] — backed by a `PostgreSQL` table (feature `postgres-backend`)
+//! - [`S3Backend`] — backed by an S3, R2, or `MinIO` bucket (feature `s3-backend`)
 //! - [`MemoryBackend`] — in-memory, for testing only
+//!
+//! Backends shared by several servers also provide expiring locks, used to
+//! elect the active node in a high-availability cluster; of the above,
+//! [`PostgresBackend`] and [`MemoryBackend`] implement them.
+//!
+//! With the `sigv4` feature, [`sigv4`] signs requests with AWS Signature
+//! Version 4 for the S3 backend and other AWS clients.
+//!
+//! With the `testing` feature, [`FaultInjectingBackend`] wraps any backend to
+//! inject latency, failures, and torn writes for chaos tests.
 
 mod error;
+#[cfg(feature = "testing")]
+mod fault;
 mod memory;
 #[cfg(feature = "postgres-backend")]
 mod postgres_backend;
@@ -19,8 +33,14 @@ mod postgres_backend;
 mod redb_backend;
 #[cfg(feature = "rocksdb-backend")]
 mod rocksdb_backend;
+#[cfg(feature = "s3-backend")]
+mod s3_backend;
+#[cfg(feature = "sigv4")]
+pub mod sigv4;
 
 pub use error::StorageError;
+#[cfg(feature = "testing")]
+pub use fault::{FaultInjectingBackend, FaultPlan, FaultStats};
 pub use memory::MemoryBackend;
 #[cfg(feature = "postgres-backend")]
 pub use postgres_backend::PostgresBackend;
@@ -28,6 +48,10 @@ pub use postgres_backend::PostgresBackend;
 pub use redb_backend::RedbBackend;
 #[cfg(feature = "rocksdb-backend")]
 pub use rocksdb_backend::RocksDbBackend;
+#[cfg(feature = "s3-backend")]
+pub use s3_backend::{S3Backend, S3Config};
+
+use std::time::Duration;
 
 /// A pluggable key-value storage backend.
 ///
@@ -83,4 +107,80 @@ pub trait StorageBackend: Send + Sync + 'static {
     async fn exists(&self, key: &str) -> Result<bool, StorageError> {
         Ok(self.get(key).await?.is_some())
     }
+
+    /// Read every key-value pair as of a single point in time, sorted by
+    /// key. Writes that race with the snapshot are either wholly included
+    /// or wholly absent.
+    ///
+    /// The default implementation lists all keys and reads them one by one,
+    /// which is NOT point-in-time consistent: a key written or deleted
+    /// mid-scan may be missed or seen in its newer state. Backends with
+    /// native snapshots override this.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::Snapshot`] (or the error of the failing read
+    /// for the default implementation) if the backend fails.
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        let mut entries = Vec::new();
+        for key in self.list("").await? {
+            if let Some(value) = self.get(&key).await? {
+                entries.push((key, value));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Take the lock `name` for `holder`, or extend it if `holder` already
+    /// has it, until `ttl` from now. A lock whose holder let it expire may
+    /// be taken by anyone.
+    ///
+    /// Returns the lock's holder afterwards: `holder` if it was taken or
+    /// extended, otherwise whoever holds it. Locks live outside the
+    /// key space and never appear in [`list`](StorageBackend::list) or
+    /// [`snapshot`](StorageBackend::snapshot).
+    ///
+    /// The default implementation supports no locks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::Lock`] if the backend fails or has no locks.
+    async fn acquire_lock(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<String, StorageError> {
+        let _ = (holder, ttl);
+        Err(StorageError::Lock {
+            name: name.to_owned(),
+            reason: "this storage backend does not support locks".to_owned(),
+        })
+    }
+
+    /// The current holder of the lock `name`, if it is held and has not
+    /// expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::Lock`] if the backend fails or has no locks.
+    async fn lock_holder(&self, name: &str) -> Result<Option<String>, StorageError> {
+        Err(StorageError::Lock {
+            name: name.to_owned(),
+            reason: "this storage backend does not support locks".to_owned(),
+        })
+    }
+
+    /// Release the lock `name` if `holder` holds it; otherwise do nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::Lock`] if the backend fails or has no locks.
+    async fn release_lock(&self, name: &str, holder: &str) -> Result<(), StorageError> {
+        let _ = holder;
+        Err(StorageError::Lock {
+            name: name.to_owned(),
+            reason: "this storage backend does not support locks".to_owned(),
+        })
+    }
 }
@@ -3,10 +3,12 @@
 //! This backend stores all data in a `BTreeMap` behind a `RwLock`. It is not
 //! persistent — all data is lost when the process exits. Use this for unit
 //! tests and integration tests where you need a real storage backend without
-//! touching disk.
+//! touching disk. Locks are held in a separate map, so clones of one backend
+//! can stand in for several servers sharing storage.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 use crate::{StorageBackend, StorageError};
@@ -31,6 +33,8 @@ use crate::{StorageBackend, StorageError};
 #[derive(Debug, Clone)]
 pub struct MemoryBackend {
     data: Arc<RwLock<BTreeMap<String, Vec<u8>>>>,
+    /// Lock name → (holder, expiry).
+    locks: Arc<RwLock<HashMap<String, (String, Instant)>>>,
 }
 
 impl MemoryBackend {
@@ -39,6 +43,7 @@ impl MemoryBackend {
     pub fn new() -> Self {
         Self {
             data: Arc::new(RwLock::new(BTreeMap::new())),
+            locks: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -82,6 +87,49 @@ impl StorageBackend for MemoryBackend {
         let data = self.data.read().await;
         Ok(data.contains_key(key))
     }
+
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        let data = self.data.read().await;
+        Ok(data.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+    }
+
+    async fn acquire_lock(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<String, StorageError> {
+        let mut locks = self.locks.write().await;
+        let now = Instant::now();
+        match locks.get(name) {
+            Some((current, expires_at)) if current != holder && *expires_at > now => {
+                Ok(current.clone())
+            }
+            _ => {
+                locks.insert(name.to_owned(), (holder.to_owned(), now + ttl));
+                Ok(holder.to_owned())
+            }
+        }
+    }
+
+    async fn lock_holder(&self, name: &str) -> Result<Option<String>, StorageError> {
+        let locks = self.locks.read().await;
+        Ok(locks
+            .get(name)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(holder, _)| holder.clone()))
+    }
+
+    async fn release_lock(&self, name: &str, holder: &str) -> Result<(), StorageError> {
+        let mut locks = self.locks.write().await;
+        if locks
+            .get(name)
+            .is_some_and(|(current, _)| current == holder)
+        {
+            locks.remove(name);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -142,6 +190,22 @@ mod tests {
         assert_eq!(keys, vec!["kv/data/a", "kv/data/b"]);
     }
 
+    #[tokio::test]
+    async fn snapshot_returns_all_entries_sorted() {
+        let backend = MemoryBackend::new();
+        backend.put("sys/b", b"2").await.unwrap();
+        backend.put("kv/a", b"1").await.unwrap();
+
+        let entries = backend.snapshot().await.unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("kv/a".to_owned(), b"1".to_vec()),
+                ("sys/b".to_owned(), b"2".to_vec())
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn list_empty_prefix_returns_all() {
         let backend = MemoryBackend::new();
@@ -180,4 +244,42 @@ mod tests {
         let val = clone.get("key").await.unwrap();
         assert_eq!(val, Some(b"val".to_vec()));
     }
+
+    #[tokio::test]
+    async fn lock_excludes_other_holders_until_released() {
+        let backend = MemoryBackend::new();
+        let ttl = Duration::from_secs(60);
+        assert_eq!(backend.acquire_lock("leader", "a", ttl).await.unwrap(), "a");
+        assert_eq!(backend.acquire_lock("leader", "b", ttl).await.unwrap(), "a");
+        assert_eq!(backend.acquire_lock("leader", "a", ttl).await.unwrap(), "a");
+        assert_eq!(
+            backend.lock_holder("leader").await.unwrap(),
+            Some("a".to_owned())
+        );
+
+        backend.release_lock("leader", "b").await.unwrap();
+        assert_eq!(
+            backend.lock_holder("leader").await.unwrap(),
+            Some("a".to_owned())
+        );
+        backend.release_lock("leader", "a").await.unwrap();
+        assert_eq!(backend.lock_holder("leader").await.unwrap(), None);
+        assert_eq!(backend.acquire_lock("leader", "b", ttl).await.unwrap(), "b");
+        assert!(backend.list("").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn expired_lock_can_be_taken() {
+        let backend = MemoryBackend::new();
+        backend
+            .acquire_lock("leader", "a", Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(backend.lock_holder("leader").await.unwrap(), None);
+        let holder = backend
+            .acquire_lock("leader", "b", Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(holder, "b");
+    }
 }
@@ -6,6 +6,15 @@
 //!
 //! Feature-gated behind `postgres-backend`. Uses `sqlx` with the Tokio
 //! runtime for fully async operations — no `spawn_blocking` needed.
+//!
+//! Snapshots run in a read-only `REPEATABLE READ` transaction, so every row
+//! is read as of the transaction's first statement.
+//!
+//! Locks live in a separate `kv_locks` table. Expiry is computed with the
+//! database's clock, so servers sharing the database need not agree on the
+//! time.
+
+use std::time::Duration;
 
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
@@ -41,7 +50,7 @@ impl std::fmt::Debug for PostgresBackend {
 impl PostgresBackend {
     /// Connect to PostgreSQL and run the initial migration.
     ///
-    /// Creates the `kv_store` table if it does not exist.
+    /// Creates the `kv_store` and `kv_locks` tables if they do not exist.
     ///
     /// # Errors
     ///
@@ -82,6 +91,20 @@ impl PostgresBackend {
             reason: format!("index creation failed: {e}"),
         })?;
 
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS kv_locks (\
+                name       TEXT        PRIMARY KEY, \
+                holder     TEXT        NOT NULL, \
+                expires_at TIMESTAMPTZ NOT NULL\
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| StorageError::Open {
+            path: database_url.to_owned(),
+            reason: format!("migration failed: {e}"),
+        })?;
+
         Ok(Self { pool })
     }
 
@@ -164,4 +187,85 @@ impl StorageBackend for PostgresBackend {
 
         Ok(row.map(|(e,)| e).unwrap_or(false))
     }
+
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        let snapshot_err = |e: sqlx::Error| StorageError::Snapshot {
+            reason: e.to_string(),
+        };
+        let mut txn = self.pool.begin().await.map_err(snapshot_err)?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *txn)
+            .await
+            .map_err(snapshot_err)?;
+        let rows: Vec<(String, Vec<u8>)> =
+            sqlx::query_as("SELECT key, value FROM kv_store ORDER BY key")
+                .fetch_all(&mut *txn)
+                .await
+                .map_err(snapshot_err)?;
+        txn.commit().await.map_err(snapshot_err)?;
+        Ok(rows)
+    }
+
+    async fn acquire_lock(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<String, StorageError> {
+        let lock_err = |e: sqlx::Error| StorageError::Lock {
+            name: name.to_owned(),
+            reason: e.to_string(),
+        };
+        // Takes the lock if it is free, ours, or expired; otherwise the
+        // conflicting row is left alone and nothing is returned.
+        let taken: Option<(String,)> = sqlx::query_as(
+            "INSERT INTO kv_locks (name, holder, expires_at) \
+             VALUES ($1, $2, now() + make_interval(secs => $3)) \
+             ON CONFLICT (name) DO UPDATE \
+             SET holder = EXCLUDED.holder, expires_at = EXCLUDED.expires_at \
+             WHERE kv_locks.holder = EXCLUDED.holder OR kv_locks.expires_at <= now() \
+             RETURNING holder",
+        )
+        .bind(name)
+        .bind(holder)
+        .bind(ttl.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(lock_err)?;
+        if let Some((holder,)) = taken {
+            return Ok(holder);
+        }
+        let (current,): (String,) = sqlx::query_as("SELECT holder FROM kv_locks WHERE name = $1")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(lock_err)?;
+        Ok(current)
+    }
+
+    async fn lock_holder(&self, name: &str) -> Result<Option<String>, StorageError> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT holder FROM kv_locks WHERE name = $1 AND expires_at > now()")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StorageError::Lock {
+                    name: name.to_owned(),
+                    reason: e.to_string(),
+                })?;
+        Ok(row.map(|(h,)| h))
+    }
+
+    async fn release_lock(&self, name: &str, holder: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM kv_locks WHERE name = $1 AND holder = $2")
+            .bind(name)
+            .bind(holder)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StorageError::Lock {
+                name: name.to_owned(),
+                reason: e.to_string(),
+            })?;
+        Ok(())
+    }
 }
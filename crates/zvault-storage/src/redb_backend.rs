@@ -4,12 +4,14 @@
 //! required (no C++ FFI). Feature-gated behind `redb-backend`.
 //!
 //! redb uses a B-tree internally, giving consistent read/write performance
-//! without LSM compaction pauses. All operations are transactional.
+//! without LSM compaction pauses. All operations are transactional, and a
+//! snapshot is a single read transaction, which redb serves from the last
+//! committed state while writers proceed.
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use redb::{Database, TableDefinition};
+use redb::{Database, ReadableTable, TableDefinition};
 
 use crate::{StorageBackend, StorageError};
 
@@ -220,4 +222,34 @@ impl StorageBackend for RedbBackend {
             reason: format!("blocking task panicked: {e}"),
         })?
     }
+
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || {
+            let txn = db.begin_read().map_err(|e| StorageError::Transaction {
+                reason: e.to_string(),
+            })?;
+            let table = txn
+                .open_table(DATA_TABLE)
+                .map_err(|e| StorageError::MissingTable {
+                    name: format!("data: {e}"),
+                })?;
+            let iter = table.iter().map_err(|e| StorageError::Snapshot {
+                reason: e.to_string(),
+            })?;
+
+            let mut entries = Vec::new();
+            for item in iter {
+                let (k, v) = item.map_err(|e| StorageError::Snapshot {
+                    reason: e.to_string(),
+                })?;
+                entries.push((k.value().to_owned(), v.value().to_vec()));
+            }
+            Ok(entries)
+        })
+        .await
+        .map_err(|e| StorageError::Snapshot {
+            reason: format!("blocking task panicked: {e}"),
+        })?
+    }
 }
@@ -4,6 +4,9 @@
 //! operations are dispatched to a blocking thread via
 //! [`tokio::task::spawn_blocking`] since `RocksDB` is a synchronous C++ library.
 //!
+//! Snapshots read through a `RocksDB` snapshot, so they see the database as
+//! of a single sequence number while writes continue.
+//!
 //! Key namespacing and encryption happen above this layer (in the barrier).
 //! This backend treats keys as opaque UTF-8 strings and values as opaque bytes.
 
@@ -158,4 +161,27 @@ impl StorageBackend for RocksDbBackend {
             reason: format!("blocking task panicked: {e}"),
         })?
     }
+
+    async fn snapshot(&self) -> Result<Vec<(String, Vec<u8>)>, StorageError> {
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || {
+            let snapshot = db.snapshot();
+            let mut entries = Vec::new();
+            for item in snapshot.iterator(rocksdb::IteratorMode::Start) {
+                let (k, v) = item.map_err(|e| StorageError::Snapshot {
+                    reason: e.to_string(),
+                })?;
+                let key =
+                    String::from_utf8(k.into_vec()).map_err(|e| StorageError::InvalidKey {
+                        reason: e.to_string(),
+                    })?;
+                entries.push((key, v.into_vec()));
+            }
+            Ok(entries)
+        })
+        .await
+        .map_err(|e| StorageError::Snapshot {
+            reason: format!("blocking task panicked: {e}"),
+        })?
+    }
 }
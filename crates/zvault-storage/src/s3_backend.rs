@@ -0,0 +1,549 @@
+//! S3-compatible object storage backend.
+//!
+//! Stores each key as one object in a bucket on AWS S3, Cloudflare R2,
+//! `MinIO`, or any other service speaking the S3 API. Requests are signed
+//! with AWS Signature Version 4; listing uses `ListObjectsV2` with the key
+//! prefix, following continuation tokens. Throttled and transiently failing
+//! requests (`429`, `5xx`, connection errors) are retried with exponential
+//! backoff. Feature-gated behind `s3-backend`.
+//!
+//! An optional local write-through cache (any other [`StorageBackend`])
+//! serves reads without a round trip. The bucket stays authoritative: writes
+//! and deletes drop the cached entry before touching the bucket, and lists
+//! always go to the bucket. The cache assumes this process is the bucket's
+//! only writer.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode, Url};
+use tracing::warn;
+
+use crate::sigv4::{self, Signer, canonical_request, uri_encode};
+use crate::{StorageBackend, StorageError};
+
+/// Base delay between retries; doubled on each attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Connection settings for an [`S3Backend`].
+#[derive(Clone)]
+pub struct S3Config {
+    /// Service endpoint, e.g. `https://s3.us-east-1.amazonaws.com` or
+    /// `http://localhost:9000` for `MinIO`.
+    pub endpoint: String,
+    /// Bucket name.
+    pub bucket: String,
+    /// Signing region (`auto` for R2).
+    pub region: String,
+    /// Access key ID.
+    pub access_key_id: String,
+    /// Secret access key.
+    pub secret_access_key: String,
+    /// Prefix prepended to every object key, e.g. `zvault/`.
+    pub prefix: String,
+    /// Address the bucket as `<endpoint>/<bucket>` instead of
+    /// `<bucket>.<endpoint host>`. Required by most `MinIO` deployments.
+    pub path_style: bool,
+    /// Retries for throttled or transiently failing requests.
+    pub max_retries: u32,
+}
+
+impl S3Config {
+    /// Settings for an AWS S3 bucket, with no key prefix, virtual-hosted
+    /// addressing, and three retries.
+    #[must_use]
+    pub fn new(
+        bucket: impl Into<String>,
+        region: impl Into<String>,
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+    ) -> Self {
+        let region = region.into();
+        Self {
+            endpoint: format!("https://s3.{region}.amazonaws.com"),
+            bucket: bucket.into(),
+            region,
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            prefix: String::new(),
+            path_style: false,
+            max_retries: 3,
+        }
+    }
+}
+
+impl std::fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"[redacted]")
+            .field("prefix", &self.prefix)
+            .field("path_style", &self.path_style)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+/// A storage backend backed by an S3-compatible bucket.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use zvault_storage::{S3Backend, S3Config};
+/// let config = S3Config::new("my-vault", "us-east-1", "AKIA...", "secret");
+/// let backend = S3Backend::new(config).unwrap();
+/// ```
+pub struct S3Backend {
+    client: reqwest::Client,
+    config: S3Config,
+    /// Base URL requests are made against (includes the bucket).
+    base: Url,
+    /// `Host` header value, as signed.
+    host: String,
+    /// Canonical URI of the bucket root (`/` or `/<bucket>`).
+    root: String,
+    cache: Option<Arc<dyn StorageBackend>>,
+}
+
+impl std::fmt::Debug for S3Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Backend")
+            .field("config", &self.config)
+            .field("cached", &self.cache.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Backend {
+    /// Create a backend for the bucket described by `config`. No request is
+    /// made until the first operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::Open`] if the endpoint is not a valid URL or
+    /// the HTTP client cannot be built.
+    pub fn new(config: S3Config) -> Result<Self, StorageError> {
+        let open_err = |reason: String| StorageError::Open {
+            path: format!("s3://{}", config.bucket),
+            reason,
+        };
+        let endpoint = Url::parse(&config.endpoint)
+            .map_err(|e| open_err(format!("invalid endpoint '{}': {e}", config.endpoint)))?;
+        let endpoint_host = endpoint
+            .host_str()
+            .ok_or_else(|| open_err(format!("endpoint '{}' has no host", config.endpoint)))?;
+        let authority = match endpoint.port() {
+            Some(port) => format!("{endpoint_host}:{port}"),
+            None => endpoint_host.to_owned(),
+        };
+        let (host, root) = if config.path_style {
+            (authority, format!("/{}", uri_encode(&config.bucket, false)))
+        } else {
+            (format!("{}.{authority}", config.bucket), "/".to_owned())
+        };
+        let base = Url::parse(&format!("{}://{host}{root}", endpoint.scheme()))
+            .map_err(|e| open_err(format!("invalid bucket URL: {e}")))?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| open_err(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            config,
+            base,
+            host,
+            root,
+            cache: None,
+        })
+    }
+
+    /// Serve reads from `cache`, writing through to it on every change.
+    #[must_use]
+    pub fn with_cache(mut self, cache: Arc<dyn StorageBackend>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Canonical URI of the object holding `key`.
+    fn object_uri(&self, key: &str) -> String {
+        let object = uri_encode(&format!("{}{key}", self.config.prefix), false);
+        if self.root == "/" {
+            format!("/{object}")
+        } else {
+            format!("{}/{object}", self.root)
+        }
+    }
+
+    /// Send a signed request, retrying throttled and transient failures.
+    ///
+    /// Returns the last response once it is not retryable or retries run
+    /// out; the error string describes a transport failure.
+    async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        query: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<reqwest::Response, String> {
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+            .collect();
+        query.sort();
+        let query = query
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let mut url = self.base.clone();
+        url.set_path(uri);
+        url.set_query((!query.is_empty()).then_some(query.as_str()));
+        let payload_hash = sigv4::payload_hash(body);
+        let signer = Signer {
+            access_key_id: &self.config.access_key_id,
+            secret_access_key: &self.config.secret_access_key,
+            region: &self.config.region,
+            service: "s3",
+        };
+
+        let mut attempt = 0;
+        loop {
+            let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+            let headers = [
+                ("host", self.host.as_str()),
+                ("x-amz-content-sha256", payload_hash.as_str()),
+                ("x-amz-date", amz_date.as_str()),
+            ];
+            let authorization = signer.authorization(
+                &amz_date,
+                &canonical_request(method.as_str(), uri, &query, &headers, &payload_hash),
+                &headers,
+            );
+
+            let result = self
+                .client
+                .request(method.clone(), url.clone())
+                .header("x-amz-content-sha256", &payload_hash)
+                .header("x-amz-date", &amz_date)
+                .header("authorization", authorization)
+                .body(body.to_vec())
+                .send()
+                .await;
+            let retryable = match &result {
+                Ok(resp) => is_retryable(resp.status()),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !retryable || attempt >= self.config.max_retries {
+                return result.map_err(|e| e.to_string());
+            }
+            match &result {
+                Ok(resp) => {
+                    warn!(status = %resp.status(), attempt, "S3 request throttled, retrying");
+                }
+                Err(e) => warn!(error = %e, attempt, "S3 request failed, retrying"),
+            }
+            tokio::time::sleep(RETRY_BASE_DELAY * 2_u32.saturating_pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let read_err = |reason: String| StorageError::Read {
+            key: key.to_owned(),
+            reason,
+        };
+        let resp = self
+            .send(Method::GET, &self.object_uri(key), &[], &[])
+            .await
+            .map_err(read_err)?;
+        match resp.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let bytes = resp.bytes().await.map_err(|e| read_err(e.to_string()))?;
+                Ok(Some(bytes.to_vec()))
+            }
+            status => Err(read_err(error_reason(status, resp).await)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for S3Backend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        if let Some(cache) = &self.cache {
+            if let Some(value) = cache.get(key).await? {
+                return Ok(Some(value));
+            }
+        }
+        let value = self.get_object(key).await?;
+        if let (Some(cache), Some(value)) = (&self.cache, &value) {
+            if let Err(e) = cache.put(key, value).await {
+                warn!(key, error = %e, "failed to populate S3 cache");
+            }
+        }
+        Ok(value)
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        // Drop the old cached value first so a failed upload cannot leave a
+        // stale entry behind.
+        if let Some(cache) = &self.cache {
+            cache.delete(key).await?;
+        }
+        let write_err = |reason: String| StorageError::Write {
+            key: key.to_owned(),
+            reason,
+        };
+        let resp = self
+            .send(Method::PUT, &self.object_uri(key), &[], value)
+            .await
+            .map_err(write_err)?;
+        if !resp.status().is_success() {
+            return Err(write_err(error_reason(resp.status(), resp).await));
+        }
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put(key, value).await {
+                warn!(key, error = %e, "failed to populate S3 cache");
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        if let Some(cache) = &self.cache {
+            cache.delete(key).await?;
+        }
+        let delete_err = |reason: String| StorageError::Delete {
+            key: key.to_owned(),
+            reason,
+        };
+        let resp = self
+            .send(Method::DELETE, &self.object_uri(key), &[], &[])
+            .await
+            .map_err(delete_err)?;
+        match resp.status() {
+            StatusCode::NOT_FOUND => Ok(()),
+            status if status.is_success() => Ok(()),
+            status => Err(delete_err(error_reason(status, resp).await)),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let list_err = |reason: String| StorageError::List {
+            prefix: prefix.to_owned(),
+            reason,
+        };
+        let object_prefix = format!("{}{prefix}", self.config.prefix);
+        let mut keys = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", object_prefix.as_str())];
+            if let Some(token) = &continuation {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let resp = self
+                .send(Method::GET, &self.root, &query, &[])
+                .await
+                .map_err(list_err)?;
+            let status = resp.status();
+            if !status.is_success() {
+                return Err(list_err(error_reason(status, resp).await));
+            }
+            let body = resp.text().await.map_err(|e| list_err(e.to_string()))?;
+            let page = parse_list_page(&body);
+            keys.extend(page.keys.into_iter().filter_map(|k| {
+                k.strip_prefix(self.config.prefix.as_str())
+                    .map(str::to_owned)
+            }));
+            match page.next_token {
+                Some(token) if page.truncated => continuation = Some(token),
+                _ => break,
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        if let Some(cache) = &self.cache {
+            if cache.exists(key).await? {
+                return Ok(true);
+            }
+        }
+        let read_err = |reason: String| StorageError::Read {
+            key: key.to_owned(),
+            reason,
+        };
+        let resp = self
+            .send(Method::HEAD, &self.object_uri(key), &[], &[])
+            .await
+            .map_err(read_err)?;
+        match resp.status() {
+            StatusCode::NOT_FOUND => Ok(false),
+            status if status.is_success() => Ok(true),
+            status => Err(read_err(format!("unexpected status {status}"))),
+        }
+    }
+}
+
+/// Throttling (`429`, S3's `503 SlowDown`) and other transient server errors.
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Describe a failed response, including the S3 error code if present.
+async fn error_reason(status: StatusCode, resp: reqwest::Response) -> String {
+    let body = resp.text().await.unwrap_or_default();
+    match xml_values(&body, "Code").into_iter().next() {
+        Some(code) => format!("{status}: {code}"),
+        None => format!("{status}"),
+    }
+}
+
+/// One page of a `ListObjectsV2` response.
+struct ListPage {
+    keys: Vec<String>,
+    truncated: bool,
+    next_token: Option<String>,
+}
+
+fn parse_list_page(body: &str) -> ListPage {
+    ListPage {
+        keys: xml_values(body, "Key"),
+        truncated: xml_values(body, "IsTruncated")
+            .first()
+            .is_some_and(|v| v == "true"),
+        next_token: xml_values(body, "NextContinuationToken").into_iter().next(),
+    }
+}
+
+/// The unescaped text of every `<tag>…</tag>` element in `body`.
+fn xml_values(body: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut values = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        values.push(xml_unescape(&rest[..end]));
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
+
+fn xml_unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map_or_else(
+                    || entity.strip_prefix('#').and_then(|n| n.parse().ok()),
+                    |hex| u32::from_str_radix(hex, 16).ok(),
+                )
+                .and_then(char::from_u32),
+        };
+        if let Some(c) = decoded {
+            out.push(c);
+            rest = &rest[semi + 1..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::MemoryBackend;
+
+    #[test]
+    fn parses_list_pages() {
+        let body = "<ListBucketResult><IsTruncated>true</IsTruncated>\
+            <Contents><Key>v/sys/a</Key></Contents>\
+            <Contents><Key>v/sys/b&amp;c</Key></Contents>\
+            <NextContinuationToken>tok==</NextContinuationToken></ListBucketResult>";
+        let page = parse_list_page(body);
+        assert_eq!(page.keys, ["v/sys/a", "v/sys/b&c"]);
+        assert!(page.truncated);
+        assert_eq!(page.next_token.as_deref(), Some("tok=="));
+        assert_eq!(xml_unescape("&#65;&#x42;&bogus"), "AB&bogus");
+    }
+
+    /// Serve one canned response per connection, counting requests.
+    async fn fake_s3(responses: Vec<(u16, &'static str)>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 8192];
+                let _ = conn.read(&mut buf).await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                let reply = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                conn.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{addr}"), hits)
+    }
+
+    fn backend(endpoint: String) -> S3Backend {
+        S3Backend::new(S3Config {
+            endpoint,
+            path_style: true,
+            ..S3Config::new("vault", "us-east-1", "AKID", "secret")
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn retries_throttled_requests() {
+        let (endpoint, hits) = fake_s3(vec![(503, "<Code>SlowDown</Code>"), (200, "v")]).await;
+        let value = backend(endpoint).get("sys/a").await.unwrap();
+        assert_eq!(value.as_deref(), Some(&b"v"[..]));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn cache_serves_reads_after_write_through() {
+        let (endpoint, hits) = fake_s3(vec![(200, "")]).await;
+        let s3 = backend(endpoint).with_cache(Arc::new(MemoryBackend::new()));
+        s3.put("sys/a", b"v").await.unwrap();
+        assert_eq!(s3.get("sys/a").await.unwrap().as_deref(), Some(&b"v"[..]));
+        assert!(s3.exists("sys/a").await.unwrap());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+}
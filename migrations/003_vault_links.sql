@@ -0,0 +1,16 @@
+-- ZVault Cloud: links between cloud organizations and self-hosted vaults
+--
+-- A self-hosted vault links itself to an org and gets a link ID; an org
+-- admin confirms that link ID here. Service tokens are only exchanged for
+-- vault tokens (and back) while both sides agree.
+
+-- ============================================================
+-- Vault links (one per organization)
+-- ============================================================
+CREATE TABLE IF NOT EXISTS vault_links (
+    org_id       UUID        PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    -- Link ID generated by the self-hosted vault (`GET /v1/sys/cloud-link`)
+    link_id      TEXT        NOT NULL,
+    confirmed_by UUID        NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,32 @@
+-- ZVault Cloud: secret version history
+--
+-- Every write to cloud_secrets also records the written version here, so
+-- overwritten and deleted values can be listed and restored. Versions older
+-- than the org tier's retention are pruned, except each secret's current one.
+
+-- ============================================================
+-- Secret versions (per environment + key)
+-- ============================================================
+CREATE TABLE IF NOT EXISTS cloud_secret_versions (
+    id              UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    environment_id  UUID        NOT NULL REFERENCES environments(id) ON DELETE CASCADE,
+    key             TEXT        NOT NULL,
+    version         INT         NOT NULL,
+    -- Encrypted value (AES-256-GCM with org encryption key)
+    encrypted_value BYTEA       NOT NULL,
+    nonce           BYTEA       NOT NULL,
+    comment         TEXT        NOT NULL DEFAULT '',
+    created_by      UUID,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (environment_id, key, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_cloud_secret_versions_env_time
+    ON cloud_secret_versions (environment_id, created_at);
+
+-- Existing secrets start their history at their current version.
+INSERT INTO cloud_secret_versions
+    (environment_id, key, version, encrypted_value, nonce, comment, created_by, created_at)
+SELECT environment_id, key, version, encrypted_value, nonce, comment, updated_by, updated_at
+FROM cloud_secrets
+ON CONFLICT (environment_id, key, version) DO NOTHING;
@@ -0,0 +1,11 @@
+-- ZVault Cloud: environment inheritance
+--
+-- An environment may inherit from another environment of the same project
+-- (typically a shared base). Reads resolve a key through the chain, nearest
+-- environment first; writes always go to the environment itself, so a key
+-- set there overrides the inherited one.
+
+ALTER TABLE environments
+    ADD COLUMN IF NOT EXISTS parent_id UUID REFERENCES environments(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_environments_parent ON environments (parent_id);
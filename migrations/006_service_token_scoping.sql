@@ -0,0 +1,7 @@
+-- ZVault Cloud: service token usage tracking and revocation
+--
+-- Records where a service token was last used from and who revoked it, so a
+-- leaked token can be traced and its revocation audited.
+
+ALTER TABLE service_tokens ADD COLUMN IF NOT EXISTS last_used_ip TEXT;
+ALTER TABLE service_tokens ADD COLUMN IF NOT EXISTS revoked_by UUID;
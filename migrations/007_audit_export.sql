@@ -0,0 +1,37 @@
+-- ZVault Cloud: audit log retention and export
+--
+-- Audit entries are pruned after the org tier's retention. An org can also
+-- have its audit log exported on a schedule, as NDJSON to an S3 bucket or
+-- pushed to an HTTPS endpoint (a SIEM collector). Entries the export has
+-- not delivered yet are never pruned.
+
+-- ============================================================
+-- Audit export configuration (one per organization)
+-- ============================================================
+CREATE TABLE IF NOT EXISTS cloud_audit_export_configs (
+    org_id                UUID        PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    -- Where to export: {"type": "s3", "bucket": ..., "region": ...} or
+    -- {"type": "https", "url": ...}
+    destination           JSONB       NOT NULL,
+    -- S3 keys or HTTPS headers as JSON (AES-256-GCM with org encryption key)
+    encrypted_credentials BYTEA       NOT NULL,
+    nonce                 BYTEA       NOT NULL,
+    interval_secs         INT         NOT NULL DEFAULT 3600,
+    enabled               BOOLEAN     NOT NULL DEFAULT true,
+    -- Delete exported entries sooner than the tier's retention
+    retention_days        INT,
+    -- Entries up to (exported_until, exported_until_id) have been delivered
+    exported_until        TIMESTAMPTZ,
+    exported_until_id     UUID,
+    last_run_at           TIMESTAMPTZ,
+    last_success_at       TIMESTAMPTZ,
+    last_error            TEXT,
+    consecutive_failures  INT         NOT NULL DEFAULT 0,
+    updated_by            UUID        NOT NULL,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at            TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Export reads an org's entries in (created_at, id) order.
+CREATE INDEX IF NOT EXISTS idx_cloud_audit_org_export
+    ON cloud_audit_log (org_id, created_at, id);
@@ -0,0 +1,28 @@
+-- ZVault Cloud: customer-managed KMS keys (bring your own key)
+--
+-- An org can have its AES-256-GCM data key wrapped by a key in its own AWS
+-- or GCP KMS. The plaintext data key is then removed from `organizations`
+-- and only recoverable through the customer's KMS key, so revoking the
+-- platform's access to that key makes the org's secrets unreadable.
+
+-- ============================================================
+-- Customer-managed KMS keys (one per organization)
+-- ============================================================
+CREATE TABLE IF NOT EXISTS org_kms_keys (
+    org_id          UUID        PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    provider        TEXT        NOT NULL CHECK (provider IN ('aws', 'gcp')),
+    -- AWS key ARN (or key ID/alias with region) or GCP crypto key resource name
+    key_id          TEXT        NOT NULL,
+    -- AWS region, when key_id is not an ARN
+    region          TEXT,
+    -- The org's data key, encrypted by the KMS key
+    wrapped_key     BYTEA       NOT NULL,
+    -- 'unavailable' after the KMS refused to unwrap the data key
+    status          TEXT        NOT NULL DEFAULT 'active'
+                                CHECK (status IN ('active', 'unavailable')),
+    last_error      TEXT,
+    last_checked_at TIMESTAMPTZ,
+    rewrapped_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    configured_by   UUID        NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,138 @@
+//! Compile-time checked secret references for the `ZVault` Rust SDK.
+//!
+//! Use it through the SDK's `macros` feature, which re-exports
+//! [`secret!`] as `zvault_sdk::secret!`.
+//!
+//! ```rust,no_run
+//! use zvault_sdk::{secret, SecretPath, SelfHosted};
+//!
+//! const DATABASE_URL: SecretPath = secret!("env/myapp/DATABASE_URL");
+//!
+//! # async fn example(vault: SelfHosted) -> Result<(), zvault_sdk::ZVaultError> {
+//! let url = DATABASE_URL.get(&vault).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A malformed path is a compile error:
+//!
+//! ```compile_fail
+//! const DATABASE_URL: zvault_sdk::SecretPath = zvault_sdk::secret!("env/myapp//DATABASE_URL");
+//! ```
+
+use std::io::{Read as _, Write as _};
+use std::net::{TcpStream, ToSocketAddrs as _};
+use std::time::Duration;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// Build-time variable that turns on existence checks.
+const CHECK_ENV: &str = "ZVAULT_CHECK_SECRETS";
+const DEFAULT_ADDR: &str = "http://127.0.0.1:8200";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_SEGMENTS: usize = 10;
+
+/// A KV secret path, checked at compile time.
+///
+/// `secret!("env/myapp/DATABASE_URL")` expands to a constant
+/// `zvault_sdk::SecretPath`. The path must be what the server accepts:
+/// segments of letters, digits, `_`, and `-`, at most ten of them, with no
+/// empty ones.
+///
+/// With `ZVAULT_CHECK_SECRETS=1` set during the build, the macro also asks
+/// the vault at `VAULT_ADDR` (default `http://127.0.0.1:8200`), with
+/// `VAULT_TOKEN`, whether the secret exists, and fails the build if not.
+/// The check needs a plain-`http` dev vault and runs only when the calling
+/// crate is recompiled, so run it from a clean build in CI.
+#[proc_macro]
+pub fn secret(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    let path = lit.value();
+    if let Err(message) = validate(&path).and_then(|()| check_exists(&path)) {
+        return syn::Error::new(lit.span(), message)
+            .to_compile_error()
+            .into();
+    }
+    quote!(::zvault_sdk::SecretPath::__checked(#path)).into()
+}
+
+/// The server's rules for secret paths, plus no empty segments.
+fn validate(path: &str) -> Result<(), String> {
+    if path.is_empty() {
+        return Err("secret path must not be empty".to_owned());
+    }
+    if let Some(c) = path
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '_' | '-' | '/'))
+    {
+        return Err(format!(
+            "secret path may only contain letters, digits, '_', '-', and '/', found {c:?}"
+        ));
+    }
+    let segments: Vec<&str> = path.split('/').collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(format!(
+            "secret path {path:?} has an empty segment (leading, trailing, or doubled '/')"
+        ));
+    }
+    if segments.len() > MAX_SEGMENTS {
+        return Err(format!(
+            "secret path exceeds maximum depth of {MAX_SEGMENTS} segments"
+        ));
+    }
+    Ok(())
+}
+
+/// With `ZVAULT_CHECK_SECRETS` set, fail unless the dev vault has `path`.
+fn check_exists(path: &str) -> Result<(), String> {
+    if !std::env::var(CHECK_ENV).is_ok_and(|v| !v.is_empty() && v != "0") {
+        return Ok(());
+    }
+    let addr = std::env::var("VAULT_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_owned());
+    let token = std::env::var("VAULT_TOKEN")
+        .map_err(|_| format!("{CHECK_ENV} is set but VAULT_TOKEN is not"))?;
+    match metadata_status(&addr, &token, path)? {
+        200 => Ok(()),
+        404 => Err(format!("secret {path:?} does not exist on {addr}")),
+        status => Err(format!(
+            "{addr} answered HTTP {status} when checking secret {path:?}"
+        )),
+    }
+}
+
+/// Status of `GET /v1/secret/metadata/{path}` on a plain-`http` vault.
+fn metadata_status(addr: &str, token: &str, path: &str) -> Result<u16, String> {
+    let host = addr
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("{CHECK_ENV} needs an http:// dev vault, not {addr}"))?
+        .trim_end_matches('/');
+    let unreachable = |err: std::io::Error| format!("cannot reach {addr} to check secrets: {err}");
+    let target = if host.contains(':') {
+        host.to_owned()
+    } else {
+        format!("{host}:80")
+    };
+    let socket = target
+        .to_socket_addrs()
+        .map_err(unreachable)?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {addr}"))?;
+    let mut stream = TcpStream::connect_timeout(&socket, CHECK_TIMEOUT).map_err(unreachable)?;
+    stream
+        .set_read_timeout(Some(CHECK_TIMEOUT))
+        .map_err(unreachable)?;
+    write!(
+        stream,
+        "GET /v1/secret/metadata/{path} HTTP/1.1\r\nHost: {host}\r\nX-Vault-Token: {token}\r\nConnection: close\r\n\r\n"
+    )
+    .map_err(unreachable)?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).map_err(unreachable)?;
+    String::from_utf8_lossy(&response)
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| format!("{addr} sent an invalid HTTP response"))
+}
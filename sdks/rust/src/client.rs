@@ -5,14 +5,17 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use tokio::sync::RwLock;
 
-use crate::error::ZVaultError;
+use crate::error::{self, ZVaultError};
+use crate::events::EventSubscription;
 use crate::types::{
-    ApiErrorBody, HealthStatus, SecretEntry, SecretKey, SecretKeysResponse, SecretResponse,
+    ApiErrorBody, FetchedSecret, HealthStatus, SecretEntry, SecretFreshness, SecretKey,
+    SecretKeysResponse, SecretResponse, SecretUpdate, SecretsResponse,
 };
 use crate::{
-    CacheEntry, ZVault, ZVaultConfig, DEFAULT_BASE_URL, DEFAULT_CACHE_TTL, DEFAULT_MAX_RETRIES,
+    CachedSecret, ZVault, ZVaultConfig, DEFAULT_BASE_URL, DEFAULT_CACHE_TTL, DEFAULT_MAX_RETRIES,
     DEFAULT_TIMEOUT, RETRY_BASE_DELAY,
 };
 
@@ -94,6 +97,20 @@ impl ZVault {
             .build()
             .map_err(ZVaultError::Network)?;
 
+        // Event streams stay open indefinitely, so only connecting is bounded.
+        let stream_client = reqwest::Client::builder()
+            .connect_timeout(timeout)
+            .user_agent("zvault-rust-sdk/0.1.0")
+            .build()
+            .map_err(ZVaultError::Network)?;
+
+        #[cfg(feature = "fallback-cache")]
+        let fallback = cfg
+            .fallback_cache
+            .as_ref()
+            .map(crate::fallback::Fallback::open)
+            .transpose()?;
+
         Ok(Self {
             token,
             base_url,
@@ -103,19 +120,87 @@ impl ZVault {
             cache_ttl,
             max_retries,
             client,
+            stream_client,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "fallback-cache")]
+            fallback,
         })
     }
 
     /// Fetch all secrets for an environment.
     ///
-    /// Results are cached in-memory. On network failure, returns last-known
-    /// cached values (graceful degradation).
+    /// Results are cached in-memory per key. Secrets that fail to fetch are
+    /// filled in from their last-known cached values (graceful degradation),
+    /// or from the fallback cache on disk if one is configured; use
+    /// [`ZVault::get_all_with_freshness`] to tell which ones.
     ///
     /// # Errors
     ///
     /// Returns an error if the API is unreachable and no cached values exist.
     pub async fn get_all(&self, env: &str) -> Result<HashMap<String, String>, ZVaultError> {
+        let secrets = self.get_all_with_freshness(env).await?;
+        Ok(secrets.into_iter().map(|(k, s)| (k, s.value)).collect())
+    }
+
+    /// Fetch all secrets for an environment into a struct.
+    ///
+    /// See [`typed`](crate::typed) for how fields map to secret keys and
+    /// how values are parsed.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: zvault_sdk::ZVault) -> Result<(), zvault_sdk::ZVaultError> {
+    /// #[derive(serde::Deserialize)]
+    /// struct Config {
+    ///     database_url: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let config: Config = client.get_typed("production").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`ZVault::get_all`], or
+    /// [`ZVaultError::Deserialize`] naming the missing or unparsable secret.
+    pub async fn get_typed<T: DeserializeOwned>(&self, env: &str) -> Result<T, ZVaultError> {
+        let secrets = self.get_all(env).await?;
+        crate::typed::from_secrets(&secrets).map_err(|err| match err {
+            ZVaultError::Deserialize(message) => ZVaultError::Deserialize(format!(
+                "environment \"{}\": {message}",
+                self.resolve_env(env)
+            )),
+            err => err,
+        })
+    }
+
+    /// Fetch all secrets for an environment, reporting each one's freshness.
+    ///
+    /// Each key is fetched individually. A key whose fetch fails keeps its
+    /// cached value, marked [`SecretFreshness::Cached`], as long as that
+    /// value is within the cache TTL; otherwise it is left out. If the key
+    /// list itself cannot be fetched, every unexpired cached key for the
+    /// environment is returned as cached. Keys no longer listed by the API
+    /// are dropped from the cache.
+    ///
+    /// With a [`FallbackCache`](crate::FallbackCache), each successful fetch
+    /// is also written to disk, and when the API is unreachable and nothing
+    /// is cached in memory, the values on disk are returned marked
+    /// [`SecretFreshness::Stale`].
+    ///
+    /// Keys the environment inherits (say, from a shared base environment)
+    /// are included, with [`FetchedSecret::inherited_from`] naming their
+    /// source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key list cannot be fetched and no unexpired
+    /// cached values exist for the environment.
+    pub async fn get_all_with_freshness(
+        &self,
+        env: &str,
+    ) -> Result<HashMap<String, FetchedSecret>, ZVaultError> {
         let env = self.resolve_env(env);
         self.require_project_config()?;
 
@@ -124,46 +209,93 @@ impl ZVault {
             self.org_id, self.project_id, env
         );
 
-        match self.request::<SecretKeysResponse>("GET", &path, None).await {
-            Ok(keys_resp) => {
-                let mut secrets = HashMap::with_capacity(keys_resp.keys.len());
-                for k in &keys_resp.keys {
-                    let secret_path =
-                        format!("{}/{}", path, urlencoding::encode(&k.key));
-                    if let Ok(resp) = self
-                        .request::<SecretResponse>("GET", &secret_path, None)
-                        .await
-                    {
-                        secrets.insert(resp.secret.key, resp.secret.value);
+        let keys_resp = match self.request::<SecretKeysResponse>("GET", &path, None).await {
+            Ok(keys_resp) => keys_resp,
+            Err(err) => {
+                // Graceful degradation: serve whatever is still cached.
+                let now = Instant::now();
+                let cache = self.cache.read().await;
+                let cached: HashMap<String, FetchedSecret> = cache
+                    .get(&env)
+                    .map(|entry| {
+                        entry
+                            .secrets
+                            .iter()
+                            .filter_map(|(k, c)| Some((k.clone(), self.cached(c, now)?)))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if !cached.is_empty() {
+                    return Ok(cached);
+                }
+                #[cfg(feature = "fallback-cache")]
+                if let Some(fallback) = &self.fallback {
+                    if crate::fallback::is_unreachable(&err) {
+                        let stale = fallback.load(&self.fallback_scope(&env)).await;
+                        if !stale.is_empty() {
+                            return Ok(stale);
+                        }
                     }
                 }
+                return Err(err);
+            }
+        };
 
-                // Update cache
-                let mut cache = self.cache.write().await;
-                cache.insert(
-                    env.clone(),
-                    CacheEntry {
-                        secrets: secrets.clone(),
-                        expires_at: Instant::now() + self.cache_ttl,
+        let mut fetched = HashMap::with_capacity(keys_resp.keys.len());
+        for k in &keys_resp.keys {
+            let secret_path = format!("{}/{}", path, urlencoding::encode(&k.key));
+            if let Ok(resp) = self
+                .request::<SecretResponse>("GET", &secret_path, None)
+                .await
+            {
+                fetched.insert(k.key.clone(), resp.secret.value);
+            }
+        }
+
+        let now = Instant::now();
+        let mut cache = self.cache.write().await;
+        let entry = cache.entry(env.clone()).or_default();
+        entry
+            .secrets
+            .retain(|key, _| keys_resp.keys.iter().any(|k| &k.key == key));
+
+        let mut secrets = HashMap::with_capacity(keys_resp.keys.len());
+        for k in &keys_resp.keys {
+            if let Some(value) = fetched.remove(&k.key) {
+                entry.secrets.insert(
+                    k.key.clone(),
+                    CachedSecret {
+                        value: value.clone(),
+                        fetched_at: now,
+                        inherited_from: k.inherited_from.clone(),
                     },
                 );
-
-                Ok(secrets)
-            }
-            Err(err) => {
-                // Graceful degradation
-                let cache = self.cache.read().await;
-                if let Some(entry) = cache.get(&env) {
-                    if Instant::now() < entry.expires_at {
-                        return Ok(entry.secrets.clone());
-                    }
-                }
-                Err(err)
+                secrets.insert(
+                    k.key.clone(),
+                    FetchedSecret {
+                        value,
+                        freshness: SecretFreshness::Fresh,
+                        inherited_from: k.inherited_from.clone(),
+                    },
+                );
+            } else if let Some(cached) = entry.secrets.get(&k.key).and_then(|c| self.cached(c, now))
+            {
+                secrets.insert(k.key.clone(), cached);
             }
         }
+        drop(cache);
+
+        #[cfg(feature = "fallback-cache")]
+        if let Some(fallback) = &self.fallback {
+            fallback.store(&self.fallback_scope(&env), &secrets).await;
+        }
+
+        Ok(secrets)
     }
 
-    /// Fetch a single secret by key. Checks cache first.
+    /// Fetch a single secret by key. Checks cache first, and falls back to
+    /// the fallback cache on disk, if configured, when the API is
+    /// unreachable.
     ///
     /// # Errors
     ///
@@ -175,11 +307,9 @@ impl ZVault {
         // Check cache
         {
             let cache = self.cache.read().await;
-            if let Some(entry) = cache.get(&env) {
-                if Instant::now() < entry.expires_at {
-                    if let Some(val) = entry.secrets.get(key) {
-                        return Ok(val.clone());
-                    }
+            if let Some(cached) = cache.get(&env).and_then(|e| e.secrets.get(key)) {
+                if cached.fetched_at.elapsed() < self.cache_ttl {
+                    return Ok(cached.value.clone());
                 }
             }
         }
@@ -196,18 +326,29 @@ impl ZVault {
             Ok(resp) => {
                 // Cache the value
                 let mut cache = self.cache.write().await;
-                let entry = cache.entry(env.clone()).or_insert_with(|| CacheEntry {
-                    secrets: HashMap::new(),
-                    expires_at: Instant::now() + self.cache_ttl,
-                });
-                entry.secrets.insert(key.to_owned(), resp.secret.value.clone());
+                cache.entry(env.clone()).or_default().secrets.insert(
+                    key.to_owned(),
+                    CachedSecret {
+                        value: resp.secret.value.clone(),
+                        fetched_at: Instant::now(),
+                        inherited_from: resp.secret.inherited_from,
+                    },
+                );
                 Ok(resp.secret.value)
             }
-            Err(ZVaultError::Api { status_code: 404, .. }) => {
-                Err(ZVaultError::NotFound {
-                    key: key.to_owned(),
-                    env,
-                })
+            Err(ZVaultError::Api {
+                status_code: 404, ..
+            }) => Err(ZVaultError::NotFound {
+                key: key.to_owned(),
+                env,
+            }),
+            #[cfg(feature = "fallback-cache")]
+            Err(e) if crate::fallback::is_unreachable(&e) && self.fallback.is_some() => {
+                let stale = match &self.fallback {
+                    Some(fallback) => fallback.load(&self.fallback_scope(&env)).await,
+                    None => HashMap::new(),
+                };
+                stale.get(key).map(|s| s.value.clone()).ok_or(e)
             }
             Err(e) => Err(e),
         }
@@ -226,7 +367,9 @@ impl ZVault {
             "/orgs/{}/projects/{}/envs/{}/secrets",
             self.org_id, self.project_id, env
         );
-        let resp = self.request::<SecretKeysResponse>("GET", &path, None).await?;
+        let resp = self
+            .request::<SecretKeysResponse>("GET", &path, None)
+            .await?;
         Ok(resp.keys)
     }
 
@@ -259,11 +402,14 @@ impl ZVault {
 
         // Update cache
         let mut cache = self.cache.write().await;
-        let entry = cache.entry(env).or_insert_with(|| CacheEntry {
-            secrets: HashMap::new(),
-            expires_at: Instant::now() + self.cache_ttl,
-        });
-        entry.secrets.insert(key.to_owned(), value.to_owned());
+        cache.entry(env).or_default().secrets.insert(
+            key.to_owned(),
+            CachedSecret {
+                value: value.to_owned(),
+                fetched_at: Instant::now(),
+                inherited_from: None,
+            },
+        );
 
         Ok(resp.secret)
     }
@@ -286,9 +432,92 @@ impl ZVault {
         );
         self.request::<serde_json::Value>("DELETE", &path, None)
             .await?;
+
+        // Drop from cache
+        let mut cache = self.cache.write().await;
+        if let Some(entry) = cache.get_mut(&env) {
+            entry.secrets.remove(key);
+        }
         Ok(())
     }
 
+    /// Set several secrets in one transaction. Requires write permission.
+    ///
+    /// Each update may carry the version it expects to replace, for
+    /// optimistic concurrency: if any secret has changed since it was read,
+    /// nothing is written and [`ZVaultError::Conflict`] is returned.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: zvault_sdk::ZVault) -> Result<(), zvault_sdk::ZVaultError> {
+    /// use zvault_sdk::SecretUpdate;
+    ///
+    /// let current = client.list_keys("production").await?;
+    /// let version = current.iter().find(|k| k.key == "API_KEY").map_or(0, |k| k.version);
+    /// client
+    ///     .set_many(
+    ///         "production",
+    ///         &[SecretUpdate {
+    ///             key: "API_KEY".to_owned(),
+    ///             value: "new-value".to_owned(),
+    ///             expected_version: Some(version),
+    ///             ..Default::default()
+    ///         }],
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `ZVaultError::Conflict` if an expected version does not match,
+    /// or another error if the API request fails.
+    pub async fn set_many(
+        &self,
+        env: &str,
+        updates: &[SecretUpdate],
+    ) -> Result<Vec<SecretEntry>, ZVaultError> {
+        let env = self.resolve_env(env);
+        self.require_project_config()?;
+
+        let path = format!(
+            "/orgs/{}/projects/{}/envs/{}/secrets",
+            self.org_id, self.project_id, env
+        );
+        let secrets: Vec<serde_json::Value> = updates
+            .iter()
+            .map(|u| {
+                serde_json::json!({
+                    "key": u.key,
+                    "value": u.value,
+                    "comment": u.comment,
+                    "expected_version": u.expected_version,
+                })
+            })
+            .collect();
+        let body = serde_json::json!({ "secrets": secrets });
+        let resp = self
+            .request::<SecretsResponse>("PUT", &path, Some(body))
+            .await?;
+
+        // Update cache
+        let now = Instant::now();
+        let mut cache = self.cache.write().await;
+        let entry = cache.entry(env).or_default();
+        for u in updates {
+            entry.secrets.insert(
+                u.key.clone(),
+                CachedSecret {
+                    value: u.value.clone(),
+                    fetched_at: now,
+                    inherited_from: None,
+                },
+            );
+        }
+
+        Ok(resp.secrets)
+    }
+
     /// Check if the API is reachable and the token is valid.
     pub async fn healthy(&self) -> HealthStatus {
         let start = Instant::now();
@@ -300,9 +529,9 @@ impl ZVault {
         let cache = self.cache.read().await;
         let cached = cache
             .values()
-            .filter(|e| Instant::now() < e.expires_at)
-            .map(|e| e.secrets.len())
-            .sum();
+            .flat_map(|e| e.secrets.values())
+            .filter(|c| c.fetched_at.elapsed() < self.cache_ttl)
+            .count();
 
         HealthStatus {
             ok,
@@ -311,8 +540,67 @@ impl ZVault {
         }
     }
 
+    /// Subscribe to vault events whose topic matches `pattern` (e.g. `kv/*`).
+    ///
+    /// Streams from a self-hosted server's `/v1/sys/events/subscribe`
+    /// endpoint. The token needs `read` on `sys/events/subscribe/<pattern>`,
+    /// and only events about paths it can read are delivered.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: zvault_sdk::ZVault) -> Result<(), zvault_sdk::ZVaultError> {
+    /// let mut events = client.subscribe("kv/*").await?;
+    /// while let Some(event) = events.next().await {
+    ///     println!("{} changed", event?.data["path"]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server is unreachable or rejects the subscription.
+    pub async fn subscribe(&self, pattern: &str) -> Result<EventSubscription, ZVaultError> {
+        let url = format!(
+            "{}/v1/sys/events/subscribe/{}",
+            self.base_url,
+            pattern.trim_start_matches('/')
+        );
+        let resp = self
+            .stream_client
+            .get(&url)
+            .header("X-Vault-Token", &self.token)
+            .header("Accept", "text/event-stream")
+            .send()
+            .await
+            .map_err(ZVaultError::Network)?;
+
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(EventSubscription::new(resp));
+        }
+
+        let body = resp.text().await.unwrap_or_default();
+        Err(error::from_response(status.as_u16(), &body))
+    }
+
     // --- Private ---
 
+    /// A cached secret as a [`FetchedSecret`], unless it has expired.
+    fn cached(&self, cached: &CachedSecret, now: Instant) -> Option<FetchedSecret> {
+        let age = now.saturating_duration_since(cached.fetched_at);
+        (age < self.cache_ttl).then(|| FetchedSecret {
+            value: cached.value.clone(),
+            freshness: SecretFreshness::Cached { age },
+            inherited_from: cached.inherited_from.clone(),
+        })
+    }
+
+    /// Key of `env` in the fallback cache file.
+    #[cfg(feature = "fallback-cache")]
+    fn fallback_scope(&self, env: &str) -> String {
+        format!("{}/{}/{}", self.org_id, self.project_id, env)
+    }
+
     fn resolve_env(&self, env: &str) -> String {
         if env.is_empty() {
             self.default_env.clone()
@@ -383,17 +671,14 @@ impl ZVault {
                     if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
                         return Err(ZVaultError::Auth(msg));
                     }
+                    if status == StatusCode::CONFLICT {
+                        return Err(ZVaultError::Conflict(msg));
+                    }
                     if status == StatusCode::NOT_FOUND {
-                        return Err(ZVaultError::Api {
-                            status_code: 404,
-                            message: msg,
-                        });
+                        return Err(ZVaultError::status(404, msg));
                     }
 
-                    last_err = Some(ZVaultError::Api {
-                        status_code: status.as_u16(),
-                        message: msg,
-                    });
+                    last_err = Some(ZVaultError::status(status.as_u16(), msg));
 
                     if attempt < self.max_retries && is_retryable(status) {
                         sleep_with_jitter(attempt).await;
@@ -417,14 +702,11 @@ impl ZVault {
             break;
         }
 
-        Err(last_err.unwrap_or(ZVaultError::Api {
-            status_code: 0,
-            message: "unknown error".to_owned(),
-        }))
+        Err(last_err.unwrap_or_else(|| ZVaultError::status(0, "unknown error".to_owned())))
     }
 }
 
-fn is_retryable(status: StatusCode) -> bool {
+pub(crate) fn is_retryable(status: StatusCode) -> bool {
     matches!(
         status,
         StatusCode::TOO_MANY_REQUESTS
@@ -435,7 +717,7 @@ fn is_retryable(status: StatusCode) -> bool {
     )
 }
 
-async fn sleep_with_jitter(attempt: u32) {
+pub(crate) async fn sleep_with_jitter(attempt: u32) {
     // RETRY_BASE_DELAY is 500ms, max attempt ~3, so values stay small.
     #[allow(clippy::cast_possible_truncation)]
     let base = (RETRY_BASE_DELAY.as_millis() as u64).saturating_mul(2u64.saturating_pow(attempt));
@@ -1,4 +1,12 @@
 //! Error types for the `ZVault` SDK.
+//!
+//! The server reports failures as an envelope such as
+//! `{"errors": ["permission denied"], "code": "permission_denied",
+//! "message": "...", "request_id": "..."}`. [`from_response`] turns it into
+//! a [`ZVaultError`] by its `code`, so callers match on variants rather than
+//! message text.
+
+use serde::Deserialize;
 
 /// All errors that can occur when using the `ZVault` SDK.
 #[derive(Debug, thiserror::Error)]
@@ -12,8 +20,12 @@ pub enum ZVaultError {
     Api {
         /// HTTP status code.
         status_code: u16,
+        /// Machine-readable error code.
+        code: ErrorCode,
         /// Error message from the API.
         message: String,
+        /// Server-assigned ID of the failed request, for log correlation.
+        request_id: Option<String>,
     },
 
     /// Authentication failed (401/403).
@@ -29,10 +41,35 @@ pub enum ZVaultError {
         env: String,
     },
 
+    /// The vault is sealed (503) and must be unsealed before use.
+    #[error("zvault is sealed")]
+    Sealed,
+
+    /// A write's expected version did not match (409); nothing was written.
+    #[error("zvault conflict: {0}")]
+    Conflict(String),
+
     /// Request timed out.
     #[error("zvault request timed out")]
     Timeout,
 
+    /// An event subscription fell behind and the server dropped events.
+    #[error("zvault event subscription missed {0} events")]
+    EventsMissed(u64),
+
+    /// A template could not be parsed or rendered.
+    #[error("template error on line {line}: {message}")]
+    Template {
+        /// Line of the offending action.
+        line: usize,
+        /// What was wrong.
+        message: String,
+    },
+
+    /// Secrets could not be deserialized into the requested type.
+    #[error("cannot deserialize secrets: {0}")]
+    Deserialize(String),
+
     /// Network or HTTP client error.
     #[error("zvault network error: {0}")]
     Network(#[from] reqwest::Error),
@@ -41,3 +78,113 @@ pub enum ZVaultError {
     #[error("zvault json error: {0}")]
     Json(#[from] serde_json::Error),
 }
+
+impl ZVaultError {
+    /// The server's error code, for errors reported by the API.
+    #[must_use]
+    pub fn code(&self) -> Option<&ErrorCode> {
+        match self {
+            Self::Api { code, .. } => Some(code),
+            Self::Sealed => Some(&ErrorCode::Sealed),
+            Self::Conflict(_) => Some(&ErrorCode::Conflict),
+            _ => None,
+        }
+    }
+
+    /// An [`ZVaultError::Api`] error for `status`, without a server body.
+    pub(crate) fn status(status: u16, message: String) -> Self {
+        Self::Api {
+            status_code: status,
+            code: ErrorCode::for_status(status),
+            message,
+            request_id: None,
+        }
+    }
+}
+
+/// Machine-readable error code from the server's error envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The vault is sealed.
+    Sealed,
+    /// The token is missing, invalid, or expired.
+    Unauthenticated,
+    /// The token's policies do not allow the request.
+    PermissionDenied,
+    /// The path or object does not exist.
+    NotFound,
+    /// The request is malformed or fails validation.
+    InvalidRequest,
+    /// The request conflicts with the current state.
+    Conflict,
+    /// The server failed to handle the request.
+    Internal,
+    /// A dependency is unavailable, or the node cannot serve the request.
+    Unavailable,
+    /// The feature needs a higher license tier.
+    FeatureNotLicensed,
+    /// The path needs multi-factor authentication.
+    MfaRequired,
+    /// The path needs control group approval.
+    ControlGroupRequired,
+    /// Too many requests; retry later.
+    RateLimited,
+    /// A code this SDK version does not know.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    /// The code servers without an error envelope imply by `status`.
+    #[must_use]
+    pub fn for_status(status: u16) -> Self {
+        match status {
+            400 | 422 => Self::InvalidRequest,
+            401 => Self::Unauthenticated,
+            403 => Self::PermissionDenied,
+            404 => Self::NotFound,
+            409 => Self::Conflict,
+            429 => Self::RateLimited,
+            503 => Self::Unavailable,
+            500..=599 => Self::Internal,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    #[serde(default)]
+    errors: Vec<String>,
+    code: Option<ErrorCode>,
+    message: Option<String>,
+    request_id: Option<String>,
+}
+
+/// Build the error for a failed response with `status` and `body`.
+pub(crate) fn from_response(status: u16, body: &str) -> ZVaultError {
+    let envelope = serde_json::from_str::<ErrorEnvelope>(body).ok();
+    let (code, message, request_id) = match envelope {
+        Some(e) => (
+            e.code.unwrap_or_else(|| ErrorCode::for_status(status)),
+            e.message.or_else(|| e.errors.into_iter().next()),
+            e.request_id,
+        ),
+        None => (ErrorCode::for_status(status), None, None),
+    };
+    let message = message.unwrap_or_else(|| format!("HTTP {status}"));
+
+    match code {
+        ErrorCode::Sealed => ZVaultError::Sealed,
+        ErrorCode::Unauthenticated | ErrorCode::PermissionDenied => ZVaultError::Auth(message),
+        ErrorCode::Conflict => ZVaultError::Conflict(message),
+        code => ZVaultError::Api {
+            status_code: status,
+            code,
+            message,
+            request_id,
+        },
+    }
+}
@@ -0,0 +1,81 @@
+//! Vault event subscriptions over Server-Sent Events.
+
+use serde::Deserialize;
+
+use crate::error::ZVaultError;
+use crate::types::VaultEvent;
+
+/// SSE event name the server sends when a subscriber missed events.
+const LAGGED_EVENT: &str = "lagged";
+
+/// A live subscription to vault events, opened by [`crate::ZVault::subscribe`].
+pub struct EventSubscription {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct Lagged {
+    missed: u64,
+}
+
+impl EventSubscription {
+    pub(crate) fn new(response: reqwest::Response) -> Self {
+        Self {
+            response,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Wait for the next event.
+    ///
+    /// Returns `None` once the server ends the stream — when the vault is
+    /// sealed or the token is revoked or expires. Yields
+    /// [`ZVaultError::EventsMissed`] when this subscriber fell behind; the
+    /// stream stays open, but anything cached should be reloaded.
+    pub async fn next(&mut self) -> Option<Result<VaultEvent, ZVaultError>> {
+        loop {
+            while let Some(message) = self.take_message() {
+                if let Some(event) = parse_message(&message) {
+                    return Some(event);
+                }
+            }
+            match self.response.chunk().await {
+                Ok(Some(bytes)) => self.buffer.extend_from_slice(&bytes),
+                Ok(None) => return None,
+                Err(e) => return Some(Err(ZVaultError::Network(e))),
+            }
+        }
+    }
+
+    /// Remove the next complete SSE message from the buffer.
+    fn take_message(&mut self) -> Option<String> {
+        let end = self.buffer.windows(2).position(|w| w == b"\n\n")?;
+        let message: Vec<u8> = self.buffer.drain(..end + 2).collect();
+        Some(String::from_utf8_lossy(&message[..end]).into_owned())
+    }
+}
+
+/// Decode one SSE message. Keep-alive comments decode to `None`.
+fn parse_message(message: &str) -> Option<Result<VaultEvent, ZVaultError>> {
+    let mut name = None;
+    let mut data = String::new();
+    for line in message.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            name = Some(value.trim_start());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    if name == Some(LAGGED_EVENT) {
+        let missed = serde_json::from_str::<Lagged>(&data).map_or(0, |l| l.missed);
+        return Some(Err(ZVaultError::EventsMissed(missed)));
+    }
+    Some(serde_json::from_str(&data).map_err(ZVaultError::Json))
+}
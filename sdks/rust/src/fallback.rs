@@ -0,0 +1,528 @@
+//! Encrypted on-disk fallback cache.
+//!
+//! The in-memory cache only helps a process that has already fetched its
+//! secrets. With a [`FallbackCache`] configured, every successful fetch is
+//! also written to a file, AES-256-GCM encrypted under a key from a local key
+//! file or the OS keychain. When the API is unreachable and nothing is cached
+//! in memory — typically right after startup — the client serves the file's
+//! last-known-good values marked [`SecretFreshness::Stale`].
+//!
+//! Only unreachability falls back: network errors, timeouts, and 5xx
+//! responses. A rejected token or a missing secret is reported as usual.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::error::ZVaultError;
+use crate::types::{FetchedSecret, SecretFreshness};
+
+/// Nonce length for AES-256-GCM (96 bits).
+const NONCE_LEN: usize = 12;
+/// Version of the decrypted file layout.
+const FORMAT_VERSION: u32 = 1;
+/// Keychain account the key is stored under.
+const KEYCHAIN_ACCOUNT: &str = "zvault-sdk";
+
+/// Where the client keeps its last-known-good secrets.
+///
+/// ```rust,no_run
+/// use zvault_sdk::{FallbackCache, FallbackKey, ZVault, ZVaultConfig};
+///
+/// # fn example() -> Result<(), zvault_sdk::ZVaultError> {
+/// let client = ZVault::with_config(ZVaultConfig {
+///     fallback_cache: Some(FallbackCache {
+///         path: "/var/lib/myapp/zvault-cache".into(),
+///         key: FallbackKey::File("/var/lib/myapp/zvault-cache.key".into()),
+///         max_age: None,
+///     }),
+///     ..Default::default()
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FallbackCache {
+    /// Cache file. Created, mode 0600 on Unix, on the first successful fetch.
+    pub path: PathBuf,
+    /// Where the encryption key comes from.
+    pub key: FallbackKey,
+    /// Oldest value to serve; `None` serves values of any age.
+    pub max_age: Option<Duration>,
+}
+
+/// Source of the fallback cache's encryption key.
+#[derive(Clone)]
+pub enum FallbackKey {
+    /// A 32-byte key in this file, generated (mode 0600 on Unix) when the
+    /// file does not exist.
+    File(PathBuf),
+    /// A key in the OS keychain under this service name, generated when
+    /// missing. Uses `security` on macOS and `secret-tool` (Secret Service)
+    /// on Linux.
+    Keychain(String),
+    /// A key the application supplies itself.
+    Raw([u8; 32]),
+}
+
+impl fmt::Debug for FallbackKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => f.debug_tuple("File").field(path).finish(),
+            Self::Keychain(service) => f.debug_tuple("Keychain").field(service).finish(),
+            Self::Raw(_) => f.write_str("Raw(***)"),
+        }
+    }
+}
+
+/// Whether `err` means the API could not be reached, as opposed to a
+/// request it answered.
+pub(crate) fn is_unreachable(err: &ZVaultError) -> bool {
+    match err {
+        ZVaultError::Network(_) | ZVaultError::Timeout => true,
+        ZVaultError::Api { status_code, .. } => *status_code == 0 || *status_code >= 500,
+        _ => false,
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    /// Secrets by `{org}/{project}/{env}`.
+    environments: HashMap<String, HashMap<String, StoredSecret>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSecret {
+    value: String,
+    /// Unix seconds.
+    fetched_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inherited_from: Option<String>,
+}
+
+/// An open fallback cache.
+pub(crate) struct Fallback {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    max_age: Option<Duration>,
+    /// Serializes read-modify-write cycles on the file.
+    lock: Mutex<()>,
+}
+
+impl Fallback {
+    /// Resolve the key for `config`, creating it when missing.
+    pub(crate) fn open(config: &FallbackCache) -> Result<Self, ZVaultError> {
+        let key = match &config.key {
+            FallbackKey::File(path) => file_key(path)?,
+            FallbackKey::Keychain(service) => keychain_key(service)?,
+            FallbackKey::Raw(key) => *key,
+        };
+        Ok(Self {
+            path: config.path.clone(),
+            cipher: Aes256Gcm::new(&key.into()),
+            max_age: config.max_age,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Replace the stored secrets of `scope` with `secrets`.
+    pub(crate) async fn store(&self, scope: &str, secrets: &HashMap<String, FetchedSecret>) {
+        let _guard = self.lock.lock().await;
+        let now = SystemTime::now();
+        let mut file = self.read().unwrap_or_default();
+        file.version = FORMAT_VERSION;
+        file.environments.insert(
+            scope.to_owned(),
+            secrets
+                .iter()
+                .map(|(key, secret)| {
+                    let age = match secret.freshness {
+                        SecretFreshness::Fresh => Duration::ZERO,
+                        SecretFreshness::Cached { age } | SecretFreshness::Stale { age } => age,
+                    };
+                    let stored = StoredSecret {
+                        value: secret.value.clone(),
+                        fetched_at: unix_secs(now.checked_sub(age).unwrap_or(now)),
+                        inherited_from: secret.inherited_from.clone(),
+                    };
+                    (key.clone(), stored)
+                })
+                .collect(),
+        );
+        // A cache that cannot be written only costs the fallback.
+        let _ = self.write(&file);
+    }
+
+    /// The stored secrets of `scope` within `max_age`, marked stale.
+    pub(crate) async fn load(&self, scope: &str) -> HashMap<String, FetchedSecret> {
+        let _guard = self.lock.lock().await;
+        let now = unix_secs(SystemTime::now());
+        let Some(mut file) = self.read() else {
+            return HashMap::new();
+        };
+        file.environments
+            .remove(scope)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(key, stored)| {
+                let age = Duration::from_secs(now.saturating_sub(stored.fetched_at));
+                if self.max_age.is_some_and(|max| age > max) {
+                    return None;
+                }
+                let secret = FetchedSecret {
+                    value: stored.value,
+                    freshness: SecretFreshness::Stale { age },
+                    inherited_from: stored.inherited_from,
+                };
+                Some((key, secret))
+            })
+            .collect()
+    }
+
+    /// The decrypted file, or `None` if it is missing, was written under
+    /// another key, or is corrupt.
+    fn read(&self) -> Option<CacheFile> {
+        let data = std::fs::read(&self.path).ok()?;
+        if data.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .ok()?;
+        let file: CacheFile = serde_json::from_slice(&plaintext).ok()?;
+        (file.version == FORMAT_VERSION).then_some(file)
+    }
+
+    /// Encrypt and atomically replace the file.
+    fn write(&self, file: &CacheFile) -> Result<(), ZVaultError> {
+        let plaintext = serde_json::to_vec(file)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| ZVaultError::Config("fallback cache encryption failed".to_owned()))?;
+
+        let mut data = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+
+        let tmp = self.path.with_extension("tmp");
+        write_private(&tmp, &data, false)?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| io_error(&self.path, &e))
+    }
+}
+
+/// Read the key in `path`, or generate one there.
+fn file_key(path: &Path) -> Result<[u8; 32], ZVaultError> {
+    match std::fs::read(path) {
+        Ok(bytes) => <[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| {
+            ZVaultError::Config(format!(
+                "fallback cache key {} must be exactly 32 bytes",
+                path.display()
+            ))
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key: [u8; 32] = Aes256Gcm::generate_key(&mut OsRng).into();
+            write_private(path, &key, true)?;
+            Ok(key)
+        }
+        Err(e) => Err(io_error(path, &e)),
+    }
+}
+
+/// Read the key stored under `service` in the OS keychain, or generate one
+/// there.
+fn keychain_key(service: &str) -> Result<[u8; 32], ZVaultError> {
+    if let Some(encoded) = keychain_lookup(service)? {
+        return STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes.as_slice()).ok())
+            .ok_or_else(|| {
+                ZVaultError::Config(format!(
+                    "keychain entry '{service}' does not hold a fallback cache key"
+                ))
+            });
+    }
+    let key: [u8; 32] = Aes256Gcm::generate_key(&mut OsRng).into();
+    keychain_store(service, &STANDARD.encode(key))?;
+    Ok(key)
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_lookup(service: &str) -> Result<Option<String>, ZVaultError> {
+    let output = Command::new("security")
+        .args([
+            "find-generic-password",
+            "-s",
+            service,
+            "-a",
+            KEYCHAIN_ACCOUNT,
+            "-w",
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| keychain_error(&e))?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_store(service: &str, secret: &str) -> Result<(), ZVaultError> {
+    // `security -i` reads the command from stdin, keeping the key out of argv.
+    let command =
+        format!("add-generic-password -U -s '{service}' -a {KEYCHAIN_ACCOUNT} -w {secret}\n");
+    run_with_stdin(Command::new("security").arg("-i"), &command)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn keychain_lookup(service: &str) -> Result<Option<String>, ZVaultError> {
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", service, "account", KEYCHAIN_ACCOUNT])
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| keychain_error(&e))?;
+    Ok((output.status.success() && !output.stdout.is_empty())
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn keychain_store(service: &str, secret: &str) -> Result<(), ZVaultError> {
+    run_with_stdin(
+        Command::new("secret-tool").args([
+            "store",
+            "--label=ZVault SDK fallback cache",
+            "service",
+            service,
+            "account",
+            KEYCHAIN_ACCOUNT,
+        ]),
+        secret,
+    )
+}
+
+#[cfg(not(unix))]
+fn keychain_lookup(_service: &str) -> Result<Option<String>, ZVaultError> {
+    Err(ZVaultError::Config(
+        "the OS keychain is not supported on this platform; use FallbackKey::File".to_owned(),
+    ))
+}
+
+#[cfg(not(unix))]
+fn keychain_store(_service: &str, _secret: &str) -> Result<(), ZVaultError> {
+    keychain_lookup("").map(|_| ())
+}
+
+#[cfg(unix)]
+fn run_with_stdin(command: &mut Command, input: &str) -> Result<(), ZVaultError> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| keychain_error(&e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| keychain_error(&e))?;
+    }
+    let status = child.wait().map_err(|e| keychain_error(&e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ZVaultError::Config(
+            "failed to store the fallback cache key in the OS keychain".to_owned(),
+        ))
+    }
+}
+
+/// Write `data` to `path`, readable only by the owner on Unix.
+fn write_private(path: &Path, data: &[u8], create_new: bool) -> Result<(), ZVaultError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true);
+    if create_new {
+        options.create_new(true);
+    } else {
+        options.create(true).truncate(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut f| f.write_all(data))
+        .map_err(|e| io_error(path, &e))
+}
+
+fn io_error(path: &Path, e: &std::io::Error) -> ZVaultError {
+    ZVaultError::Config(format!("fallback cache {}: {e}", path.display()))
+}
+
+#[cfg(unix)]
+fn keychain_error(e: &std::io::Error) -> ZVaultError {
+    ZVaultError::Config(format!("OS keychain unavailable: {e}"))
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(path: &Path, key: [u8; 32]) -> Fallback {
+        Fallback::open(&FallbackCache {
+            path: path.to_owned(),
+            key: FallbackKey::Raw(key),
+            max_age: None,
+        })
+        .unwrap()
+    }
+
+    fn secrets() -> HashMap<String, FetchedSecret> {
+        HashMap::from([(
+            "DATABASE_URL".to_owned(),
+            FetchedSecret {
+                value: "postgres://db".to_owned(),
+                freshness: SecretFreshness::Fresh,
+                inherited_from: Some("staging".to_owned()),
+            },
+        )])
+    }
+
+    #[tokio::test]
+    async fn stored_secrets_load_back_stale_and_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        let cache = open(&path, [1; 32]);
+        cache.store("org/project/dev", &secrets()).await;
+
+        let on_disk = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&on_disk).contains("postgres://db"));
+
+        let loaded = cache.load("org/project/dev").await;
+        let secret = &loaded["DATABASE_URL"];
+        assert_eq!(secret.value, "postgres://db");
+        assert_eq!(secret.inherited_from.as_deref(), Some("staging"));
+        assert!(matches!(secret.freshness, SecretFreshness::Stale { .. }));
+        assert!(cache.load("org/project/prod").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn tampered_ciphertext_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        let cache = open(&path, [1; 32]);
+        cache.store("scope", &secrets()).await;
+
+        let mut data = std::fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0x01;
+        std::fs::write(&path, data).unwrap();
+
+        assert!(cache.load("scope").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn another_key_cannot_read_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        open(&path, [1; 32]).store("scope", &secrets()).await;
+
+        assert!(open(&path, [2; 32]).load("scope").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn missing_or_corrupt_files_load_nothing_and_are_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        let cache = open(&path, [1; 32]);
+        assert!(cache.load("scope").await.is_empty());
+
+        for garbage in [&b""[..], b"short", &[0u8; 64]] {
+            std::fs::write(&path, garbage).unwrap();
+            assert!(cache.load("scope").await.is_empty());
+        }
+
+        cache.store("scope", &secrets()).await;
+        assert_eq!(cache.load("scope").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn values_older_than_max_age_are_not_served() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        open(&path, [1; 32]).store("scope", &secrets()).await;
+        let mut old = secrets();
+        old.get_mut("DATABASE_URL").unwrap().freshness = SecretFreshness::Cached {
+            age: Duration::from_secs(3600),
+        };
+        open(&path, [1; 32]).store("old", &old).await;
+
+        let cache = Fallback::open(&FallbackCache {
+            path,
+            key: FallbackKey::Raw([1; 32]),
+            max_age: Some(Duration::from_secs(60)),
+        })
+        .unwrap();
+        assert_eq!(cache.load("scope").await.len(), 1);
+        assert!(cache.load("old").await.is_empty());
+    }
+
+    #[test]
+    fn key_files_are_generated_once_and_must_hold_32_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key");
+        let key = file_key(&path).unwrap();
+        assert_eq!(file_key(&path).unwrap(), key);
+
+        std::fs::write(&path, b"too short").unwrap();
+        assert!(matches!(file_key(&path), Err(ZVaultError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn a_corrupt_cache_reports_the_api_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        std::fs::write(&path, b"not a cache file at all").unwrap();
+        let client = crate::ZVault::with_config(crate::ZVaultConfig {
+            token: "zvt_test".to_owned(),
+            // Nothing listens on the discard port.
+            base_url: "http://127.0.0.1:9".to_owned(),
+            org_id: "org".to_owned(),
+            project_id: "project".to_owned(),
+            max_retries: 1,
+            fallback_cache: Some(FallbackCache {
+                path,
+                key: FallbackKey::Raw([1; 32]),
+                max_age: None,
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let err = client.get_all("dev").await.unwrap_err();
+        assert!(is_unreachable(&err), "{err:?}");
+    }
+}
@@ -1,7 +1,37 @@
 //! Official `ZVault` SDK for Rust.
 //!
 //! Fetch secrets at runtime from `ZVault` Cloud with in-memory caching,
-//! retry with backoff, and graceful degradation.
+//! retry with backoff, and graceful degradation. The cache is kept per key:
+//! when some fetches fail, [`ZVault::get_all_with_freshness`] fills the gaps
+//! from cache and marks each entry with its [`SecretFreshness`].
+//!
+//! For the open-source server, [`ZVault::self_hosted`] returns a
+//! [`SelfHosted`] client: token or `AppRole` auth, KV v2 reads and writes,
+//! transit encryption, and database credentials whose leases renew
+//! themselves.
+//!
+//! Against a self-hosted server, [`ZVault::subscribe`] streams vault events
+//! (`kv/*`, `policy.*`, ...) so services can reload secrets when they change
+//! instead of polling.
+//!
+//! Besides reading, the client manages secrets: [`ZVault::set`],
+//! [`ZVault::delete`], and [`ZVault::set_many`], which writes a batch in one
+//! transaction with optional per-secret version checks.
+//!
+//! With the `macros` feature, `secret!("env/myapp/DATABASE_URL")` checks a
+//! KV path at compile time — and, with `ZVAULT_CHECK_SECRETS=1`, that it
+//! exists on a dev vault — and yields a [`SecretPath`] to read it with.
+//!
+//! [`ZVault::get_typed`] deserializes an environment straight into a struct,
+//! parsing each value into its field's type; see [`typed`].
+//!
+//! [`template`] renders config files with secrets inlined, for tools that
+//! cannot call the SDK themselves.
+//!
+//! With the `fallback-cache` feature, a [`FallbackCache`] keeps the
+//! last-known-good secrets in an encrypted file, so a process that starts
+//! while the API is unreachable still gets them, marked
+//! [`SecretFreshness::Stale`].
 //!
 //! # Example
 //!
@@ -20,10 +50,26 @@
 
 mod client;
 mod error;
+mod events;
+#[cfg(feature = "fallback-cache")]
+mod fallback;
+mod self_hosted;
 mod types;
 
-pub use error::ZVaultError;
-pub use types::{HealthStatus, SecretEntry, SecretKey};
+pub mod template;
+pub mod typed;
+
+pub use error::{ErrorCode, ZVaultError};
+pub use events::EventSubscription;
+#[cfg(feature = "fallback-cache")]
+pub use fallback::{FallbackCache, FallbackKey};
+pub use self_hosted::{DatabaseCredentials, SecretPath, SelfHosted};
+pub use types::{
+    FetchedSecret, HealthStatus, KvSecret, SecretEntry, SecretFreshness, SecretKey, SecretUpdate,
+    VaultEvent,
+};
+#[cfg(feature = "macros")]
+pub use zvault_macros::secret;
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -56,6 +102,10 @@ pub struct ZVaultConfig {
     pub timeout: Duration,
     /// Max retry attempts. Default: 3.
     pub max_retries: u32,
+    /// Encrypted on-disk cache served when the API is unreachable.
+    /// Default: none.
+    #[cfg(feature = "fallback-cache")]
+    pub fallback_cache: Option<FallbackCache>,
 }
 
 impl Default for ZVaultConfig {
@@ -69,13 +119,23 @@ impl Default for ZVaultConfig {
             cache_ttl: DEFAULT_CACHE_TTL,
             timeout: DEFAULT_TIMEOUT,
             max_retries: DEFAULT_MAX_RETRIES,
+            #[cfg(feature = "fallback-cache")]
+            fallback_cache: None,
         }
     }
 }
 
+/// A cached secret value and when it was fetched.
+struct CachedSecret {
+    value: String,
+    fetched_at: Instant,
+    inherited_from: Option<String>,
+}
+
+/// Cached secrets for one environment, each with its own age.
+#[derive(Default)]
 struct CacheEntry {
-    secrets: HashMap<String, String>,
-    expires_at: Instant,
+    secrets: HashMap<String, CachedSecret>,
 }
 
 /// `ZVault` SDK client.
@@ -88,5 +148,8 @@ pub struct ZVault {
     cache_ttl: Duration,
     max_retries: u32,
     client: reqwest::Client,
+    stream_client: reqwest::Client,
     cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
+    #[cfg(feature = "fallback-cache")]
+    fallback: Option<fallback::Fallback>,
 }
@@ -0,0 +1,624 @@
+//! Client for a self-hosted `ZVault` server.
+//!
+//! Speaks the open-source server's `/v1` API: token or `AppRole` auth, KV v2
+//! secrets under the `secret/` mount, transit encryption, and dynamic
+//! database credentials whose leases renew in the background.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::Engine as _;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::client::{is_retryable, sleep_with_jitter};
+use crate::error::{self, ZVaultError};
+use crate::types::KvSecret;
+use crate::{ZVault, DEFAULT_MAX_RETRIES, DEFAULT_TIMEOUT};
+
+/// Default server address when neither `addr` nor `VAULT_ADDR` is set.
+const DEFAULT_ADDR: &str = "http://127.0.0.1:8200";
+
+/// How long before the server drops a token or lease it is renewed.
+const RENEW_FRACTION: u32 = 3;
+
+/// Client for a self-hosted server, created by [`ZVault::self_hosted`].
+///
+/// Cheap to clone; clones share the token.
+#[derive(Clone)]
+pub struct SelfHosted {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    addr: String,
+    client: reqwest::Client,
+    max_retries: u32,
+    auth: RwLock<Auth>,
+}
+
+#[derive(Default)]
+struct Auth {
+    token: String,
+    /// `AppRole` credentials to log in again with before the token expires.
+    approle: Option<(String, String)>,
+    expires_at: Option<Instant>,
+    ttl: Duration,
+}
+
+/// Dynamic database credentials from [`SelfHosted::database_credentials`].
+///
+/// The lease is renewed in the background until it reaches its max TTL or
+/// this value is dropped. Once [`DatabaseCredentials::is_renewing`] turns
+/// false the credentials expire at the end of the current lease; fetch new
+/// ones before then.
+pub struct DatabaseCredentials {
+    /// Database user name.
+    pub username: String,
+    /// Database password.
+    pub password: String,
+    /// Lease ID, for [`SelfHosted::revoke_lease`].
+    pub lease_id: String,
+    /// Lease duration when issued.
+    pub lease_duration: Duration,
+    renewal: Option<JoinHandle<()>>,
+}
+
+impl DatabaseCredentials {
+    /// Whether the lease is still being renewed in the background.
+    #[must_use]
+    pub fn is_renewing(&self) -> bool {
+        self.renewal
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+    }
+}
+
+impl Drop for DatabaseCredentials {
+    fn drop(&mut self) {
+        if let Some(task) = self.renewal.take() {
+            task.abort();
+        }
+    }
+}
+
+impl std::fmt::Debug for DatabaseCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("lease_id", &self.lease_id)
+            .field("lease_duration", &self.lease_duration)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A KV secret path checked at compile time, from `secret!` (the `macros`
+/// feature).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SecretPath(&'static str);
+
+impl SecretPath {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn __checked(path: &'static str) -> Self {
+        Self(path)
+    }
+
+    /// The path under the `secret/` mount, e.g. `env/myapp/DATABASE_URL`.
+    #[must_use]
+    pub const fn path(&self) -> &'static str {
+        self.0
+    }
+
+    /// The `zvault://` reference to this secret, as used in `.env.zvault`.
+    #[must_use]
+    pub fn uri(&self) -> String {
+        format!("zvault://{}", self.0)
+    }
+
+    /// Read the secret's value, the way `zvault run` resolves a `zvault://`
+    /// reference: its `value` key, or the whole secret as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ZVaultError::NotFound` if there is no secret at the path.
+    pub async fn get(&self, client: &SelfHosted) -> Result<String, ZVaultError> {
+        let secret = client.kv_get(self.0).await?;
+        match secret.data.get("value") {
+            Some(serde_json::Value::String(value)) => Ok(value.clone()),
+            _ => Ok(serde_json::to_string(&secret.data)?),
+        }
+    }
+}
+
+impl std::fmt::Display for SecretPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    client_token: String,
+    #[serde(default)]
+    ttl: u64,
+}
+
+#[derive(Deserialize)]
+struct KvReadResponse {
+    data: KvReadData,
+}
+
+#[derive(Deserialize)]
+struct KvReadData {
+    data: serde_json::Value,
+    #[serde(default)]
+    metadata: KvMetadata,
+}
+
+#[derive(Deserialize, Default)]
+struct KvMetadata {
+    #[serde(default)]
+    version: u64,
+}
+
+#[derive(Deserialize)]
+struct KvWriteResponse {
+    data: KvMetadata,
+}
+
+#[derive(Deserialize)]
+struct KvListResponse {
+    data: KvListData,
+}
+
+#[derive(Deserialize)]
+struct KvListData {
+    #[serde(default)]
+    keys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct EncryptResponse {
+    ciphertext: String,
+}
+
+#[derive(Deserialize)]
+struct DecryptResponse {
+    plaintext: String,
+}
+
+#[derive(Deserialize)]
+struct CredsResponse {
+    username: String,
+    password: String,
+    lease_id: String,
+    lease_duration: u64,
+    #[serde(default)]
+    renewable: bool,
+}
+
+#[derive(Deserialize)]
+struct LeaseResponse {
+    ttl_secs: u64,
+}
+
+impl ZVault {
+    /// Create a client for a self-hosted server at `addr` (falling back to
+    /// `VAULT_ADDR`, then `http://127.0.0.1:8200`), authenticated with
+    /// `VAULT_TOKEN` if set. Use [`SelfHosted::with_token`] or
+    /// [`SelfHosted::login_approle`] to authenticate explicitly.
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), zvault_sdk::ZVaultError> {
+    /// let vault = zvault_sdk::ZVault::self_hosted("https://vault.internal:8200")?;
+    /// vault.login_approle("ci-role-id", "ci-secret-id").await?;
+    /// let db = vault.kv_get("myapp/database").await?;
+    /// println!("{:?}", db.data.get("url"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `ZVaultError::Network` if the HTTP client cannot be built.
+    pub fn self_hosted(addr: &str) -> Result<SelfHosted, ZVaultError> {
+        let addr = if addr.is_empty() {
+            std::env::var("VAULT_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_owned())
+        } else {
+            addr.to_owned()
+        };
+        let client = reqwest::Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .user_agent("zvault-rust-sdk/0.1.0")
+            .build()
+            .map_err(ZVaultError::Network)?;
+        Ok(SelfHosted {
+            inner: Arc::new(Inner {
+                addr: addr.trim_end_matches('/').to_owned(),
+                client,
+                max_retries: DEFAULT_MAX_RETRIES,
+                auth: RwLock::new(Auth {
+                    token: std::env::var("VAULT_TOKEN").unwrap_or_default(),
+                    ..Auth::default()
+                }),
+            }),
+        })
+    }
+}
+
+impl SelfHosted {
+    /// Authenticate with a vault token.
+    #[must_use]
+    pub fn with_token(self, token: &str) -> Self {
+        if let Ok(mut auth) = self.inner.auth.try_write() {
+            *auth = Auth {
+                token: token.to_owned(),
+                ..Auth::default()
+            };
+        }
+        self
+    }
+
+    /// Log in with `AppRole` credentials. The client logs in again on its
+    /// own before the resulting token expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ZVaultError::Api` if the credentials are rejected.
+    pub async fn login_approle(&self, role_id: &str, secret_id: &str) -> Result<(), ZVaultError> {
+        let mut auth = self.inner.auth.write().await;
+        auth.approle = Some((role_id.to_owned(), secret_id.to_owned()));
+        self.login(&mut auth).await
+    }
+
+    /// Read the latest version of a KV secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ZVaultError::NotFound` if there is no secret at `path`.
+    pub async fn kv_get(&self, path: &str) -> Result<KvSecret, ZVaultError> {
+        let path = path.trim_matches('/');
+        let resp = match self
+            .request::<KvReadResponse>(Method::GET, &format!("/v1/secret/data/{path}"), None)
+            .await
+        {
+            Err(ZVaultError::Api {
+                status_code: 404, ..
+            }) => {
+                return Err(ZVaultError::NotFound {
+                    key: path.to_owned(),
+                    env: "secret".to_owned(),
+                })
+            }
+            other => other?,
+        };
+        // Secrets written by the CLI and this SDK keep their fields under
+        // `data`; ones written through the raw API may not.
+        let stored = resp.data.data;
+        let fields = match stored.get("data") {
+            Some(serde_json::Value::Object(fields)) => fields.clone(),
+            _ => stored.as_object().cloned().unwrap_or_default(),
+        };
+        Ok(KvSecret {
+            data: fields.into_iter().collect(),
+            version: resp.data.metadata.version,
+        })
+    }
+
+    /// Write a new version of a KV secret and return its version number.
+    ///
+    /// With `cas`, the write only applies if the secret is currently at
+    /// that version (`Some(0)`: if it does not exist yet).
+    ///
+    /// # Errors
+    ///
+    /// Returns `ZVaultError::Conflict` if `cas` does not match.
+    pub async fn kv_put(
+        &self,
+        path: &str,
+        data: &HashMap<String, serde_json::Value>,
+        cas: Option<u64>,
+    ) -> Result<u64, ZVaultError> {
+        let mut body = serde_json::json!({ "data": data });
+        if let Some(cas) = cas {
+            body["options"] = serde_json::json!({ "cas": cas });
+        }
+        let resp = self
+            .request::<KvWriteResponse>(
+                Method::POST,
+                &format!("/v1/secret/data/{}", path.trim_matches('/')),
+                Some(body),
+            )
+            .await?;
+        Ok(resp.data.version)
+    }
+
+    /// Delete the latest version of a KV secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails.
+    pub async fn kv_delete(&self, path: &str) -> Result<(), ZVaultError> {
+        self.request::<serde_json::Value>(
+            Method::DELETE,
+            &format!("/v1/secret/data/{}", path.trim_matches('/')),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// List every secret path under `prefix`, relative to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails.
+    pub async fn kv_list(&self, prefix: &str) -> Result<Vec<String>, ZVaultError> {
+        let prefix = prefix.trim_matches('/');
+        let path = if prefix.is_empty() {
+            "/v1/secret/list".to_owned()
+        } else {
+            format!("/v1/secret/list/{prefix}/")
+        };
+        let resp = self
+            .request::<KvListResponse>(Method::GET, &path, None)
+            .await?;
+        Ok(resp
+            .data
+            .keys
+            .into_iter()
+            .map(|k| k.trim_start_matches('/').to_owned())
+            .collect())
+    }
+
+    /// Encrypt `plaintext` with transit key `key`, returning the
+    /// `vault:v<N>:...` ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key does not exist or the request fails.
+    pub async fn encrypt(&self, key: &str, plaintext: &[u8]) -> Result<String, ZVaultError> {
+        let body = serde_json::json!({
+            "plaintext": base64::engine::general_purpose::STANDARD.encode(plaintext),
+        });
+        let resp = self
+            .request::<EncryptResponse>(
+                Method::POST,
+                &format!("/v1/transit/encrypt/{key}"),
+                Some(body),
+            )
+            .await?;
+        Ok(resp.ciphertext)
+    }
+
+    /// Decrypt a `vault:v<N>:...` ciphertext with transit key `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ciphertext is invalid or the request fails.
+    pub async fn decrypt(&self, key: &str, ciphertext: &str) -> Result<Vec<u8>, ZVaultError> {
+        let body = serde_json::json!({ "ciphertext": ciphertext });
+        let resp = self
+            .request::<DecryptResponse>(
+                Method::POST,
+                &format!("/v1/transit/decrypt/{key}"),
+                Some(body),
+            )
+            .await?;
+        base64::engine::general_purpose::STANDARD
+            .decode(resp.plaintext)
+            .map_err(|e| ZVaultError::status(0, format!("invalid plaintext from server: {e}")))
+    }
+
+    /// Generate database credentials for `role`, renewing their lease in
+    /// the background. Must be called within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the role does not exist or the request fails.
+    pub async fn database_credentials(
+        &self,
+        role: &str,
+    ) -> Result<DatabaseCredentials, ZVaultError> {
+        let resp = self
+            .request::<CredsResponse>(Method::GET, &format!("/v1/database/creds/{role}"), None)
+            .await?;
+        let lease_duration = Duration::from_secs(resp.lease_duration);
+        let renewal = (resp.renewable && resp.lease_duration > 0).then(|| {
+            tokio::spawn(renew_lease_until_done(
+                self.clone(),
+                resp.lease_id.clone(),
+                lease_duration,
+            ))
+        });
+        Ok(DatabaseCredentials {
+            username: resp.username,
+            password: resp.password,
+            lease_id: resp.lease_id,
+            lease_duration,
+            renewal,
+        })
+    }
+
+    /// Extend a lease by `increment` and return its new total TTL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lease has expired, reached its max TTL, or
+    /// the request fails.
+    pub async fn renew_lease(
+        &self,
+        lease_id: &str,
+        increment: Duration,
+    ) -> Result<Duration, ZVaultError> {
+        let body = serde_json::json!({
+            "lease_id": lease_id,
+            "increment": increment.as_secs(),
+        });
+        let resp = self
+            .request::<LeaseResponse>(Method::POST, "/v1/sys/leases/renew", Some(body))
+            .await?;
+        Ok(Duration::from_secs(resp.ttl_secs))
+    }
+
+    /// Revoke a lease now, e.g. when done with database credentials.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails.
+    pub async fn revoke_lease(&self, lease_id: &str) -> Result<(), ZVaultError> {
+        let body = serde_json::json!({ "lease_id": lease_id });
+        self.request::<serde_json::Value>(Method::POST, "/v1/sys/leases/revoke", Some(body))
+            .await?;
+        Ok(())
+    }
+
+    // --- Private ---
+
+    /// Log in with the stored `AppRole` credentials.
+    async fn login(&self, auth: &mut Auth) -> Result<(), ZVaultError> {
+        let Some((role_id, secret_id)) = auth.approle.clone() else {
+            return Ok(());
+        };
+        let body = serde_json::json!({ "role_id": role_id, "secret_id": secret_id });
+        let resp = self
+            .send::<LoginResponse>(Method::POST, "/v1/auth/approle/login", Some(&body), None)
+            .await?;
+        auth.token = resp.client_token;
+        auth.ttl = Duration::from_secs(resp.ttl);
+        auth.expires_at = (resp.ttl > 0).then(|| Instant::now() + auth.ttl);
+        Ok(())
+    }
+
+    /// The current token, logging in again first if it is about to expire.
+    async fn token(&self) -> Result<String, ZVaultError> {
+        {
+            let auth = self.inner.auth.read().await;
+            let fresh = auth.expires_at.map_or(true, |at| {
+                at.saturating_duration_since(Instant::now()) > auth.ttl / RENEW_FRACTION
+            });
+            if fresh {
+                return Ok(auth.token.clone());
+            }
+        }
+        let mut auth = self.inner.auth.write().await;
+        self.login(&mut auth).await?;
+        Ok(auth.token.clone())
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, ZVaultError> {
+        let token = self.token().await?;
+        if token.is_empty() {
+            return Err(ZVaultError::Config(
+                "missing token — set VAULT_TOKEN, call with_token, or login_approle".to_owned(),
+            ));
+        }
+        self.send(method, path, body.as_ref(), Some(&token)).await
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+        token: Option<&str>,
+    ) -> Result<T, ZVaultError> {
+        let url = format!("{}{}", self.inner.addr, path);
+        let mut last_err = None;
+
+        for attempt in 0..=self.inner.max_retries {
+            let mut req = self.inner.client.request(method.clone(), &url);
+            if let Some(token) = token {
+                req = req.header("X-Vault-Token", token);
+            }
+            if let Some(b) = body {
+                req = req.json(b);
+            }
+
+            match req.send().await {
+                Ok(resp) => {
+                    let status = resp.status();
+                    let text = resp.text().await.map_err(ZVaultError::Network)?;
+
+                    if status.is_success() {
+                        if text.is_empty() {
+                            return serde_json::from_str("{}").map_err(ZVaultError::Json);
+                        }
+                        return serde_json::from_str(&text).map_err(ZVaultError::Json);
+                    }
+
+                    let err = error::from_response(status.as_u16(), &text);
+                    if matches!(err, ZVaultError::Auth(_) | ZVaultError::Conflict(_)) {
+                        return Err(err);
+                    }
+                    last_err = Some(err);
+
+                    if attempt < self.inner.max_retries && is_retryable(status) {
+                        sleep_with_jitter(attempt).await;
+                        continue;
+                    }
+                }
+                Err(e) => {
+                    if e.is_timeout() {
+                        last_err = Some(ZVaultError::Timeout);
+                    } else {
+                        last_err = Some(ZVaultError::Network(e));
+                    }
+
+                    if attempt < self.inner.max_retries {
+                        sleep_with_jitter(attempt).await;
+                        continue;
+                    }
+                }
+            }
+
+            break;
+        }
+
+        Err(last_err.unwrap_or_else(|| ZVaultError::status(0, "unknown error".to_owned())))
+    }
+}
+
+/// Renew `lease_id` by `duration` whenever less than a third of it is
+/// left, until the server refuses (max TTL reached, lease revoked) or the
+/// lease runs out while the server is unreachable.
+async fn renew_lease_until_done(client: SelfHosted, lease_id: String, duration: Duration) {
+    let mut ttl = duration;
+    let mut expires_at = Instant::now() + duration;
+    loop {
+        let left = expires_at.saturating_duration_since(Instant::now());
+        tokio::time::sleep(left.saturating_sub(duration / RENEW_FRACTION)).await;
+
+        match client.renew_lease(&lease_id, duration).await {
+            Ok(new_ttl) if new_ttl > ttl => {
+                expires_at += new_ttl - ttl;
+                ttl = new_ttl;
+            }
+            // No longer renewable, or refused (max TTL reached, revoked).
+            Ok(_)
+            | Err(
+                ZVaultError::Auth(_)
+                | ZVaultError::Api {
+                    status_code: 400..=499,
+                    ..
+                },
+            ) => return,
+            Err(_) if Instant::now() < expires_at => {
+                // Unreachable for now; try again shortly.
+                tokio::time::sleep(Duration::from_secs(5).min(duration / RENEW_FRACTION)).await;
+            }
+            Err(_) => return,
+        }
+    }
+}
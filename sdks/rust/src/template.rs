@@ -0,0 +1,433 @@
+//! Secret templates: config files with secrets inlined at render time.
+//!
+//! A template is any text — JSON, YAML, ini, nginx — with actions in
+//! `{{ }}`. An action names a value and pipes it through filters:
+//!
+//! ```text
+//! {{ secret "app/db" "password" }}      key of a KV secret
+//! {{ env "HOSTNAME" }}                  environment variable
+//! {{ "{{" }}                            string literal
+//! {{ secret "app/tls" "key" | json }}   filtered value
+//! ```
+//!
+//! Filters are `json` (a quoted JSON string, which is also valid YAML),
+//! `base64`, `trim`, `upper`, and `lower`. `{{-` and `-}}` trim the
+//! whitespace before and after an action, so actions can sit on their own
+//! lines without leaving blank ones.
+//!
+//! Parsing does no I/O; [`Template::render`] asks a lookup function for
+//! each secret, so callers decide how and where secrets are fetched.
+//!
+//! ```rust
+//! use zvault_sdk::template::Template;
+//!
+//! # fn example() -> Result<(), zvault_sdk::ZVaultError> {
+//! let template = Template::parse(r#"{"password": {{ secret "app/db" "password" | json }}}"#)?;
+//! let rendered = template.render(|secret| {
+//!     (secret.path == "app/db" && secret.key == "password").then(|| "p\"w".to_owned())
+//! })?;
+//! assert_eq!(rendered, r#"{"password": "p\"w"}"#);
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+
+use base64::Engine as _;
+
+use crate::ZVaultError;
+
+/// A key of a KV secret referenced by a template.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SecretRef {
+    /// Secret path, e.g. `app/db`.
+    pub path: String,
+    /// Key within the secret, e.g. `password`.
+    pub key: String,
+}
+
+/// A parsed template.
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Action {
+        line: usize,
+        value: Value,
+        filters: Vec<Filter>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Secret(SecretRef),
+    Env(String),
+    Literal(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Filter {
+    Json,
+    Base64,
+    Trim,
+    Upper,
+    Lower,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Pipe,
+}
+
+impl Template {
+    /// Parse a template.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ZVaultError::Template`] naming the line of the first
+    /// malformed action.
+    pub fn parse(source: &str) -> Result<Self, ZVaultError> {
+        let mut nodes = Vec::new();
+        let mut rest = source;
+        let mut line = 1;
+        let mut trim_next = false;
+
+        while let Some(start) = rest.find("{{") {
+            let mut text = &rest[..start];
+            if trim_next {
+                text = text.trim_start();
+            }
+            let mut inner = &rest[start + 2..];
+            if let Some(trimmed) = inner.strip_prefix('-') {
+                text = text.trim_end();
+                inner = trimmed;
+            }
+            line += rest[..start].matches('\n').count();
+            if !text.is_empty() {
+                nodes.push(Node::Text(text.to_owned()));
+            }
+
+            let (tokens, end, trim_after) = tokenize(inner, line)?;
+            trim_next = trim_after;
+            let (value, filters) = parse_action(&tokens, line)?;
+            nodes.push(Node::Action {
+                line,
+                value,
+                filters,
+            });
+            line += inner[..end].matches('\n').count();
+            rest = &inner[end + 2..];
+        }
+
+        let text = if trim_next { rest.trim_start() } else { rest };
+        if !text.is_empty() {
+            nodes.push(Node::Text(text.to_owned()));
+        }
+        Ok(Self { nodes })
+    }
+
+    /// The secrets the template references, in order of first use.
+    #[must_use]
+    pub fn secrets(&self) -> Vec<&SecretRef> {
+        let mut secrets: Vec<&SecretRef> = Vec::new();
+        for node in &self.nodes {
+            if let Node::Action {
+                value: Value::Secret(secret),
+                ..
+            } = node
+            {
+                if !secrets.contains(&secret) {
+                    secrets.push(secret);
+                }
+            }
+        }
+        secrets
+    }
+
+    /// Render the template, asking `lookup` for the value of each secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ZVaultError::Template`] if `lookup` has no value for a
+    /// secret or an environment variable is unset. Nothing is rendered
+    /// partially.
+    pub fn render<F>(&self, mut lookup: F) -> Result<String, ZVaultError>
+    where
+        F: FnMut(&SecretRef) -> Option<String>,
+    {
+        let mut out = String::new();
+        for node in &self.nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Action {
+                    line,
+                    value,
+                    filters,
+                } => {
+                    let mut value = match value {
+                        Value::Secret(secret) => lookup(secret).ok_or_else(|| {
+                            template_error(
+                                *line,
+                                format!("secret {} has no key \"{}\"", secret.path, secret.key),
+                            )
+                        })?,
+                        Value::Env(name) => std::env::var(name).map_err(|_| {
+                            template_error(*line, format!("environment variable {name} is not set"))
+                        })?,
+                        Value::Literal(text) => text.clone(),
+                    };
+                    for filter in filters {
+                        value = filter.apply(&value)?;
+                    }
+                    out.push_str(&value);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl Filter {
+    fn parse(name: &str, line: usize) -> Result<Self, ZVaultError> {
+        Ok(match name {
+            "json" => Self::Json,
+            "base64" => Self::Base64,
+            "trim" => Self::Trim,
+            "upper" => Self::Upper,
+            "lower" => Self::Lower,
+            _ => {
+                return Err(template_error(
+                    line,
+                    format!(
+                        "unknown filter \"{name}\", expected json, base64, trim, upper, or lower"
+                    ),
+                ))
+            }
+        })
+    }
+
+    fn apply(self, value: &str) -> Result<String, ZVaultError> {
+        Ok(match self {
+            Self::Json => serde_json::to_string(value)?,
+            Self::Base64 => base64::engine::general_purpose::STANDARD.encode(value),
+            Self::Trim => value.trim().to_owned(),
+            Self::Upper => value.to_uppercase(),
+            Self::Lower => value.to_lowercase(),
+        })
+    }
+}
+
+fn template_error(line: usize, message: String) -> ZVaultError {
+    ZVaultError::Template { line, message }
+}
+
+/// Split the inside of an action into tokens, returning them with the
+/// offset of the closing `}}` and whether it is preceded by a `-`.
+fn tokenize(inner: &str, line: usize) -> Result<(Vec<Token>, usize, bool), ZVaultError> {
+    let mut tokens = Vec::new();
+    let mut trim_after = false;
+    let mut chars = inner.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '}' if inner[i..].starts_with("}}") => return Ok((tokens, i, trim_after)),
+            '-' if inner[i + 1..].trim_start().starts_with("}}") => trim_after = true,
+            c if c.is_whitespace() => {}
+            '|' => tokens.push(Token::Pipe),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => text.push('\n'),
+                            Some((_, 't')) => text.push('\t'),
+                            Some((_, c @ ('"' | '\\'))) => text.push(c),
+                            _ => {
+                                return Err(template_error(
+                                    line,
+                                    "invalid escape in string".to_owned(),
+                                ))
+                            }
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => return Err(template_error(line, "unterminated string".to_owned())),
+                    }
+                }
+                tokens.push(Token::Str(text));
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(&(_, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            c => return Err(template_error(line, format!("unexpected '{c}' in action"))),
+        }
+    }
+    Err(template_error(line, "unclosed {{".to_owned()))
+}
+
+/// Parse `value (| filter)*` from an action's tokens.
+fn parse_action(tokens: &[Token], line: usize) -> Result<(Value, Vec<Filter>), ZVaultError> {
+    let mut stages = tokens.split(|t| *t == Token::Pipe);
+    let value = match stages.next().unwrap_or_default() {
+        [Token::Ident(f), Token::Str(path), Token::Str(key)] if f == "secret" => {
+            if path.is_empty() || key.is_empty() {
+                return Err(template_error(
+                    line,
+                    "secret path and key must not be empty".to_owned(),
+                ));
+            }
+            Value::Secret(SecretRef {
+                path: path.clone(),
+                key: key.clone(),
+            })
+        }
+        [Token::Ident(f), Token::Str(name)] if f == "env" => Value::Env(name.clone()),
+        [Token::Str(text)] => Value::Literal(text.clone()),
+        [] => return Err(template_error(line, "empty action".to_owned())),
+        [Token::Ident(f), ..] if f == "secret" => {
+            return Err(template_error(
+                line,
+                "expected secret \"path\" \"key\"".to_owned(),
+            ))
+        }
+        [Token::Ident(f), ..] if f == "env" => {
+            return Err(template_error(line, "expected env \"NAME\"".to_owned()))
+        }
+        _ => {
+            return Err(template_error(
+                line,
+                "expected secret \"path\" \"key\", env \"NAME\", or a string".to_owned(),
+            ))
+        }
+    };
+    let filters = stages
+        .map(|stage| match stage {
+            [Token::Ident(name)] => Filter::parse(name, line),
+            _ => Err(template_error(
+                line,
+                "expected a filter name after |".to_owned(),
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((value, filters))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str) -> Result<String, ZVaultError> {
+        Template::parse(source)?.render(|secret| {
+            match (secret.path.as_str(), secret.key.as_str()) {
+                ("app/db", "password") => Some(" s3cr3t ".to_owned()),
+                ("app/db", "user") => Some("Admin".to_owned()),
+                _ => None,
+            }
+        })
+    }
+
+    fn error_line(result: Result<impl std::fmt::Debug, ZVaultError>) -> (usize, String) {
+        match result {
+            Err(ZVaultError::Template { line, message }) => (line, message),
+            other => panic!("expected a template error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn substitutes_secrets_literals_and_filters() {
+        assert_eq!(
+            render(r#"user={{ secret "app/db" "user" }} pw={{secret "app/db" "password"}}"#)
+                .unwrap(),
+            "user=Admin pw= s3cr3t "
+        );
+        assert_eq!(render(r#"{{ "{{" }}x}}"#).unwrap(), "{{x}}");
+        assert_eq!(
+            render(r#"{{ secret "app/db" "password" | trim | upper }}"#).unwrap(),
+            "S3CR3T"
+        );
+        assert_eq!(
+            render(r#"{{ secret "app/db" "user" | lower }}"#).unwrap(),
+            "admin"
+        );
+        assert_eq!(
+            render(r#"{{ secret "app/db" "user" | base64 }}"#).unwrap(),
+            "QWRtaW4="
+        );
+        assert_eq!(render(r#"{{ "a\"b\n" | json }}"#).unwrap(), r#""a\"b\n""#);
+        assert_eq!(render("no actions").unwrap(), "no actions");
+    }
+
+    #[test]
+    fn dashes_trim_surrounding_whitespace() {
+        let source = "a:\n  {{- \"x\" -}}  \nb";
+        assert_eq!(render(source).unwrap(), "a:xb");
+    }
+
+    #[test]
+    fn reads_environment_variables() {
+        std::env::set_var("ZVAULT_TEMPLATE_TEST_HOST", "db.internal");
+        assert_eq!(
+            render(r#"host={{ env "ZVAULT_TEMPLATE_TEST_HOST" }}"#).unwrap(),
+            "host=db.internal"
+        );
+        let (line, message) = error_line(render(r#"{{ env "ZVAULT_TEMPLATE_TEST_UNSET" }}"#));
+        assert_eq!(line, 1);
+        assert!(message.contains("ZVAULT_TEMPLATE_TEST_UNSET"), "{message}");
+    }
+
+    #[test]
+    fn lists_secrets_once_in_order_of_use() {
+        let template = Template::parse(
+            r#"{{ secret "b" "k" }}{{ secret "a" "k" }}{{ secret "b" "k" | json }}{{ env "X" }}"#,
+        )
+        .unwrap();
+        let paths: Vec<&str> = template.secrets().iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, ["b", "a"]);
+    }
+
+    #[test]
+    fn malformed_actions_name_their_line() {
+        for (source, line, expected) in [
+            ("{{ }}", 1, "empty action"),
+            (
+                "a\n{{ secret \"app\" }}",
+                2,
+                "expected secret \"path\" \"key\"",
+            ),
+            ("{{ secret \"\" \"k\" }}", 1, "must not be empty"),
+            ("{{ env }}", 1, "expected env \"NAME\""),
+            ("{{ value }}", 1, "or a string"),
+            ("a\nb\n{{ \"x\" | rot13 }}", 3, "unknown filter \"rot13\""),
+            ("{{ \"x\" | }}", 1, "expected a filter name"),
+            ("{{ \"x }}", 1, "unterminated string"),
+            ("{{ \"\\q\" }}", 1, "invalid escape"),
+            ("{{ \"x\" ", 1, "unclosed {{"),
+            ("{{ \"x\" ; }}", 1, "unexpected ';'"),
+        ] {
+            let (actual_line, message) = error_line(Template::parse(source));
+            assert_eq!(actual_line, line, "{source}");
+            assert!(message.contains(expected), "{source}: {message}");
+        }
+    }
+
+    #[test]
+    fn missing_secrets_fail_the_whole_render() {
+        let (line, message) = error_line(render(
+            "ok {{ secret \"app/db\" \"user\" }}\n{{ secret \"app/db\" \"nope\" }}",
+        ));
+        assert_eq!(line, 2);
+        assert_eq!(message, "secret app/db has no key \"nope\"");
+    }
+}
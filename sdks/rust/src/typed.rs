@@ -0,0 +1,425 @@
+//! Typed secrets: an environment's secrets deserialized into a struct.
+//!
+//! Fields match secret keys case-insensitively, so `database_url` reads
+//! `DATABASE_URL`, and `#[serde(rename = "...")]` picks any other key.
+//! Values are parsed into the field's type: numbers, bools, `Option`s
+//! (absent or empty is `None`), unit enum variants, and `Vec`s from
+//! comma-separated values. A field whose type is itself a struct reads the
+//! keys under its name as a prefix — `database: Db` with `url` and `pool`
+//! fields reads `DATABASE_URL` and `DATABASE_POOL` — or, when a secret
+//! named exactly `DATABASE` exists, parses that as JSON.
+//!
+//! Secrets no field reads are ignored unless the struct has
+//! `#[serde(deny_unknown_fields)]`. Either way, errors name the offending
+//! key and list the secrets that matched no field, which is where a
+//! misspelled key shows up.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Config {
+//!     port: u16,
+//!     debug: Option<bool>,
+//!     database: Database,
+//! }
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Database {
+//!     url: String,
+//!     pool_size: u32,
+//! }
+//!
+//! # fn example() -> Result<(), zvault_sdk::ZVaultError> {
+//! let secrets = HashMap::from([
+//!     ("PORT".to_owned(), "8080".to_owned()),
+//!     ("DATABASE_URL".to_owned(), "postgres://db/app".to_owned()),
+//!     ("DATABASE_POOL_SIZE".to_owned(), "10".to_owned()),
+//! ]);
+//! let config: Config = zvault_sdk::typed::from_secrets(&secrets)?;
+//! assert_eq!(config.port, 8080);
+//! assert_eq!(config.debug, None);
+//! assert_eq!(config.database.pool_size, 10);
+//!
+//! let typo = HashMap::from([("PROT".to_owned(), "8080".to_owned())]);
+//! let err = zvault_sdk::typed::from_secrets::<Config>(&typo).err().map(|e| e.to_string());
+//! assert_eq!(
+//!     err.as_deref(),
+//!     Some("cannot deserialize secrets: missing secret PORT (unused secrets: PROT)")
+//! );
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use serde::de::value::StrDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use crate::ZVaultError;
+
+/// Deserialize `secrets` into `T`.
+///
+/// [`ZVault::get_typed`](crate::ZVault::get_typed) fetches and deserializes
+/// in one call; this is the same conversion for a map from anywhere else.
+///
+/// # Errors
+///
+/// Returns [`ZVaultError::Deserialize`] naming the missing or unparsable
+/// secret, followed by any secrets nothing read.
+pub fn from_secrets<T: DeserializeOwned>(
+    secrets: &HashMap<String, String>,
+) -> Result<T, ZVaultError> {
+    let by_key: HashMap<String, (&str, &str)> = secrets
+        .iter()
+        .map(|(k, v)| (k.to_uppercase(), (k.as_str(), v.as_str())))
+        .collect();
+    let unmatched = RefCell::new(BTreeSet::new());
+    let result = T::deserialize(Secrets {
+        prefix: String::new(),
+        by_key: &by_key,
+        unmatched: &unmatched,
+    });
+    result.map_err(|err| {
+        let unmatched: Vec<&str> = unmatched.borrow().iter().copied().collect();
+        let mut message = err.to_string();
+        if !unmatched.is_empty() {
+            message.push_str(&format!(" (unused secrets: {})", unmatched.join(", ")));
+        }
+        ZVaultError::Deserialize(message)
+    })
+}
+
+/// A deserialization error, with the missing key kept apart so the struct
+/// it is nested in can prefix it.
+#[derive(Debug)]
+struct Error {
+    missing: Option<String>,
+    prefixed: bool,
+    message: String,
+}
+
+impl Error {
+    /// Qualify a missing key with the prefix of the innermost struct.
+    fn within(mut self, prefix: &str) -> Self {
+        if let (Some(key), false) = (&mut self.missing, self.prefixed) {
+            key.insert_str(0, prefix);
+            self.prefixed = true;
+        }
+        self
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            missing: None,
+            prefixed: false,
+            message: msg.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self {
+            missing: Some(field.to_uppercase()),
+            prefixed: false,
+            message: String::new(),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.missing {
+            Some(key) => write!(f, "missing secret {key}"),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// The secrets under `prefix` (uppercase, ending in `_` unless empty), as a
+/// struct or map.
+struct Secrets<'a> {
+    prefix: String,
+    by_key: &'a HashMap<String, (&'a str, &'a str)>,
+    /// Secrets that matched no field of the structs read so far.
+    unmatched: &'a RefCell<BTreeSet<&'a str>>,
+}
+
+impl<'a> Secrets<'a> {
+    /// Keys under the prefix, without it, and their entries.
+    fn under_prefix(&self) -> impl Iterator<Item = (&'a str, (&'a str, &'a str))> + '_ {
+        self.by_key.iter().filter_map(|(upper, entry)| {
+            upper
+                .strip_prefix(self.prefix.as_str())
+                .map(|rest| (rest, *entry))
+        })
+    }
+
+    fn entries(&self, fields: &[&'static str]) -> Vec<(String, Entry<'a>)> {
+        let mut entries = Vec::new();
+        let mut exact = Vec::new();
+        let mut nested = Vec::new();
+        for field in fields {
+            let upper = field.to_uppercase();
+            let prefix = format!("{upper}_");
+            if let Some((key, value)) = self.by_key.get(&format!("{}{upper}", self.prefix)) {
+                entries.push(((*field).to_owned(), Entry::Value { key, value }));
+            } else if self
+                .under_prefix()
+                .any(|(rest, _)| rest.starts_with(&prefix))
+            {
+                entries.push((
+                    (*field).to_owned(),
+                    Entry::Nested(format!("{}{prefix}", self.prefix)),
+                ));
+            }
+            exact.push(upper);
+            nested.push(prefix);
+        }
+        // The rest are offered under their own names, so `deny_unknown_fields`
+        // rejects them and everything else ignores them.
+        for (rest, (key, value)) in self.under_prefix() {
+            let claimed = exact.iter().any(|f| f == rest)
+                || nested.iter().any(|p| rest.starts_with(p.as_str()));
+            if !claimed {
+                self.unmatched.borrow_mut().insert(key);
+                entries.push((rest.to_lowercase(), Entry::Value { key, value }));
+            }
+        }
+        entries
+    }
+}
+
+/// What a struct field is read from.
+enum Entry<'a> {
+    Value { key: &'a str, value: &'a str },
+    Nested(String),
+}
+
+impl<'de> de::Deserializer<'de> for Secrets<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let entries = self
+            .under_prefix()
+            .map(|(rest, (key, value))| (rest.to_lowercase(), Entry::Value { key, value }))
+            .collect();
+        visitor.visit_map(Fields::new(entries, self))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let entries = self.entries(fields);
+        visitor.visit_map(Fields::new(entries, self))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map enum
+        identifier ignored_any
+    }
+}
+
+/// Struct fields or map entries, in order.
+struct Fields<'a> {
+    entries: std::vec::IntoIter<(String, Entry<'a>)>,
+    pending: Option<Entry<'a>>,
+    by_key: &'a HashMap<String, (&'a str, &'a str)>,
+    unmatched: &'a RefCell<BTreeSet<&'a str>>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(entries: Vec<(String, Entry<'a>)>, secrets: Secrets<'a>) -> Self {
+        Self {
+            entries: entries.into_iter(),
+            pending: None,
+            by_key: secrets.by_key,
+            unmatched: secrets.unmatched,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for Fields<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some((field, entry)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.pending = Some(entry);
+        let field: StrDeserializer<'_, Error> = field.as_str().into_deserializer();
+        seed.deserialize(field).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.pending.take() {
+            Some(Entry::Value { key, value }) => seed.deserialize(Value { key, value }),
+            Some(Entry::Nested(prefix)) => seed
+                .deserialize(Secrets {
+                    prefix: prefix.clone(),
+                    by_key: self.by_key,
+                    unmatched: self.unmatched,
+                })
+                .map_err(|err| err.within(&prefix)),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+}
+
+/// One secret's value, parsed into whatever type its field has.
+struct Value<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+impl Value<'_> {
+    fn invalid(&self, err: impl fmt::Display) -> Error {
+        de::Error::custom(format_args!("{}: {err}", self.key))
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let parsed = self.value.trim().parse().map_err(|e| self.invalid(e))?;
+            visitor.$visit(parsed)
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for Value<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.value)
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.value.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items = if self.value.trim().is_empty() {
+            Vec::new()
+        } else {
+            self.value.split(',').map(str::trim).collect()
+        };
+        visitor.visit_seq(Items {
+            key: self.key,
+            items: items.into_iter(),
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let value: StrDeserializer<'_, Error> = self.value.trim().into_deserializer();
+        de::Deserializer::deserialize_enum(value, name, variants, visitor)
+            .map_err(|err| self.invalid(err))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut json = serde_json::Deserializer::from_str(self.value);
+        de::Deserializer::deserialize_map(&mut json, visitor).map_err(|err| self.invalid(err))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let mut json = serde_json::Deserializer::from_str(self.value);
+        de::Deserializer::deserialize_struct(&mut json, name, fields, visitor)
+            .map_err(|err| self.invalid(err))
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple tuple_struct
+        identifier ignored_any
+    }
+}
+
+/// The items of a comma-separated value.
+struct Items<'a> {
+    key: &'a str,
+    items: std::vec::IntoIter<&'a str>,
+}
+
+impl<'de> SeqAccess<'de> for Items<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.items
+            .next()
+            .map(|value| {
+                seed.deserialize(Value {
+                    key: self.key,
+                    value,
+                })
+            })
+            .transpose()
+    }
+}
@@ -1,5 +1,8 @@
 //! Public types for the `ZVault` SDK.
 
+use std::collections::HashMap;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
 /// A single secret entry returned by the API.
@@ -17,6 +20,10 @@ pub struct SecretEntry {
     pub created_at: String,
     /// ISO 8601 last-updated timestamp.
     pub updated_at: String,
+    /// Slug of the environment the value is inherited from, if the
+    /// requested environment does not set it itself.
+    #[serde(default)]
+    pub inherited_from: Option<String>,
 }
 
 /// A secret key (no value) from list operations.
@@ -30,6 +37,69 @@ pub struct SecretKey {
     pub comment: String,
     /// ISO 8601 last-updated timestamp.
     pub updated_at: String,
+    /// Slug of the environment the key is inherited from, if the listed
+    /// environment does not set it itself.
+    #[serde(default)]
+    pub inherited_from: Option<String>,
+    /// Whether the listed environment sets this key and overrides an
+    /// inherited value.
+    #[serde(default)]
+    pub overrides: bool,
+}
+
+/// One write in [`crate::ZVault::set_many`].
+#[derive(Debug, Clone, Default)]
+pub struct SecretUpdate {
+    /// Secret key name.
+    pub key: String,
+    /// New value.
+    pub value: String,
+    /// Optional comment.
+    pub comment: String,
+    /// Version the secret must currently be at for the write to apply;
+    /// `Some(0)` means it must not exist yet. `None` writes unconditionally.
+    pub expected_version: Option<i64>,
+}
+
+/// A KV secret read from a self-hosted server by [`crate::SelfHosted::kv_get`].
+#[derive(Debug, Clone)]
+pub struct KvSecret {
+    /// The secret's fields.
+    pub data: HashMap<String, serde_json::Value>,
+    /// Version number of this read.
+    pub version: u64,
+}
+
+/// How current a secret returned by [`crate::ZVault::get_all_with_freshness`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretFreshness {
+    /// Fetched from the API during this call.
+    Fresh,
+    /// Fetching it failed, so this is the last value fetched successfully,
+    /// `age` ago (always within the cache TTL).
+    Cached {
+        /// Time since the value was fetched.
+        age: Duration,
+    },
+    /// The API was unreachable and nothing was cached in memory, so this is
+    /// the last value fetched successfully as stored in the fallback cache
+    /// on disk, possibly by an earlier process.
+    Stale {
+        /// Time since the value was fetched.
+        age: Duration,
+    },
+}
+
+/// A secret value together with its freshness.
+#[derive(Debug, Clone)]
+pub struct FetchedSecret {
+    /// Decrypted secret value.
+    pub value: String,
+    /// Whether the value is fresh or served from cache.
+    pub freshness: SecretFreshness,
+    /// Slug of the environment the value is inherited from, if the
+    /// requested environment does not set it itself.
+    pub inherited_from: Option<String>,
 }
 
 /// Health check result.
@@ -43,6 +113,19 @@ pub struct HealthStatus {
     pub cached_secrets: usize,
 }
 
+/// A vault event delivered by [`crate::EventSubscription`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultEvent {
+    /// Unique event ID.
+    pub id: String,
+    /// Dotted topic name (e.g. `kv.write`).
+    pub topic: String,
+    /// RFC 3339 publish timestamp.
+    pub timestamp: String,
+    /// Event payload — paths and identifiers, never secret values.
+    pub data: serde_json::Value,
+}
+
 // --- Internal API response types ---
 
 #[derive(Deserialize)]
@@ -50,6 +133,11 @@ pub(crate) struct SecretResponse {
     pub secret: SecretEntry,
 }
 
+#[derive(Deserialize)]
+pub(crate) struct SecretsResponse {
+    pub secrets: Vec<SecretEntry>,
+}
+
 #[derive(Deserialize)]
 pub(crate) struct SecretKeysResponse {
     pub keys: Vec<SecretKey>,